@@ -1,27 +1,31 @@
 use derive_new::new;
-use satrs::hk::{HkRequest, HkRequestVariant};
+use satrs::device::{RawDeviceCommand, RawDeviceReply, RawDeviceReplyVariant};
+use satrs::hk::{HkRequest, HkSetDefinition, HkSetRegistry};
 use satrs::power::{PowerSwitchInfo, PowerSwitcherCommandSender};
 use satrs::queue::{GenericSendError, GenericTargetedMessagingError};
+use satrs::spacepackets::ByteConversionError;
 use satrs_example::{DeviceMode, TimestampHelper};
+use satrs_mib::tm_def::{params_len, ParamDef, ParamType};
 use satrs_minisim::acs::lis3mdl::{
-    MgmLis3MdlReply, MgmLis3RawValues, FIELD_LSB_PER_GAUSS_4_SENS, GAUSS_TO_MICROTESLA_FACTOR,
+    MgmLis3MdlReply, FIELD_LSB_PER_GAUSS_4_SENS, GAUSS_TO_MICROTESLA_FACTOR,
 };
 use satrs_minisim::acs::MgmRequestLis3Mdl;
 use satrs_minisim::eps::PcduSwitch;
+use satrs_minisim::fake::FakeMgmLis3Mdl;
 use satrs_minisim::{SerializableSimMsgPayload, SimReply, SimRequest};
 use std::fmt::Debug;
 use std::sync::mpsc::{self};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use satrs::mode::{
     ModeAndSubmode, ModeError, ModeProvider, ModeReply, ModeRequest, ModeRequestHandler,
 };
-use satrs::pus::{EcssTmSender, PusTmVariant};
+use satrs::pus::EcssTmSender;
 use satrs::request::{GenericMessage, MessageMetadata, UniqueApidTargetId};
 use satrs_example::config::components::{NO_SENDER, PUS_MODE_SERVICE};
+use satrs_example::config::{dev_err, mode_err};
 
-use crate::hk::PusHkHelper;
 use crate::pus::hk::{HkReply, HkReplyVariant};
 use crate::requests::CompositeRequest;
 
@@ -40,6 +44,14 @@ pub enum SetId {
     SensorData = 0,
 }
 
+/// Parameters of the [SetId::SensorData] set.
+pub const MGM_SENSOR_PARAMS: &[ParamDef] = &[
+    ParamDef::new("valid", ParamType::Bool),
+    ParamDef::new("x", ParamType::F32).with_unit("uT"),
+    ParamDef::new("y", ParamType::F32).with_unit("uT"),
+    ParamDef::new("z", ParamType::F32).with_unit("uT"),
+];
+
 #[derive(Default, Debug, PartialEq, Eq)]
 pub enum TransitionState {
     #[default]
@@ -53,18 +65,20 @@ pub trait SpiInterface {
     fn transfer(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error>;
 }
 
-#[derive(Default)]
-pub struct SpiDummyInterface {
-    pub dummy_values: MgmLis3RawValues,
+/// SPI interface to an in-process fake LIS3MDL device, which can be used when the mini simulator
+/// is not running.
+pub struct SpiFakeInterface {
+    pub mgm: FakeMgmLis3Mdl,
 }
 
-impl SpiInterface for SpiDummyInterface {
+impl SpiInterface for SpiFakeInterface {
     type Error = ();
 
     fn transfer(&mut self, _tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
-        rx[X_LOWBYTE_IDX..X_LOWBYTE_IDX + 2].copy_from_slice(&self.dummy_values.x.to_le_bytes());
-        rx[Y_LOWBYTE_IDX..Y_LOWBYTE_IDX + 2].copy_from_slice(&self.dummy_values.y.to_be_bytes());
-        rx[Z_LOWBYTE_IDX..Z_LOWBYTE_IDX + 2].copy_from_slice(&self.dummy_values.z.to_be_bytes());
+        let raw_values = self.mgm.reply().raw;
+        rx[X_LOWBYTE_IDX..X_LOWBYTE_IDX + 2].copy_from_slice(&raw_values.x.to_le_bytes());
+        rx[Y_LOWBYTE_IDX..Y_LOWBYTE_IDX + 2].copy_from_slice(&raw_values.y.to_le_bytes());
+        rx[Z_LOWBYTE_IDX..Z_LOWBYTE_IDX + 2].copy_from_slice(&raw_values.z.to_le_bytes());
         Ok(())
     }
 }
@@ -106,7 +120,7 @@ impl SpiInterface for SpiSimInterface {
 }
 
 pub enum SpiSimInterfaceWrapper {
-    Dummy(SpiDummyInterface),
+    Fake(SpiFakeInterface),
     Sim(SpiSimInterface),
 }
 
@@ -115,7 +129,7 @@ impl SpiInterface for SpiSimInterfaceWrapper {
 
     fn transfer(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
         match self {
-            SpiSimInterfaceWrapper::Dummy(dummy) => dummy.transfer(tx, rx),
+            SpiSimInterfaceWrapper::Fake(fake) => fake.transfer(tx, rx),
             SpiSimInterfaceWrapper::Sim(sim_if) => sim_if.transfer(tx, rx),
         }
     }
@@ -129,6 +143,37 @@ pub struct MgmData {
     pub z: f32,
 }
 
+/// Create the HK set registry of a MGM handler. The sensor data set is collected with an
+/// interval of 1 second by default once periodic generation was enabled.
+pub fn create_mgm_hk_sets(
+    id: UniqueApidTargetId,
+    shared_mgm_set: Arc<Mutex<MgmData>>,
+) -> HkSetRegistry {
+    let mut hk_sets = HkSetRegistry::new(id, Duration::from_secs(1), 32);
+    hk_sets
+        .add_set(
+            HkSetDefinition::new(SetId::SensorData as u32, Duration::from_secs(1)).with_provider(
+                move |hk_buf| {
+                    const SET_LEN: usize = params_len(MGM_SENSOR_PARAMS);
+                    if hk_buf.len() < SET_LEN {
+                        return Err(ByteConversionError::ToSliceTooSmall {
+                            found: hk_buf.len(),
+                            expected: SET_LEN,
+                        });
+                    }
+                    let mgm_snapshot = *shared_mgm_set.lock().unwrap();
+                    hk_buf[0] = mgm_snapshot.valid as u8;
+                    hk_buf[1..5].copy_from_slice(&mgm_snapshot.x.to_be_bytes());
+                    hk_buf[5..9].copy_from_slice(&mgm_snapshot.y.to_be_bytes());
+                    hk_buf[9..13].copy_from_slice(&mgm_snapshot.z.to_be_bytes());
+                    Ok(SET_LEN)
+                },
+            ),
+        )
+        .expect("adding MGM sensor data set failed");
+    hk_sets
+}
+
 pub struct MpscModeLeafInterface {
     pub request_rx: mpsc::Receiver<GenericMessage<ModeRequest>>,
     pub reply_to_pus_tx: mpsc::Sender<GenericMessage<ModeReply>>,
@@ -139,7 +184,6 @@ pub struct MpscModeLeafInterface {
 pub struct BufWrapper {
     tx_buf: [u8; 32],
     rx_buf: [u8; 32],
-    tm_buf: [u8; 32],
 }
 
 pub struct ModeHelpers {
@@ -173,12 +217,12 @@ pub struct MgmHandlerLis3Mdl<
     mode_interface: MpscModeLeafInterface,
     composite_request_rx: mpsc::Receiver<GenericMessage<CompositeRequest>>,
     hk_reply_tx: mpsc::Sender<GenericMessage<HkReply>>,
+    raw_reply_tx: mpsc::Sender<GenericMessage<RawDeviceReply>>,
     switch_helper: SwitchHelper,
     tm_sender: TmSender,
     pub com_interface: ComInterface,
     shared_mgm_set: Arc<Mutex<MgmData>>,
-    #[new(value = "PusHkHelper::new(id)")]
-    hk_helper: PusHkHelper,
+    hk_sets: HkSetRegistry,
     #[new(default)]
     mode_helpers: ModeHelpers,
     #[new(default)]
@@ -205,6 +249,12 @@ impl<
             log::trace!("polling LIS3MDL sensor {}", self.dev_str);
             self.poll_sensor();
         }
+        if let Err(e) =
+            self.hk_sets
+                .send_due_sets(Instant::now(), self.stamp_helper.stamp(), &self.tm_sender)
+        {
+            log::error!("{}: sending periodic HK failed: {}", self.dev_str, e);
+        }
     }
 
     pub fn handle_composite_requests(&mut self) {
@@ -217,6 +267,9 @@ impl<
                     // TODO: This object does not have actions (yet).. Still send back completion failure
                     // reply.
                     CompositeRequest::Action(_action_req) => {}
+                    CompositeRequest::RawCommand(raw_cmd) => {
+                        self.handle_raw_command(&msg.requestor_info, raw_cmd)
+                    }
                 },
 
                 Err(e) => {
@@ -235,42 +288,78 @@ impl<
     }
 
     pub fn handle_hk_request(&mut self, requestor_info: &MessageMetadata, hk_request: &HkRequest) {
-        match hk_request.variant {
-            HkRequestVariant::OneShot => {
-                let mgm_snapshot = *self.shared_mgm_set.lock().unwrap();
-                if let Ok(hk_tm) = self.hk_helper.generate_hk_report_packet(
-                    self.stamp_helper.stamp(),
-                    SetId::SensorData as u32,
-                    &mut |hk_buf| {
-                        hk_buf[0] = mgm_snapshot.valid as u8;
-                        hk_buf[1..5].copy_from_slice(&mgm_snapshot.x.to_be_bytes());
-                        hk_buf[5..9].copy_from_slice(&mgm_snapshot.y.to_be_bytes());
-                        hk_buf[9..13].copy_from_slice(&mgm_snapshot.z.to_be_bytes());
-                        Ok(13)
-                    },
-                    &mut self.bufs.tm_buf,
-                ) {
-                    // TODO: If sending the TM fails, we should also send a failure reply.
-                    self.tm_sender
-                        .send_tm(self.id.id(), PusTmVariant::Direct(hk_tm))
-                        .expect("failed to send HK TM");
-                    self.hk_reply_tx
-                        .send(GenericMessage::new(
-                            *requestor_info,
-                            HkReply::new(hk_request.unique_id, HkReplyVariant::Ack),
-                        ))
-                        .expect("failed to send HK reply");
-                } else {
-                    // TODO: Send back failure reply. Need result code for this.
-                    log::error!("TM buffer too small to generate HK data");
-                }
+        match self
+            .hk_sets
+            .handle_request(hk_request, self.stamp_helper.stamp(), &self.tm_sender)
+        {
+            Ok(()) => {
+                self.hk_reply_tx
+                    .send(GenericMessage::new(
+                        *requestor_info,
+                        HkReply::new(hk_request.unique_id, HkReplyVariant::Ack),
+                    ))
+                    .expect("failed to send HK reply");
+            }
+            Err(e) => {
+                // TODO: Send back failure reply. Need result code for this.
+                log::error!("{}: HK request handling failed: {}", self.dev_str, e);
             }
-            HkRequestVariant::EnablePeriodic => todo!(),
-            HkRequestVariant::DisablePeriodic => todo!(),
-            HkRequestVariant::ModifyCollectionInterval(_) => todo!(),
         }
     }
 
+    /// Raw commands are only forwarded to the device in RAW mode. The raw reply of the device
+    /// is sent back, followed by a completion reply.
+    pub fn handle_raw_command(
+        &mut self,
+        requestor_info: &MessageMetadata,
+        raw_cmd: &RawDeviceCommand,
+    ) {
+        if self.mode() != DeviceMode::Raw as u32 {
+            self.send_raw_reply(
+                requestor_info,
+                RawDeviceReplyVariant::Failed(mode_err::WRONG_MODE),
+            );
+            return;
+        }
+        let raw_len = raw_cmd.data.len();
+        if raw_len > self.bufs.tx_buf.len() {
+            self.send_raw_reply(
+                requestor_info,
+                RawDeviceReplyVariant::Failed(dev_err::RAW_COMMAND_TOO_LARGE),
+            );
+            return;
+        }
+        self.bufs.tx_buf[0..raw_len].copy_from_slice(&raw_cmd.data);
+        if self
+            .com_interface
+            .transfer(
+                &self.bufs.tx_buf[0..raw_len],
+                &mut self.bufs.rx_buf[0..raw_len],
+            )
+            .is_err()
+        {
+            self.send_raw_reply(
+                requestor_info,
+                RawDeviceReplyVariant::Failed(dev_err::RAW_TRANSFER_FAILED),
+            );
+            return;
+        }
+        self.send_raw_reply(
+            requestor_info,
+            RawDeviceReplyVariant::Data(self.bufs.rx_buf[0..raw_len].to_vec()),
+        );
+        self.send_raw_reply(requestor_info, RawDeviceReplyVariant::Completed);
+    }
+
+    fn send_raw_reply(&self, requestor_info: &MessageMetadata, variant: RawDeviceReplyVariant) {
+        self.raw_reply_tx
+            .send(GenericMessage::new(
+                *requestor_info,
+                RawDeviceReply::new(variant),
+            ))
+            .expect("failed to send raw reply");
+    }
+
     pub fn handle_mode_requests(&mut self) {
         loop {
             // TODO: Only allow one set mode request per cycle?
@@ -333,6 +422,7 @@ impl<
     pub fn handle_mode_transition(&mut self, target_mode_submode: ModeAndSubmode) {
         if target_mode_submode.mode() == DeviceMode::On as u32
             || target_mode_submode.mode() == DeviceMode::Normal as u32
+            || target_mode_submode.mode() == DeviceMode::Raw as u32
         {
             if self.mode_helpers.transition_state == TransitionState::Idle {
                 let result = self
@@ -397,6 +487,7 @@ impl<
             self.handle_mode_reached(Some(requestor))?;
         } else if mode_and_submode.mode() == DeviceMode::Normal as u32
             || mode_and_submode.mode() == DeviceMode::On as u32
+            || mode_and_submode.mode() == DeviceMode::Raw as u32
         {
             // TODO: Write helper method for the struct? Might help for other handlers as well..
             self.mode_helpers.transition_state = TransitionState::Idle;
@@ -475,6 +566,7 @@ mod tests {
     };
     use satrs_example::config::components::Apid;
     use satrs_minisim::acs::lis3mdl::MgmLis3RawValues;
+    use satrs_minisim::fake::{FakeClock, FakePowerBus};
 
     use crate::{eps::TestSwitchHelper, pus::hk::HkReply, requests::CompositeRequest};
 
@@ -501,19 +593,25 @@ mod tests {
         }
     }
 
-    pub struct MgmTestbench {
+    pub struct MgmTestbench<ComInterface: SpiInterface = TestSpiInterface> {
         pub mode_request_tx: mpsc::Sender<GenericMessage<ModeRequest>>,
         pub mode_reply_rx_to_pus: mpsc::Receiver<GenericMessage<ModeReply>>,
         pub mode_reply_rx_to_parent: mpsc::Receiver<GenericMessage<ModeReply>>,
         pub composite_request_tx: mpsc::Sender<GenericMessage<CompositeRequest>>,
         pub hk_reply_rx: mpsc::Receiver<GenericMessage<HkReply>>,
+        pub raw_reply_rx: mpsc::Receiver<GenericMessage<RawDeviceReply>>,
         pub tm_rx: mpsc::Receiver<PacketAsVec>,
-        pub handler:
-            MgmHandlerLis3Mdl<TestSpiInterface, mpsc::Sender<PacketAsVec>, TestSwitchHelper>,
+        pub handler: MgmHandlerLis3Mdl<ComInterface, mpsc::Sender<PacketAsVec>, TestSwitchHelper>,
     }
 
     impl MgmTestbench {
         pub fn new() -> Self {
+            Self::new_with_interface(TestSpiInterface::default())
+        }
+    }
+
+    impl<ComInterface: SpiInterface> MgmTestbench<ComInterface> {
+        pub fn new_with_interface(com_interface: ComInterface) -> Self {
             let (request_tx, request_rx) = mpsc::channel();
             let (reply_tx_to_pus, reply_rx_to_pus) = mpsc::channel();
             let (reply_tx_to_parent, reply_rx_to_parent) = mpsc::sync_channel(5);
@@ -524,6 +622,7 @@ mod tests {
             };
             let (composite_request_tx, composite_request_rx) = mpsc::channel();
             let (hk_reply_tx, hk_reply_rx) = mpsc::channel();
+            let (raw_reply_tx, raw_reply_rx) = mpsc::channel();
             let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
             let shared_mgm_set = Arc::default();
             Self {
@@ -533,16 +632,22 @@ mod tests {
                 composite_request_tx,
                 tm_rx,
                 hk_reply_rx,
+                raw_reply_rx,
                 handler: MgmHandlerLis3Mdl::new(
                     UniqueApidTargetId::new(Apid::Acs as u16, 1),
                     "TEST_MGM",
                     mode_interface,
                     composite_request_rx,
                     hk_reply_tx,
+                    raw_reply_tx,
                     TestSwitchHelper::default(),
                     tm_tx,
-                    TestSpiInterface::default(),
-                    shared_mgm_set,
+                    com_interface,
+                    shared_mgm_set.clone(),
+                    create_mgm_hk_sets(
+                        UniqueApidTargetId::new(Apid::Acs as u16, 1),
+                        shared_mgm_set,
+                    ),
                 ),
             }
         }
@@ -651,4 +756,83 @@ mod tests {
         assert!(z_diff < 0.001, "z diff too large: {}", z_diff);
         assert!(mgm_set.valid);
     }
+
+    #[test]
+    fn test_normal_handler_with_fake_device() {
+        let clock = FakeClock::new_manual();
+        let power_bus = FakePowerBus::default();
+        let mut mgm = FakeMgmLis3Mdl::new(clock, power_bus.clone());
+        mgm.set_rotation_rate(0.0);
+        let mut testbench = MgmTestbench::new_with_interface(SpiFakeInterface { mgm });
+        // The test switch helper does not switch the fake power bus, so this is done manually.
+        power_bus.set_switch_state(PcduSwitch::Mgm, SwitchStateBinary::On);
+        testbench
+            .mode_request_tx
+            .send(GenericMessage::new(
+                MessageMetadata::new(0, PUS_MODE_SERVICE.id()),
+                ModeRequest::SetMode(ModeAndSubmode::new(DeviceMode::Normal as u32, 0)),
+            ))
+            .expect("failed to send mode request");
+        testbench.handler.periodic_operation();
+        let mgm_set = *testbench.handler.shared_mgm_set.lock().unwrap();
+        let expected = FakeMgmLis3Mdl::DEFAULT_FIELD;
+        // The raw values have a resolution of roughly 0.015 micro tesla.
+        assert!((mgm_set.x - expected.x).abs() < 0.02);
+        assert!((mgm_set.y - expected.y).abs() < 0.02);
+        assert!((mgm_set.z - expected.z).abs() < 0.02);
+        assert!(mgm_set.valid);
+    }
+
+    #[test]
+    fn test_raw_command() {
+        let mut testbench = MgmTestbench::new();
+        let requestor = MessageMetadata::new(1, PUS_MODE_SERVICE.id());
+        testbench
+            .composite_request_tx
+            .send(GenericMessage::new(
+                requestor,
+                CompositeRequest::RawCommand(RawDeviceCommand::new(vec![0; 4])),
+            ))
+            .unwrap();
+        testbench.handler.periodic_operation();
+        // Raw commands are rejected outside of RAW mode.
+        let reply = testbench.raw_reply_rx.try_recv().expect("no raw reply");
+        assert_eq!(
+            reply.message.variant,
+            RawDeviceReplyVariant::Failed(mode_err::WRONG_MODE)
+        );
+        assert_eq!(testbench.handler.com_interface.call_count, 0);
+
+        testbench
+            .mode_request_tx
+            .send(GenericMessage::new(
+                MessageMetadata::new(0, PUS_MODE_SERVICE.id()),
+                ModeRequest::SetMode(ModeAndSubmode::new(DeviceMode::Raw as u32, 0)),
+            ))
+            .unwrap();
+        testbench.handler.periodic_operation();
+        assert_eq!(
+            testbench.handler.mode_and_submode().mode(),
+            DeviceMode::Raw as u32
+        );
+        // The device is not polled in RAW mode.
+        assert_eq!(testbench.handler.com_interface.call_count, 0);
+        testbench
+            .composite_request_tx
+            .send(GenericMessage::new(
+                requestor,
+                CompositeRequest::RawCommand(RawDeviceCommand::new(vec![0; 15])),
+            ))
+            .unwrap();
+        testbench.handler.periodic_operation();
+        assert_eq!(testbench.handler.com_interface.call_count, 1);
+        let reply = testbench.raw_reply_rx.try_recv().expect("no raw reply");
+        assert_eq!(reply.request_id(), 1);
+        match reply.message.variant {
+            RawDeviceReplyVariant::Data(data) => assert_eq!(data.len(), 15),
+            _ => panic!("unexpected raw reply"),
+        }
+        let reply = testbench.raw_reply_rx.try_recv().expect("no raw reply");
+        assert_eq!(reply.message.variant, RawDeviceReplyVariant::Completed);
+    }
 }
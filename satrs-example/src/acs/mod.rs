@@ -1 +1,2 @@
 pub mod mgm;
+pub mod subsystem;
@@ -0,0 +1,302 @@
+//! ACS subsystem which groups all ACS components handled by the AOCS thread.
+//!
+//! Right now, the subsystem consists of the LIS3MDL MGM device handler. The [AcsSubsystem]
+//! creates the request queues of its components, which are returned as [AcsRequestSenders] to
+//! register them at the [GenericRequestRouter], and it is the mode parent of its components.
+//! The subsystem is an [Executable] which is driven by the fixed-rate scheduler of the AOCS
+//! thread.
+use std::convert::Infallible;
+use std::sync::{mpsc, Arc, Mutex};
+
+use satrs::device::RawDeviceReply;
+use satrs::executable::{Executable, ExecutionType, OpResult};
+use satrs::id_registry::ComponentIdRegistry;
+use satrs::mode::{ModeReply, ModeRequest};
+use satrs::power::{PowerSwitchInfo, PowerSwitcherCommandSender};
+use satrs::pus::EcssTmSender;
+use satrs::request::GenericMessage;
+use satrs_example::config::components::MGM_HANDLER_0;
+use satrs_minisim::eps::PcduSwitch;
+use satrs_minisim::fake::FakeMgmLis3Mdl;
+use satrs_minisim::{SimComponent, SimRequest};
+
+use crate::acs::mgm::{
+    create_mgm_hk_sets, MgmData, MgmHandlerLis3Mdl, MpscModeLeafInterface, SpiFakeInterface,
+    SpiInterface, SpiSimInterface, SpiSimInterfaceWrapper,
+};
+use crate::interface::sim_client_udp::SimClientUdp;
+use crate::pus::hk::HkReply;
+use crate::requests::{CompositeRequest, GenericRequestRouter};
+
+pub const MGM_COMPOSITE_QUEUE_DEPTH: usize = 10;
+pub const MGM_MODE_QUEUE_DEPTH: usize = 5;
+
+/// Request queues of the ACS components.
+pub struct AcsRequestSenders {
+    pub mgm_composite_tx: mpsc::SyncSender<GenericMessage<CompositeRequest>>,
+    pub mgm_mode_tx: mpsc::SyncSender<GenericMessage<ModeRequest>>,
+}
+
+impl AcsRequestSenders {
+    /// Register all ACS components at the request router and their IDs in the component ID
+    /// registry.
+    pub fn register(
+        self,
+        request_router: &mut GenericRequestRouter,
+        component_ids: &mut ComponentIdRegistry,
+    ) {
+        component_ids
+            .register_target(MGM_HANDLER_0, "MGM_HANDLER_0")
+            .expect("registering component ID failed");
+        request_router
+            .composite_router_map
+            .register_with_target_id(MGM_HANDLER_0, self.mgm_composite_tx)
+            .expect("registering composite request target failed");
+        request_router
+            .mode_router_map
+            .register_with_target_id(MGM_HANDLER_0, self.mgm_mode_tx)
+            .expect("registering mode request target failed");
+    }
+}
+
+/// Reply and TM senders which are shared by all ACS components.
+pub struct AcsReplySenders<TmSender: EcssTmSender> {
+    pub mode_reply_tx: mpsc::Sender<GenericMessage<ModeReply>>,
+    pub hk_reply_tx: mpsc::Sender<GenericMessage<HkReply>>,
+    pub raw_reply_tx: mpsc::Sender<GenericMessage<RawDeviceReply>>,
+    pub tm_sender: TmSender,
+}
+
+/// Create the communication interface of the MGM handler. The mini simulator is used if a
+/// simulator client is available, otherwise the in-process fake device is used.
+pub fn create_mgm_com_interface(
+    sim_client: Option<&mut SimClientUdp>,
+    sim_request_tx: &mpsc::Sender<SimRequest>,
+    fake_mgm: FakeMgmLis3Mdl,
+) -> SpiSimInterfaceWrapper {
+    match sim_client {
+        Some(sim_client) => {
+            let (mgm_sim_reply_tx, mgm_sim_reply_rx) = mpsc::channel();
+            sim_client.add_reply_recipient(SimComponent::MgmLis3Mdl, mgm_sim_reply_tx);
+            SpiSimInterfaceWrapper::Sim(SpiSimInterface {
+                sim_request_tx: sim_request_tx.clone(),
+                sim_reply_rx: mgm_sim_reply_rx,
+            })
+        }
+        None => SpiSimInterfaceWrapper::Fake(SpiFakeInterface { mgm: fake_mgm }),
+    }
+}
+
+/// ACS subsystem. The [Self::periodic_operation] function should be called periodically, for
+/// example by scheduling the subsystem as an [Executable].
+pub struct AcsSubsystem<
+    ComInterface: SpiInterface,
+    TmSender: EcssTmSender,
+    SwitchHelper: PowerSwitchInfo<PcduSwitch> + PowerSwitcherCommandSender<PcduSwitch>,
+> {
+    mgm_handler: MgmHandlerLis3Mdl<ComInterface, TmSender, SwitchHelper>,
+    shared_mgm_set: Arc<Mutex<MgmData>>,
+    mode_reply_from_children_rx: mpsc::Receiver<GenericMessage<ModeReply>>,
+}
+
+impl<
+        ComInterface: SpiInterface,
+        TmSender: EcssTmSender,
+        SwitchHelper: PowerSwitchInfo<PcduSwitch> + PowerSwitcherCommandSender<PcduSwitch>,
+    > AcsSubsystem<ComInterface, TmSender, SwitchHelper>
+{
+    /// Create the ACS subsystem and its components.
+    ///
+    /// ## Parameter
+    ///
+    /// * `mgm_com_interface` - Communication interface of the MGM handler, for example created
+    ///     with [create_mgm_com_interface].
+    /// * `switch_helper` - Used to switch the power of the ACS components.
+    /// * `reply_senders` - Senders for the replies to the PUS services and the generated TM.
+    pub fn new(
+        mgm_com_interface: ComInterface,
+        switch_helper: SwitchHelper,
+        reply_senders: AcsReplySenders<TmSender>,
+    ) -> (Self, AcsRequestSenders) {
+        let (mgm_composite_tx, mgm_composite_rx) = mpsc::sync_channel(MGM_COMPOSITE_QUEUE_DEPTH);
+        let (mgm_mode_tx, mgm_mode_rx) = mpsc::sync_channel(MGM_MODE_QUEUE_DEPTH);
+        let (mode_reply_to_parent_tx, mode_reply_from_children_rx) =
+            mpsc::sync_channel(MGM_MODE_QUEUE_DEPTH);
+        let mgm_mode_leaf_interface = MpscModeLeafInterface {
+            request_rx: mgm_mode_rx,
+            reply_to_pus_tx: reply_senders.mode_reply_tx,
+            reply_to_parent_tx: mode_reply_to_parent_tx,
+        };
+        let shared_mgm_set = Arc::default();
+        let mgm_handler = MgmHandlerLis3Mdl::new(
+            MGM_HANDLER_0,
+            "MGM_0",
+            mgm_mode_leaf_interface,
+            mgm_composite_rx,
+            reply_senders.hk_reply_tx,
+            reply_senders.raw_reply_tx,
+            switch_helper,
+            reply_senders.tm_sender,
+            mgm_com_interface,
+            shared_mgm_set.clone(),
+            create_mgm_hk_sets(MGM_HANDLER_0, shared_mgm_set.clone()),
+        );
+        (
+            Self {
+                mgm_handler,
+                shared_mgm_set,
+                mode_reply_from_children_rx,
+            },
+            AcsRequestSenders {
+                mgm_composite_tx,
+                mgm_mode_tx,
+            },
+        )
+    }
+
+    pub fn mgm_handler(&self) -> &MgmHandlerLis3Mdl<ComInterface, TmSender, SwitchHelper> {
+        &self.mgm_handler
+    }
+
+    pub fn mgm_handler_mut(
+        &mut self,
+    ) -> &mut MgmHandlerLis3Mdl<ComInterface, TmSender, SwitchHelper> {
+        &mut self.mgm_handler
+    }
+
+    /// Latest MGM sensor data.
+    pub fn mgm_set(&self) -> MgmData {
+        *self.shared_mgm_set.lock().unwrap()
+    }
+
+    pub fn periodic_operation(&mut self) {
+        self.mgm_handler.periodic_operation();
+        self.handle_child_mode_replies();
+    }
+
+    fn handle_child_mode_replies(&mut self) {
+        // The subsystem does not command the modes of its children yet, so the replies are only
+        // logged.
+        while let Ok(reply) = self.mode_reply_from_children_rx.try_recv() {
+            log::debug!(
+                "ACS: mode reply from child {:#x}: {:?}",
+                reply.sender_id(),
+                reply.message
+            );
+        }
+    }
+}
+
+impl<
+        ComInterface: SpiInterface,
+        TmSender: EcssTmSender,
+        SwitchHelper: PowerSwitchInfo<PcduSwitch> + PowerSwitcherCommandSender<PcduSwitch>,
+    > Executable for AcsSubsystem<ComInterface, TmSender, SwitchHelper>
+{
+    type Error = Infallible;
+
+    fn exec_type(&self) -> ExecutionType {
+        ExecutionType::Infinite
+    }
+
+    fn task_name(&self) -> &'static str {
+        "ACS subsystem"
+    }
+
+    fn periodic_op(&mut self, _op_code: i32) -> Result<OpResult, Self::Error> {
+        self.periodic_operation();
+        Ok(OpResult::Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use satrs::mode::{ModeAndSubmode, ModeProvider};
+    use satrs::power::SwitchStateBinary;
+    use satrs::request::MessageMetadata;
+    use satrs::tmtc::PacketAsVec;
+    use satrs_example::config::components::PUS_MODE_SERVICE;
+    use satrs_example::DeviceMode;
+    use satrs_minisim::fake::{FakeClock, FakePowerBus};
+
+    use crate::eps::TestSwitchHelper;
+
+    use super::*;
+
+    #[test]
+    fn test_subsystem_mode_and_polling() {
+        let power_bus = FakePowerBus::default();
+        let mut mgm = FakeMgmLis3Mdl::new(FakeClock::new_manual(), power_bus.clone());
+        mgm.set_rotation_rate(0.0);
+        power_bus.set_switch_state(PcduSwitch::Mgm, SwitchStateBinary::On);
+        let (mode_reply_tx, mode_reply_rx) = mpsc::channel();
+        let (hk_reply_tx, _hk_reply_rx) = mpsc::channel();
+        let (raw_reply_tx, _raw_reply_rx) = mpsc::channel();
+        let (tm_tx, _tm_rx) = mpsc::channel::<PacketAsVec>();
+        let (mut acs, senders) = AcsSubsystem::new(
+            SpiFakeInterface { mgm },
+            TestSwitchHelper::default(),
+            AcsReplySenders {
+                mode_reply_tx,
+                hk_reply_tx,
+                raw_reply_tx,
+                tm_sender: tm_tx,
+            },
+        );
+        acs.periodic_operation();
+        assert!(!acs.mgm_set().valid);
+
+        senders
+            .mgm_mode_tx
+            .send(GenericMessage::new(
+                MessageMetadata::new(0, PUS_MODE_SERVICE.id()),
+                ModeRequest::SetMode(ModeAndSubmode::new(DeviceMode::Normal as u32, 0)),
+            ))
+            .expect("failed to send mode request");
+        acs.periodic_operation();
+        assert_eq!(
+            acs.mgm_handler().mode_and_submode().mode(),
+            DeviceMode::Normal as u32
+        );
+        let mode_reply = mode_reply_rx.try_recv().expect("no mode reply generated");
+        assert_eq!(
+            mode_reply.message,
+            ModeReply::ModeReply(ModeAndSubmode::new(DeviceMode::Normal as u32, 0))
+        );
+        let mgm_set = acs.mgm_set();
+        let expected = FakeMgmLis3Mdl::DEFAULT_FIELD;
+        assert!((mgm_set.x - expected.x).abs() < 0.02);
+        assert!(mgm_set.valid);
+    }
+
+    #[test]
+    fn test_request_sender_registration() {
+        let (mode_reply_tx, _mode_reply_rx) = mpsc::channel();
+        let (hk_reply_tx, _hk_reply_rx) = mpsc::channel();
+        let (raw_reply_tx, _raw_reply_rx) = mpsc::channel();
+        let (tm_tx, _tm_rx) = mpsc::channel::<PacketAsVec>();
+        let (fake_mgm_clock, power_bus) = (FakeClock::new_manual(), FakePowerBus::default());
+        let (_acs, senders) = AcsSubsystem::new(
+            SpiFakeInterface {
+                mgm: FakeMgmLis3Mdl::new(fake_mgm_clock, power_bus),
+            },
+            TestSwitchHelper::default(),
+            AcsReplySenders {
+                mode_reply_tx,
+                hk_reply_tx,
+                raw_reply_tx,
+                tm_sender: tm_tx,
+            },
+        );
+        let mut request_router = GenericRequestRouter::default();
+        senders.register(&mut request_router, &mut ComponentIdRegistry::new());
+        assert!(request_router
+            .mode_router_map
+            .unregister(MGM_HANDLER_0.raw())
+            .is_some());
+        assert!(request_router
+            .composite_router_map
+            .unregister(MGM_HANDLER_0.raw())
+            .is_some());
+    }
+}
@@ -3,15 +3,16 @@ use satrs::{
     res_code::ResultU16,
     spacepackets::{PacketId, PacketType},
 };
-use satrs_mib::res_code::ResultU16Info;
+use satrs_mib::res_code::{ResultU16Info, ResultU16Registry};
 use satrs_mib::resultcode;
 use std::{collections::HashSet, net::Ipv4Addr};
 use strum::IntoEnumIterator;
 
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use satrs::{
-    events::{EventU32TypedSev, SeverityInfo},
-    pool::{StaticMemoryPool, StaticPoolConfig},
+    events::SeverityInfo,
+    pool::{PriorityClass, StaticMemoryPool, StaticPoolConfig},
+    pus::tc_quarantine::CrcCheckPolicy,
 };
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, TryFromPrimitive, IntoPrimitive)]
@@ -33,12 +34,22 @@ pub enum GroupId {
     Tmtc = 0,
     Hk = 1,
     Mode = 2,
+    Device = 3,
+    Event = 4,
 }
 
 pub const OBSW_SERVER_ADDR: Ipv4Addr = Ipv4Addr::UNSPECIFIED;
 pub const SERVER_PORT: u16 = 7301;
 
-pub const TEST_EVENT: EventU32TypedSev<SeverityInfo> = EventU32TypedSev::<SeverityInfo>::new(0, 0);
+/// Telecommands with an invalid CRC are rejected. Bench setups with known bad CRC sources can
+/// quarantine these telecommands instead, so that link issues show up in the logs and as events.
+pub const TC_CRC_CHECK_POLICY: CrcCheckPolicy = CrcCheckPolicy::Reject;
+
+satrs::event_definitions! {
+    pub EXAMPLE_EVENT_DEFINITIONS;
+    /// Generated by the test service for the custom TC[17,128].
+    pub TEST_EVENT: SeverityInfo = (0, 0), None, "Test event triggered by a telecommand";
+}
 
 lazy_static! {
     pub static ref PACKET_ID_VALIDATOR: HashSet<PacketId> = {
@@ -81,14 +92,16 @@ pub mod tmtc_err {
           8 bytes of failure data containing 2 failure parameters, \
           P1 (u32 big endian): Expected data length, P2: Found data length"
     )]
-    pub const NOT_ENOUGH_APP_DATA: ResultU16 = ResultU16::new(GroupId::Tmtc as u8, 2);
+    pub const NOT_ENOUGH_APP_DATA: ResultU16 = ResultU16::new(GroupId::Tmtc as u8, 7);
 
     pub const TMTC_RESULTS: &[ResultU16Info] = &[
         INVALID_PUS_SERVICE_EXT,
         INVALID_PUS_SUBSERVICE_EXT,
         PUS_SERVICE_NOT_IMPLEMENTED_EXT,
+        PUS_SUBSERVICE_NOT_IMPLEMENTED_EXT,
         UNKNOWN_TARGET_ID_EXT,
         ROUTING_ERROR_EXT,
+        REQUEST_TIMEOUT_EXT,
         NOT_ENOUGH_APP_DATA_EXT,
     ];
 }
@@ -108,7 +121,7 @@ pub mod hk_err {
 
     pub const HK_ERR_RESULTS: &[ResultU16Info] = &[
         TARGET_ID_MISSING_EXT,
-        UNKNOWN_TARGET_ID_EXT,
+        UNIQUE_ID_MISSING_EXT,
         UNKNOWN_TARGET_ID_EXT,
         COLLECTION_INTERVAL_MISSING_EXT,
     ];
@@ -119,6 +132,61 @@ pub mod mode_err {
 
     #[resultcode]
     pub const WRONG_MODE: ResultU16 = ResultU16::new(GroupId::Mode as u8, 0);
+    #[resultcode(info = "The submode is not valid for the commanded mode")]
+    pub const INVALID_SUBMODE: ResultU16 = ResultU16::new(GroupId::Mode as u8, 1);
+
+    pub const MODE_ERR_RESULTS: &[ResultU16Info] = &[WRONG_MODE_EXT, INVALID_SUBMODE_EXT];
+}
+
+pub mod dev_err {
+    use super::*;
+
+    #[resultcode(info = "Raw command is larger than the transfer buffer of the device handler")]
+    pub const RAW_COMMAND_TOO_LARGE: ResultU16 = ResultU16::new(GroupId::Device as u8, 0);
+    #[resultcode]
+    pub const RAW_TRANSFER_FAILED: ResultU16 = ResultU16::new(GroupId::Device as u8, 1);
+    #[resultcode(info = "Raw command could not be parsed by the device handler")]
+    pub const INVALID_RAW_COMMAND: ResultU16 = ResultU16::new(GroupId::Device as u8, 2);
+    #[resultcode(info = "Communication with the device failed during a mode transition")]
+    pub const DEVICE_COMMUNICATION_FAILED: ResultU16 = ResultU16::new(GroupId::Device as u8, 3);
+    #[resultcode(info = "The device ID read from the device does not match the expected ID")]
+    pub const DEVICE_ID_MISMATCH: ResultU16 = ResultU16::new(GroupId::Device as u8, 4);
+
+    pub const DEV_ERR_RESULTS: &[ResultU16Info] = &[
+        RAW_COMMAND_TOO_LARGE_EXT,
+        RAW_TRANSFER_FAILED_EXT,
+        INVALID_RAW_COMMAND_EXT,
+        DEVICE_COMMUNICATION_FAILED_EXT,
+        DEVICE_ID_MISMATCH_EXT,
+    ];
+}
+
+pub mod event_err {
+    use super::*;
+
+    #[resultcode(info = "Sending the records of the event log failed")]
+    pub const EVENT_LOG_DUMP_FAILED: ResultU16 = ResultU16::new(GroupId::Event as u8, 0);
+    #[resultcode(info = "Replaying the event log as event reports failed")]
+    pub const EVENT_LOG_REPLAY_FAILED: ResultU16 = ResultU16::new(GroupId::Event as u8, 1);
+    #[resultcode(info = "Clearing the event log failed")]
+    pub const EVENT_LOG_CLEAR_FAILED: ResultU16 = ResultU16::new(GroupId::Event as u8, 2);
+
+    pub const EVENT_ERR_RESULTS: &[ResultU16Info] = &[
+        EVENT_LOG_DUMP_FAILED_EXT,
+        EVENT_LOG_REPLAY_FAILED_EXT,
+        EVENT_LOG_CLEAR_FAILED_EXT,
+    ];
+}
+
+/// Registry of all result codes of the example. Creating it panics if two result codes collide.
+pub fn result_code_registry() -> ResultU16Registry {
+    ResultU16Registry::with_codes(&[
+        tmtc_err::TMTC_RESULTS,
+        hk_err::HK_ERR_RESULTS,
+        mode_err::MODE_ERR_RESULTS,
+        dev_err::DEV_ERR_RESULTS,
+        event_err::EVENT_ERR_RESULTS,
+    ])
 }
 
 pub mod components {
@@ -133,6 +201,7 @@ pub mod components {
         Cfdp = 4,
         Tmtc = 5,
         Eps = 6,
+        Tcs = 7,
     }
 
     // Component IDs for components with the PUS APID.
@@ -144,6 +213,7 @@ pub mod components {
         PusAction = 3,
         PusMode = 4,
         PusHk = 5,
+        PusDeviceAccess = 6,
     }
 
     #[derive(Copy, Clone, PartialEq, Eq)]
@@ -156,6 +226,12 @@ pub mod components {
         Pcdu = 0,
     }
 
+    #[derive(Copy, Clone, PartialEq, Eq)]
+    pub enum TcsId {
+        Heater0 = 0,
+        TempSensor0 = 1,
+    }
+
     #[derive(Copy, Clone, PartialEq, Eq)]
     pub enum TmtcId {
         UdpServer = 0,
@@ -174,12 +250,18 @@ pub mod components {
         UniqueApidTargetId::new(Apid::GenericPus as u16, PusId::PusMode as u32);
     pub const PUS_HK_SERVICE: UniqueApidTargetId =
         UniqueApidTargetId::new(Apid::GenericPus as u16, PusId::PusHk as u32);
+    pub const PUS_DEVICE_ACCESS_SERVICE: UniqueApidTargetId =
+        UniqueApidTargetId::new(Apid::GenericPus as u16, PusId::PusDeviceAccess as u32);
     pub const PUS_SCHED_SERVICE: UniqueApidTargetId =
         UniqueApidTargetId::new(Apid::Sched as u16, 0);
     pub const MGM_HANDLER_0: UniqueApidTargetId =
         UniqueApidTargetId::new(Apid::Acs as u16, AcsId::Mgm0 as u32);
     pub const PCDU_HANDLER: UniqueApidTargetId =
         UniqueApidTargetId::new(Apid::Eps as u16, EpsId::Pcdu as u32);
+    pub const HEATER_0: UniqueApidTargetId =
+        UniqueApidTargetId::new(Apid::Tcs as u16, TcsId::Heater0 as u32);
+    pub const TEMP_SENSOR_0: UniqueApidTargetId =
+        UniqueApidTargetId::new(Apid::Tcs as u16, TcsId::TempSensor0 as u32);
     pub const UDP_SERVER: UniqueApidTargetId =
         UniqueApidTargetId::new(Apid::Tmtc as u16, TmtcId::UdpServer as u32);
     pub const TCP_SERVER: UniqueApidTargetId =
@@ -189,6 +271,16 @@ pub mod components {
 
 pub mod pool {
     use super::*;
+
+    /// Number of priority classes of the TC source queue.
+    pub const NUM_TC_PRIORITY_CLASSES: u8 = 2;
+    /// Released time-tagged telecommands have the highest priority because they should be
+    /// executed as close to their release time as possible.
+    pub const TC_PRIO_SCHEDULED: PriorityClass = 0;
+    /// Priority of telecommands which are received from ground for immediate execution.
+    pub const TC_PRIO_IMMEDIATE: PriorityClass = 1;
+    pub const TC_QUEUE_LANE_CAPACITY: usize = 50;
+
     pub fn create_static_pools() -> (StaticMemoryPool, StaticMemoryPool) {
         (
             StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
@@ -216,6 +308,18 @@ pub mod pool {
         )
     }
 
+    /// Maximum number of records of the on-board event log.
+    pub const EVENT_LOG_MAX_RECORDS: usize = 128;
+    /// Maximum size of a serialized event log record.
+    pub const EVENT_LOG_MAX_RECORD_SIZE: usize = 128;
+
+    pub fn create_event_log_pool() -> StaticMemoryPool {
+        StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(128, 32), (32, 64), (16, EVENT_LOG_MAX_RECORD_SIZE)],
+            true,
+        ))
+    }
+
     pub fn create_sched_tc_pool() -> StaticMemoryPool {
         StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![
@@ -234,6 +338,28 @@ pub mod pool {
 pub mod tasks {
     pub const FREQ_MS_UDP_TMTC: u64 = 200;
     pub const FREQ_MS_AOCS: u64 = 500;
+    pub const FREQ_MS_TCS: u64 = 1000;
+    /// Major period of the polling sequence of the EPS thread.
+    pub const FREQ_MS_EPS: u64 = 400;
     pub const FREQ_MS_PUS_STACK: u64 = 200;
     pub const SIM_CLIENT_IDLE_DELAY_MS: u64 = 5;
+    /// The TM sink waits up to this time for new TM before checking the shutdown signal.
+    pub const TM_SINK_RECV_TIMEOUT_MS: u64 = 100;
+    /// Maximum time each thread spends handling queued input after a shutdown was requested.
+    pub const SHUTDOWN_DRAIN_TIMEOUT_MS: u64 = 1000;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_codes_do_not_collide() {
+        let registry = result_code_registry();
+        assert_eq!(registry.len(), 19);
+        assert_eq!(
+            registry.name_of(tmtc_err::NOT_ENOUGH_APP_DATA),
+            Some("NOT_ENOUGH_APP_DATA")
+        );
+    }
 }
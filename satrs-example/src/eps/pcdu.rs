@@ -1,12 +1,15 @@
 use std::{
     cell::RefCell,
     collections::VecDeque,
+    convert::Infallible,
     sync::{mpsc, Arc, Mutex},
+    time::Duration,
 };
 
 use derive_new::new;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use satrs::{
+    executable::{Executable, ExecutionType, OpResult, PollingSequenceTable},
     hk::{HkRequest, HkRequestVariant},
     mode::{ModeAndSubmode, ModeError, ModeProvider, ModeReply, ModeRequestHandler},
     power::SwitchRequest,
@@ -16,13 +19,15 @@ use satrs::{
     spacepackets::ByteConversionError,
 };
 use satrs_example::{
-    config::components::{NO_SENDER, PUS_MODE_SERVICE},
+    config::{
+        components::{NO_SENDER, PUS_MODE_SERVICE},
+        tasks::FREQ_MS_EPS,
+    },
     DeviceMode, TimestampHelper,
 };
 use satrs_minisim::{
-    eps::{
-        PcduReply, PcduRequest, PcduSwitch, SwitchMap, SwitchMapBinaryWrapper, SwitchMapWrapper,
-    },
+    eps::{PcduReply, PcduRequest, PcduSwitch, SwitchMap, SwitchMapWrapper},
+    fake::FakePcdu,
     SerializableSimMsgPayload, SimReply, SimRequest,
 };
 use serde::{Deserialize, Serialize};
@@ -93,37 +98,34 @@ impl SerialInterface for SerialInterfaceToSim {
     }
 }
 
+/// Serial interface to an in-process fake PCDU, which can be used when the mini simulator is not
+/// running. Devices connected to the power bus of the fake PCDU are switched by it.
 #[derive(Default)]
 pub struct SerialInterfaceDummy {
-    // Need interior mutability here for both fields.
-    pub switch_map: RefCell<SwitchMapBinaryWrapper>,
+    pub pcdu: FakePcdu,
+    // Need interior mutability here.
     pub reply_deque: RefCell<VecDeque<SimReply>>,
 }
 
+impl SerialInterfaceDummy {
+    pub fn new(pcdu: FakePcdu) -> Self {
+        Self {
+            pcdu,
+            reply_deque: Default::default(),
+        }
+    }
+}
+
 impl SerialInterface for SerialInterfaceDummy {
     type Error = ();
 
     fn send(&self, data: &[u8]) -> Result<(), Self::Error> {
         let pcdu_req: PcduRequest = serde_json::from_slice(data).unwrap();
-        let switch_map_mut = &mut self.switch_map.borrow_mut().0;
-        match pcdu_req {
-            PcduRequest::SwitchDevice { switch, state } => {
-                match switch_map_mut.entry(switch) {
-                    std::collections::hash_map::Entry::Occupied(mut val) => {
-                        *val.get_mut() = state;
-                    }
-                    std::collections::hash_map::Entry::Vacant(vacant) => {
-                        vacant.insert(state);
-                    }
-                };
-            }
-            PcduRequest::RequestSwitchInfo => {
-                let mut reply_deque_mut = self.reply_deque.borrow_mut();
-                reply_deque_mut.push_back(SimReply::new(&PcduReply::SwitchInfo(
-                    switch_map_mut.clone(),
-                )));
-            }
-        };
+        if let Some(reply) = self.pcdu.handle_request(&pcdu_req) {
+            self.reply_deque
+                .borrow_mut()
+                .push_back(SimReply::new(&reply));
+        }
         Ok(())
     }
 
@@ -183,12 +185,33 @@ impl SerialInterface for SerialSimInterfaceWrapper {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[repr(i32)]
 pub enum OpCode {
     RegularOp = 0,
     PollAndRecvReplies = 1,
 }
 
+/// Polling sequence of the PCDU handler. The regular operation is performed at the start of the
+/// major period, and the replies of the PCDU are polled twice afterwards.
+pub fn create_pcdu_polling_sequence(pcdu_task_index: usize) -> PollingSequenceTable {
+    let mut table = PollingSequenceTable::new(0, Duration::from_millis(FREQ_MS_EPS));
+    for (offset_ms, op_code) in [
+        (0, OpCode::RegularOp),
+        (50, OpCode::PollAndRecvReplies),
+        (100, OpCode::PollAndRecvReplies),
+    ] {
+        table
+            .add_slot(
+                Duration::from_millis(offset_ms),
+                pcdu_task_index,
+                op_code.into(),
+            )
+            .expect("adding PCDU polling slot failed");
+    }
+    table
+}
+
 #[derive(Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct SwitchSet {
     pub valid: bool,
@@ -251,6 +274,9 @@ impl<ComInterface: SerialInterface, TmSender: EcssTmSender> PcduHandler<ComInter
                     // TODO: This object does not have actions (yet).. Still send back completion failure
                     // reply.
                     CompositeRequest::Action(_action_req) => {}
+                    CompositeRequest::RawCommand(_raw_cmd) => {
+                        log::warn!("{}: raw commanding is not supported", self.dev_str);
+                    }
                 },
 
                 Err(e) => {
@@ -396,6 +422,28 @@ impl<ComInterface: SerialInterface, TmSender: EcssTmSender> PcduHandler<ComInter
     }
 }
 
+impl<ComInterface: SerialInterface, TmSender: EcssTmSender> Executable
+    for PcduHandler<ComInterface, TmSender>
+{
+    type Error = Infallible;
+
+    fn exec_type(&self) -> ExecutionType {
+        ExecutionType::Infinite
+    }
+
+    fn task_name(&self) -> &'static str {
+        self.dev_str
+    }
+
+    fn periodic_op(&mut self, op_code: i32) -> Result<OpResult, Self::Error> {
+        match OpCode::try_from(op_code) {
+            Ok(op_code) => self.periodic_operation(op_code),
+            Err(e) => log::warn!("{}: {}", self.dev_str, e),
+        }
+        Ok(OpResult::Ok)
+    }
+}
+
 impl<ComInterface: SerialInterface, TmSender: EcssTmSender> ModeProvider
     for PcduHandler<ComInterface, TmSender>
 {
@@ -490,7 +538,7 @@ mod tests {
         mode::ModeRequest, power::SwitchStateBinary, request::GenericMessage, tmtc::PacketAsVec,
     };
     use satrs_example::config::components::{Apid, MGM_HANDLER_0};
-    use satrs_minisim::eps::SwitchMapBinary;
+    use satrs_minisim::eps::{SwitchMapBinary, SwitchMapBinaryWrapper};
 
     use super::*;
 
@@ -685,6 +733,24 @@ mod tests {
         drop(switch_map_shared);
     }
 
+    #[test]
+    fn test_polling_sequence() {
+        let table = create_pcdu_polling_sequence(0);
+        assert_eq!(table.period(), Duration::from_millis(FREQ_MS_EPS));
+        let op_codes: Vec<i32> = table.slots().iter().map(|slot| slot.op_code).collect();
+        assert_eq!(
+            op_codes,
+            [
+                i32::from(OpCode::RegularOp),
+                i32::from(OpCode::PollAndRecvReplies),
+                i32::from(OpCode::PollAndRecvReplies)
+            ]
+        );
+        // Invalid operation codes are ignored.
+        let mut testbench = PcduTestbench::new();
+        assert_eq!(testbench.handler.periodic_op(5), Ok(OpResult::Ok));
+    }
+
     #[test]
     fn test_switch_request_handling() {
         let mut testbench = PcduTestbench::new();
@@ -1,11 +1,18 @@
 use std::sync::mpsc::{self};
+use std::time::Duration;
 
 use crate::pus::create_verification_reporter;
+use satrs::event_log::EventLog;
 use satrs::event_man::{EventMessageU32, EventRoutingError};
+use satrs::events::EventU32;
+use satrs::id_registry::ComponentIdRegistry;
 use satrs::pus::event::EventTmHookProvider;
-use satrs::pus::verification::VerificationReporter;
+use satrs::pus::event_log::{replay_event_log, PusEventLogTmCreator};
+use satrs::pus::verification::{FailParams, VerificationReporter};
 use satrs::pus::EcssTmSender;
 use satrs::request::UniqueApidTargetId;
+use satrs::res_code::ResultU16;
+use satrs::shutdown::{report_shutdown, DrainOutcome};
 use satrs::{
     event_man::{EventManagerWithBoundedMpsc, EventSendProvider, EventU32SenderMpscBounded},
     pus::{
@@ -17,6 +24,10 @@ use satrs::{
     spacepackets::time::cds::CdsTime,
 };
 use satrs_example::config::components::PUS_EVENT_MANAGEMENT;
+use satrs_example::config::event_err;
+use satrs_example::config::pool::{
+    create_event_log_pool, EVENT_LOG_MAX_RECORDS, EVENT_LOG_MAX_RECORD_SIZE,
+};
 
 use crate::update_time;
 
@@ -33,10 +44,14 @@ impl EventTmHookProvider for EventApidSetter {
 }
 
 /// The PUS event handler subscribes for all events and converts them into ECSS PUS 5 event
-/// packets. It also handles the verification completion of PUS event service requests.
+/// packets. All events are also recorded in the on-board event log, which can be downlinked or
+/// replayed on request. It also handles the verification completion of PUS event service
+/// requests.
 pub struct PusEventHandler<TmSender: EcssTmSender> {
     event_request_rx: mpsc::Receiver<EventRequestWithToken>,
     pus_event_tm_creator: DefaultPusEventU32TmCreator<EventApidSetter>,
+    event_log: EventLog,
+    event_log_tm_creator: PusEventLogTmCreator,
     pus_event_man_rx: mpsc::Receiver<EventMessageU32>,
     tm_sender: TmSender,
     time_provider: CdsTime,
@@ -51,6 +66,7 @@ impl<TmSender: EcssTmSender> PusEventHandler<TmSender> {
         verif_handler: VerificationReporter,
         event_manager: &mut EventManagerWithBoundedMpsc,
         event_request_rx: mpsc::Receiver<EventRequestWithToken>,
+        component_ids: &mut ComponentIdRegistry,
     ) -> Self {
         let event_queue_cap = 30;
         let (pus_event_man_tx, pus_event_man_rx) = mpsc::sync_channel(event_queue_cap);
@@ -67,11 +83,14 @@ impl<TmSender: EcssTmSender> PusEventHandler<TmSender> {
         .unwrap();
         let pus_event_dispatcher =
             DefaultPusEventU32TmCreator::new_with_default_backend(event_reporter);
-        let pus_event_man_send_provider = EventU32SenderMpscBounded::new(
+        let pus_event_man_send_provider = EventU32SenderMpscBounded::new_registered(
+            component_ids,
             PUS_EVENT_MANAGEMENT.raw(),
+            "PUS_EVENT_MANAGEMENT",
             pus_event_man_tx,
             event_queue_cap,
-        );
+        )
+        .expect("registering component ID failed");
 
         event_manager.subscribe_all(pus_event_man_send_provider.target_id());
         event_manager.add_sender(pus_event_man_send_provider);
@@ -79,6 +98,14 @@ impl<TmSender: EcssTmSender> PusEventHandler<TmSender> {
         Self {
             event_request_rx,
             pus_event_tm_creator: pus_event_dispatcher,
+            event_log: EventLog::new(create_event_log_pool(), EVENT_LOG_MAX_RECORDS),
+            event_log_tm_creator: PusEventLogTmCreator::new(
+                PUS_EVENT_MANAGEMENT.raw(),
+                PUS_EVENT_MANAGEMENT.apid,
+                0,
+                EVENT_LOG_MAX_RECORD_SIZE,
+            )
+            .unwrap(),
             pus_event_man_rx,
             time_provider: CdsTime::new_with_u16_days(0, 0),
             timestamp: [0; 7],
@@ -98,6 +125,20 @@ impl<TmSender: EcssTmSender> PusEventHandler<TmSender> {
                 .completion_success(&self.tm_sender, started_token, timestamp)
                 .expect("Sending completion success failed");
         };
+        let report_failure =
+            |event_req: EventRequestWithToken, timestamp: &[u8], error_code: ResultU16| {
+                let started_token: VerificationToken<TcStateStarted> = event_req
+                    .token
+                    .try_into()
+                    .expect("expected start verification token");
+                self.verif_handler
+                    .completion_failure(
+                        &self.tm_sender,
+                        started_token,
+                        FailParams::new_no_fail_data(timestamp, &error_code),
+                    )
+                    .expect("Sending completion failure failed");
+            };
         loop {
             // handle event requests
             match self.event_request_rx.try_recv() {
@@ -116,6 +157,65 @@ impl<TmSender: EcssTmSender> PusEventHandler<TmSender> {
                         update_time(&mut self.time_provider, &mut self.timestamp);
                         report_completion(event_req, &self.timestamp);
                     }
+                    EventRequest::ReportDisabledList => {
+                        update_time(&mut self.time_provider, &mut self.timestamp);
+                        if let Err(e) = self
+                            .pus_event_tm_creator
+                            .generate_disabled_events_report(&self.tm_sender, &self.timestamp)
+                        {
+                            log::warn!("generating the disabled events report failed: {e:?}");
+                        }
+                        report_completion(event_req, &self.timestamp);
+                    }
+                    EventRequest::DumpLog => {
+                        update_time(&mut self.time_provider, &mut self.timestamp);
+                        if let Err(e) = self.event_log_tm_creator.dump_event_log(
+                            &self.tm_sender,
+                            &self.timestamp,
+                            &self.event_log,
+                        ) {
+                            log::warn!("dumping the event log failed: {e:?}");
+                            report_failure(
+                                event_req,
+                                &self.timestamp,
+                                event_err::EVENT_LOG_DUMP_FAILED,
+                            );
+                        } else {
+                            report_completion(event_req, &self.timestamp);
+                        }
+                    }
+                    EventRequest::ReplayLog => {
+                        let result = replay_event_log(
+                            &self.tm_sender,
+                            &self.pus_event_tm_creator,
+                            &self.event_log,
+                        );
+                        update_time(&mut self.time_provider, &mut self.timestamp);
+                        if let Err(e) = result {
+                            log::warn!("replaying the event log failed: {e:?}");
+                            report_failure(
+                                event_req,
+                                &self.timestamp,
+                                event_err::EVENT_LOG_REPLAY_FAILED,
+                            );
+                        } else {
+                            report_completion(event_req, &self.timestamp);
+                        }
+                    }
+                    EventRequest::ClearLog => {
+                        let result = self.event_log.clear();
+                        update_time(&mut self.time_provider, &mut self.timestamp);
+                        if let Err(e) = result {
+                            log::warn!("clearing the event log failed: {e}");
+                            report_failure(
+                                event_req,
+                                &self.timestamp,
+                                event_err::EVENT_LOG_CLEAR_FAILED,
+                            );
+                        } else {
+                            report_completion(event_req, &self.timestamp);
+                        }
+                    }
                 },
                 Err(e) => match e {
                     mpsc::TryRecvError::Empty => break,
@@ -153,6 +253,9 @@ impl<TmSender: EcssTmSender> PusEventHandler<TmSender> {
                             event_msg.params()
                         );
                     }
+                    if let Err(e) = self.event_log.record_event_msg(&self.timestamp, &event_msg) {
+                        log::warn!("recording event failed: {e}");
+                    }
                 }
                 Err(e) => match e {
                     mpsc::TryRecvError::Empty => break,
@@ -176,6 +279,7 @@ impl<TmSender: EcssTmSender> EventHandler<TmSender> {
         tm_sender: TmSender,
         event_rx: mpsc::Receiver<EventMessageU32>,
         event_request_rx: mpsc::Receiver<EventRequestWithToken>,
+        component_ids: &mut ComponentIdRegistry,
     ) -> Self {
         let mut event_manager = EventManagerWithBoundedMpsc::new(event_rx);
         let pus_event_handler = PusEventHandler::new(
@@ -183,6 +287,7 @@ impl<TmSender: EcssTmSender> EventHandler<TmSender> {
             create_verification_reporter(PUS_EVENT_MANAGEMENT.id(), PUS_EVENT_MANAGEMENT.apid),
             &mut event_manager,
             event_request_rx,
+            component_ids,
         );
 
         Self {
@@ -202,15 +307,33 @@ impl<TmSender: EcssTmSender> EventHandler<TmSender> {
         self.pus_event_handler.generate_pus_event_tm();
     }
 
+    /// Handle all queued event requests and events after a shutdown was requested, and report
+    /// the shutdown of the event manager with the [satrs::shutdown::SHUTDOWN] event.
+    pub fn shutdown(
+        &mut self,
+        event_sender: &impl EventSendProvider<EventU32>,
+        timeout: Duration,
+    ) -> DrainOutcome {
+        self.pus_event_handler.handle_event_requests();
+        let outcome = self
+            .event_manager
+            .drain(timeout, Self::routing_error_handler);
+        if let Err(e) = report_shutdown(PUS_EVENT_MANAGEMENT.id(), &outcome, event_sender) {
+            log::warn!("sending shutdown event failed: {e:?}");
+        }
+        // Route the shutdown event and generate the TM for all remaining events.
+        self.try_event_routing();
+        self.pus_event_handler.generate_pus_event_tm();
+        outcome
+    }
+
     pub fn try_event_routing(&mut self) {
-        let error_handler = |event_msg: &EventMessageU32, error: EventRoutingError| {
-            self.routing_error_handler(event_msg, error)
-        };
         // Perform the event routing.
-        self.event_manager.try_event_handling(error_handler);
+        self.event_manager
+            .try_event_handling(Self::routing_error_handler);
     }
 
-    pub fn routing_error_handler(&self, event_msg: &EventMessageU32, error: EventRoutingError) {
+    pub fn routing_error_handler(event_msg: &EventMessageU32, error: EventRoutingError) {
         log::warn!("event routing error for event {event_msg:?}: {error:?}");
     }
 }
@@ -219,12 +342,18 @@ impl<TmSender: EcssTmSender> EventHandler<TmSender> {
 mod tests {
     use satrs::{
         events::EventU32,
-        pus::verification::VerificationReporterCfg,
+        pus::{
+            event_log::EventLogSubservice,
+            verification::{RequestId, VerificationReporterCfg},
+            EcssTmtcError, PusTmVariant,
+        },
+        queue::GenericSendError,
         spacepackets::{
-            ecss::{tm::PusTmReader, PusPacket},
-            CcsdsPacket,
+            ecss::{tc::PusTcCreator, tm::PusTmReader, PusPacket},
+            CcsdsPacket, SpHeader,
         },
         tmtc::PacketAsVec,
+        ComponentId,
     };
 
     use super::*;
@@ -253,6 +382,7 @@ mod tests {
                 verif_reporter,
                 &mut event_manager,
                 event_req_rx,
+                &mut ComponentIdRegistry::new(),
             );
             Self {
                 event_tx,
@@ -294,4 +424,74 @@ mod tests {
     fn test_basic_event_disabled() {
         // TODO: Add test.
     }
+
+    /// Rejects the event log records, which lets the event log dump fail.
+    struct EventLogRecordRejector(mpsc::Sender<PacketAsVec>);
+
+    impl EcssTmSender for EventLogRecordRejector {
+        fn send_tm(&self, sender_id: ComponentId, tm: PusTmVariant) -> Result<(), EcssTmtcError> {
+            if let PusTmVariant::Direct(tm) = &tm {
+                if tm.service() == 5
+                    && tm.subservice() == u8::from(EventLogSubservice::TmEventLogRecord)
+                {
+                    return Err(EcssTmtcError::Send(GenericSendError::RxDisconnected));
+                }
+            }
+            self.0.send_tm(sender_id, tm)
+        }
+    }
+
+    #[test]
+    fn test_event_log_dump_failure() {
+        let (event_tx, event_rx) = mpsc::sync_channel(10);
+        let (event_req_tx, event_req_rx) = mpsc::sync_channel(10);
+        let (tm_sender, tm_receiver) = mpsc::channel();
+        let verif_reporter_cfg = VerificationReporterCfg::new(0x05, 2, 2, 128).unwrap();
+        let verif_reporter =
+            VerificationReporter::new(PUS_EVENT_MANAGEMENT.id(), &verif_reporter_cfg);
+        let mut event_manager = EventManagerWithBoundedMpsc::new(event_rx);
+        let mut pus_event_handler = PusEventHandler::new(
+            EventLogRecordRejector(tm_sender),
+            verif_reporter,
+            &mut event_manager,
+            event_req_rx,
+            &mut ComponentIdRegistry::new(),
+        );
+        // Record an event, so that the dump has a record to send.
+        event_tx
+            .send(EventMessageU32::new(TEST_CREATOR_ID.id(), TEST_EVENT))
+            .expect("failed to send event");
+        event_manager.try_event_handling(|_, _| {});
+        pus_event_handler.generate_pus_event_tm();
+        tm_receiver.try_recv().expect("no event TM");
+
+        let tc = PusTcCreator::new_simple(
+            SpHeader::new_for_unseg_tc(PUS_EVENT_MANAGEMENT.apid, 0, 0),
+            5,
+            EventLogSubservice::TcDumpEventLog.into(),
+            &[],
+            true,
+        );
+        let request_id = RequestId::new(&tc);
+        event_req_tx
+            .send(EventRequestWithToken {
+                request: EventRequest::DumpLog,
+                token: VerificationToken::new_started_state(request_id).into(),
+            })
+            .expect("failed to send event request");
+        pus_event_handler.handle_event_requests();
+        let tm_packet = tm_receiver.try_recv().expect("no verification TM");
+        let tm_reader = PusTmReader::new(&tm_packet.packet, 7)
+            .expect("failed to create TM reader")
+            .0;
+        assert_eq!(tm_reader.service(), 1);
+        assert_eq!(tm_reader.subservice(), 8);
+        let source_data = tm_reader.source_data();
+        assert_eq!(RequestId::from_bytes(source_data), Some(request_id));
+        assert_eq!(
+            u16::from_be_bytes(source_data[4..6].try_into().unwrap()),
+            event_err::EVENT_LOG_DUMP_FAILED.raw()
+        );
+        assert!(tm_receiver.try_recv().is_err());
+    }
 }
@@ -10,6 +10,7 @@ use log::{info, warn};
 use satrs::{
     encoding::ccsds::{SpValidity, SpacePacketValidator},
     hal::std::tcp_server::{HandledConnectionHandler, ServerConfig, TcpSpacepacketsServer},
+    shutdown::ShutdownSignal,
     spacepackets::{CcsdsPacket, PacketId},
     tmtc::{PacketSenderRaw, PacketSource},
 };
@@ -124,6 +125,7 @@ impl<TcSender: PacketSenderRaw<Error = SendError>, SendError: Debug + 'static>
         tm_source: SyncTcpTmSource,
         tc_sender: TcSender,
         valid_ids: HashSet<PacketId>,
+        shutdown: ShutdownSignal,
     ) -> Result<Self, std::io::Error> {
         Ok(Self(
             TcpSpacepacketsServer::new(
@@ -132,22 +134,21 @@ impl<TcSender: PacketSenderRaw<Error = SendError>, SendError: Debug + 'static>
                 tc_sender,
                 SimplePacketValidator { valid_ids },
                 ConnectionFinishedHandler::default(),
-                None,
+                // Connected clients are dropped when a shutdown is requested.
+                Some(shutdown.flag()),
             )?,
             PhantomData,
         ))
     }
 
     pub fn periodic_operation(&mut self) {
-        loop {
-            let result = self
-                .0
-                .handle_all_connections(Some(Duration::from_millis(400)));
-            match result {
-                Ok(_conn_result) => (),
-                Err(e) => {
-                    warn!("TCP server error: {e:?}");
-                }
+        let result = self
+            .0
+            .handle_all_connections(Some(Duration::from_millis(400)));
+        match result {
+            Ok(_conn_result) => (),
+            Err(e) => {
+                warn!("TCP server error: {e:?}");
             }
         }
     }
@@ -7,6 +7,8 @@ pub enum DeviceMode {
     Off = 0,
     On = 1,
     Normal = 2,
+    /// Raw commands are forwarded to the device directly and the device is not polled.
+    Raw = 3,
 }
 
 pub struct TimestampHelper {
@@ -4,8 +4,11 @@ pub mod events;
 pub mod hk;
 pub mod interface;
 pub mod logger;
+pub mod mib;
 pub mod pus;
 pub mod requests;
+pub mod shutdown;
+pub mod tcs;
 pub mod tmtc;
 
 use crate::eps::pcdu::{
@@ -14,32 +17,44 @@ use crate::eps::pcdu::{
 use crate::eps::PowerSwitchHelper;
 use crate::events::EventHandler;
 use crate::interface::udp::DynamicUdpTmHandler;
-use crate::pus::stack::PusStack;
+use crate::pus::stack::{PusStack, PusTask};
 use crate::tmtc::tc_source::{TcSourceTaskDynamic, TcSourceTaskStatic};
 use crate::tmtc::tm_sink::{TmSinkDynamic, TmSinkStatic};
 use log::info;
 use pus::test::create_test_service_dynamic;
+use satrs::event_man::EventU32SenderMpscBounded;
+use satrs::executable::{FixedRateScheduler, PollingSequenceExecutor};
 use satrs::hal::std::tcp_server::ServerConfig;
 use satrs::hal::std::udp_server::UdpTcServer;
+use satrs::id_registry::ComponentIdRegistry;
+use satrs::pool::PriorityPoolQueue;
 use satrs::pus::HandlingStatus;
 use satrs::request::{GenericMessage, MessageMetadata};
-use satrs::tmtc::{PacketSenderWithSharedPool, SharedPacketPool};
-use satrs_example::config::pool::{create_sched_tc_pool, create_static_pools};
+use satrs::shutdown::ShutdownSignal;
+use satrs::tmtc::stats::TmtcStatistics;
+use satrs::tmtc::{PacketSenderWithSharedPool, PriorityPacketInPoolSender, SharedPacketPool};
+use satrs_example::config::pool::{
+    create_sched_tc_pool, create_static_pools, NUM_TC_PRIORITY_CLASSES, TC_PRIO_IMMEDIATE,
+    TC_PRIO_SCHEDULED, TC_QUEUE_LANE_CAPACITY,
+};
 use satrs_example::config::tasks::{
-    FREQ_MS_AOCS, FREQ_MS_PUS_STACK, FREQ_MS_UDP_TMTC, SIM_CLIENT_IDLE_DELAY_MS,
+    FREQ_MS_AOCS, FREQ_MS_PUS_STACK, FREQ_MS_TCS, FREQ_MS_UDP_TMTC, SHUTDOWN_DRAIN_TIMEOUT_MS,
+    SIM_CLIENT_IDLE_DELAY_MS,
 };
 use satrs_example::config::{OBSW_SERVER_ADDR, PACKET_ID_VALIDATOR, SERVER_PORT};
 use satrs_example::DeviceMode;
+use satrs_minisim::fake::{create_fake_acs_and_eps, FakeClock};
 
-use crate::acs::mgm::{
-    MgmHandlerLis3Mdl, MpscModeLeafInterface, SpiDummyInterface, SpiSimInterface,
-    SpiSimInterfaceWrapper,
-};
+use crate::acs::mgm::MpscModeLeafInterface;
+use crate::acs::subsystem::{create_mgm_com_interface, AcsReplySenders, AcsSubsystem};
 use crate::interface::sim_client_udp::create_sim_client;
 use crate::interface::tcp::{SyncTcpTmSource, TcpTask};
 use crate::interface::udp::{StaticUdpTmHandler, UdpTmtcServer};
 use crate::logger::setup_logger;
 use crate::pus::action::{create_action_service_dynamic, create_action_service_static};
+use crate::pus::device_access::{
+    create_device_access_service_dynamic, create_device_access_service_static,
+};
 use crate::pus::event::{create_event_service_dynamic, create_event_service_static};
 use crate::pus::hk::{create_hk_service_dynamic, create_hk_service_static};
 use crate::pus::mode::{create_mode_service_dynamic, create_mode_service_static};
@@ -47,11 +62,15 @@ use crate::pus::scheduler::{create_scheduler_service_dynamic, create_scheduler_s
 use crate::pus::test::create_test_service_static;
 use crate::pus::{PusTcDistributor, PusTcMpscRouter};
 use crate::requests::{CompositeRequest, GenericRequestRouter};
+use crate::shutdown::{install_signal_handler, ShutdownStages};
+use crate::tcs::heater::simulated_heater_output;
+use crate::tcs::subsystem::{TcsReplySenders, TcsSubsystem};
+use crate::tcs::temp_sensor::FakeTmp117;
 use satrs::mode::{Mode, ModeAndSubmode, ModeRequest};
 use satrs::pus::event_man::EventRequestWithToken;
 use satrs::spacepackets::{time::cds::CdsTime, time::TimeWriter};
 use satrs_example::config::components::{
-    MGM_HANDLER_0, NO_SENDER, PCDU_HANDLER, TCP_SERVER, UDP_SERVER,
+    NO_SENDER, PCDU_HANDLER, PUS_EVENT_MANAGEMENT, PUS_ROUTING_SERVICE, TCP_SERVER, UDP_SERVER,
 };
 use std::net::{IpAddr, SocketAddr};
 use std::sync::{mpsc, Mutex};
@@ -59,14 +78,18 @@ use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::Duration;
 
+const EVENT_QUEUE_DEPTH: usize = 100;
+
 #[allow(dead_code)]
-fn static_tmtc_pool_main() {
+fn static_tmtc_pool_main(shutdown: ShutdownSignal, component_ids: &mut ComponentIdRegistry) {
+    let mut stages = ShutdownStages::default();
     let (tm_pool, tc_pool) = create_static_pools();
     let shared_tm_pool = Arc::new(RwLock::new(tm_pool));
     let shared_tc_pool = Arc::new(RwLock::new(tc_pool));
     let shared_tm_pool_wrapper = SharedPacketPool::new(&shared_tm_pool);
     let shared_tc_pool_wrapper = SharedPacketPool::new(&shared_tc_pool);
-    let (tc_source_tx, tc_source_rx) = mpsc::sync_channel(50);
+    let tc_source_queue =
+        PriorityPoolQueue::new_shared(NUM_TC_PRIORITY_CLASSES, TC_QUEUE_LANE_CAPACITY);
     let (tm_sink_tx, tm_sink_rx) = mpsc::sync_channel(50);
     let (tm_server_tx, tm_server_rx) = mpsc::sync_channel(50);
 
@@ -74,48 +97,90 @@ fn static_tmtc_pool_main() {
         PacketSenderWithSharedPool::new(tm_sink_tx.clone(), shared_tm_pool_wrapper.clone());
 
     let (sim_request_tx, sim_request_rx) = mpsc::channel();
-    let (mgm_sim_reply_tx, mgm_sim_reply_rx) = mpsc::channel();
     let (pcdu_sim_reply_tx, pcdu_sim_reply_rx) = mpsc::channel();
     let mut opt_sim_client = create_sim_client(sim_request_rx);
 
-    let (mgm_handler_composite_tx, mgm_handler_composite_rx) =
-        mpsc::sync_channel::<GenericMessage<CompositeRequest>>(10);
     let (pcdu_handler_composite_tx, pcdu_handler_composite_rx) =
         mpsc::sync_channel::<GenericMessage<CompositeRequest>>(30);
 
-    let (mgm_handler_mode_tx, mgm_handler_mode_rx) =
-        mpsc::sync_channel::<GenericMessage<ModeRequest>>(5);
     let (pcdu_handler_mode_tx, pcdu_handler_mode_rx) =
         mpsc::sync_channel::<GenericMessage<ModeRequest>>(5);
 
+    let (pus_hk_reply_tx, pus_hk_reply_rx) = mpsc::channel();
+    let (pus_mode_reply_tx, pus_mode_reply_rx) = mpsc::channel();
+    let (pus_raw_reply_tx, pus_raw_reply_rx) = mpsc::channel();
+
+    let shared_switch_set = Arc::new(Mutex::default());
+    let (switch_request_tx, switch_request_rx) = mpsc::sync_channel(20);
+    let switch_helper = PowerSwitchHelper::new(switch_request_tx, shared_switch_set.clone());
+
+    // In-process fake devices which are used if the mini simulator is not running.
+    let (fake_pcdu, fake_mgm) = create_fake_acs_and_eps(FakeClock::new_real_time());
+    let (acs_subsystem, acs_request_senders) = AcsSubsystem::new(
+        create_mgm_com_interface(opt_sim_client.as_mut(), &sim_request_tx, fake_mgm),
+        switch_helper,
+        AcsReplySenders {
+            mode_reply_tx: pus_mode_reply_tx.clone(),
+            hk_reply_tx: pus_hk_reply_tx.clone(),
+            raw_reply_tx: pus_raw_reply_tx.clone(),
+            tm_sender: tm_sink_tx.clone(),
+        },
+    );
+    // There is no heater and temperature sensor hardware, so a simulated heater output and a
+    // fake sensor are used.
+    let (tcs_subsystem, tcs_request_senders) = TcsSubsystem::new(
+        simulated_heater_output(),
+        FakeTmp117::default(),
+        TcsReplySenders {
+            mode_reply_tx: pus_mode_reply_tx.clone(),
+            hk_reply_tx: pus_hk_reply_tx.clone(),
+            raw_reply_tx: pus_raw_reply_tx,
+            tm_sender: tm_sink_tx.clone(),
+        },
+    );
+
     // Some request are targetable. This map is used to retrieve sender handles based on a target ID.
     let mut request_map = GenericRequestRouter::default();
+    acs_request_senders.register(&mut request_map, component_ids);
+    tcs_request_senders.register(&mut request_map, component_ids);
+    component_ids
+        .register_target(PCDU_HANDLER, "PCDU_HANDLER")
+        .expect("registering component ID failed");
     request_map
         .composite_router_map
-        .insert(MGM_HANDLER_0.id(), mgm_handler_composite_tx);
-    request_map
-        .mode_router_map
-        .insert(MGM_HANDLER_0.id(), mgm_handler_mode_tx);
-    request_map
-        .composite_router_map
-        .insert(PCDU_HANDLER.id(), pcdu_handler_composite_tx);
+        .register_with_target_id(PCDU_HANDLER, pcdu_handler_composite_tx)
+        .expect("registering composite request target failed");
     request_map
         .mode_router_map
-        .insert(PCDU_HANDLER.id(), pcdu_handler_mode_tx.clone());
+        .register_with_target_id(PCDU_HANDLER, pcdu_handler_mode_tx.clone())
+        .expect("registering mode request target failed");
 
     // This helper structure is used by all telecommand providers which need to send telecommands
     // to the TC source.
-    let tc_source = PacketSenderWithSharedPool::new(tc_source_tx, shared_tc_pool_wrapper.clone());
+    let tc_source = PacketSenderWithSharedPool::new(
+        PriorityPacketInPoolSender::new(tc_source_queue.clone(), TC_PRIO_IMMEDIATE),
+        shared_tc_pool_wrapper.clone(),
+    );
+    // Released time-tagged telecommands are sent to the TC source with a higher priority.
+    let tc_releaser = PacketSenderWithSharedPool::new(
+        PriorityPacketInPoolSender::new(tc_source_queue.clone(), TC_PRIO_SCHEDULED),
+        shared_tc_pool_wrapper.clone(),
+    );
 
     // Create event handling components
     // These sender handles are used to send event requests, for example to enable or disable
     // certain events.
-    let (event_tx, event_rx) = mpsc::sync_channel(100);
+    let (event_tx, event_rx) = mpsc::sync_channel(EVENT_QUEUE_DEPTH);
     let (event_request_tx, event_request_rx) = mpsc::channel::<EventRequestWithToken>();
 
     // The event task is the core handler to perform the event routing and TM handling as specified
     // in the sat-rs documentation.
-    let mut event_handler = EventHandler::new(tm_sink_tx.clone(), event_rx, event_request_rx);
+    let event_handler = EventHandler::new(
+        tm_sink_tx.clone(),
+        event_rx,
+        event_request_rx,
+        component_ids,
+    );
 
     let (pus_test_tx, pus_test_rx) = mpsc::channel();
     let (pus_event_tx, pus_event_rx) = mpsc::channel();
@@ -123,11 +188,11 @@ fn static_tmtc_pool_main() {
     let (pus_hk_tx, pus_hk_rx) = mpsc::channel();
     let (pus_action_tx, pus_action_rx) = mpsc::channel();
     let (pus_mode_tx, pus_mode_rx) = mpsc::channel();
+    let (pus_device_access_tx, pus_device_access_rx) = mpsc::channel();
 
     let (_pus_action_reply_tx, pus_action_reply_rx) = mpsc::channel();
-    let (pus_hk_reply_tx, pus_hk_reply_rx) = mpsc::channel();
-    let (pus_mode_reply_tx, pus_mode_reply_rx) = mpsc::channel();
 
+    let tmtc_stats = Arc::new(TmtcStatistics::default());
     let pus_router = PusTcMpscRouter {
         test_tc_sender: pus_test_tx,
         event_tc_sender: pus_event_tx,
@@ -135,7 +200,9 @@ fn static_tmtc_pool_main() {
         hk_tc_sender: pus_hk_tx,
         action_tc_sender: pus_action_tx,
         mode_tc_sender: pus_mode_tx,
+        device_access_tc_sender: pus_device_access_tx,
     };
+    pus_router.register_component_ids(component_ids);
     let pus_test_service = create_test_service_static(
         tm_sink_tx_sender.clone(),
         shared_tc_pool.clone(),
@@ -144,7 +211,7 @@ fn static_tmtc_pool_main() {
     );
     let pus_scheduler_service = create_scheduler_service_static(
         tm_sink_tx_sender.clone(),
-        tc_source.clone(),
+        tc_releaser,
         pus_sched_rx,
         create_sched_tc_pool(),
     );
@@ -172,25 +239,49 @@ fn static_tmtc_pool_main() {
         tm_sink_tx_sender.clone(),
         shared_tc_pool.clone(),
         pus_mode_rx,
-        request_map,
+        request_map.clone(),
         pus_mode_reply_rx,
     );
-    let mut pus_stack = PusStack::new(
+    let pus_device_access_service = create_device_access_service_static(
+        tm_sink_tx_sender.clone(),
+        shared_tc_pool.clone(),
+        pus_device_access_rx,
+        request_map,
+        pus_raw_reply_rx,
+    );
+    let pus_stack = PusStack::new(
         pus_test_service,
         pus_hk_service,
         pus_event_service,
         pus_action_service,
         pus_scheduler_service,
         pus_mode_service,
+        pus_device_access_service,
     );
 
     let mut tmtc_task = TcSourceTaskStatic::new(
         shared_tc_pool_wrapper.clone(),
-        tc_source_rx,
-        PusTcDistributor::new(tm_sink_tx_sender, pus_router),
+        tc_source_queue,
+        PusTcDistributor::new(
+            tm_sink_tx_sender,
+            pus_router,
+            tmtc_stats.clone(),
+            EventU32SenderMpscBounded::new_registered(
+                component_ids,
+                PUS_ROUTING_SERVICE.id(),
+                "PUS_ROUTING_SERVICE",
+                event_tx.clone(),
+                EVENT_QUEUE_DEPTH,
+            )
+            .expect("registering component ID failed"),
+        )
+        .with_tc_pool(shared_tc_pool.clone()),
     );
 
     let sock_addr = SocketAddr::new(IpAddr::V4(OBSW_SERVER_ADDR), SERVER_PORT);
+    component_ids
+        .register_target(UDP_SERVER, "UDP_SERVER")
+        .expect("registering component ID failed");
     let udp_tc_server = UdpTcServer::new(UDP_SERVER.id(), sock_addr, 2048, tc_source.clone())
         .expect("creating UDP TMTC server failed");
     let mut udp_tmtc_server = UdpTmtcServer {
@@ -201,6 +292,9 @@ fn static_tmtc_pool_main() {
         },
     };
 
+    component_ids
+        .register_target(TCP_SERVER, "TCP_SERVER")
+        .expect("registering component ID failed");
     let tcp_server_cfg = ServerConfig::new(
         TCP_SERVER.id(),
         sock_addr,
@@ -214,6 +308,7 @@ fn static_tmtc_pool_main() {
         sync_tm_tcp_source.clone(),
         tc_source.clone(),
         PACKET_ID_VALIDATOR.clone(),
+        stages.tcp_server.signal(),
     )
     .expect("tcp server creation failed");
 
@@ -222,41 +317,7 @@ fn static_tmtc_pool_main() {
         sync_tm_tcp_source,
         tm_sink_rx,
         tm_server_tx,
-    );
-
-    let (mgm_handler_mode_reply_to_parent_tx, _mgm_handler_mode_reply_to_parent_rx) =
-        mpsc::sync_channel(5);
-
-    let shared_switch_set = Arc::new(Mutex::default());
-    let (switch_request_tx, switch_request_rx) = mpsc::sync_channel(20);
-    let switch_helper = PowerSwitchHelper::new(switch_request_tx, shared_switch_set.clone());
-
-    let shared_mgm_set = Arc::default();
-    let mgm_mode_leaf_interface = MpscModeLeafInterface {
-        request_rx: mgm_handler_mode_rx,
-        reply_to_pus_tx: pus_mode_reply_tx.clone(),
-        reply_to_parent_tx: mgm_handler_mode_reply_to_parent_tx,
-    };
-
-    let mgm_spi_interface = if let Some(sim_client) = opt_sim_client.as_mut() {
-        sim_client.add_reply_recipient(satrs_minisim::SimComponent::MgmLis3Mdl, mgm_sim_reply_tx);
-        SpiSimInterfaceWrapper::Sim(SpiSimInterface {
-            sim_request_tx: sim_request_tx.clone(),
-            sim_reply_rx: mgm_sim_reply_rx,
-        })
-    } else {
-        SpiSimInterfaceWrapper::Dummy(SpiDummyInterface::default())
-    };
-    let mut mgm_handler = MgmHandlerLis3Mdl::new(
-        MGM_HANDLER_0,
-        "MGM_0",
-        mgm_mode_leaf_interface,
-        mgm_handler_composite_rx,
-        pus_hk_reply_tx.clone(),
-        switch_helper.clone(),
-        tm_sink_tx.clone(),
-        mgm_spi_interface,
-        shared_mgm_set,
+        tmtc_stats,
     );
 
     let (pcdu_handler_mode_reply_to_parent_tx, _pcdu_handler_mode_reply_to_parent_rx) =
@@ -273,10 +334,10 @@ fn static_tmtc_pool_main() {
             pcdu_sim_reply_rx,
         ))
     } else {
-        SerialSimInterfaceWrapper::Dummy(SerialInterfaceDummy::default())
+        SerialSimInterfaceWrapper::Dummy(SerialInterfaceDummy::new(fake_pcdu))
     };
 
-    let mut pcdu_handler = PcduHandler::new(
+    let pcdu_handler = PcduHandler::new(
         PCDU_HANDLER,
         "PCDU",
         pcdu_mode_leaf_interface,
@@ -295,47 +356,63 @@ fn static_tmtc_pool_main() {
         ))
         .expect("sending initial mode request failed");
 
+    info!("{} component IDs registered", component_ids.len());
     info!("Starting TMTC and UDP task");
+    let udp_shutdown = stages.tc_input.signal();
     let jh_udp_tmtc = thread::Builder::new()
         .name("SATRS tmtc-udp".to_string())
         .spawn(move || {
             info!("Running UDP server on port {SERVER_PORT}");
-            loop {
+            while !udp_shutdown.shutdown_requested() {
                 udp_tmtc_server.periodic_operation();
                 tmtc_task.periodic_operation();
                 thread::sleep(Duration::from_millis(FREQ_MS_UDP_TMTC));
             }
+            // Forward the telecommands which were already received.
+            tmtc_task.periodic_operation();
         })
         .unwrap();
 
     info!("Starting TCP task");
+    let tcp_shutdown = stages.tcp_server.signal();
     let jh_tcp = thread::Builder::new()
         .name("sat-rs tcp".to_string())
         .spawn(move || {
             info!("Running TCP server on port {SERVER_PORT}");
-            loop {
+            while !tcp_shutdown.shutdown_requested() {
                 tcp_server.periodic_operation();
             }
         })
         .unwrap();
 
     info!("Starting TM funnel task");
+    let tm_funnel_shutdown = stages.tm_sink.signal();
     let jh_tm_funnel = thread::Builder::new()
         .name("tm sink".to_string())
-        .spawn(move || loop {
-            tm_sink.operation();
+        .spawn(move || {
+            while !tm_funnel_shutdown.shutdown_requested() {
+                tm_sink.operation();
+            }
+            let outcome = tm_sink.drain(Duration::from_millis(SHUTDOWN_DRAIN_TIMEOUT_MS));
+            info!(
+                "TM sink stopped after forwarding {} TM",
+                outcome.num_drained
+            );
         })
         .unwrap();
 
     let mut opt_jh_sim_client = None;
     if let Some(mut sim_client) = opt_sim_client {
         info!("Starting UDP sim client task");
+        let sim_client_shutdown = stages.pus.signal();
         opt_jh_sim_client = Some(
             thread::Builder::new()
                 .name("sat-rs sim adapter".to_string())
-                .spawn(move || loop {
-                    if sim_client.operation() == HandlingStatus::Empty {
-                        std::thread::sleep(Duration::from_millis(SIM_CLIENT_IDLE_DELAY_MS));
+                .spawn(move || {
+                    while !sim_client_shutdown.shutdown_requested() {
+                        if sim_client.operation() == HandlingStatus::Empty {
+                            std::thread::sleep(Duration::from_millis(SIM_CLIENT_IDLE_DELAY_MS));
+                        }
                     }
                 })
                 .unwrap(),
@@ -343,104 +420,146 @@ fn static_tmtc_pool_main() {
     }
 
     info!("Starting AOCS thread");
-    let jh_aocs = thread::Builder::new()
-        .name("sat-rs aocs".to_string())
-        .spawn(move || loop {
-            mgm_handler.periodic_operation();
-            thread::sleep(Duration::from_millis(FREQ_MS_AOCS));
-        })
-        .unwrap();
+    let mut aocs_scheduler = FixedRateScheduler::new("sat-rs aocs", stages.pus.signal());
+    aocs_scheduler.add_task(
+        Box::new(acs_subsystem),
+        Duration::from_millis(FREQ_MS_AOCS),
+        0,
+    );
+    stages
+        .pus
+        .spawn_scheduler("sat-rs aocs", move || aocs_scheduler.run());
+
+    info!("Starting TCS thread");
+    let mut tcs_scheduler = FixedRateScheduler::new("sat-rs tcs", stages.pus.signal());
+    tcs_scheduler.add_task(
+        Box::new(tcs_subsystem),
+        Duration::from_millis(FREQ_MS_TCS),
+        0,
+    );
+    stages
+        .pus
+        .spawn_scheduler("sat-rs tcs", move || tcs_scheduler.run());
 
     info!("Starting EPS thread");
-    let jh_eps = thread::Builder::new()
-        .name("sat-rs eps".to_string())
-        .spawn(move || loop {
-            // TODO: We should introduce something like a fixed timeslot helper to allow a more
-            // declarative API. It would also be very useful for the AOCS task.
-            pcdu_handler.periodic_operation(eps::pcdu::OpCode::RegularOp);
-            thread::sleep(Duration::from_millis(50));
-            pcdu_handler.periodic_operation(eps::pcdu::OpCode::PollAndRecvReplies);
-            thread::sleep(Duration::from_millis(50));
-            pcdu_handler.periodic_operation(eps::pcdu::OpCode::PollAndRecvReplies);
-            thread::sleep(Duration::from_millis(300));
-        })
-        .unwrap();
+    let (mut eps_executor, _eps_pst_handle) =
+        PollingSequenceExecutor::new("sat-rs eps", stages.pus.signal());
+    let pcdu_task_index = eps_executor.add_task(Box::new(pcdu_handler));
+    eps_executor
+        .add_table(eps::pcdu::create_pcdu_polling_sequence(pcdu_task_index))
+        .expect("adding the EPS polling sequence failed");
+    stages
+        .pus
+        .spawn_scheduler("sat-rs eps", move || eps_executor.run());
 
     info!("Starting PUS handler thread");
-    let jh_pus_handler = thread::Builder::new()
-        .name("sat-rs pus".to_string())
-        .spawn(move || loop {
-            event_handler.periodic_operation();
-            pus_stack.periodic_operation();
-            thread::sleep(Duration::from_millis(FREQ_MS_PUS_STACK));
-        })
-        .unwrap();
+    let shutdown_event_sender =
+        EventU32SenderMpscBounded::new(PUS_EVENT_MANAGEMENT.id(), event_tx, EVENT_QUEUE_DEPTH);
+    // The PUS task is stopped with the stage signal itself, so it can handle the queued input
+    // before it terminates.
+    let mut pus_scheduler = FixedRateScheduler::new("sat-rs pus", ShutdownSignal::new());
+    pus_scheduler.add_task(
+        Box::new(PusTask::new(
+            event_handler,
+            pus_stack,
+            stages.pus.signal(),
+            shutdown_event_sender,
+        )),
+        Duration::from_millis(FREQ_MS_PUS_STACK),
+        0,
+    );
+    stages
+        .pus
+        .spawn_scheduler("sat-rs pus", move || pus_scheduler.run());
 
-    jh_udp_tmtc
-        .join()
-        .expect("Joining UDP TMTC server thread failed");
-    jh_tcp
-        .join()
-        .expect("Joining TCP TMTC server thread failed");
-    jh_tm_funnel
-        .join()
-        .expect("Joining TM Funnel thread failed");
+    stages.tc_input.add_thread(jh_udp_tmtc);
     if let Some(jh_sim_client) = opt_jh_sim_client {
-        jh_sim_client
-            .join()
-            .expect("Joining SIM client thread failed");
+        stages.pus.add_thread(jh_sim_client);
     }
-    jh_aocs.join().expect("Joining AOCS thread failed");
-    jh_eps.join().expect("Joining EPS thread failed");
-    jh_pus_handler
-        .join()
-        .expect("Joining PUS handler thread failed");
+    stages.tm_sink.add_thread(jh_tm_funnel);
+    stages.tcp_server.add_thread(jh_tcp);
+    stages.run(&shutdown);
 }
 
 #[allow(dead_code)]
-fn dyn_tmtc_pool_main() {
+fn dyn_tmtc_pool_main(shutdown: ShutdownSignal, component_ids: &mut ComponentIdRegistry) {
+    let mut stages = ShutdownStages::default();
     let (tc_source_tx, tc_source_rx) = mpsc::channel();
     let (tm_sink_tx, tm_sink_rx) = mpsc::channel();
     let (tm_server_tx, tm_server_rx) = mpsc::channel();
 
     let (sim_request_tx, sim_request_rx) = mpsc::channel();
-    let (mgm_sim_reply_tx, mgm_sim_reply_rx) = mpsc::channel();
     let (pcdu_sim_reply_tx, pcdu_sim_reply_rx) = mpsc::channel();
     let mut opt_sim_client = create_sim_client(sim_request_rx);
 
     // Some request are targetable. This map is used to retrieve sender handles based on a target ID.
-    let (mgm_handler_composite_tx, mgm_handler_composite_rx) =
-        mpsc::sync_channel::<GenericMessage<CompositeRequest>>(5);
     let (pcdu_handler_composite_tx, pcdu_handler_composite_rx) =
         mpsc::sync_channel::<GenericMessage<CompositeRequest>>(10);
-    let (mgm_handler_mode_tx, mgm_handler_mode_rx) =
-        mpsc::sync_channel::<GenericMessage<ModeRequest>>(5);
     let (pcdu_handler_mode_tx, pcdu_handler_mode_rx) =
         mpsc::sync_channel::<GenericMessage<ModeRequest>>(10);
 
+    let (pus_hk_reply_tx, pus_hk_reply_rx) = mpsc::channel();
+    let (pus_mode_reply_tx, pus_mode_reply_rx) = mpsc::channel();
+    let (pus_raw_reply_tx, pus_raw_reply_rx) = mpsc::channel();
+
+    let shared_switch_set = Arc::new(Mutex::default());
+    let (switch_request_tx, switch_request_rx) = mpsc::sync_channel(20);
+    let switch_helper = PowerSwitchHelper::new(switch_request_tx, shared_switch_set.clone());
+
+    // In-process fake devices which are used if the mini simulator is not running.
+    let (fake_pcdu, fake_mgm) = create_fake_acs_and_eps(FakeClock::new_real_time());
+    let (acs_subsystem, acs_request_senders) = AcsSubsystem::new(
+        create_mgm_com_interface(opt_sim_client.as_mut(), &sim_request_tx, fake_mgm),
+        switch_helper,
+        AcsReplySenders {
+            mode_reply_tx: pus_mode_reply_tx.clone(),
+            hk_reply_tx: pus_hk_reply_tx.clone(),
+            raw_reply_tx: pus_raw_reply_tx.clone(),
+            tm_sender: tm_sink_tx.clone(),
+        },
+    );
+    // There is no heater and temperature sensor hardware, so a simulated heater output and a
+    // fake sensor are used.
+    let (tcs_subsystem, tcs_request_senders) = TcsSubsystem::new(
+        simulated_heater_output(),
+        FakeTmp117::default(),
+        TcsReplySenders {
+            mode_reply_tx: pus_mode_reply_tx.clone(),
+            hk_reply_tx: pus_hk_reply_tx.clone(),
+            raw_reply_tx: pus_raw_reply_tx,
+            tm_sender: tm_sink_tx.clone(),
+        },
+    );
+
     // Some request are targetable. This map is used to retrieve sender handles based on a target ID.
     let mut request_map = GenericRequestRouter::default();
+    acs_request_senders.register(&mut request_map, component_ids);
+    tcs_request_senders.register(&mut request_map, component_ids);
+    component_ids
+        .register_target(PCDU_HANDLER, "PCDU_HANDLER")
+        .expect("registering component ID failed");
     request_map
         .composite_router_map
-        .insert(MGM_HANDLER_0.id(), mgm_handler_composite_tx);
-    request_map
-        .mode_router_map
-        .insert(MGM_HANDLER_0.id(), mgm_handler_mode_tx);
-    request_map
-        .composite_router_map
-        .insert(PCDU_HANDLER.id(), pcdu_handler_composite_tx);
+        .register_with_target_id(PCDU_HANDLER, pcdu_handler_composite_tx)
+        .expect("registering composite request target failed");
     request_map
         .mode_router_map
-        .insert(PCDU_HANDLER.id(), pcdu_handler_mode_tx.clone());
+        .register_with_target_id(PCDU_HANDLER, pcdu_handler_mode_tx.clone())
+        .expect("registering mode request target failed");
 
     // Create event handling components
     // These sender handles are used to send event requests, for example to enable or disable
     // certain events.
-    let (event_tx, event_rx) = mpsc::sync_channel(100);
+    let (event_tx, event_rx) = mpsc::sync_channel(EVENT_QUEUE_DEPTH);
     let (event_request_tx, event_request_rx) = mpsc::channel::<EventRequestWithToken>();
     // The event task is the core handler to perform the event routing and TM handling as specified
     // in the sat-rs documentation.
-    let mut event_handler = EventHandler::new(tm_sink_tx.clone(), event_rx, event_request_rx);
+    let event_handler = EventHandler::new(
+        tm_sink_tx.clone(),
+        event_rx,
+        event_request_rx,
+        component_ids,
+    );
 
     let (pus_test_tx, pus_test_rx) = mpsc::channel();
     let (pus_event_tx, pus_event_rx) = mpsc::channel();
@@ -448,11 +567,11 @@ fn dyn_tmtc_pool_main() {
     let (pus_hk_tx, pus_hk_rx) = mpsc::channel();
     let (pus_action_tx, pus_action_rx) = mpsc::channel();
     let (pus_mode_tx, pus_mode_rx) = mpsc::channel();
+    let (pus_device_access_tx, pus_device_access_rx) = mpsc::channel();
 
     let (_pus_action_reply_tx, pus_action_reply_rx) = mpsc::channel();
-    let (pus_hk_reply_tx, pus_hk_reply_rx) = mpsc::channel();
-    let (pus_mode_reply_tx, pus_mode_reply_rx) = mpsc::channel();
 
+    let tmtc_stats = Arc::new(TmtcStatistics::default());
     let pus_router = PusTcMpscRouter {
         test_tc_sender: pus_test_tx,
         event_tc_sender: pus_event_tx,
@@ -460,7 +579,9 @@ fn dyn_tmtc_pool_main() {
         hk_tc_sender: pus_hk_tx,
         action_tc_sender: pus_action_tx,
         mode_tc_sender: pus_mode_tx,
+        device_access_tc_sender: pus_device_access_tx,
     };
+    pus_router.register_component_ids(component_ids);
 
     let pus_test_service =
         create_test_service_dynamic(tm_sink_tx.clone(), event_tx.clone(), pus_test_rx);
@@ -488,24 +609,46 @@ fn dyn_tmtc_pool_main() {
     let pus_mode_service = create_mode_service_dynamic(
         tm_sink_tx.clone(),
         pus_mode_rx,
-        request_map,
+        request_map.clone(),
         pus_mode_reply_rx,
     );
-    let mut pus_stack = PusStack::new(
+    let pus_device_access_service = create_device_access_service_dynamic(
+        tm_sink_tx.clone(),
+        pus_device_access_rx,
+        request_map,
+        pus_raw_reply_rx,
+    );
+    let pus_stack = PusStack::new(
         pus_test_service,
         pus_hk_service,
         pus_event_service,
         pus_action_service,
         pus_scheduler_service,
         pus_mode_service,
+        pus_device_access_service,
     );
 
     let mut tmtc_task = TcSourceTaskDynamic::new(
         tc_source_rx,
-        PusTcDistributor::new(tm_sink_tx.clone(), pus_router),
+        PusTcDistributor::new(
+            tm_sink_tx.clone(),
+            pus_router,
+            tmtc_stats.clone(),
+            EventU32SenderMpscBounded::new_registered(
+                component_ids,
+                PUS_ROUTING_SERVICE.id(),
+                "PUS_ROUTING_SERVICE",
+                event_tx.clone(),
+                EVENT_QUEUE_DEPTH,
+            )
+            .expect("registering component ID failed"),
+        ),
     );
 
     let sock_addr = SocketAddr::new(IpAddr::V4(OBSW_SERVER_ADDR), SERVER_PORT);
+    component_ids
+        .register_target(UDP_SERVER, "UDP_SERVER")
+        .expect("registering component ID failed");
     let udp_tc_server = UdpTcServer::new(UDP_SERVER.id(), sock_addr, 2048, tc_source_tx.clone())
         .expect("creating UDP TMTC server failed");
     let mut udp_tmtc_server = UdpTmtcServer {
@@ -515,6 +658,9 @@ fn dyn_tmtc_pool_main() {
         },
     };
 
+    component_ids
+        .register_target(TCP_SERVER, "TCP_SERVER")
+        .expect("registering component ID failed");
     let tcp_server_cfg = ServerConfig::new(
         TCP_SERVER.id(),
         sock_addr,
@@ -528,44 +674,12 @@ fn dyn_tmtc_pool_main() {
         sync_tm_tcp_source.clone(),
         tc_source_tx.clone(),
         PACKET_ID_VALIDATOR.clone(),
+        stages.tcp_server.signal(),
     )
     .expect("tcp server creation failed");
 
-    let mut tm_funnel = TmSinkDynamic::new(sync_tm_tcp_source, tm_sink_rx, tm_server_tx);
-
-    let shared_switch_set = Arc::new(Mutex::default());
-    let (switch_request_tx, switch_request_rx) = mpsc::sync_channel(20);
-    let switch_helper = PowerSwitchHelper::new(switch_request_tx, shared_switch_set.clone());
-
-    let (mgm_handler_mode_reply_to_parent_tx, _mgm_handler_mode_reply_to_parent_rx) =
-        mpsc::sync_channel(5);
-    let shared_mgm_set = Arc::default();
-    let mode_leaf_interface = MpscModeLeafInterface {
-        request_rx: mgm_handler_mode_rx,
-        reply_to_pus_tx: pus_mode_reply_tx.clone(),
-        reply_to_parent_tx: mgm_handler_mode_reply_to_parent_tx,
-    };
-
-    let mgm_spi_interface = if let Some(sim_client) = opt_sim_client.as_mut() {
-        sim_client.add_reply_recipient(satrs_minisim::SimComponent::MgmLis3Mdl, mgm_sim_reply_tx);
-        SpiSimInterfaceWrapper::Sim(SpiSimInterface {
-            sim_request_tx: sim_request_tx.clone(),
-            sim_reply_rx: mgm_sim_reply_rx,
-        })
-    } else {
-        SpiSimInterfaceWrapper::Dummy(SpiDummyInterface::default())
-    };
-    let mut mgm_handler = MgmHandlerLis3Mdl::new(
-        MGM_HANDLER_0,
-        "MGM_0",
-        mode_leaf_interface,
-        mgm_handler_composite_rx,
-        pus_hk_reply_tx.clone(),
-        switch_helper.clone(),
-        tm_sink_tx.clone(),
-        mgm_spi_interface,
-        shared_mgm_set,
-    );
+    let mut tm_funnel =
+        TmSinkDynamic::new(sync_tm_tcp_source, tm_sink_rx, tm_server_tx, tmtc_stats);
 
     let (pcdu_handler_mode_reply_to_parent_tx, _pcdu_handler_mode_reply_to_parent_rx) =
         mpsc::sync_channel(10);
@@ -581,9 +695,9 @@ fn dyn_tmtc_pool_main() {
             pcdu_sim_reply_rx,
         ))
     } else {
-        SerialSimInterfaceWrapper::Dummy(SerialInterfaceDummy::default())
+        SerialSimInterfaceWrapper::Dummy(SerialInterfaceDummy::new(fake_pcdu))
     };
-    let mut pcdu_handler = PcduHandler::new(
+    let pcdu_handler = PcduHandler::new(
         PCDU_HANDLER,
         "PCDU",
         pcdu_mode_leaf_interface,
@@ -602,47 +716,63 @@ fn dyn_tmtc_pool_main() {
         ))
         .expect("sending initial mode request failed");
 
+    info!("{} component IDs registered", component_ids.len());
     info!("Starting TMTC and UDP task");
+    let udp_shutdown = stages.tc_input.signal();
     let jh_udp_tmtc = thread::Builder::new()
         .name("sat-rs tmtc-udp".to_string())
         .spawn(move || {
             info!("Running UDP server on port {SERVER_PORT}");
-            loop {
+            while !udp_shutdown.shutdown_requested() {
                 udp_tmtc_server.periodic_operation();
                 tmtc_task.periodic_operation();
                 thread::sleep(Duration::from_millis(FREQ_MS_UDP_TMTC));
             }
+            // Forward the telecommands which were already received.
+            tmtc_task.periodic_operation();
         })
         .unwrap();
 
     info!("Starting TCP task");
+    let tcp_shutdown = stages.tcp_server.signal();
     let jh_tcp = thread::Builder::new()
         .name("sat-rs tcp".to_string())
         .spawn(move || {
             info!("Running TCP server on port {SERVER_PORT}");
-            loop {
+            while !tcp_shutdown.shutdown_requested() {
                 tcp_server.periodic_operation();
             }
         })
         .unwrap();
 
     info!("Starting TM funnel task");
+    let tm_funnel_shutdown = stages.tm_sink.signal();
     let jh_tm_funnel = thread::Builder::new()
         .name("sat-rs tm-sink".to_string())
-        .spawn(move || loop {
-            tm_funnel.operation();
+        .spawn(move || {
+            while !tm_funnel_shutdown.shutdown_requested() {
+                tm_funnel.operation();
+            }
+            let outcome = tm_funnel.drain(Duration::from_millis(SHUTDOWN_DRAIN_TIMEOUT_MS));
+            info!(
+                "TM sink stopped after forwarding {} TM",
+                outcome.num_drained
+            );
         })
         .unwrap();
 
     let mut opt_jh_sim_client = None;
     if let Some(mut sim_client) = opt_sim_client {
         info!("Starting UDP sim client task");
+        let sim_client_shutdown = stages.pus.signal();
         opt_jh_sim_client = Some(
             thread::Builder::new()
                 .name("sat-rs sim adapter".to_string())
-                .spawn(move || loop {
-                    if sim_client.operation() == HandlingStatus::Empty {
-                        std::thread::sleep(Duration::from_millis(SIM_CLIENT_IDLE_DELAY_MS));
+                .spawn(move || {
+                    while !sim_client_shutdown.shutdown_requested() {
+                        if sim_client.operation() == HandlingStatus::Empty {
+                            std::thread::sleep(Duration::from_millis(SIM_CLIENT_IDLE_DELAY_MS));
+                        }
                     }
                 })
                 .unwrap(),
@@ -650,67 +780,93 @@ fn dyn_tmtc_pool_main() {
     }
 
     info!("Starting AOCS thread");
-    let jh_aocs = thread::Builder::new()
-        .name("sat-rs aocs".to_string())
-        .spawn(move || loop {
-            mgm_handler.periodic_operation();
-            thread::sleep(Duration::from_millis(FREQ_MS_AOCS));
-        })
-        .unwrap();
+    let mut aocs_scheduler = FixedRateScheduler::new("sat-rs aocs", stages.pus.signal());
+    aocs_scheduler.add_task(
+        Box::new(acs_subsystem),
+        Duration::from_millis(FREQ_MS_AOCS),
+        0,
+    );
+    stages
+        .pus
+        .spawn_scheduler("sat-rs aocs", move || aocs_scheduler.run());
+
+    info!("Starting TCS thread");
+    let mut tcs_scheduler = FixedRateScheduler::new("sat-rs tcs", stages.pus.signal());
+    tcs_scheduler.add_task(
+        Box::new(tcs_subsystem),
+        Duration::from_millis(FREQ_MS_TCS),
+        0,
+    );
+    stages
+        .pus
+        .spawn_scheduler("sat-rs tcs", move || tcs_scheduler.run());
 
     info!("Starting EPS thread");
-    let jh_eps = thread::Builder::new()
-        .name("sat-rs eps".to_string())
-        .spawn(move || loop {
-            // TODO: We should introduce something like a fixed timeslot helper to allow a more
-            // declarative API. It would also be very useful for the AOCS task.
-            pcdu_handler.periodic_operation(eps::pcdu::OpCode::RegularOp);
-            thread::sleep(Duration::from_millis(50));
-            pcdu_handler.periodic_operation(eps::pcdu::OpCode::PollAndRecvReplies);
-            thread::sleep(Duration::from_millis(50));
-            pcdu_handler.periodic_operation(eps::pcdu::OpCode::PollAndRecvReplies);
-            thread::sleep(Duration::from_millis(300));
-        })
-        .unwrap();
+    let (mut eps_executor, _eps_pst_handle) =
+        PollingSequenceExecutor::new("sat-rs eps", stages.pus.signal());
+    let pcdu_task_index = eps_executor.add_task(Box::new(pcdu_handler));
+    eps_executor
+        .add_table(eps::pcdu::create_pcdu_polling_sequence(pcdu_task_index))
+        .expect("adding the EPS polling sequence failed");
+    stages
+        .pus
+        .spawn_scheduler("sat-rs eps", move || eps_executor.run());
 
     info!("Starting PUS handler thread");
-    let jh_pus_handler = thread::Builder::new()
-        .name("sat-rs pus".to_string())
-        .spawn(move || loop {
-            pus_stack.periodic_operation();
-            event_handler.periodic_operation();
-            thread::sleep(Duration::from_millis(FREQ_MS_PUS_STACK));
-        })
-        .unwrap();
+    let shutdown_event_sender =
+        EventU32SenderMpscBounded::new(PUS_EVENT_MANAGEMENT.id(), event_tx, EVENT_QUEUE_DEPTH);
+    // The PUS task is stopped with the stage signal itself, so it can handle the queued input
+    // before it terminates.
+    let mut pus_scheduler = FixedRateScheduler::new("sat-rs pus", ShutdownSignal::new());
+    pus_scheduler.add_task(
+        Box::new(PusTask::new(
+            event_handler,
+            pus_stack,
+            stages.pus.signal(),
+            shutdown_event_sender,
+        )),
+        Duration::from_millis(FREQ_MS_PUS_STACK),
+        0,
+    );
+    stages
+        .pus
+        .spawn_scheduler("sat-rs pus", move || pus_scheduler.run());
 
-    jh_udp_tmtc
-        .join()
-        .expect("Joining UDP TMTC server thread failed");
-    jh_tcp
-        .join()
-        .expect("Joining TCP TMTC server thread failed");
-    jh_tm_funnel
-        .join()
-        .expect("Joining TM Funnel thread failed");
+    stages.tc_input.add_thread(jh_udp_tmtc);
     if let Some(jh_sim_client) = opt_jh_sim_client {
-        jh_sim_client
-            .join()
-            .expect("Joining SIM client thread failed");
+        stages.pus.add_thread(jh_sim_client);
     }
-    jh_aocs.join().expect("Joining AOCS thread failed");
-    jh_eps.join().expect("Joining EPS thread failed");
-    jh_pus_handler
-        .join()
-        .expect("Joining PUS handler thread failed");
+    stages.tm_sink.add_thread(jh_tm_funnel);
+    stages.tcp_server.add_thread(jh_tcp);
+    stages.run(&shutdown);
 }
 
 fn main() {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("--export-tm-defs") {
+        let path = args
+            .next()
+            .expect("missing path for the exported TM definitions");
+        mib::export_tm_definitions(&path).expect("exporting the TM definitions failed");
+        println!("Exported TM definitions to {path}");
+        return;
+    }
     setup_logger().expect("setting up logging with fern failed");
     println!("Running OBSW example");
+    // All components register their ID when they are created, which catches duplicate IDs
+    // before any thread is started.
+    let mut component_ids = ComponentIdRegistry::new();
+    component_ids
+        .register(NO_SENDER, "NO_SENDER")
+        .expect("registering component ID failed");
+    // Requesting a shutdown with this signal stops all threads in stages after they handled their
+    // queued input, which is required before a software update for example.
+    let shutdown = ShutdownSignal::new();
+    install_signal_handler(shutdown.clone()).expect("installing the signal handler failed");
     #[cfg(not(feature = "dyn_tmtc"))]
-    static_tmtc_pool_main();
+    static_tmtc_pool_main(shutdown, &mut component_ids);
     #[cfg(feature = "dyn_tmtc")]
-    dyn_tmtc_pool_main();
+    dyn_tmtc_pool_main(shutdown, &mut component_ids);
 }
 
 pub fn update_time(time_provider: &mut CdsTime, timestamp: &mut [u8]) {
@@ -721,3 +877,91 @@ pub fn update_time(time_provider: &mut CdsTime, timestamp: &mut [u8]) {
         .write_to_bytes(timestamp)
         .expect("Writing timestamp failed");
 }
+
+#[cfg(test)]
+mod tests {
+    use satrs::tmtc::PacketAsVec;
+
+    use super::*;
+
+    /// Runs the component ID registration of the main functions against a single registry, so
+    /// that colliding IDs or names of the example components are found without starting the
+    /// binary.
+    #[test]
+    fn test_component_id_registration() {
+        let mut component_ids = ComponentIdRegistry::new();
+        component_ids
+            .register(NO_SENDER, "NO_SENDER")
+            .expect("registering component ID failed");
+
+        let (tm_sink_tx, _tm_sink_rx) = mpsc::channel::<PacketAsVec>();
+        let (pus_hk_reply_tx, _pus_hk_reply_rx) = mpsc::channel();
+        let (pus_mode_reply_tx, _pus_mode_reply_rx) = mpsc::channel();
+        let (pus_raw_reply_tx, _pus_raw_reply_rx) = mpsc::channel();
+        let (sim_request_tx, _sim_request_rx) = mpsc::channel();
+        let (switch_request_tx, _switch_request_rx) = mpsc::sync_channel(20);
+        let switch_helper = PowerSwitchHelper::new(switch_request_tx, Arc::new(Mutex::default()));
+        let (_fake_pcdu, fake_mgm) = create_fake_acs_and_eps(FakeClock::new_manual());
+        let (_acs_subsystem, acs_request_senders) = AcsSubsystem::new(
+            create_mgm_com_interface(None, &sim_request_tx, fake_mgm),
+            switch_helper,
+            AcsReplySenders {
+                mode_reply_tx: pus_mode_reply_tx.clone(),
+                hk_reply_tx: pus_hk_reply_tx.clone(),
+                raw_reply_tx: pus_raw_reply_tx.clone(),
+                tm_sender: tm_sink_tx.clone(),
+            },
+        );
+        let (_tcs_subsystem, tcs_request_senders) = TcsSubsystem::new(
+            simulated_heater_output(),
+            FakeTmp117::default(),
+            TcsReplySenders {
+                mode_reply_tx: pus_mode_reply_tx,
+                hk_reply_tx: pus_hk_reply_tx,
+                raw_reply_tx: pus_raw_reply_tx,
+                tm_sender: tm_sink_tx.clone(),
+            },
+        );
+        let mut request_map = GenericRequestRouter::default();
+        acs_request_senders.register(&mut request_map, &mut component_ids);
+        tcs_request_senders.register(&mut request_map, &mut component_ids);
+        component_ids
+            .register_target(PCDU_HANDLER, "PCDU_HANDLER")
+            .expect("registering component ID failed");
+
+        let (event_tx, event_rx) = mpsc::sync_channel(EVENT_QUEUE_DEPTH);
+        let (_event_request_tx, event_request_rx) = mpsc::channel::<EventRequestWithToken>();
+        let _event_handler =
+            EventHandler::new(tm_sink_tx, event_rx, event_request_rx, &mut component_ids);
+        let pus_router = PusTcMpscRouter {
+            test_tc_sender: mpsc::channel().0,
+            event_tc_sender: mpsc::channel().0,
+            sched_tc_sender: mpsc::channel().0,
+            hk_tc_sender: mpsc::channel().0,
+            action_tc_sender: mpsc::channel().0,
+            mode_tc_sender: mpsc::channel().0,
+            device_access_tc_sender: mpsc::channel().0,
+        };
+        pus_router.register_component_ids(&mut component_ids);
+        EventU32SenderMpscBounded::new_registered(
+            &mut component_ids,
+            PUS_ROUTING_SERVICE.id(),
+            "PUS_ROUTING_SERVICE",
+            event_tx,
+            EVENT_QUEUE_DEPTH,
+        )
+        .expect("registering component ID failed");
+        component_ids
+            .register_target(UDP_SERVER, "UDP_SERVER")
+            .expect("registering component ID failed");
+        component_ids
+            .register_target(TCP_SERVER, "TCP_SERVER")
+            .expect("registering component ID failed");
+
+        assert_eq!(component_ids.len(), 15);
+        assert_eq!(
+            component_ids.id_of("PUS_EVENT_MANAGEMENT"),
+            Some(PUS_EVENT_MANAGEMENT.id())
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! TM definitions of the example, which can be exported for the ground segment with the
+//! `--export-tm-defs <path>` command line argument.
+use satrs::boot_report::BOOT_EVENT_DEFINITIONS;
+use satrs::error_report::INTERNAL_ERROR_EVENT_DEFINITIONS;
+use satrs::log_report::LOG_EVENT_DEFINITIONS;
+use satrs::pus::tc_quarantine::TC_QUARANTINE_EVENT_DEFINITIONS;
+use satrs::shutdown::SHUTDOWN_EVENT_DEFINITIONS;
+use satrs_example::config::components::{Apid, HEATER_0, MGM_HANDLER_0, TEMP_SENSOR_0};
+use satrs_example::config::EXAMPLE_EVENT_DEFINITIONS;
+use satrs_mib::tm_def::{pus_verification_packets, TmDefRegistry, TmPacketDef};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use crate::acs::mgm::{self, MGM_SENSOR_PARAMS};
+use crate::tcs::heater::{self, HEATER_STATUS_PARAMS};
+use crate::tcs::temp_sensor::{self, TEMPERATURE_PARAMS};
+
+/// Definitions of the HK sets, events and verification reports generated by the example.
+/// Panics if two definitions collide.
+pub fn tm_definitions() -> TmDefRegistry {
+    let mut registry = TmDefRegistry::new();
+    registry
+        .add_packets([
+            TmPacketDef::hk_set(
+                "MGM_SENSOR_DATA",
+                MGM_HANDLER_0.apid,
+                MGM_HANDLER_0.unique_id,
+                mgm::SetId::SensorData as u32,
+            )
+            .with_params(MGM_SENSOR_PARAMS),
+            TmPacketDef::hk_set(
+                "HEATER_STATUS",
+                HEATER_0.apid,
+                HEATER_0.unique_id,
+                heater::SetId::HeaterStatus as u32,
+            )
+            .with_params(HEATER_STATUS_PARAMS),
+            TmPacketDef::hk_set(
+                "TEMPERATURE",
+                TEMP_SENSOR_0.apid,
+                TEMP_SENSOR_0.unique_id,
+                temp_sensor::SetId::Temperature as u32,
+            )
+            .with_params(TEMPERATURE_PARAMS),
+        ])
+        .expect("adding HK set definitions failed");
+    // The event reports use the APID of the event sender. All events of the example and the
+    // framework events which the example generates are sent by PUS components.
+    for definitions in [
+        EXAMPLE_EVENT_DEFINITIONS,
+        INTERNAL_ERROR_EVENT_DEFINITIONS,
+        BOOT_EVENT_DEFINITIONS,
+        LOG_EVENT_DEFINITIONS,
+        SHUTDOWN_EVENT_DEFINITIONS,
+        TC_QUARANTINE_EVENT_DEFINITIONS,
+    ] {
+        registry
+            .add_event_definitions(Apid::GenericPus as u16, definitions)
+            .expect("adding event definitions failed");
+    }
+    for apid in [Apid::GenericPus, Apid::Sched] {
+        registry
+            .add_packets(pus_verification_packets(apid as u16))
+            .expect("adding verification report definitions failed");
+    }
+    registry
+}
+
+/// Write the [tm_definitions] as JSON to the given path.
+pub fn export_tm_definitions(path: impl AsRef<Path>) -> io::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    tm_definitions().write_json(writer).map_err(io::Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::acs::mgm::create_mgm_hk_sets;
+
+    use super::*;
+
+    #[test]
+    fn test_tm_definitions() {
+        let registry = tm_definitions();
+        let mgm_set_def = registry
+            .packets()
+            .iter()
+            .find(|packet| packet.name == "MGM_SENSOR_DATA")
+            .unwrap();
+        let mut hk_buf = [0; 64];
+        let mgm_set_len = create_mgm_hk_sets(MGM_HANDLER_0, Arc::default())
+            .pack_set(mgm::SetId::SensorData as u32, &mut hk_buf)
+            .unwrap();
+        assert_eq!(mgm_set_len, mgm_set_def.source_data_len());
+        assert!(registry
+            .packets()
+            .iter()
+            .any(|packet| packet.name == "TEST_EVENT" && packet.apid == Apid::GenericPus as u16));
+
+        let mut json = Vec::new();
+        registry.write_json(&mut json).unwrap();
+        assert!(String::from_utf8(json).unwrap().contains("HEATER_STATUS"));
+    }
+}
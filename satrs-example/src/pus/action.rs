@@ -4,6 +4,7 @@ use satrs::pool::SharedStaticMemoryPool;
 use satrs::pus::action::{
     ActionReplyPus, ActionReplyVariant, ActivePusActionRequestStd, DefaultActiveActionRequestMap,
 };
+use satrs::pus::app_data::{AppDataReader, NotEnoughAppData};
 use satrs::pus::verification::{
     handle_completion_failure_with_generic_params, handle_step_failure_with_generic_params,
     FailParamHelper, FailParams, TcStateAccepted, TcStateStarted, VerificationReporter,
@@ -15,9 +16,10 @@ use satrs::pus::{
     MpscTmAsVecSender, PusPacketHandlingError, PusReplyHandler, PusServiceHelper,
     PusTcToRequestConverter,
 };
-use satrs::request::{GenericMessage, UniqueApidTargetId};
+use satrs::request::GenericMessage;
 use satrs::spacepackets::ecss::tc::PusTcReader;
 use satrs::spacepackets::ecss::{EcssEnumU16, PusPacket, PusServiceId};
+use satrs::spacepackets::CcsdsPacket;
 use satrs::tmtc::{PacketAsVec, PacketSenderWithSharedPool};
 use satrs_example::config::components::PUS_ACTION_SERVICE;
 use satrs_example::config::tmtc_err;
@@ -163,27 +165,31 @@ impl PusTcToRequestConverter<ActivePusActionRequestStd, ActionRequest> for Actio
         time_stamp: &[u8],
     ) -> Result<(ActivePusActionRequestStd, ActionRequest), Self::Error> {
         let subservice = tc.subservice();
-        let user_data = tc.user_data();
-        if user_data.len() < 8 {
+        let not_enough_app_data = |e: NotEnoughAppData| {
             verif_reporter
                 .start_failure(
                     tm_sender,
                     token,
-                    FailParams::new_no_fail_data(time_stamp, &tmtc_err::NOT_ENOUGH_APP_DATA),
+                    FailParams::new(
+                        time_stamp,
+                        &tmtc_err::NOT_ENOUGH_APP_DATA,
+                        &e.to_fail_data(),
+                    ),
                 )
                 .expect("Sending start failure failed");
-            return Err(GenericConversionError::NotEnoughAppData {
-                expected: 8,
-                found: user_data.len(),
-            });
-        }
-        let target_id_and_apid = UniqueApidTargetId::from_pus_tc(tc).unwrap();
-        let action_id = u32::from_be_bytes(user_data[4..8].try_into().unwrap());
+            GenericConversionError::from(e)
+        };
+        let mut app_data = AppDataReader::new(tc.user_data());
+        let target_id_and_apid = app_data
+            .read_target_id(tc.apid())
+            .map_err(not_enough_app_data)?;
+        let action_id = app_data.read_u32().map_err(not_enough_app_data)?;
         if subservice == 128 {
-            let req_variant = if user_data.len() == 8 {
+            let user_data = app_data.read_remaining();
+            let req_variant = if user_data.is_empty() {
                 ActionRequestVariant::NoData
             } else {
-                ActionRequestVariant::VecData(user_data[8..].to_vec())
+                ActionRequestVariant::VecData(user_data.to_vec())
             };
             Ok((
                 ActivePusActionRequestStd::new(
@@ -305,7 +311,7 @@ mod tests {
     };
     use satrs::pus::verification;
     use satrs::pus::verification::test_util::TestVerificationReporter;
-    use satrs::request::MessageMetadata;
+    use satrs::request::{MessageMetadata, UniqueApidTargetId};
     use satrs::ComponentId;
     use satrs::{
         res_code::ResultU16,
@@ -346,7 +352,8 @@ mod tests {
             let mut generic_req_router = GenericRequestRouter::default();
             generic_req_router
                 .composite_router_map
-                .insert(target_id, action_req_tx);
+                .register_with_target_id(UniqueApidTargetId::from(target_id), action_req_tx)
+                .unwrap();
             Self {
                 service: PusTargetedRequestService::new(
                     PusServiceHelper::new(
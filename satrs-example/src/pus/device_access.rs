@@ -0,0 +1,362 @@
+use log::warn;
+use satrs::device::{RawDeviceCommand, RawDeviceReply, RawDeviceReplyVariant};
+use satrs::pool::SharedStaticMemoryPool;
+use satrs::pus::device_access::{
+    ActivePusRawRequestStd, DefaultActiveRawRequestMap, DeviceAccessSubservice,
+    PusRawReplyTmCreator, TARGET_ID_LEN,
+};
+use satrs::pus::verification::{
+    FailParams, TcStateAccepted, TcStateStarted, VerificationReporter,
+    VerificationReportingProvider, VerificationToken,
+};
+use satrs::pus::{
+    ActiveRequestProvider, EcssTcAndToken, EcssTcInMemConverter, EcssTcInSharedStoreConverter,
+    EcssTcInVecConverter, EcssTmSender, EcssTmtcError, GenericConversionError, MpscTcReceiver,
+    MpscTmAsVecSender, PusPacketHandlingError, PusReplyHandler, PusServiceHelper,
+    PusTcToRequestConverter,
+};
+use satrs::request::{GenericMessage, UniqueApidTargetId};
+use satrs::spacepackets::ecss::tc::{GenericPusTcSecondaryHeader, PusTcReader};
+use satrs::spacepackets::ecss::{PusPacket, PusServiceId};
+use satrs::tmtc::{PacketAsVec, PacketSenderWithSharedPool};
+use satrs_example::config::components::PUS_DEVICE_ACCESS_SERVICE;
+use satrs_example::config::tmtc_err;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::requests::GenericRequestRouter;
+
+use super::{
+    create_verification_reporter, generic_pus_request_timeout_handler, HandlingStatus,
+    PusTargetedRequestService, TargetedPusService,
+};
+
+/// Maximum length of a raw device reply which can be sent as a TM[2,130] report.
+pub const MAX_RAW_REPLY_LEN: usize = 1024;
+
+pub struct RawReplyHandler {
+    tm_creator: PusRawReplyTmCreator,
+}
+
+impl Default for RawReplyHandler {
+    fn default() -> Self {
+        Self {
+            tm_creator: PusRawReplyTmCreator::new(
+                PUS_DEVICE_ACCESS_SERVICE.id(),
+                PUS_DEVICE_ACCESS_SERVICE.apid,
+                MAX_RAW_REPLY_LEN,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+impl PusReplyHandler<ActivePusRawRequestStd, RawDeviceReply> for RawReplyHandler {
+    type Error = EcssTmtcError;
+
+    fn handle_unrequested_reply(
+        &mut self,
+        reply: &GenericMessage<RawDeviceReply>,
+        _tm_sender: &impl EcssTmSender,
+    ) -> Result<(), Self::Error> {
+        warn!("received unexpected reply for service 2: {reply:?}");
+        Ok(())
+    }
+
+    fn handle_reply(
+        &mut self,
+        reply: &GenericMessage<RawDeviceReply>,
+        active_request: &ActivePusRawRequestStd,
+        tm_sender: &(impl EcssTmSender + ?Sized),
+        verification_handler: &impl VerificationReportingProvider,
+        time_stamp: &[u8],
+    ) -> Result<bool, Self::Error> {
+        let started_token: VerificationToken<TcStateStarted> = active_request
+            .token()
+            .try_into()
+            .expect("invalid token state");
+        let remove_entry = match &reply.message.variant {
+            RawDeviceReplyVariant::Data(raw_reply) => {
+                self.tm_creator.send_raw_reply(
+                    UniqueApidTargetId::from(active_request.target_id()).unique_id,
+                    active_request.dest_id,
+                    raw_reply,
+                    time_stamp,
+                    tm_sender,
+                )?;
+                false
+            }
+            RawDeviceReplyVariant::Completed => {
+                verification_handler.completion_success(tm_sender, started_token, time_stamp)?;
+                true
+            }
+            RawDeviceReplyVariant::Failed(error_code) => {
+                verification_handler.completion_failure(
+                    tm_sender,
+                    started_token,
+                    FailParams::new(time_stamp, error_code, &[]),
+                )?;
+                true
+            }
+            _ => false,
+        };
+        Ok(remove_entry)
+    }
+
+    fn handle_request_timeout(
+        &mut self,
+        active_request: &ActivePusRawRequestStd,
+        tm_sender: &impl EcssTmSender,
+        verification_handler: &impl VerificationReportingProvider,
+        time_stamp: &[u8],
+    ) -> Result<(), Self::Error> {
+        generic_pus_request_timeout_handler(
+            tm_sender,
+            active_request,
+            verification_handler,
+            time_stamp,
+            "device access",
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct RawCommandConverter {}
+
+impl PusTcToRequestConverter<ActivePusRawRequestStd, RawDeviceCommand> for RawCommandConverter {
+    type Error = GenericConversionError;
+
+    fn convert(
+        &mut self,
+        token: VerificationToken<TcStateAccepted>,
+        tc: &PusTcReader,
+        tm_sender: &(impl EcssTmSender + ?Sized),
+        verif_reporter: &impl VerificationReportingProvider,
+        time_stamp: &[u8],
+    ) -> Result<(ActivePusRawRequestStd, RawDeviceCommand), Self::Error> {
+        let subservice = tc.subservice();
+        if subservice != u8::from(DeviceAccessSubservice::TcRawCommand) {
+            verif_reporter
+                .start_failure(
+                    tm_sender,
+                    token,
+                    FailParams::new_no_fail_data(time_stamp, &tmtc_err::INVALID_PUS_SUBSERVICE),
+                )
+                .expect("Sending start failure failed");
+            return Err(GenericConversionError::InvalidSubservice(subservice));
+        }
+        let user_data = tc.user_data();
+        if user_data.len() <= TARGET_ID_LEN {
+            verif_reporter
+                .start_failure(
+                    tm_sender,
+                    token,
+                    FailParams::new_no_fail_data(time_stamp, &tmtc_err::NOT_ENOUGH_APP_DATA),
+                )
+                .expect("Sending start failure failed");
+            return Err(GenericConversionError::NotEnoughAppData {
+                expected: TARGET_ID_LEN + 1,
+                found: user_data.len(),
+            });
+        }
+        let target_id_and_apid = UniqueApidTargetId::from_pus_tc(tc).unwrap();
+        Ok((
+            ActivePusRawRequestStd::new(
+                tc.source_id(),
+                target_id_and_apid.into(),
+                token.into(),
+                Duration::from_secs(10),
+            ),
+            RawDeviceCommand::new(user_data[TARGET_ID_LEN..].to_vec()),
+        ))
+    }
+}
+
+pub fn create_device_access_service_static(
+    tm_sender: PacketSenderWithSharedPool,
+    tc_pool: SharedStaticMemoryPool,
+    pus_device_access_rx: mpsc::Receiver<EcssTcAndToken>,
+    request_router: GenericRequestRouter,
+    reply_receiver: mpsc::Receiver<GenericMessage<RawDeviceReply>>,
+) -> DeviceAccessServiceWrapper<PacketSenderWithSharedPool, EcssTcInSharedStoreConverter> {
+    let raw_request_handler = PusTargetedRequestService::new(
+        PusServiceHelper::new(
+            PUS_DEVICE_ACCESS_SERVICE.id(),
+            pus_device_access_rx,
+            tm_sender,
+            create_verification_reporter(
+                PUS_DEVICE_ACCESS_SERVICE.id(),
+                PUS_DEVICE_ACCESS_SERVICE.apid,
+            ),
+            EcssTcInSharedStoreConverter::new(tc_pool, 2048),
+        ),
+        RawCommandConverter::default(),
+        DefaultActiveRawRequestMap::default(),
+        RawReplyHandler::default(),
+        request_router,
+        reply_receiver,
+    );
+    DeviceAccessServiceWrapper {
+        service: raw_request_handler,
+    }
+}
+
+pub fn create_device_access_service_dynamic(
+    tm_funnel_tx: mpsc::Sender<PacketAsVec>,
+    pus_device_access_rx: mpsc::Receiver<EcssTcAndToken>,
+    request_router: GenericRequestRouter,
+    reply_receiver: mpsc::Receiver<GenericMessage<RawDeviceReply>>,
+) -> DeviceAccessServiceWrapper<MpscTmAsVecSender, EcssTcInVecConverter> {
+    let raw_request_handler = PusTargetedRequestService::new(
+        PusServiceHelper::new(
+            PUS_DEVICE_ACCESS_SERVICE.id(),
+            pus_device_access_rx,
+            tm_funnel_tx,
+            create_verification_reporter(
+                PUS_DEVICE_ACCESS_SERVICE.id(),
+                PUS_DEVICE_ACCESS_SERVICE.apid,
+            ),
+            EcssTcInVecConverter::default(),
+        ),
+        RawCommandConverter::default(),
+        DefaultActiveRawRequestMap::default(),
+        RawReplyHandler::default(),
+        request_router,
+        reply_receiver,
+    );
+    DeviceAccessServiceWrapper {
+        service: raw_request_handler,
+    }
+}
+
+pub struct DeviceAccessServiceWrapper<
+    TmSender: EcssTmSender,
+    TcInMemConverter: EcssTcInMemConverter,
+> {
+    pub(crate) service: PusTargetedRequestService<
+        MpscTcReceiver,
+        TmSender,
+        TcInMemConverter,
+        VerificationReporter,
+        RawCommandConverter,
+        RawReplyHandler,
+        DefaultActiveRawRequestMap,
+        ActivePusRawRequestStd,
+        RawDeviceCommand,
+        RawDeviceReply,
+    >,
+}
+
+impl<TmSender: EcssTmSender, TcInMemConverter: EcssTcInMemConverter> TargetedPusService
+    for DeviceAccessServiceWrapper<TmSender, TcInMemConverter>
+{
+    const SERVICE_ID: u8 = PusServiceId::DeviceAccess as u8;
+    const SERVICE_STR: &'static str = "device access";
+
+    delegate::delegate! {
+        to self.service {
+            fn poll_and_handle_next_tc(
+                &mut self,
+                time_stamp: &[u8],
+            ) -> Result<HandlingStatus, PusPacketHandlingError>;
+
+            fn poll_and_handle_next_reply(
+                &mut self,
+                time_stamp: &[u8],
+            ) -> Result<HandlingStatus, EcssTmtcError>;
+
+            fn check_for_request_timeouts(&mut self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use satrs::pus::test_util::{TEST_APID, TEST_COMPONENT_ID_0, TEST_UNIQUE_ID_0};
+    use satrs::request::MessageMetadata;
+    use satrs::spacepackets::ecss::tc::PusTcCreator;
+    use satrs::spacepackets::ecss::tm::{GenericPusTmSecondaryHeader, PusTmReader};
+    use satrs::spacepackets::SpHeader;
+
+    use crate::pus::tests::{PusConverterTestbench, ReplyHandlerTestbench};
+
+    use super::*;
+
+    #[test]
+    fn raw_command_conversion() {
+        let mut testbench =
+            PusConverterTestbench::new(TEST_COMPONENT_ID_0.id(), RawCommandConverter::default());
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let mut app_data: [u8; 7] = [0; 7];
+        app_data[0..4].copy_from_slice(&TEST_UNIQUE_ID_0.to_be_bytes());
+        app_data[4..7].copy_from_slice(&[1, 2, 3]);
+        let raw_cmd_tc = PusTcCreator::new_simple(
+            sp_header,
+            2,
+            DeviceAccessSubservice::TcRawCommand as u8,
+            &app_data,
+            true,
+        );
+        let accepted_token = testbench.add_tc(&raw_cmd_tc);
+        let (_active_req, req) = testbench
+            .convert(accepted_token, &[], TEST_APID, TEST_UNIQUE_ID_0)
+            .expect("conversion failed");
+        assert_eq!(req.data, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn raw_command_conversion_no_raw_data() {
+        let mut testbench =
+            PusConverterTestbench::new(TEST_COMPONENT_ID_0.id(), RawCommandConverter::default());
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let raw_cmd_tc = PusTcCreator::new_simple(
+            sp_header,
+            2,
+            DeviceAccessSubservice::TcRawCommand as u8,
+            &TEST_UNIQUE_ID_0.to_be_bytes(),
+            true,
+        );
+        let accepted_token = testbench.add_tc(&raw_cmd_tc);
+        let result = testbench.convert(accepted_token, &[], TEST_APID, TEST_UNIQUE_ID_0);
+        assert!(matches!(
+            result,
+            Err(GenericConversionError::NotEnoughAppData {
+                expected: 5,
+                found: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn raw_reply_handling() {
+        let mut testbench =
+            ReplyHandlerTestbench::new(TEST_COMPONENT_ID_0.id(), RawReplyHandler::default());
+        let (req_id, active_req) = testbench.add_tc(TEST_APID, TEST_UNIQUE_ID_0, &[]);
+        let active_req = ActivePusRawRequestStd::new_from_common_req(0x42, active_req);
+        let data_reply = GenericMessage::new(
+            MessageMetadata::new(req_id.into(), TEST_COMPONENT_ID_0.id()),
+            RawDeviceReply::new(RawDeviceReplyVariant::Data(vec![1, 2, 3])),
+        );
+        let result = testbench.handle_reply(&data_reply, &active_req, &[0; 7]);
+        assert!(!result.expect("reply handling failed"));
+        let tm = testbench.tm_receiver.try_recv().expect("no raw reply TM");
+        let (tm, _) = PusTmReader::new(&tm.packet, 7).unwrap();
+        assert_eq!(tm.service(), 2);
+        assert_eq!(tm.subservice(), DeviceAccessSubservice::TmRawReply as u8);
+        assert_eq!(tm.dest_id(), 0x42);
+        assert_eq!(&tm.user_data()[0..4], &TEST_UNIQUE_ID_0.to_be_bytes());
+        assert_eq!(&tm.user_data()[4..], &[1, 2, 3]);
+
+        let completion_reply = GenericMessage::new(
+            MessageMetadata::new(req_id.into(), TEST_COMPONENT_ID_0.id()),
+            RawDeviceReply::new(RawDeviceReplyVariant::Completed),
+        );
+        let result = testbench.handle_reply(&completion_reply, &active_req, &[0; 7]);
+        assert!(result.expect("reply handling failed"));
+        testbench.verif_reporter.assert_full_completion_success(
+            TEST_COMPONENT_ID_0.raw(),
+            req_id,
+            None,
+        );
+    }
+}
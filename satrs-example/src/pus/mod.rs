@@ -1,6 +1,11 @@
 use crate::requests::GenericRequestRouter;
 use log::warn;
-use satrs::pool::PoolAddr;
+use satrs::event_man::EventU32SenderMpscBounded;
+use satrs::hk::{HkRequest, HkRequestVariant, HkSetRegistry};
+use satrs::id_registry::ComponentIdRegistry;
+use satrs::pool::{PoolAddr, PoolProvider, SharedStaticMemoryPool};
+use satrs::pus::tc_auth::{authenticate_and_accept_tc, AcceptAllTcs, TcAuthenticator};
+use satrs::pus::tc_quarantine::{report_crc_failure, TcCheckResult, TcCrcFilter};
 use satrs::pus::verification::{
     self, FailParams, TcStateAccepted, TcStateStarted, VerificationReporter,
     VerificationReporterCfg, VerificationReportingProvider, VerificationToken,
@@ -15,15 +20,23 @@ use satrs::queue::{GenericReceiveError, GenericSendError};
 use satrs::request::{Apid, GenericMessage, MessageMetadata};
 use satrs::spacepackets::ecss::tc::PusTcReader;
 use satrs::spacepackets::ecss::{PusPacket, PusServiceId};
+use satrs::spacepackets::CcsdsPacket;
+use satrs::tmtc::stats::{create_stats_hk_set, TcRejectionReason, TmtcStatistics};
 use satrs::tmtc::{PacketAsVec, PacketInPool};
 use satrs::ComponentId;
-use satrs_example::config::components::PUS_ROUTING_SERVICE;
-use satrs_example::config::{tmtc_err, CustomPusServiceId};
+use satrs_example::config::components::{
+    PUS_ACTION_SERVICE, PUS_DEVICE_ACCESS_SERVICE, PUS_HK_SERVICE, PUS_MODE_SERVICE,
+    PUS_ROUTING_SERVICE, PUS_SCHED_SERVICE, PUS_TEST_SERVICE,
+};
+use satrs_example::config::{tmtc_err, CustomPusServiceId, TC_CRC_CHECK_POLICY};
 use satrs_example::TimestampHelper;
 use std::fmt::Debug;
 use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 pub mod action;
+pub mod device_access;
 pub mod event;
 pub mod hk;
 pub mod mode;
@@ -46,18 +59,75 @@ pub struct PusTcMpscRouter {
     pub hk_tc_sender: Sender<EcssTcAndToken>,
     pub action_tc_sender: Sender<EcssTcAndToken>,
     pub mode_tc_sender: Sender<EcssTcAndToken>,
+    pub device_access_tc_sender: Sender<EcssTcAndToken>,
+}
+
+impl PusTcMpscRouter {
+    /// Register the IDs of the PUS services which receive telecommands from this router. The
+    /// event service shares its ID with the event handler, which registers it.
+    pub fn register_component_ids(&self, component_ids: &mut ComponentIdRegistry) {
+        for (target, name) in [
+            (PUS_TEST_SERVICE, "PUS_TEST_SERVICE"),
+            (PUS_SCHED_SERVICE, "PUS_SCHED_SERVICE"),
+            (PUS_HK_SERVICE, "PUS_HK_SERVICE"),
+            (PUS_ACTION_SERVICE, "PUS_ACTION_SERVICE"),
+            (PUS_MODE_SERVICE, "PUS_MODE_SERVICE"),
+            (PUS_DEVICE_ACCESS_SERVICE, "PUS_DEVICE_ACCESS_SERVICE"),
+        ] {
+            component_ids
+                .register_target(target, name)
+                .expect("registering component ID failed");
+        }
+    }
 }
 
+/// Set ID of the TMTC statistics HK set of the [PusTcDistributor].
+pub const TMTC_STATS_SET_ID: u32 = 0;
+
 pub struct PusTcDistributor<TmSender: EcssTmSender> {
     pub id: ComponentId,
     pub tm_sender: TmSender,
     pub verif_reporter: VerificationReporter,
     pub pus_router: PusTcMpscRouter,
+    /// All telecommands are authenticated before they are accepted. Telecommands which are
+    /// rejected by the authenticator are not distributed and an acceptance failure is reported.
+    pub tc_authenticator: Box<dyn TcAuthenticator + Send>,
+    /// Telecommands with an invalid CRC are handled according to [TC_CRC_CHECK_POLICY].
+    /// Quarantined telecommands are logged and reported with an event, but not distributed.
+    pub crc_filter: TcCrcFilter,
+    pub stats: Arc<TmtcStatistics>,
+    /// Pool of the telecommands received in a static pool. Telecommands which are not
+    /// distributed are deleted from this pool.
+    tc_pool: Option<SharedStaticMemoryPool>,
+    event_sender: EventU32SenderMpscBounded,
+    stats_hk_sets: HkSetRegistry,
     stamp_helper: TimestampHelper,
 }
 
 impl<TmSender: EcssTmSender> PusTcDistributor<TmSender> {
-    pub fn new(tm_sender: TmSender, pus_router: PusTcMpscRouter) -> Self {
+    pub fn new(
+        tm_sender: TmSender,
+        pus_router: PusTcMpscRouter,
+        stats: Arc<TmtcStatistics>,
+        event_sender: EventU32SenderMpscBounded,
+    ) -> Self {
+        // The TMTC statistics are generated periodically by default.
+        let mut stats_hk_sets =
+            HkSetRegistry::new(PUS_ROUTING_SERVICE, Duration::from_secs(1), 256);
+        stats_hk_sets
+            .add_set(create_stats_hk_set(
+                stats.clone(),
+                TMTC_STATS_SET_ID,
+                Duration::from_secs(10),
+            ))
+            .expect("adding TMTC statistics set failed");
+        stats_hk_sets
+            .handle_request(
+                &HkRequest::new(TMTC_STATS_SET_ID, HkRequestVariant::EnablePeriodic),
+                &[],
+                &tm_sender,
+            )
+            .expect("enabling TMTC statistics HK failed");
         Self {
             id: PUS_ROUTING_SERVICE.raw(),
             tm_sender,
@@ -66,15 +136,61 @@ impl<TmSender: EcssTmSender> PusTcDistributor<TmSender> {
                 PUS_ROUTING_SERVICE.apid,
             ),
             pus_router,
+            tc_authenticator: Box::new(AcceptAllTcs),
+            crc_filter: TcCrcFilter::new(TC_CRC_CHECK_POLICY).with_quarantine_handler(
+                |sender_id: ComponentId, _tc: &PusTcReader, raw_tc: &[u8]| {
+                    log::warn!(
+                        "quarantined TC with invalid CRC from {}: {:x?}",
+                        sender_id,
+                        raw_tc
+                    );
+                },
+            ),
+            stats,
+            tc_pool: None,
+            event_sender,
+            stats_hk_sets,
             stamp_helper: TimestampHelper::default(),
         }
     }
 
+    /// Set the pool of the telecommands which are passed to [Self::handle_tc_packet_in_store].
+    pub fn with_tc_pool(mut self, tc_pool: SharedStaticMemoryPool) -> Self {
+        self.tc_pool = Some(tc_pool);
+        self
+    }
+
+    /// Delete a telecommand which is not distributed from the TC pool.
+    fn discard_tc(&self, addr_opt: Option<PoolAddr>) {
+        if let (Some(tc_pool), Some(addr)) = (&self.tc_pool, addr_opt) {
+            let mut tc_pool = tc_pool.write().expect("locking TC pool failed");
+            if let Err(e) = tc_pool.delete(addr) {
+                log::warn!("deleting rejected TC from the pool failed: {}", e);
+            }
+        }
+    }
+
+    /// Send the TMTC statistics HK report if it is due.
+    pub fn send_due_stats_hk(&mut self) {
+        self.stamp_helper.update_from_now();
+        if let Err(e) = self.stats_hk_sets.send_due_sets(
+            Instant::now(),
+            self.stamp_helper.stamp(),
+            &self.tm_sender,
+        ) {
+            log::error!("sending TMTC statistics HK failed: {}", e);
+        }
+    }
+
     pub fn handle_tc_packet_vec(
         &mut self,
         packet_as_vec: PacketAsVec,
     ) -> Result<HandlingStatus, GenericSendError> {
-        self.handle_tc_generic(packet_as_vec.sender_id, None, &packet_as_vec.packet)
+        let result = self.handle_tc_generic(packet_as_vec.sender_id, None, &packet_as_vec.packet);
+        if result.is_err() {
+            self.stats.tc_rejected(TcRejectionReason::ForwardingFailed);
+        }
+        result
     }
 
     pub fn handle_tc_packet_in_store(
@@ -82,11 +198,15 @@ impl<TmSender: EcssTmSender> PusTcDistributor<TmSender> {
         packet_in_pool: PacketInPool,
         pus_tc_copy: &[u8],
     ) -> Result<HandlingStatus, GenericSendError> {
-        self.handle_tc_generic(
+        let result = self.handle_tc_generic(
             packet_in_pool.sender_id,
             Some(packet_in_pool.store_addr),
             pus_tc_copy,
-        )
+        );
+        if result.is_err() {
+            self.stats.tc_rejected(TcRejectionReason::ForwardingFailed);
+        }
+        result
     }
 
     pub fn handle_tc_generic(
@@ -95,24 +215,60 @@ impl<TmSender: EcssTmSender> PusTcDistributor<TmSender> {
         addr_opt: Option<PoolAddr>,
         raw_tc: &[u8],
     ) -> Result<HandlingStatus, GenericSendError> {
-        let pus_tc_result = PusTcReader::new(raw_tc);
-        if pus_tc_result.is_err() {
-            log::warn!(
-                "error creating PUS TC from raw data received from {}: {}",
-                sender_id,
-                pus_tc_result.unwrap_err()
-            );
-            log::warn!("raw data: {:x?}", raw_tc);
-            // TODO: Shouldn't this be an error?
-            return Ok(HandlingStatus::HandledOne);
-        }
-        let pus_tc = pus_tc_result.unwrap().0;
+        let (pus_tc, tc_len) = match self.crc_filter.check(sender_id, raw_tc) {
+            TcCheckResult::Valid(pus_tc, tc_len) => (pus_tc, tc_len),
+            TcCheckResult::Quarantined {
+                apid,
+                num_crc_failures,
+            } => {
+                self.stats.tc_rejected(TcRejectionReason::InvalidFormat);
+                if let Err(e) =
+                    report_crc_failure(self.id, apid, num_crc_failures, &self.event_sender)
+                {
+                    log::warn!("reporting TC CRC failure failed: {}", e);
+                }
+                self.discard_tc(addr_opt);
+                return Ok(HandlingStatus::HandledOne);
+            }
+            TcCheckResult::Rejected(e) => {
+                log::warn!(
+                    "error creating PUS TC from raw data received from {}: {}",
+                    sender_id,
+                    e
+                );
+                log::warn!("raw data: {:x?}", raw_tc);
+                self.stats.tc_rejected(TcRejectionReason::InvalidFormat);
+                self.discard_tc(addr_opt);
+                // TODO: Shouldn't this be an error?
+                return Ok(HandlingStatus::HandledOne);
+            }
+        };
+        self.stats.tc_received(tc_len);
         let init_token = self.verif_reporter.add_tc(&pus_tc);
         self.stamp_helper.update_from_now();
-        let accepted_token = self
-            .verif_reporter
-            .acceptance_success(&self.tm_sender, init_token, self.stamp_helper.stamp())
-            .expect("Acceptance success failure");
+        let accepted_token = match authenticate_and_accept_tc(
+            self.tc_authenticator.as_mut(),
+            &pus_tc,
+            init_token,
+            &self.verif_reporter,
+            &self.tm_sender,
+            self.stamp_helper.stamp(),
+        )
+        .expect("Acceptance verification failure")
+        {
+            Some(accepted_token) => accepted_token,
+            None => {
+                log::warn!(
+                    "TC from {} with APID {:#x} was rejected by the TC authenticator",
+                    sender_id,
+                    pus_tc.apid()
+                );
+                self.stats
+                    .tc_rejected(TcRejectionReason::AuthenticationFailed);
+                self.discard_tc(addr_opt);
+                return Ok(HandlingStatus::HandledOne);
+            }
+        };
         let service = PusServiceId::try_from(pus_tc.service());
         let tc_in_memory: TcInMemory = if let Some(store_addr) = addr_opt {
             PacketInPool::new(sender_id, store_addr).into()
@@ -141,6 +297,14 @@ impl<TmSender: EcssTmSender> PusTcDistributor<TmSender> {
                         token: Some(accepted_token.into()),
                     })?
                 }
+                PusServiceId::DeviceAccess => {
+                    self.pus_router
+                        .device_access_tc_sender
+                        .send(EcssTcAndToken {
+                            tc_in_memory,
+                            token: Some(accepted_token.into()),
+                        })?
+                }
                 _ => {
                     let result = self.verif_reporter.start_failure(
                         &self.tm_sender,
@@ -15,6 +15,7 @@ use satrs::request::GenericMessage;
 use satrs::{
     mode::{ModeAndSubmode, ModeReply, ModeRequest},
     pus::{
+        app_data::{AppDataReader, NotEnoughAppData},
         mode::Subservice,
         verification::{
             self, FailParams, TcStateAccepted, TcStateStarted, VerificationReportingProvider,
@@ -23,14 +24,13 @@ use satrs::{
         ActivePusRequestStd, ActiveRequestProvider, EcssTmSender, EcssTmtcError,
         GenericConversionError, PusReplyHandler, PusTcToRequestConverter, PusTmVariant,
     },
-    request::UniqueApidTargetId,
     spacepackets::{
         ecss::{
             tc::PusTcReader,
             tm::{PusTmCreator, PusTmSecondaryHeader},
             PusPacket,
         },
-        SpHeader,
+        CcsdsPacket, SpHeader,
     },
     ComponentId,
 };
@@ -147,24 +147,24 @@ impl PusTcToRequestConverter<ActivePusRequestStd, ModeRequest> for ModeRequestCo
         time_stamp: &[u8],
     ) -> Result<(ActivePusRequestStd, ModeRequest), Self::Error> {
         let subservice = tc.subservice();
-        let user_data = tc.user_data();
-        let not_enough_app_data = |expected: usize| {
+        let not_enough_app_data = |e: NotEnoughAppData| {
             verif_reporter
                 .start_failure(
                     tm_sender,
                     token,
-                    FailParams::new_no_fail_data(time_stamp, &tmtc_err::NOT_ENOUGH_APP_DATA),
+                    FailParams::new(
+                        time_stamp,
+                        &tmtc_err::NOT_ENOUGH_APP_DATA,
+                        &e.to_fail_data(),
+                    ),
                 )
                 .expect("Sending start failure failed");
-            Err(GenericConversionError::NotEnoughAppData {
-                expected,
-                found: user_data.len(),
-            })
+            GenericConversionError::from(e)
         };
-        if user_data.len() < core::mem::size_of::<u32>() {
-            return not_enough_app_data(4);
-        }
-        let target_id_and_apid = UniqueApidTargetId::from_pus_tc(tc).unwrap();
+        let mut app_data = AppDataReader::new(tc.user_data());
+        let target_id_and_apid = app_data
+            .read_target_id(tc.apid())
+            .map_err(not_enough_app_data)?;
         let active_request =
             ActivePusRequestStd::new(target_id_and_apid.into(), token, Duration::from_secs(30));
         let subservice_typed = Subservice::try_from(subservice);
@@ -185,11 +185,9 @@ impl PusTcToRequestConverter<ActivePusRequestStd, ModeRequest> for ModeRequestCo
         let subservice_typed = subservice_typed.unwrap();
         match subservice_typed {
             Subservice::TcSetMode => {
-                if user_data.len() < core::mem::size_of::<u32>() + ModeAndSubmode::RAW_LEN {
-                    return not_enough_app_data(4 + ModeAndSubmode::RAW_LEN);
-                }
-                let mode_and_submode = ModeAndSubmode::from_be_bytes(&tc.user_data()[4..])
-                    .expect("mode and submode extraction failed");
+                let mode_and_submode = app_data
+                    .read_mode_and_submode()
+                    .map_err(not_enough_app_data)?;
                 Ok((active_request, ModeRequest::SetMode(mode_and_submode)))
             }
             Subservice::TcReadMode => Ok((active_request, ModeRequest::ReadMode)),
@@ -3,48 +3,64 @@ use std::time::Duration;
 
 use crate::pus::create_verification_reporter;
 use log::info;
-use satrs::pool::{PoolProvider, StaticMemoryPool};
+use satrs::pool::{PoolError, PoolProvider, StaticMemoryPool};
 use satrs::pus::scheduler::{PusScheduler, TcInfo};
 use satrs::pus::scheduler_srv::PusSchedServiceHandler;
 use satrs::pus::verification::VerificationReporter;
 use satrs::pus::{
     DirectPusPacketHandlerResult, EcssTcAndToken, EcssTcInMemConverter,
-    EcssTcInSharedStoreConverter, EcssTcInVecConverter, EcssTmSender, MpscTcReceiver,
-    MpscTmAsVecSender, PartialPusHandlingError, PusServiceHelper,
+    EcssTcInSharedStoreConverter, EcssTcInVecConverter, EcssTmSender, EcssTmtcError,
+    MpscTcReceiver, MpscTmAsVecSender, PartialPusHandlingError, PusServiceHelper,
 };
+use satrs::queue::GenericSendError;
 use satrs::spacepackets::ecss::PusServiceId;
-use satrs::tmtc::{PacketAsVec, PacketInPool, PacketSenderWithSharedPool};
+use satrs::time::TimeSource;
+use satrs::tmtc::{
+    PacketAsVec, PacketInPoolSender, PacketSenderWithSharedPool, PriorityPacketInPoolSender,
+};
 use satrs::ComponentId;
 use satrs_example::config::components::PUS_SCHED_SERVICE;
 
 use super::{DirectPusService, HandlingStatus};
 
 pub trait TcReleaser {
-    fn release(&mut self, sender_id: ComponentId, enabled: bool, info: &TcInfo, tc: &[u8]) -> bool;
+    fn release(
+        &mut self,
+        sender_id: ComponentId,
+        enabled: bool,
+        info: &TcInfo,
+        tc: &[u8],
+    ) -> Result<(), EcssTmtcError>;
 }
 
-impl TcReleaser for PacketSenderWithSharedPool {
+impl TcReleaser for PacketSenderWithSharedPool<PriorityPacketInPoolSender> {
     fn release(
         &mut self,
         sender_id: ComponentId,
         enabled: bool,
         _info: &TcInfo,
         tc: &[u8],
-    ) -> bool {
+    ) -> Result<(), EcssTmtcError> {
         if enabled {
             let shared_pool = self.shared_pool.get_mut();
             // Transfer TC from scheduler TC pool to shared TC pool.
             let released_tc_addr = shared_pool
                 .0
                 .write()
-                .expect("locking pool failed")
-                .add(tc)
-                .expect("adding TC to shared pool failed");
-            self.sender
-                .send(PacketInPool::new(sender_id, released_tc_addr))
-                .expect("sending TC to TC source failed");
+                .map_err(|_| PoolError::LockError)?
+                .add(tc)?;
+            if let Err(e) = self.sender.send_packet(sender_id, released_tc_addr) {
+                // The TC source never receives the address, so the TC is deleted here to
+                // avoid leaking the pool slot.
+                shared_pool
+                    .0
+                    .write()
+                    .map_err(|_| PoolError::LockError)?
+                    .delete(released_tc_addr)?;
+                return Err(e.into());
+            }
         }
-        true
+        Ok(())
     }
 }
 
@@ -55,13 +71,13 @@ impl TcReleaser for mpsc::Sender<PacketAsVec> {
         enabled: bool,
         _info: &TcInfo,
         tc: &[u8],
-    ) -> bool {
+    ) -> Result<(), EcssTmtcError> {
         if enabled {
             // Send released TC to centralized TC source.
             self.send(PacketAsVec::new(sender_id, tc.to_vec()))
-                .expect("sending TC to TC source failed");
+                .map_err(|_| GenericSendError::RxDisconnected)?;
         }
-        true
+        Ok(())
     }
 }
 
@@ -139,32 +155,43 @@ impl<TmSender: EcssTmSender, TcInMemConverter: EcssTcInMemConverter>
 {
     pub fn release_tcs(&mut self) {
         let id = self.pus_11_handler.service_helper.id();
-        let releaser = |enabled: bool, info: &TcInfo, tc: &[u8]| -> bool {
+        let releaser = |enabled: bool, info: &TcInfo, tc: &[u8]| {
             self.tc_releaser.release(id, enabled, info, tc)
         };
 
+        let current_time = self
+            .pus_11_handler
+            .service_helper
+            .time_source()
+            .now()
+            .expect("retrieving current time failed");
         self.pus_11_handler
             .scheduler_mut()
-            .update_time_from_now()
-            .unwrap();
-        let released_tcs = self
+            .update_time(current_time);
+        let report = self
             .pus_11_handler
             .scheduler_mut()
-            .release_telecommands_with_buffer(
+            .release_telecommands_with_report(
                 releaser,
                 &mut self.sched_tc_pool,
                 &mut self.releaser_buf,
-            )
-            .expect("releasing TCs failed");
-        if released_tcs > 0 {
-            info!("{released_tcs} TC(s) released from scheduler");
+            );
+        if report.released > 0 {
+            info!("{} TC(s) released from scheduler", report.released);
+        }
+        for failure in &report.failures {
+            log::warn!(
+                "releasing scheduled TC {:?} failed: {:?}",
+                failure.info.request_id(),
+                failure.error
+            );
         }
     }
 }
 
 pub fn create_scheduler_service_static(
     tm_sender: PacketSenderWithSharedPool,
-    tc_releaser: PacketSenderWithSharedPool,
+    tc_releaser: PacketSenderWithSharedPool<PriorityPacketInPoolSender>,
     pus_sched_rx: mpsc::Receiver<EcssTcAndToken>,
     sched_tc_pool: StaticMemoryPool,
 ) -> SchedulingServiceWrapper<PacketSenderWithSharedPool, EcssTcInSharedStoreConverter> {
@@ -215,3 +242,38 @@ pub fn create_scheduler_service_dynamic(
         tc_releaser: Box::new(tc_source_sender),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, RwLock};
+
+    use satrs::pool::{PriorityPoolQueue, StaticPoolConfig};
+    use satrs::pus::scheduler::RequestId;
+    use satrs::spacepackets::ecss::tc::PusTcCreator;
+    use satrs::spacepackets::ecss::WritablePusPacket;
+    use satrs::spacepackets::SpHeader;
+    use satrs::tmtc::SharedPacketPool;
+
+    use super::*;
+
+    #[test]
+    fn test_failed_release_frees_pool_slot() {
+        let tc_pool = Arc::new(RwLock::new(StaticMemoryPool::new(
+            StaticPoolConfig::new_from_subpool_cfg_tuples(vec![(1, 32)], false),
+        )));
+        // The queue is full, so the release fails.
+        let queue = PriorityPoolQueue::new_shared(1, 1);
+        let sender = PriorityPacketInPoolSender::new(queue, 0);
+        sender.send_packet(0, 0).unwrap();
+        let mut releaser = PacketSenderWithSharedPool::new(sender, SharedPacketPool::new(&tc_pool));
+        let tc = PusTcCreator::new_simple(SpHeader::new_for_unseg_tc(1, 0, 0), 17, 1, &[], true);
+        let info = TcInfo::new(0, RequestId::from_tc(&tc));
+        let result = releaser.release(PUS_SCHED_SERVICE.id(), true, &info, &tc.to_vec().unwrap());
+        assert!(matches!(
+            result,
+            Err(EcssTmtcError::Send(GenericSendError::QueueFull(Some(1))))
+        ));
+        // The only slot of the pool is free again.
+        assert!(tc_pool.write().unwrap().add(&[1, 2, 3]).is_ok());
+    }
+}
@@ -1,14 +1,22 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use crate::events::EventHandler;
 use crate::pus::mode::ModeServiceWrapper;
 use derive_new::new;
 use satrs::{
+    event_man::EventU32SenderMpscBounded,
+    executable::{Executable, ExecutionType, OpResult},
     pus::{EcssTcInMemConverter, EcssTmSender},
+    shutdown::ShutdownSignal,
     spacepackets::time::{cds, TimeWriter},
 };
+use satrs_example::config::tasks::SHUTDOWN_DRAIN_TIMEOUT_MS;
 
 use super::{
-    action::ActionServiceWrapper, event::EventServiceWrapper, hk::HkServiceWrapper,
-    scheduler::SchedulingServiceWrapper, test::TestCustomServiceWrapper, DirectPusService,
-    HandlingStatus, TargetedPusService,
+    action::ActionServiceWrapper, device_access::DeviceAccessServiceWrapper,
+    event::EventServiceWrapper, hk::HkServiceWrapper, scheduler::SchedulingServiceWrapper,
+    test::TestCustomServiceWrapper, DirectPusService, HandlingStatus, TargetedPusService,
 };
 
 // TODO: For better extensibility, we could create 2 vectors: One for direct PUS services and one
@@ -21,6 +29,7 @@ pub struct PusStack<TmSender: EcssTmSender, TcInMemConverter: EcssTcInMemConvert
     action_srv_wrapper: ActionServiceWrapper<TmSender, TcInMemConverter>,
     schedule_srv: SchedulingServiceWrapper<TmSender, TcInMemConverter>,
     mode_srv: ModeServiceWrapper<TmSender, TcInMemConverter>,
+    device_access_srv: DeviceAccessServiceWrapper<TmSender, TcInMemConverter>,
 }
 
 impl<TmSender: EcssTmSender, TcInMemConverter: EcssTcInMemConverter>
@@ -52,11 +61,17 @@ impl<TmSender: EcssTmSender, TcInMemConverter: EcssTcInMemConverter>
                 &mut nothing_to_do,
             );
             Self::targeted_service_checker(&mut self.mode_srv, &timestamp, &mut nothing_to_do);
+            Self::targeted_service_checker(
+                &mut self.device_access_srv,
+                &timestamp,
+                &mut nothing_to_do,
+            );
             if nothing_to_do {
                 // Timeout checking is only done once.
                 self.action_srv_wrapper.check_for_request_timeouts();
                 self.hk_srv_wrapper.check_for_request_timeouts();
                 self.mode_srv.check_for_request_timeouts();
+                self.device_access_srv.check_for_request_timeouts();
                 break;
             }
             // Safety mechanism to avoid infinite loops.
@@ -93,3 +108,56 @@ impl<TmSender: EcssTmSender, TcInMemConverter: EcssTcInMemConverter>
         }
     }
 }
+
+/// Task of the PUS thread which handles the events and the PUS stack.
+///
+/// The task checks its own shutdown signal instead of being stopped by the scheduler, because it
+/// handles the queued telecommands and routes the remaining events before it terminates. It should
+/// therefore be run by a scheduler which is not stopped with the same signal.
+#[derive(new)]
+pub struct PusTask<
+    EventTmSender: EcssTmSender,
+    TmSender: EcssTmSender,
+    TcInMemConverter: EcssTcInMemConverter,
+> {
+    event_handler: EventHandler<EventTmSender>,
+    pus_stack: PusStack<TmSender, TcInMemConverter>,
+    shutdown: ShutdownSignal,
+    shutdown_event_sender: EventU32SenderMpscBounded,
+}
+
+impl<
+        EventTmSender: EcssTmSender,
+        TmSender: EcssTmSender,
+        TcInMemConverter: EcssTcInMemConverter,
+    > Executable for PusTask<EventTmSender, TmSender, TcInMemConverter>
+{
+    type Error = Infallible;
+
+    fn exec_type(&self) -> ExecutionType {
+        ExecutionType::Infinite
+    }
+
+    fn task_name(&self) -> &'static str {
+        "PUS stack"
+    }
+
+    fn periodic_op(&mut self, _op_code: i32) -> Result<OpResult, Self::Error> {
+        if self.shutdown.shutdown_requested() {
+            // Handle the queued telecommands first because they might generate events.
+            self.pus_stack.periodic_operation();
+            let outcome = self.event_handler.shutdown(
+                &self.shutdown_event_sender,
+                Duration::from_millis(SHUTDOWN_DRAIN_TIMEOUT_MS),
+            );
+            log::info!(
+                "PUS stack stopped after routing {} events",
+                outcome.num_drained
+            );
+            return Ok(OpResult::TerminationRequested);
+        }
+        self.event_handler.periodic_operation();
+        self.pus_stack.periodic_operation();
+        Ok(OpResult::Ok)
+    }
+}
@@ -94,8 +94,17 @@ impl<TmSender: EcssTmSender, TcInMemConverter: EcssTcInMemConverter> DirectPusSe
         match res.unwrap() {
             DirectPusPacketHandlerResult::Handled(handling_status) => {
                 if handling_status == HandlingStatus::HandledOne {
-                    info!("Received PUS ping command TC[17,1]");
-                    info!("Sent ping reply PUS TM[17,2]");
+                    let (tc, _) = PusTcReader::new(
+                        self.handler
+                            .service_helper
+                            .tc_in_mem_converter
+                            .tc_slice_raw(),
+                    )
+                    .unwrap();
+                    // The reply subservice directly follows the request subservice.
+                    let subservice = tc.subservice();
+                    info!("Received PUS ping command TC[17,{subservice}]");
+                    info!("Sent ping reply PUS TM[17,{}]", subservice + 1);
                 }
                 return handling_status;
             }
@@ -1,15 +1,15 @@
-use std::collections::HashMap;
 use std::sync::mpsc;
 
 use log::warn;
 use satrs::action::ActionRequest;
+use satrs::addressing::AddressRegistry;
+use satrs::device::RawDeviceCommand;
 use satrs::hk::HkRequest;
 use satrs::mode::ModeRequest;
 use satrs::pus::verification::{
     FailParams, TcStateAccepted, VerificationReportingProvider, VerificationToken,
 };
 use satrs::pus::{ActiveRequestProvider, EcssTmSender, GenericRoutingError, PusRequestRouter};
-use satrs::queue::GenericSendError;
 use satrs::request::{GenericMessage, MessageMetadata, UniqueApidTargetId};
 use satrs::spacepackets::ecss::tc::PusTcReader;
 use satrs::spacepackets::ecss::PusPacket;
@@ -22,15 +22,15 @@ use satrs_example::config::tmtc_err;
 pub enum CompositeRequest {
     Hk(HkRequest),
     Action(ActionRequest),
+    RawCommand(RawDeviceCommand),
 }
 
 #[derive(Clone)]
 pub struct GenericRequestRouter {
     pub id: ComponentId,
     // All messages which do not have a dedicated queue.
-    pub composite_router_map:
-        HashMap<ComponentId, mpsc::SyncSender<GenericMessage<CompositeRequest>>>,
-    pub mode_router_map: HashMap<ComponentId, mpsc::SyncSender<GenericMessage<ModeRequest>>>,
+    pub composite_router_map: AddressRegistry<mpsc::SyncSender<GenericMessage<CompositeRequest>>>,
+    pub mode_router_map: AddressRegistry<mpsc::SyncSender<GenericMessage<ModeRequest>>>,
 }
 
 impl Default for GenericRequestRouter {
@@ -98,16 +98,8 @@ impl PusRequestRouter<HkRequest> for GenericRequestRouter {
         target_id: ComponentId,
         hk_request: HkRequest,
     ) -> Result<(), Self::Error> {
-        if let Some(sender) = self.composite_router_map.get(&target_id) {
-            sender
-                .send(GenericMessage::new(
-                    requestor_info,
-                    CompositeRequest::Hk(hk_request),
-                ))
-                .map_err(|_| GenericRoutingError::Send(GenericSendError::RxDisconnected))?;
-            return Ok(());
-        }
-        Err(GenericRoutingError::UnknownTargetId(target_id))
+        self.composite_router_map
+            .route(requestor_info, target_id, CompositeRequest::Hk(hk_request))
     }
 }
 
@@ -120,16 +112,28 @@ impl PusRequestRouter<ActionRequest> for GenericRequestRouter {
         target_id: ComponentId,
         action_request: ActionRequest,
     ) -> Result<(), Self::Error> {
-        if let Some(sender) = self.composite_router_map.get(&target_id) {
-            sender
-                .send(GenericMessage::new(
-                    requestor_info,
-                    CompositeRequest::Action(action_request),
-                ))
-                .map_err(|_| GenericRoutingError::Send(GenericSendError::RxDisconnected))?;
-            return Ok(());
-        }
-        Err(GenericRoutingError::UnknownTargetId(target_id))
+        self.composite_router_map.route(
+            requestor_info,
+            target_id,
+            CompositeRequest::Action(action_request),
+        )
+    }
+}
+
+impl PusRequestRouter<RawDeviceCommand> for GenericRequestRouter {
+    type Error = GenericRoutingError;
+
+    fn route(
+        &self,
+        requestor_info: MessageMetadata,
+        target_id: ComponentId,
+        raw_command: RawDeviceCommand,
+    ) -> Result<(), Self::Error> {
+        self.composite_router_map.route(
+            requestor_info,
+            target_id,
+            CompositeRequest::RawCommand(raw_command),
+        )
     }
 }
 
@@ -142,12 +146,7 @@ impl PusRequestRouter<ModeRequest> for GenericRequestRouter {
         target_id: ComponentId,
         request: ModeRequest,
     ) -> Result<(), Self::Error> {
-        if let Some(sender) = self.mode_router_map.get(&target_id) {
-            sender
-                .send(GenericMessage::new(requestor_info, request))
-                .map_err(|_| GenericRoutingError::Send(GenericSendError::RxDisconnected))?;
-            return Ok(());
-        }
-        Err(GenericRoutingError::UnknownTargetId(target_id))
+        self.mode_router_map
+            .route(requestor_info, target_id, request)
     }
 }
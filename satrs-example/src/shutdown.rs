@@ -0,0 +1,124 @@
+//! Staged shutdown of the OBSW threads.
+//!
+//! A shutdown is triggered with SIGINT (Ctrl-C) or SIGTERM. The threads are then stopped in
+//! stages, and each stage is only started after all threads of the previous stage were joined:
+//!
+//!  1. [ShutdownStages::tc_input]: Stop the reception and routing of telecommands.
+//!  2. [ShutdownStages::pus]: Handle the queued telecommands, route the remaining events and stop
+//!     the device handlers.
+//!  3. [ShutdownStages::tm_sink]: Forward all queued TM to the TM servers.
+//!  4. [ShutdownStages::tcp_server]: Stop the TCP server after it sent all TM to its clients.
+//!
+//! Periodic tasks are driven by schedulers like the
+//! [FixedRateScheduler](satrs::executable::FixedRateScheduler), which are created with the
+//! [ShutdownStage::signal] of their stage and run with [ShutdownStage::spawn_scheduler].
+use std::fmt::Debug;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use log::{info, warn};
+use satrs::executable::OpResult;
+use satrs::shutdown::ShutdownSignal;
+
+/// Polling period of the main thread while it waits for the shutdown trigger.
+const TRIGGER_POLL_PERIOD_MS: u64 = 100;
+
+/// Signal and threads of one shutdown stage.
+pub struct ShutdownStage {
+    name: &'static str,
+    signal: ShutdownSignal,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ShutdownStage {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            signal: ShutdownSignal::new(),
+            threads: Vec::new(),
+        }
+    }
+
+    /// Signal which the threads of this stage poll.
+    pub fn signal(&self) -> ShutdownSignal {
+        self.signal.clone()
+    }
+
+    pub fn add_thread(&mut self, thread: JoinHandle<()>) {
+        self.threads.push(thread);
+    }
+
+    /// Run a scheduler in a new thread of this stage. The passed closure usually calls the run
+    /// function of a [FixedRateScheduler](satrs::executable::FixedRateScheduler) or a
+    /// [PollingSequenceExecutor](satrs::executable::PollingSequenceExecutor). A task error stops
+    /// the scheduler and is logged.
+    pub fn spawn_scheduler<E: Debug>(
+        &mut self,
+        thread_name: &str,
+        run: impl FnOnce() -> Result<OpResult, E> + Send + 'static,
+    ) {
+        let thread = thread::Builder::new()
+            .name(thread_name.to_string())
+            .spawn(move || {
+                if let Err(e) = run() {
+                    warn!("scheduler stopped because of a task error: {e:?}");
+                }
+            })
+            .expect("spawning the scheduler thread failed");
+        self.add_thread(thread);
+    }
+
+    fn stop(self) {
+        info!("Shutdown: stopping {}", self.name);
+        self.signal.request_shutdown();
+        for thread in self.threads {
+            let thread_name = thread.thread().name().unwrap_or("unnamed").to_string();
+            if thread.join().is_err() {
+                warn!("Shutdown: thread {thread_name} panicked");
+            }
+        }
+    }
+}
+
+pub struct ShutdownStages {
+    pub tc_input: ShutdownStage,
+    pub pus: ShutdownStage,
+    pub tm_sink: ShutdownStage,
+    pub tcp_server: ShutdownStage,
+}
+
+impl Default for ShutdownStages {
+    fn default() -> Self {
+        Self {
+            tc_input: ShutdownStage::new("TC input"),
+            pus: ShutdownStage::new("PUS stack and device handlers"),
+            tm_sink: ShutdownStage::new("TM sink"),
+            tcp_server: ShutdownStage::new("TCP server"),
+        }
+    }
+}
+
+impl ShutdownStages {
+    /// Block until the trigger is set and stop all stages in order.
+    pub fn run(self, trigger: &ShutdownSignal) {
+        while !trigger.shutdown_requested() {
+            thread::sleep(Duration::from_millis(TRIGGER_POLL_PERIOD_MS));
+        }
+        self.tc_input.stop();
+        self.pus.stop();
+        self.tm_sink.stop();
+        self.tcp_server.stop();
+        info!("Shutdown complete");
+    }
+}
+
+/// Request a shutdown with the given trigger on SIGINT or SIGTERM. A second signal terminates
+/// the process immediately, for example if a thread does not stop.
+pub fn install_signal_handler(trigger: ShutdownSignal) -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(move || {
+        if trigger.shutdown_requested() {
+            std::process::exit(1);
+        }
+        trigger.request_shutdown();
+    })
+}
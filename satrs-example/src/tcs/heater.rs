@@ -0,0 +1,705 @@
+//! Reference device handler for a heater which is enabled with a GPIO and driven by a PWM output.
+//!
+//! The handler maps its modes to [HeaterCommand]s for the [HeaterOutput]:
+//!
+//! - OFF: The heater is disabled.
+//! - ON: The heater is enabled with a duty cycle of 0 %, so it does not heat yet.
+//! - NORMAL: The heater is enabled and driven with the duty cycle in percent which is passed as
+//!   the submode.
+//! - RAW: The heater is enabled with a duty cycle of 0 % and raw [HeaterCommand]s are executed as
+//!   they are received.
+//!
+//! The heater does not have a dedicated power switch, so all mode transitions complete
+//! immediately. The handler can be used as a template for simple actuators which are commanded
+//! directly by the on-board computer.
+use derive_new::new;
+use satrs::device::{RawDeviceCommand, RawDeviceReply, RawDeviceReplyVariant};
+use satrs::hal::gpio::{OutputPin, PinMock, PinState};
+use satrs::hal::pwm::{PwmChannel, PwmMock};
+use satrs::hk::{HkRequest, HkSetDefinition, HkSetRegistry};
+use satrs::mode::{ModeAndSubmode, ModeError, ModeProvider, ModeReply, ModeRequestHandler};
+use satrs::pus::EcssTmSender;
+use satrs::queue::{GenericSendError, GenericTargetedMessagingError};
+use satrs::request::{GenericMessage, MessageMetadata, UniqueApidTargetId};
+use satrs::res_code::ResultU16;
+use satrs::spacepackets::ByteConversionError;
+use satrs_example::config::components::{NO_SENDER, PUS_MODE_SERVICE};
+use satrs_example::config::{dev_err, mode_err};
+use satrs_example::{DeviceMode, TimestampHelper};
+use satrs_mib::tm_def::{params_len, ParamDef, ParamType};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::acs::mgm::MpscModeLeafInterface;
+use crate::pus::hk::{HkReply, HkReplyVariant};
+use crate::requests::CompositeRequest;
+
+pub const MAX_DUTY_CYCLE_PERCENT: u8 = 100;
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum SetId {
+    HeaterStatus = 0,
+}
+
+/// Layout of the heater status set, which is also exported as part of the TM definitions.
+pub const HEATER_STATUS_PARAMS: &[ParamDef] = &[
+    ParamDef::new("enabled", ParamType::Bool),
+    ParamDef::new("duty_cycle", ParamType::U8).with_unit("%"),
+];
+
+/// Output stage of the heater, usually a GPIO and a PWM peripheral.
+pub trait HeaterOutput {
+    type Error: Debug;
+    /// Drive the enable line of the heater.
+    fn set_enabled(&mut self, enabled: bool) -> Result<(), Self::Error>;
+    /// Set the duty cycle of the PWM output in percent.
+    fn set_duty_cycle(&mut self, duty_cycle_percent: u8) -> Result<(), Self::Error>;
+}
+
+/// Period of the PWM output of the heater.
+pub const HEATER_PWM_PERIOD: Duration = Duration::from_millis(10);
+
+#[derive(Debug)]
+pub enum GpioPwmHeaterError<PinError: Debug, PwmError: Debug> {
+    Pin(PinError),
+    Pwm(PwmError),
+}
+
+/// Heater output which drives the enable line with an [OutputPin] and the heating power with a
+/// [PwmChannel]. On Linux targets, the pin and the channel can be provided by the
+/// [satrs::hal::gpio::SysfsGpioChip] and the [satrs::hal::pwm::SysfsPwmChip].
+impl<Pin: OutputPin, Pwm: PwmChannel> HeaterOutput for (Pin, Pwm) {
+    type Error = GpioPwmHeaterError<Pin::Error, Pwm::Error>;
+
+    fn set_enabled(&mut self, enabled: bool) -> Result<(), Self::Error> {
+        self.0
+            .set_state(PinState::from(enabled))
+            .map_err(GpioPwmHeaterError::Pin)?;
+        self.1.set_enabled(enabled).map_err(GpioPwmHeaterError::Pwm)
+    }
+
+    fn set_duty_cycle(&mut self, duty_cycle_percent: u8) -> Result<(), Self::Error> {
+        self.1
+            .set_duty_cycle_percent(duty_cycle_percent)
+            .map_err(GpioPwmHeaterError::Pwm)
+    }
+}
+
+/// Heater output which is used when no heater hardware is available. The enable line and the
+/// PWM output are simulated with the pin and PWM mocks of the HAL.
+pub fn simulated_heater_output() -> (PinMock, PwmMock) {
+    (PinMock::default(), PwmMock::new(HEATER_PWM_PERIOD))
+}
+
+/// Commands which are built by the heater handler.
+///
+/// The raw format, which is also used for raw commanding, consists of the command ID followed by
+/// the duty cycle in percent for [HeaterCommand::SetDutyCycle].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HeaterCommand {
+    Disable,
+    Enable,
+    SetDutyCycle(u8),
+}
+
+impl HeaterCommand {
+    pub const DISABLE_ID: u8 = 0;
+    pub const ENABLE_ID: u8 = 1;
+    pub const SET_DUTY_CYCLE_ID: u8 = 2;
+
+    /// Parse a raw command. Returns [None] for unknown commands and invalid duty cycles.
+    pub fn from_raw(raw: &[u8]) -> Option<Self> {
+        match raw {
+            [Self::DISABLE_ID] => Some(Self::Disable),
+            [Self::ENABLE_ID] => Some(Self::Enable),
+            [Self::SET_DUTY_CYCLE_ID, duty_cycle] if *duty_cycle <= MAX_DUTY_CYCLE_PERCENT => {
+                Some(Self::SetDutyCycle(*duty_cycle))
+            }
+            _ => None,
+        }
+    }
+
+    /// Apply the command to the given heater output.
+    pub fn execute<Output: HeaterOutput>(&self, output: &mut Output) -> Result<(), Output::Error> {
+        match self {
+            HeaterCommand::Disable => output.set_enabled(false),
+            HeaterCommand::Enable => output.set_enabled(true),
+            HeaterCommand::SetDutyCycle(duty_cycle) => output.set_duty_cycle(*duty_cycle),
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaterData {
+    pub enabled: bool,
+    pub duty_cycle_percent: u8,
+}
+
+/// Create the HK set registry of a heater handler. The status set is collected with an interval
+/// of 1 second by default once periodic generation was enabled.
+pub fn create_heater_hk_sets(
+    id: UniqueApidTargetId,
+    shared_heater_set: Arc<Mutex<HeaterData>>,
+) -> HkSetRegistry {
+    let mut hk_sets = HkSetRegistry::new(id, Duration::from_secs(1), 16);
+    hk_sets
+        .add_set(
+            HkSetDefinition::new(SetId::HeaterStatus as u32, Duration::from_secs(1)).with_provider(
+                move |hk_buf| {
+                    const SET_LEN: usize = params_len(HEATER_STATUS_PARAMS);
+                    if hk_buf.len() < SET_LEN {
+                        return Err(ByteConversionError::ToSliceTooSmall {
+                            found: hk_buf.len(),
+                            expected: SET_LEN,
+                        });
+                    }
+                    let heater_snapshot = *shared_heater_set.lock().unwrap();
+                    hk_buf[0] = heater_snapshot.enabled as u8;
+                    hk_buf[1] = heater_snapshot.duty_cycle_percent;
+                    Ok(SET_LEN)
+                },
+            ),
+        )
+        .expect("adding heater status set failed");
+    hk_sets
+}
+
+/// Example heater device handler. See the [module documentation][self] for the supported modes.
+#[derive(new)]
+#[allow(clippy::too_many_arguments)]
+pub struct HeaterHandler<Output: HeaterOutput, TmSender: EcssTmSender> {
+    dev_str: &'static str,
+    mode_interface: MpscModeLeafInterface,
+    composite_request_rx: mpsc::Receiver<GenericMessage<CompositeRequest>>,
+    hk_reply_tx: mpsc::Sender<GenericMessage<HkReply>>,
+    raw_reply_tx: mpsc::Sender<GenericMessage<RawDeviceReply>>,
+    tm_sender: TmSender,
+    pub output: Output,
+    shared_heater_set: Arc<Mutex<HeaterData>>,
+    hk_sets: HkSetRegistry,
+    #[new(value = "ModeAndSubmode::new(DeviceMode::Off as u32, 0)")]
+    mode_and_submode: ModeAndSubmode,
+    #[new(default)]
+    stamp_helper: TimestampHelper,
+}
+
+impl<Output: HeaterOutput, TmSender: EcssTmSender> HeaterHandler<Output, TmSender> {
+    pub fn heater_set(&self) -> HeaterData {
+        *self.shared_heater_set.lock().unwrap()
+    }
+
+    pub fn periodic_operation(&mut self) {
+        self.stamp_helper.update_from_now();
+        self.handle_composite_requests();
+        self.handle_mode_requests();
+        if let Err(e) =
+            self.hk_sets
+                .send_due_sets(Instant::now(), self.stamp_helper.stamp(), &self.tm_sender)
+        {
+            log::error!("{}: sending periodic HK failed: {}", self.dev_str, e);
+        }
+    }
+
+    pub fn handle_composite_requests(&mut self) {
+        loop {
+            match self.composite_request_rx.try_recv() {
+                Ok(ref msg) => match &msg.message {
+                    CompositeRequest::Hk(hk_request) => {
+                        self.handle_hk_request(&msg.requestor_info, hk_request)
+                    }
+                    // The heater is commanded with its modes and does not have actions.
+                    CompositeRequest::Action(_action_req) => {}
+                    CompositeRequest::RawCommand(raw_cmd) => {
+                        self.handle_raw_command(&msg.requestor_info, raw_cmd)
+                    }
+                },
+                Err(e) => {
+                    if e != mpsc::TryRecvError::Empty {
+                        log::warn!(
+                            "{}: failed to receive composite request: {:?}",
+                            self.dev_str,
+                            e
+                        );
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn handle_hk_request(&mut self, requestor_info: &MessageMetadata, hk_request: &HkRequest) {
+        match self
+            .hk_sets
+            .handle_request(hk_request, self.stamp_helper.stamp(), &self.tm_sender)
+        {
+            Ok(()) => {
+                self.hk_reply_tx
+                    .send(GenericMessage::new(
+                        *requestor_info,
+                        HkReply::new(hk_request.unique_id, HkReplyVariant::Ack),
+                    ))
+                    .expect("failed to send HK reply");
+            }
+            Err(e) => {
+                log::error!("{}: HK request handling failed: {}", self.dev_str, e);
+            }
+        }
+    }
+
+    /// Raw [HeaterCommand]s are only executed in RAW mode. The heater state after the command is
+    /// sent back as the raw reply data, followed by a completion reply.
+    pub fn handle_raw_command(
+        &mut self,
+        requestor_info: &MessageMetadata,
+        raw_cmd: &RawDeviceCommand,
+    ) {
+        if self.mode() != DeviceMode::Raw as u32 {
+            self.send_raw_reply(
+                requestor_info,
+                RawDeviceReplyVariant::Failed(mode_err::WRONG_MODE),
+            );
+            return;
+        }
+        let command = match HeaterCommand::from_raw(&raw_cmd.data) {
+            Some(command) => command,
+            None => {
+                self.send_raw_reply(
+                    requestor_info,
+                    RawDeviceReplyVariant::Failed(dev_err::INVALID_RAW_COMMAND),
+                );
+                return;
+            }
+        };
+        if self.execute_commands(&[command]).is_err() {
+            self.send_raw_reply(
+                requestor_info,
+                RawDeviceReplyVariant::Failed(dev_err::RAW_TRANSFER_FAILED),
+            );
+            return;
+        }
+        let heater_set = self.heater_set();
+        self.send_raw_reply(
+            requestor_info,
+            RawDeviceReplyVariant::Data(vec![
+                heater_set.enabled as u8,
+                heater_set.duty_cycle_percent,
+            ]),
+        );
+        self.send_raw_reply(requestor_info, RawDeviceReplyVariant::Completed);
+    }
+
+    fn send_raw_reply(&self, requestor_info: &MessageMetadata, variant: RawDeviceReplyVariant) {
+        self.raw_reply_tx
+            .send(GenericMessage::new(
+                *requestor_info,
+                RawDeviceReply::new(variant),
+            ))
+            .expect("failed to send raw reply");
+    }
+
+    pub fn handle_mode_requests(&mut self) {
+        loop {
+            match self.mode_interface.request_rx.try_recv() {
+                Ok(msg) => {
+                    let result = self.handle_mode_request(msg);
+                    if result.is_err() {
+                        log::warn!(
+                            "{}: mode request failed with error {:?}",
+                            self.dev_str,
+                            result.err().unwrap()
+                        );
+                    }
+                }
+                Err(e) => {
+                    if e != mpsc::TryRecvError::Empty {
+                        log::warn!("{}: failed to receive mode request: {:?}", self.dev_str, e);
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Execute the given commands in order and update the heater set after each command.
+    fn execute_commands(&mut self, commands: &[HeaterCommand]) -> Result<(), Output::Error> {
+        for command in commands {
+            if let Err(e) = command.execute(&mut self.output) {
+                log::error!(
+                    "{}: executing heater command {:?} failed: {:?}",
+                    self.dev_str,
+                    command,
+                    e
+                );
+                return Err(e);
+            }
+            let mut heater_set = self.shared_heater_set.lock().unwrap();
+            match command {
+                HeaterCommand::Disable => heater_set.enabled = false,
+                HeaterCommand::Enable => heater_set.enabled = true,
+                HeaterCommand::SetDutyCycle(duty_cycle) => {
+                    heater_set.duty_cycle_percent = *duty_cycle
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Commands which are required to reach the given mode. Returns the reason as an error if the
+    /// mode can not be reached.
+    fn commands_for_mode(
+        mode_and_submode: ModeAndSubmode,
+    ) -> Result<[HeaterCommand; 2], ResultU16> {
+        let mode = mode_and_submode.mode();
+        if mode == DeviceMode::Off as u32 {
+            Ok([HeaterCommand::SetDutyCycle(0), HeaterCommand::Disable])
+        } else if mode == DeviceMode::On as u32 || mode == DeviceMode::Raw as u32 {
+            Ok([HeaterCommand::Enable, HeaterCommand::SetDutyCycle(0)])
+        } else if mode == DeviceMode::Normal as u32 {
+            if mode_and_submode.submode() > MAX_DUTY_CYCLE_PERCENT as u16 {
+                return Err(mode_err::INVALID_SUBMODE);
+            }
+            Ok([
+                HeaterCommand::Enable,
+                HeaterCommand::SetDutyCycle(mode_and_submode.submode() as u8),
+            ])
+        } else {
+            Err(mode_err::WRONG_MODE)
+        }
+    }
+}
+
+impl<Output: HeaterOutput, TmSender: EcssTmSender> ModeProvider
+    for HeaterHandler<Output, TmSender>
+{
+    fn mode_and_submode(&self) -> ModeAndSubmode {
+        self.mode_and_submode
+    }
+}
+
+impl<Output: HeaterOutput, TmSender: EcssTmSender> ModeRequestHandler
+    for HeaterHandler<Output, TmSender>
+{
+    type Error = ModeError;
+
+    fn start_transition(
+        &mut self,
+        requestor: MessageMetadata,
+        mode_and_submode: ModeAndSubmode,
+    ) -> Result<(), ModeError> {
+        log::info!(
+            "{}: transitioning to mode {:?}",
+            self.dev_str,
+            mode_and_submode
+        );
+        let commands = match Self::commands_for_mode(mode_and_submode) {
+            Ok(commands) => commands,
+            Err(result) => {
+                return self.send_mode_reply(requestor, ModeReply::CantReachMode(result));
+            }
+        };
+        if self.execute_commands(&commands).is_err() {
+            return self.send_mode_reply(
+                requestor,
+                ModeReply::CantReachMode(dev_err::DEVICE_COMMUNICATION_FAILED),
+            );
+        }
+        self.mode_and_submode = mode_and_submode;
+        self.handle_mode_reached(Some(requestor))
+    }
+
+    fn announce_mode(&self, _requestor_info: Option<MessageMetadata>, _recursive: bool) {
+        log::info!(
+            "{} announcing mode: {:?}",
+            self.dev_str,
+            self.mode_and_submode()
+        );
+    }
+
+    fn handle_mode_reached(
+        &mut self,
+        requestor: Option<MessageMetadata>,
+    ) -> Result<(), Self::Error> {
+        self.announce_mode(requestor, false);
+        if let Some(requestor) = requestor {
+            if requestor.sender_id() == NO_SENDER {
+                return Ok(());
+            }
+            if requestor.sender_id() != PUS_MODE_SERVICE.id() {
+                log::warn!(
+                    "can not send back mode reply to sender {:x}",
+                    requestor.sender_id()
+                );
+            } else {
+                self.send_mode_reply(requestor, ModeReply::ModeReply(self.mode_and_submode()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn send_mode_reply(
+        &self,
+        requestor: MessageMetadata,
+        reply: ModeReply,
+    ) -> Result<(), Self::Error> {
+        self.mode_interface
+            .reply_to_pus_tx
+            .send(GenericMessage::new(requestor, reply))
+            .map_err(|_| GenericTargetedMessagingError::Send(GenericSendError::RxDisconnected))?;
+        Ok(())
+    }
+
+    fn handle_mode_info(
+        &mut self,
+        _requestor_info: MessageMetadata,
+        _info: ModeAndSubmode,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use satrs::hk::{HkRequestVariant, UniqueId};
+    use satrs::mode::ModeRequest;
+    use satrs::spacepackets::ecss::tm::PusTmReader;
+    use satrs::tmtc::PacketAsVec;
+    use satrs_example::config::components::HEATER_0;
+
+    use super::*;
+
+    /// Heater output which stores the commanded state and can be configured to fail.
+    #[derive(Default)]
+    pub struct TestHeaterOutput {
+        pub enabled: bool,
+        pub duty_cycle_percent: u8,
+        pub call_count: u32,
+        pub fail: bool,
+    }
+
+    impl HeaterOutput for TestHeaterOutput {
+        type Error = ();
+
+        fn set_enabled(&mut self, enabled: bool) -> Result<(), Self::Error> {
+            self.call_count += 1;
+            if self.fail {
+                return Err(());
+            }
+            self.enabled = enabled;
+            Ok(())
+        }
+
+        fn set_duty_cycle(&mut self, duty_cycle_percent: u8) -> Result<(), Self::Error> {
+            self.call_count += 1;
+            if self.fail {
+                return Err(());
+            }
+            self.duty_cycle_percent = duty_cycle_percent;
+            Ok(())
+        }
+    }
+
+    pub struct HeaterTestbench {
+        pub mode_request_tx: mpsc::Sender<GenericMessage<ModeRequest>>,
+        pub mode_reply_rx_to_pus: mpsc::Receiver<GenericMessage<ModeReply>>,
+        pub composite_request_tx: mpsc::Sender<GenericMessage<CompositeRequest>>,
+        pub hk_reply_rx: mpsc::Receiver<GenericMessage<HkReply>>,
+        pub raw_reply_rx: mpsc::Receiver<GenericMessage<RawDeviceReply>>,
+        pub tm_rx: mpsc::Receiver<PacketAsVec>,
+        pub handler: HeaterHandler<TestHeaterOutput, mpsc::Sender<PacketAsVec>>,
+    }
+
+    impl HeaterTestbench {
+        pub fn new() -> Self {
+            let (request_tx, request_rx) = mpsc::channel();
+            let (reply_tx_to_pus, reply_rx_to_pus) = mpsc::channel();
+            let (reply_tx_to_parent, _reply_rx_to_parent) = mpsc::sync_channel(5);
+            let (composite_request_tx, composite_request_rx) = mpsc::channel();
+            let (hk_reply_tx, hk_reply_rx) = mpsc::channel();
+            let (raw_reply_tx, raw_reply_rx) = mpsc::channel();
+            let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+            let shared_heater_set = Arc::default();
+            Self {
+                mode_request_tx: request_tx,
+                mode_reply_rx_to_pus: reply_rx_to_pus,
+                composite_request_tx,
+                hk_reply_rx,
+                raw_reply_rx,
+                tm_rx,
+                handler: HeaterHandler::new(
+                    "TEST_HEATER",
+                    MpscModeLeafInterface {
+                        request_rx,
+                        reply_to_pus_tx: reply_tx_to_pus,
+                        reply_to_parent_tx: reply_tx_to_parent,
+                    },
+                    composite_request_rx,
+                    hk_reply_tx,
+                    raw_reply_tx,
+                    tm_tx,
+                    TestHeaterOutput::default(),
+                    shared_heater_set.clone(),
+                    create_heater_hk_sets(HEATER_0, shared_heater_set),
+                ),
+            }
+        }
+
+        pub fn command_mode(&mut self, mode: DeviceMode, submode: u16) -> ModeReply {
+            self.mode_request_tx
+                .send(GenericMessage::new(
+                    MessageMetadata::new(0, PUS_MODE_SERVICE.id()),
+                    ModeRequest::SetMode(ModeAndSubmode::new(mode as u32, submode)),
+                ))
+                .expect("failed to send mode request");
+            self.handler.periodic_operation();
+            self.mode_reply_rx_to_pus
+                .try_recv()
+                .expect("no mode reply generated")
+                .message
+        }
+    }
+
+    #[test]
+    fn test_gpio_pwm_output() {
+        let mut output = simulated_heater_output();
+        HeaterCommand::Enable.execute(&mut output).unwrap();
+        HeaterCommand::SetDutyCycle(25)
+            .execute(&mut output)
+            .unwrap();
+        assert_eq!(output.0.state(), PinState::High);
+        assert!(output.1.enabled());
+        assert_eq!(output.1.duty_cycle(), HEATER_PWM_PERIOD / 4);
+        HeaterCommand::Disable.execute(&mut output).unwrap();
+        assert_eq!(output.0.state(), PinState::Low);
+        assert!(!output.1.enabled());
+        output.1.set_failing(true);
+        assert!(matches!(
+            HeaterCommand::Disable.execute(&mut output),
+            Err(GpioPwmHeaterError::Pwm(_))
+        ));
+    }
+
+    #[test]
+    fn test_mode_transitions() {
+        let mut testbench = HeaterTestbench::new();
+        assert_eq!(
+            testbench.handler.mode_and_submode().mode(),
+            DeviceMode::Off as u32
+        );
+        testbench.handler.periodic_operation();
+        assert_eq!(testbench.handler.output.call_count, 0);
+
+        assert_eq!(
+            testbench.command_mode(DeviceMode::Normal, 40),
+            ModeReply::ModeReply(ModeAndSubmode::new(DeviceMode::Normal as u32, 40))
+        );
+        assert!(testbench.handler.output.enabled);
+        assert_eq!(testbench.handler.output.duty_cycle_percent, 40);
+        assert_eq!(
+            testbench.handler.heater_set(),
+            HeaterData {
+                enabled: true,
+                duty_cycle_percent: 40
+            }
+        );
+
+        assert_eq!(
+            testbench.command_mode(DeviceMode::Off, 0),
+            ModeReply::ModeReply(ModeAndSubmode::new(DeviceMode::Off as u32, 0))
+        );
+        assert!(!testbench.handler.output.enabled);
+        assert_eq!(testbench.handler.heater_set(), HeaterData::default());
+    }
+
+    #[test]
+    fn test_invalid_duty_cycle() {
+        let mut testbench = HeaterTestbench::new();
+        assert_eq!(
+            testbench.command_mode(DeviceMode::Normal, 101),
+            ModeReply::CantReachMode(mode_err::INVALID_SUBMODE)
+        );
+        assert_eq!(
+            testbench.handler.mode_and_submode().mode(),
+            DeviceMode::Off as u32
+        );
+        assert_eq!(testbench.handler.output.call_count, 0);
+    }
+
+    #[test]
+    fn test_output_failure() {
+        let mut testbench = HeaterTestbench::new();
+        testbench.handler.output.fail = true;
+        assert_eq!(
+            testbench.command_mode(DeviceMode::On, 0),
+            ModeReply::CantReachMode(dev_err::DEVICE_COMMUNICATION_FAILED)
+        );
+        assert_eq!(
+            testbench.handler.mode_and_submode().mode(),
+            DeviceMode::Off as u32
+        );
+    }
+
+    #[test]
+    fn test_raw_command() {
+        let mut testbench = HeaterTestbench::new();
+        let requestor = MessageMetadata::new(1, PUS_MODE_SERVICE.id());
+        let send_raw_cmd = |testbench: &mut HeaterTestbench, data: Vec<u8>| {
+            testbench
+                .composite_request_tx
+                .send(GenericMessage::new(
+                    requestor,
+                    CompositeRequest::RawCommand(RawDeviceCommand::new(data)),
+                ))
+                .unwrap();
+            testbench.handler.periodic_operation();
+            testbench.raw_reply_rx.try_recv().expect("no raw reply")
+        };
+        let reply = send_raw_cmd(&mut testbench, vec![HeaterCommand::ENABLE_ID]);
+        assert_eq!(
+            reply.message.variant,
+            RawDeviceReplyVariant::Failed(mode_err::WRONG_MODE)
+        );
+
+        testbench.command_mode(DeviceMode::Raw, 0);
+        let reply = send_raw_cmd(&mut testbench, vec![HeaterCommand::SET_DUTY_CYCLE_ID, 200]);
+        assert_eq!(
+            reply.message.variant,
+            RawDeviceReplyVariant::Failed(dev_err::INVALID_RAW_COMMAND)
+        );
+        let reply = send_raw_cmd(&mut testbench, vec![HeaterCommand::SET_DUTY_CYCLE_ID, 75]);
+        assert_eq!(
+            reply.message.variant,
+            RawDeviceReplyVariant::Data(vec![1, 75])
+        );
+        let reply = testbench.raw_reply_rx.try_recv().expect("no raw reply");
+        assert_eq!(reply.message.variant, RawDeviceReplyVariant::Completed);
+        assert_eq!(testbench.handler.output.duty_cycle_percent, 75);
+    }
+
+    #[test]
+    fn test_hk_one_shot() {
+        let mut testbench = HeaterTestbench::new();
+        testbench.command_mode(DeviceMode::Normal, 20);
+        testbench
+            .composite_request_tx
+            .send(GenericMessage::new(
+                MessageMetadata::new(2, PUS_MODE_SERVICE.id()),
+                CompositeRequest::Hk(HkRequest::new(
+                    SetId::HeaterStatus as UniqueId,
+                    HkRequestVariant::OneShot,
+                )),
+            ))
+            .unwrap();
+        testbench.handler.periodic_operation();
+        let hk_reply = testbench.hk_reply_rx.try_recv().expect("no HK reply");
+        assert_eq!(hk_reply.message.variant, HkReplyVariant::Ack);
+        let packet = testbench.tm_rx.try_recv().expect("no HK TM");
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        let source_data = tm.user_data();
+        assert_eq!(&source_data[source_data.len() - 2..], &[1, 20]);
+    }
+}
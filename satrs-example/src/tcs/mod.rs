@@ -0,0 +1,3 @@
+pub mod heater;
+pub mod subsystem;
+pub mod temp_sensor;
@@ -0,0 +1,287 @@
+//! TCS subsystem which groups all thermal control components handled by the TCS thread.
+//!
+//! The subsystem consists of the reference heater and temperature sensor device handlers. Like
+//! the ACS subsystem, the [TcsSubsystem] creates the request queues of its components, which are
+//! returned as [TcsRequestSenders] to register them at the [GenericRequestRouter], and it is the
+//! mode parent of its components. The subsystem is an [Executable] which is driven by the
+//! fixed-rate scheduler of the TCS thread.
+use std::convert::Infallible;
+use std::sync::{mpsc, Arc};
+
+use satrs::device::RawDeviceReply;
+use satrs::executable::{Executable, ExecutionType, OpResult};
+use satrs::hal::bus::DeviceBus;
+use satrs::id_registry::ComponentIdRegistry;
+use satrs::mode::{ModeReply, ModeRequest};
+use satrs::pus::EcssTmSender;
+use satrs::request::GenericMessage;
+use satrs_example::config::components::{HEATER_0, TEMP_SENSOR_0};
+
+use crate::acs::mgm::MpscModeLeafInterface;
+use crate::pus::hk::HkReply;
+use crate::requests::{CompositeRequest, GenericRequestRouter};
+use crate::tcs::heater::{create_heater_hk_sets, HeaterHandler, HeaterOutput};
+use crate::tcs::temp_sensor::{create_temp_sensor_hk_sets, TempSensorHandler};
+
+pub const TCS_COMPOSITE_QUEUE_DEPTH: usize = 10;
+pub const TCS_MODE_QUEUE_DEPTH: usize = 5;
+
+/// Request queues of the TCS components.
+pub struct TcsRequestSenders {
+    pub heater_composite_tx: mpsc::SyncSender<GenericMessage<CompositeRequest>>,
+    pub heater_mode_tx: mpsc::SyncSender<GenericMessage<ModeRequest>>,
+    pub temp_sensor_composite_tx: mpsc::SyncSender<GenericMessage<CompositeRequest>>,
+    pub temp_sensor_mode_tx: mpsc::SyncSender<GenericMessage<ModeRequest>>,
+}
+
+impl TcsRequestSenders {
+    /// Register all TCS components at the request router and their IDs in the component ID
+    /// registry.
+    pub fn register(
+        self,
+        request_router: &mut GenericRequestRouter,
+        component_ids: &mut ComponentIdRegistry,
+    ) {
+        component_ids
+            .register_target(HEATER_0, "HEATER_0")
+            .expect("registering component ID failed");
+        component_ids
+            .register_target(TEMP_SENSOR_0, "TEMP_SENSOR_0")
+            .expect("registering component ID failed");
+        request_router
+            .composite_router_map
+            .register_with_target_id(HEATER_0, self.heater_composite_tx)
+            .expect("registering composite request target failed");
+        request_router
+            .mode_router_map
+            .register_with_target_id(HEATER_0, self.heater_mode_tx)
+            .expect("registering mode request target failed");
+        request_router
+            .composite_router_map
+            .register_with_target_id(TEMP_SENSOR_0, self.temp_sensor_composite_tx)
+            .expect("registering composite request target failed");
+        request_router
+            .mode_router_map
+            .register_with_target_id(TEMP_SENSOR_0, self.temp_sensor_mode_tx)
+            .expect("registering mode request target failed");
+    }
+}
+
+/// Reply and TM senders which are shared by all TCS components.
+pub struct TcsReplySenders<TmSender: EcssTmSender + Clone> {
+    pub mode_reply_tx: mpsc::Sender<GenericMessage<ModeReply>>,
+    pub hk_reply_tx: mpsc::Sender<GenericMessage<HkReply>>,
+    pub raw_reply_tx: mpsc::Sender<GenericMessage<RawDeviceReply>>,
+    pub tm_sender: TmSender,
+}
+
+/// TCS subsystem. The [Self::periodic_operation] function should be called periodically, for
+/// example by scheduling the subsystem as an [Executable].
+pub struct TcsSubsystem<Output: HeaterOutput, Bus: DeviceBus, TmSender: EcssTmSender + Clone> {
+    heater_handler: HeaterHandler<Output, TmSender>,
+    temp_sensor_handler: TempSensorHandler<Bus, TmSender>,
+    mode_reply_from_children_rx: mpsc::Receiver<GenericMessage<ModeReply>>,
+}
+
+impl<Output: HeaterOutput, Bus: DeviceBus, TmSender: EcssTmSender + Clone>
+    TcsSubsystem<Output, Bus, TmSender>
+{
+    /// Create the TCS subsystem and its components.
+    ///
+    /// ## Parameter
+    ///
+    /// * `heater_output` - Output stage of the heater.
+    /// * `temp_sensor_bus` - Bus which connects the temperature sensor.
+    /// * `reply_senders` - Senders for the replies to the PUS services and the generated TM.
+    pub fn new(
+        heater_output: Output,
+        temp_sensor_bus: Bus,
+        reply_senders: TcsReplySenders<TmSender>,
+    ) -> (Self, TcsRequestSenders) {
+        let (heater_composite_tx, heater_composite_rx) =
+            mpsc::sync_channel(TCS_COMPOSITE_QUEUE_DEPTH);
+        let (heater_mode_tx, heater_mode_rx) = mpsc::sync_channel(TCS_MODE_QUEUE_DEPTH);
+        let (temp_sensor_composite_tx, temp_sensor_composite_rx) =
+            mpsc::sync_channel(TCS_COMPOSITE_QUEUE_DEPTH);
+        let (temp_sensor_mode_tx, temp_sensor_mode_rx) = mpsc::sync_channel(TCS_MODE_QUEUE_DEPTH);
+        let (mode_reply_to_parent_tx, mode_reply_from_children_rx) =
+            mpsc::sync_channel(TCS_MODE_QUEUE_DEPTH * 2);
+        let shared_heater_set = Arc::default();
+        let heater_handler = HeaterHandler::new(
+            "HEATER_0",
+            MpscModeLeafInterface {
+                request_rx: heater_mode_rx,
+                reply_to_pus_tx: reply_senders.mode_reply_tx.clone(),
+                reply_to_parent_tx: mode_reply_to_parent_tx.clone(),
+            },
+            heater_composite_rx,
+            reply_senders.hk_reply_tx.clone(),
+            reply_senders.raw_reply_tx.clone(),
+            reply_senders.tm_sender.clone(),
+            heater_output,
+            shared_heater_set.clone(),
+            create_heater_hk_sets(HEATER_0, shared_heater_set),
+        );
+        let shared_temp_set = Arc::default();
+        let temp_sensor_handler = TempSensorHandler::new(
+            "TEMP_SENSOR_0",
+            MpscModeLeafInterface {
+                request_rx: temp_sensor_mode_rx,
+                reply_to_pus_tx: reply_senders.mode_reply_tx,
+                reply_to_parent_tx: mode_reply_to_parent_tx,
+            },
+            temp_sensor_composite_rx,
+            reply_senders.hk_reply_tx,
+            reply_senders.raw_reply_tx,
+            reply_senders.tm_sender,
+            temp_sensor_bus,
+            shared_temp_set.clone(),
+            create_temp_sensor_hk_sets(TEMP_SENSOR_0, shared_temp_set),
+        );
+        (
+            Self {
+                heater_handler,
+                temp_sensor_handler,
+                mode_reply_from_children_rx,
+            },
+            TcsRequestSenders {
+                heater_composite_tx,
+                heater_mode_tx,
+                temp_sensor_composite_tx,
+                temp_sensor_mode_tx,
+            },
+        )
+    }
+
+    pub fn periodic_operation(&mut self) {
+        self.temp_sensor_handler.periodic_operation();
+        self.heater_handler.periodic_operation();
+        self.handle_child_mode_replies();
+    }
+
+    fn handle_child_mode_replies(&mut self) {
+        // The subsystem does not command the modes of its children yet, so the replies are only
+        // logged.
+        while let Ok(reply) = self.mode_reply_from_children_rx.try_recv() {
+            log::debug!(
+                "TCS: mode reply from child {:#x}: {:?}",
+                reply.sender_id(),
+                reply.message
+            );
+        }
+    }
+}
+
+impl<Output: HeaterOutput, Bus: DeviceBus, TmSender: EcssTmSender + Clone> Executable
+    for TcsSubsystem<Output, Bus, TmSender>
+{
+    type Error = Infallible;
+
+    fn exec_type(&self) -> ExecutionType {
+        ExecutionType::Infinite
+    }
+
+    fn task_name(&self) -> &'static str {
+        "TCS subsystem"
+    }
+
+    fn periodic_op(&mut self, _op_code: i32) -> Result<OpResult, Self::Error> {
+        self.periodic_operation();
+        Ok(OpResult::Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use satrs::mode::{ModeAndSubmode, ModeProvider};
+    use satrs::request::MessageMetadata;
+    use satrs::tmtc::PacketAsVec;
+    use satrs_example::config::components::PUS_MODE_SERVICE;
+    use satrs_example::DeviceMode;
+
+    use crate::mib::tm_definitions;
+    use crate::tcs::heater::{self, simulated_heater_output};
+    use crate::tcs::temp_sensor::{self, FakeTmp117};
+
+    use super::*;
+
+    #[test]
+    fn test_subsystem_mode_commanding() {
+        let (mode_reply_tx, mode_reply_rx) = mpsc::channel();
+        let (hk_reply_tx, _hk_reply_rx) = mpsc::channel();
+        let (raw_reply_tx, _raw_reply_rx) = mpsc::channel();
+        let (tm_tx, _tm_rx) = mpsc::channel::<PacketAsVec>();
+        let (mut tcs, senders) = TcsSubsystem::new(
+            simulated_heater_output(),
+            FakeTmp117::default(),
+            TcsReplySenders {
+                mode_reply_tx,
+                hk_reply_tx,
+                raw_reply_tx,
+                tm_sender: tm_tx,
+            },
+        );
+        let requestor = MessageMetadata::new(0, PUS_MODE_SERVICE.id());
+        senders
+            .heater_mode_tx
+            .send(GenericMessage::new(
+                requestor,
+                ModeRequest::SetMode(ModeAndSubmode::new(DeviceMode::Normal as u32, 50)),
+            ))
+            .unwrap();
+        senders
+            .temp_sensor_mode_tx
+            .send(GenericMessage::new(
+                requestor,
+                ModeRequest::SetMode(ModeAndSubmode::new(DeviceMode::Normal as u32, 0)),
+            ))
+            .unwrap();
+        tcs.periodic_operation();
+        assert_eq!(mode_reply_rx.try_iter().count(), 2);
+        assert_eq!(
+            tcs.heater_handler.mode_and_submode(),
+            ModeAndSubmode::new(DeviceMode::Normal as u32, 50)
+        );
+        assert_eq!(tcs.heater_handler.heater_set().duty_cycle_percent, 50);
+        let temp_set = tcs.temp_sensor_handler.temp_set();
+        assert!(temp_set.valid);
+        assert_eq!(temp_set.temperature_celsius, 20.0);
+
+        let mut request_router = GenericRequestRouter::default();
+        let mut component_ids = ComponentIdRegistry::new();
+        senders.register(&mut request_router, &mut component_ids);
+        assert_eq!(
+            component_ids.id_of("TEMP_SENSOR_0"),
+            Some(TEMP_SENSOR_0.raw())
+        );
+        assert!(request_router
+            .mode_router_map
+            .unregister(HEATER_0.raw())
+            .is_some());
+        assert!(request_router
+            .composite_router_map
+            .unregister(TEMP_SENSOR_0.raw())
+            .is_some());
+    }
+
+    #[test]
+    fn test_tm_definitions_match_hk_sets() {
+        let registry = tm_definitions();
+        let set_def = |name| {
+            registry
+                .packets()
+                .iter()
+                .find(|packet| packet.name == name)
+                .unwrap()
+        };
+        let mut hk_buf = [0; 64];
+        let heater_set_len = create_heater_hk_sets(HEATER_0, Arc::default())
+            .pack_set(heater::SetId::HeaterStatus as u32, &mut hk_buf)
+            .unwrap();
+        assert_eq!(heater_set_len, set_def("HEATER_STATUS").source_data_len());
+        let temp_set_len = create_temp_sensor_hk_sets(TEMP_SENSOR_0, Arc::default())
+            .pack_set(temp_sensor::SetId::Temperature as u32, &mut hk_buf)
+            .unwrap();
+        assert_eq!(temp_set_len, set_def("TEMPERATURE").source_data_len());
+    }
+}
@@ -0,0 +1,586 @@
+//! Reference device handler for a TMP117-like digital temperature sensor which is connected
+//! through an I2C or SPI bus.
+//!
+//! The handler demonstrates command building and reply parsing for a register based device
+//! behind the [DeviceBus] trait:
+//!
+//! - OFF: The device is not accessed and the temperature set is invalid.
+//! - ON: The device ID register is read once when leaving the OFF mode to verify that the
+//!   expected device is connected.
+//! - NORMAL: Like ON, but the temperature register is polled and converted in each cycle.
+//! - RAW: Raw commands are written to the bus as they are received and a reply of
+//!   [REGISTER_LEN] bytes is read back.
+//!
+//! The sensor is assumed to be powered permanently, so the transitions complete immediately.
+use derive_new::new;
+use satrs::device::{RawDeviceCommand, RawDeviceReply, RawDeviceReplyVariant};
+use satrs::hal::bus::DeviceBus;
+use satrs::hk::{HkRequest, HkSetDefinition, HkSetRegistry};
+use satrs::mode::{ModeAndSubmode, ModeError, ModeProvider, ModeReply, ModeRequestHandler};
+use satrs::pus::EcssTmSender;
+use satrs::queue::{GenericSendError, GenericTargetedMessagingError};
+use satrs::request::{GenericMessage, MessageMetadata, UniqueApidTargetId};
+use satrs::spacepackets::ByteConversionError;
+use satrs_example::config::components::{NO_SENDER, PUS_MODE_SERVICE};
+use satrs_example::config::{dev_err, mode_err};
+use satrs_example::{DeviceMode, TimestampHelper};
+use satrs_mib::tm_def::{params_len, ParamDef, ParamType};
+use serde::{Deserialize, Serialize};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::acs::mgm::MpscModeLeafInterface;
+use crate::pus::hk::{HkReply, HkReplyVariant};
+use crate::requests::CompositeRequest;
+
+pub const TEMPERATURE_REG: u8 = 0x00;
+pub const DEVICE_ID_REG: u8 = 0x0F;
+/// Expected content of the device ID register.
+pub const DEVICE_ID: u16 = 0x0117;
+/// All registers are 16 bits wide and transferred in big endian format.
+pub const REGISTER_LEN: usize = 2;
+/// Resolution of the temperature register.
+pub const CELSIUS_PER_LSB: f32 = 0.0078125;
+pub const MAX_RAW_COMMAND_LEN: usize = 8;
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum SetId {
+    Temperature = 0,
+}
+
+/// Layout of the temperature set, which is also exported as part of the TM definitions.
+pub const TEMPERATURE_PARAMS: &[ParamDef] = &[
+    ParamDef::new("valid", ParamType::Bool),
+    ParamDef::new("temperature", ParamType::F32).with_unit("degC"),
+];
+
+/// Build the command to read the given register, which sets the register pointer of the device.
+pub fn build_read_register_cmd(register: u8) -> [u8; 1] {
+    [register]
+}
+
+pub fn parse_register(reply: &[u8]) -> Result<u16, ByteConversionError> {
+    if reply.len() < REGISTER_LEN {
+        return Err(ByteConversionError::FromSliceTooSmall {
+            found: reply.len(),
+            expected: REGISTER_LEN,
+        });
+    }
+    Ok(u16::from_be_bytes([reply[0], reply[1]]))
+}
+
+/// Convert the reply to a temperature register read into degrees celsius.
+pub fn parse_temperature(reply: &[u8]) -> Result<f32, ByteConversionError> {
+    Ok(parse_register(reply)? as i16 as f32 * CELSIUS_PER_LSB)
+}
+
+/// Bus to an in-process fake TMP117 device, which can be used when no sensor is available.
+#[derive(Debug)]
+pub struct FakeTmp117 {
+    pub temperature_celsius: f32,
+    pub device_id: u16,
+}
+
+impl Default for FakeTmp117 {
+    fn default() -> Self {
+        Self {
+            temperature_celsius: 20.0,
+            device_id: DEVICE_ID,
+        }
+    }
+}
+
+impl DeviceBus for FakeTmp117 {
+    type Error = ();
+
+    fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
+        if rx.len() < REGISTER_LEN {
+            return Err(());
+        }
+        let value = match tx.first() {
+            Some(&TEMPERATURE_REG) => {
+                (self.temperature_celsius / CELSIUS_PER_LSB).round() as i16 as u16
+            }
+            Some(&DEVICE_ID_REG) => self.device_id,
+            _ => return Err(()),
+        };
+        rx[0..REGISTER_LEN].copy_from_slice(&value.to_be_bytes());
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct TempSensorData {
+    pub valid: bool,
+    pub temperature_celsius: f32,
+}
+
+/// Create the HK set registry of a temperature sensor handler. The temperature set is collected
+/// with an interval of 1 second by default once periodic generation was enabled.
+pub fn create_temp_sensor_hk_sets(
+    id: UniqueApidTargetId,
+    shared_temp_set: Arc<Mutex<TempSensorData>>,
+) -> HkSetRegistry {
+    let mut hk_sets = HkSetRegistry::new(id, Duration::from_secs(1), 16);
+    hk_sets
+        .add_set(
+            HkSetDefinition::new(SetId::Temperature as u32, Duration::from_secs(1)).with_provider(
+                move |hk_buf| {
+                    const SET_LEN: usize = params_len(TEMPERATURE_PARAMS);
+                    if hk_buf.len() < SET_LEN {
+                        return Err(ByteConversionError::ToSliceTooSmall {
+                            found: hk_buf.len(),
+                            expected: SET_LEN,
+                        });
+                    }
+                    let temp_snapshot = *shared_temp_set.lock().unwrap();
+                    hk_buf[0] = temp_snapshot.valid as u8;
+                    hk_buf[1..5].copy_from_slice(&temp_snapshot.temperature_celsius.to_be_bytes());
+                    Ok(SET_LEN)
+                },
+            ),
+        )
+        .expect("adding temperature set failed");
+    hk_sets
+}
+
+/// Example temperature sensor device handler. See the [module documentation][self] for the
+/// supported modes.
+#[derive(new)]
+#[allow(clippy::too_many_arguments)]
+pub struct TempSensorHandler<Bus: DeviceBus, TmSender: EcssTmSender> {
+    dev_str: &'static str,
+    mode_interface: MpscModeLeafInterface,
+    composite_request_rx: mpsc::Receiver<GenericMessage<CompositeRequest>>,
+    hk_reply_tx: mpsc::Sender<GenericMessage<HkReply>>,
+    raw_reply_tx: mpsc::Sender<GenericMessage<RawDeviceReply>>,
+    tm_sender: TmSender,
+    pub bus: Bus,
+    shared_temp_set: Arc<Mutex<TempSensorData>>,
+    hk_sets: HkSetRegistry,
+    #[new(value = "ModeAndSubmode::new(DeviceMode::Off as u32, 0)")]
+    mode_and_submode: ModeAndSubmode,
+    #[new(default)]
+    rx_buf: [u8; REGISTER_LEN],
+    #[new(default)]
+    stamp_helper: TimestampHelper,
+}
+
+impl<Bus: DeviceBus, TmSender: EcssTmSender> TempSensorHandler<Bus, TmSender> {
+    #[allow(dead_code)]
+    pub fn temp_set(&self) -> TempSensorData {
+        *self.shared_temp_set.lock().unwrap()
+    }
+
+    pub fn periodic_operation(&mut self) {
+        self.stamp_helper.update_from_now();
+        self.handle_composite_requests();
+        self.handle_mode_requests();
+        if self.mode() == DeviceMode::Normal as u32 {
+            log::trace!("polling temperature sensor {}", self.dev_str);
+            self.poll_sensor();
+        }
+        if let Err(e) =
+            self.hk_sets
+                .send_due_sets(Instant::now(), self.stamp_helper.stamp(), &self.tm_sender)
+        {
+            log::error!("{}: sending periodic HK failed: {}", self.dev_str, e);
+        }
+    }
+
+    pub fn handle_composite_requests(&mut self) {
+        loop {
+            match self.composite_request_rx.try_recv() {
+                Ok(ref msg) => match &msg.message {
+                    CompositeRequest::Hk(hk_request) => {
+                        self.handle_hk_request(&msg.requestor_info, hk_request)
+                    }
+                    // The sensor does not have actions.
+                    CompositeRequest::Action(_action_req) => {}
+                    CompositeRequest::RawCommand(raw_cmd) => {
+                        self.handle_raw_command(&msg.requestor_info, raw_cmd)
+                    }
+                },
+                Err(e) => {
+                    if e != mpsc::TryRecvError::Empty {
+                        log::warn!(
+                            "{}: failed to receive composite request: {:?}",
+                            self.dev_str,
+                            e
+                        );
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn handle_hk_request(&mut self, requestor_info: &MessageMetadata, hk_request: &HkRequest) {
+        match self
+            .hk_sets
+            .handle_request(hk_request, self.stamp_helper.stamp(), &self.tm_sender)
+        {
+            Ok(()) => {
+                self.hk_reply_tx
+                    .send(GenericMessage::new(
+                        *requestor_info,
+                        HkReply::new(hk_request.unique_id, HkReplyVariant::Ack),
+                    ))
+                    .expect("failed to send HK reply");
+            }
+            Err(e) => {
+                log::error!("{}: HK request handling failed: {}", self.dev_str, e);
+            }
+        }
+    }
+
+    /// Raw commands are only forwarded to the device in RAW mode. The register content read
+    /// back from the device is sent back, followed by a completion reply.
+    pub fn handle_raw_command(
+        &mut self,
+        requestor_info: &MessageMetadata,
+        raw_cmd: &RawDeviceCommand,
+    ) {
+        if self.mode() != DeviceMode::Raw as u32 {
+            self.send_raw_reply(
+                requestor_info,
+                RawDeviceReplyVariant::Failed(mode_err::WRONG_MODE),
+            );
+            return;
+        }
+        if raw_cmd.data.is_empty() {
+            self.send_raw_reply(
+                requestor_info,
+                RawDeviceReplyVariant::Failed(dev_err::INVALID_RAW_COMMAND),
+            );
+            return;
+        }
+        if raw_cmd.data.len() > MAX_RAW_COMMAND_LEN {
+            self.send_raw_reply(
+                requestor_info,
+                RawDeviceReplyVariant::Failed(dev_err::RAW_COMMAND_TOO_LARGE),
+            );
+            return;
+        }
+        if self
+            .bus
+            .write_read(&raw_cmd.data, &mut self.rx_buf)
+            .is_err()
+        {
+            self.send_raw_reply(
+                requestor_info,
+                RawDeviceReplyVariant::Failed(dev_err::RAW_TRANSFER_FAILED),
+            );
+            return;
+        }
+        self.send_raw_reply(
+            requestor_info,
+            RawDeviceReplyVariant::Data(self.rx_buf.to_vec()),
+        );
+        self.send_raw_reply(requestor_info, RawDeviceReplyVariant::Completed);
+    }
+
+    fn send_raw_reply(&self, requestor_info: &MessageMetadata, variant: RawDeviceReplyVariant) {
+        self.raw_reply_tx
+            .send(GenericMessage::new(
+                *requestor_info,
+                RawDeviceReply::new(variant),
+            ))
+            .expect("failed to send raw reply");
+    }
+
+    pub fn handle_mode_requests(&mut self) {
+        loop {
+            match self.mode_interface.request_rx.try_recv() {
+                Ok(msg) => {
+                    let result = self.handle_mode_request(msg);
+                    if result.is_err() {
+                        log::warn!(
+                            "{}: mode request failed with error {:?}",
+                            self.dev_str,
+                            result.err().unwrap()
+                        );
+                    }
+                }
+                Err(e) => {
+                    if e != mpsc::TryRecvError::Empty {
+                        log::warn!("{}: failed to receive mode request: {:?}", self.dev_str, e);
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Read the given register from the device.
+    fn read_register(&mut self, register: u8) -> Result<&[u8], Bus::Error> {
+        self.bus
+            .write_read(&build_read_register_cmd(register), &mut self.rx_buf)?;
+        Ok(&self.rx_buf)
+    }
+
+    pub fn poll_sensor(&mut self) {
+        let result = match self.read_register(TEMPERATURE_REG) {
+            Ok(reply) => parse_temperature(reply).map_err(|e| format!("{e}")),
+            Err(e) => Err(format!("{e:?}")),
+        };
+        let mut temp_set = self.shared_temp_set.lock().unwrap();
+        match result {
+            Ok(temperature_celsius) => {
+                temp_set.temperature_celsius = temperature_celsius;
+                temp_set.valid = true;
+            }
+            Err(e) => {
+                log::warn!("{}: reading temperature failed: {}", self.dev_str, e);
+                temp_set.valid = false;
+            }
+        }
+    }
+
+    /// Check the device ID of the connected device. Returns the reason as an error if the check
+    /// failed.
+    fn check_device_id(&mut self) -> Result<(), ModeReply> {
+        let result = match self.read_register(DEVICE_ID_REG) {
+            Ok(reply) => parse_register(reply).map_err(|e| format!("{e}")),
+            Err(e) => Err(format!("{e:?}")),
+        };
+        let device_id = result.map_err(|e| {
+            log::error!("{}: reading device ID failed: {}", self.dev_str, e);
+            ModeReply::CantReachMode(dev_err::DEVICE_COMMUNICATION_FAILED)
+        })?;
+        if device_id != DEVICE_ID {
+            log::error!(
+                "{}: unexpected device ID {:#06x}, expected {:#06x}",
+                self.dev_str,
+                device_id,
+                DEVICE_ID
+            );
+            return Err(ModeReply::CantReachMode(dev_err::DEVICE_ID_MISMATCH));
+        }
+        Ok(())
+    }
+}
+
+impl<Bus: DeviceBus, TmSender: EcssTmSender> ModeProvider for TempSensorHandler<Bus, TmSender> {
+    fn mode_and_submode(&self) -> ModeAndSubmode {
+        self.mode_and_submode
+    }
+}
+
+impl<Bus: DeviceBus, TmSender: EcssTmSender> ModeRequestHandler
+    for TempSensorHandler<Bus, TmSender>
+{
+    type Error = ModeError;
+
+    fn start_transition(
+        &mut self,
+        requestor: MessageMetadata,
+        mode_and_submode: ModeAndSubmode,
+    ) -> Result<(), ModeError> {
+        log::info!(
+            "{}: transitioning to mode {:?}",
+            self.dev_str,
+            mode_and_submode
+        );
+        let mode = mode_and_submode.mode();
+        if mode == DeviceMode::Off as u32 {
+            self.shared_temp_set.lock().unwrap().valid = false;
+        } else if mode == DeviceMode::On as u32
+            || mode == DeviceMode::Normal as u32
+            || mode == DeviceMode::Raw as u32
+        {
+            if self.mode() == DeviceMode::Off as u32 {
+                if let Err(reply) = self.check_device_id() {
+                    return self.send_mode_reply(requestor, reply);
+                }
+            }
+            if mode != DeviceMode::Normal as u32 {
+                self.shared_temp_set.lock().unwrap().valid = false;
+            }
+        } else {
+            return self.send_mode_reply(requestor, ModeReply::CantReachMode(mode_err::WRONG_MODE));
+        }
+        self.mode_and_submode = mode_and_submode;
+        self.handle_mode_reached(Some(requestor))
+    }
+
+    fn announce_mode(&self, _requestor_info: Option<MessageMetadata>, _recursive: bool) {
+        log::info!(
+            "{} announcing mode: {:?}",
+            self.dev_str,
+            self.mode_and_submode()
+        );
+    }
+
+    fn handle_mode_reached(
+        &mut self,
+        requestor: Option<MessageMetadata>,
+    ) -> Result<(), Self::Error> {
+        self.announce_mode(requestor, false);
+        if let Some(requestor) = requestor {
+            if requestor.sender_id() == NO_SENDER {
+                return Ok(());
+            }
+            if requestor.sender_id() != PUS_MODE_SERVICE.id() {
+                log::warn!(
+                    "can not send back mode reply to sender {:x}",
+                    requestor.sender_id()
+                );
+            } else {
+                self.send_mode_reply(requestor, ModeReply::ModeReply(self.mode_and_submode()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn send_mode_reply(
+        &self,
+        requestor: MessageMetadata,
+        reply: ModeReply,
+    ) -> Result<(), Self::Error> {
+        self.mode_interface
+            .reply_to_pus_tx
+            .send(GenericMessage::new(requestor, reply))
+            .map_err(|_| GenericTargetedMessagingError::Send(GenericSendError::RxDisconnected))?;
+        Ok(())
+    }
+
+    fn handle_mode_info(
+        &mut self,
+        _requestor_info: MessageMetadata,
+        _info: ModeAndSubmode,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use satrs::mode::ModeRequest;
+    use satrs::tmtc::PacketAsVec;
+    use satrs_example::config::components::TEMP_SENSOR_0;
+
+    use super::*;
+
+    pub struct TempSensorTestbench {
+        pub mode_request_tx: mpsc::Sender<GenericMessage<ModeRequest>>,
+        pub mode_reply_rx_to_pus: mpsc::Receiver<GenericMessage<ModeReply>>,
+        pub composite_request_tx: mpsc::Sender<GenericMessage<CompositeRequest>>,
+        pub raw_reply_rx: mpsc::Receiver<GenericMessage<RawDeviceReply>>,
+        pub handler: TempSensorHandler<FakeTmp117, mpsc::Sender<PacketAsVec>>,
+    }
+
+    impl TempSensorTestbench {
+        pub fn new() -> Self {
+            let (request_tx, request_rx) = mpsc::channel();
+            let (reply_tx_to_pus, reply_rx_to_pus) = mpsc::channel();
+            let (reply_tx_to_parent, _reply_rx_to_parent) = mpsc::sync_channel(5);
+            let (composite_request_tx, composite_request_rx) = mpsc::channel();
+            let (hk_reply_tx, _hk_reply_rx) = mpsc::channel();
+            let (raw_reply_tx, raw_reply_rx) = mpsc::channel();
+            let (tm_tx, _tm_rx) = mpsc::channel::<PacketAsVec>();
+            let shared_temp_set = Arc::default();
+            Self {
+                mode_request_tx: request_tx,
+                mode_reply_rx_to_pus: reply_rx_to_pus,
+                composite_request_tx,
+                raw_reply_rx,
+                handler: TempSensorHandler::new(
+                    "TEST_TEMP_SENSOR",
+                    MpscModeLeafInterface {
+                        request_rx,
+                        reply_to_pus_tx: reply_tx_to_pus,
+                        reply_to_parent_tx: reply_tx_to_parent,
+                    },
+                    composite_request_rx,
+                    hk_reply_tx,
+                    raw_reply_tx,
+                    tm_tx,
+                    FakeTmp117::default(),
+                    shared_temp_set.clone(),
+                    create_temp_sensor_hk_sets(TEMP_SENSOR_0, shared_temp_set),
+                ),
+            }
+        }
+
+        pub fn command_mode(&mut self, mode: DeviceMode) -> ModeReply {
+            self.mode_request_tx
+                .send(GenericMessage::new(
+                    MessageMetadata::new(0, PUS_MODE_SERVICE.id()),
+                    ModeRequest::SetMode(ModeAndSubmode::new(mode as u32, 0)),
+                ))
+                .expect("failed to send mode request");
+            self.handler.periodic_operation();
+            self.mode_reply_rx_to_pus
+                .try_recv()
+                .expect("no mode reply generated")
+                .message
+        }
+    }
+
+    #[test]
+    fn test_reply_parsing() {
+        assert_eq!(parse_temperature(&[0x0C, 0x80]).unwrap(), 25.0);
+        assert_eq!(parse_temperature(&[0xFF, 0x80]).unwrap(), -1.0);
+        assert!(parse_temperature(&[0x0C]).is_err());
+        assert_eq!(parse_register(&DEVICE_ID.to_be_bytes()).unwrap(), DEVICE_ID);
+    }
+
+    #[test]
+    fn test_normal_mode_polling() {
+        let mut testbench = TempSensorTestbench::new();
+        testbench.handler.periodic_operation();
+        assert!(!testbench.handler.temp_set().valid);
+        testbench.handler.bus.temperature_celsius = -12.5;
+        assert_eq!(
+            testbench.command_mode(DeviceMode::Normal),
+            ModeReply::ModeReply(ModeAndSubmode::new(DeviceMode::Normal as u32, 0))
+        );
+        let temp_set = testbench.handler.temp_set();
+        assert!(temp_set.valid);
+        assert_eq!(temp_set.temperature_celsius, -12.5);
+
+        testbench.command_mode(DeviceMode::Off);
+        assert!(!testbench.handler.temp_set().valid);
+    }
+
+    #[test]
+    fn test_device_id_mismatch() {
+        let mut testbench = TempSensorTestbench::new();
+        testbench.handler.bus.device_id = 0x0116;
+        assert_eq!(
+            testbench.command_mode(DeviceMode::On),
+            ModeReply::CantReachMode(dev_err::DEVICE_ID_MISMATCH)
+        );
+        assert_eq!(
+            testbench.handler.mode_and_submode().mode(),
+            DeviceMode::Off as u32
+        );
+    }
+
+    #[test]
+    fn test_raw_command() {
+        let mut testbench = TempSensorTestbench::new();
+        testbench.command_mode(DeviceMode::Raw);
+        testbench
+            .composite_request_tx
+            .send(GenericMessage::new(
+                MessageMetadata::new(1, PUS_MODE_SERVICE.id()),
+                CompositeRequest::RawCommand(RawDeviceCommand::new(vec![DEVICE_ID_REG])),
+            ))
+            .unwrap();
+        testbench.handler.periodic_operation();
+        let reply = testbench.raw_reply_rx.try_recv().expect("no raw reply");
+        assert_eq!(
+            reply.message.variant,
+            RawDeviceReplyVariant::Data(DEVICE_ID.to_be_bytes().to_vec())
+        );
+        let reply = testbench.raw_reply_rx.try_recv().expect("no raw reply");
+        assert_eq!(reply.message.variant, RawDeviceReplyVariant::Completed);
+        // The device is not polled in RAW mode.
+        assert!(!testbench.handler.temp_set().valid);
+    }
+}
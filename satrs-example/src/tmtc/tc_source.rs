@@ -1,5 +1,5 @@
 use satrs::{
-    pool::PoolProvider,
+    pool::{PoolProvider, SharedPriorityPoolQueue},
     pus::HandlingStatus,
     tmtc::{PacketAsVec, PacketInPool, PacketSenderWithSharedPool, SharedPacketPool},
 };
@@ -10,9 +10,11 @@ use satrs::pus::MpscTmAsVecSender;
 use crate::pus::PusTcDistributor;
 
 // TC source components where static pools are the backing memory of the received telecommands.
+// The telecommands are released in the order of their priority class, and in FIFO order for
+// telecommands with the same priority.
 pub struct TcSourceTaskStatic {
     shared_tc_pool: SharedPacketPool,
-    tc_receiver: mpsc::Receiver<PacketInPool>,
+    tc_queue: SharedPriorityPoolQueue<PacketInPool>,
     tc_buf: [u8; 4096],
     pus_distributor: PusTcDistributor<PacketSenderWithSharedPool>,
 }
@@ -20,12 +22,12 @@ pub struct TcSourceTaskStatic {
 impl TcSourceTaskStatic {
     pub fn new(
         shared_tc_pool: SharedPacketPool,
-        tc_receiver: mpsc::Receiver<PacketInPool>,
+        tc_queue: SharedPriorityPoolQueue<PacketInPool>,
         pus_receiver: PusTcDistributor<PacketSenderWithSharedPool>,
     ) -> Self {
         Self {
             shared_tc_pool,
-            tc_receiver,
+            tc_queue,
             tc_buf: [0; 4096],
             pus_distributor: pus_receiver,
         }
@@ -33,13 +35,15 @@ impl TcSourceTaskStatic {
 
     pub fn periodic_operation(&mut self) {
         self.poll_tc();
+        self.pus_distributor.send_due_stats_hk();
     }
 
     pub fn poll_tc(&mut self) -> HandlingStatus {
         // Right now, we only expect ECSS PUS packets.
         // If packets like CFDP are expected, we might have to check the APID first.
-        match self.tc_receiver.try_recv() {
-            Ok(packet_in_pool) => {
+        let next_tc = self.tc_queue.lock().expect("locking tc queue failed").pop();
+        match next_tc {
+            Some((_priority, packet_in_pool)) => {
                 let pool = self
                     .shared_tc_pool
                     .0
@@ -53,13 +57,7 @@ impl TcSourceTaskStatic {
                     .ok();
                 HandlingStatus::HandledOne
             }
-            Err(e) => match e {
-                TryRecvError::Empty => HandlingStatus::Empty,
-                TryRecvError::Disconnected => {
-                    log::warn!("tmtc thread: sender disconnected");
-                    HandlingStatus::Empty
-                }
-            },
+            None => HandlingStatus::Empty,
         }
     }
 }
@@ -83,6 +81,7 @@ impl TcSourceTaskDynamic {
 
     pub fn periodic_operation(&mut self) {
         self.poll_tc();
+        self.pus_distributor.send_due_stats_hk();
     }
 
     pub fn poll_tc(&mut self) -> HandlingStatus {
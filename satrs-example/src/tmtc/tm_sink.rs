@@ -1,13 +1,16 @@
-use std::{
-    collections::HashMap,
-    sync::mpsc::{self},
-};
+use std::sync::mpsc::{self};
+use std::sync::Arc;
+use std::time::Duration;
 
-use log::info;
+use log::{info, warn};
+use satrs::pus::HandlingStatus;
+use satrs::shutdown::{drain_with_timeout, DrainOutcome};
+use satrs::tmtc::stats::TmtcStatistics;
+use satrs::tmtc::tm_funnel::TmFunnelCounters;
+use satrs::tmtc::tm_priority::{PriorityTmQueue, TmSchedulingPolicy, NUM_DEFAULT_TM_PRIORITIES};
 use satrs::tmtc::{PacketAsVec, PacketInPool, SharedPacketPool};
 use satrs::{
     pool::PoolProvider,
-    seq_count::{CcsdsSimpleSeqCountProvider, SequenceCountProviderCore},
     spacepackets::{
         ecss::{tm::PusTmZeroCopyWriter, PusPacket},
         time::cds::MIN_CDS_FIELD_LEN,
@@ -15,33 +18,22 @@ use satrs::{
     },
 };
 
-use crate::interface::tcp::SyncTcpTmSource;
+use satrs_example::config::tasks::TM_SINK_RECV_TIMEOUT_MS;
 
-#[derive(Default)]
-pub struct CcsdsSeqCounterMap {
-    apid_seq_counter_map: HashMap<u16, CcsdsSimpleSeqCountProvider>,
-}
-impl CcsdsSeqCounterMap {
-    pub fn get_and_increment(&mut self, apid: u16) -> u16 {
-        self.apid_seq_counter_map
-            .entry(apid)
-            .or_default()
-            .get_and_increment()
-    }
-}
+use crate::interface::tcp::SyncTcpTmSource;
 
 pub struct TmFunnelCommon {
-    seq_counter_map: CcsdsSeqCounterMap,
-    msg_counter_map: HashMap<u8, u16>,
+    counters: TmFunnelCounters,
     sync_tm_tcp_source: SyncTcpTmSource,
+    stats: Arc<TmtcStatistics>,
 }
 
 impl TmFunnelCommon {
-    pub fn new(sync_tm_tcp_source: SyncTcpTmSource) -> Self {
+    pub fn new(sync_tm_tcp_source: SyncTcpTmSource, stats: Arc<TmtcStatistics>) -> Self {
         Self {
-            seq_counter_map: Default::default(),
-            msg_counter_map: Default::default(),
+            counters: TmFunnelCounters::new(MIN_CDS_FIELD_LEN),
             sync_tm_tcp_source,
+            stats,
         }
     }
 
@@ -49,20 +41,8 @@ impl TmFunnelCommon {
     // a sequence counter
     fn apply_packet_processing(&mut self, mut zero_copy_writer: PusTmZeroCopyWriter) {
         // zero_copy_writer.set_apid(PUS_APID);
-        zero_copy_writer.set_seq_count(
-            self.seq_counter_map
-                .get_and_increment(zero_copy_writer.apid()),
-        );
-        let entry = self
-            .msg_counter_map
-            .entry(zero_copy_writer.service())
-            .or_insert(0);
-        zero_copy_writer.set_msg_count(*entry);
-        if *entry == u16::MAX {
-            *entry = 0;
-        } else {
-            *entry += 1;
-        }
+        self.counters.apply_to_writer(&mut zero_copy_writer);
+        self.stats.tm_generated(Some(zero_copy_writer.service()));
 
         Self::packet_printout(&zero_copy_writer);
         // This operation has to come last!
@@ -92,9 +72,10 @@ impl TmSinkStatic {
         sync_tm_tcp_source: SyncTcpTmSource,
         tm_funnel_rx: mpsc::Receiver<PacketInPool>,
         tm_server_tx: mpsc::SyncSender<PacketInPool>,
+        stats: Arc<TmtcStatistics>,
     ) -> Self {
         Self {
-            common: TmFunnelCommon::new(sync_tm_tcp_source),
+            common: TmFunnelCommon::new(sync_tm_tcp_source, stats),
             shared_tm_store,
             tm_funnel_rx,
             tm_server_tx,
@@ -102,27 +83,46 @@ impl TmSinkStatic {
     }
 
     pub fn operation(&mut self) {
-        if let Ok(pus_tm_in_pool) = self.tm_funnel_rx.recv() {
-            // Read the TM, set sequence counter and message counter, and finally update
-            // the CRC.
-            let shared_pool = self.shared_tm_store.0.clone();
-            let mut pool_guard = shared_pool.write().expect("Locking TM pool failed");
-            let mut tm_copy = Vec::new();
-            pool_guard
-                .modify(&pus_tm_in_pool.store_addr, |buf| {
-                    let zero_copy_writer = PusTmZeroCopyWriter::new(buf, MIN_CDS_FIELD_LEN)
-                        .expect("Creating TM zero copy writer failed");
-                    self.common.apply_packet_processing(zero_copy_writer);
-                    tm_copy = buf.to_vec()
-                })
-                .expect("Reading TM from pool failed");
-            self.tm_server_tx
-                .send(pus_tm_in_pool)
-                .expect("Sending TM to server failed");
-            // We could also do this step in the update closure, but I'd rather avoid this, could
-            // lead to nested locking.
-            self.common.sync_tm_tcp_source.add_tm(&tm_copy);
+        if let Ok(pus_tm_in_pool) = self
+            .tm_funnel_rx
+            .recv_timeout(Duration::from_millis(TM_SINK_RECV_TIMEOUT_MS))
+        {
+            self.handle_tm(pus_tm_in_pool);
+        }
+    }
+
+    /// Forward all queued TM until the queue is empty or the timeout elapsed.
+    pub fn drain(&mut self, timeout: Duration) -> DrainOutcome {
+        drain_with_timeout(timeout, || match self.tm_funnel_rx.try_recv() {
+            Ok(pus_tm_in_pool) => {
+                self.handle_tm(pus_tm_in_pool);
+                HandlingStatus::HandledOne
+            }
+            Err(_) => HandlingStatus::Empty,
+        })
+    }
+
+    fn handle_tm(&mut self, pus_tm_in_pool: PacketInPool) {
+        // Read the TM, set sequence counter and message counter, and finally update
+        // the CRC.
+        let shared_pool = self.shared_tm_store.0.clone();
+        let mut pool_guard = shared_pool.write().expect("Locking TM pool failed");
+        let mut tm_copy = Vec::new();
+        pool_guard
+            .modify(&pus_tm_in_pool.store_addr, |buf| {
+                let zero_copy_writer = PusTmZeroCopyWriter::new(buf, MIN_CDS_FIELD_LEN)
+                    .expect("Creating TM zero copy writer failed");
+                self.common.apply_packet_processing(zero_copy_writer);
+                tm_copy = buf.to_vec()
+            })
+            .expect("Reading TM from pool failed");
+        if self.tm_server_tx.send(pus_tm_in_pool).is_err() {
+            // The TM server might already be stopped during a shutdown.
+            warn!("Sending TM to server failed");
         }
+        // We could also do this step in the update closure, but I'd rather avoid this, could
+        // lead to nested locking.
+        self.common.sync_tm_tcp_source.add_tm(&tm_copy);
     }
 }
 
@@ -130,6 +130,8 @@ pub struct TmSinkDynamic {
     common: TmFunnelCommon,
     tm_funnel_rx: mpsc::Receiver<PacketAsVec>,
     tm_server_tx: mpsc::Sender<PacketAsVec>,
+    /// Verification and event TM is forwarded before all other TM.
+    tm_queue: PriorityTmQueue,
 }
 
 impl TmSinkDynamic {
@@ -137,25 +139,63 @@ impl TmSinkDynamic {
         sync_tm_tcp_source: SyncTcpTmSource,
         tm_funnel_rx: mpsc::Receiver<PacketAsVec>,
         tm_server_tx: mpsc::Sender<PacketAsVec>,
+        stats: Arc<TmtcStatistics>,
     ) -> Self {
         Self {
-            common: TmFunnelCommon::new(sync_tm_tcp_source),
+            common: TmFunnelCommon::new(sync_tm_tcp_source, stats),
             tm_funnel_rx,
             tm_server_tx,
+            tm_queue: PriorityTmQueue::new(
+                NUM_DEFAULT_TM_PRIORITIES,
+                TmSchedulingPolicy::StrictPriority,
+            ),
         }
     }
 
     pub fn operation(&mut self) {
-        if let Ok(mut tm) = self.tm_funnel_rx.recv() {
-            // Read the TM, set sequence counter and message counter, and finally update
-            // the CRC.
-            let zero_copy_writer = PusTmZeroCopyWriter::new(&mut tm.packet, MIN_CDS_FIELD_LEN)
-                .expect("Creating TM zero copy writer failed");
-            self.common.apply_packet_processing(zero_copy_writer);
-            self.common.sync_tm_tcp_source.add_tm(&tm.packet);
-            self.tm_server_tx
-                .send(tm)
-                .expect("Sending TM to server failed");
+        if let Ok(tm) = self
+            .tm_funnel_rx
+            .recv_timeout(Duration::from_millis(TM_SINK_RECV_TIMEOUT_MS))
+        {
+            self.tm_queue.push_classified(tm);
+            self.queue_pending_tm();
+        }
+        while let Some(tm) = self.tm_queue.pop() {
+            self.handle_tm(tm);
+        }
+    }
+
+    /// Forward all queued TM until the queue is empty or the timeout elapsed.
+    pub fn drain(&mut self, timeout: Duration) -> DrainOutcome {
+        drain_with_timeout(timeout, || {
+            self.queue_pending_tm();
+            match self.tm_queue.pop() {
+                Some(tm) => {
+                    self.handle_tm(tm);
+                    HandlingStatus::HandledOne
+                }
+                None => HandlingStatus::Empty,
+            }
+        })
+    }
+
+    /// Queue all pending TM so it can be forwarded in order of priority.
+    fn queue_pending_tm(&mut self) {
+        while let Ok(tm) = self.tm_funnel_rx.try_recv() {
+            self.tm_queue.push_classified(tm);
+        }
+    }
+
+    fn handle_tm(&mut self, mut tm: PacketAsVec) {
+        // Read the TM, set sequence counter and message counter, and finally update
+        // the CRC.
+        let zero_copy_writer = PusTmZeroCopyWriter::new(&mut tm.packet, MIN_CDS_FIELD_LEN)
+            .expect("Creating TM zero copy writer failed");
+        self.common.apply_packet_processing(zero_copy_writer);
+        self.common.sync_tm_tcp_source.add_tm(&tm.packet);
+        if self.tm_server_tx.send(tm).is_err() {
+            // The TM server might already be stopped during a shutdown.
+            warn!("Sending TM to server failed");
         }
     }
 }
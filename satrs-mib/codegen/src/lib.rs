@@ -9,6 +9,9 @@ use syn::{parse_macro_input, ItemConst, LitStr};
 /// [`satrs_mib::res_code::ResultU16Info`](https://docs.rs/satrs-mib/latest/satrs_mib/res_code/struct.ResultU16Info.html)
 /// instances. These instances can then be used for tasks like generating CSVs or YAML files with
 /// the list of all result codes. This information is valuable for both operators and developers.
+///
+/// The optional `info` attribute adds a description and the optional `group` attribute adds the
+/// name of the result code group, for example `#[resultcode(group = "TMTC", info = "...")]`.
 #[proc_macro_attribute]
 pub fn resultcode(
     args: proc_macro::TokenStream,
@@ -16,10 +19,14 @@ pub fn resultcode(
 ) -> proc_macro::TokenStream {
     // Handle attributes first.
     let mut info_str: Option<LitStr> = None;
+    let mut group_str: Option<LitStr> = None;
     let res_code_parser = syn::meta::parser(|meta| {
         if meta.path.is_ident("info") {
             info_str = Some(meta.value()?.parse()?);
             Ok(())
+        } else if meta.path.is_ident("group") {
+            group_str = Some(meta.value()?.parse()?);
+            Ok(())
         } else {
             Err(meta.error("unsupported property for resultcode attribute"))
         }
@@ -32,13 +39,13 @@ pub fn resultcode(
     let name_as_str = result_code_name.to_string();
     let gen_struct_name = format_ident!("{}_EXT", result_code_name);
     let info_str = info_str.map_or(String::from(""), |v| v.value());
-    // TODO: Group string
+    let group_str = group_str.map_or(String::from(""), |v| v.value());
     let generated_struct = quote! {
         const #gen_struct_name: satrs_mib::res_code::ResultU16Info =
             satrs_mib::res_code::ResultU16Info::const_new(
                 #name_as_str,
                 &#result_code_name,
-                "",
+                #group_str,
                 #info_str
             );
     };
@@ -0,0 +1,11 @@
+//! Basic check which verifies that the group attribute can be used together with the info
+use satrs_mib::resultcode;
+use satrs_shared::res_code::ResultU16;
+
+#[resultcode(
+    group = "TMTC",
+    info = "This is a test result where the first parameter is foo"
+)]
+const _TEST_RESULT: ResultU16 = ResultU16::new(0, 1);
+
+fn main() {}
@@ -6,3 +6,5 @@ extern crate std;
 
 pub use satrs_mib_codegen::*;
 pub mod res_code;
+#[cfg(feature = "std")]
+pub mod tm_def;
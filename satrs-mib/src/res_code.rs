@@ -55,11 +55,51 @@ impl From<ResultU16Info> for ResultU16InfoSerializable {
     }
 }
 
+/// Two result code information objects which have the same raw result code.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ResultU16Collision {
+    pub raw: u16,
+    pub first: &'static str,
+    pub second: &'static str,
+}
+
+impl core::fmt::Display for ResultU16Collision {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "result codes {} and {} have the same raw value {:#06x}",
+            self.first, self.second, self.raw
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ResultU16Collision {}
+
+/// Find the first pair of result codes which have the same group ID and unique ID. A result code
+/// which is listed twice is reported as well. This function does not require heap allocations.
+pub fn find_collision(codes: &[ResultU16Info]) -> Option<ResultU16Collision> {
+    for (idx, first) in codes.iter().enumerate() {
+        for second in &codes[idx + 1..] {
+            if first.result.raw() == second.result.raw() {
+                return Some(ResultU16Collision {
+                    raw: first.result.raw(),
+                    first: first.name,
+                    second: second.name,
+                });
+            }
+        }
+    }
+    None
+}
+
 #[cfg(feature = "std")]
 pub mod stdmod {
     use super::*;
+    use std::collections::HashMap;
     use std::fs::File;
     use std::io;
+    use std::vec::Vec;
 
     pub fn print_resultcodes_as_csv(
         writer_builder: csv::WriterBuilder,
@@ -86,6 +126,104 @@ pub mod stdmod {
         wtr.flush()?;
         Ok(())
     }
+
+    /// Registry for all result codes of an application. The result codes are usually declared
+    /// in separate lists per group, which can be combined with this registry to check for
+    /// collisions between them and to export them as one list.
+    #[derive(Debug, Default, Clone)]
+    pub struct ResultU16Registry {
+        codes: HashMap<u16, ResultU16Info>,
+    }
+
+    impl ResultU16Registry {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Create a registry from several lists of result codes.
+        ///
+        /// This function is intended to be called at startup and panics if two result codes
+        /// collide. Use [Self::register] to handle collisions gracefully.
+        pub fn with_codes(lists: &[&[ResultU16Info]]) -> Self {
+            let mut registry = Self::new();
+            for list in lists {
+                registry
+                    .register_all(list)
+                    .unwrap_or_else(|e| panic!("invalid result codes: {e}"));
+            }
+            registry
+        }
+
+        /// Register a new result code. The code is rejected if a code with the same raw value
+        /// was already registered.
+        pub fn register(&mut self, info: ResultU16Info) -> Result<(), ResultU16Collision> {
+            let raw = info.result.raw();
+            if let Some(existing) = self.codes.get(&raw) {
+                return Err(ResultU16Collision {
+                    raw,
+                    first: existing.name,
+                    second: info.name,
+                });
+            }
+            self.codes.insert(raw, info);
+            Ok(())
+        }
+
+        pub fn register_all(&mut self, codes: &[ResultU16Info]) -> Result<(), ResultU16Collision> {
+            for info in codes {
+                self.register(*info)?;
+            }
+            Ok(())
+        }
+
+        pub fn len(&self) -> usize {
+            self.codes.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.codes.is_empty()
+        }
+
+        pub fn get(&self, result: ResultU16) -> Option<&ResultU16Info> {
+            self.codes.get(&result.raw())
+        }
+
+        pub fn name_of(&self, result: ResultU16) -> Option<&'static str> {
+            self.get(result).map(|info| info.name)
+        }
+
+        /// All registered result codes sorted by their raw value.
+        pub fn codes(&self) -> Vec<ResultU16Info> {
+            let mut codes: Vec<ResultU16Info> = self.codes.values().copied().collect();
+            codes.sort_unstable_by_key(|info| info.result.raw());
+            codes
+        }
+
+        /// Export all result codes sorted by their raw value as CSV.
+        pub fn write_csv(
+            &self,
+            writer_builder: csv::WriterBuilder,
+            writer: impl io::Write,
+        ) -> Result<(), csv::Error> {
+            let mut wtr = writer_builder.from_writer(writer);
+            for info in self.codes() {
+                wtr.serialize(ResultU16InfoSerializable::from(info))?;
+            }
+            wtr.flush()?;
+            Ok(())
+        }
+
+        /// Export all result codes sorted by their raw value as a JSON array. The field names
+        /// are the same as the CSV column names.
+        pub fn write_json(&self, writer: impl io::Write) -> Result<(), serde_json::Error> {
+            let codes: Vec<ResultU16InfoSerializable> = self
+                .codes()
+                .into_iter()
+                .map(ResultU16InfoSerializable::from)
+                .collect();
+            serde_json::to_writer_pretty(writer, &codes)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -120,6 +258,13 @@ mod tests {
         NOT_ENOUGH_APP_DATA_EXT,
     ];
 
+    #[resultcode(group = "HK")]
+    pub const UNKNOWN_TARGET_ID: ResultU16 = ResultU16::new(1, 0);
+    #[resultcode]
+    pub const COLLIDING_CODE: ResultU16 = ResultU16::new(GroupId::Tmtc as u8, 1);
+
+    pub const HK_RESULTS: &[ResultU16Info] = &[UNKNOWN_TARGET_ID_EXT];
+
     const CSV_NAME: &str = "dummy.csv";
 
     #[test]
@@ -166,4 +311,94 @@ mod tests {
         }
         std::fs::remove_file(Path::new("dummy.csv")).expect("Removing dummy csv failed");
     }
+
+    #[test]
+    fn test_find_collision() {
+        assert!(find_collision(TMTC_RESULTS).is_none());
+        let codes = [
+            INVALID_PUS_SERVICE_EXT,
+            INVALID_PUS_SUBSERVICE_EXT,
+            COLLIDING_CODE_EXT,
+        ];
+        assert_eq!(
+            find_collision(&codes),
+            Some(ResultU16Collision {
+                raw: 0x0001,
+                first: "INVALID_PUS_SUBSERVICE",
+                second: "COLLIDING_CODE"
+            })
+        );
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = ResultU16Registry::with_codes(&[HK_RESULTS, TMTC_RESULTS]);
+        assert_eq!(registry.len(), 4);
+        assert_eq!(
+            registry.name_of(NOT_ENOUGH_APP_DATA),
+            Some("NOT_ENOUGH_APP_DATA")
+        );
+        assert_eq!(registry.get(UNKNOWN_TARGET_ID).unwrap().group_str, "HK");
+        let names: std::vec::Vec<&str> = registry.codes().iter().map(|info| info.name).collect();
+        assert_eq!(
+            names,
+            [
+                "INVALID_PUS_SERVICE",
+                "INVALID_PUS_SUBSERVICE",
+                "NOT_ENOUGH_APP_DATA",
+                "UNKNOWN_TARGET_ID"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_registry_collision() {
+        let mut registry = ResultU16Registry::with_codes(&[TMTC_RESULTS]);
+        assert_eq!(
+            registry.register(COLLIDING_CODE_EXT),
+            Err(ResultU16Collision {
+                raw: 0x0001,
+                first: "INVALID_PUS_SUBSERVICE",
+                second: "COLLIDING_CODE"
+            })
+        );
+        assert_eq!(registry.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid result codes")]
+    fn test_registry_panics_on_collision() {
+        ResultU16Registry::with_codes(&[TMTC_RESULTS, &[COLLIDING_CODE_EXT]]);
+    }
+
+    #[test]
+    fn test_registry_csv_export() {
+        let registry = ResultU16Registry::with_codes(&[HK_RESULTS, TMTC_RESULTS]);
+        let mut wtrb = csv::WriterBuilder::new();
+        wtrb.delimiter(b';');
+        let mut csv = std::vec::Vec::new();
+        registry.write_csv(wtrb, &mut csv).unwrap();
+        let csv = std::string::String::from_utf8(csv).unwrap();
+        let lines: std::vec::Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "raw;group_id;unique_id;name;group_str;info");
+        assert_eq!(lines[4], "0x0100;0x01;0x00;UNKNOWN_TARGET_ID;HK;");
+    }
+
+    #[test]
+    fn test_registry_json_export() {
+        let registry = ResultU16Registry::with_codes(&[HK_RESULTS, TMTC_RESULTS]);
+        let mut json = std::vec::Vec::new();
+        registry.write_json(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let codes = json.as_array().unwrap();
+        assert_eq!(codes.len(), 4);
+        assert_eq!(codes[2]["raw"], "0x0002");
+        assert_eq!(codes[2]["name"], "NOT_ENOUGH_APP_DATA");
+        assert_eq!(
+            codes[2]["info"],
+            "Not enough data inside the TC application data field"
+        );
+        assert_eq!(codes[3]["group_str"], "HK");
+    }
 }
@@ -0,0 +1,719 @@
+//! # Telemetry definition export
+//!
+//! This module allows describing the layout of the TM packets generated by the On-Board
+//! Software (OBSW) inside the OBSW code and exporting it for the generation of the ground
+//! database. The OBSW code stays the single source of truth for the packet layouts.
+//!
+//! Each TM packet is described by a [TmPacketDef], which contains the PUS service and
+//! subservice, the APID and up to two keys which discriminate packets of the same type, for
+//! example the target ID and set ID of housekeeping packets or the raw event ID of event packets.
+//! The source data of the packet is described as a list of [ParamDef]s, which are placed one
+//! after another. The [TmPacketDef::hk_set], [TmPacketDef::event] and [pus_verification_packets]
+//! helpers create definitions which already contain the fixed part of the respective packets.
+//! With the `satrs` feature, [TmPacketDef::from_event_definition] creates the definition of an
+//! event report from a sat-rs `EventDefinition`, which is usually declared with the
+//! `satrs::event_definitions` macro.
+//!
+//! All definitions are collected inside a [TmDefRegistry], which rejects duplicate packets and
+//! exports the definitions
+//!
+//!  - as JSON with [TmDefRegistry::write_json].
+//!  - as tables modelled after the SCOS-2000 MIB tables with [TmDefRegistry::write_pid_table],
+//!    [TmDefRegistry::write_plf_table] and [TmDefRegistry::write_pcf_table]. The packets are
+//!    numbered in registration order starting with 1 to create the SCOS-2000 packet ID (SPID),
+//!    and parameter offsets are given relative to the start of the source data.
+//!
+//! # Example
+//!
+//! ```
+//! use satrs_mib::tm_def::{ParamDef, ParamType, TmDefRegistry, TmPacketDef};
+//!
+//! const TEMPERATURE: ParamDef = ParamDef::new("temperature", ParamType::F32).with_unit("degC");
+//!
+//! let mut registry = TmDefRegistry::new();
+//! registry
+//!     .add_packet(TmPacketDef::hk_set("TEMP_SENSOR_SET", 0x67, 1, 0).with_param(TEMPERATURE))
+//!     .unwrap();
+//! let hk_set = &registry.packets()[0];
+//! // The temperature is placed after the target ID and the set ID.
+//! assert_eq!(hk_set.params()[2].offset, 8);
+//! assert_eq!(hk_set.source_data_len(), 12);
+//! ```
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "satrs")]
+use satrs::event_registry::{EventDefinition, EventParamType};
+use serde::Serialize;
+use std::format;
+use std::io;
+use std::string::String;
+use std::vec;
+use std::vec::Vec;
+
+pub const PUS_VERIFICATION_SERVICE: u8 = 1;
+pub const PUS_HK_SERVICE: u8 = 3;
+pub const PUS_HK_REPORT_SUBSERVICE: u8 = 25;
+pub const PUS_EVENT_SERVICE: u8 = 5;
+
+/// Type of a TM parameter. All multi-byte types are transferred in big endian format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParamType {
+    /// Boolean which occupies one byte.
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    /// Byte array with a fixed length.
+    Bytes(usize),
+}
+
+impl ParamType {
+    /// Size of the parameter in bytes.
+    pub const fn size(&self) -> usize {
+        match self {
+            ParamType::Bool | ParamType::U8 | ParamType::I8 => 1,
+            ParamType::U16 | ParamType::I16 => 2,
+            ParamType::U32 | ParamType::I32 | ParamType::F32 => 4,
+            ParamType::U64 | ParamType::I64 | ParamType::F64 => 8,
+            ParamType::Bytes(len) => *len,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            ParamType::Bool => "bool",
+            ParamType::U8 => "u8",
+            ParamType::U16 => "u16",
+            ParamType::U32 => "u32",
+            ParamType::U64 => "u64",
+            ParamType::I8 => "i8",
+            ParamType::I16 => "i16",
+            ParamType::I32 => "i32",
+            ParamType::I64 => "i64",
+            ParamType::F32 => "f32",
+            ParamType::F64 => "f64",
+            ParamType::Bytes(_) => "bytes",
+        }
+    }
+
+    /// SCOS-2000 parameter type code (PTC) and parameter format code (PFC). Booleans are
+    /// described as 8 bit unsigned integers because they occupy a full byte.
+    pub const fn ptc_pfc(&self) -> (u8, usize) {
+        match self {
+            ParamType::Bool | ParamType::U8 => (3, 4),
+            ParamType::U16 => (3, 12),
+            ParamType::U32 => (3, 14),
+            ParamType::U64 => (3, 16),
+            ParamType::I8 => (4, 4),
+            ParamType::I16 => (4, 12),
+            ParamType::I32 => (4, 14),
+            ParamType::I64 => (4, 16),
+            ParamType::F32 => (5, 1),
+            ParamType::F64 => (5, 2),
+            ParamType::Bytes(len) => (7, *len),
+        }
+    }
+}
+
+/// Definition of a single TM parameter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParamDef {
+    pub name: &'static str,
+    pub param_type: ParamType,
+    pub unit: &'static str,
+    pub info: &'static str,
+}
+
+impl ParamDef {
+    pub const fn new(name: &'static str, param_type: ParamType) -> Self {
+        Self {
+            name,
+            param_type,
+            unit: "",
+            info: "",
+        }
+    }
+
+    pub const fn with_unit(mut self, unit: &'static str) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub const fn with_info(mut self, info: &'static str) -> Self {
+        self.info = info;
+        self
+    }
+}
+
+/// Combined size of the given parameters in bytes. This can be used by packet generators to
+/// size their buffers from the same definitions which are exported.
+pub const fn params_len(params: &[ParamDef]) -> usize {
+    let mut len = 0;
+    let mut idx = 0;
+    while idx < params.len() {
+        len += params[idx].param_type.size();
+        idx += 1;
+    }
+    len
+}
+
+/// Parameter of a [TmPacketDef] together with its offset inside the source data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PlacedParam {
+    pub def: ParamDef,
+    pub offset: usize,
+}
+
+/// Definition of a TM packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmPacketDef {
+    pub name: &'static str,
+    pub info: &'static str,
+    pub apid: u16,
+    pub service: u8,
+    pub subservice: u8,
+    /// Value which discriminates packets with the same APID, service and subservice, for example
+    /// the target ID of housekeeping packets or the raw event ID of event packets.
+    pub key: Option<u32>,
+    /// Second discriminating value, for example the set ID of housekeeping packets.
+    pub secondary_key: Option<u32>,
+    params: Vec<PlacedParam>,
+    source_data_len: usize,
+}
+
+impl TmPacketDef {
+    pub fn new(name: &'static str, apid: u16, service: u8, subservice: u8) -> Self {
+        Self {
+            name,
+            info: "",
+            apid,
+            service,
+            subservice,
+            key: None,
+            secondary_key: None,
+            params: Vec::new(),
+            source_data_len: 0,
+        }
+    }
+
+    /// Definition of the TM[3,25] housekeeping report of a set which is generated by a
+    /// [HkSetRegistry](https://docs.rs/satrs/latest/satrs/hk/struct.HkSetRegistry.html). The
+    /// source data starts with the target ID and the set ID, which are used as the keys, and the
+    /// set parameters have to be added afterwards.
+    pub fn hk_set(name: &'static str, apid: u16, target_id: u32, set_id: u32) -> Self {
+        Self::new(name, apid, PUS_HK_SERVICE, PUS_HK_REPORT_SUBSERVICE)
+            .with_key(target_id)
+            .with_secondary_key(set_id)
+            .with_param(ParamDef::new("target_id", ParamType::U32))
+            .with_param(ParamDef::new("set_id", ParamType::U32))
+    }
+
+    /// Definition of the PUS event report of an event with the given raw 32 bit event ID. The
+    /// subservice is derived from the severity which is encoded in the two most significant bits
+    /// of the event ID. The source data starts with the event ID, and the parameters of the
+    /// auxiliary data can be added afterwards. The raw event ID is used as the key.
+    pub fn event(name: &'static str, apid: u16, raw_event_id: u32) -> Self {
+        let subservice = (raw_event_id >> 30) as u8 + 1;
+        Self::new(name, apid, PUS_EVENT_SERVICE, subservice)
+            .with_key(raw_event_id)
+            .with_param(ParamDef::new("event_id", ParamType::U32))
+    }
+
+    /// Definition of the PUS event report of a sat-rs event definition. The name and the
+    /// description of the event are used for the packet, and the auxiliary data is described by
+    /// the parameter type of the event. Parameter types without a fixed size, for example
+    /// strings, are not described.
+    #[cfg(feature = "satrs")]
+    pub fn from_event_definition(apid: u16, definition: &EventDefinition) -> Self {
+        let packet =
+            Self::event(definition.name, apid, definition.raw()).with_info(definition.description);
+        let param = |param_type| ParamDef::new("param", param_type);
+        match definition.param_type {
+            EventParamType::U8 => packet.with_param(param(ParamType::U8)),
+            EventParamType::U16 => packet.with_param(param(ParamType::U16)),
+            EventParamType::U32 => packet.with_param(param(ParamType::U32)),
+            EventParamType::U64 => packet.with_param(param(ParamType::U64)),
+            EventParamType::I8 => packet.with_param(param(ParamType::I8)),
+            EventParamType::I16 => packet.with_param(param(ParamType::I16)),
+            EventParamType::I32 => packet.with_param(param(ParamType::I32)),
+            EventParamType::I64 => packet.with_param(param(ParamType::I64)),
+            EventParamType::F32 => packet.with_param(param(ParamType::F32)),
+            EventParamType::F64 => packet.with_param(param(ParamType::F64)),
+            EventParamType::U32Pair => packet.with_params(&[
+                ParamDef::new("param_0", ParamType::U32),
+                ParamDef::new("param_1", ParamType::U32),
+            ]),
+            EventParamType::None
+            | EventParamType::EcssEnum
+            | EventParamType::Bytes
+            | EventParamType::String => packet,
+        }
+    }
+
+    pub fn with_info(mut self, info: &'static str) -> Self {
+        self.info = info;
+        self
+    }
+
+    pub fn with_key(mut self, key: u32) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn with_secondary_key(mut self, key: u32) -> Self {
+        self.secondary_key = Some(key);
+        self
+    }
+
+    /// Append a parameter to the source data.
+    pub fn with_param(mut self, param: ParamDef) -> Self {
+        self.params.push(PlacedParam {
+            def: param,
+            offset: self.source_data_len,
+        });
+        self.source_data_len += param.param_type.size();
+        self
+    }
+
+    /// Append several parameters to the source data.
+    pub fn with_params(self, params: &[ParamDef]) -> Self {
+        params
+            .iter()
+            .fold(self, |def, param| def.with_param(*param))
+    }
+
+    pub fn params(&self) -> &[PlacedParam] {
+        &self.params
+    }
+
+    /// Length of the source data described by the parameters.
+    pub fn source_data_len(&self) -> usize {
+        self.source_data_len
+    }
+
+    fn id(&self) -> (u16, u8, u8, Option<u32>, Option<u32>) {
+        (
+            self.apid,
+            self.service,
+            self.subservice,
+            self.key,
+            self.secondary_key,
+        )
+    }
+}
+
+/// Definitions of the PUS verification reports TM[1,1] to TM[1,8] for the given APID as they are
+/// generated by the sat-rs verification reporter. All reports start with the request ID of the
+/// verified telecommand. Step reports contain a one byte step number, and failure reports
+/// contain a two byte failure code.
+pub fn pus_verification_packets(apid: u16) -> Vec<TmPacketDef> {
+    const REQUEST_ID: ParamDef = ParamDef::new("request_id", ParamType::U32)
+        .with_info("Packet ID and sequence control of the verified telecommand");
+    const STEP: ParamDef = ParamDef::new("step", ParamType::U8);
+    const FAILURE_CODE: ParamDef = ParamDef::new("failure_code", ParamType::U16);
+    let report = |name, subservice, params: &[ParamDef]| {
+        TmPacketDef::new(name, apid, PUS_VERIFICATION_SERVICE, subservice)
+            .with_param(REQUEST_ID)
+            .with_params(params)
+    };
+    vec![
+        report("ACCEPTANCE_SUCCESS", 1, &[]),
+        report("ACCEPTANCE_FAILURE", 2, &[FAILURE_CODE]),
+        report("START_SUCCESS", 3, &[]),
+        report("START_FAILURE", 4, &[FAILURE_CODE]),
+        report("STEP_SUCCESS", 5, &[STEP]),
+        report("STEP_FAILURE", 6, &[STEP, FAILURE_CODE]),
+        report("COMPLETION_SUCCESS", 7, &[]),
+        report("COMPLETION_FAILURE", 8, &[FAILURE_CODE]),
+    ]
+}
+
+/// Two packet definitions which have the same APID, service, subservice and keys.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TmDefCollision {
+    pub first: &'static str,
+    pub second: &'static str,
+}
+
+impl Display for TmDefCollision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "TM packets {} and {} can not be distinguished",
+            self.first, self.second
+        )
+    }
+}
+
+impl std::error::Error for TmDefCollision {}
+
+#[derive(Debug, Serialize)]
+struct ParamInfoSerializable {
+    name: &'static str,
+    #[serde(rename = "type")]
+    type_name: &'static str,
+    size: usize,
+    offset: usize,
+    unit: &'static str,
+    info: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct TmPacketInfoSerializable {
+    spid: u32,
+    name: &'static str,
+    apid: u16,
+    service: u8,
+    subservice: u8,
+    key: Option<u32>,
+    secondary_key: Option<u32>,
+    source_data_len: usize,
+    info: &'static str,
+    params: Vec<ParamInfoSerializable>,
+}
+
+/// Packet identification table entry.
+#[derive(Debug, Serialize)]
+struct PidEntry {
+    pid_type: u8,
+    pid_stype: u8,
+    pid_apid: u16,
+    pid_pi1_val: Option<u32>,
+    pid_pi2_val: Option<u32>,
+    pid_spid: u32,
+    pid_descr: &'static str,
+}
+
+/// Parameter location table entry.
+#[derive(Debug, Serialize)]
+struct PlfEntry {
+    plf_name: String,
+    plf_spid: u32,
+    plf_offby: usize,
+    plf_offbi: u8,
+}
+
+/// Parameter characteristics table entry.
+#[derive(Debug, Serialize)]
+struct PcfEntry {
+    pcf_name: String,
+    pcf_descr: &'static str,
+    pcf_unit: &'static str,
+    pcf_ptc: u8,
+    pcf_pfc: usize,
+}
+
+/// Registry for the definitions of all TM packets of an application.
+#[derive(Debug, Default, Clone)]
+pub struct TmDefRegistry {
+    packets: Vec<TmPacketDef>,
+}
+
+impl TmDefRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new packet definition. The definition is rejected if a packet with the same
+    /// APID, service, subservice and keys was already registered.
+    pub fn add_packet(&mut self, packet: TmPacketDef) -> Result<(), TmDefCollision> {
+        if let Some(existing) = self.packets.iter().find(|p| p.id() == packet.id()) {
+            return Err(TmDefCollision {
+                first: existing.name,
+                second: packet.name,
+            });
+        }
+        self.packets.push(packet);
+        Ok(())
+    }
+
+    pub fn add_packets(
+        &mut self,
+        packets: impl IntoIterator<Item = TmPacketDef>,
+    ) -> Result<(), TmDefCollision> {
+        for packet in packets {
+            self.add_packet(packet)?;
+        }
+        Ok(())
+    }
+
+    /// Register the event reports of all given sat-rs event definitions, see
+    /// [TmPacketDef::from_event_definition].
+    #[cfg(feature = "satrs")]
+    pub fn add_event_definitions(
+        &mut self,
+        apid: u16,
+        definitions: &[EventDefinition],
+    ) -> Result<(), TmDefCollision> {
+        self.add_packets(
+            definitions
+                .iter()
+                .map(|definition| TmPacketDef::from_event_definition(apid, definition)),
+        )
+    }
+
+    /// All packet definitions in registration order.
+    pub fn packets(&self) -> &[TmPacketDef] {
+        &self.packets
+    }
+
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    fn packets_with_spid(&self) -> impl Iterator<Item = (u32, &TmPacketDef)> {
+        self.packets
+            .iter()
+            .enumerate()
+            .map(|(idx, packet)| (idx as u32 + 1, packet))
+    }
+
+    /// Export all packet definitions including their parameters as a JSON array.
+    pub fn write_json(&self, writer: impl io::Write) -> Result<(), serde_json::Error> {
+        let packets: Vec<TmPacketInfoSerializable> = self
+            .packets_with_spid()
+            .map(|(spid, packet)| TmPacketInfoSerializable {
+                spid,
+                name: packet.name,
+                apid: packet.apid,
+                service: packet.service,
+                subservice: packet.subservice,
+                key: packet.key,
+                secondary_key: packet.secondary_key,
+                source_data_len: packet.source_data_len,
+                info: packet.info,
+                params: packet
+                    .params
+                    .iter()
+                    .map(|param| ParamInfoSerializable {
+                        name: param.def.name,
+                        type_name: param.def.param_type.name(),
+                        size: param.def.param_type.size(),
+                        offset: param.offset,
+                        unit: param.def.unit,
+                        info: param.def.info,
+                    })
+                    .collect(),
+            })
+            .collect();
+        serde_json::to_writer_pretty(writer, &packets)
+    }
+
+    /// Export the packet identification table with one row per packet.
+    pub fn write_pid_table(
+        &self,
+        writer_builder: csv::WriterBuilder,
+        writer: impl io::Write,
+    ) -> Result<(), csv::Error> {
+        let mut wtr = writer_builder.from_writer(writer);
+        for (spid, packet) in self.packets_with_spid() {
+            wtr.serialize(PidEntry {
+                pid_type: packet.service,
+                pid_stype: packet.subservice,
+                pid_apid: packet.apid,
+                pid_pi1_val: packet.key,
+                pid_pi2_val: packet.secondary_key,
+                pid_spid: spid,
+                pid_descr: packet.name,
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Export the parameter location table with one row per packet parameter. The parameter
+    /// names are qualified with the packet name.
+    pub fn write_plf_table(
+        &self,
+        writer_builder: csv::WriterBuilder,
+        writer: impl io::Write,
+    ) -> Result<(), csv::Error> {
+        let mut wtr = writer_builder.from_writer(writer);
+        for (spid, packet) in self.packets_with_spid() {
+            for param in &packet.params {
+                wtr.serialize(PlfEntry {
+                    plf_name: qualified_name(packet, param),
+                    plf_spid: spid,
+                    plf_offby: param.offset,
+                    plf_offbi: 0,
+                })?;
+            }
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Export the parameter characteristics table with one row per packet parameter. The
+    /// parameter names are qualified with the packet name.
+    pub fn write_pcf_table(
+        &self,
+        writer_builder: csv::WriterBuilder,
+        writer: impl io::Write,
+    ) -> Result<(), csv::Error> {
+        let mut wtr = writer_builder.from_writer(writer);
+        for packet in &self.packets {
+            for param in &packet.params {
+                let (ptc, pfc) = param.def.param_type.ptc_pfc();
+                wtr.serialize(PcfEntry {
+                    pcf_name: qualified_name(packet, param),
+                    pcf_descr: param.def.info,
+                    pcf_unit: param.def.unit,
+                    pcf_ptc: ptc,
+                    pcf_pfc: pfc,
+                })?;
+            }
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+fn qualified_name(packet: &TmPacketDef, param: &PlacedParam) -> String {
+    format!("{}.{}", packet.name, param.def.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEATER_PARAMS: &[ParamDef] = &[
+        ParamDef::new("enabled", ParamType::Bool),
+        ParamDef::new("duty_cycle", ParamType::U8).with_unit("%"),
+    ];
+
+    fn test_registry() -> TmDefRegistry {
+        let mut registry = TmDefRegistry::new();
+        registry
+            .add_packet(TmPacketDef::hk_set("HEATER_SET", 0x67, 5, 0).with_params(HEATER_PARAMS))
+            .unwrap();
+        registry
+            .add_packet(
+                TmPacketDef::event("OVERTEMPERATURE", 0x67, 0xC001_0002)
+                    .with_param(ParamDef::new("temperature", ParamType::F32).with_unit("degC")),
+            )
+            .unwrap();
+        registry
+    }
+
+    fn table_lines(export: impl Fn(csv::WriterBuilder, &mut Vec<u8>)) -> Vec<String> {
+        let mut wtrb = csv::WriterBuilder::new();
+        wtrb.delimiter(b'\t');
+        let mut table = Vec::new();
+        export(wtrb, &mut table);
+        String::from_utf8(table)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_packet_layouts() {
+        assert_eq!(params_len(HEATER_PARAMS), 2);
+        let registry = test_registry();
+        let hk_set = &registry.packets()[0];
+        assert_eq!(hk_set.key, Some(5));
+        assert_eq!(hk_set.secondary_key, Some(0));
+        assert_eq!(hk_set.source_data_len(), 10);
+        assert_eq!(hk_set.params()[3].offset, 9);
+        let event = &registry.packets()[1];
+        // High severity.
+        assert_eq!(event.subservice, 4);
+        assert_eq!(event.params()[1].offset, 4);
+
+        let verification = pus_verification_packets(0x02);
+        assert_eq!(verification.len(), 8);
+        assert_eq!(verification[5].name, "STEP_FAILURE");
+        assert_eq!(verification[5].source_data_len(), 7);
+        assert!(verification
+            .iter()
+            .all(|p| p.params()[0].def.name == "request_id"));
+    }
+
+    #[test]
+    fn test_collision() {
+        let mut registry = test_registry();
+        assert_eq!(
+            registry.add_packet(TmPacketDef::hk_set("OTHER_SET", 0x67, 5, 0)),
+            Err(TmDefCollision {
+                first: "HEATER_SET",
+                second: "OTHER_SET"
+            })
+        );
+        // The same set ID is allowed for another target.
+        registry
+            .add_packet(TmPacketDef::hk_set("OTHER_SET", 0x67, 6, 0))
+            .unwrap();
+        registry
+            .add_packets(pus_verification_packets(0x67))
+            .unwrap();
+        assert_eq!(registry.len(), 11);
+    }
+
+    #[test]
+    fn test_json_export() {
+        let mut json = Vec::new();
+        test_registry().write_json(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let packets = json.as_array().unwrap();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0]["spid"], 1);
+        assert_eq!(packets[0]["service"], 3);
+        assert_eq!(packets[0]["subservice"], 25);
+        assert_eq!(packets[0]["params"][3]["name"], "duty_cycle");
+        assert_eq!(packets[0]["params"][3]["type"], "u8");
+        assert_eq!(packets[0]["params"][3]["unit"], "%");
+        assert_eq!(packets[1]["key"], 0xC001_0002_u32);
+    }
+
+    #[test]
+    fn test_scos_table_export() {
+        let registry = test_registry();
+        let pid = table_lines(|wtrb, buf| registry.write_pid_table(wtrb, buf).unwrap());
+        assert_eq!(pid.len(), 3);
+        assert_eq!(
+            pid[0],
+            "pid_type\tpid_stype\tpid_apid\tpid_pi1_val\tpid_pi2_val\tpid_spid\tpid_descr"
+        );
+        assert_eq!(pid[1], "3\t25\t103\t5\t0\t1\tHEATER_SET");
+        assert_eq!(pid[2], "5\t4\t103\t3221291010\t\t2\tOVERTEMPERATURE");
+        let plf = table_lines(|wtrb, buf| registry.write_plf_table(wtrb, buf).unwrap());
+        assert_eq!(plf.len(), 7);
+        assert_eq!(plf[4], "HEATER_SET.duty_cycle\t1\t9\t0");
+        assert_eq!(plf[6], "OVERTEMPERATURE.temperature\t2\t4\t0");
+        let pcf = table_lines(|wtrb, buf| registry.write_pcf_table(wtrb, buf).unwrap());
+        assert_eq!(pcf[6], "OVERTEMPERATURE.temperature\t\tdegC\t5\t1");
+    }
+
+    #[cfg(feature = "satrs")]
+    #[test]
+    fn test_event_definitions() {
+        use satrs::error_report::INTERNAL_ERROR_EVENT_DEFINITIONS;
+
+        let mut registry = TmDefRegistry::new();
+        registry
+            .add_event_definitions(0x02, INTERNAL_ERROR_EVENT_DEFINITIONS)
+            .unwrap();
+        assert_eq!(registry.len(), INTERNAL_ERROR_EVENT_DEFINITIONS.len());
+        let definition = &INTERNAL_ERROR_EVENT_DEFINITIONS[0];
+        let packet = &registry.packets()[0];
+        assert_eq!(packet.name, definition.name);
+        assert_eq!(packet.info, definition.description);
+        assert_eq!(packet.key, Some(definition.raw()));
+        // Event ID followed by the two parameters.
+        assert_eq!(packet.source_data_len(), 12);
+        assert!(registry
+            .add_event_definitions(0x02, INTERNAL_ERROR_EVENT_DEFINITIONS)
+            .is_err());
+    }
+}
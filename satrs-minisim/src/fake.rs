@@ -0,0 +1,547 @@
+//! Simulated device models which run inside the process of the on-board software.
+//!
+//! The models of the mini simulator run inside a separate simulation process which is accessed
+//! via UDP. For integration tests and for running the example application without the
+//! simulator, this module provides simple device models which can be embedded directly into
+//! the communication interfaces of device handlers. They produce plausible data streams and
+//! accept commands, and some of them allow injecting faults to exercise FDIR handling.
+//!
+//! All models use a [FakeClock] for time-dependent behaviour. The clock can either follow the
+//! real time or be advanced manually, which allows deterministic tests. Devices which are
+//! connected to a [FakePowerBus] only produce valid data while their power switch is on. The
+//! [FakePcdu] switches the power bus.
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use satrs::power::SwitchStateBinary;
+use serde::{Deserialize, Serialize};
+
+use crate::acs::lis3mdl::MgmLis3MdlReply;
+use crate::acs::{MgmReplyCommon, MgmSensorValuesMicroTesla};
+use crate::eps::{PcduReply, PcduRequest, PcduSwitch, SwitchMapBinary, SwitchMapBinaryWrapper};
+
+/// Clock used by the fake device models.
+#[derive(Debug, Clone)]
+pub enum FakeClock {
+    /// Follows the real time since the creation of the clock.
+    RealTime(Instant),
+    /// Only advances when [FakeClock::advance] is called.
+    Manual(Arc<Mutex<Duration>>),
+}
+
+impl FakeClock {
+    pub fn new_real_time() -> Self {
+        Self::RealTime(Instant::now())
+    }
+
+    pub fn new_manual() -> Self {
+        Self::Manual(Arc::default())
+    }
+
+    /// Time elapsed since the creation of the clock.
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            FakeClock::RealTime(start) => start.elapsed(),
+            FakeClock::Manual(elapsed) => *elapsed.lock().unwrap(),
+        }
+    }
+
+    /// Advance a manual clock. This does nothing for a real-time clock.
+    pub fn advance(&self, duration: Duration) {
+        if let FakeClock::Manual(elapsed) = self {
+            *elapsed.lock().unwrap() += duration;
+        }
+    }
+}
+
+/// Shared power bus which stores the states of all PCDU switches. All switches are off
+/// initially.
+#[derive(Clone)]
+pub struct FakePowerBus(Arc<Mutex<SwitchMapBinary>>);
+
+impl Default for FakePowerBus {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(SwitchMapBinaryWrapper::default().0)))
+    }
+}
+
+impl FakePowerBus {
+    pub fn switch_state(&self, switch: PcduSwitch) -> SwitchStateBinary {
+        self.0
+            .lock()
+            .unwrap()
+            .get(&switch)
+            .copied()
+            .unwrap_or(SwitchStateBinary::Off)
+    }
+
+    pub fn set_switch_state(&self, switch: PcduSwitch, state: SwitchStateBinary) {
+        self.0.lock().unwrap().insert(switch, state);
+    }
+
+    pub fn switch_map(&self) -> SwitchMapBinary {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Power input of a fake device. Devices without a power switch are always powered.
+#[derive(Clone, Default)]
+struct PowerInput(Option<(FakePowerBus, PcduSwitch)>);
+
+impl PowerInput {
+    fn switch_state(&self) -> SwitchStateBinary {
+        match &self.0 {
+            Some((bus, switch)) => bus.switch_state(*switch),
+            None => SwitchStateBinary::On,
+        }
+    }
+
+    fn is_on(&self) -> bool {
+        self.switch_state() == SwitchStateBinary::On
+    }
+}
+
+/// Fake PCDU which handles the [PcduRequest]s by switching the [FakePowerBus].
+#[derive(Clone, Default)]
+pub struct FakePcdu {
+    power_bus: FakePowerBus,
+}
+
+impl FakePcdu {
+    pub fn new(power_bus: FakePowerBus) -> Self {
+        Self { power_bus }
+    }
+
+    pub fn power_bus(&self) -> &FakePowerBus {
+        &self.power_bus
+    }
+
+    /// Handle a PCDU request. Only the switch info request generates a reply.
+    pub fn handle_request(&self, request: &PcduRequest) -> Option<PcduReply> {
+        match request {
+            PcduRequest::SwitchDevice { switch, state } => {
+                self.power_bus.set_switch_state(*switch, *state);
+                None
+            }
+            PcduRequest::RequestSwitchInfo => {
+                Some(PcduReply::SwitchInfo(self.power_bus.switch_map()))
+            }
+        }
+    }
+}
+
+fn rotate_around_z(x: f32, y: f32, angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.sin_cos();
+    (x * cos - y * sin, x * sin + y * cos)
+}
+
+/// Fake LIS3MDL magnetometer. The measured field is a constant field in the inertial frame,
+/// seen from a spacecraft which rotates around its z-axis with a configurable rate.
+pub struct FakeMgmLis3Mdl {
+    clock: FakeClock,
+    power: PowerInput,
+    field: MgmSensorValuesMicroTesla,
+    rotation_rate_rad_per_s: f32,
+    stuck_values: Option<MgmSensorValuesMicroTesla>,
+}
+
+impl FakeMgmLis3Mdl {
+    pub const DEFAULT_FIELD: MgmSensorValuesMicroTesla = MgmSensorValuesMicroTesla {
+        x: 20.0,
+        y: -10.0,
+        z: 35.0,
+    };
+
+    /// Create a magnetometer which is powered by the [PcduSwitch::Mgm] switch of the given
+    /// power bus.
+    pub fn new(clock: FakeClock, power_bus: FakePowerBus) -> Self {
+        Self {
+            clock,
+            power: PowerInput(Some((power_bus, PcduSwitch::Mgm))),
+            field: Self::DEFAULT_FIELD,
+            rotation_rate_rad_per_s: 0.05,
+            stuck_values: None,
+        }
+    }
+
+    pub fn set_field(&mut self, field: MgmSensorValuesMicroTesla) {
+        self.field = field;
+    }
+
+    pub fn set_rotation_rate(&mut self, rotation_rate_rad_per_s: f32) {
+        self.rotation_rate_rad_per_s = rotation_rate_rad_per_s;
+    }
+
+    /// A stuck sensor keeps reporting the last measured values.
+    pub fn set_stuck(&mut self, stuck: bool) {
+        self.stuck_values = if stuck {
+            Some(self.current_field())
+        } else {
+            None
+        };
+    }
+
+    fn current_field(&self) -> MgmSensorValuesMicroTesla {
+        let angle = self.rotation_rate_rad_per_s * self.clock.elapsed().as_secs_f32();
+        let (x, y) = rotate_around_z(self.field.x, self.field.y, angle);
+        MgmSensorValuesMicroTesla {
+            x,
+            y,
+            z: self.field.z,
+        }
+    }
+
+    /// Current sensor values. A device which is switched off returns [None].
+    pub fn sensor_values(&self) -> Option<MgmSensorValuesMicroTesla> {
+        if !self.power.is_on() {
+            return None;
+        }
+        Some(self.stuck_values.unwrap_or_else(|| self.current_field()))
+    }
+
+    /// Reply of the device with the raw sensor values. The raw values of a device which is
+    /// switched off have all bits set.
+    pub fn reply(&self) -> MgmLis3MdlReply {
+        MgmLis3MdlReply::new(MgmReplyCommon {
+            switch_state: self.power.switch_state(),
+            sensor_values: self.stuck_values.unwrap_or_else(|| self.current_field()),
+        })
+    }
+}
+
+/// Reply of the [FakeSunSensor].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SunSensorReply {
+    pub switch_state: SwitchStateBinary,
+    /// Raw photodiode counts of the cells facing +x, -x, +y and -y.
+    pub channels: [u16; 4],
+}
+
+/// Fake coarse sun sensor with four photodiode cells. The cells face the x and y axes and are
+/// tilted by 45 degrees towards the +z boresight. The sun vector rotates around the z-axis
+/// together with the spacecraft.
+pub struct FakeSunSensor {
+    clock: FakeClock,
+    power: PowerInput,
+    sun_vector: [f32; 3],
+    rotation_rate_rad_per_s: f32,
+    eclipse: bool,
+}
+
+impl FakeSunSensor {
+    /// Raw count of a cell which is illuminated at normal incidence.
+    pub const MAX_COUNTS: u16 = 4095;
+    /// Raw count of a cell in darkness.
+    pub const DARK_COUNTS: u16 = 12;
+
+    pub fn new(clock: FakeClock) -> Self {
+        Self {
+            clock,
+            power: PowerInput::default(),
+            sun_vector: [0.6, 0.0, 0.8],
+            rotation_rate_rad_per_s: 0.05,
+            eclipse: false,
+        }
+    }
+
+    /// Connect the sensor to a switch of the power bus.
+    pub fn with_power_switch(mut self, power_bus: FakePowerBus, switch: PcduSwitch) -> Self {
+        self.power = PowerInput(Some((power_bus, switch)));
+        self
+    }
+
+    /// Set the sun vector in the body frame at time 0. The vector is normalized.
+    pub fn set_sun_vector(&mut self, sun_vector: [f32; 3]) {
+        let norm = sun_vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            self.sun_vector = sun_vector.map(|v| v / norm);
+        }
+    }
+
+    pub fn set_rotation_rate(&mut self, rotation_rate_rad_per_s: f32) {
+        self.rotation_rate_rad_per_s = rotation_rate_rad_per_s;
+    }
+
+    pub fn set_eclipse(&mut self, eclipse: bool) {
+        self.eclipse = eclipse;
+    }
+
+    pub fn reply(&self) -> SunSensorReply {
+        let switch_state = self.power.switch_state();
+        if switch_state == SwitchStateBinary::Off {
+            return SunSensorReply {
+                switch_state,
+                channels: [0; 4],
+            };
+        }
+        if self.eclipse {
+            return SunSensorReply {
+                switch_state,
+                channels: [Self::DARK_COUNTS; 4],
+            };
+        }
+        let angle = self.rotation_rate_rad_per_s * self.clock.elapsed().as_secs_f32();
+        let (x, y) = rotate_around_z(self.sun_vector[0], self.sun_vector[1], angle);
+        let z = self.sun_vector[2];
+        let tilt = core::f32::consts::FRAC_1_SQRT_2;
+        let cell_normals = [[tilt, 0.0], [-tilt, 0.0], [0.0, tilt], [0.0, -tilt]];
+        let mut channels = [0; 4];
+        for (channel, normal) in channels.iter_mut().zip(cell_normals) {
+            let cos_incidence = x * normal[0] + y * normal[1] + z * tilt;
+            let illuminated = (cos_incidence.max(0.0)
+                * (Self::MAX_COUNTS - Self::DARK_COUNTS) as f32)
+                .round() as u16;
+            *channel = Self::DARK_COUNTS + illuminated;
+        }
+        SunSensorReply {
+            switch_state,
+            channels,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReactionWheelRequest {
+    SetSpeed { speed_rpm: i32 },
+    RequestStatus,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReactionWheelStatus {
+    pub switch_state: SwitchStateBinary,
+    pub speed_rpm: i32,
+    pub target_speed_rpm: i32,
+    pub current_ma: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReactionWheelReply {
+    Status(ReactionWheelStatus),
+    /// The commanded speed exceeds the maximum speed of the wheel.
+    SpeedOutOfRange {
+        speed_rpm: i32,
+    },
+}
+
+/// Fake reaction wheel which accelerates towards the commanded speed with a constant
+/// acceleration. A wheel which is switched off is not driven and coasts down with the same
+/// rate. A jammed wheel stops immediately and draws its stall current.
+pub struct FakeReactionWheel {
+    clock: FakeClock,
+    power: PowerInput,
+    max_speed_rpm: i32,
+    acceleration_rpm_per_s: f32,
+    speed_rpm: f32,
+    target_speed_rpm: i32,
+    jammed: bool,
+    last_update: Duration,
+}
+
+impl FakeReactionWheel {
+    pub const IDLE_CURRENT_MA: u32 = 80;
+    pub const ACCELERATION_CURRENT_MA: u32 = 400;
+    pub const STALL_CURRENT_MA: u32 = 1200;
+
+    pub fn new(clock: FakeClock, max_speed_rpm: i32, acceleration_rpm_per_s: f32) -> Self {
+        let last_update = clock.elapsed();
+        Self {
+            clock,
+            power: PowerInput::default(),
+            max_speed_rpm,
+            acceleration_rpm_per_s,
+            speed_rpm: 0.0,
+            target_speed_rpm: 0,
+            jammed: false,
+            last_update,
+        }
+    }
+
+    /// Connect the wheel to a switch of the power bus.
+    pub fn with_power_switch(mut self, power_bus: FakePowerBus, switch: PcduSwitch) -> Self {
+        self.power = PowerInput(Some((power_bus, switch)));
+        self
+    }
+
+    pub fn set_jammed(&mut self, jammed: bool) {
+        self.update();
+        self.jammed = jammed;
+        if jammed {
+            self.speed_rpm = 0.0;
+        }
+    }
+
+    /// Integrate the wheel speed up to the current time of the clock.
+    fn update(&mut self) {
+        let now = self.clock.elapsed();
+        let dt = now.saturating_sub(self.last_update).as_secs_f32();
+        self.last_update = now;
+        if self.jammed {
+            return;
+        }
+        let target = if self.power.is_on() {
+            self.target_speed_rpm as f32
+        } else {
+            0.0
+        };
+        let max_step = self.acceleration_rpm_per_s * dt;
+        let diff = target - self.speed_rpm;
+        self.speed_rpm += diff.clamp(-max_step, max_step);
+    }
+
+    pub fn status(&mut self) -> ReactionWheelStatus {
+        self.update();
+        let switch_state = self.power.switch_state();
+        let speed_rpm = self.speed_rpm.round() as i32;
+        let current_ma = if switch_state == SwitchStateBinary::Off {
+            0
+        } else if self.jammed && self.target_speed_rpm != 0 {
+            Self::STALL_CURRENT_MA
+        } else if speed_rpm != self.target_speed_rpm {
+            Self::ACCELERATION_CURRENT_MA
+        } else {
+            Self::IDLE_CURRENT_MA
+        };
+        ReactionWheelStatus {
+            switch_state,
+            speed_rpm,
+            target_speed_rpm: self.target_speed_rpm,
+            current_ma,
+        }
+    }
+
+    /// Handle a reaction wheel request. Requests sent to a wheel which is switched off are
+    /// ignored.
+    pub fn handle_request(&mut self, request: &ReactionWheelRequest) -> Option<ReactionWheelReply> {
+        if !self.power.is_on() {
+            return None;
+        }
+        match request {
+            ReactionWheelRequest::SetSpeed { speed_rpm } => {
+                if speed_rpm.abs() > self.max_speed_rpm {
+                    return Some(ReactionWheelReply::SpeedOutOfRange {
+                        speed_rpm: *speed_rpm,
+                    });
+                }
+                self.update();
+                self.target_speed_rpm = *speed_rpm;
+                None
+            }
+            ReactionWheelRequest::RequestStatus => Some(ReactionWheelReply::Status(self.status())),
+        }
+    }
+}
+
+/// Create a power bus with a [FakePcdu] and a [FakeMgmLis3Mdl] connected to it, all sharing the
+/// given clock.
+pub fn create_fake_acs_and_eps(clock: FakeClock) -> (FakePcdu, FakeMgmLis3Mdl) {
+    let power_bus = FakePowerBus::default();
+    (
+        FakePcdu::new(power_bus.clone()),
+        FakeMgmLis3Mdl::new(clock, power_bus),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::acs::lis3mdl::MgmLis3RawValues;
+    use crate::acs::ALL_ONES_SENSOR_VAL;
+
+    use super::*;
+
+    #[test]
+    fn test_pcdu_switches_power_bus() {
+        let pcdu = FakePcdu::default();
+        assert_eq!(
+            pcdu.power_bus().switch_state(PcduSwitch::Mgm),
+            SwitchStateBinary::Off
+        );
+        assert!(pcdu
+            .handle_request(&PcduRequest::SwitchDevice {
+                switch: PcduSwitch::Mgm,
+                state: SwitchStateBinary::On,
+            })
+            .is_none());
+        let PcduReply::SwitchInfo(switch_map) = pcdu
+            .handle_request(&PcduRequest::RequestSwitchInfo)
+            .expect("no switch info reply");
+        assert_eq!(switch_map[&PcduSwitch::Mgm], SwitchStateBinary::On);
+        assert_eq!(switch_map[&PcduSwitch::Mgt], SwitchStateBinary::Off);
+    }
+
+    #[test]
+    fn test_mgm_power_and_rotation() {
+        let clock = FakeClock::new_manual();
+        let (pcdu, mut mgm) = create_fake_acs_and_eps(clock.clone());
+        assert!(mgm.sensor_values().is_none());
+        assert_eq!(
+            mgm.reply().raw,
+            MgmLis3RawValues {
+                x: ALL_ONES_SENSOR_VAL,
+                y: ALL_ONES_SENSOR_VAL,
+                z: ALL_ONES_SENSOR_VAL,
+            }
+        );
+        pcdu.power_bus()
+            .set_switch_state(PcduSwitch::Mgm, SwitchStateBinary::On);
+        mgm.set_rotation_rate(core::f32::consts::FRAC_PI_2);
+        let initial = mgm.sensor_values().unwrap();
+        assert_eq!(initial, FakeMgmLis3Mdl::DEFAULT_FIELD);
+        clock.advance(Duration::from_secs(1));
+        let rotated = mgm.sensor_values().unwrap();
+        // Rotated by 90 degrees around the z-axis.
+        assert!((rotated.x - (-initial.y)).abs() < 0.01);
+        assert!((rotated.y - initial.x).abs() < 0.01);
+        assert_eq!(rotated.z, initial.z);
+        mgm.set_stuck(true);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(mgm.sensor_values().unwrap(), rotated);
+    }
+
+    #[test]
+    fn test_sun_sensor() {
+        let clock = FakeClock::new_manual();
+        let mut sun_sensor = FakeSunSensor::new(clock);
+        sun_sensor.set_sun_vector([1.0, 0.0, 0.0]);
+        let reply = sun_sensor.reply();
+        assert_eq!(reply.switch_state, SwitchStateBinary::On);
+        // The cell facing +x is illuminated, the cell facing -x is dark.
+        assert!(reply.channels[0] > 2000);
+        assert_eq!(reply.channels[1], FakeSunSensor::DARK_COUNTS);
+        sun_sensor.set_eclipse(true);
+        assert_eq!(sun_sensor.reply().channels, [FakeSunSensor::DARK_COUNTS; 4]);
+    }
+
+    #[test]
+    fn test_reaction_wheel_spin_up() {
+        let clock = FakeClock::new_manual();
+        let power_bus = FakePowerBus::default();
+        let mut wheel = FakeReactionWheel::new(clock.clone(), 6000, 100.0)
+            .with_power_switch(power_bus.clone(), PcduSwitch::Mgt);
+        assert!(wheel
+            .handle_request(&ReactionWheelRequest::RequestStatus)
+            .is_none());
+        power_bus.set_switch_state(PcduSwitch::Mgt, SwitchStateBinary::On);
+        assert_eq!(
+            wheel.handle_request(&ReactionWheelRequest::SetSpeed { speed_rpm: 7000 }),
+            Some(ReactionWheelReply::SpeedOutOfRange { speed_rpm: 7000 })
+        );
+        assert!(wheel
+            .handle_request(&ReactionWheelRequest::SetSpeed { speed_rpm: 300 })
+            .is_none());
+        clock.advance(Duration::from_secs(2));
+        let status = wheel.status();
+        assert_eq!(status.speed_rpm, 200);
+        assert_eq!(
+            status.current_ma,
+            FakeReactionWheel::ACCELERATION_CURRENT_MA
+        );
+        clock.advance(Duration::from_secs(2));
+        let status = wheel.status();
+        assert_eq!(status.speed_rpm, 300);
+        assert_eq!(status.current_ma, FakeReactionWheel::IDLE_CURRENT_MA);
+        wheel.set_jammed(true);
+        let status = wheel.status();
+        assert_eq!(status.speed_rpm, 0);
+        assert_eq!(status.current_ma, FakeReactionWheel::STALL_CURRENT_MA);
+    }
+}
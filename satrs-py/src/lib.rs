@@ -0,0 +1,259 @@
+//! # Python bindings for the sat-rs TMTC client utilities
+//!
+//! This crate exposes the ground-side helpers of the `satrs::client` module to Python, so
+//! operators and test engineers can script against an on-board software built with sat-rs
+//! without duplicating the packet definitions in Python. The Python module `satrs_py` contains
+//!
+//!  - `PusTcBuilder` which creates common PUS telecommands as `Tc` objects.
+//!  - `Tm` which parses raw PUS telemetry and prints a human readable summary.
+//!  - `TcpClient` which exchanges packets with the TCP TMTC servers of sat-rs.
+//!
+//! The module is built with [maturin](https://www.maturin.rs), for example by running
+//! `maturin develop` inside the crate directory.
+use std::time::Duration;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use satrs::client::{self, BuiltTc, TcpFraming, TcpTmtcClient, TmPrinter, TmtcClient};
+use satrs::spacepackets::ecss::tm::PusTmReader;
+use satrs::spacepackets::ecss::PusPacket;
+use satrs::spacepackets::time::cds::{CdsTime, SubmillisPrecision};
+use satrs::spacepackets::time::UnixTime;
+use satrs::spacepackets::CcsdsPacket;
+
+fn value_error(e: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn duration_from_secs(secs: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(secs).map_err(value_error)
+}
+
+/// Serialized PUS telecommand together with its verification request ID.
+#[pyclass(frozen, name = "Tc", module = "satrs_py")]
+struct PyTc(BuiltTc);
+
+#[pymethods]
+impl PyTc {
+    #[getter]
+    fn request_id(&self) -> u32 {
+        self.0.request_id.raw()
+    }
+
+    #[getter]
+    fn packet<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.0.packet)
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.packet.len()
+    }
+}
+
+/// Builder for common PUS telecommands. Each telecommand gets the next packet sequence count.
+#[pyclass(name = "PusTcBuilder", module = "satrs_py")]
+struct PyPusTcBuilder(client::PusTcBuilder);
+
+#[pymethods]
+impl PyPusTcBuilder {
+    #[new]
+    fn new(apid: u16) -> Self {
+        Self(client::PusTcBuilder::new(apid))
+    }
+
+    #[getter]
+    fn apid(&self) -> u16 {
+        self.0.apid()
+    }
+
+    /// Build a generic telecommand with the given service, subservice and application data.
+    #[pyo3(signature = (service, subservice, app_data = &[]))]
+    fn build(&mut self, service: u8, subservice: u8, app_data: &[u8]) -> PyResult<PyTc> {
+        Ok(PyTc(
+            self.0
+                .build(service, subservice, app_data)
+                .map_err(value_error)?,
+        ))
+    }
+
+    /// Ping telecommand TC[17,1].
+    fn ping(&mut self) -> PyResult<PyTc> {
+        Ok(PyTc(self.0.ping().map_err(value_error)?))
+    }
+
+    /// Enable the periodic generation of a HK set with TC[3,5].
+    fn enable_hk(&mut self, target_id: u32, set_id: u32) -> PyResult<PyTc> {
+        Ok(PyTc(
+            self.0.enable_hk(target_id, set_id).map_err(value_error)?,
+        ))
+    }
+
+    /// Disable the periodic generation of a HK set with TC[3,6].
+    fn disable_hk(&mut self, target_id: u32, set_id: u32) -> PyResult<PyTc> {
+        Ok(PyTc(
+            self.0.disable_hk(target_id, set_id).map_err(value_error)?,
+        ))
+    }
+
+    /// Request a single HK report with TC[3,27].
+    fn one_shot_hk(&mut self, target_id: u32, set_id: u32) -> PyResult<PyTc> {
+        Ok(PyTc(
+            self.0.one_shot_hk(target_id, set_id).map_err(value_error)?,
+        ))
+    }
+
+    /// Insert a telecommand into the time-based schedule with TC[11,4]. The release time is
+    /// given in seconds since the UNIX epoch and is encoded as a CDS short timestamp.
+    fn schedule_insert(&mut self, release_time: f64, tc: PyRef<'_, PyTc>) -> PyResult<PyTc> {
+        if !release_time.is_finite() || release_time < 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "invalid release time {release_time}"
+            )));
+        }
+        let secs = release_time.trunc();
+        let unix_time = UnixTime::new(secs as i64, ((release_time - secs) * 1e9) as u32);
+        let release_time =
+            CdsTime::from_unix_time_with_u16_days(&unix_time, SubmillisPrecision::Absent)
+                .map_err(value_error)?;
+        Ok(PyTc(
+            self.0
+                .schedule_insert(&release_time, &tc.0)
+                .map_err(value_error)?,
+        ))
+    }
+}
+
+/// Parsed PUS telemetry packet.
+#[pyclass(frozen, name = "Tm", module = "satrs_py")]
+struct PyTm {
+    raw: Vec<u8>,
+    timestamp_len: usize,
+    #[pyo3(get)]
+    apid: u16,
+    #[pyo3(get)]
+    seq_count: u16,
+    #[pyo3(get)]
+    service: u8,
+    #[pyo3(get)]
+    subservice: u8,
+    /// Request ID of verification reports, [None] for all other packets.
+    #[pyo3(get)]
+    request_id: Option<u32>,
+    timestamp: Vec<u8>,
+    source_data: Vec<u8>,
+}
+
+#[pymethods]
+impl PyTm {
+    /// Parse a raw PUS TM packet. The timestamp length defaults to the length of a CDS short
+    /// timestamp.
+    #[new]
+    #[pyo3(signature = (raw, timestamp_len = 7))]
+    fn new(raw: &[u8], timestamp_len: usize) -> PyResult<Self> {
+        let (tm, _) = PusTmReader::new(raw, timestamp_len).map_err(value_error)?;
+        Ok(Self {
+            raw: raw.to_vec(),
+            timestamp_len,
+            apid: tm.apid(),
+            seq_count: tm.seq_count(),
+            service: tm.service(),
+            subservice: tm.subservice(),
+            request_id: client::verification_request_id(&tm).map(|id| id.raw()),
+            timestamp: tm.timestamp().to_vec(),
+            source_data: tm.source_data().to_vec(),
+        })
+    }
+
+    #[getter]
+    fn raw<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.raw)
+    }
+
+    #[getter]
+    fn timestamp<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.timestamp)
+    }
+
+    #[getter]
+    fn source_data<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.source_data)
+    }
+
+    fn __str__(&self) -> String {
+        TmPrinter::new(&self.raw, self.timestamp_len).to_string()
+    }
+}
+
+/// Client for the TCP TMTC servers of sat-rs.
+#[pyclass(name = "TcpClient", module = "satrs_py")]
+struct PyTcpClient(TcpTmtcClient);
+
+#[pymethods]
+impl PyTcpClient {
+    /// Connect to a TCP TMTC server. The framing is either "cobs" or "spacepackets" and must
+    /// match the server.
+    #[new]
+    #[pyo3(signature = (addr, framing = "cobs", max_packet_size = 4096))]
+    fn new(py: Python<'_>, addr: &str, framing: &str, max_packet_size: usize) -> PyResult<Self> {
+        let framing = match framing {
+            "cobs" => TcpFraming::Cobs,
+            "spacepackets" => TcpFraming::SpacePackets,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown framing {other:?}, expected \"cobs\" or \"spacepackets\""
+                )))
+            }
+        };
+        let client = py.allow_threads(|| TcpTmtcClient::connect(addr, framing, max_packet_size))?;
+        Ok(Self(client))
+    }
+
+    /// Send one raw packet, for example the packet of a `Tc`.
+    fn send(&mut self, packet: &[u8]) -> PyResult<()> {
+        Ok(self.0.send_packet(packet)?)
+    }
+
+    /// Receive one raw packet. Returns `None` if no packet was received until the timeout in
+    /// seconds expired.
+    fn recv<'py>(
+        &mut self,
+        py: Python<'py>,
+        timeout: f64,
+    ) -> PyResult<Option<Bound<'py, PyBytes>>> {
+        let timeout = duration_from_secs(timeout)?;
+        let client = &mut self.0;
+        let packet = py.allow_threads(|| client.recv_packet(timeout))?;
+        Ok(packet.map(|packet| PyBytes::new_bound(py, &packet)))
+    }
+
+    /// Receive all packets which arrive until the timeout in seconds expired.
+    fn recv_all<'py>(
+        &mut self,
+        py: Python<'py>,
+        timeout: f64,
+    ) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+        let timeout = duration_from_secs(timeout)?;
+        let client = &mut self.0;
+        let packets = py.allow_threads(|| client.recv_packets(timeout))?;
+        Ok(packets
+            .iter()
+            .map(|packet| PyBytes::new_bound(py, packet))
+            .collect())
+    }
+
+    /// Number of received COBS frames which could not be decoded.
+    #[getter]
+    fn num_broken_frames(&self) -> u32 {
+        self.0.num_broken_frames()
+    }
+}
+
+#[pymodule]
+fn satrs_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyTc>()?;
+    m.add_class::<PyPusTcBuilder>()?;
+    m.add_class::<PyTm>()?;
+    m.add_class::<PyTcpClient>()?;
+    Ok(())
+}
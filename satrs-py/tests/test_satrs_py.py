@@ -0,0 +1,124 @@
+"""Tests for the satrs_py bindings. Build the module with `maturin develop` before running pytest."""
+
+import socket
+import struct
+import threading
+
+import pytest
+
+from satrs_py import PusTcBuilder, TcpClient, Tm
+
+APID = 0x02
+
+
+def crc16_ccitt_false(data: bytes) -> int:
+    crc = 0xFFFF
+    for byte in data:
+        crc ^= byte << 8
+        for _ in range(8):
+            crc = ((crc << 1) ^ 0x1021) if crc & 0x8000 else crc << 1
+            crc &= 0xFFFF
+    return crc
+
+
+def pus_tm(service: int, subservice: int, source_data: bytes, seq_count: int = 0) -> bytes:
+    """Create a PUS C TM packet with an empty CDS short timestamp."""
+    sec_header = struct.pack(">BBBHH", 0x20, service, subservice, 0, 0) + bytes(7)
+    data_len = len(sec_header) + len(source_data) + 2 - 1
+    header = struct.pack(">HHH", 0x0800 | APID, 0xC000 | seq_count, data_len)
+    packet = header + sec_header + source_data
+    return packet + struct.pack(">H", crc16_ccitt_false(packet))
+
+
+def cobs_encode(data: bytes) -> bytes:
+    encoded = bytearray()
+    for block in data.split(b"\x00"):
+        while len(block) >= 254:
+            encoded += bytes([255]) + block[:254]
+            block = block[254:]
+        encoded += bytes([len(block) + 1]) + block
+    return bytes(encoded)
+
+
+def cobs_decode(data: bytes) -> bytes:
+    decoded = bytearray()
+    idx = 0
+    while idx < len(data):
+        code = data[idx]
+        decoded += data[idx + 1 : idx + code]
+        idx += code
+        if code < 255 and idx < len(data):
+            decoded.append(0)
+    return bytes(decoded)
+
+
+def test_tc_builder():
+    builder = PusTcBuilder(APID)
+    assert builder.apid == APID
+    ping = builder.ping()
+    packet = ping.packet
+    assert len(ping) == len(packet)
+    # TC packet type with secondary header, APID and sequence count 0.
+    assert struct.unpack(">HH", packet[0:4]) == (0x1800 | APID, 0xC000)
+    assert (packet[7], packet[8]) == (17, 1)
+    assert ping.request_id == int.from_bytes(packet[0:4], "big")
+
+    hk_request = builder.one_shot_hk(0x1020, 3)
+    assert struct.unpack(">H", hk_request.packet[2:4])[0] & 0x3FFF == 1
+    assert (hk_request.packet[7], hk_request.packet[8]) == (3, 27)
+    assert hk_request.packet[11:19] == struct.pack(">II", 0x1020, 3)
+
+    insert = builder.schedule_insert(1_700_000_000.5, ping)
+    assert (insert.packet[7], insert.packet[8]) == (11, 4)
+    assert insert.packet.endswith(packet + insert.packet[-2:])
+    with pytest.raises(ValueError):
+        builder.schedule_insert(-1.0, ping)
+
+
+def test_tm_parsing():
+    ping = PusTcBuilder(APID).ping()
+    raw_tm = pus_tm(1, 1, ping.packet[0:4], seq_count=5)
+    tm = Tm(raw_tm)
+    assert (tm.service, tm.subservice) == (1, 1)
+    assert tm.apid == APID
+    assert tm.seq_count == 5
+    assert tm.request_id == ping.request_id
+    assert tm.timestamp == bytes(7)
+    assert tm.raw == raw_tm
+    assert "acceptance success" in str(tm)
+
+    hk_tm = Tm(pus_tm(3, 25, bytes(10)))
+    assert hk_tm.request_id is None
+    assert hk_tm.source_data == bytes(10)
+    with pytest.raises(ValueError):
+        Tm(raw_tm[:-1])
+
+
+def test_tcp_client_cobs():
+    server = socket.create_server(("127.0.0.1", 0))
+    received = []
+    reply = pus_tm(17, 2, b"")
+
+    def serve():
+        conn, _ = server.accept()
+        with conn:
+            frame = b""
+            while frame.count(b"\x00") < 2:
+                frame += conn.recv(4096)
+            received.append(cobs_decode(frame.strip(b"\x00")))
+            conn.sendall(b"\x00" + cobs_encode(reply) + b"\x00")
+
+    server_thread = threading.Thread(target=serve)
+    server_thread.start()
+    client = TcpClient(f"127.0.0.1:{server.getsockname()[1]}")
+    ping = PusTcBuilder(APID).ping()
+    client.send(ping.packet)
+    packet = client.recv(2.0)
+    server_thread.join()
+    server.close()
+    assert received == [ping.packet]
+    assert packet == reply
+    assert str(Tm(packet)).endswith("ping reply")
+    assert client.num_broken_frames == 0
+    with pytest.raises(ValueError):
+        TcpClient("127.0.0.1:1", framing="unknown")
@@ -0,0 +1,102 @@
+//! Compares the throughput of the [SharedStaticMemoryPool], which uses a single lock for the whole
+//! pool, with the [SharedShardedMemoryPool], which uses one lock per subpool, when multiple
+//! threads store and delete packets concurrently.
+//!
+//! Run with `cargo bench --bench pool_contention`.
+use std::sync::{Arc, Barrier, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use satrs::pool::{
+    PoolProvider, ShardedStaticMemoryPool, SharedShardedMemoryPool, SharedStaticMemoryPool,
+    StaticMemoryPool, StaticPoolConfig,
+};
+
+const NUM_THREADS: usize = 4;
+const NUM_ITERATIONS: usize = 200_000;
+
+fn pool_cfg() -> StaticPoolConfig {
+    // Every thread uses its own subpool.
+    StaticPoolConfig::new_from_subpool_cfg_tuples(
+        vec![(32, 16), (32, 64), (32, 256), (32, 1024)],
+        false,
+    )
+}
+
+/// Packet size for the given thread index, so every thread hits a different subpool.
+fn packet_len(thread_idx: usize) -> usize {
+    16 << (2 * (thread_idx % 4))
+}
+
+/// Spawns one thread for every work item created by `create_work` and returns the time until all
+/// threads have finished.
+fn run_threads(create_work: impl Fn(usize) -> Box<dyn FnOnce() + Send>) -> Duration {
+    let barrier = Arc::new(Barrier::new(NUM_THREADS + 1));
+    let handles: Vec<_> = (0..NUM_THREADS)
+        .map(|thread_idx| {
+            let barrier = barrier.clone();
+            let work = create_work(thread_idx);
+            thread::spawn(move || {
+                barrier.wait();
+                work();
+            })
+        })
+        .collect();
+    barrier.wait();
+    let start = Instant::now();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    start.elapsed()
+}
+
+fn bench_rw_lock_pool() -> Duration {
+    let shared_pool = SharedStaticMemoryPool::new(RwLock::new(StaticMemoryPool::new(pool_cfg())));
+    run_threads(|thread_idx| {
+        let shared_pool = shared_pool.clone();
+        Box::new(move || {
+            let data = vec![thread_idx as u8; packet_len(thread_idx)];
+            let mut read_buf = vec![0; data.len()];
+            for _ in 0..NUM_ITERATIONS {
+                let addr = shared_pool.write().unwrap().add(&data).unwrap();
+                shared_pool
+                    .read()
+                    .unwrap()
+                    .read(&addr, &mut read_buf)
+                    .unwrap();
+                shared_pool.write().unwrap().delete(addr).unwrap();
+            }
+        })
+    })
+}
+
+fn bench_sharded_pool() -> Duration {
+    let shared_pool: SharedShardedMemoryPool = ShardedStaticMemoryPool::new_shared(pool_cfg());
+    run_threads(|thread_idx| {
+        let mut shared_pool = shared_pool.clone();
+        Box::new(move || {
+            let data = vec![thread_idx as u8; packet_len(thread_idx)];
+            let mut read_buf = vec![0; data.len()];
+            for _ in 0..NUM_ITERATIONS {
+                let addr = shared_pool.add(&data).unwrap();
+                shared_pool.read(&addr, &mut read_buf).unwrap();
+                shared_pool.delete(addr).unwrap();
+            }
+        })
+    })
+}
+
+fn report(name: &str, elapsed: Duration) {
+    let num_ops = (NUM_THREADS * NUM_ITERATIONS) as f64;
+    println!(
+        "{name:<24} {:>10.3} ms {:>12.0} store/read/delete cycles/s",
+        elapsed.as_secs_f64() * 1000.0,
+        num_ops / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    println!("{NUM_THREADS} threads with {NUM_ITERATIONS} iterations each");
+    report("RwLock<StaticMemoryPool>", bench_rw_lock_pool());
+    report("ShardedStaticMemoryPool", bench_sharded_pool());
+}
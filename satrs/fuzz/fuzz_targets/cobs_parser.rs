@@ -0,0 +1,24 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use satrs::encoding::parse_buffer_for_cobs_encoded_packets;
+use satrs::tmtc::PacketSenderRaw;
+use satrs::ComponentId;
+
+struct PacketSink;
+
+impl PacketSenderRaw for PacketSink {
+    type Error = ();
+
+    fn send_packet(&self, _sender_id: ComponentId, packet: &[u8]) -> Result<(), Self::Error> {
+        assert!(!packet.is_empty());
+        Ok(())
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut buf = data.to_vec();
+    let mut next_write_idx = 0;
+    parse_buffer_for_cobs_encoded_packets(&mut buf, 0, &PacketSink, &mut next_write_idx).unwrap();
+    assert!(next_write_idx <= buf.len());
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use satrs::encoding::{parse_tc_lenient, parse_tm_lenient};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((_, len)) = parse_tc_lenient(data) {
+        assert!(len <= data.len());
+    }
+    for timestamp_len in [0, 7] {
+        if let Ok((_, len)) = parse_tm_lenient(data, timestamp_len) {
+            assert!(len <= data.len());
+        }
+    }
+});
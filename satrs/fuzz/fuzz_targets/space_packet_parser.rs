@@ -0,0 +1,31 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use satrs::encoding::ccsds::{ApidValidator, SpValidity};
+use satrs::encoding::{parse_buffer_for_ccsds_space_packets, SpacePacketStreamParser};
+use satrs::tmtc::PacketSenderRaw;
+use satrs::ComponentId;
+
+struct PacketSink;
+
+impl PacketSenderRaw for PacketSink {
+    type Error = ();
+
+    fn send_packet(&self, _sender_id: ComponentId, packet: &[u8]) -> Result<(), Self::Error> {
+        assert!(packet.len() >= 7);
+        Ok(())
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    for validity in [SpValidity::Skip, SpValidity::Invalid] {
+        let validator = ApidValidator::new_with_unknown_apid_validity(vec![0x02], validity);
+        parse_buffer_for_ccsds_space_packets(data, &validator, 0, &PacketSink).unwrap();
+        // Feed the data in chunks whose size is determined by the first byte.
+        let chunk_size = data.first().map(|byte| *byte as usize + 1).unwrap_or(1);
+        let mut parser = SpacePacketStreamParser::new(256);
+        for chunk in data.chunks(chunk_size) {
+            parser.feed(chunk, &validator, 0, &PacketSink).unwrap();
+        }
+    }
+});
@@ -0,0 +1,449 @@
+//! # Central addressing registry
+//!
+//! On-board software usually addresses components in several ways. Internally, every component is
+//! identified by its [ComponentId], which is also called object ID. Telecommands address
+//! components using a [UniqueApidTargetId], which consists of the APID of the application and a
+//! target ID inside that application. Requests are finally sent to the component using some queue
+//! handle.
+//!
+//! The [AddressRegistry] bundles these three mappings in one place so that the PUS request
+//! routers for housekeeping, actions and modes do not require ad-hoc maps in user code. It
+//! implements [crate::pus::PusRequestRouter] if the handle is a [MessageSender].
+//!
+//! Lookup failures are reported with an [AddressLookupError], which can be converted into the
+//! standardized verification failure codes inside the [addr_res_code] module.
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::queue::{GenericSendError, GenericTargetedMessagingError};
+use crate::request::{Apid, GenericMessage, MessageMetadata, MessageSender, UniqueApidTargetId};
+use crate::ComponentId;
+use satrs_shared::res_code::ResultU16;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// Result codes for addressing failures which can be used for verification failure reports.
+/// These codes are not specific to a PUS service, so the group ID of the PUS verification
+/// service 1 is used. The failure data of these reports is generated with
+/// [AddressLookupError::write_failure_data].
+pub mod addr_res_code {
+    use satrs_shared::res_code::ResultU16;
+
+    /// No component is registered for the object ID.
+    pub const UNKNOWN_OBJECT_ID: ResultU16 = ResultU16::new(1, 10);
+    /// No component is registered for the APID and target ID of a telecommand.
+    pub const UNKNOWN_TARGET_ID: ResultU16 = ResultU16::new(1, 11);
+    /// The request could not be sent to the queue of the component.
+    pub const ROUTING_ERROR: ResultU16 = ResultU16::new(1, 12);
+}
+
+/// Size of the failure data written by [AddressLookupError::write_failure_data].
+pub const ADDRESS_FAILURE_DATA_LEN: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AddressLookupError {
+    UnknownId(ComponentId),
+    UnknownTarget(UniqueApidTargetId),
+}
+
+impl AddressLookupError {
+    /// Standardized verification failure code for this error.
+    pub fn failure_code(&self) -> ResultU16 {
+        match self {
+            AddressLookupError::UnknownId(_) => addr_res_code::UNKNOWN_OBJECT_ID,
+            AddressLookupError::UnknownTarget(_) => addr_res_code::UNKNOWN_TARGET_ID,
+        }
+    }
+
+    /// The raw ID which could not be resolved. For an unknown target, this is the raw value of
+    /// the [UniqueApidTargetId].
+    pub fn raw_id(&self) -> ComponentId {
+        match self {
+            AddressLookupError::UnknownId(id) => *id,
+            AddressLookupError::UnknownTarget(target) => target.raw(),
+        }
+    }
+
+    /// Write the raw ID as the big endian failure data of a verification failure report.
+    pub fn write_failure_data(&self, buf: &mut [u8; ADDRESS_FAILURE_DATA_LEN]) {
+        buf.copy_from_slice(&self.raw_id().to_be_bytes());
+    }
+}
+
+impl Display for AddressLookupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AddressLookupError::UnknownId(id) => write!(f, "unknown object ID {id}"),
+            AddressLookupError::UnknownTarget(target) => write!(f, "unknown target: {target}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for AddressLookupError {}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AddressRegistrationError {
+    /// A component with the same object ID was already registered.
+    DuplicateId(ComponentId),
+    /// A component with the same APID and target ID was already registered.
+    DuplicateTarget(UniqueApidTargetId),
+}
+
+impl Display for AddressRegistrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AddressRegistrationError::DuplicateId(id) => write!(f, "duplicate object ID {id}"),
+            AddressRegistrationError::DuplicateTarget(target) => {
+                write!(f, "duplicate target: {target}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for AddressRegistrationError {}
+
+/// Address information of a single component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressEntry<Handle> {
+    pub id: ComponentId,
+    pub target_id: UniqueApidTargetId,
+    pub handle: Handle,
+}
+
+impl<Handle> AddressEntry<Handle> {
+    pub fn apid(&self) -> Apid {
+        self.target_id.apid
+    }
+}
+
+/// Registry which maps object IDs to the [UniqueApidTargetId] and the request queue handle of
+/// a component.
+///
+/// Every object ID and every target ID can only be registered once, so the mapping between them
+/// is unique in both directions.
+#[derive(Debug, Clone)]
+pub struct AddressRegistry<Handle> {
+    entries: HashMap<ComponentId, AddressEntry<Handle>>,
+    ids_by_target: HashMap<UniqueApidTargetId, ComponentId>,
+}
+
+impl<Handle> Default for AddressRegistry<Handle> {
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+            ids_by_target: Default::default(),
+        }
+    }
+}
+
+impl<Handle> AddressRegistry<Handle> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a component.
+    ///
+    /// ## Parameter
+    ///
+    /// * `id` - Object ID of the component.
+    /// * `target_id` - APID and target ID used to address the component with telecommands.
+    /// * `handle` - Queue handle used to send requests to the component.
+    pub fn register(
+        &mut self,
+        id: ComponentId,
+        target_id: UniqueApidTargetId,
+        handle: Handle,
+    ) -> Result<(), AddressRegistrationError> {
+        if self.entries.contains_key(&id) {
+            return Err(AddressRegistrationError::DuplicateId(id));
+        }
+        if self.ids_by_target.contains_key(&target_id) {
+            return Err(AddressRegistrationError::DuplicateTarget(target_id));
+        }
+        self.ids_by_target.insert(target_id, id);
+        self.entries.insert(
+            id,
+            AddressEntry {
+                id,
+                target_id,
+                handle,
+            },
+        );
+        Ok(())
+    }
+
+    /// Register a component which uses the raw value of its [UniqueApidTargetId] as the object
+    /// ID.
+    pub fn register_with_target_id(
+        &mut self,
+        target_id: UniqueApidTargetId,
+        handle: Handle,
+    ) -> Result<(), AddressRegistrationError> {
+        self.register(target_id.raw(), target_id, handle)
+    }
+
+    pub fn unregister(&mut self, id: ComponentId) -> Option<AddressEntry<Handle>> {
+        let entry = self.entries.remove(&id)?;
+        self.ids_by_target.remove(&entry.target_id);
+        Some(entry)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, id: ComponentId) -> bool {
+        self.entries.contains_key(&id)
+    }
+
+    pub fn entry(&self, id: ComponentId) -> Result<&AddressEntry<Handle>, AddressLookupError> {
+        self.entries
+            .get(&id)
+            .ok_or(AddressLookupError::UnknownId(id))
+    }
+
+    pub fn target_id(&self, id: ComponentId) -> Result<UniqueApidTargetId, AddressLookupError> {
+        self.entry(id).map(|entry| entry.target_id)
+    }
+
+    pub fn apid(&self, id: ComponentId) -> Result<Apid, AddressLookupError> {
+        self.entry(id).map(|entry| entry.apid())
+    }
+
+    pub fn handle(&self, id: ComponentId) -> Result<&Handle, AddressLookupError> {
+        self.entry(id).map(|entry| &entry.handle)
+    }
+
+    /// Resolve the object ID of the component addressed by the given target ID.
+    pub fn resolve_target(
+        &self,
+        target_id: UniqueApidTargetId,
+    ) -> Result<ComponentId, AddressLookupError> {
+        self.ids_by_target
+            .get(&target_id)
+            .copied()
+            .ok_or(AddressLookupError::UnknownTarget(target_id))
+    }
+
+    pub fn entry_by_target(
+        &self,
+        target_id: UniqueApidTargetId,
+    ) -> Result<&AddressEntry<Handle>, AddressLookupError> {
+        let id = self.resolve_target(target_id)?;
+        self.entry(id)
+    }
+
+    pub fn handle_by_target(
+        &self,
+        target_id: UniqueApidTargetId,
+    ) -> Result<&Handle, AddressLookupError> {
+        self.entry_by_target(target_id).map(|entry| &entry.handle)
+    }
+
+    /// Object IDs of all components with the given APID, sorted in ascending order.
+    pub fn ids_of_apid(&self, apid: Apid) -> Vec<ComponentId> {
+        let mut ids: Vec<ComponentId> = self
+            .entries
+            .values()
+            .filter(|entry| entry.apid() == apid)
+            .map(|entry| entry.id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &AddressEntry<Handle>> {
+        self.entries.values()
+    }
+
+    /// Send a message to the component with the given object ID.
+    pub fn send_to_object<Msg>(
+        &self,
+        requestor_info: MessageMetadata,
+        id: ComponentId,
+        message: Msg,
+    ) -> Result<(), GenericTargetedMessagingError>
+    where
+        Handle: MessageSender<Msg>,
+    {
+        self.handle(id)
+            .map_err(|_| GenericSendError::TargetDoesNotExist(id))?
+            .send(GenericMessage::new(requestor_info, message))
+    }
+
+    /// Send a message to the component addressed by the given target ID.
+    pub fn send_to_target<Msg>(
+        &self,
+        requestor_info: MessageMetadata,
+        target_id: UniqueApidTargetId,
+        message: Msg,
+    ) -> Result<(), GenericTargetedMessagingError>
+    where
+        Handle: MessageSender<Msg>,
+    {
+        self.handle_by_target(target_id)
+            .map_err(|_| GenericSendError::TargetDoesNotExist(target_id.raw()))?
+            .send(GenericMessage::new(requestor_info, message))
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use super::*;
+    use crate::pus::{GenericRoutingError, PusRequestRouter};
+
+    impl From<AddressLookupError> for GenericRoutingError {
+        fn from(value: AddressLookupError) -> Self {
+            GenericRoutingError::UnknownTargetId(value.raw_id())
+        }
+    }
+
+    /// Standardized verification failure code for a [GenericRoutingError].
+    pub fn routing_failure_code(error: &GenericRoutingError) -> ResultU16 {
+        match error {
+            GenericRoutingError::UnknownTargetId(_) => addr_res_code::UNKNOWN_TARGET_ID,
+            GenericRoutingError::Send(_) => addr_res_code::ROUTING_ERROR,
+        }
+    }
+
+    /// The target ID passed to the router is interpreted as the raw [UniqueApidTargetId] which
+    /// is extracted from the telecommand by the PUS request converters.
+    impl<Msg, Handle: MessageSender<Msg>> PusRequestRouter<Msg> for AddressRegistry<Handle> {
+        type Error = GenericRoutingError;
+
+        fn route(
+            &self,
+            requestor_info: MessageMetadata,
+            target_id: ComponentId,
+            request: Msg,
+        ) -> Result<(), Self::Error> {
+            let handle = self.handle_by_target(UniqueApidTargetId::from(target_id))?;
+            handle
+                .send(GenericMessage::new(requestor_info, request))
+                .map_err(|e| match e {
+                    GenericTargetedMessagingError::Send(e) => GenericRoutingError::Send(e),
+                    GenericTargetedMessagingError::Receive(_) => {
+                        GenericRoutingError::Send(GenericSendError::RxDisconnected)
+                    }
+                })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use alloc::vec;
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::pus::{GenericRoutingError, PusRequestRouter};
+
+    const TARGET_0: UniqueApidTargetId = UniqueApidTargetId::new(0x02, 1);
+    const TARGET_1: UniqueApidTargetId = UniqueApidTargetId::new(0x02, 2);
+    const TARGET_2: UniqueApidTargetId = UniqueApidTargetId::new(0x03, 1);
+
+    #[test]
+    fn test_register_and_lookup() {
+        let mut registry = AddressRegistry::new();
+        registry.register(10, TARGET_0, "handle 0").unwrap();
+        registry
+            .register_with_target_id(TARGET_1, "handle 1")
+            .unwrap();
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.target_id(10).unwrap(), TARGET_0);
+        assert_eq!(registry.apid(10).unwrap(), 0x02);
+        assert_eq!(*registry.handle(10).unwrap(), "handle 0");
+        assert_eq!(registry.resolve_target(TARGET_0).unwrap(), 10);
+        assert_eq!(registry.resolve_target(TARGET_1).unwrap(), TARGET_1.raw());
+        assert_eq!(*registry.handle_by_target(TARGET_1).unwrap(), "handle 1");
+    }
+
+    #[test]
+    fn test_duplicates_rejected() {
+        let mut registry = AddressRegistry::new();
+        registry.register(10, TARGET_0, ()).unwrap();
+        assert_eq!(
+            registry.register(10, TARGET_1, ()),
+            Err(AddressRegistrationError::DuplicateId(10))
+        );
+        assert_eq!(
+            registry.register(11, TARGET_0, ()),
+            Err(AddressRegistrationError::DuplicateTarget(TARGET_0))
+        );
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_lookup_errors() {
+        let mut registry = AddressRegistry::new();
+        registry.register(10, TARGET_0, ()).unwrap();
+        let error = registry.entry(11).unwrap_err();
+        assert_eq!(error, AddressLookupError::UnknownId(11));
+        assert_eq!(error.failure_code(), addr_res_code::UNKNOWN_OBJECT_ID);
+        let error = registry.resolve_target(TARGET_2).unwrap_err();
+        assert_eq!(error, AddressLookupError::UnknownTarget(TARGET_2));
+        assert_eq!(error.failure_code(), addr_res_code::UNKNOWN_TARGET_ID);
+        let mut fail_data = [0; ADDRESS_FAILURE_DATA_LEN];
+        error.write_failure_data(&mut fail_data);
+        assert_eq!(u64::from_be_bytes(fail_data), TARGET_2.raw());
+    }
+
+    #[test]
+    fn test_unregister_and_apid_query() {
+        let mut registry = AddressRegistry::new();
+        registry.register_with_target_id(TARGET_1, ()).unwrap();
+        registry.register_with_target_id(TARGET_0, ()).unwrap();
+        registry.register_with_target_id(TARGET_2, ()).unwrap();
+        assert_eq!(
+            registry.ids_of_apid(0x02),
+            vec![TARGET_0.raw(), TARGET_1.raw()]
+        );
+        let entry = registry.unregister(TARGET_0.raw()).unwrap();
+        assert_eq!(entry.target_id, TARGET_0);
+        assert!(!registry.contains(TARGET_0.raw()));
+        assert!(registry.resolve_target(TARGET_0).is_err());
+        // The target ID can be re-used after the component was removed.
+        registry.register(20, TARGET_0, ()).unwrap();
+    }
+
+    #[test]
+    fn test_pus_routing() {
+        let (tx, rx) = mpsc::channel::<GenericMessage<u32>>();
+        let mut registry = AddressRegistry::new();
+        registry.register(10, TARGET_0, tx).unwrap();
+        let metadata = MessageMetadata::new(1, 5);
+        registry.route(metadata, TARGET_0.raw(), 42).unwrap();
+        let msg = rx.try_recv().unwrap();
+        assert_eq!(msg.requestor_info, metadata);
+        assert_eq!(msg.message, 42);
+        registry.send_to_object(metadata, 10, 43).unwrap();
+        assert_eq!(rx.try_recv().unwrap().message, 43);
+
+        let error = registry.route(metadata, TARGET_1.raw(), 44).unwrap_err();
+        assert!(matches!(
+            error,
+            GenericRoutingError::UnknownTargetId(id) if id == TARGET_1.raw()
+        ));
+        assert_eq!(
+            routing_failure_code(&error),
+            addr_res_code::UNKNOWN_TARGET_ID
+        );
+        drop(rx);
+        let error = registry.route(metadata, TARGET_0.raw(), 45).unwrap_err();
+        assert!(matches!(
+            error,
+            GenericRoutingError::Send(GenericSendError::RxDisconnected)
+        ));
+        assert_eq!(routing_failure_code(&error), addr_res_code::ROUTING_ERROR);
+    }
+}
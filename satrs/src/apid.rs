@@ -0,0 +1,174 @@
+//! # Run-time configurable APID
+//!
+//! The TM generators of this crate usually use a fixed APID which is configured when they are
+//! created. Some missions need to change the APID at run-time, for example if the same software
+//! image is used for multiple satellites or if the APID is switched after the separation from
+//! the launcher.
+//!
+//! All core TM generators store their APID as an [ApidHandle]. The handle can either be a fixed
+//! APID or a [SharedApid], which is a cheaply clonable handle to an APID shared by multiple
+//! TM generators. Changing the shared APID changes the APID of all TM generators using it.
+//!
+//! # Example
+//!
+//! ```
+//! use satrs::apid::SharedApid;
+//! use satrs::pus::event::EventReportCreator;
+//! use satrs::pus::verification::VerificationReportCreator;
+//!
+//! let shared_apid = SharedApid::new(0x02).unwrap();
+//! let verif_creator = VerificationReportCreator::new_with_apid_handle(shared_apid.clone());
+//! let event_creator = EventReportCreator::new_with_apid_handle(shared_apid.clone(), 0);
+//! assert!(shared_apid.set(0x03));
+//! assert_eq!(verif_creator.apid(), 0x03);
+//! assert_eq!(event_creator.apid(), 0x03);
+//! ```
+use spacepackets::MAX_APID;
+
+use crate::request::Apid;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// Generic trait for objects which provide an APID.
+pub trait ApidProvider {
+    fn apid(&self) -> Apid;
+}
+
+impl ApidProvider for Apid {
+    fn apid(&self) -> Apid {
+        *self
+    }
+}
+
+/// APID used by a TM generator. The contained APID is always valid.
+#[derive(Debug, Clone)]
+pub enum ApidHandle {
+    Fixed(Apid),
+    #[cfg(feature = "std")]
+    Shared(SharedApid),
+}
+
+impl ApidHandle {
+    /// Create a fixed APID handle. Returns [None] if the APID is larger than [MAX_APID].
+    pub fn new_fixed(apid: Apid) -> Option<Self> {
+        if apid > MAX_APID {
+            return None;
+        }
+        Some(Self::Fixed(apid))
+    }
+
+    pub fn apid(&self) -> Apid {
+        match self {
+            ApidHandle::Fixed(apid) => *apid,
+            #[cfg(feature = "std")]
+            ApidHandle::Shared(shared) => shared.get(),
+        }
+    }
+
+    /// Set the APID. Please note that this changes the APID of all TM generators using the same
+    /// [SharedApid]. Returns false if the APID is larger than [MAX_APID].
+    pub fn set_apid(&mut self, apid: Apid) -> bool {
+        match self {
+            ApidHandle::Fixed(fixed) => {
+                if apid > MAX_APID {
+                    return false;
+                }
+                *fixed = apid;
+                true
+            }
+            #[cfg(feature = "std")]
+            ApidHandle::Shared(shared) => shared.set(apid),
+        }
+    }
+}
+
+impl ApidProvider for ApidHandle {
+    fn apid(&self) -> Apid {
+        ApidHandle::apid(self)
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use core::fmt::{Debug, Formatter};
+    use std::sync::atomic::{AtomicU16, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// APID which can be shared between multiple TM generators and changed at run-time.
+    #[derive(Clone)]
+    pub struct SharedApid(Arc<AtomicU16>);
+
+    impl SharedApid {
+        /// Returns [None] if the APID is larger than [MAX_APID].
+        pub fn new(apid: Apid) -> Option<Self> {
+            if apid > MAX_APID {
+                return None;
+            }
+            Some(Self(Arc::new(AtomicU16::new(apid))))
+        }
+
+        pub fn get(&self) -> Apid {
+            self.0.load(Ordering::Relaxed)
+        }
+
+        /// Returns false and keeps the current APID if the APID is larger than [MAX_APID].
+        pub fn set(&self, apid: Apid) -> bool {
+            if apid > MAX_APID {
+                return false;
+            }
+            self.0.store(apid, Ordering::Relaxed);
+            true
+        }
+    }
+
+    impl Debug for SharedApid {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            f.debug_tuple("SharedApid").field(&self.get()).finish()
+        }
+    }
+
+    impl ApidProvider for SharedApid {
+        fn apid(&self) -> Apid {
+            self.get()
+        }
+    }
+
+    impl From<SharedApid> for ApidHandle {
+        fn from(shared: SharedApid) -> Self {
+            Self::Shared(shared)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_handle() {
+        let mut handle = ApidHandle::new_fixed(0x02).unwrap();
+        assert_eq!(handle.apid(), 0x02);
+        assert!(handle.set_apid(0x05));
+        assert_eq!(handle.apid(), 0x05);
+        assert!(!handle.set_apid(MAX_APID + 1));
+        assert_eq!(handle.apid(), 0x05);
+        assert!(ApidHandle::new_fixed(MAX_APID + 1).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_shared_handle() {
+        let shared = SharedApid::new(0x02).unwrap();
+        let handle_0 = ApidHandle::from(shared.clone());
+        let mut handle_1 = ApidHandle::from(shared.clone());
+        assert!(handle_1.set_apid(0x10));
+        assert_eq!(handle_0.apid(), 0x10);
+        assert_eq!(shared.apid(), 0x10);
+        assert!(!shared.set(MAX_APID + 1));
+        assert_eq!(handle_0.apid(), 0x10);
+        assert!(SharedApid::new(MAX_APID + 1).is_none());
+    }
+}
@@ -0,0 +1,627 @@
+//! # Assembly components with redundant children
+//!
+//! Many subsystems contain redundant devices, for example two magnetometers or two reaction
+//! wheel drivers, where only a subset of the devices is required for operation. An assembly is
+//! the mode tree node which owns these device handlers. It receives [ModeRequest]s from its
+//! parent and commands its children accordingly.
+//!
+//! The [RedundantAssembly] implements the standard redundancy logic for such assemblies:
+//!
+//! - A commanded mode is forwarded to the configured number of active children. Healthy children
+//!   which are already active are preferred, all other healthy children are kept as spares.
+//! - The commanded mode is reached and reported to the requestor once all active children
+//!   confirmed the mode.
+//! - When an active child is faulty, either because it replied with [ModeReply::CantReachMode]
+//!   or [ModeReply::WrongMode] or because [RedundantAssembly::handle_child_fault] was called, for
+//!   example on a fault event of the child, the child is commanded to the off mode and a healthy
+//!   spare is commanded to the mode of the assembly instead.
+//! - If no healthy child is left, the assembly switches to its off mode and a pending requestor
+//!   receives a [ModeReply::CantReachMode] reply with the [assembly_res_code::NO_HEALTHY_CHILD]
+//!   result code. If the last child fails after the mode was reached, this reply is sent to the
+//!   requestor of the last mode command.
+use alloc::vec::Vec;
+
+use crate::mode::{
+    ModeAndSubmode, ModeError, ModeProvider, ModeReply, ModeReplyReceiver, ModeReplySender,
+    ModeRequest, ModeRequestHandler, ModeRequestReceiver, ModeRequestSender,
+};
+use crate::request::{GenericMessage, MessageMetadata, RequestId};
+use crate::ComponentId;
+
+/// Result codes used by the [RedundantAssembly] for [ModeReply::CantReachMode] replies.
+pub mod assembly_res_code {
+    use satrs_shared::res_code::ResultU16;
+
+    pub const GROUP_ID: u8 = 0x80;
+    /// The commanded mode can not be reached because all children are faulty.
+    pub const NO_HEALTHY_CHILD: ResultU16 = ResultU16::new(GROUP_ID, 0);
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChildHealth {
+    Healthy,
+    /// Faulty children are not commanded anymore until they are marked healthy again.
+    Faulty,
+}
+
+/// State of one child of a [RedundantAssembly].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AssemblyChild {
+    pub id: ComponentId,
+    pub health: ChildHealth,
+    /// Active children are commanded to the mode of the assembly, all other children are spares.
+    pub active: bool,
+    /// Last mode commanded to the child.
+    pub commanded: Option<ModeAndSubmode>,
+    /// Last mode reported by the child.
+    pub reported: Option<ModeAndSubmode>,
+}
+
+impl AssemblyChild {
+    fn new(id: ComponentId) -> Self {
+        Self {
+            id,
+            health: ChildHealth::Healthy,
+            active: false,
+            commanded: None,
+            reported: None,
+        }
+    }
+
+    fn transition_done(&self) -> bool {
+        self.health == ChildHealth::Faulty
+            || self.commanded.is_none()
+            || self.commanded == self.reported
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct AssemblyTransition {
+    target: ModeAndSubmode,
+    requestor: Option<MessageMetadata>,
+}
+
+/// Assembly which owns several redundant children and implements the redundancy logic described
+/// in the [module documentation](self).
+///
+/// The `Interface` is used to receive mode requests from the parent, to reply to the parent, to
+/// command the children and to receive the replies of the children, for example a
+/// [ModeInterface](crate::mode::ModeInterface).
+pub struct RedundantAssembly<Interface> {
+    id: ComponentId,
+    interface: Interface,
+    children: Vec<AssemblyChild>,
+    num_active: usize,
+    off_mode: ModeAndSubmode,
+    mode: ModeAndSubmode,
+    transition: Option<AssemblyTransition>,
+    /// Requestor of the last mode command, which is informed if the mode is lost.
+    last_requestor: Option<MessageMetadata>,
+    next_request_id: RequestId,
+}
+
+impl<Interface> RedundantAssembly<Interface> {
+    /// Create a new assembly without children. The assembly starts in the off mode.
+    ///
+    /// ## Parameter
+    ///
+    /// * `id` - Component ID of the assembly.
+    /// * `interface` - Mode interface to the parent and to the children.
+    /// * `off_mode` - Mode of inactive children and of the assembly if it is off.
+    /// * `num_active` - Number of children which are commanded to the mode of the assembly.
+    ///   Must be larger than 0.
+    pub fn new(
+        id: ComponentId,
+        interface: Interface,
+        off_mode: ModeAndSubmode,
+        num_active: usize,
+    ) -> Self {
+        assert!(
+            num_active > 0,
+            "number of active children must be larger than 0"
+        );
+        Self {
+            id,
+            interface,
+            children: Vec::new(),
+            num_active,
+            off_mode,
+            mode: off_mode,
+            transition: None,
+            last_requestor: None,
+            next_request_id: 0,
+        }
+    }
+
+    /// Add a child. Children which are added first are preferred when active children are
+    /// selected.
+    pub fn add_child(&mut self, child_id: ComponentId) {
+        self.children.push(AssemblyChild::new(child_id));
+    }
+
+    pub fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    pub fn children(&self) -> &[AssemblyChild] {
+        &self.children
+    }
+
+    pub fn child(&self, child_id: ComponentId) -> Option<&AssemblyChild> {
+        self.children.iter().find(|child| child.id == child_id)
+    }
+
+    pub fn interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    pub fn interface_mut(&mut self) -> &mut Interface {
+        &mut self.interface
+    }
+
+    pub fn off_mode(&self) -> ModeAndSubmode {
+        self.off_mode
+    }
+
+    /// Target mode of an ongoing transition.
+    pub fn target_mode(&self) -> Option<ModeAndSubmode> {
+        self.transition.map(|transition| transition.target)
+    }
+
+    fn child_idx(&self, child_id: ComponentId) -> Option<usize> {
+        self.children.iter().position(|child| child.id == child_id)
+    }
+
+    fn next_request_id(&mut self) -> RequestId {
+        let request_id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        request_id
+    }
+}
+
+impl<Interface: ModeRequestSender + ModeRequestReceiver + ModeReplySender + ModeReplyReceiver>
+    RedundantAssembly<Interface>
+{
+    /// Handle all pending mode requests of the parent and all pending mode replies of the
+    /// children. This function should be called periodically.
+    pub fn periodic_operation(&mut self) -> Result<(), ModeError> {
+        while let Some(request) = self.interface.try_recv_mode_request()? {
+            self.handle_mode_request(request)?;
+        }
+        while let Some(reply) = self.interface.try_recv_mode_reply()? {
+            self.handle_child_reply(reply)?;
+        }
+        Ok(())
+    }
+
+    /// Handle a mode reply of a child. Replies of unknown senders are ignored.
+    pub fn handle_child_reply(
+        &mut self,
+        reply: GenericMessage<ModeReply>,
+    ) -> Result<(), ModeError> {
+        let idx = match self.child_idx(reply.sender_id()) {
+            Some(idx) => idx,
+            None => return Ok(()),
+        };
+        match reply.message {
+            ModeReply::ModeReply(mode_and_submode) => {
+                self.children[idx].reported = Some(mode_and_submode);
+                self.check_transition_done()
+            }
+            ModeReply::CantReachMode(_) | ModeReply::WrongMode { .. } => {
+                self.handle_child_fault(reply.sender_id())
+            }
+        }
+    }
+
+    /// Mark a child as faulty, for example on a fault event of the child. If the child was
+    /// active, it is commanded to the off mode and a healthy spare takes over.
+    pub fn handle_child_fault(&mut self, child_id: ComponentId) -> Result<(), ModeError> {
+        let idx = match self.child_idx(child_id) {
+            Some(idx) => idx,
+            None => return Ok(()),
+        };
+        let child = &mut self.children[idx];
+        child.health = ChildHealth::Faulty;
+        if !child.active {
+            return Ok(());
+        }
+        child.active = false;
+        self.command_child(idx, self.off_mode)?;
+        let target = self.target_mode().unwrap_or(self.mode);
+        if target == self.off_mode {
+            return self.check_transition_done();
+        }
+        let requestor = self.transition.and_then(|transition| transition.requestor);
+        self.command_active_children(requestor, target)
+    }
+
+    /// Set the health of a child. Children which are marked healthy again are used as spares.
+    pub fn set_child_health(
+        &mut self,
+        child_id: ComponentId,
+        health: ChildHealth,
+    ) -> Result<(), ModeError> {
+        match health {
+            ChildHealth::Faulty => self.handle_child_fault(child_id),
+            ChildHealth::Healthy => {
+                if let Some(idx) = self.child_idx(child_id) {
+                    self.children[idx].health = ChildHealth::Healthy;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn command_child(&mut self, idx: usize, mode: ModeAndSubmode) -> Result<(), ModeError> {
+        let request_id = self.next_request_id();
+        let child = &mut self.children[idx];
+        child.commanded = Some(mode);
+        child.reported = None;
+        self.interface
+            .send_mode_request(request_id, child.id, ModeRequest::SetMode(mode))?;
+        Ok(())
+    }
+
+    /// Select the active children and command them to the target mode.
+    fn command_active_children(
+        &mut self,
+        requestor: Option<MessageMetadata>,
+        target: ModeAndSubmode,
+    ) -> Result<(), ModeError> {
+        let mut num_active = self
+            .children
+            .iter()
+            .filter(|child| child.active && child.health == ChildHealth::Healthy)
+            .count();
+        for child in self.children.iter_mut() {
+            if num_active == self.num_active {
+                break;
+            }
+            if !child.active && child.health == ChildHealth::Healthy {
+                child.active = true;
+                num_active += 1;
+            }
+        }
+        if num_active == 0 {
+            self.transition = None;
+            self.mode = self.off_mode;
+            if let Some(requestor) = requestor.or(self.last_requestor) {
+                self.send_mode_reply(
+                    requestor,
+                    ModeReply::CantReachMode(assembly_res_code::NO_HEALTHY_CHILD),
+                )?;
+            }
+            return Ok(());
+        }
+        self.transition = Some(AssemblyTransition { target, requestor });
+        for idx in 0..self.children.len() {
+            let child = &self.children[idx];
+            if child.active && child.commanded != Some(target) {
+                self.command_child(idx, target)?;
+            }
+        }
+        self.check_transition_done()
+    }
+
+    fn check_transition_done(&mut self) -> Result<(), ModeError> {
+        let transition = match self.transition {
+            Some(transition) => transition,
+            None => return Ok(()),
+        };
+        if self.children.iter().all(|child| child.transition_done()) {
+            self.handle_mode_reached(transition.requestor)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Interface> ModeProvider for RedundantAssembly<Interface> {
+    fn mode_and_submode(&self) -> ModeAndSubmode {
+        self.mode
+    }
+}
+
+impl<Interface: ModeRequestSender + ModeRequestReceiver + ModeReplySender + ModeReplyReceiver>
+    ModeRequestHandler for RedundantAssembly<Interface>
+{
+    type Error = ModeError;
+
+    fn start_transition(
+        &mut self,
+        requestor: MessageMetadata,
+        mode_and_submode: ModeAndSubmode,
+    ) -> Result<(), Self::Error> {
+        self.last_requestor = Some(requestor);
+        if mode_and_submode != self.off_mode {
+            return self.command_active_children(Some(requestor), mode_and_submode);
+        }
+        self.transition = Some(AssemblyTransition {
+            target: mode_and_submode,
+            requestor: Some(requestor),
+        });
+        for idx in 0..self.children.len() {
+            let child = &mut self.children[idx];
+            let commanded = child.commanded;
+            child.active = false;
+            if child.health == ChildHealth::Healthy
+                && commanded.is_some()
+                && commanded != Some(self.off_mode)
+            {
+                self.command_child(idx, self.off_mode)?;
+            }
+        }
+        self.check_transition_done()
+    }
+
+    fn announce_mode(&self, _requestor_info: Option<MessageMetadata>, recursive: bool) {
+        if !recursive {
+            return;
+        }
+        for child in self.children.iter().filter(|child| child.active) {
+            if self
+                .interface
+                .send_mode_request(0, child.id, ModeRequest::AnnounceModeRecursive)
+                .is_err()
+            {
+                trace_warn!("announcing mode to child {} failed", child.id);
+            }
+        }
+    }
+
+    fn handle_mode_reached(
+        &mut self,
+        requestor_info: Option<MessageMetadata>,
+    ) -> Result<(), Self::Error> {
+        if let Some(transition) = self.transition.take() {
+            self.mode = transition.target;
+        }
+        if let Some(requestor) = requestor_info {
+            self.send_mode_reply(requestor, ModeReply::ModeReply(self.mode))?;
+        }
+        Ok(())
+    }
+
+    fn handle_mode_info(
+        &mut self,
+        _requestor_info: MessageMetadata,
+        _info: ModeAndSubmode,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn send_mode_reply(
+        &self,
+        requestor_info: MessageMetadata,
+        reply: ModeReply,
+    ) -> Result<(), Self::Error> {
+        ModeReplySender::send_mode_reply(&self.interface, requestor_info, reply)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mode::ModeRequestorAndHandlerMpsc;
+    use satrs_shared::res_code::ResultU16;
+    use std::sync::mpsc;
+
+    const ASSEMBLY_ID: ComponentId = 10;
+    const PARENT_ID: ComponentId = 1;
+    const CHILD_0: ComponentId = 20;
+    const CHILD_1: ComponentId = 21;
+    const OFF: ModeAndSubmode = ModeAndSubmode::new(0, 0);
+    const ON: ModeAndSubmode = ModeAndSubmode::new(1, 0);
+
+    struct Testbench {
+        assembly: RedundantAssembly<ModeRequestorAndHandlerMpsc>,
+        parent_request_tx: mpsc::Sender<GenericMessage<ModeRequest>>,
+        parent_reply_rx: mpsc::Receiver<GenericMessage<ModeReply>>,
+        child_reply_tx: mpsc::Sender<GenericMessage<ModeReply>>,
+        child_request_rx: [mpsc::Receiver<GenericMessage<ModeRequest>>; 2],
+    }
+
+    impl Testbench {
+        fn new() -> Self {
+            let (parent_request_tx, request_rx) = mpsc::channel();
+            let (child_reply_tx, reply_rx) = mpsc::channel();
+            let (parent_reply_tx, parent_reply_rx) = mpsc::channel();
+            let (child_0_tx, child_0_rx) = mpsc::channel();
+            let (child_1_tx, child_1_rx) = mpsc::channel();
+            let mut interface = ModeRequestorAndHandlerMpsc::new(ASSEMBLY_ID, request_rx, reply_rx);
+            interface.add_request_target(CHILD_0, child_0_tx);
+            interface.add_request_target(CHILD_1, child_1_tx);
+            interface.add_reply_target(PARENT_ID, parent_reply_tx);
+            let mut assembly = RedundantAssembly::new(ASSEMBLY_ID, interface, OFF, 1);
+            assembly.add_child(CHILD_0);
+            assembly.add_child(CHILD_1);
+            Self {
+                assembly,
+                parent_request_tx,
+                parent_reply_rx,
+                child_reply_tx,
+                child_request_rx: [child_0_rx, child_1_rx],
+            }
+        }
+
+        fn command(&mut self, mode: ModeAndSubmode) {
+            self.parent_request_tx
+                .send(GenericMessage::new(
+                    MessageMetadata::new(5, PARENT_ID),
+                    ModeRequest::SetMode(mode),
+                ))
+                .unwrap();
+            self.assembly.periodic_operation().unwrap();
+        }
+
+        fn child_idx(child_id: ComponentId) -> usize {
+            (child_id - CHILD_0) as usize
+        }
+
+        fn expect_child_request(&self, child_id: ComponentId, mode: ModeAndSubmode) {
+            let request = self.child_request_rx[Self::child_idx(child_id)]
+                .try_recv()
+                .expect("no request for child");
+            assert_eq!(request.sender_id(), ASSEMBLY_ID);
+            assert_eq!(request.message, ModeRequest::SetMode(mode));
+        }
+
+        fn check_no_child_request(&self, child_id: ComponentId) {
+            assert!(self.child_request_rx[Self::child_idx(child_id)]
+                .try_recv()
+                .is_err());
+        }
+
+        fn child_reply(&mut self, child_id: ComponentId, reply: ModeReply) {
+            self.child_reply_tx
+                .send(GenericMessage::new(
+                    MessageMetadata::new(0, child_id),
+                    reply,
+                ))
+                .unwrap();
+            self.assembly.periodic_operation().unwrap();
+        }
+
+        fn expect_parent_reply(&self, reply: ModeReply) {
+            let parent_reply = self.parent_reply_rx.try_recv().expect("no parent reply");
+            assert_eq!(parent_reply.request_id(), 5);
+            assert_eq!(parent_reply.sender_id(), ASSEMBLY_ID);
+            assert_eq!(parent_reply.message, reply);
+        }
+
+        fn switch_on(&mut self) {
+            self.command(ON);
+            self.expect_child_request(CHILD_0, ON);
+            self.check_no_child_request(CHILD_1);
+            self.child_reply(CHILD_0, ModeReply::ModeReply(ON));
+            self.expect_parent_reply(ModeReply::ModeReply(ON));
+        }
+    }
+
+    #[test]
+    fn test_mode_command_uses_one_child() {
+        let mut tb = Testbench::new();
+        assert_eq!(tb.assembly.mode_and_submode(), OFF);
+        tb.command(ON);
+        assert_eq!(tb.assembly.target_mode(), Some(ON));
+        assert!(tb.parent_reply_rx.try_recv().is_err());
+        tb.expect_child_request(CHILD_0, ON);
+        tb.check_no_child_request(CHILD_1);
+        tb.child_reply(CHILD_0, ModeReply::ModeReply(ON));
+        tb.expect_parent_reply(ModeReply::ModeReply(ON));
+        assert_eq!(tb.assembly.mode_and_submode(), ON);
+        assert_eq!(tb.assembly.target_mode(), None);
+        let child = tb.assembly.child(CHILD_0).unwrap();
+        assert!(child.active);
+        assert_eq!(child.reported, Some(ON));
+        assert!(!tb.assembly.child(CHILD_1).unwrap().active);
+    }
+
+    #[test]
+    fn test_failover_on_fault() {
+        let mut tb = Testbench::new();
+        tb.switch_on();
+        tb.assembly.handle_child_fault(CHILD_0).unwrap();
+        tb.expect_child_request(CHILD_0, OFF);
+        tb.expect_child_request(CHILD_1, ON);
+        assert_eq!(tb.assembly.target_mode(), Some(ON));
+        tb.child_reply(CHILD_1, ModeReply::ModeReply(ON));
+        assert_eq!(tb.assembly.mode_and_submode(), ON);
+        // The failover was not requested by the parent.
+        assert!(tb.parent_reply_rx.try_recv().is_err());
+        let child = tb.assembly.child(CHILD_0).unwrap();
+        assert_eq!(child.health, ChildHealth::Faulty);
+        assert!(!child.active);
+        assert!(tb.assembly.child(CHILD_1).unwrap().active);
+    }
+
+    #[test]
+    fn test_failover_during_transition() {
+        let mut tb = Testbench::new();
+        tb.command(ON);
+        tb.expect_child_request(CHILD_0, ON);
+        tb.child_reply(CHILD_0, ModeReply::CantReachMode(ResultU16::new(1, 2)));
+        tb.expect_child_request(CHILD_0, OFF);
+        tb.expect_child_request(CHILD_1, ON);
+        tb.child_reply(CHILD_1, ModeReply::ModeReply(ON));
+        tb.expect_parent_reply(ModeReply::ModeReply(ON));
+    }
+
+    #[test]
+    fn test_failover_without_spare() {
+        let mut tb = Testbench::new();
+        tb.assembly
+            .set_child_health(CHILD_1, ChildHealth::Faulty)
+            .unwrap();
+        tb.switch_on();
+        tb.assembly.handle_child_fault(CHILD_0).unwrap();
+        tb.expect_child_request(CHILD_0, OFF);
+        tb.check_no_child_request(CHILD_1);
+        // The parent is informed that the commanded mode was lost.
+        tb.expect_parent_reply(ModeReply::CantReachMode(
+            assembly_res_code::NO_HEALTHY_CHILD,
+        ));
+        assert_eq!(tb.assembly.mode_and_submode(), OFF);
+        assert_eq!(tb.assembly.target_mode(), None);
+    }
+
+    #[test]
+    fn test_no_healthy_child() {
+        let mut tb = Testbench::new();
+        tb.assembly
+            .set_child_health(CHILD_1, ChildHealth::Faulty)
+            .unwrap();
+        tb.command(ON);
+        tb.expect_child_request(CHILD_0, ON);
+        tb.child_reply(
+            CHILD_0,
+            ModeReply::WrongMode {
+                expected: ON,
+                reached: OFF,
+            },
+        );
+        tb.expect_child_request(CHILD_0, OFF);
+        tb.check_no_child_request(CHILD_1);
+        tb.expect_parent_reply(ModeReply::CantReachMode(
+            assembly_res_code::NO_HEALTHY_CHILD,
+        ));
+        assert_eq!(tb.assembly.mode_and_submode(), OFF);
+
+        // A recovered child can be used again.
+        tb.assembly
+            .set_child_health(CHILD_1, ChildHealth::Healthy)
+            .unwrap();
+        tb.command(ON);
+        tb.expect_child_request(CHILD_1, ON);
+    }
+
+    #[test]
+    fn test_switch_off() {
+        let mut tb = Testbench::new();
+        tb.switch_on();
+        tb.command(OFF);
+        tb.expect_child_request(CHILD_0, OFF);
+        tb.check_no_child_request(CHILD_1);
+        assert_eq!(tb.assembly.target_mode(), Some(OFF));
+        tb.child_reply(CHILD_0, ModeReply::ModeReply(OFF));
+        tb.expect_parent_reply(ModeReply::ModeReply(OFF));
+        assert_eq!(tb.assembly.mode_and_submode(), OFF);
+        assert!(tb.assembly.children().iter().all(|child| !child.active));
+    }
+
+    #[test]
+    fn test_read_mode_and_unknown_child_reply() {
+        let mut tb = Testbench::new();
+        tb.child_reply(99, ModeReply::ModeReply(ON));
+        tb.parent_request_tx
+            .send(GenericMessage::new(
+                MessageMetadata::new(5, PARENT_ID),
+                ModeRequest::ReadMode,
+            ))
+            .unwrap();
+        tb.assembly.periodic_operation().unwrap();
+        tb.expect_parent_reply(ModeReply::ModeReply(OFF));
+    }
+}
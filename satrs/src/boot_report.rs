@@ -0,0 +1,380 @@
+//! # Boot report
+//!
+//! After a reboot, the ground usually wants to know which software version is running, why the
+//! on-board computer was reset and how often it has booted so far. The [BootReporter] gathers
+//! this information into a [BootInfo] and reports it with a dedicated boot report TM packet and
+//! the [BOOT] event of info severity.
+//!
+//! The reset reason is retrieved from a mission-specific [ResetReasonProvider], which usually
+//! reads a hardware register. The [BootCounter] keeps the boot count in a
+//! [NvStorage][crate::nv_storage::NvStorage] record.
+//!
+//! The boot report TM uses the mission-specific
+//! [ONBOARD_MESSAGE_SERVICE][crate::log_report::ONBOARD_MESSAGE_SERVICE] with the
+//! [BOOT_REPORT_SUBSERVICE] by default. Its source data is the serialized [BootInfo]. The [BOOT]
+//! event is sent with a [U32Pair][crate::params::U32Pair] parameter which contains the raw
+//! [ResetReason] and the boot count.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::mpsc;
+//! use satrs::boot_report::{
+//!     BootCounter, BootInfo, BootReportCfg, BootReporter, ResetReason, SoftwareVersion,
+//! };
+//! use satrs::event_man::EventU32SenderMpsc;
+//! use satrs::nv_storage::RamNvStorage;
+//! use satrs::tmtc::PacketAsVec;
+//!
+//! const BOOT_COUNTER_KEY: u16 = 0;
+//!
+//! let mut storage = RamNvStorage::default();
+//! let mut boot_counter = BootCounter::new(&mut storage, BOOT_COUNTER_KEY);
+//! let mut reset_reason = ResetReason::Watchdog;
+//! let boot_info =
+//!     BootInfo::gather(SoftwareVersion::new(1, 2, 0), &mut reset_reason, &mut boot_counter)
+//!         .unwrap();
+//! assert_eq!(boot_info.boot_count, 1);
+//!
+//! let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+//! let (event_tx, event_rx) = mpsc::channel();
+//! let reporter = BootReporter::new(1, BootReportCfg::new(0x02));
+//! reporter.send_tm(&boot_info, &tm_tx, &[0; 7]).unwrap();
+//! reporter
+//!     .send_event(&boot_info, &EventU32SenderMpsc::new(0, event_tx))
+//!     .unwrap();
+//! assert!(tm_rx.try_recv().is_ok());
+//! assert!(event_rx.try_recv().is_ok());
+//! ```
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+use spacepackets::{ByteConversionError, SpHeader};
+
+use crate::error_report::FRAMEWORK_EVENT_GROUP_ID;
+use crate::event_man::{EventMessage, EventSendProvider};
+use crate::events::{EventU32, SeverityInfo};
+use crate::log_report::ONBOARD_MESSAGE_SERVICE;
+use crate::nv_storage::{NvKey, NvStorage};
+use crate::params::{Params, ParamsRaw};
+use crate::pus::{EcssTmSender, EcssTmtcError, PusTmVariant};
+use crate::ComponentId;
+
+/// Subservice of the boot report TM generated by the [BootReporter] by default.
+pub const BOOT_REPORT_SUBSERVICE: u8 = 2;
+
+crate::event_definitions! {
+    pub BOOT_EVENT_DEFINITIONS;
+    /// The on-board software has booted.
+    pub BOOT: SeverityInfo = (FRAMEWORK_EVENT_GROUP_ID, 3), U32Pair,
+        "Software booted, contains the reset reason and the boot count";
+}
+
+/// Reason of the last reset of the on-board computer.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum ResetReason {
+    #[default]
+    Unknown = 0,
+    PowerOn = 1,
+    /// Reset triggered by an external reset pin or a supervisor.
+    External = 2,
+    Watchdog = 3,
+    /// Reset requested by the software, for example by a reboot command.
+    Software = 4,
+    Brownout = 5,
+    /// Reset after a fatal exception or a panic.
+    Exception = 6,
+}
+
+/// Generic trait for mission-specific components which determine the [ResetReason], for example
+/// by reading the reset status register of the microcontroller.
+pub trait ResetReasonProvider {
+    fn reset_reason(&mut self) -> ResetReason;
+}
+
+/// A fixed reset reason, which is useful for hosted environments and tests.
+impl ResetReasonProvider for ResetReason {
+    fn reset_reason(&mut self) -> ResetReason {
+        *self
+    }
+}
+
+/// Version of the on-board software.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SoftwareVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl SoftwareVersion {
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+/// Boot counter which is kept in a [NvStorage] record.
+pub struct BootCounter<Storage: NvStorage> {
+    storage: Storage,
+    key: NvKey,
+}
+
+impl<Storage: NvStorage> BootCounter<Storage> {
+    pub fn new(storage: Storage, key: NvKey) -> Self {
+        Self { storage, key }
+    }
+
+    pub fn key(&self) -> NvKey {
+        self.key
+    }
+
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
+    /// Stored boot count, which is 0 if no boot was counted yet.
+    pub fn boot_count(&mut self) -> Result<u32, Storage::Error> {
+        Ok(self.storage.load_u32(self.key)?.unwrap_or(0))
+    }
+
+    /// Increment the stored boot count and return the new value. This should be called exactly
+    /// once per boot.
+    pub fn increment(&mut self) -> Result<u32, Storage::Error> {
+        let boot_count = self.boot_count()?.wrapping_add(1);
+        self.storage.store_u32(self.key, boot_count)?;
+        Ok(boot_count)
+    }
+}
+
+/// Information contained in the boot report.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BootInfo {
+    pub version: SoftwareVersion,
+    pub reset_reason: ResetReason,
+    pub boot_count: u32,
+}
+
+impl BootInfo {
+    /// Length of the serialized boot information: The three version bytes, the reset reason byte
+    /// and the boot count as a big endian [u32].
+    pub const SIZE_AS_BYTES: usize = 8;
+
+    pub const fn new(version: SoftwareVersion, reset_reason: ResetReason, boot_count: u32) -> Self {
+        Self {
+            version,
+            reset_reason,
+            boot_count,
+        }
+    }
+
+    /// Gather the boot information. This increments the boot counter, so it should be called
+    /// exactly once per boot.
+    pub fn gather<Storage: NvStorage>(
+        version: SoftwareVersion,
+        reset_reason_provider: &mut impl ResetReasonProvider,
+        boot_counter: &mut BootCounter<Storage>,
+    ) -> Result<Self, Storage::Error> {
+        Ok(Self {
+            version,
+            reset_reason: reset_reason_provider.reset_reason(),
+            boot_count: boot_counter.increment()?,
+        })
+    }
+
+    pub fn write_to_be_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+        if buf.len() < Self::SIZE_AS_BYTES {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: Self::SIZE_AS_BYTES,
+            });
+        }
+        buf[0] = self.version.major;
+        buf[1] = self.version.minor;
+        buf[2] = self.version.patch;
+        buf[3] = self.reset_reason.into();
+        buf[4..8].copy_from_slice(&self.boot_count.to_be_bytes());
+        Ok(Self::SIZE_AS_BYTES)
+    }
+
+    /// Deserialize boot information previously written with [Self::write_to_be_bytes]. Returns
+    /// [None] if the buffer is too short or the reset reason is invalid.
+    pub fn from_bytes(buf: &[u8]) -> Option<Self> {
+        if buf.len() < Self::SIZE_AS_BYTES {
+            return None;
+        }
+        Some(Self {
+            version: SoftwareVersion::new(buf[0], buf[1], buf[2]),
+            reset_reason: ResetReason::try_from(buf[3]).ok()?,
+            boot_count: u32::from_be_bytes(buf[4..8].try_into().unwrap()),
+        })
+    }
+}
+
+/// Configuration of the boot report TM generated by the [BootReporter].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BootReportCfg {
+    pub apid: u16,
+    pub service: u8,
+    pub subservice: u8,
+    pub dest_id: u16,
+}
+
+impl BootReportCfg {
+    /// Configuration with the [ONBOARD_MESSAGE_SERVICE], the [BOOT_REPORT_SUBSERVICE] and the
+    /// destination ID 0.
+    pub const fn new(apid: u16) -> Self {
+        Self {
+            apid,
+            service: ONBOARD_MESSAGE_SERVICE,
+            subservice: BOOT_REPORT_SUBSERVICE,
+            dest_id: 0,
+        }
+    }
+}
+
+/// Reports the [BootInfo] with the boot report TM and the [BOOT] event.
+#[derive(Debug, Copy, Clone)]
+pub struct BootReporter {
+    id: ComponentId,
+    pub cfg: BootReportCfg,
+}
+
+impl BootReporter {
+    /// Create a new reporter. The given component ID is used as the sender ID of the TM and the
+    /// event.
+    pub fn new(id: ComponentId, cfg: BootReportCfg) -> Self {
+        Self { id, cfg }
+    }
+
+    pub fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    /// Send the boot report TM.
+    pub fn send_tm(
+        &self,
+        info: &BootInfo,
+        tm_sender: &impl EcssTmSender,
+        time_stamp: &[u8],
+    ) -> Result<(), EcssTmtcError> {
+        let mut source_data = [0; BootInfo::SIZE_AS_BYTES];
+        info.write_to_be_bytes(&mut source_data)?;
+        let sec_header = PusTmSecondaryHeader::new(
+            self.cfg.service,
+            self.cfg.subservice,
+            0,
+            self.cfg.dest_id,
+            time_stamp,
+        );
+        let tm = PusTmCreator::new(
+            SpHeader::new_from_apid(self.cfg.apid),
+            sec_header,
+            &source_data,
+            true,
+        );
+        tm_sender.send_tm(self.id, PusTmVariant::Direct(tm))
+    }
+
+    /// Send the [BOOT] event.
+    pub fn send_event<Sender: EventSendProvider<EventU32>>(
+        &self,
+        info: &BootInfo,
+        event_sender: &Sender,
+    ) -> Result<(), Sender::Error> {
+        let params = Params::from(ParamsRaw::from((
+            u8::from(info.reset_reason) as u32,
+            info.boot_count,
+        )));
+        event_sender.send(EventMessage::new_with_params(self.id, BOOT.into(), &params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::PusPacket;
+
+    use super::*;
+    use crate::event_man::EventU32SenderMpsc;
+    use crate::nv_storage::RamNvStorage;
+    use crate::params::{ParamsHeapless, U32Pair};
+    use crate::tmtc::PacketAsVec;
+
+    const REPORTER_ID: ComponentId = 0x05;
+    const BOOT_COUNTER_KEY: NvKey = 4;
+
+    #[test]
+    fn test_boot_counter() {
+        let mut storage = RamNvStorage::default();
+        let mut boot_counter = BootCounter::new(&mut storage, BOOT_COUNTER_KEY);
+        assert_eq!(boot_counter.boot_count().unwrap(), 0);
+        assert_eq!(boot_counter.increment().unwrap(), 1);
+        // Reboot.
+        let mut boot_counter = BootCounter::new(&mut storage, BOOT_COUNTER_KEY);
+        let info = BootInfo::gather(
+            SoftwareVersion::new(0, 3, 1),
+            &mut ResetReason::Software,
+            &mut boot_counter,
+        )
+        .unwrap();
+        assert_eq!(
+            info,
+            BootInfo::new(SoftwareVersion::new(0, 3, 1), ResetReason::Software, 2)
+        );
+        assert_eq!(storage.load_u32(BOOT_COUNTER_KEY).unwrap(), Some(2));
+    }
+
+    #[test]
+    fn test_boot_info_conversion() {
+        let info = BootInfo::new(SoftwareVersion::new(1, 2, 3), ResetReason::Brownout, 500);
+        let mut buf = [0; 8];
+        assert_eq!(info.write_to_be_bytes(&mut buf).unwrap(), 8);
+        assert_eq!(buf, [1, 2, 3, 5, 0, 0, 0x01, 0xf4]);
+        assert_eq!(BootInfo::from_bytes(&buf), Some(info));
+        buf[3] = 0xff;
+        assert_eq!(BootInfo::from_bytes(&buf), None);
+        assert!(info.write_to_be_bytes(&mut buf[0..7]).is_err());
+    }
+
+    #[test]
+    fn test_boot_report_tm_and_event() {
+        let info = BootInfo::new(SoftwareVersion::new(1, 0, 0), ResetReason::Watchdog, 7);
+        let reporter = BootReporter::new(REPORTER_ID, BootReportCfg::new(0x20));
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        reporter.send_tm(&info, &tm_tx, &[0; 7]).unwrap();
+        let packet = tm_rx.try_recv().unwrap();
+        assert_eq!(packet.sender_id, REPORTER_ID);
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(tm.service(), ONBOARD_MESSAGE_SERVICE);
+        assert_eq!(tm.subservice(), BOOT_REPORT_SUBSERVICE);
+        assert_eq!(BootInfo::from_bytes(tm.source_data()), Some(info));
+
+        let (event_tx, event_rx) = mpsc::channel();
+        reporter
+            .send_event(&info, &EventU32SenderMpsc::new(0, event_tx))
+            .unwrap();
+        let event_msg = event_rx.try_recv().unwrap();
+        assert_eq!(event_msg.sender_id(), REPORTER_ID);
+        assert_eq!(event_msg.event(), EventU32::from(BOOT));
+        assert_eq!(
+            event_msg.params(),
+            Some(&Params::Heapless(ParamsHeapless::Raw(ParamsRaw::U32Pair(
+                U32Pair(3, 7)
+            ))))
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! # Ground-side TMTC client utilities
+//!
+//! This module contains small helpers to talk to an on-board software built with sat-rs from the
+//! ground side, for example to write integration tests or simple ground tools in Rust. It is
+//! only available with the `client` feature.
+//!
+//! The module provides
+//!
+//!  - The [UdpTmtcClient] for the [crate::hal::std::udp_server::UdpTcServer].
+//!  - The [TcpTmtcClient] for the [crate::hal::std::tcp_server::TcpTmtcInCobsServer] and the
+//!    [crate::hal::std::tcp_server::TcpSpacepacketsServer]. The [TcpFraming] must match the
+//!    server.
+//!  - The [PusTcBuilder] which creates common PUS telecommands like ping requests, HK
+//!    generation requests or time-tagged commands for the TC scheduler.
+//!  - The [TmPrinter] which prints a human readable summary of received PUS telemetry.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use satrs::client::{PusTcBuilder, TmPrinter, TmtcClient, UdpTmtcClient};
+//!
+//! let mut client = UdpTmtcClient::new("127.0.0.1:0", "127.0.0.1:7301", 4096).unwrap();
+//! let mut tc_builder = PusTcBuilder::new(0x02);
+//! let ping = tc_builder.ping().unwrap();
+//! client.send_packet(&ping.packet).unwrap();
+//! for tm in client.recv_packets(Duration::from_secs(1)).unwrap() {
+//!     println!("{}", TmPrinter::new(&tm, 7));
+//! }
+//! ```
+use std::io;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use spacepackets::ecss::PusError;
+use spacepackets::time::TimestampError;
+
+mod pus;
+mod tcp;
+mod udp;
+
+pub use pus::*;
+pub use tcp::*;
+pub use udp::*;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("PUS error: {0}")]
+    Pus(#[from] PusError),
+    #[error("timestamp error: {0}")]
+    Timestamp(#[from] TimestampError),
+}
+
+/// Generic trait for clients which exchange raw TMTC packets with an on-board software.
+pub trait TmtcClient {
+    /// Send one raw packet, for example a PUS telecommand.
+    fn send_packet(&mut self, packet: &[u8]) -> io::Result<()>;
+
+    /// Receive one raw packet. Returns [None] if no packet was received until the timeout
+    /// expired.
+    fn recv_packet(&mut self, timeout: Duration) -> io::Result<Option<Vec<u8>>>;
+
+    /// Receive all packets which arrive until the timeout expired.
+    fn recv_packets(&mut self, timeout: Duration) -> io::Result<Vec<Vec<u8>>> {
+        let deadline = Instant::now() + timeout;
+        let mut packets = Vec::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(packets);
+            }
+            match self.recv_packet(remaining)? {
+                Some(packet) => packets.push(packet),
+                None => return Ok(packets),
+            }
+        }
+    }
+}
+
+/// Sockets reject a read timeout of zero, so the smallest timeout is one millisecond.
+fn read_timeout(timeout: Duration) -> Duration {
+    timeout.max(Duration::from_millis(1))
+}
+
+fn is_timeout(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::WouldBlock || error.kind() == io::ErrorKind::TimedOut
+}
@@ -0,0 +1,300 @@
+use core::fmt::{Display, Formatter};
+use std::vec::Vec;
+
+use spacepackets::ecss::tm::PusTmReader;
+use spacepackets::ecss::{hk, scheduling, PusError, PusPacket};
+use spacepackets::time::TimeWriter;
+use spacepackets::CcsdsPacket;
+
+use crate::events::EventU32;
+use crate::pus::tc_builder::TcBuilder;
+use crate::pus::verification::RequestId;
+use crate::request::Apid;
+use crate::seq_count::CcsdsSimpleSeqCountProvider;
+
+use super::ClientError;
+
+/// Serialized PUS telecommand together with its verification request ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltTc {
+    pub request_id: RequestId,
+    pub packet: Vec<u8>,
+}
+
+/// Builder for common PUS telecommands. Each telecommand gets the next packet sequence count.
+pub struct PusTcBuilder {
+    apid: Apid,
+    seq_count: CcsdsSimpleSeqCountProvider,
+}
+
+impl PusTcBuilder {
+    pub fn new(apid: Apid) -> Self {
+        Self {
+            apid,
+            seq_count: CcsdsSimpleSeqCountProvider::default(),
+        }
+    }
+
+    pub fn apid(&self) -> Apid {
+        self.apid
+    }
+
+    /// Build a generic telecommand with the given service, subservice and application data.
+    pub fn build(
+        &mut self,
+        service: u8,
+        subservice: u8,
+        app_data: &[u8],
+    ) -> Result<BuiltTc, PusError> {
+        Self::finish(self.tc_builder(service, subservice).app_data(app_data))
+    }
+
+    /// [TcBuilder] with the APID and the next sequence count of this builder.
+    pub fn tc_builder(&mut self, service: u8, subservice: u8) -> TcBuilder {
+        TcBuilder::new(service, subservice)
+            .apid(self.apid)
+            .seq_count_from(&self.seq_count)
+    }
+
+    fn finish(tc: TcBuilder) -> Result<BuiltTc, PusError> {
+        Ok(BuiltTc {
+            request_id: tc.request_id(),
+            packet: tc.to_vec()?,
+        })
+    }
+
+    /// Ping telecommand TC[17,1].
+    pub fn ping(&mut self) -> Result<BuiltTc, PusError> {
+        self.build(17, 1, &[])
+    }
+
+    /// Enable the periodic generation of a HK set with TC[3,5]. The application data contains
+    /// the target ID and the set ID as big endian [u32] values.
+    pub fn enable_hk(&mut self, target_id: u32, set_id: u32) -> Result<BuiltTc, PusError> {
+        self.hk_request(hk::Subservice::TcEnableHkGeneration, target_id, set_id)
+    }
+
+    /// Disable the periodic generation of a HK set with TC[3,6].
+    pub fn disable_hk(&mut self, target_id: u32, set_id: u32) -> Result<BuiltTc, PusError> {
+        self.hk_request(hk::Subservice::TcDisableHkGeneration, target_id, set_id)
+    }
+
+    /// Request a single HK report with TC[3,27].
+    pub fn one_shot_hk(&mut self, target_id: u32, set_id: u32) -> Result<BuiltTc, PusError> {
+        self.hk_request(hk::Subservice::TcGenerateOneShotHk, target_id, set_id)
+    }
+
+    fn hk_request(
+        &mut self,
+        subservice: hk::Subservice,
+        target_id: u32,
+        set_id: u32,
+    ) -> Result<BuiltTc, PusError> {
+        Self::finish(
+            self.tc_builder(3, subservice as u8)
+                .app_data_value(target_id)
+                .app_data_value(set_id),
+        )
+    }
+
+    /// Insert a telecommand into the time-based schedule with TC[11,4]. The application data has
+    /// the format expected by [crate::pus::scheduler::generate_insert_telecommand_app_data].
+    pub fn schedule_insert(
+        &mut self,
+        release_time: &impl TimeWriter,
+        tc: &BuiltTc,
+    ) -> Result<BuiltTc, ClientError> {
+        let mut app_data = Vec::with_capacity(2 + release_time.len_written() + tc.packet.len());
+        app_data.extend_from_slice(&1_u16.to_be_bytes());
+        app_data.extend_from_slice(&release_time.to_vec()?);
+        app_data.extend_from_slice(&tc.packet);
+        Ok(self.build(
+            11,
+            scheduling::Subservice::TcInsertActivity as u8,
+            &app_data,
+        )?)
+    }
+}
+
+/// Request ID of a verification TM, or [None] if the TM is not a valid verification TM.
+pub fn verification_request_id(tm: &PusTmReader) -> Option<RequestId> {
+    if tm.service() != 1 {
+        return None;
+    }
+    RequestId::from_bytes(tm.source_data())
+}
+
+/// Prints a one-line human readable summary of a raw PUS TM packet.
+///
+/// Verification reports, ping replies and event reports are decoded, all other packets are
+/// only summarized. Invalid packets are printed with the parser error.
+pub struct TmPrinter<'raw> {
+    raw_tm: &'raw [u8],
+    timestamp_len: usize,
+}
+
+impl<'raw> TmPrinter<'raw> {
+    pub fn new(raw_tm: &'raw [u8], timestamp_len: usize) -> Self {
+        Self {
+            raw_tm,
+            timestamp_len,
+        }
+    }
+
+    fn fmt_verification(tm: &PusTmReader, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let report = match tm.subservice() {
+            1 => "acceptance success",
+            2 => "acceptance failure",
+            3 => "start success",
+            4 => "start failure",
+            5 => "step success",
+            6 => "step failure",
+            7 => "completion success",
+            8 => "completion failure",
+            _ => "unknown verification report",
+        };
+        match verification_request_id(tm) {
+            Some(req_id) => write!(f, "{report} for request ID {req_id}"),
+            None => write!(f, "{report} without request ID"),
+        }
+    }
+
+    fn fmt_event(tm: &PusTmReader, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let source_data = tm.source_data();
+        if source_data.len() < 4 {
+            return write!(f, "event report without event ID");
+        }
+        let event = EventU32::from(u32::from_be_bytes(source_data[0..4].try_into().unwrap()));
+        write!(
+            f,
+            "{:?} event with group ID {} and unique ID {}, {} bytes of auxiliary data",
+            event.severity(),
+            event.group_id(),
+            event.unique_id(),
+            source_data.len() - 4
+        )
+    }
+}
+
+impl Display for TmPrinter<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let tm = match PusTmReader::new(self.raw_tm, self.timestamp_len) {
+            Ok((tm, _)) => tm,
+            Err(e) => return write!(f, "invalid PUS TM with {} bytes: {e}", self.raw_tm.len()),
+        };
+        write!(
+            f,
+            "TM[{},{}] APID {:#05x} SSC {}: ",
+            tm.service(),
+            tm.subservice(),
+            tm.apid(),
+            tm.seq_count()
+        )?;
+        match (tm.service(), tm.subservice()) {
+            (1, _) => Self::fmt_verification(&tm, f),
+            (5, 1..=4) => Self::fmt_event(&tm, f),
+            (17, 2) => write!(f, "ping reply"),
+            (3, 25) => write!(
+                f,
+                "HK report with {} bytes of source data",
+                tm.source_data().len()
+            ),
+            _ => write!(f, "{} bytes of source data", tm.source_data().len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{GenericEvent, Severity};
+    use crate::pus::scheduler::generate_insert_telecommand_app_data_as_vec;
+    use crate::pus::verification::{
+        VerificationReporter, VerificationReporterCfg, VerificationReportingProvider,
+    };
+    use crate::tmtc::PacketAsVec;
+    use spacepackets::ecss::tc::{PusTcCreator, PusTcReader};
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::time::cds::CdsTime;
+    use spacepackets::SpHeader;
+    use std::string::ToString;
+    use std::sync::mpsc;
+
+    const TEST_APID: u16 = 0x02;
+
+    #[test]
+    fn test_tc_builder() {
+        let mut builder = PusTcBuilder::new(TEST_APID);
+        let ping = builder.ping().unwrap();
+        let (tc, _) = PusTcReader::new(&ping.packet).unwrap();
+        assert_eq!((tc.service(), tc.subservice()), (17, 1));
+        assert_eq!(tc.apid(), TEST_APID);
+        assert_eq!(tc.seq_count(), 0);
+        assert_eq!(ping.request_id, RequestId::new(&tc));
+
+        let enable_hk = builder.enable_hk(0x1020, 3).unwrap();
+        let (tc, _) = PusTcReader::new(&enable_hk.packet).unwrap();
+        assert_eq!(tc.seq_count(), 1);
+        assert_eq!(tc.subservice(), hk::Subservice::TcEnableHkGeneration as u8);
+        assert_eq!(tc.app_data(), &[0, 0, 0x10, 0x20, 0, 0, 0, 3]);
+    }
+
+    #[test]
+    fn test_schedule_insert() {
+        let mut builder = PusTcBuilder::new(TEST_APID);
+        let ping = builder.ping().unwrap();
+        let release_time = CdsTime::new_with_u16_days(100, 1000);
+        let insert = builder.schedule_insert(&release_time, &ping).unwrap();
+        let (tc, _) = PusTcReader::new(&insert.packet).unwrap();
+        assert_eq!((tc.service(), tc.subservice()), (11, 4));
+        let ping_tc = PusTcCreator::new_simple(
+            SpHeader::new_for_unseg_tc(TEST_APID, 0, 0),
+            17,
+            1,
+            &[],
+            true,
+        );
+        let expected =
+            generate_insert_telecommand_app_data_as_vec(&release_time, &ping_tc).unwrap();
+        assert_eq!(tc.app_data(), expected.as_slice());
+    }
+
+    #[test]
+    fn test_tm_printer() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let cfg = VerificationReporterCfg::new(TEST_APID, 1, 2, 8).unwrap();
+        let mut reporter = VerificationReporter::new(0, &cfg);
+        let mut builder = PusTcBuilder::new(TEST_APID);
+        let ping = builder.ping().unwrap();
+        let token = reporter.add_tc_with_req_id(ping.request_id);
+        reporter.acceptance_success(&tm_tx, token, &[0; 7]).unwrap();
+        let tm = tm_rx.try_recv().unwrap();
+        assert_eq!(
+            TmPrinter::new(&tm.packet, 7).to_string(),
+            std::format!(
+                "TM[1,1] APID 0x002 SSC 0: acceptance success for request ID {}",
+                ping.request_id
+            )
+        );
+
+        let event = EventU32::new(Severity::Medium, 2, 5);
+        let sec_header = PusTmSecondaryHeader::new_simple(5, 3, &[0; 7]);
+        let event_tm = PusTmCreator::new(
+            SpHeader::new_from_apid(TEST_APID),
+            sec_header,
+            &event.raw().to_be_bytes(),
+            true,
+        )
+        .to_vec()
+        .unwrap();
+        assert_eq!(
+            TmPrinter::new(&event_tm, 7).to_string(),
+            "TM[5,3] APID 0x002 SSC 0: Medium event with group ID 2 and unique ID 5, \
+            0 bytes of auxiliary data"
+        );
+        assert!(TmPrinter::new(&[0; 4], 7)
+            .to_string()
+            .starts_with("invalid PUS TM with 4 bytes"));
+    }
+}
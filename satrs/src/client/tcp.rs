@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use std::vec;
+use std::vec::Vec;
+
+use spacepackets::CCSDS_HEADER_LEN;
+
+use crate::encoding::encode_packet_with_cobs;
+
+use super::{is_timeout, read_timeout, TmtcClient};
+
+/// Framing of the packets exchanged with a TCP TMTC server.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TcpFraming {
+    /// Packets are encoded with COBS and delimited by a 0 sentinel value, as expected by the
+    /// [crate::hal::std::tcp_server::TcpTmtcInCobsServer].
+    Cobs,
+    /// Packets are CCSDS space packets which are sent without additional framing, as expected
+    /// by the [crate::hal::std::tcp_server::TcpSpacepacketsServer].
+    SpacePackets,
+}
+
+/// Client for the TCP TMTC servers of this crate.
+pub struct TcpTmtcClient {
+    pub stream: TcpStream,
+    framing: TcpFraming,
+    encoding_buf: Vec<u8>,
+    read_buf: Vec<u8>,
+    rx_bytes: Vec<u8>,
+    rx_packets: VecDeque<Vec<u8>>,
+    num_broken_frames: u32,
+}
+
+impl TcpTmtcClient {
+    /// Connect to a TCP TMTC server.
+    ///
+    /// ## Parameter
+    ///
+    /// * `server_addr` - Address of the TCP TMTC server.
+    /// * `framing` - Framing used by the server.
+    /// * `max_packet_size` - Maximum size of sent packets.
+    pub fn connect(
+        server_addr: impl ToSocketAddrs,
+        framing: TcpFraming,
+        max_packet_size: usize,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            stream: TcpStream::connect(server_addr)?,
+            framing,
+            encoding_buf: vec![0; cobs::max_encoding_length(max_packet_size) + 2],
+            read_buf: vec![0; 4096],
+            rx_bytes: Vec::new(),
+            rx_packets: VecDeque::new(),
+            num_broken_frames: 0,
+        })
+    }
+
+    pub fn framing(&self) -> TcpFraming {
+        self.framing
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    /// Number of received COBS frames which could not be decoded.
+    pub fn num_broken_frames(&self) -> u32 {
+        self.num_broken_frames
+    }
+
+    fn parse_received_bytes(&mut self) {
+        match self.framing {
+            TcpFraming::Cobs => self.parse_cobs_frames(),
+            TcpFraming::SpacePackets => self.parse_space_packets(),
+        }
+    }
+
+    fn parse_cobs_frames(&mut self) {
+        let mut frame_start = 0;
+        for idx in 0..self.rx_bytes.len() {
+            if self.rx_bytes[idx] != 0 {
+                continue;
+            }
+            if idx > frame_start {
+                let mut frame = self.rx_bytes[frame_start..idx].to_vec();
+                match cobs::decode_in_place(&mut frame) {
+                    Ok(len) => {
+                        frame.truncate(len);
+                        self.rx_packets.push_back(frame);
+                    }
+                    Err(_) => self.num_broken_frames += 1,
+                }
+            }
+            frame_start = idx + 1;
+        }
+        // Keep the incomplete tail frame.
+        self.rx_bytes.drain(..frame_start);
+    }
+
+    fn parse_space_packets(&mut self) {
+        let mut packet_start = 0;
+        while self.rx_bytes.len() - packet_start >= CCSDS_HEADER_LEN {
+            let data_len = u16::from_be_bytes([
+                self.rx_bytes[packet_start + 4],
+                self.rx_bytes[packet_start + 5],
+            ]) as usize;
+            let packet_len = CCSDS_HEADER_LEN + data_len + 1;
+            if self.rx_bytes.len() - packet_start < packet_len {
+                break;
+            }
+            self.rx_packets
+                .push_back(self.rx_bytes[packet_start..packet_start + packet_len].to_vec());
+            packet_start += packet_len;
+        }
+        self.rx_bytes.drain(..packet_start);
+    }
+}
+
+impl TmtcClient for TcpTmtcClient {
+    fn send_packet(&mut self, packet: &[u8]) -> io::Result<()> {
+        match self.framing {
+            TcpFraming::Cobs => {
+                let mut encoded_len = 0;
+                if !encode_packet_with_cobs(packet, &mut self.encoding_buf, &mut encoded_len) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "packet larger than the maximum packet size",
+                    ));
+                }
+                self.stream.write_all(&self.encoding_buf[..encoded_len])
+            }
+            TcpFraming::SpacePackets => self.stream.write_all(packet),
+        }
+    }
+
+    fn recv_packet(&mut self, timeout: Duration) -> io::Result<Option<Vec<u8>>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(packet) = self.rx_packets.pop_front() {
+                return Ok(Some(packet));
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            self.stream
+                .set_read_timeout(Some(read_timeout(remaining)))?;
+            match self.stream.read(&mut self.read_buf) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "connection closed by server",
+                    ))
+                }
+                Ok(len) => {
+                    self.rx_bytes.extend_from_slice(&self.read_buf[..len]);
+                    self.parse_received_bytes();
+                }
+                Err(e) if is_timeout(&e) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::tests::{INVERTED_PACKET, SIMPLE_PACKET};
+    use spacepackets::ecss::tc::PusTcCreator;
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::SpHeader;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_cobs_framing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client =
+            TcpTmtcClient::connect(listener.local_addr().unwrap(), TcpFraming::Cobs, 64).unwrap();
+        let (mut server_stream, _) = listener.accept().unwrap();
+        client.send_packet(&SIMPLE_PACKET).unwrap();
+        let mut buf = [0; 16];
+        let len = server_stream.read(&mut buf).unwrap();
+        let mut encoded = [0; 16];
+        let mut encoded_len = 0;
+        encode_packet_with_cobs(&SIMPLE_PACKET, &mut encoded, &mut encoded_len);
+        assert_eq!(&buf[..len], &encoded[..encoded_len]);
+
+        // Send one complete frame and the start of a second frame.
+        encoded_len = 0;
+        encode_packet_with_cobs(&INVERTED_PACKET, &mut encoded, &mut encoded_len);
+        server_stream.write_all(&buf[..len]).unwrap();
+        server_stream.write_all(&encoded[..3]).unwrap();
+        server_stream.flush().unwrap();
+        let packet = client.recv_packet(Duration::from_millis(100)).unwrap();
+        assert_eq!(packet.unwrap(), SIMPLE_PACKET);
+        server_stream.write_all(&encoded[3..encoded_len]).unwrap();
+        let packet = client.recv_packet(Duration::from_millis(100)).unwrap();
+        assert_eq!(packet.unwrap(), INVERTED_PACKET);
+        assert!(client
+            .recv_packet(Duration::from_millis(5))
+            .unwrap()
+            .is_none());
+        assert_eq!(client.num_broken_frames(), 0);
+    }
+
+    #[test]
+    fn test_space_packet_framing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client =
+            TcpTmtcClient::connect(listener.local_addr().unwrap(), TcpFraming::SpacePackets, 64)
+                .unwrap();
+        let (mut server_stream, _) = listener.accept().unwrap();
+        let ping = PusTcCreator::new_simple(SpHeader::new_from_apid(0x02), 17, 1, &[], true)
+            .to_vec()
+            .unwrap();
+        client.send_packet(&ping).unwrap();
+        let mut buf = [0; 32];
+        let len = server_stream.read(&mut buf).unwrap();
+        assert_eq!(&buf[..len], ping.as_slice());
+
+        // Two packets, the second one split across two writes.
+        server_stream.write_all(&ping).unwrap();
+        server_stream.write_all(&ping[..8]).unwrap();
+        server_stream.flush().unwrap();
+        let packet = client.recv_packet(Duration::from_millis(100)).unwrap();
+        assert_eq!(packet.unwrap(), ping);
+        server_stream.write_all(&ping[8..]).unwrap();
+        let packet = client.recv_packet(Duration::from_millis(100)).unwrap();
+        assert_eq!(packet.unwrap(), ping);
+        drop(server_stream);
+        let error = client.recv_packet(Duration::from_millis(100)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,93 @@
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+use std::vec;
+use std::vec::Vec;
+
+use super::{is_timeout, read_timeout, TmtcClient};
+
+/// Client for a UDP TMTC server like the [crate::hal::std::udp_server::UdpTcServer].
+///
+/// The server usually sends TM to the address of the last received TC, so the client should
+/// send at least one packet before waiting for telemetry.
+pub struct UdpTmtcClient {
+    pub socket: UdpSocket,
+    server_addr: SocketAddr,
+    recv_buf: Vec<u8>,
+}
+
+impl UdpTmtcClient {
+    /// Create a new client.
+    ///
+    /// ## Parameter
+    ///
+    /// * `bind_addr` - Local address of the client. Port 0 can be used to let the operating
+    ///   system pick a free port.
+    /// * `server_addr` - Address of the UDP TMTC server.
+    /// * `max_packet_size` - Maximum expected size of received packets. Larger packets are
+    ///   truncated.
+    pub fn new(
+        bind_addr: impl ToSocketAddrs,
+        server_addr: impl ToSocketAddrs,
+        max_packet_size: usize,
+    ) -> io::Result<Self> {
+        let server_addr = server_addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no server address given")
+        })?;
+        Ok(Self {
+            socket: UdpSocket::bind(bind_addr)?,
+            server_addr,
+            recv_buf: vec![0; max_packet_size],
+        })
+    }
+
+    pub fn server_addr(&self) -> SocketAddr {
+        self.server_addr
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl TmtcClient for UdpTmtcClient {
+    fn send_packet(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.socket.send_to(packet, self.server_addr)?;
+        Ok(())
+    }
+
+    fn recv_packet(&mut self, timeout: Duration) -> io::Result<Option<Vec<u8>>> {
+        self.socket.set_read_timeout(Some(read_timeout(timeout)))?;
+        match self.socket.recv(&mut self.recv_buf) {
+            Ok(len) => Ok(Some(self.recv_buf[..len].to_vec())),
+            Err(e) if is_timeout(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_udp_loopback() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut client =
+            UdpTmtcClient::new("127.0.0.1:0", server.local_addr().unwrap(), 64).unwrap();
+        client.send_packet(&[1, 2, 3]).unwrap();
+        let mut buf = [0; 16];
+        let (len, client_addr) = server.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..len], &[1, 2, 3]);
+        assert_eq!(client_addr, client.local_addr().unwrap());
+        server.send_to(&[4, 5], client_addr).unwrap();
+        server.send_to(&[6], client_addr).unwrap();
+        let packets = client.recv_packets(Duration::from_millis(50)).unwrap();
+        assert_eq!(packets, vec![vec![4, 5], vec![6]]);
+        assert!(client
+            .recv_packet(Duration::from_millis(5))
+            .unwrap()
+            .is_none());
+    }
+}
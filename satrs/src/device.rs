@@ -0,0 +1,56 @@
+//! # Device handler support
+//!
+//! This module contains the message types for raw device commanding. A device handler which
+//! supports a dedicated RAW mode forwards the data of a [RawDeviceCommand] to its device as is
+//! and passes the raw device replies back as [RawDeviceReply]s. This is a common requirement
+//! for direct device access during commissioning and on-ground testing, for example with the
+//! PUS 2 device access service provided by the [crate::pus::device_access] module.
+use crate::request::GenericMessage;
+use satrs_shared::res_code::ResultU16;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+/// Reply variants for a [RawDeviceCommand].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawDeviceReplyVariant {
+    /// Raw data received from the device. Multiple data replies can be sent for one raw command.
+    #[cfg(feature = "alloc")]
+    Data(alloc::vec::Vec<u8>),
+    /// Handling of the raw command is complete and no more replies will follow.
+    Completed,
+    /// The raw command could not be handled, for example because the device handler is not in
+    /// RAW mode.
+    Failed(ResultU16),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawDeviceReply {
+    pub variant: RawDeviceReplyVariant,
+}
+
+impl RawDeviceReply {
+    pub fn new(variant: RawDeviceReplyVariant) -> Self {
+        Self { variant }
+    }
+}
+
+pub type GenericRawDeviceReply = GenericMessage<RawDeviceReply>;
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::vec::Vec;
+
+    /// Raw command which is forwarded to the device as is by the device handler.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct RawDeviceCommand {
+        pub data: Vec<u8>,
+    }
+
+    impl RawDeviceCommand {
+        pub fn new(data: Vec<u8>) -> Self {
+            Self { data }
+        }
+    }
+}
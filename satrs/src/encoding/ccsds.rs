@@ -1,6 +1,8 @@
 use spacepackets::{CcsdsPacket, SpHeader};
 
-use crate::{tmtc::PacketSenderRaw, ComponentId};
+use crate::{tmtc::PacketSenderRaw, ComponentId, ValidatorU16Id};
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SpValidity {
@@ -21,6 +23,47 @@ pub trait SpacePacketValidator {
     fn validate(&self, sp_header: &SpHeader, raw_buf: &[u8]) -> SpValidity;
 }
 
+/// Simple [SpacePacketValidator] which checks the APID of space packets against a set of
+/// valid APIDs.
+///
+/// Packets with a valid APID are forwarded. Packets with an unknown APID are treated according
+/// to the configured [SpValidity], which is [SpValidity::Skip] by default. If only packets with
+/// known APIDs are expected on a link, [SpValidity::Invalid] can be used so that the parser
+/// tries to re-synchronize when encountering an unknown APID.
+///
+/// Please note that the [ValidatorU16Id] implementation for slices uses a binary search, so the
+/// slice needs to be sorted.
+#[derive(Debug, Clone)]
+pub struct ApidValidator<Apids: ValidatorU16Id> {
+    pub apids: Apids,
+    pub unknown_apid_validity: SpValidity,
+}
+
+impl<Apids: ValidatorU16Id> ApidValidator<Apids> {
+    pub fn new(apids: Apids) -> Self {
+        Self {
+            apids,
+            unknown_apid_validity: SpValidity::Skip,
+        }
+    }
+
+    pub fn new_with_unknown_apid_validity(apids: Apids, unknown_apid_validity: SpValidity) -> Self {
+        Self {
+            apids,
+            unknown_apid_validity,
+        }
+    }
+}
+
+impl<Apids: ValidatorU16Id> SpacePacketValidator for ApidValidator<Apids> {
+    fn validate(&self, sp_header: &SpHeader, _raw_buf: &[u8]) -> SpValidity {
+        if self.apids.validate(sp_header.apid()) {
+            return SpValidity::Valid;
+        }
+        self.unknown_apid_validity
+    }
+}
+
 #[derive(Default, Debug, PartialEq, Eq)]
 pub struct ParseResult {
     pub packets_found: u32,
@@ -53,6 +96,19 @@ pub fn parse_buffer_for_ccsds_space_packets<SendError>(
     sender_id: ComponentId,
     packet_sender: &(impl PacketSenderRaw<Error = SendError> + ?Sized),
 ) -> Result<ParseResult, SendError> {
+    parse_space_packets_with_end_idx(buf, packet_validator, sender_id, packet_sender)
+        .map(|(parse_result, _)| parse_result)
+}
+
+/// Generic parser implementation which also returns the index where parsing stopped. This index
+/// might be larger than the buffer length if a skipped packet extends beyond the end of the
+/// buffer.
+fn parse_space_packets_with_end_idx<SendError>(
+    buf: &[u8],
+    packet_validator: &(impl SpacePacketValidator + ?Sized),
+    sender_id: ComponentId,
+    packet_sender: &(impl PacketSenderRaw<Error = SendError> + ?Sized),
+) -> Result<(ParseResult, usize), SendError> {
     let mut parse_result = ParseResult::default();
     let mut current_idx = 0;
     let buf_len = buf.len();
@@ -84,11 +140,141 @@ pub fn parse_buffer_for_ccsds_space_packets<SendError>(
             }
         }
     }
-    Ok(parse_result)
+    Ok((parse_result, current_idx))
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    /// Incremental parser for a stream of tightly packed CCSDS space packets.
+    ///
+    /// Byte streams like TCP connections or serial links do not preserve packet boundaries, so a
+    /// single read operation might return a part of a space packet or multiple packets. This
+    /// parser buffers incomplete packets across calls to [Self::feed] and recovers the packet
+    /// boundaries using the length field of the space packet header. Complete packets are
+    /// checked with a [SpacePacketValidator] and forwarded to a [PacketSenderRaw] using the
+    /// same rules as [parse_buffer_for_ccsds_space_packets].
+    ///
+    /// Skipped packets which extend beyond the currently fed data are skipped in the following
+    /// calls as well. If a valid packet is larger than the internal buffer, the whole packet is
+    /// discarded, and the number of dropped bytes can be retrieved with
+    /// [Self::num_dropped_bytes].
+    #[derive(Debug)]
+    pub struct SpacePacketStreamParser {
+        buf: Vec<u8>,
+        fill_len: usize,
+        bytes_to_skip: usize,
+        num_dropped_bytes: u64,
+    }
+
+    impl SpacePacketStreamParser {
+        /// Create a new parser. The buffer size should be at least as large as the largest
+        /// expected space packet, and it must be at least 7 bytes, which is the size of the
+        /// smallest possible space packet.
+        pub fn new(buf_size: usize) -> Self {
+            assert!(buf_size >= 7, "buffer size must be at least 7 bytes");
+            Self {
+                buf: vec![0; buf_size],
+                fill_len: 0,
+                bytes_to_skip: 0,
+                num_dropped_bytes: 0,
+            }
+        }
+
+        /// Feed new data into the parser. All complete and valid packets are forwarded to the
+        /// `packet_sender`, and the number of forwarded packets is returned.
+        ///
+        /// If the [PacketSenderRaw::send_packet] call fails, the error is returned and all
+        /// currently buffered data is discarded, because the parser can not determine which
+        /// packets were forwarded successfully.
+        pub fn feed<SendError>(
+            &mut self,
+            mut data: &[u8],
+            packet_validator: &(impl SpacePacketValidator + ?Sized),
+            sender_id: ComponentId,
+            packet_sender: &(impl PacketSenderRaw<Error = SendError> + ?Sized),
+        ) -> Result<u32, SendError> {
+            let mut packets_found = 0;
+            while !data.is_empty() {
+                if self.bytes_to_skip > 0 {
+                    let skip_len = core::cmp::min(self.bytes_to_skip, data.len());
+                    self.bytes_to_skip -= skip_len;
+                    data = &data[skip_len..];
+                    continue;
+                }
+                if self.fill_len == self.buf.len() {
+                    // The buffer is full but only contains the start of a packet which is larger
+                    // than the buffer, so the packet can never be completed. Drop the buffered
+                    // data and skip the rest of the packet.
+                    let packet_len = SpHeader::from_be_bytes(&self.buf[..self.fill_len])
+                        .map(|(sp_header, _)| sp_header.total_len())
+                        .unwrap_or(self.fill_len);
+                    self.bytes_to_skip = packet_len.saturating_sub(self.fill_len);
+                    self.num_dropped_bytes += packet_len as u64;
+                    self.fill_len = 0;
+                    continue;
+                }
+                let copy_len = core::cmp::min(self.buf.len() - self.fill_len, data.len());
+                self.buf[self.fill_len..self.fill_len + copy_len]
+                    .copy_from_slice(&data[..copy_len]);
+                self.fill_len += copy_len;
+                data = &data[copy_len..];
+                let (parse_result, end_idx) = match parse_space_packets_with_end_idx(
+                    &self.buf[..self.fill_len],
+                    packet_validator,
+                    sender_id,
+                    packet_sender,
+                ) {
+                    Ok(result) => result,
+                    Err(e) => {
+                        self.num_dropped_bytes += self.fill_len as u64;
+                        self.fill_len = 0;
+                        return Err(e);
+                    }
+                };
+                packets_found += parse_result.packets_found;
+                let retained_start = match parse_result.incomplete_tail_start {
+                    Some(tail_start) => tail_start,
+                    None => {
+                        if end_idx > self.fill_len {
+                            self.bytes_to_skip = end_idx - self.fill_len;
+                        }
+                        core::cmp::min(end_idx, self.fill_len)
+                    }
+                };
+                self.buf.copy_within(retained_start..self.fill_len, 0);
+                self.fill_len -= retained_start;
+            }
+            Ok(packets_found)
+        }
+
+        /// Number of bytes which are currently buffered, for example the start of a space packet
+        /// which was not received completely yet.
+        pub fn buffered_len(&self) -> usize {
+            self.fill_len
+        }
+
+        /// Number of bytes which were discarded because of oversized packets or send failures.
+        pub fn num_dropped_bytes(&self) -> u64 {
+            self.num_dropped_bytes
+        }
+
+        /// Discard all buffered data, for example after a connection was re-established.
+        pub fn reset(&mut self) {
+            self.fill_len = 0;
+            self.bytes_to_skip = 0;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use alloc::{vec, vec::Vec};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
     use spacepackets::{
         ecss::{tc::PusTcCreator, WritablePusPacket},
         CcsdsPacket, PacketId, PacketSequenceCtrl, PacketType, SequenceFlags, SpHeader,
@@ -96,7 +282,10 @@ mod tests {
 
     use crate::{encoding::tests::TcCacher, ComponentId};
 
-    use super::{parse_buffer_for_ccsds_space_packets, SpValidity, SpacePacketValidator};
+    use super::{
+        parse_buffer_for_ccsds_space_packets, ApidValidator, SpValidity, SpacePacketStreamParser,
+        SpacePacketValidator,
+    };
 
     const PARSER_ID: ComponentId = 0x05;
     const TEST_APID_0: u16 = 0x02;
@@ -296,4 +485,261 @@ mod tests {
         let parse_result = parse_result.unwrap();
         assert_eq!(parse_result.packets_found, 1);
     }
+
+    fn write_two_packets(buffer: &mut [u8]) -> (usize, usize) {
+        let ping_tc =
+            PusTcCreator::new_simple(SpHeader::new_from_apid(TEST_APID_0), 17, 1, &[], true);
+        let action_tc =
+            PusTcCreator::new_simple(SpHeader::new_from_apid(TEST_APID_1), 8, 0, &[], true);
+        let packet_len_ping = ping_tc
+            .write_to_bytes(buffer)
+            .expect("writing packet failed");
+        let packet_len_action = action_tc
+            .write_to_bytes(&mut buffer[packet_len_ping..])
+            .expect("writing packet failed");
+        (packet_len_ping, packet_len_action)
+    }
+
+    #[test]
+    fn test_apid_validator() {
+        let mut buffer: [u8; 32] = [0; 32];
+        let (len_ping, len_action) = write_two_packets(&mut buffer);
+        let tc_cacher = TcCacher::default();
+        let apids: &[u16] = &[TEST_APID_1];
+        let validator = ApidValidator::new(apids);
+        let parse_result = parse_buffer_for_ccsds_space_packets(
+            &buffer[..len_ping + len_action],
+            &validator,
+            PARSER_ID,
+            &tc_cacher,
+        )
+        .expect("parsing failed");
+        assert_eq!(parse_result.packets_found, 1);
+        let mut queue = tc_cacher.tc_queue.borrow_mut();
+        assert_eq!(queue.len(), 1);
+        let packet = queue.pop_front().unwrap();
+        assert_eq!(packet.packet, buffer[len_ping..len_ping + len_action]);
+    }
+
+    #[test]
+    fn test_stream_parser_byte_by_byte() {
+        let mut buffer: [u8; 32] = [0; 32];
+        let (len_ping, len_action) = write_two_packets(&mut buffer);
+        let total_len = len_ping + len_action;
+        let tc_cacher = TcCacher::default();
+        let validator = ApidValidator::new(vec![TEST_APID_0, TEST_APID_1]);
+        let mut parser = SpacePacketStreamParser::new(64);
+        let mut packets_found = 0;
+        for byte in &buffer[..total_len] {
+            packets_found += parser
+                .feed(
+                    core::slice::from_ref(byte),
+                    &validator,
+                    PARSER_ID,
+                    &tc_cacher,
+                )
+                .expect("parsing failed");
+        }
+        assert_eq!(packets_found, 2);
+        assert_eq!(parser.buffered_len(), 0);
+        let mut queue = tc_cacher.tc_queue.borrow_mut();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop_front().unwrap().packet, buffer[..len_ping]);
+        assert_eq!(
+            queue.pop_front().unwrap().packet,
+            buffer[len_ping..total_len]
+        );
+    }
+
+    #[test]
+    fn test_stream_parser_split_packet() {
+        let mut buffer: [u8; 32] = [0; 32];
+        let (len_ping, len_action) = write_two_packets(&mut buffer);
+        let total_len = len_ping + len_action;
+        let tc_cacher = TcCacher::default();
+        let validator = ApidValidator::new(vec![TEST_APID_0, TEST_APID_1]);
+        let mut parser = SpacePacketStreamParser::new(64);
+        let packets_found = parser
+            .feed(&buffer[..total_len - 4], &validator, PARSER_ID, &tc_cacher)
+            .expect("parsing failed");
+        assert_eq!(packets_found, 1);
+        assert_eq!(parser.buffered_len(), len_action - 4);
+        let packets_found = parser
+            .feed(
+                &buffer[total_len - 4..total_len],
+                &validator,
+                PARSER_ID,
+                &tc_cacher,
+            )
+            .expect("parsing failed");
+        assert_eq!(packets_found, 1);
+        assert_eq!(parser.buffered_len(), 0);
+        let mut queue = tc_cacher.tc_queue.borrow_mut();
+        assert_eq!(queue.len(), 2);
+        queue.pop_front().unwrap();
+        assert_eq!(
+            queue.pop_front().unwrap().packet,
+            buffer[len_ping..total_len]
+        );
+    }
+
+    #[test]
+    fn test_stream_parser_skip_across_feeds() {
+        let mut buffer: [u8; 32] = [0; 32];
+        let (len_ping, len_action) = write_two_packets(&mut buffer);
+        let total_len = len_ping + len_action;
+        let tc_cacher = TcCacher::default();
+        // Only the second packet is relevant, the first one needs to be skipped.
+        let validator = ApidValidator::new(TEST_APID_1);
+        let mut parser = SpacePacketStreamParser::new(64);
+        let packets_found = parser
+            .feed(&buffer[..len_ping - 3], &validator, PARSER_ID, &tc_cacher)
+            .expect("parsing failed");
+        assert_eq!(packets_found, 0);
+        assert_eq!(parser.buffered_len(), 0);
+        let packets_found = parser
+            .feed(
+                &buffer[len_ping - 3..total_len],
+                &validator,
+                PARSER_ID,
+                &tc_cacher,
+            )
+            .expect("parsing failed");
+        assert_eq!(packets_found, 1);
+        let mut queue = tc_cacher.tc_queue.borrow_mut();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(
+            queue.pop_front().unwrap().packet,
+            buffer[len_ping..total_len]
+        );
+    }
+
+    #[test]
+    fn test_stream_parser_oversized_packet() {
+        let mut buffer: [u8; 32] = [0; 32];
+        let (len_ping, _) = write_two_packets(&mut buffer);
+        let tc_cacher = TcCacher::default();
+        let validator = ApidValidator::new(vec![TEST_APID_0, TEST_APID_1]);
+        // Buffer is too small for the ping packet.
+        let mut parser = SpacePacketStreamParser::new(len_ping - 1);
+        let packets_found = parser
+            .feed(&buffer[..len_ping], &validator, PARSER_ID, &tc_cacher)
+            .expect("parsing failed");
+        assert_eq!(packets_found, 0);
+        assert_eq!(parser.num_dropped_bytes(), len_ping as u64);
+        assert_eq!(parser.buffered_len(), 0);
+        assert!(tc_cacher.tc_queue.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_stream_parser_oversized_packet_followed_by_valid_packet() {
+        let ping = PusTcCreator::new_simple(
+            SpHeader::new_for_unseg_tc(TEST_APID_0, 1, 0),
+            17,
+            1,
+            &[],
+            true,
+        )
+        .to_vec()
+        .unwrap();
+        // The oversized packet contains a valid packet in its application data, which must not
+        // be forwarded.
+        let mut app_data = ping.clone();
+        app_data.resize(32, 0);
+        let oversized = PusTcCreator::new_simple(
+            SpHeader::new_for_unseg_tc(TEST_APID_0, 0, 0),
+            17,
+            1,
+            &app_data,
+            true,
+        )
+        .to_vec()
+        .unwrap();
+        let mut stream = oversized.clone();
+        stream.extend_from_slice(&ping);
+        let tc_cacher = TcCacher::default();
+        let validator = ApidValidator::new(vec![TEST_APID_0]);
+        let mut parser = SpacePacketStreamParser::new(ping.len() + 4);
+        let packets_found = parser
+            .feed(&stream, &validator, PARSER_ID, &tc_cacher)
+            .expect("parsing failed");
+        assert_eq!(packets_found, 1);
+        assert_eq!(parser.num_dropped_bytes(), oversized.len() as u64);
+        assert_eq!(parser.buffered_len(), 0);
+        let mut queue = tc_cacher.tc_queue.borrow_mut();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop_front().unwrap().packet, ping);
+    }
+
+    #[test]
+    fn test_stream_parser_random_chunks() {
+        let mut rng = StdRng::seed_from_u64(0x5cc5);
+        let validator = ApidValidator::new(vec![TEST_APID_0]);
+        let mut stream = Vec::new();
+        let mut packets = Vec::new();
+        for seq_count in 0..20 {
+            let app_data = vec![seq_count as u8; rng.gen_range(0..32)];
+            let tc = PusTcCreator::new_simple(
+                SpHeader::new_for_unseg_tc(TEST_APID_0, seq_count, 0),
+                17,
+                1,
+                &app_data,
+                true,
+            );
+            let raw = tc.to_vec().unwrap();
+            stream.extend_from_slice(&raw);
+            packets.push(raw);
+        }
+        for _ in 0..50 {
+            let tc_cacher = TcCacher::default();
+            let mut parser = SpacePacketStreamParser::new(64);
+            let mut remaining = stream.as_slice();
+            let mut packets_found = 0;
+            while !remaining.is_empty() {
+                let chunk_len = rng.gen_range(1..=remaining.len().min(40));
+                packets_found += parser
+                    .feed(&remaining[..chunk_len], &validator, PARSER_ID, &tc_cacher)
+                    .unwrap();
+                remaining = &remaining[chunk_len..];
+            }
+            assert_eq!(packets_found as usize, packets.len());
+            assert_eq!(parser.buffered_len(), 0);
+            let queue = tc_cacher.tc_queue.borrow();
+            for (received, sent) in queue.iter().zip(packets.iter()) {
+                assert_eq!(&received.packet, sent);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_data_does_not_panic() {
+        let mut rng = StdRng::seed_from_u64(0x5cc6);
+        let validators = [
+            ApidValidator::new_with_unknown_apid_validity(vec![TEST_APID_0], SpValidity::Skip),
+            ApidValidator::new_with_unknown_apid_validity(vec![TEST_APID_0], SpValidity::Invalid),
+        ];
+        let mut buf = [0; 128];
+        for _ in 0..2000 {
+            let len = rng.gen_range(0..buf.len());
+            rng.fill(&mut buf[..len]);
+            for validator in &validators {
+                let tc_cacher = TcCacher::default();
+                let parse_result = parse_buffer_for_ccsds_space_packets(
+                    &buf[..len],
+                    validator,
+                    PARSER_ID,
+                    &tc_cacher,
+                )
+                .unwrap();
+                if let Some(tail_start) = parse_result.incomplete_tail_start {
+                    assert!(tail_start < len);
+                }
+                let mut parser = SpacePacketStreamParser::new(32);
+                parser
+                    .feed(&buf[..len], validator, PARSER_ID, &tc_cacher)
+                    .unwrap();
+                assert!(parser.buffered_len() <= 32);
+            }
+        }
+    }
 }
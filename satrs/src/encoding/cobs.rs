@@ -101,14 +101,16 @@ pub fn parse_buffer_for_cobs_encoded_packets<SendError>(
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use alloc::{vec, vec::Vec};
     use cobs::encode;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     use crate::{
         encoding::tests::{encode_simple_packet, TcCacher, INVERTED_PACKET, SIMPLE_PACKET},
         ComponentId,
     };
 
-    use super::parse_buffer_for_cobs_encoded_packets;
+    use super::{encode_packet_with_cobs, parse_buffer_for_cobs_encoded_packets};
 
     const PARSER_ID: ComponentId = 0x05;
 
@@ -280,4 +282,65 @@ pub(crate) mod tests {
         assert!(queue.is_empty());
         assert_eq!(next_write_idx, 0);
     }
+
+    #[test]
+    fn test_random_packets_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(0xc0b5);
+        let mut encoded_buf = vec![0; 1024];
+        for _ in 0..200 {
+            let test_sender = TcCacher::default();
+            let mut packets = Vec::new();
+            let mut current_idx = 0;
+            for _ in 0..rng.gen_range(1..8) {
+                let mut packet = vec![0; rng.gen_range(1..64)];
+                rng.fill(packet.as_mut_slice());
+                assert!(encode_packet_with_cobs(
+                    &packet,
+                    &mut encoded_buf,
+                    &mut current_idx
+                ));
+                packets.push(packet);
+            }
+            let mut next_write_idx = 0;
+            let packets_found = parse_buffer_for_cobs_encoded_packets(
+                &mut encoded_buf[..current_idx],
+                PARSER_ID,
+                &test_sender,
+                &mut next_write_idx,
+            )
+            .unwrap();
+            assert_eq!(packets_found as usize, packets.len());
+            let queue = test_sender.tc_queue.borrow();
+            for (received, sent) in queue.iter().zip(packets.iter()) {
+                assert_eq!(&received.packet, sent);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_data_does_not_panic() {
+        let mut rng = StdRng::seed_from_u64(0xc0b6);
+        let mut buf = [0; 128];
+        for _ in 0..2000 {
+            let test_sender = TcCacher::default();
+            let len = rng.gen_range(0..buf.len());
+            rng.fill(&mut buf[..len]);
+            // Make sentinel values more likely than for uniformly distributed data.
+            for byte in buf[..len].iter_mut() {
+                if rng.gen_ratio(1, 8) {
+                    *byte = 0;
+                }
+            }
+            let mut next_write_idx = 0;
+            let packets_found = parse_buffer_for_cobs_encoded_packets(
+                &mut buf[..len],
+                PARSER_ID,
+                &test_sender,
+                &mut next_write_idx,
+            )
+            .unwrap();
+            assert_eq!(packets_found as usize, test_sender.tc_queue.borrow().len());
+            assert!(next_write_idx <= len);
+        }
+    }
 }
@@ -1,8 +1,12 @@
 pub mod ccsds;
 pub mod cobs;
+pub mod pus;
 
 pub use crate::encoding::ccsds::parse_buffer_for_ccsds_space_packets;
+#[cfg(feature = "alloc")]
+pub use crate::encoding::ccsds::SpacePacketStreamParser;
 pub use crate::encoding::cobs::{encode_packet_with_cobs, parse_buffer_for_cobs_encoded_packets};
+pub use crate::encoding::pus::{parse_tc_lenient, parse_tm_lenient};
 
 #[cfg(test)]
 pub(crate) mod tests {
@@ -0,0 +1,295 @@
+//! Robust parsing entry points for PUS packets received from untrusted sources.
+//!
+//! Uplinked data can be truncated or corrupted in arbitrary ways. The functions in this module
+//! check the space packet header of the raw data before the packet is parsed with the
+//! [PusTcReader] or [PusTmReader]. They never panic, return a descriptive [LenientParseError]
+//! for malformed packets, and tolerate trailing bytes after the packet, for example padding or
+//! the start of the next packet. The returned size can be used to advance to the next packet.
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use spacepackets::ecss::tc::PusTcReader;
+use spacepackets::ecss::tm::PusTmReader;
+use spacepackets::ecss::PusError;
+use spacepackets::{CcsdsPacket, PacketType, SpHeader, CCSDS_HEADER_LEN};
+
+/// Size of the PUS C TC secondary header.
+const PUS_TC_SEC_HEADER_LEN: usize = 5;
+/// Size of the PUS C TM secondary header without the timestamp.
+const PUS_TM_SEC_HEADER_LEN_WITHOUT_TIMESTAMP: usize = 7;
+const CRC_LEN: usize = 2;
+
+/// Minimum size of a PUS C telecommand.
+pub const MIN_PUS_TC_LEN: usize = CCSDS_HEADER_LEN + PUS_TC_SEC_HEADER_LEN + CRC_LEN;
+
+/// Minimum size of a PUS C telemetry packet with the given timestamp length.
+pub const fn min_pus_tm_len(timestamp_len: usize) -> usize {
+    CCSDS_HEADER_LEN + PUS_TM_SEC_HEADER_LEN_WITHOUT_TIMESTAMP + timestamp_len + CRC_LEN
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LenientParseError {
+    /// The raw buffer is smaller than the packet, or smaller than the space packet header.
+    Truncated {
+        expected: usize,
+        found: usize,
+    },
+    /// The packet length field of the space packet header specifies a packet which is smaller
+    /// than the smallest possible packet.
+    PacketTooSmall {
+        expected: usize,
+        found: usize,
+    },
+    WrongPacketType(PacketType),
+    /// The secondary header flag is not set, so the packet can not be a PUS packet.
+    NoSecondaryHeader,
+    /// The packet could not be parsed as a PUS packet, for example because the CRC check failed.
+    Pus(PusError),
+}
+
+impl Display for LenientParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LenientParseError::Truncated { expected, found } => {
+                write!(
+                    f,
+                    "truncated packet, expected {expected} bytes, found {found}"
+                )
+            }
+            LenientParseError::PacketTooSmall { expected, found } => write!(
+                f,
+                "packet length {found} smaller than minimum packet length {expected}"
+            ),
+            LenientParseError::WrongPacketType(packet_type) => {
+                write!(f, "wrong packet type {packet_type:?}")
+            }
+            LenientParseError::NoSecondaryHeader => write!(f, "secondary header flag not set"),
+            LenientParseError::Pus(e) => write!(f, "PUS error: {e}"),
+        }
+    }
+}
+
+impl From<PusError> for LenientParseError {
+    fn from(value: PusError) -> Self {
+        Self::Pus(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for LenientParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LenientParseError::Pus(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Check the space packet header of the raw data and return the total packet length.
+fn check_sp_header(
+    raw: &[u8],
+    packet_type: PacketType,
+    min_len: usize,
+) -> Result<usize, LenientParseError> {
+    if raw.len() < CCSDS_HEADER_LEN {
+        return Err(LenientParseError::Truncated {
+            expected: CCSDS_HEADER_LEN,
+            found: raw.len(),
+        });
+    }
+    // Can not fail, the buffer is large enough.
+    let (sp_header, _) = SpHeader::from_be_bytes(raw).unwrap();
+    if sp_header.packet_type() != packet_type {
+        return Err(LenientParseError::WrongPacketType(sp_header.packet_type()));
+    }
+    if !sp_header.sec_header_flag() {
+        return Err(LenientParseError::NoSecondaryHeader);
+    }
+    let total_len = sp_header.total_len();
+    if total_len < min_len {
+        return Err(LenientParseError::PacketTooSmall {
+            expected: min_len,
+            found: total_len,
+        });
+    }
+    if raw.len() < total_len {
+        return Err(LenientParseError::Truncated {
+            expected: total_len,
+            found: raw.len(),
+        });
+    }
+    Ok(total_len)
+}
+
+/// Parse a PUS telecommand from untrusted raw data. Returns the telecommand and its size.
+///
+/// Bytes after the end of the packet specified by the space packet header are ignored.
+pub fn parse_tc_lenient(raw: &[u8]) -> Result<(PusTcReader<'_>, usize), LenientParseError> {
+    let total_len = check_sp_header(raw, PacketType::Tc, MIN_PUS_TC_LEN)?;
+    let (tc, _) = PusTcReader::new(&raw[..total_len])?;
+    Ok((tc, total_len))
+}
+
+/// Parse a PUS telemetry packet with the given timestamp length from untrusted raw data.
+/// Returns the telemetry packet and its size.
+///
+/// Bytes after the end of the packet specified by the space packet header are ignored.
+pub fn parse_tm_lenient(
+    raw: &[u8],
+    timestamp_len: usize,
+) -> Result<(PusTmReader<'_>, usize), LenientParseError> {
+    let total_len = check_sp_header(raw, PacketType::Tm, min_pus_tm_len(timestamp_len))?;
+    let (tm, _) = PusTmReader::new(&raw[..total_len], timestamp_len)?;
+    Ok((tm, total_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use spacepackets::ecss::tc::PusTcCreator;
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::ecss::{PusPacket, WritablePusPacket};
+
+    const TEST_APID: u16 = 0x02;
+    const NUM_RANDOM_CASES: usize = 2000;
+
+    fn create_tc(app_data: &[u8]) -> Vec<u8> {
+        PusTcCreator::new_simple(SpHeader::new_from_apid(TEST_APID), 17, 1, app_data, true)
+            .to_vec()
+            .unwrap()
+    }
+
+    fn create_tm(source_data: &[u8]) -> Vec<u8> {
+        let sec_header = PusTmSecondaryHeader::new_simple(17, 2, &[0; 7]);
+        PusTmCreator::new(
+            SpHeader::new_from_apid(TEST_APID),
+            sec_header,
+            source_data,
+            true,
+        )
+        .to_vec()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_packets_with_trailing_bytes() {
+        let mut raw = create_tc(&[1, 2, 3]);
+        let tc_len = raw.len();
+        raw.extend_from_slice(&[0xff; 4]);
+        let (tc, len) = parse_tc_lenient(&raw).unwrap();
+        assert_eq!(len, tc_len);
+        assert_eq!(tc.app_data(), &[1, 2, 3]);
+        assert_eq!(tc.service(), 17);
+
+        let mut raw = create_tm(&[4, 5]);
+        let tm_len = raw.len();
+        raw.push(0);
+        let (tm, len) = parse_tm_lenient(&raw, 7).unwrap();
+        assert_eq!(len, tm_len);
+        assert_eq!(tm.source_data(), &[4, 5]);
+    }
+
+    #[test]
+    fn test_wrong_packet_type() {
+        assert_eq!(
+            parse_tc_lenient(&create_tm(&[])).unwrap_err(),
+            LenientParseError::WrongPacketType(PacketType::Tm)
+        );
+        assert_eq!(
+            parse_tm_lenient(&create_tc(&[]), 7).unwrap_err(),
+            LenientParseError::WrongPacketType(PacketType::Tc)
+        );
+    }
+
+    #[test]
+    fn test_packet_length_too_small() {
+        // Valid space packet header, but a packet which is too small for a PUS TC.
+        let mut raw = create_tc(&[]);
+        raw[4..6].copy_from_slice(&2_u16.to_be_bytes());
+        assert_eq!(
+            parse_tc_lenient(&raw).unwrap_err(),
+            LenientParseError::PacketTooSmall {
+                expected: MIN_PUS_TC_LEN,
+                found: 9
+            }
+        );
+    }
+
+    #[test]
+    fn test_all_truncations() {
+        let raw = create_tc(&[1, 2, 3, 4]);
+        for len in 0..raw.len() {
+            let error = parse_tc_lenient(&raw[..len]).unwrap_err();
+            assert!(
+                matches!(error, LenientParseError::Truncated { .. }),
+                "unexpected error {error} for length {len}"
+            );
+        }
+        let raw = create_tm(&[1, 2, 3, 4]);
+        for len in 0..raw.len() {
+            let error = parse_tm_lenient(&raw[..len], 7).unwrap_err();
+            assert!(matches!(error, LenientParseError::Truncated { .. }));
+        }
+    }
+
+    #[test]
+    fn test_single_bit_flips_are_detected() {
+        let raw = create_tc(&[1, 2, 3, 4]);
+        for byte in 0..raw.len() {
+            // Flipped bits in the packet length field can result in a shorter packet which
+            // might pass the CRC check.
+            if byte == 4 || byte == 5 {
+                continue;
+            }
+            for bit in 0..8 {
+                let mut corrupted = raw.clone();
+                corrupted[byte] ^= 1 << bit;
+                assert!(
+                    parse_tc_lenient(&corrupted).is_err(),
+                    "bit flip in byte {byte} bit {bit} not detected"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_data_does_not_panic() {
+        let mut rng = StdRng::seed_from_u64(0x5a7);
+        let mut buf = [0; 64];
+        for _ in 0..NUM_RANDOM_CASES {
+            let len = rng.gen_range(0..buf.len());
+            rng.fill(&mut buf[..len]);
+            if let Ok((_, packet_len)) = parse_tc_lenient(&buf[..len]) {
+                assert!(packet_len <= len);
+            }
+            if let Ok((_, packet_len)) = parse_tm_lenient(&buf[..len], 7) {
+                assert!(packet_len <= len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_corruption_does_not_panic() {
+        let mut rng = StdRng::seed_from_u64(0x5a8);
+        let tc = create_tc(&[0; 16]);
+        let tm = create_tm(&[0; 16]);
+        for _ in 0..NUM_RANDOM_CASES {
+            let mut corrupted_tc = tc.clone();
+            let mut corrupted_tm = tm.clone();
+            for _ in 0..rng.gen_range(1..4) {
+                let idx = rng.gen_range(0..tc.len());
+                corrupted_tc[idx] = rng.gen();
+                let idx = rng.gen_range(0..tm.len());
+                corrupted_tm[idx] = rng.gen();
+            }
+            let tc_len = rng.gen_range(0..=tc.len());
+            let tm_len = rng.gen_range(0..=tm.len());
+            let _ = parse_tc_lenient(&corrupted_tc[..tc_len]);
+            let _ = parse_tm_lenient(&corrupted_tm[..tm_len], 7);
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! # Reporting internal framework errors as events
+//!
+//! Many framework errors, for example [EcssTmtcError]s or the [EventRoutingError]s passed to the
+//! error handler of [crate::event_man::EventManager::try_event_handling], are only returned to
+//! the caller where they are often ignored. The [InternalErrorReporter] converts these errors into
+//! events and sends them to the event manager. This way, they can be packaged as TM by the PUS
+//! event service and a silent malfunction of the framework becomes observable on the ground.
+//!
+//! TMTC errors are reported with the [TMTC_ERROR] event of medium severity and event routing
+//! errors with the [EVENT_ROUTING_ERROR] event of low severity. Both events are sent with a
+//! [U32Pair][crate::params::U32Pair] parameter, which contains the [InternalErrorKind] and an
+//! additional error specific detail value, for example the index of a full subpool.
+//!
+//! Each event is rate limited separately to avoid flooding the event manager and the downlink.
+//! This is also required because the reported event routing errors are routed by the event
+//! manager as well, which can lead to further routing errors.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::mpsc;
+//! use std::time::Duration;
+//! use satrs::error_report::{InternalErrorReporter, EVENT_ROUTING_ERROR};
+//! use satrs::event_man::{EventRoutingError, EventU32SenderMpsc};
+//! use satrs::events::EventU32;
+//!
+//! let (event_tx, event_rx) = mpsc::channel();
+//! let mut reporter = InternalErrorReporter::new(1, EventU32SenderMpsc::new(0, event_tx));
+//! let reported = reporter
+//!     .report_event_routing_error(&EventRoutingError::NoSenderForId(5), Duration::ZERO)
+//!     .unwrap();
+//! assert!(reported);
+//! let event_msg = event_rx.try_recv().unwrap();
+//! assert_eq!(event_msg.event(), EventU32::from(EVENT_ROUTING_ERROR));
+//! ```
+use core::time::Duration;
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::event_man::{
+    EventMessage, EventRoutingError, EventSendProvider, ListenerKey, RateLimit, RateLimitState,
+};
+use crate::events::{EventU32, EventU32TypedSev, HasSeverity, SeverityLow, SeverityMedium};
+use crate::params::{Params, ParamsRaw};
+use crate::pool::PoolError;
+use crate::pus::EcssTmtcError;
+use crate::queue::{GenericReceiveError, GenericSendError};
+use crate::ComponentId;
+
+/// Group ID reserved for events generated by the framework. Missions should not use this group
+/// ID for their own events.
+pub const FRAMEWORK_EVENT_GROUP_ID: u16 = crate::events::MAX_GROUP_ID_U32_EVENT;
+
+crate::event_definitions! {
+    pub INTERNAL_ERROR_EVENT_DEFINITIONS;
+    /// An error occurred during the handling of TM or TC.
+    pub TMTC_ERROR: SeverityMedium = (FRAMEWORK_EVENT_GROUP_ID, 0), U32Pair,
+        "Internal TMTC error, contains the error kind and a detail value";
+    /// An event could not be routed to one of its listeners.
+    pub EVENT_ROUTING_ERROR: SeverityLow = (FRAMEWORK_EVENT_GROUP_ID, 1), U32Pair,
+        "Internal event routing error, contains the error kind and a detail value";
+}
+
+// All framework events share the FRAMEWORK_EVENT_GROUP_ID. Some definition lists are only
+// available with the alloc or std feature, so every feature level checks its own set of lists.
+crate::assert_unique_event_ids!(
+    INTERNAL_ERROR_EVENT_DEFINITIONS,
+    crate::boot_report::BOOT_EVENT_DEFINITIONS,
+    crate::log_report::LOG_EVENT_DEFINITIONS,
+);
+#[cfg(feature = "alloc")]
+crate::assert_unique_event_ids!(
+    INTERNAL_ERROR_EVENT_DEFINITIONS,
+    crate::boot_report::BOOT_EVENT_DEFINITIONS,
+    crate::log_report::LOG_EVENT_DEFINITIONS,
+    crate::pus::tc_quarantine::TC_QUARANTINE_EVENT_DEFINITIONS,
+);
+#[cfg(feature = "std")]
+crate::assert_unique_event_ids!(
+    INTERNAL_ERROR_EVENT_DEFINITIONS,
+    crate::boot_report::BOOT_EVENT_DEFINITIONS,
+    crate::log_report::LOG_EVENT_DEFINITIONS,
+    crate::pus::tc_quarantine::TC_QUARANTINE_EVENT_DEFINITIONS,
+    crate::shutdown::SHUTDOWN_EVENT_DEFINITIONS,
+    crate::hal::std::tcp_server::TCP_SERVER_EVENT_DEFINITIONS,
+);
+
+/// Kind of a reported internal error. This is the first value of the event parameters.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u32)]
+pub enum InternalErrorKind {
+    /// [EcssTmtcError::Store]. The detail value is the index of the full subpool or the size of
+    /// the data which was too large.
+    Store = 0,
+    ByteConversion = 1,
+    Pus = 2,
+    /// [EcssTmtcError::CantSendAddr]. The detail value contains the lower 32 bits of the store
+    /// address.
+    CantSendAddr = 3,
+    CantSendDirectTm = 4,
+    /// [EcssTmtcError::Send]. The detail value is the capacity of the full queue or the lower 32
+    /// bits of the target component ID.
+    TmtcSend = 5,
+    /// [EcssTmtcError::Receive]. The detail value contains the lower 32 bits of the disconnected
+    /// component ID.
+    TmtcReceive = 6,
+    /// [EcssTmtcError::TmTooLarge]. The detail value is the length of the rejected packet.
+    TmTooLarge = 7,
+    /// [EventRoutingError::Send]. The detail value is the same as for [Self::TmtcSend].
+    EventSend = 16,
+    /// [EventRoutingError::NoSendersForKey]. The detail value is the raw event, the group ID or
+    /// the severity of the listener key.
+    NoSendersForKey = 17,
+    /// [EventRoutingError::NoSenderForId]. The detail value contains the lower 32 bits of the
+    /// listener ID.
+    NoSenderForId = 18,
+}
+
+fn send_error_detail(error: &GenericSendError) -> u32 {
+    match error {
+        GenericSendError::QueueFull(Some(capacity)) => *capacity,
+        GenericSendError::TargetDoesNotExist(id) => *id as u32,
+        _ => 0,
+    }
+}
+
+/// Convert a TMTC error into its [InternalErrorKind] and detail value.
+pub fn tmtc_error_details(error: &EcssTmtcError) -> (InternalErrorKind, u32) {
+    match error {
+        EcssTmtcError::Store(e) => {
+            let detail = match e {
+                PoolError::StoreFull(subpool) => *subpool as u32,
+                PoolError::DataTooLarge(len) => u32::try_from(*len).unwrap_or(u32::MAX),
+                _ => 0,
+            };
+            (InternalErrorKind::Store, detail)
+        }
+        EcssTmtcError::ByteConversion(_) => (InternalErrorKind::ByteConversion, 0),
+        EcssTmtcError::Pus(_) => (InternalErrorKind::Pus, 0),
+        EcssTmtcError::CantSendAddr(addr) => (InternalErrorKind::CantSendAddr, *addr as u32),
+        EcssTmtcError::CantSendDirectTm => (InternalErrorKind::CantSendDirectTm, 0),
+        EcssTmtcError::Send(e) => (InternalErrorKind::TmtcSend, send_error_detail(e)),
+        EcssTmtcError::Receive(e) => {
+            let detail = match e {
+                GenericReceiveError::TxDisconnected(Some(id)) => *id as u32,
+                _ => 0,
+            };
+            (InternalErrorKind::TmtcReceive, detail)
+        }
+        EcssTmtcError::TmTooLarge { len, .. } => (
+            InternalErrorKind::TmTooLarge,
+            u32::try_from(*len).unwrap_or(u32::MAX),
+        ),
+    }
+}
+
+/// Convert an event routing error into its [InternalErrorKind] and detail value.
+pub fn event_routing_error_details(error: &EventRoutingError) -> (InternalErrorKind, u32) {
+    match error {
+        EventRoutingError::Send(e) => (InternalErrorKind::EventSend, send_error_detail(e)),
+        EventRoutingError::NoSendersForKey(key) => {
+            let detail = match key {
+                ListenerKey::Single(raw_event) => *raw_event,
+                ListenerKey::Group(group_id) => *group_id as u32,
+                ListenerKey::Severity(severity) => *severity as u32,
+                ListenerKey::All => 0,
+            };
+            (InternalErrorKind::NoSendersForKey, detail)
+        }
+        EventRoutingError::NoSenderForId(id) => (InternalErrorKind::NoSenderForId, *id as u32),
+    }
+}
+
+/// Converts internal framework errors into rate limited events.
+///
+/// The reporter sends the events with its own component ID as the sender ID. The timestamps
+/// passed to the reporting functions are monotonic timestamps which are used for the rate
+/// limiting.
+pub struct InternalErrorReporter<Sender: EventSendProvider<EventU32>> {
+    id: ComponentId,
+    sender: Sender,
+    tmtc_error_limit: RateLimitState,
+    routing_error_limit: RateLimitState,
+}
+
+impl<Sender: EventSendProvider<EventU32>> InternalErrorReporter<Sender> {
+    /// Each event is reported at most 5 times per minute by default.
+    pub const DEFAULT_RATE_LIMIT: RateLimit = RateLimit::new(5, Duration::from_secs(60));
+
+    /// Create a new reporter with the [Self::DEFAULT_RATE_LIMIT].
+    pub fn new(id: ComponentId, sender: Sender) -> Self {
+        Self::new_with_rate_limit(id, sender, Self::DEFAULT_RATE_LIMIT)
+    }
+
+    /// Create a new reporter.
+    ///
+    /// ## Parameter
+    ///
+    /// * `id` - Component ID used as the sender ID of the generated events.
+    /// * `sender` - Sender used to send the events to the event manager.
+    /// * `rate_limit` - Rate limit which is applied to each event separately.
+    pub fn new_with_rate_limit(id: ComponentId, sender: Sender, rate_limit: RateLimit) -> Self {
+        Self {
+            id,
+            sender,
+            tmtc_error_limit: RateLimitState::new(rate_limit),
+            routing_error_limit: RateLimitState::new(rate_limit),
+        }
+    }
+
+    pub fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    pub fn sender(&self) -> &Sender {
+        &self.sender
+    }
+
+    pub fn rate_limit(&self) -> RateLimit {
+        self.tmtc_error_limit.rate_limit()
+    }
+
+    /// Report a TMTC error with the [TMTC_ERROR] event. Returns [false] if the error was not
+    /// reported because of the rate limit.
+    pub fn report_tmtc_error(
+        &mut self,
+        error: &EcssTmtcError,
+        timestamp: Duration,
+    ) -> Result<bool, Sender::Error> {
+        if !self.tmtc_error_limit.check(timestamp) {
+            return Ok(false);
+        }
+        self.send_event(TMTC_ERROR, tmtc_error_details(error))?;
+        Ok(true)
+    }
+
+    /// Report an event routing error with the [EVENT_ROUTING_ERROR] event. Returns [false] if
+    /// the error was not reported because of the rate limit.
+    pub fn report_event_routing_error(
+        &mut self,
+        error: &EventRoutingError,
+        timestamp: Duration,
+    ) -> Result<bool, Sender::Error> {
+        if !self.routing_error_limit.check(timestamp) {
+            return Ok(false);
+        }
+        self.send_event(EVENT_ROUTING_ERROR, event_routing_error_details(error))?;
+        Ok(true)
+    }
+
+    /// Number of TMTC errors which were not reported because of the rate limit.
+    pub fn num_rate_limited_tmtc_errors(&self) -> u32 {
+        self.tmtc_error_limit.num_rate_limited()
+    }
+
+    /// Number of event routing errors which were not reported because of the rate limit.
+    pub fn num_rate_limited_routing_errors(&self) -> u32 {
+        self.routing_error_limit.num_rate_limited()
+    }
+
+    fn send_event<Severity: HasSeverity>(
+        &self,
+        event: EventU32TypedSev<Severity>,
+        (kind, detail): (InternalErrorKind, u32),
+    ) -> Result<(), Sender::Error> {
+        let params = Params::from(ParamsRaw::from((u32::from(kind), detail)));
+        self.sender.send(EventMessage::new_with_params(
+            self.id,
+            event.into(),
+            &params,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_man::{
+        EventManagerWithMpsc, EventMessageU32, EventRoutingResult, EventU32SenderMpsc,
+    };
+    use crate::params::{ParamsHeapless, U32Pair};
+    use std::sync::mpsc;
+    use std::vec::Vec;
+
+    const REPORTER_ID: ComponentId = 0x05;
+
+    fn event_params(event_msg: &EventMessageU32) -> (u32, u32) {
+        match event_msg.params() {
+            Some(Params::Heapless(ParamsHeapless::Raw(ParamsRaw::U32Pair(U32Pair(
+                kind,
+                detail,
+            ))))) => (*kind, *detail),
+            params => panic!("unexpected event parameters {params:?}"),
+        }
+    }
+
+    #[test]
+    fn test_tmtc_error_report() {
+        let (event_tx, event_rx) = mpsc::channel();
+        let mut reporter =
+            InternalErrorReporter::new(REPORTER_ID, EventU32SenderMpsc::new(0, event_tx));
+        let error = EcssTmtcError::Store(PoolError::StoreFull(2));
+        assert!(reporter.report_tmtc_error(&error, Duration::ZERO).unwrap());
+        let event_msg = event_rx.try_recv().unwrap();
+        assert_eq!(event_msg.sender_id(), REPORTER_ID);
+        assert_eq!(event_msg.event(), EventU32::from(TMTC_ERROR));
+        assert_eq!(
+            event_params(&event_msg),
+            (InternalErrorKind::Store as u32, 2)
+        );
+        assert_eq!(
+            tmtc_error_details(&EcssTmtcError::Send(GenericSendError::QueueFull(Some(8)))),
+            (InternalErrorKind::TmtcSend, 8)
+        );
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let (event_tx, event_rx) = mpsc::channel();
+        let mut reporter = InternalErrorReporter::new_with_rate_limit(
+            REPORTER_ID,
+            EventU32SenderMpsc::new(0, event_tx),
+            RateLimit::new(2, Duration::from_secs(10)),
+        );
+        let error = EventRoutingError::NoSenderForId(3);
+        let mut results = Vec::new();
+        for secs in [0, 1, 2, 10] {
+            results.push(
+                reporter
+                    .report_event_routing_error(&error, Duration::from_secs(secs))
+                    .unwrap(),
+            );
+        }
+        assert_eq!(results, [true, true, false, true]);
+        assert_eq!(reporter.num_rate_limited_routing_errors(), 1);
+        assert_eq!(reporter.num_rate_limited_tmtc_errors(), 0);
+        // TMTC errors have their own rate limit.
+        assert!(reporter
+            .report_tmtc_error(&EcssTmtcError::CantSendDirectTm, Duration::from_secs(2))
+            .unwrap());
+        assert_eq!(event_rx.try_iter().count(), 4);
+    }
+
+    #[test]
+    fn test_routing_errors_of_event_manager() {
+        let (event_tx, event_rx) = mpsc::channel();
+        let mut event_man = EventManagerWithMpsc::new(event_rx);
+        let mut reporter =
+            InternalErrorReporter::new(REPORTER_ID, EventU32SenderMpsc::new(0, event_tx.clone()));
+        let test_event = EventU32::new(crate::events::Severity::Info, 1, 1);
+        // Listener without a registered sender.
+        event_man.subscribe_single(&test_event, 7);
+        let (listener_tx, listener_rx) = mpsc::channel();
+        event_man.subscribe_group(FRAMEWORK_EVENT_GROUP_ID, 8);
+        event_man.add_sender(EventU32SenderMpsc::new(8, listener_tx));
+        event_tx.send(EventMessage::new(1, test_event)).unwrap();
+        let result = event_man.try_event_handling(|_, error| {
+            reporter
+                .report_event_routing_error(&error, Duration::ZERO)
+                .unwrap();
+        });
+        assert!(matches!(result, EventRoutingResult::Handled { .. }));
+        let result = event_man.try_event_handling(|_, _| panic!("unexpected routing error"));
+        assert!(matches!(result, EventRoutingResult::Handled { .. }));
+        let event_msg = listener_rx.try_recv().unwrap();
+        assert_eq!(event_msg.event(), EventU32::from(EVENT_ROUTING_ERROR));
+        assert_eq!(
+            event_params(&event_msg),
+            (InternalErrorKind::NoSenderForId as u32, 7)
+        );
+    }
+}
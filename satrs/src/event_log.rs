@@ -0,0 +1,567 @@
+//! On-board event log.
+//!
+//! The [EventLog] records events together with a timestamp, the ID of the event sender and the
+//! event parameters. The records are stored inside a [pool][crate::pool::PoolProvider] and are organized as a ring
+//! buffer: If the maximum number of records is reached or the pool is full, the oldest record
+//! will be overwritten.
+//!
+//! The recommended way to record all routed events is to subscribe a dedicated event sender for
+//! all events using [crate::event_man::EventManager::subscribe_all] and then record all events
+//! received through the corresponding receiver.
+//!
+//! The event log can be serialized using [EventLog::write_to_bytes] and restored using
+//! [EventLog::restore_from_bytes]. This allows storing the event history in a non-volatile
+//! memory and restoring it after an on-board reboot. The [PUS event log][crate::pus::event_log]
+//! module provides components to downlink or replay the stored event history as PUS 5 telemetry.
+//!
+//! # Record format
+//!
+//! Each record is stored in the following big endian format:
+//!
+//!  1. Length of the timestamp as a [u8].
+//!  2. Timestamp bytes.
+//!  3. Sender ID as a [u64].
+//!  4. Event as a [u32].
+//!  5. Length of the parameters as a [u16].
+//!  6. Parameter bytes.
+//!
+//! The serialized event log consists of the number of records as a [u32] followed by all records,
+//! starting with the oldest record.
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use spacepackets::ByteConversionError;
+
+use crate::events::{EventU32, GenericEvent};
+use crate::pool::PoolError;
+use crate::ComponentId;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+/// Length of the fixed fields of a serialized [EventLogRecord].
+pub const EVENT_LOG_RECORD_FIXED_LEN: usize = 1 + 8 + 4 + 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventLogError {
+    /// The timestamp length exceeds the maximum length of [u8::MAX].
+    TimestampTooLarge(usize),
+    /// The parameter length exceeds the maximum length of [u16::MAX].
+    ParamsTooLarge(usize),
+    Pool(PoolError),
+    ByteConversion(ByteConversionError),
+}
+
+impl From<PoolError> for EventLogError {
+    fn from(value: PoolError) -> Self {
+        Self::Pool(value)
+    }
+}
+
+impl From<ByteConversionError> for EventLogError {
+    fn from(value: ByteConversionError) -> Self {
+        Self::ByteConversion(value)
+    }
+}
+
+impl Display for EventLogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EventLogError::TimestampTooLarge(len) => {
+                write!(f, "timestamp with length {len} is too large")
+            }
+            EventLogError::ParamsTooLarge(len) => {
+                write!(f, "parameters with length {len} are too large")
+            }
+            EventLogError::Pool(e) => write!(f, "pool error: {e}"),
+            EventLogError::ByteConversion(e) => write!(f, "byte conversion error: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for EventLogError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EventLogError::Pool(e) => Some(e),
+            EventLogError::ByteConversion(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A single record of the [EventLog].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EventLogRecord<'a> {
+    timestamp: &'a [u8],
+    sender_id: ComponentId,
+    event: EventU32,
+    params: &'a [u8],
+}
+
+impl<'a> EventLogRecord<'a> {
+    pub fn new(
+        timestamp: &'a [u8],
+        sender_id: ComponentId,
+        event: EventU32,
+        params: &'a [u8],
+    ) -> Result<Self, EventLogError> {
+        if timestamp.len() > u8::MAX as usize {
+            return Err(EventLogError::TimestampTooLarge(timestamp.len()));
+        }
+        if params.len() > u16::MAX as usize {
+            return Err(EventLogError::ParamsTooLarge(params.len()));
+        }
+        Ok(Self {
+            timestamp,
+            sender_id,
+            event,
+            params,
+        })
+    }
+
+    pub fn timestamp(&self) -> &'a [u8] {
+        self.timestamp
+    }
+
+    pub fn sender_id(&self) -> ComponentId {
+        self.sender_id
+    }
+
+    pub fn event(&self) -> EventU32 {
+        self.event
+    }
+
+    pub fn params(&self) -> &'a [u8] {
+        self.params
+    }
+
+    pub fn written_len(&self) -> usize {
+        EVENT_LOG_RECORD_FIXED_LEN + self.timestamp.len() + self.params.len()
+    }
+
+    /// Write the record to the given buffer. Returns the number of written bytes.
+    pub fn write_to_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+        let written_len = self.written_len();
+        if buf.len() < written_len {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: written_len,
+            });
+        }
+        let mut current_idx = 0;
+        buf[current_idx] = self.timestamp.len() as u8;
+        current_idx += 1;
+        buf[current_idx..current_idx + self.timestamp.len()].copy_from_slice(self.timestamp);
+        current_idx += self.timestamp.len();
+        buf[current_idx..current_idx + 8].copy_from_slice(&self.sender_id.to_be_bytes());
+        current_idx += 8;
+        buf[current_idx..current_idx + 4].copy_from_slice(&self.event.raw().to_be_bytes());
+        current_idx += 4;
+        buf[current_idx..current_idx + 2]
+            .copy_from_slice(&(self.params.len() as u16).to_be_bytes());
+        current_idx += 2;
+        buf[current_idx..current_idx + self.params.len()].copy_from_slice(self.params);
+        current_idx += self.params.len();
+        Ok(current_idx)
+    }
+
+    /// Read a record from the given buffer. Returns the record and the number of read bytes.
+    pub fn from_bytes(buf: &'a [u8]) -> Result<(Self, usize), ByteConversionError> {
+        let check_len = |expected: usize| {
+            if buf.len() < expected {
+                return Err(ByteConversionError::FromSliceTooSmall {
+                    found: buf.len(),
+                    expected,
+                });
+            }
+            Ok(())
+        };
+        check_len(EVENT_LOG_RECORD_FIXED_LEN)?;
+        let timestamp_len = buf[0] as usize;
+        let mut current_idx = 1;
+        check_len(EVENT_LOG_RECORD_FIXED_LEN + timestamp_len)?;
+        let timestamp = &buf[current_idx..current_idx + timestamp_len];
+        current_idx += timestamp_len;
+        let sender_id =
+            ComponentId::from_be_bytes(buf[current_idx..current_idx + 8].try_into().unwrap());
+        current_idx += 8;
+        let event = EventU32::from(u32::from_be_bytes(
+            buf[current_idx..current_idx + 4].try_into().unwrap(),
+        ));
+        current_idx += 4;
+        let params_len =
+            u16::from_be_bytes(buf[current_idx..current_idx + 2].try_into().unwrap()) as usize;
+        current_idx += 2;
+        check_len(EVENT_LOG_RECORD_FIXED_LEN + timestamp_len + params_len)?;
+        let params = &buf[current_idx..current_idx + params_len];
+        current_idx += params_len;
+        Ok((
+            Self {
+                timestamp,
+                sender_id,
+                event,
+                params,
+            },
+            current_idx,
+        ))
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::collections::VecDeque;
+    use alloc::vec;
+
+    use super::*;
+    use crate::event_man::EventMessageU32;
+    use crate::params::{Params, WritableToBeBytes};
+    use crate::pool::{PoolAddr, PoolProvider, StaticMemoryPool};
+
+    /// Ring-buffer event log which stores [EventLogRecord]s inside a [PoolProvider].
+    ///
+    /// Please note that the pool should be exclusively used by the event log. The record size
+    /// is [EVENT_LOG_RECORD_FIXED_LEN] plus the length of the timestamp and the parameters, which
+    /// should be considered when configuring the pool.
+    pub struct EventLog<Pool: PoolProvider = StaticMemoryPool> {
+        pool: Pool,
+        record_addrs: VecDeque<PoolAddr>,
+        max_records: usize,
+        num_overwritten: u32,
+    }
+
+    impl<Pool: PoolProvider> EventLog<Pool> {
+        /// Create a new event log.
+        ///
+        /// ## Parameter
+        ///
+        /// * `pool` - Pool used to store the records.
+        /// * `max_records` - Maximum number of records. If this number is reached, the oldest
+        ///   record is overwritten.
+        pub fn new(pool: Pool, max_records: usize) -> Self {
+            Self {
+                pool,
+                record_addrs: VecDeque::with_capacity(max_records),
+                max_records,
+                num_overwritten: 0,
+            }
+        }
+
+        /// Add a record to the event log.
+        ///
+        /// If the maximum number of records is reached or the pool is full, the oldest records
+        /// are overwritten until the new record can be stored.
+        pub fn record(&mut self, record: &EventLogRecord) -> Result<(), EventLogError> {
+            if self.max_records == 0 {
+                return Err(PoolError::NoCapacity.into());
+            }
+            while self.record_addrs.len() >= self.max_records {
+                self.overwrite_oldest()?;
+            }
+            loop {
+                match self.pool.free_element(record.written_len(), |buf| {
+                    // The buffer has the exact size of the record.
+                    record.write_to_bytes(buf).unwrap();
+                }) {
+                    Ok(addr) => {
+                        self.record_addrs.push_back(addr);
+                        return Ok(());
+                    }
+                    Err(PoolError::StoreFull(_)) if !self.record_addrs.is_empty() => {
+                        self.overwrite_oldest()?;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+
+        /// Add an event message to the event log.
+        ///
+        /// Returns whether the event parameters were recorded. Parameters stored inside a pool
+        /// are not recorded.
+        pub fn record_event_msg(
+            &mut self,
+            timestamp: &[u8],
+            event_msg: &EventMessageU32,
+        ) -> Result<bool, EventLogError> {
+            let mut params_were_recorded = true;
+            let heapless_buf;
+            let params: &[u8] = match event_msg.params() {
+                None => &[],
+                Some(Params::Heapless(heapless_param)) => {
+                    let mut buf = vec![0; heapless_param.written_len()];
+                    heapless_param.write_to_be_bytes(&mut buf)?;
+                    heapless_buf = buf;
+                    &heapless_buf
+                }
+                Some(Params::Vec(vec)) => vec,
+                Some(Params::String(string)) => string.as_bytes(),
+                Some(_) => {
+                    params_were_recorded = false;
+                    &[]
+                }
+            };
+            self.record(&EventLogRecord::new(
+                timestamp,
+                event_msg.sender_id(),
+                event_msg.event(),
+                params,
+            )?)?;
+            Ok(params_were_recorded)
+        }
+
+        /// Number of stored records.
+        pub fn len(&self) -> usize {
+            self.record_addrs.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.record_addrs.is_empty()
+        }
+
+        pub fn max_records(&self) -> usize {
+            self.max_records
+        }
+
+        /// Number of records which were overwritten since the creation of the event log.
+        pub fn num_overwritten(&self) -> u32 {
+            self.num_overwritten
+        }
+
+        pub fn pool(&self) -> &Pool {
+            &self.pool
+        }
+
+        /// Delete all records.
+        pub fn clear(&mut self) -> Result<(), PoolError> {
+            while let Some(addr) = self.record_addrs.pop_front() {
+                self.pool.delete(addr)?;
+            }
+            Ok(())
+        }
+
+        /// Call the given closure for each record, starting with the oldest record. Returns the
+        /// number of visited records.
+        pub fn for_each_record<
+            E: From<EventLogError>,
+            F: FnMut(&EventLogRecord) -> Result<(), E>,
+        >(
+            &self,
+            mut f: F,
+        ) -> Result<u32, E> {
+            let mut buf = vec![];
+            for addr in &self.record_addrs {
+                let len = self
+                    .pool
+                    .len_of_data(addr)
+                    .map_err(|e| E::from(EventLogError::from(e)))?;
+                buf.resize(len, 0);
+                self.pool
+                    .read(addr, &mut buf)
+                    .map_err(|e| E::from(EventLogError::from(e)))?;
+                let (record, _) = EventLogRecord::from_bytes(&buf)
+                    .map_err(|e| E::from(EventLogError::from(e)))?;
+                f(&record)?;
+            }
+            Ok(self.record_addrs.len() as u32)
+        }
+
+        /// Length of the serialized event log.
+        pub fn written_len(&self) -> Result<usize, PoolError> {
+            let mut len = 4;
+            for addr in &self.record_addrs {
+                len += self.pool.len_of_data(addr)?;
+            }
+            Ok(len)
+        }
+
+        /// Serialize the event log into the given buffer, for example to store it in a
+        /// non-volatile memory. Returns the number of written bytes.
+        pub fn write_to_bytes(&self, buf: &mut [u8]) -> Result<usize, EventLogError> {
+            let written_len = self.written_len()?;
+            if buf.len() < written_len {
+                return Err(ByteConversionError::ToSliceTooSmall {
+                    found: buf.len(),
+                    expected: written_len,
+                }
+                .into());
+            }
+            buf[0..4].copy_from_slice(&(self.record_addrs.len() as u32).to_be_bytes());
+            let mut current_idx = 4;
+            for addr in &self.record_addrs {
+                current_idx += self.pool.read(addr, &mut buf[current_idx..])?;
+            }
+            Ok(current_idx)
+        }
+
+        /// Restore records from a serialized event log created with [Self::write_to_bytes].
+        /// The restored records are added to the event log as regular records. Returns the
+        /// number of restored records.
+        pub fn restore_from_bytes(&mut self, buf: &[u8]) -> Result<u32, EventLogError> {
+            if buf.len() < 4 {
+                return Err(ByteConversionError::FromSliceTooSmall {
+                    found: buf.len(),
+                    expected: 4,
+                }
+                .into());
+            }
+            let num_records = u32::from_be_bytes(buf[0..4].try_into().unwrap());
+            let mut current_idx = 4;
+            for _ in 0..num_records {
+                let (record, read_len) = EventLogRecord::from_bytes(&buf[current_idx..])?;
+                self.record(&record)?;
+                current_idx += read_len;
+            }
+            Ok(num_records)
+        }
+
+        fn overwrite_oldest(&mut self) -> Result<(), PoolError> {
+            if let Some(addr) = self.record_addrs.pop_front() {
+                self.pool.delete(addr)?;
+                self.num_overwritten += 1;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use super::*;
+    use crate::event_man::EventMessageU32;
+    use crate::events::Severity;
+    use crate::params::{Params, ParamsHeapless};
+    use crate::pool::{StaticMemoryPool, StaticPoolConfig};
+
+    const TEST_EVENT_0: EventU32 = EventU32::new(Severity::Info, 1, 1);
+    const TEST_EVENT_1: EventU32 = EventU32::new(Severity::High, 1, 2);
+    const TEST_STAMP: [u8; 7] = [1, 2, 3, 4, 5, 6, 7];
+
+    fn pool(num_blocks: u16) -> StaticMemoryPool {
+        StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(num_blocks, 64)],
+            false,
+        ))
+    }
+
+    fn recorded_events(log: &EventLog) -> std::vec::Vec<EventU32> {
+        let mut events = vec![];
+        log.for_each_record(|record| {
+            events.push(record.event());
+            Ok::<(), EventLogError>(())
+        })
+        .unwrap();
+        events
+    }
+
+    #[test]
+    fn test_record_conversion() {
+        let record = EventLogRecord::new(&TEST_STAMP, 5, TEST_EVENT_0, &[1, 2, 3]).unwrap();
+        assert_eq!(record.written_len(), EVENT_LOG_RECORD_FIXED_LEN + 7 + 3);
+        let mut buf: [u8; 32] = [0; 32];
+        let written_len = record.write_to_bytes(&mut buf).unwrap();
+        assert_eq!(written_len, record.written_len());
+        let (read_back, read_len) = EventLogRecord::from_bytes(&buf).unwrap();
+        assert_eq!(read_len, written_len);
+        assert_eq!(read_back, record);
+        assert_eq!(read_back.timestamp(), &TEST_STAMP);
+        assert_eq!(read_back.sender_id(), 5);
+        assert_eq!(read_back.event(), TEST_EVENT_0);
+        assert_eq!(read_back.params(), &[1, 2, 3]);
+        assert!(EventLogRecord::from_bytes(&buf[0..written_len - 1]).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_too_large() {
+        let stamp = [0; 256];
+        assert_eq!(
+            EventLogRecord::new(&stamp, 5, TEST_EVENT_0, &[]).unwrap_err(),
+            EventLogError::TimestampTooLarge(256)
+        );
+    }
+
+    #[test]
+    fn test_basic_log() {
+        let mut log = EventLog::new(pool(8), 4);
+        assert!(log.is_empty());
+        log.record_event_msg(&TEST_STAMP, &EventMessageU32::new(1, TEST_EVENT_0))
+            .unwrap();
+        let params_were_recorded = log
+            .record_event_msg(
+                &TEST_STAMP,
+                &EventMessageU32::new_with_params(
+                    2,
+                    TEST_EVENT_1,
+                    &Params::Heapless(ParamsHeapless::Raw(5_u32.into())),
+                ),
+            )
+            .unwrap();
+        assert!(params_were_recorded);
+        assert_eq!(log.len(), 2);
+        let mut records = vec![];
+        let num_records = log
+            .for_each_record(|record| {
+                records.push((record.sender_id(), record.event(), record.params().to_vec()));
+                Ok::<(), EventLogError>(())
+            })
+            .unwrap();
+        assert_eq!(num_records, 2);
+        assert_eq!(records[0], (1, TEST_EVENT_0, vec![]));
+        assert_eq!(records[1], (2, TEST_EVENT_1, vec![0, 0, 0, 5]));
+        log.clear().unwrap();
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_ring_buffer_overwrite() {
+        let mut log = EventLog::new(pool(8), 2);
+        for event in [TEST_EVENT_0, TEST_EVENT_1, TEST_EVENT_0] {
+            log.record_event_msg(&TEST_STAMP, &EventMessageU32::new(1, event))
+                .unwrap();
+        }
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.num_overwritten(), 1);
+        assert_eq!(recorded_events(&log), vec![TEST_EVENT_1, TEST_EVENT_0]);
+    }
+
+    #[test]
+    fn test_overwrite_when_pool_full() {
+        let mut log = EventLog::new(pool(2), 10);
+        for event in [TEST_EVENT_0, TEST_EVENT_1, TEST_EVENT_0] {
+            log.record_event_msg(&TEST_STAMP, &EventMessageU32::new(1, event))
+                .unwrap();
+        }
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.num_overwritten(), 1);
+        assert_eq!(recorded_events(&log), vec![TEST_EVENT_1, TEST_EVENT_0]);
+    }
+
+    #[test]
+    fn test_persistence() {
+        let mut log = EventLog::new(pool(8), 4);
+        log.record_event_msg(&TEST_STAMP, &EventMessageU32::new(1, TEST_EVENT_0))
+            .unwrap();
+        log.record_event_msg(&TEST_STAMP, &EventMessageU32::new(2, TEST_EVENT_1))
+            .unwrap();
+        let mut buf: [u8; 128] = [0; 128];
+        let written_len = log.write_to_bytes(&mut buf).unwrap();
+        assert_eq!(written_len, log.written_len().unwrap());
+        assert!(log.write_to_bytes(&mut buf[0..written_len - 1]).is_err());
+
+        // Simulate a reboot.
+        let mut restored_log = EventLog::new(pool(8), 4);
+        assert_eq!(
+            restored_log
+                .restore_from_bytes(&buf[0..written_len])
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            recorded_events(&restored_log),
+            vec![TEST_EVENT_0, TEST_EVENT_1]
+        );
+    }
+}
@@ -36,6 +36,17 @@
 //! events to package them as telemetry or start actions where applicable.
 //! Other components might only be interested in certain events. For example, a thermal system
 //! handler might only be interested in temperature events generated by a thermal sensor component.
+//! Components can also subscribe for all events of a certain [Severity] by using
+//! [EventManager::subscribe_severity].
+//!
+//! # Rate limiting
+//!
+//! A chattering event source, for example a defective sensor, can flood all subscribers with
+//! events. The [ListenerMapProvider::check_rate_limit] hook allows limiting the number of
+//! occurrences of an event inside a time window before it is routed. The [DefaultListenerMap]
+//! implements this hook and can be configured with a [RateLimit] for each event, for example by
+//! using [EventManager::set_rate_limit]. Events exceeding the limit are not routed, and the
+//! routing result will be [EventRoutingResult::RateLimited].
 //!
 //! # Examples
 //!
@@ -48,18 +59,23 @@
 //! The [PUS event](https://egit.irs.uni-stuttgart.de/rust/sat-rs/src/branch/main/satrs-example/src/pus/event.rs)
 //! module and the generic [events module](https://egit.irs.uni-stuttgart.de/rust/sat-rs/src/branch/main/satrs-example/src/events.rs)
 //! show how the event management modules can be integrated into a more complex software.
-use crate::events::{EventU16, EventU32, GenericEvent, LargestEventRaw, LargestGroupIdRaw};
+use crate::events::{
+    EventU16, EventU32, GenericEvent, LargestEventRaw, LargestGroupIdRaw, Severity,
+};
 use crate::params::Params;
 use crate::queue::GenericSendError;
 use core::fmt::Debug;
 use core::marker::PhantomData;
 use core::slice::Iter;
+use core::time::Duration;
 
 use crate::ComponentId;
 
 #[cfg(feature = "alloc")]
 pub use alloc_mod::*;
 
+#[cfg(feature = "heapless")]
+pub use heapless_mod::*;
 #[cfg(feature = "std")]
 pub use std_mod::*;
 
@@ -67,9 +83,77 @@ pub use std_mod::*;
 pub enum ListenerKey {
     Single(LargestEventRaw),
     Group(LargestGroupIdRaw),
+    Severity(Severity),
     All,
 }
 
+/// Rate limit for a single event.
+///
+/// An event is routed at most `max_occurrences` times inside a time window of length `window`.
+/// The window starts with the first occurrence of the event after the previous window has
+/// expired.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct RateLimit {
+    pub max_occurrences: u32,
+    pub window: Duration,
+}
+
+impl RateLimit {
+    pub const fn new(max_occurrences: u32, window: Duration) -> Self {
+        Self {
+            max_occurrences,
+            window,
+        }
+    }
+}
+
+/// Rate limiting state for a single event.
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimitState {
+    rate_limit: RateLimit,
+    window_start: Option<Duration>,
+    occurrences: u32,
+    num_rate_limited: u32,
+}
+
+impl RateLimitState {
+    pub(crate) const fn new(rate_limit: RateLimit) -> Self {
+        Self {
+            rate_limit,
+            window_start: None,
+            occurrences: 0,
+            num_rate_limited: 0,
+        }
+    }
+
+    pub(crate) fn rate_limit(&self) -> RateLimit {
+        self.rate_limit
+    }
+
+    pub(crate) fn num_rate_limited(&self) -> u32 {
+        self.num_rate_limited
+    }
+
+    /// Check whether an occurrence at the given monotonic timestamp is inside the rate limit
+    /// and update the state accordingly.
+    pub(crate) fn check(&mut self, timestamp: Duration) -> bool {
+        let window_expired = match self.window_start {
+            Some(window_start) => timestamp.saturating_sub(window_start) >= self.rate_limit.window,
+            None => true,
+        };
+        if window_expired {
+            self.window_start = Some(timestamp);
+            self.occurrences = 0;
+        }
+        if self.occurrences >= self.rate_limit.max_occurrences {
+            self.num_rate_limited = self.num_rate_limited.saturating_add(1);
+            return false;
+        }
+        self.occurrences += 1;
+        true
+    }
+}
+
 #[derive(Debug)]
 pub struct EventMessage<Event: GenericEvent, ParamProvider: Debug = Params> {
     sender_id: ComponentId,
@@ -130,6 +214,18 @@ pub trait EventReceiveProvider<Event: GenericEvent, ParamsProvider: Debug = Para
     /// This function has to be provided by any event receiver. A call may or may not return
     /// an event and optional auxiliary data.
     fn try_recv_event(&self) -> Result<Option<EventMessage<Event, ParamsProvider>>, Self::Error>;
+
+    /// Blocking variant of [Self::try_recv_event] which waits up to the given timeout for the
+    /// next event. `Ok(None)` is returned if no event was received until the timeout elapsed.
+    ///
+    /// Receivers which can not block use the default implementation, which does not wait and
+    /// is equivalent to [Self::try_recv_event].
+    fn recv_event_timeout(
+        &self,
+        _timeout: Duration,
+    ) -> Result<Option<EventMessage<Event, ParamsProvider>>, Self::Error> {
+        self.try_recv_event()
+    }
 }
 
 pub trait ListenerMapProvider {
@@ -138,7 +234,20 @@ pub trait ListenerMapProvider {
     fn contains_listener(&self, key: &ListenerKey) -> bool;
     fn get_listener_ids(&self, key: &ListenerKey) -> Option<Iter<ComponentId>>;
     fn add_listener(&mut self, key: ListenerKey, listener_id: ComponentId) -> bool;
+    /// Remove a listener for the given key. Returns whether the listener was removed.
+    fn remove_listener(&mut self, key: &ListenerKey, listener_id: ComponentId) -> bool;
+    /// Remove the listener for all keys. Returns the number of removed subscriptions.
+    fn remove_listener_for_all_keys(&mut self, listener_id: ComponentId) -> u32;
     fn remove_duplicates(&mut self, key: &ListenerKey);
+
+    /// Check whether an event may be routed at the given monotonic timestamp and update the
+    /// rate limiting state. Returns [false] if the event exceeds its rate limit and should be
+    /// dropped.
+    ///
+    /// The default implementation does not limit any events.
+    fn check_rate_limit(&mut self, _event: LargestEventRaw, _timestamp: Duration) -> bool {
+        true
+    }
 }
 
 pub trait SenderMapProvider<
@@ -151,6 +260,11 @@ pub trait SenderMapProvider<
 
     fn get_send_event_provider(&self, target_id: &ComponentId) -> Option<&EventSender>;
     fn add_send_event_provider(&mut self, send_provider: EventSender) -> bool;
+    /// Remove the send provider with the given target ID and return it if it exists.
+    fn remove_send_event_provider(&mut self, target_id: &ComponentId) -> Option<EventSender>;
+    /// Insert a send provider, replacing an existing send provider with the same target ID.
+    /// The replaced send provider is returned if it exists.
+    fn replace_send_event_provider(&mut self, send_provider: EventSender) -> Option<EventSender>;
 }
 
 /// Generic event manager implementation.
@@ -176,6 +290,8 @@ pub struct EventManager<
     event_receiver: EventReceiver,
     sender_map: SenderMap,
     listener_map: ListenerMap,
+    #[cfg(feature = "std")]
+    init_time: std::time::Instant,
     phantom: core::marker::PhantomData<(EventSender, Event, ParamProvider)>,
 }
 
@@ -188,6 +304,10 @@ pub enum EventRoutingResult<Event: GenericEvent, ParamProvider: Debug> {
         num_recipients: u32,
         event_msg: EventMessage<Event, ParamProvider>,
     },
+    /// An event was received but exceeded its rate limit and was dropped.
+    RateLimited {
+        event_msg: EventMessage<Event, ParamProvider>,
+    },
 }
 
 #[derive(Debug)]
@@ -227,6 +347,55 @@ impl<
     pub fn subscribe_all(&mut self, sender_id: ComponentId) {
         self.update_listeners(ListenerKey::All, sender_id);
     }
+
+    /// Subscribe for all events with the given severity.
+    pub fn subscribe_severity(&mut self, severity: Severity, sender_id: ComponentId) {
+        self.update_listeners(ListenerKey::Severity(severity), sender_id);
+    }
+
+    /// Remove a subscription for a unique event. Returns whether the subscription existed.
+    pub fn unsubscribe_single(&mut self, event: &Event, sender_id: ComponentId) -> bool {
+        self.listener_map
+            .remove_listener(&ListenerKey::Single(event.raw_as_largest_type()), sender_id)
+    }
+
+    /// Remove a subscription for an event group. Returns whether the subscription existed.
+    pub fn unsubscribe_group(
+        &mut self,
+        group_id: LargestGroupIdRaw,
+        sender_id: ComponentId,
+    ) -> bool {
+        self.listener_map
+            .remove_listener(&ListenerKey::Group(group_id), sender_id)
+    }
+
+    /// Remove a subscription for a severity. Returns whether the subscription existed.
+    pub fn unsubscribe_severity(&mut self, severity: Severity, sender_id: ComponentId) -> bool {
+        self.listener_map
+            .remove_listener(&ListenerKey::Severity(severity), sender_id)
+    }
+
+    /// Remove a subscription created with [Self::subscribe_all]. Returns whether the
+    /// subscription existed.
+    pub fn unsubscribe_all(&mut self, sender_id: ComponentId) -> bool {
+        self.listener_map
+            .remove_listener(&ListenerKey::All, sender_id)
+    }
+
+    /// Remove all subscriptions of a listener. Returns the number of removed subscriptions.
+    pub fn remove_listener(&mut self, sender_id: ComponentId) -> u32 {
+        self.listener_map.remove_listener_for_all_keys(sender_id)
+    }
+
+    pub fn listener_map(&self) -> &ListenerMap {
+        &self.listener_map
+    }
+
+    /// Mutable access to the listener map, which can be used to configure filtering
+    /// provided by the listener map implementation.
+    pub fn listener_map_mut(&mut self) -> &mut ListenerMap {
+        &mut self.listener_map
+    }
 }
 impl<
         EventReceiver: EventReceiveProvider<Event, ParamProvider>,
@@ -246,6 +415,8 @@ impl<
             listener_map,
             sender_map,
             event_receiver,
+            #[cfg(feature = "std")]
+            init_time: std::time::Instant::now(),
             phantom: PhantomData,
         }
     }
@@ -260,6 +431,22 @@ impl<
         }
     }
 
+    /// Remove the sender component with the given ID and return it if it exists. Subscriptions
+    /// of that ID are kept, so routing to the ID will fail with
+    /// [EventRoutingError::NoSenderForId] until a new sender with the same ID is added.
+    pub fn remove_sender(&mut self, sender_id: &ComponentId) -> Option<EventSenderMap> {
+        self.sender_map.remove_send_event_provider(sender_id)
+    }
+
+    /// Add a sender component, replacing an existing sender component with the same ID.
+    /// The replaced sender component is returned if it exists.
+    ///
+    /// This can be used to reconfigure event routing at runtime, for example when a consumer
+    /// thread is restarted with a new channel. The subscriptions of the ID remain unchanged.
+    pub fn replace_sender(&mut self, send_provider: EventSenderMap) -> Option<EventSenderMap> {
+        self.sender_map.replace_send_event_provider(send_provider)
+    }
+
     /// Generic function to update the event subscribers.
     fn update_listeners(&mut self, key: ListenerKey, sender_id: ComponentId) {
         self.listener_map.add_listener(key, sender_id);
@@ -283,10 +470,88 @@ impl<
     /// If an error occurs during the routing, the error handler will be called. The error handler
     /// should take a reference to the event message as the first argument, and the routing error
     /// as the second argument.
+    ///
+    /// With the `std` feature enabled, rate limits of the listener map are checked using the
+    /// time elapsed since the creation of the event manager. Without the `std` feature, no rate
+    /// limits are applied and [Self::try_event_handling_with_timestamp] should be used instead.
     pub fn try_event_handling<E: FnMut(&EventMessage<Event, ParamProvider>, EventRoutingError)>(
-        &self,
+        &mut self,
+        error_handler: E,
+    ) -> EventRoutingResult<Event, ParamProvider> {
+        let event_msg = self.event_receiver.try_recv_event();
+        #[cfg(feature = "std")]
+        let timestamp = Some(self.init_time.elapsed());
+        #[cfg(not(feature = "std"))]
+        let timestamp = None;
+        self.handle_next_event(event_msg, timestamp, error_handler)
+    }
+
+    /// Blocking variant of [Self::try_event_handling] which waits up to the given timeout for
+    /// the next event using [EventReceiveProvider::recv_event_timeout]. This allows the event
+    /// manager thread to sleep until an event arrives instead of polling periodically.
+    pub fn event_handling_with_timeout<
+        E: FnMut(&EventMessage<Event, ParamProvider>, EventRoutingError),
+    >(
+        &mut self,
+        timeout: Duration,
+        error_handler: E,
+    ) -> EventRoutingResult<Event, ParamProvider> {
+        let event_msg = self.event_receiver.recv_event_timeout(timeout);
+        #[cfg(feature = "std")]
+        let timestamp = Some(self.init_time.elapsed());
+        #[cfg(not(feature = "std"))]
+        let timestamp = None;
+        self.handle_next_event(event_msg, timestamp, error_handler)
+    }
+
+    /// Route all queued events until the queue is empty or the timeout elapsed. This can be used
+    /// to drain the event manager when the application is shut down.
+    #[cfg(feature = "std")]
+    pub fn drain<E: FnMut(&EventMessage<Event, ParamProvider>, EventRoutingError)>(
+        &mut self,
+        timeout: Duration,
         mut error_handler: E,
+    ) -> crate::shutdown::DrainOutcome {
+        crate::shutdown::drain_with_timeout(timeout, || {
+            match self.try_event_handling(&mut error_handler) {
+                EventRoutingResult::Empty => crate::pus::HandlingStatus::Empty,
+                _ => crate::pus::HandlingStatus::HandledOne,
+            }
+        })
+    }
+
+    /// Same as [Self::try_event_handling], but rate limits are checked with the passed
+    /// monotonic timestamp. This is useful for systems without the `std` feature or if the
+    /// rate limiting should use a custom time source.
+    pub fn try_event_handling_with_timestamp<
+        E: FnMut(&EventMessage<Event, ParamProvider>, EventRoutingError),
+    >(
+        &mut self,
+        timestamp: Duration,
+        error_handler: E,
     ) -> EventRoutingResult<Event, ParamProvider> {
+        let event_msg = self.event_receiver.try_recv_event();
+        self.handle_next_event(event_msg, Some(timestamp), error_handler)
+    }
+
+    fn handle_next_event<E: FnMut(&EventMessage<Event, ParamProvider>, EventRoutingError)>(
+        &mut self,
+        event_msg: Result<Option<EventMessage<Event, ParamProvider>>, EventReceiver::Error>,
+        timestamp: Option<Duration>,
+        mut error_handler: E,
+    ) -> EventRoutingResult<Event, ParamProvider> {
+        let event_msg = match event_msg {
+            Ok(Some(event_msg)) => event_msg,
+            _ => return EventRoutingResult::Empty,
+        };
+        if let Some(timestamp) = timestamp {
+            if !self
+                .listener_map
+                .check_rate_limit(event_msg.event.raw_as_largest_type(), timestamp)
+            {
+                return EventRoutingResult::RateLimited { event_msg };
+            }
+        }
         let mut num_recipients = 0;
         let mut send_handler =
             |key: &ListenerKey, event_msg: &EventMessage<Event, ParamProvider>| {
@@ -299,31 +564,40 @@ impl<
                                     event_msg.event,
                                     event_msg.params.as_ref(),
                                 )) {
+                                    trace_warn!(
+                                        "sending event {:#x} to component {} failed",
+                                        event_msg.event.raw_as_largest_type(),
+                                        *id
+                                    );
                                     error_handler(event_msg, EventRoutingError::Send(e));
                                 } else {
                                     num_recipients += 1;
                                 }
                             } else {
+                                trace_warn!("no event sender for component {}", *id);
                                 error_handler(event_msg, EventRoutingError::NoSenderForId(*id));
                             }
                         }
                     } else {
+                        trace_warn!(
+                            "no event senders for event {:#x}",
+                            event_msg.event.raw_as_largest_type()
+                        );
                         error_handler(event_msg, EventRoutingError::NoSendersForKey(*key));
                     }
                 }
             };
-        if let Ok(Some(event_msg)) = self.event_receiver.try_recv_event() {
-            let single_key = ListenerKey::Single(event_msg.event.raw_as_largest_type());
-            send_handler(&single_key, &event_msg);
-            let group_key = ListenerKey::Group(event_msg.event.group_id_as_largest_type());
-            send_handler(&group_key, &event_msg);
-            send_handler(&ListenerKey::All, &event_msg);
-            return EventRoutingResult::Handled {
-                num_recipients,
-                event_msg,
-            };
+        let single_key = ListenerKey::Single(event_msg.event.raw_as_largest_type());
+        send_handler(&single_key, &event_msg);
+        let group_key = ListenerKey::Group(event_msg.event.group_id_as_largest_type());
+        send_handler(&group_key, &event_msg);
+        let severity_key = ListenerKey::Severity(event_msg.event.severity());
+        send_handler(&severity_key, &event_msg);
+        send_handler(&ListenerKey::All, &event_msg);
+        EventRoutingResult::Handled {
+            num_recipients,
+            event_msg,
         }
-        EventRoutingResult::Empty
     }
 }
 
@@ -376,17 +650,83 @@ pub mod alloc_mod {
                 listener_map: DefaultListenerMap::default(),
                 sender_map: DefaultSenderMap::default(),
                 event_receiver,
+                #[cfg(feature = "std")]
+                init_time: std::time::Instant::now(),
                 phantom: PhantomData,
             }
         }
     }
 
+    impl<
+            EventReceiver: EventReceiveProvider<Event, ParamProvider>,
+            SenderMap: SenderMapProvider<EventSender, Event, ParamProvider>,
+            EventSender: EventSendProvider<Event, ParamProvider>,
+            Event: GenericEvent + Copy,
+            ParamProvider: Debug,
+        >
+        EventManager<
+            EventReceiver,
+            SenderMap,
+            DefaultListenerMap,
+            EventSender,
+            Event,
+            ParamProvider,
+        >
+    {
+        /// Set the rate limit for an event. An existing rate limit will be replaced.
+        pub fn set_rate_limit(&mut self, event: &Event, rate_limit: RateLimit) {
+            self.listener_map
+                .set_rate_limit(event.raw_as_largest_type(), rate_limit);
+        }
+
+        /// Remove the rate limit for an event. Returns whether a rate limit existed.
+        pub fn remove_rate_limit(&mut self, event: &Event) -> bool {
+            self.listener_map
+                .remove_rate_limit(event.raw_as_largest_type())
+        }
+
+        /// Number of occurrences of an event which were dropped because of its rate limit.
+        /// Returns [None] if no rate limit is configured for the event.
+        pub fn num_rate_limited(&self, event: &Event) -> Option<u32> {
+            self.listener_map
+                .num_rate_limited(event.raw_as_largest_type())
+        }
+    }
+
     /// Default listener map.
     ///
-    /// Simple implementation which uses a [HashMap] and a [Vec] internally.
+    /// Simple implementation which uses a [HashMap] and a [Vec] internally. It also supports
+    /// a [RateLimit] for each event.
     #[derive(Default)]
     pub struct DefaultListenerMap {
         listeners: HashMap<ListenerKey, Vec<ComponentId>>,
+        rate_limits: HashMap<LargestEventRaw, RateLimitState>,
+    }
+
+    impl DefaultListenerMap {
+        /// Set the rate limit for an event. An existing rate limit and its state will be
+        /// replaced.
+        pub fn set_rate_limit(&mut self, event: LargestEventRaw, rate_limit: RateLimit) {
+            self.rate_limits
+                .insert(event, RateLimitState::new(rate_limit));
+        }
+
+        /// Remove the rate limit for an event. Returns whether a rate limit existed.
+        pub fn remove_rate_limit(&mut self, event: LargestEventRaw) -> bool {
+            self.rate_limits.remove(&event).is_some()
+        }
+
+        pub fn rate_limit(&self, event: LargestEventRaw) -> Option<RateLimit> {
+            self.rate_limits.get(&event).map(|state| state.rate_limit())
+        }
+
+        /// Number of occurrences of an event which were dropped because of its rate limit.
+        /// Returns [None] if no rate limit is configured for the event.
+        pub fn num_rate_limited(&self, event: LargestEventRaw) -> Option<u32> {
+            self.rate_limits
+                .get(&event)
+                .map(|state| state.num_rate_limited())
+        }
     }
 
     impl ListenerMapProvider for DefaultListenerMap {
@@ -416,12 +756,43 @@ pub mod alloc_mod {
             true
         }
 
+        fn remove_listener(&mut self, key: &ListenerKey, listener_id: ComponentId) -> bool {
+            let mut removed = false;
+            if let Some(list) = self.listeners.get_mut(key) {
+                let len_before = list.len();
+                list.retain(|id| *id != listener_id);
+                removed = list.len() != len_before;
+                if list.is_empty() {
+                    self.listeners.remove(key);
+                }
+            }
+            removed
+        }
+
+        fn remove_listener_for_all_keys(&mut self, listener_id: ComponentId) -> u32 {
+            let mut num_removed = 0;
+            self.listeners.retain(|_, list| {
+                let len_before = list.len();
+                list.retain(|id| *id != listener_id);
+                num_removed += (len_before - list.len()) as u32;
+                !list.is_empty()
+            });
+            num_removed
+        }
+
         fn remove_duplicates(&mut self, key: &ListenerKey) {
             if let Some(list) = self.listeners.get_mut(key) {
                 list.sort_unstable();
                 list.dedup();
             }
         }
+
+        fn check_rate_limit(&mut self, event: LargestEventRaw, timestamp: Duration) -> bool {
+            match self.rate_limits.get_mut(&event) {
+                Some(state) => state.check(timestamp),
+                None => true,
+            }
+        }
     }
 
     /// Default sender map.
@@ -474,11 +845,215 @@ pub mod alloc_mod {
             }
             self.senders.insert(id, send_provider).is_none()
         }
+
+        fn remove_send_event_provider(&mut self, id: &ComponentId) -> Option<EventSender> {
+            self.senders.remove(id)
+        }
+
+        fn replace_send_event_provider(
+            &mut self,
+            send_provider: EventSender,
+        ) -> Option<EventSender> {
+            self.senders
+                .insert(send_provider.target_id(), send_provider)
+        }
+    }
+}
+
+/// Event channel components which are backed by the lock-free [heapless::mpmc::MpMcQueue].
+///
+/// These components do not require a heap or an operating system. The queue can be allocated
+/// statically, and events can be posted safely from interrupt handlers or other cores, for
+/// example to forward them to an [EventManager] running in the main loop. The capacity of the
+/// queue must be a power of 2.
+///
+/// Please note that the [heapless::mpmc::MpMcQueue] requires atomic compare-and-swap
+/// instructions, which are not available on all targets.
+///
+/// # Example
+///
+/// ```
+/// use heapless::mpmc::MpMcQueue;
+/// use satrs::event_man::{
+///     EventMessageU32, EventReceiveProvider, EventReceiverMpmc, EventSendProvider,
+///     EventSenderMpmc,
+/// };
+/// use satrs::events::{EventU32, Severity};
+///
+/// const TEST_EVENT: EventU32 = EventU32::new(Severity::Info, 0, 5);
+/// static EVENT_QUEUE: MpMcQueue<EventMessageU32, 16> = MpMcQueue::new();
+///
+/// // The sender can be used inside an ISR.
+/// let sender = EventSenderMpmc::new(1, &EVENT_QUEUE);
+/// sender.send(EventMessageU32::new(2, TEST_EVENT)).unwrap();
+///
+/// let receiver = EventReceiverMpmc::new(&EVENT_QUEUE);
+/// let event = receiver.try_recv_event().unwrap().unwrap();
+/// assert_eq!(event.event(), TEST_EVENT);
+/// ```
+#[cfg(feature = "heapless")]
+pub mod heapless_mod {
+    use heapless::mpmc::MpMcQueue;
+
+    use crate::queue::GenericReceiveError;
+
+    use super::*;
+
+    /// Event sender which uses a [MpMcQueue] as the message queue backend.
+    pub struct EventSenderMpmc<
+        'queue,
+        Event: GenericEvent,
+        const N: usize,
+        ParamProvider: Debug = Params,
+    > {
+        target_id: ComponentId,
+        queue: &'queue MpMcQueue<EventMessage<Event, ParamProvider>, N>,
+    }
+
+    impl<'queue, Event: GenericEvent, const N: usize, ParamProvider: Debug>
+        EventSenderMpmc<'queue, Event, N, ParamProvider>
+    {
+        pub fn new(
+            target_id: ComponentId,
+            queue: &'queue MpMcQueue<EventMessage<Event, ParamProvider>, N>,
+        ) -> Self {
+            Self { target_id, queue }
+        }
+    }
+
+    impl<Event: GenericEvent, const N: usize, ParamProvider: Debug> Clone
+        for EventSenderMpmc<'_, Event, N, ParamProvider>
+    {
+        fn clone(&self) -> Self {
+            Self {
+                target_id: self.target_id,
+                queue: self.queue,
+            }
+        }
+    }
+
+    impl<Event: GenericEvent, const N: usize, ParamProvider: Debug>
+        EventSendProvider<Event, ParamProvider> for EventSenderMpmc<'_, Event, N, ParamProvider>
+    {
+        type Error = GenericSendError;
+
+        fn target_id(&self) -> ComponentId {
+            self.target_id
+        }
+
+        fn send(&self, message: EventMessage<Event, ParamProvider>) -> Result<(), Self::Error> {
+            self.queue
+                .enqueue(message)
+                .map_err(|_| GenericSendError::QueueFull(Some(N as u32)))
+        }
+    }
+
+    /// Event receiver which uses a [MpMcQueue] as the message queue backend.
+    pub struct EventReceiverMpmc<
+        'queue,
+        Event: GenericEvent,
+        const N: usize,
+        ParamProvider: Debug = Params,
+    > {
+        queue: &'queue MpMcQueue<EventMessage<Event, ParamProvider>, N>,
+    }
+
+    impl<'queue, Event: GenericEvent, const N: usize, ParamProvider: Debug>
+        EventReceiverMpmc<'queue, Event, N, ParamProvider>
+    {
+        pub fn new(queue: &'queue MpMcQueue<EventMessage<Event, ParamProvider>, N>) -> Self {
+            Self { queue }
+        }
+    }
+
+    impl<Event: GenericEvent, const N: usize, ParamProvider: Debug>
+        EventReceiveProvider<Event, ParamProvider>
+        for EventReceiverMpmc<'_, Event, N, ParamProvider>
+    {
+        type Error = GenericReceiveError;
+
+        fn try_recv_event(
+            &self,
+        ) -> Result<Option<EventMessage<Event, ParamProvider>>, Self::Error> {
+            Ok(self.queue.dequeue())
+        }
+    }
+
+    pub type EventU32SenderMpmc<'queue, const N: usize> = EventSenderMpmc<'queue, EventU32, N>;
+    pub type EventU16SenderMpmc<'queue, const N: usize> = EventSenderMpmc<'queue, EventU16, N>;
+    pub type EventU32ReceiverMpmc<'queue, const N: usize> = EventReceiverMpmc<'queue, EventU32, N>;
+    pub type EventU16ReceiverMpmc<'queue, const N: usize> = EventReceiverMpmc<'queue, EventU16, N>;
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::events::Severity;
+        use crate::params::ParamsRaw;
+
+        const TEST_EVENT: EventU32 = EventU32::new(Severity::Info, 0, 5);
+
+        #[test]
+        fn test_send_and_receive() {
+            let queue: MpMcQueue<EventMessageU32, 4> = MpMcQueue::new();
+            let sender = EventU32SenderMpmc::new(1, &queue);
+            let receiver = EventU32ReceiverMpmc::new(&queue);
+            assert!(receiver.try_recv_event().unwrap().is_none());
+            let params = Params::from(ParamsRaw::from(5_u32));
+            sender
+                .send(EventMessageU32::new_with_params(2, TEST_EVENT, &params))
+                .unwrap();
+            let event = receiver.try_recv_event().unwrap().unwrap();
+            assert_eq!(event.sender_id(), 2);
+            assert_eq!(event.event(), TEST_EVENT);
+            assert_eq!(event.params(), Some(&params));
+        }
+
+        #[test]
+        fn test_queue_full() {
+            let queue: MpMcQueue<EventMessageU32, 2> = MpMcQueue::new();
+            let sender = EventU32SenderMpmc::new(1, &queue);
+            sender.send(EventMessageU32::new(2, TEST_EVENT)).unwrap();
+            sender.send(EventMessageU32::new(2, TEST_EVENT)).unwrap();
+            assert_eq!(
+                sender
+                    .send(EventMessageU32::new(2, TEST_EVENT))
+                    .unwrap_err(),
+                GenericSendError::QueueFull(Some(2))
+            );
+        }
+
+        #[cfg(feature = "alloc")]
+        #[test]
+        fn test_event_manager_with_mpmc_queues() {
+            let event_queue: MpMcQueue<EventMessageU32, 4> = MpMcQueue::new();
+            let listener_queue: MpMcQueue<EventMessageU32, 4> = MpMcQueue::new();
+            let event_sender = EventU32SenderMpmc::new(0, &event_queue);
+            let mut event_man = EventManager::new(EventU32ReceiverMpmc::new(&event_queue));
+            event_man.subscribe_single(&TEST_EVENT, 1);
+            event_man.add_sender(EventU32SenderMpmc::new(1, &listener_queue));
+            event_sender
+                .send(EventMessageU32::new(2, TEST_EVENT))
+                .unwrap();
+            let result = event_man.try_event_handling(|_, _| {
+                panic!("unexpected routing error");
+            });
+            assert!(matches!(
+                result,
+                EventRoutingResult::Handled {
+                    num_recipients: 1,
+                    ..
+                }
+            ));
+            let listener_receiver = EventU32ReceiverMpmc::new(&listener_queue);
+            let event = listener_receiver.try_recv_event().unwrap().unwrap();
+            assert_eq!(event.event(), TEST_EVENT);
+        }
     }
 }
 
 #[cfg(feature = "std")]
 pub mod std_mod {
+    use crate::id_registry::{ComponentIdError, ComponentIdRegistry};
     use crate::queue::GenericReceiveError;
 
     use super::*;
@@ -503,6 +1078,21 @@ pub mod std_mod {
                 },
             }
         }
+
+        fn recv_event_timeout(
+            &self,
+            timeout: Duration,
+        ) -> Result<Option<EventMessage<Event, ParamProvider>>, Self::Error> {
+            match self.recv_timeout(timeout) {
+                Ok(msg) => Ok(Some(msg)),
+                Err(e) => match e {
+                    mpsc::RecvTimeoutError::Timeout => Ok(None),
+                    mpsc::RecvTimeoutError::Disconnected => {
+                        Err(GenericReceiveError::TxDisconnected(None))
+                    }
+                },
+            }
+        }
     }
 
     pub type EventU32ReceiverMpsc<ParamProvider = Params> =
@@ -522,6 +1112,18 @@ pub mod std_mod {
         pub fn new(target_id: ComponentId, sender: mpsc::Sender<EventMessage<Event>>) -> Self {
             Self { target_id, sender }
         }
+
+        /// Create a new sender and register its target ID with the given name in the
+        /// [ComponentIdRegistry].
+        pub fn new_registered(
+            registry: &mut ComponentIdRegistry,
+            target_id: ComponentId,
+            name: &'static str,
+            sender: mpsc::Sender<EventMessage<Event>>,
+        ) -> Result<Self, ComponentIdError> {
+            registry.register(target_id, name)?;
+            Ok(Self::new(target_id, sender))
+        }
     }
 
     impl<Event: GenericEvent + Send> EventSendProvider<Event> for EventSenderMpsc<Event> {
@@ -559,6 +1161,19 @@ pub mod std_mod {
                 capacity,
             }
         }
+
+        /// Create a new sender and register its target ID with the given name in the
+        /// [ComponentIdRegistry].
+        pub fn new_registered(
+            registry: &mut ComponentIdRegistry,
+            target_id: ComponentId,
+            name: &'static str,
+            sender: mpsc::SyncSender<EventMessage<Event>>,
+            capacity: usize,
+        ) -> Result<Self, ComponentIdError> {
+            registry.register(target_id, name)?;
+            Ok(Self::new(target_id, sender, capacity))
+        }
     }
 
     impl<Event: GenericEvent + Send> EventSendProvider<Event> for EventSenderMpscBounded<Event> {
@@ -592,6 +1207,7 @@ mod tests {
     use super::*;
     use crate::event_man::EventManager;
     use crate::events::{EventU32, GenericEvent, Severity};
+    use crate::id_registry::{ComponentIdError, ComponentIdRegistry};
     use crate::params::{ParamsHeapless, ParamsRaw};
     use crate::pus::test_util::{TEST_COMPONENT_ID_0, TEST_COMPONENT_ID_1};
     use std::format;
@@ -818,6 +1434,234 @@ mod tests {
         check_next_event(event_1, &all_events_rx);
     }
 
+    #[test]
+    fn test_unsubscribe() {
+        let (event_sender, mut event_man) = generic_event_man();
+        let event_grp_0 = EventU32::new(Severity::Info, 0, 0);
+        let (listener_tx, listener_rx) = mpsc::channel();
+        let listener = EventU32SenderMpsc::new(0, listener_tx);
+        event_man.subscribe_single(&event_grp_0, listener.target_id());
+        event_man.subscribe_group(event_grp_0.group_id(), listener.target_id());
+        event_man.subscribe_all(listener.target_id());
+        event_man.add_sender(listener);
+        let error_handler = |event_msg: &EventMessageU32, e: EventRoutingError| {
+            panic!("routing error occurred for event {:?}: {:?}", event_msg, e);
+        };
+        event_sender
+            .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), event_grp_0))
+            .expect("sending event failed");
+        let res = event_man.try_event_handling(&error_handler);
+        check_handled_event(res, event_grp_0, 3, TEST_COMPONENT_ID_0.id());
+
+        assert!(event_man.unsubscribe_single(&event_grp_0, 0));
+        assert!(!event_man.unsubscribe_single(&event_grp_0, 0));
+        assert!(event_man.unsubscribe_group(event_grp_0.group_id(), 0));
+        event_sender
+            .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), event_grp_0))
+            .expect("sending event failed");
+        let res = event_man.try_event_handling(&error_handler);
+        check_handled_event(res, event_grp_0, 1, TEST_COMPONENT_ID_0.id());
+
+        assert!(event_man.unsubscribe_all(0));
+        event_sender
+            .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), event_grp_0))
+            .expect("sending event failed");
+        let res = event_man.try_event_handling(&error_handler);
+        check_handled_event(res, event_grp_0, 0, TEST_COMPONENT_ID_0.id());
+        assert_eq!(listener_rx.try_iter().count(), 4);
+    }
+
+    #[test]
+    fn test_remove_listener() {
+        let (event_sender, mut event_man) = generic_event_man();
+        let event_grp_0 = EventU32::new(Severity::Info, 0, 0);
+        let (listener_tx, _listener_rx) = mpsc::channel();
+        let listener = EventU32SenderMpsc::new(0, listener_tx);
+        event_man.subscribe_single(&event_grp_0, listener.target_id());
+        event_man.subscribe_all(listener.target_id());
+        event_man.add_sender(listener);
+        assert_eq!(event_man.remove_listener(0), 2);
+        assert_eq!(event_man.remove_listener(0), 0);
+        event_sender
+            .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), event_grp_0))
+            .expect("sending event failed");
+        let res = event_man.try_event_handling(|_, e| {
+            panic!("unexpected routing error: {:?}", e);
+        });
+        check_handled_event(res, event_grp_0, 0, TEST_COMPONENT_ID_0.id());
+    }
+
+    #[test]
+    fn test_remove_and_replace_sender() {
+        let (event_sender, mut event_man) = generic_event_man();
+        let event_grp_0 = EventU32::new(Severity::Info, 0, 0);
+        let (listener_tx, listener_rx) = mpsc::channel();
+        event_man.subscribe_single(&event_grp_0, 0);
+        event_man.add_sender(EventU32SenderMpsc::new(0, listener_tx));
+
+        // Simulate a restarted consumer thread with a new channel.
+        let (new_listener_tx, new_listener_rx) = mpsc::channel();
+        let replaced = event_man.replace_sender(EventU32SenderMpsc::new(0, new_listener_tx));
+        assert!(replaced.is_some());
+        event_sender
+            .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), event_grp_0))
+            .expect("sending event failed");
+        let res = event_man.try_event_handling(|_, e| {
+            panic!("unexpected routing error: {:?}", e);
+        });
+        check_handled_event(res, event_grp_0, 1, TEST_COMPONENT_ID_0.id());
+        assert!(listener_rx.try_recv().is_err());
+        let event_msg = new_listener_rx.try_recv().expect("no event received");
+        assert_eq!(event_msg.event(), event_grp_0);
+
+        assert!(event_man.remove_sender(&0).is_some());
+        assert!(event_man.remove_sender(&0).is_none());
+        event_sender
+            .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), event_grp_0))
+            .expect("sending event failed");
+        let mut no_sender_error = false;
+        let res = event_man.try_event_handling(|_, e| {
+            if let EventRoutingError::NoSenderForId(id) = e {
+                assert_eq!(id, 0);
+                no_sender_error = true;
+            }
+        });
+        check_handled_event(res, event_grp_0, 0, TEST_COMPONENT_ID_0.id());
+        assert!(no_sender_error);
+    }
+
+    #[test]
+    fn test_severity_subscription() {
+        let (event_sender, mut event_man) = generic_event_man();
+        let info_event = EventU32::new(Severity::Info, 0, 0);
+        let high_sev_event = EventU32::new(Severity::High, 1, 0);
+        let (listener_tx, listener_rx) = mpsc::channel();
+        let listener = EventU32SenderMpsc::new(0, listener_tx);
+        event_man.subscribe_severity(Severity::High, listener.target_id());
+        event_man.add_sender(listener);
+        let error_handler = |event_msg: &EventMessageU32, e: EventRoutingError| {
+            panic!("routing error occurred for event {:?}: {:?}", event_msg, e);
+        };
+        event_sender
+            .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), info_event))
+            .expect("sending event failed");
+        let res = event_man.try_event_handling(&error_handler);
+        check_handled_event(res, info_event, 0, TEST_COMPONENT_ID_0.id());
+        event_sender
+            .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), high_sev_event))
+            .expect("sending event failed");
+        let res = event_man.try_event_handling(&error_handler);
+        check_handled_event(res, high_sev_event, 1, TEST_COMPONENT_ID_0.id());
+        check_next_event(high_sev_event, &listener_rx);
+        assert!(listener_rx.try_recv().is_err());
+
+        assert!(event_man.unsubscribe_severity(Severity::High, 0));
+        assert!(!event_man.unsubscribe_severity(Severity::High, 0));
+    }
+
+    #[test]
+    fn test_rate_limiting() {
+        let (event_sender, mut event_man) = generic_event_man();
+        let chattering_event = EventU32::new(Severity::Low, 2, 1);
+        let (listener_tx, listener_rx) = mpsc::channel();
+        let listener = EventU32SenderMpsc::new(0, listener_tx);
+        event_man.subscribe_all(listener.target_id());
+        event_man.add_sender(listener);
+        event_man.set_rate_limit(
+            &chattering_event,
+            RateLimit::new(2, Duration::from_millis(100)),
+        );
+        assert_eq!(event_man.num_rate_limited(&chattering_event), Some(0));
+        assert_eq!(event_man.num_rate_limited(&TEST_EVENT), None);
+        let error_handler = |event_msg: &EventMessageU32, e: EventRoutingError| {
+            panic!("routing error occurred for event {:?}: {:?}", event_msg, e);
+        };
+        for _ in 0..3 {
+            event_sender
+                .send(EventMessage::new(
+                    TEST_COMPONENT_ID_0.id(),
+                    chattering_event,
+                ))
+                .expect("sending event failed");
+        }
+        let res =
+            event_man.try_event_handling_with_timestamp(Duration::from_millis(0), &error_handler);
+        check_handled_event(res, chattering_event, 1, TEST_COMPONENT_ID_0.id());
+        let res =
+            event_man.try_event_handling_with_timestamp(Duration::from_millis(50), &error_handler);
+        check_handled_event(res, chattering_event, 1, TEST_COMPONENT_ID_0.id());
+        let res =
+            event_man.try_event_handling_with_timestamp(Duration::from_millis(99), &error_handler);
+        assert!(matches!(res, EventRoutingResult::RateLimited { .. }));
+        assert_eq!(event_man.num_rate_limited(&chattering_event), Some(1));
+
+        // Other events are not affected by the rate limit.
+        event_sender
+            .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), TEST_EVENT))
+            .expect("sending event failed");
+        let res =
+            event_man.try_event_handling_with_timestamp(Duration::from_millis(99), &error_handler);
+        check_handled_event(res, TEST_EVENT, 1, TEST_COMPONENT_ID_0.id());
+
+        // A new window starts after the previous one has expired.
+        event_sender
+            .send(EventMessage::new(
+                TEST_COMPONENT_ID_0.id(),
+                chattering_event,
+            ))
+            .expect("sending event failed");
+        let res =
+            event_man.try_event_handling_with_timestamp(Duration::from_millis(100), &error_handler);
+        check_handled_event(res, chattering_event, 1, TEST_COMPONENT_ID_0.id());
+        assert_eq!(listener_rx.try_iter().count(), 4);
+
+        assert!(event_man.remove_rate_limit(&chattering_event));
+        assert!(!event_man.remove_rate_limit(&chattering_event));
+        assert_eq!(event_man.num_rate_limited(&chattering_event), None);
+    }
+
+    #[test]
+    fn test_event_handling_with_timeout() {
+        let (event_sender, mut event_man) = generic_event_man();
+        let (listener_sender, listener_receiver) = mpsc::channel();
+        event_man.subscribe_all(1);
+        event_man.add_sender(EventU32SenderMpsc::new(1, listener_sender));
+        let error_handler = |event_msg: &EventMessageU32, e: EventRoutingError| {
+            panic!("routing error occurred for event {:?}: {:?}", event_msg, e);
+        };
+        let res = event_man.event_handling_with_timeout(Duration::from_millis(5), error_handler);
+        assert!(matches!(res, EventRoutingResult::Empty));
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            event_sender
+                .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), TEST_EVENT))
+                .unwrap();
+        });
+        let res = event_man.event_handling_with_timeout(Duration::from_secs(2), error_handler);
+        check_handled_event(res, TEST_EVENT, 1, TEST_COMPONENT_ID_0.id());
+        check_next_event(TEST_EVENT, &listener_receiver);
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn test_drain() {
+        let (event_sender, mut event_man) = generic_event_man();
+        let (listener_sender, listener_receiver) = mpsc::channel();
+        event_man.subscribe_all(1);
+        event_man.add_sender(EventU32SenderMpsc::new(1, listener_sender));
+        for _ in 0..3 {
+            event_sender
+                .send(EventMessage::new(TEST_COMPONENT_ID_0.id(), TEST_EVENT))
+                .unwrap();
+        }
+        let outcome = event_man.drain(Duration::from_secs(1), |event_msg, e| {
+            panic!("routing error occurred for event {:?}: {:?}", event_msg, e);
+        });
+        assert_eq!(outcome.num_drained, 3);
+        assert!(!outcome.timed_out);
+        assert_eq!(listener_receiver.try_iter().count(), 3);
+    }
+
     #[test]
     fn test_bounded_event_sender_queue_full() {
         let (event_sender, _event_receiver) = mpsc::sync_channel(3);
@@ -849,4 +1693,31 @@ mod tests {
             panic!("Expected error");
         }
     }
+
+    #[test]
+    fn test_registered_event_senders() {
+        let mut registry = ComponentIdRegistry::new();
+        let (listener_tx, _listener_rx) = mpsc::channel();
+        let listener =
+            EventU32SenderMpsc::new_registered(&mut registry, 1, "LISTENER", listener_tx)
+                .expect("registering listener failed");
+        assert_eq!(listener.target_id(), 1);
+        assert_eq!(registry.name_of(1), Some("LISTENER"));
+        let (bounded_tx, _bounded_rx) = mpsc::sync_channel(3);
+        let error = EventU32SenderMpscBounded::new_registered(
+            &mut registry,
+            1,
+            "BOUNDED_LISTENER",
+            bounded_tx,
+            3,
+        );
+        assert!(matches!(
+            error,
+            Err(ComponentIdError::DuplicateId {
+                id: 1,
+                first: "LISTENER",
+                second: "BOUNDED_LISTENER"
+            })
+        ));
+    }
 }
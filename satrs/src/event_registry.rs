@@ -0,0 +1,759 @@
+//! # Event definition registry
+//!
+//! Events are transmitted as raw [u32] values, which are hard to interpret on the ground without
+//! additional information. This module allows to declare events together with a name, a
+//! description and the type of the expected parameters using [EventDefinition]s.
+//!
+//! The [crate::event_definitions] macro declares the [EventU32TypedSev] constants and a constant
+//! list of their definitions in one go. The definitions can then be exported as CSV with
+//! [write_csv] or as JSON with [write_json] for the ingestion by a ground system. The
+//! [EventDefinitionRegistry] provides lookups by raw value and name and checks for duplicates
+//! at startup.
+//!
+//! Events are identified by their group and unique ID, independently of their severity. The
+//! [crate::event_definitions] macro checks at compile time that the group and unique ID pairs
+//! of a definition list are unique. Usually, each subsystem declares its own list. The
+//! [crate::assert_unique_event_ids] macro performs the same check for all lists of a binary, so
+//! collisions between the events of different subsystems are found at compile time as well.
+//!
+//! # Examples
+//!
+//! ```
+//! use satrs::event_definitions;
+//! use satrs::event_registry::EventDefinitionRegistry;
+//! use satrs::events::{SeverityHigh, SeverityInfo};
+//!
+//! event_definitions! {
+//!     pub EVENT_DEFINITIONS;
+//!     /// A telecommand was received.
+//!     pub TC_RECEIVED: SeverityInfo = (1, 0), U32, "A telecommand was received";
+//!     pub TEMPERATURE_HIGH: SeverityHigh = (2, 0), F32, "The temperature is too high";
+//! }
+//!
+//! event_definitions! {
+//!     pub ACS_EVENT_DEFINITIONS;
+//!     pub MGM_FAULTY: SeverityHigh = (3, 0), None, "The MGM is faulty";
+//! }
+//!
+//! // Fails to compile if two lists contain events with the same group and unique ID.
+//! satrs::assert_unique_event_ids!(EVENT_DEFINITIONS, ACS_EVENT_DEFINITIONS);
+//!
+//! let registry =
+//!     EventDefinitionRegistry::with_definition_lists(&[EVENT_DEFINITIONS, ACS_EVENT_DEFINITIONS]);
+//! assert_eq!(registry.name_of(TC_RECEIVED).unwrap(), "TC_RECEIVED");
+//! let csv = registry.to_csv();
+//! assert!(csv.contains("TEMPERATURE_HIGH"));
+//! ```
+use core::fmt::{Display, Formatter, Write};
+
+use crate::events::{EventU32, EventU32TypedSev, GenericEvent, HasSeverity, Severity};
+use crate::params::{Params, ParamsHeapless, ParamsRaw};
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+/// Type of the parameters which are expected to be sent together with an event.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EventParamType {
+    None,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    U32Pair,
+    EcssEnum,
+    Bytes,
+    String,
+}
+
+impl EventParamType {
+    /// Name of the type which is used for the export.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            EventParamType::None => "none",
+            EventParamType::U8 => "u8",
+            EventParamType::U16 => "u16",
+            EventParamType::U32 => "u32",
+            EventParamType::U64 => "u64",
+            EventParamType::I8 => "i8",
+            EventParamType::I16 => "i16",
+            EventParamType::I32 => "i32",
+            EventParamType::I64 => "i64",
+            EventParamType::F32 => "f32",
+            EventParamType::F64 => "f64",
+            EventParamType::U32Pair => "u32_pair",
+            EventParamType::EcssEnum => "ecss_enum",
+            EventParamType::Bytes => "bytes",
+            EventParamType::String => "string",
+        }
+    }
+
+    /// Check whether the given parameters have the expected type.
+    pub fn matches(&self, params: Option<&Params>) -> bool {
+        let params = match params {
+            Some(params) => params,
+            None => return *self == EventParamType::None,
+        };
+        match params {
+            Params::Heapless(ParamsHeapless::Raw(raw)) => matches!(
+                (self, raw),
+                (EventParamType::U8, ParamsRaw::U8(_))
+                    | (EventParamType::U16, ParamsRaw::U16(_))
+                    | (EventParamType::U32, ParamsRaw::U32(_))
+                    | (EventParamType::U64, ParamsRaw::U64(_))
+                    | (EventParamType::I8, ParamsRaw::I8(_))
+                    | (EventParamType::I16, ParamsRaw::I16(_))
+                    | (EventParamType::I32, ParamsRaw::I32(_))
+                    | (EventParamType::I64, ParamsRaw::I64(_))
+                    | (EventParamType::F32, ParamsRaw::F32(_))
+                    | (EventParamType::F64, ParamsRaw::F64(_))
+                    | (EventParamType::U32Pair, ParamsRaw::U32Pair(_))
+            ),
+            Params::Heapless(ParamsHeapless::EcssEnum(_)) => *self == EventParamType::EcssEnum,
+            Params::Store(_) => *self == EventParamType::Bytes,
+            #[cfg(feature = "alloc")]
+            Params::Vec(_) => *self == EventParamType::Bytes,
+            #[cfg(feature = "alloc")]
+            Params::String(_) => *self == EventParamType::String,
+        }
+    }
+}
+
+impl Display for EventParamType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Name of the severity which is used for the export.
+pub const fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "INFO",
+        Severity::Low => "LOW",
+        Severity::Medium => "MEDIUM",
+        Severity::High => "HIGH",
+    }
+}
+
+/// Declaration of a single event.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EventDefinition {
+    pub event: EventU32,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub param_type: EventParamType,
+}
+
+impl EventDefinition {
+    pub const fn new(
+        event: EventU32,
+        name: &'static str,
+        description: &'static str,
+        param_type: EventParamType,
+    ) -> Self {
+        Self {
+            event,
+            name,
+            description,
+            param_type,
+        }
+    }
+
+    pub fn new_typed<SEVERITY: HasSeverity>(
+        event: EventU32TypedSev<SEVERITY>,
+        name: &'static str,
+        description: &'static str,
+        param_type: EventParamType,
+    ) -> Self {
+        Self::new(event.into(), name, description, param_type)
+    }
+
+    pub fn raw(&self) -> u32 {
+        self.event.raw()
+    }
+}
+
+/// Conflict between two event definitions found by [find_duplicate].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EventDefinitionError {
+    /// Two definitions have the same raw event value.
+    DuplicateEvent {
+        raw: u32,
+        first: &'static str,
+        second: &'static str,
+    },
+    /// Two definitions have the same name.
+    DuplicateName(&'static str),
+}
+
+impl Display for EventDefinitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EventDefinitionError::DuplicateEvent { raw, first, second } => {
+                write!(
+                    f,
+                    "events {first} and {second} have the same raw value {raw:#010x}"
+                )
+            }
+            EventDefinitionError::DuplicateName(name) => {
+                write!(f, "event name {name} is used more than once")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EventDefinitionError {}
+
+/// Find the first pair of definitions which have the same raw event value or the same name.
+/// This function does not require heap allocations and has a quadratic complexity, so it is
+/// intended to be called once at startup.
+pub fn find_duplicate(definitions: &[EventDefinition]) -> Option<EventDefinitionError> {
+    for (idx, first) in definitions.iter().enumerate() {
+        for second in &definitions[idx + 1..] {
+            if first.raw() == second.raw() {
+                return Some(EventDefinitionError::DuplicateEvent {
+                    raw: first.raw(),
+                    first: first.name,
+                    second: second.name,
+                });
+            }
+            if first.name == second.name {
+                return Some(EventDefinitionError::DuplicateName(first.name));
+            }
+        }
+    }
+    None
+}
+
+/// Two event definitions with the same group and unique ID found by [find_id_collision].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EventIdCollision {
+    pub group_id: u16,
+    pub unique_id: u16,
+    pub first: &'static str,
+    pub second: &'static str,
+}
+
+impl Display for EventIdCollision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "events {} and {} have the same group ID {} and unique ID {}",
+            self.first, self.second, self.group_id, self.unique_id
+        )
+    }
+}
+
+/// Find the first pair of definitions in the given definition lists which have the same group
+/// and unique ID. The severity is ignored because events with different severities but the same
+/// IDs can not be distinguished on the ground.
+///
+/// This is a const function, so it can be evaluated at compile time. This is done by the
+/// [crate::event_definitions] and [crate::assert_unique_event_ids] macros.
+pub const fn find_id_collision(lists: &[&[EventDefinition]]) -> Option<EventIdCollision> {
+    let mut list_idx = 0;
+    while list_idx < lists.len() {
+        let mut idx = 0;
+        while idx < lists[list_idx].len() {
+            let first = &lists[list_idx][idx];
+            let group_id = first.event.const_group_id();
+            let unique_id = first.event.const_unique_id();
+            // Compare with all following definitions of the same and of the following lists.
+            let mut other_list_idx = list_idx;
+            let mut other_idx = idx + 1;
+            while other_list_idx < lists.len() {
+                while other_idx < lists[other_list_idx].len() {
+                    let second = &lists[other_list_idx][other_idx];
+                    if second.event.const_group_id() == group_id
+                        && second.event.const_unique_id() == unique_id
+                    {
+                        return Some(EventIdCollision {
+                            group_id,
+                            unique_id,
+                            first: first.name,
+                            second: second.name,
+                        });
+                    }
+                    other_idx += 1;
+                }
+                other_list_idx += 1;
+                other_idx = 0;
+            }
+            idx += 1;
+        }
+        list_idx += 1;
+    }
+    None
+}
+
+/// Header line of the CSV export.
+pub const CSV_HEADER: &str = "raw,name,group_id,unique_id,severity,param_type,description";
+
+fn write_csv_field(writer: &mut impl Write, field: &str) -> core::fmt::Result {
+    if !field.contains([',', '"', '\n', '\r']) {
+        return writer.write_str(field);
+    }
+    writer.write_char('"')?;
+    for c in field.chars() {
+        if c == '"' {
+            writer.write_char('"')?;
+        }
+        writer.write_char(c)?;
+    }
+    writer.write_char('"')
+}
+
+fn write_json_str(writer: &mut impl Write, value: &str) -> core::fmt::Result {
+    writer.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => writer.write_str("\\\"")?,
+            '\\' => writer.write_str("\\\\")?,
+            '\n' => writer.write_str("\\n")?,
+            '\r' => writer.write_str("\\r")?,
+            '\t' => writer.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(writer, "\\u{:04x}", c as u32)?,
+            c => writer.write_char(c)?,
+        }
+    }
+    writer.write_char('"')
+}
+
+/// Export the definitions as CSV with the [CSV_HEADER] as the first line. The raw event value
+/// is written in hexadecimal format.
+pub fn write_csv(writer: &mut impl Write, definitions: &[EventDefinition]) -> core::fmt::Result {
+    writeln!(writer, "{CSV_HEADER}")?;
+    for definition in definitions {
+        write!(writer, "{:#010x},", definition.raw())?;
+        write_csv_field(writer, definition.name)?;
+        write!(
+            writer,
+            ",{},{},{},{},",
+            definition.event.group_id(),
+            definition.event.unique_id(),
+            severity_name(definition.event.severity()),
+            definition.param_type
+        )?;
+        write_csv_field(writer, definition.description)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Export the definitions as a JSON array of objects. The field names are the same as the
+/// CSV column names.
+pub fn write_json(writer: &mut impl Write, definitions: &[EventDefinition]) -> core::fmt::Result {
+    writer.write_char('[')?;
+    for (idx, definition) in definitions.iter().enumerate() {
+        if idx > 0 {
+            writer.write_char(',')?;
+        }
+        write!(writer, "{{\"raw\":{},\"name\":", definition.raw())?;
+        write_json_str(writer, definition.name)?;
+        write!(
+            writer,
+            ",\"group_id\":{},\"unique_id\":{},\"severity\":\"{}\",\"param_type\":\"{}\",\"description\":",
+            definition.event.group_id(),
+            definition.event.unique_id(),
+            severity_name(definition.event.severity()),
+            definition.param_type
+        )?;
+        write_json_str(writer, definition.description)?;
+        writer.write_char('}')?;
+    }
+    writer.write_char(']')
+}
+
+/// Declare [EventU32TypedSev] constants together with a constant list of their
+/// [EventDefinition]s.
+///
+/// The first line specifies the visibility and the name of the definition list. Each event is
+/// declared with its name, its severity type, the group and unique ID, the variant of the
+/// [EventParamType] and a description. The name of the constant is used as the event name.
+///
+/// The macro checks at compile time that the group and unique ID pairs of the list are unique.
+/// Use [crate::assert_unique_event_ids] to check multiple lists.
+#[macro_export]
+macro_rules! event_definitions {
+    (
+        $list_vis: vis $list_name: ident;
+        $(
+            $(#[$meta: meta])*
+            $vis: vis $name: ident: $severity: ty = ($group_id: expr, $unique_id: expr),
+                $param_type: ident, $description: expr;
+        )+
+    ) => {
+        $(
+            $(#[$meta])*
+            $vis const $name: $crate::events::EventU32TypedSev<$severity> =
+                $crate::events::EventU32TypedSev::new($group_id, $unique_id);
+        )+
+
+        $list_vis const $list_name: &[$crate::event_registry::EventDefinition] = &[
+            $(
+                $crate::event_registry::EventDefinition::new(
+                    $crate::events::EventU32::new(
+                        <$severity as $crate::events::HasSeverity>::SEVERITY,
+                        $group_id,
+                        $unique_id,
+                    ),
+                    stringify!($name),
+                    $description,
+                    $crate::event_registry::EventParamType::$param_type,
+                ),
+            )+
+        ];
+
+        $crate::assert_unique_event_ids!($list_name);
+    };
+}
+
+/// Check at compile time that the group and unique ID pairs of all given [EventDefinition]
+/// lists are unique. This is intended to be used once per binary with the definition lists of
+/// all subsystems, which are usually declared with the [crate::event_definitions] macro.
+#[macro_export]
+macro_rules! assert_unique_event_ids {
+    ($($list: expr),+ $(,)?) => {
+        const _: () = assert!(
+            $crate::event_registry::find_id_collision(&[$($list),+]).is_none(),
+            "event definitions contain duplicate group and unique ID pairs"
+        );
+    };
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use hashbrown::HashMap;
+
+    /// Registry of [EventDefinition]s which allows lookups by raw event value and name.
+    #[derive(Debug, Default, Clone)]
+    pub struct EventDefinitionRegistry {
+        definitions: Vec<EventDefinition>,
+        by_raw: HashMap<u32, usize>,
+        by_name: HashMap<&'static str, usize>,
+    }
+
+    impl EventDefinitionRegistry {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Create a registry from a list of definitions, for example the list generated by the
+        /// [crate::event_definitions] macro.
+        ///
+        /// This function is intended to be called at startup and panics if the list contains
+        /// duplicate events or names. Use [Self::register] to handle duplicates gracefully.
+        pub fn with_definitions(definitions: &[EventDefinition]) -> Self {
+            let mut registry = Self::new();
+            registry.register_all(definitions).unwrap_or_else(|e| {
+                panic!("invalid event definitions: {e}");
+            });
+            registry
+        }
+
+        /// Register a new definition. The definition is rejected if an event with the same raw
+        /// value or the same name was already registered.
+        pub fn register(
+            &mut self,
+            definition: EventDefinition,
+        ) -> Result<(), EventDefinitionError> {
+            if let Some(idx) = self.by_raw.get(&definition.raw()) {
+                return Err(EventDefinitionError::DuplicateEvent {
+                    raw: definition.raw(),
+                    first: self.definitions[*idx].name,
+                    second: definition.name,
+                });
+            }
+            if self.by_name.contains_key(definition.name) {
+                return Err(EventDefinitionError::DuplicateName(definition.name));
+            }
+            let idx = self.definitions.len();
+            self.by_raw.insert(definition.raw(), idx);
+            self.by_name.insert(definition.name, idx);
+            self.definitions.push(definition);
+            Ok(())
+        }
+
+        /// Create a registry from multiple definition lists, for example the per-subsystem lists
+        /// generated by the [crate::event_definitions] macro. This function panics on duplicates
+        /// like [Self::with_definitions].
+        pub fn with_definition_lists(lists: &[&[EventDefinition]]) -> Self {
+            let mut registry = Self::new();
+            for definitions in lists {
+                registry.register_all(definitions).unwrap_or_else(|e| {
+                    panic!("invalid event definitions: {e}");
+                });
+            }
+            registry
+        }
+
+        pub fn register_all(
+            &mut self,
+            definitions: &[EventDefinition],
+        ) -> Result<(), EventDefinitionError> {
+            for definition in definitions {
+                self.register(*definition)?;
+            }
+            Ok(())
+        }
+
+        pub fn len(&self) -> usize {
+            self.definitions.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.definitions.is_empty()
+        }
+
+        /// All definitions in registration order.
+        pub fn definitions(&self) -> &[EventDefinition] {
+            &self.definitions
+        }
+
+        pub fn get(&self, event: impl Into<EventU32>) -> Option<&EventDefinition> {
+            self.get_by_raw(event.into().raw())
+        }
+
+        pub fn get_by_raw(&self, raw: u32) -> Option<&EventDefinition> {
+            self.by_raw.get(&raw).map(|idx| &self.definitions[*idx])
+        }
+
+        pub fn get_by_name(&self, name: &str) -> Option<&EventDefinition> {
+            self.by_name.get(name).map(|idx| &self.definitions[*idx])
+        }
+
+        pub fn name_of(&self, event: impl Into<EventU32>) -> Option<&'static str> {
+            self.get(event).map(|definition| definition.name)
+        }
+
+        /// Check whether the parameters have the type declared for the event. Returns [None] if
+        /// the event is not registered.
+        pub fn params_match(
+            &self,
+            event: impl Into<EventU32>,
+            params: Option<&Params>,
+        ) -> Option<bool> {
+            self.get(event)
+                .map(|definition| definition.param_type.matches(params))
+        }
+
+        pub fn to_csv(&self) -> String {
+            let mut csv = String::new();
+            // Writing into a string can not fail.
+            write_csv(&mut csv, &self.definitions).unwrap();
+            csv
+        }
+
+        pub fn to_json(&self) -> String {
+            let mut json = String::new();
+            write_json(&mut json, &self.definitions).unwrap();
+            json
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+
+    use crate::events::{SeverityHigh, SeverityInfo, SeverityLow, SeverityMedium};
+    use crate::params::U32;
+
+    event_definitions! {
+        TEST_DEFINITIONS;
+        /// Test event with a doc comment.
+        TEST_INFO: SeverityInfo = (1, 0), None, "Informational test event";
+        TEST_LOW: SeverityLow = (1, 1), U32, "Event with a \"quoted\", comma separated description";
+        TEST_HIGH: SeverityHigh = (2, 5), String, "High severity event";
+    }
+
+    event_definitions! {
+        OTHER_TEST_DEFINITIONS;
+        TEST_OTHER: SeverityMedium = (3, 0), None, "Event of another subsystem";
+    }
+
+    crate::assert_unique_event_ids!(TEST_DEFINITIONS, OTHER_TEST_DEFINITIONS);
+
+    #[test]
+    fn test_macro_generated_definitions() {
+        assert_eq!(TEST_DEFINITIONS.len(), 3);
+        assert_eq!(TEST_DEFINITIONS[0].event, EventU32::from(TEST_INFO));
+        assert_eq!(TEST_DEFINITIONS[0].name, "TEST_INFO");
+        assert_eq!(TEST_DEFINITIONS[1].param_type, EventParamType::U32);
+        assert_eq!(TEST_DEFINITIONS[2].event.severity(), Severity::High);
+        assert_eq!(
+            EventDefinition::new_typed(
+                TEST_HIGH,
+                "TEST_HIGH",
+                "High severity event",
+                EventParamType::String
+            ),
+            TEST_DEFINITIONS[2]
+        );
+        assert!(find_duplicate(TEST_DEFINITIONS).is_none());
+    }
+
+    #[test]
+    fn test_find_duplicate() {
+        let mut definitions = [
+            TEST_DEFINITIONS[0],
+            TEST_DEFINITIONS[1],
+            TEST_DEFINITIONS[0],
+        ];
+        definitions[2].name = "OTHER_NAME";
+        assert_eq!(
+            find_duplicate(&definitions),
+            Some(EventDefinitionError::DuplicateEvent {
+                raw: TEST_INFO.raw(),
+                first: "TEST_INFO",
+                second: "OTHER_NAME"
+            })
+        );
+        definitions[2] = TEST_DEFINITIONS[2];
+        definitions[2].name = "TEST_LOW";
+        assert_eq!(
+            find_duplicate(&definitions),
+            Some(EventDefinitionError::DuplicateName("TEST_LOW"))
+        );
+    }
+
+    #[test]
+    fn test_find_id_collision() {
+        const NO_COLLISION: Option<EventIdCollision> =
+            find_id_collision(&[TEST_DEFINITIONS, OTHER_TEST_DEFINITIONS]);
+        assert!(NO_COLLISION.is_none());
+        // Same IDs as TEST_HIGH, but with a different severity.
+        const COLLIDING: &[EventDefinition] = &[EventDefinition::new(
+            EventU32::new(Severity::Low, 2, 5),
+            "COLLIDING",
+            "Colliding event",
+            EventParamType::None,
+        )];
+        const COLLISION: Option<EventIdCollision> =
+            find_id_collision(&[TEST_DEFINITIONS, OTHER_TEST_DEFINITIONS, COLLIDING]);
+        let collision = COLLISION.unwrap();
+        assert_eq!(
+            collision,
+            EventIdCollision {
+                group_id: 2,
+                unique_id: 5,
+                first: "TEST_HIGH",
+                second: "COLLIDING"
+            }
+        );
+        assert_eq!(
+            collision.to_string(),
+            "events TEST_HIGH and COLLIDING have the same group ID 2 and unique ID 5"
+        );
+        // Collision inside a single list.
+        assert!(find_id_collision(&[&[TEST_DEFINITIONS[1], TEST_DEFINITIONS[1]]]).is_some());
+    }
+
+    #[test]
+    fn test_param_type_matching() {
+        let u32_param = Params::Heapless(ParamsHeapless::Raw(ParamsRaw::U32(U32(5))));
+        assert!(EventParamType::U32.matches(Some(&u32_param)));
+        assert!(!EventParamType::U16.matches(Some(&u32_param)));
+        assert!(!EventParamType::None.matches(Some(&u32_param)));
+        assert!(EventParamType::None.matches(None));
+        assert!(EventParamType::String.matches(Some(&Params::String("test".into()))));
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let registry = EventDefinitionRegistry::with_definitions(TEST_DEFINITIONS);
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.name_of(TEST_LOW), Some("TEST_LOW"));
+        assert_eq!(
+            registry.get_by_raw(TEST_HIGH.raw()).unwrap().name,
+            "TEST_HIGH"
+        );
+        assert_eq!(
+            registry.get_by_name("TEST_INFO").unwrap().event,
+            EventU32::from(TEST_INFO)
+        );
+        assert_eq!(registry.params_match(TEST_INFO, None), Some(true));
+        assert!(registry.get(EventU32::new(Severity::Info, 3, 0)).is_none());
+    }
+
+    #[test]
+    fn test_registry_rejects_duplicates() {
+        let mut registry = EventDefinitionRegistry::with_definitions(TEST_DEFINITIONS);
+        let mut duplicate = TEST_DEFINITIONS[1];
+        duplicate.name = "NEW_NAME";
+        assert_eq!(
+            registry.register(duplicate),
+            Err(EventDefinitionError::DuplicateEvent {
+                raw: TEST_LOW.raw(),
+                first: "TEST_LOW",
+                second: "NEW_NAME"
+            })
+        );
+        assert_eq!(registry.len(), 3);
+    }
+
+    #[test]
+    fn test_registry_from_definition_lists() {
+        let registry = EventDefinitionRegistry::with_definition_lists(&[
+            TEST_DEFINITIONS,
+            OTHER_TEST_DEFINITIONS,
+        ]);
+        assert_eq!(registry.len(), 4);
+        assert_eq!(registry.name_of(TEST_OTHER), Some("TEST_OTHER"));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid event definitions")]
+    fn test_registry_panics_on_duplicates() {
+        EventDefinitionRegistry::with_definitions(&[TEST_DEFINITIONS[0], TEST_DEFINITIONS[0]]);
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let registry = EventDefinitionRegistry::with_definitions(TEST_DEFINITIONS);
+        let csv = registry.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap(), CSV_HEADER);
+        assert_eq!(
+            lines.next().unwrap(),
+            "0x00010000,TEST_INFO,1,0,INFO,none,Informational test event"
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "0x40010001,TEST_LOW,1,1,LOW,u32,\"Event with a \"\"quoted\"\", comma separated description\""
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "0xc0020005,TEST_HIGH,2,5,HIGH,string,High severity event"
+        );
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn test_json_export() {
+        let registry = EventDefinitionRegistry::with_definitions(TEST_DEFINITIONS);
+        let json: serde_json::Value = serde_json::from_str(&registry.to_json()).unwrap();
+        let entries = json.as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1]["raw"], TEST_LOW.raw());
+        assert_eq!(entries[1]["name"], "TEST_LOW");
+        assert_eq!(
+            entries[1]["description"],
+            "Event with a \"quoted\", comma separated description"
+        );
+        assert_eq!(entries[2]["severity"], "HIGH");
+        assert_eq!(entries[2]["param_type"], "string");
+        assert_eq!(entries[2]["group_id"], 2);
+    }
+}
@@ -358,6 +358,16 @@ impl EventU32 {
         Self::from(u32::from_be_bytes(bytes))
     }
 
+    /// Const variant of [GenericEvent::group_id] which can be used in const contexts.
+    pub const fn const_group_id(&self) -> u16 {
+        self.base.group_id
+    }
+
+    /// Const variant of [GenericEvent::unique_id] which can be used in const contexts.
+    pub const fn const_unique_id(&self) -> u16 {
+        self.base.unique_id
+    }
+
     const_from_fn!(const_from_info, EventU32TypedSev, SeverityInfo);
     const_from_fn!(const_from_low, EventU32TypedSev, SeverityLow);
     const_from_fn!(const_from_medium, EventU32TypedSev, SeverityMedium);
@@ -1,13 +1,14 @@
 //! Task scheduling module
-use alloc::string::String;
-use bus::BusReader;
-use std::boxed::Box;
-use std::sync::mpsc::TryRecvError;
-use std::thread::JoinHandle;
-use std::time::Duration;
-use std::vec;
-use std::vec::Vec;
-use std::{io, thread};
+//!
+//! The [Executable] trait and the [SuperloopRunner] do not require the standard library, so the
+//! same component code, for example device handlers or controllers, can be driven by the
+//! thread based schedulers of this module on a host as well as by a superloop on an RTOS or
+//! bare-metal system.
+use core::fmt::{Display, Formatter};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum OpResult {
@@ -21,7 +22,7 @@ pub enum ExecutionType {
     OneShot,
 }
 
-pub trait Executable: Send {
+pub trait Executable {
     type Error;
 
     fn exec_type(&self) -> ExecutionType;
@@ -29,126 +30,1429 @@ pub trait Executable: Send {
     fn periodic_op(&mut self, op_code: i32) -> Result<OpResult, Self::Error>;
 }
 
-/// This function allows executing one task which implements the [Executable] trait
+/// Maximum number of tasks of a [SuperloopRunner], which is limited by the number of task
+/// trigger flags of the [RunnerSignals].
+pub const MAX_SUPERLOOP_TASKS: usize = 32;
+
+/// Abstraction for a critical section, which is a code section that can not be interrupted by
+/// other execution contexts accessing the same data. On bare-metal systems, this is usually
+/// implemented by disabling interrupts, while a mutex can be used on a host or an RTOS.
+pub trait CriticalSection {
+    fn with<R>(&self, f: impl FnOnce() -> R) -> R;
+}
+
+/// Monotonic tick source used by the [SuperloopRunner].
 ///
-/// # Arguments
+/// The tick rate is defined by the implementation, for example the milliseconds counted by a
+/// system timer. All task periods passed to the runner are specified in ticks.
+pub trait TickSource {
+    fn ticks(&self) -> u64;
+}
+
+/// Signals which can be used to trigger tasks of a [SuperloopRunner] and to request its stop
+/// from other execution contexts like interrupt handlers.
 ///
-/// * `executable`: Executable task
-/// * `task_freq`: Optional frequency of task. Required for periodic and fixed cycle tasks.
-///    If [None] is passed, no sleeping will be performed.
-/// * `op_code`: Operation code which is passed to the executable task
-///    [operation call][Executable::periodic_op]
-/// * `termination`: Optional termination handler which can cancel threads with a broadcast
-pub fn exec_sched_single<T: Executable<Error = E> + Send + 'static + ?Sized, E: Send + 'static>(
-    mut executable: Box<T>,
-    task_freq: Option<Duration>,
+/// Read-modify-write operations on the signals are performed inside the [CriticalSection], so
+/// only atomic loads and stores are required. These are also available on targets without
+/// compare-and-swap instructions. The signals can be placed inside a static.
+#[derive(Debug)]
+pub struct RunnerSignals<Cs: CriticalSection> {
+    cs: Cs,
+    triggers: AtomicU32,
+    stop: AtomicBool,
+}
+
+impl<Cs: CriticalSection> RunnerSignals<Cs> {
+    pub const fn new(cs: Cs) -> Self {
+        Self {
+            cs,
+            triggers: AtomicU32::new(0),
+            stop: AtomicBool::new(false),
+        }
+    }
+
+    /// Release the task with the given index at the next poll of the runner. Indices equal to
+    /// or larger than [MAX_SUPERLOOP_TASKS] are ignored.
+    pub fn trigger(&self, task_index: usize) {
+        if task_index >= MAX_SUPERLOOP_TASKS {
+            return;
+        }
+        self.cs.with(|| {
+            let triggers = self.triggers.load(Ordering::Relaxed);
+            self.triggers
+                .store(triggers | (1 << task_index), Ordering::Relaxed);
+        });
+    }
+
+    pub fn has_pending_triggers(&self) -> bool {
+        self.triggers.load(Ordering::Relaxed) != 0
+    }
+
+    pub fn request_stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn stop_requested(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    fn take_triggers(&self) -> u32 {
+        self.cs.with(|| {
+            let triggers = self.triggers.load(Ordering::Relaxed);
+            self.triggers.store(0, Ordering::Relaxed);
+            triggers
+        })
+    }
+}
+
+/// Release configuration of a task scheduled by the [SuperloopRunner].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Release {
+    /// Release the task periodically with the given period in ticks. The period must be larger
+    /// than 0. Releases which were missed because the runner was busy are skipped.
+    Periodic(u64),
+    /// Only release the task when it is triggered using [RunnerSignals::trigger].
+    Triggered,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PollStatus {
+    /// Tasks are still scheduled. Contains the tick of the next periodic release if there are
+    /// periodic tasks.
+    Pending {
+        next_release: Option<u64>,
+    },
+    /// All tasks are done.
+    Done,
+    StopRequested,
+}
+
+/// The maximum number of tasks of the runner was reached.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RunnerFullError;
+
+impl Display for RunnerFullError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "superloop runner is full")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RunnerFullError {}
+
+struct SuperloopTask<'a, E> {
+    executable: &'a mut dyn Executable<Error = E>,
+    release: Release,
     op_code: i32,
-    mut termination: Option<BusReader<()>>,
-) -> Result<JoinHandle<Result<OpResult, E>>, io::Error> {
-    let mut cycle_count = 0;
-    thread::Builder::new()
-        .name(String::from(executable.task_name()))
-        .spawn(move || loop {
-            if let Some(ref mut terminator) = termination {
-                match terminator.try_recv() {
-                    Ok(_) | Err(TryRecvError::Disconnected) => {
-                        return Ok(OpResult::Ok);
-                    }
-                    Err(TryRecvError::Empty) => (),
+    next_release: Option<u64>,
+    cycle_count: u32,
+}
+
+/// Cooperative task runner which does not require the standard library or an allocator.
+///
+/// The runner drives up to `N` [Executable] tasks in a superloop. It can be used on bare-metal
+/// systems or inside an RTOS task as well as on a host. The time is retrieved from a
+/// user-supplied [TickSource], and tasks can be triggered from interrupt handlers using the
+/// shared [RunnerSignals], which are protected by a user-supplied [CriticalSection].
+///
+/// The [ExecutionType] of the tasks is respected, so one-shot tasks and tasks with a fixed
+/// number of cycles are removed after their last execution. Tasks which return
+/// [OpResult::TerminationRequested] are removed as well.
+pub struct SuperloopRunner<'a, E, Ticks: TickSource, Cs: CriticalSection, const N: usize> {
+    tick_source: Ticks,
+    signals: &'a RunnerSignals<Cs>,
+    tasks: [Option<SuperloopTask<'a, E>>; N],
+}
+
+impl<'a, E, Ticks: TickSource, Cs: CriticalSection, const N: usize>
+    SuperloopRunner<'a, E, Ticks, Cs, N>
+{
+    /// Create a new runner. `N` must not be larger than [MAX_SUPERLOOP_TASKS].
+    pub fn new(tick_source: Ticks, signals: &'a RunnerSignals<Cs>) -> Self {
+        assert!(
+            N <= MAX_SUPERLOOP_TASKS,
+            "the runner supports at most {MAX_SUPERLOOP_TASKS} tasks"
+        );
+        Self {
+            tick_source,
+            signals,
+            tasks: [(); N].map(|_| None),
+        }
+    }
+
+    /// Add a task and return its index, which can be used to trigger the task using the
+    /// [RunnerSignals]. The index of a removed task is re-used for the next added task.
+    pub fn add_task(
+        &mut self,
+        executable: &'a mut dyn Executable<Error = E>,
+        release: Release,
+        op_code: i32,
+    ) -> Result<usize, RunnerFullError> {
+        if let Release::Periodic(period) = release {
+            assert!(period > 0, "task period must be larger than 0");
+        }
+        let (index, slot) = self
+            .tasks
+            .iter_mut()
+            .enumerate()
+            .find(|(_, slot)| slot.is_none())
+            .ok_or(RunnerFullError)?;
+        *slot = Some(SuperloopTask {
+            executable,
+            release,
+            op_code,
+            next_release: None,
+            cycle_count: 0,
+        });
+        Ok(index)
+    }
+
+    pub fn num_tasks(&self) -> usize {
+        self.tasks.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn tick_source(&self) -> &Ticks {
+        &self.tick_source
+    }
+
+    /// Execute all tasks which are due or were triggered once.
+    ///
+    /// The first task error is returned immediately. The remaining due tasks are executed at
+    /// the next poll.
+    pub fn poll(&mut self) -> Result<PollStatus, E> {
+        if self.signals.stop_requested() {
+            return Ok(PollStatus::StopRequested);
+        }
+        let triggers = self.signals.take_triggers();
+        let now = self.tick_source.ticks();
+        for (index, slot) in self.tasks.iter_mut().enumerate() {
+            let task = match slot {
+                Some(task) => task,
+                None => continue,
+            };
+            let mut released = triggers & (1 << index) != 0;
+            if let Release::Periodic(period) = task.release {
+                let next_release = *task.next_release.get_or_insert(now);
+                if next_release <= now {
+                    let missed_releases = (now - next_release) / period;
+                    task.next_release = Some(next_release + (missed_releases + 1) * period);
+                    released = true;
                 }
             }
-            match executable.exec_type() {
-                ExecutionType::OneShot => {
-                    executable.periodic_op(op_code)?;
-                    return Ok(OpResult::Ok);
-                }
-                ExecutionType::Infinite => {
-                    executable.periodic_op(op_code)?;
-                }
-                ExecutionType::Cycles(cycles) => {
-                    executable.periodic_op(op_code)?;
-                    cycle_count += 1;
-                    if cycle_count == cycles {
-                        return Ok(OpResult::Ok);
-                    }
-                }
+            if !released {
+                continue;
             }
-            if let Some(freq) = task_freq {
-                thread::sleep(freq);
+            let exec_type = task.executable.exec_type();
+            let op_result = task.executable.periodic_op(task.op_code)?;
+            let done = op_result == OpResult::TerminationRequested
+                || match exec_type {
+                    ExecutionType::OneShot => true,
+                    ExecutionType::Infinite => false,
+                    ExecutionType::Cycles(cycles) => {
+                        task.cycle_count += 1;
+                        task.cycle_count >= cycles
+                    }
+                };
+            if done {
+                *slot = None;
             }
+        }
+        if self.tasks.iter().all(|slot| slot.is_none()) {
+            return Ok(PollStatus::Done);
+        }
+        Ok(PollStatus::Pending {
+            next_release: self
+                .tasks
+                .iter()
+                .flatten()
+                .filter_map(|task| task.next_release)
+                .min(),
         })
-}
+    }
 
-/// This function allows executing multiple tasks as long as the tasks implement the
-/// [Executable] trait
-///
-/// # Arguments
-///
-/// * `executable_vec`: Vector of executable objects
-/// * `task_freq`: Optional frequency of task. Required for periodic and fixed cycle tasks
-/// * `op_code`: Operation code which is passed to the executable task [operation call][Executable::periodic_op]
-/// * `termination`: Optional termination handler which can cancel threads with a broadcast
-pub fn exec_sched_multi<T: Executable<Error = E> + Send + 'static + ?Sized, E: Send + 'static>(
-    task_name: &'static str,
-    mut executable_vec: Vec<Box<T>>,
-    task_freq: Option<Duration>,
-    op_code: i32,
-    mut termination: Option<BusReader<()>>,
-) -> Result<JoinHandle<Result<OpResult, E>>, io::Error> {
-    let mut cycle_counts = vec![0; executable_vec.len()];
-    let mut removal_flags = vec![false; executable_vec.len()];
-
-    thread::Builder::new()
-        .name(String::from(task_name))
-        .spawn(move || loop {
-            if let Some(ref mut terminator) = termination {
-                match terminator.try_recv() {
-                    Ok(_) | Err(TryRecvError::Disconnected) => {
-                        removal_flags.iter_mut().for_each(|x| *x = true);
+    /// Run the superloop until all tasks are done or a stop was requested.
+    ///
+    /// The `idle` hook is called with the next periodic release tick after each poll. It can be
+    /// used to put the processor to sleep until that tick or until an interrupt occurs. Returns
+    /// [OpResult::Ok] when all tasks are done and [OpResult::TerminationRequested] when a stop
+    /// was requested.
+    pub fn run(&mut self, mut idle: impl FnMut(Option<u64>)) -> Result<OpResult, E> {
+        loop {
+            match self.poll()? {
+                PollStatus::Pending { next_release } => {
+                    if !self.signals.has_pending_triggers() {
+                        idle(next_release);
                     }
-                    Err(TryRecvError::Empty) => (),
                 }
+                PollStatus::Done => return Ok(OpResult::Ok),
+                PollStatus::StopRequested => return Ok(OpResult::TerminationRequested),
             }
-            for (idx, executable) in executable_vec.iter_mut().enumerate() {
-                match executable.exec_type() {
-                    ExecutionType::OneShot => {
-                        executable.periodic_op(op_code)?;
-                        removal_flags[idx] = true;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use crate::event_man::{EventMessage, EventSendProvider};
+    use crate::events::EventU32;
+    use crate::hk::{HkDatasetSnapshotBuffer, SharedHkDataset};
+    use crate::params::{Params, U32Pair};
+    use crate::ComponentId;
+    use alloc::collections::VecDeque;
+    use alloc::string::String;
+    use bus::BusReader;
+    use core::fmt::{Display, Formatter};
+    use std::boxed::Box;
+    use std::error::Error;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::TryRecvError;
+    use std::sync::{Arc, Mutex};
+    use std::thread::JoinHandle;
+    use std::time::{Duration, Instant};
+    use std::vec;
+    use std::vec::Vec;
+    use std::{io, thread};
+
+    use super::{CriticalSection, Executable, ExecutionType, OpResult, TickSource};
+
+    /// Statistics of a set of timing samples.
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+    pub struct TimingStats {
+        pub num_samples: u32,
+        pub min: Duration,
+        pub max: Duration,
+        pub mean: Duration,
+        /// Population standard deviation of the samples.
+        pub std_dev: Duration,
+    }
+
+    /// Sliding window of timing samples. If the window is full, the oldest sample is discarded
+    /// when a new sample is added.
+    #[derive(Debug, Clone)]
+    pub struct TimingWindow {
+        samples: VecDeque<Duration>,
+        capacity: usize,
+    }
+
+    impl TimingWindow {
+        /// Create a new window which holds up to `capacity` samples. The capacity must be larger
+        /// than 0.
+        pub fn new(capacity: usize) -> Self {
+            assert!(capacity > 0, "timing window capacity must be larger than 0");
+            Self {
+                samples: VecDeque::with_capacity(capacity),
+                capacity,
+            }
+        }
+
+        pub fn add_sample(&mut self, sample: Duration) {
+            if self.samples.len() == self.capacity {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(sample);
+        }
+
+        pub fn num_samples(&self) -> usize {
+            self.samples.len()
+        }
+
+        pub fn clear(&mut self) {
+            self.samples.clear();
+        }
+
+        /// Calculate the statistics of all samples in the window. Returns [None] if the window is
+        /// empty.
+        pub fn stats(&self) -> Option<TimingStats> {
+            if self.samples.is_empty() {
+                return None;
+            }
+            let num_samples = self.samples.len() as f64;
+            let mut min = Duration::MAX;
+            let mut max = Duration::ZERO;
+            let mut sum = 0.0;
+            for sample in &self.samples {
+                min = min.min(*sample);
+                max = max.max(*sample);
+                sum += sample.as_secs_f64();
+            }
+            let mean = sum / num_samples;
+            let variance = self
+                .samples
+                .iter()
+                .map(|sample| {
+                    let diff = sample.as_secs_f64() - mean;
+                    diff * diff
+                })
+                .sum::<f64>()
+                / num_samples;
+            Some(TimingStats {
+                num_samples: self.samples.len() as u32,
+                min,
+                max,
+                mean: Duration::from_secs_f64(mean),
+                std_dev: Duration::from_secs_f64(variance.sqrt()),
+            })
+        }
+    }
+
+    /// Timing report of one task which is published by a [TaskTimingMonitor].
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+    pub struct TaskTimingReport {
+        pub task_name: &'static str,
+        /// Statistics of the time required for the [Executable::periodic_op] call.
+        pub execution_time: Option<TimingStats>,
+        /// Statistics of the release jitter, which is the delay between the planned release
+        /// time of a task and the actual start of its [Executable::periodic_op] call. For tasks
+        /// scheduled with [exec_sched_multi_with_timing], this includes the execution time of the
+        /// tasks scheduled before it in the same cycle.
+        pub release_jitter: Option<TimingStats>,
+    }
+
+    /// Handle which can be used to read the [TaskTimingReport] of a task and to reset its timing
+    /// statistics, for example from a telecommand handler.
+    #[derive(Clone)]
+    pub struct TaskTimingHandle {
+        report: SharedHkDataset<TaskTimingReport>,
+        reset_request: Arc<AtomicBool>,
+    }
+
+    impl TaskTimingHandle {
+        /// Snapshot of the current timing report.
+        pub fn report(&self) -> TaskTimingReport {
+            self.report.snapshot().data
+        }
+
+        /// Shared dataset containing the timing report. This can be used to expose the report in
+        /// a data pool.
+        pub fn dataset(&self) -> &SharedHkDataset<TaskTimingReport> {
+            &self.report
+        }
+
+        /// Request a reset of the timing statistics. The reset is performed by the scheduler
+        /// before the next sample is recorded.
+        pub fn request_reset(&self) {
+            self.reset_request.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Measures the execution time and release jitter of a task over a sliding window.
+    ///
+    /// The monitor is passed to [exec_sched_single_with_timing] or [exec_sched_multi_with_timing]
+    /// and updates the shared [TaskTimingReport] after each task execution. The report can be read
+    /// and the statistics can be reset using the [TaskTimingHandle] returned by [Self::new].
+    pub struct TaskTimingMonitor {
+        task_name: &'static str,
+        execution_time: TimingWindow,
+        release_jitter: TimingWindow,
+        handle: TaskTimingHandle,
+    }
+
+    impl TaskTimingMonitor {
+        /// Create a new monitor and its associated handle.
+        ///
+        /// ## Parameter
+        ///
+        /// * `task_name` - Name of the monitored task, which is included in the report.
+        /// * `window_size` - Number of samples used to calculate the statistics.
+        pub fn new(task_name: &'static str, window_size: usize) -> (Self, TaskTimingHandle) {
+            let handle = TaskTimingHandle {
+                report: HkDatasetSnapshotBuffer::new_shared(TaskTimingReport {
+                    task_name,
+                    ..Default::default()
+                }),
+                reset_request: Arc::new(AtomicBool::new(false)),
+            };
+            (
+                Self {
+                    task_name,
+                    execution_time: TimingWindow::new(window_size),
+                    release_jitter: TimingWindow::new(window_size),
+                    handle: handle.clone(),
+                },
+                handle,
+            )
+        }
+
+        pub fn handle(&self) -> &TaskTimingHandle {
+            &self.handle
+        }
+
+        /// Record the timing of one task execution and update the shared report.
+        pub fn record(&mut self, execution_time: Duration, release_jitter: Option<Duration>) {
+            if self.handle.reset_request.swap(false, Ordering::Relaxed) {
+                self.execution_time.clear();
+                self.release_jitter.clear();
+            }
+            self.execution_time.add_sample(execution_time);
+            if let Some(jitter) = release_jitter {
+                self.release_jitter.add_sample(jitter);
+            }
+            self.handle.report.write(TaskTimingReport {
+                task_name: self.task_name,
+                execution_time: self.execution_time.stats(),
+                release_jitter: self.release_jitter.stats(),
+            });
+        }
+    }
+
+    fn timed_periodic_op<T: Executable<Error = E> + ?Sized, E>(
+        executable: &mut T,
+        op_code: i32,
+        monitor: Option<&mut TaskTimingMonitor>,
+        planned_release: Option<Instant>,
+    ) -> Result<OpResult, E> {
+        let start = Instant::now();
+        let result = executable.periodic_op(op_code);
+        if let Some(monitor) = monitor {
+            monitor.record(
+                start.elapsed(),
+                planned_release.map(|release| start.saturating_duration_since(release)),
+            );
+        }
+        result
+    }
+
+    /// This function allows executing one task which implements the [Executable] trait
+    ///
+    /// # Arguments
+    ///
+    /// * `executable`: Executable task
+    /// * `task_freq`: Optional frequency of task. Required for periodic and fixed cycle tasks.
+    ///    If [None] is passed, no sleeping will be performed.
+    /// * `op_code`: Operation code which is passed to the executable task
+    ///    [operation call][Executable::periodic_op]
+    /// * `termination`: Optional termination handler which can cancel threads with a broadcast
+    pub fn exec_sched_single<
+        T: Executable<Error = E> + Send + 'static + ?Sized,
+        E: Send + 'static,
+    >(
+        executable: Box<T>,
+        task_freq: Option<Duration>,
+        op_code: i32,
+        termination: Option<BusReader<()>>,
+    ) -> Result<JoinHandle<Result<OpResult, E>>, io::Error> {
+        exec_sched_single_generic(executable, task_freq, op_code, termination, None)
+    }
+
+    /// Variant of [exec_sched_single] which additionally measures the execution time and release
+    /// jitter of the task using the passed [TaskTimingMonitor].
+    ///
+    /// The release jitter is only measured if a task frequency is specified.
+    pub fn exec_sched_single_with_timing<
+        T: Executable<Error = E> + Send + 'static + ?Sized,
+        E: Send + 'static,
+    >(
+        executable: Box<T>,
+        task_freq: Option<Duration>,
+        op_code: i32,
+        termination: Option<BusReader<()>>,
+        timing_monitor: TaskTimingMonitor,
+    ) -> Result<JoinHandle<Result<OpResult, E>>, io::Error> {
+        exec_sched_single_generic(
+            executable,
+            task_freq,
+            op_code,
+            termination,
+            Some(timing_monitor),
+        )
+    }
+
+    fn exec_sched_single_generic<
+        T: Executable<Error = E> + Send + 'static + ?Sized,
+        E: Send + 'static,
+    >(
+        mut executable: Box<T>,
+        task_freq: Option<Duration>,
+        op_code: i32,
+        mut termination: Option<BusReader<()>>,
+        mut timing_monitor: Option<TaskTimingMonitor>,
+    ) -> Result<JoinHandle<Result<OpResult, E>>, io::Error> {
+        let mut cycle_count = 0;
+        let mut planned_release = None;
+        thread::Builder::new()
+            .name(String::from(executable.task_name()))
+            .spawn(move || loop {
+                if let Some(ref mut terminator) = termination {
+                    match terminator.try_recv() {
+                        Ok(_) | Err(TryRecvError::Disconnected) => {
+                            return Ok(OpResult::Ok);
+                        }
+                        Err(TryRecvError::Empty) => (),
                     }
-                    ExecutionType::Infinite => {
-                        executable.periodic_op(op_code)?;
+                }
+                let exec_type = executable.exec_type();
+                timed_periodic_op(
+                    executable.as_mut(),
+                    op_code,
+                    timing_monitor.as_mut(),
+                    planned_release,
+                )?;
+                match exec_type {
+                    ExecutionType::OneShot => {
+                        return Ok(OpResult::Ok);
                     }
+                    ExecutionType::Infinite => (),
                     ExecutionType::Cycles(cycles) => {
-                        executable.periodic_op(op_code)?;
-                        cycle_counts[idx] += 1;
-                        if cycle_counts[idx] == cycles {
+                        cycle_count += 1;
+                        if cycle_count == cycles {
+                            return Ok(OpResult::Ok);
+                        }
+                    }
+                }
+                if let Some(freq) = task_freq {
+                    planned_release = Some(Instant::now() + freq);
+                    thread::sleep(freq);
+                }
+            })
+    }
+
+    /// This function allows executing multiple tasks as long as the tasks implement the
+    /// [Executable] trait
+    ///
+    /// # Arguments
+    ///
+    /// * `executable_vec`: Vector of executable objects
+    /// * `task_freq`: Optional frequency of task. Required for periodic and fixed cycle tasks
+    /// * `op_code`: Operation code which is passed to the executable task [operation call][Executable::periodic_op]
+    /// * `termination`: Optional termination handler which can cancel threads with a broadcast
+    pub fn exec_sched_multi<
+        T: Executable<Error = E> + Send + 'static + ?Sized,
+        E: Send + 'static,
+    >(
+        task_name: &'static str,
+        executable_vec: Vec<Box<T>>,
+        task_freq: Option<Duration>,
+        op_code: i32,
+        termination: Option<BusReader<()>>,
+    ) -> Result<JoinHandle<Result<OpResult, E>>, io::Error> {
+        let timing_monitors = executable_vec.iter().map(|_| None).collect();
+        exec_sched_multi_generic(
+            task_name,
+            executable_vec,
+            task_freq,
+            op_code,
+            termination,
+            timing_monitors,
+        )
+    }
+
+    /// Variant of [exec_sched_multi] which additionally measures the execution time and release
+    /// jitter of each task.
+    ///
+    /// `timing_monitors` contains one optional [TaskTimingMonitor] for each task in
+    /// `executable_vec`, with the same ordering. The function panics if the length of the two
+    /// vectors is not the same.
+    pub fn exec_sched_multi_with_timing<
+        T: Executable<Error = E> + Send + 'static + ?Sized,
+        E: Send + 'static,
+    >(
+        task_name: &'static str,
+        executable_vec: Vec<Box<T>>,
+        task_freq: Option<Duration>,
+        op_code: i32,
+        termination: Option<BusReader<()>>,
+        timing_monitors: Vec<Option<TaskTimingMonitor>>,
+    ) -> Result<JoinHandle<Result<OpResult, E>>, io::Error> {
+        assert_eq!(
+            executable_vec.len(),
+            timing_monitors.len(),
+            "one optional timing monitor is required for each task"
+        );
+        exec_sched_multi_generic(
+            task_name,
+            executable_vec,
+            task_freq,
+            op_code,
+            termination,
+            timing_monitors,
+        )
+    }
+
+    fn exec_sched_multi_generic<
+        T: Executable<Error = E> + Send + 'static + ?Sized,
+        E: Send + 'static,
+    >(
+        task_name: &'static str,
+        mut executable_vec: Vec<Box<T>>,
+        task_freq: Option<Duration>,
+        op_code: i32,
+        mut termination: Option<BusReader<()>>,
+        mut timing_monitors: Vec<Option<TaskTimingMonitor>>,
+    ) -> Result<JoinHandle<Result<OpResult, E>>, io::Error> {
+        let mut cycle_counts = vec![0; executable_vec.len()];
+        let mut removal_flags = vec![false; executable_vec.len()];
+        let mut planned_release = None;
+
+        thread::Builder::new()
+            .name(String::from(task_name))
+            .spawn(move || loop {
+                if let Some(ref mut terminator) = termination {
+                    match terminator.try_recv() {
+                        Ok(_) | Err(TryRecvError::Disconnected) => {
+                            removal_flags.iter_mut().for_each(|x| *x = true);
+                        }
+                        Err(TryRecvError::Empty) => (),
+                    }
+                }
+                for (idx, executable) in executable_vec.iter_mut().enumerate() {
+                    let exec_type = executable.exec_type();
+                    timed_periodic_op(
+                        executable.as_mut(),
+                        op_code,
+                        timing_monitors[idx].as_mut(),
+                        planned_release,
+                    )?;
+                    match exec_type {
+                        ExecutionType::OneShot => {
                             removal_flags[idx] = true;
                         }
+                        ExecutionType::Infinite => (),
+                        ExecutionType::Cycles(cycles) => {
+                            cycle_counts[idx] += 1;
+                            if cycle_counts[idx] == cycles {
+                                removal_flags[idx] = true;
+                            }
+                        }
                     }
                 }
+                let mut removal_iter = removal_flags.iter();
+                executable_vec.retain(|_| !*removal_iter.next().unwrap());
+                removal_iter = removal_flags.iter();
+                cycle_counts.retain(|_| !*removal_iter.next().unwrap());
+                removal_iter = removal_flags.iter();
+                timing_monitors.retain(|_| !*removal_iter.next().unwrap());
+                removal_flags.retain(|&i| !i);
+                if executable_vec.is_empty() {
+                    return Ok(OpResult::Ok);
+                }
+                let freq = task_freq.unwrap_or_else(|| panic!("No task frequency specified"));
+                planned_release = Some(Instant::now() + freq);
+                thread::sleep(freq);
+            })
+    }
+
+    /// Information about a deadline overrun of a task scheduled by the [FixedRateScheduler] or
+    /// the [PollingSequenceExecutor].
+    ///
+    /// The deadline of a task release is the planned release time of the next cycle. For slots of a
+    /// [PollingSequenceTable], the period is the length of the slot.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct DeadlineOverrun {
+        pub task_name: &'static str,
+        pub period: Duration,
+        /// Time by which the completion of the task exceeded its deadline.
+        pub overrun: Duration,
+        /// Total number of deadline overruns of the task, including this one.
+        pub num_overruns: u32,
+    }
+
+    /// Generic handler for the deadline overruns detected by the [FixedRateScheduler] and the
+    /// [PollingSequenceExecutor].
+    ///
+    /// This trait is implemented for all closures taking a [DeadlineOverrun].
+    pub trait OverrunHandler: Send {
+        fn handle_overrun(&mut self, overrun: &DeadlineOverrun);
+    }
+
+    impl<F: FnMut(&DeadlineOverrun) + Send> OverrunHandler for F {
+        fn handle_overrun(&mut self, overrun: &DeadlineOverrun) {
+            self(overrun)
+        }
+    }
+
+    /// [OverrunHandler] which reports each deadline overrun as an event.
+    ///
+    /// The event is sent with a [U32Pair] parameter containing the task period and the overrun
+    /// in milliseconds.
+    pub struct OverrunEventReporter<Sender: EventSendProvider<EventU32> + Send> {
+        sender_id: ComponentId,
+        event: EventU32,
+        sender: Sender,
+    }
+
+    impl<Sender: EventSendProvider<EventU32> + Send> OverrunEventReporter<Sender> {
+        pub fn new(sender_id: ComponentId, event: EventU32, sender: Sender) -> Self {
+            Self {
+                sender_id,
+                event,
+                sender,
+            }
+        }
+    }
+
+    impl<Sender: EventSendProvider<EventU32> + Send> OverrunHandler for OverrunEventReporter<Sender> {
+        fn handle_overrun(&mut self, overrun: &DeadlineOverrun) {
+            let params = Params::from(crate::params::ParamsRaw::from(U32Pair(
+                u32::try_from(overrun.period.as_millis()).unwrap_or(u32::MAX),
+                u32::try_from(overrun.overrun.as_millis()).unwrap_or(u32::MAX),
+            )));
+            // Overruns are not critical enough to stop the scheduler if the event can not be sent.
+            let _ = self.sender.send(EventMessage::new_with_params(
+                self.sender_id,
+                self.event,
+                &params,
+            ));
+        }
+    }
+
+    /// Shared signal which can be used to request the shutdown of a [FixedRateScheduler] or
+    /// other long-running components from another thread. See the [crate::shutdown] module for
+    /// more information.
+    #[derive(Debug, Default, Clone)]
+    pub struct ShutdownSignal(Arc<AtomicBool>);
+
+    impl ShutdownSignal {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Create a signal from an existing shared flag.
+        pub fn from_flag(flag: Arc<AtomicBool>) -> Self {
+            Self(flag)
+        }
+
+        /// Shared flag of the signal. This can be passed to components which expect an
+        /// [`Arc<AtomicBool>`] as the stop signal, for example the TCP TMTC servers.
+        pub fn flag(&self) -> Arc<AtomicBool> {
+            self.0.clone()
+        }
+
+        pub fn request_shutdown(&self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+
+        pub fn shutdown_requested(&self) -> bool {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    /// [TickSource] which counts the milliseconds elapsed since its creation using the
+    /// monotonic system clock. This allows running the [SuperloopRunner](super::SuperloopRunner)
+    /// on a host.
+    #[derive(Debug, Copy, Clone)]
+    pub struct StdTickSource {
+        start: Instant,
+    }
+
+    impl Default for StdTickSource {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl StdTickSource {
+        pub fn new() -> Self {
+            Self {
+                start: Instant::now(),
             }
-            let mut removal_iter = removal_flags.iter();
-            executable_vec.retain(|_| !*removal_iter.next().unwrap());
-            removal_iter = removal_flags.iter();
-            cycle_counts.retain(|_| !*removal_iter.next().unwrap());
-            removal_flags.retain(|&i| !i);
-            if executable_vec.is_empty() {
-                return Ok(OpResult::Ok);
+        }
+
+        /// Sleep until the given tick was reached. This can be used as the idle hook of the
+        /// [SuperloopRunner](super::SuperloopRunner).
+        pub fn sleep_until(&self, tick: u64) {
+            let now = self.ticks();
+            if tick > now {
+                thread::sleep(Duration::from_millis(tick - now));
             }
-            let freq = task_freq.unwrap_or_else(|| panic!("No task frequency specified"));
-            thread::sleep(freq);
-        })
+        }
+    }
+
+    impl TickSource for StdTickSource {
+        fn ticks(&self) -> u64 {
+            self.start.elapsed().as_millis() as u64
+        }
+    }
+
+    /// [CriticalSection] based on a mutex, which can be used to share the
+    /// [RunnerSignals](super::RunnerSignals) between threads on a host.
+    #[derive(Debug, Default)]
+    pub struct MutexCriticalSection(Mutex<()>);
+
+    impl MutexCriticalSection {
+        pub const fn new() -> Self {
+            Self(Mutex::new(()))
+        }
+    }
+
+    impl CriticalSection for MutexCriticalSection {
+        fn with<R>(&self, f: impl FnOnce() -> R) -> R {
+            let _guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+            f()
+        }
+    }
+
+    struct FixedRateTask<E> {
+        executable: Box<dyn Executable<Error = E> + Send>,
+        period: Duration,
+        op_code: i32,
+        timing_monitor: Option<TaskTimingMonitor>,
+        next_release: Option<Instant>,
+        cycle_count: u32,
+        num_overruns: u32,
+    }
+
+    /// Fixed-rate scheduler which drives multiple [Executable] tasks with individual periods in one
+    /// cooperative loop.
+    ///
+    /// In contrast to [exec_sched_multi], the release times are absolute, so the execution time of
+    /// the tasks does not cause the schedule to drift. Each task is released at the start of its
+    /// period, and its deadline is the start of the next period. If a task completes after its
+    /// deadline, the overrun is passed to the optional [OverrunHandler], and all releases which
+    /// were missed are skipped.
+    ///
+    /// The scheduler can run in the current thread using [Self::run] or in its own thread using
+    /// [Self::spawn]. It returns when all tasks are done or when a shutdown was requested with the
+    /// [ShutdownSignal].
+    pub struct FixedRateScheduler<E> {
+        name: &'static str,
+        tasks: Vec<FixedRateTask<E>>,
+        overrun_handler: Option<Box<dyn OverrunHandler>>,
+        shutdown: ShutdownSignal,
+    }
+
+    impl<E> FixedRateScheduler<E> {
+        pub fn new(name: &'static str, shutdown: ShutdownSignal) -> Self {
+            Self {
+                name,
+                tasks: Vec::new(),
+                overrun_handler: None,
+                shutdown,
+            }
+        }
+
+        /// Add a task which is released with the given period. The period must be larger than 0.
+        pub fn add_task(
+            &mut self,
+            executable: Box<dyn Executable<Error = E> + Send>,
+            period: Duration,
+            op_code: i32,
+        ) {
+            self.add_task_generic(executable, period, op_code, None);
+        }
+
+        /// Variant of [Self::add_task] which additionally measures the execution time and release
+        /// jitter of the task using the passed [TaskTimingMonitor].
+        pub fn add_task_with_timing(
+            &mut self,
+            executable: Box<dyn Executable<Error = E> + Send>,
+            period: Duration,
+            op_code: i32,
+            timing_monitor: TaskTimingMonitor,
+        ) {
+            self.add_task_generic(executable, period, op_code, Some(timing_monitor));
+        }
+
+        fn add_task_generic(
+            &mut self,
+            executable: Box<dyn Executable<Error = E> + Send>,
+            period: Duration,
+            op_code: i32,
+            timing_monitor: Option<TaskTimingMonitor>,
+        ) {
+            assert!(!period.is_zero(), "task period must be larger than 0");
+            self.tasks.push(FixedRateTask {
+                executable,
+                period,
+                op_code,
+                timing_monitor,
+                next_release: None,
+                cycle_count: 0,
+                num_overruns: 0,
+            });
+        }
+
+        pub fn set_overrun_handler(&mut self, handler: impl OverrunHandler + 'static) {
+            self.overrun_handler = Some(Box::new(handler));
+        }
+
+        pub fn num_tasks(&self) -> usize {
+            self.tasks.len()
+        }
+
+        pub fn shutdown_signal(&self) -> &ShutdownSignal {
+            &self.shutdown
+        }
+
+        /// Run the scheduler in the current thread.
+        ///
+        /// Returns [OpResult::Ok] when all tasks are done and [OpResult::TerminationRequested]
+        /// when a shutdown was requested. Tasks which return [OpResult::TerminationRequested] are
+        /// removed from the schedule. The first task error is returned immediately.
+        pub fn run(&mut self) -> Result<OpResult, E> {
+            let start = Instant::now();
+            for task in &mut self.tasks {
+                task.next_release.get_or_insert(start);
+            }
+            loop {
+                if self.shutdown.shutdown_requested() {
+                    return Ok(OpResult::TerminationRequested);
+                }
+                if self.tasks.is_empty() {
+                    return Ok(OpResult::Ok);
+                }
+                let mut idx = 0;
+                while idx < self.tasks.len() {
+                    let now = Instant::now();
+                    let task = &mut self.tasks[idx];
+                    let planned_release = *task.next_release.get_or_insert(now);
+                    if planned_release > now {
+                        idx += 1;
+                        continue;
+                    }
+                    let exec_type = task.executable.exec_type();
+                    let op_result = timed_periodic_op(
+                        task.executable.as_mut(),
+                        task.op_code,
+                        task.timing_monitor.as_mut(),
+                        Some(planned_release),
+                    )?;
+                    let finished = Instant::now();
+                    let deadline = planned_release + task.period;
+                    let mut next_release = deadline;
+                    if finished > deadline {
+                        task.num_overruns = task.num_overruns.saturating_add(1);
+                        if let Some(handler) = self.overrun_handler.as_mut() {
+                            handler.handle_overrun(&DeadlineOverrun {
+                                task_name: task.executable.task_name(),
+                                period: task.period,
+                                overrun: finished - deadline,
+                                num_overruns: task.num_overruns,
+                            });
+                        }
+                        let missed_releases =
+                            ((finished - deadline).as_nanos() / task.period.as_nanos()) as u32 + 1;
+                        next_release += task.period * missed_releases;
+                    }
+                    task.next_release = Some(next_release);
+                    let done = op_result == OpResult::TerminationRequested
+                        || match exec_type {
+                            ExecutionType::OneShot => true,
+                            ExecutionType::Infinite => false,
+                            ExecutionType::Cycles(cycles) => {
+                                task.cycle_count += 1;
+                                task.cycle_count >= cycles
+                            }
+                        };
+                    if done {
+                        self.tasks.remove(idx);
+                    } else {
+                        idx += 1;
+                    }
+                }
+                if let Some(next_release) = self.tasks.iter().filter_map(|t| t.next_release).min() {
+                    let now = Instant::now();
+                    if next_release > now {
+                        thread::sleep(next_release - now);
+                    }
+                }
+            }
+        }
+    }
+
+    impl<E: Send + 'static> FixedRateScheduler<E> {
+        /// Run the scheduler in a new thread which is named after the scheduler.
+        pub fn spawn(mut self) -> Result<JoinHandle<Result<OpResult, E>>, io::Error>
+        where
+            Self: Send + 'static,
+        {
+            thread::Builder::new()
+                .name(String::from(self.name))
+                .spawn(move || self.run())
+        }
+    }
+
+    /// Identifier of a [PollingSequenceTable].
+    pub type PstTableId = u32;
+
+    /// Slot of a [PollingSequenceTable].
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct PstSlot {
+        /// Release time of the slot relative to the start of the major period.
+        pub offset: Duration,
+        /// Index of the task as returned by [PollingSequenceExecutor::add_task].
+        pub task_index: usize,
+        /// Operation code which is passed to [Executable::periodic_op]. For device handlers, this
+        /// can be used to select the communication step which is performed in the slot.
+        pub op_code: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum PstError {
+        /// The slot offset is not smaller than the major period of the table.
+        SlotOutsidePeriod {
+            offset: Duration,
+            period: Duration,
+        },
+        /// The slot references a task which was not added to the executor.
+        InvalidTaskIndex(usize),
+        DuplicateTable(PstTableId),
+        UnknownTable(PstTableId),
+    }
+
+    impl Display for PstError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                PstError::SlotOutsidePeriod { offset, period } => write!(
+                    f,
+                    "slot offset {offset:?} is outside of the major period {period:?}"
+                ),
+                PstError::InvalidTaskIndex(index) => write!(f, "invalid task index {index}"),
+                PstError::DuplicateTable(id) => write!(f, "duplicate table ID {id}"),
+                PstError::UnknownTable(id) => write!(f, "unknown table ID {id}"),
+            }
+        }
+    }
+
+    impl Error for PstError {}
+
+    /// Polling sequence table (PST) containing an ordered list of slots which are executed
+    /// cyclically within a major period.
+    ///
+    /// The slots are ordered by their offset. Slots with the same offset are executed in the order
+    /// they were added.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct PollingSequenceTable {
+        id: PstTableId,
+        period: Duration,
+        slots: Vec<PstSlot>,
+    }
+
+    impl PollingSequenceTable {
+        /// Create a new empty table. The major period must be larger than 0.
+        pub fn new(id: PstTableId, period: Duration) -> Self {
+            assert!(!period.is_zero(), "major period must be larger than 0");
+            Self {
+                id,
+                period,
+                slots: Vec::new(),
+            }
+        }
+
+        /// Add a slot which releases the task with the given index at `offset` relative to the
+        /// start of the major period.
+        pub fn add_slot(
+            &mut self,
+            offset: Duration,
+            task_index: usize,
+            op_code: i32,
+        ) -> Result<(), PstError> {
+            if offset >= self.period {
+                return Err(PstError::SlotOutsidePeriod {
+                    offset,
+                    period: self.period,
+                });
+            }
+            let pos = self.slots.partition_point(|slot| slot.offset <= offset);
+            self.slots.insert(
+                pos,
+                PstSlot {
+                    offset,
+                    task_index,
+                    op_code,
+                },
+            );
+            Ok(())
+        }
+
+        pub fn id(&self) -> PstTableId {
+            self.id
+        }
+
+        pub fn period(&self) -> Duration {
+            self.period
+        }
+
+        pub fn slots(&self) -> &[PstSlot] {
+            &self.slots
+        }
+
+        /// Deadline of the slot with the given index relative to the start of the major period,
+        /// which is the offset of the next slot with a larger offset or the end of the period.
+        fn slot_deadline(&self, slot_index: usize) -> Duration {
+            let offset = self.slots[slot_index].offset;
+            self.slots[slot_index + 1..]
+                .iter()
+                .find(|slot| slot.offset > offset)
+                .map_or(self.period, |slot| slot.offset)
+        }
+    }
+
+    /// Status report of a [PollingSequenceExecutor] which is published as a HK dataset.
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+    pub struct PstReport {
+        pub active_table: Option<PstTableId>,
+        /// Number of completed major periods.
+        pub num_cycles: u64,
+        pub num_slot_overruns: u32,
+        /// Number of table switch requests which were rejected because the table is unknown.
+        pub num_rejected_switches: u32,
+    }
+
+    /// Handle which can be used to read the [PstReport] of a [PollingSequenceExecutor] and to
+    /// switch the active table from other threads, for example from a mode or telecommand handler.
+    #[derive(Clone)]
+    pub struct PstHandle {
+        report: SharedHkDataset<PstReport>,
+        switch_request: Arc<Mutex<Option<PstTableId>>>,
+    }
+
+    impl PstHandle {
+        /// Snapshot of the current report.
+        pub fn report(&self) -> PstReport {
+            self.report.snapshot().data
+        }
+
+        /// Shared dataset containing the report. This can be used to expose the report in a
+        /// data pool.
+        pub fn dataset(&self) -> &SharedHkDataset<PstReport> {
+            &self.report
+        }
+
+        /// Request a switch to the table with the given ID. The switch is performed by the
+        /// executor at the start of the next major period. Only the last request is kept.
+        pub fn request_table_switch(&self, table_id: PstTableId) {
+            *self
+                .switch_request
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(table_id);
+        }
+    }
+
+    /// Cooperative executor for [PollingSequenceTable]s.
+    ///
+    /// The executor owns a set of [Executable] tasks and a set of tables referencing these tasks.
+    /// The slots of the active table are released at their absolute offset within the major
+    /// period, which makes it suitable to drive the communication steps of device handlers
+    /// deterministically. The [ExecutionType] of the tasks is ignored because the tasks are only
+    /// released by the table slots.
+    ///
+    /// The deadline of a slot is the offset of the next slot, or the end of the major period for
+    /// the last slot. Slot overruns are counted in the [PstReport] and passed to the optional
+    /// [OverrunHandler], using the slot length as the [DeadlineOverrun::period]. If a whole major
+    /// period overruns, the next period starts immediately.
+    ///
+    /// The active table can be switched at runtime using [PstHandle::request_table_switch]. The
+    /// executor returns when a shutdown was requested with the [ShutdownSignal] or when a task
+    /// returns [OpResult::TerminationRequested].
+    pub struct PollingSequenceExecutor<E> {
+        name: &'static str,
+        tasks: Vec<Box<dyn Executable<Error = E> + Send>>,
+        num_overruns: Vec<u32>,
+        tables: Vec<PollingSequenceTable>,
+        active_table: usize,
+        overrun_handler: Option<Box<dyn OverrunHandler>>,
+        shutdown: ShutdownSignal,
+        report: PstReport,
+        handle: PstHandle,
+    }
+
+    impl<E> PollingSequenceExecutor<E> {
+        /// Create a new executor without any tasks and tables and its associated handle.
+        pub fn new(name: &'static str, shutdown: ShutdownSignal) -> (Self, PstHandle) {
+            let handle = PstHandle {
+                report: HkDatasetSnapshotBuffer::new_shared(PstReport::default()),
+                switch_request: Arc::default(),
+            };
+            (
+                Self {
+                    name,
+                    tasks: Vec::new(),
+                    num_overruns: Vec::new(),
+                    tables: Vec::new(),
+                    active_table: 0,
+                    overrun_handler: None,
+                    shutdown,
+                    report: PstReport::default(),
+                    handle: handle.clone(),
+                },
+                handle,
+            )
+        }
+
+        /// Add a task and return its index, which is used to reference the task in the table slots.
+        pub fn add_task(&mut self, executable: Box<dyn Executable<Error = E> + Send>) -> usize {
+            self.tasks.push(executable);
+            self.num_overruns.push(0);
+            self.tasks.len() - 1
+        }
+
+        /// Add a table. The first added table becomes the active table.
+        pub fn add_table(&mut self, table: PollingSequenceTable) -> Result<(), PstError> {
+            if self.tables.iter().any(|t| t.id == table.id) {
+                return Err(PstError::DuplicateTable(table.id));
+            }
+            if let Some(slot) = table
+                .slots
+                .iter()
+                .find(|slot| slot.task_index >= self.tasks.len())
+            {
+                return Err(PstError::InvalidTaskIndex(slot.task_index));
+            }
+            self.tables.push(table);
+            if self.tables.len() == 1 {
+                self.set_active_table(0);
+            }
+            Ok(())
+        }
+
+        /// Switch the active table immediately. This is intended to be used before the executor is
+        /// started. [PstHandle::request_table_switch] should be used at runtime.
+        pub fn switch_table(&mut self, table_id: PstTableId) -> Result<(), PstError> {
+            let index = self
+                .tables
+                .iter()
+                .position(|t| t.id == table_id)
+                .ok_or(PstError::UnknownTable(table_id))?;
+            self.set_active_table(index);
+            Ok(())
+        }
+
+        pub fn active_table(&self) -> Option<&PollingSequenceTable> {
+            self.tables.get(self.active_table)
+        }
+
+        pub fn set_overrun_handler(&mut self, handler: impl OverrunHandler + 'static) {
+            self.overrun_handler = Some(Box::new(handler));
+        }
+
+        pub fn num_tasks(&self) -> usize {
+            self.tasks.len()
+        }
+
+        pub fn num_tables(&self) -> usize {
+            self.tables.len()
+        }
+
+        pub fn handle(&self) -> &PstHandle {
+            &self.handle
+        }
+
+        pub fn shutdown_signal(&self) -> &ShutdownSignal {
+            &self.shutdown
+        }
+
+        fn set_active_table(&mut self, index: usize) {
+            self.active_table = index;
+            self.report.active_table = Some(self.tables[index].id);
+            self.handle.report.write(self.report);
+        }
+
+        fn handle_switch_request(&mut self) {
+            let request = self
+                .handle
+                .switch_request
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .take();
+            if let Some(table_id) = request {
+                if self.switch_table(table_id).is_err() {
+                    self.report.num_rejected_switches =
+                        self.report.num_rejected_switches.saturating_add(1);
+                    self.handle.report.write(self.report);
+                }
+            }
+        }
+
+        /// Run the executor in the current thread.
+        ///
+        /// Returns [OpResult::Ok] immediately if no table was added and
+        /// [OpResult::TerminationRequested] when a shutdown was requested or a task requested
+        /// the termination. The first task error is returned immediately.
+        pub fn run(&mut self) -> Result<OpResult, E> {
+            let mut cycle_start = Instant::now();
+            loop {
+                if self.shutdown.shutdown_requested() {
+                    return Ok(OpResult::TerminationRequested);
+                }
+                self.handle_switch_request();
+                if self.tables.is_empty() {
+                    return Ok(OpResult::Ok);
+                }
+                if self.execute_cycle(cycle_start)? == OpResult::TerminationRequested {
+                    return Ok(OpResult::TerminationRequested);
+                }
+                self.report.num_cycles += 1;
+                self.handle.report.write(self.report);
+                cycle_start += self.tables[self.active_table].period;
+                let now = Instant::now();
+                if now > cycle_start {
+                    cycle_start = now;
+                }
+            }
+        }
+
+        fn execute_cycle(&mut self, cycle_start: Instant) -> Result<OpResult, E> {
+            let table = &self.tables[self.active_table];
+            for (slot_index, slot) in table.slots.iter().enumerate() {
+                if self.shutdown.shutdown_requested() {
+                    return Ok(OpResult::TerminationRequested);
+                }
+                let release = cycle_start + slot.offset;
+                let now = Instant::now();
+                if release > now {
+                    thread::sleep(release - now);
+                }
+                let task = &mut self.tasks[slot.task_index];
+                let op_result = task.periodic_op(slot.op_code)?;
+                let finished = Instant::now();
+                let slot_deadline = table.slot_deadline(slot_index);
+                let deadline = cycle_start + slot_deadline;
+                if finished > deadline {
+                    let num_overruns = &mut self.num_overruns[slot.task_index];
+                    *num_overruns = num_overruns.saturating_add(1);
+                    self.report.num_slot_overruns = self.report.num_slot_overruns.saturating_add(1);
+                    self.handle.report.write(self.report);
+                    if let Some(handler) = self.overrun_handler.as_mut() {
+                        handler.handle_overrun(&DeadlineOverrun {
+                            task_name: task.task_name(),
+                            period: slot_deadline - slot.offset,
+                            overrun: finished - deadline,
+                            num_overruns: *num_overruns,
+                        });
+                    }
+                }
+                if op_result == OpResult::TerminationRequested {
+                    return Ok(OpResult::TerminationRequested);
+                }
+            }
+            Ok(OpResult::Ok)
+        }
+    }
+
+    impl<E: Send + 'static> PollingSequenceExecutor<E> {
+        /// Run the executor in a new thread which is named after the executor.
+        pub fn spawn(mut self) -> Result<JoinHandle<Result<OpResult, E>>, io::Error>
+        where
+            Self: Send + 'static,
+        {
+            thread::Builder::new()
+                .name(String::from(self.name))
+                .spawn(move || self.run())
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{exec_sched_multi, exec_sched_single, Executable, ExecutionType, OpResult};
+    use super::{
+        exec_sched_multi, exec_sched_multi_with_timing, exec_sched_single,
+        exec_sched_single_with_timing, DeadlineOverrun, Executable, ExecutionType,
+        FixedRateScheduler, MutexCriticalSection, OpResult, OverrunEventReporter, PollStatus,
+        PollingSequenceExecutor, PollingSequenceTable, PstError, PstHandle, Release,
+        RunnerFullError, RunnerSignals, ShutdownSignal, StdTickSource, SuperloopRunner,
+        TaskTimingMonitor, TimingWindow,
+    };
+    use crate::event_man::{EventMessage, EventSenderMpsc};
+    use crate::events::{EventU32, Severity};
+    use crate::params::{Params, ParamsHeapless, ParamsRaw, U32Pair};
     use bus::Bus;
     use std::boxed::Box;
     use std::error::Error;
     use std::string::{String, ToString};
+    use std::sync::mpsc;
     use std::sync::{Arc, Mutex};
     use std::time::Duration;
+    use std::time::Instant;
     use std::vec::Vec;
     use std::{fmt, thread, vec};
 
@@ -423,7 +1727,7 @@ mod tests {
         });
         assert_eq!(cycled_task_0.task_name(), CYCLE_TASK_NAME);
         assert_eq!(one_shot_task.task_name(), ONE_SHOT_TASK_NAME);
-        let task_vec: Vec<Box<dyn Executable<Error = ExampleError>>> =
+        let task_vec: Vec<Box<dyn Executable<Error = ExampleError> + Send>> =
             vec![one_shot_task, cycled_task_0, cycled_task_1];
         let jh = exec_sched_multi(
             "multi-task-name",
@@ -493,7 +1797,7 @@ mod tests {
         });
         assert_eq!(periodic_task_0.task_name(), PERIODIC_TASK_NAME);
         assert_eq!(periodic_task_1.task_name(), PERIODIC_TASK_NAME);
-        let task_vec: Vec<Box<dyn Executable<Error = ExampleError>>> =
+        let task_vec: Vec<Box<dyn Executable<Error = ExampleError> + Send>> =
             vec![cycled_task, periodic_task_0, periodic_task_1];
         let jh = exec_sched_multi(
             "multi-task-name",
@@ -513,4 +1817,562 @@ mod tests {
         assert!(range.contains(&data.exec_num));
         assert_eq!(data.op_code, expected_op_code);
     }
+
+    fn assert_almost_eq(actual: Duration, expected: Duration) {
+        let diff = if actual > expected {
+            actual - expected
+        } else {
+            expected - actual
+        };
+        assert!(
+            diff < Duration::from_micros(1),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_timing_window_stats() {
+        let mut window = TimingWindow::new(4);
+        assert!(window.stats().is_none());
+        for millis in [2, 4, 4, 4, 5, 5, 7, 9] {
+            window.add_sample(Duration::from_millis(millis));
+        }
+        assert_eq!(window.num_samples(), 4);
+        // Only the last four samples are considered.
+        let stats = window.stats().unwrap();
+        assert_eq!(stats.num_samples, 4);
+        assert_eq!(stats.min, Duration::from_millis(5));
+        assert_eq!(stats.max, Duration::from_millis(9));
+        assert_almost_eq(stats.mean, Duration::from_micros(6500));
+        // Population standard deviation of 5, 5, 7 and 9 ms is sqrt(2.75) ms.
+        assert_almost_eq(stats.std_dev, Duration::from_micros(1658));
+        window.clear();
+        assert!(window.stats().is_none());
+    }
+
+    #[test]
+    fn test_cycles_single_with_timing() {
+        let shared = Arc::new(Mutex::new(TestInfo {
+            exec_num: 0,
+            op_code: 0,
+        }));
+        let cycled_task = Box::new(FixedCyclesTask {
+            exec_num: shared.clone(),
+            cycles: 3,
+        });
+        let (monitor, timing_handle) = TaskTimingMonitor::new(CYCLE_TASK_NAME, 10);
+        let jh = exec_sched_single_with_timing(
+            cycled_task,
+            Some(Duration::from_millis(5)),
+            0,
+            None,
+            monitor,
+        )
+        .expect("thread creation failed");
+        let thread_res = jh.join().expect("Cycles Task failed");
+        assert!(thread_res.is_ok());
+        let report = timing_handle.report();
+        assert_eq!(report.task_name, CYCLE_TASK_NAME);
+        let exec_stats = report.execution_time.expect("no execution time stats");
+        assert_eq!(exec_stats.num_samples, 3);
+        assert!(exec_stats.min <= exec_stats.mean);
+        assert!(exec_stats.mean <= exec_stats.max);
+        // No planned release for the first cycle.
+        let jitter_stats = report.release_jitter.expect("no release jitter stats");
+        assert_eq!(jitter_stats.num_samples, 2);
+        assert_eq!(timing_handle.dataset().generation(), 3);
+    }
+
+    #[test]
+    fn test_timing_reset() {
+        let (mut monitor, timing_handle) = TaskTimingMonitor::new(PERIODIC_TASK_NAME, 10);
+        monitor.record(Duration::from_millis(10), None);
+        monitor.record(Duration::from_millis(20), Some(Duration::from_millis(1)));
+        let report = timing_handle.report();
+        assert_eq!(report.execution_time.unwrap().num_samples, 2);
+        assert_eq!(report.release_jitter.unwrap().num_samples, 1);
+        timing_handle.request_reset();
+        monitor.record(Duration::from_millis(30), None);
+        let report = timing_handle.report();
+        let exec_stats = report.execution_time.unwrap();
+        assert_eq!(exec_stats.num_samples, 1);
+        assert_almost_eq(exec_stats.mean, Duration::from_millis(30));
+        assert!(report.release_jitter.is_none());
+    }
+
+    #[test]
+    fn test_multi_with_timing() {
+        let shared = Arc::new(Mutex::new(TestInfo {
+            exec_num: 0,
+            op_code: 0,
+        }));
+        let one_shot_task = Box::new(OneShotTask {
+            exec_num: shared.clone(),
+        });
+        let cycled_task = Box::new(FixedCyclesTask {
+            exec_num: shared.clone(),
+            cycles: 2,
+        });
+        let (monitor, timing_handle) = TaskTimingMonitor::new(CYCLE_TASK_NAME, 10);
+        let task_vec: Vec<Box<dyn Executable<Error = ExampleError> + Send>> =
+            vec![one_shot_task, cycled_task];
+        let jh = exec_sched_multi_with_timing(
+            "multi-task-name",
+            task_vec,
+            Some(Duration::from_millis(5)),
+            0,
+            None,
+            vec![None, Some(monitor)],
+        )
+        .expect("thread creation failed");
+        let thread_res = jh.join().expect("Cycles Task failed");
+        assert!(thread_res.is_ok());
+        assert_eq!(shared.lock().unwrap().exec_num, 3);
+        let report = timing_handle.report();
+        assert_eq!(report.execution_time.unwrap().num_samples, 2);
+        assert_eq!(report.release_jitter.unwrap().num_samples, 1);
+    }
+
+    const SLOW_TASK_NAME: &str = "Slow Task";
+
+    struct SlowTask {
+        cycles: u32,
+        exec_time: Duration,
+    }
+
+    impl Executable for SlowTask {
+        type Error = ExampleError;
+
+        fn exec_type(&self) -> ExecutionType {
+            ExecutionType::Cycles(self.cycles)
+        }
+
+        fn task_name(&self) -> &'static str {
+            SLOW_TASK_NAME
+        }
+
+        fn periodic_op(&mut self, _op_code: i32) -> Result<OpResult, ExampleError> {
+            thread::sleep(self.exec_time);
+            Ok(OpResult::Ok)
+        }
+    }
+
+    #[test]
+    fn test_fixed_rate_scheduler() {
+        let shared = Arc::new(Mutex::new(TestInfo {
+            exec_num: 0,
+            op_code: 0,
+        }));
+        let mut scheduler: FixedRateScheduler<ExampleError> =
+            FixedRateScheduler::new("fixed-rate", ShutdownSignal::new());
+        scheduler.add_task(
+            Box::new(FixedCyclesTask {
+                cycles: 3,
+                exec_num: shared.clone(),
+            }),
+            Duration::from_millis(10),
+            5,
+        );
+        scheduler.add_task(
+            Box::new(OneShotTask {
+                exec_num: shared.clone(),
+            }),
+            Duration::from_millis(100),
+            5,
+        );
+        assert_eq!(scheduler.num_tasks(), 2);
+        let start = Instant::now();
+        assert_eq!(scheduler.run().unwrap(), OpResult::Ok);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(scheduler.num_tasks(), 0);
+        let info = shared.lock().unwrap();
+        assert_eq!(info.exec_num, 4);
+        assert_eq!(info.op_code, 5);
+    }
+
+    #[test]
+    fn test_fixed_rate_scheduler_task_error() {
+        let shared = Arc::new(Mutex::new(TestInfo {
+            exec_num: 0,
+            op_code: 0,
+        }));
+        let mut scheduler: FixedRateScheduler<ExampleError> =
+            FixedRateScheduler::new("fixed-rate", ShutdownSignal::new());
+        scheduler.add_task(
+            Box::new(PeriodicTask {
+                exec_num: shared.clone(),
+            }),
+            Duration::from_millis(10),
+            -1,
+        );
+        let error = scheduler.run().unwrap_err();
+        assert_eq!(error.to_string(), "Example Task Failure with code -1");
+    }
+
+    #[test]
+    fn test_fixed_rate_scheduler_overrun() {
+        let overruns: Arc<Mutex<Vec<DeadlineOverrun>>> = Arc::default();
+        let overruns_clone = overruns.clone();
+        let mut scheduler: FixedRateScheduler<ExampleError> =
+            FixedRateScheduler::new("fixed-rate", ShutdownSignal::new());
+        scheduler.add_task(
+            Box::new(SlowTask {
+                cycles: 2,
+                exec_time: Duration::from_millis(20),
+            }),
+            Duration::from_millis(5),
+            0,
+        );
+        scheduler.set_overrun_handler(move |overrun: &DeadlineOverrun| {
+            overruns_clone.lock().unwrap().push(*overrun);
+        });
+        assert_eq!(scheduler.run().unwrap(), OpResult::Ok);
+        let overruns = overruns.lock().unwrap();
+        assert_eq!(overruns.len(), 2);
+        assert_eq!(overruns[0].task_name, SLOW_TASK_NAME);
+        assert_eq!(overruns[0].period, Duration::from_millis(5));
+        assert!(overruns[0].overrun >= Duration::from_millis(15));
+        assert_eq!(overruns[1].num_overruns, 2);
+    }
+
+    #[test]
+    fn test_overrun_event_reporter() {
+        const OVERRUN_EVENT: EventU32 = EventU32::new(Severity::Low, 1, 1);
+        let (event_tx, event_rx) = mpsc::channel::<EventMessage<EventU32>>();
+        let mut scheduler: FixedRateScheduler<ExampleError> =
+            FixedRateScheduler::new("fixed-rate", ShutdownSignal::new());
+        scheduler.add_task(
+            Box::new(SlowTask {
+                cycles: 1,
+                exec_time: Duration::from_millis(20),
+            }),
+            Duration::from_millis(5),
+            0,
+        );
+        scheduler.set_overrun_handler(OverrunEventReporter::new(
+            1,
+            OVERRUN_EVENT,
+            EventSenderMpsc::new(2, event_tx),
+        ));
+        scheduler.run().unwrap();
+        let event = event_rx.try_recv().expect("no overrun event received");
+        assert_eq!(event.sender_id(), 1);
+        assert_eq!(event.event(), OVERRUN_EVENT);
+        if let Some(Params::Heapless(ParamsHeapless::Raw(ParamsRaw::U32Pair(U32Pair(
+            period,
+            overrun,
+        ))))) = event.params()
+        {
+            assert_eq!(*period, 5);
+            assert!(*overrun >= 15);
+        } else {
+            panic!("unexpected event parameters {:?}", event.params());
+        }
+    }
+
+    #[test]
+    fn test_fixed_rate_scheduler_shutdown() {
+        let shared = Arc::new(Mutex::new(TestInfo {
+            exec_num: 0,
+            op_code: 0,
+        }));
+        let shutdown = ShutdownSignal::new();
+        let mut scheduler: FixedRateScheduler<ExampleError> =
+            FixedRateScheduler::new("fixed-rate", shutdown.clone());
+        scheduler.add_task(
+            Box::new(PeriodicTask {
+                exec_num: shared.clone(),
+            }),
+            Duration::from_millis(5),
+            0,
+        );
+        let jh = scheduler.spawn().expect("thread creation failed");
+        thread::sleep(Duration::from_millis(20));
+        shutdown.request_shutdown();
+        let result = jh.join().expect("scheduler thread panicked");
+        assert_eq!(result.unwrap(), OpResult::TerminationRequested);
+        assert!(shared.lock().unwrap().exec_num >= 1);
+    }
+
+    const RECORDING_TASK_NAME: &str = "Recording Task";
+
+    /// Records the passed operation codes and requests the termination after a fixed number of
+    /// executions.
+    struct RecordingTask {
+        op_codes: Arc<Mutex<Vec<i32>>>,
+        max_executions: usize,
+        switch_request: Option<(i32, PstHandle, u32)>,
+    }
+
+    impl Executable for RecordingTask {
+        type Error = ExampleError;
+
+        fn exec_type(&self) -> ExecutionType {
+            ExecutionType::Infinite
+        }
+
+        fn task_name(&self) -> &'static str {
+            RECORDING_TASK_NAME
+        }
+
+        fn periodic_op(&mut self, op_code: i32) -> Result<OpResult, ExampleError> {
+            let mut op_codes = self.op_codes.lock().unwrap();
+            op_codes.push(op_code);
+            if let Some((trigger_op_code, handle, table_id)) = &self.switch_request {
+                if *trigger_op_code == op_code {
+                    handle.request_table_switch(*table_id);
+                }
+            }
+            if op_codes.len() >= self.max_executions {
+                return Ok(OpResult::TerminationRequested);
+            }
+            Ok(OpResult::Ok)
+        }
+    }
+
+    #[test]
+    fn test_pst_slot_ordering() {
+        let mut table = PollingSequenceTable::new(1, Duration::from_millis(100));
+        table.add_slot(Duration::from_millis(50), 0, 2).unwrap();
+        table.add_slot(Duration::from_millis(0), 0, 1).unwrap();
+        table.add_slot(Duration::from_millis(50), 0, 3).unwrap();
+        let op_codes: Vec<i32> = table.slots().iter().map(|slot| slot.op_code).collect();
+        assert_eq!(op_codes, vec![1, 2, 3]);
+        assert_eq!(table.slot_deadline(0), Duration::from_millis(50));
+        assert_eq!(table.slot_deadline(1), Duration::from_millis(100));
+        assert_eq!(
+            table.add_slot(Duration::from_millis(100), 0, 4),
+            Err(PstError::SlotOutsidePeriod {
+                offset: Duration::from_millis(100),
+                period: Duration::from_millis(100)
+            })
+        );
+    }
+
+    #[test]
+    fn test_pst_table_validation() {
+        let (mut executor, _handle) =
+            PollingSequenceExecutor::<ExampleError>::new("pst", ShutdownSignal::new());
+        let mut table = PollingSequenceTable::new(1, Duration::from_millis(10));
+        table.add_slot(Duration::ZERO, 0, 0).unwrap();
+        assert_eq!(
+            executor.add_table(table.clone()),
+            Err(PstError::InvalidTaskIndex(0))
+        );
+        executor.add_task(Box::new(PeriodicTask {
+            exec_num: Arc::new(Mutex::new(TestInfo {
+                exec_num: 0,
+                op_code: 0,
+            })),
+        }));
+        executor.add_table(table.clone()).unwrap();
+        assert_eq!(executor.add_table(table), Err(PstError::DuplicateTable(1)));
+        assert_eq!(executor.switch_table(2), Err(PstError::UnknownTable(2)));
+        assert_eq!(executor.active_table().unwrap().id(), 1);
+        assert_eq!(executor.num_tables(), 1);
+    }
+
+    #[test]
+    fn test_pst_execution() {
+        let op_codes: Arc<Mutex<Vec<i32>>> = Arc::default();
+        let (mut executor, handle) =
+            PollingSequenceExecutor::<ExampleError>::new("pst", ShutdownSignal::new());
+        let task_index = executor.add_task(Box::new(RecordingTask {
+            op_codes: op_codes.clone(),
+            max_executions: 6,
+            switch_request: None,
+        }));
+        let mut table = PollingSequenceTable::new(1, Duration::from_millis(10));
+        table
+            .add_slot(Duration::from_millis(5), task_index, 3)
+            .unwrap();
+        table.add_slot(Duration::ZERO, task_index, 1).unwrap();
+        table
+            .add_slot(Duration::from_millis(2), task_index, 2)
+            .unwrap();
+        executor.add_table(table).unwrap();
+        let start = Instant::now();
+        assert_eq!(executor.run().unwrap(), OpResult::TerminationRequested);
+        assert!(start.elapsed() >= Duration::from_millis(15));
+        assert_eq!(*op_codes.lock().unwrap(), vec![1, 2, 3, 1, 2, 3]);
+        let report = handle.report();
+        assert_eq!(report.active_table, Some(1));
+        assert_eq!(report.num_cycles, 1);
+        assert_eq!(report.num_slot_overruns, 0);
+    }
+
+    #[test]
+    fn test_pst_table_switch() {
+        let op_codes: Arc<Mutex<Vec<i32>>> = Arc::default();
+        let (mut executor, handle) =
+            PollingSequenceExecutor::<ExampleError>::new("pst", ShutdownSignal::new());
+        let task_index = executor.add_task(Box::new(RecordingTask {
+            op_codes: op_codes.clone(),
+            max_executions: 4,
+            switch_request: Some((1, handle.clone(), 2)),
+        }));
+        let mut table_1 = PollingSequenceTable::new(1, Duration::from_millis(5));
+        table_1.add_slot(Duration::ZERO, task_index, 1).unwrap();
+        let mut table_2 = PollingSequenceTable::new(2, Duration::from_millis(5));
+        table_2.add_slot(Duration::ZERO, task_index, 2).unwrap();
+        executor.add_table(table_1).unwrap();
+        executor.add_table(table_2).unwrap();
+        handle.request_table_switch(3);
+        assert_eq!(executor.run().unwrap(), OpResult::TerminationRequested);
+        assert_eq!(*op_codes.lock().unwrap(), vec![1, 2, 2, 2]);
+        let report = handle.report();
+        assert_eq!(report.active_table, Some(2));
+        assert_eq!(report.num_rejected_switches, 1);
+    }
+
+    #[test]
+    fn test_pst_slot_overrun() {
+        let overruns: Arc<Mutex<Vec<DeadlineOverrun>>> = Arc::default();
+        let overruns_clone = overruns.clone();
+        let (mut executor, handle) =
+            PollingSequenceExecutor::<ExampleError>::new("pst", ShutdownSignal::new());
+        let slow_task = executor.add_task(Box::new(SlowTask {
+            cycles: 1,
+            exec_time: Duration::from_millis(20),
+        }));
+        let recording_task = executor.add_task(Box::new(RecordingTask {
+            op_codes: Arc::default(),
+            max_executions: 1,
+            switch_request: None,
+        }));
+        let mut table = PollingSequenceTable::new(1, Duration::from_millis(50));
+        table.add_slot(Duration::ZERO, slow_task, 0).unwrap();
+        table
+            .add_slot(Duration::from_millis(5), recording_task, 0)
+            .unwrap();
+        executor.add_table(table).unwrap();
+        executor.set_overrun_handler(move |overrun: &DeadlineOverrun| {
+            overruns_clone.lock().unwrap().push(*overrun);
+        });
+        assert_eq!(executor.run().unwrap(), OpResult::TerminationRequested);
+        let overruns = overruns.lock().unwrap();
+        assert_eq!(overruns.len(), 1);
+        assert_eq!(overruns[0].task_name, SLOW_TASK_NAME);
+        assert_eq!(overruns[0].period, Duration::from_millis(5));
+        assert!(overruns[0].overrun >= Duration::from_millis(15));
+        assert_eq!(handle.report().num_slot_overruns, 1);
+    }
+
+    #[test]
+    fn test_pst_shutdown() {
+        let shared = Arc::new(Mutex::new(TestInfo {
+            exec_num: 0,
+            op_code: 0,
+        }));
+        let shutdown = ShutdownSignal::new();
+        let (mut executor, handle) =
+            PollingSequenceExecutor::<ExampleError>::new("pst", shutdown.clone());
+        let task_index = executor.add_task(Box::new(PeriodicTask {
+            exec_num: shared.clone(),
+        }));
+        let mut table = PollingSequenceTable::new(1, Duration::from_millis(5));
+        table.add_slot(Duration::ZERO, task_index, 0).unwrap();
+        executor.add_table(table).unwrap();
+        let jh = executor.spawn().expect("thread creation failed");
+        thread::sleep(Duration::from_millis(20));
+        shutdown.request_shutdown();
+        let result = jh.join().expect("executor thread panicked");
+        assert_eq!(result.unwrap(), OpResult::TerminationRequested);
+        assert!(shared.lock().unwrap().exec_num >= 1);
+        assert!(handle.report().num_cycles >= 1);
+    }
+
+    #[test]
+    fn test_superloop_runner() {
+        let shared = Arc::new(Mutex::new(TestInfo {
+            exec_num: 0,
+            op_code: 0,
+        }));
+        let mut one_shot_task = OneShotTask {
+            exec_num: shared.clone(),
+        };
+        let mut cycles_task = FixedCyclesTask {
+            cycles: 3,
+            exec_num: shared.clone(),
+        };
+        let signals = RunnerSignals::new(MutexCriticalSection::new());
+        let mut runner: SuperloopRunner<ExampleError, StdTickSource, MutexCriticalSection, 4> =
+            SuperloopRunner::new(StdTickSource::new(), &signals);
+        runner
+            .add_task(&mut one_shot_task, Release::Periodic(100), 2)
+            .unwrap();
+        runner
+            .add_task(&mut cycles_task, Release::Periodic(5), 2)
+            .unwrap();
+        assert_eq!(runner.num_tasks(), 2);
+        let tick_source = StdTickSource::new();
+        let start = Instant::now();
+        let result = runner.run(|next_release| {
+            tick_source.sleep_until(next_release.expect("no periodic release"))
+        });
+        assert_eq!(result.unwrap(), OpResult::Ok);
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert_eq!(runner.num_tasks(), 0);
+        let info = shared.lock().unwrap();
+        assert_eq!(info.exec_num, 4);
+        assert_eq!(info.op_code, 2);
+    }
+
+    #[test]
+    fn test_superloop_runner_triggered_task() {
+        let shared = Arc::new(Mutex::new(TestInfo {
+            exec_num: 0,
+            op_code: 0,
+        }));
+        let mut periodic_task = PeriodicTask {
+            exec_num: shared.clone(),
+        };
+        let signals = RunnerSignals::new(MutexCriticalSection::new());
+        let mut runner: SuperloopRunner<ExampleError, StdTickSource, MutexCriticalSection, 1> =
+            SuperloopRunner::new(StdTickSource::new(), &signals);
+        let task_index = runner
+            .add_task(&mut periodic_task, Release::Triggered, 1)
+            .unwrap();
+        assert_eq!(
+            runner.poll().unwrap(),
+            PollStatus::Pending { next_release: None }
+        );
+        assert_eq!(shared.lock().unwrap().exec_num, 0);
+        signals.trigger(task_index);
+        assert!(signals.has_pending_triggers());
+        runner.poll().unwrap();
+        assert!(!signals.has_pending_triggers());
+        assert_eq!(shared.lock().unwrap().exec_num, 1);
+        runner.poll().unwrap();
+        assert_eq!(shared.lock().unwrap().exec_num, 1);
+        signals.request_stop();
+        assert_eq!(runner.poll().unwrap(), PollStatus::StopRequested);
+        assert_eq!(runner.run(|_| ()).unwrap(), OpResult::TerminationRequested);
+    }
+
+    #[test]
+    fn test_superloop_runner_full_and_error() {
+        let shared = Arc::new(Mutex::new(TestInfo {
+            exec_num: 0,
+            op_code: 0,
+        }));
+        let mut task_0 = PeriodicTask {
+            exec_num: shared.clone(),
+        };
+        let mut task_1 = PeriodicTask {
+            exec_num: shared.clone(),
+        };
+        let signals = RunnerSignals::new(MutexCriticalSection::new());
+        let mut runner: SuperloopRunner<ExampleError, StdTickSource, MutexCriticalSection, 1> =
+            SuperloopRunner::new(StdTickSource::new(), &signals);
+        runner
+            .add_task(&mut task_0, Release::Periodic(10), -1)
+            .unwrap();
+        assert_eq!(
+            runner.add_task(&mut task_1, Release::Periodic(10), 0),
+            Err(RunnerFullError)
+        );
+        let error = runner.poll().unwrap_err();
+        assert_eq!(error.to_string(), "Example Task Failure with code -1");
+    }
 }
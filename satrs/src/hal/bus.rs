@@ -0,0 +1,610 @@
+//! # Bus abstraction for device communication
+//!
+//! Device handlers usually communicate with their devices through byte-oriented buses. The traits
+//! of this module decouple device handlers from the bus implementation, so the same handler can
+//! run on Linux, for example using the device nodes of the kernel drivers, and on embedded
+//! targets.
+//!
+//!  - [SpiBus] for transactional full-duplex SPI communication with a single device. The chip
+//!    select handling is part of the implementation.
+//!  - [I2cBus] for transactional I2C communication with addressed devices.
+//!  - [UartBus] for streaming communication without transaction boundaries.
+//!
+//! Most register based devices are accessed by writing a command and reading back a reply. The
+//! [DeviceBus] trait abstracts this pattern for a single device, which allows writing device
+//! handlers which work with both SPI and I2C variants of a device. The [SpiDevice] and
+//! [I2cDevice] adapters implement it on top of the bus traits.
+//!
+//! The following implementations are provided:
+//!
+//!  - The [BusMock] which records all transfers and returns queued replies. It implements all
+//!    bus traits and is intended for tests of device handlers. It requires the `alloc` feature.
+//!  - The [IoUart] which implements the [UartBus] for any [std::io::Read] and [std::io::Write]
+//!    implementation, for example a serial port opened with the
+//!    [SerialPortConfig][crate::hal::std::serial_cobs_server::SerialPortConfig]. It requires the
+//!    `std` feature.
+//!  - Adapters for the [embedded-hal](https://docs.rs/embedded-hal) SPI and I2C traits and the
+//!    [embedded-io](https://docs.rs/embedded-io) traits. They require the `embedded-hal` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use satrs::hal::bus::{BusMock, DeviceBus, I2cDevice};
+//!
+//! let mut bus = BusMock::new();
+//! bus.queue_reply(&[0x01, 0x17]);
+//! let mut device = I2cDevice::new(bus, 0x48);
+//! let mut reply = [0; 2];
+//! // Read the device ID register.
+//! device.write_read(&[0x0F], &mut reply).unwrap();
+//! assert_eq!(reply, [0x01, 0x17]);
+//! assert_eq!(device.bus.transfers()[0].address, Some(0x48));
+//! ```
+use core::fmt::{Debug, Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+#[cfg(feature = "embedded-hal")]
+pub use embedded_hal_mod::*;
+
+/// 7-bit I2C address.
+pub type I2cAddress = u8;
+
+/// Transactional SPI communication with a single device.
+pub trait SpiBus {
+    type Error: Debug;
+
+    /// Full-duplex transfer. The bytes of `tx` are written while bytes are read into `rx`. The
+    /// transfer length is the maximum of both lengths. Missing bytes of `tx` are sent as zeros and
+    /// surplus read bytes are discarded.
+    fn transfer(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Write the bytes and discard the read bytes.
+    fn write(&mut self, tx: &[u8]) -> Result<(), Self::Error>;
+
+    /// Write the bytes of `tx` and read into `rx` afterwards inside one transaction, so the chip
+    /// select stays asserted in between.
+    fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+/// Transactional I2C communication with addressed devices.
+pub trait I2cBus {
+    type Error: Debug;
+
+    fn write(&mut self, address: I2cAddress, tx: &[u8]) -> Result<(), Self::Error>;
+
+    fn read(&mut self, address: I2cAddress, rx: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Write the bytes of `tx` and read into `rx` afterwards with a repeated start condition in
+    /// between.
+    fn write_read(
+        &mut self,
+        address: I2cAddress,
+        tx: &[u8],
+        rx: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Streaming UART communication.
+pub trait UartBus {
+    type Error: Debug;
+
+    /// Write all bytes.
+    fn write(&mut self, tx: &[u8]) -> Result<(), Self::Error>;
+
+    /// Wait until all written bytes were sent.
+    fn flush(&mut self) -> Result<(), Self::Error>;
+
+    /// Read the received bytes into the buffer without blocking. Returns the number of read
+    /// bytes, which is 0 if no bytes were received.
+    fn read_available(&mut self, rx: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+/// Command and reply based communication with a single device.
+pub trait DeviceBus {
+    type Error: Debug;
+
+    /// Write the bytes of `tx` to the device and read `rx.len()` reply bytes afterwards. Either
+    /// buffer may be empty.
+    fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+impl<Bus: DeviceBus + ?Sized> DeviceBus for &mut Bus {
+    type Error = Bus::Error;
+
+    fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).write_read(tx, rx)
+    }
+}
+
+/// [DeviceBus] for a device which is connected to an [SpiBus].
+#[derive(Debug)]
+pub struct SpiDevice<Bus: SpiBus> {
+    pub bus: Bus,
+}
+
+impl<Bus: SpiBus> SpiDevice<Bus> {
+    pub fn new(bus: Bus) -> Self {
+        Self { bus }
+    }
+}
+
+impl<Bus: SpiBus> DeviceBus for SpiDevice<Bus> {
+    type Error = Bus::Error;
+
+    fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
+        if rx.is_empty() {
+            return self.bus.write(tx);
+        }
+        self.bus.write_read(tx, rx)
+    }
+}
+
+/// [DeviceBus] for the device with the given address on an [I2cBus].
+#[derive(Debug)]
+pub struct I2cDevice<Bus: I2cBus> {
+    pub bus: Bus,
+    pub address: I2cAddress,
+}
+
+impl<Bus: I2cBus> I2cDevice<Bus> {
+    pub fn new(bus: Bus, address: I2cAddress) -> Self {
+        Self { bus, address }
+    }
+}
+
+impl<Bus: I2cBus> DeviceBus for I2cDevice<Bus> {
+    type Error = Bus::Error;
+
+    fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
+        if rx.is_empty() {
+            return self.bus.write(self.address, tx);
+        }
+        if tx.is_empty() {
+            return self.bus.read(self.address, rx);
+        }
+        self.bus.write_read(self.address, tx, rx)
+    }
+}
+
+/// Error of the [BusMock], which is returned for all operations after a failure was injected
+/// with [BusMock::set_failing].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BusMockError;
+
+impl Display for BusMockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "injected bus failure")
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for BusMockError {}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::collections::VecDeque;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    /// Transfer which was performed with a [BusMock].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct MockTransfer {
+        /// I2C address of the transfer, [None] for SPI and UART transfers.
+        pub address: Option<I2cAddress>,
+        pub written: Vec<u8>,
+        /// Number of read bytes.
+        pub read_len: usize,
+    }
+
+    /// Bus mock which implements all bus traits. It is intended for tests of device handlers.
+    ///
+    /// All transfers are recorded and can be retrieved with [Self::transfers]. Each transfer
+    /// which reads data consumes one reply queued with [Self::queue_reply]. The read buffer is
+    /// filled with the reply and padded with zeros, and surplus reply bytes are discarded. UART
+    /// reads only consume the reply bytes which fit into the read buffer, and they read nothing
+    /// if no reply is queued.
+    #[derive(Debug, Default, Clone)]
+    pub struct BusMock {
+        replies: VecDeque<Vec<u8>>,
+        transfers: Vec<MockTransfer>,
+        failing: bool,
+    }
+
+    impl BusMock {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn queue_reply(&mut self, reply: &[u8]) {
+            self.replies.push_back(reply.to_vec());
+        }
+
+        pub fn num_queued_replies(&self) -> usize {
+            self.replies.len()
+        }
+
+        /// All operations fail with a [BusMockError] while this is set. Failed operations are
+        /// not recorded.
+        pub fn set_failing(&mut self, failing: bool) {
+            self.failing = failing;
+        }
+
+        pub fn transfers(&self) -> &[MockTransfer] {
+            &self.transfers
+        }
+
+        /// Retrieve and clear the recorded transfers.
+        pub fn take_transfers(&mut self) -> Vec<MockTransfer> {
+            core::mem::take(&mut self.transfers)
+        }
+
+        fn transfer_generic(
+            &mut self,
+            address: Option<I2cAddress>,
+            tx: &[u8],
+            rx: &mut [u8],
+        ) -> Result<(), BusMockError> {
+            if self.failing {
+                return Err(BusMockError);
+            }
+            if !rx.is_empty() {
+                rx.fill(0);
+                if let Some(reply) = self.replies.pop_front() {
+                    let len = reply.len().min(rx.len());
+                    rx[..len].copy_from_slice(&reply[..len]);
+                }
+            }
+            self.transfers.push(MockTransfer {
+                address,
+                written: tx.to_vec(),
+                read_len: rx.len(),
+            });
+            Ok(())
+        }
+    }
+
+    impl SpiBus for BusMock {
+        type Error = BusMockError;
+
+        fn transfer(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
+            self.transfer_generic(None, tx, rx)
+        }
+
+        fn write(&mut self, tx: &[u8]) -> Result<(), Self::Error> {
+            self.transfer_generic(None, tx, &mut [])
+        }
+
+        fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
+            self.transfer_generic(None, tx, rx)
+        }
+    }
+
+    impl I2cBus for BusMock {
+        type Error = BusMockError;
+
+        fn write(&mut self, address: I2cAddress, tx: &[u8]) -> Result<(), Self::Error> {
+            self.transfer_generic(Some(address), tx, &mut [])
+        }
+
+        fn read(&mut self, address: I2cAddress, rx: &mut [u8]) -> Result<(), Self::Error> {
+            self.transfer_generic(Some(address), &[], rx)
+        }
+
+        fn write_read(
+            &mut self,
+            address: I2cAddress,
+            tx: &[u8],
+            rx: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            self.transfer_generic(Some(address), tx, rx)
+        }
+    }
+
+    impl UartBus for BusMock {
+        type Error = BusMockError;
+
+        fn write(&mut self, tx: &[u8]) -> Result<(), Self::Error> {
+            self.transfer_generic(None, tx, &mut [])
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            if self.failing {
+                return Err(BusMockError);
+            }
+            Ok(())
+        }
+
+        fn read_available(&mut self, rx: &mut [u8]) -> Result<usize, Self::Error> {
+            if self.failing {
+                return Err(BusMockError);
+            }
+            let mut reply = match self.replies.pop_front() {
+                Some(reply) => reply,
+                None => return Ok(0),
+            };
+            let len = reply.len().min(rx.len());
+            rx[..len].copy_from_slice(&reply[..len]);
+            if len < reply.len() {
+                reply.drain(..len);
+                self.replies.push_front(reply);
+            }
+            self.transfers.push(MockTransfer {
+                address: None,
+                written: Vec::new(),
+                read_len: len,
+            });
+            Ok(len)
+        }
+    }
+
+    impl DeviceBus for BusMock {
+        type Error = BusMockError;
+
+        fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
+            self.transfer_generic(None, tx, rx)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::io::{self, ErrorKind, Read, Write};
+
+    use super::*;
+
+    /// [UartBus] for a port which implements [Read] and [Write], for example a serial port. The
+    /// port should be configured with a short read timeout or to be non-blocking, because
+    /// [UartBus::read_available] treats timeouts as no received data.
+    #[derive(Debug)]
+    pub struct IoUart<Port: Read + Write> {
+        pub port: Port,
+    }
+
+    impl<Port: Read + Write> IoUart<Port> {
+        pub fn new(port: Port) -> Self {
+            Self { port }
+        }
+    }
+
+    impl<Port: Read + Write> UartBus for IoUart<Port> {
+        type Error = io::Error;
+
+        fn write(&mut self, tx: &[u8]) -> Result<(), Self::Error> {
+            self.port.write_all(tx)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.port.flush()
+        }
+
+        fn read_available(&mut self, rx: &mut [u8]) -> Result<usize, Self::Error> {
+            match self.port.read(rx) {
+                Ok(len) => Ok(len),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => Ok(0),
+                Err(e) => Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+pub mod embedded_hal_mod {
+    use embedded_hal::i2c::I2c;
+    use embedded_hal::spi::{Operation, SpiDevice as EhSpiDevice};
+
+    use super::*;
+
+    /// [SpiBus] for an embedded-hal [SPI device][EhSpiDevice], which manages the chip select.
+    #[derive(Debug)]
+    pub struct EmbeddedHalSpi<Spi: EhSpiDevice> {
+        pub spi: Spi,
+    }
+
+    impl<Spi: EhSpiDevice> EmbeddedHalSpi<Spi> {
+        pub fn new(spi: Spi) -> Self {
+            Self { spi }
+        }
+    }
+
+    impl<Spi: EhSpiDevice> SpiBus for EmbeddedHalSpi<Spi> {
+        type Error = Spi::Error;
+
+        fn transfer(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
+            self.spi.transfer(rx, tx)
+        }
+
+        fn write(&mut self, tx: &[u8]) -> Result<(), Self::Error> {
+            self.spi.write(tx)
+        }
+
+        fn write_read(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), Self::Error> {
+            self.spi
+                .transaction(&mut [Operation::Write(tx), Operation::Read(rx)])
+        }
+    }
+
+    /// [I2cBus] for an embedded-hal [I2c] bus.
+    #[derive(Debug)]
+    pub struct EmbeddedHalI2c<Bus: I2c> {
+        pub i2c: Bus,
+    }
+
+    impl<Bus: I2c> EmbeddedHalI2c<Bus> {
+        pub fn new(i2c: Bus) -> Self {
+            Self { i2c }
+        }
+    }
+
+    impl<Bus: I2c> I2cBus for EmbeddedHalI2c<Bus> {
+        type Error = Bus::Error;
+
+        fn write(&mut self, address: I2cAddress, tx: &[u8]) -> Result<(), Self::Error> {
+            self.i2c.write(address, tx)
+        }
+
+        fn read(&mut self, address: I2cAddress, rx: &mut [u8]) -> Result<(), Self::Error> {
+            self.i2c.read(address, rx)
+        }
+
+        fn write_read(
+            &mut self,
+            address: I2cAddress,
+            tx: &[u8],
+            rx: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            self.i2c.write_read(address, tx, rx)
+        }
+    }
+
+    /// [UartBus] for a serial peripheral which implements the embedded-io traits.
+    #[derive(Debug)]
+    pub struct EmbeddedIoUart<Uart: embedded_io::Read + embedded_io::Write + embedded_io::ReadReady> {
+        pub uart: Uart,
+    }
+
+    impl<Uart: embedded_io::Read + embedded_io::Write + embedded_io::ReadReady> EmbeddedIoUart<Uart> {
+        pub fn new(uart: Uart) -> Self {
+            Self { uart }
+        }
+    }
+
+    impl<Uart: embedded_io::Read + embedded_io::Write + embedded_io::ReadReady> UartBus
+        for EmbeddedIoUart<Uart>
+    {
+        type Error = Uart::Error;
+
+        fn write(&mut self, tx: &[u8]) -> Result<(), Self::Error> {
+            self.uart.write_all(tx)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.uart.flush()
+        }
+
+        fn read_available(&mut self, rx: &mut [u8]) -> Result<usize, Self::Error> {
+            if rx.is_empty() || !self.uart.read_ready()? {
+                return Ok(0);
+            }
+            self.uart.read(rx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::io;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_mock_spi_device() {
+        let mut bus = BusMock::new();
+        bus.queue_reply(&[1, 2, 3]);
+        let mut device = SpiDevice::new(bus);
+        let mut rx = [0xff; 4];
+        device.write_read(&[0x80], &mut rx).unwrap();
+        // The reply is padded with zeros.
+        assert_eq!(rx, [1, 2, 3, 0]);
+        device.write_read(&[0x01, 0x02], &mut []).unwrap();
+        assert_eq!(
+            device.bus.take_transfers(),
+            vec![
+                MockTransfer {
+                    address: None,
+                    written: vec![0x80],
+                    read_len: 4
+                },
+                MockTransfer {
+                    address: None,
+                    written: vec![0x01, 0x02],
+                    read_len: 0
+                }
+            ]
+        );
+        assert!(device.bus.transfers().is_empty());
+    }
+
+    #[test]
+    fn test_mock_i2c_device() {
+        let mut device = I2cDevice::new(BusMock::new(), 0x48);
+        device.bus.queue_reply(&[5, 6]);
+        let mut rx = [0; 1];
+        // Read without a command.
+        device.write_read(&[], &mut rx).unwrap();
+        assert_eq!(rx, [5]);
+        assert_eq!(device.bus.num_queued_replies(), 0);
+        device.bus.set_failing(true);
+        assert_eq!(device.write_read(&[0x0F], &mut rx), Err(BusMockError));
+        assert_eq!(device.bus.transfers().len(), 1);
+        assert_eq!(device.bus.transfers()[0].address, Some(0x48));
+    }
+
+    #[test]
+    fn test_mock_uart() {
+        let mut uart = BusMock::new();
+        let mut rx = [0; 4];
+        assert_eq!(uart.read_available(&mut rx), Ok(0));
+        uart.queue_reply(&[1, 2, 3, 4, 5, 6]);
+        UartBus::write(&mut uart, &[7, 8]).unwrap();
+        assert_eq!(uart.read_available(&mut rx), Ok(4));
+        assert_eq!(rx, [1, 2, 3, 4]);
+        // The remaining bytes are read with the next call.
+        assert_eq!(uart.read_available(&mut rx), Ok(2));
+        assert_eq!(&rx[0..2], &[5, 6]);
+        assert_eq!(uart.transfers()[0].written, vec![7, 8]);
+    }
+
+    /// Port which returns the queued bytes and reports a timeout if no bytes are left.
+    #[derive(Default)]
+    struct TestPort {
+        rx: VecDeque<u8>,
+        tx: Vec<u8>,
+    }
+
+    impl io::Read for TestPort {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.rx.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "timeout"));
+            }
+            let len = buf.len().min(self.rx.len());
+            for (idx, byte) in self.rx.drain(..len).enumerate() {
+                buf[idx] = byte;
+            }
+            Ok(len)
+        }
+    }
+
+    impl io::Write for TestPort {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.tx.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_io_uart() {
+        let mut uart = IoUart::new(TestPort::default());
+        let mut rx = [0; 8];
+        assert_eq!(uart.read_available(&mut rx).unwrap(), 0);
+        uart.port.rx.extend([1, 2, 3]);
+        assert_eq!(uart.read_available(&mut rx).unwrap(), 3);
+        assert_eq!(&rx[0..3], &[1, 2, 3]);
+        uart.write(&[4, 5]).unwrap();
+        uart.flush().unwrap();
+        assert_eq!(uart.port.tx, vec![4, 5]);
+    }
+}
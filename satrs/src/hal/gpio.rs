@@ -0,0 +1,359 @@
+//! # Discrete inputs and outputs
+//!
+//! Components like power switchers or heaters drive discrete output lines and read back discrete
+//! inputs, for example enable lines or status flags. The [OutputPin] and [InputPin] traits
+//! decouple these components from the GPIO implementation of the target.
+//!
+//! The following implementations are provided:
+//!
+//!  - The [PinMock] which can be used as an output and an input and is intended for tests.
+//!  - The [SysfsGpioChip] which provides the pins of the Linux GPIO sysfs interface, which is
+//!    usually located at `/sys/class/gpio`. It requires the `std` feature.
+//!
+//! The sysfs interface is deprecated in favour of the GPIO character devices at
+//! `/dev/gpiochipN`, but it is still available on the kernels of most flight computers and only
+//! requires file access. The character devices are controlled with `ioctl` calls, which would
+//! require an additional dependency for all users of the `std` feature. Projects which use the
+//! character devices can implement [OutputPin] and [InputPin] for the line handles of a crate
+//! like [gpio-cdev](https://docs.rs/gpio-cdev).
+//!
+//! # Example
+//!
+//! ```
+//! use satrs::hal::gpio::{OutputPin, PinMock, PinState};
+//!
+//! let mut enable_line = PinMock::default();
+//! enable_line.set_high().unwrap();
+//! assert_eq!(enable_line.state(), PinState::High);
+//! assert_eq!(enable_line.num_writes(), 1);
+//! ```
+use core::fmt::{Debug, Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PinState {
+    #[default]
+    Low,
+    High,
+}
+
+impl PinState {
+    pub fn is_high(&self) -> bool {
+        *self == PinState::High
+    }
+}
+
+impl From<bool> for PinState {
+    fn from(high: bool) -> Self {
+        if high {
+            PinState::High
+        } else {
+            PinState::Low
+        }
+    }
+}
+
+impl From<PinState> for bool {
+    fn from(state: PinState) -> Self {
+        state.is_high()
+    }
+}
+
+/// Discrete output line.
+pub trait OutputPin {
+    type Error: Debug;
+
+    fn set_state(&mut self, state: PinState) -> Result<(), Self::Error>;
+
+    /// Read back the state which is currently driven.
+    fn output_state(&mut self) -> Result<PinState, Self::Error>;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set_state(PinState::High)
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.set_state(PinState::Low)
+    }
+}
+
+/// Discrete input line.
+pub trait InputPin {
+    type Error: Debug;
+
+    fn input_state(&mut self) -> Result<PinState, Self::Error>;
+
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.input_state()?.is_high())
+    }
+}
+
+/// Error of the [PinMock], which is returned for all operations after a failure was injected with
+/// [PinMock::set_failing].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PinMockError;
+
+impl Display for PinMockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "injected pin failure")
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for PinMockError {}
+
+/// Pin mock which can be used as an [OutputPin] and as an [InputPin]. Written states are read
+/// back as the input state, and the input state can be set directly with [Self::set_input] to
+/// simulate external signals.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PinMock {
+    state: PinState,
+    num_writes: u32,
+    failing: bool,
+}
+
+impl PinMock {
+    pub fn new(state: PinState) -> Self {
+        Self {
+            state,
+            ..Default::default()
+        }
+    }
+
+    pub fn state(&self) -> PinState {
+        self.state
+    }
+
+    /// Number of successful [OutputPin::set_state] calls.
+    pub fn num_writes(&self) -> u32 {
+        self.num_writes
+    }
+
+    /// Set the input state without counting it as a write.
+    pub fn set_input(&mut self, state: PinState) {
+        self.state = state;
+    }
+
+    /// All operations fail with a [PinMockError] while this is set.
+    pub fn set_failing(&mut self, failing: bool) {
+        self.failing = failing;
+    }
+
+    fn check_failure(&self) -> Result<(), PinMockError> {
+        if self.failing {
+            return Err(PinMockError);
+        }
+        Ok(())
+    }
+}
+
+impl OutputPin for PinMock {
+    type Error = PinMockError;
+
+    fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+        self.check_failure()?;
+        self.state = state;
+        self.num_writes += 1;
+        Ok(())
+    }
+
+    fn output_state(&mut self) -> Result<PinState, Self::Error> {
+        self.check_failure()?;
+        Ok(self.state)
+    }
+}
+
+impl InputPin for PinMock {
+    type Error = PinMockError;
+
+    fn input_state(&mut self) -> Result<PinState, Self::Error> {
+        self.check_failure()?;
+        Ok(self.state)
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::format;
+    use std::fs;
+    use std::io::{self, ErrorKind};
+    use std::path::{Path, PathBuf};
+    use std::string::ToString;
+
+    use super::*;
+
+    pub const SYSFS_GPIO_ROOT: &str = "/sys/class/gpio";
+
+    /// Provider for the pins of the Linux GPIO sysfs interface. Pins are exported when they are
+    /// requested and were not exported before.
+    #[derive(Debug, Clone)]
+    pub struct SysfsGpioChip {
+        root: PathBuf,
+    }
+
+    impl Default for SysfsGpioChip {
+        fn default() -> Self {
+            Self::new(SYSFS_GPIO_ROOT)
+        }
+    }
+
+    impl SysfsGpioChip {
+        /// Create a provider for the sysfs interface at the given root path. Use [Self::default]
+        /// for the default location [SYSFS_GPIO_ROOT].
+        pub fn new(root: impl AsRef<Path>) -> Self {
+            Self {
+                root: root.as_ref().to_path_buf(),
+            }
+        }
+
+        /// Request the given GPIO line as an output which is driven with the initial state.
+        pub fn output(&self, line: u32, initial_state: PinState) -> io::Result<SysfsOutputPin> {
+            let line_path = self.export(line)?;
+            let direction = match initial_state {
+                PinState::Low => "low",
+                PinState::High => "high",
+            };
+            fs::write(line_path.join("direction"), direction)?;
+            Ok(SysfsOutputPin {
+                line,
+                value_path: line_path.join("value"),
+            })
+        }
+
+        /// Request the given GPIO line as an input.
+        pub fn input(&self, line: u32) -> io::Result<SysfsInputPin> {
+            let line_path = self.export(line)?;
+            fs::write(line_path.join("direction"), "in")?;
+            Ok(SysfsInputPin {
+                line,
+                value_path: line_path.join("value"),
+            })
+        }
+
+        fn export(&self, line: u32) -> io::Result<PathBuf> {
+            let line_path = self.root.join(format!("gpio{line}"));
+            if !line_path.exists() {
+                fs::write(self.root.join("export"), line.to_string())?;
+            }
+            Ok(line_path)
+        }
+    }
+
+    fn read_value(value_path: &Path) -> io::Result<PinState> {
+        match fs::read_to_string(value_path)?.trim() {
+            "0" => Ok(PinState::Low),
+            "1" => Ok(PinState::High),
+            other => Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid GPIO value {other:?}"),
+            )),
+        }
+    }
+
+    /// Output pin of the Linux GPIO sysfs interface.
+    #[derive(Debug)]
+    pub struct SysfsOutputPin {
+        line: u32,
+        value_path: PathBuf,
+    }
+
+    impl SysfsOutputPin {
+        pub fn line(&self) -> u32 {
+            self.line
+        }
+    }
+
+    impl OutputPin for SysfsOutputPin {
+        type Error = io::Error;
+
+        fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+            let value = match state {
+                PinState::Low => "0",
+                PinState::High => "1",
+            };
+            fs::write(&self.value_path, value)
+        }
+
+        fn output_state(&mut self) -> Result<PinState, Self::Error> {
+            read_value(&self.value_path)
+        }
+    }
+
+    /// Input pin of the Linux GPIO sysfs interface.
+    #[derive(Debug)]
+    pub struct SysfsInputPin {
+        line: u32,
+        value_path: PathBuf,
+    }
+
+    impl SysfsInputPin {
+        pub fn line(&self) -> u32 {
+            self.line
+        }
+    }
+
+    impl InputPin for SysfsInputPin {
+        type Error = io::Error;
+
+        fn input_state(&mut self) -> Result<PinState, Self::Error> {
+            read_value(&self.value_path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_pin_mock() {
+        let mut pin = PinMock::new(PinState::High);
+        assert!(pin.is_high().unwrap());
+        pin.set_low().unwrap();
+        assert_eq!(pin.output_state().unwrap(), PinState::Low);
+        pin.set_input(PinState::High);
+        assert_eq!(pin.input_state().unwrap(), PinState::High);
+        assert_eq!(pin.num_writes(), 1);
+        pin.set_failing(true);
+        assert_eq!(pin.set_high(), Err(PinMockError));
+        assert_eq!(pin.input_state(), Err(PinMockError));
+    }
+
+    #[test]
+    fn test_sysfs_gpio() {
+        let root = tempfile::tempdir().unwrap();
+        // The directories of the lines are created by the kernel on export.
+        fs::create_dir(root.path().join("gpio5")).unwrap();
+        fs::create_dir(root.path().join("gpio6")).unwrap();
+        let chip = SysfsGpioChip::new(root.path());
+        let mut output = chip.output(5, PinState::High).unwrap();
+        assert_eq!(output.line(), 5);
+        assert_eq!(
+            fs::read_to_string(root.path().join("gpio5/direction")).unwrap(),
+            "high"
+        );
+        output.set_low().unwrap();
+        assert_eq!(output.output_state().unwrap(), PinState::Low);
+        fs::write(root.path().join("gpio5/value"), "1\n").unwrap();
+        assert_eq!(output.output_state().unwrap(), PinState::High);
+
+        let mut input = chip.input(6).unwrap();
+        fs::write(root.path().join("gpio6/value"), "x").unwrap();
+        assert_eq!(
+            input.input_state().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        // The export file is only written for lines which were not exported yet. The line
+        // directory is not created here, so configuring the direction fails.
+        assert!(!root.path().join("export").exists());
+        assert!(chip.input(7).is_err());
+        assert_eq!(fs::read_to_string(root.path().join("export")).unwrap(), "7");
+    }
+}
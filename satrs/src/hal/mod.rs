@@ -1,3 +1,7 @@
 //! # Hardware Abstraction Layer module
+pub mod bus;
+pub mod gpio;
+pub mod nvm;
+pub mod pwm;
 #[cfg(feature = "std")]
 pub mod std;
@@ -0,0 +1,516 @@
+//! # Non-volatile memory abstraction
+//!
+//! The [NonVolatileMemory] trait abstracts block-oriented non-volatile memories like EEPROMs,
+//! FRAMs or flash memories. Components which persist data, for example persistent counters, event
+//! logs or parameter stores, can use this trait instead of accessing the memory directly.
+//!
+//! The [NvmInfo] describes the geometry of the memory and contains hints which allow users to
+//! limit the wear of the memory. Flash memories need to be erased before they can be written,
+//! and the number of erase cycles of each erase block is limited. Erased memory has the value
+//! [ERASED_VALUE].
+//!
+//! The following implementations are provided:
+//!
+//!  - The [RamNvm] which keeps the memory contents in RAM and counts the erase cycles of each
+//!    erase block. It can emulate memories with and without erase requirement and is intended
+//!    for simulations and tests. It requires the `alloc` feature.
+//!  - The [FileNvm] which keeps the memory contents in a file. It requires the `std` feature.
+//!
+//! The [NvmSlotStorage][crate::nv_storage::NvmSlotStorage] can be used to store key-value
+//! records on top of a [NonVolatileMemory].
+//!
+//! # Example
+//!
+//! ```
+//! use satrs::hal::nvm::{NonVolatileMemory, NvmError, NvmInfo, RamNvm};
+//!
+//! // Flash memory with 4 erase blocks of 256 bytes each.
+//! let mut flash = RamNvm::new(NvmInfo::new_flash(1024, 256, 4, 10_000));
+//! flash.write(256, &[1, 2, 3, 4]).unwrap();
+//! // The written area needs to be erased before it can be written again.
+//! assert_eq!(
+//!     flash.write(256, &[5, 6, 7, 8]),
+//!     Err(NvmError::NotErased { offset: 256 })
+//! );
+//! flash.erase(256, 256).unwrap();
+//! flash.write(256, &[5, 6, 7, 8]).unwrap();
+//! assert_eq!(flash.erase_count(1), Some(1));
+//! ```
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// Value of erased memory.
+pub const ERASED_VALUE: u8 = 0xff;
+
+/// Geometry of a [NonVolatileMemory] and hints for limiting its wear.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NvmInfo {
+    /// Total size of the memory in bytes.
+    pub size: usize,
+    /// Size of the erase blocks in bytes. Erase operations need to be aligned to this size.
+    pub erase_block_size: usize,
+    /// Write granularity in bytes. Write operations need to be aligned to this size.
+    pub write_size: usize,
+    /// Memory areas need to be erased before they can be written again.
+    pub erase_required: bool,
+    /// Number of erase cycles each erase block is rated for, or [None] if the wear of the memory
+    /// does not need to be considered.
+    pub max_erase_cycles: Option<u32>,
+}
+
+impl NvmInfo {
+    /// Memory like an EEPROM, a FRAM or a file which can be overwritten without erasing it first.
+    /// Erase operations are still supported and fill the memory with the [ERASED_VALUE].
+    pub const fn new_eeprom(size: usize, erase_block_size: usize) -> Self {
+        Self {
+            size,
+            erase_block_size,
+            write_size: 1,
+            erase_required: false,
+            max_erase_cycles: None,
+        }
+    }
+
+    /// Flash memory which needs to be erased before it can be written.
+    pub const fn new_flash(
+        size: usize,
+        erase_block_size: usize,
+        write_size: usize,
+        max_erase_cycles: u32,
+    ) -> Self {
+        Self {
+            size,
+            erase_block_size,
+            write_size,
+            erase_required: true,
+            max_erase_cycles: Some(max_erase_cycles),
+        }
+    }
+
+    pub fn num_erase_blocks(&self) -> usize {
+        self.size / self.erase_block_size.max(1)
+    }
+
+    /// Check that the given memory area is inside the memory and aligned to the given alignment.
+    pub fn check_access(
+        &self,
+        offset: usize,
+        len: usize,
+        alignment: usize,
+    ) -> Result<(), NvmError> {
+        let in_bounds = match offset.checked_add(len) {
+            Some(end) => end <= self.size,
+            None => false,
+        };
+        if !in_bounds {
+            return Err(NvmError::OutOfBounds {
+                offset,
+                len,
+                size: self.size,
+            });
+        }
+        if alignment > 1 && (offset % alignment != 0 || len % alignment != 0) {
+            return Err(NvmError::NotAligned {
+                offset,
+                len,
+                alignment,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NvmError {
+    OutOfBounds {
+        offset: usize,
+        len: usize,
+        size: usize,
+    },
+    /// Write or erase operation which is not aligned to the write size or the erase block size.
+    NotAligned {
+        offset: usize,
+        len: usize,
+        alignment: usize,
+    },
+    /// Write to a memory area which was not erased.
+    NotErased { offset: usize },
+}
+
+impl Display for NvmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NvmError::OutOfBounds { offset, len, size } => write!(
+                f,
+                "memory area with offset {offset:#x} and length {len} out of bounds for memory \
+                size {size}"
+            ),
+            NvmError::NotAligned {
+                offset,
+                len,
+                alignment,
+            } => write!(
+                f,
+                "memory area with offset {offset:#x} and length {len} not aligned to {alignment}"
+            ),
+            NvmError::NotErased { offset } => {
+                write!(f, "memory at offset {offset:#x} not erased")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for NvmError {}
+
+/// Generic abstraction for block-oriented non-volatile memories.
+pub trait NonVolatileMemory {
+    type Error;
+
+    fn info(&self) -> NvmInfo;
+
+    /// Read the memory area starting at the given offset into the buffer. The length of the
+    /// memory area is the length of the buffer.
+    fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Write the data to the memory area starting at the given offset. The area needs to be
+    /// aligned to the [NvmInfo::write_size], and it needs to be erased first if
+    /// [NvmInfo::erase_required] is set.
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Erase the memory area starting at the given offset. The area needs to be aligned to the
+    /// [NvmInfo::erase_block_size].
+    fn erase(&mut self, offset: usize, len: usize) -> Result<(), Self::Error>;
+}
+
+impl<Nvm: NonVolatileMemory + ?Sized> NonVolatileMemory for &mut Nvm {
+    type Error = Nvm::Error;
+
+    fn info(&self) -> NvmInfo {
+        (**self).info()
+    }
+
+    fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).read(offset, buf)
+    }
+
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Self::Error> {
+        (**self).write(offset, data)
+    }
+
+    fn erase(&mut self, offset: usize, len: usize) -> Result<(), Self::Error> {
+        (**self).erase(offset, len)
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    /// [NonVolatileMemory] which keeps the memory contents in RAM. It is intended for simulations
+    /// and tests.
+    ///
+    /// If [NvmInfo::erase_required] is set, writes to memory which was not erased are rejected
+    /// like on a flash memory. The erase cycles of each erase block are counted, which allows
+    /// verifying the wear of components using the memory.
+    #[derive(Debug, Clone)]
+    pub struct RamNvm {
+        info: NvmInfo,
+        memory: Vec<u8>,
+        erase_counts: Vec<u32>,
+    }
+
+    impl RamNvm {
+        /// Create a new erased memory.
+        pub fn new(info: NvmInfo) -> Self {
+            Self {
+                info,
+                memory: vec![ERASED_VALUE; info.size],
+                erase_counts: vec![0; info.num_erase_blocks()],
+            }
+        }
+
+        pub fn memory(&self) -> &[u8] {
+            &self.memory
+        }
+
+        /// Number of erase cycles of the erase block with the given index.
+        pub fn erase_count(&self, block_idx: usize) -> Option<u32> {
+            self.erase_counts.get(block_idx).copied()
+        }
+
+        /// Highest number of erase cycles of all erase blocks.
+        pub fn max_erase_count(&self) -> u32 {
+            self.erase_counts.iter().copied().max().unwrap_or(0)
+        }
+    }
+
+    impl NonVolatileMemory for RamNvm {
+        type Error = NvmError;
+
+        fn info(&self) -> NvmInfo {
+            self.info
+        }
+
+        fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), Self::Error> {
+            self.info.check_access(offset, buf.len(), 1)?;
+            buf.copy_from_slice(&self.memory[offset..offset + buf.len()]);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Self::Error> {
+            self.info
+                .check_access(offset, data.len(), self.info.write_size)?;
+            let area = &mut self.memory[offset..offset + data.len()];
+            if self.info.erase_required {
+                if let Some(pos) = area.iter().position(|byte| *byte != ERASED_VALUE) {
+                    return Err(NvmError::NotErased {
+                        offset: offset + pos,
+                    });
+                }
+            }
+            area.copy_from_slice(data);
+            Ok(())
+        }
+
+        fn erase(&mut self, offset: usize, len: usize) -> Result<(), Self::Error> {
+            let block_size = self.info.erase_block_size;
+            self.info.check_access(offset, len, block_size)?;
+            self.memory[offset..offset + len].fill(ERASED_VALUE);
+            let first_block = offset / block_size.max(1);
+            let num_blocks = len / block_size.max(1);
+            for count in self.erase_counts[first_block..first_block + num_blocks].iter_mut() {
+                *count = count.saturating_add(1);
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::fs::{File, OpenOptions};
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::path::Path;
+    use std::vec;
+
+    use super::*;
+
+    #[derive(Debug)]
+    pub enum FileNvmError {
+        Nvm(NvmError),
+        Io(std::io::Error),
+    }
+
+    impl Display for FileNvmError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                FileNvmError::Nvm(e) => write!(f, "NVM error: {e}"),
+                FileNvmError::Io(e) => write!(f, "NVM file error: {e}"),
+            }
+        }
+    }
+
+    impl From<NvmError> for FileNvmError {
+        fn from(value: NvmError) -> Self {
+            Self::Nvm(value)
+        }
+    }
+
+    impl From<std::io::Error> for FileNvmError {
+        fn from(value: std::io::Error) -> Self {
+            Self::Io(value)
+        }
+    }
+
+    impl Error for FileNvmError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                FileNvmError::Nvm(e) => Some(e),
+                FileNvmError::Io(e) => Some(e),
+            }
+        }
+    }
+
+    /// [NonVolatileMemory] which keeps the memory contents in a file.
+    ///
+    /// The memory behaves like an EEPROM and can be overwritten without erasing it first. Writes
+    /// are passed to the operating system directly, and [Self::sync] can be used to ensure that
+    /// they reached the disk.
+    #[derive(Debug)]
+    pub struct FileNvm {
+        file: File,
+        info: NvmInfo,
+    }
+
+    impl FileNvm {
+        /// Open or create the file which backs the memory.
+        ///
+        /// If the file is shorter than the memory size, it is extended with erased memory.
+        /// Existing memory contents are preserved.
+        ///
+        /// ## Parameter
+        ///
+        /// * `path` - Path of the file.
+        /// * `size` - Size of the memory in bytes.
+        /// * `erase_block_size` - Size of the erase blocks in bytes.
+        pub fn open(
+            path: impl AsRef<Path>,
+            size: usize,
+            erase_block_size: usize,
+        ) -> Result<Self, FileNvmError> {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)?;
+            let file_len = file.metadata()?.len() as usize;
+            if file_len < size {
+                file.seek(SeekFrom::Start(file_len as u64))?;
+                file.write_all(&vec![ERASED_VALUE; size - file_len])?;
+            }
+            Ok(Self {
+                file,
+                info: NvmInfo::new_eeprom(size, erase_block_size),
+            })
+        }
+
+        /// Ensure that all writes reached the disk.
+        pub fn sync(&self) -> Result<(), FileNvmError> {
+            self.file.sync_data()?;
+            Ok(())
+        }
+    }
+
+    impl NonVolatileMemory for FileNvm {
+        type Error = FileNvmError;
+
+        fn info(&self) -> NvmInfo {
+            self.info
+        }
+
+        fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), Self::Error> {
+            self.info.check_access(offset, buf.len(), 1)?;
+            self.file.seek(SeekFrom::Start(offset as u64))?;
+            self.file.read_exact(buf)?;
+            Ok(())
+        }
+
+        fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Self::Error> {
+            self.info.check_access(offset, data.len(), 1)?;
+            self.file.seek(SeekFrom::Start(offset as u64))?;
+            self.file.write_all(data)?;
+            Ok(())
+        }
+
+        fn erase(&mut self, offset: usize, len: usize) -> Result<(), Self::Error> {
+            self.info
+                .check_access(offset, len, self.info.erase_block_size)?;
+            self.file.seek(SeekFrom::Start(offset as u64))?;
+            self.file.write_all(&vec![ERASED_VALUE; len])?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+    use std::fs;
+
+    #[test]
+    fn test_access_checks() {
+        let info = NvmInfo::new_flash(1024, 256, 4, 100);
+        assert_eq!(info.num_erase_blocks(), 4);
+        assert!(info.check_access(1020, 4, 4).is_ok());
+        assert_eq!(
+            info.check_access(1020, 8, 4),
+            Err(NvmError::OutOfBounds {
+                offset: 1020,
+                len: 8,
+                size: 1024
+            })
+        );
+        assert!(info.check_access(usize::MAX, 2, 1).is_err());
+        assert_eq!(
+            info.check_access(2, 4, 4),
+            Err(NvmError::NotAligned {
+                offset: 2,
+                len: 4,
+                alignment: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_ram_nvm_flash() {
+        let mut flash = RamNvm::new(NvmInfo::new_flash(512, 256, 2, 100));
+        flash.write(2, &[1, 2]).unwrap();
+        let mut buf = [0; 4];
+        flash.read(0, &mut buf).unwrap();
+        assert_eq!(buf, [0xff, 0xff, 1, 2]);
+        assert_eq!(
+            flash.write(0, &[3, 4, 5, 6]),
+            Err(NvmError::NotErased { offset: 2 })
+        );
+        assert!(flash.write(1, &[3, 4]).is_err());
+        assert!(flash.erase(0, 128).is_err());
+        flash.erase(0, 512).unwrap();
+        flash.erase(256, 256).unwrap();
+        flash.write(0, &[3, 4, 5, 6]).unwrap();
+        assert_eq!(&flash.memory()[0..4], &[3, 4, 5, 6]);
+        assert_eq!(flash.erase_count(0), Some(1));
+        assert_eq!(flash.erase_count(1), Some(2));
+        assert_eq!(flash.erase_count(2), None);
+        assert_eq!(flash.max_erase_count(), 2);
+    }
+
+    #[test]
+    fn test_ram_nvm_eeprom() {
+        let mut eeprom = RamNvm::new(NvmInfo::new_eeprom(64, 16));
+        eeprom.write(3, &[1, 2, 3]).unwrap();
+        eeprom.write(4, &[4]).unwrap();
+        let mut buf = [0; 3];
+        eeprom.read(3, &mut buf).unwrap();
+        assert_eq!(buf, [1, 4, 3]);
+    }
+
+    #[test]
+    fn test_file_nvm() {
+        let path = std::env::temp_dir().join(format!("satrs-nvm-test-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut nvm = FileNvm::open(&path, 128, 32).unwrap();
+        let mut buf = [0; 4];
+        nvm.read(0, &mut buf).unwrap();
+        assert_eq!(buf, [ERASED_VALUE; 4]);
+        nvm.write(32, &[1, 2, 3, 4]).unwrap();
+        nvm.sync().unwrap();
+        assert!(matches!(
+            nvm.read(126, &mut buf),
+            Err(FileNvmError::Nvm(NvmError::OutOfBounds { .. }))
+        ));
+        drop(nvm);
+
+        // The contents are preserved when the file is opened again.
+        let mut nvm = FileNvm::open(&path, 128, 32).unwrap();
+        nvm.read(32, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3, 4]);
+        nvm.erase(32, 32).unwrap();
+        nvm.read(32, &mut buf).unwrap();
+        assert_eq!(buf, [ERASED_VALUE; 4]);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 128);
+        fs::remove_file(&path).unwrap();
+    }
+}
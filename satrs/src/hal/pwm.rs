@@ -0,0 +1,302 @@
+//! # PWM channels
+//!
+//! The [PwmChannel] trait abstracts a single pulse width modulated output, which is used for
+//! example to drive heaters or magnetorquers with a variable power.
+//!
+//! The following implementations are provided:
+//!
+//!  - The [PwmMock] which stores the configuration and is intended for tests.
+//!  - The [SysfsPwmChip] which provides the channels of a PWM chip of the Linux PWM sysfs
+//!    interface, which is usually located at `/sys/class/pwm`. It requires the `std` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use core::time::Duration;
+//! use satrs::hal::pwm::{PwmChannel, PwmMock};
+//!
+//! let mut heater_pwm = PwmMock::default();
+//! heater_pwm.set_period(Duration::from_millis(10)).unwrap();
+//! heater_pwm.set_duty_cycle_percent(25).unwrap();
+//! heater_pwm.set_enabled(true).unwrap();
+//! assert_eq!(heater_pwm.duty_cycle(), Duration::from_micros(2500));
+//! ```
+use core::fmt::{Debug, Display, Formatter};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// Calculate the active time for the given period and duty cycle in percent. Duty cycles above
+/// 100 % are clamped.
+pub fn duty_cycle_from_percent(period: Duration, percent: u8) -> Duration {
+    let nanos = period.as_nanos() * u128::from(percent.min(100)) / 100;
+    Duration::from_nanos(nanos as u64)
+}
+
+/// Single PWM output.
+pub trait PwmChannel {
+    type Error: Debug;
+
+    fn set_period(&mut self, period: Duration) -> Result<(), Self::Error>;
+
+    fn period(&mut self) -> Result<Duration, Self::Error>;
+
+    /// Set the active time of each period, which must not exceed the period.
+    fn set_duty_cycle(&mut self, duty_cycle: Duration) -> Result<(), Self::Error>;
+
+    fn set_enabled(&mut self, enabled: bool) -> Result<(), Self::Error>;
+
+    /// Set the duty cycle in percent of the current period.
+    fn set_duty_cycle_percent(&mut self, percent: u8) -> Result<(), Self::Error> {
+        let period = self.period()?;
+        self.set_duty_cycle(duty_cycle_from_percent(period, percent))
+    }
+}
+
+/// Errors of the [PwmMock].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PwmMockError {
+    /// Failure injected with [PwmMock::set_failing].
+    Injected,
+    /// The duty cycle exceeds the period.
+    InvalidDutyCycle,
+}
+
+impl Display for PwmMockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PwmMockError::Injected => write!(f, "injected PWM failure"),
+            PwmMockError::InvalidDutyCycle => write!(f, "duty cycle exceeds the period"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for PwmMockError {}
+
+/// PWM mock which stores the configuration. Like real hardware, it rejects duty cycles which
+/// exceed the period.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PwmMock {
+    period: Duration,
+    duty_cycle: Duration,
+    enabled: bool,
+    failing: bool,
+}
+
+impl PwmMock {
+    pub fn new(period: Duration) -> Self {
+        Self {
+            period,
+            ..Default::default()
+        }
+    }
+
+    pub fn duty_cycle(&self) -> Duration {
+        self.duty_cycle
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// All operations fail with [PwmMockError::Injected] while this is set.
+    pub fn set_failing(&mut self, failing: bool) {
+        self.failing = failing;
+    }
+
+    fn check_failure(&self) -> Result<(), PwmMockError> {
+        if self.failing {
+            return Err(PwmMockError::Injected);
+        }
+        Ok(())
+    }
+}
+
+impl PwmChannel for PwmMock {
+    type Error = PwmMockError;
+
+    fn set_period(&mut self, period: Duration) -> Result<(), Self::Error> {
+        self.check_failure()?;
+        if self.duty_cycle > period {
+            return Err(PwmMockError::InvalidDutyCycle);
+        }
+        self.period = period;
+        Ok(())
+    }
+
+    fn period(&mut self) -> Result<Duration, Self::Error> {
+        self.check_failure()?;
+        Ok(self.period)
+    }
+
+    fn set_duty_cycle(&mut self, duty_cycle: Duration) -> Result<(), Self::Error> {
+        self.check_failure()?;
+        if duty_cycle > self.period {
+            return Err(PwmMockError::InvalidDutyCycle);
+        }
+        self.duty_cycle = duty_cycle;
+        Ok(())
+    }
+
+    fn set_enabled(&mut self, enabled: bool) -> Result<(), Self::Error> {
+        self.check_failure()?;
+        self.enabled = enabled;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::format;
+    use std::fs;
+    use std::io::{self, ErrorKind};
+    use std::path::{Path, PathBuf};
+    use std::string::ToString;
+
+    use super::*;
+
+    pub const SYSFS_PWM_ROOT: &str = "/sys/class/pwm";
+
+    /// Provider for the channels of a PWM chip of the Linux PWM sysfs interface. Channels are
+    /// exported when they are requested and were not exported before.
+    #[derive(Debug, Clone)]
+    pub struct SysfsPwmChip {
+        chip_path: PathBuf,
+    }
+
+    impl SysfsPwmChip {
+        /// Create a provider for the given PWM chip below [SYSFS_PWM_ROOT].
+        pub fn new(chip: u32) -> Self {
+            Self::with_chip_path(Path::new(SYSFS_PWM_ROOT).join(format!("pwmchip{chip}")))
+        }
+
+        /// Create a provider for the PWM chip directory at the given path.
+        pub fn with_chip_path(chip_path: impl AsRef<Path>) -> Self {
+            Self {
+                chip_path: chip_path.as_ref().to_path_buf(),
+            }
+        }
+
+        /// Request the given channel of the chip.
+        pub fn channel(&self, channel: u32) -> io::Result<SysfsPwmChannel> {
+            let channel_path = self.chip_path.join(format!("pwm{channel}"));
+            if !channel_path.exists() {
+                fs::write(self.chip_path.join("export"), channel.to_string())?;
+            }
+            Ok(SysfsPwmChannel {
+                channel,
+                channel_path,
+            })
+        }
+    }
+
+    /// Channel of the Linux PWM sysfs interface. All times are written in nanoseconds.
+    #[derive(Debug)]
+    pub struct SysfsPwmChannel {
+        channel: u32,
+        channel_path: PathBuf,
+    }
+
+    impl SysfsPwmChannel {
+        pub fn channel(&self) -> u32 {
+            self.channel
+        }
+
+        fn write_attr(&self, attr: &str, value: u128) -> io::Result<()> {
+            fs::write(self.channel_path.join(attr), value.to_string())
+        }
+    }
+
+    impl PwmChannel for SysfsPwmChannel {
+        type Error = io::Error;
+
+        fn set_period(&mut self, period: Duration) -> Result<(), Self::Error> {
+            self.write_attr("period", period.as_nanos())
+        }
+
+        fn period(&mut self) -> Result<Duration, Self::Error> {
+            let period = fs::read_to_string(self.channel_path.join("period"))?;
+            let nanos = period.trim().parse::<u64>().map_err(|_| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid PWM period {period:?}"),
+                )
+            })?;
+            Ok(Duration::from_nanos(nanos))
+        }
+
+        fn set_duty_cycle(&mut self, duty_cycle: Duration) -> Result<(), Self::Error> {
+            self.write_attr("duty_cycle", duty_cycle.as_nanos())
+        }
+
+        fn set_enabled(&mut self, enabled: bool) -> Result<(), Self::Error> {
+            self.write_attr("enable", u128::from(enabled))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_duty_cycle_from_percent() {
+        let period = Duration::from_millis(20);
+        assert_eq!(duty_cycle_from_percent(period, 0), Duration::ZERO);
+        assert_eq!(
+            duty_cycle_from_percent(period, 50),
+            Duration::from_millis(10)
+        );
+        assert_eq!(duty_cycle_from_percent(period, 150), period);
+    }
+
+    #[test]
+    fn test_pwm_mock() {
+        let mut pwm = PwmMock::new(Duration::from_millis(1));
+        pwm.set_duty_cycle_percent(100).unwrap();
+        assert_eq!(pwm.duty_cycle(), Duration::from_millis(1));
+        assert_eq!(
+            pwm.set_duty_cycle(Duration::from_millis(2)),
+            Err(PwmMockError::InvalidDutyCycle)
+        );
+        // The period can not be shortened below the duty cycle.
+        assert_eq!(
+            pwm.set_period(Duration::from_micros(500)),
+            Err(PwmMockError::InvalidDutyCycle)
+        );
+        pwm.set_enabled(true).unwrap();
+        assert!(pwm.enabled());
+        pwm.set_failing(true);
+        assert_eq!(pwm.set_enabled(false), Err(PwmMockError::Injected));
+        assert!(pwm.enabled());
+    }
+
+    #[test]
+    fn test_sysfs_pwm() {
+        let chip_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(chip_dir.path().join("pwm1")).unwrap();
+        let chip = SysfsPwmChip::with_chip_path(chip_dir.path());
+        let mut pwm = chip.channel(1).unwrap();
+        assert_eq!(pwm.channel(), 1);
+        pwm.set_period(Duration::from_micros(100)).unwrap();
+        pwm.set_duty_cycle_percent(10).unwrap();
+        pwm.set_enabled(true).unwrap();
+        let read_attr = |attr: &str| fs::read_to_string(chip_dir.path().join("pwm1").join(attr));
+        assert_eq!(read_attr("period").unwrap(), "100000");
+        assert_eq!(read_attr("duty_cycle").unwrap(), "10000");
+        assert_eq!(read_attr("enable").unwrap(), "1");
+        assert!(!chip_dir.path().join("export").exists());
+
+        chip.channel(2).unwrap();
+        assert_eq!(
+            fs::read_to_string(chip_dir.path().join("export")).unwrap(),
+            "2"
+        );
+    }
+}
@@ -0,0 +1,489 @@
+//! Asynchronous TCP TMTC servers based on the [tokio] runtime.
+//!
+//! These servers re-use the [TcpTcParser] and [TcpTmSender] abstractions of the blocking
+//! [TcpTmtcGenericServer][crate::hal::std::tcp_server::TcpTmtcGenericServer], so the same TMTC
+//! encoding flavours can be used inside async applications without having to spawn dedicated
+//! threads for the TMTC front-end.
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+use delegate::delegate;
+use socket2::{Domain, Socket, Type};
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::hal::std::tcp_cobs_server::{CobsTcParser, CobsTmSender};
+use crate::hal::std::tcp_server::{
+    ConnectionResult, HandledConnectionHandler, HandledConnectionInfo, ServerConfig, TcpTcParser,
+    TcpTmSender, TcpTmtcError,
+};
+use crate::tmtc::{PacketSenderRaw, PacketSource};
+use crate::ComponentId;
+
+/// Asynchronous variant of the generic
+/// [TcpTmtcGenericServer][crate::hal::std::tcp_server::TcpTmtcGenericServer].
+///
+/// It uses the same core abstractions as the blocking server:
+///
+/// 1. [TcpTcParser] to parse for telecommands from the raw bytestream received from a client.
+/// 2. Parsed telecommands will be sent using the [PacketSenderRaw] object.
+/// 3. [TcpTmSender] to encode telemetry pulled from a TM source. The encoded telemetry is
+///    written to an intermediate buffer first and is then sent to the client asynchronously.
+/// 4. [PacketSource] as a generic TM source used by the [TcpTmSender].
+///
+/// The [ServerConfig::inner_loop_delay] is used as the read timeout for client connections.
+/// If no data was received from the client during that time, the server will check for
+/// telemetry to send back to the client and for the optional stop signal.
+pub struct AsyncTcpTmtcGenericServer<
+    TmSource: PacketSource<Error = TmError>,
+    TcSender: PacketSenderRaw<Error = TcSendError>,
+    TmSender: TcpTmSender<TmError, TcSendError>,
+    TcParser: TcpTcParser<TmError, TcSendError>,
+    HandledConnection: HandledConnectionHandler,
+    TmError,
+    TcSendError,
+> {
+    pub id: ComponentId,
+    pub finished_handler: HandledConnection,
+    pub(crate) listener: TcpListener,
+    pub(crate) inner_loop_delay: Duration,
+    pub(crate) tm_source: TmSource,
+    pub(crate) tm_buffer: Vec<u8>,
+    pub(crate) tm_send_buffer: Vec<u8>,
+    pub(crate) tc_sender: TcSender,
+    pub(crate) tc_buffer: Vec<u8>,
+    pub tc_handler: TcParser,
+    pub tm_handler: TmSender,
+    stop_signal: Option<Arc<AtomicBool>>,
+}
+
+impl<
+        TmSource: PacketSource<Error = TmError>,
+        TcSender: PacketSenderRaw<Error = TcSendError>,
+        TmSender: TcpTmSender<TmError, TcSendError>,
+        TcParser: TcpTcParser<TmError, TcSendError>,
+        HandledConnection: HandledConnectionHandler,
+        TmError: 'static,
+        TcSendError: 'static,
+    >
+    AsyncTcpTmtcGenericServer<
+        TmSource,
+        TcSender,
+        TmSender,
+        TcParser,
+        HandledConnection,
+        TmError,
+        TcSendError,
+    >
+{
+    /// Create a new asynchronous generic TMTC server instance.
+    ///
+    /// This function must be called from within a [tokio] runtime context with enabled IO
+    /// driver, otherwise it will panic.
+    ///
+    /// ## Parameter
+    ///
+    /// * `cfg` - Configuration of the server.
+    /// * `tc_parser` - Parser which extracts telecommands from the raw bytestream received from
+    ///    the client.
+    /// * `tm_sender` - Encodes telemetry retrieved from the specified TM source.
+    /// * `tm_source` - Generic TM source used by the server to pull telemetry packets which are
+    ///     then sent back to the client.
+    /// * `tc_sender` - Any received telecommand which was decoded successfully will be forwarded
+    ///     using this TC sender.
+    /// * `stop_signal` - Can be used to stop the server even if a connection is ongoing.
+    pub fn new(
+        cfg: ServerConfig,
+        tc_parser: TcParser,
+        tm_sender: TmSender,
+        tm_source: TmSource,
+        tc_sender: TcSender,
+        finished_handler: HandledConnection,
+        stop_signal: Option<Arc<AtomicBool>>,
+    ) -> Result<Self, std::io::Error> {
+        let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
+        socket.set_reuse_address(cfg.reuse_addr)?;
+        #[cfg(unix)]
+        socket.set_reuse_port(cfg.reuse_port)?;
+        // Required by tokio when converting from a standard listener.
+        socket.set_nonblocking(true)?;
+        let addr = (cfg.addr).into();
+        socket.bind(&addr)?;
+        socket.listen(128)?;
+        let listener: std::net::TcpListener = socket.into();
+
+        Ok(Self {
+            id: cfg.id,
+            tc_handler: tc_parser,
+            tm_handler: tm_sender,
+            listener: TcpListener::from_std(listener)?,
+            inner_loop_delay: cfg.inner_loop_delay,
+            tm_source,
+            tm_buffer: vec![0; cfg.tm_buffer_size],
+            tm_send_buffer: Vec::new(),
+            tc_sender,
+            tc_buffer: vec![0; cfg.tc_buffer_size],
+            stop_signal,
+            finished_handler,
+        })
+    }
+
+    /// Retrieve the internal [TcpListener] class.
+    pub fn listener(&mut self) -> &mut TcpListener {
+        &mut self.listener
+    }
+
+    /// Can be used to retrieve the local assigned address of the TCP server. This is especially
+    /// useful if using the port number 0 for OS auto-assignment.
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Asynchronous variant of
+    /// [TcpTmtcGenericServer::handle_all_connections][crate::hal::std::tcp_server::TcpTmtcGenericServer::handle_all_connections].
+    ///
+    /// It waits for a client connection for the optionally specified timeout and then handles
+    /// connections until no further connection can be accepted immediately.
+    pub async fn handle_all_connections(
+        &mut self,
+        accept_timeout: Option<Duration>,
+    ) -> Result<ConnectionResult, TcpTmtcError<TmError, TcSendError>> {
+        let (stream, addr) = match accept_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, self.listener.accept()).await {
+                Ok(result) => result?,
+                Err(_) => return Ok(ConnectionResult::AcceptTimeout),
+            },
+            None => self.listener.accept().await?,
+        };
+        self.handle_accepted_connection(stream, addr).await?;
+        let mut handled_connections = 1;
+        // There might be mutliple connections available. Accept until all of them have
+        // been handled.
+        loop {
+            match tokio::time::timeout(Duration::ZERO, self.listener.accept()).await {
+                Ok(result) => {
+                    let (stream, addr) = result?;
+                    self.handle_accepted_connection(stream, addr).await?;
+                    handled_connections += 1;
+                }
+                Err(_) => break,
+            }
+        }
+        Ok(ConnectionResult::HandledConnections(handled_connections))
+    }
+
+    async fn handle_accepted_connection(
+        &mut self,
+        stream: TcpStream,
+        addr: SocketAddr,
+    ) -> Result<(), TcpTmtcError<TmError, TcSendError>> {
+        let mut connection_result = HandledConnectionInfo::new(addr);
+        let result = self
+            .handle_connection_data(stream, &mut connection_result)
+            .await;
+        connection_result.dropped_by_error = result.is_err();
+        self.finished_handler.handled_connection(connection_result);
+        result
+    }
+
+    async fn handle_connection_data(
+        &mut self,
+        mut stream: TcpStream,
+        connection_result: &mut HandledConnectionInfo,
+    ) -> Result<(), TcpTmtcError<TmError, TcSendError>> {
+        let mut current_write_idx = 0;
+        let mut next_write_idx = 0;
+        loop {
+            let read_result = tokio::time::timeout(
+                self.inner_loop_delay,
+                stream.read(&mut self.tc_buffer[current_write_idx..]),
+            )
+            .await;
+            match read_result {
+                Ok(Ok(0)) => {
+                    // Connection closed by client. If any TC was read, parse for complete packets.
+                    // After that, break the outer loop.
+                    if current_write_idx > 0 {
+                        self.tc_handler.handle_tc_parsing(
+                            &mut self.tc_buffer,
+                            self.id,
+                            &self.tc_sender,
+                            connection_result,
+                            current_write_idx,
+                            &mut next_write_idx,
+                        )?;
+                    }
+                    break;
+                }
+                Ok(Ok(read_len)) => {
+                    current_write_idx += read_len;
+                    // TC buffer is full, we must parse for complete packets now.
+                    if current_write_idx == self.tc_buffer.capacity() {
+                        self.tc_handler.handle_tc_parsing(
+                            &mut self.tc_buffer,
+                            self.id,
+                            &self.tc_sender,
+                            connection_result,
+                            current_write_idx,
+                            &mut next_write_idx,
+                        )?;
+                        current_write_idx = next_write_idx;
+                    }
+                }
+                Ok(Err(e)) => {
+                    return Err(TcpTmtcError::Io(e));
+                }
+                // Read timeout, the client did not send anything during the inner loop delay.
+                Err(_) => {
+                    self.tc_handler.handle_tc_parsing(
+                        &mut self.tc_buffer,
+                        self.id,
+                        &self.tc_sender,
+                        connection_result,
+                        current_write_idx,
+                        &mut next_write_idx,
+                    )?;
+                    current_write_idx = next_write_idx;
+
+                    if !self.send_tm(&mut stream, connection_result).await? {
+                        // Optional stop signal handling.
+                        if self.stop_signal.is_some()
+                            && self.stop_signal.as_ref().unwrap().load(Ordering::Relaxed)
+                        {
+                            connection_result.stopped_by_signal = true;
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+        self.send_tm(&mut stream, connection_result).await?;
+        Ok(())
+    }
+
+    async fn send_tm(
+        &mut self,
+        stream: &mut TcpStream,
+        connection_result: &mut HandledConnectionInfo,
+    ) -> Result<bool, TcpTmtcError<TmError, TcSendError>> {
+        self.tm_send_buffer.clear();
+        let tm_was_sent = self.tm_handler.handle_tm_sending(
+            &mut self.tm_buffer,
+            &mut self.tm_source,
+            connection_result,
+            &mut self.tm_send_buffer,
+        )?;
+        if !self.tm_send_buffer.is_empty() {
+            stream.write_all(&self.tm_send_buffer).await?;
+        }
+        Ok(tm_was_sent)
+    }
+}
+
+/// Asynchronous variant of the
+/// [TcpTmtcInCobsServer][crate::hal::std::tcp_server::TcpTmtcInCobsServer].
+///
+/// Telecommands and telemetry are framed with the
+/// [COBS protocol](https://en.wikipedia.org/wiki/Consistent_Overhead_Byte_Stuffing) and the
+/// sentinel value 0 as the packet delimiter.
+pub struct AsyncTcpTmtcInCobsServer<
+    TmSource: PacketSource<Error = TmError>,
+    TcSender: PacketSenderRaw<Error = SendError>,
+    HandledConnection: HandledConnectionHandler,
+    TmError,
+    SendError: 'static,
+> {
+    pub generic_server: AsyncTcpTmtcGenericServer<
+        TmSource,
+        TcSender,
+        CobsTmSender,
+        CobsTcParser,
+        HandledConnection,
+        TmError,
+        SendError,
+    >,
+}
+
+impl<
+        TmSource: PacketSource<Error = TmError>,
+        TcSender: PacketSenderRaw<Error = TcError>,
+        HandledConnection: HandledConnectionHandler,
+        TmError: 'static,
+        TcError: 'static,
+    > AsyncTcpTmtcInCobsServer<TmSource, TcSender, HandledConnection, TmError, TcError>
+{
+    /// Create a new asynchronous TCP TMTC server which exchanges TMTC packets encoded with
+    /// [COBS protocol](https://en.wikipedia.org/wiki/Consistent_Overhead_Byte_Stuffing).
+    ///
+    /// This function must be called from within a [tokio] runtime context with enabled IO
+    /// driver, otherwise it will panic.
+    ///
+    /// ## Parameter
+    ///
+    /// * `cfg` - Configuration of the server.
+    /// * `tm_source` - Generic TM source used by the server to pull telemetry packets which are
+    ///     then sent back to the client.
+    /// * `tc_sender` - Any received telecommands which were decoded successfully will be
+    ///     forwarded using this TC sender.
+    pub fn new(
+        cfg: ServerConfig,
+        tm_source: TmSource,
+        tc_sender: TcSender,
+        handled_connection: HandledConnection,
+        stop_signal: Option<Arc<AtomicBool>>,
+    ) -> Result<Self, io::Error> {
+        Ok(Self {
+            generic_server: AsyncTcpTmtcGenericServer::new(
+                cfg,
+                CobsTcParser::default(),
+                CobsTmSender::new(cfg.tm_buffer_size),
+                tm_source,
+                tc_sender,
+                handled_connection,
+                stop_signal,
+            )?,
+        })
+    }
+
+    delegate! {
+        to self.generic_server {
+            pub fn listener(&mut self) -> &mut TcpListener;
+
+            /// Can be used to retrieve the local assigned address of the TCP server. This is especially
+            /// useful if using the port number 0 for OS auto-assignment.
+            pub fn local_addr(&self) -> std::io::Result<SocketAddr>;
+        }
+    }
+
+    /// Delegation to the [AsyncTcpTmtcGenericServer::handle_all_connections] call.
+    pub async fn handle_all_connections(
+        &mut self,
+        accept_timeout: Option<Duration>,
+    ) -> Result<ConnectionResult, TcpTmtcError<TmError, TcError>> {
+        self.generic_server
+            .handle_all_connections(accept_timeout)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{sync::atomic::AtomicBool, time::Duration};
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use std::sync::mpsc;
+
+    use alloc::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    use crate::{
+        encoding::{
+            cobs::encode_packet_with_cobs,
+            tests::{INVERTED_PACKET, SIMPLE_PACKET},
+        },
+        hal::std::tcp_server::{
+            tests::{ConnectionFinishedHandler, SyncTmSource},
+            ConnectionResult, ServerConfig,
+        },
+        queue::GenericSendError,
+        tmtc::PacketAsVec,
+        ComponentId,
+    };
+
+    use super::AsyncTcpTmtcInCobsServer;
+
+    const TCP_SERVER_ID: ComponentId = 0x05;
+
+    fn generic_tmtc_server(
+        addr: &SocketAddr,
+        tc_sender: mpsc::Sender<PacketAsVec>,
+        tm_source: SyncTmSource,
+        stop_signal: Option<Arc<AtomicBool>>,
+    ) -> AsyncTcpTmtcInCobsServer<
+        SyncTmSource,
+        mpsc::Sender<PacketAsVec>,
+        ConnectionFinishedHandler,
+        (),
+        GenericSendError,
+    > {
+        AsyncTcpTmtcInCobsServer::new(
+            ServerConfig::new(TCP_SERVER_ID, *addr, Duration::from_millis(2), 1024, 1024),
+            tm_source,
+            tc_sender,
+            ConnectionFinishedHandler::default(),
+            stop_signal,
+        )
+        .expect("TCP server generation failed")
+    }
+
+    #[tokio::test]
+    async fn test_async_server_accept_timeout() {
+        let auto_port_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0);
+        let (tc_sender, _tc_receiver) = mpsc::channel();
+        let mut tcp_server =
+            generic_tmtc_server(&auto_port_addr, tc_sender, SyncTmSource::default(), None);
+        let result = tcp_server
+            .handle_all_connections(Some(Duration::from_millis(20)))
+            .await
+            .expect("handling connections failed");
+        assert_eq!(result, ConnectionResult::AcceptTimeout);
+    }
+
+    #[tokio::test]
+    async fn test_async_server_basic_multi_tm_multi_tc() {
+        let auto_port_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0);
+        let (tc_sender, tc_receiver) = mpsc::channel();
+        let mut tm_source = SyncTmSource::default();
+        tm_source.add_tm(&INVERTED_PACKET);
+        tm_source.add_tm(&SIMPLE_PACKET);
+        let mut tcp_server = generic_tmtc_server(&auto_port_addr, tc_sender, tm_source, None);
+        let dest_addr = tcp_server
+            .local_addr()
+            .expect("retrieving dest addr failed");
+        let server_task = tokio::spawn(async move {
+            let result = tcp_server
+                .handle_all_connections(Some(Duration::from_millis(100)))
+                .await
+                .expect("handling connection failed");
+            assert_eq!(result, ConnectionResult::HandledConnections(1));
+            tcp_server
+                .generic_server
+                .finished_handler
+                .check_last_connection(2, 2);
+        });
+        let mut encoded_buf: [u8; 32] = [0; 32];
+        let mut current_idx = 0;
+        encode_packet_with_cobs(&SIMPLE_PACKET, &mut encoded_buf, &mut current_idx);
+        encode_packet_with_cobs(&INVERTED_PACKET, &mut encoded_buf, &mut current_idx);
+        let mut stream = TcpStream::connect(dest_addr)
+            .await
+            .expect("connecting to TCP server failed");
+        stream
+            .write_all(&encoded_buf[..current_idx])
+            .await
+            .expect("writing to TCP server failed");
+        stream.shutdown().await.expect("shutting down write failed");
+        let mut read_buf: [u8; 16] = [0; 16];
+        stream
+            .read_exact(&mut read_buf)
+            .await
+            .expect("reading TM failed");
+        let mut expected_tm_buf: [u8; 16] = [0; 16];
+        current_idx = 0;
+        encode_packet_with_cobs(&INVERTED_PACKET, &mut expected_tm_buf, &mut current_idx);
+        encode_packet_with_cobs(&SIMPLE_PACKET, &mut expected_tm_buf, &mut current_idx);
+        assert_eq!(read_buf, expected_tm_buf);
+        server_task.await.expect("server task failed");
+
+        let packet_with_sender = tc_receiver.recv().expect("receiving TC failed");
+        assert_eq!(packet_with_sender.packet, &SIMPLE_PACKET);
+        assert_eq!(packet_with_sender.sender_id, TCP_SERVER_ID);
+        let packet_with_sender = tc_receiver.recv().expect("receiving TC failed");
+        assert_eq!(packet_with_sender.packet, &INVERTED_PACKET);
+        matches!(tc_receiver.try_recv(), Err(mpsc::TryRecvError::Empty));
+    }
+}
@@ -0,0 +1,174 @@
+//! Asynchronous UDP TC server based on the [tokio] runtime.
+use crate::hal::std::udp_server::ReceiveResult;
+use crate::tmtc::PacketSenderRaw;
+use crate::ComponentId;
+use core::fmt::Debug;
+use std::io;
+use std::net::SocketAddr;
+use std::vec;
+use std::vec::Vec;
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+/// Asynchronous variant of the [UdpTcServer][crate::hal::std::udp_server::UdpTcServer].
+///
+/// This UDP server can be used to receive CCSDS space packet telecommands or any other telecommand
+/// format. All received telecommands are forwarded to a TC sender which implements
+/// [PacketSenderRaw].
+///
+/// # Examples
+///
+/// ```
+/// # let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+/// # rt.block_on(async {
+/// use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+/// use std::sync::mpsc;
+/// use spacepackets::ecss::WritablePusPacket;
+/// use satrs::hal::std::async_udp_server::AsyncUdpTcServer;
+/// use satrs::ComponentId;
+/// use spacepackets::SpHeader;
+/// use spacepackets::ecss::tc::PusTcCreator;
+///
+/// const UDP_SERVER_ID: ComponentId = 0x05;
+///
+/// let (packet_sender, packet_receiver) = mpsc::channel();
+/// let mut udp_tc_server = AsyncUdpTcServer::new(UDP_SERVER_ID, "127.0.0.1:0", 2048, packet_sender)
+///     .await
+///     .expect("Creating UDP TMTC server failed");
+/// let dest_addr = udp_tc_server.local_addr().unwrap();
+/// let sph = SpHeader::new_from_apid(0x02);
+/// let pus_tc = PusTcCreator::new_simple(sph, 17, 1, &[], true);
+/// let ping_tc_raw = pus_tc.to_vec().unwrap();
+///
+/// let client = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+/// client.send_to(&ping_tc_raw, dest_addr).await.unwrap();
+/// udp_tc_server.recv_tc().await.expect("receiving TC failed");
+/// let sent_packet_with_sender = packet_receiver.try_recv().expect("expected telecommand");
+/// assert_eq!(sent_packet_with_sender.packet, ping_tc_raw);
+/// assert_eq!(sent_packet_with_sender.sender_id, UDP_SERVER_ID);
+/// # })
+/// ```
+pub struct AsyncUdpTcServer<TcSender: PacketSenderRaw<Error = SendError>, SendError> {
+    pub id: ComponentId,
+    pub socket: UdpSocket,
+    recv_buf: Vec<u8>,
+    sender_addr: Option<SocketAddr>,
+    pub tc_sender: TcSender,
+}
+
+impl<TcSender: PacketSenderRaw<Error = SendError>, SendError: Debug + 'static>
+    AsyncUdpTcServer<TcSender, SendError>
+{
+    pub async fn new<A: ToSocketAddrs>(
+        id: ComponentId,
+        addr: A,
+        max_recv_size: usize,
+        tc_sender: TcSender,
+    ) -> Result<Self, io::Error> {
+        Ok(Self {
+            id,
+            socket: UdpSocket::bind(addr).await?,
+            recv_buf: vec![0; max_recv_size],
+            sender_addr: None,
+            tc_sender,
+        })
+    }
+
+    /// Wait for the next telecommand and forward it to the TC sender.
+    pub async fn recv_tc(&mut self) -> Result<(usize, SocketAddr), ReceiveResult<SendError>> {
+        let (num_bytes, from) = self.socket.recv_from(&mut self.recv_buf).await?;
+        self.handle_received_tc(num_bytes, from)
+    }
+
+    /// Non-blocking variant of [Self::recv_tc] which returns [ReceiveResult::NothingReceived] if
+    /// no telecommand is available.
+    pub fn try_recv_tc(&mut self) -> Result<(usize, SocketAddr), ReceiveResult<SendError>> {
+        let (num_bytes, from) = match self.socket.try_recv_from(&mut self.recv_buf) {
+            Ok(res) => res,
+            Err(e) => {
+                return if e.kind() == io::ErrorKind::WouldBlock {
+                    Err(ReceiveResult::NothingReceived)
+                } else {
+                    Err(e.into())
+                }
+            }
+        };
+        self.handle_received_tc(num_bytes, from)
+    }
+
+    fn handle_received_tc(
+        &mut self,
+        num_bytes: usize,
+        from: SocketAddr,
+    ) -> Result<(usize, SocketAddr), ReceiveResult<SendError>> {
+        self.sender_addr = Some(from);
+        self.tc_sender
+            .send_packet(self.id, &self.recv_buf[0..num_bytes])
+            .map_err(ReceiveResult::Send)?;
+        Ok((num_bytes, from))
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn last_sender(&self) -> Option<SocketAddr> {
+        self.sender_addr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncUdpTcServer;
+    use crate::hal::std::udp_server::ReceiveResult;
+    use crate::ComponentId;
+    use spacepackets::ecss::tc::PusTcCreator;
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::SpHeader;
+    use std::sync::mpsc;
+    use tokio::net::UdpSocket;
+
+    const UDP_SERVER_ID: ComponentId = 0x05;
+
+    #[tokio::test]
+    async fn test_basic() {
+        let (tc_tx, tc_rx) = mpsc::channel();
+        let mut udp_tc_server = AsyncUdpTcServer::new(UDP_SERVER_ID, "127.0.0.1:0", 2048, tc_tx)
+            .await
+            .expect("creating UDP TMTC server failed");
+        let dest_addr = udp_tc_server.local_addr().unwrap();
+        let sph = SpHeader::new_from_apid(0x02);
+        let ping_tc = PusTcCreator::new_simple(sph, 17, 1, &[], true)
+            .to_vec()
+            .unwrap();
+        let client = UdpSocket::bind("127.0.0.1:0")
+            .await
+            .expect("creating UDP client failed");
+        client
+            .send_to(&ping_tc, dest_addr)
+            .await
+            .expect("sending PUS TC failed");
+        let (len, _) = udp_tc_server
+            .recv_tc()
+            .await
+            .expect("receiving telecommand failed");
+        assert_eq!(len, ping_tc.len());
+        assert_eq!(
+            udp_tc_server.last_sender().expect("no sender set"),
+            client.local_addr().unwrap()
+        );
+        let packet = tc_rx.try_recv().expect("no packet forwarded");
+        assert_eq!(packet.packet, ping_tc);
+        assert_eq!(packet.sender_id, UDP_SERVER_ID);
+    }
+
+    #[tokio::test]
+    async fn test_nothing_received() {
+        let (tc_tx, _tc_rx) = mpsc::channel();
+        let mut udp_tc_server = AsyncUdpTcServer::new(UDP_SERVER_ID, "127.0.0.1:0", 2048, tc_tx)
+            .await
+            .expect("creating UDP TMTC server failed");
+        let result = udp_tc_server.try_recv_tc();
+        assert!(result.is_err());
+        matches!(result.unwrap_err(), ReceiveResult::NothingReceived);
+    }
+}
@@ -1,6 +1,14 @@
 //! Helper modules intended to be used on systems with a full [std] runtime.
+pub mod tcp_multi_client_server;
 pub mod tcp_server;
 pub mod udp_server;
 
+#[cfg(feature = "tokio")]
+pub mod async_tcp_server;
+#[cfg(feature = "tokio")]
+pub mod async_udp_server;
+#[cfg(feature = "serial")]
+pub mod serial_cobs_server;
+
 mod tcp_cobs_server;
 mod tcp_spacepackets_server;
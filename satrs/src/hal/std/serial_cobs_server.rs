@@ -0,0 +1,566 @@
+//! TMTC server which exchanges COBS encoded packets via a serial (UART) link.
+//!
+//! Many EGSE and flatsat setups use UART links instead of sockets to exchange telecommands and
+//! telemetry. This module provides a server which uses the same
+//! [COBS framing](https://en.wikipedia.org/wiki/Consistent_Overhead_Byte_Stuffing) as the
+//! [TcpTmtcInCobsServer][crate::hal::std::tcp_server::TcpTmtcInCobsServer] and which takes care of
+//! re-opening the serial port if the link is lost, for example because a USB to UART adapter was
+//! unplugged.
+use core::time::Duration;
+use std::io::{self, Read, Write};
+use std::string::String;
+use std::time::Instant;
+use std::vec;
+use std::vec::Vec;
+use thiserror::Error;
+
+use crate::encoding::{encode_packet_with_cobs, parse_buffer_for_cobs_encoded_packets};
+use crate::tmtc::{PacketSenderRaw, PacketSource};
+use crate::ComponentId;
+
+/// Generic abstraction for an object which can open a serial port.
+///
+/// This is used by the [SerialCobsTmtcServer] to (re-)open the serial port. The opened port
+/// should be configured with a read timeout, because the server performs blocking reads.
+pub trait SerialPortProvider {
+    type Port: Read + Write;
+
+    fn open(&mut self) -> io::Result<Self::Port>;
+}
+
+/// Configuration for a serial port which is opened using the [serialport] crate.
+///
+/// ## Parameters
+///
+/// * `port_name` - Name of the port, for example `/dev/ttyUSB0` or `COM3`.
+/// * `baud_rate` - Baud rate of the serial link.
+/// * `read_timeout` - Timeout for read operations on the serial port. This also determines
+///     how long a call to [SerialCobsTmtcServer::periodic_operation] blocks at most if no
+///     telecommands are received.
+#[derive(Debug, Clone)]
+pub struct SerialPortConfig {
+    pub port_name: String,
+    pub baud_rate: u32,
+    pub read_timeout: Duration,
+}
+
+impl SerialPortConfig {
+    pub fn new(port_name: impl Into<String>, baud_rate: u32, read_timeout: Duration) -> Self {
+        Self {
+            port_name: port_name.into(),
+            baud_rate,
+            read_timeout,
+        }
+    }
+}
+
+impl SerialPortProvider for SerialPortConfig {
+    type Port = std::boxed::Box<dyn serialport::SerialPort>;
+
+    fn open(&mut self) -> io::Result<Self::Port> {
+        Ok(serialport::new(&self.port_name, self.baud_rate)
+            .timeout(self.read_timeout)
+            .open()?)
+    }
+}
+
+/// Configuration struct for the [SerialCobsTmtcServer].
+///
+/// ## Parameters
+///
+/// * `id` - Component ID of the server, which is also used as the sender ID for all forwarded
+///     telecommands.
+/// * `reconnect_interval` - Minimum interval between two attempts to (re-)open the serial port.
+/// * `tm_buffer_size` - Size of the TM buffer used to read TM from the [PacketSource]. It should
+///     be large enough to hold the maximum expected TM size.
+/// * `tc_buffer_size` - Size of the TC buffer used to read encoded telecommands. It should be
+///     large enough to hold multiple encoded telecommands.
+#[derive(Debug, Copy, Clone)]
+pub struct SerialServerConfig {
+    pub id: ComponentId,
+    pub reconnect_interval: Duration,
+    pub tm_buffer_size: usize,
+    pub tc_buffer_size: usize,
+}
+
+impl SerialServerConfig {
+    pub fn new(
+        id: ComponentId,
+        reconnect_interval: Duration,
+        tm_buffer_size: usize,
+        tc_buffer_size: usize,
+    ) -> Self {
+        Self {
+            id,
+            reconnect_interval,
+            tm_buffer_size,
+            tc_buffer_size,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SerialTmtcError<TmError, TcError> {
+    #[error("TM retrieval error: {0}")]
+    TmError(TmError),
+    #[error("TC retrieval error: {0}")]
+    TcError(TcError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Result of one [SerialCobsTmtcServer::periodic_operation] call.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SerialHandlingResult {
+    /// The serial port is not open and could not be (re-)opened.
+    Disconnected,
+    Handled {
+        num_received_tcs: u32,
+        num_sent_tms: u32,
+    },
+}
+
+/// Statistics of the serial link.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SerialLinkInfo {
+    pub num_received_tcs: u32,
+    pub num_sent_tms: u32,
+    /// Number of times the serial port was (re-)opened successfully.
+    pub num_connects: u32,
+    /// Number of times the link was lost because of an I/O error.
+    pub num_link_losses: u32,
+}
+
+/// TMTC server which exchanges packets framed with the COBS protocol and the sentinel value 0
+/// via a serial link.
+///
+/// Received telecommands are parsed with [parse_buffer_for_cobs_encoded_packets] and forwarded
+/// using a generic [PacketSenderRaw]. Telemetry is pulled from a [PacketSource], encoded with
+/// [encode_packet_with_cobs] and written to the serial port.
+///
+/// The serial port is opened lazily using a [SerialPortProvider], for example a
+/// [SerialPortConfig]. If any I/O error except a read timeout occurs, the port is closed and the
+/// server will try to re-open it with the configured reconnect interval.
+pub struct SerialCobsTmtcServer<
+    PortProvider: SerialPortProvider,
+    TmSource: PacketSource<Error = TmError>,
+    TcSender: PacketSenderRaw<Error = TcError>,
+    TmError,
+    TcError,
+> {
+    pub id: ComponentId,
+    pub port_provider: PortProvider,
+    pub tm_source: TmSource,
+    pub tc_sender: TcSender,
+    pub link_info: SerialLinkInfo,
+    port: Option<PortProvider::Port>,
+    reconnect_interval: Duration,
+    last_connect_attempt: Option<Instant>,
+    tm_buffer: Vec<u8>,
+    tm_encoding_buffer: Vec<u8>,
+    tc_buffer: Vec<u8>,
+    tc_write_idx: usize,
+}
+
+impl<
+        PortProvider: SerialPortProvider,
+        TmSource: PacketSource<Error = TmError>,
+        TcSender: PacketSenderRaw<Error = TcError>,
+        TmError: 'static,
+        TcError: 'static,
+    > SerialCobsTmtcServer<PortProvider, TmSource, TcSender, TmError, TcError>
+{
+    /// Create a new serial TMTC server. The serial port will be opened on the first call of
+    /// [Self::periodic_operation] or by calling [Self::connect] explicitly.
+    pub fn new(
+        cfg: SerialServerConfig,
+        port_provider: PortProvider,
+        tm_source: TmSource,
+        tc_sender: TcSender,
+    ) -> Self {
+        Self {
+            id: cfg.id,
+            port_provider,
+            tm_source,
+            tc_sender,
+            link_info: SerialLinkInfo::default(),
+            port: None,
+            reconnect_interval: cfg.reconnect_interval,
+            last_connect_attempt: None,
+            tm_buffer: vec![0; cfg.tm_buffer_size],
+            tm_encoding_buffer: vec![0; cobs::max_encoding_length(cfg.tm_buffer_size) + 2],
+            tc_buffer: vec![0; cfg.tc_buffer_size],
+            tc_write_idx: 0,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.port.is_some()
+    }
+
+    /// Try to open the serial port, ignoring the reconnect interval.
+    pub fn connect(&mut self) -> io::Result<()> {
+        self.last_connect_attempt = Some(Instant::now());
+        let port = self.port_provider.open()?;
+        self.port = Some(port);
+        self.tc_write_idx = 0;
+        self.link_info.num_connects += 1;
+        Ok(())
+    }
+
+    /// Close the serial port. It will be re-opened on the next [Self::periodic_operation] call
+    /// after the reconnect interval has passed.
+    pub fn disconnect(&mut self) {
+        self.port = None;
+        self.tc_write_idx = 0;
+    }
+
+    fn handle_link_loss(&mut self) {
+        self.disconnect();
+        self.link_info.num_link_losses += 1;
+    }
+
+    fn reconnect_if_necessary(&mut self) -> bool {
+        if self.port.is_some() {
+            return true;
+        }
+        if let Some(last_attempt) = self.last_connect_attempt {
+            if last_attempt.elapsed() < self.reconnect_interval {
+                return false;
+            }
+        }
+        self.connect().is_ok()
+    }
+
+    /// This function should be called periodically. It performs the following steps:
+    ///
+    /// 1. (Re-)open the serial port if it is not open and the reconnect interval has passed.
+    /// 2. Perform one read operation on the serial port, which blocks for at most the
+    ///    configured read timeout, and forward all complete telecommands.
+    /// 3. Send all telemetry available from the TM source.
+    ///
+    /// I/O errors are returned to the user, but the serial port is closed in that case and the
+    /// next call will try to re-open it.
+    pub fn periodic_operation(
+        &mut self,
+    ) -> Result<SerialHandlingResult, SerialTmtcError<TmError, TcError>> {
+        if !self.reconnect_if_necessary() {
+            return Ok(SerialHandlingResult::Disconnected);
+        }
+        let num_received_tcs = self.handle_tc_reception()?;
+        let num_sent_tms = self.handle_tm_sending()?;
+        Ok(SerialHandlingResult::Handled {
+            num_received_tcs,
+            num_sent_tms,
+        })
+    }
+
+    fn handle_tc_reception(&mut self) -> Result<u32, SerialTmtcError<TmError, TcError>> {
+        let port = self.port.as_mut().unwrap();
+        if self.tc_write_idx == self.tc_buffer.len() {
+            // Buffer is full and does not contain any complete packets. Drop the data.
+            self.tc_write_idx = 0;
+        }
+        match port.read(&mut self.tc_buffer[self.tc_write_idx..]) {
+            Ok(read_len) => self.tc_write_idx += read_len,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => (),
+                _ => {
+                    self.handle_link_loss();
+                    return Err(e.into());
+                }
+            },
+        }
+        if self.tc_write_idx == 0 {
+            return Ok(0);
+        }
+        let mut next_write_idx = 0;
+        let packets_found = parse_buffer_for_cobs_encoded_packets(
+            &mut self.tc_buffer[..self.tc_write_idx],
+            self.id,
+            &self.tc_sender,
+            &mut next_write_idx,
+        )
+        .map_err(|e| SerialTmtcError::TcError(e))?;
+        // If no packet was found, the buffer might contain the start of a packet which was not
+        // received completely yet, so the write index is kept.
+        if packets_found > 0 {
+            self.tc_write_idx = next_write_idx;
+        }
+        self.link_info.num_received_tcs += packets_found;
+        Ok(packets_found)
+    }
+
+    fn handle_tm_sending(&mut self) -> Result<u32, SerialTmtcError<TmError, TcError>> {
+        let mut num_sent_tms = 0;
+        loop {
+            // Write TM until TM source is exhausted.
+            let read_tm_len = self
+                .tm_source
+                .retrieve_packet(&mut self.tm_buffer)
+                .map_err(|e| SerialTmtcError::TmError(e))?;
+            if read_tm_len == 0 {
+                return Ok(num_sent_tms);
+            }
+            let mut encoded_len = 0;
+            encode_packet_with_cobs(
+                &self.tm_buffer[..read_tm_len],
+                &mut self.tm_encoding_buffer,
+                &mut encoded_len,
+            );
+            let port = self.port.as_mut().unwrap();
+            if let Err(e) = port.write_all(&self.tm_encoding_buffer[..encoded_len]) {
+                self.handle_link_loss();
+                return Err(e.into());
+            }
+            num_sent_tms += 1;
+            self.link_info.num_sent_tms += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::collections::VecDeque;
+    use std::io::{self, Read, Write};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::vec::Vec;
+
+    use crate::encoding::{
+        encode_packet_with_cobs,
+        tests::{INVERTED_PACKET, SIMPLE_PACKET},
+    };
+    use crate::hal::std::tcp_server::tests::SyncTmSource;
+    use crate::queue::GenericSendError;
+    use crate::tmtc::PacketAsVec;
+    use crate::ComponentId;
+
+    use super::*;
+
+    const SERIAL_SERVER_ID: ComponentId = 0x05;
+
+    #[derive(Default)]
+    struct LinkState {
+        to_server: VecDeque<u8>,
+        from_server: Vec<u8>,
+        broken: bool,
+    }
+
+    #[derive(Default, Clone)]
+    struct MockPort {
+        state: Arc<Mutex<LinkState>>,
+    }
+
+    impl Read for MockPort {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut state = self.state.lock().unwrap();
+            if state.broken {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            if state.to_server.is_empty() {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            let mut read_len = 0;
+            while read_len < buf.len() {
+                match state.to_server.pop_front() {
+                    Some(byte) => buf[read_len] = byte,
+                    None => break,
+                }
+                read_len += 1;
+            }
+            Ok(read_len)
+        }
+    }
+
+    impl Write for MockPort {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut state = self.state.lock().unwrap();
+            if state.broken {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            state.from_server.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockPortProvider {
+        port: MockPort,
+        available: bool,
+    }
+
+    impl SerialPortProvider for MockPortProvider {
+        type Port = MockPort;
+
+        fn open(&mut self) -> io::Result<Self::Port> {
+            if !self.available {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            self.port.state.lock().unwrap().broken = false;
+            Ok(self.port.clone())
+        }
+    }
+
+    fn generic_server(
+        reconnect_interval: Duration,
+        tm_source: SyncTmSource,
+        tc_sender: mpsc::Sender<PacketAsVec>,
+    ) -> SerialCobsTmtcServer<
+        MockPortProvider,
+        SyncTmSource,
+        mpsc::Sender<PacketAsVec>,
+        (),
+        GenericSendError,
+    > {
+        SerialCobsTmtcServer::new(
+            SerialServerConfig::new(SERIAL_SERVER_ID, reconnect_interval, 64, 64),
+            MockPortProvider {
+                port: MockPort::default(),
+                available: true,
+            },
+            tm_source,
+            tc_sender,
+        )
+    }
+
+    fn send_to_server(server_port: &MockPort, packet: &[u8]) {
+        let mut encoded_buf: [u8; 32] = [0; 32];
+        let mut current_idx = 0;
+        encode_packet_with_cobs(packet, &mut encoded_buf, &mut current_idx);
+        server_port
+            .state
+            .lock()
+            .unwrap()
+            .to_server
+            .extend(&encoded_buf[..current_idx]);
+    }
+
+    #[test]
+    fn test_tc_reception() {
+        let (tc_tx, tc_rx) = mpsc::channel();
+        let mut server = generic_server(Duration::ZERO, SyncTmSource::default(), tc_tx);
+        send_to_server(&server.port_provider.port, &SIMPLE_PACKET);
+        send_to_server(&server.port_provider.port, &INVERTED_PACKET);
+        let result = server.periodic_operation().expect("handling failed");
+        assert_eq!(
+            result,
+            SerialHandlingResult::Handled {
+                num_received_tcs: 2,
+                num_sent_tms: 0
+            }
+        );
+        assert!(server.is_connected());
+        let packet = tc_rx.try_recv().expect("no TC received");
+        assert_eq!(packet.packet, SIMPLE_PACKET);
+        assert_eq!(packet.sender_id, SERIAL_SERVER_ID);
+        let packet = tc_rx.try_recv().expect("no TC received");
+        assert_eq!(packet.packet, INVERTED_PACKET);
+        assert!(tc_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_split_tc_reception() {
+        let (tc_tx, tc_rx) = mpsc::channel();
+        let mut server = generic_server(Duration::ZERO, SyncTmSource::default(), tc_tx);
+        let mut encoded_buf: [u8; 16] = [0; 16];
+        let mut current_idx = 0;
+        encode_packet_with_cobs(&SIMPLE_PACKET, &mut encoded_buf, &mut current_idx);
+        let port = server.port_provider.port.clone();
+        port.state
+            .lock()
+            .unwrap()
+            .to_server
+            .extend(&encoded_buf[..3]);
+        server.periodic_operation().expect("handling failed");
+        assert!(tc_rx.try_recv().is_err());
+        port.state
+            .lock()
+            .unwrap()
+            .to_server
+            .extend(&encoded_buf[3..current_idx]);
+        server.periodic_operation().expect("handling failed");
+        let packet = tc_rx.try_recv().expect("no TC received");
+        assert_eq!(packet.packet, SIMPLE_PACKET);
+    }
+
+    #[test]
+    fn test_tm_sending() {
+        let (tc_tx, _tc_rx) = mpsc::channel();
+        let mut tm_source = SyncTmSource::default();
+        tm_source.add_tm(&SIMPLE_PACKET);
+        let mut server = generic_server(Duration::ZERO, tm_source, tc_tx);
+        let result = server.periodic_operation().expect("handling failed");
+        assert_eq!(
+            result,
+            SerialHandlingResult::Handled {
+                num_received_tcs: 0,
+                num_sent_tms: 1
+            }
+        );
+        let mut expected: [u8; 16] = [0; 16];
+        let mut current_idx = 0;
+        encode_packet_with_cobs(&SIMPLE_PACKET, &mut expected, &mut current_idx);
+        let state = server.port_provider.port.state.lock().unwrap();
+        assert_eq!(state.from_server, &expected[..current_idx]);
+    }
+
+    #[test]
+    fn test_reconnect_handling() {
+        let (tc_tx, tc_rx) = mpsc::channel();
+        let mut server = generic_server(Duration::ZERO, SyncTmSource::default(), tc_tx);
+        server.port_provider.available = false;
+        assert_eq!(
+            server.periodic_operation().unwrap(),
+            SerialHandlingResult::Disconnected
+        );
+        server.port_provider.available = true;
+        server.periodic_operation().expect("handling failed");
+        assert!(server.is_connected());
+        assert_eq!(server.link_info.num_connects, 1);
+
+        // Link is lost.
+        server.port_provider.port.state.lock().unwrap().broken = true;
+        let result = server.periodic_operation();
+        assert!(matches!(result, Err(SerialTmtcError::Io(_))));
+        assert!(!server.is_connected());
+        assert_eq!(server.link_info.num_link_losses, 1);
+
+        // Port is re-opened on the next call.
+        send_to_server(&server.port_provider.port, &SIMPLE_PACKET);
+        let result = server.periodic_operation().expect("handling failed");
+        assert!(server.is_connected());
+        assert_eq!(server.link_info.num_connects, 2);
+        assert_eq!(
+            result,
+            SerialHandlingResult::Handled {
+                num_received_tcs: 1,
+                num_sent_tms: 0
+            }
+        );
+        assert_eq!(tc_rx.try_recv().unwrap().packet, SIMPLE_PACKET);
+    }
+
+    #[test]
+    fn test_reconnect_interval() {
+        let (tc_tx, _tc_rx) = mpsc::channel();
+        let mut server = generic_server(Duration::from_secs(60), SyncTmSource::default(), tc_tx);
+        server.port_provider.available = false;
+        assert_eq!(
+            server.periodic_operation().unwrap(),
+            SerialHandlingResult::Disconnected
+        );
+        server.port_provider.available = true;
+        // Reconnect interval has not passed yet.
+        assert_eq!(
+            server.periodic_operation().unwrap(),
+            SerialHandlingResult::Disconnected
+        );
+        server.connect().expect("explicit connect failed");
+        assert!(server.is_connected());
+    }
+}
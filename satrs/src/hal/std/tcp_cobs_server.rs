@@ -4,7 +4,7 @@ use cobs::encode;
 use core::sync::atomic::AtomicBool;
 use core::time::Duration;
 use delegate::delegate;
-use mio::net::{TcpListener, TcpStream};
+use mio::net::TcpListener;
 use std::io::Write;
 use std::net::SocketAddr;
 use std::vec::Vec;
@@ -14,7 +14,8 @@ use crate::tmtc::PacketSenderRaw;
 use crate::tmtc::PacketSource;
 
 use crate::hal::std::tcp_server::{
-    ConnectionResult, ServerConfig, TcpTcParser, TcpTmSender, TcpTmtcError, TcpTmtcGenericServer,
+    ConnectionResult, ServerConfig, SharedConnectionState, TcpTcParser, TcpTmSender, TcpTmtcError,
+    TcpTmtcGenericServer,
 };
 use crate::ComponentId;
 
@@ -52,7 +53,7 @@ pub struct CobsTmSender {
 }
 
 impl CobsTmSender {
-    fn new(tm_buffer_size: usize) -> Self {
+    pub fn new(tm_buffer_size: usize) -> Self {
         Self {
             // The buffer should be large enough to hold the maximum expected TM size encoded with
             // COBS.
@@ -67,7 +68,7 @@ impl<TmError, TcError> TcpTmSender<TmError, TcError> for CobsTmSender {
         tm_buffer: &mut [u8],
         tm_source: &mut (impl PacketSource<Error = TmError> + ?Sized),
         conn_result: &mut HandledConnectionInfo,
-        stream: &mut TcpStream,
+        stream: &mut (impl Write + ?Sized),
     ) -> Result<bool, TcpTmtcError<TmError, TcError>> {
         let mut tm_was_sent = false;
         loop {
@@ -181,6 +182,9 @@ impl<
             /// useful if using the port number 0 for OS auto-assignment.
             pub fn local_addr(&self) -> std::io::Result<SocketAddr>;
 
+            /// Delegation to the [TcpTmtcGenericServer::connection_state] call.
+            pub fn connection_state(&self) -> SharedConnectionState;
+
             /// Delegation to the [TcpTmtcGenericServer::handle_all_connections] call.
             pub fn handle_all_connections(
                 &mut self,
@@ -207,10 +211,14 @@ mod tests {
 
     use crate::{
         encoding::tests::{INVERTED_PACKET, SIMPLE_PACKET},
+        event_man::EventU32SenderMpsc,
+        events::EventU32,
         hal::std::tcp_server::{
             tests::{ConnectionFinishedHandler, SyncTmSource},
-            ConnectionResult, ServerConfig,
+            ConnectionEventReporter, ConnectionResult, DisconnectReason, ServerConfig,
+            CONNECTION_ESTABLISHED, CONNECTION_LOST,
         },
+        params::{Params, ParamsRaw},
         queue::GenericSendError,
         tmtc::PacketAsVec,
         ComponentId,
@@ -491,4 +499,140 @@ mod tests {
         // No need to drop the connection, the stop signal should take take of everything.
         thread_jh.join().expect("thread join failed");
     }
+
+    #[test]
+    fn test_server_idle_timeout_and_connection_events() {
+        let auto_port_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0);
+        let (tc_sender, _tc_receiver) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        let mut tcp_server = TcpTmtcInCobsServer::new(
+            ServerConfig::new(
+                TCP_SERVER_ID,
+                auto_port_addr,
+                Duration::from_millis(2),
+                1024,
+                1024,
+            )
+            .with_idle_timeout(Duration::from_millis(30))
+            .with_keep_alive(Duration::from_secs(1)),
+            SyncTmSource::default(),
+            tc_sender,
+            ConnectionEventReporter::new(
+                TCP_SERVER_ID,
+                EventU32SenderMpsc::new(0, event_tx),
+                ConnectionFinishedHandler::default(),
+            ),
+            None,
+        )
+        .expect("TCP server generation failed");
+        let dest_addr = tcp_server
+            .local_addr()
+            .expect("retrieving dest addr failed");
+        let connection_state = tcp_server.connection_state();
+        assert!(!connection_state.is_connected());
+        assert!(connection_state.idle_time().is_none());
+        let thread_jh = thread::spawn(move || {
+            let result = tcp_server.handle_all_connections(Some(Duration::from_millis(100)));
+            assert_eq!(result.unwrap(), ConnectionResult::HandledConnections(1));
+            let reporter = &mut tcp_server.generic_server.finished_handler;
+            assert_eq!(reporter.num_send_errors(), 0);
+            reporter.inner.check_last_connection(0, 0);
+            reporter.inner.check_no_connections_left();
+        });
+        // We connect but stay silent, so the server should drop the connection.
+        let start = Instant::now();
+        let mut stream = TcpStream::connect(dest_addr).expect("connecting to TCP server failed");
+        let mut read_buf: [u8; 16] = [0; 16];
+        stream
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .unwrap();
+        assert_eq!(stream.read(&mut read_buf).expect("read failed"), 0);
+        assert!(start.elapsed() >= Duration::from_millis(30));
+        thread_jh.join().expect("thread join failed");
+        assert!(!connection_state.is_connected());
+
+        let established = event_rx
+            .try_recv()
+            .expect("no connection established event");
+        assert_eq!(established.event(), EventU32::from(CONNECTION_ESTABLISHED));
+        assert_eq!(established.sender_id(), TCP_SERVER_ID);
+        let local_port = stream.local_addr().unwrap().port() as u32;
+        assert_eq!(
+            established.params(),
+            Some(&Params::from(ParamsRaw::from((0x7f00_0001, local_port))))
+        );
+        let lost = event_rx.try_recv().expect("no connection lost event");
+        assert_eq!(lost.event(), EventU32::from(CONNECTION_LOST));
+        assert_eq!(
+            lost.params(),
+            Some(&Params::from(ParamsRaw::from((
+                DisconnectReason::IdleTimeout as u32,
+                0
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_server_reports_connection_lost_on_error() {
+        let auto_port_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0);
+        // The TC receiver is dropped, so forwarding the received TC fails.
+        let (tc_sender, tc_receiver) = mpsc::channel();
+        drop(tc_receiver);
+        let (event_tx, event_rx) = mpsc::channel();
+        let mut tcp_server = TcpTmtcInCobsServer::new(
+            ServerConfig::new(
+                TCP_SERVER_ID,
+                auto_port_addr,
+                Duration::from_millis(2),
+                1024,
+                1024,
+            ),
+            SyncTmSource::default(),
+            tc_sender,
+            ConnectionEventReporter::new(
+                TCP_SERVER_ID,
+                EventU32SenderMpsc::new(0, event_tx),
+                ConnectionFinishedHandler::default(),
+            ),
+            None,
+        )
+        .expect("TCP server generation failed");
+        let dest_addr = tcp_server
+            .local_addr()
+            .expect("retrieving dest addr failed");
+        let connection_state = tcp_server.connection_state();
+        let thread_jh = thread::spawn(move || {
+            let result = tcp_server.handle_all_connections(Some(Duration::from_millis(400)));
+            assert!(result.is_err());
+            let reporter = &mut tcp_server.generic_server.finished_handler;
+            reporter.inner.check_last_connection(0, 0);
+            reporter.inner.check_no_connections_left();
+        });
+        let mut stream = TcpStream::connect(dest_addr).expect("connecting to TCP server failed");
+        let mut encoded_buf: [u8; 16] = [0; 16];
+        let mut current_idx = 0;
+        encode_simple_packet(&mut encoded_buf, &mut current_idx);
+        stream
+            .write_all(&encoded_buf[..current_idx])
+            .expect("writing to TCP server failed");
+        stream
+            .shutdown(std::net::Shutdown::Write)
+            .expect("shutting down write failed");
+        thread_jh.join().expect("thread join failed");
+        assert!(!connection_state.is_connected());
+
+        let established = event_rx
+            .try_recv()
+            .expect("no connection established event");
+        assert_eq!(established.event(), EventU32::from(CONNECTION_ESTABLISHED));
+        let lost = event_rx.try_recv().expect("no connection lost event");
+        assert_eq!(lost.event(), EventU32::from(CONNECTION_LOST));
+        assert_eq!(
+            lost.params(),
+            Some(&Params::from(ParamsRaw::from((
+                DisconnectReason::Error as u32,
+                0
+            ))))
+        );
+    }
 }
@@ -0,0 +1,660 @@
+//! TCP TMTC server which serves multiple simultaneous client connections.
+//!
+//! The [TcpTmtcGenericServer][super::tcp_server::TcpTmtcGenericServer] handles one client
+//! connection at a time. The [TcpTmtcMultiClientServer] uses the same [TcpTcParser] and
+//! [TcpTmSender] abstractions, but polls all client connections and the listener with a single
+//! [Poll] instance. This allows connecting a telemetry viewer and a commanding tool at the same
+//! time, for example.
+//!
+//! Telecommands received from any client are forwarded to the TC sender. Each telemetry packet
+//! retrieved from the TM source is sent to all clients accepted by the [TmRoutingRule] of the
+//! server. The [ReplicateTm] rule replicates all telemetry to all clients, and any closure with
+//! the signature `FnMut(&ClientInfo, &[u8]) -> bool` can be used as a custom routing rule.
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::time::Duration;
+use mio::net::TcpStream;
+use mio::{Events, Interest, Poll, Token};
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use super::tcp_server::{
+    configure_client_stream, create_listener, HandledConnectionHandler, HandledConnectionInfo,
+    ServerConfig, TcpTcParser, TcpTmSender, TcpTmtcError,
+};
+use crate::tmtc::{PacketSenderRaw, PacketSource};
+use crate::ComponentId;
+
+/// Identifier of a client connection, which is unique for the lifetime of a server.
+pub type ClientId = usize;
+
+const LISTENER_TOKEN: Token = Token(0);
+
+/// Information about a client connected to the [TcpTmtcMultiClientServer].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub id: ClientId,
+    pub addr: SocketAddr,
+    /// Time at which the connection was established.
+    pub since: Instant,
+}
+
+/// Routing rule which decides which clients receive a telemetry packet.
+pub trait TmRoutingRule {
+    /// Returns whether the telemetry packet should be sent to the given client.
+    fn route_tm(&mut self, client: &ClientInfo, tm: &[u8]) -> bool;
+}
+
+/// [TmRoutingRule] which replicates all telemetry to all clients.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ReplicateTm;
+
+impl TmRoutingRule for ReplicateTm {
+    fn route_tm(&mut self, _client: &ClientInfo, _tm: &[u8]) -> bool {
+        true
+    }
+}
+
+impl<F: FnMut(&ClientInfo, &[u8]) -> bool> TmRoutingRule for F {
+    fn route_tm(&mut self, client: &ClientInfo, tm: &[u8]) -> bool {
+        self(client, tm)
+    }
+}
+
+/// Result of one [TcpTmtcMultiClientServer::handle_connections] call.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct MultiClientHandlingResult {
+    /// Number of newly accepted client connections.
+    pub accepted: u32,
+    /// Number of connections which were rejected because the maximum number of clients was
+    /// reached.
+    pub rejected: u32,
+    /// Number of client connections which were closed or dropped.
+    pub disconnected: u32,
+    /// Number of telemetry packets retrieved from the TM source.
+    pub tm_packets: u32,
+}
+
+/// [PacketSource] adapter which provides a single packet once. It is used to pass replicated
+/// telemetry to the [TcpTmSender] of each client.
+struct SinglePacketSource<'packet, Error> {
+    packet: Option<&'packet [u8]>,
+    phantom: PhantomData<fn() -> Error>,
+}
+
+impl<'packet, Error> SinglePacketSource<'packet, Error> {
+    fn new(packet: &'packet [u8]) -> Self {
+        Self {
+            packet: Some(packet),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Error> PacketSource for SinglePacketSource<'_, Error> {
+    type Error = Error;
+
+    fn retrieve_packet(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        match self.packet.take() {
+            Some(packet) => {
+                buffer[0..packet.len()].copy_from_slice(packet);
+                Ok(packet.len())
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+struct Client {
+    info: ClientInfo,
+    stream: TcpStream,
+    tc_buffer: Vec<u8>,
+    current_write_idx: usize,
+    connection_info: HandledConnectionInfo,
+    last_rx: Instant,
+}
+
+/// TCP TMTC server which serves multiple simultaneous client connections. See the
+/// [module documentation][self] for more details.
+///
+/// In contrast to the generic server, [Self::handle_connections] does not block while a client
+/// is connected. It should be called periodically, for example in a dedicated thread. The
+/// [ServerConfig::idle_timeout] and [ServerConfig::keep_alive] settings apply to each client
+/// connection, and the [HandledConnectionHandler] is notified about each established and
+/// finished connection. The [ServerConfig::inner_loop_delay] is not used, the poll timeout
+/// passed to [Self::handle_connections] determines the delay of each call instead.
+///
+/// Client connections are non-blocking. A client which can not receive telemetry fast enough
+/// is disconnected if its socket buffer is full.
+pub struct TcpTmtcMultiClientServer<
+    TmSource: PacketSource<Error = TmError>,
+    TcSender: PacketSenderRaw<Error = TcSendError>,
+    TmSender: TcpTmSender<TmError, TcSendError>,
+    TcParser: TcpTcParser<TmError, TcSendError>,
+    TmRouter: TmRoutingRule,
+    HandledConnection: HandledConnectionHandler,
+    TmError,
+    TcSendError,
+> {
+    pub id: ComponentId,
+    pub finished_handler: HandledConnection,
+    pub tc_handler: TcParser,
+    pub tm_handler: TmSender,
+    pub tm_router: TmRouter,
+    listener: mio::net::TcpListener,
+    poll: Poll,
+    events: Events,
+    tm_source: TmSource,
+    tm_buffer: Vec<u8>,
+    tm_send_buffer: Vec<u8>,
+    tc_sender: TcSender,
+    tc_buffer_size: usize,
+    max_clients: usize,
+    idle_timeout: Option<Duration>,
+    keep_alive: Option<Duration>,
+    clients: BTreeMap<ClientId, Client>,
+    next_client_id: ClientId,
+    phantom: PhantomData<(TmError, TcSendError)>,
+}
+
+impl<
+        TmSource: PacketSource<Error = TmError>,
+        TcSender: PacketSenderRaw<Error = TcSendError>,
+        TmSender: TcpTmSender<TmError, TcSendError>,
+        TcParser: TcpTcParser<TmError, TcSendError>,
+        TmRouter: TmRoutingRule,
+        HandledConnection: HandledConnectionHandler,
+        TmError: 'static,
+        TcSendError: 'static,
+    >
+    TcpTmtcMultiClientServer<
+        TmSource,
+        TcSender,
+        TmSender,
+        TcParser,
+        TmRouter,
+        HandledConnection,
+        TmError,
+        TcSendError,
+    >
+{
+    /// Create a new multi-client TMTC server instance.
+    ///
+    /// ## Parameter
+    ///
+    /// * `cfg` - Configuration of the server.
+    /// * `max_clients` - Maximum number of simultaneous client connections. Additional
+    ///    connections are closed immediately.
+    /// * `tc_parser` - Parser which extracts telecommands from the raw bytestream received from
+    ///    the clients.
+    /// * `tm_sender` - Encodes and sends telemetry to a client.
+    /// * `tm_router` - Decides which clients receive a telemetry packet.
+    /// * `tm_source` - Generic TM source used by the server to pull telemetry packets.
+    /// * `tc_sender` - Any received telecommand which was decoded successfully will be forwarded
+    ///     using this TC sender.
+    /// * `finished_handler` - Notified about each established and finished client connection.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        cfg: ServerConfig,
+        max_clients: usize,
+        tc_parser: TcParser,
+        tm_sender: TmSender,
+        tm_router: TmRouter,
+        tm_source: TmSource,
+        tc_sender: TcSender,
+        finished_handler: HandledConnection,
+    ) -> Result<Self, std::io::Error> {
+        let mut listener = create_listener(&cfg)?;
+        let poll = Poll::new()?;
+        poll.registry()
+            .register(&mut listener, LISTENER_TOKEN, Interest::READABLE)?;
+        Ok(Self {
+            id: cfg.id,
+            finished_handler,
+            tc_handler: tc_parser,
+            tm_handler: tm_sender,
+            tm_router,
+            listener,
+            poll,
+            events: Events::with_capacity(32),
+            tm_source,
+            tm_buffer: vec![0; cfg.tm_buffer_size],
+            tm_send_buffer: vec![0; cfg.tm_buffer_size],
+            tc_sender,
+            tc_buffer_size: cfg.tc_buffer_size,
+            max_clients,
+            idle_timeout: cfg.idle_timeout,
+            keep_alive: cfg.keep_alive,
+            clients: BTreeMap::new(),
+            next_client_id: 1,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Can be used to retrieve the local assigned address of the TCP server. This is especially
+    /// useful if using the port number 0 for OS auto-assignment.
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn num_clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Iterate over all connected clients.
+    pub fn clients(&self) -> impl Iterator<Item = &ClientInfo> {
+        self.clients.values().map(|client| &client.info)
+    }
+
+    /// Close the connection to the client with the given ID. Returns whether the client was
+    /// connected.
+    pub fn disconnect(&mut self, client_id: ClientId) -> bool {
+        match self.clients.remove(&client_id) {
+            Some(client) => {
+                self.finish_client(client);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Handle all pending client connections. This call performs the following steps:
+    ///
+    /// 1. It polls the listener and all client connections for the given poll timeout.
+    /// 2. New connections are accepted up to the maximum number of clients.
+    /// 3. All telecommands received from the clients are parsed with the [TcpTcParser] and
+    ///    forwarded to the TC sender.
+    /// 4. All telemetry of the TM source is sent to the clients selected by the
+    ///    [TmRoutingRule].
+    /// 5. Connections which were closed by the client, which failed or which exceeded the
+    ///    idle timeout are removed.
+    pub fn handle_connections(
+        &mut self,
+        poll_timeout: Option<Duration>,
+    ) -> Result<MultiClientHandlingResult, TcpTmtcError<TmError, TcSendError>> {
+        let mut result = MultiClientHandlingResult::default();
+        self.poll.poll(&mut self.events, poll_timeout)?;
+        let mut acceptable_connection = false;
+        let mut readable_clients = Vec::new();
+        for event in self.events.iter() {
+            if event.token() == LISTENER_TOKEN {
+                acceptable_connection = true;
+            } else {
+                readable_clients.push(event.token().0);
+            }
+        }
+        if acceptable_connection {
+            self.accept_connections(&mut result)?;
+        }
+        let mut closed_clients = Vec::new();
+        for client_id in readable_clients {
+            if let Some(client) = self.clients.get_mut(&client_id) {
+                let connected =
+                    Self::read_from_client(self.id, &mut self.tc_handler, &self.tc_sender, client)?;
+                if !connected {
+                    closed_clients.push(client_id);
+                }
+            }
+        }
+        self.route_tm(&mut result, &mut closed_clients)?;
+        if let Some(idle_timeout) = self.idle_timeout {
+            for (client_id, client) in self.clients.iter_mut() {
+                if client.last_rx.elapsed() >= idle_timeout && !closed_clients.contains(client_id) {
+                    client.connection_info.dropped_by_idle_timeout = true;
+                    closed_clients.push(*client_id);
+                }
+            }
+        }
+        for client_id in closed_clients {
+            if let Some(client) = self.clients.remove(&client_id) {
+                self.finish_client(client);
+                result.disconnected += 1;
+            }
+        }
+        Ok(result)
+    }
+
+    fn accept_connections(
+        &mut self,
+        result: &mut MultiClientHandlingResult,
+    ) -> Result<(), TcpTmtcError<TmError, TcSendError>> {
+        // There might be mutliple connections available. Accept until all of them have
+        // been handled.
+        loop {
+            match self.listener.accept() {
+                Ok((mut stream, addr)) => {
+                    if self.clients.len() >= self.max_clients {
+                        // Dropping the stream closes the connection.
+                        result.rejected += 1;
+                        continue;
+                    }
+                    configure_client_stream(&stream, self.keep_alive)?;
+                    let id = self.next_client_id;
+                    self.next_client_id += 1;
+                    self.poll
+                        .registry()
+                        .register(&mut stream, Token(id), Interest::READABLE)?;
+                    let now = Instant::now();
+                    self.clients.insert(
+                        id,
+                        Client {
+                            info: ClientInfo {
+                                id,
+                                addr,
+                                since: now,
+                            },
+                            stream,
+                            tc_buffer: vec![0; self.tc_buffer_size],
+                            current_write_idx: 0,
+                            connection_info: HandledConnectionInfo::new(addr),
+                            last_rx: now,
+                        },
+                    );
+                    self.finished_handler.connection_established(addr);
+                    result.accepted += 1;
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(err) => return Err(TcpTmtcError::Io(err)),
+            }
+        }
+    }
+
+    /// Read all available data from a client and parse it for telecommands. Returns whether the
+    /// client is still connected.
+    fn read_from_client(
+        id: ComponentId,
+        tc_handler: &mut TcParser,
+        tc_sender: &TcSender,
+        client: &mut Client,
+    ) -> Result<bool, TcpTmtcError<TmError, TcSendError>> {
+        loop {
+            match client
+                .stream
+                .read(&mut client.tc_buffer[client.current_write_idx..])
+            {
+                Ok(0) => {
+                    // Connection closed by client. Parse the remaining data for complete
+                    // packets.
+                    if client.current_write_idx > 0 {
+                        Self::parse_tcs(id, tc_handler, tc_sender, client)?;
+                    }
+                    return Ok(false);
+                }
+                Ok(read_len) => {
+                    client.last_rx = Instant::now();
+                    client.current_write_idx += read_len;
+                    // TC buffer is full, we must parse for complete packets now.
+                    if client.current_write_idx == client.tc_buffer.len() {
+                        Self::parse_tcs(id, tc_handler, tc_sender, client)?;
+                    }
+                }
+                Err(e) => match e.kind() {
+                    io::ErrorKind::WouldBlock => {
+                        Self::parse_tcs(id, tc_handler, tc_sender, client)?;
+                        return Ok(true);
+                    }
+                    io::ErrorKind::Interrupted => continue,
+                    // Any other error, for example a connection reset, ends the connection of
+                    // this client only.
+                    _ => {
+                        client.connection_info.dropped_by_error = true;
+                        return Ok(false);
+                    }
+                },
+            }
+        }
+    }
+
+    fn parse_tcs(
+        id: ComponentId,
+        tc_handler: &mut TcParser,
+        tc_sender: &TcSender,
+        client: &mut Client,
+    ) -> Result<(), TcpTmtcError<TmError, TcSendError>> {
+        let mut next_write_idx = 0;
+        tc_handler.handle_tc_parsing(
+            &mut client.tc_buffer,
+            id,
+            tc_sender,
+            &mut client.connection_info,
+            client.current_write_idx,
+            &mut next_write_idx,
+        )?;
+        client.current_write_idx = next_write_idx;
+        Ok(())
+    }
+
+    fn route_tm(
+        &mut self,
+        result: &mut MultiClientHandlingResult,
+        closed_clients: &mut Vec<ClientId>,
+    ) -> Result<(), TcpTmtcError<TmError, TcSendError>> {
+        loop {
+            let tm_len = self
+                .tm_source
+                .retrieve_packet(&mut self.tm_buffer)
+                .map_err(|e| TcpTmtcError::TmError(e))?;
+            if tm_len == 0 {
+                return Ok(());
+            }
+            result.tm_packets += 1;
+            let tm = &self.tm_buffer[..tm_len];
+            for (client_id, client) in self.clients.iter_mut() {
+                if closed_clients.contains(client_id) || !self.tm_router.route_tm(&client.info, tm)
+                {
+                    continue;
+                }
+                let mut packet_source = SinglePacketSource::<TmError>::new(tm);
+                if self
+                    .tm_handler
+                    .handle_tm_sending(
+                        &mut self.tm_send_buffer,
+                        &mut packet_source,
+                        &mut client.connection_info,
+                        &mut client.stream,
+                    )
+                    .is_err()
+                {
+                    client.connection_info.dropped_by_error = true;
+                    closed_clients.push(*client_id);
+                }
+            }
+        }
+    }
+
+    fn finish_client(&mut self, mut client: Client) {
+        // The connection is closed when the stream is dropped, so a deregistration error can
+        // be ignored.
+        let _ = self.poll.registry().deregister(&mut client.stream);
+        self.finished_handler
+            .handled_connection(client.connection_info);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{IpAddr, Ipv4Addr, TcpStream};
+    use std::sync::mpsc;
+    use std::thread;
+
+    use cobs::encode;
+
+    use super::*;
+    use crate::encoding::tests::{INVERTED_PACKET, SIMPLE_PACKET};
+    use crate::hal::std::tcp_server::tests::{ConnectionFinishedHandler, SyncTmSource};
+    use crate::hal::std::tcp_server::{CobsTcParser, CobsTmSender};
+    use crate::queue::GenericSendError;
+    use crate::tmtc::PacketAsVec;
+
+    const TCP_SERVER_ID: ComponentId = 0x05;
+
+    type TestServer<TmRouter> = TcpTmtcMultiClientServer<
+        SyncTmSource,
+        mpsc::Sender<PacketAsVec>,
+        CobsTmSender,
+        CobsTcParser,
+        TmRouter,
+        ConnectionFinishedHandler,
+        (),
+        GenericSendError,
+    >;
+
+    fn multi_client_server<TmRouter: TmRoutingRule>(
+        tc_sender: mpsc::Sender<PacketAsVec>,
+        tm_source: SyncTmSource,
+        tm_router: TmRouter,
+        max_clients: usize,
+    ) -> TestServer<TmRouter> {
+        let auto_port_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0);
+        TcpTmtcMultiClientServer::new(
+            ServerConfig::new(
+                TCP_SERVER_ID,
+                auto_port_addr,
+                Duration::from_millis(2),
+                64,
+                64,
+            ),
+            max_clients,
+            CobsTcParser::default(),
+            CobsTmSender::new(64),
+            tm_router,
+            tm_source,
+            tc_sender,
+            ConnectionFinishedHandler::default(),
+        )
+        .expect("TCP server generation failed")
+    }
+
+    fn encoded_packet(packet: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![0; cobs::max_encoding_length(packet.len()) + 2];
+        let len = encode(packet, &mut encoded[1..]);
+        encoded.truncate(len + 2);
+        encoded
+    }
+
+    fn handle_until<TmRouter: TmRoutingRule>(
+        server: &mut TestServer<TmRouter>,
+        mut condition: impl FnMut(&TestServer<TmRouter>) -> bool,
+    ) {
+        let start = Instant::now();
+        while !condition(server) {
+            server
+                .handle_connections(Some(Duration::from_millis(5)))
+                .expect("handling connections failed");
+            if start.elapsed() > Duration::from_millis(500) {
+                panic!("condition not reached in time");
+            }
+        }
+    }
+
+    fn read_exact_with_timeout(stream: &mut TcpStream, len: usize) -> Vec<u8> {
+        stream
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        let mut buf = vec![0; len];
+        stream.read_exact(&mut buf).expect("reading TM failed");
+        buf
+    }
+
+    #[test]
+    fn test_tm_replication_and_tc_forwarding() {
+        let (tc_sender, tc_receiver) = mpsc::channel();
+        let mut tm_source = SyncTmSource::default();
+        let mut server = multi_client_server(tc_sender, tm_source.clone(), ReplicateTm, 4);
+        let dest_addr = server.local_addr().unwrap();
+        let mut viewer = TcpStream::connect(dest_addr).unwrap();
+        let mut commander = TcpStream::connect(dest_addr).unwrap();
+        handle_until(&mut server, |server| server.num_clients() == 2);
+
+        tm_source.add_tm(&SIMPLE_PACKET);
+        let result = server.handle_connections(Some(Duration::ZERO)).unwrap();
+        assert_eq!(result.tm_packets, 1);
+        let expected = encoded_packet(&SIMPLE_PACKET);
+        assert_eq!(
+            read_exact_with_timeout(&mut viewer, expected.len()),
+            expected
+        );
+        assert_eq!(
+            read_exact_with_timeout(&mut commander, expected.len()),
+            expected
+        );
+
+        commander
+            .write_all(&encoded_packet(&INVERTED_PACKET))
+            .unwrap();
+        let start = Instant::now();
+        let tc = loop {
+            server
+                .handle_connections(Some(Duration::from_millis(5)))
+                .unwrap();
+            if let Ok(tc) = tc_receiver.try_recv() {
+                break tc;
+            }
+            if start.elapsed() > Duration::from_millis(500) {
+                panic!("TC was not received");
+            }
+        };
+        assert_eq!(tc.sender_id, TCP_SERVER_ID);
+        assert_eq!(tc.packet, INVERTED_PACKET);
+
+        drop(viewer);
+        handle_until(&mut server, |server| server.num_clients() == 1);
+        server.finished_handler.check_last_connection(1, 0);
+        drop(commander);
+        handle_until(&mut server, |server| server.num_clients() == 0);
+        server.finished_handler.check_last_connection(1, 1);
+        server.finished_handler.check_no_connections_left();
+    }
+
+    #[test]
+    fn test_tm_routing_and_max_clients() {
+        let (tc_sender, _tc_receiver) = mpsc::channel();
+        let mut tm_source = SyncTmSource::default();
+        // Only the first client receives telemetry.
+        let router = |client: &ClientInfo, _tm: &[u8]| client.id == 1;
+        let mut server = multi_client_server(tc_sender, tm_source.clone(), router, 2);
+        let dest_addr = server.local_addr().unwrap();
+        let mut first = TcpStream::connect(dest_addr).unwrap();
+        handle_until(&mut server, |server| server.num_clients() == 1);
+        let mut second = TcpStream::connect(dest_addr).unwrap();
+        handle_until(&mut server, |server| server.num_clients() == 2);
+        assert_eq!(
+            server.clients().map(|client| client.id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        let mut third = TcpStream::connect(dest_addr).unwrap();
+        let result = server
+            .handle_connections(Some(Duration::from_millis(50)))
+            .unwrap();
+        assert_eq!(result.rejected, 1);
+        assert_eq!(server.num_clients(), 2);
+        let mut buf: [u8; 8] = [0; 8];
+        third
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        assert_eq!(third.read(&mut buf).unwrap(), 0);
+
+        tm_source.add_tm(&SIMPLE_PACKET);
+        server.handle_connections(Some(Duration::ZERO)).unwrap();
+        let expected = encoded_packet(&SIMPLE_PACKET);
+        assert_eq!(
+            read_exact_with_timeout(&mut first, expected.len()),
+            expected
+        );
+        second.set_nonblocking(true).unwrap();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(
+            second.read(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        assert!(server.disconnect(1));
+        assert!(!server.disconnect(1));
+        server.finished_handler.check_last_connection(1, 0);
+        assert_eq!(server.num_clients(), 1);
+    }
+}
@@ -6,11 +6,17 @@ use core::sync::atomic::AtomicBool;
 use core::time::Duration;
 use mio::net::{TcpListener, TcpStream};
 use mio::{Events, Interest, Poll, Token};
-use socket2::{Domain, Socket, Type};
-use std::io::{self, Read};
+use socket2::{Domain, SockRef, Socket, TcpKeepalive, Type};
+use std::io::{self, Read, Write};
 use std::net::SocketAddr;
+use std::sync::Mutex;
 use std::thread;
+use std::time::Instant;
 
+use crate::error_report::FRAMEWORK_EVENT_GROUP_ID;
+use crate::event_man::{EventMessage, EventSendProvider};
+use crate::events::{EventU32, SeverityInfo, SeverityLow};
+use crate::params::{Params, ParamsRaw};
 use crate::tmtc::{PacketSenderRaw, PacketSource};
 use crate::ComponentId;
 use thiserror::Error;
@@ -41,6 +47,12 @@ pub use crate::hal::std::tcp_spacepackets_server::{SpacepacketsTmSender, TcpSpac
 /// * `reuse_port` - Can be used to set the `SO_REUSEPORT` option on the raw socket. This is
 ///     especially useful if the address and port are static for the server. Set to false by
 ///     default.
+/// * `idle_timeout` - Optional idle timeout for client connections. A connection is dropped by
+///     the server if no data was received from the client for this period. This allows
+///     distinguishing a silent or dead link from a link where no TC is sent. Disabled by default.
+/// * `keep_alive` - Optional idle time after which TCP keep-alive probes are sent on client
+///     connections. This allows the operating system to detect dead links even if the client
+///     is not expected to send any data. Disabled by default.
 #[derive(Debug, Copy, Clone)]
 pub struct ServerConfig {
     pub id: ComponentId,
@@ -50,6 +62,8 @@ pub struct ServerConfig {
     pub tc_buffer_size: usize,
     pub reuse_addr: bool,
     pub reuse_port: bool,
+    pub idle_timeout: Option<Duration>,
+    pub keep_alive: Option<Duration>,
 }
 
 impl ServerConfig {
@@ -68,6 +82,97 @@ impl ServerConfig {
             tc_buffer_size,
             reuse_addr: true,
             reuse_port: true,
+            idle_timeout: None,
+            keep_alive: None,
+        }
+    }
+
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    pub fn with_keep_alive(mut self, keep_alive: Duration) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+}
+
+crate::event_definitions! {
+    pub TCP_SERVER_EVENT_DEFINITIONS;
+    /// A ground connection to a TCP TMTC server was established.
+    pub CONNECTION_ESTABLISHED: SeverityInfo = (FRAMEWORK_EVENT_GROUP_ID, 4), U32Pair,
+        "Client connected, contains the IPv4 address (0 for IPv6) and the port of the client";
+    /// A ground connection to a TCP TMTC server was closed or dropped.
+    pub CONNECTION_LOST: SeverityLow = (FRAMEWORK_EVENT_GROUP_ID, 5), U32Pair,
+        "Client disconnected, contains the disconnect reason and the number of received TCs";
+}
+
+/// Reason why a client connection of a TCP TMTC server ended.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u32)]
+pub enum DisconnectReason {
+    /// The connection was closed by the client.
+    ClosedByClient = 0,
+    /// The connection was dropped because no data was received within the idle timeout.
+    IdleTimeout = 1,
+    /// The server was stopped using the stop signal.
+    StopSignal = 2,
+    /// The connection was dropped because of an I/O, TC or TM handling error.
+    Error = 3,
+}
+
+/// Current connection state of a TCP TMTC server.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// No client is connected.
+    Disconnected,
+    Connected {
+        addr: SocketAddr,
+        /// Time at which the connection was established.
+        since: Instant,
+        /// Time at which data was last received from the client. This is the time of the
+        /// connection establishment if no data was received yet.
+        last_rx: Instant,
+    },
+}
+
+/// Handle to the [ConnectionState] of a TCP TMTC server which can be shared with other threads.
+/// It is retrieved with [TcpTmtcGenericServer::connection_state].
+#[derive(Debug, Clone)]
+pub struct SharedConnectionState(Arc<Mutex<ConnectionState>>);
+
+impl Default for SharedConnectionState {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(ConnectionState::Disconnected)))
+    }
+}
+
+impl SharedConnectionState {
+    pub fn get(&self) -> ConnectionState {
+        *self.0.lock().unwrap()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        matches!(self.get(), ConnectionState::Connected { .. })
+    }
+
+    /// Time elapsed since data was last received from the connected client. Returns [None] if no
+    /// client is connected.
+    pub fn idle_time(&self) -> Option<Duration> {
+        match self.get() {
+            ConnectionState::Disconnected => None,
+            ConnectionState::Connected { last_rx, .. } => Some(last_rx.elapsed()),
+        }
+    }
+
+    fn set(&self, state: ConnectionState) {
+        *self.0.lock().unwrap() = state;
+    }
+
+    fn update_last_rx(&self) {
+        if let ConnectionState::Connected { last_rx, .. } = &mut *self.0.lock().unwrap() {
+            *last_rx = Instant::now();
         }
     }
 }
@@ -98,6 +203,10 @@ pub struct HandledConnectionInfo {
     /// The generic TCP server can be stopped using an external signal. If this happened, this
     /// boolean will be set to true.
     pub stopped_by_signal: bool,
+    /// Set to true if the connection was dropped because of the configured idle timeout.
+    pub dropped_by_idle_timeout: bool,
+    /// Set to true if the connection was dropped because of an error.
+    pub dropped_by_error: bool,
 }
 
 impl HandledConnectionInfo {
@@ -107,14 +216,107 @@ impl HandledConnectionInfo {
             num_received_tcs: 0,
             num_sent_tms: 0,
             stopped_by_signal: false,
+            dropped_by_idle_timeout: false,
+            dropped_by_error: false,
+        }
+    }
+
+    pub fn disconnect_reason(&self) -> DisconnectReason {
+        if self.dropped_by_error {
+            DisconnectReason::Error
+        } else if self.stopped_by_signal {
+            DisconnectReason::StopSignal
+        } else if self.dropped_by_idle_timeout {
+            DisconnectReason::IdleTimeout
+        } else {
+            DisconnectReason::ClosedByClient
         }
     }
 }
 
 pub trait HandledConnectionHandler {
+    /// Called when a client connection was accepted, before any data is exchanged. The default
+    /// implementation does nothing.
+    fn connection_established(&mut self, _addr: SocketAddr) {}
+
     fn handled_connection(&mut self, info: HandledConnectionInfo);
 }
 
+/// [HandledConnectionHandler] which generates the [CONNECTION_ESTABLISHED] and the
+/// [CONNECTION_LOST] event and forwards all calls to an inner handler.
+///
+/// Errors when sending the events are counted and can be retrieved with
+/// [Self::num_send_errors].
+pub struct ConnectionEventReporter<
+    EventSender: EventSendProvider<EventU32>,
+    Inner: HandledConnectionHandler = (),
+> {
+    pub id: ComponentId,
+    pub event_sender: EventSender,
+    pub inner: Inner,
+    num_send_errors: u32,
+}
+
+impl<EventSender: EventSendProvider<EventU32>, Inner: HandledConnectionHandler>
+    ConnectionEventReporter<EventSender, Inner>
+{
+    /// Create a new event reporter.
+    ///
+    /// ## Parameter
+    ///
+    /// * `id` - Sender ID of the generated events, usually the ID of the TCP server.
+    /// * `event_sender` - Sender for the generated events.
+    /// * `inner` - Inner handler which receives all calls after the event was generated.
+    pub fn new(id: ComponentId, event_sender: EventSender, inner: Inner) -> Self {
+        Self {
+            id,
+            event_sender,
+            inner,
+            num_send_errors: 0,
+        }
+    }
+
+    pub fn num_send_errors(&self) -> u32 {
+        self.num_send_errors
+    }
+
+    fn send_event(&mut self, event: EventU32, params: (u32, u32)) {
+        let params = Params::from(ParamsRaw::from(params));
+        if self
+            .event_sender
+            .send(EventMessage::new_with_params(self.id, event, &params))
+            .is_err()
+        {
+            self.num_send_errors += 1;
+        }
+    }
+}
+
+impl<EventSender: EventSendProvider<EventU32>, Inner: HandledConnectionHandler>
+    HandledConnectionHandler for ConnectionEventReporter<EventSender, Inner>
+{
+    fn connection_established(&mut self, addr: SocketAddr) {
+        let raw_ip = match addr {
+            SocketAddr::V4(addr) => u32::from(*addr.ip()),
+            SocketAddr::V6(_) => 0,
+        };
+        self.send_event(CONNECTION_ESTABLISHED.into(), (raw_ip, addr.port() as u32));
+        self.inner.connection_established(addr);
+    }
+
+    fn handled_connection(&mut self, info: HandledConnectionInfo) {
+        self.send_event(
+            CONNECTION_LOST.into(),
+            (info.disconnect_reason() as u32, info.num_received_tcs),
+        );
+        self.inner.handled_connection(info);
+    }
+}
+
+impl HandledConnectionHandler for () {
+    fn handled_connection(&mut self, _info: HandledConnectionInfo) {}
+}
+
 /// Generic parser abstraction for an object which can parse for telecommands given a raw
 /// bytestream received from a TCP socket and send them using a generic [PacketSenderRaw]
 /// implementation. This allows different encoding schemes for telecommands.
@@ -131,7 +333,8 @@ pub trait TcpTcParser<TmError, SendError> {
 }
 
 /// Generic sender abstraction for an object which can pull telemetry from a given TM source
-/// using a [PacketSource] and then send them back to a client using a given [TcpStream].
+/// using a [PacketSource] and then send them back to a client using a given [Write]
+/// implementation, which generally is the [TcpStream] of the client.
 /// The concrete implementation can also perform any encoding steps which are necessary before
 /// sending back the data to a client.
 pub trait TcpTmSender<TmError, TcError> {
@@ -140,10 +343,38 @@ pub trait TcpTmSender<TmError, TcError> {
         tm_buffer: &mut [u8],
         tm_source: &mut (impl PacketSource<Error = TmError> + ?Sized),
         conn_result: &mut HandledConnectionInfo,
-        stream: &mut TcpStream,
+        stream: &mut (impl Write + ?Sized),
     ) -> Result<bool, TcpTmtcError<TmError, TcError>>;
 }
 
+/// Create the non-blocking TCP listener for the given server configuration.
+pub(crate) fn create_listener(cfg: &ServerConfig) -> io::Result<TcpListener> {
+    // Create a TCP listener bound to two addresses.
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
+
+    socket.set_reuse_address(cfg.reuse_addr)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(cfg.reuse_port)?;
+    // MIO does not do this for us. We want the accept calls to be non-blocking.
+    socket.set_nonblocking(true)?;
+    let addr = (cfg.addr).into();
+    socket.bind(&addr)?;
+    socket.listen(128)?;
+    let listener: std::net::TcpListener = socket.into();
+    Ok(TcpListener::from_std(listener))
+}
+
+/// Apply the optional TCP keep-alive configuration to an accepted client connection.
+pub(crate) fn configure_client_stream(
+    stream: &TcpStream,
+    keep_alive: Option<Duration>,
+) -> io::Result<()> {
+    if let Some(keep_alive) = keep_alive {
+        SockRef::from(stream).set_tcp_keepalive(&TcpKeepalive::new().with_time(keep_alive))?;
+    }
+    Ok(())
+}
+
 /// TCP TMTC server implementation for exchange of generic TMTC packets in a generic way which
 /// stays agnostic to the encoding scheme and format used for both telecommands and telemetry.
 ///
@@ -184,6 +415,9 @@ pub struct TcpTmtcGenericServer<
     pub tc_handler: TcParser,
     pub tm_handler: TmSender,
     stop_signal: Option<Arc<AtomicBool>>,
+    idle_timeout: Option<Duration>,
+    keep_alive: Option<Duration>,
+    connection_state: SharedConnectionState,
 }
 
 impl<
@@ -227,24 +461,11 @@ impl<
         finished_handler: HandledConnection,
         stop_signal: Option<Arc<AtomicBool>>,
     ) -> Result<Self, std::io::Error> {
-        // Create a TCP listener bound to two addresses.
-        let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
-
-        socket.set_reuse_address(cfg.reuse_addr)?;
-        #[cfg(unix)]
-        socket.set_reuse_port(cfg.reuse_port)?;
-        // MIO does not do this for us. We want the accept calls to be non-blocking.
-        socket.set_nonblocking(true)?;
-        let addr = (cfg.addr).into();
-        socket.bind(&addr)?;
-        socket.listen(128)?;
-
+        let mut mio_listener = create_listener(&cfg)?;
         // Create a poll instance.
         let poll = Poll::new()?;
         // Create storage for events.
         let events = Events::with_capacity(32);
-        let listener: std::net::TcpListener = socket.into();
-        let mut mio_listener = TcpListener::from_std(listener);
 
         // Start listening for incoming connections.
         poll.registry().register(
@@ -267,9 +488,19 @@ impl<
             tc_buffer: vec![0; cfg.tc_buffer_size],
             stop_signal,
             finished_handler,
+            idle_timeout: cfg.idle_timeout,
+            keep_alive: cfg.keep_alive,
+            connection_state: SharedConnectionState::default(),
         })
     }
 
+    /// Retrieve a handle to the current [ConnectionState] of the server. The handle can be
+    /// shared with other threads, for example to check whether a ground connection exists while
+    /// the server thread is blocked handling a connection.
+    pub fn connection_state(&self) -> SharedConnectionState {
+        self.connection_state.clone()
+    }
+
     /// Retrieve the internal [TcpListener] class.
     pub fn listener(&mut self) -> &mut TcpListener {
         &mut self.listener
@@ -348,12 +579,33 @@ impl<
 
     fn handle_accepted_connection(
         &mut self,
-        mut stream: TcpStream,
+        stream: TcpStream,
         addr: SocketAddr,
+    ) -> Result<(), TcpTmtcError<TmError, TcSendError>> {
+        configure_client_stream(&stream, self.keep_alive)?;
+        let now = Instant::now();
+        self.connection_state.set(ConnectionState::Connected {
+            addr,
+            since: now,
+            last_rx: now,
+        });
+        self.finished_handler.connection_established(addr);
+        let mut connection_result = HandledConnectionInfo::new(addr);
+        let result = self.handle_connection_data(stream, &mut connection_result);
+        connection_result.dropped_by_error = result.is_err();
+        self.connection_state.set(ConnectionState::Disconnected);
+        self.finished_handler.handled_connection(connection_result);
+        result
+    }
+
+    fn handle_connection_data(
+        &mut self,
+        mut stream: TcpStream,
+        connection_result: &mut HandledConnectionInfo,
     ) -> Result<(), TcpTmtcError<TmError, TcSendError>> {
         let mut current_write_idx;
         let mut next_write_idx = 0;
-        let mut connection_result = HandledConnectionInfo::new(addr);
+        let mut last_rx = Instant::now();
         current_write_idx = next_write_idx;
         loop {
             let read_result = stream.read(&mut self.tc_buffer[current_write_idx..]);
@@ -366,7 +618,7 @@ impl<
                             &mut self.tc_buffer,
                             self.id,
                             &self.tc_sender,
-                            &mut connection_result,
+                            connection_result,
                             current_write_idx,
                             &mut next_write_idx,
                         )?;
@@ -374,6 +626,8 @@ impl<
                     break;
                 }
                 Ok(read_len) => {
+                    last_rx = Instant::now();
+                    self.connection_state.update_last_rx();
                     current_write_idx += read_len;
                     // TC buffer is full, we must parse for complete packets now.
                     if current_write_idx == self.tc_buffer.capacity() {
@@ -381,7 +635,7 @@ impl<
                             &mut self.tc_buffer,
                             self.id,
                             &self.tc_sender,
-                            &mut connection_result,
+                            connection_result,
                             current_write_idx,
                             &mut next_write_idx,
                         )?;
@@ -396,7 +650,7 @@ impl<
                             &mut self.tc_buffer,
                             self.id,
                             &self.tc_sender,
-                            &mut connection_result,
+                            connection_result,
                             current_write_idx,
                             &mut next_write_idx,
                         )?;
@@ -405,7 +659,7 @@ impl<
                         if !self.tm_handler.handle_tm_sending(
                             &mut self.tm_buffer,
                             &mut self.tm_source,
-                            &mut connection_result,
+                            connection_result,
                             &mut stream,
                         )? {
                             // No TC read, no TM was sent, but the client has not disconnected.
@@ -420,7 +674,14 @@ impl<
                                     .load(std::sync::atomic::Ordering::Relaxed)
                             {
                                 connection_result.stopped_by_signal = true;
-                                self.finished_handler.handled_connection(connection_result);
+                                return Ok(());
+                            }
+                        }
+                        if let Some(idle_timeout) = self.idle_timeout {
+                            if last_rx.elapsed() >= idle_timeout {
+                                // The client was silent for too long, the link is considered
+                                // dead.
+                                connection_result.dropped_by_idle_timeout = true;
                                 return Ok(());
                             }
                         }
@@ -434,10 +695,9 @@ impl<
         self.tm_handler.handle_tm_sending(
             &mut self.tm_buffer,
             &mut self.tm_source,
-            &mut connection_result,
+            connection_result,
             &mut stream,
         )?;
-        self.finished_handler.handled_connection(connection_result);
         Ok(())
     }
 }
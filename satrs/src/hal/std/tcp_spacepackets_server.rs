@@ -1,7 +1,7 @@
 use alloc::sync::Arc;
 use core::{sync::atomic::AtomicBool, time::Duration};
 use delegate::delegate;
-use mio::net::{TcpListener, TcpStream};
+use mio::net::TcpListener;
 use std::{io::Write, net::SocketAddr};
 
 use crate::{
@@ -11,8 +11,8 @@ use crate::{
 };
 
 use super::tcp_server::{
-    ConnectionResult, HandledConnectionHandler, HandledConnectionInfo, ServerConfig, TcpTcParser,
-    TcpTmSender, TcpTmtcError, TcpTmtcGenericServer,
+    ConnectionResult, HandledConnectionHandler, HandledConnectionInfo, ServerConfig,
+    SharedConnectionState, TcpTcParser, TcpTmSender, TcpTmtcError, TcpTmtcGenericServer,
 };
 
 impl<T: SpacePacketValidator, TmError, TcError: 'static> TcpTcParser<TmError, TcError> for T {
@@ -53,7 +53,7 @@ impl<TmError, TcError> TcpTmSender<TmError, TcError> for SpacepacketsTmSender {
         tm_buffer: &mut [u8],
         tm_source: &mut (impl PacketSource<Error = TmError> + ?Sized),
         conn_result: &mut HandledConnectionInfo,
-        stream: &mut TcpStream,
+        stream: &mut (impl Write + ?Sized),
     ) -> Result<bool, TcpTmtcError<TmError, TcError>> {
         let mut tm_was_sent = false;
         loop {
@@ -158,6 +158,9 @@ impl<
             /// useful if using the port number 0 for OS auto-assignment.
             pub fn local_addr(&self) -> std::io::Result<SocketAddr>;
 
+            /// Delegation to the [TcpTmtcGenericServer::connection_state] call.
+            pub fn connection_state(&self) -> SharedConnectionState;
+
             /// Delegation to the [TcpTmtcGenericServer::handle_all_connections] call.
             pub fn handle_all_connections(
                 &mut self,
@@ -1,5 +1,8 @@
 use crate::ComponentId;
 
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
 pub type CollectionIntervalFactor = u32;
 /// Unique Identifier for a certain housekeeping dataset.
 pub type UniqueId = u32;
@@ -38,3 +41,835 @@ impl TargetedHkRequest {
         }
     }
 }
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use core::fmt::{Display, Formatter};
+    use spacepackets::ecss::hk;
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::{ByteConversionError, SpHeader};
+    use std::boxed::Box;
+    use std::error::Error;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use std::time::{Duration, Instant};
+    use std::vec;
+    use std::vec::Vec;
+
+    use crate::apid::ApidHandle;
+    use crate::param_db::{ParamDb, ParamDbError, ParamId};
+    use crate::pus::{EcssTmSender, EcssTmtcError, PusTmVariant};
+    use crate::request::UniqueApidTargetId;
+    use crate::tm_suppression::{HkGenerationPolicy, TmSuppressionProvider};
+
+    use super::{CollectionIntervalFactor, HkRequest, HkRequestVariant, UniqueId};
+
+    /// Consistent copy of a housekeeping dataset, tagged with the generation counter of the
+    /// update which produced it.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+    pub struct HkSnapshot<T> {
+        pub generation: u64,
+        pub data: T,
+    }
+
+    /// Double-buffered housekeeping dataset which allows reading snapshot-consistent copies of
+    /// multi-parameter HK sets which are written by other threads.
+    ///
+    /// Writers always update the back buffer and then publish it as a whole by switching the
+    /// active buffer index and incrementing the generation counter. Readers always copy a fully
+    /// published buffer, so a HK packet generated from a [HkSnapshot] reflects one consistent
+    /// point in time and values can not be torn across the set.
+    ///
+    /// ## Blocking behaviour
+    ///
+    /// Every reader only holds a buffer lock for the time required to clone the dataset once.
+    /// A writer locks the published buffer only to copy it into the back buffer, so writer
+    /// blocking is bounded by the clone duration of the readers accessing the dataset at the same
+    /// time. Concurrent writers are serialized.
+    ///
+    /// The user-provided update closure runs on the back buffer, so readers usually do not wait
+    /// for a writer updating the parameters. The buffer is not lock-free for readers though: a
+    /// reader can wait while a writer copies the published buffer, and a reader which is
+    /// preempted between selecting and locking the published buffer can wait for the update
+    /// closure of the next but one writer, which reuses that buffer. Update closures should
+    /// therefore be short.
+    #[derive(Debug)]
+    pub struct HkDatasetSnapshotBuffer<T> {
+        buffers: [Mutex<HkSnapshot<T>>; 2],
+        active_idx: AtomicUsize,
+        writer_lock: Mutex<()>,
+    }
+
+    pub type SharedHkDataset<T> = Arc<HkDatasetSnapshotBuffer<T>>;
+
+    impl<T: Default> Default for HkDatasetSnapshotBuffer<T> {
+        fn default() -> Self {
+            Self {
+                buffers: [
+                    Mutex::new(HkSnapshot::default()),
+                    Mutex::new(HkSnapshot::default()),
+                ],
+                active_idx: AtomicUsize::new(0),
+                writer_lock: Mutex::new(()),
+            }
+        }
+    }
+
+    // The buffers are only published after they were written completely, so a poisoned lock
+    // caused by a panicking writer does not expose inconsistent data to readers.
+    fn lock_ignore_poison<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+        mutex.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    impl<T: Clone> HkDatasetSnapshotBuffer<T> {
+        pub fn new(initial: T) -> Self {
+            Self {
+                buffers: [
+                    Mutex::new(HkSnapshot {
+                        generation: 0,
+                        data: initial.clone(),
+                    }),
+                    Mutex::new(HkSnapshot {
+                        generation: 0,
+                        data: initial,
+                    }),
+                ],
+                active_idx: AtomicUsize::new(0),
+                writer_lock: Mutex::new(()),
+            }
+        }
+
+        pub fn new_shared(initial: T) -> SharedHkDataset<T> {
+            Arc::new(Self::new(initial))
+        }
+
+        /// Replace the whole dataset and publish it. Returns the generation of the published
+        /// dataset.
+        pub fn write(&self, data: T) -> u64 {
+            self.update_with(false, |back| *back = data)
+        }
+
+        /// Update the dataset using a user-provided closure and publish it. The closure receives
+        /// a copy of the last published dataset and can modify any number of parameters before
+        /// the update becomes visible to readers atomically. Returns the generation of the
+        /// published dataset.
+        pub fn update<F: FnOnce(&mut T)>(&self, updater: F) -> u64 {
+            self.update_with(true, updater)
+        }
+
+        fn update_with<F: FnOnce(&mut T)>(&self, copy_active: bool, updater: F) -> u64 {
+            let _writer_guard = lock_ignore_poison(&self.writer_lock);
+            // Only writers switch the active index and writers are serialized, so the active
+            // index can not change until the new buffer is published.
+            let active_idx = self.active_idx.load(Ordering::Acquire);
+            let back_idx = active_idx ^ 1;
+            let mut back = lock_ignore_poison(&self.buffers[back_idx]);
+            let active = lock_ignore_poison(&self.buffers[active_idx]);
+            let generation = active.generation.wrapping_add(1);
+            if copy_active {
+                back.data.clone_from(&active.data);
+            }
+            drop(active);
+            updater(&mut back.data);
+            back.generation = generation;
+            drop(back);
+            self.active_idx.store(back_idx, Ordering::Release);
+            generation
+        }
+
+        /// Retrieve a consistent copy of the last published dataset.
+        pub fn snapshot(&self) -> HkSnapshot<T> {
+            let active_idx = self.active_idx.load(Ordering::Acquire);
+            lock_ignore_poison(&self.buffers[active_idx]).clone()
+        }
+
+        /// Call a user-provided closure with a reference to the last published dataset. This
+        /// avoids a copy of the dataset, for example when serializing it into a HK packet
+        /// directly. The closure should be short, as it delays the next but one writer.
+        pub fn with_snapshot<R, F: FnOnce(&HkSnapshot<T>) -> R>(&self, reader: F) -> R {
+            let active_idx = self.active_idx.load(Ordering::Acquire);
+            reader(&lock_ignore_poison(&self.buffers[active_idx]))
+        }
+
+        /// Generation counter of the last published dataset.
+        pub fn generation(&self) -> u64 {
+            let active_idx = self.active_idx.load(Ordering::Acquire);
+            lock_ignore_poison(&self.buffers[active_idx]).generation
+        }
+    }
+
+    /// Writer closure which serializes a part of a HK set into the provided buffer and returns
+    /// the written length.
+    pub type HkDataProvider =
+        Box<dyn FnMut(&mut [u8]) -> Result<usize, ByteConversionError> + Send>;
+
+    /// Entry of a [HkSetDefinition].
+    pub enum HkSetEntry {
+        /// Parameter of the [ParamDb] of the [HkSetRegistry], which is serialized in its raw
+        /// big endian format.
+        Param(ParamId),
+        Provider(HkDataProvider),
+    }
+
+    /// Definition of a HK set which consists of an ordered list of entries.
+    pub struct HkSetDefinition {
+        set_id: UniqueId,
+        collection_interval: Duration,
+        essential: bool,
+        entries: Vec<HkSetEntry>,
+    }
+
+    impl HkSetDefinition {
+        /// Create a new empty HK set which is collected with the given interval once periodic
+        /// generation was enabled.
+        pub fn new(set_id: UniqueId, collection_interval: Duration) -> Self {
+            Self {
+                set_id,
+                collection_interval,
+                essential: false,
+                entries: Vec::new(),
+            }
+        }
+
+        /// Mark the set as essential. Essential sets are still generated periodically if the
+        /// [HkGenerationPolicy::EssentialOnly] policy is active.
+        pub fn essential(mut self) -> Self {
+            self.essential = true;
+            self
+        }
+
+        pub fn with_param(mut self, param_id: ParamId) -> Self {
+            self.entries.push(HkSetEntry::Param(param_id));
+            self
+        }
+
+        pub fn with_provider(
+            mut self,
+            provider: impl FnMut(&mut [u8]) -> Result<usize, ByteConversionError> + Send + 'static,
+        ) -> Self {
+            self.entries.push(HkSetEntry::Provider(Box::new(provider)));
+            self
+        }
+
+        pub fn set_id(&self) -> UniqueId {
+            self.set_id
+        }
+
+        pub fn collection_interval(&self) -> Duration {
+            self.collection_interval
+        }
+
+        pub fn is_essential(&self) -> bool {
+            self.essential
+        }
+
+        pub fn entries(&self) -> &[HkSetEntry] {
+            &self.entries
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum HkSetError {
+        UnknownSet(UniqueId),
+        DuplicateSet(UniqueId),
+        /// The collection interval factor must be larger than 0.
+        InvalidCollectionInterval(CollectionIntervalFactor),
+        Param(ParamDbError),
+        ByteConversion(ByteConversionError),
+        Send(EcssTmtcError),
+    }
+
+    impl Display for HkSetError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                HkSetError::UnknownSet(set_id) => write!(f, "unknown HK set {set_id}"),
+                HkSetError::DuplicateSet(set_id) => write!(f, "duplicate HK set {set_id}"),
+                HkSetError::InvalidCollectionInterval(factor) => {
+                    write!(f, "invalid collection interval factor {factor}")
+                }
+                HkSetError::Param(e) => write!(f, "parameter error: {e}"),
+                HkSetError::ByteConversion(e) => write!(f, "byte conversion error: {e}"),
+                HkSetError::Send(e) => write!(f, "sending HK packet failed: {e}"),
+            }
+        }
+    }
+
+    impl From<ParamDbError> for HkSetError {
+        fn from(value: ParamDbError) -> Self {
+            Self::Param(value)
+        }
+    }
+
+    impl From<ByteConversionError> for HkSetError {
+        fn from(value: ByteConversionError) -> Self {
+            Self::ByteConversion(value)
+        }
+    }
+
+    impl From<EcssTmtcError> for HkSetError {
+        fn from(value: EcssTmtcError) -> Self {
+            Self::Send(value)
+        }
+    }
+
+    impl Error for HkSetError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                HkSetError::Param(e) => Some(e),
+                HkSetError::ByteConversion(e) => Some(e),
+                HkSetError::Send(e) => Some(e),
+                _ => None,
+            }
+        }
+    }
+
+    struct HkSet {
+        definition: HkSetDefinition,
+        periodic: bool,
+        next_collection: Option<Instant>,
+    }
+
+    /// Registry of the HK sets of one component which packs the sets into PUS TM[3,25] HK
+    /// report packets.
+    ///
+    /// The source data of a HK report starts with the unique ID of the component and the set ID,
+    /// both as big endian [u32] values, followed by the entries of the set. The registry handles
+    /// all [HkRequestVariant]s, so a component only needs to forward its HK requests with
+    /// [Self::handle_request] and to call [Self::send_due_sets] periodically. The collection
+    /// interval factor of a [HkRequestVariant::ModifyCollectionInterval] request is a multiple of
+    /// the base interval of the registry.
+    ///
+    /// Periodic HK reports can be suppressed or reduced depending on the system mode by
+    /// configuring a [TmSuppressionProvider] with [Self::with_tm_suppression].
+    pub struct HkSetRegistry {
+        target_id: UniqueApidTargetId,
+        apid: ApidHandle,
+        base_interval: Duration,
+        param_db: Option<Arc<ParamDb>>,
+        tm_suppression: Option<Box<dyn TmSuppressionProvider + Send>>,
+        sets: Vec<HkSet>,
+        buf: Vec<u8>,
+    }
+
+    impl HkSetRegistry {
+        /// Create a new registry without any sets.
+        ///
+        /// ## Parameter
+        ///
+        /// * `target_id` - ID of the component. The APID is used for the generated packets.
+        /// * `base_interval` - Base interval used for collection interval factors.
+        /// * `max_set_len` - Maximum length of the source data of a HK report.
+        pub fn new(
+            target_id: UniqueApidTargetId,
+            base_interval: Duration,
+            max_set_len: usize,
+        ) -> Self {
+            Self {
+                target_id,
+                apid: ApidHandle::Fixed(target_id.apid),
+                base_interval,
+                param_db: None,
+                tm_suppression: None,
+                sets: Vec::new(),
+                buf: vec![0; max_set_len],
+            }
+        }
+
+        /// Set the parameter database which is used for [HkSetEntry::Param] entries.
+        pub fn with_param_db(mut self, param_db: Arc<ParamDb>) -> Self {
+            self.param_db = Some(param_db);
+            self
+        }
+
+        /// Set the APID used for the generated packets, for example a [crate::apid::SharedApid]
+        /// which can be changed at run-time. The APID of the target ID is used by default.
+        pub fn with_apid_handle(mut self, apid: impl Into<ApidHandle>) -> Self {
+            self.apid = apid.into();
+            self
+        }
+
+        /// Set the provider of the TM generation policy which is applied to periodic HK reports.
+        /// One-shot reports requested explicitly are never suppressed.
+        pub fn with_tm_suppression(
+            mut self,
+            provider: impl TmSuppressionProvider + Send + 'static,
+        ) -> Self {
+            self.tm_suppression = Some(Box::new(provider));
+            self
+        }
+
+        pub fn add_set(&mut self, definition: HkSetDefinition) -> Result<(), HkSetError> {
+            if self.set(definition.set_id).is_some() {
+                return Err(HkSetError::DuplicateSet(definition.set_id));
+            }
+            self.sets.push(HkSet {
+                definition,
+                periodic: false,
+                next_collection: None,
+            });
+            Ok(())
+        }
+
+        pub fn num_sets(&self) -> usize {
+            self.sets.len()
+        }
+
+        pub fn is_periodic(&self, set_id: UniqueId) -> Option<bool> {
+            self.set(set_id).map(|set| set.periodic)
+        }
+
+        pub fn collection_interval(&self, set_id: UniqueId) -> Option<Duration> {
+            self.set(set_id)
+                .map(|set| set.definition.collection_interval)
+        }
+
+        fn set(&self, set_id: UniqueId) -> Option<&HkSet> {
+            self.sets.iter().find(|set| set.definition.set_id == set_id)
+        }
+
+        fn set_mut(&mut self, set_id: UniqueId) -> Result<&mut HkSet, HkSetError> {
+            self.sets
+                .iter_mut()
+                .find(|set| set.definition.set_id == set_id)
+                .ok_or(HkSetError::UnknownSet(set_id))
+        }
+
+        /// Handle a HK request. A HK report is sent immediately for one-shot requests.
+        pub fn handle_request(
+            &mut self,
+            request: &HkRequest,
+            timestamp: &[u8],
+            tm_sender: &(impl EcssTmSender + ?Sized),
+        ) -> Result<(), HkSetError> {
+            let base_interval = self.base_interval;
+            let set = self.set_mut(request.unique_id)?;
+            match request.variant {
+                HkRequestVariant::OneShot => {
+                    return self.send_set(request.unique_id, timestamp, tm_sender);
+                }
+                HkRequestVariant::EnablePeriodic => {
+                    set.periodic = true;
+                    set.next_collection = None;
+                }
+                HkRequestVariant::DisablePeriodic => set.periodic = false,
+                HkRequestVariant::ModifyCollectionInterval(factor) => {
+                    if factor == 0 {
+                        return Err(HkSetError::InvalidCollectionInterval(factor));
+                    }
+                    set.definition.collection_interval = base_interval * factor;
+                }
+            }
+            Ok(())
+        }
+
+        /// Pack the source data of the HK report of the given set into the provided buffer.
+        /// Returns the written length.
+        pub fn pack_set(&mut self, set_id: UniqueId, buf: &mut [u8]) -> Result<usize, HkSetError> {
+            let target_id = self.target_id.unique_id;
+            let param_db = self.param_db.as_deref();
+            let set = self
+                .sets
+                .iter_mut()
+                .find(|set| set.definition.set_id == set_id)
+                .ok_or(HkSetError::UnknownSet(set_id))?;
+            if buf.len() < 8 {
+                return Err(ByteConversionError::ToSliceTooSmall {
+                    found: buf.len(),
+                    expected: 8,
+                }
+                .into());
+            }
+            buf[0..4].copy_from_slice(&target_id.to_be_bytes());
+            buf[4..8].copy_from_slice(&set_id.to_be_bytes());
+            let mut current_idx = 8;
+            for entry in &mut set.definition.entries {
+                current_idx += match entry {
+                    HkSetEntry::Param(param_id) => param_db
+                        .ok_or(ParamDbError::UnknownParam(*param_id))?
+                        .write_to_be_bytes(*param_id, &mut buf[current_idx..])?,
+                    HkSetEntry::Provider(provider) => provider(&mut buf[current_idx..])?,
+                };
+            }
+            Ok(current_idx)
+        }
+
+        /// Generate the HK report of the given set and send it.
+        pub fn send_set(
+            &mut self,
+            set_id: UniqueId,
+            timestamp: &[u8],
+            tm_sender: &(impl EcssTmSender + ?Sized),
+        ) -> Result<(), HkSetError> {
+            let mut buf = core::mem::take(&mut self.buf);
+            let result = self.pack_set(set_id, &mut buf).and_then(|len| {
+                let sec_header =
+                    PusTmSecondaryHeader::new(3, hk::Subservice::TmHkPacket as u8, 0, 0, timestamp);
+                let tm = PusTmCreator::new(
+                    SpHeader::new_from_apid(self.apid.apid()),
+                    sec_header,
+                    &buf[..len],
+                    true,
+                );
+                tm_sender
+                    .send_tm(self.target_id.id(), PusTmVariant::Direct(tm))
+                    .map_err(HkSetError::from)
+            });
+            self.buf = buf;
+            result
+        }
+
+        /// Send the HK reports of all periodic sets which are due at the given time. The first
+        /// report of a set is sent immediately after periodic generation was enabled.
+        ///
+        /// Returns the number of sent reports. All due sets are handled even if one of them
+        /// fails, and the first error is returned in that case. Sets which are suppressed by the
+        /// active [HkGenerationPolicy] are skipped and sent immediately once they are enabled
+        /// again.
+        pub fn send_due_sets(
+            &mut self,
+            now: Instant,
+            timestamp: &[u8],
+            tm_sender: &(impl EcssTmSender + ?Sized),
+        ) -> Result<usize, HkSetError> {
+            let mut num_sent = 0;
+            let mut result = Ok(());
+            let hk_policy = match &self.tm_suppression {
+                Some(provider) => provider.tm_policy().hk,
+                None => HkGenerationPolicy::All,
+            };
+            for idx in 0..self.sets.len() {
+                let set = &mut self.sets[idx];
+                if !set.periodic
+                    || !hk_policy.set_enabled(set.definition.essential)
+                    || set.next_collection.is_some_and(|next| next > now)
+                {
+                    continue;
+                }
+                let interval = set.definition.collection_interval * hk_policy.interval_factor();
+                let next_collection = match set.next_collection {
+                    Some(next) if next + interval > now => next + interval,
+                    _ => now + interval,
+                };
+                set.next_collection = Some(next_collection);
+                let set_id = set.definition.set_id;
+                match self.send_set(set_id, timestamp, tm_sender) {
+                    Ok(()) => num_sent += 1,
+                    Err(e) => {
+                        if result.is_ok() {
+                            result = Err(e);
+                        }
+                    }
+                }
+            }
+            result.map(|_| num_sent)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Severity;
+    use crate::mode::ModeAndSubmode;
+    use crate::param_db::{ParamDb, ParamId};
+    use crate::request::UniqueApidTargetId;
+    use crate::tm_suppression::{
+        HkGenerationPolicy, ModeTmPolicyTable, SystemModeObserver, TmGenerationPolicy,
+    };
+    use crate::tmtc::PacketAsVec;
+    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::PusPacket;
+    use spacepackets::CcsdsPacket;
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use std::vec::Vec;
+
+    #[derive(Debug, Default, Copy, Clone, PartialEq)]
+    struct TestSet {
+        val_0: u32,
+        val_1: u32,
+        val_2: u32,
+    }
+
+    #[test]
+    fn test_snapshot_basic() {
+        let dataset = HkDatasetSnapshotBuffer::new(TestSet::default());
+        assert_eq!(dataset.generation(), 0);
+        assert_eq!(dataset.snapshot().data, TestSet::default());
+        let generation = dataset.update(|set| set.val_0 = 5);
+        assert_eq!(generation, 1);
+        let snapshot = dataset.snapshot();
+        assert_eq!(snapshot.generation, 1);
+        assert_eq!(snapshot.data.val_0, 5);
+        assert_eq!(snapshot.data.val_1, 0);
+    }
+
+    #[test]
+    fn test_update_keeps_previous_values() {
+        let dataset = HkDatasetSnapshotBuffer::new(TestSet::default());
+        dataset.update(|set| set.val_0 = 1);
+        dataset.update(|set| set.val_1 = 2);
+        dataset.update(|set| set.val_2 = 3);
+        let snapshot = dataset.snapshot();
+        assert_eq!(snapshot.generation, 3);
+        assert_eq!(
+            snapshot.data,
+            TestSet {
+                val_0: 1,
+                val_1: 2,
+                val_2: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_write_and_with_snapshot() {
+        let dataset = HkDatasetSnapshotBuffer::<TestSet>::default();
+        let new_set = TestSet {
+            val_0: 10,
+            val_1: 20,
+            val_2: 30,
+        };
+        assert_eq!(dataset.write(new_set), 1);
+        let sum = dataset.with_snapshot(|snapshot| {
+            snapshot.data.val_0 + snapshot.data.val_1 + snapshot.data.val_2
+        });
+        assert_eq!(sum, 60);
+    }
+
+    #[test]
+    fn test_snapshot_consistency_multi_threaded() {
+        let dataset = HkDatasetSnapshotBuffer::new_shared(TestSet::default());
+        let writer_dataset = Arc::clone(&dataset);
+        let writer = thread::spawn(move || {
+            for i in 1..=1000 {
+                writer_dataset.update(|set| {
+                    set.val_0 = i;
+                    set.val_1 = i;
+                    set.val_2 = i;
+                });
+            }
+        });
+        let mut last_generation = 0;
+        for _ in 0..1000 {
+            let snapshot = dataset.snapshot();
+            assert_eq!(snapshot.data.val_0, snapshot.data.val_1);
+            assert_eq!(snapshot.data.val_1, snapshot.data.val_2);
+            assert_eq!(snapshot.data.val_0 as u64, snapshot.generation);
+            assert!(snapshot.generation >= last_generation);
+            last_generation = snapshot.generation;
+        }
+        writer.join().unwrap();
+        assert_eq!(dataset.generation(), 1000);
+    }
+
+    const TARGET_ID: UniqueApidTargetId = UniqueApidTargetId::new(0x02, 0x10);
+    const TEST_PARAM: ParamId = ParamId::new(1, 1);
+    const TIMESTAMP: [u8; 7] = [0; 7];
+    const MODE_NORMAL: u32 = 1;
+    const MODE_SAFE: u32 = 2;
+    const MODE_REDUCED: u32 = 3;
+
+    fn create_registry() -> HkSetRegistry {
+        let param_db = Arc::new(ParamDb::default());
+        param_db.register(TEST_PARAM, 0x0102_u16).unwrap();
+        let mut registry =
+            HkSetRegistry::new(TARGET_ID, Duration::from_millis(100), 64).with_param_db(param_db);
+        registry
+            .add_set(
+                HkSetDefinition::new(1, Duration::from_millis(100))
+                    .with_param(TEST_PARAM)
+                    .with_provider(|buf: &mut [u8]| {
+                        buf[0] = 5;
+                        Ok(1)
+                    }),
+            )
+            .unwrap();
+        registry
+    }
+
+    fn check_hk_report(packet: &PacketAsVec, set_id: UniqueId) -> Vec<u8> {
+        assert_eq!(packet.sender_id, TARGET_ID.id());
+        let (tm, _) = PusTmReader::new(&packet.packet, TIMESTAMP.len()).unwrap();
+        assert_eq!(tm.apid(), TARGET_ID.apid);
+        assert_eq!(tm.service(), 3);
+        assert_eq!(tm.subservice(), 25);
+        let source_data = tm.user_data();
+        assert_eq!(source_data[0..4], TARGET_ID.unique_id.to_be_bytes());
+        assert_eq!(source_data[4..8], set_id.to_be_bytes());
+        source_data[8..].to_vec()
+    }
+
+    #[test]
+    fn test_hk_set_packing() {
+        let mut registry = create_registry();
+        assert_eq!(registry.num_sets(), 1);
+        let mut buf = [0; 16];
+        assert_eq!(registry.pack_set(1, &mut buf).unwrap(), 11);
+        assert_eq!(buf[8..11], [0x01, 0x02, 5]);
+        assert_eq!(
+            registry.pack_set(2, &mut buf),
+            Err(HkSetError::UnknownSet(2))
+        );
+        assert_eq!(
+            registry.add_set(HkSetDefinition::new(1, Duration::from_secs(1))),
+            Err(HkSetError::DuplicateSet(1))
+        );
+    }
+
+    #[test]
+    fn test_one_shot_request() {
+        let mut registry = create_registry();
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        registry
+            .handle_request(
+                &HkRequest::new(1, HkRequestVariant::OneShot),
+                &TIMESTAMP,
+                &tm_tx,
+            )
+            .unwrap();
+        let packet = tm_rx.try_recv().expect("no HK report");
+        assert_eq!(check_hk_report(&packet, 1), [0x01, 0x02, 5]);
+        assert!(tm_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_periodic_generation() {
+        let mut registry = create_registry();
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let start = Instant::now();
+        assert_eq!(
+            registry.send_due_sets(start, &TIMESTAMP, &tm_tx).unwrap(),
+            0
+        );
+        registry
+            .handle_request(
+                &HkRequest::new(1, HkRequestVariant::EnablePeriodic),
+                &TIMESTAMP,
+                &tm_tx,
+            )
+            .unwrap();
+        assert_eq!(registry.is_periodic(1), Some(true));
+        assert_eq!(
+            registry.send_due_sets(start, &TIMESTAMP, &tm_tx).unwrap(),
+            1
+        );
+        assert_eq!(
+            registry
+                .send_due_sets(start + Duration::from_millis(50), &TIMESTAMP, &tm_tx)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            registry
+                .send_due_sets(start + Duration::from_millis(100), &TIMESTAMP, &tm_tx)
+                .unwrap(),
+            1
+        );
+        assert_eq!(tm_rx.try_iter().count(), 2);
+
+        registry
+            .handle_request(
+                &HkRequest::new(1, HkRequestVariant::ModifyCollectionInterval(3)),
+                &TIMESTAMP,
+                &tm_tx,
+            )
+            .unwrap();
+        assert_eq!(
+            registry.collection_interval(1),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(
+            registry.handle_request(
+                &HkRequest::new(1, HkRequestVariant::ModifyCollectionInterval(0)),
+                &TIMESTAMP,
+                &tm_tx,
+            ),
+            Err(HkSetError::InvalidCollectionInterval(0))
+        );
+        registry
+            .handle_request(
+                &HkRequest::new(1, HkRequestVariant::DisablePeriodic),
+                &TIMESTAMP,
+                &tm_tx,
+            )
+            .unwrap();
+        assert_eq!(
+            registry
+                .send_due_sets(start + Duration::from_secs(1), &TIMESTAMP, &tm_tx)
+                .unwrap(),
+            0
+        );
+        assert!(tm_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_periodic_generation_with_tm_suppression() {
+        let observer = SystemModeObserver::new(
+            ModeAndSubmode::new_mode_only(MODE_NORMAL),
+            ModeTmPolicyTable::new(TmGenerationPolicy::ALL)
+                .with_policy(
+                    MODE_SAFE,
+                    TmGenerationPolicy::new(Severity::High, HkGenerationPolicy::EssentialOnly),
+                )
+                .with_policy(
+                    MODE_REDUCED,
+                    TmGenerationPolicy::new(
+                        Severity::Info,
+                        HkGenerationPolicy::Reduced { interval_factor: 2 },
+                    ),
+                ),
+        );
+        let mut registry = create_registry().with_tm_suppression(observer.clone());
+        registry
+            .add_set(HkSetDefinition::new(2, Duration::from_millis(100)).essential())
+            .unwrap();
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        for set_id in [1, 2] {
+            registry
+                .handle_request(
+                    &HkRequest::new(set_id, HkRequestVariant::EnablePeriodic),
+                    &TIMESTAMP,
+                    &tm_tx,
+                )
+                .unwrap();
+        }
+        let start = Instant::now();
+        observer.set_mode(ModeAndSubmode::new_mode_only(MODE_SAFE));
+        assert_eq!(
+            registry.send_due_sets(start, &TIMESTAMP, &tm_tx).unwrap(),
+            1
+        );
+        assert!(check_hk_report(&tm_rx.try_recv().unwrap(), 2).is_empty());
+
+        // The non-essential set is sent immediately once it is enabled again. The reduced policy
+        // doubles the collection interval.
+        observer.set_mode(ModeAndSubmode::new_mode_only(MODE_REDUCED));
+        let now = start + Duration::from_millis(100);
+        assert_eq!(registry.send_due_sets(now, &TIMESTAMP, &tm_tx).unwrap(), 2);
+        assert_eq!(
+            registry
+                .send_due_sets(now + Duration::from_millis(100), &TIMESTAMP, &tm_tx)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            registry
+                .send_due_sets(now + Duration::from_millis(200), &TIMESTAMP, &tm_tx)
+                .unwrap(),
+            2
+        );
+
+        // One-shot reports are never suppressed.
+        observer.set_mode(ModeAndSubmode::new_mode_only(MODE_SAFE));
+        registry
+            .handle_request(
+                &HkRequest::new(1, HkRequestVariant::OneShot),
+                &TIMESTAMP,
+                &tm_tx,
+            )
+            .unwrap();
+        assert_eq!(tm_rx.try_iter().count(), 5);
+    }
+}
@@ -0,0 +1,269 @@
+//! # Component ID registry
+//!
+//! Every component which sends or receives messages is identified by a [ComponentId]. These IDs
+//! are usually assigned by hand, for example as [UniqueApidTargetId] constants, and a duplicate
+//! ID leads to misrouted messages or replies which are hard to debug.
+//!
+//! The [ComponentIdRegistry] keeps track of all used IDs together with a human readable name.
+//! Fixed IDs are registered with [ComponentIdRegistry::register], and unique IDs for components
+//! which do not require a fixed ID can be allocated with [ComponentIdRegistry::allocate].
+//! Collisions of IDs or names are reported as a [ComponentIdError], so they are caught when the
+//! application is set up.
+use core::fmt::{Display, Formatter};
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::request::UniqueApidTargetId;
+use crate::ComponentId;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ComponentIdError {
+    /// The ID was already registered with the name `first`.
+    DuplicateId {
+        id: ComponentId,
+        first: &'static str,
+        second: &'static str,
+    },
+    /// The name was already registered for the ID `first_id`.
+    DuplicateName {
+        name: &'static str,
+        first_id: ComponentId,
+    },
+    /// No free ID is left for allocation.
+    Exhausted,
+}
+
+impl Display for ComponentIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ComponentIdError::DuplicateId { id, first, second } => {
+                write!(
+                    f,
+                    "component ID {id:#x} of {second} already used by {first}"
+                )
+            }
+            ComponentIdError::DuplicateName { name, first_id } => {
+                write!(f, "component name {name} already used by ID {first_id:#x}")
+            }
+            ComponentIdError::Exhausted => write!(f, "no free component ID left"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ComponentIdError {}
+
+/// Registry of all [ComponentId]s of an application.
+#[derive(Debug, Default, Clone)]
+pub struct ComponentIdRegistry {
+    names: HashMap<ComponentId, &'static str>,
+    ids: HashMap<&'static str, ComponentId>,
+    next_id: ComponentId,
+}
+
+impl ComponentIdRegistry {
+    /// Create an empty registry. IDs are allocated starting from 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty registry which allocates IDs starting from `first_id`. This can be used to
+    /// keep allocated IDs in a range which is not used for fixed IDs.
+    pub fn with_allocation_start(first_id: ComponentId) -> Self {
+        Self {
+            names: HashMap::new(),
+            ids: HashMap::new(),
+            next_id: first_id,
+        }
+    }
+
+    /// Create a registry from several lists of fixed IDs.
+    ///
+    /// This function is intended to be called at startup and panics if two IDs or names collide.
+    /// Use [Self::register] to handle collisions gracefully.
+    pub fn with_ids(lists: &[&[(ComponentId, &'static str)]]) -> Self {
+        let mut registry = Self::new();
+        for list in lists {
+            for (id, name) in *list {
+                registry
+                    .register(*id, *name)
+                    .unwrap_or_else(|e| panic!("invalid component IDs: {e}"));
+            }
+        }
+        registry
+    }
+
+    /// Register a fixed ID. The ID is rejected if it was already registered or allocated, and
+    /// the name is rejected if it is already used by another ID.
+    pub fn register(
+        &mut self,
+        id: ComponentId,
+        name: &'static str,
+    ) -> Result<ComponentId, ComponentIdError> {
+        if let Some(first) = self.names.get(&id) {
+            return Err(ComponentIdError::DuplicateId {
+                id,
+                first: *first,
+                second: name,
+            });
+        }
+        self.check_name(name)?;
+        self.insert(id, name);
+        Ok(id)
+    }
+
+    /// Register the raw ID of a [UniqueApidTargetId].
+    pub fn register_target(
+        &mut self,
+        target: UniqueApidTargetId,
+        name: &'static str,
+    ) -> Result<ComponentId, ComponentIdError> {
+        self.register(target.raw(), name)
+    }
+
+    /// Allocate the next free ID. IDs which were already registered are skipped.
+    /// [ComponentId::MAX] is never allocated because it is commonly used as a placeholder for
+    /// no sender. The name is rejected if it is already used by another ID.
+    pub fn allocate(&mut self, name: &'static str) -> Result<ComponentId, ComponentIdError> {
+        self.check_name(name)?;
+        while self.names.contains_key(&self.next_id) {
+            if self.next_id == ComponentId::MAX - 1 {
+                return Err(ComponentIdError::Exhausted);
+            }
+            self.next_id += 1;
+        }
+        if self.next_id == ComponentId::MAX {
+            return Err(ComponentIdError::Exhausted);
+        }
+        let id = self.next_id;
+        self.insert(id, name);
+        if id < ComponentId::MAX - 1 {
+            self.next_id += 1;
+        }
+        Ok(id)
+    }
+
+    pub fn contains(&self, id: ComponentId) -> bool {
+        self.names.contains_key(&id)
+    }
+
+    pub fn name_of(&self, id: ComponentId) -> Option<&'static str> {
+        self.names.get(&id).copied()
+    }
+
+    /// Look up the ID of a component by its name.
+    pub fn id_of(&self, name: &str) -> Option<ComponentId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    fn check_name(&self, name: &'static str) -> Result<(), ComponentIdError> {
+        if let Some(first_id) = self.ids.get(name) {
+            return Err(ComponentIdError::DuplicateName {
+                name,
+                first_id: *first_id,
+            });
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, id: ComponentId, name: &'static str) {
+        self.names.insert(id, name);
+        self.ids.insert(name, id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+
+    const FIXED_IDS: &[(ComponentId, &str)] = &[(0, "ZERO"), (2, "TWO")];
+
+    #[test]
+    fn test_register_and_allocate() {
+        let mut registry = ComponentIdRegistry::with_ids(&[FIXED_IDS]);
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.allocate("FIRST").unwrap(), 1);
+        assert_eq!(registry.allocate("SECOND").unwrap(), 3);
+        let target = UniqueApidTargetId::new(0x02, 5);
+        assert_eq!(
+            registry.register_target(target, "TARGET").unwrap(),
+            target.raw()
+        );
+        assert_eq!(registry.name_of(3), Some("SECOND"));
+        assert_eq!(registry.id_of("TARGET"), Some(target.raw()));
+        assert_eq!(registry.id_of("UNKNOWN"), None);
+        assert!(registry.contains(2));
+        assert_eq!(registry.len(), 5);
+    }
+
+    #[test]
+    fn test_collision() {
+        let mut registry = ComponentIdRegistry::new();
+        registry.allocate("ALLOCATED").unwrap();
+        let error = registry.register(0, "FIXED").unwrap_err();
+        assert_eq!(
+            error,
+            ComponentIdError::DuplicateId {
+                id: 0,
+                first: "ALLOCATED",
+                second: "FIXED"
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "component ID 0x0 of FIXED already used by ALLOCATED"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_name() {
+        let mut registry = ComponentIdRegistry::with_ids(&[FIXED_IDS]);
+        let error = registry.register(5, "TWO").unwrap_err();
+        assert_eq!(
+            error,
+            ComponentIdError::DuplicateName {
+                name: "TWO",
+                first_id: 2
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "component name TWO already used by ID 0x2"
+        );
+        assert_eq!(
+            registry.allocate("ZERO"),
+            Err(ComponentIdError::DuplicateName {
+                name: "ZERO",
+                first_id: 0
+            })
+        );
+        // Rejected names do not consume an ID.
+        assert_eq!(registry.allocate("ONE").unwrap(), 1);
+        assert_eq!(registry.id_of("TWO"), Some(2));
+        assert_eq!(registry.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid component IDs")]
+    fn test_collision_between_lists() {
+        ComponentIdRegistry::with_ids(&[FIXED_IDS, &[(2, "ALSO_TWO")]]);
+    }
+
+    #[test]
+    fn test_allocation_exhausted() {
+        let mut registry = ComponentIdRegistry::with_allocation_start(ComponentId::MAX - 2);
+        assert_eq!(registry.allocate("A").unwrap(), ComponentId::MAX - 2);
+        assert_eq!(registry.allocate("B").unwrap(), ComponentId::MAX - 1);
+        assert_eq!(registry.allocate("C"), Err(ComponentIdError::Exhausted));
+    }
+}
@@ -0,0 +1,37 @@
+//! Crate-internal instrumentation macros.
+//!
+//! The macros forward to [defmt](https://docs.rs/defmt) if the `defmt` feature is enabled and the
+//! crate is built for a bare-metal target, which allows embedded users to get trace output via
+//! RTT. Otherwise, they forward to the [log](https://docs.rs/log) crate if the `log` feature is
+//! enabled. Without any of these features, the instrumentation is compiled out.
+//!
+//! Only the `{}` and `{:#x}` format parameters with primitive arguments should be used, because
+//! they are supported by both backends.
+
+/// Used if no backend is enabled so that the format string and arguments are still checked.
+#[allow(dead_code)]
+#[inline(always)]
+pub(crate) fn discard(_args: core::fmt::Arguments) {}
+
+macro_rules! instrument {
+    ($defmt_macro: ident, $log_macro: ident, $($arg: tt)+) => {{
+        #[cfg(all(feature = "defmt", target_os = "none"))]
+        ::defmt::$defmt_macro!($($arg)+);
+        #[cfg(all(feature = "log", not(all(feature = "defmt", target_os = "none"))))]
+        ::log::$log_macro!($($arg)+);
+        #[cfg(not(any(all(feature = "defmt", target_os = "none"), feature = "log")))]
+        $crate::instrument::discard(::core::format_args!($($arg)+));
+    }};
+}
+
+macro_rules! trace_debug {
+    ($($arg: tt)+) => {
+        instrument!(debug, debug, $($arg)+)
+    };
+}
+
+macro_rules! trace_warn {
+    ($($arg: tt)+) => {
+        instrument!(warn, warn, $($arg)+)
+    };
+}
@@ -13,6 +13,13 @@
 //!    and subscribe to route events.
 //!  - The [pus] module which provides special support for projects using
 //!    the [ECSS PUS C standard](https://ecss.nl/standard/ecss-e-st-70-41c-space-engineering-telemetry-and-telecommand-packet-utilization-15-april-2016/).
+//!
+//! ## Instrumentation
+//!
+//! Key code paths like TC routing decisions, verification failures, pool allocation failures and
+//! event routing errors can emit trace output. The `defmt` feature enables the output via
+//! [defmt](https://docs.rs/defmt) for bare-metal targets, and the `log` feature enables it via
+//! the [log](https://docs.rs/log) crate for all other targets.
 #![no_std]
 #![cfg_attr(docs_rs, feature(doc_auto_cfg))]
 #[cfg(feature = "alloc")]
@@ -22,16 +29,39 @@ extern crate downcast_rs;
 #[cfg(any(feature = "std", test))]
 extern crate std;
 
+#[macro_use]
+mod instrument;
+
+#[cfg(feature = "alloc")]
+pub mod addressing;
+pub mod apid;
+#[cfg(feature = "alloc")]
+pub mod assembly;
+pub mod boot_report;
 #[cfg(feature = "alloc")]
 pub mod cfdp;
+#[cfg(feature = "client")]
+pub mod client;
 pub mod encoding;
+pub mod error_report;
+pub mod event_log;
 pub mod event_man;
+pub mod event_registry;
 pub mod events;
-#[cfg(feature = "std")]
 pub mod executable;
 pub mod hal;
+#[cfg(feature = "alloc")]
+pub mod id_registry;
+pub mod log_report;
 #[cfg(feature = "std")]
 pub mod mode_tree;
+pub mod nv_storage;
+#[cfg(feature = "alloc")]
+pub mod objects;
+#[cfg(feature = "std")]
+pub mod param_db;
+#[cfg(feature = "std")]
+pub mod param_stats;
 pub mod pool;
 pub mod power;
 pub mod pus;
@@ -39,10 +69,17 @@ pub mod queue;
 pub mod request;
 pub mod res_code;
 pub mod seq_count;
+#[cfg(feature = "std")]
+pub mod shutdown;
 pub mod time;
+pub mod tm_suppression;
 pub mod tmtc;
+pub mod wakeup;
+#[cfg(feature = "std")]
+pub mod watchdog;
 
 pub mod action;
+pub mod device;
 pub mod hk;
 pub mod mode;
 pub mod params;
@@ -0,0 +1,752 @@
+//! # Forwarding log records to the ground
+//!
+//! Textual diagnostics are usually only available on a debug console, which is not accessible
+//! during the mission. The [LogForwarder] converts log records into telemetry, so warnings and
+//! errors reach the ground without an additional mission-specific component.
+//!
+//! The [LogSeverityMap] determines which log levels are forwarded and which event [Severity] they
+//! are mapped to. By default, only warnings and errors are forwarded. The forwarded records are
+//! rate limited to avoid flooding the downlink, for example if an error is logged in every cycle
+//! of a periodic task.
+//!
+//! The forwarded records are passed to a [LogRecordSink]. The following sinks are provided:
+//!
+//!  - The [LogTmSink] which packages each record as a PUS on-board message TM with the
+//!    mission-specific [ONBOARD_MESSAGE_SERVICE]. It does not require a heap.
+//!  - The [LogEventSink] which sends each record as the [log_event] with the message as a string
+//!    parameter to the event manager. This way, the record is packaged as PUS event TM and can be
+//!    handled by all event listeners. It requires the `alloc` feature.
+//!
+//! The [LogForwarder] does not require a heap and formats the message into a fixed size buffer,
+//! so it can be used in `no_std` environments. It can be called with [LogForwarder::forward]
+//! from any logging facility, or with [LogForwarder::forward_record] from a custom [log::Log]
+//! implementation if the `log` feature is enabled. With the `std` and `log` features, the
+//! [PusLogger] can be installed as the backend of the [log](https://docs.rs/log) facade directly.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::mpsc;
+//! use std::time::Duration;
+//! use satrs::event_man::EventU32SenderMpsc;
+//! use satrs::events::Severity;
+//! use satrs::log_report::{log_event, LogEventSink, LogForwarder, LogLevel};
+//! use satrs::params::Params;
+//!
+//! let (event_tx, event_rx) = mpsc::channel();
+//! let sink = LogEventSink::new(1, EventU32SenderMpsc::new(0, event_tx));
+//! let mut forwarder: LogForwarder<_> = LogForwarder::new(sink);
+//! let forwarded = forwarder
+//!     .forward(LogLevel::Warn, "acs", format_args!("sun sensor {} stuck", 2), Duration::ZERO)
+//!     .unwrap();
+//! assert!(forwarded);
+//! let event_msg = event_rx.try_recv().unwrap();
+//! assert_eq!(event_msg.event(), log_event(Severity::Low));
+//! assert_eq!(
+//!     event_msg.params(),
+//!     Some(&Params::String("acs: sun sensor 2 stuck".into()))
+//! );
+//! ```
+use core::fmt::Write;
+use core::time::Duration;
+
+use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+use spacepackets::SpHeader;
+
+use crate::error_report::FRAMEWORK_EVENT_GROUP_ID;
+use crate::event_man::{RateLimit, RateLimitState};
+use crate::events::{EventU32, Severity, SeverityLow};
+use crate::pus::{EcssTmSender, EcssTmtcError, PusTmVariant};
+use crate::time::{RawTimestamp, TimeSource, TimestampFormat};
+use crate::ComponentId;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+#[cfg(all(feature = "std", feature = "log"))]
+pub use std_mod::*;
+
+crate::event_definitions! {
+    pub LOG_EVENT_DEFINITIONS;
+    /// Definition of the [log_event]. The actual severity is determined by the
+    /// [LogSeverityMap].
+    pub LOG_EVENT: SeverityLow = (FRAMEWORK_EVENT_GROUP_ID, LOG_EVENT_UNIQUE_ID), String,
+        "Forwarded log record, contains the target and the message";
+}
+
+/// Unique ID of the [log_event] inside the [FRAMEWORK_EVENT_GROUP_ID].
+pub const LOG_EVENT_UNIQUE_ID: u16 = 2;
+
+/// Mission-specific PUS service used by the [LogTmSink] by default.
+pub const ONBOARD_MESSAGE_SERVICE: u8 = 128;
+/// Subservice of the on-board message TM generated by the [LogTmSink] by default.
+pub const ONBOARD_MESSAGE_SUBSERVICE: u8 = 1;
+
+/// Event used by the [LogEventSink]. The severity is determined by the [LogSeverityMap].
+pub const fn log_event(severity: Severity) -> EventU32 {
+    EventU32::new(severity, FRAMEWORK_EVENT_GROUP_ID, LOG_EVENT_UNIQUE_ID)
+}
+
+/// Level of a log record.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum LogLevel {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+#[cfg(feature = "log")]
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+/// Maps each [LogLevel] to the [Severity] of the forwarded record. Records with a level which is
+/// not mapped are not forwarded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LogSeverityMap {
+    severities: [Option<Severity>; 5],
+}
+
+impl Default for LogSeverityMap {
+    /// Errors are mapped to [Severity::Medium] and warnings to [Severity::Low].
+    fn default() -> Self {
+        Self::new()
+            .with_severity(LogLevel::Error, Some(Severity::Medium))
+            .with_severity(LogLevel::Warn, Some(Severity::Low))
+    }
+}
+
+impl LogSeverityMap {
+    /// Severity map which does not forward any records.
+    pub const fn new() -> Self {
+        Self {
+            severities: [None; 5],
+        }
+    }
+
+    pub const fn with_severity(mut self, level: LogLevel, severity: Option<Severity>) -> Self {
+        self.severities[level as usize - 1] = severity;
+        self
+    }
+
+    pub fn set_severity(&mut self, level: LogLevel, severity: Option<Severity>) {
+        self.severities[level as usize - 1] = severity;
+    }
+
+    pub fn severity(&self, level: LogLevel) -> Option<Severity> {
+        self.severities[level as usize - 1]
+    }
+}
+
+/// Log record which is passed to a [LogRecordSink].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ForwardedLogRecord<'a> {
+    pub level: LogLevel,
+    pub severity: Severity,
+    /// Target of the record, which is usually the module path of the origin.
+    pub target: &'a str,
+    /// Formatted message, which might be truncated.
+    pub message: &'a str,
+}
+
+/// Generic trait for components which package forwarded log records as telemetry.
+pub trait LogRecordSink {
+    type Error;
+
+    fn send_log_record(&mut self, record: &ForwardedLogRecord) -> Result<(), Self::Error>;
+}
+
+/// Formatter target which truncates the output to the size of the buffer.
+struct TruncatingWriter<'buf> {
+    buf: &'buf mut [u8],
+    len: usize,
+}
+
+impl Write for TruncatingWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let mut write_len = core::cmp::min(s.len(), self.buf.len() - self.len);
+        while !s.is_char_boundary(write_len) {
+            write_len -= 1;
+        }
+        self.buf[self.len..self.len + write_len].copy_from_slice(&s.as_bytes()[..write_len]);
+        self.len += write_len;
+        Ok(())
+    }
+}
+
+/// Forwards rate limited log records to a [LogRecordSink].
+///
+/// The message is formatted into a buffer of `MAX_MSG_LEN` bytes and truncated if it does not
+/// fit. The timestamps passed to the forwarding functions are monotonic timestamps which are used
+/// for the rate limiting.
+pub struct LogForwarder<Sink: LogRecordSink, const MAX_MSG_LEN: usize = 128> {
+    sink: Sink,
+    severity_map: LogSeverityMap,
+    rate_limit: RateLimitState,
+    msg_buf: [u8; MAX_MSG_LEN],
+}
+
+impl<Sink: LogRecordSink, const MAX_MSG_LEN: usize> LogForwarder<Sink, MAX_MSG_LEN> {
+    /// At most 10 records are forwarded per minute by default.
+    pub const DEFAULT_RATE_LIMIT: RateLimit = RateLimit::new(10, Duration::from_secs(60));
+
+    /// Create a new forwarder with the default [LogSeverityMap] and the
+    /// [Self::DEFAULT_RATE_LIMIT].
+    pub fn new(sink: Sink) -> Self {
+        Self::new_with_cfg(sink, LogSeverityMap::default(), Self::DEFAULT_RATE_LIMIT)
+    }
+
+    /// Create a new forwarder.
+    ///
+    /// ## Parameter
+    ///
+    /// * `sink` - Sink which packages the forwarded records as telemetry.
+    /// * `severity_map` - Determines which records are forwarded with which severity.
+    /// * `rate_limit` - Rate limit which is applied to all forwarded records.
+    pub fn new_with_cfg(sink: Sink, severity_map: LogSeverityMap, rate_limit: RateLimit) -> Self {
+        Self {
+            sink,
+            severity_map,
+            rate_limit: RateLimitState::new(rate_limit),
+            msg_buf: [0; MAX_MSG_LEN],
+        }
+    }
+
+    pub fn sink(&self) -> &Sink {
+        &self.sink
+    }
+
+    pub fn sink_mut(&mut self) -> &mut Sink {
+        &mut self.sink
+    }
+
+    pub fn severity_map(&self) -> &LogSeverityMap {
+        &self.severity_map
+    }
+
+    pub fn severity_map_mut(&mut self) -> &mut LogSeverityMap {
+        &mut self.severity_map
+    }
+
+    pub fn rate_limit(&self) -> RateLimit {
+        self.rate_limit.rate_limit()
+    }
+
+    /// Number of records which were not forwarded because of the rate limit.
+    pub fn num_rate_limited(&self) -> u32 {
+        self.rate_limit.num_rate_limited()
+    }
+
+    /// Returns whether records with the given level are forwarded.
+    pub fn level_enabled(&self, level: LogLevel) -> bool {
+        self.severity_map.severity(level).is_some()
+    }
+
+    /// Forward a log record. Returns [false] if the record was not forwarded because its level
+    /// is not mapped or because of the rate limit.
+    pub fn forward(
+        &mut self,
+        level: LogLevel,
+        target: &str,
+        args: core::fmt::Arguments,
+        timestamp: Duration,
+    ) -> Result<bool, Sink::Error> {
+        let severity = match self.severity_map.severity(level) {
+            Some(severity) => severity,
+            None => return Ok(false),
+        };
+        if !self.rate_limit.check(timestamp) {
+            return Ok(false);
+        }
+        let mut writer = TruncatingWriter {
+            buf: &mut self.msg_buf,
+            len: 0,
+        };
+        // The writer never fails, but a faulty Display implementation might.
+        let _ = writer.write_fmt(args);
+        let len = writer.len;
+        let message = core::str::from_utf8(&self.msg_buf[..len]).unwrap_or("");
+        self.sink.send_log_record(&ForwardedLogRecord {
+            level,
+            severity,
+            target,
+            message,
+        })?;
+        Ok(true)
+    }
+
+    /// Forward a record of the [log](https://docs.rs/log) facade.
+    #[cfg(feature = "log")]
+    pub fn forward_record(
+        &mut self,
+        record: &log::Record,
+        timestamp: Duration,
+    ) -> Result<bool, Sink::Error> {
+        self.forward(
+            record.level().into(),
+            record.target(),
+            *record.args(),
+            timestamp,
+        )
+    }
+}
+
+/// Configuration of the on-board message TM generated by the [LogTmSink].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LogTmCfg {
+    pub apid: u16,
+    pub service: u8,
+    pub subservice: u8,
+    pub dest_id: u16,
+    pub timestamp_format: TimestampFormat,
+}
+
+impl LogTmCfg {
+    /// Configuration with the [ONBOARD_MESSAGE_SERVICE], the [ONBOARD_MESSAGE_SUBSERVICE], the
+    /// destination ID 0 and CDS short timestamps.
+    pub const fn new(apid: u16) -> Self {
+        Self {
+            apid,
+            service: ONBOARD_MESSAGE_SERVICE,
+            subservice: ONBOARD_MESSAGE_SUBSERVICE,
+            dest_id: 0,
+            timestamp_format: TimestampFormat::CdsShort,
+        }
+    }
+}
+
+/// [LogRecordSink] which packages each record as a PUS on-board message TM.
+///
+/// The source data of the TM consists of the [LogLevel] and the [Severity] as one byte each,
+/// followed by the length of the target as one byte, the target and the message. The target and
+/// the message are truncated if the source data does not fit into `MAX_SOURCE_DATA_LEN` bytes.
+/// A zeroed timestamp is used if the time source fails.
+pub struct LogTmSink<
+    TmSender: EcssTmSender,
+    Time: TimeSource,
+    const MAX_SOURCE_DATA_LEN: usize = 256,
+> {
+    id: ComponentId,
+    pub cfg: LogTmCfg,
+    tm_sender: TmSender,
+    time_source: Time,
+    source_data_buf: [u8; MAX_SOURCE_DATA_LEN],
+}
+
+impl<TmSender: EcssTmSender, Time: TimeSource, const MAX_SOURCE_DATA_LEN: usize>
+    LogTmSink<TmSender, Time, MAX_SOURCE_DATA_LEN>
+{
+    /// Header of the source data: level, severity and target length.
+    pub const SOURCE_DATA_HEADER_LEN: usize = 3;
+
+    pub fn new(id: ComponentId, cfg: LogTmCfg, tm_sender: TmSender, time_source: Time) -> Self {
+        Self {
+            id,
+            cfg,
+            tm_sender,
+            time_source,
+            source_data_buf: [0; MAX_SOURCE_DATA_LEN],
+        }
+    }
+
+    pub fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    pub fn tm_sender(&self) -> &TmSender {
+        &self.tm_sender
+    }
+
+    pub fn time_source(&self) -> &Time {
+        &self.time_source
+    }
+}
+
+impl<TmSender: EcssTmSender, Time: TimeSource, const MAX_SOURCE_DATA_LEN: usize> LogRecordSink
+    for LogTmSink<TmSender, Time, MAX_SOURCE_DATA_LEN>
+{
+    type Error = EcssTmtcError;
+
+    fn send_log_record(&mut self, record: &ForwardedLogRecord) -> Result<(), Self::Error> {
+        let header_len = Self::SOURCE_DATA_HEADER_LEN;
+        if MAX_SOURCE_DATA_LEN < header_len {
+            return Err(EcssTmtcError::TmTooLarge {
+                len: header_len,
+                max_len: MAX_SOURCE_DATA_LEN,
+            });
+        }
+        let max_target_len = core::cmp::min(MAX_SOURCE_DATA_LEN - header_len, u8::MAX as usize);
+        let mut writer = TruncatingWriter {
+            buf: &mut self.source_data_buf[header_len..header_len + max_target_len],
+            len: 0,
+        };
+        writer.write_str(record.target).ok();
+        let target_len = writer.len;
+        let mut writer = TruncatingWriter {
+            buf: &mut self.source_data_buf[header_len + target_len..],
+            len: 0,
+        };
+        writer.write_str(record.message).ok();
+        let source_data_len = header_len + target_len + writer.len;
+        self.source_data_buf[0] = record.level as u8;
+        self.source_data_buf[1] = record.severity as u8;
+        self.source_data_buf[2] = target_len as u8;
+        let time_stamp = self
+            .time_source
+            .stamp(self.cfg.timestamp_format)
+            .unwrap_or_else(|_| RawTimestamp::zeroed(self.cfg.timestamp_format));
+        let sec_header = PusTmSecondaryHeader::new(
+            self.cfg.service,
+            self.cfg.subservice,
+            0,
+            self.cfg.dest_id,
+            &time_stamp,
+        );
+        let tm = PusTmCreator::new(
+            SpHeader::new_from_apid(self.cfg.apid),
+            sec_header,
+            &self.source_data_buf[..source_data_len],
+            true,
+        );
+        self.tm_sender.send_tm(self.id, PusTmVariant::Direct(tm))
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::string::String;
+
+    use super::*;
+    use crate::event_man::{EventMessage, EventSendProvider};
+    use crate::params::Params;
+
+    /// [LogRecordSink] which sends each record as the [log_event] to the event manager. The
+    /// event parameter is a [Params::String] which contains the target and the message separated
+    /// by a colon.
+    pub struct LogEventSink<Sender: EventSendProvider<EventU32>> {
+        id: ComponentId,
+        sender: Sender,
+    }
+
+    impl<Sender: EventSendProvider<EventU32>> LogEventSink<Sender> {
+        /// Create a new sink. The given component ID is used as the sender ID of the events.
+        pub fn new(id: ComponentId, sender: Sender) -> Self {
+            Self { id, sender }
+        }
+
+        pub fn id(&self) -> ComponentId {
+            self.id
+        }
+
+        pub fn sender(&self) -> &Sender {
+            &self.sender
+        }
+    }
+
+    impl<Sender: EventSendProvider<EventU32>> LogRecordSink for LogEventSink<Sender> {
+        type Error = Sender::Error;
+
+        fn send_log_record(&mut self, record: &ForwardedLogRecord) -> Result<(), Self::Error> {
+            let mut text = String::with_capacity(record.target.len() + 2 + record.message.len());
+            text.push_str(record.target);
+            text.push_str(": ");
+            text.push_str(record.message);
+            self.sender.send(EventMessage::new_with_params(
+                self.id,
+                log_event(record.severity),
+                &Params::String(text),
+            ))
+        }
+    }
+}
+
+#[cfg(all(feature = "std", feature = "log"))]
+pub mod std_mod {
+    use core::cell::Cell;
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use std::boxed::Box;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    use super::*;
+
+    /// Backend for the [log](https://docs.rs/log) facade which forwards the log records with a
+    /// [LogForwarder].
+    ///
+    /// The monotonic timestamps for the rate limiting are measured from the creation of the
+    /// logger. Records of different threads are forwarded one after another. Records which are
+    /// logged by a thread while it forwards another record, for example by the sink itself, are
+    /// dropped to avoid a deadlock and counted, see [Self::num_dropped]. Errors of the sink are
+    /// ignored.
+    pub struct PusLogger<Sink: LogRecordSink + Send, const MAX_MSG_LEN: usize = 128> {
+        forwarder: Mutex<LogForwarder<Sink, MAX_MSG_LEN>>,
+        start: Instant,
+        num_dropped: AtomicU32,
+    }
+
+    std::thread_local! {
+        static FORWARDING: Cell<bool> = const { Cell::new(false) };
+    }
+
+    impl<Sink: LogRecordSink + Send, const MAX_MSG_LEN: usize> PusLogger<Sink, MAX_MSG_LEN> {
+        pub fn new(forwarder: LogForwarder<Sink, MAX_MSG_LEN>) -> Self {
+            Self {
+                forwarder: Mutex::new(forwarder),
+                start: Instant::now(),
+                num_dropped: AtomicU32::new(0),
+            }
+        }
+
+        /// Number of records which were dropped because they were logged while the same thread
+        /// forwarded another record, or because the forwarder lock was poisoned.
+        pub fn num_dropped(&self) -> u32 {
+            self.num_dropped.load(Ordering::Relaxed)
+        }
+
+        /// Number of records which were not forwarded because of the rate limit.
+        pub fn num_rate_limited(&self) -> u32 {
+            self.forwarder
+                .lock()
+                .map(|forwarder| forwarder.num_rate_limited())
+                .unwrap_or(0)
+        }
+
+        /// Install the logger as the global logger of the [log] facade and set the maximum log
+        /// level.
+        pub fn init(self, max_level: log::LevelFilter) -> Result<(), log::SetLoggerError>
+        where
+            Sink: 'static,
+        {
+            log::set_logger(Box::leak(Box::new(self)))?;
+            log::set_max_level(max_level);
+            Ok(())
+        }
+    }
+
+    impl<Sink: LogRecordSink + Send, const MAX_MSG_LEN: usize> log::Log
+        for PusLogger<Sink, MAX_MSG_LEN>
+    {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            // Locking the forwarder again from the forwarding thread would deadlock.
+            if FORWARDING.with(Cell::get) {
+                return false;
+            }
+            self.forwarder
+                .lock()
+                .map(|forwarder| forwarder.level_enabled(metadata.level().into()))
+                .unwrap_or(false)
+        }
+
+        fn log(&self, record: &log::Record) {
+            if FORWARDING.with(Cell::get) {
+                self.num_dropped.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            let mut forwarder = match self.forwarder.lock() {
+                Ok(forwarder) => forwarder,
+                Err(_) => {
+                    self.num_dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            };
+            FORWARDING.with(|forwarding| forwarding.set(true));
+            let _ = forwarder.forward_record(record, self.start.elapsed());
+            FORWARDING.with(|forwarding| forwarding.set(false));
+        }
+
+        fn flush(&self) {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::vec::Vec;
+
+    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::PusPacket;
+    use spacepackets::time::UnixTime;
+
+    use super::*;
+    use crate::event_man::EventU32SenderMpsc;
+    use crate::params::Params;
+    use crate::pus::MpscTmAsVecSender;
+    use crate::time::FixedTimeSource;
+    use crate::tmtc::PacketAsVec;
+
+    const SINK_ID: ComponentId = 0x05;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        records: Vec<(LogLevel, Severity, std::string::String, std::string::String)>,
+    }
+
+    impl LogRecordSink for RecordingSink {
+        type Error = ();
+
+        fn send_log_record(&mut self, record: &ForwardedLogRecord) -> Result<(), Self::Error> {
+            self.records.push((
+                record.level,
+                record.severity,
+                record.target.into(),
+                record.message.into(),
+            ));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_severity_mapping_and_truncation() {
+        let mut forwarder: LogForwarder<_, 8> = LogForwarder::new(RecordingSink::default());
+        assert!(!forwarder
+            .forward(LogLevel::Info, "t", format_args!("info"), Duration::ZERO)
+            .unwrap());
+        assert!(forwarder
+            .forward(
+                LogLevel::Error,
+                "t",
+                format_args!("{}äbc", 1234567),
+                Duration::ZERO
+            )
+            .unwrap());
+        forwarder
+            .severity_map_mut()
+            .set_severity(LogLevel::Info, Some(Severity::Info));
+        assert!(forwarder
+            .forward(LogLevel::Info, "t", format_args!("info"), Duration::ZERO)
+            .unwrap());
+        let records = &forwarder.sink().records;
+        assert_eq!(records.len(), 2);
+        // The message is truncated at a character boundary.
+        assert_eq!(
+            records[0],
+            (
+                LogLevel::Error,
+                Severity::Medium,
+                "t".into(),
+                "1234567".into()
+            )
+        );
+        assert_eq!(records[1].1, Severity::Info);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut forwarder: LogForwarder<_> = LogForwarder::new_with_cfg(
+            RecordingSink::default(),
+            LogSeverityMap::default(),
+            RateLimit::new(2, Duration::from_secs(10)),
+        );
+        let mut results = Vec::new();
+        for secs in [0, 1, 2, 10] {
+            results.push(
+                forwarder
+                    .forward(
+                        LogLevel::Warn,
+                        "t",
+                        format_args!("warning"),
+                        Duration::from_secs(secs),
+                    )
+                    .unwrap(),
+            );
+        }
+        assert_eq!(results, [true, true, false, true]);
+        assert_eq!(forwarder.num_rate_limited(), 1);
+        assert_eq!(forwarder.sink().records.len(), 3);
+    }
+
+    #[test]
+    fn test_event_sink() {
+        let (event_tx, event_rx) = mpsc::channel();
+        let sink = LogEventSink::new(SINK_ID, EventU32SenderMpsc::new(0, event_tx));
+        let mut forwarder: LogForwarder<_> = LogForwarder::new(sink);
+        forwarder
+            .forward(
+                LogLevel::Error,
+                "tcs",
+                format_args!("heater off"),
+                Duration::ZERO,
+            )
+            .unwrap();
+        let event_msg = event_rx.try_recv().unwrap();
+        assert_eq!(event_msg.sender_id(), SINK_ID);
+        assert_eq!(event_msg.event(), log_event(Severity::Medium));
+        assert_eq!(
+            event_msg.params(),
+            Some(&Params::String("tcs: heater off".into()))
+        );
+    }
+
+    #[test]
+    fn test_tm_sink() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let time_source = FixedTimeSource::new(UnixTime::new_only_secs(1_700_000_000));
+        let sink: LogTmSink<MpscTmAsVecSender, _, 16> =
+            LogTmSink::new(SINK_ID, LogTmCfg::new(0x20), tm_tx, time_source);
+        let mut forwarder: LogForwarder<_> = LogForwarder::new(sink);
+        forwarder
+            .forward(
+                LogLevel::Warn,
+                "pwr",
+                format_args!("battery voltage low"),
+                Duration::ZERO,
+            )
+            .unwrap();
+        let packet = tm_rx.try_recv().unwrap();
+        assert_eq!(packet.sender_id, SINK_ID);
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(tm.service(), ONBOARD_MESSAGE_SERVICE);
+        assert_eq!(tm.subservice(), ONBOARD_MESSAGE_SUBSERVICE);
+        assert_eq!(tm.timestamp(), time_source.cds_short_stamp().unwrap());
+        let source_data = tm.source_data();
+        assert_eq!(source_data.len(), 16);
+        assert_eq!(source_data[0], LogLevel::Warn as u8);
+        assert_eq!(source_data[1], Severity::Low as u8);
+        assert_eq!(source_data[2], 3);
+        assert_eq!(&source_data[3..6], b"pwr");
+        assert_eq!(&source_data[6..], b"battery vo");
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_pus_logger() {
+        use log::Log;
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let sink = LogEventSink::new(SINK_ID, EventU32SenderMpsc::new(0, event_tx));
+        let logger = PusLogger::new(LogForwarder::<_>::new(sink));
+        let warn_metadata = log::Metadata::builder()
+            .level(log::Level::Warn)
+            .target("aocs")
+            .build();
+        assert!(logger.enabled(&warn_metadata));
+        assert!(!logger.enabled(&log::Metadata::builder().level(log::Level::Info).build()));
+        logger.log(
+            &log::Record::builder()
+                .metadata(warn_metadata)
+                .args(format_args!("gyro {} saturated", 1))
+                .build(),
+        );
+        assert_eq!(logger.num_dropped(), 0);
+        let event_msg = event_rx.try_recv().unwrap();
+        assert_eq!(event_msg.event(), log_event(Severity::Low));
+        assert_eq!(
+            event_msg.params(),
+            Some(&Params::String("aocs: gyro 1 saturated".into()))
+        );
+        assert!(event_rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,430 @@
+//! # Non-volatile storage abstraction
+//!
+//! Several framework components need to keep a small amount of state across reboots, for example
+//! the boot counter of the [BootCounter][crate::boot_report::BootCounter] or the counter of a
+//! [PersistentSeqCountProvider][crate::seq_count::PersistentSeqCountProvider]. The [NvStorage]
+//! trait abstracts a simple key-value store for such records, which could for example be
+//! implemented on top of an EEPROM, a FRAM, a flash page or a file.
+//!
+//! Each component which uses the storage is configured with its own [NvKey]. The keys are
+//! assigned by the mission. The [NvSeqCountBackend] adapter allows using an [NvStorage] record as
+//! the backend of a persistent sequence counter.
+//!
+//! The [NvmSlotStorage] implements the [NvStorage] on top of a
+//! [NonVolatileMemory][crate::hal::nvm::NonVolatileMemory]. With the `alloc` feature, the
+//! [RamNvStorage] can be used for simulations and tests.
+//!
+//! # Example
+//!
+//! ```
+//! use satrs::nv_storage::{NvSeqCountBackend, NvStorage, RamNvStorage};
+//! use satrs::seq_count::{PersistentSeqCountProvider, SequenceCountProviderCore};
+//!
+//! const SEQ_COUNT_KEY: u16 = 1;
+//!
+//! let mut storage = RamNvStorage::default();
+//! let seq_counter =
+//!     PersistentSeqCountProvider::new(NvSeqCountBackend::new(&mut storage, SEQ_COUNT_KEY), 1)
+//!         .unwrap();
+//! assert_eq!(seq_counter.get_and_increment(), 0);
+//! drop(seq_counter);
+//! assert_eq!(storage.load_u16(SEQ_COUNT_KEY).unwrap(), Some(1));
+//! ```
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::hal::nvm::{NonVolatileMemory, NvmError, ERASED_VALUE};
+use crate::seq_count::SeqCountNvBackend;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+/// Key of a record inside a [NvStorage].
+pub type NvKey = u16;
+
+/// Generic key-value store for small records which need to be kept across reboots.
+///
+/// The trait provides helper methods to load and store integer records in big endian format.
+pub trait NvStorage {
+    type Error;
+
+    /// Load the record with the given key into the provided buffer. Returns the length of the
+    /// record, or [None] if no record was stored yet or if it does not fit into the buffer.
+    fn load(&mut self, key: NvKey, buf: &mut [u8]) -> Result<Option<usize>, Self::Error>;
+
+    /// Store the record with the given key, replacing any previously stored record.
+    fn store(&mut self, key: NvKey, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Load a [u16] record. Returns [None] if no record with a matching length was stored.
+    fn load_u16(&mut self, key: NvKey) -> Result<Option<u16>, Self::Error> {
+        let mut buf = [0; 2];
+        Ok(match self.load(key, &mut buf)? {
+            Some(2) => Some(u16::from_be_bytes(buf)),
+            _ => None,
+        })
+    }
+
+    fn store_u16(&mut self, key: NvKey, value: u16) -> Result<(), Self::Error> {
+        self.store(key, &value.to_be_bytes())
+    }
+
+    /// Load a [u32] record. Returns [None] if no record with a matching length was stored.
+    fn load_u32(&mut self, key: NvKey) -> Result<Option<u32>, Self::Error> {
+        let mut buf = [0; 4];
+        Ok(match self.load(key, &mut buf)? {
+            Some(4) => Some(u32::from_be_bytes(buf)),
+            _ => None,
+        })
+    }
+
+    fn store_u32(&mut self, key: NvKey, value: u32) -> Result<(), Self::Error> {
+        self.store(key, &value.to_be_bytes())
+    }
+}
+
+impl<Storage: NvStorage + ?Sized> NvStorage for &mut Storage {
+    type Error = Storage::Error;
+
+    fn load(&mut self, key: NvKey, buf: &mut [u8]) -> Result<Option<usize>, Self::Error> {
+        (**self).load(key, buf)
+    }
+
+    fn store(&mut self, key: NvKey, data: &[u8]) -> Result<(), Self::Error> {
+        (**self).store(key, data)
+    }
+}
+
+/// [SeqCountNvBackend] which stores the sequence counter as a record of a [NvStorage].
+pub struct NvSeqCountBackend<Storage: NvStorage> {
+    storage: Storage,
+    key: NvKey,
+}
+
+impl<Storage: NvStorage> NvSeqCountBackend<Storage> {
+    pub fn new(storage: Storage, key: NvKey) -> Self {
+        Self { storage, key }
+    }
+
+    pub fn key(&self) -> NvKey {
+        self.key
+    }
+
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+}
+
+impl<Storage: NvStorage> SeqCountNvBackend for NvSeqCountBackend<Storage> {
+    type Error = Storage::Error;
+
+    fn load(&mut self) -> Result<Option<u16>, Self::Error> {
+        self.storage.load_u16(self.key)
+    }
+
+    fn store(&mut self, value: u16) -> Result<(), Self::Error> {
+        self.storage.store_u16(self.key, value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NvmSlotError<NvmErr> {
+    Nvm(NvmErr),
+    /// The key is larger than the number of slots of the memory.
+    InvalidKey(NvKey),
+    RecordTooLarge {
+        len: usize,
+        max_len: usize,
+    },
+}
+
+impl<NvmErr: Display> Display for NvmSlotError<NvmErr> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NvmSlotError::Nvm(e) => write!(f, "NVM error: {e}"),
+            NvmSlotError::InvalidKey(key) => write!(f, "no NVM slot for key {key}"),
+            NvmSlotError::RecordTooLarge { len, max_len } => write!(
+                f,
+                "record with length {len} exceeds maximum length {max_len} of NVM slot"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<NvmErr: Error + 'static> Error for NvmSlotError<NvmErr> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NvmSlotError::Nvm(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// [NvStorage] which stores each record in a fixed-size slot of a [NonVolatileMemory].
+///
+/// The record with the key `n` is stored at the offset `n * slot_size`. It consists of the record
+/// length as a big endian [u16] followed by the record data. A slot with an erased length field
+/// does not contain a record. If the memory needs to be erased before writing, the slot is erased
+/// before each store operation. Therefore, the slot size needs to be a multiple of the erase
+/// block size for these memories.
+///
+/// The store operation is not atomic: A reset during a store operation can corrupt the record.
+pub struct NvmSlotStorage<Nvm: NonVolatileMemory> {
+    nvm: Nvm,
+    slot_size: usize,
+}
+
+impl<Nvm: NonVolatileMemory> NvmSlotStorage<Nvm> {
+    /// Length of the record header.
+    pub const HEADER_LEN: usize = 2;
+    /// Maximum write size of the memory which is supported.
+    pub const MAX_WRITE_SIZE: usize = 64;
+
+    /// Create a new slot storage. Returns [NvmError::NotAligned] if the slot size is not aligned
+    /// to the erase block size of a memory which needs to be erased before writing, or if the
+    /// slot size or the write size of the memory is not supported.
+    pub fn new(nvm: Nvm, slot_size: usize) -> Result<Self, NvmError> {
+        let info = nvm.info();
+        let alignment = if info.erase_required {
+            info.erase_block_size.max(info.write_size)
+        } else {
+            info.write_size
+        };
+        if slot_size <= Self::HEADER_LEN
+            || info.write_size > Self::MAX_WRITE_SIZE
+            || (alignment > 1 && slot_size % alignment != 0)
+        {
+            return Err(NvmError::NotAligned {
+                offset: 0,
+                len: slot_size,
+                alignment,
+            });
+        }
+        Ok(Self { nvm, slot_size })
+    }
+
+    pub fn nvm(&self) -> &Nvm {
+        &self.nvm
+    }
+
+    pub fn nvm_mut(&mut self) -> &mut Nvm {
+        &mut self.nvm
+    }
+
+    pub fn slot_size(&self) -> usize {
+        self.slot_size
+    }
+
+    pub fn num_slots(&self) -> usize {
+        self.nvm.info().size / self.slot_size
+    }
+
+    /// Maximum length of a record.
+    pub fn max_record_len(&self) -> usize {
+        core::cmp::min(self.slot_size - Self::HEADER_LEN, u16::MAX as usize - 1)
+    }
+
+    fn slot_offset(&self, key: NvKey) -> Result<usize, NvmSlotError<Nvm::Error>> {
+        if key as usize >= self.num_slots() {
+            return Err(NvmSlotError::InvalidKey(key));
+        }
+        Ok(key as usize * self.slot_size)
+    }
+}
+
+impl<Nvm: NonVolatileMemory> NvStorage for NvmSlotStorage<Nvm> {
+    type Error = NvmSlotError<Nvm::Error>;
+
+    fn load(&mut self, key: NvKey, buf: &mut [u8]) -> Result<Option<usize>, Self::Error> {
+        let offset = self.slot_offset(key)?;
+        let mut header = [0; Self::HEADER_LEN];
+        self.nvm
+            .read(offset, &mut header)
+            .map_err(NvmSlotError::Nvm)?;
+        let len = u16::from_be_bytes(header) as usize;
+        if len > self.max_record_len() || len > buf.len() {
+            return Ok(None);
+        }
+        self.nvm
+            .read(offset + Self::HEADER_LEN, &mut buf[..len])
+            .map_err(NvmSlotError::Nvm)?;
+        Ok(Some(len))
+    }
+
+    fn store(&mut self, key: NvKey, data: &[u8]) -> Result<(), Self::Error> {
+        let offset = self.slot_offset(key)?;
+        let max_len = self.max_record_len();
+        if data.len() > max_len {
+            return Err(NvmSlotError::RecordTooLarge {
+                len: data.len(),
+                max_len,
+            });
+        }
+        let info = self.nvm.info();
+        if info.erase_required {
+            self.nvm
+                .erase(offset, self.slot_size)
+                .map_err(NvmSlotError::Nvm)?;
+        }
+        // The record is padded with erased memory to the write size of the memory.
+        let write_size = info.write_size.max(1);
+        let record_len = Self::HEADER_LEN + data.len();
+        let padded_len = (record_len + write_size - 1) / write_size * write_size;
+        let header = (data.len() as u16).to_be_bytes();
+        let mut chunk = [ERASED_VALUE; Self::MAX_WRITE_SIZE];
+        let chunk_len = (Self::MAX_WRITE_SIZE / write_size) * write_size;
+        let mut written = 0;
+        while written < padded_len {
+            let current_len = core::cmp::min(chunk_len, padded_len - written);
+            for (idx, byte) in chunk[..current_len].iter_mut().enumerate() {
+                let pos = written + idx;
+                *byte = if pos < Self::HEADER_LEN {
+                    header[pos]
+                } else if pos < record_len {
+                    data[pos - Self::HEADER_LEN]
+                } else {
+                    ERASED_VALUE
+                };
+            }
+            self.nvm
+                .write(offset + written, &chunk[..current_len])
+                .map_err(NvmSlotError::Nvm)?;
+            written += current_len;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::vec::Vec;
+    use core::convert::Infallible;
+
+    use hashbrown::HashMap;
+
+    use super::*;
+
+    /// [NvStorage] which keeps the records in RAM. It is intended for simulations and tests.
+    #[derive(Debug, Default, Clone)]
+    pub struct RamNvStorage {
+        records: HashMap<NvKey, Vec<u8>>,
+    }
+
+    impl RamNvStorage {
+        pub fn num_records(&self) -> usize {
+            self.records.len()
+        }
+
+        pub fn record(&self, key: NvKey) -> Option<&[u8]> {
+            self.records.get(&key).map(|record| record.as_slice())
+        }
+
+        /// Remove all records, which simulates an erased storage.
+        pub fn clear(&mut self) {
+            self.records.clear();
+        }
+    }
+
+    impl NvStorage for RamNvStorage {
+        type Error = Infallible;
+
+        fn load(&mut self, key: NvKey, buf: &mut [u8]) -> Result<Option<usize>, Self::Error> {
+            Ok(match self.records.get(&key) {
+                Some(record) if record.len() <= buf.len() => {
+                    buf[..record.len()].copy_from_slice(record);
+                    Some(record.len())
+                }
+                _ => None,
+            })
+        }
+
+        fn store(&mut self, key: NvKey, data: &[u8]) -> Result<(), Self::Error> {
+            self.records.insert(key, data.to_vec());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::hal::nvm::{NvmInfo, RamNvm};
+    use crate::seq_count::{PersistentSeqCountProvider, SequenceCountProviderCore};
+
+    #[test]
+    fn test_integer_records() {
+        let mut storage = RamNvStorage::default();
+        assert_eq!(storage.load_u32(1).unwrap(), None);
+        storage.store_u32(1, 0x01020304).unwrap();
+        storage.store_u16(2, 0x0506).unwrap();
+        assert_eq!(storage.record(1), Some([1, 2, 3, 4].as_slice()));
+        assert_eq!(storage.load_u32(1).unwrap(), Some(0x01020304));
+        assert_eq!(storage.load_u16(2).unwrap(), Some(0x0506));
+        // Records with a different length are not interpreted.
+        assert_eq!(storage.load_u16(1).unwrap(), None);
+        let mut small_buf = [0; 1];
+        assert_eq!(storage.load(1, &mut small_buf).unwrap(), None);
+        assert_eq!(storage.num_records(), 2);
+    }
+
+    #[test]
+    fn test_seq_count_backend() {
+        let mut storage = RamNvStorage::default();
+        let counter =
+            PersistentSeqCountProvider::new(NvSeqCountBackend::new(&mut storage, 3), 10).unwrap();
+        assert_eq!(counter.get_and_increment(), 0);
+        assert_eq!(counter.backend().key(), 3);
+        drop(counter);
+        assert_eq!(storage.load_u16(3).unwrap(), Some(10));
+        // Reboot: The counter continues after the reserved block.
+        let counter =
+            PersistentSeqCountProvider::new(NvSeqCountBackend::new(&mut storage, 3), 10).unwrap();
+        assert_eq!(counter.get(), 10);
+    }
+
+    #[test]
+    fn test_nvm_slot_storage_flash() {
+        let mut flash = RamNvm::new(NvmInfo::new_flash(1024, 256, 4, 100));
+        assert!(NvmSlotStorage::new(&mut flash, 128).is_err());
+        let mut storage = NvmSlotStorage::new(&mut flash, 256).unwrap();
+        assert_eq!(storage.num_slots(), 4);
+        assert_eq!(storage.max_record_len(), 254);
+        assert_eq!(storage.load_u32(1).unwrap(), None);
+        storage.store_u32(1, 5).unwrap();
+        storage.store_u32(1, 6).unwrap();
+        assert_eq!(storage.load_u32(1).unwrap(), Some(6));
+        storage.store(2, &[1, 2, 3]).unwrap();
+        let mut buf = [0; 8];
+        assert_eq!(storage.load(2, &mut buf).unwrap(), Some(3));
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+        assert_eq!(storage.store(4, &[1]), Err(NvmSlotError::InvalidKey(4)));
+        assert_eq!(
+            storage.store(0, &[0; 255]),
+            Err(NvmSlotError::RecordTooLarge {
+                len: 255,
+                max_len: 254
+            })
+        );
+        assert_eq!(flash.erase_count(1), Some(2));
+        assert_eq!(
+            &flash.memory()[512..520],
+            &[0, 3, 1, 2, 3, 0xff, 0xff, 0xff]
+        );
+    }
+
+    #[test]
+    fn test_nvm_slot_storage_eeprom() {
+        let eeprom = RamNvm::new(NvmInfo::new_eeprom(256, 16));
+        let mut storage = NvmSlotStorage::new(eeprom, 100).unwrap();
+        assert_eq!(storage.num_slots(), 2);
+        let long_record = [0x42; 98];
+        storage.store(1, &long_record).unwrap();
+        let mut buf = [0; 98];
+        assert_eq!(storage.load(1, &mut buf).unwrap(), Some(98));
+        assert_eq!(buf, long_record);
+        // The record does not fit into the buffer.
+        assert_eq!(storage.load(1, &mut buf[..4]).unwrap(), None);
+        assert_eq!(storage.nvm().max_erase_count(), 0);
+    }
+}
@@ -0,0 +1,403 @@
+//! # Object manager with dependency-aware initialization
+//!
+//! Large on-board software compositions consist of many components which depend on each other
+//! during their bring-up. For example, a device handler might require that the communication
+//! interface it uses was initialized first.
+//!
+//! The [ObjectManager] owns all [ManagedObject]s, which are identified by their [ComponentId].
+//! Each object can declare the IDs of the objects it depends on. [ObjectManager::initialize]
+//! initializes all objects in a topologically sorted order, so every object is initialized after
+//! all its dependencies. Missing dependencies and dependency cycles are detected before any
+//! object is initialized.
+//!
+//! The objects can be retrieved as their concrete type with [ObjectManager::get] and
+//! [ObjectManager::get_mut] because the [ManagedObject] trait implements [Downcast].
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use downcast_rs::{impl_downcast, Downcast};
+use hashbrown::{HashMap, HashSet};
+use satrs_shared::res_code::ResultU16;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::ComponentId;
+
+/// Generic trait for objects which are managed by the [ObjectManager].
+pub trait ManagedObject: Downcast {
+    fn id(&self) -> ComponentId;
+
+    /// IDs of the objects which need to be initialized before this object.
+    fn dependencies(&self) -> &[ComponentId] {
+        &[]
+    }
+
+    /// Initialize the object. The failure is reported using a generic result code.
+    fn initialize(&mut self) -> Result<(), ResultU16>;
+}
+
+impl_downcast!(ManagedObject);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectManagerError {
+    /// An object with the same ID was already inserted.
+    DuplicateId(ComponentId),
+    /// An object depends on an object which does not exist.
+    MissingDependency {
+        object: ComponentId,
+        dependency: ComponentId,
+    },
+    /// The objects depend on each other in a cycle. The first object is repeated at the end of
+    /// the list, so an object directly depending on itself is reported as `[id, id]`.
+    DependencyCycle(Vec<ComponentId>),
+    /// The initialization of an object failed. Objects which were ordered after the failed
+    /// object were not initialized.
+    InitFailed {
+        object: ComponentId,
+        code: ResultU16,
+    },
+}
+
+impl Display for ObjectManagerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ObjectManagerError::DuplicateId(id) => write!(f, "duplicate object ID {id}"),
+            ObjectManagerError::MissingDependency { object, dependency } => {
+                write!(f, "object {object} depends on missing object {dependency}")
+            }
+            ObjectManagerError::DependencyCycle(cycle) => {
+                write!(f, "dependency cycle between objects: ")?;
+                for (idx, id) in cycle.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, " -> ")?;
+                    }
+                    write!(f, "{id}")?;
+                }
+                Ok(())
+            }
+            ObjectManagerError::InitFailed { object, code } => {
+                write!(
+                    f,
+                    "initialization of object {object} failed with code {}:{}",
+                    code.group_id(),
+                    code.unique_id()
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ObjectManagerError {}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum VisitState {
+    InProgress,
+    Done,
+}
+
+/// Container for all [ManagedObject]s of an application.
+#[derive(Default)]
+pub struct ObjectManager {
+    objects: HashMap<ComponentId, Box<dyn ManagedObject>>,
+    /// IDs of all objects in insertion order. This makes the initialization order deterministic
+    /// for objects which do not depend on each other.
+    insertion_order: Vec<ComponentId>,
+}
+
+impl ObjectManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a new object. Returns [ObjectManagerError::DuplicateId] if an object with the same
+    /// ID already exists.
+    pub fn insert(&mut self, object: Box<dyn ManagedObject>) -> Result<(), ObjectManagerError> {
+        let id = object.id();
+        if self.objects.contains_key(&id) {
+            return Err(ObjectManagerError::DuplicateId(id));
+        }
+        self.objects.insert(id, object);
+        self.insertion_order.push(id);
+        Ok(())
+    }
+
+    /// Remove the object with the given ID.
+    pub fn remove(&mut self, id: ComponentId) -> Option<Box<dyn ManagedObject>> {
+        let object = self.objects.remove(&id)?;
+        self.insertion_order.retain(|other| *other != id);
+        Some(object)
+    }
+
+    pub fn contains(&self, id: ComponentId) -> bool {
+        self.objects.contains_key(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Retrieve the object with the given ID as its concrete type. Returns [None] if the object
+    /// does not exist or has a different type.
+    pub fn get<T: ManagedObject>(&self, id: ComponentId) -> Option<&T> {
+        self.objects.get(&id)?.downcast_ref::<T>()
+    }
+
+    /// Mutable variant of [Self::get].
+    pub fn get_mut<T: ManagedObject>(&mut self, id: ComponentId) -> Option<&mut T> {
+        self.objects.get_mut(&id)?.downcast_mut::<T>()
+    }
+
+    pub fn get_dyn(&self, id: ComponentId) -> Option<&dyn ManagedObject> {
+        self.objects.get(&id).map(|object| object.as_ref())
+    }
+
+    pub fn get_dyn_mut(&mut self, id: ComponentId) -> Option<&mut dyn ManagedObject> {
+        match self.objects.get_mut(&id) {
+            Some(object) => Some(object.as_mut()),
+            None => None,
+        }
+    }
+
+    /// Calculate the initialization order of all objects. Every object is ordered after all its
+    /// dependencies. Independent objects are ordered by their insertion order.
+    pub fn initialization_order(&self) -> Result<Vec<ComponentId>, ObjectManagerError> {
+        let mut order = Vec::with_capacity(self.objects.len());
+        let mut states = HashMap::with_capacity(self.objects.len());
+        let mut path = Vec::new();
+        for id in &self.insertion_order {
+            self.visit(*id, &mut states, &mut path, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Initialize all objects in the order determined by [Self::initialization_order]. No object
+    /// is initialized if the dependencies are invalid.
+    pub fn initialize(&mut self) -> Result<(), ObjectManagerError> {
+        let order = self.initialization_order()?;
+        for id in order {
+            // The order only contains existing objects.
+            let object = self.objects.get_mut(&id).unwrap();
+            object
+                .initialize()
+                .map_err(|code| ObjectManagerError::InitFailed { object: id, code })?;
+        }
+        Ok(())
+    }
+
+    /// Depth-first search which appends the object to the order after all its dependencies.
+    fn visit(
+        &self,
+        id: ComponentId,
+        states: &mut HashMap<ComponentId, VisitState>,
+        path: &mut Vec<ComponentId>,
+        order: &mut Vec<ComponentId>,
+    ) -> Result<(), ObjectManagerError> {
+        match states.get(&id) {
+            Some(VisitState::Done) => return Ok(()),
+            Some(VisitState::InProgress) => {
+                // The object is part of the current path, so the path contains a cycle.
+                let start = path.iter().position(|other| *other == id).unwrap();
+                let mut cycle = path[start..].to_vec();
+                cycle.push(id);
+                return Err(ObjectManagerError::DependencyCycle(cycle));
+            }
+            None => (),
+        }
+        states.insert(id, VisitState::InProgress);
+        path.push(id);
+        let object = &self.objects[&id];
+        // Duplicate dependency entries are allowed.
+        let mut visited_deps = HashSet::new();
+        for dependency in object.dependencies() {
+            if !visited_deps.insert(*dependency) {
+                continue;
+            }
+            if !self.objects.contains_key(dependency) {
+                return Err(ObjectManagerError::MissingDependency {
+                    object: id,
+                    dependency: *dependency,
+                });
+            }
+            self.visit(*dependency, states, path, order)?;
+        }
+        path.pop();
+        states.insert(id, VisitState::Done);
+        order.push(id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::rc::Rc;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use core::cell::RefCell;
+
+    use super::*;
+
+    const INIT_FAILED: ResultU16 = ResultU16::new(1, 2);
+
+    struct TestObject {
+        id: ComponentId,
+        dependencies: Vec<ComponentId>,
+        init_log: Rc<RefCell<Vec<ComponentId>>>,
+        fail_init: bool,
+        value: u32,
+    }
+
+    impl TestObject {
+        fn new(
+            id: ComponentId,
+            dependencies: &[ComponentId],
+            init_log: &Rc<RefCell<Vec<ComponentId>>>,
+        ) -> Box<Self> {
+            Box::new(Self {
+                id,
+                dependencies: dependencies.to_vec(),
+                init_log: init_log.clone(),
+                fail_init: false,
+                value: 0,
+            })
+        }
+    }
+
+    impl ManagedObject for TestObject {
+        fn id(&self) -> ComponentId {
+            self.id
+        }
+
+        fn dependencies(&self) -> &[ComponentId] {
+            &self.dependencies
+        }
+
+        fn initialize(&mut self) -> Result<(), ResultU16> {
+            if self.fail_init {
+                return Err(INIT_FAILED);
+            }
+            self.init_log.borrow_mut().push(self.id);
+            Ok(())
+        }
+    }
+
+    struct OtherObject;
+
+    impl ManagedObject for OtherObject {
+        fn id(&self) -> ComponentId {
+            100
+        }
+
+        fn initialize(&mut self) -> Result<(), ResultU16> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_initialization_order() {
+        let init_log = Rc::new(RefCell::new(Vec::new()));
+        let mut manager = ObjectManager::new();
+        manager
+            .insert(TestObject::new(1, &[3, 2], &init_log))
+            .unwrap();
+        manager.insert(TestObject::new(2, &[4], &init_log)).unwrap();
+        manager.insert(TestObject::new(3, &[], &init_log)).unwrap();
+        manager.insert(TestObject::new(4, &[], &init_log)).unwrap();
+        manager.insert(TestObject::new(5, &[], &init_log)).unwrap();
+        assert_eq!(manager.len(), 5);
+        assert_eq!(manager.initialization_order().unwrap(), vec![3, 4, 2, 1, 5]);
+        manager.initialize().unwrap();
+        assert_eq!(*init_log.borrow(), vec![3, 4, 2, 1, 5]);
+    }
+
+    #[test]
+    fn test_duplicate_id() {
+        let init_log = Rc::new(RefCell::new(Vec::new()));
+        let mut manager = ObjectManager::new();
+        manager.insert(TestObject::new(1, &[], &init_log)).unwrap();
+        assert_eq!(
+            manager.insert(TestObject::new(1, &[], &init_log)),
+            Err(ObjectManagerError::DuplicateId(1))
+        );
+    }
+
+    #[test]
+    fn test_missing_dependency() {
+        let init_log = Rc::new(RefCell::new(Vec::new()));
+        let mut manager = ObjectManager::new();
+        manager.insert(TestObject::new(1, &[2], &init_log)).unwrap();
+        let error = manager.initialize().unwrap_err();
+        assert_eq!(
+            error,
+            ObjectManagerError::MissingDependency {
+                object: 1,
+                dependency: 2
+            }
+        );
+        assert_eq!(error.to_string(), "object 1 depends on missing object 2");
+        assert!(init_log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_dependency_cycle() {
+        let init_log = Rc::new(RefCell::new(Vec::new()));
+        let mut manager = ObjectManager::new();
+        manager.insert(TestObject::new(1, &[2], &init_log)).unwrap();
+        manager.insert(TestObject::new(2, &[3], &init_log)).unwrap();
+        manager.insert(TestObject::new(3, &[1], &init_log)).unwrap();
+        let error = manager.initialize().unwrap_err();
+        assert_eq!(error, ObjectManagerError::DependencyCycle(vec![1, 2, 3, 1]));
+        assert_eq!(
+            error.to_string(),
+            "dependency cycle between objects: 1 -> 2 -> 3 -> 1"
+        );
+        assert!(init_log.borrow().is_empty());
+
+        let mut manager = ObjectManager::new();
+        manager.insert(TestObject::new(1, &[1], &init_log)).unwrap();
+        assert_eq!(
+            manager.initialization_order().unwrap_err(),
+            ObjectManagerError::DependencyCycle(vec![1, 1])
+        );
+    }
+
+    #[test]
+    fn test_init_failure() {
+        let init_log = Rc::new(RefCell::new(Vec::new()));
+        let mut manager = ObjectManager::new();
+        let mut failing = TestObject::new(1, &[], &init_log);
+        failing.fail_init = true;
+        manager.insert(failing).unwrap();
+        manager.insert(TestObject::new(2, &[1], &init_log)).unwrap();
+        assert_eq!(
+            manager.initialize().unwrap_err(),
+            ObjectManagerError::InitFailed {
+                object: 1,
+                code: INIT_FAILED
+            }
+        );
+        assert!(init_log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_get_mut_and_remove() {
+        let init_log = Rc::new(RefCell::new(Vec::new()));
+        let mut manager = ObjectManager::new();
+        manager.insert(TestObject::new(1, &[], &init_log)).unwrap();
+        manager.insert(Box::new(OtherObject)).unwrap();
+        manager.get_mut::<TestObject>(1).unwrap().value = 5;
+        assert_eq!(manager.get::<TestObject>(1).unwrap().value, 5);
+        assert!(manager.get::<OtherObject>(1).is_none());
+        assert!(manager.get::<OtherObject>(100).is_some());
+        assert_eq!(manager.get_dyn(100).unwrap().id(), 100);
+        let removed = manager.remove(1).unwrap();
+        assert_eq!(removed.id(), 1);
+        assert!(!manager.contains(1));
+        assert!(manager.remove(1).is_none());
+        assert_eq!(manager.initialization_order().unwrap(), vec![100]);
+    }
+}
@@ -0,0 +1,754 @@
+//! Parameter database.
+//!
+//! The [ParamDb] is a registry of typed parameters which are identified by a [ParamId]
+//! consisting of a domain ID and a unique ID. All access methods only require a shared
+//! reference, so the database can be shared between threads using an [Arc](std::sync::Arc).
+//! The parameters are guarded by a [RwLock], so flight code reading parameters does not block
+//! other readers.
+//!
+//! Supported parameter types are all primitive integer and floating point types and fixed-size
+//! arrays of them. Type safe access is provided by the [ParamDbValue] trait, and the size of a
+//! parameter can not change after it was registered.
+//!
+//! Parameter changes can be observed with a [ParamChangeListener], for example the
+//! [ParamChangeEventReporter] which generates an event for each change. The [ParamValue] can be
+//! serialized to and deserialized from a raw big endian format, which allows using the database
+//! as the backend for parameter management and housekeeping services and for persisting the
+//! parameters with a [ParamPersistence] implementation.
+//!
+//! # Example
+//!
+//! ```
+//! use satrs::param_db::{ParamDb, ParamId};
+//!
+//! const GAIN: ParamId = ParamId::new(1, 1);
+//! const OFFSETS: ParamId = ParamId::new(1, 2);
+//!
+//! let param_db = ParamDb::default();
+//! param_db.register(GAIN, 0.5_f32).unwrap();
+//! param_db.register(OFFSETS, [0_i16; 3]).unwrap();
+//!
+//! param_db.set(OFFSETS, [-1_i16, 2, 3]).unwrap();
+//! assert_eq!(param_db.get::<f32>(GAIN).unwrap(), 0.5);
+//! assert_eq!(param_db.get::<[i16; 3]>(OFFSETS).unwrap(), [-1, 2, 3]);
+//! // The type of a parameter can not be changed.
+//! assert!(param_db.set(GAIN, 2_u32).is_err());
+//! ```
+use core::fmt::{Display, Formatter};
+use hashbrown::HashMap;
+use spacepackets::ByteConversionError;
+use std::boxed::Box;
+use std::error::Error;
+use std::sync::{Mutex, RwLock};
+use std::vec;
+use std::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::event_man::{EventMessage, EventSendProvider};
+use crate::events::EventU32;
+use crate::params::{Params, ParamsRaw, WritableToBeBytes};
+use crate::ComponentId;
+
+/// Unique identifier of a parameter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParamId {
+    pub domain_id: u16,
+    pub unique_id: u16,
+}
+
+impl ParamId {
+    pub const fn new(domain_id: u16, unique_id: u16) -> Self {
+        Self {
+            domain_id,
+            unique_id,
+        }
+    }
+
+    /// Raw 32 bit representation with the domain ID in the upper 16 bits.
+    pub const fn raw(&self) -> u32 {
+        ((self.domain_id as u32) << 16) | self.unique_id as u32
+    }
+
+    pub const fn from_raw(raw: u32) -> Self {
+        Self::new((raw >> 16) as u16, raw as u16)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PrimitiveType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl PrimitiveType {
+    /// Size of the type in bytes.
+    pub const fn size(&self) -> usize {
+        match self {
+            PrimitiveType::U8 | PrimitiveType::I8 => 1,
+            PrimitiveType::U16 | PrimitiveType::I16 => 2,
+            PrimitiveType::U32 | PrimitiveType::I32 | PrimitiveType::F32 => 4,
+            PrimitiveType::U64 | PrimitiveType::I64 | PrimitiveType::F64 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParamType {
+    Scalar(PrimitiveType),
+    /// Array of a primitive type with a fixed length.
+    Array(PrimitiveType, usize),
+}
+
+impl ParamType {
+    /// Size of a parameter of this type in its raw format.
+    pub const fn raw_len(&self) -> usize {
+        match self {
+            ParamType::Scalar(primitive) => primitive.size(),
+            ParamType::Array(primitive, len) => primitive.size() * *len,
+        }
+    }
+}
+
+/// Value of a parameter stored inside the [ParamDb].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParamValue {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    U8Array(Vec<u8>),
+    I8Array(Vec<i8>),
+    U16Array(Vec<u16>),
+    I16Array(Vec<i16>),
+    U32Array(Vec<u32>),
+    I32Array(Vec<i32>),
+    U64Array(Vec<u64>),
+    I64Array(Vec<i64>),
+    F32Array(Vec<f32>),
+    F64Array(Vec<f64>),
+}
+
+macro_rules! param_value_impls {
+    ($(($ty: ty, $Scalar: ident, $Array: ident),)+) => {
+        impl ParamValue {
+            pub fn param_type(&self) -> ParamType {
+                match self {
+                    $(
+                        ParamValue::$Scalar(_) => ParamType::Scalar(PrimitiveType::$Scalar),
+                        ParamValue::$Array(values) => {
+                            ParamType::Array(PrimitiveType::$Scalar, values.len())
+                        }
+                    )+
+                }
+            }
+
+            /// Deserialize a parameter of the given type from its raw big endian format.
+            pub fn from_be_bytes(
+                param_type: ParamType,
+                buf: &[u8],
+            ) -> Result<Self, ByteConversionError> {
+                let expected = param_type.raw_len();
+                if buf.len() < expected {
+                    return Err(ByteConversionError::FromSliceTooSmall {
+                        found: buf.len(),
+                        expected,
+                    });
+                }
+                Ok(match param_type {
+                    $(
+                        ParamType::Scalar(PrimitiveType::$Scalar) => ParamValue::$Scalar(
+                            <$ty>::from_be_bytes(buf[..expected].try_into().unwrap()),
+                        ),
+                        ParamType::Array(PrimitiveType::$Scalar, _) => ParamValue::$Array(
+                            buf[..expected]
+                                .chunks_exact(core::mem::size_of::<$ty>())
+                                .map(|chunk| <$ty>::from_be_bytes(chunk.try_into().unwrap()))
+                                .collect(),
+                        ),
+                    )+
+                })
+            }
+        }
+
+        impl WritableToBeBytes for ParamValue {
+            fn written_len(&self) -> usize {
+                self.param_type().raw_len()
+            }
+
+            fn write_to_be_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+                let written_len = self.written_len();
+                if buf.len() < written_len {
+                    return Err(ByteConversionError::ToSliceTooSmall {
+                        found: buf.len(),
+                        expected: written_len,
+                    });
+                }
+                match self {
+                    $(
+                        ParamValue::$Scalar(value) => {
+                            buf[..written_len].copy_from_slice(&value.to_be_bytes());
+                        }
+                        ParamValue::$Array(values) => {
+                            for (chunk, value) in buf[..written_len]
+                                .chunks_exact_mut(core::mem::size_of::<$ty>())
+                                .zip(values)
+                            {
+                                chunk.copy_from_slice(&value.to_be_bytes());
+                            }
+                        }
+                    )+
+                }
+                Ok(written_len)
+            }
+        }
+
+        $(
+            impl ParamDbValue for $ty {
+                fn from_param_value(value: &ParamValue) -> Option<Self> {
+                    match value {
+                        ParamValue::$Scalar(value) => Some(*value),
+                        _ => None,
+                    }
+                }
+
+                fn into_param_value(self) -> ParamValue {
+                    ParamValue::$Scalar(self)
+                }
+            }
+
+            impl<const N: usize> ParamDbValue for [$ty; N] {
+                fn from_param_value(value: &ParamValue) -> Option<Self> {
+                    match value {
+                        ParamValue::$Array(values) => values.as_slice().try_into().ok(),
+                        _ => None,
+                    }
+                }
+
+                fn into_param_value(self) -> ParamValue {
+                    ParamValue::$Array(self.to_vec())
+                }
+            }
+
+            impl ParamDbValue for Vec<$ty> {
+                fn from_param_value(value: &ParamValue) -> Option<Self> {
+                    match value {
+                        ParamValue::$Array(values) => Some(values.clone()),
+                        _ => None,
+                    }
+                }
+
+                fn into_param_value(self) -> ParamValue {
+                    ParamValue::$Array(self)
+                }
+            }
+        )+
+    };
+}
+
+param_value_impls!(
+    (u8, U8, U8Array),
+    (i8, I8, I8Array),
+    (u16, U16, U16Array),
+    (i16, I16, I16Array),
+    (u32, U32, U32Array),
+    (i32, I32, I32Array),
+    (u64, U64, U64Array),
+    (i64, I64, I64Array),
+    (f32, F32, F32Array),
+    (f64, F64, F64Array),
+);
+
+/// Types which can be stored inside the [ParamDb].
+///
+/// This trait is implemented for all supported primitive types, for arrays and vectors of them
+/// and for the [ParamValue] itself.
+pub trait ParamDbValue: Sized {
+    /// Returns [None] if the value has a different type.
+    fn from_param_value(value: &ParamValue) -> Option<Self>;
+    fn into_param_value(self) -> ParamValue;
+}
+
+impl ParamDbValue for ParamValue {
+    fn from_param_value(value: &ParamValue) -> Option<Self> {
+        Some(value.clone())
+    }
+
+    fn into_param_value(self) -> ParamValue {
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamDbError {
+    UnknownParam(ParamId),
+    AlreadyRegistered(ParamId),
+    /// The requested or passed type does not match the registered type of the parameter.
+    TypeMismatch {
+        id: ParamId,
+        registered: ParamType,
+    },
+    ByteConversion(ByteConversionError),
+}
+
+impl Display for ParamDbError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParamDbError::UnknownParam(id) => write!(f, "unknown parameter {id:?}"),
+            ParamDbError::AlreadyRegistered(id) => {
+                write!(f, "parameter {id:?} is already registered")
+            }
+            ParamDbError::TypeMismatch { id, registered } => write!(
+                f,
+                "type mismatch for parameter {id:?} with registered type {registered:?}"
+            ),
+            ParamDbError::ByteConversion(e) => write!(f, "byte conversion error: {e}"),
+        }
+    }
+}
+
+impl From<ByteConversionError> for ParamDbError {
+    fn from(value: ByteConversionError) -> Self {
+        Self::ByteConversion(value)
+    }
+}
+
+impl Error for ParamDbError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParamDbError::ByteConversion(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Generic listener which is notified about all parameter changes of a [ParamDb].
+///
+/// This trait is implemented for all closures taking the [ParamId] and the new [ParamValue].
+pub trait ParamChangeListener: Send {
+    fn param_changed(&mut self, id: ParamId, value: &ParamValue);
+}
+
+impl<F: FnMut(ParamId, &ParamValue) + Send> ParamChangeListener for F {
+    fn param_changed(&mut self, id: ParamId, value: &ParamValue) {
+        self(id, value)
+    }
+}
+
+/// [ParamChangeListener] which reports each parameter change as an event.
+///
+/// The event is sent with a [u32] parameter containing the [raw parameter ID][ParamId::raw].
+pub struct ParamChangeEventReporter<Sender: EventSendProvider<EventU32> + Send> {
+    sender_id: ComponentId,
+    event: EventU32,
+    sender: Sender,
+}
+
+impl<Sender: EventSendProvider<EventU32> + Send> ParamChangeEventReporter<Sender> {
+    pub fn new(sender_id: ComponentId, event: EventU32, sender: Sender) -> Self {
+        Self {
+            sender_id,
+            event,
+            sender,
+        }
+    }
+}
+
+impl<Sender: EventSendProvider<EventU32> + Send> ParamChangeListener
+    for ParamChangeEventReporter<Sender>
+{
+    fn param_changed(&mut self, id: ParamId, _value: &ParamValue) {
+        let params = Params::from(ParamsRaw::from(id.raw()));
+        // The parameter was already changed, so a failed notification is not propagated.
+        let _ = self.sender.send(EventMessage::new_with_params(
+            self.sender_id,
+            self.event,
+            &params,
+        ));
+    }
+}
+
+/// Generic backend which can be used to persist the parameters of a [ParamDb], for example in a
+/// file or a non-volatile memory.
+///
+/// The parameters are passed in their raw big endian format.
+pub trait ParamPersistence {
+    type Error;
+
+    fn store(&mut self, id: ParamId, raw: &[u8]) -> Result<(), Self::Error>;
+
+    /// Load the raw parameter into the provided buffer, which has the size of the registered
+    /// parameter. Returns [None] if no value was stored for the parameter.
+    fn load(&mut self, id: ParamId, buf: &mut [u8]) -> Result<Option<usize>, Self::Error>;
+}
+
+/// Thread-safe parameter database. See the [module documentation][self] for more details.
+#[derive(Default)]
+pub struct ParamDb {
+    params: RwLock<HashMap<ParamId, ParamValue>>,
+    listeners: Mutex<Vec<Box<dyn ParamChangeListener>>>,
+}
+
+impl ParamDb {
+    /// Register a new parameter with its initial value, which also determines its type.
+    pub fn register(&self, id: ParamId, initial: impl ParamDbValue) -> Result<(), ParamDbError> {
+        let mut params = self.params.write().unwrap_or_else(|e| e.into_inner());
+        if params.contains_key(&id) {
+            return Err(ParamDbError::AlreadyRegistered(id));
+        }
+        params.insert(id, initial.into_param_value());
+        Ok(())
+    }
+
+    pub fn add_listener(&self, listener: impl ParamChangeListener + 'static) {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Box::new(listener));
+    }
+
+    pub fn contains(&self, id: ParamId) -> bool {
+        self.params
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(&id)
+    }
+
+    pub fn num_params(&self) -> usize {
+        self.params.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// IDs of all registered parameters in ascending order.
+    pub fn ids(&self) -> Vec<ParamId> {
+        let mut ids: Vec<ParamId> = self
+            .params
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn param_type(&self, id: ParamId) -> Result<ParamType, ParamDbError> {
+        self.with_value(id, |value| value.param_type())
+    }
+
+    /// Retrieve a typed parameter.
+    pub fn get<T: ParamDbValue>(&self, id: ParamId) -> Result<T, ParamDbError> {
+        self.with_value(id, |value| {
+            T::from_param_value(value).ok_or_else(|| ParamDbError::TypeMismatch {
+                id,
+                registered: value.param_type(),
+            })
+        })?
+    }
+
+    /// Call the given closure with a reference to the parameter value. This avoids copying
+    /// large array parameters.
+    pub fn with_value<R>(
+        &self,
+        id: ParamId,
+        f: impl FnOnce(&ParamValue) -> R,
+    ) -> Result<R, ParamDbError> {
+        let params = self.params.read().unwrap_or_else(|e| e.into_inner());
+        let value = params.get(&id).ok_or(ParamDbError::UnknownParam(id))?;
+        Ok(f(value))
+    }
+
+    /// Update a parameter. The type and size of the new value must match the registered
+    /// parameter. All listeners are notified if the value changed.
+    pub fn set(&self, id: ParamId, value: impl ParamDbValue) -> Result<(), ParamDbError> {
+        let value = value.into_param_value();
+        let changed = {
+            let mut params = self.params.write().unwrap_or_else(|e| e.into_inner());
+            let current = params.get_mut(&id).ok_or(ParamDbError::UnknownParam(id))?;
+            if current.param_type() != value.param_type() {
+                return Err(ParamDbError::TypeMismatch {
+                    id,
+                    registered: current.param_type(),
+                });
+            }
+            let changed = *current != value;
+            *current = value.clone();
+            changed
+        };
+        // Listeners are notified after the lock was released, so they can access the database.
+        if changed {
+            for listener in self
+                .listeners
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter_mut()
+            {
+                listener.param_changed(id, &value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a parameter in its raw big endian format into the provided buffer. Returns the
+    /// written length.
+    pub fn write_to_be_bytes(&self, id: ParamId, buf: &mut [u8]) -> Result<usize, ParamDbError> {
+        let written_len = self.with_value(id, |value| value.write_to_be_bytes(buf))??;
+        Ok(written_len)
+    }
+
+    /// Update a parameter from its raw big endian format. Returns the number of bytes read
+    /// from the buffer.
+    pub fn set_from_be_bytes(&self, id: ParamId, buf: &[u8]) -> Result<usize, ParamDbError> {
+        let param_type = self.param_type(id)?;
+        self.set(id, ParamValue::from_be_bytes(param_type, buf)?)?;
+        Ok(param_type.raw_len())
+    }
+
+    /// Store all parameters using the given persistence backend.
+    pub fn persist<P: ParamPersistence>(&self, persistence: &mut P) -> Result<(), P::Error> {
+        let mut buf = Vec::new();
+        for id in self.ids() {
+            let written_len = match self.with_value(id, |value| {
+                buf.resize(value.written_len(), 0);
+                value.write_to_be_bytes(&mut buf)
+            }) {
+                Ok(Ok(written_len)) => written_len,
+                // The parameter was removed concurrently or the buffer is too small, which
+                // can not happen because it was resized.
+                _ => continue,
+            };
+            persistence.store(id, &buf[..written_len])?;
+        }
+        Ok(())
+    }
+
+    /// Restore all registered parameters from the given persistence backend. Stored values
+    /// which do not match the size of the registered parameter are ignored.
+    ///
+    /// Returns the number of restored parameters. Listeners are notified for all parameters
+    /// which changed.
+    pub fn restore<P: ParamPersistence>(&self, persistence: &mut P) -> Result<usize, P::Error> {
+        let mut restored = 0;
+        for id in self.ids() {
+            let param_type = match self.param_type(id) {
+                Ok(param_type) => param_type,
+                Err(_) => continue,
+            };
+            let mut buf = vec![0; param_type.raw_len()];
+            if persistence.load(id, &mut buf)? != Some(param_type.raw_len()) {
+                continue;
+            }
+            if self.set_from_be_bytes(id, &buf).is_ok() {
+                restored += 1;
+            }
+        }
+        Ok(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::event_man::EventSenderMpsc;
+    use crate::events::Severity;
+    use crate::params::ParamsHeapless;
+
+    use super::*;
+
+    const PARAM_U8: ParamId = ParamId::new(1, 1);
+    const PARAM_F64: ParamId = ParamId::new(1, 2);
+    const PARAM_I16_ARRAY: ParamId = ParamId::new(2, 1);
+    const CHANGE_EVENT: EventU32 = EventU32::new(Severity::Info, 3, 1);
+
+    fn create_db() -> ParamDb {
+        let db = ParamDb::default();
+        db.register(PARAM_U8, 5_u8).unwrap();
+        db.register(PARAM_F64, 1.5_f64).unwrap();
+        db.register(PARAM_I16_ARRAY, [1_i16, -2, 3]).unwrap();
+        db
+    }
+
+    #[derive(Default)]
+    struct TestPersistence {
+        stored: HashMap<ParamId, Vec<u8>>,
+    }
+
+    impl ParamPersistence for TestPersistence {
+        type Error = ();
+
+        fn store(&mut self, id: ParamId, raw: &[u8]) -> Result<(), Self::Error> {
+            self.stored.insert(id, raw.to_vec());
+            Ok(())
+        }
+
+        fn load(&mut self, id: ParamId, buf: &mut [u8]) -> Result<Option<usize>, Self::Error> {
+            match self.stored.get(&id) {
+                Some(raw) if raw.len() <= buf.len() => {
+                    buf[..raw.len()].copy_from_slice(raw);
+                    Ok(Some(raw.len()))
+                }
+                Some(_) => Ok(Some(0)),
+                None => Ok(None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_param_id() {
+        let id = ParamId::new(0x0102, 0x0304);
+        assert_eq!(id.raw(), 0x01020304);
+        assert_eq!(ParamId::from_raw(0x01020304), id);
+    }
+
+    #[test]
+    fn test_typed_access() {
+        let db = create_db();
+        assert_eq!(db.num_params(), 3);
+        assert_eq!(db.get::<u8>(PARAM_U8).unwrap(), 5);
+        assert_eq!(db.get::<f64>(PARAM_F64).unwrap(), 1.5);
+        assert_eq!(db.get::<[i16; 3]>(PARAM_I16_ARRAY).unwrap(), [1, -2, 3]);
+        assert_eq!(db.get::<Vec<i16>>(PARAM_I16_ARRAY).unwrap(), vec![1, -2, 3]);
+        db.set(PARAM_U8, 7_u8).unwrap();
+        assert_eq!(db.get::<u8>(PARAM_U8).unwrap(), 7);
+        assert_eq!(
+            db.param_type(PARAM_I16_ARRAY).unwrap(),
+            ParamType::Array(PrimitiveType::I16, 3)
+        );
+        assert_eq!(db.ids(), vec![PARAM_U8, PARAM_F64, PARAM_I16_ARRAY]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let db = create_db();
+        assert_eq!(
+            db.register(PARAM_U8, 1_u8),
+            Err(ParamDbError::AlreadyRegistered(PARAM_U8))
+        );
+        let unknown = ParamId::new(5, 5);
+        assert_eq!(
+            db.get::<u8>(unknown),
+            Err(ParamDbError::UnknownParam(unknown))
+        );
+        assert_eq!(
+            db.set(unknown, 1_u8),
+            Err(ParamDbError::UnknownParam(unknown))
+        );
+        assert!(matches!(
+            db.get::<u16>(PARAM_U8),
+            Err(ParamDbError::TypeMismatch { .. })
+        ));
+        assert_eq!(
+            db.set(PARAM_I16_ARRAY, [1_i16, 2]),
+            Err(ParamDbError::TypeMismatch {
+                id: PARAM_I16_ARRAY,
+                registered: ParamType::Array(PrimitiveType::I16, 3),
+            })
+        );
+        assert_eq!(db.get::<[i16; 3]>(PARAM_I16_ARRAY).unwrap(), [1, -2, 3]);
+    }
+
+    #[test]
+    fn test_raw_access() {
+        let db = create_db();
+        let mut buf = [0; 8];
+        assert_eq!(db.write_to_be_bytes(PARAM_I16_ARRAY, &mut buf).unwrap(), 6);
+        assert_eq!(buf[..6], [0, 1, 0xff, 0xfe, 0, 3]);
+        assert_eq!(
+            db.write_to_be_bytes(PARAM_I16_ARRAY, &mut buf[..4]),
+            Err(ParamDbError::ByteConversion(
+                ByteConversionError::ToSliceTooSmall {
+                    found: 4,
+                    expected: 6
+                }
+            ))
+        );
+        assert_eq!(
+            db.set_from_be_bytes(PARAM_F64, &2.5_f64.to_be_bytes())
+                .unwrap(),
+            8
+        );
+        assert_eq!(db.get::<f64>(PARAM_F64).unwrap(), 2.5);
+        assert!(db.set_from_be_bytes(PARAM_F64, &[0; 4]).is_err());
+    }
+
+    #[test]
+    fn test_change_notification() {
+        let db = create_db();
+        let changes: Arc<Mutex<Vec<(ParamId, ParamValue)>>> = Arc::default();
+        let changes_clone = changes.clone();
+        db.add_listener(move |id: ParamId, value: &ParamValue| {
+            changes_clone.lock().unwrap().push((id, value.clone()));
+        });
+        let (event_tx, event_rx) = mpsc::channel::<EventMessage<EventU32>>();
+        db.add_listener(ParamChangeEventReporter::new(
+            1,
+            CHANGE_EVENT,
+            EventSenderMpsc::new(2, event_tx),
+        ));
+        db.set(PARAM_U8, 10_u8).unwrap();
+        // Setting the same value again does not cause a notification.
+        db.set(PARAM_U8, 10_u8).unwrap();
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![(PARAM_U8, ParamValue::U8(10))]
+        );
+        let event = event_rx.try_recv().expect("no change event");
+        assert_eq!(event.event(), CHANGE_EVENT);
+        assert_eq!(
+            event.params(),
+            Some(&Params::Heapless(ParamsHeapless::Raw(ParamsRaw::U32(
+                PARAM_U8.raw().into()
+            ))))
+        );
+        assert!(event_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_persistence() {
+        let db = create_db();
+        db.set(PARAM_I16_ARRAY, [4_i16, 5, 6]).unwrap();
+        let mut persistence = TestPersistence::default();
+        db.persist(&mut persistence).unwrap();
+        assert_eq!(persistence.stored.len(), 3);
+
+        let restored_db = create_db();
+        restored_db.register(ParamId::new(9, 9), 0_u32).unwrap();
+        assert_eq!(restored_db.restore(&mut persistence).unwrap(), 3);
+        assert_eq!(
+            restored_db.get::<[i16; 3]>(PARAM_I16_ARRAY).unwrap(),
+            [4, 5, 6]
+        );
+        assert_eq!(restored_db.get::<u32>(ParamId::new(9, 9)).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_shared_access() {
+        let db = Arc::new(create_db());
+        let db_clone = db.clone();
+        thread::spawn(move || db_clone.set(PARAM_F64, 3.0_f64).unwrap())
+            .join()
+            .unwrap();
+        assert_eq!(db.get::<f64>(PARAM_F64).unwrap(), 3.0);
+    }
+}
@@ -0,0 +1,650 @@
+//! Parameter statistics reporting.
+//!
+//! The [ParamStatsRegistry] implements the functionality of the ECSS PUS parameter statistics
+//! reporting service (PUS 4) for scalar parameters of a [ParamDb]. It samples the registered
+//! parameters, keeps the minimum, maximum, mean and standard deviation over a rolling window of
+//! the most recent samples and packs the statistics into TM[4,2] statistics reports.
+//!
+//! The window is configured per parameter as a number of samples. Because the parameters are
+//! sampled each time [ParamStatsRegistry::sample] is called, calling it with a fixed interval
+//! turns the window into a time window. Reports are generated on request, for example with
+//! [ParamStatsRequest::Report], or periodically with [ParamStatsRegistry::send_due_report]
+//! after periodic reporting was enabled. The statistics can be reset on command or after each
+//! report.
+//!
+//! The source data of a statistics report starts with the unique ID of the component as a big
+//! endian [u32] and the number of parameters as a big endian [u16]. For each parameter, it
+//! contains the [raw parameter ID][ParamId::raw] as a [u32], the number of samples as a [u32]
+//! and the minimum, maximum, mean and standard deviation as big endian [f64] values.
+use core::fmt::{Display, Formatter};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+use spacepackets::{ByteConversionError, SpHeader};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::vec;
+use std::vec::Vec;
+
+use crate::param_db::{ParamDb, ParamDbError, ParamId, ParamValue};
+use crate::pus::{EcssTmSender, EcssTmtcError, PusTmVariant};
+use crate::request::UniqueApidTargetId;
+
+/// PUS service number of the parameter statistics reporting service.
+pub const PARAM_STATS_SERVICE: u8 = 4;
+
+/// Serialized length of the statistics of one parameter.
+pub const PARAM_STATS_ENTRY_LEN: usize = 8 + 4 * 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum Subservice {
+    TcReportStats = 1,
+    TmStatsReport = 2,
+    TcResetStats = 3,
+    TcEnablePeriodicReporting = 4,
+    TcDisablePeriodicReporting = 5,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParamStatsRequest {
+    /// Send a statistics report immediately and reset the statistics afterwards if `reset` is
+    /// set.
+    Report {
+        reset: bool,
+    },
+    Reset,
+    /// Enable periodic reports with the given interval.
+    EnablePeriodic(Duration),
+    DisablePeriodic,
+}
+
+impl ParamStatsRequest {
+    /// Convert a PUS 4 telecommand into a request. The application data of a report request
+    /// optionally contains a reset flag as the first byte. The application data of a request to
+    /// enable periodic reporting contains the interval in milliseconds as a big endian [u32].
+    pub fn from_tc(subservice: u8, app_data: &[u8]) -> Result<Self, ParamStatsError> {
+        match Subservice::try_from(subservice) {
+            Ok(Subservice::TcReportStats) => Ok(Self::Report {
+                reset: app_data.first().is_some_and(|reset| *reset != 0),
+            }),
+            Ok(Subservice::TcResetStats) => Ok(Self::Reset),
+            Ok(Subservice::TcEnablePeriodicReporting) => {
+                if app_data.len() < 4 {
+                    return Err(ByteConversionError::FromSliceTooSmall {
+                        found: app_data.len(),
+                        expected: 4,
+                    }
+                    .into());
+                }
+                Ok(Self::EnablePeriodic(Duration::from_millis(
+                    u32::from_be_bytes(app_data[0..4].try_into().unwrap()) as u64,
+                )))
+            }
+            Ok(Subservice::TcDisablePeriodicReporting) => Ok(Self::DisablePeriodic),
+            _ => Err(ParamStatsError::InvalidSubservice(subservice)),
+        }
+    }
+}
+
+/// Statistics of a parameter over its rolling window.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParamStatistics {
+    pub num_samples: u32,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation.
+    pub stddev: f64,
+}
+
+impl ParamStatistics {
+    /// Calculate the statistics of the given samples. Returns [None] if there are no samples.
+    pub fn from_samples<'a>(samples: impl Iterator<Item = &'a f64> + Clone) -> Option<Self> {
+        let mut num_samples = 0;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        let mut sum = 0.0;
+        for sample in samples.clone() {
+            num_samples += 1;
+            min = min.min(*sample);
+            max = max.max(*sample);
+            sum += *sample;
+        }
+        if num_samples == 0 {
+            return None;
+        }
+        let mean = sum / num_samples as f64;
+        let variance = samples
+            .map(|sample| (*sample - mean) * (*sample - mean))
+            .sum::<f64>()
+            / num_samples as f64;
+        Some(Self {
+            num_samples,
+            min,
+            max,
+            mean,
+            stddev: variance.sqrt(),
+        })
+    }
+
+    pub fn write_to_be_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+        let len = PARAM_STATS_ENTRY_LEN - 4;
+        if buf.len() < len {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: len,
+            });
+        }
+        buf[0..4].copy_from_slice(&self.num_samples.to_be_bytes());
+        for (idx, value) in [self.min, self.max, self.mean, self.stddev]
+            .iter()
+            .enumerate()
+        {
+            buf[4 + idx * 8..12 + idx * 8].copy_from_slice(&value.to_be_bytes());
+        }
+        Ok(len)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamStatsError {
+    DuplicateParam(ParamId),
+    UnknownParam(ParamId),
+    /// Statistics can only be generated for scalar parameters.
+    NotScalar(ParamId),
+    /// The window must contain at least one sample.
+    InvalidWindow(usize),
+    /// The reporting interval must be larger than 0.
+    InvalidInterval,
+    /// The subservice is not a valid statistics request.
+    InvalidSubservice(u8),
+    Param(ParamDbError),
+    ByteConversion(ByteConversionError),
+    Send(EcssTmtcError),
+}
+
+impl Display for ParamStatsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParamStatsError::DuplicateParam(id) => {
+                write!(f, "statistics for parameter {id:?} already registered")
+            }
+            ParamStatsError::UnknownParam(id) => {
+                write!(f, "no statistics registered for parameter {id:?}")
+            }
+            ParamStatsError::NotScalar(id) => write!(f, "parameter {id:?} is not a scalar"),
+            ParamStatsError::InvalidWindow(window) => write!(f, "invalid window size {window}"),
+            ParamStatsError::InvalidInterval => write!(f, "invalid reporting interval"),
+            ParamStatsError::InvalidSubservice(subservice) => {
+                write!(f, "invalid statistics request subservice {subservice}")
+            }
+            ParamStatsError::Param(e) => write!(f, "parameter error: {e}"),
+            ParamStatsError::ByteConversion(e) => write!(f, "byte conversion error: {e}"),
+            ParamStatsError::Send(e) => write!(f, "sending statistics report failed: {e}"),
+        }
+    }
+}
+
+impl From<ParamDbError> for ParamStatsError {
+    fn from(value: ParamDbError) -> Self {
+        Self::Param(value)
+    }
+}
+
+impl From<ByteConversionError> for ParamStatsError {
+    fn from(value: ByteConversionError) -> Self {
+        Self::ByteConversion(value)
+    }
+}
+
+impl From<EcssTmtcError> for ParamStatsError {
+    fn from(value: EcssTmtcError) -> Self {
+        Self::Send(value)
+    }
+}
+
+impl Error for ParamStatsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParamStatsError::Param(e) => Some(e),
+            ParamStatsError::ByteConversion(e) => Some(e),
+            ParamStatsError::Send(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Convert a scalar parameter value into a [f64]. 64 bit integers with a large magnitude lose
+/// precision.
+pub fn scalar_as_f64(value: &ParamValue) -> Option<f64> {
+    Some(match value {
+        ParamValue::U8(value) => *value as f64,
+        ParamValue::I8(value) => *value as f64,
+        ParamValue::U16(value) => *value as f64,
+        ParamValue::I16(value) => *value as f64,
+        ParamValue::U32(value) => *value as f64,
+        ParamValue::I32(value) => *value as f64,
+        ParamValue::U64(value) => *value as f64,
+        ParamValue::I64(value) => *value as f64,
+        ParamValue::F32(value) => *value as f64,
+        ParamValue::F64(value) => *value,
+        _ => return None,
+    })
+}
+
+struct ParamWindow {
+    param_id: ParamId,
+    window: usize,
+    samples: VecDeque<f64>,
+}
+
+/// Registry of the parameters of a [ParamDb] for which statistics are generated. See the
+/// [module documentation][self] for more details.
+pub struct ParamStatsRegistry {
+    target_id: UniqueApidTargetId,
+    param_db: Arc<ParamDb>,
+    params: Vec<ParamWindow>,
+    reporting_interval: Option<Duration>,
+    reset_after_periodic_report: bool,
+    next_report: Option<Instant>,
+}
+
+impl ParamStatsRegistry {
+    /// Create a new registry without any parameters.
+    ///
+    /// ## Parameter
+    ///
+    /// * `target_id` - ID of the component. The APID is used for the generated packets.
+    /// * `param_db` - Parameter database from which the parameters are sampled.
+    pub fn new(target_id: UniqueApidTargetId, param_db: Arc<ParamDb>) -> Self {
+        Self {
+            target_id,
+            param_db,
+            params: Vec::new(),
+            reporting_interval: None,
+            reset_after_periodic_report: false,
+            next_report: None,
+        }
+    }
+
+    /// Reset the statistics after each periodic report, so that each report covers the samples
+    /// since the previous report.
+    pub fn with_reset_after_periodic_report(mut self) -> Self {
+        self.reset_after_periodic_report = true;
+        self
+    }
+
+    /// Generate statistics for the given scalar parameter over a rolling window of `window`
+    /// samples.
+    pub fn add_param(&mut self, param_id: ParamId, window: usize) -> Result<(), ParamStatsError> {
+        if self.params.iter().any(|param| param.param_id == param_id) {
+            return Err(ParamStatsError::DuplicateParam(param_id));
+        }
+        if window == 0 {
+            return Err(ParamStatsError::InvalidWindow(window));
+        }
+        if self.param_db.with_value(param_id, scalar_as_f64)?.is_none() {
+            return Err(ParamStatsError::NotScalar(param_id));
+        }
+        self.params.push(ParamWindow {
+            param_id,
+            window,
+            samples: VecDeque::with_capacity(window),
+        });
+        Ok(())
+    }
+
+    pub fn remove_param(&mut self, param_id: ParamId) -> Result<(), ParamStatsError> {
+        let idx = self
+            .params
+            .iter()
+            .position(|param| param.param_id == param_id)
+            .ok_or(ParamStatsError::UnknownParam(param_id))?;
+        self.params.remove(idx);
+        Ok(())
+    }
+
+    pub fn num_params(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn reporting_interval(&self) -> Option<Duration> {
+        self.reporting_interval
+    }
+
+    /// Sample all registered parameters. The oldest sample of a parameter is dropped if its
+    /// window is full.
+    pub fn sample(&mut self) -> Result<(), ParamStatsError> {
+        for param in &mut self.params {
+            let sample = self
+                .param_db
+                .with_value(param.param_id, scalar_as_f64)?
+                .ok_or(ParamStatsError::NotScalar(param.param_id))?;
+            if param.samples.len() == param.window {
+                param.samples.pop_front();
+            }
+            param.samples.push_back(sample);
+        }
+        Ok(())
+    }
+
+    /// Statistics of the given parameter. Returns [None] if no sample was taken since the last
+    /// reset.
+    pub fn statistics(
+        &self,
+        param_id: ParamId,
+    ) -> Result<Option<ParamStatistics>, ParamStatsError> {
+        let param = self
+            .params
+            .iter()
+            .find(|param| param.param_id == param_id)
+            .ok_or(ParamStatsError::UnknownParam(param_id))?;
+        Ok(ParamStatistics::from_samples(param.samples.iter()))
+    }
+
+    /// Clear the samples of all parameters.
+    pub fn reset(&mut self) {
+        self.params
+            .iter_mut()
+            .for_each(|param| param.samples.clear());
+    }
+
+    /// Handle a statistics request. A report is sent immediately for
+    /// [ParamStatsRequest::Report] requests.
+    pub fn handle_request(
+        &mut self,
+        request: &ParamStatsRequest,
+        timestamp: &[u8],
+        tm_sender: &(impl EcssTmSender + ?Sized),
+    ) -> Result<(), ParamStatsError> {
+        match request {
+            ParamStatsRequest::Report { reset } => {
+                self.send_report(timestamp, tm_sender)?;
+                if *reset {
+                    self.reset();
+                }
+            }
+            ParamStatsRequest::Reset => self.reset(),
+            ParamStatsRequest::EnablePeriodic(interval) => {
+                if interval.is_zero() {
+                    return Err(ParamStatsError::InvalidInterval);
+                }
+                self.reporting_interval = Some(*interval);
+                self.next_report = None;
+            }
+            ParamStatsRequest::DisablePeriodic => self.reporting_interval = None,
+        }
+        Ok(())
+    }
+
+    /// Length of the source data of a statistics report.
+    pub fn report_len(&self) -> usize {
+        6 + self.params.len() * PARAM_STATS_ENTRY_LEN
+    }
+
+    /// Pack the source data of the statistics report into the provided buffer. Parameters
+    /// without samples are reported with zero samples and all values set to 0. Returns the
+    /// written length.
+    pub fn pack_report(&self, buf: &mut [u8]) -> Result<usize, ParamStatsError> {
+        let len = self.report_len();
+        if buf.len() < len {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: len,
+            }
+            .into());
+        }
+        buf[0..4].copy_from_slice(&self.target_id.unique_id.to_be_bytes());
+        buf[4..6].copy_from_slice(&(self.params.len() as u16).to_be_bytes());
+        let mut current_idx = 6;
+        for param in &self.params {
+            buf[current_idx..current_idx + 4].copy_from_slice(&param.param_id.raw().to_be_bytes());
+            current_idx += 4;
+            let stats =
+                ParamStatistics::from_samples(param.samples.iter()).unwrap_or(ParamStatistics {
+                    num_samples: 0,
+                    min: 0.0,
+                    max: 0.0,
+                    mean: 0.0,
+                    stddev: 0.0,
+                });
+            current_idx += stats.write_to_be_bytes(&mut buf[current_idx..])?;
+        }
+        Ok(current_idx)
+    }
+
+    /// Generate the TM[4,2] statistics report and send it.
+    pub fn send_report(
+        &self,
+        timestamp: &[u8],
+        tm_sender: &(impl EcssTmSender + ?Sized),
+    ) -> Result<(), ParamStatsError> {
+        let mut buf = vec![0; self.report_len()];
+        let len = self.pack_report(&mut buf)?;
+        let sec_header = PusTmSecondaryHeader::new(
+            PARAM_STATS_SERVICE,
+            Subservice::TmStatsReport as u8,
+            0,
+            0,
+            timestamp,
+        );
+        let tm = PusTmCreator::new(
+            SpHeader::new_from_apid(self.target_id.apid),
+            sec_header,
+            &buf[..len],
+            true,
+        );
+        tm_sender.send_tm(self.target_id.id(), PusTmVariant::Direct(tm))?;
+        Ok(())
+    }
+
+    /// Send the statistics report if periodic reporting is enabled and the report is due at the
+    /// given time. The first report is sent immediately after periodic reporting was enabled.
+    /// Returns whether a report was sent.
+    pub fn send_due_report(
+        &mut self,
+        now: Instant,
+        timestamp: &[u8],
+        tm_sender: &(impl EcssTmSender + ?Sized),
+    ) -> Result<bool, ParamStatsError> {
+        let interval = match self.reporting_interval {
+            Some(interval) => interval,
+            None => return Ok(false),
+        };
+        if self.next_report.is_some_and(|next| next > now) {
+            return Ok(false);
+        }
+        self.next_report = match self.next_report {
+            Some(next) if next + interval > now => Some(next + interval),
+            _ => Some(now + interval),
+        };
+        self.send_report(timestamp, tm_sender)?;
+        if self.reset_after_periodic_report {
+            self.reset();
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::PusPacket;
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::tmtc::PacketAsVec;
+
+    const TEST_ID: UniqueApidTargetId = UniqueApidTargetId::new(0x02, 0x05);
+    const TEMPERATURE: ParamId = ParamId::new(1, 1);
+    const COUNTER: ParamId = ParamId::new(1, 2);
+    const OFFSETS: ParamId = ParamId::new(1, 3);
+
+    fn create_registry() -> (Arc<ParamDb>, ParamStatsRegistry) {
+        let param_db = Arc::new(ParamDb::default());
+        param_db.register(TEMPERATURE, 0.0_f32).unwrap();
+        param_db.register(COUNTER, 0_u16).unwrap();
+        param_db.register(OFFSETS, [0_i16; 3]).unwrap();
+        let mut registry = ParamStatsRegistry::new(TEST_ID, param_db.clone());
+        registry.add_param(TEMPERATURE, 4).unwrap();
+        registry.add_param(COUNTER, 2).unwrap();
+        (param_db, registry)
+    }
+
+    #[test]
+    fn test_registration() {
+        let (_param_db, mut registry) = create_registry();
+        assert_eq!(registry.num_params(), 2);
+        assert_eq!(
+            registry.add_param(TEMPERATURE, 4),
+            Err(ParamStatsError::DuplicateParam(TEMPERATURE))
+        );
+        assert_eq!(
+            registry.add_param(OFFSETS, 4),
+            Err(ParamStatsError::NotScalar(OFFSETS))
+        );
+        assert_eq!(
+            registry.add_param(ParamId::new(2, 1), 0),
+            Err(ParamStatsError::InvalidWindow(0))
+        );
+        assert!(matches!(
+            registry.add_param(ParamId::new(2, 1), 1),
+            Err(ParamStatsError::Param(ParamDbError::UnknownParam(_)))
+        ));
+        registry.remove_param(COUNTER).unwrap();
+        assert_eq!(registry.num_params(), 1);
+    }
+
+    #[test]
+    fn test_rolling_statistics() {
+        let (param_db, mut registry) = create_registry();
+        assert_eq!(registry.statistics(TEMPERATURE).unwrap(), None);
+        for (temperature, counter) in [(1.0_f32, 1_u16), (2.0, 2), (3.0, 3), (6.0, 4)] {
+            param_db.set(TEMPERATURE, temperature).unwrap();
+            param_db.set(COUNTER, counter).unwrap();
+            registry.sample().unwrap();
+        }
+        let stats = registry.statistics(TEMPERATURE).unwrap().unwrap();
+        assert_eq!(stats.num_samples, 4);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 6.0);
+        assert_eq!(stats.mean, 3.0);
+        assert!((stats.stddev - 3.5_f64.sqrt()).abs() < 1e-9);
+        // Only the last two samples are kept for the counter.
+        let stats = registry.statistics(COUNTER).unwrap().unwrap();
+        assert_eq!(stats.num_samples, 2);
+        assert_eq!(stats.min, 3.0);
+        assert_eq!(stats.mean, 3.5);
+        assert_eq!(stats.stddev, 0.5);
+        registry.reset();
+        assert_eq!(registry.statistics(COUNTER).unwrap(), None);
+    }
+
+    #[test]
+    fn test_report_on_request_with_reset() {
+        let (param_db, mut registry) = create_registry();
+        param_db.set(TEMPERATURE, 2.5_f32).unwrap();
+        registry.sample().unwrap();
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        registry
+            .handle_request(&ParamStatsRequest::Report { reset: true }, &[0; 7], &tm_tx)
+            .unwrap();
+        let packet = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(tm.service(), PARAM_STATS_SERVICE);
+        assert_eq!(tm.subservice(), Subservice::TmStatsReport as u8);
+        let data = tm.user_data();
+        assert_eq!(data.len(), registry.report_len());
+        assert_eq!(&data[0..4], &TEST_ID.unique_id.to_be_bytes());
+        assert_eq!(&data[4..6], &[0, 2]);
+        assert_eq!(&data[6..10], &TEMPERATURE.raw().to_be_bytes());
+        assert_eq!(&data[10..14], &[0, 0, 0, 1]);
+        assert_eq!(f64::from_be_bytes(data[14..22].try_into().unwrap()), 2.5);
+        assert_eq!(registry.statistics(TEMPERATURE).unwrap(), None);
+    }
+
+    #[test]
+    fn test_request_from_tc() {
+        assert_eq!(
+            ParamStatsRequest::from_tc(1, &[1]),
+            Ok(ParamStatsRequest::Report { reset: true })
+        );
+        assert_eq!(
+            ParamStatsRequest::from_tc(1, &[]),
+            Ok(ParamStatsRequest::Report { reset: false })
+        );
+        assert_eq!(
+            ParamStatsRequest::from_tc(4, &500_u32.to_be_bytes()),
+            Ok(ParamStatsRequest::EnablePeriodic(Duration::from_millis(
+                500
+            )))
+        );
+        assert!(matches!(
+            ParamStatsRequest::from_tc(4, &[0; 2]),
+            Err(ParamStatsError::ByteConversion(_))
+        ));
+        assert_eq!(
+            ParamStatsRequest::from_tc(Subservice::TmStatsReport as u8, &[]),
+            Err(ParamStatsError::InvalidSubservice(2))
+        );
+    }
+
+    #[test]
+    fn test_periodic_report() {
+        let (_param_db, mut registry) = create_registry();
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let now = Instant::now();
+        assert!(!registry.send_due_report(now, &[0; 7], &tm_tx).unwrap());
+        assert_eq!(
+            registry.handle_request(
+                &ParamStatsRequest::EnablePeriodic(Duration::ZERO),
+                &[0; 7],
+                &tm_tx
+            ),
+            Err(ParamStatsError::InvalidInterval)
+        );
+        registry
+            .handle_request(
+                &ParamStatsRequest::EnablePeriodic(Duration::from_secs(10)),
+                &[0; 7],
+                &tm_tx,
+            )
+            .unwrap();
+        assert!(registry.send_due_report(now, &[0; 7], &tm_tx).unwrap());
+        assert!(!registry
+            .send_due_report(now + Duration::from_secs(5), &[0; 7], &tm_tx)
+            .unwrap());
+        assert!(registry
+            .send_due_report(now + Duration::from_secs(10), &[0; 7], &tm_tx)
+            .unwrap());
+        assert_eq!(tm_rx.try_iter().count(), 2);
+        registry
+            .handle_request(&ParamStatsRequest::DisablePeriodic, &[0; 7], &tm_tx)
+            .unwrap();
+        assert!(!registry
+            .send_due_report(now + Duration::from_secs(20), &[0; 7], &tm_tx)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_reset_after_periodic_report() {
+        let (param_db, registry) = create_registry();
+        let mut registry = registry.with_reset_after_periodic_report();
+        param_db.set(COUNTER, 5_u16).unwrap();
+        registry.sample().unwrap();
+        let (tm_tx, _tm_rx) = mpsc::channel::<PacketAsVec>();
+        registry
+            .handle_request(
+                &ParamStatsRequest::EnablePeriodic(Duration::from_secs(1)),
+                &[0; 7],
+                &tm_tx,
+            )
+            .unwrap();
+        assert!(registry
+            .send_due_report(Instant::now(), &[0; 7], &tm_tx)
+            .unwrap());
+        assert_eq!(registry.statistics(COUNTER).unwrap(), None);
+    }
+}
@@ -9,6 +9,8 @@
 //!  - The [StaticMemoryPool] required [alloc] support but pre-allocated all required memory
 //!    and does not perform dynamic run-time allocations for the storage of TMTC packets.
 //!  - The [StaticHeaplessMemoryPool] which can be grown by user provided static storage.
+//!  - The [ShardedStaticMemoryPool] which uses the same layout as the [StaticMemoryPool], but has
+//!    one lock per subpool and can therefore be shared between threads without a global lock.
 //!
 //! # Example for the [StaticMemoryPool]
 //!
@@ -86,6 +88,8 @@ use serde::{Deserialize, Serialize};
 use spacepackets::ByteConversionError;
 #[cfg(feature = "std")]
 use std::error::Error;
+#[cfg(feature = "std")]
+pub use std_mod::*;
 
 type NumBlocks = u16;
 pub type PoolAddr = u64;
@@ -171,6 +175,8 @@ pub enum PoolError {
     DataDoesNotExist(PoolAddr),
     ByteConversionError(spacepackets::ByteConversionError),
     LockError,
+    /// The pool lock is currently held by another user. Only returned by non-blocking operations.
+    WouldBlock,
     /// Internal or configuration errors
     InternalError(u32),
 }
@@ -202,6 +208,9 @@ impl Display for PoolError {
             PoolError::LockError => {
                 write!(f, "lock error")
             }
+            PoolError::WouldBlock => {
+                write!(f, "pool is currently locked")
+            }
         }
     }
 }
@@ -228,6 +237,11 @@ impl Error for PoolError {
 /// in the store at its core. The API was designed so internal optimizations can be performed
 /// more easily and that is is also possible to make the pool structure [Sync] without the whole
 /// pool structure being wrapped inside a lock.
+///
+/// The trait is object safe, so it can be used as a `dyn PoolProvider` trait object. Implementors
+/// provide the closure based [Self::free_element_dyn] and [Self::modify_dyn] methods, while the
+/// generic [Self::free_element] and [Self::modify] convenience methods are provided for all
+/// sized pool types.
 pub trait PoolProvider {
     /// Add new data to the pool. The provider should attempt to reserve a memory block with the
     /// appropriate size and then copy the given data to the block. Yields a [PoolAddr] which can
@@ -239,18 +253,45 @@ pub trait PoolProvider {
     /// block to the closure. This allows the user to write data to the memory block.
     /// The function should yield a [PoolAddr] which can be used to access the data stored in the
     /// pool.
-    fn free_element<W: FnMut(&mut [u8])>(
+    fn free_element_dyn(
         &mut self,
         len: usize,
-        writer: W,
+        writer: &mut dyn FnMut(&mut [u8]),
     ) -> Result<PoolAddr, PoolError>;
 
+    /// Generic variant of [Self::free_element_dyn].
+    fn free_element<W: FnMut(&mut [u8])>(
+        &mut self,
+        len: usize,
+        mut writer: W,
+    ) -> Result<PoolAddr, PoolError>
+    where
+        Self: Sized,
+    {
+        self.free_element_dyn(len, &mut writer)
+    }
+
     /// Modify data added previously using a given [PoolAddr]. The provider should use the store
     /// address to determine if a memory block exists for that address. If it does, it should
     /// call the user-provided closure and pass a mutable reference to the memory block
     /// to the closure. This allows the user to modify the memory block.
-    fn modify<U: FnMut(&mut [u8])>(&mut self, addr: &PoolAddr, updater: U)
-        -> Result<(), PoolError>;
+    fn modify_dyn(
+        &mut self,
+        addr: &PoolAddr,
+        updater: &mut dyn FnMut(&mut [u8]),
+    ) -> Result<(), PoolError>;
+
+    /// Generic variant of [Self::modify_dyn].
+    fn modify<U: FnMut(&mut [u8])>(
+        &mut self,
+        addr: &PoolAddr,
+        mut updater: U,
+    ) -> Result<(), PoolError>
+    where
+        Self: Sized,
+    {
+        self.modify_dyn(addr, &mut updater)
+    }
 
     /// The provider should copy the data from the memory block to the user-provided buffer if
     /// it exists.
@@ -294,6 +335,18 @@ pub trait PoolProviderWithGuards: PoolProvider {
     fn modify_with_guard(&mut self, addr: PoolAddr) -> PoolRwGuard<Self>;
 }
 
+/// The guards only require the [PoolProvider] API, so they are available for all pools,
+/// including `dyn PoolProvider` trait objects.
+impl<Pool: PoolProvider + ?Sized> PoolProviderWithGuards for Pool {
+    fn read_with_guard(&mut self, addr: PoolAddr) -> PoolGuard<Self> {
+        PoolGuard::new(self, addr)
+    }
+
+    fn modify_with_guard(&mut self, addr: PoolAddr) -> PoolRwGuard<Self> {
+        PoolRwGuard::new(self, addr)
+    }
+}
+
 pub struct PoolGuard<'a, MemProvider: PoolProvider + ?Sized> {
     pool: &'a mut MemProvider,
     pub addr: PoolAddr,
@@ -303,7 +356,7 @@ pub struct PoolGuard<'a, MemProvider: PoolProvider + ?Sized> {
 
 /// This helper object can be used to safely access pool data without worrying about memory
 /// leaks.
-impl<'a, MemProvider: PoolProvider> PoolGuard<'a, MemProvider> {
+impl<'a, MemProvider: PoolProvider + ?Sized> PoolGuard<'a, MemProvider> {
     pub fn new(pool: &'a mut MemProvider, addr: PoolAddr) -> Self {
         Self {
             pool,
@@ -343,7 +396,7 @@ pub struct PoolRwGuard<'a, MemProvider: PoolProvider + ?Sized> {
     guard: PoolGuard<'a, MemProvider>,
 }
 
-impl<'a, MemProvider: PoolProvider> PoolRwGuard<'a, MemProvider> {
+impl<'a, MemProvider: PoolProvider + ?Sized> PoolRwGuard<'a, MemProvider> {
     pub fn new(pool: &'a mut MemProvider, addr: PoolAddr) -> Self {
         Self {
             guard: PoolGuard::new(pool, addr),
@@ -351,7 +404,7 @@ impl<'a, MemProvider: PoolProvider> PoolRwGuard<'a, MemProvider> {
     }
 
     pub fn update<U: FnMut(&mut [u8])>(&mut self, updater: &mut U) -> Result<(), PoolError> {
-        self.guard.pool.modify(&self.guard.addr, updater)
+        self.guard.pool.modify_dyn(&self.guard.addr, updater)
     }
 
     delegate!(
@@ -368,6 +421,25 @@ type UsedBlockSize = usize;
 pub const STORE_FREE: UsedBlockSize = UsedBlockSize::MAX;
 pub const MAX_BLOCK_SIZE: UsedBlockSize = STORE_FREE - 1;
 
+/// Emits trace output for a failed allocation and returns the error.
+fn allocation_failure(data_len: usize, error: PoolError) -> PoolError {
+    match &error {
+        PoolError::StoreFull(subpool) => trace_warn!(
+            "pool allocation of {} bytes failed, subpool {} is full",
+            data_len,
+            subpool
+        ),
+        PoolError::DataTooLarge(_) => {
+            trace_warn!(
+                "pool allocation of {} bytes failed, data too large",
+                data_len
+            )
+        }
+        _ => trace_warn!("pool allocation of {} bytes failed", data_len),
+    }
+    error
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, new)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct SubpoolConfig {
@@ -644,22 +716,27 @@ pub mod heapless_mod {
         fn add(&mut self, data: &[u8]) -> Result<PoolAddr, PoolError> {
             let data_len = data.len();
             if data_len > MAX_BLOCK_SIZE {
-                return Err(PoolError::DataTooLarge(data_len));
+                return Err(allocation_failure(
+                    data_len,
+                    PoolError::DataTooLarge(data_len),
+                ));
             }
-            let addr = self.reserve(data_len)?;
+            let addr = self
+                .reserve(data_len)
+                .map_err(|e| allocation_failure(data_len, e))?;
             self.write(&addr, data)?;
             Ok(addr.into())
         }
 
-        fn free_element<W: FnMut(&mut [u8])>(
+        fn free_element_dyn(
             &mut self,
             len: usize,
-            mut writer: W,
+            writer: &mut dyn FnMut(&mut [u8]),
         ) -> Result<PoolAddr, PoolError> {
             if len > MAX_BLOCK_SIZE {
-                return Err(PoolError::DataTooLarge(len));
+                return Err(allocation_failure(len, PoolError::DataTooLarge(len)));
             }
-            let addr = self.reserve(len)?;
+            let addr = self.reserve(len).map_err(|e| allocation_failure(len, e))?;
             let raw_pos = self.raw_pos(&addr).unwrap();
             let block =
                 &mut self.pool.get_mut(addr.pool_idx as usize).unwrap().1[raw_pos..raw_pos + len];
@@ -667,10 +744,10 @@ pub mod heapless_mod {
             Ok(addr.into())
         }
 
-        fn modify<U: FnMut(&mut [u8])>(
+        fn modify_dyn(
             &mut self,
             addr: &PoolAddr,
-            mut updater: U,
+            updater: &mut dyn FnMut(&mut [u8]),
         ) -> Result<(), PoolError> {
             let addr = StaticPoolAddr::from(*addr);
             let curr_size = self.addr_check(&addr)?;
@@ -736,29 +813,18 @@ pub mod heapless_mod {
             })
         }
     }
-
-    impl<const MAX_NUM_SUBPOOLS: usize> PoolProviderWithGuards
-        for StaticHeaplessMemoryPool<MAX_NUM_SUBPOOLS>
-    {
-        fn modify_with_guard(&mut self, addr: PoolAddr) -> PoolRwGuard<Self> {
-            PoolRwGuard::new(self, addr)
-        }
-
-        fn read_with_guard(&mut self, addr: PoolAddr) -> PoolGuard<Self> {
-            PoolGuard::new(self, addr)
-        }
-    }
 }
 
 #[cfg(feature = "alloc")]
 mod alloc_mod {
     use super::*;
     use crate::pool::{PoolAddr, PoolError, StoreIdError};
+    use alloc::collections::VecDeque;
     use alloc::vec;
     use alloc::vec::Vec;
     use spacepackets::ByteConversionError;
     #[cfg(feature = "std")]
-    use std::sync::{Arc, RwLock};
+    use std::sync::{Arc, Mutex, RwLock};
 
     #[cfg(feature = "std")]
     pub type SharedStaticMemoryPool = Arc<RwLock<StaticMemoryPool>>;
@@ -804,6 +870,10 @@ mod alloc_mod {
             &self.cfg
         }
 
+        pub fn spill_to_higher_subpools(&self) -> bool {
+            self.spill_to_higher_subpools
+        }
+
         pub fn sanitize(&mut self) -> usize {
             self.cfg.retain(|&subpool_cfg| {
                 subpool_cfg.num_blocks > 0 && subpool_cfg.block_size < MAX_BLOCK_SIZE
@@ -956,22 +1026,27 @@ mod alloc_mod {
         fn add(&mut self, data: &[u8]) -> Result<PoolAddr, PoolError> {
             let data_len = data.len();
             if data_len > MAX_BLOCK_SIZE {
-                return Err(PoolError::DataTooLarge(data_len));
+                return Err(allocation_failure(
+                    data_len,
+                    PoolError::DataTooLarge(data_len),
+                ));
             }
-            let addr = self.reserve(data_len)?;
+            let addr = self
+                .reserve(data_len)
+                .map_err(|e| allocation_failure(data_len, e))?;
             self.write(&addr, data)?;
             Ok(addr.into())
         }
 
-        fn free_element<W: FnMut(&mut [u8])>(
+        fn free_element_dyn(
             &mut self,
             len: usize,
-            mut writer: W,
+            writer: &mut dyn FnMut(&mut [u8]),
         ) -> Result<PoolAddr, PoolError> {
             if len > MAX_BLOCK_SIZE {
-                return Err(PoolError::DataTooLarge(len));
+                return Err(allocation_failure(len, PoolError::DataTooLarge(len)));
             }
-            let addr = self.reserve(len)?;
+            let addr = self.reserve(len).map_err(|e| allocation_failure(len, e))?;
             let raw_pos = self.raw_pos(&addr).unwrap();
             let block =
                 &mut self.pool.get_mut(addr.pool_idx as usize).unwrap()[raw_pos..raw_pos + len];
@@ -979,10 +1054,10 @@ mod alloc_mod {
             Ok(addr.into())
         }
 
-        fn modify<U: FnMut(&mut [u8])>(
+        fn modify_dyn(
             &mut self,
             addr: &PoolAddr,
-            mut updater: U,
+            updater: &mut dyn FnMut(&mut [u8]),
         ) -> Result<(), PoolError> {
             let addr = StaticPoolAddr::from(*addr);
             let curr_size = self.addr_check(&addr)?;
@@ -1054,21 +1129,756 @@ mod alloc_mod {
         }
     }
 
-    impl PoolProviderWithGuards for StaticMemoryPool {
-        fn modify_with_guard(&mut self, addr: PoolAddr) -> PoolRwGuard<Self> {
-            PoolRwGuard::new(self, addr)
+    /// Priority class of an entry inside a [PriorityPoolQueue]. Lower values have a higher
+    /// priority, so 0 is the highest priority.
+    pub type PriorityClass = u8;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub enum PriorityQueueError {
+        /// The priority class is not configured for the queue.
+        InvalidPriority(PriorityClass),
+        /// The lane of the given priority class is full.
+        LaneFull(PriorityClass),
+    }
+
+    impl Display for PriorityQueueError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                PriorityQueueError::InvalidPriority(prio) => {
+                    write!(f, "invalid priority class {prio}")
+                }
+                PriorityQueueError::LaneFull(prio) => {
+                    write!(f, "lane for priority class {prio} is full")
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl Error for PriorityQueueError {}
+
+    /// Generic trait for queue entries which reference data stored in a [PoolProvider].
+    pub trait PoolAddrProvider {
+        fn pool_addr(&self) -> PoolAddr;
+    }
+
+    impl PoolAddrProvider for PoolAddr {
+        fn pool_addr(&self) -> PoolAddr {
+            *self
+        }
+    }
+
+    /// Queueing discipline for entries stored inside a [PoolProvider].
+    ///
+    /// A pool only hands out addresses and does not track any ordering of the stored entries.
+    /// This queue maintains one FIFO lane for each priority class. [Self::pop] always yields the
+    /// oldest entry of the highest priority class which is not empty. This can be used to release
+    /// stored telecommands with different priorities, for example scheduled and immediate
+    /// telecommands.
+    ///
+    /// The capacity of each lane is fixed, and the lanes are pre-allocated on creation.
+    #[derive(Debug, Clone)]
+    pub struct PriorityPoolQueue<Entry = PoolAddr> {
+        lanes: Vec<VecDeque<Entry>>,
+        lane_capacity: usize,
+    }
+
+    #[cfg(feature = "std")]
+    pub type SharedPriorityPoolQueue<Entry = PoolAddr> = Arc<Mutex<PriorityPoolQueue<Entry>>>;
+
+    impl<Entry> PriorityPoolQueue<Entry> {
+        /// Create a new queue with `num_priority_classes` lanes which can hold up to
+        /// `lane_capacity` entries each.
+        pub fn new(num_priority_classes: u8, lane_capacity: usize) -> Self {
+            let mut lanes = Vec::with_capacity(num_priority_classes as usize);
+            for _ in 0..num_priority_classes {
+                lanes.push(VecDeque::with_capacity(lane_capacity));
+            }
+            Self {
+                lanes,
+                lane_capacity,
+            }
+        }
+
+        #[cfg(feature = "std")]
+        pub fn new_shared(
+            num_priority_classes: u8,
+            lane_capacity: usize,
+        ) -> SharedPriorityPoolQueue<Entry> {
+            Arc::new(Mutex::new(Self::new(num_priority_classes, lane_capacity)))
+        }
+
+        pub fn num_priority_classes(&self) -> u8 {
+            self.lanes.len() as u8
+        }
+
+        pub fn lane_capacity(&self) -> usize {
+            self.lane_capacity
+        }
+
+        /// Append an entry to the lane of the given priority class.
+        pub fn push(
+            &mut self,
+            priority: PriorityClass,
+            entry: Entry,
+        ) -> Result<(), PriorityQueueError> {
+            let lane = self
+                .lanes
+                .get_mut(priority as usize)
+                .ok_or(PriorityQueueError::InvalidPriority(priority))?;
+            if lane.len() >= self.lane_capacity {
+                return Err(PriorityQueueError::LaneFull(priority));
+            }
+            lane.push_back(entry);
+            Ok(())
+        }
+
+        /// Retrieve the oldest entry of the highest priority class which contains entries.
+        pub fn pop(&mut self) -> Option<(PriorityClass, Entry)> {
+            self.lanes
+                .iter_mut()
+                .enumerate()
+                .find_map(|(prio, lane)| lane.pop_front().map(|entry| (prio as u8, entry)))
+        }
+
+        /// Peek at the entry which would be returned by [Self::pop].
+        pub fn peek(&self) -> Option<(PriorityClass, &Entry)> {
+            self.lanes
+                .iter()
+                .enumerate()
+                .find_map(|(prio, lane)| lane.front().map(|entry| (prio as u8, entry)))
+        }
+
+        /// Number of entries in the lane of the given priority class. Returns [None] if the
+        /// priority class is invalid.
+        pub fn len_of_lane(&self, priority: PriorityClass) -> Option<usize> {
+            self.lanes.get(priority as usize).map(|lane| lane.len())
+        }
+
+        pub fn len(&self) -> usize {
+            self.lanes.iter().map(|lane| lane.len()).sum()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.lanes.iter().all(|lane| lane.is_empty())
+        }
+    }
+
+    impl<Entry: PoolAddrProvider> PriorityPoolQueue<Entry> {
+        /// Remove all entries from the queue and delete the associated data from the pool.
+        /// Returns the number of deleted entries.
+        pub fn clear_and_delete(
+            &mut self,
+            pool: &mut (impl PoolProvider + ?Sized),
+        ) -> Result<usize, PoolError> {
+            let mut num_deleted = 0;
+            for lane in self.lanes.iter_mut() {
+                while let Some(entry) = lane.pop_front() {
+                    pool.delete(entry.pool_addr())?;
+                    num_deleted += 1;
+                }
+            }
+            Ok(num_deleted)
+        }
+    }
+
+    impl PriorityPoolQueue<PoolAddr> {
+        /// Store data inside the given pool and append the resulting address to the lane of the
+        /// given priority class. The data is deleted from the pool again if the queue can not
+        /// accept the entry.
+        pub fn add_and_push(
+            &mut self,
+            pool: &mut (impl PoolProvider + ?Sized),
+            priority: PriorityClass,
+            data: &[u8],
+        ) -> Result<PoolAddr, PriorityPoolQueueError> {
+            match self.len_of_lane(priority) {
+                None => return Err(PriorityQueueError::InvalidPriority(priority).into()),
+                Some(len) if len >= self.lane_capacity => {
+                    return Err(PriorityQueueError::LaneFull(priority).into())
+                }
+                _ => (),
+            }
+            let addr = pool.add(data)?;
+            self.push(priority, addr)?;
+            Ok(addr)
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum PriorityPoolQueueError {
+        Pool(PoolError),
+        Queue(PriorityQueueError),
+    }
+
+    impl From<PoolError> for PriorityPoolQueueError {
+        fn from(value: PoolError) -> Self {
+            Self::Pool(value)
+        }
+    }
+
+    impl From<PriorityQueueError> for PriorityPoolQueueError {
+        fn from(value: PriorityQueueError) -> Self {
+            Self::Queue(value)
+        }
+    }
+
+    impl Display for PriorityPoolQueueError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                PriorityPoolQueueError::Pool(e) => write!(f, "pool error: {e}"),
+                PriorityPoolQueueError::Queue(e) => write!(f, "queue error: {e}"),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl Error for PriorityPoolQueueError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                PriorityPoolQueueError::Pool(e) => Some(e),
+                PriorityPoolQueueError::Queue(e) => Some(e),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_mod {
+    use super::*;
+    use crate::event_man::{EventMessageU32, EventSendProvider};
+    use crate::events::EventU32;
+    use crate::params::{Params, ParamsRaw};
+    use crate::ComponentId;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex, MutexGuard, RwLock, TryLockError};
+    use std::time::{Duration, Instant};
+    use std::vec;
+    use std::vec::Vec;
+
+    pub type SharedExpiringPool<Pool = StaticMemoryPool> = Arc<RwLock<ExpiringPool<Pool>>>;
+
+    /// Configuration for the diagnostic event emitted by [ExpiringPool::expire_stale].
+    ///
+    /// The event contains the number of expired entries as a [ParamsRaw::U32] parameter.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct ExpiryEventConfig {
+        pub sender_id: ComponentId,
+        pub event: EventU32,
+    }
+
+    /// Pool wrapper which adds an optional time-to-live mechanism to a [PoolProvider].
+    ///
+    /// Entries can be tagged with a deadline. The [Self::expire_stale] sweep deletes all entries
+    /// whose deadline has elapsed. This prevents leaked addresses, for example because the
+    /// receiver of a stored packet died, from exhausting a pool over long runtimes. Entries without
+    /// a deadline are never expired.
+    ///
+    /// The wrapper implements [PoolProvider] and tracks all deletions, so a deadline can never
+    /// apply to a new entry which re-uses the address of a deleted entry.
+    pub struct ExpiringPool<Pool: PoolProvider = StaticMemoryPool> {
+        pool: Pool,
+        deadlines: HashMap<PoolAddr, Instant>,
+        expiry_event: Option<ExpiryEventConfig>,
+    }
+
+    impl<Pool: PoolProvider> ExpiringPool<Pool> {
+        pub fn new(pool: Pool) -> Self {
+            Self {
+                pool,
+                deadlines: HashMap::new(),
+                expiry_event: None,
+            }
+        }
+
+        /// Configure the diagnostic event which is emitted by [Self::expire_stale] if any
+        /// entries expired.
+        pub fn with_expiry_event(mut self, expiry_event: ExpiryEventConfig) -> Self {
+            self.expiry_event = Some(expiry_event);
+            self
+        }
+
+        pub fn new_shared(pool: Pool) -> SharedExpiringPool<Pool> {
+            Arc::new(RwLock::new(Self::new(pool)))
+        }
+
+        pub fn pool(&self) -> &Pool {
+            &self.pool
+        }
+
+        /// Add new data to the pool and tag it with a deadline which is `ttl` in the future.
+        pub fn add_with_ttl(&mut self, data: &[u8], ttl: Duration) -> Result<PoolAddr, PoolError> {
+            let addr = self.add(data)?;
+            self.deadlines.insert(addr, Instant::now() + ttl);
+            Ok(addr)
+        }
+
+        /// Tag an existing entry with a deadline. An existing deadline will be overwritten.
+        pub fn set_deadline(&mut self, addr: PoolAddr, deadline: Instant) -> Result<(), PoolError> {
+            if !self.pool.has_element_at(&addr)? {
+                return Err(PoolError::DataDoesNotExist(addr));
+            }
+            self.deadlines.insert(addr, deadline);
+            Ok(())
+        }
+
+        /// Remove the deadline of an entry, for example if it was handed over to a receiver
+        /// which takes care of deleting it.
+        pub fn clear_deadline(&mut self, addr: &PoolAddr) -> Option<Instant> {
+            self.deadlines.remove(addr)
+        }
+
+        pub fn deadline(&self, addr: &PoolAddr) -> Option<Instant> {
+            self.deadlines.get(addr).copied()
+        }
+
+        /// Number of entries which are tagged with a deadline.
+        pub fn num_tagged_entries(&self) -> usize {
+            self.deadlines.len()
+        }
+
+        /// Delete all entries whose deadline is earlier or equal to `now`. Returns the number of
+        /// deleted entries.
+        ///
+        /// If any entries expired and an [ExpiryEventConfig] was configured, a diagnostic event
+        /// will be sent using the given event sender. The sweep itself is not affected by
+        /// failures to send the event.
+        pub fn expire_stale(
+            &mut self,
+            now: Instant,
+            event_sender: Option<&(impl EventSendProvider<EventU32> + ?Sized)>,
+        ) -> Result<u32, PoolError> {
+            let expired: Vec<PoolAddr> = self
+                .deadlines
+                .iter()
+                .filter(|(_, deadline)| **deadline <= now)
+                .map(|(addr, _)| *addr)
+                .collect();
+            let mut num_expired = 0;
+            for addr in expired {
+                self.deadlines.remove(&addr);
+                match self.pool.delete(addr) {
+                    Ok(()) => num_expired += 1,
+                    // Entry was already deleted by bypassing the wrapper.
+                    Err(PoolError::DataDoesNotExist(_)) => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            if num_expired > 0 {
+                if let (Some(event_cfg), Some(sender)) = (self.expiry_event, event_sender) {
+                    sender
+                        .send(EventMessageU32::new_with_params(
+                            event_cfg.sender_id,
+                            event_cfg.event,
+                            &Params::from(ParamsRaw::from(num_expired)),
+                        ))
+                        .ok();
+                }
+            }
+            Ok(num_expired)
+        }
+    }
+
+    impl<Pool: PoolProvider> PoolProvider for ExpiringPool<Pool> {
+        fn add(&mut self, data: &[u8]) -> Result<PoolAddr, PoolError> {
+            self.pool.add(data)
+        }
+
+        fn free_element_dyn(
+            &mut self,
+            len: usize,
+            writer: &mut dyn FnMut(&mut [u8]),
+        ) -> Result<PoolAddr, PoolError> {
+            self.pool.free_element_dyn(len, writer)
+        }
+
+        fn modify_dyn(
+            &mut self,
+            addr: &PoolAddr,
+            updater: &mut dyn FnMut(&mut [u8]),
+        ) -> Result<(), PoolError> {
+            self.pool.modify_dyn(addr, updater)
+        }
+
+        fn read(&self, addr: &PoolAddr, buf: &mut [u8]) -> Result<usize, PoolError> {
+            self.pool.read(addr, buf)
+        }
+
+        fn delete(&mut self, addr: PoolAddr) -> Result<(), PoolError> {
+            self.deadlines.remove(&addr);
+            self.pool.delete(addr)
+        }
+
+        fn has_element_at(&self, addr: &PoolAddr) -> Result<bool, PoolError> {
+            self.pool.has_element_at(addr)
+        }
+
+        fn len_of_data(&self, addr: &PoolAddr) -> Result<usize, PoolError> {
+            self.pool.len_of_data(addr)
+        }
+    }
+
+    fn try_lock_error<Guard>(error: TryLockError<Guard>) -> PoolError {
+        match error {
+            TryLockError::Poisoned(_) => PoolError::LockError,
+            TryLockError::WouldBlock => PoolError::WouldBlock,
+        }
+    }
+
+    /// Guard for data inside a shared pool which owns a handle to the pool instead of borrowing
+    /// the pool.
+    ///
+    /// The [PoolGuard] and the [PoolRwGuard] borrow the pool for their whole lifetime, so the lock
+    /// of a shared pool has to be held as long as the guard exists. This guard only locks the pool
+    /// for the individual operations. It can be passed to helper functions, stored together with
+    /// other state or held across await points while the pool is used by other components, for
+    /// example to store the TM which is generated while the TC data is still being processed.
+    ///
+    /// Like the [PoolGuard], the data is deleted when the guard is dropped unless
+    /// [Self::release] was called.
+    pub struct SharedPoolGuard<Pool: PoolProvider + ?Sized = StaticMemoryPool> {
+        pool: Arc<RwLock<Pool>>,
+        addr: PoolAddr,
+        no_deletion: bool,
+    }
+
+    impl<Pool: PoolProvider + ?Sized> SharedPoolGuard<Pool> {
+        pub fn new(pool: &Arc<RwLock<Pool>>, addr: PoolAddr) -> Self {
+            Self {
+                pool: pool.clone(),
+                addr,
+                no_deletion: false,
+            }
+        }
+
+        pub fn addr(&self) -> PoolAddr {
+            self.addr
+        }
+
+        pub fn len_of_data(&self) -> Result<usize, PoolError> {
+            self.pool
+                .read()
+                .map_err(|_| PoolError::LockError)?
+                .len_of_data(&self.addr)
+        }
+
+        pub fn read(&self, buf: &mut [u8]) -> Result<usize, PoolError> {
+            self.pool
+                .read()
+                .map_err(|_| PoolError::LockError)?
+                .read(&self.addr, buf)
+        }
+
+        /// Non-blocking variant of [Self::read]. Returns [PoolError::WouldBlock] if the pool is
+        /// currently locked for writing.
+        pub fn try_read(&self, buf: &mut [u8]) -> Result<usize, PoolError> {
+            self.pool
+                .try_read()
+                .map_err(try_lock_error)?
+                .read(&self.addr, buf)
+        }
+
+        pub fn read_as_vec(&self) -> Result<Vec<u8>, PoolError> {
+            self.pool
+                .read()
+                .map_err(|_| PoolError::LockError)?
+                .read_as_vec(&self.addr)
+        }
+
+        pub fn modify<U: FnMut(&mut [u8])>(&self, mut updater: U) -> Result<(), PoolError> {
+            self.pool
+                .write()
+                .map_err(|_| PoolError::LockError)?
+                .modify_dyn(&self.addr, &mut updater)
+        }
+
+        /// Non-blocking variant of [Self::modify]. Returns [PoolError::WouldBlock] if the pool is
+        /// currently locked.
+        pub fn try_modify<U: FnMut(&mut [u8])>(&self, mut updater: U) -> Result<(), PoolError> {
+            self.pool
+                .try_write()
+                .map_err(try_lock_error)?
+                .modify_dyn(&self.addr, &mut updater)
+        }
+
+        /// Disable the automatic deletion of the data and return its address.
+        pub fn release(mut self) -> PoolAddr {
+            self.no_deletion = true;
+            self.addr
+        }
+    }
+
+    impl<Pool: PoolProvider + ?Sized> Drop for SharedPoolGuard<Pool> {
+        fn drop(&mut self) {
+            if self.no_deletion {
+                return;
+            }
+            let deleted = match self.pool.write() {
+                Ok(mut pool) => pool.delete(self.addr).is_ok(),
+                Err(_) => false,
+            };
+            if !deleted {
+                trace_warn!("deleting guarded pool data at address {} failed", self.addr);
+            }
+        }
+    }
+
+    pub type SharedShardedMemoryPool = Arc<ShardedStaticMemoryPool>;
+
+    struct Subpool {
+        data: Vec<u8>,
+        sizes_list: Vec<UsedBlockSize>,
+    }
+
+    /// Memory pool with the same subpool layout and addressing as the [StaticMemoryPool], but
+    /// with a separate lock for every subpool.
+    ///
+    /// The [SharedStaticMemoryPool] serializes all pool operations of all threads with a single
+    /// [RwLock]. This pool only locks the subpool which is accessed, so threads which store data
+    /// of different sizes, for example high-rate TM generators, do not contend on one lock.
+    /// All operations only require a shared reference, so the pool can be shared with the
+    /// [SharedShardedMemoryPool] without an additional lock. The [PoolProvider] trait is
+    /// implemented for both types, so the pool can be used with all components which are generic
+    /// over the [PoolProvider].
+    ///
+    /// Data spilled to higher subpools is handled like in the [StaticMemoryPool]. Only one
+    /// subpool lock is held at any time.
+    pub struct ShardedStaticMemoryPool {
+        pool_cfg: StaticPoolConfig,
+        subpools: Vec<Mutex<Subpool>>,
+    }
+
+    impl ShardedStaticMemoryPool {
+        /// Create a new sharded pool from the [given configuration][StaticPoolConfig]. This
+        /// function will sanitize the given configuration as well.
+        pub fn new(mut cfg: StaticPoolConfig) -> Self {
+            cfg.sanitize();
+            let subpools = cfg
+                .subpool_cfg()
+                .iter()
+                .map(|subpool_cfg| {
+                    Mutex::new(Subpool {
+                        data: vec![0; subpool_cfg.num_blocks as usize * subpool_cfg.block_size],
+                        sizes_list: vec![STORE_FREE; subpool_cfg.num_blocks as usize],
+                    })
+                })
+                .collect();
+            Self {
+                pool_cfg: cfg,
+                subpools,
+            }
+        }
+
+        pub fn new_shared(cfg: StaticPoolConfig) -> SharedShardedMemoryPool {
+            Arc::new(Self::new(cfg))
+        }
+
+        pub fn pool_cfg(&self) -> &StaticPoolConfig {
+            &self.pool_cfg
+        }
+
+        fn block_size(&self, pool_idx: u16) -> usize {
+            self.pool_cfg.subpool_cfg()[pool_idx as usize].block_size
+        }
+
+        fn lock_subpool(
+            &self,
+            pool_idx: u16,
+            addr: Option<PoolAddr>,
+        ) -> Result<MutexGuard<'_, Subpool>, PoolError> {
+            self.subpools
+                .get(pool_idx as usize)
+                .ok_or(PoolError::InvalidStoreId(
+                    StoreIdError::InvalidSubpool(pool_idx),
+                    addr,
+                ))?
+                .lock()
+                .map_err(|_| PoolError::LockError)
+        }
+
+        /// Lock the subpool of the given address and call the given closure with the subpool,
+        /// the start of the block and the size of the stored data.
+        fn with_block<R>(
+            &self,
+            addr: &PoolAddr,
+            f: impl FnOnce(&mut Subpool, usize, UsedBlockSize) -> Result<R, PoolError>,
+        ) -> Result<R, PoolError> {
+            let static_addr = StaticPoolAddr::from(*addr);
+            let mut subpool = self.lock_subpool(static_addr.pool_idx, Some(*addr))?;
+            let curr_size = *subpool
+                .sizes_list
+                .get(static_addr.packet_idx as usize)
+                .ok_or(PoolError::InvalidStoreId(
+                    StoreIdError::InvalidPacketIdx(static_addr.packet_idx),
+                    Some(*addr),
+                ))?;
+            let raw_pos = static_addr.packet_idx as usize * self.block_size(static_addr.pool_idx);
+            f(&mut subpool, raw_pos, curr_size)
+        }
+
+        /// Like [Self::with_block], but returns an error if no data is stored at the address.
+        fn with_used_block<R>(
+            &self,
+            addr: &PoolAddr,
+            f: impl FnOnce(&mut Subpool, usize, UsedBlockSize) -> Result<R, PoolError>,
+        ) -> Result<R, PoolError> {
+            self.with_block(addr, |subpool, raw_pos, curr_size| {
+                if curr_size == STORE_FREE {
+                    return Err(PoolError::DataDoesNotExist(*addr));
+                }
+                f(subpool, raw_pos, curr_size)
+            })
+        }
+
+        pub fn add(&self, data: &[u8]) -> Result<PoolAddr, PoolError> {
+            self.free_element(data.len(), |block| block.copy_from_slice(data))
+        }
+
+        /// Reserve a free block and call the writer while only the lock of the subpool which
+        /// contains the block is held.
+        pub fn free_element<W: FnMut(&mut [u8])>(
+            &self,
+            len: usize,
+            mut writer: W,
+        ) -> Result<PoolAddr, PoolError> {
+            if len > MAX_BLOCK_SIZE {
+                return Err(allocation_failure(len, PoolError::DataTooLarge(len)));
+            }
+            let mut pool_idx = self
+                .pool_cfg
+                .subpool_cfg()
+                .iter()
+                .position(|subpool_cfg| subpool_cfg.block_size >= len)
+                .ok_or_else(|| allocation_failure(len, PoolError::DataTooLarge(len)))?
+                as u16;
+            loop {
+                let mut guard = self.lock_subpool(pool_idx, None)?;
+                let subpool = &mut *guard;
+                if let Some(packet_idx) = subpool
+                    .sizes_list
+                    .iter()
+                    .position(|size| *size == STORE_FREE)
+                {
+                    subpool.sizes_list[packet_idx] = len;
+                    let raw_pos = packet_idx * self.block_size(pool_idx);
+                    writer(&mut subpool.data[raw_pos..raw_pos + len]);
+                    return Ok(StaticPoolAddr {
+                        pool_idx,
+                        packet_idx: packet_idx as u16,
+                    }
+                    .into());
+                }
+                if !self.pool_cfg.spill_to_higher_subpools()
+                    || pool_idx as usize + 1 == self.subpools.len()
+                {
+                    return Err(allocation_failure(len, PoolError::StoreFull(pool_idx)));
+                }
+                pool_idx += 1;
+            }
+        }
+
+        pub fn modify<U: FnMut(&mut [u8])>(
+            &self,
+            addr: &PoolAddr,
+            mut updater: U,
+        ) -> Result<(), PoolError> {
+            self.with_used_block(addr, |subpool, raw_pos, curr_size| {
+                updater(&mut subpool.data[raw_pos..raw_pos + curr_size]);
+                Ok(())
+            })
+        }
+
+        pub fn read(&self, addr: &PoolAddr, buf: &mut [u8]) -> Result<usize, PoolError> {
+            self.with_used_block(addr, |subpool, raw_pos, curr_size| {
+                if buf.len() < curr_size {
+                    return Err(ByteConversionError::ToSliceTooSmall {
+                        found: buf.len(),
+                        expected: curr_size,
+                    }
+                    .into());
+                }
+                buf[..curr_size].copy_from_slice(&subpool.data[raw_pos..raw_pos + curr_size]);
+                Ok(curr_size)
+            })
+        }
+
+        pub fn delete(&self, addr: PoolAddr) -> Result<(), PoolError> {
+            let static_addr = StaticPoolAddr::from(addr);
+            self.with_used_block(&addr, |subpool, raw_pos, _| {
+                let block_size = self.block_size(static_addr.pool_idx);
+                subpool.sizes_list[static_addr.packet_idx as usize] = STORE_FREE;
+                subpool.data[raw_pos..raw_pos + block_size].fill(0);
+                Ok(())
+            })
+        }
+
+        pub fn has_element_at(&self, addr: &PoolAddr) -> Result<bool, PoolError> {
+            self.with_block(addr, |_, _, curr_size| Ok(curr_size != STORE_FREE))
         }
 
-        fn read_with_guard(&mut self, addr: PoolAddr) -> PoolGuard<Self> {
-            PoolGuard::new(self, addr)
+        pub fn len_of_data(&self, addr: &PoolAddr) -> Result<usize, PoolError> {
+            self.with_block(addr, |_, _, curr_size| {
+                Ok(match curr_size {
+                    STORE_FREE => 0,
+                    _ => curr_size,
+                })
+            })
         }
     }
+
+    macro_rules! sharded_pool_provider_impl {
+        ($ty: ty) => {
+            impl PoolProvider for $ty {
+                fn add(&mut self, data: &[u8]) -> Result<PoolAddr, PoolError> {
+                    ShardedStaticMemoryPool::add(self, data)
+                }
+
+                fn free_element_dyn(
+                    &mut self,
+                    len: usize,
+                    writer: &mut dyn FnMut(&mut [u8]),
+                ) -> Result<PoolAddr, PoolError> {
+                    ShardedStaticMemoryPool::free_element(self, len, writer)
+                }
+
+                fn modify_dyn(
+                    &mut self,
+                    addr: &PoolAddr,
+                    updater: &mut dyn FnMut(&mut [u8]),
+                ) -> Result<(), PoolError> {
+                    ShardedStaticMemoryPool::modify(self, addr, updater)
+                }
+
+                fn read(&self, addr: &PoolAddr, buf: &mut [u8]) -> Result<usize, PoolError> {
+                    ShardedStaticMemoryPool::read(self, addr, buf)
+                }
+
+                fn delete(&mut self, addr: PoolAddr) -> Result<(), PoolError> {
+                    ShardedStaticMemoryPool::delete(self, addr)
+                }
+
+                fn has_element_at(&self, addr: &PoolAddr) -> Result<bool, PoolError> {
+                    ShardedStaticMemoryPool::has_element_at(self, addr)
+                }
+
+                fn len_of_data(&self, addr: &PoolAddr) -> Result<usize, PoolError> {
+                    ShardedStaticMemoryPool::len_of_data(self, addr)
+                }
+            }
+        };
+    }
+
+    sharded_pool_provider_impl!(ShardedStaticMemoryPool);
+    sharded_pool_provider_impl!(SharedShardedMemoryPool);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::boxed::Box;
     use std::vec;
+    use std::vec::Vec;
 
     fn basic_small_pool() -> StaticMemoryPool {
         // 4 buckets of 4 bytes, 2 of 8 bytes and 1 of 16 bytes
@@ -1884,4 +2694,326 @@ mod tests {
             generic_test_spillage_fails_across_multiple_subpools(&mut heapless_pool);
         }
     }
+
+    #[test]
+    fn test_priority_queue_fifo_per_lane() {
+        let mut queue: PriorityPoolQueue<u32> = PriorityPoolQueue::new(3, 4);
+        assert_eq!(queue.num_priority_classes(), 3);
+        assert!(queue.is_empty());
+        queue.push(2, 20).unwrap();
+        queue.push(1, 10).unwrap();
+        queue.push(2, 21).unwrap();
+        queue.push(0, 0).unwrap();
+        queue.push(1, 11).unwrap();
+        assert_eq!(queue.len(), 5);
+        assert_eq!(queue.len_of_lane(1), Some(2));
+        assert_eq!(queue.peek(), Some((0, &0)));
+        assert_eq!(queue.pop(), Some((0, 0)));
+        assert_eq!(queue.pop(), Some((1, 10)));
+        assert_eq!(queue.pop(), Some((1, 11)));
+        assert_eq!(queue.pop(), Some((2, 20)));
+        assert_eq!(queue.pop(), Some((2, 21)));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_priority_queue_errors() {
+        let mut queue: PriorityPoolQueue<u32> = PriorityPoolQueue::new(2, 1);
+        assert_eq!(
+            queue.push(2, 0).unwrap_err(),
+            PriorityQueueError::InvalidPriority(2)
+        );
+        queue.push(1, 0).unwrap();
+        assert_eq!(
+            queue.push(1, 1).unwrap_err(),
+            PriorityQueueError::LaneFull(1)
+        );
+        assert_eq!(queue.len_of_lane(2), None);
+    }
+
+    #[test]
+    fn test_priority_queue_with_pool() {
+        let mut pool = basic_small_pool();
+        let mut queue = PriorityPoolQueue::new(2, 1);
+        let addr_low = queue.add_and_push(&mut pool, 1, &[1, 2, 3]).unwrap();
+        let addr_high = queue.add_and_push(&mut pool, 0, &[4, 5, 6]).unwrap();
+        // Lane is full, the data should not be stored in the pool.
+        let error = queue.add_and_push(&mut pool, 0, &[7, 8, 9]).unwrap_err();
+        assert_eq!(
+            error,
+            PriorityPoolQueueError::Queue(PriorityQueueError::LaneFull(0))
+        );
+        assert_eq!(queue.pop(), Some((0, addr_high)));
+        let mut read_buf: [u8; 4] = [0; 4];
+        assert_eq!(pool.read(&addr_high, &mut read_buf).unwrap(), 3);
+        assert_eq!(&read_buf[0..3], &[4, 5, 6]);
+        assert_eq!(queue.clear_and_delete(&mut pool).unwrap(), 1);
+        assert!(!pool.has_element_at(&addr_low).unwrap());
+        assert!(queue.is_empty());
+    }
+
+    mod expiring_pool {
+        use super::*;
+        use crate::event_man::EventMessageU32;
+        use crate::events::{EventU32, Severity};
+        use crate::params::{Params, ParamsRaw};
+        use std::sync::mpsc;
+        use std::time::{Duration, Instant};
+
+        const EXPIRY_EVENT: EventU32 = EventU32::new(Severity::Low, 1, 2);
+
+        #[test]
+        fn test_expire_stale() {
+            let mut pool =
+                ExpiringPool::new(basic_small_pool()).with_expiry_event(ExpiryEventConfig {
+                    sender_id: 5,
+                    event: EXPIRY_EVENT,
+                });
+            let (event_tx, event_rx) = mpsc::channel();
+            let event_sender = crate::event_man::EventU32SenderMpsc::new(1, event_tx);
+            let now = Instant::now();
+            let stale_addr = pool.add(&[1, 2, 3]).unwrap();
+            pool.set_deadline(stale_addr, now).unwrap();
+            let addr_with_ttl = pool
+                .add_with_ttl(&[4, 5, 6], Duration::from_secs(60))
+                .unwrap();
+            let addr_without_ttl = pool.add(&[7, 8, 9]).unwrap();
+            assert_eq!(pool.num_tagged_entries(), 2);
+            assert!(pool.deadline(&addr_without_ttl).is_none());
+
+            let num_expired = pool.expire_stale(now, Some(&event_sender)).unwrap();
+            assert_eq!(num_expired, 1);
+            assert!(!pool.has_element_at(&stale_addr).unwrap());
+            assert!(pool.has_element_at(&addr_with_ttl).unwrap());
+            assert!(pool.has_element_at(&addr_without_ttl).unwrap());
+            let event: EventMessageU32 = event_rx.try_recv().expect("no expiry event");
+            assert_eq!(event.sender_id(), 5);
+            assert_eq!(event.event(), EXPIRY_EVENT);
+            assert_eq!(event.params(), Some(&Params::from(ParamsRaw::from(1_u32))));
+
+            let num_expired = pool
+                .expire_stale(now + Duration::from_secs(120), Some(&event_sender))
+                .unwrap();
+            assert_eq!(num_expired, 1);
+            assert!(!pool.has_element_at(&addr_with_ttl).unwrap());
+            assert!(pool.has_element_at(&addr_without_ttl).unwrap());
+            assert_eq!(pool.num_tagged_entries(), 0);
+        }
+
+        #[test]
+        fn test_deletion_clears_deadline() {
+            let mut pool = ExpiringPool::new(basic_small_pool());
+            let now = Instant::now();
+            let addr = pool.add(&[1, 2, 3]).unwrap();
+            pool.set_deadline(addr, now).unwrap();
+            pool.delete(addr).unwrap();
+            assert!(pool.deadline(&addr).is_none());
+            // The address might be re-used for new data, which must not expire.
+            let new_addr = pool.add(&[4, 5, 6]).unwrap();
+            let num_expired = pool
+                .expire_stale(now, None::<&crate::event_man::EventU32SenderMpsc>)
+                .unwrap();
+            assert_eq!(num_expired, 0);
+            assert!(pool.has_element_at(&new_addr).unwrap());
+        }
+
+        #[test]
+        fn test_set_deadline_invalid_addr() {
+            let mut pool = ExpiringPool::new(basic_small_pool());
+            let addr = pool.add(&[1, 2, 3]).unwrap();
+            pool.delete(addr).unwrap();
+            assert_eq!(
+                pool.set_deadline(addr, Instant::now()).unwrap_err(),
+                PoolError::DataDoesNotExist(addr)
+            );
+        }
+    }
+
+    mod sharded_pool {
+        use super::*;
+        use std::sync::Arc;
+        use std::thread;
+
+        fn basic_small_sharded_pool() -> ShardedStaticMemoryPool {
+            let pool_cfg =
+                StaticPoolConfig::new_from_subpool_cfg_tuples(vec![(4, 4), (2, 8), (1, 16)], false);
+            ShardedStaticMemoryPool::new(pool_cfg)
+        }
+
+        #[test]
+        fn test_generic_pool_operations() {
+            generic_test_add_and_read::<16>(&mut basic_small_sharded_pool());
+            generic_test_add_smaller_than_full_slot(&mut basic_small_sharded_pool());
+            generic_test_delete(&mut basic_small_sharded_pool());
+            generic_test_modify(&mut basic_small_sharded_pool());
+            generic_test_consecutive_reservation(&mut basic_small_sharded_pool());
+            generic_test_read_does_not_exist(&mut basic_small_sharded_pool());
+            generic_test_store_full(&mut basic_small_sharded_pool());
+            generic_test_invalid_pool_idx(&mut basic_small_sharded_pool());
+            generic_test_invalid_packet_idx(&mut basic_small_sharded_pool());
+            generic_test_add_too_large(&mut basic_small_sharded_pool());
+            generic_test_data_too_large_1(&mut basic_small_sharded_pool());
+            generic_test_free_element_too_large(&mut basic_small_sharded_pool());
+            generic_modify_pool_index_above_0(&mut basic_small_sharded_pool());
+        }
+
+        #[test]
+        fn test_guards() {
+            let mut shared_pool = Arc::new(basic_small_sharded_pool());
+            generic_test_pool_guard_deletion(&mut shared_pool);
+            generic_test_pool_guard_with_release(&mut shared_pool);
+            generic_test_pool_modify_guard(&mut shared_pool);
+        }
+
+        #[test]
+        fn test_spillage() {
+            let pool_cfg =
+                StaticPoolConfig::new_from_subpool_cfg_tuples(vec![(1, 8), (1, 16)], true);
+            generic_test_spillage_fails_as_well(&mut ShardedStaticMemoryPool::new(
+                pool_cfg.clone(),
+            ));
+            let pool_cfg =
+                StaticPoolConfig::new_from_subpool_cfg_tuples(vec![(1, 8), (1, 12), (1, 16)], true);
+            generic_test_spillage_works_across_multiple_subpools(
+                &mut ShardedStaticMemoryPool::new(pool_cfg.clone()),
+            );
+            generic_test_spillage_fails_across_multiple_subpools(
+                &mut ShardedStaticMemoryPool::new(pool_cfg),
+            );
+        }
+
+        #[test]
+        fn test_concurrent_access() {
+            const NUM_ITERATIONS: usize = 200;
+            let shared_pool = ShardedStaticMemoryPool::new_shared(
+                StaticPoolConfig::new_from_subpool_cfg_tuples(vec![(8, 4), (8, 16)], false),
+            );
+            let handles: Vec<_> = [4, 16]
+                .into_iter()
+                .map(|data_len| {
+                    let mut pool = shared_pool.clone();
+                    thread::spawn(move || {
+                        let mut read_buf = [0; 16];
+                        for i in 0..NUM_ITERATIONS {
+                            let data = vec![i as u8; data_len];
+                            let addr = pool.add(&data).unwrap();
+                            assert_eq!(pool.read(&addr, &mut read_buf).unwrap(), data_len);
+                            assert_eq!(&read_buf[0..data_len], data.as_slice());
+                            pool.delete(addr).unwrap();
+                        }
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.join().unwrap();
+            }
+            for (pool_idx, subpool_cfg) in shared_pool.pool_cfg().subpool_cfg().iter().enumerate() {
+                for packet_idx in 0..subpool_cfg.num_blocks {
+                    let addr = PoolAddr::from(StaticPoolAddr {
+                        pool_idx: pool_idx as u16,
+                        packet_idx,
+                    });
+                    assert!(!shared_pool.has_element_at(&addr).unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pool_provider_trait_object() {
+        let mut pools: Vec<Box<dyn PoolProvider>> = vec![
+            Box::new(basic_small_pool()),
+            Box::new(ExpiringPool::new(basic_small_pool())),
+        ];
+        for pool in pools.iter_mut() {
+            let addr = pool
+                .free_element_dyn(3, &mut |buf: &mut [u8]| buf.copy_from_slice(&[1, 2, 3]))
+                .unwrap();
+            pool.modify_dyn(&addr, &mut |buf: &mut [u8]| buf[0] = 4)
+                .unwrap();
+            assert_eq!(pool.read_as_vec(&addr).unwrap(), vec![4, 2, 3]);
+            let mut rw_guard = pool.modify_with_guard(addr);
+            rw_guard.update(&mut |buf: &mut [u8]| buf[1] = 5).unwrap();
+            let mut read_buf = [0; 3];
+            assert_eq!(rw_guard.read(&mut read_buf).unwrap(), 3);
+            assert_eq!(read_buf, [4, 5, 3]);
+            drop(rw_guard);
+            assert!(!pool.has_element_at(&addr).unwrap());
+        }
+    }
+
+    mod shared_pool_guard {
+        use super::*;
+        use std::sync::{Arc, RwLock};
+        use std::thread;
+
+        fn shared_small_pool() -> SharedStaticMemoryPool {
+            Arc::new(RwLock::new(basic_small_pool()))
+        }
+
+        #[test]
+        fn test_deletion_on_drop() {
+            let shared_pool = shared_small_pool();
+            let addr = shared_pool.write().unwrap().add(&[1, 2, 3]).unwrap();
+            let guard = SharedPoolGuard::new(&shared_pool, addr);
+            assert_eq!(guard.addr(), addr);
+            assert_eq!(guard.len_of_data().unwrap(), 3);
+            // The pool can be used while the guard exists.
+            let other_addr = shared_pool.write().unwrap().add(&[4]).unwrap();
+            guard.modify(|buf| buf[0] = 7).unwrap();
+            assert_eq!(guard.read_as_vec().unwrap(), vec![7, 2, 3]);
+            drop(guard);
+            let pool = shared_pool.read().unwrap();
+            assert!(!pool.has_element_at(&addr).unwrap());
+            assert!(pool.has_element_at(&other_addr).unwrap());
+        }
+
+        #[test]
+        fn test_release() {
+            let shared_pool = shared_small_pool();
+            let addr = shared_pool.write().unwrap().add(&[1, 2, 3]).unwrap();
+            let guard = SharedPoolGuard::new(&shared_pool, addr);
+            assert_eq!(guard.release(), addr);
+            assert!(shared_pool.read().unwrap().has_element_at(&addr).unwrap());
+        }
+
+        #[test]
+        fn test_non_blocking_access() {
+            let shared_pool = shared_small_pool();
+            let addr = shared_pool.write().unwrap().add(&[1, 2, 3]).unwrap();
+            let guard = SharedPoolGuard::new(&shared_pool, addr);
+            let mut read_buf = [0; 3];
+            {
+                let _read_lock = shared_pool.read().unwrap();
+                assert_eq!(guard.try_read(&mut read_buf).unwrap(), 3);
+                assert_eq!(
+                    guard.try_modify(|buf| buf[0] = 4).unwrap_err(),
+                    PoolError::WouldBlock
+                );
+            }
+            {
+                let _write_lock = shared_pool.write().unwrap();
+                assert_eq!(
+                    guard.try_read(&mut read_buf).unwrap_err(),
+                    PoolError::WouldBlock
+                );
+            }
+            guard.try_modify(|buf| buf[0] = 4).unwrap();
+            assert_eq!(guard.try_read(&mut read_buf).unwrap(), 3);
+            assert_eq!(read_buf, [4, 2, 3]);
+        }
+
+        #[test]
+        fn test_guard_can_be_moved_to_other_thread() {
+            let shared_pool = shared_small_pool();
+            let addr = shared_pool.write().unwrap().add(&[1, 2, 3]).unwrap();
+            let guard = SharedPoolGuard::new(&shared_pool, addr);
+            let data = thread::spawn(move || guard.read_as_vec().unwrap())
+                .join()
+                .unwrap();
+            assert_eq!(data, vec![1, 2, 3]);
+            assert!(!shared_pool.read().unwrap().has_element_at(&addr).unwrap());
+        }
+    }
 }
@@ -0,0 +1,347 @@
+//! # Typed extraction of telecommand application data
+//!
+//! Service handlers usually check the length of the application data manually before
+//! extracting values like target IDs with `from_be_bytes`. The [AppDataReader] is a zero-copy
+//! cursor over the application data with typed getters. All getters check the remaining length
+//! and return a structured [NotEnoughAppData] error otherwise, which can be converted directly
+//! into the failure data of a verification failure report with [NotEnoughAppData::to_fail_data].
+//!
+//! # Example
+//!
+//! ```
+//! use satrs::mode::ModeAndSubmode;
+//! use satrs::pus::app_data::{AppDataReader, NotEnoughAppData};
+//!
+//! let app_data = [0, 0, 0, 5, 0, 0, 0, 2, 0, 1];
+//! let mut reader = AppDataReader::new(&app_data);
+//! let target_id = reader.read_target_id(0x02).unwrap();
+//! assert_eq!(target_id.unique_id, 5);
+//! let mode_and_submode: ModeAndSubmode = reader.read().unwrap();
+//! assert_eq!(mode_and_submode, ModeAndSubmode::new(2, 1));
+//! assert!(reader.is_empty());
+//! assert_eq!(
+//!     reader.read::<u32>().unwrap_err().not_enough_app_data(),
+//!     Some(NotEnoughAppData { expected: 14, found: 10 })
+//! );
+//! ```
+use core::fmt::{Display, Formatter};
+use core::mem::size_of;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::mode::ModeAndSubmode;
+use crate::request::{Apid, UniqueApidTargetId};
+
+/// The application data is shorter than required.
+///
+/// The `expected` and `found` sizes always refer to the complete application data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NotEnoughAppData {
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl NotEnoughAppData {
+    /// Failure data for a verification failure report, which contains the found size followed
+    /// by the expected size as big endian [u32] values.
+    pub fn to_fail_data(&self) -> [u8; 8] {
+        let mut fail_data = [0; 8];
+        fail_data[0..4].copy_from_slice(&(self.found as u32).to_be_bytes());
+        fail_data[4..8].copy_from_slice(&(self.expected as u32).to_be_bytes());
+        fail_data
+    }
+}
+
+impl Display for NotEnoughAppData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "not enough application data, expected at least {}, found {}",
+            self.expected, self.found
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for NotEnoughAppData {}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AppDataError {
+    NotEnoughAppData(NotEnoughAppData),
+    /// The raw value at the given offset is not a valid enumeration value.
+    InvalidEnumValue {
+        offset: usize,
+        raw: u64,
+    },
+}
+
+impl AppDataError {
+    pub fn not_enough_app_data(&self) -> Option<NotEnoughAppData> {
+        match self {
+            AppDataError::NotEnoughAppData(e) => Some(*e),
+            _ => None,
+        }
+    }
+}
+
+impl From<NotEnoughAppData> for AppDataError {
+    fn from(value: NotEnoughAppData) -> Self {
+        Self::NotEnoughAppData(value)
+    }
+}
+
+impl Display for AppDataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AppDataError::NotEnoughAppData(e) => write!(f, "{e}"),
+            AppDataError::InvalidEnumValue { offset, raw } => {
+                write!(f, "invalid enumeration value {raw} at offset {offset}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for AppDataError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppDataError::NotEnoughAppData(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Types which can be read from the application data by the [AppDataReader].
+pub trait FromAppData: Sized {
+    /// Size of the raw value.
+    const RAW_LEN: usize;
+
+    /// Create the value from a big endian raw buffer which has exactly [Self::RAW_LEN] bytes.
+    fn from_raw(raw: &[u8]) -> Self;
+}
+
+macro_rules! from_app_data_impl {
+    ($($ty: ty,)+) => {
+        $(
+            impl FromAppData for $ty {
+                const RAW_LEN: usize = size_of::<$ty>();
+
+                fn from_raw(raw: &[u8]) -> Self {
+                    <$ty>::from_be_bytes(raw.try_into().unwrap())
+                }
+            }
+        )+
+    };
+}
+
+from_app_data_impl!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64,);
+
+impl FromAppData for ModeAndSubmode {
+    const RAW_LEN: usize = ModeAndSubmode::RAW_LEN;
+
+    fn from_raw(raw: &[u8]) -> Self {
+        ModeAndSubmode::from_be_bytes(raw).unwrap()
+    }
+}
+
+/// Zero-copy reader for telecommand application data.
+#[derive(Debug, Clone)]
+pub struct AppDataReader<'app_data> {
+    app_data: &'app_data [u8],
+    offset: usize,
+}
+
+impl<'app_data> AppDataReader<'app_data> {
+    pub fn new(app_data: &'app_data [u8]) -> Self {
+        Self {
+            app_data,
+            offset: 0,
+        }
+    }
+
+    /// Offset of the next value.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Application data which was not read yet.
+    pub fn remaining(&self) -> &'app_data [u8] {
+        &self.app_data[self.offset..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset == self.app_data.len()
+    }
+
+    /// Check that at least `len` more bytes are available without consuming them.
+    pub fn ensure_remaining(&self, len: usize) -> Result<(), NotEnoughAppData> {
+        if self.app_data.len() - self.offset < len {
+            return Err(NotEnoughAppData {
+                expected: self.offset + len,
+                found: self.app_data.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Read the given number of raw bytes.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'app_data [u8], NotEnoughAppData> {
+        self.ensure_remaining(len)?;
+        let bytes = &self.app_data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// Read all remaining bytes, which might be empty.
+    pub fn read_remaining(&mut self) -> &'app_data [u8] {
+        let bytes = self.remaining();
+        self.offset = self.app_data.len();
+        bytes
+    }
+
+    /// Read a value like a primitive integer or a [ModeAndSubmode].
+    pub fn read<T: FromAppData>(&mut self) -> Result<T, NotEnoughAppData> {
+        Ok(T::from_raw(self.read_bytes(T::RAW_LEN)?))
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, NotEnoughAppData> {
+        self.read()
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, NotEnoughAppData> {
+        self.read()
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, NotEnoughAppData> {
+        self.read()
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, NotEnoughAppData> {
+        self.read()
+    }
+
+    pub fn read_mode_and_submode(&mut self) -> Result<ModeAndSubmode, NotEnoughAppData> {
+        self.read()
+    }
+
+    /// Read an enumeration which is serialized as the raw type `Raw`, for example
+    /// `read_enum::<u8, _>()` for an enumeration with an [u8] representation.
+    /// The raw value is consumed even if it is not a valid enumeration value.
+    pub fn read_enum<Raw: FromAppData + Into<u64> + Copy, E: TryFrom<Raw>>(
+        &mut self,
+    ) -> Result<E, AppDataError> {
+        let offset = self.offset;
+        let raw: Raw = self.read()?;
+        E::try_from(raw).map_err(|_| AppDataError::InvalidEnumValue {
+            offset,
+            raw: raw.into(),
+        })
+    }
+
+    /// Read the unique ID of a [UniqueApidTargetId] as a big endian [u32]. The APID is usually
+    /// the APID of the telecommand.
+    pub fn read_target_id(&mut self, apid: Apid) -> Result<UniqueApidTargetId, NotEnoughAppData> {
+        Ok(UniqueApidTargetId::new(apid, self.read_u32()?))
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_mod {
+    use super::*;
+    use crate::pus::GenericConversionError;
+    use std::string::ToString;
+
+    impl From<NotEnoughAppData> for GenericConversionError {
+        fn from(value: NotEnoughAppData) -> Self {
+            GenericConversionError::NotEnoughAppData {
+                expected: value.expected,
+                found: value.found,
+            }
+        }
+    }
+
+    impl From<AppDataError> for GenericConversionError {
+        fn from(value: AppDataError) -> Self {
+            match value {
+                AppDataError::NotEnoughAppData(e) => e.into(),
+                e => GenericConversionError::InvalidAppData(e.to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pus::GenericConversionError;
+    use spacepackets::ecss::hk;
+
+    #[test]
+    fn test_typed_getters() {
+        let app_data = [
+            1, 0, 2, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 4, 0xff, 0x40, 0x20, 0, 0,
+        ];
+        let mut reader = AppDataReader::new(&app_data);
+        assert_eq!(reader.read_u8().unwrap(), 1);
+        assert_eq!(reader.read_u16().unwrap(), 2);
+        assert_eq!(reader.read_u32().unwrap(), 3);
+        assert_eq!(reader.read_u64().unwrap(), 4);
+        assert_eq!(reader.read::<i8>().unwrap(), -1);
+        assert_eq!(reader.offset(), 16);
+        assert_eq!(reader.remaining(), &[0x40, 0x20, 0, 0]);
+        assert_eq!(reader.read::<f32>().unwrap(), 2.5);
+        assert!(reader.is_empty());
+        assert_eq!(reader.read_remaining(), &[]);
+    }
+
+    #[test]
+    fn test_not_enough_app_data() {
+        let app_data = [0, 0, 0, 1, 0, 2];
+        let mut reader = AppDataReader::new(&app_data);
+        assert_eq!(reader.read_target_id(0x02).unwrap().unique_id, 1);
+        let error = reader.read_mode_and_submode().unwrap_err();
+        assert_eq!(
+            error,
+            NotEnoughAppData {
+                expected: 4 + ModeAndSubmode::RAW_LEN,
+                found: 6
+            }
+        );
+        // Nothing was consumed.
+        assert_eq!(reader.offset(), 4);
+        assert_eq!(
+            error.to_fail_data(),
+            [0, 0, 0, 6, 0, 0, 0, 4 + ModeAndSubmode::RAW_LEN as u8]
+        );
+        assert_eq!(reader.read_bytes(2).unwrap(), &[0, 2]);
+        assert!(matches!(
+            GenericConversionError::from(error),
+            GenericConversionError::NotEnoughAppData {
+                expected: 10,
+                found: 6
+            }
+        ));
+    }
+
+    #[test]
+    fn test_enum() {
+        let app_data = [hk::Subservice::TcGenerateOneShotHk as u8, 0xff];
+        let mut reader = AppDataReader::new(&app_data);
+        let subservice: hk::Subservice = reader.read_enum::<u8, _>().unwrap();
+        assert_eq!(subservice, hk::Subservice::TcGenerateOneShotHk);
+        assert_eq!(
+            reader.read_enum::<u8, hk::Subservice>().unwrap_err(),
+            AppDataError::InvalidEnumValue {
+                offset: 1,
+                raw: 0xff
+            }
+        );
+        assert!(matches!(
+            reader.read_enum::<u8, hk::Subservice>().unwrap_err(),
+            AppDataError::NotEnoughAppData(NotEnoughAppData {
+                expected: 3,
+                found: 2
+            })
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+//! # PUS TM destination ID routing
+//!
+//! The destination ID field of the PUS TM secondary header is usually set from the fixed
+//! destination IDs configured for each TM generator. Missions with multiple ground segment
+//! destinations can instead configure a central [TmDestIdTable], which maps the service and
+//! subservice of generated TM to a destination ID.
+//!
+//! The table implements the [VerificationHookProvider] and the [EventTmHookProvider] traits, so
+//! it can be passed as the TM hook of the [crate::pus::verification::VerificationReporter] and
+//! the [crate::pus::event::EventReporter]. The [SharedTmDestIdTable] is a cheaply clonable
+//! handle to one table which allows updating the routing of all reporters at run-time. Other TM
+//! generators can use [TmDestIdTable::apply] before sending their packets.
+//!
+//! Please note that the routing table overrides the destination ID set by the reporters. To keep
+//! the [crate::pus::verification::DestIdRouting] of the verification reporter, no entries should
+//! be added for service 1.
+use alloc::collections::BTreeMap;
+use spacepackets::ecss::tm::PusTmCreator;
+use spacepackets::ecss::PusPacket;
+
+use crate::pus::event::EventTmHookProvider;
+use crate::pus::verification::VerificationHookProvider;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// Routing table which maps the service and subservice of TM packets to a destination ID.
+///
+/// A destination ID configured for a subservice has precedence over the destination ID of the
+/// service, which has precedence over the default destination ID. Packets without a matching
+/// entry keep their destination ID.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TmDestIdTable {
+    default: Option<u16>,
+    services: BTreeMap<u8, u16>,
+    subservices: BTreeMap<(u8, u8), u16>,
+}
+
+impl TmDestIdTable {
+    /// Create a table without a default destination ID.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_default(mut self, dest_id: u16) -> Self {
+        self.default = Some(dest_id);
+        self
+    }
+
+    pub fn with_service(mut self, service: u8, dest_id: u16) -> Self {
+        self.set_service_dest_id(service, dest_id);
+        self
+    }
+
+    pub fn with_subservice(mut self, service: u8, subservice: u8, dest_id: u16) -> Self {
+        self.set_subservice_dest_id(service, subservice, dest_id);
+        self
+    }
+
+    pub fn default_dest_id(&self) -> Option<u16> {
+        self.default
+    }
+
+    pub fn set_default_dest_id(&mut self, dest_id: Option<u16>) {
+        self.default = dest_id;
+    }
+
+    pub fn set_service_dest_id(&mut self, service: u8, dest_id: u16) {
+        self.services.insert(service, dest_id);
+    }
+
+    pub fn remove_service_dest_id(&mut self, service: u8) -> Option<u16> {
+        self.services.remove(&service)
+    }
+
+    pub fn set_subservice_dest_id(&mut self, service: u8, subservice: u8, dest_id: u16) {
+        self.subservices.insert((service, subservice), dest_id);
+    }
+
+    pub fn remove_subservice_dest_id(&mut self, service: u8, subservice: u8) -> Option<u16> {
+        self.subservices.remove(&(service, subservice))
+    }
+
+    /// Destination ID for TM with the given service and subservice, or [None] if the table
+    /// does not contain a matching entry.
+    pub fn dest_id(&self, service: u8, subservice: u8) -> Option<u16> {
+        self.subservices
+            .get(&(service, subservice))
+            .or_else(|| self.services.get(&service))
+            .copied()
+            .or(self.default)
+    }
+
+    /// Set the destination ID of the TM if the table contains a matching entry. Returns whether
+    /// the destination ID was set.
+    pub fn apply(&self, tm: &mut PusTmCreator) -> bool {
+        if let Some(dest_id) = self.dest_id(tm.service(), tm.subservice()) {
+            tm.set_dest_id(dest_id);
+            return true;
+        }
+        false
+    }
+}
+
+impl VerificationHookProvider for TmDestIdTable {
+    fn modify_tm(&self, tm: &mut PusTmCreator) {
+        self.apply(tm);
+    }
+}
+
+impl EventTmHookProvider for TmDestIdTable {
+    fn modify_tm(&self, tm: &mut PusTmCreator) {
+        self.apply(tm);
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+    use super::*;
+
+    /// Handle to a [TmDestIdTable] which is shared by multiple TM generators. Changes to the
+    /// table are applied to all TM generated afterwards.
+    #[derive(Debug, Default, Clone)]
+    pub struct SharedTmDestIdTable(Arc<RwLock<TmDestIdTable>>);
+
+    impl SharedTmDestIdTable {
+        pub fn new(table: TmDestIdTable) -> Self {
+            Self(Arc::new(RwLock::new(table)))
+        }
+
+        pub fn read(&self) -> RwLockReadGuard<'_, TmDestIdTable> {
+            self.0.read().unwrap()
+        }
+
+        pub fn write(&self) -> RwLockWriteGuard<'_, TmDestIdTable> {
+            self.0.write().unwrap()
+        }
+
+        pub fn dest_id(&self, service: u8, subservice: u8) -> Option<u16> {
+            self.read().dest_id(service, subservice)
+        }
+
+        pub fn apply(&self, tm: &mut PusTmCreator) -> bool {
+            self.read().apply(tm)
+        }
+    }
+
+    impl From<TmDestIdTable> for SharedTmDestIdTable {
+        fn from(table: TmDestIdTable) -> Self {
+            Self::new(table)
+        }
+    }
+
+    impl VerificationHookProvider for SharedTmDestIdTable {
+        fn modify_tm(&self, tm: &mut PusTmCreator) {
+            self.apply(tm);
+        }
+    }
+
+    impl EventTmHookProvider for SharedTmDestIdTable {
+        fn modify_tm(&self, tm: &mut PusTmCreator) {
+            self.apply(tm);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::events::{EventU32, Severity};
+    use crate::pus::event::EventReporter;
+    use crate::pus::test_util::TEST_COMPONENT_ID_0;
+    use crate::pus::verification::{
+        VerificationReporter, VerificationReporterCfg, VerificationReportingProvider,
+    };
+    use crate::tmtc::PacketAsVec;
+    use spacepackets::ecss::tc::{PusTcCreator, PusTcSecondaryHeader};
+    use spacepackets::ecss::tm::{GenericPusTmSecondaryHeader, PusTmReader, PusTmSecondaryHeader};
+    use spacepackets::SpHeader;
+    use std::sync::mpsc;
+
+    const TEST_APID: u16 = 0x02;
+    const GROUND_DEST_ID: u16 = 5;
+    const ONBOARD_DEST_ID: u16 = 6;
+
+    fn create_table() -> TmDestIdTable {
+        TmDestIdTable::new()
+            .with_default(1)
+            .with_service(1, GROUND_DEST_ID)
+            .with_subservice(5, 4, ONBOARD_DEST_ID)
+    }
+
+    fn dest_id_of(packet: &PacketAsVec) -> u16 {
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        tm.dest_id()
+    }
+
+    #[test]
+    fn test_lookup_precedence() {
+        let mut table = create_table();
+        assert_eq!(table.dest_id(1, 7), Some(GROUND_DEST_ID));
+        assert_eq!(table.dest_id(5, 4), Some(ONBOARD_DEST_ID));
+        assert_eq!(table.dest_id(5, 1), Some(1));
+        table.set_default_dest_id(None);
+        assert_eq!(table.dest_id(5, 1), None);
+        assert_eq!(table.remove_subservice_dest_id(5, 4), Some(ONBOARD_DEST_ID));
+        assert_eq!(table.dest_id(5, 4), None);
+    }
+
+    #[test]
+    fn test_apply() {
+        let table = TmDestIdTable::new().with_service(3, GROUND_DEST_ID);
+        let sec_header = PusTmSecondaryHeader::new_simple(3, 25, &[]);
+        let mut tm =
+            PusTmCreator::new_no_source_data(SpHeader::new_from_apid(TEST_APID), sec_header, true);
+        assert!(table.apply(&mut tm));
+        assert_eq!(tm.dest_id(), GROUND_DEST_ID);
+        let sec_header = PusTmSecondaryHeader::new_simple(17, 2, &[]);
+        let mut tm =
+            PusTmCreator::new_no_source_data(SpHeader::new_from_apid(TEST_APID), sec_header, true);
+        assert!(!table.apply(&mut tm));
+        assert_eq!(tm.dest_id(), 0);
+    }
+
+    #[test]
+    fn test_shared_table_as_reporter_hooks() {
+        let (tm_tx, tm_rx) = mpsc::channel();
+        let shared_table = SharedTmDestIdTable::new(create_table());
+        let cfg = VerificationReporterCfg::new(TEST_APID, 1, 2, 8).unwrap();
+        let mut verif_reporter = VerificationReporter::new_with_hook(
+            TEST_COMPONENT_ID_0.id(),
+            &cfg,
+            shared_table.clone(),
+        );
+        let event_reporter = EventReporter::new_with_hook(
+            TEST_COMPONENT_ID_0.id(),
+            TEST_APID,
+            0,
+            16,
+            shared_table.clone(),
+        )
+        .unwrap();
+
+        let tc = PusTcCreator::new_no_app_data(
+            SpHeader::new_from_apid(TEST_APID),
+            PusTcSecondaryHeader::new_simple(17, 1),
+            true,
+        );
+        let token = verif_reporter.add_tc(&tc);
+        verif_reporter
+            .acceptance_success(&tm_tx, token, &[0; 7])
+            .unwrap();
+        assert_eq!(dest_id_of(&tm_rx.try_recv().unwrap()), GROUND_DEST_ID);
+
+        let event = EventU32::new(Severity::Info, 1, 1);
+        event_reporter
+            .event_info(&tm_tx, &[0; 7], event, None)
+            .unwrap();
+        assert_eq!(dest_id_of(&tm_rx.try_recv().unwrap()), 1);
+
+        // Updating the central table changes the routing of all reporters.
+        shared_table.write().set_service_dest_id(5, ONBOARD_DEST_ID);
+        event_reporter
+            .event_info(&tm_tx, &[0; 7], event, None)
+            .unwrap();
+        assert_eq!(dest_id_of(&tm_rx.try_recv().unwrap()), ONBOARD_DEST_ID);
+        assert!(tm_rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! # PUS Service 2 device access support
+//!
+//! The device access service allows direct access to devices by forwarding raw commands to
+//! the device handlers and routing the raw device replies back to ground. This is a common
+//! requirement for commissioning and on-ground testing.
+//!
+//! A TC[2,128] raw device command contains the unique target ID of the device handler as a big
+//! endian [u32], which can be extracted using [crate::request::UniqueApidTargetId::from_pus_tc],
+//! followed by the raw command data. The raw command is forwarded to the device handler as a
+//! [crate::device::RawDeviceCommand], and device handlers are expected to only accept raw
+//! commands in their RAW mode. Raw replies of the device are passed back as
+//! [crate::device::RawDeviceReply]s and can be sent as TM[2,130] raw device replies using the
+//! [PusRawReplyTmCreator]. The source data of these reports has the same format as the
+//! application data of the raw command. The reports are addressed to the source ID of the raw
+//! command, which is kept in the [ActivePusRawRequestStd].
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum DeviceAccessSubservice {
+    TcRawCommand = 128,
+    TmRawReply = 130,
+}
+
+/// Length of the unique target ID which precedes the raw data of raw commands and replies.
+pub const TARGET_ID_LEN: usize = 4;
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::{ByteConversionError, SpHeader, MAX_APID};
+
+    use super::*;
+    use crate::pus::{EcssTmSender, EcssTmtcError};
+    use crate::ComponentId;
+
+    /// Creates and sends TM[2,130] raw device reply reports.
+    pub struct PusRawReplyTmCreator {
+        id: ComponentId,
+        apid: u16,
+        source_data_buf: Vec<u8>,
+    }
+
+    impl PusRawReplyTmCreator {
+        /// Create a new TM creator. Returns [None] if the APID is invalid.
+        ///
+        /// ## Parameter
+        ///
+        /// * `id` - Component ID used to send the telemetry.
+        /// * `apid` - APID of the generated telemetry.
+        /// * `max_reply_len` - Maximum length of a raw device reply, excluding the target ID.
+        pub fn new(id: ComponentId, apid: u16, max_reply_len: usize) -> Option<Self> {
+            if apid > MAX_APID {
+                return None;
+            }
+            Some(Self {
+                id,
+                apid,
+                source_data_buf: vec![0; TARGET_ID_LEN + max_reply_len],
+            })
+        }
+
+        pub fn max_reply_len(&self) -> usize {
+            self.source_data_buf.len() - TARGET_ID_LEN
+        }
+
+        /// Send a TM[2,130] raw device reply report for the device handler with the given
+        /// unique target ID. The destination ID is usually the source ID of the raw command.
+        pub fn send_raw_reply(
+            &mut self,
+            unique_id: u32,
+            dest_id: u16,
+            raw_reply: &[u8],
+            time_stamp: &[u8],
+            sender: &(impl EcssTmSender + ?Sized),
+        ) -> Result<(), EcssTmtcError> {
+            if raw_reply.len() > self.max_reply_len() {
+                return Err(ByteConversionError::ToSliceTooSmall {
+                    found: self.max_reply_len(),
+                    expected: raw_reply.len(),
+                }
+                .into());
+            }
+            let source_data_len = TARGET_ID_LEN + raw_reply.len();
+            self.source_data_buf[0..TARGET_ID_LEN].copy_from_slice(&unique_id.to_be_bytes());
+            self.source_data_buf[TARGET_ID_LEN..source_data_len].copy_from_slice(raw_reply);
+            let sec_header = PusTmSecondaryHeader::new(
+                2,
+                DeviceAccessSubservice::TmRawReply.into(),
+                0,
+                dest_id,
+                time_stamp,
+            );
+            let tm_creator = PusTmCreator::new(
+                SpHeader::new_from_apid(self.apid),
+                sec_header,
+                &self.source_data_buf[0..source_data_len],
+                true,
+            );
+            sender.send_tm(self.id, tm_creator.into())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use crate::pus::verification::{self, TcStateToken};
+    use crate::pus::{ActivePusRequestStd, ActiveRequestProvider, DefaultActiveRequestMap};
+    use crate::ComponentId;
+
+    /// Active raw device request, which keeps the source ID of the raw command as the
+    /// destination ID of the raw device replies.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ActivePusRawRequestStd {
+        pub dest_id: u16,
+        common: ActivePusRequestStd,
+    }
+
+    impl ActiveRequestProvider for ActivePusRawRequestStd {
+        delegate::delegate! {
+            to self.common {
+                fn target_id(&self) -> ComponentId;
+                fn token(&self) -> verification::TcStateToken;
+                fn set_token(&mut self, token: verification::TcStateToken);
+                fn has_timed_out(&self) -> bool;
+                fn timeout(&self) -> core::time::Duration;
+            }
+        }
+    }
+
+    impl ActivePusRawRequestStd {
+        pub fn new_from_common_req(dest_id: u16, common: ActivePusRequestStd) -> Self {
+            Self { dest_id, common }
+        }
+
+        pub fn new(
+            dest_id: u16,
+            target_id: ComponentId,
+            token: TcStateToken,
+            timeout: core::time::Duration,
+        ) -> Self {
+            Self {
+                dest_id,
+                common: ActivePusRequestStd::new(target_id, token, timeout),
+            }
+        }
+    }
+
+    pub type DefaultActiveRawRequestMap = DefaultActiveRequestMap<ActivePusRawRequestStd>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use spacepackets::ecss::tm::{GenericPusTmSecondaryHeader, PusTmReader};
+    use spacepackets::ecss::PusPacket;
+
+    use super::*;
+    use crate::pus::EcssTmtcError;
+    use crate::tmtc::PacketAsVec;
+
+    const TEST_ID: u64 = 0x05;
+    const TEST_APID: u16 = 0x02;
+
+    #[test]
+    fn test_raw_reply() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let mut tm_creator = PusRawReplyTmCreator::new(TEST_ID, TEST_APID, 16).unwrap();
+        assert_eq!(tm_creator.max_reply_len(), 16);
+        tm_creator
+            .send_raw_reply(0x0102_0304, 0x42, &[1, 2, 3], &[0; 7], &tm_tx)
+            .unwrap();
+        let packet = tm_rx.try_recv().unwrap();
+        assert_eq!(packet.sender_id, TEST_ID);
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(tm.apid(), TEST_APID);
+        assert_eq!(tm.dest_id(), 0x42);
+        assert_eq!(tm.service(), 2);
+        assert_eq!(
+            tm.subservice(),
+            u8::from(DeviceAccessSubservice::TmRawReply)
+        );
+        assert_eq!(tm.user_data(), &[1, 2, 3, 4, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_raw_reply_too_large() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let mut tm_creator = PusRawReplyTmCreator::new(TEST_ID, TEST_APID, 2).unwrap();
+        let result = tm_creator.send_raw_reply(1, 0, &[1, 2, 3], &[0; 7], &tm_tx);
+        assert!(matches!(result, Err(EcssTmtcError::ByteConversion(_))));
+        assert!(tm_rx.try_recv().is_err());
+        assert!(PusRawReplyTmCreator::new(TEST_ID, 0xffff, 2).is_none());
+    }
+}
@@ -1,26 +1,87 @@
+use crate::apid::ApidHandle;
+use crate::events::Severity;
 use crate::pus::source_buffer_large_enough;
+use crate::ComponentId;
 use spacepackets::ecss::tm::PusTmCreator;
 use spacepackets::ecss::tm::PusTmSecondaryHeader;
 use spacepackets::ecss::EcssEnumeration;
 use spacepackets::ByteConversionError;
-use spacepackets::{SpHeader, MAX_APID};
+use spacepackets::SpHeader;
 
 #[cfg(feature = "alloc")]
 pub use alloc_mod::*;
 
 pub use spacepackets::ecss::event::*;
 
+/// Returns the event report subservice for events of the given severity.
+pub fn subservice_for_severity(severity: Severity) -> Subservice {
+    match severity {
+        Severity::Info => Subservice::TmInfoReport,
+        Severity::Low => Subservice::TmLowSeverityReport,
+        Severity::Medium => Subservice::TmMediumSeverityReport,
+        Severity::High => Subservice::TmHighSeverityReport,
+    }
+}
+
+/// APID and optional sender ID used for the TM of a group of events. This allows systems with
+/// multiple APIDs to report events under the APID of the subsystem which raised them, for
+/// example payload events under the payload APID.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EventTmRoute {
+    pub apid: u16,
+    /// Sender ID passed to the TM sender. The ID of the event reporter is used if this is [None].
+    pub sender_id: Option<ComponentId>,
+}
+
+impl EventTmRoute {
+    pub const fn new(apid: u16) -> Self {
+        Self {
+            apid,
+            sender_id: None,
+        }
+    }
+
+    pub const fn new_with_sender_id(apid: u16, sender_id: ComponentId) -> Self {
+        Self {
+            apid,
+            sender_id: Some(sender_id),
+        }
+    }
+}
+
 pub struct EventReportCreator {
-    apid: u16,
+    apid: ApidHandle,
     pub dest_id: u16,
 }
 
 impl EventReportCreator {
     pub fn new(apid: u16, dest_id: u16) -> Option<Self> {
-        if apid > MAX_APID {
-            return None;
+        Some(Self::new_with_apid_handle(
+            ApidHandle::new_fixed(apid)?,
+            dest_id,
+        ))
+    }
+
+    /// Create a report creator which reads the APID from the given handle, for example a
+    /// [crate::apid::SharedApid] which can be changed at run-time.
+    pub fn new_with_apid_handle(apid: impl Into<ApidHandle>, dest_id: u16) -> Self {
+        Self {
+            apid: apid.into(),
+            dest_id,
         }
-        Some(Self { dest_id, apid })
+    }
+
+    pub fn apid(&self) -> u16 {
+        self.apid.apid()
+    }
+
+    /// Set the APID. Please note that this changes the APID of all users of a shared APID.
+    pub fn set_apid(&mut self, apid: u16) -> bool {
+        self.apid.set_apid(apid)
+    }
+
+    pub fn set_apid_handle(&mut self, apid: impl Into<ApidHandle>) {
+        self.apid = apid.into();
     }
 
     pub fn event_info<'time, 'src_data>(
@@ -87,6 +148,67 @@ impl EventReportCreator {
         )
     }
 
+    /// Generate an event report with the given subservice, APID and message counter instead of
+    /// the APID of the report creator and a message counter of 0.
+    #[allow(clippy::too_many_arguments)]
+    pub fn event_report_with_apid<'time, 'src_data>(
+        &self,
+        subservice: Subservice,
+        apid: u16,
+        msg_counter: u16,
+        time_stamp: &'time [u8],
+        event_id: impl EcssEnumeration,
+        params: Option<&'src_data [u8]>,
+        src_data_buf: &'src_data mut [u8],
+    ) -> Result<PusTmCreator<'time, 'src_data>, ByteConversionError> {
+        self.generate_generic_event_tm(
+            subservice,
+            apid,
+            msg_counter,
+            time_stamp,
+            event_id,
+            params,
+            src_data_buf,
+        )
+    }
+
+    /// Generate a [Subservice::TmDisabledEventsReport] packet. The source data contains the
+    /// number of events as a [u16], followed by the events.
+    pub fn disabled_events_report<'time, 'src_data, Event: EcssEnumeration>(
+        &self,
+        time_stamp: &'time [u8],
+        events: &[Event],
+        src_data_buf: &'src_data mut [u8],
+    ) -> Result<PusTmCreator<'time, 'src_data>, ByteConversionError> {
+        let src_data_len = 2 + events.iter().map(|event| event.size()).sum::<usize>();
+        source_buffer_large_enough(src_data_buf.len(), src_data_len)?;
+        // The number of events is limited by the size of the count field.
+        let num_events =
+            u16::try_from(events.len()).map_err(|_| ByteConversionError::ToSliceTooSmall {
+                found: src_data_buf.len(),
+                expected: src_data_len,
+            })?;
+        src_data_buf[0..2].copy_from_slice(&num_events.to_be_bytes());
+        let mut current_idx = 2;
+        for event in events {
+            event.write_to_be_bytes(&mut src_data_buf[current_idx..current_idx + event.size()])?;
+            current_idx += event.size();
+        }
+        let sec_header = PusTmSecondaryHeader::new(
+            5,
+            Subservice::TmDisabledEventsReport.into(),
+            0,
+            self.dest_id,
+            time_stamp,
+        );
+        Ok(PusTmCreator::new(
+            SpHeader::new_from_apid(self.apid()),
+            sec_header,
+            &src_data_buf[0..current_idx],
+            true,
+        ))
+    }
+
     fn generate_and_send_generic_tm<'time, 'src_data>(
         &self,
         subservice: Subservice,
@@ -95,12 +217,23 @@ impl EventReportCreator {
         params: Option<&'src_data [u8]>,
         src_data_buf: &'src_data mut [u8],
     ) -> Result<PusTmCreator<'time, 'src_data>, ByteConversionError> {
-        self.generate_generic_event_tm(subservice, time_stamp, event_id, params, src_data_buf)
+        self.generate_generic_event_tm(
+            subservice,
+            self.apid(),
+            0,
+            time_stamp,
+            event_id,
+            params,
+            src_data_buf,
+        )
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn generate_generic_event_tm<'time, 'src_data>(
         &self,
         subservice: Subservice,
+        apid: u16,
+        msg_counter: u16,
         time_stamp: &'time [u8],
         event_id: impl EcssEnumeration,
         params: Option<&'src_data [u8]>,
@@ -112,7 +245,7 @@ impl EventReportCreator {
         }
         source_buffer_large_enough(src_data_buf.len(), src_data_len)?;
         let sec_header =
-            PusTmSecondaryHeader::new(5, subservice.into(), 0, self.dest_id, time_stamp);
+            PusTmSecondaryHeader::new(5, subservice.into(), msg_counter, self.dest_id, time_stamp);
         let mut current_idx = 0;
         event_id.write_to_be_bytes(&mut src_data_buf[0..event_id.size()])?;
         current_idx += event_id.size();
@@ -121,7 +254,7 @@ impl EventReportCreator {
             current_idx += aux_data.len();
         }
         Ok(PusTmCreator::new(
-            SpHeader::new_from_apid(self.apid),
+            SpHeader::new_from_apid(apid),
             sec_header,
             &src_data_buf[0..current_idx],
             true,
@@ -133,7 +266,6 @@ impl EventReportCreator {
 mod alloc_mod {
     use super::*;
     use crate::pus::{EcssTmSender, EcssTmtcError};
-    use crate::ComponentId;
     use alloc::vec;
     use alloc::vec::Vec;
     use core::cell::RefCell;
@@ -259,12 +391,91 @@ mod alloc_mod {
             sender.send_tm(self.id, tm_creator.into())?;
             Ok(())
         }
+
+        /// Send an event report with the given subservice using the APID and sender ID of the
+        /// given [EventTmRoute] and the given message counter.
+        #[allow(clippy::too_many_arguments)]
+        pub fn routed_event_report(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            subservice: Subservice,
+            route: &EventTmRoute,
+            msg_counter: u16,
+            time_stamp: &[u8],
+            event_id: impl EcssEnumeration,
+            params: Option<&[u8]>,
+        ) -> Result<(), EcssTmtcError> {
+            let mut mut_buf = self.source_data_buf.borrow_mut();
+            let mut tm_creator = self
+                .report_creator
+                .event_report_with_apid(
+                    subservice,
+                    route.apid,
+                    msg_counter,
+                    time_stamp,
+                    event_id,
+                    params,
+                    mut_buf.as_mut_slice(),
+                )
+                .map_err(PusError::ByteConversion)?;
+            self.tm_hook.modify_tm(&mut tm_creator);
+            sender.send_tm(route.sender_id.unwrap_or(self.id), tm_creator.into())?;
+            Ok(())
+        }
+
+        /// Send the given list of disabled events as [Subservice::TmDisabledEventsReport]
+        /// packets. If the list does not fit into a single packet, it is split across multiple
+        /// packets. At least one packet is sent, even if the list is empty. Returns the number of
+        /// sent packets.
+        pub fn disabled_events_report<Event: EcssEnumeration>(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            events: &[Event],
+        ) -> Result<u32, EcssTmtcError> {
+            let mut mut_buf = self.source_data_buf.borrow_mut();
+            let event_size = events.first().map(|event| event.size()).unwrap_or(0);
+            let events_per_packet = if event_size == 0 {
+                usize::MAX
+            } else {
+                (mut_buf.len().saturating_sub(2) / event_size).min(u16::MAX as usize)
+            };
+            if events_per_packet == 0 {
+                return Err(
+                    PusError::ByteConversion(ByteConversionError::ToSliceTooSmall {
+                        found: mut_buf.len(),
+                        expected: 2 + event_size,
+                    })
+                    .into(),
+                );
+            }
+            let mut send_report = |events: &[Event]| -> Result<(), EcssTmtcError> {
+                let mut tm_creator = self
+                    .report_creator
+                    .disabled_events_report(time_stamp, events, mut_buf.as_mut_slice())
+                    .map_err(PusError::ByteConversion)?;
+                self.tm_hook.modify_tm(&mut tm_creator);
+                sender.send_tm(self.id, tm_creator.into())?;
+                Ok(())
+            };
+            if events.is_empty() {
+                send_report(events)?;
+                return Ok(1);
+            }
+            let mut num_packets = 0;
+            for chunk in events.chunks(events_per_packet) {
+                send_report(chunk)?;
+                num_packets += 1;
+            }
+            Ok(num_packets)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::apid::SharedApid;
     use crate::events::{EventU32, Severity};
     use crate::pus::test_util::TEST_COMPONENT_ID_0;
     use crate::pus::tests::CommonTmInfo;
@@ -470,6 +681,142 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
+    struct DisabledReportSender {
+        pub reports: RefCell<VecDeque<(CommonTmInfo, Vec<u8>)>>,
+    }
+
+    impl ChannelWithId for DisabledReportSender {
+        fn id(&self) -> ComponentId {
+            0
+        }
+    }
+
+    impl EcssTmSender for DisabledReportSender {
+        fn send_tm(&self, _: ComponentId, tm: PusTmVariant) -> Result<(), EcssTmtcError> {
+            if let PusTmVariant::Direct(tm) = tm {
+                self.reports
+                    .borrow_mut()
+                    .push_back((CommonTmInfo::new_from_tm(&tm), tm.source_data().to_vec()));
+                return Ok(());
+            }
+            panic!("DisabledReportSender: unexpected call with address");
+        }
+    }
+
+    fn disabled_events(num_events: u16) -> Vec<EventU32> {
+        (0..num_events)
+            .map(|unique_id| EventU32::new(Severity::Low, EXAMPLE_GROUP_ID, unique_id))
+            .collect()
+    }
+
+    #[test]
+    fn disabled_events_report() {
+        let sender = DisabledReportSender::default();
+        let reporter = EventReporter::new(TEST_COMPONENT_ID_0.id(), EXAMPLE_APID, 0, 32).unwrap();
+        let time_stamp_empty: [u8; 7] = [0; 7];
+        let events = disabled_events(3);
+        let num_packets = reporter
+            .disabled_events_report(&sender, &time_stamp_empty, &events)
+            .unwrap();
+        assert_eq!(num_packets, 1);
+        let (tm_info, src_data) = sender.reports.borrow_mut().pop_front().unwrap();
+        assert_eq!(tm_info.subservice, Subservice::TmDisabledEventsReport as u8);
+        assert_eq!(src_data.len(), 2 + 3 * 4);
+        assert_eq!(u16::from_be_bytes(src_data[0..2].try_into().unwrap()), 3);
+        for (idx, event) in events.iter().enumerate() {
+            let start = 2 + idx * 4;
+            assert_eq!(
+                EventU32::from(u32::from_be_bytes(
+                    src_data[start..start + 4].try_into().unwrap()
+                )),
+                *event
+            );
+        }
+    }
+
+    #[test]
+    fn empty_disabled_events_report() {
+        let sender = DisabledReportSender::default();
+        let reporter = EventReporter::new(TEST_COMPONENT_ID_0.id(), EXAMPLE_APID, 0, 32).unwrap();
+        let num_packets = reporter
+            .disabled_events_report::<EventU32>(&sender, &[0; 7], &[])
+            .unwrap();
+        assert_eq!(num_packets, 1);
+        let (_, src_data) = sender.reports.borrow_mut().pop_front().unwrap();
+        assert_eq!(src_data, [0, 0]);
+    }
+
+    #[test]
+    fn split_disabled_events_report() {
+        let sender = DisabledReportSender::default();
+        // Space for 3 events per packet.
+        let reporter = EventReporter::new(TEST_COMPONENT_ID_0.id(), EXAMPLE_APID, 0, 15).unwrap();
+        let events = disabled_events(7);
+        let num_packets = reporter
+            .disabled_events_report(&sender, &[0; 7], &events)
+            .unwrap();
+        assert_eq!(num_packets, 3);
+        let reports = sender.reports.borrow();
+        let num_events_per_report: Vec<u16> = reports
+            .iter()
+            .map(|(_, src_data)| u16::from_be_bytes(src_data[0..2].try_into().unwrap()))
+            .collect();
+        assert_eq!(num_events_per_report, [3, 3, 1]);
+        let last_event = &reports[2].1[2..6];
+        assert_eq!(
+            EventU32::from(u32::from_be_bytes(last_event.try_into().unwrap())),
+            events[6]
+        );
+    }
+
+    #[test]
+    fn disabled_events_report_buffer_too_small() {
+        let sender = DisabledReportSender::default();
+        let reporter = EventReporter::new(TEST_COMPONENT_ID_0.id(), EXAMPLE_APID, 0, 5).unwrap();
+        let result = reporter.disabled_events_report(&sender, &[0; 7], &disabled_events(1));
+        assert!(matches!(
+            result,
+            Err(EcssTmtcError::Pus(PusError::ByteConversion(
+                ByteConversionError::ToSliceTooSmall {
+                    found: 5,
+                    expected: 6
+                }
+            )))
+        ));
+    }
+
+    #[test]
+    fn event_with_shared_apid() {
+        let mut sender = TestSender::default();
+        let shared_apid = SharedApid::new(EXAMPLE_APID).unwrap();
+        let mut reporter = EventReporter::new(TEST_COMPONENT_ID_0.id(), 0, 0, 4).unwrap();
+        reporter.report_creator.set_apid_handle(shared_apid.clone());
+        let event = EventU32::new_checked(Severity::Info, EXAMPLE_GROUP_ID, EXAMPLE_EVENT_ID_0)
+            .expect("Error creating example event");
+        let time_stamp_empty: [u8; 7] = [0; 7];
+        report_basic_event(
+            &mut reporter,
+            &mut sender,
+            &time_stamp_empty,
+            event,
+            Severity::Info,
+            None,
+        );
+        assert!(shared_apid.set(0x22));
+        report_basic_event(
+            &mut reporter,
+            &mut sender,
+            &time_stamp_empty,
+            event,
+            Severity::Info,
+            None,
+        );
+        let mut service_queue = sender.service_queue.borrow_mut();
+        assert_eq!(service_queue.pop_front().unwrap().common.apid, EXAMPLE_APID);
+        assert_eq!(service_queue.pop_front().unwrap().common.apid, 0x22);
+    }
+
     #[test]
     fn insufficient_buffer() {
         let mut sender = TestSender::default();
@@ -0,0 +1,1057 @@
+//! # PUS 19 event-action service
+//!
+//! This module provides the components for a PUS service 19 style event-action service. Ground
+//! can link stored telecommands to event IDs. When the event manager routes a matching event to
+//! the event-action component, the linked telecommand is released into the TC source.
+//!
+//! The [EventActionTable] is the central component. It maps event IDs to action telecommands
+//! which are kept inside a [PoolProvider]. Each event-action definition can be enabled or
+//! disabled separately. Newly added definitions are disabled and need to be enabled explicitly.
+//! Additionally, the whole event-action function can be enabled or disabled.
+//!
+//! The [std] [PusEventActionServiceHandler] handles the PUS 19 telecommands to manage the table.
+//! It is also able to receive the events routed by the [crate::event_man::EventManager] and to
+//! release the action telecommands of all matching event-action definitions with the
+//! [PusEventActionServiceHandler::handle_events] method.
+use crate::events::{EventU32, GenericEvent};
+use crate::pool::{PoolAddr, PoolError, PoolProvider};
+use crate::tmtc::PacketSenderRaw;
+use crate::ComponentId;
+use alloc::collections::BTreeMap;
+use core::fmt::{Display, Formatter};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use spacepackets::ecss::tc::PusTcReader;
+use spacepackets::ecss::{PusError, PusPacket};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// PUS service number of the event-action service.
+pub const EVENT_ACTION_SERVICE: u8 = 19;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum EventActionSubservice {
+    TcAddEventActions = 1,
+    TcDeleteEventActions = 2,
+    TcDeleteAllEventActions = 3,
+    TcEnableEventActions = 4,
+    TcDisableEventActions = 5,
+    TcReportEventActionStatus = 6,
+    TmEventActionStatusReport = 7,
+    TcEnableEventActionFunction = 8,
+    TcDisableEventActionFunction = 9,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventActionError {
+    PusError(PusError),
+    StoreError(PoolError),
+    /// Event-action telecommands can not be used as actions.
+    NestedEventActionTc,
+    /// An enabled event-action definition already exists for the event.
+    AlreadyDefined(EventU32),
+    /// No event-action definition exists for the event.
+    UnknownEvent(EventU32),
+    /// The event-action definition for the event is enabled and can not be deleted.
+    ActionEnabled(EventU32),
+    /// The maximum number of event-action definitions was reached.
+    TableFull,
+}
+
+impl Display for EventActionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EventActionError::PusError(e) => write!(f, "event action: {e}"),
+            EventActionError::StoreError(e) => write!(f, "event action: {e}"),
+            EventActionError::NestedEventActionTc => {
+                write!(f, "event action: nested event action TCs are not allowed")
+            }
+            EventActionError::AlreadyDefined(event) => {
+                write!(
+                    f,
+                    "event action: enabled action already defined for {event:?}"
+                )
+            }
+            EventActionError::UnknownEvent(event) => {
+                write!(f, "event action: no action defined for {event:?}")
+            }
+            EventActionError::ActionEnabled(event) => {
+                write!(f, "event action: action for {event:?} is enabled")
+            }
+            EventActionError::TableFull => write!(f, "event action: table is full"),
+        }
+    }
+}
+
+impl From<PusError> for EventActionError {
+    fn from(value: PusError) -> Self {
+        Self::PusError(value)
+    }
+}
+
+impl From<PoolError> for EventActionError {
+    fn from(value: PoolError) -> Self {
+        Self::StoreError(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for EventActionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EventActionError::PusError(e) => Some(e),
+            EventActionError::StoreError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Error which can occur when releasing an action telecommand.
+#[derive(Debug)]
+pub enum ActionReleaseError<SendError> {
+    StoreError(PoolError),
+    Send(SendError),
+}
+
+impl<SendError: Display> Display for ActionReleaseError<SendError> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ActionReleaseError::StoreError(e) => write!(f, "reading action TC failed: {e}"),
+            ActionReleaseError::Send(e) => write!(f, "sending action TC failed: {e}"),
+        }
+    }
+}
+
+impl<SendError> From<PoolError> for ActionReleaseError<SendError> {
+    fn from(value: PoolError) -> Self {
+        Self::StoreError(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<SendError: Error + 'static> Error for ActionReleaseError<SendError> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ActionReleaseError::StoreError(e) => Some(e),
+            ActionReleaseError::Send(e) => Some(e),
+        }
+    }
+}
+
+/// Single event-action definition of the [EventActionTable].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EventActionEntry {
+    tc_addr: PoolAddr,
+    enabled: bool,
+}
+
+impl EventActionEntry {
+    /// Address of the action telecommand inside the action TC pool.
+    pub fn tc_addr(&self) -> PoolAddr {
+        self.tc_addr
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// Table of event-action definitions. See the [module documentation][self] for more details.
+///
+/// The action telecommands are stored inside a user provided [PoolProvider] which has to be
+/// passed to all methods which add, delete or release action telecommands.
+#[derive(Debug, Clone)]
+pub struct EventActionTable {
+    actions: BTreeMap<u32, EventActionEntry>,
+    max_num_actions: Option<usize>,
+    function_enabled: bool,
+}
+
+impl Default for EventActionTable {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl EventActionTable {
+    /// Create a new table with the event-action function enabled.
+    ///
+    /// ## Parameter
+    ///
+    /// * `max_num_actions` - Optional maximum number of event-action definitions.
+    pub fn new(max_num_actions: Option<usize>) -> Self {
+        Self {
+            actions: BTreeMap::new(),
+            max_num_actions,
+            function_enabled: true,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn entry(&self, event: EventU32) -> Option<&EventActionEntry> {
+        self.actions.get(&event.raw())
+    }
+
+    /// Iterate over all event-action definitions, sorted by the raw event ID.
+    pub fn iter(&self) -> impl Iterator<Item = (EventU32, &EventActionEntry)> {
+        self.actions
+            .iter()
+            .map(|(raw_event, entry)| (EventU32::from(*raw_event), entry))
+    }
+
+    pub fn is_function_enabled(&self) -> bool {
+        self.function_enabled
+    }
+
+    /// Enable the event-action function. Action telecommands are only released while the
+    /// function is enabled.
+    pub fn enable_function(&mut self) {
+        self.function_enabled = true;
+    }
+
+    pub fn disable_function(&mut self) {
+        self.function_enabled = false;
+    }
+
+    /// Add an event-action definition. The new definition is disabled.
+    ///
+    /// The raw telecommand is checked and stored inside the pool. A disabled definition for the
+    /// same event is replaced and its telecommand is deleted from the pool. Telecommands of the
+    /// event-action service itself are rejected to avoid recursive action definitions. The table
+    /// is only modified if all pool operations succeeded.
+    pub fn add(
+        &mut self,
+        event: EventU32,
+        tc: &[u8],
+        pool: &mut (impl PoolProvider + ?Sized),
+    ) -> Result<(), EventActionError> {
+        let (check_tc, tc_len) = PusTcReader::new(tc)?;
+        if PusPacket::service(&check_tc) == EVENT_ACTION_SERVICE {
+            return Err(EventActionError::NestedEventActionTc);
+        }
+        match self.actions.get(&event.raw()) {
+            Some(entry) if entry.enabled => return Err(EventActionError::AlreadyDefined(event)),
+            Some(_) => (),
+            None => {
+                if let Some(max_num_actions) = self.max_num_actions {
+                    if self.actions.len() >= max_num_actions {
+                        return Err(EventActionError::TableFull);
+                    }
+                }
+            }
+        }
+        let tc_addr = pool.add(&tc[0..tc_len])?;
+        if let Some(replaced) = self.actions.get(&event.raw()) {
+            if let Err(e) = pool.delete(replaced.tc_addr) {
+                // Keep the replaced definition and do not leak the new telecommand.
+                let _ = pool.delete(tc_addr);
+                return Err(e.into());
+            }
+        }
+        self.actions.insert(
+            event.raw(),
+            EventActionEntry {
+                tc_addr,
+                enabled: false,
+            },
+        );
+        Ok(())
+    }
+
+    /// Delete an event-action definition and its telecommand. Enabled definitions can not be
+    /// deleted.
+    pub fn remove(
+        &mut self,
+        event: EventU32,
+        pool: &mut (impl PoolProvider + ?Sized),
+    ) -> Result<(), EventActionError> {
+        match self.actions.get(&event.raw()) {
+            Some(entry) if entry.enabled => Err(EventActionError::ActionEnabled(event)),
+            Some(entry) => {
+                pool.delete(entry.tc_addr)?;
+                self.actions.remove(&event.raw());
+                Ok(())
+            }
+            None => Err(EventActionError::UnknownEvent(event)),
+        }
+    }
+
+    /// Delete all event-action definitions, including the enabled ones. All definitions are
+    /// removed from the table even if deleting a telecommand from the pool fails. In that case,
+    /// the first pool error is returned.
+    pub fn remove_all(
+        &mut self,
+        pool: &mut (impl PoolProvider + ?Sized),
+    ) -> Result<(), EventActionError> {
+        let mut result = Ok(());
+        for entry in core::mem::take(&mut self.actions).values() {
+            if let Err(e) = pool.delete(entry.tc_addr) {
+                if result.is_ok() {
+                    result = Err(e.into());
+                }
+            }
+        }
+        result
+    }
+
+    pub fn enable(&mut self, event: EventU32) -> Result<(), EventActionError> {
+        self.set_enabled(event, true)
+    }
+
+    pub fn disable(&mut self, event: EventU32) -> Result<(), EventActionError> {
+        self.set_enabled(event, false)
+    }
+
+    fn set_enabled(&mut self, event: EventU32, enabled: bool) -> Result<(), EventActionError> {
+        match self.actions.get_mut(&event.raw()) {
+            Some(entry) => {
+                entry.enabled = enabled;
+                Ok(())
+            }
+            None => Err(EventActionError::UnknownEvent(event)),
+        }
+    }
+
+    /// Release the action telecommand linked to the event, if the event-action function and
+    /// the definition for the event are enabled.
+    ///
+    /// The releaser closure is called with the raw action telecommand. The telecommand remains
+    /// inside the pool so it can be released again. Returns whether a telecommand was released.
+    pub fn release_action<R: FnOnce(&[u8])>(
+        &self,
+        event: EventU32,
+        pool: &(impl PoolProvider + ?Sized),
+        releaser: R,
+    ) -> Result<bool, PoolError> {
+        if !self.function_enabled {
+            return Ok(false);
+        }
+        match self.actions.get(&event.raw()) {
+            Some(entry) if entry.enabled => {
+                let tc = pool.read_as_vec(&entry.tc_addr)?;
+                releaser(&tc);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Variant of [Self::release_action] which sends the action telecommand to a TC source.
+    ///
+    /// ## Parameter
+    ///
+    /// * `sender_id` - Sender ID of the released telecommand.
+    /// * `event` - Event which was routed to the event-action component.
+    /// * `pool` - Pool containing the action telecommands.
+    /// * `tc_sender` - Sender which injects the telecommand into the TC source.
+    pub fn send_action<Sender: PacketSenderRaw + ?Sized>(
+        &self,
+        sender_id: ComponentId,
+        event: EventU32,
+        pool: &(impl PoolProvider + ?Sized),
+        tc_sender: &Sender,
+    ) -> Result<bool, ActionReleaseError<Sender::Error>> {
+        let mut send_result = Ok(());
+        let released = self.release_action(event, pool, |tc| {
+            send_result = tc_sender.send_packet(sender_id, tc);
+        })?;
+        send_result.map_err(ActionReleaseError::Send)?;
+        Ok(released)
+    }
+}
+
+/// Result codes used by the [PusEventActionServiceHandler] for verification failure reports.
+/// The group ID of these codes is the PUS service number 19.
+pub mod event_action_res_code {
+    use satrs_shared::res_code::ResultU16;
+
+    /// No event-action definition exists for at least one event.
+    pub const UNKNOWN_EVENT_ACTION: ResultU16 = ResultU16::new(19, 0);
+    /// An enabled event-action definition already exists for at least one event.
+    pub const EVENT_ACTION_ALREADY_DEFINED: ResultU16 = ResultU16::new(19, 1);
+    /// At least one enabled event-action definition could not be deleted.
+    pub const EVENT_ACTION_ENABLED: ResultU16 = ResultU16::new(19, 2);
+    /// At least one action telecommand was invalid or an event-action telecommand itself.
+    pub const INVALID_ACTION_TC: ResultU16 = ResultU16::new(19, 3);
+    /// Storing or deleting at least one action telecommand in the TC pool failed, or the
+    /// event-action table is full.
+    pub const TC_POOL_ERROR: ResultU16 = ResultU16::new(19, 4);
+    /// Generating or sending the event-action status report failed.
+    pub const REPORT_GENERATION_FAILED: ResultU16 = ResultU16::new(19, 5);
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use super::*;
+    use crate::event_man::EventReceiveProvider;
+    use crate::pus::harness::{PusServiceContext, PusServiceCore};
+    use crate::pus::verification::{
+        TcStateAccepted, TcStateStarted, VerificationReporter, VerificationReportingProvider,
+        VerificationToken,
+    };
+    use crate::pus::{
+        DirectPusPacketHandlerResult, EcssTcInMemConverter, EcssTcInSharedStoreConverter,
+        EcssTcInVecConverter, EcssTcReceiver, EcssTmSender, GenericConversionError, HandlingStatus,
+        MpscTcReceiver, PartialPusHandlingError, PusPacketHandlingError, PusServiceHelper,
+        PusTmVariant,
+    };
+    use crate::queue::GenericReceiveError;
+    use crate::tmtc::{PacketAsVec, PacketSenderWithSharedPool};
+    use core::fmt::Debug;
+    use satrs_shared::res_code::ResultU16;
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::SpHeader;
+    use std::string::ToString;
+    use std::sync::mpsc;
+    use std::vec::Vec;
+
+    /// Error which can occur when handling the events routed to the event-action component.
+    #[derive(Debug)]
+    pub enum EventActionHandlingError<SendError> {
+        Receive(GenericReceiveError),
+        Release(ActionReleaseError<SendError>),
+    }
+
+    impl<SendError: Display> Display for EventActionHandlingError<SendError> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                EventActionHandlingError::Receive(e) => write!(f, "receiving event failed: {e}"),
+                EventActionHandlingError::Release(e) => write!(f, "{e}"),
+            }
+        }
+    }
+
+    impl<SendError> From<ActionReleaseError<SendError>> for EventActionHandlingError<SendError> {
+        fn from(value: ActionReleaseError<SendError>) -> Self {
+            Self::Release(value)
+        }
+    }
+
+    impl<SendError: Error + 'static> Error for EventActionHandlingError<SendError> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                EventActionHandlingError::Receive(e) => Some(e),
+                EventActionHandlingError::Release(e) => Some(e),
+            }
+        }
+    }
+
+    /// Read the event list consisting of the N field as a [u16] followed by N raw events, each
+    /// serialized as a big-endian [u32].
+    fn events_from_app_data(
+        app_data: &[u8],
+    ) -> Result<impl Iterator<Item = EventU32> + '_, GenericConversionError> {
+        if app_data.len() < 2 {
+            return Err(GenericConversionError::NotEnoughAppData {
+                expected: 2,
+                found: app_data.len(),
+            });
+        }
+        let num_events = u16::from_be_bytes(app_data[0..2].try_into().unwrap()) as usize;
+        let expected_len = 2 + num_events * 4;
+        if app_data.len() < expected_len {
+            return Err(GenericConversionError::NotEnoughAppData {
+                expected: expected_len,
+                found: app_data.len(),
+            });
+        }
+        Ok(app_data[2..expected_len]
+            .chunks_exact(4)
+            .map(|raw_event| EventU32::from(u32::from_be_bytes(raw_event.try_into().unwrap()))))
+    }
+
+    /// Read the event-action definition list consisting of the N field as a [u16] followed by
+    /// N definitions. Each definition is a big-endian raw [u32] event followed by the raw
+    /// action telecommand.
+    fn definitions_from_app_data(
+        app_data: &[u8],
+    ) -> Result<Vec<(EventU32, &[u8])>, GenericConversionError> {
+        if app_data.len() < 2 {
+            return Err(GenericConversionError::NotEnoughAppData {
+                expected: 2,
+                found: app_data.len(),
+            });
+        }
+        let num_definitions = u16::from_be_bytes(app_data[0..2].try_into().unwrap()) as usize;
+        let mut definitions = Vec::with_capacity(num_definitions);
+        let mut current_idx = 2;
+        for _ in 0..num_definitions {
+            if app_data.len() < current_idx + 4 {
+                return Err(GenericConversionError::NotEnoughAppData {
+                    expected: current_idx + 4,
+                    found: app_data.len(),
+                });
+            }
+            let event = EventU32::from(u32::from_be_bytes(
+                app_data[current_idx..current_idx + 4].try_into().unwrap(),
+            ));
+            current_idx += 4;
+            let (_, tc_len) = PusTcReader::new(&app_data[current_idx..])
+                .map_err(|e| GenericConversionError::InvalidAppData(e.to_string()))?;
+            definitions.push((event, &app_data[current_idx..current_idx + tc_len]));
+            current_idx += tc_len;
+        }
+        Ok(definitions)
+    }
+
+    fn failure_code(error: &EventActionError) -> ResultU16 {
+        match error {
+            EventActionError::UnknownEvent(_) => event_action_res_code::UNKNOWN_EVENT_ACTION,
+            EventActionError::AlreadyDefined(_) => {
+                event_action_res_code::EVENT_ACTION_ALREADY_DEFINED
+            }
+            EventActionError::ActionEnabled(_) => event_action_res_code::EVENT_ACTION_ENABLED,
+            EventActionError::PusError(_) | EventActionError::NestedEventActionTc => {
+                event_action_res_code::INVALID_ACTION_TC
+            }
+            EventActionError::StoreError(_) | EventActionError::TableFull => {
+                event_action_res_code::TC_POOL_ERROR
+            }
+        }
+    }
+
+    /// This is a helper class for [std] environments to handle PUS 19 (event-action service)
+    /// packets and to release the action telecommands of routed events.
+    ///
+    /// The handler owns the [EventActionTable]. The action telecommands are stored inside a
+    /// pool which is passed to [Self::poll_and_handle_next_tc] and [Self::handle_events].
+    pub struct PusEventActionServiceHandler<
+        TcReceiver: EcssTcReceiver,
+        TmSender: EcssTmSender,
+        TcInMemConverter: EcssTcInMemConverter,
+        VerificationReporter: VerificationReportingProvider,
+    > {
+        pub service_helper:
+            PusServiceHelper<TcReceiver, TmSender, TcInMemConverter, VerificationReporter>,
+        table: EventActionTable,
+    }
+
+    impl<
+            TcReceiver: EcssTcReceiver,
+            TmSender: EcssTmSender,
+            TcInMemConverter: EcssTcInMemConverter,
+            VerificationReporter: VerificationReportingProvider,
+        >
+        PusEventActionServiceHandler<TcReceiver, TmSender, TcInMemConverter, VerificationReporter>
+    {
+        pub fn new(
+            service_helper: PusServiceHelper<
+                TcReceiver,
+                TmSender,
+                TcInMemConverter,
+                VerificationReporter,
+            >,
+            table: EventActionTable,
+        ) -> Self {
+            Self {
+                service_helper,
+                table,
+            }
+        }
+
+        pub fn table(&self) -> &EventActionTable {
+            &self.table
+        }
+
+        pub fn table_mut(&mut self) -> &mut EventActionTable {
+            &mut self.table
+        }
+
+        pub fn poll_and_handle_next_tc<ErrorCb: FnMut(&PartialPusHandlingError)>(
+            &mut self,
+            mut error_callback: ErrorCb,
+            time_stamp: &[u8],
+            action_tc_pool: &mut (impl PoolProvider + ?Sized),
+        ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
+            let mut core = PusEventActionServiceCore {
+                table: &mut self.table,
+                action_tc_pool,
+            };
+            self.service_helper.poll_and_handle_next_tc_with_core(
+                &mut core,
+                &mut error_callback,
+                time_stamp,
+            )
+        }
+
+        /// Handle all events routed to the event-action component and send the action
+        /// telecommands of the matching event-action definitions to the TC source. The
+        /// component ID of the service helper is used as the sender ID of the released
+        /// telecommands.
+        ///
+        /// The event receiver is usually the receiving end of the event sender which was
+        /// registered at the [crate::event_man::EventManager] for all events which should
+        /// trigger actions. Returns the number of released telecommands.
+        pub fn handle_events<
+            ParamProvider: Debug,
+            EventReceiver: EventReceiveProvider<EventU32, ParamProvider, Error = GenericReceiveError>,
+            TcSender: PacketSenderRaw + ?Sized,
+        >(
+            &self,
+            event_receiver: &EventReceiver,
+            action_tc_pool: &(impl PoolProvider + ?Sized),
+            tc_sender: &TcSender,
+        ) -> Result<u32, EventActionHandlingError<TcSender::Error>> {
+            let mut num_released = 0;
+            while let Some(event_msg) = event_receiver
+                .try_recv_event()
+                .map_err(EventActionHandlingError::Receive)?
+            {
+                if self.table.send_action(
+                    self.service_helper.id(),
+                    event_msg.event(),
+                    action_tc_pool,
+                    tc_sender,
+                )? {
+                    num_released += 1;
+                }
+            }
+            Ok(num_released)
+        }
+    }
+
+    /// [PusServiceCore] of the PUS 19 event-action service. It is created by the
+    /// [PusEventActionServiceHandler] for each handled telecommand because it borrows the
+    /// event-action table and the action TC pool.
+    pub struct PusEventActionServiceCore<'a, Pool: PoolProvider + ?Sized> {
+        pub table: &'a mut EventActionTable,
+        pub action_tc_pool: &'a mut Pool,
+    }
+
+    impl<
+            TmSender: EcssTmSender,
+            VerificationReporter: VerificationReportingProvider,
+            Pool: PoolProvider + ?Sized,
+        > PusServiceCore<TmSender, VerificationReporter> for PusEventActionServiceCore<'_, Pool>
+    {
+        fn service(&self) -> u8 {
+            EVENT_ACTION_SERVICE
+        }
+
+        fn check_subservice(&self, subservice: u8) -> bool {
+            EventActionSubservice::try_from(subservice).is_ok()
+        }
+
+        fn handle_request(
+            &mut self,
+            ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+            tc: &PusTcReader,
+            token: VerificationToken<TcStateAccepted>,
+            error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+        ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
+            let subservice = PusPacket::subservice(tc);
+            let srv = match EventActionSubservice::try_from(subservice) {
+                Ok(srv) => srv,
+                Err(_) => {
+                    return Ok(DirectPusPacketHandlerResult::CustomSubservice(
+                        subservice, token,
+                    ))
+                }
+            };
+            match srv {
+                EventActionSubservice::TcAddEventActions => {
+                    let definitions = definitions_from_app_data(tc.user_data())?;
+                    let opt_started_token = ctx.start_success(token, error_callback);
+                    let mut results = Vec::with_capacity(definitions.len());
+                    for (event, action_tc) in definitions {
+                        results.push(self.table.add(event, action_tc, self.action_tc_pool));
+                    }
+                    Self::report_completion(ctx, opt_started_token, results, error_callback);
+                }
+                EventActionSubservice::TcDeleteEventActions
+                | EventActionSubservice::TcEnableEventActions
+                | EventActionSubservice::TcDisableEventActions => {
+                    let events = events_from_app_data(tc.user_data())?;
+                    let opt_started_token = ctx.start_success(token, error_callback);
+                    let results = events
+                        .map(|event| match srv {
+                            EventActionSubservice::TcDeleteEventActions => {
+                                self.table.remove(event, self.action_tc_pool)
+                            }
+                            EventActionSubservice::TcEnableEventActions => self.table.enable(event),
+                            _ => self.table.disable(event),
+                        })
+                        .collect();
+                    Self::report_completion(ctx, opt_started_token, results, error_callback);
+                }
+                EventActionSubservice::TcDeleteAllEventActions => {
+                    let opt_started_token = ctx.start_success(token, error_callback);
+                    let result = self.table.remove_all(self.action_tc_pool);
+                    Self::report_completion(
+                        ctx,
+                        opt_started_token,
+                        std::vec![result],
+                        error_callback,
+                    );
+                }
+                EventActionSubservice::TcEnableEventActionFunction => {
+                    let opt_started_token = ctx.start_success(token, error_callback);
+                    self.table.enable_function();
+                    ctx.completion_success(opt_started_token, error_callback);
+                }
+                EventActionSubservice::TcDisableEventActionFunction => {
+                    let opt_started_token = ctx.start_success(token, error_callback);
+                    self.table.disable_function();
+                    ctx.completion_success(opt_started_token, error_callback);
+                }
+                EventActionSubservice::TcReportEventActionStatus => {
+                    let opt_started_token = ctx.start_success(token, error_callback);
+                    let num_definitions = match u16::try_from(self.table.len()) {
+                        Ok(num_definitions) => num_definitions,
+                        // The number of definitions does not fit into the N field.
+                        Err(_) => {
+                            ctx.completion_failure(
+                                opt_started_token,
+                                &event_action_res_code::REPORT_GENERATION_FAILED,
+                                &[],
+                                error_callback,
+                            );
+                            return Ok(HandlingStatus::HandledOne.into());
+                        }
+                    };
+                    // The report contains the N field followed by the raw event and the
+                    // enabled state for each definition.
+                    let mut source_data = Vec::with_capacity(2 + self.table.len() * 5);
+                    source_data.extend_from_slice(&num_definitions.to_be_bytes());
+                    for (event, entry) in self.table.iter() {
+                        source_data.extend_from_slice(&event.raw().to_be_bytes());
+                        source_data.push(entry.enabled() as u8);
+                    }
+                    // Sequence count will be handled centrally in TM funnel.
+                    let reply_header = SpHeader::new_for_unseg_tm(ctx.verif_reporter.apid(), 0, 0);
+                    let sec_header = PusTmSecondaryHeader::new(
+                        EVENT_ACTION_SERVICE,
+                        EventActionSubservice::TmEventActionStatusReport.into(),
+                        0,
+                        tc.source_id(),
+                        ctx.time_stamp,
+                    );
+                    let report = PusTmCreator::new(reply_header, sec_header, &source_data, true);
+                    match ctx.tm_sender.send_tm(ctx.id, PusTmVariant::Direct(report)) {
+                        Ok(_) => ctx.completion_success(opt_started_token, error_callback),
+                        Err(e) => {
+                            error_callback(&PartialPusHandlingError::TmSend(e));
+                            ctx.completion_failure(
+                                opt_started_token,
+                                &event_action_res_code::REPORT_GENERATION_FAILED,
+                                &[],
+                                error_callback,
+                            );
+                        }
+                    }
+                }
+                EventActionSubservice::TmEventActionStatusReport => {
+                    return Err(GenericConversionError::InvalidSubservice(subservice).into())
+                }
+            }
+            Ok(HandlingStatus::HandledOne.into())
+        }
+    }
+
+    impl<Pool: PoolProvider + ?Sized> PusEventActionServiceCore<'_, Pool> {
+        /// Generate the completion report for a request which consists of multiple
+        /// instructions. The failure data contains the number of successful and failed
+        /// instructions as big-endian [u32] values. The failure code is derived from the first
+        /// failed instruction.
+        fn report_completion<
+            TmSender: EcssTmSender,
+            VerificationReporter: VerificationReportingProvider,
+        >(
+            ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+            opt_started_token: Option<VerificationToken<TcStateStarted>>,
+            results: Vec<Result<(), EventActionError>>,
+            error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+        ) {
+            let mut num_success: u32 = 0;
+            let mut num_failed: u32 = 0;
+            let mut opt_failure_code = None;
+            for result in results {
+                match result {
+                    Ok(_) => num_success += 1,
+                    Err(e) => {
+                        num_failed += 1;
+                        if opt_failure_code.is_none() {
+                            opt_failure_code = Some(failure_code(&e));
+                        }
+                    }
+                }
+            }
+            let mut failure_data: [u8; 8] = [0; 8];
+            failure_data[0..4].copy_from_slice(&num_success.to_be_bytes());
+            failure_data[4..8].copy_from_slice(&num_failed.to_be_bytes());
+            ctx.completion(
+                opt_started_token,
+                opt_failure_code
+                    .as_ref()
+                    .map(|code| (code, failure_data.as_slice())),
+                error_callback,
+            );
+        }
+    }
+
+    /// Helper type definition for a PUS 19 handler with a dynamic TMTC memory backend and
+    /// regular mpsc queues.
+    pub type PusService19EventActionHandlerDynWithMpsc = PusEventActionServiceHandler<
+        MpscTcReceiver,
+        mpsc::Sender<PacketAsVec>,
+        EcssTcInVecConverter,
+        VerificationReporter,
+    >;
+    /// Helper type definition for a PUS 19 handler with a shared store TMTC memory backend and
+    /// regular mpsc queues.
+    pub type PusService19EventActionHandlerStaticWithMpsc = PusEventActionServiceHandler<
+        MpscTcReceiver,
+        PacketSenderWithSharedPool,
+        EcssTcInSharedStoreConverter,
+        VerificationReporter,
+    >;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_man::EventMessageU32;
+    use crate::events::Severity;
+    use crate::pool::{StaticMemoryPool, StaticPoolAddr, StaticPoolConfig};
+    use crate::pus::test_harness::PusServiceTestHarness;
+    use crate::pus::test_util::TEST_APID;
+    use crate::pus::PartialPusHandlingError;
+    use crate::tmtc::PacketAsVec;
+    use core::time::Duration;
+    use spacepackets::ecss::tc::{PusTcCreator, PusTcSecondaryHeader};
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::SpHeader;
+    use std::sync::mpsc;
+    use std::vec;
+    use std::vec::Vec;
+
+    const TEST_EVENT_0: EventU32 = EventU32::new(Severity::High, 1, 5);
+    const TEST_EVENT_1: EventU32 = EventU32::new(Severity::Medium, 1, 6);
+    const TEST_SENDER_ID: ComponentId = 0x05;
+
+    fn pool() -> StaticMemoryPool {
+        StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(8, 32), (4, 64)],
+            false,
+        ))
+    }
+
+    fn action_tc(service: u8, subservice: u8) -> Vec<u8> {
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(service, subservice);
+        PusTcCreator::new_no_app_data(sp_header, sec_header, true)
+            .to_vec()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_table_add_enable_release() {
+        let mut pool = pool();
+        let mut table = EventActionTable::default();
+        let tc = action_tc(17, 1);
+        table.add(TEST_EVENT_0, &tc, &mut pool).unwrap();
+        assert_eq!(table.len(), 1);
+        assert!(!table.entry(TEST_EVENT_0).unwrap().enabled());
+        // Definitions are disabled after adding them.
+        assert!(!table
+            .release_action(TEST_EVENT_0, &pool, |_| panic!())
+            .unwrap());
+
+        table.enable(TEST_EVENT_0).unwrap();
+        let mut released = Vec::new();
+        assert!(table
+            .release_action(TEST_EVENT_0, &pool, |raw| released.extend_from_slice(raw))
+            .unwrap());
+        assert_eq!(released, tc);
+        assert!(!table
+            .release_action(TEST_EVENT_1, &pool, |_| panic!())
+            .unwrap());
+
+        table.disable_function();
+        assert!(!table
+            .release_action(TEST_EVENT_0, &pool, |_| panic!())
+            .unwrap());
+    }
+
+    #[test]
+    fn test_table_add_and_remove_rules() {
+        let mut pool = pool();
+        let mut table = EventActionTable::new(Some(1));
+        assert_eq!(
+            table.add(TEST_EVENT_0, &action_tc(19, 4), &mut pool),
+            Err(EventActionError::NestedEventActionTc)
+        );
+        table
+            .add(TEST_EVENT_0, &action_tc(17, 1), &mut pool)
+            .unwrap();
+        let old_addr = table.entry(TEST_EVENT_0).unwrap().tc_addr();
+        // Disabled definitions are replaced.
+        table
+            .add(TEST_EVENT_0, &action_tc(17, 3), &mut pool)
+            .unwrap();
+        assert!(!pool.has_element_at(&old_addr).unwrap());
+        assert_eq!(
+            table.add(TEST_EVENT_1, &action_tc(17, 1), &mut pool),
+            Err(EventActionError::TableFull)
+        );
+
+        table.enable(TEST_EVENT_0).unwrap();
+        assert_eq!(
+            table.add(TEST_EVENT_0, &action_tc(17, 1), &mut pool),
+            Err(EventActionError::AlreadyDefined(TEST_EVENT_0))
+        );
+        assert_eq!(
+            table.remove(TEST_EVENT_0, &mut pool),
+            Err(EventActionError::ActionEnabled(TEST_EVENT_0))
+        );
+        assert_eq!(
+            table.enable(TEST_EVENT_1),
+            Err(EventActionError::UnknownEvent(TEST_EVENT_1))
+        );
+        let addr = table.entry(TEST_EVENT_0).unwrap().tc_addr();
+        table.remove_all(&mut pool).unwrap();
+        assert!(table.is_empty());
+        assert!(!pool.has_element_at(&addr).unwrap());
+    }
+
+    #[test]
+    fn test_failed_replacement_keeps_definition() {
+        let mut pool = pool();
+        let mut table = EventActionTable::default();
+        // Deleting the telecommand of this definition from the pool fails.
+        let invalid_addr = StaticPoolAddr::INVALID_ADDR as PoolAddr;
+        table.actions.insert(
+            TEST_EVENT_0.raw(),
+            EventActionEntry {
+                tc_addr: invalid_addr,
+                enabled: false,
+            },
+        );
+        assert!(matches!(
+            table.add(TEST_EVENT_0, &action_tc(17, 1), &mut pool),
+            Err(EventActionError::StoreError(_))
+        ));
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.entry(TEST_EVENT_0).unwrap().tc_addr(), invalid_addr);
+        // The new telecommand was deleted again, so all slots of the first subpool are free.
+        for _ in 0..8 {
+            pool.add(&[0; 16]).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_service_handler_and_event_release() {
+        let (mut harness, service_helper) = PusServiceTestHarness::new(TEST_SENDER_ID, TEST_APID);
+        let mut handler = PusEventActionServiceHandler::new(service_helper, Default::default());
+        let mut pool = pool();
+        let timeout = Duration::from_millis(50);
+        let action = action_tc(17, 1);
+
+        let mut app_data = Vec::new();
+        app_data.extend_from_slice(&1_u16.to_be_bytes());
+        app_data.extend_from_slice(&TEST_EVENT_0.raw().to_be_bytes());
+        app_data.extend_from_slice(&action);
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(19, 1);
+        let add_tc = PusTcCreator::new(sp_header, sec_header, &app_data, true);
+        let token = harness.inject_tc(&add_tc.to_vec().unwrap()).unwrap();
+        handler
+            .poll_and_handle_next_tc(
+                |_: &PartialPusHandlingError| {},
+                harness.time_stamp(),
+                &mut pool,
+            )
+            .unwrap();
+        harness.expect_verification_tm(1, token.request_id(), timeout);
+        harness.expect_verification_tm(3, token.request_id(), timeout);
+        harness.expect_verification_tm(7, token.request_id(), timeout);
+
+        let mut app_data = Vec::new();
+        app_data.extend_from_slice(&1_u16.to_be_bytes());
+        app_data.extend_from_slice(&TEST_EVENT_0.raw().to_be_bytes());
+        let sec_header = PusTcSecondaryHeader::new_simple(19, 4);
+        let enable_tc = PusTcCreator::new(sp_header, sec_header, &app_data, true);
+        let token = harness.inject_tc(&enable_tc.to_vec().unwrap()).unwrap();
+        handler
+            .poll_and_handle_next_tc(
+                |_: &PartialPusHandlingError| {},
+                harness.time_stamp(),
+                &mut pool,
+            )
+            .unwrap();
+        harness.expect_verification_tm(7, token.request_id(), timeout);
+        assert!(handler.table().entry(TEST_EVENT_0).unwrap().enabled());
+
+        let sec_header = PusTcSecondaryHeader::new_simple(19, 6);
+        let report_tc = PusTcCreator::new_no_app_data(sp_header, sec_header, true);
+        let token = harness.inject_tc(&report_tc.to_vec().unwrap()).unwrap();
+        handler
+            .poll_and_handle_next_tc(
+                |_: &PartialPusHandlingError| {},
+                harness.time_stamp(),
+                &mut pool,
+            )
+            .unwrap();
+        let mut expected_report = Vec::new();
+        expected_report.extend_from_slice(&1_u16.to_be_bytes());
+        expected_report.extend_from_slice(&TEST_EVENT_0.raw().to_be_bytes());
+        expected_report.push(1);
+        assert_eq!(
+            harness.expect_tm(19, 7, timeout).user_data(),
+            expected_report
+        );
+        harness.expect_verification_tm(7, token.request_id(), timeout);
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let (tc_tx, tc_rx) = mpsc::channel::<PacketAsVec>();
+        event_tx
+            .send(EventMessageU32::new(1, TEST_EVENT_1))
+            .unwrap();
+        event_tx
+            .send(EventMessageU32::new(1, TEST_EVENT_0))
+            .unwrap();
+        assert_eq!(handler.handle_events(&event_rx, &pool, &tc_tx).unwrap(), 1);
+        let released = tc_rx.try_recv().unwrap();
+        assert_eq!(released.sender_id, TEST_SENDER_ID);
+        assert_eq!(released.packet, action);
+        assert!(tc_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_deleting_unknown_event_action() {
+        let (mut harness, service_helper) = PusServiceTestHarness::new(TEST_SENDER_ID, TEST_APID);
+        let mut handler = PusEventActionServiceHandler::new(service_helper, Default::default());
+        let mut pool = pool();
+        let mut app_data = Vec::new();
+        app_data.extend_from_slice(&1_u16.to_be_bytes());
+        app_data.extend_from_slice(&TEST_EVENT_0.raw().to_be_bytes());
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(19, 2);
+        let delete_tc = PusTcCreator::new(sp_header, sec_header, &app_data, true);
+        let token = harness.inject_tc(&delete_tc.to_vec().unwrap()).unwrap();
+        handler
+            .poll_and_handle_next_tc(
+                |_: &PartialPusHandlingError| {},
+                harness.time_stamp(),
+                &mut pool,
+            )
+            .unwrap();
+        let timeout = Duration::from_millis(50);
+        harness.expect_verification_tm(1, token.request_id(), timeout);
+        harness.expect_verification_tm(3, token.request_id(), timeout);
+        harness.expect_verification_tm(8, token.request_id(), timeout);
+    }
+}
@@ -0,0 +1,301 @@
+//! PUS 5 extension to downlink or replay the [on-board event log][crate::event_log].
+//!
+//! The extension uses custom subservices of the PUS event service, which are listed in the
+//! [EventLogSubservice] enumeration. The [PusEventLogTmCreator] downlinks each stored record
+//! as a [EventLogSubservice::TmEventLogRecord] packet, where the source data contains the
+//! serialized [EventLogRecord][crate::event_log::EventLogRecord]. Alternatively, the [replay_event_log] function can be used to
+//! generate regular PUS 5 event reports for the stored records, for example after an on-board
+//! reboot.
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+/// Custom subservices of the PUS event service used for the on-board event log.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum EventLogSubservice {
+    /// Downlink all records of the event log.
+    TcDumpEventLog = 128,
+    /// A single record of the event log.
+    TmEventLogRecord = 129,
+    /// Replay all records of the event log as regular event reports.
+    TcReplayEventLog = 130,
+    /// Delete all records of the event log.
+    TcClearEventLog = 131,
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::SpHeader;
+
+    use super::*;
+    use crate::apid::ApidHandle;
+    use crate::event_log::{EventLog, EventLogError, EventLogRecord};
+    use crate::events::EventU32;
+    use crate::pool::PoolProvider;
+    use crate::pus::event::EventTmHookProvider;
+    use crate::pus::event_man::{
+        EventManError, PusEventReportingMapProvider, PusEventTmCreatorWithMap,
+    };
+    use crate::pus::{EcssTmSender, EcssTmtcError};
+    use crate::ComponentId;
+
+    #[derive(Debug)]
+    pub enum EventLogTmError {
+        Log(EventLogError),
+        EventMan(EventManError),
+    }
+
+    impl From<EventLogError> for EventLogTmError {
+        fn from(value: EventLogError) -> Self {
+            Self::Log(value)
+        }
+    }
+
+    impl From<EventManError> for EventLogTmError {
+        fn from(value: EventManError) -> Self {
+            Self::EventMan(value)
+        }
+    }
+
+    impl From<EcssTmtcError> for EventLogTmError {
+        fn from(value: EcssTmtcError) -> Self {
+            Self::EventMan(value.into())
+        }
+    }
+
+    /// Creates [EventLogSubservice::TmEventLogRecord] packets for all records of an [EventLog].
+    pub struct PusEventLogTmCreator {
+        id: ComponentId,
+        apid: ApidHandle,
+        pub dest_id: u16,
+        source_data_buf: Vec<u8>,
+    }
+
+    impl PusEventLogTmCreator {
+        /// Create a new TM creator. Returns [None] if the APID is invalid.
+        ///
+        /// ## Parameter
+        ///
+        /// * `id` - Component ID used to send the telemetry.
+        /// * `apid` - APID of the generated telemetry.
+        /// * `dest_id` - Destination ID of the generated telemetry.
+        /// * `max_record_size` - Maximum size of a serialized record.
+        pub fn new(
+            id: ComponentId,
+            apid: u16,
+            dest_id: u16,
+            max_record_size: usize,
+        ) -> Option<Self> {
+            Some(Self::new_with_apid_handle(
+                id,
+                ApidHandle::new_fixed(apid)?,
+                dest_id,
+                max_record_size,
+            ))
+        }
+
+        /// Like [Self::new], but the APID is read from the given handle, for example a
+        /// [crate::apid::SharedApid] which can be changed at run-time.
+        pub fn new_with_apid_handle(
+            id: ComponentId,
+            apid: impl Into<ApidHandle>,
+            dest_id: u16,
+            max_record_size: usize,
+        ) -> Self {
+            Self {
+                id,
+                apid: apid.into(),
+                dest_id,
+                source_data_buf: vec![0; max_record_size],
+            }
+        }
+
+        pub fn apid(&self) -> u16 {
+            self.apid.apid()
+        }
+
+        pub fn set_apid_handle(&mut self, apid: impl Into<ApidHandle>) {
+            self.apid = apid.into();
+        }
+
+        /// Send one [EventLogSubservice::TmEventLogRecord] packet for each record of the event
+        /// log, starting with the oldest record. Returns the number of sent packets.
+        pub fn dump_event_log<Pool: PoolProvider>(
+            &mut self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            event_log: &EventLog<Pool>,
+        ) -> Result<u32, EventLogTmError> {
+            event_log.for_each_record(|record: &EventLogRecord| {
+                let record_len = record
+                    .write_to_bytes(&mut self.source_data_buf)
+                    .map_err(EventLogError::from)?;
+                let sec_header = PusTmSecondaryHeader::new(
+                    5,
+                    EventLogSubservice::TmEventLogRecord.into(),
+                    0,
+                    self.dest_id,
+                    time_stamp,
+                );
+                let tm_creator = PusTmCreator::new(
+                    SpHeader::new_from_apid(self.apid.apid()),
+                    sec_header,
+                    &self.source_data_buf[0..record_len],
+                    true,
+                );
+                sender.send_tm(self.id, tm_creator.into())?;
+                Ok::<(), EventLogTmError>(())
+            })
+        }
+    }
+
+    /// Generate regular PUS 5 event reports for all records of the event log, starting with the
+    /// oldest record. The reports use the time stamp stored in the records. Events which are
+    /// disabled for reporting are skipped. Returns the number of generated reports.
+    pub fn replay_event_log<
+        Pool: PoolProvider,
+        ReportingMap: PusEventReportingMapProvider<EventU32>,
+        EventTmHook: EventTmHookProvider,
+    >(
+        sender: &(impl EcssTmSender + ?Sized),
+        event_tm_creator: &PusEventTmCreatorWithMap<ReportingMap, EventU32, EventTmHook>,
+        event_log: &EventLog<Pool>,
+    ) -> Result<u32, EventLogTmError> {
+        let mut num_reports = 0;
+        event_log.for_each_record(|record: &EventLogRecord| {
+            let params = if record.params().is_empty() {
+                None
+            } else {
+                Some(record.params())
+            };
+            if event_tm_creator.generate_pus_event_tm_generic(
+                sender,
+                record.timestamp(),
+                record.event(),
+                params,
+            )? {
+                num_reports += 1;
+            }
+            Ok::<(), EventLogTmError>(())
+        })?;
+        Ok(num_reports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::vec;
+
+    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::PusPacket;
+
+    use super::*;
+    use crate::event_log::{EventLog, EventLogRecord};
+    use crate::event_man::EventMessageU32;
+    use crate::events::{EventU32, Severity};
+    use crate::params::Params;
+    use crate::pool::{StaticMemoryPool, StaticPoolConfig};
+    use crate::pus::event_man::{DefaultPusEventU32TmCreator, EventReporter};
+    use crate::pus::test_util::TEST_COMPONENT_ID_0;
+    use crate::tmtc::PacketAsVec;
+
+    const TEST_APID: u16 = 0x02;
+    const TEST_EVENT_0: EventU32 = EventU32::new(Severity::Info, 1, 1);
+    const TEST_EVENT_1: EventU32 = EventU32::new(Severity::Low, 1, 2);
+    const STORED_STAMP: [u8; 7] = [1, 2, 3, 4, 5, 6, 7];
+    const CURRENT_STAMP: [u8; 7] = [0; 7];
+
+    fn event_log() -> EventLog {
+        let mut event_log = EventLog::new(
+            StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+                vec![(8, 64)],
+                false,
+            )),
+            8,
+        );
+        event_log
+            .record_event_msg(&STORED_STAMP, &EventMessageU32::new(1, TEST_EVENT_0))
+            .unwrap();
+        event_log
+            .record_event_msg(
+                &STORED_STAMP,
+                &EventMessageU32::new_with_params(2, TEST_EVENT_1, &Params::Vec(vec![1, 2])),
+            )
+            .unwrap();
+        event_log
+    }
+
+    #[test]
+    fn test_subservice_conversion() {
+        assert_eq!(
+            EventLogSubservice::try_from(130).unwrap(),
+            EventLogSubservice::TcReplayEventLog
+        );
+        assert!(EventLogSubservice::try_from(1).is_err());
+    }
+
+    #[test]
+    fn test_dump_event_log() {
+        let event_log = event_log();
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let mut tm_creator =
+            PusEventLogTmCreator::new(TEST_COMPONENT_ID_0.id(), TEST_APID, 0, 64).unwrap();
+        assert_eq!(
+            tm_creator
+                .dump_event_log(&tm_tx, &CURRENT_STAMP, &event_log)
+                .unwrap(),
+            2
+        );
+        for (expected_sender, expected_event) in [(1, TEST_EVENT_0), (2, TEST_EVENT_1)] {
+            let packet = tm_rx.try_recv().expect("no TM received");
+            assert_eq!(packet.sender_id, TEST_COMPONENT_ID_0.id());
+            let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+            assert_eq!(tm.service(), 5);
+            assert_eq!(tm.subservice(), EventLogSubservice::TmEventLogRecord as u8);
+            assert_eq!(tm.timestamp(), CURRENT_STAMP);
+            let (record, _) = EventLogRecord::from_bytes(tm.source_data()).unwrap();
+            assert_eq!(record.sender_id(), expected_sender);
+            assert_eq!(record.event(), expected_event);
+            assert_eq!(record.timestamp(), STORED_STAMP);
+        }
+        assert!(tm_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_replay_event_log() {
+        let event_log = event_log();
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let mut event_tm_creator = DefaultPusEventU32TmCreator::new_with_default_backend(
+            EventReporter::new(TEST_COMPONENT_ID_0.id(), TEST_APID, 0, 128).unwrap(),
+        );
+        event_tm_creator
+            .disable_tm_for_event(&TEST_EVENT_0)
+            .unwrap();
+        assert_eq!(
+            replay_event_log(&tm_tx, &event_tm_creator, &event_log).unwrap(),
+            1
+        );
+        let packet = tm_rx.try_recv().expect("no TM received");
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(tm.service(), 5);
+        assert_eq!(tm.subservice(), 2);
+        assert_eq!(tm.timestamp(), STORED_STAMP);
+        assert_eq!(
+            EventU32::from(u32::from_be_bytes(
+                tm.source_data()[0..4].try_into().unwrap()
+            )),
+            TEST_EVENT_1
+        );
+        assert_eq!(&tm.source_data()[4..], &[1, 2]);
+        assert!(tm_rx.try_recv().is_err());
+    }
+}
@@ -12,6 +12,9 @@ use crate::pus::verification::TcStateToken;
 #[cfg(feature = "alloc")]
 use crate::pus::EcssTmSender;
 use crate::pus::EcssTmtcError;
+use crate::time::TimeSourceError;
+#[cfg(feature = "alloc")]
+use crate::time::{TimeSource, TimestampFormat};
 #[cfg(feature = "alloc")]
 pub use alloc_mod::*;
 #[cfg(feature = "heapless")]
@@ -34,20 +37,35 @@ pub trait PusEventReportingMapProvider<Event: GenericEvent> {
     fn event_enabled(&self, event: &Event) -> bool;
     fn enable_event_reporting(&mut self, event: &Event) -> Result<bool, Self::Error>;
     fn disable_event_reporting(&mut self, event: &Event) -> Result<bool, Self::Error>;
+    /// Call the given closure for each event which is disabled for reporting.
+    fn for_each_disabled_event<F: FnMut(&Event)>(&self, f: F);
 }
 
 #[cfg(feature = "heapless")]
 pub mod heapless_mod {
     use super::*;
-    use crate::events::LargestEventRaw;
-    use core::marker::PhantomData;
 
-    // TODO: After a new version of heapless is released which uses hash32 version 0.3, try using
-    //       regular Event type again.
-    #[derive(Default)]
+    /// Backend provider which stores up to N disabled events in a pre-allocated list.
     pub struct HeaplessPusMgmtBackendProvider<const N: usize, Provider: GenericEvent> {
-        disabled: heapless::FnvIndexSet<LargestEventRaw, N>,
-        phantom: PhantomData<Provider>,
+        disabled: heapless::Vec<Provider, N>,
+    }
+
+    impl<const N: usize, Provider: GenericEvent> Default
+        for HeaplessPusMgmtBackendProvider<N, Provider>
+    {
+        fn default() -> Self {
+            Self {
+                disabled: heapless::Vec::new(),
+            }
+        }
+    }
+
+    impl<const N: usize, Provider: GenericEvent> HeaplessPusMgmtBackendProvider<N, Provider> {
+        fn disabled_idx(&self, event: &Provider) -> Option<usize> {
+            self.disabled
+                .iter()
+                .position(|disabled| disabled.raw_as_largest_type() == event.raw_as_largest_type())
+        }
     }
 
     impl<const N: usize, Provider: GenericEvent> PusEventReportingMapProvider<Provider>
@@ -56,17 +74,30 @@ pub mod heapless_mod {
         type Error = ();
 
         fn event_enabled(&self, event: &Provider) -> bool {
-            self.disabled.contains(&event.raw_as_largest_type())
+            self.disabled_idx(event).is_none()
         }
 
         fn enable_event_reporting(&mut self, event: &Provider) -> Result<bool, Self::Error> {
-            self.disabled
-                .insert(event.raw_as_largest_type())
-                .map_err(|_| ())
+            match self.disabled_idx(event) {
+                Some(idx) => {
+                    self.disabled.swap_remove(idx);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
         }
 
+        /// Returns an error if the list of disabled events is full.
         fn disable_event_reporting(&mut self, event: &Provider) -> Result<bool, Self::Error> {
-            Ok(self.disabled.remove(&event.raw_as_largest_type()))
+            if self.disabled_idx(event).is_some() {
+                return Ok(false);
+            }
+            self.disabled.push(*event).map_err(|_| ())?;
+            Ok(true)
+        }
+
+        fn for_each_disabled_event<F: FnMut(&Provider)>(&self, f: F) {
+            self.disabled.iter().for_each(f);
         }
     }
 }
@@ -75,6 +106,14 @@ pub mod heapless_mod {
 pub enum EventRequest<Event: GenericEvent = EventU32> {
     Enable(Event),
     Disable(Event),
+    /// Generate a report of all events which are disabled for reporting.
+    ReportDisabledList,
+    /// Downlink the records of the [on-board event log][crate::event_log].
+    DumpLog,
+    /// Replay the records of the on-board event log as regular event reports.
+    ReplayLog,
+    /// Delete all records of the on-board event log.
+    ClearLog,
 }
 
 #[derive(Debug)]
@@ -87,6 +126,7 @@ pub struct EventRequestWithToken<Event: GenericEvent = EventU32> {
 pub enum EventManError {
     EcssTmtcError(EcssTmtcError),
     SeverityMissmatch(Severity, Severity),
+    TimeSource(TimeSourceError),
 }
 
 impl From<EcssTmtcError> for EventManError {
@@ -95,14 +135,26 @@ impl From<EcssTmtcError> for EventManError {
     }
 }
 
+impl From<TimeSourceError> for EventManError {
+    fn from(v: TimeSourceError) -> Self {
+        Self::TimeSource(v)
+    }
+}
+
 #[cfg(feature = "alloc")]
 pub mod alloc_mod {
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
     use core::marker::PhantomData;
+    use hashbrown::HashMap;
 
     use crate::{
-        events::EventU16,
+        events::{EventU16, LargestGroupIdRaw},
         params::{Params, WritableToBeBytes},
-        pus::event::{DummyEventHook, EventTmHookProvider},
+        pool::PoolProvider,
+        pus::event::{subservice_for_severity, DummyEventHook, EventTmHookProvider, EventTmRoute},
+        tm_suppression::TmSuppressionProvider,
     };
 
     use super::*;
@@ -140,6 +192,129 @@ pub mod alloc_mod {
         fn disable_event_reporting(&mut self, event: &Event) -> Result<bool, Self::Error> {
             Ok(self.disabled.insert(*event))
         }
+
+        fn for_each_disabled_event<F: FnMut(&Event)>(&self, f: F) {
+            self.disabled.iter().for_each(f);
+        }
+    }
+
+    /// Hook to customize the serialization of [Params] into the auxiliary data field of
+    /// PUS event TM.
+    pub trait EventParamsEncoder {
+        /// Serialize the parameters. The passed buffer can be used to store the serialized
+        /// parameters. Returns the serialized parameters, or [None] if the parameters can not be
+        /// serialized by this encoder and should not be propagated.
+        fn encode_params<'buf>(
+            &self,
+            params: &'buf Params,
+            buf: &'buf mut [u8],
+        ) -> Result<Option<&'buf [u8]>, EcssTmtcError>;
+    }
+
+    /// Default [EventParamsEncoder].
+    ///
+    /// [Params::Heapless] parameters are serialized in network endianness, while [Params::Vec]
+    /// and [Params::String] parameters are propagated as raw bytes. [Params::Store] parameters
+    /// are not propagated.
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+    pub struct DefaultEventParamsEncoder;
+
+    impl EventParamsEncoder for DefaultEventParamsEncoder {
+        fn encode_params<'buf>(
+            &self,
+            params: &'buf Params,
+            buf: &'buf mut [u8],
+        ) -> Result<Option<&'buf [u8]>, EcssTmtcError> {
+            match params {
+                Params::Heapless(heapless_param) => {
+                    let written_len = heapless_param
+                        .write_to_be_bytes(buf)
+                        .map_err(EcssTmtcError::ByteConversion)?;
+                    Ok(Some(&buf[0..written_len]))
+                }
+                Params::Vec(vec) => Ok(Some(vec)),
+                Params::String(string) => Ok(Some(string.as_bytes())),
+                Params::Store(_) => Ok(None),
+            }
+        }
+    }
+
+    /// [EventParamsEncoder] which also propagates [Params::Store] parameters by reading the
+    /// parameters from the given pool. All other parameters are serialized like the
+    /// [DefaultEventParamsEncoder] does.
+    pub struct PoolParamsEncoder<'pool, Pool: PoolProvider> {
+        pool: &'pool Pool,
+    }
+
+    impl<'pool, Pool: PoolProvider> PoolParamsEncoder<'pool, Pool> {
+        pub fn new(pool: &'pool Pool) -> Self {
+            Self { pool }
+        }
+    }
+
+    impl<Pool: PoolProvider> EventParamsEncoder for PoolParamsEncoder<'_, Pool> {
+        fn encode_params<'buf>(
+            &self,
+            params: &'buf Params,
+            buf: &'buf mut [u8],
+        ) -> Result<Option<&'buf [u8]>, EcssTmtcError> {
+            if let Params::Store(addr) = params {
+                let read_len = self.pool.read(addr, buf).map_err(EcssTmtcError::Store)?;
+                return Ok(Some(&buf[0..read_len]));
+            }
+            DefaultEventParamsEncoder.encode_params(params, buf)
+        }
+    }
+
+    /// Maps event groups to the [EventTmRoute] used for their event TM and keeps track of one
+    /// PUS message counter per APID.
+    ///
+    /// Events of groups without a route are reported with the APID of the [EventReporter].
+    #[derive(Debug, Default)]
+    pub struct EventApidMap {
+        routes: HashMap<LargestGroupIdRaw, EventTmRoute>,
+        // Interior mutability is used because the event TM is generated with a shared reference.
+        msg_counters: RefCell<HashMap<u16, u16>>,
+    }
+
+    impl EventApidMap {
+        /// Report all events of the given group with the given route. Returns the previous route
+        /// of the group.
+        pub fn add_route(
+            &mut self,
+            group_id: LargestGroupIdRaw,
+            route: EventTmRoute,
+        ) -> Option<EventTmRoute> {
+            self.routes.insert(group_id, route)
+        }
+
+        /// Builder variant of [Self::add_route].
+        pub fn with_route(mut self, group_id: LargestGroupIdRaw, route: EventTmRoute) -> Self {
+            self.add_route(group_id, route);
+            self
+        }
+
+        pub fn remove_route(&mut self, group_id: LargestGroupIdRaw) -> Option<EventTmRoute> {
+            self.routes.remove(&group_id)
+        }
+
+        pub fn route(&self, group_id: LargestGroupIdRaw) -> Option<&EventTmRoute> {
+            self.routes.get(&group_id)
+        }
+
+        /// Retrieve the next message counter for the given APID and increment it.
+        pub fn next_msg_counter(&self, apid: u16) -> u16 {
+            let mut msg_counters = self.msg_counters.borrow_mut();
+            let counter = msg_counters.entry(apid).or_default();
+            let current = *counter;
+            *counter = counter.wrapping_add(1);
+            current
+        }
+
+        /// Reset the message counters of all APIDs to 0.
+        pub fn reset_msg_counters(&self) {
+            self.msg_counters.borrow_mut().clear();
+        }
     }
 
     #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -155,6 +330,9 @@ pub mod alloc_mod {
     > {
         pub reporter: EventReporter<EventTmHook>,
         reporting_map: ReportingMap,
+        tm_suppression: Option<Box<dyn TmSuppressionProvider + Send>>,
+        timestamp_format: TimestampFormat,
+        apid_map: Option<EventApidMap>,
         phantom: PhantomData<Event>,
     }
 
@@ -168,10 +346,64 @@ pub mod alloc_mod {
             Self {
                 reporter,
                 reporting_map: backend,
+                tm_suppression: None,
+                timestamp_format: TimestampFormat::default(),
+                apid_map: None,
                 phantom: PhantomData,
             }
         }
 
+        /// Set the provider of the TM generation policy. Event TM for events below the minimum
+        /// severity of the active policy is suppressed.
+        pub fn with_tm_suppression(
+            mut self,
+            provider: impl TmSuppressionProvider + Send + 'static,
+        ) -> Self {
+            self.tm_suppression = Some(Box::new(provider));
+            self
+        }
+
+        /// Replace the default CDS short [TimestampFormat] of event TM which is timestamped with a
+        /// [TimeSource].
+        pub fn with_timestamp_format(mut self, format: TimestampFormat) -> Self {
+            self.timestamp_format = format;
+            self
+        }
+
+        pub fn timestamp_format(&self) -> TimestampFormat {
+            self.timestamp_format
+        }
+
+        /// Set the [EventApidMap] which maps event groups to different APIDs or sender IDs.
+        ///
+        /// If a map is set, the message counter of the event TM is incremented separately for
+        /// each APID, including the default APID of the [EventReporter]. The TM hook of the
+        /// reporter is still applied to all packets.
+        pub fn with_apid_map(mut self, apid_map: EventApidMap) -> Self {
+            self.apid_map = Some(apid_map);
+            self
+        }
+
+        pub fn apid_map(&self) -> Option<&EventApidMap> {
+            self.apid_map.as_ref()
+        }
+
+        pub fn apid_map_mut(&mut self) -> Option<&mut EventApidMap> {
+            self.apid_map.as_mut()
+        }
+
+        /// Check whether TM is generated for the given event. This is the case if the event is
+        /// enabled for reporting and not suppressed by the active TM generation policy.
+        pub fn event_tm_enabled(&self, event: &Event) -> bool {
+            if !self.reporting_map.event_enabled(event) {
+                return false;
+            }
+            match &self.tm_suppression {
+                Some(provider) => provider.tm_policy().event_enabled(event.severity()),
+                None => true,
+            }
+        }
+
         pub fn enable_tm_for_event(&mut self, event: &Event) -> Result<bool, ReportingMap::Error> {
             self.reporting_map.enable_event_reporting(event)
         }
@@ -187,9 +419,25 @@ pub mod alloc_mod {
             event: Event,
             params: Option<&[u8]>,
         ) -> Result<bool, EventManError> {
-            if !self.reporting_map.event_enabled(&event) {
+            if !self.event_tm_enabled(&event) {
                 return Ok(false);
             }
+            if let Some(apid_map) = &self.apid_map {
+                let route = apid_map
+                    .route(event.group_id_as_largest_type())
+                    .copied()
+                    .unwrap_or(EventTmRoute::new(self.reporter.report_creator.apid()));
+                self.reporter.routed_event_report(
+                    sender,
+                    subservice_for_severity(event.severity()),
+                    &route,
+                    apid_map.next_msg_counter(route.apid),
+                    time_stamp,
+                    event,
+                    params,
+                )?;
+                return Ok(true);
+            }
             match event.severity() {
                 Severity::Info => self
                     .reporter
@@ -214,6 +462,42 @@ pub mod alloc_mod {
             }
         }
 
+        /// Like [Self::generate_pus_event_tm_generic], but timestamps the event TM with the
+        /// current time of the given [TimeSource] using the configured [TimestampFormat].
+        pub fn generate_pus_event_tm_with_time_source(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_source: &(impl TimeSource + ?Sized),
+            event: Event,
+            params: Option<&[u8]>,
+        ) -> Result<bool, EventManError> {
+            if !self.event_tm_enabled(&event) {
+                return Ok(false);
+            }
+            let time_stamp = time_source.stamp(self.timestamp_format)?;
+            self.generate_pus_event_tm_generic(sender, &time_stamp, event, params)
+        }
+
+        /// Generate [Subservice::TmDisabledEventsReport][crate::pus::event::Subservice] packets
+        /// which contain all events disabled for reporting, sorted by their raw value. The list is
+        /// split across multiple packets if it does not fit into a single packet. Returns the
+        /// number of sent packets.
+        pub fn generate_disabled_events_report(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+        ) -> Result<u32, EventManError> {
+            let mut disabled_events = Vec::new();
+            self.reporting_map
+                .for_each_disabled_event(|event| disabled_events.push(*event));
+            disabled_events.sort_unstable_by_key(|event| event.raw_as_largest_type());
+            self.reporter
+                .disabled_events_report(sender, time_stamp, &disabled_events)
+                .map_err(|e| e.into())
+        }
+
+        /// Generate an event TM where the event parameters are serialized into the auxiliary
+        /// data using the [DefaultEventParamsEncoder].
         pub fn generate_pus_event_tm_generic_with_generic_params(
             &self,
             sender: &(impl EcssTmSender + ?Sized),
@@ -221,43 +505,43 @@ pub mod alloc_mod {
             event: Event,
             small_data_buf: &mut [u8],
             params: Option<&Params>,
+        ) -> Result<EventGenerationResult, EventManError> {
+            self.generate_pus_event_tm_generic_with_params_encoder(
+                sender,
+                time_stamp,
+                event,
+                small_data_buf,
+                params,
+                &DefaultEventParamsEncoder,
+            )
+        }
+
+        /// Generate an event TM where the event parameters are serialized into the auxiliary
+        /// data using the given [EventParamsEncoder]. The small data buffer is passed to the
+        /// encoder and can be used as intermediate storage for the serialized parameters.
+        pub fn generate_pus_event_tm_generic_with_params_encoder(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            event: Event,
+            small_data_buf: &mut [u8],
+            params: Option<&Params>,
+            encoder: &(impl EventParamsEncoder + ?Sized),
         ) -> Result<EventGenerationResult, EventManError> {
             let mut result = EventGenerationResult {
                 event_was_enabled: false,
                 params_were_propagated: true,
             };
-            if params.is_none() {
-                result.event_was_enabled =
-                    self.generate_pus_event_tm_generic(sender, time_stamp, event, None)?;
-                return Ok(result);
-            }
-            let params = params.unwrap();
-            result.event_was_enabled = match params {
-                Params::Heapless(heapless_param) => {
-                    heapless_param
-                        .write_to_be_bytes(&mut small_data_buf[..heapless_param.written_len()])
-                        .map_err(EcssTmtcError::ByteConversion)?;
-                    self.generate_pus_event_tm_generic(
-                        sender,
-                        time_stamp,
-                        event,
-                        Some(small_data_buf),
-                    )?
-                }
-                Params::Vec(vec) => {
-                    self.generate_pus_event_tm_generic(sender, time_stamp, event, Some(vec))?
-                }
-                Params::String(string) => self.generate_pus_event_tm_generic(
-                    sender,
-                    time_stamp,
-                    event,
-                    Some(string.as_bytes()),
-                )?,
-                _ => {
-                    result.params_were_propagated = false;
-                    self.generate_pus_event_tm_generic(sender, time_stamp, event, None)?
+            let aux_data = match params {
+                Some(params) => {
+                    let aux_data = encoder.encode_params(params, small_data_buf)?;
+                    result.params_were_propagated = aux_data.is_some();
+                    aux_data
                 }
+                None => None,
             };
+            result.event_was_enabled =
+                self.generate_pus_event_tm_generic(sender, time_stamp, event, aux_data)?;
             Ok(result)
         }
     }
@@ -269,6 +553,9 @@ pub mod alloc_mod {
             Self {
                 reporter,
                 reporting_map: DefaultPusEventReportingMap::default(),
+                tm_suppression: None,
+                timestamp_format: TimestampFormat::default(),
+                apid_map: None,
                 phantom: PhantomData,
             }
         }
@@ -312,11 +599,20 @@ mod tests {
     use alloc::string::{String, ToString};
     use alloc::vec;
     use spacepackets::ecss::event::Subservice;
-    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::tm::{GenericPusTmSecondaryHeader, PusTmReader};
     use spacepackets::ecss::PusPacket;
+    use spacepackets::CcsdsPacket;
 
     use super::*;
+    use crate::mode::ModeAndSubmode;
+    use crate::params::Params;
+    use crate::pool::{PoolAddr, PoolProvider, StaticMemoryPool, StaticPoolConfig};
+    use crate::pus::event::EventTmRoute;
     use crate::request::UniqueApidTargetId;
+    use crate::tm_suppression::{
+        HkGenerationPolicy, ModeTmPolicyTable, SystemModeObserver, TmGenerationPolicy,
+    };
+    use crate::ComponentId;
     use crate::{events::SeverityInfo, tmtc::PacketAsVec};
     use std::sync::mpsc::{self, TryRecvError};
 
@@ -351,6 +647,42 @@ mod tests {
         event_rx.try_recv().expect("Receiving event TM failed");
     }
 
+    #[test]
+    fn test_event_with_time_source() {
+        let event_man = create_basic_man_1();
+        let (event_tx, event_rx) = mpsc::channel::<PacketAsVec>();
+        let time_source =
+            crate::time::FixedTimeSource::new(spacepackets::time::UnixTime::new_only_secs(100));
+        let event_sent = event_man
+            .generate_pus_event_tm_with_time_source(
+                &event_tx,
+                &time_source,
+                INFO_EVENT.into(),
+                None,
+            )
+            .expect("Sending info event failed");
+        assert!(event_sent);
+        let tm = event_rx.try_recv().expect("Receiving event TM failed");
+        let (tm, _) = PusTmReader::new(&tm.packet, 7).unwrap();
+        assert_eq!(tm.timestamp(), time_source.cds_short_stamp().unwrap());
+
+        let event_man = create_basic_man_1().with_timestamp_format(TimestampFormat::CdsLong);
+        event_man
+            .generate_pus_event_tm_with_time_source(
+                &event_tx,
+                &time_source,
+                INFO_EVENT.into(),
+                None,
+            )
+            .expect("Sending info event failed");
+        let tm = event_rx.try_recv().expect("Receiving event TM failed");
+        let (tm, _) = PusTmReader::new(&tm.packet, 8).unwrap();
+        assert_eq!(
+            TimestampFormat::CdsLong.read_stamp(tm.timestamp()).unwrap(),
+            (time_source.time, 8)
+        );
+    }
+
     #[test]
     fn test_disable_event() {
         let mut event_man = create_basic_man_2();
@@ -391,6 +723,32 @@ mod tests {
         event_rx.try_recv().expect("No info event received");
     }
 
+    #[test]
+    fn test_event_tm_suppression() {
+        let observer = SystemModeObserver::new(
+            ModeAndSubmode::new_mode_only(1),
+            ModeTmPolicyTable::new(TmGenerationPolicy::ALL).with_policy(
+                2,
+                TmGenerationPolicy::new(Severity::Medium, HkGenerationPolicy::All),
+            ),
+        );
+        let event_man = create_basic_man_1().with_tm_suppression(observer.clone());
+        let (event_tx, event_rx) = mpsc::channel::<PacketAsVec>();
+        assert!(event_man
+            .generate_pus_event_tm_generic(&event_tx, &EMPTY_STAMP, LOW_SEV_EVENT, None)
+            .unwrap());
+        observer.set_mode(ModeAndSubmode::new_mode_only(2));
+        assert!(!event_man.event_tm_enabled(&LOW_SEV_EVENT));
+        assert!(!event_man
+            .generate_pus_event_tm_generic(&event_tx, &EMPTY_STAMP, LOW_SEV_EVENT, None)
+            .unwrap());
+        let high_sev_event = EventU32::new(Severity::High, 1, 6);
+        assert!(event_man
+            .generate_pus_event_tm_generic(&event_tx, &EMPTY_STAMP, high_sev_event, None)
+            .unwrap());
+        assert_eq!(event_rx.try_iter().count(), 2);
+    }
+
     #[test]
     fn test_event_with_generic_string_param() {
         let event_man = create_basic_man_1();
@@ -447,13 +805,233 @@ mod tests {
         assert_eq!(vec_data, param_data);
     }
 
+    #[test]
+    fn test_disabled_events_report() {
+        let mut event_man = create_basic_man_1();
+        let (event_tx, event_rx) = mpsc::channel::<PacketAsVec>();
+        event_man.disable_tm_for_event(&LOW_SEV_EVENT).unwrap();
+        event_man
+            .disable_tm_for_event_with_sev(&INFO_EVENT)
+            .unwrap();
+        let num_packets = event_man
+            .generate_disabled_events_report(&event_tx, &EMPTY_STAMP)
+            .expect("generating disabled events report failed");
+        assert_eq!(num_packets, 1);
+        let report = event_rx.try_recv().expect("no report received");
+        let (tm, _) = PusTmReader::new(&report.packet, 7).expect("reading TM failed");
+        assert_eq!(tm.service(), 5);
+        assert_eq!(tm.subservice(), Subservice::TmDisabledEventsReport as u8);
+        let src_data = tm.source_data();
+        assert_eq!(src_data.len(), 2 + 2 * 4);
+        assert_eq!(u16::from_be_bytes(src_data[0..2].try_into().unwrap()), 2);
+        // The events are sorted by their raw value.
+        assert_eq!(
+            u32::from_be_bytes(src_data[2..6].try_into().unwrap()),
+            INFO_EVENT.raw()
+        );
+        assert_eq!(
+            u32::from_be_bytes(src_data[6..10].try_into().unwrap()),
+            LOW_SEV_EVENT.raw()
+        );
+        assert!(event_rx.try_recv().is_err());
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn test_heapless_reporting_map() {
+        let mut reporting_map = HeaplessPusMgmtBackendProvider::<2, EventU32>::default();
+        assert!(reporting_map.event_enabled(&LOW_SEV_EVENT));
+        assert_eq!(
+            reporting_map.disable_event_reporting(&LOW_SEV_EVENT),
+            Ok(true)
+        );
+        assert_eq!(
+            reporting_map.disable_event_reporting(&LOW_SEV_EVENT),
+            Ok(false)
+        );
+        assert!(!reporting_map.event_enabled(&LOW_SEV_EVENT));
+        assert_eq!(
+            reporting_map.disable_event_reporting(INFO_EVENT.as_ref()),
+            Ok(true)
+        );
+        let third_event = EventU32::new(Severity::High, 2, 2);
+        assert!(reporting_map.disable_event_reporting(&third_event).is_err());
+        let mut num_disabled = 0;
+        reporting_map.for_each_disabled_event(|_| num_disabled += 1);
+        assert_eq!(num_disabled, 2);
+        assert_eq!(
+            reporting_map.enable_event_reporting(&LOW_SEV_EVENT),
+            Ok(true)
+        );
+        assert_eq!(
+            reporting_map.enable_event_reporting(&LOW_SEV_EVENT),
+            Ok(false)
+        );
+        assert!(reporting_map.event_enabled(&LOW_SEV_EVENT));
+    }
+
     #[test]
     fn test_event_with_generic_store_param_not_propagated() {
-        // TODO: Test this.
+        let event_man = create_basic_man_1();
+        let mut small_data_buf = [0; 128];
+        let (event_tx, event_rx) = mpsc::channel::<PacketAsVec>();
+        let res = event_man
+            .generate_pus_event_tm_generic_with_generic_params(
+                &event_tx,
+                &EMPTY_STAMP,
+                INFO_EVENT.into(),
+                &mut small_data_buf,
+                Some(&PoolAddr::default().into()),
+            )
+            .expect("generating event TM failed");
+        assert!(res.event_was_enabled);
+        assert!(!res.params_were_propagated);
+        let event_tm = event_rx.try_recv().expect("no event received");
+        let (tm, _) = PusTmReader::new(&event_tm.packet, 7).expect("reading TM failed");
+        assert_eq!(tm.user_data().len(), 4);
     }
 
     #[test]
     fn test_event_with_generic_heapless_param() {
-        // TODO: Test this.
+        let event_man = create_basic_man_1();
+        // The buffer is larger than the parameter, only the parameter should be propagated.
+        let mut small_data_buf = [0xff; 128];
+        let (event_tx, event_rx) = mpsc::channel::<PacketAsVec>();
+        let params = Params::Heapless((2_u32, 3_u32).into());
+        let res = event_man
+            .generate_pus_event_tm_generic_with_generic_params(
+                &event_tx,
+                &EMPTY_STAMP,
+                LOW_SEV_EVENT,
+                &mut small_data_buf,
+                Some(&params),
+            )
+            .expect("generating event TM failed");
+        assert!(res.event_was_enabled);
+        assert!(res.params_were_propagated);
+        let event_tm = event_rx.try_recv().expect("no event received");
+        let (tm, _) = PusTmReader::new(&event_tm.packet, 7).expect("reading TM failed");
+        assert_eq!(tm.subservice(), Subservice::TmLowSeverityReport as u8);
+        assert_eq!(tm.user_data().len(), 4 + 8);
+        assert_eq!(&tm.user_data()[4..], &[0, 0, 0, 2, 0, 0, 0, 3]);
+    }
+
+    #[test]
+    fn test_event_with_store_param_and_pool_encoder() {
+        let event_man = create_basic_man_1();
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(4, 16)],
+            false,
+        ));
+        let addr = pool.add(&[1, 2, 3]).expect("adding params failed");
+        let mut small_data_buf = [0; 128];
+        let (event_tx, event_rx) = mpsc::channel::<PacketAsVec>();
+        let res = event_man
+            .generate_pus_event_tm_generic_with_params_encoder(
+                &event_tx,
+                &EMPTY_STAMP,
+                INFO_EVENT.into(),
+                &mut small_data_buf,
+                Some(&addr.into()),
+                &PoolParamsEncoder::new(&pool),
+            )
+            .expect("generating event TM failed");
+        assert!(res.params_were_propagated);
+        let event_tm = event_rx.try_recv().expect("no event received");
+        let (tm, _) = PusTmReader::new(&event_tm.packet, 7).expect("reading TM failed");
+        assert_eq!(&tm.user_data()[4..], &[1, 2, 3]);
+    }
+
+    struct ReversingParamsEncoder;
+
+    impl EventParamsEncoder for ReversingParamsEncoder {
+        fn encode_params<'buf>(
+            &self,
+            params: &'buf Params,
+            buf: &'buf mut [u8],
+        ) -> Result<Option<&'buf [u8]>, EcssTmtcError> {
+            if let Params::Vec(vec) = params {
+                for (idx, byte) in vec.iter().rev().enumerate() {
+                    buf[idx] = *byte;
+                }
+                return Ok(Some(&buf[0..vec.len()]));
+            }
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_event_with_custom_params_encoder() {
+        let event_man = create_basic_man_1();
+        let mut small_data_buf = [0; 128];
+        let (event_tx, event_rx) = mpsc::channel::<PacketAsVec>();
+        let res = event_man
+            .generate_pus_event_tm_generic_with_params_encoder(
+                &event_tx,
+                &EMPTY_STAMP,
+                INFO_EVENT.into(),
+                &mut small_data_buf,
+                Some(&vec![1, 2, 3].into()),
+                &ReversingParamsEncoder,
+            )
+            .expect("generating event TM failed");
+        assert!(res.params_were_propagated);
+        let event_tm = event_rx.try_recv().expect("no event received");
+        let (tm, _) = PusTmReader::new(&event_tm.packet, 7).expect("reading TM failed");
+        assert_eq!(&tm.user_data()[4..], &[3, 2, 1]);
+    }
+
+    #[test]
+    fn test_event_apid_map() {
+        const PAYLOAD_APID: u16 = 0x10;
+        const PAYLOAD_SENDER_ID: ComponentId = 0x20;
+        const PAYLOAD_EVENT: EventU32 = EventU32::new(Severity::Medium, 2, 1);
+        let apid_map = EventApidMap::default().with_route(
+            PAYLOAD_EVENT.group_id(),
+            EventTmRoute::new_with_sender_id(PAYLOAD_APID, PAYLOAD_SENDER_ID),
+        );
+        let event_man = create_basic_man_1().with_apid_map(apid_map);
+        let (event_tx, event_rx) = mpsc::channel::<PacketAsVec>();
+        let events = [
+            PAYLOAD_EVENT,
+            INFO_EVENT.into(),
+            PAYLOAD_EVENT,
+            LOW_SEV_EVENT,
+        ];
+        for event in events {
+            assert!(event_man
+                .generate_pus_event_tm_generic(&event_tx, &EMPTY_STAMP, event, None)
+                .expect("generating event TM failed"));
+        }
+        let expected = [
+            (
+                PAYLOAD_APID,
+                PAYLOAD_SENDER_ID,
+                0,
+                Subservice::TmMediumSeverityReport,
+            ),
+            (TEST_APID, TEST_ID.raw(), 0, Subservice::TmInfoReport),
+            (
+                PAYLOAD_APID,
+                PAYLOAD_SENDER_ID,
+                1,
+                Subservice::TmMediumSeverityReport,
+            ),
+            (TEST_APID, TEST_ID.raw(), 1, Subservice::TmLowSeverityReport),
+        ];
+        for (apid, sender_id, msg_counter, subservice) in expected {
+            let event_tm = event_rx.try_recv().expect("no event received");
+            assert_eq!(event_tm.sender_id, sender_id);
+            let (tm, _) = PusTmReader::new(&event_tm.packet, 7).expect("reading TM failed");
+            assert_eq!(tm.apid(), apid);
+            assert_eq!(tm.msg_counter(), msg_counter);
+            assert_eq!(tm.subservice(), subservice as u8);
+        }
+        assert!(event_rx.try_recv().is_err());
+        event_man.apid_map().unwrap().reset_msg_counters();
+        assert_eq!(
+            event_man.apid_map().unwrap().next_msg_counter(PAYLOAD_APID),
+            0
+        );
     }
 }
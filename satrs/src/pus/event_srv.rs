@@ -1,12 +1,15 @@
 use crate::events::EventU32;
+use crate::pus::event_log::EventLogSubservice;
 use crate::pus::event_man::{EventRequest, EventRequestWithToken};
-use crate::pus::verification::TcStateToken;
+use crate::pus::verification::{TcStateAccepted, TcStateToken, VerificationToken};
 use crate::pus::{DirectPusPacketHandlerResult, PartialPusHandlingError, PusPacketHandlingError};
 use crate::queue::GenericSendError;
 use spacepackets::ecss::event::Subservice;
+use spacepackets::ecss::tc::PusTcReader;
 use spacepackets::ecss::PusPacket;
 use std::sync::mpsc::Sender;
 
+use super::harness::{PusServiceContext, PusServiceCore};
 use super::verification::VerificationReportingProvider;
 use super::{
     EcssTcInMemConverter, EcssTcReceiver, EcssTmSender, GenericConversionError,
@@ -21,7 +24,7 @@ pub struct PusEventServiceHandler<
 > {
     pub service_helper:
         PusServiceHelper<TcReceiver, TmSender, TcInMemConverter, VerificationReporter>,
-    event_request_tx: Sender<EventRequestWithToken>,
+    core: PusEventServiceCore,
 }
 
 impl<
@@ -42,7 +45,7 @@ impl<
     ) -> Self {
         Self {
             service_helper,
-            event_request_tx,
+            core: PusEventServiceCore::new(event_request_tx),
         }
     }
 
@@ -51,93 +54,119 @@ impl<
         mut error_callback: ErrorCb,
         time_stamp: &[u8],
     ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
-        let possible_packet = self.service_helper.retrieve_and_accept_next_packet()?;
-        if possible_packet.is_none() {
-            return Ok(HandlingStatus::Empty.into());
-        }
-        let ecss_tc_and_token = possible_packet.unwrap();
-        self.service_helper
-            .tc_in_mem_converter_mut()
-            .cache(&ecss_tc_and_token.tc_in_memory)?;
-        let tc = self.service_helper.tc_in_mem_converter().convert()?;
+        self.service_helper.poll_and_handle_next_tc_with_core(
+            &mut self.core,
+            &mut error_callback,
+            time_stamp,
+        )
+    }
+}
+
+/// [PusServiceCore] of the PUS 5 event service. The event requests are forwarded to the event
+/// manager, which is also responsible for the completion verification reporting.
+pub struct PusEventServiceCore {
+    event_request_tx: Sender<EventRequestWithToken>,
+}
+
+impl PusEventServiceCore {
+    pub fn new(event_request_tx: Sender<EventRequestWithToken>) -> Self {
+        Self { event_request_tx }
+    }
+
+    fn forward_request<
+        TmSender: EcssTmSender,
+        VerificationReporter: VerificationReportingProvider,
+    >(
+        &self,
+        ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+        request: EventRequest,
+        token: VerificationToken<TcStateAccepted>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) -> Result<(), PusPacketHandlingError> {
+        let token: TcStateToken = match ctx.start_success(token, error_callback) {
+            Some(start_token) => start_token.into(),
+            None => token.into(),
+        };
+        self.event_request_tx
+            .send(EventRequestWithToken { request, token })
+            .map_err(|_| {
+                PusPacketHandlingError::RequestRouting(GenericRoutingError::Send(
+                    GenericSendError::RxDisconnected,
+                ))
+            })
+    }
+}
+
+impl<TmSender: EcssTmSender, VerificationReporter: VerificationReportingProvider>
+    PusServiceCore<TmSender, VerificationReporter> for PusEventServiceCore
+{
+    fn service(&self) -> u8 {
+        5
+    }
+
+    fn check_subservice(&self, subservice: u8) -> bool {
+        EventLogSubservice::try_from(subservice).is_ok() || Subservice::try_from(subservice).is_ok()
+    }
+
+    fn handle_request(
+        &mut self,
+        ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+        tc: &PusTcReader,
+        token: VerificationToken<TcStateAccepted>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
         let subservice = tc.subservice();
-        let srv = Subservice::try_from(subservice);
-        if srv.is_err() {
-            return Ok(DirectPusPacketHandlerResult::CustomSubservice(
-                tc.subservice(),
-                ecss_tc_and_token.token,
-            ));
-        }
-        let mut handle_enable_disable_request =
-            |enable: bool| -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
-                if tc.user_data().len() < 4 {
-                    return Err(GenericConversionError::NotEnoughAppData {
-                        expected: 4,
-                        found: tc.user_data().len(),
-                    }
-                    .into());
+        if let Ok(event_log_srv) = EventLogSubservice::try_from(subservice) {
+            let request = match event_log_srv {
+                EventLogSubservice::TcDumpEventLog => EventRequest::DumpLog,
+                EventLogSubservice::TcReplayEventLog => EventRequest::ReplayLog,
+                EventLogSubservice::TcClearEventLog => EventRequest::ClearLog,
+                EventLogSubservice::TmEventLogRecord => {
+                    return Err(PusPacketHandlingError::RequestConversion(
+                        GenericConversionError::WrongService(subservice),
+                    ))
                 }
-                let user_data = tc.user_data();
-                let event_u32 =
-                    EventU32::from(u32::from_be_bytes(user_data[0..4].try_into().unwrap()));
-                let mut token: TcStateToken = ecss_tc_and_token.token.into();
-                match self.service_helper.common.verif_reporter.start_success(
-                    &self.service_helper.common.tm_sender,
-                    ecss_tc_and_token.token,
-                    time_stamp,
-                ) {
-                    Ok(start_token) => {
-                        token = start_token.into();
-                    }
-                    Err(e) => {
-                        error_callback(&PartialPusHandlingError::Verification(e));
-                    }
-                }
-
-                let event_req_with_token = if enable {
-                    EventRequestWithToken {
-                        request: EventRequest::Enable(event_u32),
-                        token,
-                    }
-                } else {
-                    EventRequestWithToken {
-                        request: EventRequest::Disable(event_u32),
-                        token,
-                    }
-                };
-                self.event_request_tx
-                    .send(event_req_with_token)
-                    .map_err(|_| {
-                        PusPacketHandlingError::RequestRouting(GenericRoutingError::Send(
-                            GenericSendError::RxDisconnected,
-                        ))
-                    })?;
-                Ok(HandlingStatus::HandledOne.into())
             };
+            self.forward_request(ctx, request, token, error_callback)?;
+            return Ok(HandlingStatus::HandledOne.into());
+        }
+        let srv = match Subservice::try_from(subservice) {
+            Ok(srv) => srv,
+            Err(_) => {
+                return Ok(DirectPusPacketHandlerResult::CustomSubservice(
+                    subservice, token,
+                ))
+            }
+        };
+        let event_from_user_data = || -> Result<EventU32, PusPacketHandlingError> {
+            if tc.user_data().len() < 4 {
+                return Err(GenericConversionError::NotEnoughAppData {
+                    expected: 4,
+                    found: tc.user_data().len(),
+                }
+                .into());
+            }
+            let user_data = tc.user_data();
+            Ok(EventU32::from(u32::from_be_bytes(
+                user_data[0..4].try_into().unwrap(),
+            )))
+        };
 
-        match srv.unwrap() {
+        let request = match srv {
             Subservice::TmInfoReport
             | Subservice::TmLowSeverityReport
             | Subservice::TmMediumSeverityReport
-            | Subservice::TmHighSeverityReport => {
+            | Subservice::TmHighSeverityReport
+            | Subservice::TmDisabledEventsReport => {
                 return Err(PusPacketHandlingError::RequestConversion(
-                    GenericConversionError::WrongService(tc.subservice()),
+                    GenericConversionError::WrongService(subservice),
                 ))
             }
-            Subservice::TcEnableEventGeneration => {
-                handle_enable_disable_request(true)?;
-            }
-            Subservice::TcDisableEventGeneration => {
-                handle_enable_disable_request(false)?;
-            }
-            Subservice::TcReportDisabledList | Subservice::TmDisabledEventsReport => {
-                return Ok(DirectPusPacketHandlerResult::SubserviceNotImplemented(
-                    subservice,
-                    ecss_tc_and_token.token,
-                ));
-            }
-        }
-
+            Subservice::TcEnableEventGeneration => EventRequest::Enable(event_from_user_data()?),
+            Subservice::TcDisableEventGeneration => EventRequest::Disable(event_from_user_data()?),
+            Subservice::TcReportDisabledList => EventRequest::ReportDisabledList,
+        };
+        self.forward_request(ctx, request, token, error_callback)?;
         Ok(HandlingStatus::HandledOne.into())
     }
 }
@@ -157,6 +186,7 @@ mod tests {
     };
     use std::sync::mpsc::{self, Sender};
 
+    use crate::pus::event_log::EventLogSubservice;
     use crate::pus::event_man::EventRequest;
     use crate::pus::test_util::{PusTestHarness, SimplePusPacketHandler, TEST_APID};
     use crate::pus::verification::{
@@ -283,6 +313,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_report_disabled_list() {
+        let (event_request_tx, event_request_rx) = mpsc::channel();
+        let mut test_harness = Pus5HandlerWithStoreTester::new(event_request_tx);
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header =
+            PusTcSecondaryHeader::new_simple(5, Subservice::TcReportDisabledList as u8);
+        let tc = PusTcCreator::new_no_app_data(sp_header, sec_header, true);
+        let token = test_harness.init_verification(&tc);
+        test_harness.send_tc(&token, &tc);
+        let request_id = token.request_id();
+        test_harness.handle_one_tc().unwrap();
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        assert!(test_harness.check_no_tm_available());
+        let event_request = event_request_rx
+            .try_recv()
+            .expect("no event request received");
+        assert_eq!(event_request.request, EventRequest::ReportDisabledList);
+    }
+
+    #[test]
+    fn test_event_log_requests() {
+        for (subservice, expected_request) in [
+            (EventLogSubservice::TcDumpEventLog, EventRequest::DumpLog),
+            (
+                EventLogSubservice::TcReplayEventLog,
+                EventRequest::ReplayLog,
+            ),
+            (EventLogSubservice::TcClearEventLog, EventRequest::ClearLog),
+        ] {
+            let (event_request_tx, event_request_rx) = mpsc::channel();
+            let mut test_harness = Pus5HandlerWithStoreTester::new(event_request_tx);
+            let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+            let sec_header = PusTcSecondaryHeader::new_simple(5, subservice as u8);
+            let tc = PusTcCreator::new_no_app_data(sp_header, sec_header, true);
+            let token = test_harness.init_verification(&tc);
+            test_harness.send_tc(&token, &tc);
+            let request_id = token.request_id();
+            test_harness.handle_one_tc().unwrap();
+            test_harness.check_next_verification_tm(1, request_id);
+            test_harness.check_next_verification_tm(3, request_id);
+            assert!(test_harness.check_no_tm_available());
+            let event_request = event_request_rx
+                .try_recv()
+                .expect("no event request received");
+            assert_eq!(event_request.request, expected_request);
+        }
+    }
+
     #[test]
     fn test_empty_tc_queue() {
         let (event_request_tx, _) = mpsc::channel();
@@ -0,0 +1,581 @@
+//! # Multithreaded executor for PUS service handlers
+//!
+//! The simplest way to drive PUS service handlers is one thread which polls all handlers in a
+//! loop until all of them are idle. However, one busy handler, for example a memory dump or
+//! file transfer service, can then delay lightweight services like the PUS 17 test service for
+//! a long time.
+//!
+//! The [PusServiceExecutor] drives many handlers from a configurable number of worker threads.
+//! Each worker owns a run queue of services and steals services from the other workers when its
+//! own queue is empty, so busy services are spread across the available workers.
+//!
+//! - **Fairness**: A service is polled at most [PusExecutorConfig::polls_per_turn] times before
+//!   it is re-queued behind all other services of the run queue.
+//! - **Idle backoff**: When a service has nothing to handle, it is not polled again for
+//!   [PusExecutorConfig::min_idle_backoff]. The backoff is doubled for every further idle poll
+//!   up to [PusExecutorConfig::max_idle_backoff] and reset as soon as the service handles
+//!   something. Workers sleep when no service is due. [PusServiceExecutorHandle::wake_all] can
+//!   be used to poll all services immediately, for example after new telecommands were routed.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::atomic::{AtomicU32, Ordering};
+//! use std::sync::Arc;
+//! use satrs::executable::ShutdownSignal;
+//! use satrs::pus::executor::{PusExecutorConfig, PusServiceExecutor};
+//! use satrs::pus::HandlingStatus;
+//!
+//! let pending = Arc::new(AtomicU32::new(5));
+//! let pending_shared = pending.clone();
+//! let mut executor = PusServiceExecutor::new(
+//!     "pus",
+//!     PusExecutorConfig::default(),
+//!     ShutdownSignal::new(),
+//! );
+//! executor.add_service("test", move || {
+//!     if pending_shared.load(Ordering::Relaxed) == 0 {
+//!         return HandlingStatus::Empty;
+//!     }
+//!     pending_shared.fetch_sub(1, Ordering::Relaxed);
+//!     HandlingStatus::HandledOne
+//! });
+//! let handle = executor.spawn().unwrap();
+//! while pending.load(Ordering::Relaxed) > 0 {
+//!     std::thread::yield_now();
+//! }
+//! handle.stop().unwrap();
+//! ```
+use std::boxed::Box;
+use std::collections::VecDeque;
+use std::format;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use crate::executable::ShutdownSignal;
+
+use super::HandlingStatus;
+
+/// Service handler which can be driven by the [PusServiceExecutor].
+///
+/// This trait is implemented for all closures returning a [HandlingStatus], so existing
+/// handlers can be wrapped easily, for example by calling
+/// [PusService17TestHandler::poll_and_handle_next_tc](crate::pus::test::PusService17TestHandler::poll_and_handle_next_tc)
+/// inside a closure.
+pub trait PusServicePoller: Send {
+    /// Handle at most one telecommand or reply.
+    fn poll(&mut self) -> HandlingStatus;
+
+    /// Called after the service reported [HandlingStatus::Empty]. This can be used for
+    /// periodic work which only needs to be done when the service is idle, for example request
+    /// timeout checks.
+    fn on_idle(&mut self) {}
+}
+
+impl<F: FnMut() -> HandlingStatus + Send> PusServicePoller for F {
+    fn poll(&mut self) -> HandlingStatus {
+        self()
+    }
+}
+
+/// Configuration of the [PusServiceExecutor].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PusExecutorConfig {
+    /// Number of worker threads. Must be larger than 0.
+    pub num_threads: usize,
+    /// Maximum number of consecutive polls of one service before the next service is polled.
+    /// Must be larger than 0.
+    pub polls_per_turn: u32,
+    pub min_idle_backoff: Duration,
+    pub max_idle_backoff: Duration,
+}
+
+impl Default for PusExecutorConfig {
+    fn default() -> Self {
+        Self {
+            num_threads: 2,
+            polls_per_turn: 8,
+            min_idle_backoff: Duration::from_millis(1),
+            max_idle_backoff: Duration::from_millis(20),
+        }
+    }
+}
+
+/// Statistics of one service driven by the [PusServiceExecutor].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PusServiceStats {
+    pub name: &'static str,
+    /// Number of [PusServicePoller::poll] calls.
+    pub num_polls: u64,
+    /// Number of polls which returned [HandlingStatus::HandledOne].
+    pub num_handled: u64,
+}
+
+struct ServiceSlot {
+    service: Box<dyn PusServicePoller>,
+    backoff: Duration,
+    next_poll: Instant,
+    wake_generation: u64,
+}
+
+#[derive(Default)]
+struct ServiceCounters {
+    num_polls: AtomicU64,
+    num_handled: AtomicU64,
+}
+
+enum TurnResult {
+    Polled,
+    NotDue(Instant),
+}
+
+struct ExecutorShared {
+    cfg: PusExecutorConfig,
+    names: Vec<&'static str>,
+    services: Vec<Mutex<ServiceSlot>>,
+    counters: Vec<ServiceCounters>,
+    /// One run queue of service indices per worker. Every service index is either contained in
+    /// exactly one run queue or currently handled by a worker.
+    run_queues: Vec<Mutex<VecDeque<usize>>>,
+    wake_generation: AtomicU64,
+    idle_lock: Mutex<()>,
+    idle_cond: Condvar,
+    shutdown: ShutdownSignal,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A panicking service only poisons its own slot, which is never polled again.
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl ExecutorShared {
+    /// Pop the next service of the own run queue, or steal one from the back of the run queue
+    /// of another worker.
+    fn next_service(&self, worker: usize) -> Option<usize> {
+        if let Some(idx) = lock(&self.run_queues[worker]).pop_front() {
+            return Some(idx);
+        }
+        let num_queues = self.run_queues.len();
+        (1..num_queues)
+            .map(|offset| (worker + offset) % num_queues)
+            .find_map(|victim| lock(&self.run_queues[victim]).pop_back())
+    }
+
+    fn run_turn(&self, idx: usize) -> TurnResult {
+        let mut slot = lock(&self.services[idx]);
+        let now = Instant::now();
+        let wake_generation = self.wake_generation.load(Ordering::Acquire);
+        if slot.wake_generation != wake_generation {
+            slot.wake_generation = wake_generation;
+            slot.backoff = Duration::ZERO;
+            slot.next_poll = now;
+        }
+        if slot.next_poll > now {
+            return TurnResult::NotDue(slot.next_poll);
+        }
+        let counters = &self.counters[idx];
+        let mut num_handled = 0;
+        for _ in 0..self.cfg.polls_per_turn {
+            counters.num_polls.fetch_add(1, Ordering::Relaxed);
+            if slot.service.poll() == HandlingStatus::Empty {
+                break;
+            }
+            counters.num_handled.fetch_add(1, Ordering::Relaxed);
+            num_handled += 1;
+        }
+        if num_handled == 0 {
+            slot.service.on_idle();
+            slot.backoff = if slot.backoff.is_zero() {
+                self.cfg.min_idle_backoff
+            } else {
+                (slot.backoff * 2).min(self.cfg.max_idle_backoff)
+            };
+            slot.next_poll = Instant::now() + slot.backoff;
+        } else {
+            slot.backoff = Duration::ZERO;
+            slot.next_poll = now;
+        }
+        TurnResult::Polled
+    }
+
+    /// Sleep for the given time unless a wake-up or shutdown was requested in the meantime.
+    fn idle_wait(&self, seen_generation: u64, timeout: Duration) {
+        let guard = lock(&self.idle_lock);
+        if self.shutdown.shutdown_requested()
+            || self.wake_generation.load(Ordering::Acquire) != seen_generation
+        {
+            return;
+        }
+        let _ = self.idle_cond.wait_timeout(guard, timeout);
+    }
+
+    fn notify_all(&self) {
+        let _guard = lock(&self.idle_lock);
+        self.wake_generation.fetch_add(1, Ordering::AcqRel);
+        self.idle_cond.notify_all();
+    }
+
+    fn run_worker(&self, worker: usize) {
+        let mut num_not_due = 0;
+        let mut earliest_due: Option<Instant> = None;
+        let mut seen_generation = self.wake_generation.load(Ordering::Acquire);
+        while !self.shutdown.shutdown_requested() {
+            let idx = match self.next_service(worker) {
+                Some(idx) => idx,
+                None => {
+                    // All services are currently handled by other workers.
+                    self.idle_wait(seen_generation, self.cfg.min_idle_backoff);
+                    seen_generation = self.wake_generation.load(Ordering::Acquire);
+                    continue;
+                }
+            };
+            let turn_result = self.run_turn(idx);
+            lock(&self.run_queues[worker]).push_back(idx);
+            match turn_result {
+                TurnResult::Polled => {
+                    num_not_due = 0;
+                    earliest_due = None;
+                }
+                TurnResult::NotDue(next_poll) => {
+                    num_not_due += 1;
+                    let earliest = earliest_due.map_or(next_poll, |due| due.min(next_poll));
+                    earliest_due = Some(earliest);
+                    if num_not_due >= self.services.len() {
+                        let timeout = earliest
+                            .saturating_duration_since(Instant::now())
+                            .min(self.cfg.max_idle_backoff);
+                        self.idle_wait(seen_generation, timeout);
+                        seen_generation = self.wake_generation.load(Ordering::Acquire);
+                        num_not_due = 0;
+                        earliest_due = None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Executor which drives PUS service handlers from a pool of worker threads.
+///
+/// Please refer to the [module documentation](self) for details on the scheduling.
+pub struct PusServiceExecutor {
+    name: &'static str,
+    cfg: PusExecutorConfig,
+    services: Vec<(&'static str, Box<dyn PusServicePoller>)>,
+    shutdown: ShutdownSignal,
+}
+
+impl PusServiceExecutor {
+    pub fn new(name: &'static str, cfg: PusExecutorConfig, shutdown: ShutdownSignal) -> Self {
+        assert!(
+            cfg.num_threads > 0,
+            "number of threads must be larger than 0"
+        );
+        assert!(
+            cfg.polls_per_turn > 0,
+            "polls per turn must be larger than 0"
+        );
+        Self {
+            name,
+            cfg,
+            services: Vec::new(),
+            shutdown,
+        }
+    }
+
+    /// Add a service. The name is only used for the [PusServiceStats].
+    pub fn add_service(&mut self, name: &'static str, service: impl PusServicePoller + 'static) {
+        self.services.push((name, Box::new(service)));
+    }
+
+    pub fn num_services(&self) -> usize {
+        self.services.len()
+    }
+
+    pub fn cfg(&self) -> &PusExecutorConfig {
+        &self.cfg
+    }
+
+    pub fn shutdown_signal(&self) -> &ShutdownSignal {
+        &self.shutdown
+    }
+
+    /// Spawn the worker threads. The threads are named after the executor with the worker index
+    /// as a suffix. They run until a shutdown is requested with the [ShutdownSignal] or with
+    /// [PusServiceExecutorHandle::stop].
+    pub fn spawn(self) -> Result<PusServiceExecutorHandle, io::Error> {
+        let now = Instant::now();
+        let num_threads = self.cfg.num_threads;
+        let mut run_queues: Vec<VecDeque<usize>> =
+            (0..num_threads).map(|_| VecDeque::new()).collect();
+        let mut names = Vec::with_capacity(self.services.len());
+        let mut services = Vec::with_capacity(self.services.len());
+        for (idx, (name, service)) in self.services.into_iter().enumerate() {
+            run_queues[idx % num_threads].push_back(idx);
+            names.push(name);
+            services.push(Mutex::new(ServiceSlot {
+                service,
+                backoff: Duration::ZERO,
+                next_poll: now,
+                wake_generation: 0,
+            }));
+        }
+        let shared = Arc::new(ExecutorShared {
+            cfg: self.cfg,
+            counters: (0..services.len())
+                .map(|_| ServiceCounters::default())
+                .collect(),
+            names,
+            services,
+            run_queues: run_queues.into_iter().map(Mutex::new).collect(),
+            wake_generation: AtomicU64::new(0),
+            idle_lock: Mutex::new(()),
+            idle_cond: Condvar::new(),
+            shutdown: self.shutdown,
+        });
+        let mut workers = Vec::with_capacity(num_threads);
+        for worker in 0..num_threads {
+            let worker_shared = shared.clone();
+            match thread::Builder::new()
+                .name(format!("{}-{}", self.name, worker))
+                .spawn(move || worker_shared.run_worker(worker))
+            {
+                Ok(join_handle) => workers.push(join_handle),
+                Err(e) => {
+                    shared.shutdown.request_shutdown();
+                    shared.notify_all();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(PusServiceExecutorHandle { shared, workers })
+    }
+}
+
+/// Handle of a spawned [PusServiceExecutor].
+pub struct PusServiceExecutorHandle {
+    shared: Arc<ExecutorShared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl PusServiceExecutorHandle {
+    /// Poll all services as soon as possible, independently of their idle backoff.
+    pub fn wake_all(&self) {
+        self.shared.notify_all();
+    }
+
+    pub fn shutdown_signal(&self) -> &ShutdownSignal {
+        &self.shared.shutdown
+    }
+
+    /// Statistics of all services in the order they were added.
+    pub fn stats(&self) -> Vec<PusServiceStats> {
+        self.shared
+            .names
+            .iter()
+            .zip(self.shared.counters.iter())
+            .map(|(name, counters)| PusServiceStats {
+                name: *name,
+                num_polls: counters.num_polls.load(Ordering::Relaxed),
+                num_handled: counters.num_handled.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Request a shutdown and wait for all workers to finish.
+    pub fn stop(self) -> thread::Result<()> {
+        self.shared.shutdown.request_shutdown();
+        self.shared.notify_all();
+        self.join()
+    }
+
+    /// Wait for all workers to finish, which requires a shutdown request using the
+    /// [ShutdownSignal]. Returns the error of the first worker which panicked.
+    pub fn join(self) -> thread::Result<()> {
+        let mut result = Ok(());
+        for worker in self.workers {
+            let worker_result = worker.join();
+            if result.is_ok() {
+                result = worker_result;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn wait_for(mut condition: impl FnMut() -> bool) {
+        let start = Instant::now();
+        while !condition() {
+            assert!(start.elapsed() < TIMEOUT, "condition not met until timeout");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn pending_work(pending: Arc<AtomicU32>) -> impl PusServicePoller {
+        move || {
+            if pending
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |p| p.checked_sub(1))
+                .is_ok()
+            {
+                HandlingStatus::HandledOne
+            } else {
+                HandlingStatus::Empty
+            }
+        }
+    }
+
+    #[test]
+    fn test_heavy_service_does_not_starve_light_service() {
+        let cfg = PusExecutorConfig {
+            num_threads: 1,
+            polls_per_turn: 4,
+            ..Default::default()
+        };
+        let mut executor = PusServiceExecutor::new("pus", cfg, ShutdownSignal::new());
+        let heavy_polls = Arc::new(AtomicU32::new(0));
+        let heavy_polls_shared = heavy_polls.clone();
+        executor.add_service("heavy", move || {
+            heavy_polls_shared.fetch_add(1, Ordering::Relaxed);
+            HandlingStatus::HandledOne
+        });
+        let light_pending = Arc::new(AtomicU32::new(10));
+        let heavy_polls_at_completion = Arc::new(AtomicU32::new(u32::MAX));
+        let light_pending_shared = light_pending.clone();
+        let heavy_polls_shared = heavy_polls.clone();
+        let completion_shared = heavy_polls_at_completion.clone();
+        let mut light_service = pending_work(light_pending.clone());
+        executor.add_service("light", move || {
+            let status = light_service.poll();
+            if light_pending_shared.load(Ordering::Relaxed) == 0 {
+                let _ = completion_shared.compare_exchange(
+                    u32::MAX,
+                    heavy_polls_shared.load(Ordering::Relaxed),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+            }
+            status
+        });
+        let handle = executor.spawn().unwrap();
+        wait_for(|| light_pending.load(Ordering::Relaxed) == 0);
+        let stats = handle.stats();
+        handle.stop().unwrap();
+        // Both services alternate after 4 polls each.
+        assert_eq!(heavy_polls_at_completion.load(Ordering::Relaxed), 12);
+        assert_eq!(stats[0].name, "heavy");
+        assert_eq!(stats[1].name, "light");
+        assert_eq!(stats[1].num_handled, 10);
+    }
+
+    #[test]
+    fn test_idle_backoff() {
+        let cfg = PusExecutorConfig {
+            num_threads: 1,
+            min_idle_backoff: Duration::from_millis(5),
+            max_idle_backoff: Duration::from_millis(20),
+            ..Default::default()
+        };
+        struct IdleService {
+            num_idle: Arc<AtomicU32>,
+        }
+        impl PusServicePoller for IdleService {
+            fn poll(&mut self) -> HandlingStatus {
+                HandlingStatus::Empty
+            }
+            fn on_idle(&mut self) {
+                self.num_idle.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let num_idle = Arc::new(AtomicU32::new(0));
+        let mut executor = PusServiceExecutor::new("pus", cfg, ShutdownSignal::new());
+        executor.add_service(
+            "idle",
+            IdleService {
+                num_idle: num_idle.clone(),
+            },
+        );
+        let handle = executor.spawn().unwrap();
+        thread::sleep(Duration::from_millis(100));
+        let stats = handle.stats();
+        handle.stop().unwrap();
+        // Backoff sequence 5, 10, 20, 20, ... ms.
+        assert!(stats[0].num_polls >= 2, "{:?}", stats[0]);
+        assert!(stats[0].num_polls <= 8, "{:?}", stats[0]);
+        assert_eq!(stats[0].num_handled, 0);
+        assert!(num_idle.load(Ordering::Relaxed) as u64 >= stats[0].num_polls - 1);
+    }
+
+    #[test]
+    fn test_wake_all() {
+        let cfg = PusExecutorConfig {
+            num_threads: 1,
+            min_idle_backoff: Duration::from_secs(60),
+            max_idle_backoff: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let pending = Arc::new(AtomicU32::new(0));
+        let mut executor = PusServiceExecutor::new("pus", cfg, ShutdownSignal::new());
+        executor.add_service("service", pending_work(pending.clone()));
+        let handle = executor.spawn().unwrap();
+        wait_for(|| handle.stats()[0].num_polls == 1);
+        pending.store(3, Ordering::Relaxed);
+        handle.wake_all();
+        wait_for(|| pending.load(Ordering::Relaxed) == 0);
+        assert_eq!(handle.stats()[0].num_handled, 3);
+        handle.stop().unwrap();
+    }
+
+    #[test]
+    fn test_multiple_workers() {
+        let cfg = PusExecutorConfig {
+            num_threads: 3,
+            ..Default::default()
+        };
+        let mut executor = PusServiceExecutor::new("pus", cfg, ShutdownSignal::new());
+        let pending: Vec<_> = (0..6).map(|_| Arc::new(AtomicU32::new(100))).collect();
+        for service_pending in &pending {
+            executor.add_service("service", pending_work(service_pending.clone()));
+        }
+        assert_eq!(executor.num_services(), 6);
+        let handle = executor.spawn().unwrap();
+        wait_for(|| pending.iter().all(|p| p.load(Ordering::Relaxed) == 0));
+        for stats in handle.stats() {
+            assert_eq!(stats.num_handled, 100);
+        }
+        handle.stop().unwrap();
+    }
+
+    #[test]
+    fn test_external_shutdown_signal() {
+        let shutdown = ShutdownSignal::new();
+        let mut executor =
+            PusServiceExecutor::new("pus", PusExecutorConfig::default(), shutdown.clone());
+        executor.add_service("service", || HandlingStatus::Empty);
+        let handle = executor.spawn().unwrap();
+        shutdown.request_shutdown();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_panicking_service() {
+        let mut executor =
+            PusServiceExecutor::new("pus", PusExecutorConfig::default(), ShutdownSignal::new());
+        executor.add_service("service", || -> HandlingStatus {
+            panic!("service failure")
+        });
+        let handle = executor.spawn().unwrap();
+        wait_for(|| handle.stats()[0].num_polls == 1);
+        assert!(handle.stop().is_err());
+    }
+}
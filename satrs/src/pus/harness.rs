@@ -0,0 +1,525 @@
+//! # Generic PUS service handler harness
+//!
+//! Most PUS service handlers share the same skeleton: Poll the next accepted telecommand,
+//! convert it from its memory representation, check the service number, generate a timestamp
+//! and perform the verification reporting for the request. The [PusServiceHarness] performs
+//! these common steps, and dispatches the telecommand to a [PusServiceCore] implementation, which
+//! only contains the service-specific logic.
+//!
+//! The [PusServiceContext] passed to the core provides the TM sender, the verification reporter
+//! and the timestamp, as well as some convenience methods for the verification reporting.
+//! The acknowledgement flags of each telecommand are registered at the verification reporter
+//! before the telecommand is dispatched, so reporters which honor the flags, for example the
+//! [crate::pus::verification::VerificationReporter] with a configured
+//! [crate::pus::verification::AckFlagPolicy], skip the success reports which were not requested.
+//! The source ID of the telecommand is attached to the verification token passed to the core, so
+//! it is available for authorization decisions and to all components the token is passed on to.
+//!
+//! The harness logic is also available as the
+//! [PusServiceHelper::poll_and_handle_next_tc_with_core] method, which can be used by handlers
+//! which need to pass additional per-call state to their core.
+use satrs_shared::res_code::ResultU16;
+use spacepackets::ecss::tc::{GenericPusTcSecondaryHeader, PusTcReader};
+use spacepackets::ecss::PusPacket;
+
+use crate::time::RawTimestamp;
+use crate::ComponentId;
+
+use super::verification::{
+    FailParams, TcStateAccepted, TcStateStarted, VerificationReportingProvider, VerificationToken,
+};
+use super::{
+    DirectPusPacketHandlerResult, EcssTcInMemConverter, EcssTcReceiver, EcssTmSender,
+    GenericConversionError, HandlingStatus, PartialPusHandlingError, PusPacketHandlingError,
+    PusServiceHelper,
+};
+
+/// Context of a single telecommand which is passed to the [PusServiceCore].
+pub struct PusServiceContext<'a, TmSender: EcssTmSender, VerificationReporter> {
+    /// ID of the service handler component, which should be used as the sender ID of the
+    /// generated telemetry.
+    pub id: ComponentId,
+    pub tm_sender: &'a TmSender,
+    pub verif_reporter: &'a VerificationReporter,
+    pub time_stamp: &'a [u8],
+}
+
+impl<TmSender: EcssTmSender, VerificationReporter: VerificationReportingProvider>
+    PusServiceContext<'_, TmSender, VerificationReporter>
+{
+    /// Report the start success of the telecommand. Returns [None] and reports the error using
+    /// the error callback if the verification report could not be sent.
+    pub fn start_success(
+        &self,
+        token: VerificationToken<TcStateAccepted>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) -> Option<VerificationToken<TcStateStarted>> {
+        match self
+            .verif_reporter
+            .start_success(self.tm_sender, token, self.time_stamp)
+        {
+            Ok(started_token) => Some(started_token),
+            Err(e) => {
+                error_callback(&PartialPusHandlingError::Verification(e));
+                None
+            }
+        }
+    }
+
+    /// Report the completion success of the telecommand if a started token is available.
+    pub fn completion_success(
+        &self,
+        opt_started_token: Option<VerificationToken<TcStateStarted>>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) {
+        self.completion(opt_started_token, None, error_callback)
+    }
+
+    /// Report the completion failure of the telecommand if a started token is available.
+    pub fn completion_failure(
+        &self,
+        opt_started_token: Option<VerificationToken<TcStateStarted>>,
+        failure_code: &ResultU16,
+        failure_data: &[u8],
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) {
+        self.completion(
+            opt_started_token,
+            Some((failure_code, failure_data)),
+            error_callback,
+        )
+    }
+
+    /// Report the completion success if no failure is passed, and a completion failure with the
+    /// given failure code and failure data otherwise.
+    pub fn completion(
+        &self,
+        opt_started_token: Option<VerificationToken<TcStateStarted>>,
+        failure: Option<(&ResultU16, &[u8])>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) {
+        let started_token = match opt_started_token {
+            Some(token) => token,
+            None => return,
+        };
+        let result = match failure {
+            Some((failure_code, failure_data)) => self.verif_reporter.completion_failure(
+                self.tm_sender,
+                started_token,
+                FailParams::new(self.time_stamp, failure_code, failure_data),
+            ),
+            None => self.verif_reporter.completion_success(
+                self.tm_sender,
+                started_token,
+                self.time_stamp,
+            ),
+        };
+        if let Err(e) = result {
+            error_callback(&PartialPusHandlingError::Verification(e));
+        }
+    }
+}
+
+/// Service-specific logic of a PUS service handler which is driven by the [PusServiceHarness].
+pub trait PusServiceCore<
+    TmSender: EcssTmSender,
+    VerificationReporter: VerificationReportingProvider,
+>
+{
+    /// PUS service number handled by this core. Telecommands with a different service number
+    /// are rejected with a [GenericConversionError::WrongService] error.
+    fn service(&self) -> u8;
+
+    /// Check whether the subservice is handled by this core. Telecommands with subservices
+    /// which are not handled are returned to the caller as a
+    /// [DirectPusPacketHandlerResult::CustomSubservice].
+    fn check_subservice(&self, subservice: u8) -> bool;
+
+    /// Handle a telecommand with a valid service number and a handled subservice. The core is
+    /// responsible for the start and completion verification reporting.
+    fn handle_request(
+        &mut self,
+        ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+        tc: &PusTcReader,
+        token: VerificationToken<TcStateAccepted>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError>;
+}
+
+impl<
+        TcReceiver: EcssTcReceiver,
+        TmSender: EcssTmSender,
+        TcInMemConverter: EcssTcInMemConverter,
+        VerificationReporter: VerificationReportingProvider,
+    > PusServiceHelper<TcReceiver, TmSender, TcInMemConverter, VerificationReporter>
+{
+    /// Poll the next accepted telecommand, perform the common checks and dispatch it to the
+    /// given [PusServiceCore].
+    pub fn poll_and_handle_next_tc_with_core<
+        Core: PusServiceCore<TmSender, VerificationReporter>,
+    >(
+        &mut self,
+        core: &mut Core,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+        time_stamp: &[u8],
+    ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
+        let ecss_tc_and_token = match self.retrieve_and_accept_next_packet()? {
+            Some(tc_and_token) => tc_and_token,
+            None => return Ok(HandlingStatus::Empty.into()),
+        };
+        self.tc_in_mem_converter
+            .cache(&ecss_tc_and_token.tc_in_memory)?;
+        let tc = self.tc_in_mem_converter.convert()?;
+        if tc.service() != core.service() {
+            return Err(GenericConversionError::WrongService(tc.service()).into());
+        }
+        let token = ecss_tc_and_token
+            .token
+            .with_source_id(tc.source_id())
+            .with_ack_flags(tc.ack_flags());
+        if !core.check_subservice(tc.subservice()) {
+            return Ok(DirectPusPacketHandlerResult::CustomSubservice(
+                tc.subservice(),
+                token,
+            ));
+        }
+        let ctx = PusServiceContext {
+            id: self.common.id,
+            tm_sender: &self.common.tm_sender,
+            verif_reporter: &self.common.verif_reporter,
+            time_stamp,
+        };
+        core.handle_request(&ctx, &tc, token, error_callback)
+    }
+}
+
+/// Generic PUS service handler which combines a [PusServiceHelper] with a [PusServiceCore].
+pub struct PusServiceHarness<
+    TcReceiver: EcssTcReceiver,
+    TmSender: EcssTmSender,
+    TcInMemConverter: EcssTcInMemConverter,
+    VerificationReporter: VerificationReportingProvider,
+    Core: PusServiceCore<TmSender, VerificationReporter>,
+> {
+    pub service_helper:
+        PusServiceHelper<TcReceiver, TmSender, TcInMemConverter, VerificationReporter>,
+    pub core: Core,
+}
+
+impl<
+        TcReceiver: EcssTcReceiver,
+        TmSender: EcssTmSender,
+        TcInMemConverter: EcssTcInMemConverter,
+        VerificationReporter: VerificationReportingProvider,
+        Core: PusServiceCore<TmSender, VerificationReporter>,
+    > PusServiceHarness<TcReceiver, TmSender, TcInMemConverter, VerificationReporter, Core>
+{
+    pub fn new(
+        service_helper: PusServiceHelper<
+            TcReceiver,
+            TmSender,
+            TcInMemConverter,
+            VerificationReporter,
+        >,
+        core: Core,
+    ) -> Self {
+        Self {
+            service_helper,
+            core,
+        }
+    }
+
+    /// Poll and handle the next telecommand using the provided timestamp for the generated
+    /// telemetry.
+    pub fn poll_and_handle_next_tc<ErrorCb: FnMut(&PartialPusHandlingError)>(
+        &mut self,
+        mut error_callback: ErrorCb,
+        time_stamp: &[u8],
+    ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
+        self.service_helper.poll_and_handle_next_tc_with_core(
+            &mut self.core,
+            &mut error_callback,
+            time_stamp,
+        )
+    }
+
+    /// Poll and handle the next telecommand. The timestamp for the generated telemetry is
+    /// retrieved from the time source of the service helper using its timestamp format. A zeroed
+    /// timestamp is used if the time source fails, and the error is reported using the error
+    /// callback.
+    pub fn poll_and_handle_next_tc_with_current_time<ErrorCb: FnMut(&PartialPusHandlingError)>(
+        &mut self,
+        mut error_callback: ErrorCb,
+    ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
+        let time_stamp = self.service_helper.current_stamp().unwrap_or_else(|e| {
+            error_callback(&PartialPusHandlingError::TimeSource(e));
+            RawTimestamp::zeroed(self.service_helper.timestamp_format())
+        });
+        self.poll_and_handle_next_tc(error_callback, &time_stamp)
+    }
+
+    /// Handle all queued telecommands until the queue is empty or the timeout elapsed. This can
+    /// be used to drain the handler when the application is shut down. The first handling error
+    /// aborts the drain.
+    #[cfg(feature = "std")]
+    pub fn drain<ErrorCb: FnMut(&PartialPusHandlingError)>(
+        &mut self,
+        timeout: core::time::Duration,
+        mut error_callback: ErrorCb,
+    ) -> Result<crate::shutdown::DrainOutcome, PusPacketHandlingError> {
+        let mut result = Ok(());
+        let outcome = crate::shutdown::drain_with_timeout(timeout, || {
+            match self.poll_and_handle_next_tc_with_current_time(&mut error_callback) {
+                Ok(DirectPusPacketHandlerResult::Handled(HandlingStatus::Empty)) => {
+                    HandlingStatus::Empty
+                }
+                Ok(_) => HandlingStatus::HandledOne,
+                Err(e) => {
+                    result = Err(e);
+                    HandlingStatus::Empty
+                }
+            }
+        });
+        result.map(|_| outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spacepackets::ecss::tc::{AckOpts, PusTcCreator, PusTcSecondaryHeader};
+    use spacepackets::time::cuc::FractionalResolution;
+    use spacepackets::time::UnixTime;
+    use spacepackets::SpHeader;
+
+    use crate::pus::test_util::TEST_APID;
+    use crate::pus::tests::PusServiceHandlerWithVecCommon;
+    use crate::pus::verification::{AckFlagPolicy, RequestId, VerificationReporter};
+    use crate::pus::{EcssTcInVecConverter, MpscTcReceiver, MpscTmAsVecSender};
+    use crate::time::{FixedTimeSource, TimeSource, TimestampFormat};
+
+    use super::*;
+
+    const TEST_FAILURE_CODE: ResultU16 = ResultU16::new(1, 2);
+
+    /// Completes subservice 1 successfully and subservice 2 with a failure.
+    #[derive(Default)]
+    struct TestCore {
+        num_handled: u32,
+        last_source_id: Option<u16>,
+    }
+
+    impl<TmSender: EcssTmSender, VerificationReporter: VerificationReportingProvider>
+        PusServiceCore<TmSender, VerificationReporter> for TestCore
+    {
+        fn service(&self) -> u8 {
+            200
+        }
+
+        fn check_subservice(&self, subservice: u8) -> bool {
+            subservice == 1 || subservice == 2
+        }
+
+        fn handle_request(
+            &mut self,
+            ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+            tc: &PusTcReader,
+            token: VerificationToken<TcStateAccepted>,
+            error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+        ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
+            self.num_handled += 1;
+            self.last_source_id = token.source_id();
+            let opt_started_token = ctx.start_success(token, error_callback);
+            if tc.subservice() == 1 {
+                ctx.completion_success(opt_started_token, error_callback);
+            } else {
+                ctx.completion_failure(opt_started_token, &TEST_FAILURE_CODE, &[], error_callback);
+            }
+            Ok(HandlingStatus::HandledOne.into())
+        }
+    }
+
+    type TestHarness = PusServiceHarness<
+        MpscTcReceiver,
+        MpscTmAsVecSender,
+        EcssTcInVecConverter,
+        VerificationReporter,
+        TestCore,
+    >;
+
+    fn create_harness() -> (PusServiceHandlerWithVecCommon, TestHarness) {
+        let (common, service_helper) =
+            PusServiceHandlerWithVecCommon::new_with_standard_verif_reporter(0);
+        (
+            common,
+            PusServiceHarness::new(service_helper, TestCore::default()),
+        )
+    }
+
+    fn send_tc(
+        common: &PusServiceHandlerWithVecCommon,
+        harness: &mut TestHarness,
+        service: u8,
+        subservice: u8,
+    ) -> RequestId {
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(service, subservice);
+        let tc = PusTcCreator::new_no_app_data(sp_header, sec_header, true);
+        let helper = &mut harness.service_helper;
+        let init_token = helper.verif_reporter_mut().add_tc(&tc);
+        let token = helper
+            .verif_reporter()
+            .acceptance_success(helper.tm_sender(), init_token, &[0; 7])
+            .expect("acceptance success failure");
+        common.send_tc(helper.id(), &token, &tc);
+        token.request_id()
+    }
+
+    #[test]
+    fn test_success_and_failure() {
+        let (common, mut harness) = create_harness();
+        let request_id = send_tc(&common, &mut harness, 200, 1);
+        let result = harness
+            .poll_and_handle_next_tc(|e| panic!("partial error: {e:?}"), &[0; 7])
+            .unwrap();
+        assert!(matches!(
+            result,
+            DirectPusPacketHandlerResult::Handled(HandlingStatus::HandledOne)
+        ));
+        common.check_next_verification_tm(1, request_id);
+        common.check_next_verification_tm(3, request_id);
+        common.check_next_verification_tm(7, request_id);
+
+        let request_id = send_tc(&common, &mut harness, 200, 2);
+        harness
+            .poll_and_handle_next_tc(|e| panic!("partial error: {e:?}"), &[0; 7])
+            .unwrap();
+        common.check_next_verification_tm(1, request_id);
+        common.check_next_verification_tm(3, request_id);
+        common.check_next_verification_tm(8, request_id);
+        assert_eq!(harness.core.num_handled, 2);
+        let result = harness
+            .poll_and_handle_next_tc(|e| panic!("partial error: {e:?}"), &[0; 7])
+            .unwrap();
+        assert!(matches!(
+            result,
+            DirectPusPacketHandlerResult::Handled(HandlingStatus::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_ack_flags_and_source_id_are_attached() {
+        let (common, mut harness) = create_harness();
+        let helper = &mut harness.service_helper;
+        helper.verif_reporter_mut().ack_flag_policy = AckFlagPolicy::Honor;
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let ack_flags = AckOpts::Acceptance as u8 | AckOpts::Completion as u8;
+        let sec_header = PusTcSecondaryHeader::new(200, 1, ack_flags, 7);
+        let tc = PusTcCreator::new_no_app_data(sp_header, sec_header, true);
+        // Simulate a telecommand which was accepted by a different reporter, so the harness has
+        // to attach the acknowledgement flags and the source ID to the token.
+        let init_token = helper
+            .verif_reporter_mut()
+            .add_tc_with_req_id(RequestId::new(&tc));
+        let token = helper
+            .verif_reporter()
+            .acceptance_success(helper.tm_sender(), init_token, &[0; 7])
+            .expect("acceptance success failure");
+        common.send_tc(helper.id(), &token, &tc);
+        harness
+            .poll_and_handle_next_tc(|e| panic!("partial error: {e:?}"), &[0; 7])
+            .unwrap();
+        common.check_next_verification_tm(1, token.request_id());
+        common.check_next_verification_tm(7, token.request_id());
+        assert!(common.check_no_tm_available());
+        assert_eq!(harness.core.last_source_id, Some(7));
+    }
+
+    #[test]
+    fn test_wrong_service_and_custom_subservice() {
+        let (common, mut harness) = create_harness();
+        send_tc(&common, &mut harness, 17, 1);
+        let result = harness.poll_and_handle_next_tc(|_| (), &[0; 7]);
+        assert!(matches!(
+            result,
+            Err(PusPacketHandlingError::RequestConversion(
+                GenericConversionError::WrongService(17)
+            ))
+        ));
+        send_tc(&common, &mut harness, 200, 128);
+        let result = harness.poll_and_handle_next_tc(|_| (), &[0; 7]).unwrap();
+        assert!(matches!(
+            result,
+            DirectPusPacketHandlerResult::CustomSubservice(128, _)
+        ));
+        assert_eq!(harness.core.num_handled, 0);
+    }
+
+    #[test]
+    fn test_drain() {
+        let (common, mut harness) = create_harness();
+        let timeout = core::time::Duration::from_secs(1);
+        let outcome = harness
+            .drain(timeout, |e| panic!("partial error: {e:?}"))
+            .unwrap();
+        assert_eq!(outcome.num_drained, 0);
+        send_tc(&common, &mut harness, 200, 1);
+        send_tc(&common, &mut harness, 200, 2);
+        let outcome = harness
+            .drain(timeout, |e| panic!("partial error: {e:?}"))
+            .unwrap();
+        assert_eq!(outcome.num_drained, 2);
+        assert!(!outcome.timed_out);
+        assert_eq!(harness.core.num_handled, 2);
+
+        send_tc(&common, &mut harness, 17, 1);
+        assert!(matches!(
+            harness.drain(timeout, |_| ()),
+            Err(PusPacketHandlingError::RequestConversion(
+                GenericConversionError::WrongService(17)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_current_time_stamp() {
+        let (mut common, mut harness) = create_harness();
+        let time = UnixTime::new_only_secs(1_700_000_000);
+        harness
+            .service_helper
+            .set_time_source(FixedTimeSource::new(time));
+        let request_id = send_tc(&common, &mut harness, 200, 1);
+        harness
+            .poll_and_handle_next_tc_with_current_time(|e| panic!("partial error: {e:?}"))
+            .unwrap();
+        let expected_stamp = FixedTimeSource::new(time).cds_short_stamp().unwrap();
+        common.check_next_verification_tm(1, request_id);
+        let start_tm = common.read_next_tm();
+        assert_eq!(start_tm.subservice(), 3);
+        assert_eq!(start_tm.timestamp(), expected_stamp);
+        common.check_next_verification_tm(7, request_id);
+    }
+
+    #[test]
+    fn test_current_time_stamp_cuc() {
+        let (mut common, mut harness) = create_harness();
+        let time = UnixTime::new_only_secs(1_700_000_000);
+        // 7 byte CUC timestamp with a 2 byte fine time field.
+        let format = TimestampFormat::Cuc {
+            fine_resolution: FractionalResolution::FifteenUs,
+            leap_seconds: 37,
+        };
+        harness
+            .service_helper
+            .set_time_source(FixedTimeSource::new(time));
+        harness.service_helper.set_timestamp_format(format);
+        let request_id = send_tc(&common, &mut harness, 200, 1);
+        harness
+            .poll_and_handle_next_tc_with_current_time(|e| panic!("partial error: {e:?}"))
+            .unwrap();
+        common.check_next_verification_tm(1, request_id);
+        let start_tm = common.read_next_tm();
+        assert_eq!(start_tm.subservice(), 3);
+        assert_eq!(format.read_stamp(start_tm.timestamp()).unwrap(), (time, 7));
+        common.check_next_verification_tm(7, request_id);
+    }
+}
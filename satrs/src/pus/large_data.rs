@@ -0,0 +1,926 @@
+//! # PUS Service 13 large packet transfer support
+//!
+//! Data units which exceed the maximum size of a single packet, for example memory dumps or
+//! software images, are transferred as a sequence of parts. Each part is sent as a separate
+//! packet and starts with a [PartHeader], which contains the large message transaction ID and
+//! the part sequence number, followed by the part data.
+//!
+//! The [LargeDataSplitter] splits a data unit into first, intermediate and last parts, and the
+//! [PusLargeDataTmCreator] sends these parts as TM[13,1], TM[13,2] and TM[13,3] packets.
+//! The [LargeDataReassembler] reassembles uplinked TC[13,9], TC[13,10] and TC[13,11] parts back
+//! into a buffer or a pool entry, and aborts transfers on sequence errors or timeouts. A
+//! TM[13,16] uplink abortion report can be generated with the [PusLargeDataTmCreator] in that
+//! case.
+use core::fmt::{Display, Formatter};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use spacepackets::ByteConversionError;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::pool::PoolError;
+use crate::pus::EcssTmtcError;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum LargeDataSubservice {
+    TmFirstDownlinkPart = 1,
+    TmIntermediateDownlinkPart = 2,
+    TmLastDownlinkPart = 3,
+    TcFirstUplinkPart = 9,
+    TcIntermediateUplinkPart = 10,
+    TcLastUplinkPart = 11,
+    TmUplinkAbortion = 16,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PartType {
+    First,
+    Intermediate,
+    Last,
+}
+
+impl PartType {
+    pub fn downlink_subservice(&self) -> LargeDataSubservice {
+        match self {
+            PartType::First => LargeDataSubservice::TmFirstDownlinkPart,
+            PartType::Intermediate => LargeDataSubservice::TmIntermediateDownlinkPart,
+            PartType::Last => LargeDataSubservice::TmLastDownlinkPart,
+        }
+    }
+
+    pub fn uplink_subservice(&self) -> LargeDataSubservice {
+        match self {
+            PartType::First => LargeDataSubservice::TcFirstUplinkPart,
+            PartType::Intermediate => LargeDataSubservice::TcIntermediateUplinkPart,
+            PartType::Last => LargeDataSubservice::TcLastUplinkPart,
+        }
+    }
+
+    /// Part type of an uplink subservice. Returns [None] for all other subservices.
+    pub fn from_uplink_subservice(subservice: u8) -> Option<Self> {
+        match LargeDataSubservice::try_from(subservice).ok()? {
+            LargeDataSubservice::TcFirstUplinkPart => Some(PartType::First),
+            LargeDataSubservice::TcIntermediateUplinkPart => Some(PartType::Intermediate),
+            LargeDataSubservice::TcLastUplinkPart => Some(PartType::Last),
+            _ => None,
+        }
+    }
+}
+
+/// Failure reason which is part of the TM[13,16] uplink abortion report.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u16)]
+pub enum UplinkAbortReason {
+    /// No part was received within the reception timeout.
+    Timeout = 0,
+    /// A part with an unexpected sequence number was received.
+    SequenceError = 1,
+    /// The reassembled data would exceed the maximum data size.
+    MaxSizeExceeded = 2,
+    /// The maximum number of concurrent uplink transfers was reached.
+    MaxTransfersReached = 3,
+    /// The transfer was aborted by the on-board software.
+    Aborted = 4,
+}
+
+/// Header which precedes the data of every part.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartHeader {
+    pub transaction_id: u16,
+    pub part_seq_num: u32,
+}
+
+impl PartHeader {
+    pub const LEN: usize = 6;
+
+    pub const fn new(transaction_id: u16, part_seq_num: u32) -> Self {
+        Self {
+            transaction_id,
+            part_seq_num,
+        }
+    }
+
+    pub fn write_to_be_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+        if buf.len() < Self::LEN {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: Self::LEN,
+            });
+        }
+        buf[0..2].copy_from_slice(&self.transaction_id.to_be_bytes());
+        buf[2..6].copy_from_slice(&self.part_seq_num.to_be_bytes());
+        Ok(Self::LEN)
+    }
+
+    pub fn from_be_bytes(buf: &[u8]) -> Result<Self, ByteConversionError> {
+        if buf.len() < Self::LEN {
+            return Err(ByteConversionError::FromSliceTooSmall {
+                found: buf.len(),
+                expected: Self::LEN,
+            });
+        }
+        Ok(Self {
+            transaction_id: u16::from_be_bytes(buf[0..2].try_into().unwrap()),
+            part_seq_num: u32::from_be_bytes(buf[2..6].try_into().unwrap()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LargeDataError {
+    /// Data units must be at least two bytes long to be split into a first and a last part.
+    DataTooSmall(usize),
+    /// The maximum part length must be larger than 0.
+    InvalidMaxPartLen,
+    InvalidSubservice(u8),
+    /// Intermediate or last part received for a transaction which is not active.
+    UnknownTransaction(u16),
+    UnexpectedSequenceNumber {
+        transaction_id: u16,
+        expected: u32,
+        found: u32,
+    },
+    MaxSizeExceeded {
+        transaction_id: u16,
+        max_size: usize,
+    },
+    MaxTransfersReached {
+        transaction_id: u16,
+        max_transfers: usize,
+    },
+    ByteConversion(ByteConversionError),
+    Pool(PoolError),
+    Send(EcssTmtcError),
+}
+
+impl LargeDataError {
+    /// Failure reason for the uplink abortion report if the error aborted an uplink transfer.
+    pub fn abort_reason(&self) -> Option<(u16, UplinkAbortReason)> {
+        match self {
+            LargeDataError::UnexpectedSequenceNumber { transaction_id, .. } => {
+                Some((*transaction_id, UplinkAbortReason::SequenceError))
+            }
+            LargeDataError::MaxSizeExceeded { transaction_id, .. } => {
+                Some((*transaction_id, UplinkAbortReason::MaxSizeExceeded))
+            }
+            LargeDataError::MaxTransfersReached { transaction_id, .. } => {
+                Some((*transaction_id, UplinkAbortReason::MaxTransfersReached))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for LargeDataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LargeDataError::DataTooSmall(len) => {
+                write!(f, "data with length {len} can not be split into parts")
+            }
+            LargeDataError::InvalidMaxPartLen => write!(f, "invalid maximum part length"),
+            LargeDataError::InvalidSubservice(subservice) => {
+                write!(f, "invalid large data subservice {subservice}")
+            }
+            LargeDataError::UnknownTransaction(id) => {
+                write!(f, "unknown large data transaction {id}")
+            }
+            LargeDataError::UnexpectedSequenceNumber {
+                transaction_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "transaction {transaction_id}: expected part sequence number {expected}, \
+                found {found}"
+            ),
+            LargeDataError::MaxSizeExceeded {
+                transaction_id,
+                max_size,
+            } => write!(
+                f,
+                "transaction {transaction_id}: maximum data size {max_size} exceeded"
+            ),
+            LargeDataError::MaxTransfersReached {
+                transaction_id,
+                max_transfers,
+            } => write!(
+                f,
+                "transaction {transaction_id}: maximum number of transfers {max_transfers} \
+                reached"
+            ),
+            LargeDataError::ByteConversion(e) => write!(f, "large data error: {e}"),
+            LargeDataError::Pool(e) => write!(f, "large data error: {e}"),
+            LargeDataError::Send(e) => write!(f, "large data error: {e}"),
+        }
+    }
+}
+
+impl From<ByteConversionError> for LargeDataError {
+    fn from(value: ByteConversionError) -> Self {
+        Self::ByteConversion(value)
+    }
+}
+
+impl From<PoolError> for LargeDataError {
+    fn from(value: PoolError) -> Self {
+        Self::Pool(value)
+    }
+}
+
+impl From<EcssTmtcError> for LargeDataError {
+    fn from(value: EcssTmtcError) -> Self {
+        Self::Send(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for LargeDataError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LargeDataError::ByteConversion(e) => Some(e),
+            LargeDataError::Pool(e) => Some(e),
+            LargeDataError::Send(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Single part of a split data unit.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct LargeDataPart<'data> {
+    pub part_type: PartType,
+    pub header: PartHeader,
+    pub data: &'data [u8],
+}
+
+impl LargeDataPart<'_> {
+    /// Length of the part header and the part data.
+    pub fn len_written(&self) -> usize {
+        PartHeader::LEN + self.data.len()
+    }
+
+    /// Write the part header followed by the part data. Returns the written length.
+    pub fn write_to_be_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+        if buf.len() < self.len_written() {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: self.len_written(),
+            });
+        }
+        self.header.write_to_be_bytes(buf)?;
+        buf[PartHeader::LEN..self.len_written()].copy_from_slice(self.data);
+        Ok(self.len_written())
+    }
+}
+
+/// Iterator which splits a data unit into a first part, optional intermediate parts and a last
+/// part. The part sequence numbers start at 1.
+///
+/// All parts except the last one have the same length. Data which fits into a single part is
+/// split into two parts of roughly equal size because every transfer consists of at least a
+/// first and a last part.
+#[derive(Debug, Clone)]
+pub struct LargeDataSplitter<'data> {
+    transaction_id: u16,
+    data: &'data [u8],
+    part_len: usize,
+    num_parts: u32,
+    next_seq_num: u32,
+}
+
+impl<'data> LargeDataSplitter<'data> {
+    pub fn new(
+        transaction_id: u16,
+        data: &'data [u8],
+        max_part_len: usize,
+    ) -> Result<Self, LargeDataError> {
+        if max_part_len == 0 {
+            return Err(LargeDataError::InvalidMaxPartLen);
+        }
+        if data.len() < 2 {
+            return Err(LargeDataError::DataTooSmall(data.len()));
+        }
+        let part_len = if data.len() <= max_part_len {
+            (data.len() + 1) / 2
+        } else {
+            max_part_len
+        };
+        Ok(Self {
+            transaction_id,
+            data,
+            part_len,
+            num_parts: ((data.len() + part_len - 1) / part_len) as u32,
+            next_seq_num: 1,
+        })
+    }
+
+    pub fn transaction_id(&self) -> u16 {
+        self.transaction_id
+    }
+
+    pub fn num_parts(&self) -> u32 {
+        self.num_parts
+    }
+}
+
+impl<'data> Iterator for LargeDataSplitter<'data> {
+    type Item = LargeDataPart<'data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_seq_num > self.num_parts {
+            return None;
+        }
+        let seq_num = self.next_seq_num;
+        self.next_seq_num += 1;
+        let start = (seq_num - 1) as usize * self.part_len;
+        let end = core::cmp::min(start + self.part_len, self.data.len());
+        let part_type = if seq_num == 1 {
+            PartType::First
+        } else if seq_num == self.num_parts {
+            PartType::Last
+        } else {
+            PartType::Intermediate
+        };
+        Some(LargeDataPart {
+            part_type,
+            header: PartHeader::new(self.transaction_id, seq_num),
+            data: &self.data[start..end],
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::SpHeader;
+
+    use super::*;
+    use crate::apid::ApidHandle;
+    use crate::pus::EcssTmSender;
+    use crate::ComponentId;
+
+    /// Creates and sends the PUS 13 downlink parts and the uplink abortion reports.
+    pub struct PusLargeDataTmCreator {
+        id: ComponentId,
+        apid: ApidHandle,
+        pub dest_id: u16,
+        max_part_len: usize,
+        source_data_buf: Vec<u8>,
+    }
+
+    impl PusLargeDataTmCreator {
+        /// Create a new TM creator. Returns [None] if the APID is invalid or the maximum part
+        /// length is 0.
+        ///
+        /// ## Parameter
+        ///
+        /// * `id` - Component ID used to send the telemetry.
+        /// * `apid` - APID of the generated telemetry.
+        /// * `dest_id` - Destination ID of the generated telemetry.
+        /// * `max_part_len` - Maximum length of the data of a single downlink part, excluding
+        ///    the [PartHeader].
+        pub fn new(id: ComponentId, apid: u16, dest_id: u16, max_part_len: usize) -> Option<Self> {
+            Self::new_with_apid_handle(id, ApidHandle::new_fixed(apid)?, dest_id, max_part_len)
+        }
+
+        /// Like [Self::new], but the APID is read from the given handle, for example a
+        /// [crate::apid::SharedApid] which can be changed at run-time. Returns [None] if the
+        /// maximum part length is 0.
+        pub fn new_with_apid_handle(
+            id: ComponentId,
+            apid: impl Into<ApidHandle>,
+            dest_id: u16,
+            max_part_len: usize,
+        ) -> Option<Self> {
+            if max_part_len == 0 {
+                return None;
+            }
+            Some(Self {
+                id,
+                apid: apid.into(),
+                dest_id,
+                max_part_len,
+                source_data_buf: vec![0; PartHeader::LEN + max_part_len],
+            })
+        }
+
+        pub fn apid(&self) -> u16 {
+            self.apid.apid()
+        }
+
+        pub fn set_apid_handle(&mut self, apid: impl Into<ApidHandle>) {
+            self.apid = apid.into();
+        }
+
+        pub fn max_part_len(&self) -> usize {
+            self.max_part_len
+        }
+
+        /// Split the data unit and send all parts. Returns the number of sent parts.
+        pub fn send_large_data(
+            &mut self,
+            transaction_id: u16,
+            data: &[u8],
+            time_stamp: &[u8],
+            sender: &(impl EcssTmSender + ?Sized),
+        ) -> Result<u32, LargeDataError> {
+            let splitter = LargeDataSplitter::new(transaction_id, data, self.max_part_len)?;
+            let num_parts = splitter.num_parts();
+            for part in splitter {
+                self.send_part(&part, time_stamp, sender)?;
+            }
+            Ok(num_parts)
+        }
+
+        /// Send a single downlink part.
+        pub fn send_part(
+            &mut self,
+            part: &LargeDataPart,
+            time_stamp: &[u8],
+            sender: &(impl EcssTmSender + ?Sized),
+        ) -> Result<(), LargeDataError> {
+            let len = part.write_to_be_bytes(&mut self.source_data_buf)?;
+            self.send_tm(
+                part.part_type.downlink_subservice(),
+                len,
+                time_stamp,
+                sender,
+            )
+        }
+
+        /// Send a TM[13,16] uplink abortion report. The source data consists of the transaction
+        /// ID followed by the failure reason as a big endian [u16].
+        pub fn send_uplink_abortion(
+            &mut self,
+            transaction_id: u16,
+            reason: UplinkAbortReason,
+            time_stamp: &[u8],
+            sender: &(impl EcssTmSender + ?Sized),
+        ) -> Result<(), LargeDataError> {
+            self.source_data_buf[0..2].copy_from_slice(&transaction_id.to_be_bytes());
+            self.source_data_buf[2..4].copy_from_slice(&u16::from(reason).to_be_bytes());
+            self.send_tm(LargeDataSubservice::TmUplinkAbortion, 4, time_stamp, sender)
+        }
+
+        fn send_tm(
+            &self,
+            subservice: LargeDataSubservice,
+            source_data_len: usize,
+            time_stamp: &[u8],
+            sender: &(impl EcssTmSender + ?Sized),
+        ) -> Result<(), LargeDataError> {
+            let sec_header =
+                PusTmSecondaryHeader::new(13, subservice.into(), 0, self.dest_id, time_stamp);
+            let tm_creator = PusTmCreator::new(
+                SpHeader::new_from_apid(self.apid.apid()),
+                sec_header,
+                &self.source_data_buf[0..source_data_len],
+                true,
+            );
+            sender.send_tm(self.id, tm_creator.into())?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::time::{Duration, Instant};
+    use std::vec::Vec;
+
+    use hashbrown::HashMap;
+    use spacepackets::ecss::PusPacket;
+
+    use super::*;
+    use crate::pool::{PoolAddr, PoolProvider};
+
+    /// Reassembled data of a completed uplink transfer.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct CompletedUplink {
+        pub transaction_id: u16,
+        pub data: Vec<u8>,
+    }
+
+    #[derive(Debug)]
+    struct UplinkTransfer {
+        next_seq_num: u32,
+        data: Vec<u8>,
+        last_activity: Instant,
+    }
+
+    /// Reassembles uplinked parts of multiple concurrent transfers.
+    ///
+    /// A transfer is started by a first part, and the sequence number of every subsequent part
+    /// must be incremented by one. A new first part for an active transaction restarts the
+    /// transfer. Transfers are aborted and removed if a part with an unexpected sequence number
+    /// is received, if the maximum data size is exceeded or if no part was received for the
+    /// configured timeout. The [LargeDataError::abort_reason] of the returned errors and the
+    /// transaction IDs returned by [Self::check_timeouts] can be used to generate uplink
+    /// abortion reports.
+    #[derive(Debug)]
+    pub struct LargeDataReassembler {
+        timeout: Duration,
+        max_data_size: usize,
+        max_transfers: usize,
+        transfers: HashMap<u16, UplinkTransfer>,
+    }
+
+    impl LargeDataReassembler {
+        /// Create a new reassembler.
+        ///
+        /// ## Parameter
+        ///
+        /// * `timeout` - Transfers are aborted if no part was received for this duration.
+        /// * `max_data_size` - Maximum size of a reassembled data unit.
+        /// * `max_transfers` - Maximum number of concurrent transfers.
+        pub fn new(timeout: Duration, max_data_size: usize, max_transfers: usize) -> Self {
+            Self {
+                timeout,
+                max_data_size,
+                max_transfers,
+                transfers: HashMap::new(),
+            }
+        }
+
+        pub fn timeout(&self) -> Duration {
+            self.timeout
+        }
+
+        pub fn num_active_transfers(&self) -> usize {
+            self.transfers.len()
+        }
+
+        pub fn is_active(&self, transaction_id: u16) -> bool {
+            self.transfers.contains_key(&transaction_id)
+        }
+
+        /// Handle a TC[13,9], TC[13,10] or TC[13,11] packet. The caller is responsible for
+        /// routing only service 13 packets to the reassembler.
+        pub fn handle_tc(
+            &mut self,
+            tc: &(impl PusPacket + ?Sized),
+            now: Instant,
+        ) -> Result<Option<CompletedUplink>, LargeDataError> {
+            let part_type = PartType::from_uplink_subservice(tc.subservice())
+                .ok_or(LargeDataError::InvalidSubservice(tc.subservice()))?;
+            self.handle_part(part_type, tc.user_data(), now)
+        }
+
+        /// Handle a single uplinked part. The raw part consists of the [PartHeader] followed by
+        /// the part data. Returns the reassembled data once the last part was received.
+        pub fn handle_part(
+            &mut self,
+            part_type: PartType,
+            raw_part: &[u8],
+            now: Instant,
+        ) -> Result<Option<CompletedUplink>, LargeDataError> {
+            let header = PartHeader::from_be_bytes(raw_part)?;
+            let part_data = &raw_part[PartHeader::LEN..];
+            let transaction_id = header.transaction_id;
+            if part_type == PartType::First {
+                if !self.transfers.contains_key(&transaction_id)
+                    && self.transfers.len() >= self.max_transfers
+                {
+                    return Err(LargeDataError::MaxTransfersReached {
+                        transaction_id,
+                        max_transfers: self.max_transfers,
+                    });
+                }
+                self.transfers.insert(
+                    transaction_id,
+                    UplinkTransfer {
+                        next_seq_num: header.part_seq_num,
+                        data: Vec::new(),
+                        last_activity: now,
+                    },
+                );
+            }
+            let transfer = self
+                .transfers
+                .get_mut(&transaction_id)
+                .ok_or(LargeDataError::UnknownTransaction(transaction_id))?;
+            if header.part_seq_num != transfer.next_seq_num {
+                let expected = transfer.next_seq_num;
+                self.transfers.remove(&transaction_id);
+                return Err(LargeDataError::UnexpectedSequenceNumber {
+                    transaction_id,
+                    expected,
+                    found: header.part_seq_num,
+                });
+            }
+            if transfer.data.len() + part_data.len() > self.max_data_size {
+                self.transfers.remove(&transaction_id);
+                return Err(LargeDataError::MaxSizeExceeded {
+                    transaction_id,
+                    max_size: self.max_data_size,
+                });
+            }
+            transfer.data.extend_from_slice(part_data);
+            transfer.next_seq_num = transfer.next_seq_num.wrapping_add(1);
+            transfer.last_activity = now;
+            if part_type == PartType::Last {
+                let transfer = self.transfers.remove(&transaction_id).unwrap();
+                return Ok(Some(CompletedUplink {
+                    transaction_id,
+                    data: transfer.data,
+                }));
+            }
+            Ok(None)
+        }
+
+        /// Handle a single uplinked part and store the reassembled data inside the pool once
+        /// the last part was received. Returns the transaction ID and the pool address of the
+        /// completed data unit.
+        pub fn handle_part_into_pool(
+            &mut self,
+            part_type: PartType,
+            raw_part: &[u8],
+            now: Instant,
+            pool: &mut (impl PoolProvider + ?Sized),
+        ) -> Result<Option<(u16, PoolAddr)>, LargeDataError> {
+            match self.handle_part(part_type, raw_part, now)? {
+                Some(completed) => Ok(Some((completed.transaction_id, pool.add(&completed.data)?))),
+                None => Ok(None),
+            }
+        }
+
+        /// Abort an active transfer. Returns whether the transfer was active.
+        pub fn abort(&mut self, transaction_id: u16) -> bool {
+            self.transfers.remove(&transaction_id).is_some()
+        }
+
+        /// Abort all transfers which did not receive a part for the configured timeout.
+        /// Returns the sorted transaction IDs of the aborted transfers.
+        pub fn check_timeouts(&mut self, now: Instant) -> Vec<u16> {
+            let timeout = self.timeout;
+            let mut timed_out: Vec<u16> = self
+                .transfers
+                .iter()
+                .filter(|(_, transfer)| {
+                    now.saturating_duration_since(transfer.last_activity) >= timeout
+                })
+                .map(|(id, _)| *id)
+                .collect();
+            timed_out.sort_unstable();
+            for id in &timed_out {
+                self.transfers.remove(id);
+            }
+            timed_out
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+    use std::vec::Vec;
+
+    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::PusPacket;
+
+    use super::*;
+    use crate::pool::{PoolProvider, StaticMemoryPool, StaticPoolConfig};
+    use crate::tmtc::PacketAsVec;
+
+    const TEST_ID: u64 = 0x05;
+    const TEST_APID: u16 = 0x02;
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn test_splitting() {
+        let data = test_data(10);
+        let splitter = LargeDataSplitter::new(3, &data, 4).unwrap();
+        assert_eq!(splitter.num_parts(), 3);
+        let parts: Vec<LargeDataPart> = splitter.collect();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].part_type, PartType::First);
+        assert_eq!(parts[0].header, PartHeader::new(3, 1));
+        assert_eq!(parts[0].data, &data[0..4]);
+        assert_eq!(parts[1].part_type, PartType::Intermediate);
+        assert_eq!(parts[1].header.part_seq_num, 2);
+        assert_eq!(parts[2].part_type, PartType::Last);
+        assert_eq!(parts[2].header.part_seq_num, 3);
+        assert_eq!(parts[2].data, &data[8..10]);
+
+        // Data which fits into a single part is still split into a first and a last part.
+        let parts: Vec<LargeDataPart> =
+            LargeDataSplitter::new(3, &data[0..5], 8).unwrap().collect();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].data, &data[0..3]);
+        assert_eq!(parts[1].part_type, PartType::Last);
+        assert_eq!(parts[1].data, &data[3..5]);
+        assert_eq!(
+            LargeDataSplitter::new(3, &data[0..1], 8).unwrap_err(),
+            LargeDataError::DataTooSmall(1)
+        );
+    }
+
+    #[test]
+    fn test_downlink() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let mut tm_creator = PusLargeDataTmCreator::new(TEST_ID, TEST_APID, 0, 8).unwrap();
+        let data = test_data(20);
+        assert_eq!(
+            tm_creator
+                .send_large_data(1, &data, &[0; 7], &tm_tx)
+                .unwrap(),
+            3
+        );
+        let mut reassembled = Vec::new();
+        let expected_subservices = [
+            LargeDataSubservice::TmFirstDownlinkPart,
+            LargeDataSubservice::TmIntermediateDownlinkPart,
+            LargeDataSubservice::TmLastDownlinkPart,
+        ];
+        for (idx, subservice) in expected_subservices.iter().enumerate() {
+            let packet = tm_rx.try_recv().unwrap();
+            assert_eq!(packet.sender_id, TEST_ID);
+            let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+            assert_eq!(tm.service(), 13);
+            assert_eq!(tm.subservice(), u8::from(*subservice));
+            let header = PartHeader::from_be_bytes(tm.user_data()).unwrap();
+            assert_eq!(header, PartHeader::new(1, idx as u32 + 1));
+            reassembled.extend_from_slice(&tm.user_data()[PartHeader::LEN..]);
+        }
+        assert_eq!(reassembled, data);
+
+        tm_creator
+            .send_uplink_abortion(1, UplinkAbortReason::Timeout, &[0; 7], &tm_tx)
+            .unwrap();
+        let packet = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(
+            tm.subservice(),
+            u8::from(LargeDataSubservice::TmUplinkAbortion)
+        );
+        assert_eq!(tm.user_data(), &[0, 1, 0, 0]);
+    }
+
+    fn raw_parts(
+        transaction_id: u16,
+        data: &[u8],
+        max_part_len: usize,
+    ) -> Vec<(PartType, Vec<u8>)> {
+        LargeDataSplitter::new(transaction_id, data, max_part_len)
+            .unwrap()
+            .map(|part| {
+                let mut buf = vec![0; part.len_written()];
+                part.write_to_be_bytes(&mut buf).unwrap();
+                (part.part_type, buf)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reassembly() {
+        let mut reassembler = LargeDataReassembler::new(Duration::from_secs(5), 64, 2);
+        let data_0 = test_data(30);
+        let data_1 = test_data(12);
+        let parts_0 = raw_parts(0, &data_0, 8);
+        let parts_1 = raw_parts(1, &data_1, 8);
+        let now = Instant::now();
+        // Interleaved transfers.
+        for (part_type, raw) in &parts_0[0..2] {
+            assert!(reassembler
+                .handle_part(*part_type, raw, now)
+                .unwrap()
+                .is_none());
+        }
+        assert!(reassembler
+            .handle_part(parts_1[0].0, &parts_1[0].1, now)
+            .unwrap()
+            .is_none());
+        assert_eq!(reassembler.num_active_transfers(), 2);
+        let completed = reassembler
+            .handle_part(parts_1[1].0, &parts_1[1].1, now)
+            .unwrap()
+            .unwrap();
+        assert_eq!(completed.transaction_id, 1);
+        assert_eq!(completed.data, data_1);
+        let mut completed = None;
+        for (part_type, raw) in &parts_0[2..] {
+            completed = reassembler.handle_part(*part_type, raw, now).unwrap();
+        }
+        assert_eq!(completed.unwrap().data, data_0);
+        assert_eq!(reassembler.num_active_transfers(), 0);
+    }
+
+    #[test]
+    fn test_reassembly_errors() {
+        let mut reassembler = LargeDataReassembler::new(Duration::from_secs(5), 16, 1);
+        let now = Instant::now();
+        let parts = raw_parts(2, &test_data(24), 8);
+        assert_eq!(
+            reassembler
+                .handle_part(parts[1].0, &parts[1].1, now)
+                .unwrap_err(),
+            LargeDataError::UnknownTransaction(2)
+        );
+        reassembler
+            .handle_part(parts[0].0, &parts[0].1, now)
+            .unwrap();
+        let error = reassembler
+            .handle_part(parts[2].0, &parts[2].1, now)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            LargeDataError::UnexpectedSequenceNumber {
+                transaction_id: 2,
+                expected: 2,
+                found: 3
+            }
+        );
+        assert_eq!(
+            error.abort_reason(),
+            Some((2, UplinkAbortReason::SequenceError))
+        );
+        assert!(!reassembler.is_active(2));
+
+        // The maximum data size is 16 bytes.
+        let parts = raw_parts(3, &test_data(24), 8);
+        reassembler
+            .handle_part(parts[0].0, &parts[0].1, now)
+            .unwrap();
+        reassembler
+            .handle_part(parts[1].0, &parts[1].1, now)
+            .unwrap();
+        assert_eq!(
+            reassembler
+                .handle_part(parts[2].0, &parts[2].1, now)
+                .unwrap_err(),
+            LargeDataError::MaxSizeExceeded {
+                transaction_id: 3,
+                max_size: 16
+            }
+        );
+
+        // Only one concurrent transfer is allowed.
+        let parts_4 = raw_parts(4, &test_data(24), 8);
+        let parts_5 = raw_parts(5, &test_data(24), 8);
+        reassembler
+            .handle_part(parts_4[0].0, &parts_4[0].1, now)
+            .unwrap();
+        assert_eq!(
+            reassembler
+                .handle_part(parts_5[0].0, &parts_5[0].1, now)
+                .unwrap_err()
+                .abort_reason(),
+            Some((5, UplinkAbortReason::MaxTransfersReached))
+        );
+        assert!(reassembler.abort(4));
+        assert!(!reassembler.abort(4));
+    }
+
+    #[test]
+    fn test_timeout_and_pool() {
+        let mut reassembler = LargeDataReassembler::new(Duration::from_secs(5), 64, 4);
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(4, 32)],
+            false,
+        ));
+        let start = Instant::now();
+        let parts_0 = raw_parts(0, &test_data(24), 8);
+        let parts_1 = raw_parts(1, &test_data(24), 8);
+        reassembler
+            .handle_part(parts_0[0].0, &parts_0[0].1, start)
+            .unwrap();
+        reassembler
+            .handle_part(parts_1[0].0, &parts_1[0].1, start)
+            .unwrap();
+        let later = start + Duration::from_secs(4);
+        reassembler
+            .handle_part(parts_1[1].0, &parts_1[1].1, later)
+            .unwrap();
+        assert_eq!(
+            reassembler.check_timeouts(start + Duration::from_secs(6)),
+            vec![0]
+        );
+        assert!(!reassembler.is_active(0));
+        let (transaction_id, addr) = reassembler
+            .handle_part_into_pool(parts_1[2].0, &parts_1[2].1, later, &mut pool)
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction_id, 1);
+        let mut read_buf = [0; 32];
+        let read_len = pool.read(&addr, &mut read_buf).unwrap();
+        assert_eq!(&read_buf[..read_len], test_data(24).as_slice());
+    }
+}
@@ -0,0 +1,563 @@
+//! # PUS Service 6 memory management support
+//!
+//! The memory management service allows loading, dumping and checking raw memory areas. All
+//! memory accesses are performed through the [MemoryProvider] abstraction, which allows targets
+//! to expose RAM regions, pool entries or register banks. A memory area is identified by a
+//! [MemoryId], a start address and a length, which are serialized as the [MemoryAreaHeader].
+//!
+//! The application data of the telecommands and the source data of the reports have the
+//! following format:
+//!
+//! * TC[6,2] load raw memory: header, data and the CRC16 of the data.
+//! * TC[6,5] dump raw memory and TC[6,9] check raw memory: header only.
+//! * TM[6,6] dumped raw memory report: header, data and the CRC16 of the data.
+//! * TM[6,10] checked raw memory report: header and the CRC16 of the data.
+//!
+//! The CRC16 is the CRC-16-CCITT-FALSE checksum which is also used for PUS packets.
+//! The service handler for these telecommands is the `PusMemoryServiceHandler` inside the
+//! `memory_srv` module, which requires the `std` feature.
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+use crc::{Crc, CRC_16_IBM_3740};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use spacepackets::ByteConversionError;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::pool::{PoolAddr, PoolError, PoolProvider};
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+/// CRC-16-CCITT-FALSE checksum used for the memory data.
+pub const CRC_CCITT_FALSE: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_3740);
+
+pub type MemoryId = u16;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum MemorySubservice {
+    TcLoadRawMemory = 2,
+    TcDumpRawMemory = 5,
+    TmDumpedRawMemory = 6,
+    TcCheckRawMemory = 9,
+    TmCheckedRawMemory = 10,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MemoryAccess {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryError {
+    UnknownMemory(MemoryId),
+    OutOfBounds {
+        memory_id: MemoryId,
+        address: u64,
+        len: usize,
+    },
+    /// The access was rejected by the address validation of the [MemoryProvider].
+    AccessDenied {
+        memory_id: MemoryId,
+        address: u64,
+        len: usize,
+        access: MemoryAccess,
+    },
+    Pool(PoolError),
+}
+
+impl Display for MemoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MemoryError::UnknownMemory(memory_id) => write!(f, "unknown memory ID {memory_id}"),
+            MemoryError::OutOfBounds {
+                memory_id,
+                address,
+                len,
+            } => write!(
+                f,
+                "memory area with address {address:#x} and length {len} of memory \
+                {memory_id} out of bounds"
+            ),
+            MemoryError::AccessDenied {
+                memory_id,
+                address,
+                len,
+                access,
+            } => write!(
+                f,
+                "{access:?} access to memory area with address {address:#x} and length {len} \
+                of memory {memory_id} denied"
+            ),
+            MemoryError::Pool(e) => write!(f, "memory pool error: {e}"),
+        }
+    }
+}
+
+impl From<PoolError> for MemoryError {
+    fn from(value: PoolError) -> Self {
+        Self::Pool(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for MemoryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MemoryError::Pool(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Generic abstraction for memory which can be loaded, dumped and checked by the memory
+/// management service.
+pub trait MemoryProvider {
+    /// Address validation hook which is called by the memory management service before every
+    /// access. The default implementation accepts all accesses, and implementors can override
+    /// it to protect memory areas.
+    fn validate_access(
+        &self,
+        _memory_id: MemoryId,
+        _address: u64,
+        _len: usize,
+        _access: MemoryAccess,
+    ) -> Result<(), MemoryError> {
+        Ok(())
+    }
+
+    /// Read the memory area starting at the given address into the buffer. The length of the
+    /// memory area is the length of the buffer.
+    fn read(
+        &mut self,
+        memory_id: MemoryId,
+        address: u64,
+        buf: &mut [u8],
+    ) -> Result<(), MemoryError>;
+
+    /// Write the data to the memory area starting at the given address.
+    fn write(&mut self, memory_id: MemoryId, address: u64, data: &[u8]) -> Result<(), MemoryError>;
+}
+
+/// Header which identifies a memory area inside the memory management requests and reports.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryAreaHeader {
+    pub memory_id: MemoryId,
+    pub address: u64,
+    pub len: u32,
+}
+
+impl MemoryAreaHeader {
+    pub const LEN: usize = 14;
+
+    pub const fn new(memory_id: MemoryId, address: u64, len: u32) -> Self {
+        Self {
+            memory_id,
+            address,
+            len,
+        }
+    }
+
+    pub fn write_to_be_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+        if buf.len() < Self::LEN {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: Self::LEN,
+            });
+        }
+        buf[0..2].copy_from_slice(&self.memory_id.to_be_bytes());
+        buf[2..10].copy_from_slice(&self.address.to_be_bytes());
+        buf[10..14].copy_from_slice(&self.len.to_be_bytes());
+        Ok(Self::LEN)
+    }
+
+    pub fn from_be_bytes(buf: &[u8]) -> Result<Self, ByteConversionError> {
+        if buf.len() < Self::LEN {
+            return Err(ByteConversionError::FromSliceTooSmall {
+                found: buf.len(),
+                expected: Self::LEN,
+            });
+        }
+        Ok(Self {
+            memory_id: u16::from_be_bytes(buf[0..2].try_into().unwrap()),
+            address: u64::from_be_bytes(buf[2..10].try_into().unwrap()),
+            len: u32::from_be_bytes(buf[10..14].try_into().unwrap()),
+        })
+    }
+}
+
+/// Memory provider for a single contiguous memory region, for example a RAM buffer or a
+/// register bank. The region is mapped to the given base address.
+#[derive(Debug)]
+pub struct MemoryRegion<'mem> {
+    memory_id: MemoryId,
+    base_address: u64,
+    mem: &'mem mut [u8],
+    writable: bool,
+}
+
+impl<'mem> MemoryRegion<'mem> {
+    pub fn new(
+        memory_id: MemoryId,
+        base_address: u64,
+        mem: &'mem mut [u8],
+        writable: bool,
+    ) -> Self {
+        Self {
+            memory_id,
+            base_address,
+            mem,
+            writable,
+        }
+    }
+
+    pub fn memory_id(&self) -> MemoryId {
+        self.memory_id
+    }
+
+    pub fn base_address(&self) -> u64 {
+        self.base_address
+    }
+
+    pub fn len(&self) -> usize {
+        self.mem.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mem.is_empty()
+    }
+
+    pub fn writable(&self) -> bool {
+        self.writable
+    }
+
+    /// Check whether the memory area is fully contained inside the region.
+    pub fn contains(&self, memory_id: MemoryId, address: u64, len: usize) -> bool {
+        self.offsets(memory_id, address, len).is_ok()
+    }
+
+    fn offsets(
+        &self,
+        memory_id: MemoryId,
+        address: u64,
+        len: usize,
+    ) -> Result<Range<usize>, MemoryError> {
+        if memory_id != self.memory_id {
+            return Err(MemoryError::UnknownMemory(memory_id));
+        }
+        let range = address
+            .checked_sub(self.base_address)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .and_then(|start| start.checked_add(len).map(|end| start..end));
+        match range {
+            Some(range) if range.end <= self.mem.len() => Ok(range),
+            _ => Err(MemoryError::OutOfBounds {
+                memory_id,
+                address,
+                len,
+            }),
+        }
+    }
+}
+
+impl MemoryProvider for MemoryRegion<'_> {
+    fn validate_access(
+        &self,
+        memory_id: MemoryId,
+        address: u64,
+        len: usize,
+        access: MemoryAccess,
+    ) -> Result<(), MemoryError> {
+        self.offsets(memory_id, address, len)?;
+        if access == MemoryAccess::Write && !self.writable {
+            return Err(MemoryError::AccessDenied {
+                memory_id,
+                address,
+                len,
+                access,
+            });
+        }
+        Ok(())
+    }
+
+    fn read(
+        &mut self,
+        memory_id: MemoryId,
+        address: u64,
+        buf: &mut [u8],
+    ) -> Result<(), MemoryError> {
+        let range = self.offsets(memory_id, address, buf.len())?;
+        buf.copy_from_slice(&self.mem[range]);
+        Ok(())
+    }
+
+    fn write(&mut self, memory_id: MemoryId, address: u64, data: &[u8]) -> Result<(), MemoryError> {
+        self.validate_access(memory_id, address, data.len(), MemoryAccess::Write)?;
+        let range = self.offsets(memory_id, address, data.len())?;
+        self.mem[range].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+/// Memory provider which exposes the entries of a pool. The address of a memory area is the
+/// [PoolAddr] of the entry, and memory areas always start at the beginning of an entry.
+#[derive(Debug)]
+pub struct PoolMemory<Pool: PoolProvider> {
+    memory_id: MemoryId,
+    pool: Pool,
+}
+
+impl<Pool: PoolProvider> PoolMemory<Pool> {
+    pub fn new(memory_id: MemoryId, pool: Pool) -> Self {
+        Self { memory_id, pool }
+    }
+
+    pub fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    pub fn pool_mut(&mut self) -> &mut Pool {
+        &mut self.pool
+    }
+
+    fn check_entry(
+        &self,
+        memory_id: MemoryId,
+        addr: PoolAddr,
+        len: usize,
+    ) -> Result<(), MemoryError> {
+        if memory_id != self.memory_id {
+            return Err(MemoryError::UnknownMemory(memory_id));
+        }
+        if len > self.pool.len_of_data(&addr)? {
+            return Err(MemoryError::OutOfBounds {
+                memory_id,
+                address: addr,
+                len,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<Pool: PoolProvider> MemoryProvider for PoolMemory<Pool> {
+    fn read(
+        &mut self,
+        memory_id: MemoryId,
+        address: u64,
+        buf: &mut [u8],
+    ) -> Result<(), MemoryError> {
+        self.check_entry(memory_id, address, buf.len())?;
+        self.pool.modify(&address, |entry| {
+            let len = buf.len();
+            buf.copy_from_slice(&entry[..len]);
+        })?;
+        Ok(())
+    }
+
+    fn write(&mut self, memory_id: MemoryId, address: u64, data: &[u8]) -> Result<(), MemoryError> {
+        self.check_entry(memory_id, address, data.len())?;
+        self.pool.modify(&address, |entry| {
+            entry[..data.len()].copy_from_slice(data);
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    /// Memory provider which combines multiple [MemoryRegion]s. Multiple regions may share the
+    /// same memory ID, but a memory area must be fully contained in a single region.
+    #[derive(Debug, Default)]
+    pub struct MemoryRegionMap<'mem> {
+        regions: Vec<MemoryRegion<'mem>>,
+    }
+
+    impl<'mem> MemoryRegionMap<'mem> {
+        pub fn add_region(&mut self, region: MemoryRegion<'mem>) {
+            self.regions.push(region);
+        }
+
+        pub fn num_regions(&self) -> usize {
+            self.regions.len()
+        }
+
+        fn region_index(
+            &self,
+            memory_id: MemoryId,
+            address: u64,
+            len: usize,
+        ) -> Result<usize, MemoryError> {
+            if let Some(idx) = self
+                .regions
+                .iter()
+                .position(|region| region.contains(memory_id, address, len))
+            {
+                return Ok(idx);
+            }
+            if self
+                .regions
+                .iter()
+                .any(|region| region.memory_id() == memory_id)
+            {
+                return Err(MemoryError::OutOfBounds {
+                    memory_id,
+                    address,
+                    len,
+                });
+            }
+            Err(MemoryError::UnknownMemory(memory_id))
+        }
+    }
+
+    impl MemoryProvider for MemoryRegionMap<'_> {
+        fn validate_access(
+            &self,
+            memory_id: MemoryId,
+            address: u64,
+            len: usize,
+            access: MemoryAccess,
+        ) -> Result<(), MemoryError> {
+            let idx = self.region_index(memory_id, address, len)?;
+            self.regions[idx].validate_access(memory_id, address, len, access)
+        }
+
+        fn read(
+            &mut self,
+            memory_id: MemoryId,
+            address: u64,
+            buf: &mut [u8],
+        ) -> Result<(), MemoryError> {
+            let idx = self.region_index(memory_id, address, buf.len())?;
+            self.regions[idx].read(memory_id, address, buf)
+        }
+
+        fn write(
+            &mut self,
+            memory_id: MemoryId,
+            address: u64,
+            data: &[u8],
+        ) -> Result<(), MemoryError> {
+            let idx = self.region_index(memory_id, address, data.len())?;
+            self.regions[idx].write(memory_id, address, data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::{StaticMemoryPool, StaticPoolConfig};
+
+    #[test]
+    fn test_header_conversion() {
+        let header = MemoryAreaHeader::new(3, 0x2000_0000, 64);
+        let mut buf = [0; MemoryAreaHeader::LEN];
+        assert_eq!(header.write_to_be_bytes(&mut buf).unwrap(), 14);
+        assert_eq!(MemoryAreaHeader::from_be_bytes(&buf).unwrap(), header);
+        assert!(MemoryAreaHeader::from_be_bytes(&buf[0..13]).is_err());
+    }
+
+    #[test]
+    fn test_memory_region() {
+        let mut ram = [0_u8; 16];
+        let mut region = MemoryRegion::new(1, 0x1000, &mut ram, true);
+        region.write(1, 0x1004, &[1, 2, 3, 4]).unwrap();
+        let mut buf = [0; 6];
+        region.read(1, 0x1002, &mut buf).unwrap();
+        assert_eq!(buf, [0, 0, 1, 2, 3, 4]);
+        assert_eq!(
+            region.read(1, 0x100c, &mut buf).unwrap_err(),
+            MemoryError::OutOfBounds {
+                memory_id: 1,
+                address: 0x100c,
+                len: 6
+            }
+        );
+        assert_eq!(
+            region.read(1, 0x0fff, &mut buf).unwrap_err(),
+            MemoryError::OutOfBounds {
+                memory_id: 1,
+                address: 0x0fff,
+                len: 6
+            }
+        );
+        assert_eq!(
+            region.read(2, 0x1000, &mut buf).unwrap_err(),
+            MemoryError::UnknownMemory(2)
+        );
+
+        let mut registers = [0_u8; 4];
+        let mut read_only = MemoryRegion::new(2, 0, &mut registers, false);
+        assert!(read_only
+            .validate_access(2, 0, 4, MemoryAccess::Read)
+            .is_ok());
+        assert_eq!(
+            read_only.write(2, 0, &[1]).unwrap_err(),
+            MemoryError::AccessDenied {
+                memory_id: 2,
+                address: 0,
+                len: 1,
+                access: MemoryAccess::Write
+            }
+        );
+    }
+
+    #[test]
+    fn test_region_map() {
+        let mut ram_0 = [0_u8; 8];
+        let mut ram_1 = [0_u8; 8];
+        let mut map = MemoryRegionMap::default();
+        map.add_region(MemoryRegion::new(1, 0, &mut ram_0, true));
+        map.add_region(MemoryRegion::new(1, 0x100, &mut ram_1, true));
+        assert_eq!(map.num_regions(), 2);
+        map.write(1, 0x104, &[5, 6]).unwrap();
+        let mut buf = [0; 2];
+        map.read(1, 0x104, &mut buf).unwrap();
+        assert_eq!(buf, [5, 6]);
+        assert!(matches!(
+            map.validate_access(1, 0x10, 2, MemoryAccess::Read),
+            Err(MemoryError::OutOfBounds { .. })
+        ));
+        assert_eq!(
+            map.read(3, 0, &mut buf).unwrap_err(),
+            MemoryError::UnknownMemory(3)
+        );
+        drop(map);
+        assert_eq!(&ram_1[4..6], &[5, 6]);
+    }
+
+    #[test]
+    fn test_pool_memory() {
+        let pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            alloc::vec![(4, 16)],
+            false,
+        ));
+        let mut pool_memory = PoolMemory::new(5, pool);
+        let addr = pool_memory.pool_mut().add(&[1, 2, 3, 4]).unwrap();
+        pool_memory.write(5, addr, &[9, 9]).unwrap();
+        let mut buf = [0; 4];
+        pool_memory.read(5, addr, &mut buf).unwrap();
+        assert_eq!(buf, [9, 9, 3, 4]);
+        let mut too_large = [0; 5];
+        assert!(matches!(
+            pool_memory.read(5, addr, &mut too_large),
+            Err(MemoryError::OutOfBounds { .. })
+        ));
+        assert_eq!(
+            pool_memory.read(4, addr, &mut buf).unwrap_err(),
+            MemoryError::UnknownMemory(4)
+        );
+    }
+}
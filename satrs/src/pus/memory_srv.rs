@@ -0,0 +1,795 @@
+use super::large_data::{PartHeader, PusLargeDataTmCreator};
+use super::memory::{
+    MemoryAccess, MemoryAreaHeader, MemoryProvider, MemorySubservice, CRC_CCITT_FALSE,
+};
+use super::tm_limit::TmSizeLimit;
+use super::verification::{
+    FailParams, TcStateAccepted, TcStateStarted, VerificationReporter,
+    VerificationReportingProvider, VerificationToken,
+};
+use super::{
+    DirectPusPacketHandlerResult, EcssTcInMemConverter, EcssTcInSharedStoreConverter,
+    EcssTcInVecConverter, EcssTcReceiver, EcssTmSender, EcssTmtcError, GenericConversionError,
+    HandlingStatus, MpscTcReceiver, PartialPusHandlingError, PusPacketHandlingError,
+    PusServiceHelper, PusTmVariant,
+};
+use crate::tmtc::{PacketAsVec, PacketSenderWithSharedPool};
+use alloc::vec;
+use satrs_shared::res_code::ResultU16;
+use spacepackets::ecss::tc::GenericPusTcSecondaryHeader;
+use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+use spacepackets::ecss::PusPacket;
+use spacepackets::SpHeader;
+use std::sync::mpsc;
+
+/// Result codes used by the [PusMemoryServiceHandler] for verification failure reports. The
+/// group ID of these codes is the PUS service number 6. The failure data of all failure reports
+/// is the [MemoryAreaHeader] of the request.
+pub mod mem_res_code {
+    use satrs_shared::res_code::ResultU16;
+
+    /// The access was rejected by the address validation of the memory provider.
+    pub const ACCESS_DENIED: ResultU16 = ResultU16::new(6, 0);
+    /// Reading or writing the memory area failed.
+    pub const MEMORY_ACCESS_FAILED: ResultU16 = ResultU16::new(6, 1);
+    /// The CRC of the data of a load request is invalid.
+    pub const CRC_MISMATCH: ResultU16 = ResultU16::new(6, 2);
+    /// The requested dump exceeds the maximum dump length of the handler.
+    pub const DUMP_TOO_LARGE: ResultU16 = ResultU16::new(6, 3);
+    /// Generating or sending a memory report failed.
+    pub const REPORT_GENERATION_FAILED: ResultU16 = ResultU16::new(6, 4);
+    /// The memory area exceeds the end of the 64-bit address space.
+    pub const INVALID_MEMORY_AREA: ResultU16 = ResultU16::new(6, 5);
+}
+
+/// Default maximum source data length of a single report generated by the
+/// [PusMemoryServiceHandler].
+pub const DEFAULT_MAX_REPORT_SOURCE_DATA_LEN: usize = 1024;
+/// Default maximum length of a memory area dumped by the [PusMemoryServiceHandler].
+pub const DEFAULT_MAX_DUMP_LEN: usize = 0x10000;
+
+/// This is a helper class for [std] environments to handle PUS 6 (memory management service)
+/// packets. All memory accesses are performed through the wrapped [MemoryProvider], and the
+/// [MemoryProvider::validate_access] hook is called before every access.
+///
+/// Dump reports which exceed the maximum report source data length are transferred with the
+/// PUS 13 large data transfer service. In that case, the source data of the TM[6,6] report is
+/// split into TM[13,1], TM[13,2] and TM[13,3] parts.
+pub struct PusMemoryServiceHandler<
+    TcReceiver: EcssTcReceiver,
+    TmSender: EcssTmSender,
+    TcInMemConverter: EcssTcInMemConverter,
+    VerificationReporter: VerificationReportingProvider,
+    Memory: MemoryProvider,
+> {
+    pub service_helper:
+        PusServiceHelper<TcReceiver, TmSender, TcInMemConverter, VerificationReporter>,
+    memory: Memory,
+    /// Maximum source data length of a single generated report.
+    pub max_report_source_data_len: usize,
+    /// Maximum length of a dumped memory area.
+    pub max_dump_len: usize,
+    next_transaction_id: u16,
+}
+
+impl<
+        TcReceiver: EcssTcReceiver,
+        TmSender: EcssTmSender,
+        TcInMemConverter: EcssTcInMemConverter,
+        VerificationReporter: VerificationReportingProvider,
+        Memory: MemoryProvider,
+    >
+    PusMemoryServiceHandler<TcReceiver, TmSender, TcInMemConverter, VerificationReporter, Memory>
+{
+    pub fn new(
+        service_helper: PusServiceHelper<
+            TcReceiver,
+            TmSender,
+            TcInMemConverter,
+            VerificationReporter,
+        >,
+        memory: Memory,
+    ) -> Self {
+        Self {
+            service_helper,
+            memory,
+            max_report_source_data_len: DEFAULT_MAX_REPORT_SOURCE_DATA_LEN,
+            max_dump_len: DEFAULT_MAX_DUMP_LEN,
+            next_transaction_id: 0,
+        }
+    }
+
+    /// Derive the maximum report source data length from a central [TmSizeLimit], so that the
+    /// generated reports with a timestamp of the given length never exceed the limit.
+    pub fn apply_tm_size_limit(&mut self, limit: &TmSizeLimit, time_stamp_len: usize) {
+        self.max_report_source_data_len = limit.max_source_data_len(time_stamp_len);
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.memory
+    }
+
+    pub fn poll_and_handle_next_tc<ErrorCb: FnMut(&PartialPusHandlingError)>(
+        &mut self,
+        mut error_callback: ErrorCb,
+        time_stamp: &[u8],
+    ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
+        let possible_packet = self.service_helper.retrieve_and_accept_next_packet()?;
+        if possible_packet.is_none() {
+            return Ok(HandlingStatus::Empty.into());
+        }
+        let ecss_tc_and_token = possible_packet.unwrap();
+        self.service_helper
+            .tc_in_mem_converter_mut()
+            .cache(&ecss_tc_and_token.tc_in_memory)?;
+        let tc = self.service_helper.tc_in_mem_converter().convert()?;
+        let token = ecss_tc_and_token
+            .token
+            .with_source_id(tc.source_id())
+            .with_ack_flags(tc.ack_flags());
+        let subservice = tc.subservice();
+        let mem_subservice = match MemorySubservice::try_from(subservice) {
+            Ok(mem_subservice) => mem_subservice,
+            Err(_) => {
+                return Ok(DirectPusPacketHandlerResult::CustomSubservice(
+                    subservice, token,
+                ))
+            }
+        };
+        let access = match mem_subservice {
+            MemorySubservice::TcLoadRawMemory => MemoryAccess::Write,
+            MemorySubservice::TcDumpRawMemory | MemorySubservice::TcCheckRawMemory => {
+                MemoryAccess::Read
+            }
+            MemorySubservice::TmDumpedRawMemory | MemorySubservice::TmCheckedRawMemory => {
+                return Err(GenericConversionError::InvalidSubservice(subservice).into())
+            }
+        };
+        let app_data = tc.user_data();
+        let header = MemoryAreaHeader::from_be_bytes(app_data).map_err(|_| {
+            GenericConversionError::NotEnoughAppData {
+                expected: MemoryAreaHeader::LEN,
+                found: app_data.len(),
+            }
+        })?;
+        let mut failure_data = [0; MemoryAreaHeader::LEN];
+        header.write_to_be_bytes(&mut failure_data).unwrap();
+        let len = header.len as usize;
+        let mut load_data: &[u8] = &[];
+        let mut rejection = None;
+        if header.address.checked_add(u64::from(header.len)).is_none() {
+            rejection = Some(mem_res_code::INVALID_MEMORY_AREA);
+        }
+        if mem_subservice == MemorySubservice::TcLoadRawMemory {
+            let expected_len = MemoryAreaHeader::LEN + len + 2;
+            if app_data.len() < expected_len {
+                return Err(GenericConversionError::NotEnoughAppData {
+                    expected: expected_len,
+                    found: app_data.len(),
+                }
+                .into());
+            }
+            load_data = &app_data[MemoryAreaHeader::LEN..MemoryAreaHeader::LEN + len];
+            let crc =
+                u16::from_be_bytes(app_data[expected_len - 2..expected_len].try_into().unwrap());
+            if rejection.is_none() && CRC_CCITT_FALSE.checksum(load_data) != crc {
+                rejection = Some(mem_res_code::CRC_MISMATCH);
+            }
+        }
+        if rejection.is_none()
+            && mem_subservice == MemorySubservice::TcDumpRawMemory
+            && len > self.max_dump_len
+        {
+            rejection = Some(mem_res_code::DUMP_TOO_LARGE);
+        }
+        if rejection.is_none()
+            && self
+                .memory
+                .validate_access(header.memory_id, header.address, len, access)
+                .is_err()
+        {
+            rejection = Some(mem_res_code::ACCESS_DENIED);
+        }
+        if let Some(failure_code) = rejection {
+            self.start_failure(
+                &mut error_callback,
+                token,
+                time_stamp,
+                &failure_code,
+                &failure_data,
+            );
+            return Ok(HandlingStatus::HandledOne.into());
+        }
+        let dest_id = tc.source_id();
+        let opt_started_token = self.start_success(&mut error_callback, token, time_stamp);
+        let failure_code = match mem_subservice {
+            MemorySubservice::TcLoadRawMemory => self
+                .memory
+                .write(header.memory_id, header.address, load_data)
+                .err()
+                .map(|_| mem_res_code::MEMORY_ACCESS_FAILED),
+            MemorySubservice::TcDumpRawMemory => {
+                self.dump(&mut error_callback, &header, dest_id, time_stamp)
+            }
+            _ => self.check(&mut error_callback, &header, dest_id, time_stamp),
+        };
+        self.completion(
+            &mut error_callback,
+            opt_started_token,
+            time_stamp,
+            failure_code
+                .as_ref()
+                .map(|code| (code, failure_data.as_slice())),
+        );
+        Ok(HandlingStatus::HandledOne.into())
+    }
+
+    /// Dump the memory area and send the TM[6,6] report, using the large data transfer service
+    /// if necessary. Returns the failure code if the dump failed.
+    fn dump(
+        &mut self,
+        error_callback: &mut impl FnMut(&PartialPusHandlingError),
+        header: &MemoryAreaHeader,
+        dest_id: u16,
+        time_stamp: &[u8],
+    ) -> Option<ResultU16> {
+        let len = header.len as usize;
+        let data_range = MemoryAreaHeader::LEN..MemoryAreaHeader::LEN + len;
+        let mut report = vec![0; MemoryAreaHeader::LEN + len + 2];
+        header.write_to_be_bytes(&mut report).unwrap();
+        if self
+            .memory
+            .read(
+                header.memory_id,
+                header.address,
+                &mut report[data_range.clone()],
+            )
+            .is_err()
+        {
+            return Some(mem_res_code::MEMORY_ACCESS_FAILED);
+        }
+        let crc = CRC_CCITT_FALSE.checksum(&report[data_range.clone()]);
+        report[data_range.end..].copy_from_slice(&crc.to_be_bytes());
+        if report.len() <= self.max_report_source_data_len {
+            return self.report_result(
+                error_callback,
+                self.send_report(
+                    MemorySubservice::TmDumpedRawMemory,
+                    dest_id,
+                    &report,
+                    time_stamp,
+                ),
+            );
+        }
+        let large_data_creator = PusLargeDataTmCreator::new(
+            self.service_helper.id(),
+            self.service_helper.verif_reporter().apid(),
+            dest_id,
+            self.max_report_source_data_len
+                .saturating_sub(PartHeader::LEN),
+        );
+        let mut large_data_creator = match large_data_creator {
+            Some(creator) => creator,
+            None => return Some(mem_res_code::REPORT_GENERATION_FAILED),
+        };
+        let transaction_id = self.next_transaction_id;
+        self.next_transaction_id = self.next_transaction_id.wrapping_add(1);
+        match large_data_creator.send_large_data(
+            transaction_id,
+            &report,
+            time_stamp,
+            &self.service_helper.common.tm_sender,
+        ) {
+            Ok(_) => None,
+            Err(_) => Some(mem_res_code::REPORT_GENERATION_FAILED),
+        }
+    }
+
+    /// Calculate the CRC of the memory area and send the TM[6,10] report. The memory area is
+    /// read in chunks. Returns the failure code if the check failed.
+    fn check(
+        &mut self,
+        error_callback: &mut impl FnMut(&PartialPusHandlingError),
+        header: &MemoryAreaHeader,
+        dest_id: u16,
+        time_stamp: &[u8],
+    ) -> Option<ResultU16> {
+        let mut digest = CRC_CCITT_FALSE.digest();
+        let mut chunk = [0; 256];
+        let mut offset = 0;
+        while offset < header.len as usize {
+            let chunk_len = core::cmp::min(chunk.len(), header.len as usize - offset);
+            let address = match header.address.checked_add(offset as u64) {
+                Some(address) => address,
+                None => return Some(mem_res_code::INVALID_MEMORY_AREA),
+            };
+            if self
+                .memory
+                .read(header.memory_id, address, &mut chunk[..chunk_len])
+                .is_err()
+            {
+                return Some(mem_res_code::MEMORY_ACCESS_FAILED);
+            }
+            digest.update(&chunk[..chunk_len]);
+            offset += chunk_len;
+        }
+        let mut report = [0; MemoryAreaHeader::LEN + 2];
+        header.write_to_be_bytes(&mut report).unwrap();
+        report[MemoryAreaHeader::LEN..].copy_from_slice(&digest.finalize().to_be_bytes());
+        self.report_result(
+            error_callback,
+            self.send_report(
+                MemorySubservice::TmCheckedRawMemory,
+                dest_id,
+                &report,
+                time_stamp,
+            ),
+        )
+    }
+
+    fn send_report(
+        &self,
+        subservice: MemorySubservice,
+        dest_id: u16,
+        source_data: &[u8],
+        time_stamp: &[u8],
+    ) -> Result<(), EcssTmtcError> {
+        let sec_header = PusTmSecondaryHeader::new(6, subservice.into(), 0, dest_id, time_stamp);
+        let tm = PusTmCreator::new(
+            SpHeader::new_from_apid(self.service_helper.verif_reporter().apid()),
+            sec_header,
+            source_data,
+            true,
+        );
+        self.service_helper
+            .common
+            .tm_sender
+            .send_tm(self.service_helper.id(), PusTmVariant::Direct(tm))
+    }
+
+    fn report_result(
+        &self,
+        error_callback: &mut impl FnMut(&PartialPusHandlingError),
+        result: Result<(), EcssTmtcError>,
+    ) -> Option<ResultU16> {
+        match result {
+            Ok(_) => None,
+            Err(e) => {
+                error_callback(&PartialPusHandlingError::TmSend(e));
+                Some(mem_res_code::REPORT_GENERATION_FAILED)
+            }
+        }
+    }
+
+    fn start_success(
+        &self,
+        error_callback: &mut impl FnMut(&PartialPusHandlingError),
+        token: VerificationToken<TcStateAccepted>,
+        time_stamp: &[u8],
+    ) -> Option<VerificationToken<TcStateStarted>> {
+        match self.service_helper.verif_reporter().start_success(
+            &self.service_helper.common.tm_sender,
+            token,
+            time_stamp,
+        ) {
+            Ok(started_token) => Some(started_token),
+            Err(e) => {
+                error_callback(&PartialPusHandlingError::Verification(e));
+                None
+            }
+        }
+    }
+
+    fn start_failure(
+        &self,
+        error_callback: &mut impl FnMut(&PartialPusHandlingError),
+        token: VerificationToken<TcStateAccepted>,
+        time_stamp: &[u8],
+        failure_code: &ResultU16,
+        failure_data: &[u8],
+    ) {
+        if let Err(e) = self.service_helper.verif_reporter().start_failure(
+            &self.service_helper.common.tm_sender,
+            token,
+            FailParams::new(time_stamp, failure_code, failure_data),
+        ) {
+            error_callback(&PartialPusHandlingError::Verification(e));
+        }
+    }
+
+    /// Report the completion success or a completion failure with the given failure code and
+    /// failure data.
+    fn completion(
+        &self,
+        error_callback: &mut impl FnMut(&PartialPusHandlingError),
+        opt_started_token: Option<VerificationToken<TcStateStarted>>,
+        time_stamp: &[u8],
+        failure: Option<(&ResultU16, &[u8])>,
+    ) {
+        let started_token = match opt_started_token {
+            Some(token) => token,
+            None => return,
+        };
+        let result = match failure {
+            Some((failure_code, failure_data)) => {
+                self.service_helper.verif_reporter().completion_failure(
+                    &self.service_helper.common.tm_sender,
+                    started_token,
+                    FailParams::new(time_stamp, failure_code, failure_data),
+                )
+            }
+            None => self.service_helper.verif_reporter().completion_success(
+                &self.service_helper.common.tm_sender,
+                started_token,
+                time_stamp,
+            ),
+        };
+        if let Err(e) = result {
+            error_callback(&PartialPusHandlingError::Verification(e));
+        }
+    }
+}
+
+/// Helper type definition for a PUS 6 handler with a dynamic TMTC memory backend and regular
+/// mpsc queues.
+pub type PusService6MemoryHandlerDynWithMpsc<Memory> = PusMemoryServiceHandler<
+    MpscTcReceiver,
+    mpsc::Sender<PacketAsVec>,
+    EcssTcInVecConverter,
+    VerificationReporter,
+    Memory,
+>;
+/// Helper type definition for a PUS 6 handler with a dynamic TMTC memory backend and bounded MPSC
+/// queues.
+pub type PusService6MemoryHandlerDynWithBoundedMpsc<Memory> = PusMemoryServiceHandler<
+    MpscTcReceiver,
+    mpsc::SyncSender<PacketAsVec>,
+    EcssTcInVecConverter,
+    VerificationReporter,
+    Memory,
+>;
+/// Helper type definition for a PUS 6 handler with a shared store TMTC memory backend and bounded
+/// mpsc queues.
+pub type PusService6MemoryHandlerStaticWithBoundedMpsc<Memory> = PusMemoryServiceHandler<
+    MpscTcReceiver,
+    PacketSenderWithSharedPool,
+    EcssTcInSharedStoreConverter,
+    VerificationReporter,
+    Memory,
+>;
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use delegate::delegate;
+    use spacepackets::ecss::tc::{PusTcCreator, PusTcSecondaryHeader};
+    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::PusPacket;
+    use spacepackets::time::cds;
+    use spacepackets::time::TimeWriter;
+    use spacepackets::SpHeader;
+
+    use super::*;
+    use crate::pus::large_data::LargeDataSubservice;
+    use crate::pus::memory::{MemoryError, MemoryId};
+    use crate::pus::test_util::{PusTestHarness, TEST_APID};
+    use crate::pus::tests::PusServiceHandlerWithVecCommon;
+    use crate::pus::tm_limit::TmSizePolicy;
+    use crate::pus::verification::RequestId;
+
+    const TEST_MEMORY_ID: MemoryId = 1;
+
+    /// Test memory which rejects all writes to the first 4 bytes.
+    struct TestMemory {
+        mem: Vec<u8>,
+    }
+
+    impl TestMemory {
+        fn offsets(
+            &self,
+            memory_id: MemoryId,
+            address: u64,
+            len: usize,
+        ) -> Result<core::ops::Range<usize>, MemoryError> {
+            if memory_id != TEST_MEMORY_ID {
+                return Err(MemoryError::UnknownMemory(memory_id));
+            }
+            let start = address as usize;
+            if start + len > self.mem.len() {
+                return Err(MemoryError::OutOfBounds {
+                    memory_id,
+                    address,
+                    len,
+                });
+            }
+            Ok(start..start + len)
+        }
+    }
+
+    impl MemoryProvider for TestMemory {
+        fn validate_access(
+            &self,
+            memory_id: MemoryId,
+            address: u64,
+            len: usize,
+            access: MemoryAccess,
+        ) -> Result<(), MemoryError> {
+            self.offsets(memory_id, address, len)?;
+            if access == MemoryAccess::Write && address < 4 {
+                return Err(MemoryError::AccessDenied {
+                    memory_id,
+                    address,
+                    len,
+                    access,
+                });
+            }
+            Ok(())
+        }
+
+        fn read(
+            &mut self,
+            memory_id: MemoryId,
+            address: u64,
+            buf: &mut [u8],
+        ) -> Result<(), MemoryError> {
+            let range = self.offsets(memory_id, address, buf.len())?;
+            buf.copy_from_slice(&self.mem[range]);
+            Ok(())
+        }
+
+        fn write(
+            &mut self,
+            memory_id: MemoryId,
+            address: u64,
+            data: &[u8],
+        ) -> Result<(), MemoryError> {
+            let range = self.offsets(memory_id, address, data.len())?;
+            self.mem[range].copy_from_slice(data);
+            Ok(())
+        }
+    }
+
+    struct Pus6HandlerTester {
+        common: PusServiceHandlerWithVecCommon,
+        handler: PusService6MemoryHandlerDynWithMpsc<TestMemory>,
+    }
+
+    impl Pus6HandlerTester {
+        pub fn new() -> Self {
+            let (common, srv_handler) =
+                PusServiceHandlerWithVecCommon::new_with_standard_verif_reporter(0);
+            let memory = TestMemory {
+                mem: (0..64).collect(),
+            };
+            Self {
+                common,
+                handler: PusMemoryServiceHandler::new(srv_handler, memory),
+            }
+        }
+
+        pub fn send_request(&mut self, subservice: MemorySubservice, app_data: &[u8]) -> RequestId {
+            let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+            let sec_header = PusTcSecondaryHeader::new_simple(6, subservice.into());
+            let tc = PusTcCreator::new(tc_header, sec_header, app_data, true);
+            let token = self.init_verification(&tc);
+            self.send_tc(&token, &tc);
+            let time_stamp = cds::CdsTime::new_with_u16_days(0, 0).to_vec().unwrap();
+            self.handler
+                .poll_and_handle_next_tc(|_| {}, &time_stamp)
+                .unwrap();
+            token.request_id()
+        }
+
+        pub fn check_failure(&mut self, subservice: u8, failure_code: ResultU16) {
+            let failure_tm = self.read_next_tm();
+            assert_eq!(failure_tm.service(), 1);
+            assert_eq!(failure_tm.subservice(), subservice);
+            let user_data = failure_tm.user_data();
+            assert_eq!(
+                u16::from_be_bytes(user_data[4..6].try_into().unwrap()),
+                failure_code.raw()
+            );
+        }
+    }
+
+    impl PusTestHarness for Pus6HandlerTester {
+        fn init_verification(&mut self, tc: &PusTcCreator) -> VerificationToken<TcStateAccepted> {
+            let init_token = self.handler.service_helper.verif_reporter_mut().add_tc(tc);
+            self.handler
+                .service_helper
+                .verif_reporter()
+                .acceptance_success(self.handler.service_helper.tm_sender(), init_token, &[0; 7])
+                .expect("acceptance success failure")
+        }
+
+        fn send_tc(&self, token: &VerificationToken<TcStateAccepted>, tc: &PusTcCreator) {
+            self.common
+                .send_tc(self.handler.service_helper.id(), token, tc);
+        }
+
+        delegate! {
+            to self.common {
+                fn read_next_tm(&mut self) -> PusTmReader<'_>;
+                fn check_no_tm_available(&self) -> bool;
+                fn check_next_verification_tm(&self, subservice: u8, expected_request_id: RequestId);
+            }
+        }
+    }
+
+    fn request_app_data(header: MemoryAreaHeader, data: Option<&[u8]>) -> Vec<u8> {
+        let mut app_data = vec![0; MemoryAreaHeader::LEN];
+        header.write_to_be_bytes(&mut app_data).unwrap();
+        if let Some(data) = data {
+            app_data.extend_from_slice(data);
+            app_data.extend_from_slice(&CRC_CCITT_FALSE.checksum(data).to_be_bytes());
+        }
+        app_data
+    }
+
+    #[test]
+    fn test_load_memory() {
+        let mut tester = Pus6HandlerTester::new();
+        let app_data = request_app_data(
+            MemoryAreaHeader::new(TEST_MEMORY_ID, 8, 3),
+            Some(&[1, 2, 3]),
+        );
+        let request_id = tester.send_request(MemorySubservice::TcLoadRawMemory, &app_data);
+        tester.check_next_verification_tm(1, request_id);
+        tester.check_next_verification_tm(3, request_id);
+        tester.check_next_verification_tm(7, request_id);
+        assert!(tester.check_no_tm_available());
+        assert_eq!(&tester.handler.memory().mem[8..11], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_load_memory_rejected() {
+        let mut tester = Pus6HandlerTester::new();
+        let mut app_data = request_app_data(
+            MemoryAreaHeader::new(TEST_MEMORY_ID, 8, 3),
+            Some(&[1, 2, 3]),
+        );
+        // Corrupt the CRC.
+        *app_data.last_mut().unwrap() ^= 0xff;
+        let request_id = tester.send_request(MemorySubservice::TcLoadRawMemory, &app_data);
+        tester.check_next_verification_tm(1, request_id);
+        tester.check_failure(4, mem_res_code::CRC_MISMATCH);
+
+        // Writing the first 4 bytes is rejected by the address validation.
+        let app_data = request_app_data(MemoryAreaHeader::new(TEST_MEMORY_ID, 2, 1), Some(&[1]));
+        let request_id = tester.send_request(MemorySubservice::TcLoadRawMemory, &app_data);
+        tester.check_next_verification_tm(1, request_id);
+        tester.check_failure(4, mem_res_code::ACCESS_DENIED);
+        assert!(tester.check_no_tm_available());
+        assert_eq!(
+            &tester.handler.memory().mem[0..12],
+            &(0..12).collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn test_dump_memory() {
+        let mut tester = Pus6HandlerTester::new();
+        let header = MemoryAreaHeader::new(TEST_MEMORY_ID, 4, 8);
+        let request_id = tester.send_request(
+            MemorySubservice::TcDumpRawMemory,
+            &request_app_data(header, None),
+        );
+        tester.check_next_verification_tm(1, request_id);
+        tester.check_next_verification_tm(3, request_id);
+        let report = tester.read_next_tm();
+        assert_eq!(report.service(), 6);
+        assert_eq!(
+            report.subservice(),
+            u8::from(MemorySubservice::TmDumpedRawMemory)
+        );
+        let source_data = report.user_data();
+        assert_eq!(
+            MemoryAreaHeader::from_be_bytes(source_data).unwrap(),
+            header
+        );
+        let data = &source_data[MemoryAreaHeader::LEN..MemoryAreaHeader::LEN + 8];
+        assert_eq!(data, &(4..12).collect::<Vec<u8>>());
+        assert_eq!(
+            u16::from_be_bytes(source_data[MemoryAreaHeader::LEN + 8..].try_into().unwrap()),
+            CRC_CCITT_FALSE.checksum(data)
+        );
+        tester.check_next_verification_tm(7, request_id);
+        assert!(tester.check_no_tm_available());
+    }
+
+    #[test]
+    fn test_apply_tm_size_limit() {
+        let mut tester = Pus6HandlerTester::new();
+        let limit = TmSizeLimit::new(128, TmSizePolicy::Segment).unwrap();
+        tester.handler.apply_tm_size_limit(&limit, 7);
+        assert_eq!(tester.handler.max_report_source_data_len, 106);
+    }
+
+    #[test]
+    fn test_large_dump() {
+        let mut tester = Pus6HandlerTester::new();
+        tester.handler.max_report_source_data_len = 32;
+        let header = MemoryAreaHeader::new(TEST_MEMORY_ID, 0, 40);
+        let request_id = tester.send_request(
+            MemorySubservice::TcDumpRawMemory,
+            &request_app_data(header, None),
+        );
+        tester.check_next_verification_tm(1, request_id);
+        tester.check_next_verification_tm(3, request_id);
+        // The report has a length of 56 bytes and is split into parts with 26 bytes of data.
+        let mut report = Vec::new();
+        for subservice in [
+            LargeDataSubservice::TmFirstDownlinkPart,
+            LargeDataSubservice::TmIntermediateDownlinkPart,
+            LargeDataSubservice::TmLastDownlinkPart,
+        ] {
+            let part = tester.read_next_tm();
+            assert_eq!(part.service(), 13);
+            assert_eq!(part.subservice(), u8::from(subservice));
+            report.extend_from_slice(&part.user_data()[PartHeader::LEN..]);
+        }
+        assert_eq!(report.len(), 56);
+        assert_eq!(MemoryAreaHeader::from_be_bytes(&report).unwrap(), header);
+        assert_eq!(
+            &report[MemoryAreaHeader::LEN..54],
+            &(0..40).collect::<Vec<u8>>()
+        );
+        tester.check_next_verification_tm(7, request_id);
+
+        tester.handler.max_dump_len = 32;
+        let request_id = tester.send_request(
+            MemorySubservice::TcDumpRawMemory,
+            &request_app_data(header, None),
+        );
+        tester.check_next_verification_tm(1, request_id);
+        tester.check_failure(4, mem_res_code::DUMP_TOO_LARGE);
+    }
+
+    #[test]
+    fn test_check_memory() {
+        let mut tester = Pus6HandlerTester::new();
+        let header = MemoryAreaHeader::new(TEST_MEMORY_ID, 0, 64);
+        let request_id = tester.send_request(
+            MemorySubservice::TcCheckRawMemory,
+            &request_app_data(header, None),
+        );
+        tester.check_next_verification_tm(1, request_id);
+        tester.check_next_verification_tm(3, request_id);
+        let report = tester.read_next_tm();
+        assert_eq!(
+            report.subservice(),
+            u8::from(MemorySubservice::TmCheckedRawMemory)
+        );
+        let source_data = report.user_data();
+        assert_eq!(source_data.len(), MemoryAreaHeader::LEN + 2);
+        assert_eq!(
+            u16::from_be_bytes(source_data[MemoryAreaHeader::LEN..].try_into().unwrap()),
+            CRC_CCITT_FALSE.checksum(&(0..64).collect::<Vec<u8>>())
+        );
+        tester.check_next_verification_tm(7, request_id);
+
+        // Out of bounds accesses are rejected by the address validation.
+        let request_id = tester.send_request(
+            MemorySubservice::TcCheckRawMemory,
+            &request_app_data(MemoryAreaHeader::new(TEST_MEMORY_ID, 60, 8), None),
+        );
+        tester.check_next_verification_tm(1, request_id);
+        tester.check_failure(4, mem_res_code::ACCESS_DENIED);
+    }
+
+    #[test]
+    fn test_memory_area_overflow() {
+        let mut tester = Pus6HandlerTester::new();
+        let request_id = tester.send_request(
+            MemorySubservice::TcCheckRawMemory,
+            &request_app_data(MemoryAreaHeader::new(TEST_MEMORY_ID, u64::MAX - 4, 8), None),
+        );
+        tester.check_next_verification_tm(1, request_id);
+        tester.check_failure(4, mem_res_code::INVALID_MEMORY_AREA);
+        assert!(tester.check_no_tm_available());
+    }
+}
@@ -25,16 +25,39 @@ use spacepackets::ecss::PusError;
 use spacepackets::{ByteConversionError, SpHeader};
 
 pub mod action;
+pub mod app_data;
+#[cfg(feature = "alloc")]
+pub mod dest_id;
+pub mod device_access;
 pub mod event;
+#[cfg(feature = "alloc")]
+pub mod event_action;
+pub mod event_log;
 pub mod event_man;
 #[cfg(feature = "std")]
 pub mod event_srv;
+#[cfg(feature = "std")]
+pub mod executor;
+#[cfg(feature = "std")]
+pub mod harness;
+pub mod large_data;
+pub mod memory;
+#[cfg(feature = "std")]
+pub mod memory_srv;
 pub mod mode;
 pub mod scheduler;
 #[cfg(feature = "std")]
 pub mod scheduler_srv;
+pub mod tc_auth;
+#[cfg(feature = "alloc")]
+pub mod tc_builder;
+#[cfg(feature = "alloc")]
+pub mod tc_quarantine;
 #[cfg(feature = "std")]
 pub mod test;
+#[cfg(all(feature = "std", any(feature = "test_util", test)))]
+pub mod test_harness;
+pub mod tm_limit;
 pub mod verification;
 
 #[cfg(feature = "alloc")]
@@ -43,6 +66,9 @@ pub use alloc_mod::*;
 #[cfg(feature = "std")]
 pub use std_mod::*;
 
+#[cfg(feature = "heapless")]
+pub use heapless_mod::*;
+
 use self::verification::VerificationReportingProvider;
 
 /// Generic handling status for an object which is able to continuosly handle a queue to handle
@@ -81,6 +107,11 @@ pub enum EcssTmtcError {
     CantSendDirectTm,
     Send(GenericSendError),
     Receive(GenericReceiveError),
+    /// The TM packet exceeds the configured [tm_limit::TmSizeLimit].
+    TmTooLarge {
+        len: usize,
+        max_len: usize,
+    },
 }
 
 impl Display for EcssTmtcError {
@@ -107,6 +138,9 @@ impl Display for EcssTmtcError {
             EcssTmtcError::Receive(e) => {
                 write!(f, "ecss tmtc error {e}")
             }
+            EcssTmtcError::TmTooLarge { len, max_len } => {
+                write!(f, "TM packet length {len} exceeds maximum length {max_len}")
+            }
         }
     }
 }
@@ -167,6 +201,20 @@ pub trait ChannelWithId: Send {
 /// This sender object is responsible for sending PUS telemetry to a TM sink.
 pub trait EcssTmSender: Send {
     fn send_tm(&self, sender_id: ComponentId, tm: PusTmVariant) -> Result<(), EcssTmtcError>;
+
+    /// Send a batch of telemetry packets. Senders can override this to send the batch more
+    /// efficiently, for example with a single pool lock acquisition. The default implementation
+    /// sends the packets individually with [Self::send_tm].
+    fn send_tm_batch(
+        &self,
+        sender_id: ComponentId,
+        tms: &[PusTmCreator],
+    ) -> Result<(), EcssTmtcError> {
+        for tm in tms {
+            self.send_tm(sender_id, PusTmVariant::Direct(tm.clone()))?;
+        }
+        Ok(())
+    }
 }
 
 /// Generic trait for a user supplied sender object.
@@ -233,6 +281,13 @@ pub struct AcceptedEcssTcAndToken {
     pub token: VerificationToken<TcStateAccepted>,
 }
 
+impl AcceptedEcssTcAndToken {
+    /// Source ID of the telecommand if it is carried by the verification token.
+    pub fn source_id(&self) -> Option<u16> {
+        self.token.source_id()
+    }
+}
+
 impl From<AcceptedEcssTcAndToken> for EcssTcAndToken {
     fn from(value: AcceptedEcssTcAndToken) -> Self {
         EcssTcAndToken {
@@ -283,6 +338,16 @@ impl From<PoolError> for TryRecvTmtcError {
 /// Generic trait for a user supplied receiver object.
 pub trait EcssTcReceiver {
     fn recv_tc(&self) -> Result<EcssTcAndToken, TryRecvTmtcError>;
+
+    /// Blocking variant of [Self::recv_tc] which waits up to the given timeout for the next
+    /// telecommand. [TryRecvTmtcError::Empty] is returned if no telecommand was received until
+    /// the timeout elapsed.
+    ///
+    /// Receivers which can not block, for example receivers based on local queues, use the
+    /// default implementation, which does not wait and is equivalent to [Self::recv_tc].
+    fn recv_tc_timeout(&self, _timeout: Duration) -> Result<EcssTcAndToken, TryRecvTmtcError> {
+        self.recv_tc()
+    }
 }
 
 /// Generic trait for objects which can send ECSS PUS telecommands.
@@ -330,6 +395,51 @@ pub trait PusRequestRouter<Request> {
     ) -> Result<(), Self::Error>;
 }
 
+/// Typed envelope for a targeted request which was created from a PUS telecommand.
+///
+/// It bundles the request with the ID of the sender, the ID of the target and the verification
+/// token of the telecommand. The request ID of the metadata is the raw verification request ID,
+/// which allows correlating replies with the telecommand. The source ID of the telecommand is
+/// carried by the token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PusRequestEnvelope<Request> {
+    pub requestor_info: MessageMetadata,
+    pub target_id: ComponentId,
+    pub token: VerificationToken<TcStateAccepted>,
+    pub request: Request,
+}
+
+impl<Request> PusRequestEnvelope<Request> {
+    pub fn new(
+        token: VerificationToken<TcStateAccepted>,
+        sender_id: ComponentId,
+        target_id: ComponentId,
+        request: Request,
+    ) -> Self {
+        Self {
+            requestor_info: MessageMetadata::new(token.request_id().raw(), sender_id),
+            target_id,
+            token,
+            request,
+        }
+    }
+
+    /// Source ID of the telecommand if it is carried by the verification token.
+    pub fn source_id(&self) -> Option<u16> {
+        self.token.source_id()
+    }
+
+    /// Route the request to its target. The verification token is returned on success so it
+    /// can be stored, for example inside an active request map.
+    pub fn route<Router: PusRequestRouter<Request>>(
+        self,
+        router: &Router,
+    ) -> Result<VerificationToken<TcStateAccepted>, Router::Error> {
+        router.route(self.requestor_info, self.target_id, self.request)?;
+        Ok(self.token)
+    }
+}
+
 pub trait PusReplyHandler<ActiveRequestInfo: ActiveRequestProvider, ReplyType> {
     type Error;
 
@@ -360,9 +470,88 @@ pub trait PusReplyHandler<ActiveRequestInfo: ActiveRequestProvider, ReplyType> {
     ) -> Result<(), Self::Error>;
 }
 
+/// Channel backends based on the [heapless::spsc] single-producer single-consumer queues.
+///
+/// These backends do not require a heap and can be used in `no_std` environments. Because the
+/// [heapless::spsc::Producer] and [heapless::spsc::Consumer] require mutable access, they are
+/// wrapped inside a [RefCell]. The queues can not detect a disconnected endpoint, so only
+/// the [GenericSendError::QueueFull] and [TryRecvTmtcError::Empty] errors are returned.
+///
+/// Telecommands can only be passed around as [PacketInPool] without a heap, so there is no
+/// [EcssTcSender] implementation. TCs can be sent by storing them in a pool and then sending
+/// a [EcssTcAndToken] using the [heapless::spsc::Producer] directly.
+#[cfg(feature = "heapless")]
+pub mod heapless_mod {
+    use core::cell::RefCell;
+    use heapless::spsc::{Consumer, Producer};
+
+    use super::*;
+
+    /// [EcssTmSender] implementation for the producer of a [heapless::spsc::Queue] with a
+    /// capacity of N - 1. Only TM inside a pool can be sent.
+    pub struct HeaplessTmInStoreSender<'queue, const N: usize> {
+        producer: RefCell<Producer<'queue, PacketInPool, N>>,
+    }
+
+    impl<'queue, const N: usize> HeaplessTmInStoreSender<'queue, N> {
+        pub fn new(producer: Producer<'queue, PacketInPool, N>) -> Self {
+            Self {
+                producer: RefCell::new(producer),
+            }
+        }
+
+        pub fn into_inner(self) -> Producer<'queue, PacketInPool, N> {
+            self.producer.into_inner()
+        }
+    }
+
+    impl<const N: usize> EcssTmSender for HeaplessTmInStoreSender<'_, N> {
+        fn send_tm(&self, sender_id: ComponentId, tm: PusTmVariant) -> Result<(), EcssTmtcError> {
+            match tm {
+                PusTmVariant::InStore(addr) => self
+                    .producer
+                    .borrow_mut()
+                    .enqueue(PacketInPool::new(sender_id, addr))
+                    .map_err(|_| {
+                        // The usable capacity of the heapless queue is N - 1.
+                        EcssTmtcError::Send(GenericSendError::QueueFull(Some((N - 1) as u32)))
+                    }),
+                PusTmVariant::Direct(_) => Err(EcssTmtcError::CantSendDirectTm),
+            }
+        }
+    }
+
+    /// [EcssTcReceiver] implementation for the consumer of a [heapless::spsc::Queue].
+    pub struct HeaplessTcReceiver<'queue, const N: usize> {
+        consumer: RefCell<Consumer<'queue, EcssTcAndToken, N>>,
+    }
+
+    impl<'queue, const N: usize> HeaplessTcReceiver<'queue, N> {
+        pub fn new(consumer: Consumer<'queue, EcssTcAndToken, N>) -> Self {
+            Self {
+                consumer: RefCell::new(consumer),
+            }
+        }
+
+        pub fn into_inner(self) -> Consumer<'queue, EcssTcAndToken, N> {
+            self.consumer.into_inner()
+        }
+    }
+
+    impl<const N: usize> EcssTcReceiver for HeaplessTcReceiver<'_, N> {
+        fn recv_tc(&self) -> Result<EcssTcAndToken, TryRecvTmtcError> {
+            self.consumer
+                .borrow_mut()
+                .dequeue()
+                .ok_or(TryRecvTmtcError::Empty)
+        }
+    }
+}
+
 #[cfg(feature = "alloc")]
 pub mod alloc_mod {
     use hashbrown::HashMap;
+    use spacepackets::ecss::PusPacket;
 
     use super::*;
 
@@ -443,6 +632,68 @@ pub mod alloc_mod {
 
     impl_downcast!(EcssTcReceiverExt);
 
+    /// Simple [EcssTcReceiver] which stores the telecommands as `Vec<u8>` inside a local queue.
+    ///
+    /// This receiver does not require a TC pool or a message queue and can be used together with
+    /// the [EcssTcInVecConverter] to drive the PUS service handlers directly, for example in
+    /// simple tools, tests or ground support software. The telecommands can be pushed while the
+    /// receiver is owned by a service handler because interior mutability is used.
+    #[derive(Debug, Default)]
+    pub struct EcssTcInVecQueue {
+        queue: core::cell::RefCell<alloc::collections::VecDeque<EcssTcAndToken>>,
+    }
+
+    impl EcssTcInVecQueue {
+        pub fn push(&self, tc_and_token: EcssTcAndToken) {
+            self.queue.borrow_mut().push_back(tc_and_token);
+        }
+
+        /// Push a raw telecommand. The telecommand is copied into a [alloc::vec::Vec].
+        pub fn push_raw(
+            &self,
+            sender_id: ComponentId,
+            tc_raw: &[u8],
+            token: impl Into<TcStateToken>,
+        ) {
+            self.push(EcssTcAndToken::new(
+                PacketAsVec::new(sender_id, tc_raw.to_vec()),
+                token,
+            ));
+        }
+
+        /// Serialize and push a telecommand.
+        pub fn push_tc(
+            &self,
+            sender_id: ComponentId,
+            tc: &PusTcCreator,
+            token: impl Into<TcStateToken>,
+        ) -> Result<(), PusError> {
+            use spacepackets::ecss::WritablePusPacket;
+            self.push(EcssTcAndToken::new(
+                PacketAsVec::new(sender_id, tc.to_vec()?),
+                token,
+            ));
+            Ok(())
+        }
+
+        pub fn len(&self) -> usize {
+            self.queue.borrow().len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.queue.borrow().is_empty()
+        }
+    }
+
+    impl EcssTcReceiver for EcssTcInVecQueue {
+        fn recv_tc(&self) -> Result<EcssTcAndToken, TryRecvTmtcError> {
+            self.queue
+                .borrow_mut()
+                .pop_front()
+                .ok_or(TryRecvTmtcError::Empty)
+        }
+    }
+
     /// This trait is an abstraction for the conversion of a PUS telecommand into a generic request
     /// type.
     ///
@@ -654,6 +905,187 @@ pub mod alloc_mod {
         }
     }
     */
+
+    /// Selects the telecommands which are handled by a handler registered at the
+    /// [PusTcRouter].
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum PusTcFilter {
+        /// All subservices of a service.
+        Service(u8),
+        /// Exactly one subservice of a service.
+        ServiceAndSubservice(u8, u8),
+        /// Default handler for all telecommands not matched by any other filter.
+        Default,
+    }
+
+    impl PusTcFilter {
+        pub fn matches(&self, service: u8, subservice: u8) -> bool {
+            match self {
+                PusTcFilter::Service(srv) => *srv == service,
+                PusTcFilter::ServiceAndSubservice(srv, subsrv) => {
+                    *srv == service && *subsrv == subservice
+                }
+                PusTcFilter::Default => true,
+            }
+        }
+
+        /// Precedence of the filter. Lower values have a higher precedence.
+        fn precedence(&self) -> u8 {
+            match self {
+                PusTcFilter::ServiceAndSubservice(_, _) => 0,
+                PusTcFilter::Service(_) => 1,
+                PusTcFilter::Default => 2,
+            }
+        }
+    }
+
+    /// ID which is assigned to each handler registered at the [PusTcRouter]. It can be used to
+    /// remove the handler again.
+    pub type PusTcHandlerId = u32;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum PusTcRoutingError<SendError> {
+        /// No handler is registered for the service and subservice of the telecommand.
+        NoHandler {
+            service: u8,
+            subservice: u8,
+        },
+        Send(SendError),
+    }
+
+    impl<SendError: Display> Display for PusTcRoutingError<SendError> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                PusTcRoutingError::NoHandler {
+                    service,
+                    subservice,
+                } => {
+                    write!(
+                        f,
+                        "no handler for service {service} and subservice {subservice}"
+                    )
+                }
+                PusTcRoutingError::Send(e) => write!(f, "pus tc routing error: {e}"),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<SendError: Error + 'static> Error for PusTcRoutingError<SendError> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                PusTcRoutingError::Send(e) => Some(e),
+                _ => None,
+            }
+        }
+    }
+
+    /// Router for PUS telecommands which forwards each telecommand to a handler based on the
+    /// service and subservice of the telecommand.
+    ///
+    /// Handlers can be registered for whole services, for service and subservice combinations,
+    /// and as a fall-through default handler. A handler registered for the service and subservice
+    /// combination takes precedence over a handler for the whole service, which in turn takes
+    /// precedence over the default handler. Each filter can only be registered once.
+    ///
+    /// The router itself implements [PacketSenderPusTc], so routers can also be nested.
+    pub struct PusTcRouter<Sender: PacketSenderPusTc> {
+        handlers: alloc::vec::Vec<(PusTcHandlerId, PusTcFilter, Sender)>,
+        next_id: PusTcHandlerId,
+    }
+
+    impl<Sender: PacketSenderPusTc> Default for PusTcRouter<Sender> {
+        fn default() -> Self {
+            Self {
+                handlers: alloc::vec::Vec::new(),
+                next_id: 0,
+            }
+        }
+    }
+
+    impl<Sender: PacketSenderPusTc> PusTcRouter<Sender> {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Register a handler for the given filter. Returns the ID of the handler which can be
+        /// used to remove it, or the handler back if the filter is already registered.
+        pub fn add_handler(
+            &mut self,
+            filter: PusTcFilter,
+            handler: Sender,
+        ) -> Result<PusTcHandlerId, Sender> {
+            if self
+                .handlers
+                .iter()
+                .any(|(_, existing, _)| *existing == filter)
+            {
+                return Err(handler);
+            }
+            let id = self.next_id;
+            self.next_id = self.next_id.wrapping_add(1);
+            self.handlers.push((id, filter, handler));
+            Ok(id)
+        }
+
+        /// Remove the handler with the given ID. Returns the filter and the handler if a handler
+        /// was removed.
+        pub fn remove_handler(&mut self, id: PusTcHandlerId) -> Option<(PusTcFilter, Sender)> {
+            let idx = self
+                .handlers
+                .iter()
+                .position(|(handler_id, _, _)| *handler_id == id)?;
+            let (_, filter, handler) = self.handlers.remove(idx);
+            Some((filter, handler))
+        }
+
+        /// Iterator over the IDs and filters of all registered handlers in the order of
+        /// registration.
+        pub fn handlers(&self) -> impl Iterator<Item = (PusTcHandlerId, &PusTcFilter)> {
+            self.handlers.iter().map(|(id, filter, _)| (*id, filter))
+        }
+
+        pub fn num_handlers(&self) -> usize {
+            self.handlers.len()
+        }
+
+        /// Retrieve the ID and the handler which would be used for the given service and
+        /// subservice.
+        pub fn handler_for(
+            &self,
+            service: u8,
+            subservice: u8,
+        ) -> Option<(PusTcHandlerId, &Sender)> {
+            self.handlers
+                .iter()
+                .filter(|(_, filter, _)| filter.matches(service, subservice))
+                .min_by_key(|(_, filter, _)| filter.precedence())
+                .map(|(id, _, handler)| (*id, handler))
+        }
+    }
+
+    impl<Sender: PacketSenderPusTc> PacketSenderPusTc for PusTcRouter<Sender> {
+        type Error = PusTcRoutingError<Sender::Error>;
+
+        fn send_pus_tc(
+            &self,
+            sender_id: ComponentId,
+            header: &SpHeader,
+            pus_tc: &PusTcReader,
+        ) -> Result<(), Self::Error> {
+            let service = pus_tc.service();
+            let subservice = pus_tc.subservice();
+            let (_, handler) =
+                self.handler_for(service, subservice)
+                    .ok_or(PusTcRoutingError::NoHandler {
+                        service,
+                        subservice,
+                    })?;
+            handler
+                .send_pus_tc(sender_id, header, pus_tc)
+                .map_err(PusTcRoutingError::Send)
+        }
+    }
 }
 
 #[cfg(feature = "std")]
@@ -681,7 +1113,11 @@ pub mod std_mod {
 
     use super::verification::{TcStateToken, VerificationReportingProvider};
     use super::{AcceptedEcssTcAndToken, ActiveRequestProvider, TcInMemory};
+    use crate::time::{
+        RawTimestamp, SystemTimeSource, TimeSource, TimeSourceError, TimestampFormat,
+    };
     use crate::tmtc::PacketInPool;
+    use alloc::boxed::Box;
 
     impl From<mpsc::SendError<PoolAddr>> for EcssTmtcError {
         fn from(_: mpsc::SendError<PoolAddr>) -> Self {
@@ -753,6 +1189,40 @@ pub mod std_mod {
         }
     }
 
+    /// Serialize the TC into a [PacketAsVec] so it can be sent as a [EcssTcAndToken]. The
+    /// [EcssTcSender] trait does not provide a sender ID, so the sender ID is set to 0.
+    fn tc_as_vec_and_token(
+        tc: PusTcCreator,
+        token: Option<TcStateToken>,
+    ) -> Result<EcssTcAndToken, EcssTmtcError> {
+        Ok(EcssTcAndToken {
+            tc_in_memory: TcInMemory::Vec(PacketAsVec::new(0, tc.to_vec()?)),
+            token,
+        })
+    }
+
+    impl EcssTcSender for mpsc::Sender<EcssTcAndToken> {
+        fn send_tc(
+            &self,
+            tc: PusTcCreator,
+            token: Option<TcStateToken>,
+        ) -> Result<(), EcssTmtcError> {
+            self.send(tc_as_vec_and_token(tc, token)?)
+                .map_err(|e| EcssTmtcError::Send(e.into()))
+        }
+    }
+
+    impl EcssTcSender for mpsc::SyncSender<EcssTcAndToken> {
+        fn send_tc(
+            &self,
+            tc: PusTcCreator,
+            token: Option<TcStateToken>,
+        ) -> Result<(), EcssTmtcError> {
+            self.try_send(tc_as_vec_and_token(tc, token)?)
+                .map_err(|e| EcssTmtcError::Send(e.into()))
+        }
+    }
+
     pub type MpscTcReceiver = mpsc::Receiver<EcssTcAndToken>;
 
     impl EcssTcReceiver for MpscTcReceiver {
@@ -764,6 +1234,15 @@ pub mod std_mod {
                 )),
             })
         }
+
+        fn recv_tc_timeout(&self, timeout: Duration) -> Result<EcssTcAndToken, TryRecvTmtcError> {
+            self.recv_timeout(timeout).map_err(|e| match e {
+                mpsc::RecvTimeoutError::Timeout => TryRecvTmtcError::Empty,
+                mpsc::RecvTimeoutError::Disconnected => TryRecvTmtcError::Tmtc(
+                    EcssTmtcError::from(GenericReceiveError::TxDisconnected(None)),
+                ),
+            })
+        }
     }
 
     #[cfg(feature = "crossbeam")]
@@ -820,6 +1299,42 @@ pub mod std_mod {
         }
 
         pub type CrossbeamTcReceiver = cb::Receiver<EcssTcAndToken>;
+
+        impl EcssTcReceiver for CrossbeamTcReceiver {
+            fn recv_tc(&self) -> Result<EcssTcAndToken, TryRecvTmtcError> {
+                self.try_recv().map_err(|e| match e {
+                    cb::TryRecvError::Empty => TryRecvTmtcError::Empty,
+                    cb::TryRecvError::Disconnected => TryRecvTmtcError::Tmtc(EcssTmtcError::from(
+                        GenericReceiveError::TxDisconnected(None),
+                    )),
+                })
+            }
+
+            fn recv_tc_timeout(
+                &self,
+                timeout: Duration,
+            ) -> Result<EcssTcAndToken, TryRecvTmtcError> {
+                self.recv_timeout(timeout).map_err(|e| match e {
+                    cb::RecvTimeoutError::Timeout => TryRecvTmtcError::Empty,
+                    cb::RecvTimeoutError::Disconnected => TryRecvTmtcError::Tmtc(
+                        EcssTmtcError::from(GenericReceiveError::TxDisconnected(None)),
+                    ),
+                })
+            }
+        }
+
+        /// Telecommands are sent with [cb::Sender::try_send], so sending to a full bounded
+        /// channel returns a [GenericSendError::QueueFull] error instead of blocking.
+        impl EcssTcSender for cb::Sender<EcssTcAndToken> {
+            fn send_tc(
+                &self,
+                tc: PusTcCreator,
+                token: Option<TcStateToken>,
+            ) -> Result<(), EcssTmtcError> {
+                self.try_send(tc_as_vec_and_token(tc, token)?)
+                    .map_err(|e| EcssTmtcError::Send(e.into()))
+            }
+        }
     }
 
     #[derive(Debug, Clone, PartialEq, Eq)]
@@ -931,6 +1446,8 @@ pub mod std_mod {
         Verification(EcssTmtcError),
         #[error("invalid verification token")]
         NoVerificationToken,
+        #[error("time source error: {0}")]
+        TimeSource(TimeSourceError),
     }
 
     /// Generic result type for handlers which can process PUS packets.
@@ -973,7 +1490,8 @@ pub mod std_mod {
 
     /// Converter structure for PUS telecommands which are stored inside a `Vec<u8>` structure.
     /// Please note that this structure is not able to convert TCs which are stored inside a
-    /// [SharedStaticMemoryPool].
+    /// [SharedStaticMemoryPool]. It can be used together with the [EcssTcInVecQueue] to use the
+    /// PUS service handlers without any pool involvement.
     #[derive(Default, Clone)]
     pub struct EcssTcInVecConverter {
         sender_id: Option<ComponentId>,
@@ -1080,6 +1598,12 @@ pub mod std_mod {
         pub tc_receiver: TcReceiver,
         pub tm_sender: TmSender,
         pub verif_reporter: VerificationReporter,
+        /// Source of the current time. This is the system clock by default, but it can be
+        /// replaced by a simulated time source, for example for software-in-the-loop simulations.
+        pub time_source: Box<dyn TimeSource + Send>,
+        /// Format of the timestamps created from the [TimeSource]. This is the CDS short format
+        /// by default.
+        pub timestamp_format: TimestampFormat,
     }
 
     /// This is a high-level PUS packet handler helper.
@@ -1121,11 +1645,53 @@ pub mod std_mod {
                     tc_receiver,
                     tm_sender,
                     verif_reporter: verification_handler,
+                    time_source: Box::new(SystemTimeSource),
+                    timestamp_format: TimestampFormat::default(),
                 },
                 tc_in_mem_converter,
             }
         }
 
+        /// Replace the default system clock [TimeSource] of the helper.
+        pub fn with_time_source(mut self, time_source: impl TimeSource + Send + 'static) -> Self {
+            self.set_time_source(time_source);
+            self
+        }
+
+        pub fn set_time_source(&mut self, time_source: impl TimeSource + Send + 'static) {
+            self.common.time_source = Box::new(time_source);
+        }
+
+        pub fn time_source(&self) -> &dyn TimeSource {
+            self.common.time_source.as_ref()
+        }
+
+        /// Current time of the [TimeSource] as a CDS short timestamp, which can be used to
+        /// timestamp the generated telemetry.
+        pub fn cds_short_stamp(&self) -> Result<[u8; 7], TimeSourceError> {
+            self.common.time_source.cds_short_stamp()
+        }
+
+        /// Replace the default CDS short [TimestampFormat] of the helper.
+        pub fn with_timestamp_format(mut self, format: TimestampFormat) -> Self {
+            self.set_timestamp_format(format);
+            self
+        }
+
+        pub fn set_timestamp_format(&mut self, format: TimestampFormat) {
+            self.common.timestamp_format = format;
+        }
+
+        pub fn timestamp_format(&self) -> TimestampFormat {
+            self.common.timestamp_format
+        }
+
+        /// Current time of the [TimeSource] as a timestamp with the configured
+        /// [TimestampFormat], which can be used to timestamp the generated telemetry.
+        pub fn current_stamp(&self) -> Result<RawTimestamp, TimeSourceError> {
+            self.common.time_source.stamp(self.common.timestamp_format)
+        }
+
         pub fn id(&self) -> ComponentId {
             self.common.id
         }
@@ -1142,7 +1708,23 @@ pub mod std_mod {
         pub fn retrieve_and_accept_next_packet(
             &mut self,
         ) -> Result<Option<AcceptedEcssTcAndToken>, PusPacketHandlingError> {
-            match self.common.tc_receiver.recv_tc() {
+            Self::accept_received_tc(self.common.tc_receiver.recv_tc())
+        }
+
+        /// Blocking variant of [Self::retrieve_and_accept_next_packet] which waits up to the given
+        /// timeout for the next telecommand using [EcssTcReceiver::recv_tc_timeout]. This allows
+        /// service threads to sleep until work arrives instead of polling periodically.
+        pub fn retrieve_and_accept_next_packet_with_timeout(
+            &mut self,
+            timeout: Duration,
+        ) -> Result<Option<AcceptedEcssTcAndToken>, PusPacketHandlingError> {
+            Self::accept_received_tc(self.common.tc_receiver.recv_tc_timeout(timeout))
+        }
+
+        fn accept_received_tc(
+            recv_result: Result<EcssTcAndToken, TryRecvTmtcError>,
+        ) -> Result<Option<AcceptedEcssTcAndToken>, PusPacketHandlingError> {
+            match recv_result {
                 Ok(EcssTcAndToken {
                     tc_in_memory,
                     token,
@@ -1190,6 +1772,10 @@ pub mod std_mod {
             TcInMemConverter,
             VerificationReporter,
         >;
+    /// Helper type definition for a service helper which does not require a pool or a message
+    /// queue. The telecommands are pushed into the [EcssTcInVecQueue] of the helper.
+    pub type PusServiceHelperWithVecQueue<TmSender, VerificationReporter> =
+        PusServiceHelper<EcssTcInVecQueue, TmSender, EcssTcInVecConverter, VerificationReporter>;
     pub type PusServiceHelperStaticWithMpsc<TcInMemConverter, VerificationReporter> =
         PusServiceHelper<
             MpscTcReceiver,
@@ -1320,15 +1906,13 @@ pub mod tests {
         }
 
         pub fn new_from_tm(tm: &PusTmCreator) -> Self {
-            let mut timestamp = [0; 7];
-            timestamp.clone_from_slice(&tm.timestamp()[0..7]);
             Self {
                 subservice: PusPacket::subservice(tm),
                 apid: tm.apid(),
                 seq_count: tm.seq_count(),
                 msg_counter: tm.msg_counter(),
                 dest_id: tm.dest_id(),
-                timestamp: timestamp.to_vec(),
+                timestamp: tm.timestamp().to_vec(),
             }
         }
     }
@@ -1666,4 +2250,264 @@ pub mod tests {
             self.routing_requests.borrow_mut().pop_front().unwrap()
         }
     }
+
+    #[derive(Default)]
+    struct PusTcRecorder {
+        received: RefCell<Vec<(ComponentId, u8, u8)>>,
+    }
+
+    impl PacketSenderPusTc for PusTcRecorder {
+        type Error = GenericSendError;
+
+        fn send_pus_tc(
+            &self,
+            sender_id: ComponentId,
+            _header: &SpHeader,
+            pus_tc: &PusTcReader,
+        ) -> Result<(), Self::Error> {
+            self.received
+                .borrow_mut()
+                .push((sender_id, pus_tc.service(), pus_tc.subservice()));
+            Ok(())
+        }
+    }
+
+    fn route_tc(router: &PusTcRouter<PusTcRecorder>, service: u8, subservice: u8) {
+        let sph = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let tc = PusTcCreator::new_simple(sph, service, subservice, &[], true);
+        let tc_raw = tc.to_vec().unwrap();
+        let (tc_reader, _) = PusTcReader::new(&tc_raw).unwrap();
+        router
+            .send_pus_tc(TEST_COMPONENT_ID_0.id(), &sph, &tc_reader)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_pus_tc_router_precedence() {
+        let mut router = PusTcRouter::new();
+        let service_id = router
+            .add_handler(PusTcFilter::Service(17), PusTcRecorder::default())
+            .unwrap();
+        let subservice_id = router
+            .add_handler(
+                PusTcFilter::ServiceAndSubservice(17, 2),
+                PusTcRecorder::default(),
+            )
+            .unwrap();
+        let default_id = router
+            .add_handler(PusTcFilter::Default, PusTcRecorder::default())
+            .unwrap();
+        assert_eq!(router.num_handlers(), 3);
+        assert_eq!(router.handler_for(17, 1).unwrap().0, service_id);
+        assert_eq!(router.handler_for(17, 2).unwrap().0, subservice_id);
+        assert_eq!(router.handler_for(3, 1).unwrap().0, default_id);
+
+        route_tc(&router, 17, 1);
+        route_tc(&router, 17, 2);
+        route_tc(&router, 3, 1);
+        let (_, service_handler) = router.handler_for(17, 1).unwrap();
+        assert_eq!(
+            *service_handler.received.borrow(),
+            [(TEST_COMPONENT_ID_0.id(), 17, 1)]
+        );
+        let (_, subservice_handler) = router.handler_for(17, 2).unwrap();
+        assert_eq!(
+            *subservice_handler.received.borrow(),
+            [(TEST_COMPONENT_ID_0.id(), 17, 2)]
+        );
+        let (_, default_handler) = router.handler_for(3, 1).unwrap();
+        assert_eq!(
+            *default_handler.received.borrow(),
+            [(TEST_COMPONENT_ID_0.id(), 3, 1)]
+        );
+    }
+
+    #[test]
+    fn test_pus_tc_router_duplicate_filter() {
+        let mut router = PusTcRouter::new();
+        router
+            .add_handler(PusTcFilter::Default, PusTcRecorder::default())
+            .unwrap();
+        assert!(router
+            .add_handler(PusTcFilter::Default, PusTcRecorder::default())
+            .is_err());
+        assert_eq!(router.num_handlers(), 1);
+    }
+
+    #[test]
+    fn test_pus_tc_router_no_handler() {
+        let mut router = PusTcRouter::new();
+        let id = router
+            .add_handler(PusTcFilter::Service(17), PusTcRecorder::default())
+            .unwrap();
+        let sph = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let tc = PusTcCreator::new_simple(sph, 3, 1, &[], true);
+        let tc_raw = tc.to_vec().unwrap();
+        let (tc_reader, _) = PusTcReader::new(&tc_raw).unwrap();
+        let result = router.send_pus_tc(TEST_COMPONENT_ID_0.id(), &sph, &tc_reader);
+        assert_eq!(
+            result.unwrap_err(),
+            PusTcRoutingError::NoHandler {
+                service: 3,
+                subservice: 1
+            }
+        );
+        let (filter, _) = router.remove_handler(id).unwrap();
+        assert_eq!(filter, PusTcFilter::Service(17));
+        assert_eq!(router.num_handlers(), 0);
+    }
+    fn check_tc_and_token(tc_and_token: EcssTcAndToken, expected_tc: &PusTcCreator) {
+        assert!(tc_and_token.token.is_none());
+        if let TcInMemory::Vec(packet) = tc_and_token.tc_in_memory {
+            assert_eq!(packet.packet, expected_tc.to_vec().unwrap());
+        } else {
+            panic!("unexpected TC in memory variant");
+        }
+    }
+
+    #[test]
+    fn test_mpsc_tc_sender_and_receiver() {
+        let (tx, rx) = mpsc::sync_channel::<EcssTcAndToken>(1);
+        let sph = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let tc = PusTcCreator::new_simple(sph, 17, 1, &[], true);
+        tx.send_tc(tc.clone(), None).unwrap();
+        assert!(matches!(
+            tx.send_tc(tc.clone(), None),
+            Err(EcssTmtcError::Send(GenericSendError::QueueFull(None)))
+        ));
+        check_tc_and_token(rx.recv_tc().unwrap(), &tc);
+        assert!(matches!(rx.recv_tc(), Err(TryRecvTmtcError::Empty)));
+        drop(tx);
+        assert!(matches!(
+            rx.recv_tc(),
+            Err(TryRecvTmtcError::Tmtc(EcssTmtcError::Receive(
+                GenericReceiveError::TxDisconnected(None)
+            )))
+        ));
+    }
+
+    #[test]
+    fn test_mpsc_tc_receiver_with_timeout() {
+        let (tx, rx) = mpsc::channel::<EcssTcAndToken>();
+        let sph = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let tc = PusTcCreator::new_simple(sph, 17, 1, &[], true);
+        assert!(matches!(
+            rx.recv_tc_timeout(Duration::from_millis(5)),
+            Err(TryRecvTmtcError::Empty)
+        ));
+        let sender_tc = tc.clone();
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            tx.send_tc(sender_tc, None).unwrap();
+        });
+        check_tc_and_token(rx.recv_tc_timeout(Duration::from_secs(2)).unwrap(), &tc);
+        sender.join().unwrap();
+        assert!(matches!(
+            rx.recv_tc_timeout(Duration::from_millis(5)),
+            Err(TryRecvTmtcError::Tmtc(EcssTmtcError::Receive(
+                GenericReceiveError::TxDisconnected(None)
+            )))
+        ));
+        // Local queues can not block and return immediately.
+        let vec_queue = EcssTcInVecQueue::default();
+        assert!(matches!(
+            vec_queue.recv_tc_timeout(Duration::from_secs(60)),
+            Err(TryRecvTmtcError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_service_with_vec_queue() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let verif_cfg = VerificationReporterCfg::new(TEST_APID, 1, 2, 8).unwrap();
+        let service_helper: PusServiceHelperWithVecQueue<_, _> = PusServiceHelper::new(
+            TEST_COMPONENT_ID_0.id(),
+            EcssTcInVecQueue::default(),
+            tm_tx,
+            VerificationReporter::new(TEST_COMPONENT_ID_0.id(), &verif_cfg),
+            EcssTcInVecConverter::default(),
+        );
+        let mut handler = crate::pus::test::PusService17TestHandler::new(service_helper);
+        let sph = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let ping_tc = PusTcCreator::new_simple(sph, 17, 1, &[], true);
+        let helper = &mut handler.service_helper;
+        let init_token = helper.verif_reporter_mut().add_tc(&ping_tc);
+        let token = helper
+            .verif_reporter()
+            .acceptance_success(helper.tm_sender(), init_token, &[0; 7])
+            .unwrap();
+        helper
+            .common
+            .tc_receiver
+            .push_tc(TEST_COMPONENT_ID_0.id(), &ping_tc, token)
+            .unwrap();
+        assert_eq!(helper.common.tc_receiver.len(), 1);
+        let result = handler
+            .poll_and_handle_next_tc(|e| panic!("partial error: {e:?}"), &[0; 7])
+            .unwrap();
+        assert!(matches!(
+            result,
+            DirectPusPacketHandlerResult::Handled(HandlingStatus::HandledOne)
+        ));
+        assert!(handler.service_helper.common.tc_receiver.is_empty());
+        // Acceptance, start, ping reply and completion.
+        let services: Vec<(u8, u8)> = tm_rx
+            .try_iter()
+            .map(|tm| {
+                let (tm, _) = PusTmReader::new(&tm.packet, 7).unwrap();
+                (tm.service(), tm.subservice())
+            })
+            .collect();
+        assert_eq!(services, [(1, 1), (1, 3), (17, 2), (1, 7)]);
+    }
+
+    #[cfg(feature = "crossbeam")]
+    #[test]
+    fn test_crossbeam_tc_sender_and_receiver() {
+        let (tx, rx) = crossbeam_channel::bounded::<EcssTcAndToken>(1);
+        let sph = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let tc = PusTcCreator::new_simple(sph, 17, 1, &[], true);
+        tx.send_tc(tc.clone(), None).unwrap();
+        assert!(matches!(
+            tx.send_tc(tc.clone(), None),
+            Err(EcssTmtcError::Send(GenericSendError::QueueFull(None)))
+        ));
+        check_tc_and_token(rx.recv_tc().unwrap(), &tc);
+        assert!(matches!(rx.recv_tc(), Err(TryRecvTmtcError::Empty)));
+        drop(tx);
+        assert!(rx.recv_tc().is_err());
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn test_heapless_tm_sender_and_tc_receiver() {
+        let mut tm_queue: heapless::spsc::Queue<PacketInPool, 2> = heapless::spsc::Queue::new();
+        let (producer, mut consumer) = tm_queue.split();
+        let tm_sender = HeaplessTmInStoreSender::new(producer);
+        tm_sender
+            .send_tm(TEST_COMPONENT_ID_0.id(), PusTmVariant::InStore(1))
+            .unwrap();
+        assert!(matches!(
+            tm_sender.send_tm(TEST_COMPONENT_ID_0.id(), PusTmVariant::InStore(2)),
+            Err(EcssTmtcError::Send(GenericSendError::QueueFull(Some(1))))
+        ));
+        assert_eq!(
+            consumer.dequeue().unwrap(),
+            PacketInPool::new(TEST_COMPONENT_ID_0.id(), 1)
+        );
+
+        let mut tc_queue: heapless::spsc::Queue<EcssTcAndToken, 2> = heapless::spsc::Queue::new();
+        let (mut producer, consumer) = tc_queue.split();
+        let tc_receiver = HeaplessTcReceiver::new(consumer);
+        assert!(matches!(
+            tc_receiver.recv_tc(),
+            Err(TryRecvTmtcError::Empty)
+        ));
+        let tc_and_token = EcssTcAndToken {
+            tc_in_memory: TcInMemory::Pool(PacketInPool::new(TEST_COMPONENT_ID_0.id(), 5)),
+            token: None,
+        };
+        producer.enqueue(tc_and_token.clone()).unwrap();
+        assert_eq!(tc_receiver.recv_tc().unwrap(), tc_and_token);
+    }
 }
@@ -4,6 +4,7 @@
 //! to perform the scheduling of telecommands like specified in the ECSS standard.
 use core::fmt::{Debug, Display, Formatter};
 use core::time::Duration;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use spacepackets::ecss::scheduling::TimeWindowType;
@@ -15,8 +16,13 @@ use spacepackets::{ByteConversionError, CcsdsPacket};
 use std::error::Error;
 
 use crate::pool::{PoolError, PoolProvider};
+use crate::time::TimestampFormat;
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+use crate::time::{TimeSource, TimeSourceError};
 #[cfg(feature = "alloc")]
 pub use alloc_mod::*;
+#[cfg(feature = "heapless")]
+pub use heapless_mod::*;
 
 /// This is the request ID as specified in ECSS-E-ST-70-41C 5.4.11.2 of the standard.
 ///
@@ -31,6 +37,9 @@ pub struct RequestId {
 }
 
 impl RequestId {
+    /// Length of the raw request ID consisting of the source ID, the APID and the sequence count.
+    pub const RAW_LEN: usize = 6;
+
     pub fn source_id(&self) -> u16 {
         self.source_id
     }
@@ -56,17 +65,66 @@ impl RequestId {
     pub fn as_u64(&self) -> u64 {
         ((self.source_id as u64) << 32) | ((self.apid as u64) << 16) | self.seq_count as u64
     }
+
+    /// Read a request ID from its raw format, which consists of the source ID, the APID and the
+    /// sequence count, each serialized as a big-endian [u16].
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, ByteConversionError> {
+        if buf.len() < Self::RAW_LEN {
+            return Err(ByteConversionError::FromSliceTooSmall {
+                found: buf.len(),
+                expected: Self::RAW_LEN,
+            });
+        }
+        Ok(Self {
+            source_id: u16::from_be_bytes(buf[0..2].try_into().unwrap()),
+            apid: u16::from_be_bytes(buf[2..4].try_into().unwrap()),
+            seq_count: u16::from_be_bytes(buf[4..6].try_into().unwrap()),
+        })
+    }
+
+    /// Write the request ID in its raw format. Returns the number of written bytes.
+    pub fn write_to_be_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+        if buf.len() < Self::RAW_LEN {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: Self::RAW_LEN,
+            });
+        }
+        buf[0..2].copy_from_slice(&self.source_id.to_be_bytes());
+        buf[2..4].copy_from_slice(&self.apid.to_be_bytes());
+        buf[4..6].copy_from_slice(&self.seq_count.to_be_bytes());
+        Ok(Self::RAW_LEN)
+    }
 }
 
 pub type AddrInStore = u64;
 
+/// PUS 11 subservices for the group management specified in ECSS-E-ST-70-41C 6.11.7.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum GroupSubservice {
+    TcCreateGroups = 22,
+    TcDeleteGroups = 23,
+    TcEnableGroups = 24,
+    TcDisableGroups = 25,
+    TcReportAllGroupsStatus = 26,
+    TmGroupsStatusReport = 27,
+}
+
+/// Identifier of a group of scheduled telecommands, which can be enabled and disabled as a whole
+/// as specified in ECSS-E-ST-70-41C 6.11.3.2.
+pub type GroupId = u16;
+
 /// This is the format stored internally by the TC scheduler for each scheduled telecommand.
-/// It consists of a generic address for that telecommand in the TC pool and a request ID.
+/// It consists of a generic address for that telecommand in the TC pool, a request ID and an
+/// optional group ID.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TcInfo {
     addr: AddrInStore,
     request_id: RequestId,
+    group_id: Option<GroupId>,
 }
 
 impl TcInfo {
@@ -78,8 +136,25 @@ impl TcInfo {
         self.request_id
     }
 
+    pub fn group_id(&self) -> Option<GroupId> {
+        self.group_id
+    }
+
     pub fn new(addr: u64, request_id: RequestId) -> Self {
-        TcInfo { addr, request_id }
+        TcInfo {
+            addr,
+            request_id,
+            group_id: None,
+        }
+    }
+
+    /// Like [Self::new], but assigns the telecommand to a group.
+    pub fn new_with_group(addr: u64, request_id: RequestId, group_id: GroupId) -> Self {
+        TcInfo {
+            addr,
+            request_id,
+            group_id: Some(group_id),
+        }
     }
 }
 
@@ -137,6 +212,95 @@ impl<TimeProvider: CcsdsTimeProvider + Clone> TimeWindow<TimeProvider> {
     }
 }
 
+impl<TimeProvider: CcsdsTimeProvider> TimeWindow<TimeProvider> {
+    /// Checks whether the given time is inside the time window. The check is inclusive, similarly
+    /// to the ranged retrieval of the [PusScheduler].
+    pub fn contains(&self, time: &UnixTime) -> bool {
+        let after_start = self
+            .start_time
+            .as_ref()
+            .map_or(true, |start_time| time >= &start_time.unix_time());
+        let before_end = self
+            .end_time
+            .as_ref()
+            .map_or(true, |end_time| time <= &end_time.unix_time());
+        after_start && before_end
+    }
+}
+
+impl<TimeProvider: CcsdsTimeProvider + TimeReader> TimeWindow<TimeProvider> {
+    /// Read a time window from the application data of a PUS 11 filter request.
+    ///
+    /// The time window type is serialized as a big-endian [u32] and followed by the start time
+    /// and/or the end time, depending on the type.
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, ScheduleError> {
+        if buf.len() < 4 {
+            return Err(ByteConversionError::FromSliceTooSmall {
+                found: buf.len(),
+                expected: 4,
+            }
+            .into());
+        }
+        let raw_type = u32::from_be_bytes(buf[0..4].try_into().unwrap());
+        let read_stamp = |offset: usize| -> Result<TimeProvider, ScheduleError> {
+            Ok(TimeProvider::from_bytes(&buf[offset..])?)
+        };
+        match raw_type {
+            0 => Ok(Self::new_select_all()),
+            1 => {
+                let start_time = read_stamp(4)?;
+                let end_time = read_stamp(4 + start_time.len_as_bytes())?;
+                Ok(Self {
+                    time_window_type: TimeWindowType::TimeTagToTimeTag,
+                    start_time: Some(start_time),
+                    end_time: Some(end_time),
+                })
+            }
+            2 => Ok(Self {
+                time_window_type: TimeWindowType::FromTimeTag,
+                start_time: Some(read_stamp(4)?),
+                end_time: None,
+            }),
+            3 => Ok(Self {
+                time_window_type: TimeWindowType::ToTimeTag,
+                start_time: None,
+                end_time: Some(read_stamp(4)?),
+            }),
+            _ => Err(ScheduleError::InvalidTimeWindowType(raw_type)),
+        }
+    }
+}
+
+impl<TimeProvider: TimeWriter> TimeWindow<TimeProvider> {
+    /// Length of the time window when written with [Self::write_to_bytes].
+    pub fn len_written(&self) -> usize {
+        4 + self.start_time.as_ref().map_or(0, |t| t.len_written())
+            + self.end_time.as_ref().map_or(0, |t| t.len_written())
+    }
+
+    /// Write the time window in the format expected by [TimeWindow::from_bytes]. Returns the
+    /// number of written bytes.
+    pub fn write_to_bytes(&self, buf: &mut [u8]) -> Result<usize, ScheduleError> {
+        let required_len = self.len_written();
+        if buf.len() < required_len {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: required_len,
+            }
+            .into());
+        }
+        buf[0..4].copy_from_slice(&(self.time_window_type as u32).to_be_bytes());
+        let mut current_len = 4;
+        if let Some(start_time) = &self.start_time {
+            current_len += start_time.write_to_bytes(&mut buf[current_len..])?;
+        }
+        if let Some(end_time) = &self.end_time {
+            current_len += end_time.write_to_bytes(&mut buf[current_len..])?;
+        }
+        Ok(current_len)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ScheduleError {
@@ -157,6 +321,39 @@ pub enum ScheduleError {
     WrongSubservice(u8),
     WrongService(u8),
     ByteConversionError(ByteConversionError),
+    /// The maximum number of scheduled telecommands was reached.
+    ScheduleFull,
+    /// Invalid raw time window type inside a filter request.
+    InvalidTimeWindowType(u32),
+    /// Shifting the release time by the given offset in milliseconds would overflow the
+    /// timestamp.
+    TimeShiftOverflow(i64),
+    /// The group with the given ID does not exist.
+    UnknownGroup(GroupId),
+    /// The group with the given ID already exists.
+    GroupAlreadyExists(GroupId),
+    /// The group with the given ID can not be deleted because it still contains scheduled
+    /// telecommands.
+    GroupNotEmpty(GroupId),
+    /// The scheduler does not support groups.
+    GroupsNotSupported,
+    /// The APID of the telecommand to schedule is not allowed by the [InsertionChecks].
+    ApidNotAllowed(u16),
+    /// The service of the telecommand to schedule is not allowed by the [InsertionChecks].
+    ServiceNotAllowed(u8),
+    /// The release time lies further in the past than the past margin of the
+    /// [InsertionChecks].
+    ReleaseTimeInPast {
+        current_time: UnixTime,
+        past_margin: Duration,
+        release_time: UnixTime,
+    },
+    /// The release time lies further in the future than the horizon of the [InsertionChecks].
+    ReleaseTimeBeyondHorizon {
+        current_time: UnixTime,
+        horizon: Duration,
+        release_time: UnixTime,
+    },
 }
 
 impl Display for ScheduleError {
@@ -196,6 +393,53 @@ impl Display for ScheduleError {
             ScheduleError::ByteConversionError(e) => {
                 write!(f, "pus scheduling: {e}")
             }
+            ScheduleError::ScheduleFull => {
+                write!(f, "pus scheduling: schedule is full")
+            }
+            ScheduleError::InvalidTimeWindowType(raw) => {
+                write!(f, "pus scheduling: invalid time window type {raw}")
+            }
+            ScheduleError::TimeShiftOverflow(offset_ms) => {
+                write!(f, "pus scheduling: time shift of {offset_ms} ms overflows")
+            }
+            ScheduleError::UnknownGroup(id) => {
+                write!(f, "pus scheduling: unknown group {id}")
+            }
+            ScheduleError::GroupAlreadyExists(id) => {
+                write!(f, "pus scheduling: group {id} already exists")
+            }
+            ScheduleError::GroupNotEmpty(id) => {
+                write!(f, "pus scheduling: group {id} is not empty")
+            }
+            ScheduleError::GroupsNotSupported => {
+                write!(f, "pus scheduling: groups are not supported")
+            }
+            ScheduleError::ApidNotAllowed(apid) => {
+                write!(f, "pus scheduling: APID {apid:#05x} is not allowed")
+            }
+            ScheduleError::ServiceNotAllowed(service) => {
+                write!(f, "pus scheduling: service {service} is not allowed")
+            }
+            ScheduleError::ReleaseTimeInPast {
+                current_time,
+                past_margin,
+                release_time,
+            } => {
+                write!(
+                    f,
+                    "pus scheduling: release time in the past, current time: {current_time:?}, past margin: {past_margin:?}, release time: {release_time:?}"
+                )
+            }
+            ScheduleError::ReleaseTimeBeyondHorizon {
+                current_time,
+                horizon,
+                release_time,
+            } => {
+                write!(
+                    f,
+                    "pus scheduling: release time beyond horizon, current time: {current_time:?}, horizon: {horizon:?}, release time: {release_time:?}"
+                )
+            }
         }
     }
 }
@@ -258,6 +502,88 @@ pub trait PusSchedulerProvider {
         info: TcInfo,
     ) -> Result<(), ScheduleError>;
 
+    /// Delete the scheduled telecommand with the given request ID from the schedule and from the
+    /// passed TC pool. Returns whether a telecommand with the given request ID was found.
+    fn delete_by_request_id_and_from_pool(
+        &mut self,
+        req_id: &RequestId,
+        pool: &mut (impl PoolProvider + ?Sized),
+    ) -> Result<bool, PoolError>;
+
+    /// Delete all scheduled telecommands inside the given time window from the schedule and from
+    /// the passed TC pool.
+    ///
+    /// This function returns the number of deleted commands on success. In case any deletion
+    /// fails, the last deletion error will be supplied in addition to the number of deleted
+    /// commands.
+    fn delete_by_time_window(
+        &mut self,
+        time_window: TimeWindow<Self::TimeProvider>,
+        pool: &mut (impl PoolProvider + ?Sized),
+    ) -> Result<u64, (u64, PoolError)>;
+
+    /// Shift the release time of all scheduled telecommands by a signed offset in milliseconds.
+    /// Returns the number of shifted telecommands.
+    ///
+    /// The time shift is rejected without modifying the schedule if the release time of any
+    /// telecommand would end up inside the time margin.
+    fn time_shift_all(&mut self, offset_ms: i64) -> Result<u64, ScheduleError>;
+
+    /// Shift the release time of the scheduled telecommand with the given request ID by a signed
+    /// offset in milliseconds. Returns whether a telecommand with the given request ID was found.
+    fn time_shift_by_request_id(
+        &mut self,
+        req_id: &RequestId,
+        offset_ms: i64,
+    ) -> Result<bool, ScheduleError>;
+
+    /// Shift the release time of all scheduled telecommands inside the given time window by a
+    /// signed offset in milliseconds. Returns the number of shifted telecommands.
+    fn time_shift_by_time_window(
+        &mut self,
+        time_window: TimeWindow<Self::TimeProvider>,
+        offset_ms: i64,
+    ) -> Result<u64, ScheduleError>;
+
+    /// Call the passed closure for each scheduled telecommand together with its release time,
+    /// ordered by ascending release time.
+    fn for_each_scheduled_tc(&self, f: impl FnMut(&UnixTime, &TcInfo));
+
+    /// Create a new group, which is enabled by default. The default implementation returns
+    /// [ScheduleError::GroupsNotSupported].
+    fn create_group(&mut self, _group_id: GroupId) -> Result<(), ScheduleError> {
+        Err(ScheduleError::GroupsNotSupported)
+    }
+
+    /// Delete an empty group. The default implementation returns
+    /// [ScheduleError::GroupsNotSupported].
+    fn delete_group(&mut self, _group_id: GroupId) -> Result<(), ScheduleError> {
+        Err(ScheduleError::GroupsNotSupported)
+    }
+
+    /// Enable a group. Telecommands of a disabled group are handled like the telecommands of a
+    /// disabled scheduler when their release time is reached. The default implementation returns
+    /// [ScheduleError::GroupsNotSupported].
+    fn enable_group(&mut self, _group_id: GroupId) -> Result<(), ScheduleError> {
+        Err(ScheduleError::GroupsNotSupported)
+    }
+
+    /// Disable a group. The default implementation returns [ScheduleError::GroupsNotSupported].
+    fn disable_group(&mut self, _group_id: GroupId) -> Result<(), ScheduleError> {
+        Err(ScheduleError::GroupsNotSupported)
+    }
+
+    /// Call the passed closure for each group with the group ID and whether the group is
+    /// enabled. The default implementation does not have any groups.
+    fn for_each_group(&self, _f: impl FnMut(GroupId, bool)) {}
+
+    /// Current time of the scheduler, which is used to check the release times of inserted
+    /// telecommands against the [InsertionChecks]. The default implementation returns [None],
+    /// which disables these release time checks.
+    fn scheduler_time(&self) -> Option<UnixTime> {
+        None
+    }
+
     /// Insert a telecommand based on the fully wrapped time-tagged telecommand. The timestamp
     /// provider needs to be supplied via a generic.
     fn insert_wrapped_tc<TimeProvider>(
@@ -265,24 +591,27 @@ pub trait PusSchedulerProvider {
         pus_tc: &(impl IsPusTelecommand + PusPacket + GenericPusTcSecondaryHeader),
         pool: &mut (impl PoolProvider + ?Sized),
     ) -> Result<TcInfo, ScheduleError> {
-        if PusPacket::service(pus_tc) != 11 {
-            return Err(ScheduleError::WrongService(PusPacket::service(pus_tc)));
-        }
-        if PusPacket::subservice(pus_tc) != 4 {
-            return Err(ScheduleError::WrongSubservice(PusPacket::subservice(
-                pus_tc,
-            )));
-        }
-        if pus_tc.user_data().is_empty() {
-            return Err(ScheduleError::TcDataEmpty);
-        }
-        let user_data = pus_tc.user_data();
+        let user_data = wrapped_tc_user_data(pus_tc)?;
         let stamp: Self::TimeProvider = TimeReader::from_bytes(user_data)?;
         let unix_stamp = stamp.unix_time();
         let stamp_len = stamp.len_as_bytes();
         self.insert_unwrapped_tc(unix_stamp, &user_data[stamp_len..], pool)
     }
 
+    /// Insert a telecommand based on the fully wrapped time-tagged telecommand. The release time
+    /// is parsed with the given [TimestampFormat], which also allows CUC release times. The fine
+    /// time resolution of CUC release times is determined by their P-field.
+    fn insert_wrapped_tc_with_format(
+        &mut self,
+        pus_tc: &(impl IsPusTelecommand + PusPacket + GenericPusTcSecondaryHeader),
+        format: TimestampFormat,
+        pool: &mut (impl PoolProvider + ?Sized),
+    ) -> Result<TcInfo, ScheduleError> {
+        let user_data = wrapped_tc_user_data(pus_tc)?;
+        let (release_time, stamp_len) = format.read_stamp(user_data)?;
+        self.insert_unwrapped_tc(release_time, &user_data[stamp_len..], pool)
+    }
+
     /// Insert a telecommand which was already unwrapped from the outer Service 11 packet but still
     /// needs to be stored inside the telecommand pool.
     fn insert_unwrapped_tc(
@@ -306,6 +635,50 @@ pub trait PusSchedulerProvider {
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Like [Self::insert_unwrapped_tc], but assigns the telecommand to an existing group. The
+    /// stored telecommand is deleted from the pool again if the insertion fails, for example
+    /// because the group does not exist.
+    fn insert_unwrapped_tc_in_group(
+        &mut self,
+        time_stamp: UnixTime,
+        tc: &[u8],
+        group_id: GroupId,
+        pool: &mut (impl PoolProvider + ?Sized),
+    ) -> Result<TcInfo, ScheduleError> {
+        let check_tc = PusTcReader::new(tc)?;
+        if PusPacket::service(&check_tc.0) == 11 && PusPacket::subservice(&check_tc.0) == 4 {
+            return Err(ScheduleError::NestedScheduledTc);
+        }
+        let req_id = RequestId::from_tc(&check_tc.0);
+        let addr = pool.add(tc)?;
+        let info = TcInfo::new_with_group(addr, req_id, group_id);
+        if let Err(e) = self.insert_unwrapped_and_stored_tc(time_stamp, info) {
+            // Do not leak the stored telecommand.
+            let _ = pool.delete(addr);
+            return Err(e);
+        }
+        Ok(info)
+    }
+}
+
+/// Check that the telecommand is a time-tagged TC[11,4] telecommand with application data and
+/// return its user data.
+fn wrapped_tc_user_data(
+    pus_tc: &(impl IsPusTelecommand + PusPacket + GenericPusTcSecondaryHeader),
+) -> Result<&[u8], ScheduleError> {
+    if PusPacket::service(pus_tc) != 11 {
+        return Err(ScheduleError::WrongService(PusPacket::service(pus_tc)));
+    }
+    if PusPacket::subservice(pus_tc) != 4 {
+        return Err(ScheduleError::WrongSubservice(PusPacket::subservice(
+            pus_tc,
+        )));
+    }
+    if pus_tc.user_data().is_empty() {
+        return Err(ScheduleError::TcDataEmpty);
+    }
+    Ok(pus_tc.user_data())
 }
 
 /// Helper function to generate the application data for a PUS telecommand to insert an
@@ -336,6 +709,90 @@ pub fn generate_insert_telecommand_app_data(
     Ok(current_len)
 }
 
+/// Helper function to generate the application data for a PUS telecommand to delete activities
+/// identified by their request IDs according to ECSS-E-ST-70-41C 8.11.2.5.
+///
+/// The N field is set to a [u16] unsigned bytefield with the number of request IDs.
+pub fn generate_delete_by_request_id_app_data(
+    buf: &mut [u8],
+    request_ids: &[RequestId],
+) -> Result<usize, ScheduleError> {
+    if request_ids.len() > u16::MAX as usize {
+        return Err(ByteConversionError::ToSliceTooSmall {
+            found: u16::MAX as usize,
+            expected: request_ids.len(),
+        }
+        .into());
+    }
+    let required_len = 2 + request_ids.len() * RequestId::RAW_LEN;
+    if required_len > buf.len() {
+        return Err(ByteConversionError::ToSliceTooSmall {
+            found: buf.len(),
+            expected: required_len,
+        }
+        .into());
+    }
+    buf[0..2].copy_from_slice(&(request_ids.len() as u16).to_be_bytes());
+    let mut current_len = 2;
+    for request_id in request_ids {
+        current_len += request_id.write_to_be_bytes(&mut buf[current_len..])?;
+    }
+    Ok(current_len)
+}
+
+/// Helper function to generate the application data for a PUS telecommand to time-shift
+/// activities identified by their request IDs according to ECSS-E-ST-70-41C 8.11.2.8.
+///
+/// The time offset is serialized as a big-endian [i64] in milliseconds and followed by the
+/// request ID list in the format generated by [generate_delete_by_request_id_app_data].
+pub fn generate_time_shift_by_request_id_app_data(
+    buf: &mut [u8],
+    offset_ms: i64,
+    request_ids: &[RequestId],
+) -> Result<usize, ScheduleError> {
+    if buf.len() < 8 {
+        return Err(ByteConversionError::ToSliceTooSmall {
+            found: buf.len(),
+            expected: 8 + 2 + request_ids.len() * RequestId::RAW_LEN,
+        }
+        .into());
+    }
+    buf[0..8].copy_from_slice(&offset_ms.to_be_bytes());
+    Ok(8 + generate_delete_by_request_id_app_data(&mut buf[8..], request_ids)?)
+}
+
+/// Shift a UNIX timestamp by a signed offset in milliseconds. Returns [None] if the
+/// result can not be represented.
+pub fn shift_unix_time(time: &UnixTime, offset_ms: i64) -> Option<UnixTime> {
+    const NANOS_PER_SEC: i128 = 1_000_000_000;
+    let shifted_nanos = time.as_secs() as i128 * NANOS_PER_SEC
+        + time.subsec_nanos() as i128
+        + offset_ms as i128 * 1_000_000;
+    let secs = i64::try_from(shifted_nanos.div_euclid(NANOS_PER_SEC)).ok()?;
+    Some(UnixTime::new(
+        secs,
+        shifted_nanos.rem_euclid(NANOS_PER_SEC) as u32,
+    ))
+}
+
+/// Policy which determines how the scheduler reacts to a correction of the on-board time, for
+/// example after a time synchronization with the ground.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TimeJumpPolicy {
+    /// Shift the release times of all scheduled telecommands by the time correction. The
+    /// remaining time until the release of each telecommand is preserved.
+    #[default]
+    ShiftSchedule,
+    /// Keep the absolute release times. Telecommands which are overdue by more than the given
+    /// margin after a forward jump are deleted instead of being released all at once. Overdue
+    /// telecommands within the margin are released normally.
+    ReleaseOverdueWithMargin(Duration),
+    /// Keep the absolute release times, but suspend the release of all telecommands until the
+    /// scheduler is resumed explicitly.
+    Suspend,
+}
+
 #[cfg(feature = "alloc")]
 pub mod alloc_mod {
     use alloc::{
@@ -345,9 +802,14 @@ pub mod alloc_mod {
         },
         vec::Vec,
     };
+    use spacepackets::ecss::scheduling;
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
     use spacepackets::time::cds::{self, DaysLen24Bits};
+    use spacepackets::{SpHeader, MAX_APID};
 
-    use crate::pool::PoolAddr;
+    use crate::pool::{PoolAddr, StaticMemoryPool};
+    use crate::pus::{EcssTmSender, EcssTmtcError};
+    use crate::ComponentId;
 
     use super::*;
 
@@ -367,6 +829,137 @@ pub mod alloc_mod {
         Ok(vec)
     }
 
+    /// Additional checks for telecommands which are inserted into the schedule with TC[11,4].
+    /// All checks are disabled by default.
+    ///
+    /// The release time checks use the [PusSchedulerProvider::scheduler_time]. Please note that
+    /// the scheduler itself still rejects all release times inside its time margin.
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    pub struct InsertionChecks {
+        /// Only telecommands with one of these APIDs may be scheduled. All APIDs are allowed if
+        /// this is [None].
+        pub allowed_apids: Option<Vec<u16>>,
+        /// Only telecommands of one of these services may be scheduled. All services are
+        /// allowed if this is [None].
+        pub allowed_services: Option<Vec<u8>>,
+        /// Release times which lie further in the past than this margin are rejected with
+        /// [ScheduleError::ReleaseTimeInPast].
+        pub past_margin: Option<Duration>,
+        /// Release times which lie further in the future than this horizon are rejected with
+        /// [ScheduleError::ReleaseTimeBeyondHorizon].
+        pub horizon: Option<Duration>,
+    }
+
+    impl InsertionChecks {
+        pub fn with_allowed_apids(mut self, apids: &[u16]) -> Self {
+            self.allowed_apids = Some(apids.to_vec());
+            self
+        }
+
+        pub fn with_allowed_services(mut self, services: &[u8]) -> Self {
+            self.allowed_services = Some(services.to_vec());
+            self
+        }
+
+        pub fn with_past_margin(mut self, past_margin: Duration) -> Self {
+            self.past_margin = Some(past_margin);
+            self
+        }
+
+        pub fn with_horizon(mut self, horizon: Duration) -> Self {
+            self.horizon = Some(horizon);
+            self
+        }
+
+        /// Check a telecommand which should be scheduled with the given release time. The
+        /// release time checks are skipped if the current time is [None].
+        pub fn check(
+            &self,
+            current_time: Option<&UnixTime>,
+            release_time: &UnixTime,
+            tc: &(impl CcsdsPacket + PusPacket),
+        ) -> Result<(), ScheduleError> {
+            if let Some(allowed_apids) = &self.allowed_apids {
+                if !allowed_apids.contains(&tc.apid()) {
+                    return Err(ScheduleError::ApidNotAllowed(tc.apid()));
+                }
+            }
+            if let Some(allowed_services) = &self.allowed_services {
+                if !allowed_services.contains(&PusPacket::service(tc)) {
+                    return Err(ScheduleError::ServiceNotAllowed(PusPacket::service(tc)));
+                }
+            }
+            let current_time = match current_time {
+                Some(current_time) => *current_time,
+                None => return Ok(()),
+            };
+            if let Some(past_margin) = self.past_margin {
+                if *release_time + past_margin < current_time {
+                    return Err(ScheduleError::ReleaseTimeInPast {
+                        current_time,
+                        past_margin,
+                        release_time: *release_time,
+                    });
+                }
+            }
+            if let Some(horizon) = self.horizon {
+                if *release_time > current_time + horizon {
+                    return Err(ScheduleError::ReleaseTimeBeyondHorizon {
+                        current_time,
+                        horizon,
+                        release_time: *release_time,
+                    });
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Reason why the release of a single telecommand failed.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum TcReleaseError<E> {
+        /// The telecommand could not be read from the TC store.
+        Read(PoolError),
+        /// The release callback returned an error.
+        Release(E),
+        /// The released telecommand could not be deleted from the TC store.
+        Deletion(PoolError),
+    }
+
+    /// Failed release of a single telecommand.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct TcReleaseFailure<E> {
+        pub info: TcInfo,
+        pub error: TcReleaseError<E>,
+    }
+
+    /// Report returned by [PusScheduler::release_telecommands_with_report].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ReleaseReport<E> {
+        /// Number of telecommands which were passed to the release callback successfully. This
+        /// includes telecommands which were only deleted because the scheduler or their group
+        /// was disabled.
+        pub released: u64,
+        /// All telecommands whose release failed, in release order.
+        pub failures: Vec<TcReleaseFailure<E>>,
+    }
+
+    impl<E> Default for ReleaseReport<E> {
+        fn default() -> Self {
+            Self {
+                released: 0,
+                failures: Vec::new(),
+            }
+        }
+    }
+
+    impl<E> ReleaseReport<E> {
+        /// Whether all due telecommands were released without errors.
+        pub fn all_released(&self) -> bool {
+            self.failures.is_empty()
+        }
+    }
+
     enum DeletionResult {
         WithoutStoreDeletion(Option<PoolAddr>),
         WithStoreDeletion(Result<bool, PoolError>),
@@ -392,7 +985,8 @@ pub mod alloc_mod {
     /// user always correctly increment for sequence counter due to overflows. To avoid this issue,
     /// it can make sense to split up telecommand groups by the APID to avoid overflows.
     ///
-    /// Currently, sub-schedules and groups are not supported.
+    /// Scheduled telecommands can be assigned to groups, which can be enabled and disabled
+    /// individually. Sub-schedules are currently not supported.
     #[derive(Debug)]
     pub struct PusScheduler {
         // TODO: Use MonotonicTime from tai-time crate instead of UnixTime and cache leap seconds.
@@ -403,6 +997,9 @@ pub mod alloc_mod {
         pub(crate) current_time: UnixTime,
         time_margin: Duration,
         enabled: bool,
+        groups: BTreeMap<GroupId, bool>,
+        time_jump_policy: TimeJumpPolicy,
+        suspended: bool,
     }
     impl PusScheduler {
         /// Create a new PUS scheduler.
@@ -421,6 +1018,9 @@ pub mod alloc_mod {
                 current_time: init_current_time,
                 time_margin,
                 enabled: true,
+                groups: BTreeMap::new(),
+                time_jump_policy: TimeJumpPolicy::default(),
+                suspended: false,
             }
         }
 
@@ -430,6 +1030,15 @@ pub mod alloc_mod {
             Ok(Self::new(UnixTime::now()?, time_margin))
         }
 
+        /// Like [Self::new], but sets the `init_current_time` parameter to the current time of
+        /// the given [TimeSource].
+        pub fn new_with_time_source(
+            time_source: &(impl TimeSource + ?Sized),
+            time_margin: Duration,
+        ) -> Result<Self, TimeSourceError> {
+            Ok(Self::new(time_source.now()?, time_margin))
+        }
+
         pub fn num_scheduled_telecommands(&self) -> u64 {
             let mut num_entries = 0;
             for entries in &self.tc_map {
@@ -460,6 +1069,11 @@ pub mod alloc_mod {
                     release_time: time_stamp,
                 });
             }
+            if let Some(group_id) = info.group_id {
+                if !self.groups.contains_key(&group_id) {
+                    return Err(ScheduleError::UnknownGroup(group_id));
+                }
+            }
             match self.tc_map.entry(time_stamp) {
                 Entry::Vacant(e) => {
                     e.insert(alloc::vec![info]);
@@ -563,7 +1177,7 @@ pub mod alloc_mod {
 
         /// Retrieve a range over all scheduled commands.
         pub fn retrieve_all(
-            &mut self,
+            &self,
         ) -> alloc::collections::btree_map::Range<'_, UnixTime, alloc::vec::Vec<TcInfo>> {
             self.tc_map.range(..)
         }
@@ -573,7 +1187,7 @@ pub mod alloc_mod {
         /// It should be noted that the ranged extraction is always inclusive. For example, a range
         /// from 50 to 100 unix seconds would also include command scheduled at 100 unix seconds.
         pub fn retrieve_by_time_filter<TimeProvider: CcsdsTimeProvider>(
-            &mut self,
+            &self,
             time_window: TimeWindow<TimeProvider>,
         ) -> Range<'_, UnixTime, alloc::vec::Vec<TcInfo>> {
             match time_window.time_window_type() {
@@ -597,6 +1211,28 @@ pub mod alloc_mod {
             }
         }
 
+        /// Number of scheduled telecommands inside the provided time window. The time window is
+        /// inclusive like for [Self::retrieve_by_time_filter].
+        pub fn num_scheduled_telecommands_in_window<TimeProvider: CcsdsTimeProvider>(
+            &self,
+            time_window: TimeWindow<TimeProvider>,
+        ) -> u64 {
+            self.retrieve_by_time_filter(time_window)
+                .map(|(_, tc_infos)| tc_infos.len() as u64)
+                .sum()
+        }
+
+        /// Release time of the next scheduled telecommand, or [None] if the schedule is empty.
+        ///
+        /// Telecommands inside disabled groups are considered as well because they are still
+        /// removed from the schedule at their release time.
+        pub fn next_release_time(&self) -> Option<UnixTime> {
+            self.tc_map
+                .iter()
+                .find(|(_, tc_infos)| !tc_infos.is_empty())
+                .map(|(release_time, _)| *release_time)
+        }
+
         /// Deletes a scheduled command with the given request  ID. Returns the store address if a
         /// scheduled command was found in the map and deleted, and None otherwise.
         ///
@@ -668,12 +1304,182 @@ pub mod alloc_mod {
             DeletionResult::WithStoreDeletion(Ok(false))
         }
 
+        /// Returns whether the group with the given ID is enabled, or [None] if the group does
+        /// not exist.
+        pub fn is_group_enabled(&self, group_id: GroupId) -> Option<bool> {
+            self.groups.get(&group_id).copied()
+        }
+
+        /// Number of scheduled telecommands which are assigned to the given group.
+        pub fn num_scheduled_telecommands_in_group(&self, group_id: GroupId) -> u64 {
+            self.tc_map
+                .values()
+                .flatten()
+                .filter(|info| info.group_id == Some(group_id))
+                .count() as u64
+        }
+
+        /// Whether a telecommand should be released for execution, which is the case if the
+        /// scheduler and the group of the telecommand are enabled.
+        fn release_enabled(&self, info: &TcInfo) -> bool {
+            self.enabled
+                && info.group_id.map_or(true, |group_id| {
+                    self.is_group_enabled(group_id) == Some(true)
+                })
+        }
+
+        /// Shift the release time of all scheduled telecommands for which the `filter` closure
+        /// returns [true] by a signed offset in milliseconds. The closure receives the current
+        /// release time and the telecommand information. Returns the number of shifted
+        /// telecommands.
+        ///
+        /// The time shift is atomic: If the new release time of any selected telecommand is
+        /// inside the time margin or can not be represented, an error is returned and the
+        /// schedule is not modified.
+        pub fn time_shift_filtered(
+            &mut self,
+            offset_ms: i64,
+            mut filter: impl FnMut(&UnixTime, &TcInfo) -> bool,
+        ) -> Result<u64, ScheduleError> {
+            let mut shifted_tcs = Vec::new();
+            for (release_time, tc_infos) in &self.tc_map {
+                for info in tc_infos {
+                    if !filter(release_time, info) {
+                        continue;
+                    }
+                    let new_release_time = shift_unix_time(release_time, offset_ms)
+                        .ok_or(ScheduleError::TimeShiftOverflow(offset_ms))?;
+                    if new_release_time < self.current_time + self.time_margin {
+                        return Err(ScheduleError::ReleaseTimeInTimeMargin {
+                            current_time: self.current_time,
+                            time_margin: self.time_margin,
+                            release_time: new_release_time,
+                        });
+                    }
+                    shifted_tcs.push((*release_time, new_release_time, *info));
+                }
+            }
+            for (old_release_time, _, info) in &shifted_tcs {
+                if let Entry::Occupied(mut tc_infos) = self.tc_map.entry(*old_release_time) {
+                    if let Some(idx) = tc_infos.get().iter().position(|v| v == info) {
+                        tc_infos.get_mut().remove(idx);
+                    }
+                    if tc_infos.get().is_empty() {
+                        tc_infos.remove();
+                    }
+                }
+            }
+            let num_shifted = shifted_tcs.len() as u64;
+            for (_, new_release_time, info) in shifted_tcs {
+                self.tc_map.entry(new_release_time).or_default().push(info);
+            }
+            Ok(num_shifted)
+        }
+
+        pub fn time_jump_policy(&self) -> TimeJumpPolicy {
+            self.time_jump_policy
+        }
+
+        pub fn set_time_jump_policy(&mut self, policy: TimeJumpPolicy) {
+            self.time_jump_policy = policy;
+        }
+
+        /// Whether the release of telecommands was suspended by a time correction with the
+        /// [TimeJumpPolicy::Suspend] policy.
+        pub fn is_suspended(&self) -> bool {
+            self.suspended
+        }
+
+        /// Resume the release of telecommands after a suspension.
+        pub fn resume(&mut self) {
+            self.suspended = false;
+        }
+
+        /// Notify the scheduler of a correction of the on-board time by a signed offset in
+        /// milliseconds. The current time of the scheduler is corrected by the same offset, and
+        /// the schedule is handled according to the configured [TimeJumpPolicy].
+        ///
+        /// Returns the number of shifted telecommands for the [TimeJumpPolicy::ShiftSchedule]
+        /// policy and the number of deleted telecommands for the
+        /// [TimeJumpPolicy::ReleaseOverdueWithMargin] policy. If deleting an overdue telecommand
+        /// from the pool fails, the method still removes all overdue telecommands from the
+        /// schedule and returns the last pool error.
+        pub fn handle_time_correction(
+            &mut self,
+            offset_ms: i64,
+            pool: &mut (impl PoolProvider + ?Sized),
+        ) -> Result<u64, ScheduleError> {
+            let corrected_time = shift_unix_time(&self.current_time, offset_ms)
+                .ok_or(ScheduleError::TimeShiftOverflow(offset_ms))?;
+            match self.time_jump_policy {
+                TimeJumpPolicy::ShiftSchedule => {
+                    let mut shifted_map: BTreeMap<UnixTime, Vec<TcInfo>> = BTreeMap::new();
+                    for (release_time, tc_infos) in &self.tc_map {
+                        let new_release_time = shift_unix_time(release_time, offset_ms)
+                            .ok_or(ScheduleError::TimeShiftOverflow(offset_ms))?;
+                        shifted_map
+                            .entry(new_release_time)
+                            .or_default()
+                            .extend_from_slice(tc_infos);
+                    }
+                    let num_shifted = self.num_scheduled_telecommands();
+                    self.tc_map = shifted_map;
+                    self.current_time = corrected_time;
+                    Ok(num_shifted)
+                }
+                TimeJumpPolicy::ReleaseOverdueWithMargin(margin) => {
+                    self.current_time = corrected_time;
+                    let margin_ms = i64::try_from(margin.as_millis()).unwrap_or(i64::MAX);
+                    let cutoff = match shift_unix_time(&corrected_time, -margin_ms) {
+                        Some(cutoff) => cutoff,
+                        None => return Ok(0),
+                    };
+                    let remaining = self.tc_map.split_off(&cutoff);
+                    let overdue = core::mem::replace(&mut self.tc_map, remaining);
+                    let mut num_deleted = 0;
+                    let mut store_error = Ok(());
+                    for info in overdue.values().flatten() {
+                        match pool.delete(info.addr) {
+                            Ok(_) => num_deleted += 1,
+                            Err(e) => store_error = Err(ScheduleError::StoreError(e)),
+                        }
+                    }
+                    store_error.map(|_| num_deleted)
+                }
+                TimeJumpPolicy::Suspend => {
+                    self.current_time = corrected_time;
+                    self.suspended = true;
+                    Ok(0)
+                }
+            }
+        }
+
+        /// Shift the release time of all scheduled telecommands with the given APID by a signed
+        /// offset in milliseconds. Returns the number of shifted telecommands.
+        pub fn time_shift_by_apid(
+            &mut self,
+            apid: u16,
+            offset_ms: i64,
+        ) -> Result<u64, ScheduleError> {
+            self.time_shift_filtered(offset_ms, |_, info| info.request_id.apid == apid)
+        }
+
         #[cfg(feature = "std")]
         pub fn update_time_from_now(&mut self) -> Result<(), SystemTimeError> {
             self.current_time = UnixTime::now()?;
             Ok(())
         }
 
+        /// Update the current time of the scheduler using the given [TimeSource]. This allows
+        /// running the scheduler with a simulated mission time.
+        pub fn update_time_from_source(
+            &mut self,
+            time_source: &(impl TimeSource + ?Sized),
+        ) -> Result<(), TimeSourceError> {
+            self.current_time = time_source.now()?;
+            Ok(())
+        }
+
         /// Utility method which calls [Self::telecommands_to_release] and then calls a releaser
         /// closure for each telecommand which should be released. This function will also delete
         /// the telecommands from the holding store after calling the release closure if the user
@@ -718,6 +1524,9 @@ pub mod alloc_mod {
             tc_store: &mut (impl PoolProvider + ?Sized),
             mut tc_buf: Option<&mut [u8]>,
         ) -> Result<u64, (u64, PoolError)> {
+            if self.suspended {
+                return Ok(0);
+            }
             let tcs_to_release = self.telecommands_to_release();
             let mut released_tcs = 0;
             let mut store_error = Ok(());
@@ -728,13 +1537,13 @@ pub mod alloc_mod {
                             tc_store
                                 .read(&info.addr, buf)
                                 .map_err(|e| (released_tcs, e))?;
-                            releaser(self.enabled, info, buf)
+                            releaser(self.release_enabled(info), info, buf)
                         }
                         None => {
                             let tc = tc_store
                                 .read_as_vec(&info.addr)
                                 .map_err(|e| (released_tcs, e))?;
-                            releaser(self.enabled, info, &tc)
+                            releaser(self.release_enabled(info), info, &tc)
                         }
                     };
                     released_tcs += 1;
@@ -752,18 +1561,88 @@ pub mod alloc_mod {
                 .map_err(|e| (released_tcs, e))
         }
 
-        /// This utility method is similar to [Self::release_telecommands] but will not perform
-        /// store deletions and thus does not require a mutable reference of the TC store.
+        /// Release all due telecommands while isolating failures of single telecommands.
         ///
-        /// It will returns a [Vec] of [TcInfo]s to transfer the list of released
-        /// telecommands to the user. The user should take care of deleting those telecommands
-        /// from the holding store to prevent memory leaks.
-        pub fn release_telecommands_no_deletion<R: FnMut(bool, &TcInfo, &[u8])>(
-            &mut self,
-            mut releaser: R,
+        /// In contrast to [Self::release_telecommands_with_buffer], errors are not propagated
+        /// immediately. Instead, an error when reading a telecommand from the store, an error
+        /// returned by the release closure or an error when deleting the telecommand is recorded
+        /// in the returned [ReleaseReport], and the release of the remaining due telecommands
+        /// continues. All due telecommands are removed from the schedule and deleted from the
+        /// store, also if they could not be read or their release failed. A failed deletion is
+        /// recorded as a separate failure.
+        ///
+        /// # Arguments
+        ///
+        /// * `releaser` - Closure where the first argument is whether the scheduler and the group
+        ///     of the telecommand are enabled, the second argument is the telecommand information
+        ///     and the third argument is the raw telecommand.
+        /// * `tc_store` - The holding store of the telecommands.
+        /// * `tc_buf` - Buffer to hold each telecommand being released.
+        pub fn release_telecommands_with_report<
+            E,
+            R: FnMut(bool, &TcInfo, &[u8]) -> Result<(), E>,
+        >(
+            &mut self,
+            mut releaser: R,
+            tc_store: &mut (impl PoolProvider + ?Sized),
+            tc_buf: &mut [u8],
+        ) -> ReleaseReport<E> {
+            let mut report = ReleaseReport::default();
+            if self.suspended {
+                return report;
+            }
+            for (_, tcs) in self.telecommands_to_release() {
+                for info in tcs {
+                    let tc_len = match tc_store.read(&info.addr, tc_buf) {
+                        Ok(tc_len) => tc_len,
+                        Err(e) => {
+                            report.failures.push(TcReleaseFailure {
+                                info: *info,
+                                error: TcReleaseError::Read(e),
+                            });
+                            if let Err(e) = tc_store.delete(info.addr) {
+                                report.failures.push(TcReleaseFailure {
+                                    info: *info,
+                                    error: TcReleaseError::Deletion(e),
+                                });
+                            }
+                            continue;
+                        }
+                    };
+                    match releaser(self.release_enabled(info), info, &tc_buf[..tc_len]) {
+                        Ok(()) => report.released += 1,
+                        Err(e) => report.failures.push(TcReleaseFailure {
+                            info: *info,
+                            error: TcReleaseError::Release(e),
+                        }),
+                    }
+                    if let Err(e) = tc_store.delete(info.addr) {
+                        report.failures.push(TcReleaseFailure {
+                            info: *info,
+                            error: TcReleaseError::Deletion(e),
+                        });
+                    }
+                }
+            }
+            self.tc_map.retain(|k, _| k > &self.current_time);
+            report
+        }
+
+        /// This utility method is similar to [Self::release_telecommands] but will not perform
+        /// store deletions and thus does not require a mutable reference of the TC store.
+        ///
+        /// It will returns a [Vec] of [TcInfo]s to transfer the list of released
+        /// telecommands to the user. The user should take care of deleting those telecommands
+        /// from the holding store to prevent memory leaks.
+        pub fn release_telecommands_no_deletion<R: FnMut(bool, &TcInfo, &[u8])>(
+            &mut self,
+            mut releaser: R,
             tc_store: &(impl PoolProvider + ?Sized),
             tc_buf: &mut [u8],
         ) -> Result<alloc::vec::Vec<TcInfo>, (alloc::vec::Vec<TcInfo>, PoolError)> {
+            if self.suspended {
+                return Ok(alloc::vec::Vec::new());
+            }
             let tcs_to_release = self.telecommands_to_release();
             let mut released_tcs = alloc::vec::Vec::new();
             for tc in tcs_to_release {
@@ -771,7 +1650,7 @@ pub mod alloc_mod {
                     tc_store
                         .read(&info.addr, tc_buf)
                         .map_err(|e| (released_tcs.clone(), e))?;
-                    releaser(self.is_enabled(), info, tc_buf);
+                    releaser(self.release_enabled(info), info, tc_buf);
                     released_tcs.push(*info);
                 }
             }
@@ -779,8 +1658,12 @@ pub mod alloc_mod {
             Ok(released_tcs)
         }
 
-        /// Retrieve all telecommands which should be release based on the current time.
+        /// Retrieve all telecommands which should be release based on the current time. No
+        /// telecommands are returned while the scheduler is suspended.
         pub fn telecommands_to_release(&self) -> Range<'_, UnixTime, Vec<TcInfo>> {
+            if self.suspended {
+                return self.tc_map.range(..UnixTime::new(i64::MIN, 0));
+            }
             self.tc_map.range(..=self.current_time)
         }
     }
@@ -790,6 +1673,7 @@ pub mod alloc_mod {
 
         /// This will disable the scheduler and clear the schedule as specified in 6.11.4.4.
         /// Be careful with this command as it will delete all the commands in the schedule.
+        /// All groups are deleted as well.
         ///
         /// The holding store for the telecommands needs to be passed so all the stored telecommands
         /// can be deleted to avoid a memory leak. If at last one deletion operation fails, the error
@@ -806,6 +1690,7 @@ pub mod alloc_mod {
                 }
             }
             self.tc_map.clear();
+            self.groups.clear();
             deletion_ok
         }
 
@@ -823,6 +1708,10 @@ pub mod alloc_mod {
             self.enabled = false;
         }
 
+        fn scheduler_time(&self) -> Option<UnixTime> {
+            Some(self.current_time)
+        }
+
         fn insert_unwrapped_and_stored_tc(
             &mut self,
             time_stamp: UnixTime,
@@ -835,6 +1724,11 @@ pub mod alloc_mod {
                     release_time: time_stamp,
                 });
             }
+            if let Some(group_id) = info.group_id {
+                if !self.groups.contains_key(&group_id) {
+                    return Err(ScheduleError::UnknownGroup(group_id));
+                }
+            }
             match self.tc_map.entry(time_stamp) {
                 Entry::Vacant(e) => {
                     e.insert(alloc::vec![info]);
@@ -845,1239 +1739,3086 @@ pub mod alloc_mod {
             }
             Ok(())
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::pool::{
-        PoolAddr, PoolError, PoolProvider, StaticMemoryPool, StaticPoolAddr, StaticPoolConfig,
-    };
-    use alloc::collections::btree_map::Range;
-    use spacepackets::ecss::tc::{PusTcCreator, PusTcReader, PusTcSecondaryHeader};
-    use spacepackets::ecss::WritablePusPacket;
-    use spacepackets::time::{cds, TimeWriter, UnixTime};
-    use spacepackets::{PacketId, PacketSequenceCtrl, PacketType, SequenceFlags, SpHeader};
-    use std::time::Duration;
-    use std::vec::Vec;
-    #[allow(unused_imports)]
-    use std::{println, vec};
+        fn delete_by_request_id_and_from_pool(
+            &mut self,
+            req_id: &RequestId,
+            pool: &mut (impl PoolProvider + ?Sized),
+        ) -> Result<bool, PoolError> {
+            PusScheduler::delete_by_request_id_and_from_pool(self, req_id, pool)
+        }
 
-    fn pus_tc_base(timestamp: UnixTime, buf: &mut [u8]) -> (SpHeader, usize) {
-        let cds_time =
-            cds::CdsTime::from_unix_time_with_u16_days(&timestamp, cds::SubmillisPrecision::Absent)
-                .unwrap();
-        let len_time_stamp = cds_time.write_to_bytes(buf).unwrap();
-        let len_packet = base_ping_tc_simple_ctor(0, &[])
-            .write_to_bytes(&mut buf[len_time_stamp..])
-            .unwrap();
-        (
-            SpHeader::new_for_unseg_tc(0x02, 0x34, len_packet as u16),
-            len_packet + len_time_stamp,
-        )
-    }
+        fn delete_by_time_window(
+            &mut self,
+            time_window: TimeWindow<Self::TimeProvider>,
+            pool: &mut (impl PoolProvider + ?Sized),
+        ) -> Result<u64, (u64, PoolError)> {
+            self.delete_by_time_filter(time_window, pool)
+        }
 
-    fn scheduled_tc(timestamp: UnixTime, buf: &mut [u8]) -> PusTcCreator {
-        let (sph, len_app_data) = pus_tc_base(timestamp, buf);
-        PusTcCreator::new_simple(sph, 11, 4, &buf[..len_app_data], true)
-    }
+        fn time_shift_all(&mut self, offset_ms: i64) -> Result<u64, ScheduleError> {
+            self.time_shift_filtered(offset_ms, |_, _| true)
+        }
 
-    fn wrong_tc_service(timestamp: UnixTime, buf: &mut [u8]) -> PusTcCreator {
-        let (sph, len_app_data) = pus_tc_base(timestamp, buf);
-        PusTcCreator::new_simple(sph, 12, 4, &buf[..len_app_data], true)
-    }
+        /// Please note that this function will only shift the first telecommand with a request ID
+        /// match, similarly to [Self::delete_by_request_id].
+        fn time_shift_by_request_id(
+            &mut self,
+            req_id: &RequestId,
+            offset_ms: i64,
+        ) -> Result<bool, ScheduleError> {
+            let mut found = false;
+            let num_shifted = self.time_shift_filtered(offset_ms, |_, info| {
+                if found || &info.request_id != req_id {
+                    return false;
+                }
+                found = true;
+                true
+            })?;
+            Ok(num_shifted > 0)
+        }
 
-    fn wrong_tc_subservice(timestamp: UnixTime, buf: &mut [u8]) -> PusTcCreator {
-        let (sph, len_app_data) = pus_tc_base(timestamp, buf);
-        PusTcCreator::new_simple(sph, 11, 5, &buf[..len_app_data], true)
-    }
+        fn time_shift_by_time_window(
+            &mut self,
+            time_window: TimeWindow<Self::TimeProvider>,
+            offset_ms: i64,
+        ) -> Result<u64, ScheduleError> {
+            let release_times: Vec<UnixTime> = self
+                .retrieve_by_time_filter(time_window)
+                .map(|(release_time, _)| *release_time)
+                .collect();
+            self.time_shift_filtered(offset_ms, |release_time, _| {
+                release_times.binary_search(release_time).is_ok()
+            })
+        }
 
-    fn double_wrapped_time_tagged_tc(timestamp: UnixTime, buf: &mut [u8]) -> PusTcCreator {
-        let cds_time =
-            cds::CdsTime::from_unix_time_with_u16_days(&timestamp, cds::SubmillisPrecision::Absent)
-                .unwrap();
-        let len_time_stamp = cds_time.write_to_bytes(buf).unwrap();
-        let sph = SpHeader::new_for_unseg_tc(0x02, 0x34, 0);
-        // app data should not matter, double wrapped time-tagged commands should be rejected right
-        // away
-        let inner_time_tagged_tc = PusTcCreator::new_simple(sph, 11, 4, &[], true);
-        let packet_len = inner_time_tagged_tc
-            .write_to_bytes(&mut buf[len_time_stamp..])
-            .expect("writing inner time tagged tc failed");
-        PusTcCreator::new_simple(sph, 11, 4, &buf[..len_time_stamp + packet_len], true)
-    }
+        fn for_each_scheduled_tc(&self, mut f: impl FnMut(&UnixTime, &TcInfo)) {
+            for (release_time, tc_infos) in &self.tc_map {
+                for info in tc_infos {
+                    f(release_time, info);
+                }
+            }
+        }
 
-    fn invalid_time_tagged_cmd() -> PusTcCreator<'static> {
-        let sph = SpHeader::new_for_unseg_tc(0x02, 0x34, 1);
-        PusTcCreator::new_simple(sph, 11, 4, &[], true)
-    }
+        fn create_group(&mut self, group_id: GroupId) -> Result<(), ScheduleError> {
+            match self.groups.entry(group_id) {
+                Entry::Vacant(e) => {
+                    e.insert(true);
+                    Ok(())
+                }
+                Entry::Occupied(_) => Err(ScheduleError::GroupAlreadyExists(group_id)),
+            }
+        }
 
-    fn base_ping_tc_simple_ctor(seq_count: u16, app_data: &'static [u8]) -> PusTcCreator<'static> {
-        let sph = SpHeader::new_for_unseg_tc(0x02, seq_count, 0);
-        PusTcCreator::new_simple(sph, 17, 1, app_data, true)
-    }
+        fn delete_group(&mut self, group_id: GroupId) -> Result<(), ScheduleError> {
+            if !self.groups.contains_key(&group_id) {
+                return Err(ScheduleError::UnknownGroup(group_id));
+            }
+            if self.num_scheduled_telecommands_in_group(group_id) > 0 {
+                return Err(ScheduleError::GroupNotEmpty(group_id));
+            }
+            self.groups.remove(&group_id);
+            Ok(())
+        }
 
-    fn ping_tc_to_store(
-        pool: &mut StaticMemoryPool,
-        buf: &mut [u8],
-        seq_count: u16,
-        app_data: &'static [u8],
-    ) -> TcInfo {
-        let ping_tc = base_ping_tc_simple_ctor(seq_count, app_data);
-        let ping_size = ping_tc.write_to_bytes(buf).expect("writing ping TC failed");
-        let first_addr = pool.add(&buf[0..ping_size]).unwrap();
-        TcInfo::new(first_addr, RequestId::from_tc(&ping_tc))
+        fn enable_group(&mut self, group_id: GroupId) -> Result<(), ScheduleError> {
+            let enabled = self
+                .groups
+                .get_mut(&group_id)
+                .ok_or(ScheduleError::UnknownGroup(group_id))?;
+            *enabled = true;
+            Ok(())
+        }
+
+        fn disable_group(&mut self, group_id: GroupId) -> Result<(), ScheduleError> {
+            let enabled = self
+                .groups
+                .get_mut(&group_id)
+                .ok_or(ScheduleError::UnknownGroup(group_id))?;
+            *enabled = false;
+            Ok(())
+        }
+
+        fn for_each_group(&self, mut f: impl FnMut(GroupId, bool)) {
+            for (group_id, enabled) in &self.groups {
+                f(*group_id, *enabled);
+            }
+        }
     }
 
-    #[test]
-    fn test_enable_api() {
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        assert!(scheduler.is_enabled());
-        scheduler.disable();
-        assert!(!scheduler.is_enabled());
-        scheduler.enable();
-        assert!(scheduler.is_enabled());
+    /// Creates PUS 11 schedule reports based on the content of a [PusSchedulerProvider].
+    ///
+    /// Release times are serialized as the UNIX seconds in a big-endian [i64] followed by the
+    /// sub-second nanoseconds in a big-endian [u32].
+    ///
+    /// The summary report only contains the number of scheduled telecommands as a big-endian [u32],
+    /// followed by the release time of the next telecommand if the schedule is not empty.
+    ///
+    /// The detailed report starts with the number of entries N as a big-endian [u16], followed by N
+    /// entries. Each entry consists of the release time, the [RequestId], the length of the
+    /// telecommand as a big-endian [u16] and the raw telecommand. The length is 0 if the report was
+    /// generated without the telecommands. The detailed report is split into multiple packets if
+    /// the entries do not fit into the maximum source data length.
+    pub struct PusScheduleReportCreator {
+        id: ComponentId,
+        apid: u16,
+        pub dest_id: u16,
+        source_data_buf: Vec<u8>,
     }
 
-    #[test]
-    fn test_reset() {
-        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
-            vec![(10, 32), (5, 64)],
-            false,
-        ));
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+    /// Length of a serialized release time inside a schedule report.
+    pub const SCHEDULE_REPORT_RELEASE_TIME_LEN: usize = 12;
 
-        let mut buf: [u8; 32] = [0; 32];
-        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+    const GROUP_STATUS_ENTRY_LEN: usize = 3;
 
-        scheduler
-            .insert_unwrapped_and_stored_tc(
-                UnixTime::new_only_secs(100),
-                TcInfo::new(tc_info_0.addr, tc_info_0.request_id),
+    impl PusScheduleReportCreator {
+        /// Create a new report creator. Returns [None] if the APID is invalid.
+        ///
+        /// ## Parameter
+        ///
+        /// * `id` - Component ID used to send the telemetry.
+        /// * `apid` - APID of the generated telemetry.
+        /// * `dest_id` - Destination ID of the generated telemetry.
+        /// * `max_source_data_len` - Maximum source data length of a generated report.
+        pub fn new(
+            id: ComponentId,
+            apid: u16,
+            dest_id: u16,
+            max_source_data_len: usize,
+        ) -> Option<Self> {
+            if apid > MAX_APID {
+                return None;
+            }
+            Some(Self {
+                id,
+                apid,
+                dest_id,
+                source_data_buf: alloc::vec![0; max_source_data_len],
+            })
+        }
+
+        /// Send a summary report containing the number of scheduled telecommands and the next
+        /// release time.
+        pub fn summary_report(
+            &mut self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            scheduler: &impl PusSchedulerProvider,
+        ) -> Result<(), EcssTmtcError> {
+            let mut num_tcs: u32 = 0;
+            let mut next_release_time = None;
+            scheduler.for_each_scheduled_tc(|release_time, _| {
+                if next_release_time.is_none() {
+                    next_release_time = Some(*release_time);
+                }
+                num_tcs += 1;
+            });
+            let required_len =
+                4 + next_release_time.map_or(0, |_| SCHEDULE_REPORT_RELEASE_TIME_LEN);
+            if self.source_data_buf.len() < required_len {
+                return Err(ByteConversionError::ToSliceTooSmall {
+                    found: self.source_data_buf.len(),
+                    expected: required_len,
+                }
+                .into());
+            }
+            self.source_data_buf[0..4].copy_from_slice(&num_tcs.to_be_bytes());
+            if let Some(release_time) = next_release_time {
+                write_release_time(&release_time, &mut self.source_data_buf[4..]);
+            }
+            self.send_report(
+                sender,
+                time_stamp,
+                scheduling::Subservice::TmSummaryReport as u8,
+                required_len,
             )
-            .unwrap();
+        }
 
-        let app_data = &[0, 1, 2];
-        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, app_data);
-        scheduler
-            .insert_unwrapped_and_stored_tc(
-                UnixTime::new_only_secs(200),
-                TcInfo::new(tc_info_1.addr, tc_info_1.request_id),
+        /// Send a detailed report for all scheduled telecommands without the telecommands
+        /// themselves. Returns the number of sent packets.
+        pub fn detailed_report(
+            &mut self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            scheduler: &impl PusSchedulerProvider,
+        ) -> Result<u32, EcssTmtcError> {
+            self.detailed_report_generic::<StaticMemoryPool>(sender, time_stamp, scheduler, None)
+        }
+
+        /// Send a detailed report for all scheduled telecommands which also contains the raw
+        /// telecommands read from the passed TC pool. Returns the number of sent packets.
+        pub fn detailed_report_with_tcs(
+            &mut self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            scheduler: &impl PusSchedulerProvider,
+            tc_pool: &(impl PoolProvider + ?Sized),
+        ) -> Result<u32, EcssTmtcError> {
+            self.detailed_report_generic(sender, time_stamp, scheduler, Some(tc_pool))
+        }
+
+        fn detailed_report_generic<Pool: PoolProvider + ?Sized>(
+            &mut self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            scheduler: &impl PusSchedulerProvider,
+            tc_pool: Option<&Pool>,
+        ) -> Result<u32, EcssTmtcError> {
+            // Each report starts with the number of entries, even if the schedule is empty.
+            if self.source_data_buf.len() < 2 {
+                return Err(ByteConversionError::ToSliceTooSmall {
+                    found: self.source_data_buf.len(),
+                    expected: 2,
+                }
+                .into());
+            }
+            let mut entries = Vec::new();
+            scheduler.for_each_scheduled_tc(|release_time, info| {
+                entries.push((*release_time, *info));
+            });
+            let mut num_packets = 0;
+            let mut num_entries: u16 = 0;
+            let mut current_len = 2;
+            for (release_time, info) in entries {
+                let tc_len = match tc_pool {
+                    Some(pool) => pool.len_of_data(&info.addr)?,
+                    None => 0,
+                };
+                let entry_len = SCHEDULE_REPORT_RELEASE_TIME_LEN + RequestId::RAW_LEN + 2 + tc_len;
+                if current_len + entry_len > self.source_data_buf.len() || num_entries == u16::MAX {
+                    if num_entries == 0 {
+                        return Err(ByteConversionError::ToSliceTooSmall {
+                            found: self.source_data_buf.len(),
+                            expected: 2 + entry_len,
+                        }
+                        .into());
+                    }
+                    self.send_detailed_report(sender, time_stamp, num_entries, current_len)?;
+                    num_packets += 1;
+                    num_entries = 0;
+                    current_len = 2;
+                }
+                let buf = &mut self.source_data_buf[current_len..current_len + entry_len];
+                write_release_time(&release_time, buf);
+                let mut idx = SCHEDULE_REPORT_RELEASE_TIME_LEN;
+                idx += info.request_id.write_to_be_bytes(&mut buf[idx..])?;
+                buf[idx..idx + 2].copy_from_slice(&(tc_len as u16).to_be_bytes());
+                idx += 2;
+                if let Some(pool) = tc_pool {
+                    pool.read(&info.addr, &mut buf[idx..])?;
+                }
+                current_len += entry_len;
+                num_entries += 1;
+            }
+            if num_entries > 0 || num_packets == 0 {
+                self.send_detailed_report(sender, time_stamp, num_entries, current_len)?;
+                num_packets += 1;
+            }
+            Ok(num_packets)
+        }
+
+        /// Send a report containing the status of all groups. The report starts with the number
+        /// of groups N as a big-endian [u16], followed by N entries consisting of the group ID as
+        /// a big-endian [u16] and a status byte which is 1 for enabled groups and 0 otherwise.
+        /// Returns the number of sent packets.
+        pub fn groups_status_report(
+            &mut self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            scheduler: &impl PusSchedulerProvider,
+        ) -> Result<u32, EcssTmtcError> {
+            let mut groups = Vec::new();
+            scheduler.for_each_group(|group_id, enabled| groups.push((group_id, enabled)));
+            let groups_per_packet = (self.source_data_buf.len().saturating_sub(2)
+                / GROUP_STATUS_ENTRY_LEN)
+                .min(u16::MAX as usize);
+            if groups_per_packet == 0 {
+                return Err(ByteConversionError::ToSliceTooSmall {
+                    found: self.source_data_buf.len(),
+                    expected: 2 + GROUP_STATUS_ENTRY_LEN,
+                }
+                .into());
+            }
+            if groups.is_empty() {
+                self.send_groups_status_report(sender, time_stamp, &groups)?;
+                return Ok(1);
+            }
+            let mut num_packets = 0;
+            for chunk in groups.chunks(groups_per_packet) {
+                self.send_groups_status_report(sender, time_stamp, chunk)?;
+                num_packets += 1;
+            }
+            Ok(num_packets)
+        }
+
+        fn send_groups_status_report(
+            &mut self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            groups: &[(GroupId, bool)],
+        ) -> Result<(), EcssTmtcError> {
+            self.source_data_buf[0..2].copy_from_slice(&(groups.len() as u16).to_be_bytes());
+            for (idx, (group_id, enabled)) in groups.iter().enumerate() {
+                let offset = 2 + idx * GROUP_STATUS_ENTRY_LEN;
+                self.source_data_buf[offset..offset + 2].copy_from_slice(&group_id.to_be_bytes());
+                self.source_data_buf[offset + 2] = *enabled as u8;
+            }
+            self.send_report(
+                sender,
+                time_stamp,
+                GroupSubservice::TmGroupsStatusReport.into(),
+                2 + groups.len() * GROUP_STATUS_ENTRY_LEN,
             )
-            .unwrap();
+        }
 
-        let app_data = &[0, 1, 2];
-        let tc_info_2 = ping_tc_to_store(&mut pool, &mut buf, 2, app_data);
-        scheduler
-            .insert_unwrapped_and_stored_tc(
-                UnixTime::new_only_secs(300),
-                TcInfo::new(tc_info_2.addr(), tc_info_2.request_id()),
+        fn send_detailed_report(
+            &mut self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            num_entries: u16,
+            source_data_len: usize,
+        ) -> Result<(), EcssTmtcError> {
+            self.source_data_buf[0..2].copy_from_slice(&num_entries.to_be_bytes());
+            self.send_report(
+                sender,
+                time_stamp,
+                scheduling::Subservice::TmDetailReport as u8,
+                source_data_len,
             )
-            .unwrap();
+        }
 
-        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
-        assert!(scheduler.is_enabled());
-        scheduler.reset(&mut pool).expect("deletion of TCs failed");
-        assert!(!scheduler.is_enabled());
-        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
-        assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
-        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
-        assert!(!pool.has_element_at(&tc_info_2.addr()).unwrap());
+        fn send_report(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            time_stamp: &[u8],
+            subservice: u8,
+            source_data_len: usize,
+        ) -> Result<(), EcssTmtcError> {
+            let sec_header = PusTmSecondaryHeader::new(11, subservice, 0, self.dest_id, time_stamp);
+            let tm_creator = PusTmCreator::new(
+                SpHeader::new_from_apid(self.apid),
+                sec_header,
+                &self.source_data_buf[0..source_data_len],
+                true,
+            );
+            sender.send_tm(self.id, tm_creator.into())
+        }
     }
 
-    #[test]
-    fn insert_multi_with_same_time() {
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+    fn write_release_time(release_time: &UnixTime, buf: &mut [u8]) {
+        buf[0..8].copy_from_slice(&release_time.as_secs().to_be_bytes());
+        buf[8..12].copy_from_slice(&release_time.subsec_nanos().to_be_bytes());
+    }
+}
 
-        scheduler
-            .insert_unwrapped_and_stored_tc(
-                UnixTime::new_only_secs(100),
-                TcInfo::new(
-                    PoolAddr::from(StaticPoolAddr {
-                        pool_idx: 0,
-                        packet_idx: 1,
-                    }),
-                    RequestId {
-                        seq_count: 1,
-                        apid: 0,
-                        source_id: 0,
-                    },
-                ),
-            )
-            .unwrap();
+#[cfg(feature = "heapless")]
+pub mod heapless_mod {
+    use super::*;
 
-        scheduler
-            .insert_unwrapped_and_stored_tc(
+    /// PUS scheduler which does not require `alloc` support and stores up to N scheduled
+    /// telecommands in a pre-allocated list.
+    ///
+    /// The list is ordered by the release time and telecommands with the same release time are
+    /// released in the order of insertion. The API for insertion and release is similar to the
+    /// API of the [PusScheduler], and it implements the [PusSchedulerProvider] so it can be used
+    /// with the PUS 11 service handler as well. Groups are not supported.
+    #[derive(Debug)]
+    pub struct HeaplessPusScheduler<const N: usize> {
+        tcs: heapless::Vec<(UnixTime, TcInfo), N>,
+        current_time: UnixTime,
+        time_margin: Duration,
+        enabled: bool,
+    }
+
+    impl<const N: usize> HeaplessPusScheduler<N> {
+        /// Create a new scheduler.
+        ///
+        /// ## Parameter
+        ///
+        /// * `init_current_time` - The time to initialize the scheduler with.
+        /// * `time_margin` - If the release time of a new telecommand is earlier than the time
+        ///      margin added to the current time, it will not be inserted into the schedule.
+        pub fn new(init_current_time: UnixTime, time_margin: Duration) -> Self {
+            Self {
+                tcs: heapless::Vec::new(),
+                current_time: init_current_time,
+                time_margin,
+                enabled: true,
+            }
+        }
+
+        /// Maximum number of scheduled telecommands.
+        pub const fn capacity(&self) -> usize {
+            N
+        }
+
+        pub fn num_scheduled_telecommands(&self) -> u64 {
+            self.tcs.len() as u64
+        }
+
+        pub fn update_time(&mut self, current_time: UnixTime) {
+            self.current_time = current_time;
+        }
+
+        /// Update the current time of the scheduler using the given [TimeSource].
+        pub fn update_time_from_source(
+            &mut self,
+            time_source: &(impl TimeSource + ?Sized),
+        ) -> Result<(), TimeSourceError> {
+            self.current_time = time_source.now()?;
+            Ok(())
+        }
+
+        pub fn current_time(&self) -> &UnixTime {
+            &self.current_time
+        }
+
+        /// Call the passed closure for each telecommand which should be released based on the
+        /// current time.
+        pub fn telecommands_to_release(&self, mut f: impl FnMut(&UnixTime, &TcInfo)) {
+            for (release_time, info) in self.tcs.iter() {
+                if release_time > &self.current_time {
+                    break;
+                }
+                f(release_time, info);
+            }
+        }
+
+        /// Release all telecommands for which the release time was reached. This function
+        /// behaves like [PusScheduler::release_telecommands_with_buffer].
+        ///
+        /// # Arguments
+        ///
+        /// * `releaser` - Closure where the first argument is whether the scheduler is enabled and
+        ///     the second argument is the telecommand information also containing the store
+        ///     address. This closure should return whether the command should be deleted.
+        /// * `tc_store` - The holding store of the telecommands.
+        /// * `tc_buf` - Buffer to hold each telecommand being released.
+        pub fn release_telecommands_with_buffer<R: FnMut(bool, &TcInfo, &[u8]) -> bool>(
+            &mut self,
+            mut releaser: R,
+            tc_store: &mut (impl PoolProvider + ?Sized),
+            tc_buf: &mut [u8],
+        ) -> Result<u64, (u64, PoolError)> {
+            let mut released_tcs = 0;
+            let mut store_error = Ok(());
+            let num_to_release = self
+                .tcs
+                .iter()
+                .take_while(|(release_time, _)| release_time <= &self.current_time)
+                .count();
+            for (_, info) in self.tcs[0..num_to_release].iter() {
+                tc_store
+                    .read(&info.addr, tc_buf)
+                    .map_err(|e| (released_tcs, e))?;
+                let should_delete = releaser(self.enabled, info, tc_buf);
+                released_tcs += 1;
+                if should_delete {
+                    let res = tc_store.delete(info.addr);
+                    if res.is_err() {
+                        store_error = res;
+                    }
+                }
+            }
+            self.remove_range(0, num_to_release);
+            store_error
+                .map(|_| released_tcs)
+                .map_err(|e| (released_tcs, e))
+        }
+
+        /// Delete all scheduled telecommands for which the `filter` closure returns [true] from
+        /// the schedule and from the passed pool. This function returns the number of deleted
+        /// commands on success. In case any deletion fails, the last deletion error will be
+        /// supplied in addition to the number of deleted commands.
+        pub fn delete_filtered(
+            &mut self,
+            mut filter: impl FnMut(&UnixTime, &TcInfo) -> bool,
+            pool: &mut (impl PoolProvider + ?Sized),
+        ) -> Result<u64, (u64, PoolError)> {
+            let mut num_deleted = 0;
+            let mut store_error = None;
+            let mut idx = 0;
+            while idx < self.tcs.len() {
+                let (release_time, info) = self.tcs[idx];
+                if !filter(&release_time, &info) {
+                    idx += 1;
+                    continue;
+                }
+                match pool.delete(info.addr) {
+                    Ok(_) => num_deleted += 1,
+                    Err(e) => store_error = Some(e),
+                }
+                self.remove_range(idx, idx + 1);
+            }
+            if let Some(e) = store_error {
+                return Err((num_deleted, e));
+            }
+            Ok(num_deleted)
+        }
+
+        /// Shift the release time of all scheduled telecommands for which the `filter` closure
+        /// returns [true] by a signed offset in milliseconds. This function behaves like
+        /// [PusScheduler::time_shift_filtered].
+        pub fn time_shift_filtered(
+            &mut self,
+            offset_ms: i64,
+            mut filter: impl FnMut(&UnixTime, &TcInfo) -> bool,
+        ) -> Result<u64, ScheduleError> {
+            let mut shifted_tcs: heapless::Vec<(usize, UnixTime), N> = heapless::Vec::new();
+            for (idx, (release_time, info)) in self.tcs.iter().enumerate() {
+                if !filter(release_time, info) {
+                    continue;
+                }
+                let new_release_time = shift_unix_time(release_time, offset_ms)
+                    .ok_or(ScheduleError::TimeShiftOverflow(offset_ms))?;
+                if new_release_time < self.current_time + self.time_margin {
+                    return Err(ScheduleError::ReleaseTimeInTimeMargin {
+                        current_time: self.current_time,
+                        time_margin: self.time_margin,
+                        release_time: new_release_time,
+                    });
+                }
+                // Can not fail, both lists have the same capacity.
+                shifted_tcs.push((idx, new_release_time)).unwrap();
+            }
+            for (idx, new_release_time) in shifted_tcs.iter() {
+                self.tcs[*idx].0 = *new_release_time;
+            }
+            // Stable insertion sort to restore the ordering by release time.
+            for idx in 1..self.tcs.len() {
+                let mut current = idx;
+                while current > 0 && self.tcs[current - 1].0 > self.tcs[current].0 {
+                    self.tcs.swap(current - 1, current);
+                    current -= 1;
+                }
+            }
+            Ok(shifted_tcs.len() as u64)
+        }
+
+        fn remove_range(&mut self, start: usize, end: usize) {
+            let num_removed = end - start;
+            self.tcs[start..].rotate_left(num_removed);
+            self.tcs.truncate(self.tcs.len() - num_removed);
+        }
+    }
+
+    impl<const N: usize> PusSchedulerProvider for HeaplessPusScheduler<N> {
+        type TimeProvider = spacepackets::time::cds::CdsTime;
+
+        /// This will disable the scheduler and clear the schedule. The stored telecommands are
+        /// deleted from the passed pool. If at last one deletion operation fails, the error will
+        /// be returned but the method will still try to delete all the commands in the schedule.
+        fn reset(&mut self, store: &mut (impl PoolProvider + ?Sized)) -> Result<(), PoolError> {
+            self.enabled = false;
+            let mut deletion_ok = Ok(());
+            for (_, info) in self.tcs.iter() {
+                let res = store.delete(info.addr);
+                if res.is_err() {
+                    deletion_ok = res;
+                }
+            }
+            self.tcs.clear();
+            deletion_ok
+        }
+
+        fn is_enabled(&self) -> bool {
+            self.enabled
+        }
+
+        fn enable(&mut self) {
+            self.enabled = true;
+        }
+
+        fn disable(&mut self) {
+            self.enabled = false;
+        }
+
+        fn scheduler_time(&self) -> Option<UnixTime> {
+            Some(self.current_time)
+        }
+
+        fn insert_unwrapped_and_stored_tc(
+            &mut self,
+            time_stamp: UnixTime,
+            info: TcInfo,
+        ) -> Result<(), ScheduleError> {
+            if time_stamp < self.current_time + self.time_margin {
+                return Err(ScheduleError::ReleaseTimeInTimeMargin {
+                    current_time: self.current_time,
+                    time_margin: self.time_margin,
+                    release_time: time_stamp,
+                });
+            }
+            if let Some(group_id) = info.group_id {
+                return Err(ScheduleError::UnknownGroup(group_id));
+            }
+            let insert_idx = self
+                .tcs
+                .iter()
+                .position(|(release_time, _)| release_time > &time_stamp)
+                .unwrap_or(self.tcs.len());
+            self.tcs
+                .push((time_stamp, info))
+                .map_err(|_| ScheduleError::ScheduleFull)?;
+            self.tcs[insert_idx..].rotate_right(1);
+            Ok(())
+        }
+
+        fn delete_by_request_id_and_from_pool(
+            &mut self,
+            req_id: &RequestId,
+            pool: &mut (impl PoolProvider + ?Sized),
+        ) -> Result<bool, PoolError> {
+            match self
+                .tcs
+                .iter()
+                .position(|(_, info)| &info.request_id == req_id)
+            {
+                Some(idx) => {
+                    let addr = self.tcs[idx].1.addr;
+                    self.remove_range(idx, idx + 1);
+                    pool.delete(addr)?;
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+
+        fn delete_by_time_window(
+            &mut self,
+            time_window: TimeWindow<Self::TimeProvider>,
+            pool: &mut (impl PoolProvider + ?Sized),
+        ) -> Result<u64, (u64, PoolError)> {
+            self.delete_filtered(|release_time, _| time_window.contains(release_time), pool)
+        }
+
+        fn time_shift_all(&mut self, offset_ms: i64) -> Result<u64, ScheduleError> {
+            self.time_shift_filtered(offset_ms, |_, _| true)
+        }
+
+        fn time_shift_by_request_id(
+            &mut self,
+            req_id: &RequestId,
+            offset_ms: i64,
+        ) -> Result<bool, ScheduleError> {
+            let mut found = false;
+            let num_shifted = self.time_shift_filtered(offset_ms, |_, info| {
+                if found || &info.request_id != req_id {
+                    return false;
+                }
+                found = true;
+                true
+            })?;
+            Ok(num_shifted > 0)
+        }
+
+        fn time_shift_by_time_window(
+            &mut self,
+            time_window: TimeWindow<Self::TimeProvider>,
+            offset_ms: i64,
+        ) -> Result<u64, ScheduleError> {
+            self.time_shift_filtered(offset_ms, |release_time, _| {
+                time_window.contains(release_time)
+            })
+        }
+
+        fn for_each_scheduled_tc(&self, mut f: impl FnMut(&UnixTime, &TcInfo)) {
+            for (release_time, info) in self.tcs.iter() {
+                f(release_time, info);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::{
+        PoolAddr, PoolError, PoolProvider, StaticMemoryPool, StaticPoolAddr, StaticPoolConfig,
+    };
+    use crate::pus::test_util::TEST_COMPONENT_ID_0;
+    use crate::tmtc::PacketAsVec;
+    use alloc::collections::btree_map::Range;
+    use spacepackets::ecss::scheduling;
+    use spacepackets::ecss::tc::{PusTcCreator, PusTcReader, PusTcSecondaryHeader};
+    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::time::cuc::FractionalResolution;
+    use spacepackets::time::{cds, TimeWriter, UnixTime};
+    use spacepackets::{PacketId, PacketSequenceCtrl, PacketType, SequenceFlags, SpHeader};
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use std::vec::Vec;
+    #[allow(unused_imports)]
+    use std::{println, vec};
+
+    fn pus_tc_base(timestamp: UnixTime, buf: &mut [u8]) -> (SpHeader, usize) {
+        let cds_time =
+            cds::CdsTime::from_unix_time_with_u16_days(&timestamp, cds::SubmillisPrecision::Absent)
+                .unwrap();
+        let len_time_stamp = cds_time.write_to_bytes(buf).unwrap();
+        let len_packet = base_ping_tc_simple_ctor(0, &[])
+            .write_to_bytes(&mut buf[len_time_stamp..])
+            .unwrap();
+        (
+            SpHeader::new_for_unseg_tc(0x02, 0x34, len_packet as u16),
+            len_packet + len_time_stamp,
+        )
+    }
+
+    fn scheduled_tc(timestamp: UnixTime, buf: &mut [u8]) -> PusTcCreator {
+        let (sph, len_app_data) = pus_tc_base(timestamp, buf);
+        PusTcCreator::new_simple(sph, 11, 4, &buf[..len_app_data], true)
+    }
+
+    fn wrong_tc_service(timestamp: UnixTime, buf: &mut [u8]) -> PusTcCreator {
+        let (sph, len_app_data) = pus_tc_base(timestamp, buf);
+        PusTcCreator::new_simple(sph, 12, 4, &buf[..len_app_data], true)
+    }
+
+    fn wrong_tc_subservice(timestamp: UnixTime, buf: &mut [u8]) -> PusTcCreator {
+        let (sph, len_app_data) = pus_tc_base(timestamp, buf);
+        PusTcCreator::new_simple(sph, 11, 5, &buf[..len_app_data], true)
+    }
+
+    fn double_wrapped_time_tagged_tc(timestamp: UnixTime, buf: &mut [u8]) -> PusTcCreator {
+        let cds_time =
+            cds::CdsTime::from_unix_time_with_u16_days(&timestamp, cds::SubmillisPrecision::Absent)
+                .unwrap();
+        let len_time_stamp = cds_time.write_to_bytes(buf).unwrap();
+        let sph = SpHeader::new_for_unseg_tc(0x02, 0x34, 0);
+        // app data should not matter, double wrapped time-tagged commands should be rejected right
+        // away
+        let inner_time_tagged_tc = PusTcCreator::new_simple(sph, 11, 4, &[], true);
+        let packet_len = inner_time_tagged_tc
+            .write_to_bytes(&mut buf[len_time_stamp..])
+            .expect("writing inner time tagged tc failed");
+        PusTcCreator::new_simple(sph, 11, 4, &buf[..len_time_stamp + packet_len], true)
+    }
+
+    fn invalid_time_tagged_cmd() -> PusTcCreator<'static> {
+        let sph = SpHeader::new_for_unseg_tc(0x02, 0x34, 1);
+        PusTcCreator::new_simple(sph, 11, 4, &[], true)
+    }
+
+    fn base_ping_tc_simple_ctor(seq_count: u16, app_data: &'static [u8]) -> PusTcCreator<'static> {
+        let sph = SpHeader::new_for_unseg_tc(0x02, seq_count, 0);
+        PusTcCreator::new_simple(sph, 17, 1, app_data, true)
+    }
+
+    fn ping_tc_to_store(
+        pool: &mut StaticMemoryPool,
+        buf: &mut [u8],
+        seq_count: u16,
+        app_data: &'static [u8],
+    ) -> TcInfo {
+        let ping_tc = base_ping_tc_simple_ctor(seq_count, app_data);
+        let ping_size = ping_tc.write_to_bytes(buf).expect("writing ping TC failed");
+        let first_addr = pool.add(&buf[0..ping_size]).unwrap();
+        TcInfo::new(first_addr, RequestId::from_tc(&ping_tc))
+    }
+
+    #[test]
+    fn test_insertion_checks() {
+        let ping_tc = base_ping_tc_simple_ctor(0, &[]);
+        let current_time = UnixTime::new_only_secs(1000);
+        let unchecked = InsertionChecks::default();
+        assert!(unchecked
+            .check(Some(&current_time), &UnixTime::new_only_secs(0), &ping_tc)
+            .is_ok());
+
+        let checks = InsertionChecks::default()
+            .with_allowed_apids(&[0x02, 0x03])
+            .with_allowed_services(&[17])
+            .with_past_margin(Duration::from_secs(10))
+            .with_horizon(Duration::from_secs(100));
+        assert!(checks
+            .check(
+                Some(&current_time),
+                &UnixTime::new_only_secs(1050),
+                &ping_tc
+            )
+            .is_ok());
+        assert_eq!(
+            checks.check(
+                Some(&current_time),
+                &UnixTime::new_only_secs(1050),
+                &PusTcCreator::new_simple(SpHeader::new_for_unseg_tc(0x05, 0, 0), 17, 1, &[], true)
+            ),
+            Err(ScheduleError::ApidNotAllowed(0x05))
+        );
+        assert_eq!(
+            checks.check(
+                Some(&current_time),
+                &UnixTime::new_only_secs(1050),
+                &PusTcCreator::new_simple(SpHeader::new_for_unseg_tc(0x02, 0, 0), 8, 1, &[], true)
+            ),
+            Err(ScheduleError::ServiceNotAllowed(8))
+        );
+        // Within the past margin.
+        assert!(checks
+            .check(Some(&current_time), &UnixTime::new_only_secs(995), &ping_tc)
+            .is_ok());
+        assert_eq!(
+            checks.check(Some(&current_time), &UnixTime::new_only_secs(989), &ping_tc),
+            Err(ScheduleError::ReleaseTimeInPast {
+                current_time,
+                past_margin: Duration::from_secs(10),
+                release_time: UnixTime::new_only_secs(989)
+            })
+        );
+        assert_eq!(
+            checks.check(
+                Some(&current_time),
+                &UnixTime::new_only_secs(1101),
+                &ping_tc
+            ),
+            Err(ScheduleError::ReleaseTimeBeyondHorizon {
+                current_time,
+                horizon: Duration::from_secs(100),
+                release_time: UnixTime::new_only_secs(1101)
+            })
+        );
+        // Without the current time, only the packet checks are performed.
+        assert!(checks
+            .check(None, &UnixTime::new_only_secs(0), &ping_tc)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_enable_api() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        assert!(scheduler.is_enabled());
+        scheduler.disable();
+        assert!(!scheduler.is_enabled());
+        scheduler.enable();
+        assert!(scheduler.is_enabled());
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+
+        scheduler
+            .insert_unwrapped_and_stored_tc(
+                UnixTime::new_only_secs(100),
+                TcInfo::new(tc_info_0.addr, tc_info_0.request_id),
+            )
+            .unwrap();
+
+        let app_data = &[0, 1, 2];
+        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, app_data);
+        scheduler
+            .insert_unwrapped_and_stored_tc(
+                UnixTime::new_only_secs(200),
+                TcInfo::new(tc_info_1.addr, tc_info_1.request_id),
+            )
+            .unwrap();
+
+        let app_data = &[0, 1, 2];
+        let tc_info_2 = ping_tc_to_store(&mut pool, &mut buf, 2, app_data);
+        scheduler
+            .insert_unwrapped_and_stored_tc(
+                UnixTime::new_only_secs(300),
+                TcInfo::new(tc_info_2.addr(), tc_info_2.request_id()),
+            )
+            .unwrap();
+
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
+        assert!(scheduler.is_enabled());
+        scheduler.reset(&mut pool).expect("deletion of TCs failed");
+        assert!(!scheduler.is_enabled());
+        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
+        assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
+        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
+        assert!(!pool.has_element_at(&tc_info_2.addr()).unwrap());
+    }
+
+    #[test]
+    fn insert_multi_with_same_time() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
+        scheduler
+            .insert_unwrapped_and_stored_tc(
+                UnixTime::new_only_secs(100),
+                TcInfo::new(
+                    PoolAddr::from(StaticPoolAddr {
+                        pool_idx: 0,
+                        packet_idx: 1,
+                    }),
+                    RequestId {
+                        seq_count: 1,
+                        apid: 0,
+                        source_id: 0,
+                    },
+                ),
+            )
+            .unwrap();
+
+        scheduler
+            .insert_unwrapped_and_stored_tc(
+                UnixTime::new_only_secs(100),
+                TcInfo::new(
+                    PoolAddr::from(StaticPoolAddr {
+                        pool_idx: 0,
+                        packet_idx: 2,
+                    }),
+                    RequestId {
+                        seq_count: 2,
+                        apid: 1,
+                        source_id: 5,
+                    },
+                ),
+            )
+            .unwrap();
+
+        scheduler
+            .insert_unwrapped_and_stored_tc(
+                UnixTime::new_only_secs(300),
+                TcInfo::new(
+                    StaticPoolAddr {
+                        pool_idx: 0,
+                        packet_idx: 2,
+                    }
+                    .into(),
+                    RequestId {
+                        source_id: 10,
+                        seq_count: 20,
+                        apid: 23,
+                    },
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
+    }
+
+    #[test]
+    fn test_time_update() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let time = UnixTime::new(1, 2_000_000);
+        scheduler.update_time(time);
+        assert_eq!(scheduler.current_time(), &time);
+    }
+
+    fn common_check(
+        enabled: bool,
+        store_addr: &PoolAddr,
+        expected_store_addrs: Vec<PoolAddr>,
+        counter: &mut usize,
+    ) {
+        assert!(enabled);
+        assert!(expected_store_addrs.contains(store_addr));
+        *counter += 1;
+    }
+    fn common_check_disabled(
+        enabled: bool,
+        store_addr: &PoolAddr,
+        expected_store_addrs: Vec<PoolAddr>,
+        counter: &mut usize,
+    ) {
+        assert!(!enabled);
+        assert!(expected_store_addrs.contains(store_addr));
+        *counter += 1;
+    }
+
+    #[test]
+    fn test_request_id() {
+        let src_id_to_set = 12;
+        let apid_to_set = 0x22;
+        let seq_count = 105;
+        let sp_header = SpHeader::new_for_unseg_tc(apid_to_set, 105, 0);
+        let mut sec_header = PusTcSecondaryHeader::new_simple(17, 1);
+        sec_header.source_id = src_id_to_set;
+        let ping_tc = PusTcCreator::new_no_app_data(sp_header, sec_header, true);
+        let req_id = RequestId::from_tc(&ping_tc);
+        assert_eq!(req_id.source_id(), src_id_to_set);
+        assert_eq!(req_id.apid(), apid_to_set);
+        assert_eq!(req_id.seq_count(), seq_count);
+        assert_eq!(
+            req_id.as_u64(),
+            ((src_id_to_set as u64) << 32) | (apid_to_set as u64) << 16 | seq_count as u64
+        );
+    }
+    #[test]
+    fn test_release_telecommands() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
+            .expect("insertion failed");
+
+        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(200), tc_info_1)
+            .expect("insertion failed");
+
+        let mut i = 0;
+        let mut test_closure_1 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
+            common_check(boolvar, &tc_info.addr, vec![tc_info_0.addr()], &mut i);
+            true
+        };
+
+        // test 1: too early, no tcs
+        scheduler.update_time(UnixTime::new_only_secs(99));
+
+        let mut tc_buf: [u8; 128] = [0; 128];
+        scheduler
+            .release_telecommands_with_buffer(&mut test_closure_1, &mut pool, &mut tc_buf)
+            .expect("deletion failed");
+
+        // test 2: exact time stamp of tc, releases 1 tc
+        scheduler.update_time(UnixTime::new_only_secs(100));
+
+        let mut released = scheduler
+            .release_telecommands(&mut test_closure_1, &mut pool)
+            .expect("deletion failed");
+        assert_eq!(released, 1);
+        // TC is deleted.
+        assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
+
+        // test 3, late timestamp, release 1 overdue tc
+        let mut test_closure_2 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
+            common_check(boolvar, &tc_info.addr, vec![tc_info_1.addr()], &mut i);
+            true
+        };
+
+        scheduler.update_time(UnixTime::new_only_secs(206));
+
+        released = scheduler
+            .release_telecommands_with_buffer(&mut test_closure_2, &mut pool, &mut tc_buf)
+            .expect("deletion failed");
+        assert_eq!(released, 1);
+        // TC is deleted.
+        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
+
+        //test 4: no tcs left
+        scheduler
+            .release_telecommands(&mut test_closure_2, &mut pool)
+            .expect("deletion failed");
+
+        // check that 2 total tcs have been released
+        assert_eq!(i, 2);
+    }
+
+    #[test]
+    fn release_multi_with_same_time() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
+            .expect("insertion failed");
+
+        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_1)
+            .expect("insertion failed");
+
+        let mut i = 0;
+        let mut test_closure = |boolvar: bool, store_addr: &TcInfo, _tc: &[u8]| {
+            common_check(
+                boolvar,
+                &store_addr.addr,
+                vec![tc_info_0.addr(), tc_info_1.addr()],
+                &mut i,
+            );
+            true
+        };
+
+        // test 1: too early, no tcs
+        scheduler.update_time(UnixTime::new_only_secs(99));
+        let mut tc_buf: [u8; 128] = [0; 128];
+
+        let mut released = scheduler
+            .release_telecommands_with_buffer(&mut test_closure, &mut pool, &mut tc_buf)
+            .expect("deletion failed");
+        assert_eq!(released, 0);
+
+        // test 2: exact time stamp of tc, releases 2 tc
+        scheduler.update_time(UnixTime::new_only_secs(100));
+
+        released = scheduler
+            .release_telecommands(&mut test_closure, &mut pool)
+            .expect("deletion failed");
+        assert_eq!(released, 2);
+        assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
+        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
+
+        //test 3: no tcs left
+        released = scheduler
+            .release_telecommands(&mut test_closure, &mut pool)
+            .expect("deletion failed");
+        assert_eq!(released, 0);
+
+        // check that 2 total tcs have been released
+        assert_eq!(i, 2);
+    }
+
+    #[test]
+    fn release_with_scheduler_disabled() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
+        scheduler.disable();
+
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
+            .expect("insertion failed");
+
+        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(200), tc_info_1)
+            .expect("insertion failed");
+
+        let mut i = 0;
+        let mut test_closure_1 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
+            common_check_disabled(boolvar, &tc_info.addr, vec![tc_info_0.addr()], &mut i);
+            true
+        };
+
+        let mut tc_buf: [u8; 128] = [0; 128];
+
+        // test 1: too early, no tcs
+        scheduler.update_time(UnixTime::new_only_secs(99));
+
+        scheduler
+            .release_telecommands_with_buffer(&mut test_closure_1, &mut pool, &mut tc_buf)
+            .expect("deletion failed");
+
+        // test 2: exact time stamp of tc, releases 1 tc
+        scheduler.update_time(UnixTime::new_only_secs(100));
+
+        let mut released = scheduler
+            .release_telecommands(&mut test_closure_1, &mut pool)
+            .expect("deletion failed");
+        assert_eq!(released, 1);
+        assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
+
+        // test 3, late timestamp, release 1 overdue tc
+        let mut test_closure_2 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
+            common_check_disabled(boolvar, &tc_info.addr, vec![tc_info_1.addr()], &mut i);
+            true
+        };
+
+        scheduler.update_time(UnixTime::new_only_secs(206));
+
+        released = scheduler
+            .release_telecommands(&mut test_closure_2, &mut pool)
+            .expect("deletion failed");
+        assert_eq!(released, 1);
+        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
+
+        //test 4: no tcs left
+        scheduler
+            .release_telecommands(&mut test_closure_2, &mut pool)
+            .expect("deletion failed");
+
+        // check that 2 total tcs have been released
+        assert_eq!(i, 2);
+    }
+
+    #[test]
+    fn insert_unwrapped_tc() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+
+        let info = scheduler
+            .insert_unwrapped_tc(
                 UnixTime::new_only_secs(100),
-                TcInfo::new(
-                    PoolAddr::from(StaticPoolAddr {
-                        pool_idx: 0,
-                        packet_idx: 2,
-                    }),
-                    RequestId {
-                        seq_count: 2,
-                        apid: 1,
-                        source_id: 5,
-                    },
-                ),
+                &buf[..pool.len_of_data(&tc_info_0.addr()).unwrap()],
+                &mut pool,
             )
             .unwrap();
 
+        assert!(pool.has_element_at(&tc_info_0.addr()).unwrap());
+
+        let mut read_buf: [u8; 64] = [0; 64];
+        pool.read(&tc_info_0.addr(), &mut read_buf).unwrap();
+        let check_tc = PusTcReader::new(&read_buf).expect("incorrect Pus tc raw data");
+        assert_eq!(check_tc.0, base_ping_tc_simple_ctor(0, &[]));
+
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+
+        scheduler.update_time(UnixTime::new_only_secs(101));
+
+        let mut addr_vec = Vec::new();
+
+        let mut i = 0;
+        let mut test_closure = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
+            common_check(boolvar, &tc_info.addr, vec![info.addr], &mut i);
+            // check that tc remains unchanged
+            addr_vec.push(tc_info.addr);
+            false
+        };
+
         scheduler
-            .insert_unwrapped_and_stored_tc(
-                UnixTime::new_only_secs(300),
-                TcInfo::new(
-                    StaticPoolAddr {
-                        pool_idx: 0,
-                        packet_idx: 2,
-                    }
-                    .into(),
-                    RequestId {
-                        source_id: 10,
-                        seq_count: 20,
-                        apid: 23,
-                    },
-                ),
-            )
+            .release_telecommands(&mut test_closure, &mut pool)
+            .unwrap();
+
+        let read_len = pool.read(&addr_vec[0], &mut read_buf).unwrap();
+        let check_tc = PusTcReader::new(&read_buf).expect("incorrect Pus tc raw data");
+        assert_eq!(read_len, check_tc.1);
+        assert_eq!(check_tc.0, base_ping_tc_simple_ctor(0, &[]));
+    }
+
+    #[test]
+    fn insert_wrapped_tc() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+
+        let mut buf: [u8; 32] = [0; 32];
+        let tc = scheduled_tc(UnixTime::new_only_secs(100), &mut buf);
+
+        let info = match scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool) {
+            Ok(addr) => addr,
+            Err(e) => {
+                panic!("unexpected error {e}");
+            }
+        };
+
+        assert!(pool.has_element_at(&info.addr).unwrap());
+
+        let read_len = pool.read(&info.addr, &mut buf).unwrap();
+        let check_tc = PusTcReader::new(&buf).expect("incorrect Pus tc raw data");
+        assert_eq!(read_len, check_tc.1);
+        assert_eq!(check_tc.0, base_ping_tc_simple_ctor(0, &[]));
+
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+
+        scheduler.update_time(UnixTime::new_only_secs(101));
+
+        let mut addr_vec = Vec::new();
+
+        let mut i = 0;
+        let mut test_closure = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
+            common_check(boolvar, &tc_info.addr, vec![info.addr], &mut i);
+            // check that tc remains unchanged
+            addr_vec.push(tc_info.addr);
+            false
+        };
+
+        let mut tc_buf: [u8; 64] = [0; 64];
+
+        scheduler
+            .release_telecommands_with_buffer(&mut test_closure, &mut pool, &mut tc_buf)
+            .unwrap();
+
+        let read_len = pool.read(&addr_vec[0], &mut buf).unwrap();
+        let check_tc = PusTcReader::new(&buf).expect("incorrect PUS tc raw data");
+        assert_eq!(read_len, check_tc.1);
+        assert_eq!(check_tc.0, base_ping_tc_simple_ctor(0, &[]));
+    }
+
+    #[test]
+    fn insert_wrong_service() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+
+        let mut buf: [u8; 32] = [0; 32];
+        let tc = wrong_tc_service(UnixTime::new_only_secs(100), &mut buf);
+
+        let err = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
+        assert!(err.is_err());
+        let err = err.unwrap_err();
+        match err {
+            ScheduleError::WrongService(wrong_service) => {
+                assert_eq!(wrong_service, 12);
+            }
+            _ => {
+                panic!("unexpected error")
+            }
+        }
+    }
+
+    #[test]
+    fn insert_wrong_subservice() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+
+        let mut buf: [u8; 32] = [0; 32];
+        let tc = wrong_tc_subservice(UnixTime::new_only_secs(100), &mut buf);
+
+        let err = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
+        assert!(err.is_err());
+        let err = err.unwrap_err();
+        match err {
+            ScheduleError::WrongSubservice(wrong_subsrv) => {
+                assert_eq!(wrong_subsrv, 5);
+            }
+            _ => {
+                panic!("unexpected error")
+            }
+        }
+    }
+
+    #[test]
+    fn insert_wrapped_tc_faulty_app_data() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let tc = invalid_time_tagged_cmd();
+        let insert_res = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
+        assert!(insert_res.is_err());
+        let err = insert_res.unwrap_err();
+        match err {
+            ScheduleError::TcDataEmpty => {}
+            _ => panic!("unexpected error {err}"),
+        }
+    }
+
+    #[test]
+    fn insert_wrapped_tc_with_cuc_release_time() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let format = TimestampFormat::Cuc {
+            fine_resolution: FractionalResolution::FourMs,
+            leap_seconds: 37,
+        };
+        let release_time = UnixTime::new(100, 500_000_000);
+        let mut buf: [u8; 32] = [0; 32];
+        let len_time_stamp = format.write_stamp(&release_time, &mut buf).unwrap();
+        assert_eq!(len_time_stamp, format.stamp_len());
+        let len_packet = base_ping_tc_simple_ctor(0, &[])
+            .write_to_bytes(&mut buf[len_time_stamp..])
             .unwrap();
+        let tc = PusTcCreator::new_simple(
+            SpHeader::new_for_unseg_tc(0x02, 0x34, 0),
+            11,
+            4,
+            &buf[..len_time_stamp + len_packet],
+            true,
+        );
 
-        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
+        let info = scheduler
+            .insert_wrapped_tc_with_format(&tc, format, &mut pool)
+            .unwrap();
+        assert!(pool.has_element_at(&info.addr).unwrap());
+        let mut release_times = Vec::new();
+        scheduler.for_each_scheduled_tc(|time, _| release_times.push(*time));
+        assert_eq!(release_times, vec![release_time]);
+
+        // A CDS release time can not be parsed as a CUC timestamp.
+        let tc = scheduled_tc(UnixTime::new_only_secs(100), &mut buf);
+        assert!(matches!(
+            scheduler.insert_wrapped_tc_with_format(&tc, format, &mut pool),
+            Err(ScheduleError::TimestampError(_))
+        ));
+        let info = scheduler
+            .insert_wrapped_tc_with_format(&tc, TimestampFormat::CdsShort, &mut pool)
+            .unwrap();
+        assert!(pool.has_element_at(&info.addr).unwrap());
+        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
     }
 
     #[test]
-    fn test_time_update() {
+    fn insert_doubly_wrapped_time_tagged_cmd() {
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        let time = UnixTime::new(1, 2_000_000);
-        scheduler.update_time(time);
-        assert_eq!(scheduler.current_time(), &time);
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut buf: [u8; 64] = [0; 64];
+        let tc = double_wrapped_time_tagged_tc(UnixTime::new_only_secs(50), &mut buf);
+        let insert_res = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
+        assert!(insert_res.is_err());
+        let err = insert_res.unwrap_err();
+        match err {
+            ScheduleError::NestedScheduledTc => {}
+            _ => panic!("unexpected error {err}"),
+        }
     }
 
-    fn common_check(
-        enabled: bool,
-        store_addr: &PoolAddr,
-        expected_store_addrs: Vec<PoolAddr>,
-        counter: &mut usize,
-    ) {
-        assert!(enabled);
-        assert!(expected_store_addrs.contains(store_addr));
-        *counter += 1;
+    #[test]
+    fn test_ctor_from_current() {
+        let scheduler = PusScheduler::new_with_current_init_time(Duration::from_secs(5))
+            .expect("creation from current time failed");
+        let current_time = scheduler.current_time;
+        assert!(current_time.as_secs() > 0);
     }
-    fn common_check_disabled(
-        enabled: bool,
-        store_addr: &PoolAddr,
-        expected_store_addrs: Vec<PoolAddr>,
-        counter: &mut usize,
-    ) {
-        assert!(!enabled);
-        assert!(expected_store_addrs.contains(store_addr));
-        *counter += 1;
+
+    #[test]
+    fn test_update_from_current() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        assert_eq!(scheduler.current_time.as_secs(), 0);
+        scheduler
+            .update_time_from_now()
+            .expect("updating scheduler time from now failed");
+        assert!(scheduler.current_time.as_secs() > 0);
     }
 
     #[test]
-    fn test_request_id() {
-        let src_id_to_set = 12;
-        let apid_to_set = 0x22;
-        let seq_count = 105;
-        let sp_header = SpHeader::new_for_unseg_tc(apid_to_set, 105, 0);
-        let mut sec_header = PusTcSecondaryHeader::new_simple(17, 1);
-        sec_header.source_id = src_id_to_set;
-        let ping_tc = PusTcCreator::new_no_app_data(sp_header, sec_header, true);
-        let req_id = RequestId::from_tc(&ping_tc);
-        assert_eq!(req_id.source_id(), src_id_to_set);
-        assert_eq!(req_id.apid(), apid_to_set);
-        assert_eq!(req_id.seq_count(), seq_count);
-        assert_eq!(
-            req_id.as_u64(),
-            ((src_id_to_set as u64) << 32) | (apid_to_set as u64) << 16 | seq_count as u64
-        );
+    fn test_update_from_time_source() {
+        let time_source = crate::time::FixedTimeSource::new(UnixTime::new_only_secs(100));
+        let mut scheduler =
+            PusScheduler::new_with_time_source(&time_source, Duration::from_secs(5)).unwrap();
+        assert_eq!(scheduler.current_time().as_secs(), 100);
+        let time_source = crate::time::FixedTimeSource::new(UnixTime::new_only_secs(200));
+        scheduler.update_time_from_source(&time_source).unwrap();
+        assert_eq!(scheduler.current_time().as_secs(), 200);
     }
+
     #[test]
-    fn test_release_telecommands() {
+    fn release_time_within_time_margin() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
 
         let mut buf: [u8; 32] = [0; 32];
-        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
 
-        scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
-            .expect("insertion failed");
+        let tc = scheduled_tc(UnixTime::new_only_secs(4), &mut buf);
+        let insert_res = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
+        assert!(insert_res.is_err());
+        let err = insert_res.unwrap_err();
+        match err {
+            ScheduleError::ReleaseTimeInTimeMargin {
+                current_time,
+                time_margin,
+                release_time,
+            } => {
+                assert_eq!(current_time, UnixTime::new_only_secs(0));
+                assert_eq!(time_margin, Duration::from_secs(5));
+                assert_eq!(release_time, UnixTime::new_only_secs(4));
+            }
+            _ => panic!("unexepcted error {err}"),
+        }
+    }
 
-        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+    #[test]
+    fn test_store_error_propagation_release() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
         scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(200), tc_info_1)
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
             .expect("insertion failed");
 
         let mut i = 0;
-        let mut test_closure_1 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
-            common_check(boolvar, &tc_info.addr, vec![tc_info_0.addr()], &mut i);
+        let test_closure_1 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
+            common_check_disabled(boolvar, &tc_info.addr, vec![tc_info_0.addr()], &mut i);
             true
         };
 
-        // test 1: too early, no tcs
-        scheduler.update_time(UnixTime::new_only_secs(99));
-
-        let mut tc_buf: [u8; 128] = [0; 128];
-        scheduler
-            .release_telecommands_with_buffer(&mut test_closure_1, &mut pool, &mut tc_buf)
-            .expect("deletion failed");
-
-        // test 2: exact time stamp of tc, releases 1 tc
+        // premature deletion
+        pool.delete(tc_info_0.addr()).expect("deletion failed");
+        // scheduler will only auto-delete if it is disabled.
+        scheduler.disable();
         scheduler.update_time(UnixTime::new_only_secs(100));
-
-        let mut released = scheduler
-            .release_telecommands(&mut test_closure_1, &mut pool)
-            .expect("deletion failed");
-        assert_eq!(released, 1);
-        // TC is deleted.
-        assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
-
-        // test 3, late timestamp, release 1 overdue tc
-        let mut test_closure_2 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
-            common_check(boolvar, &tc_info.addr, vec![tc_info_1.addr()], &mut i);
-            true
-        };
-
-        scheduler.update_time(UnixTime::new_only_secs(206));
-
-        released = scheduler
-            .release_telecommands_with_buffer(&mut test_closure_2, &mut pool, &mut tc_buf)
-            .expect("deletion failed");
-        assert_eq!(released, 1);
-        // TC is deleted.
-        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
-
-        //test 4: no tcs left
-        scheduler
-            .release_telecommands(&mut test_closure_2, &mut pool)
-            .expect("deletion failed");
-
-        // check that 2 total tcs have been released
-        assert_eq!(i, 2);
+        let release_res = scheduler.release_telecommands(test_closure_1, &mut pool);
+        assert!(release_res.is_err());
+        let err = release_res.unwrap_err();
+        // TC could not even be read..
+        assert_eq!(err.0, 0);
+        match err.1 {
+            PoolError::DataDoesNotExist(addr) => {
+                assert_eq!(tc_info_0.addr(), addr);
+            }
+            _ => panic!("unexpected error {}", err.1),
+        }
     }
 
     #[test]
-    fn release_multi_with_same_time() {
+    fn test_store_error_propagation_reset() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-
         let mut buf: [u8; 32] = [0; 32];
         let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
-
         scheduler
             .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
             .expect("insertion failed");
 
-        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
-        scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_1)
-            .expect("insertion failed");
-
-        let mut i = 0;
-        let mut test_closure = |boolvar: bool, store_addr: &TcInfo, _tc: &[u8]| {
-            common_check(
-                boolvar,
-                &store_addr.addr,
-                vec![tc_info_0.addr(), tc_info_1.addr()],
-                &mut i,
-            );
-            true
-        };
-
-        // test 1: too early, no tcs
-        scheduler.update_time(UnixTime::new_only_secs(99));
-        let mut tc_buf: [u8; 128] = [0; 128];
-
-        let mut released = scheduler
-            .release_telecommands_with_buffer(&mut test_closure, &mut pool, &mut tc_buf)
-            .expect("deletion failed");
-        assert_eq!(released, 0);
-
-        // test 2: exact time stamp of tc, releases 2 tc
-        scheduler.update_time(UnixTime::new_only_secs(100));
-
-        released = scheduler
-            .release_telecommands(&mut test_closure, &mut pool)
-            .expect("deletion failed");
-        assert_eq!(released, 2);
-        assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
-        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
-
-        //test 3: no tcs left
-        released = scheduler
-            .release_telecommands(&mut test_closure, &mut pool)
-            .expect("deletion failed");
-        assert_eq!(released, 0);
+        // premature deletion
+        pool.delete(tc_info_0.addr()).expect("deletion failed");
+        let reset_res = scheduler.reset(&mut pool);
+        assert!(reset_res.is_err());
+        let err = reset_res.unwrap_err();
+        match err {
+            PoolError::DataDoesNotExist(addr) => {
+                assert_eq!(addr, tc_info_0.addr());
+            }
+            _ => panic!("unexpected error {err}"),
+        }
+    }
 
-        // check that 2 total tcs have been released
-        assert_eq!(i, 2);
+    #[test]
+    fn test_delete_by_req_id_simple_retrieve_addr() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
+            .expect("inserting tc failed");
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+        let addr = scheduler
+            .delete_by_request_id(&tc_info_0.request_id())
+            .unwrap();
+        assert!(pool.has_element_at(&tc_info_0.addr()).unwrap());
+        assert_eq!(tc_info_0.addr(), addr);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
     }
 
     #[test]
-    fn release_with_scheduler_disabled() {
+    fn test_delete_by_req_id_simple_delete_all() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-
-        scheduler.disable();
-
         let mut buf: [u8; 32] = [0; 32];
         let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
-
         scheduler
             .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
-            .expect("insertion failed");
-
-        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
-        scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(200), tc_info_1)
-            .expect("insertion failed");
-
-        let mut i = 0;
-        let mut test_closure_1 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
-            common_check_disabled(boolvar, &tc_info.addr, vec![tc_info_0.addr()], &mut i);
-            true
-        };
-
-        let mut tc_buf: [u8; 128] = [0; 128];
-
-        // test 1: too early, no tcs
-        scheduler.update_time(UnixTime::new_only_secs(99));
-
-        scheduler
-            .release_telecommands_with_buffer(&mut test_closure_1, &mut pool, &mut tc_buf)
-            .expect("deletion failed");
-
-        // test 2: exact time stamp of tc, releases 1 tc
-        scheduler.update_time(UnixTime::new_only_secs(100));
-
-        let mut released = scheduler
-            .release_telecommands(&mut test_closure_1, &mut pool)
-            .expect("deletion failed");
-        assert_eq!(released, 1);
+            .expect("inserting tc failed");
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+        let del_res =
+            scheduler.delete_by_request_id_and_from_pool(&tc_info_0.request_id(), &mut pool);
+        assert!(del_res.is_ok());
+        assert!(del_res.unwrap());
         assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
-
-        // test 3, late timestamp, release 1 overdue tc
-        let mut test_closure_2 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
-            common_check_disabled(boolvar, &tc_info.addr, vec![tc_info_1.addr()], &mut i);
-            true
-        };
-
-        scheduler.update_time(UnixTime::new_only_secs(206));
-
-        released = scheduler
-            .release_telecommands(&mut test_closure_2, &mut pool)
-            .expect("deletion failed");
-        assert_eq!(released, 1);
-        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
-
-        //test 4: no tcs left
-        scheduler
-            .release_telecommands(&mut test_closure_2, &mut pool)
-            .expect("deletion failed");
-
-        // check that 2 total tcs have been released
-        assert_eq!(i, 2);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
     }
 
     #[test]
-    fn insert_unwrapped_tc() {
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-
+    fn test_delete_by_req_id_complex() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
         let mut buf: [u8; 32] = [0; 32];
         let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
+            .expect("inserting tc failed");
+        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_1)
+            .expect("inserting tc failed");
+        let tc_info_2 = ping_tc_to_store(&mut pool, &mut buf, 2, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_2)
+            .expect("inserting tc failed");
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
 
-        let info = scheduler
-            .insert_unwrapped_tc(
-                UnixTime::new_only_secs(100),
-                &buf[..pool.len_of_data(&tc_info_0.addr()).unwrap()],
-                &mut pool,
-            )
-            .unwrap();
-
+        // Delete first packet
+        let addr_0 = scheduler.delete_by_request_id(&tc_info_0.request_id());
+        assert!(addr_0.is_some());
+        assert_eq!(addr_0.unwrap(), tc_info_0.addr());
         assert!(pool.has_element_at(&tc_info_0.addr()).unwrap());
+        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
 
-        let mut read_buf: [u8; 64] = [0; 64];
-        pool.read(&tc_info_0.addr(), &mut read_buf).unwrap();
-        let check_tc = PusTcReader::new(&read_buf).expect("incorrect Pus tc raw data");
-        assert_eq!(check_tc.0, base_ping_tc_simple_ctor(0, &[]));
-
+        // Delete next packet
+        let del_res =
+            scheduler.delete_by_request_id_and_from_pool(&tc_info_2.request_id(), &mut pool);
+        assert!(del_res.is_ok());
+        assert!(del_res.unwrap());
+        assert!(!pool.has_element_at(&tc_info_2.addr()).unwrap());
         assert_eq!(scheduler.num_scheduled_telecommands(), 1);
 
-        scheduler.update_time(UnixTime::new_only_secs(101));
-
-        let mut addr_vec = Vec::new();
-
-        let mut i = 0;
-        let mut test_closure = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
-            common_check(boolvar, &tc_info.addr, vec![info.addr], &mut i);
-            // check that tc remains unchanged
-            addr_vec.push(tc_info.addr);
-            false
-        };
-
-        scheduler
-            .release_telecommands(&mut test_closure, &mut pool)
-            .unwrap();
-
-        let read_len = pool.read(&addr_vec[0], &mut read_buf).unwrap();
-        let check_tc = PusTcReader::new(&read_buf).expect("incorrect Pus tc raw data");
-        assert_eq!(read_len, check_tc.1);
-        assert_eq!(check_tc.0, base_ping_tc_simple_ctor(0, &[]));
+        // Delete last packet
+        let addr_1 =
+            scheduler.delete_by_request_id_and_from_pool(&tc_info_1.request_id(), &mut pool);
+        assert!(addr_1.is_ok());
+        assert!(addr_1.unwrap());
+        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
+        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
     }
 
     #[test]
-    fn insert_wrapped_tc() {
+    fn insert_full_store_test() {
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
 
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
-            vec![(10, 32), (5, 64)],
+            vec![(1, 64)],
             false,
         ));
 
         let mut buf: [u8; 32] = [0; 32];
+        // Store is full after this.
+        pool.add(&[0, 1, 2]).unwrap();
         let tc = scheduled_tc(UnixTime::new_only_secs(100), &mut buf);
 
-        let info = match scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool) {
-            Ok(addr) => addr,
-            Err(e) => {
-                panic!("unexpected error {e}");
-            }
-        };
-
-        assert!(pool.has_element_at(&info.addr).unwrap());
-
-        let read_len = pool.read(&info.addr, &mut buf).unwrap();
-        let check_tc = PusTcReader::new(&buf).expect("incorrect Pus tc raw data");
-        assert_eq!(read_len, check_tc.1);
-        assert_eq!(check_tc.0, base_ping_tc_simple_ctor(0, &[]));
-
-        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+        let insert_res = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
+        assert!(insert_res.is_err());
+        let err = insert_res.unwrap_err();
+        match err {
+            ScheduleError::StoreError(e) => match e {
+                PoolError::StoreFull(_) => {}
+                _ => panic!("unexpected store error {e}"),
+            },
+            _ => panic!("unexpected error {err}"),
+        }
+    }
 
-        scheduler.update_time(UnixTime::new_only_secs(101));
+    fn insert_command_with_release_time(
+        pool: &mut StaticMemoryPool,
+        scheduler: &mut PusScheduler,
+        seq_count: u16,
+        release_secs: u64,
+    ) -> TcInfo {
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info = ping_tc_to_store(pool, &mut buf, seq_count, &[]);
 
-        let mut addr_vec = Vec::new();
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(release_secs as i64), tc_info)
+            .expect("inserting tc failed");
+        tc_info
+    }
 
-        let mut i = 0;
-        let mut test_closure = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
-            common_check(boolvar, &tc_info.addr, vec![info.addr], &mut i);
-            // check that tc remains unchanged
-            addr_vec.push(tc_info.addr);
-            false
+    #[test]
+    fn test_time_window_retrieval_select_all() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let tc_info_0 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
+        let check_range = |range: Range<UnixTime, Vec<TcInfo>>| {
+            let mut tcs_in_range = 0;
+            for (idx, time_bucket) in range.enumerate() {
+                tcs_in_range += 1;
+                if idx == 0 {
+                    assert_eq!(*time_bucket.0, UnixTime::new_only_secs(50));
+                    assert_eq!(time_bucket.1.len(), 1);
+                    assert_eq!(time_bucket.1[0].request_id, tc_info_0.request_id);
+                } else if idx == 1 {
+                    assert_eq!(*time_bucket.0, UnixTime::new_only_secs(100));
+                    assert_eq!(time_bucket.1.len(), 1);
+                    assert_eq!(time_bucket.1[0].request_id, tc_info_1.request_id);
+                }
+            }
+            assert_eq!(tcs_in_range, 2);
         };
+        let range = scheduler.retrieve_all();
+        check_range(range);
+        let range = scheduler.retrieve_by_time_filter(TimeWindow::<cds::CdsTime>::new_select_all());
+        check_range(range);
+    }
 
-        let mut tc_buf: [u8; 64] = [0; 64];
-
-        scheduler
-            .release_telecommands_with_buffer(&mut test_closure, &mut pool, &mut tc_buf)
-            .unwrap();
+    #[test]
+    fn test_time_window_retrieval_select_from_stamp() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let _ = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        let tc_info_2 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
+        let start_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(100),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating start stamp failed");
+        let time_window = TimeWindow::new_from_time(&start_stamp);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
 
-        let read_len = pool.read(&addr_vec[0], &mut buf).unwrap();
-        let check_tc = PusTcReader::new(&buf).expect("incorrect PUS tc raw data");
-        assert_eq!(read_len, check_tc.1);
-        assert_eq!(check_tc.0, base_ping_tc_simple_ctor(0, &[]));
+        let range = scheduler.retrieve_by_time_filter(time_window);
+        let mut tcs_in_range = 0;
+        for (idx, time_bucket) in range.enumerate() {
+            tcs_in_range += 1;
+            if idx == 0 {
+                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(100));
+                assert_eq!(time_bucket.1.len(), 1);
+                assert_eq!(time_bucket.1[0].request_id, tc_info_1.request_id());
+            } else if idx == 1 {
+                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(150));
+                assert_eq!(time_bucket.1.len(), 1);
+                assert_eq!(time_bucket.1[0].request_id, tc_info_2.request_id());
+            }
+        }
+        assert_eq!(tcs_in_range, 2);
     }
 
     #[test]
-    fn insert_wrong_service() {
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-
+    fn test_time_window_retrieval_select_to_time() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let tc_info_0 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        let _ = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
 
-        let mut buf: [u8; 32] = [0; 32];
-        let tc = wrong_tc_service(UnixTime::new_only_secs(100), &mut buf);
-
-        let err = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
-        assert!(err.is_err());
-        let err = err.unwrap_err();
-        match err {
-            ScheduleError::WrongService(wrong_service) => {
-                assert_eq!(wrong_service, 12);
-            }
-            _ => {
-                panic!("unexpected error")
+        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(100),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating start stamp failed");
+        let time_window = TimeWindow::new_to_time(&end_stamp);
+        let range = scheduler.retrieve_by_time_filter(time_window);
+        let mut tcs_in_range = 0;
+        for (idx, time_bucket) in range.enumerate() {
+            tcs_in_range += 1;
+            if idx == 0 {
+                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(50));
+                assert_eq!(time_bucket.1.len(), 1);
+                assert_eq!(time_bucket.1[0].request_id, tc_info_0.request_id());
+            } else if idx == 1 {
+                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(100));
+                assert_eq!(time_bucket.1.len(), 1);
+                assert_eq!(time_bucket.1[0].request_id, tc_info_1.request_id());
             }
         }
+        assert_eq!(tcs_in_range, 2);
     }
 
     #[test]
-    fn insert_wrong_subservice() {
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-
+    fn test_time_window_retrieval_select_from_time_to_time() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let _ = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        let tc_info_2 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
+        let _ = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 200);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 4);
 
-        let mut buf: [u8; 32] = [0; 32];
-        let tc = wrong_tc_subservice(UnixTime::new_only_secs(100), &mut buf);
-
-        let err = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
-        assert!(err.is_err());
-        let err = err.unwrap_err();
-        match err {
-            ScheduleError::WrongSubservice(wrong_subsrv) => {
-                assert_eq!(wrong_subsrv, 5);
-            }
-            _ => {
-                panic!("unexpected error")
+        let start_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(100),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating start stamp failed");
+        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(150),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating end stamp failed");
+        let time_window = TimeWindow::new_from_time_to_time(&start_stamp, &end_stamp);
+        let range = scheduler.retrieve_by_time_filter(time_window);
+        let mut tcs_in_range = 0;
+        for (idx, time_bucket) in range.enumerate() {
+            tcs_in_range += 1;
+            if idx == 0 {
+                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(100));
+                assert_eq!(time_bucket.1.len(), 1);
+                assert_eq!(time_bucket.1[0].request_id, tc_info_1.request_id());
+            } else if idx == 1 {
+                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(150));
+                assert_eq!(time_bucket.1.len(), 1);
+                assert_eq!(time_bucket.1[0].request_id, tc_info_2.request_id());
             }
         }
+        assert_eq!(tcs_in_range, 2);
     }
 
     #[test]
-    fn insert_wrapped_tc_faulty_app_data() {
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+    fn test_deletion_all() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
-        let tc = invalid_time_tagged_cmd();
-        let insert_res = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
-        assert!(insert_res.is_err());
-        let err = insert_res.unwrap_err();
-        match err {
-            ScheduleError::TcDataEmpty => {}
-            _ => panic!("unexpected error {err}"),
-        }
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
+        let del_res = scheduler.delete_all(&mut pool);
+        assert!(del_res.is_ok());
+        assert_eq!(del_res.unwrap(), 2);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
+        // Contrary to reset, this does not disable the scheduler.
+        assert!(scheduler.is_enabled());
+
+        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
+        let del_res = scheduler
+            .delete_by_time_filter(TimeWindow::<cds::CdsTime>::new_select_all(), &mut pool);
+        assert!(del_res.is_ok());
+        assert_eq!(del_res.unwrap(), 2);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
+        // Contrary to reset, this does not disable the scheduler.
+        assert!(scheduler.is_enabled());
     }
 
     #[test]
-    fn insert_doubly_wrapped_time_tagged_cmd() {
+    fn test_deletion_from_start_time() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let cmd_0_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        let cmd_1_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
+        let start_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(100),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating start stamp failed");
+        let time_window = TimeWindow::new_from_time(&start_stamp);
+        let del_res = scheduler.delete_by_time_filter(time_window, &mut pool);
+        assert!(del_res.is_ok());
+        assert_eq!(del_res.unwrap(), 2);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+        assert!(!pool.has_element_at(&cmd_0_to_delete.addr()).unwrap());
+        assert!(!pool.has_element_at(&cmd_1_to_delete.addr()).unwrap());
+    }
+
+    #[test]
+    fn test_deletion_to_end_time() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
-        let mut buf: [u8; 64] = [0; 64];
-        let tc = double_wrapped_time_tagged_tc(UnixTime::new_only_secs(50), &mut buf);
-        let insert_res = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
-        assert!(insert_res.is_err());
-        let err = insert_res.unwrap_err();
-        match err {
-            ScheduleError::NestedScheduledTc => {}
-            _ => panic!("unexpected error {err}"),
-        }
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let cmd_0_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let cmd_1_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
+
+        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(100),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating start stamp failed");
+        let time_window = TimeWindow::new_to_time(&end_stamp);
+        let del_res = scheduler.delete_by_time_filter(time_window, &mut pool);
+        assert!(del_res.is_ok());
+        assert_eq!(del_res.unwrap(), 2);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+        assert!(!pool.has_element_at(&cmd_0_to_delete.addr()).unwrap());
+        assert!(!pool.has_element_at(&cmd_1_to_delete.addr()).unwrap());
     }
 
     #[test]
-    fn test_ctor_from_current() {
-        let scheduler = PusScheduler::new_with_current_init_time(Duration::from_secs(5))
-            .expect("creation from current time failed");
-        let current_time = scheduler.current_time;
-        assert!(current_time.as_secs() > 0);
+    fn test_deletion_from_start_time_to_end_time() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let cmd_out_of_range_0 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let cmd_0_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        let cmd_1_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
+        let cmd_out_of_range_1 =
+            insert_command_with_release_time(&mut pool, &mut scheduler, 0, 200);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 4);
+
+        let start_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(100),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating start stamp failed");
+        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(150),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating end stamp failed");
+        let time_window = TimeWindow::new_from_time_to_time(&start_stamp, &end_stamp);
+        let del_res = scheduler.delete_by_time_filter(time_window, &mut pool);
+        assert!(del_res.is_ok());
+        assert_eq!(del_res.unwrap(), 2);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
+        assert!(pool.has_element_at(&cmd_out_of_range_0.addr()).unwrap());
+        assert!(!pool.has_element_at(&cmd_0_to_delete.addr()).unwrap());
+        assert!(!pool.has_element_at(&cmd_1_to_delete.addr()).unwrap());
+        assert!(pool.has_element_at(&cmd_out_of_range_1.addr()).unwrap());
     }
 
     #[test]
-    fn test_update_from_current() {
+    fn test_release_without_deletion() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        assert_eq!(scheduler.current_time.as_secs(), 0);
+
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+
         scheduler
-            .update_time_from_now()
-            .expect("updating scheduler time from now failed");
-        assert!(scheduler.current_time.as_secs() > 0);
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
+            .expect("insertion failed");
+
+        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(200), tc_info_1)
+            .expect("insertion failed");
+
+        let mut i = 0;
+        let mut test_closure_1 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
+            common_check(
+                boolvar,
+                &tc_info.addr,
+                vec![tc_info_0.addr(), tc_info_1.addr()],
+                &mut i,
+            );
+        };
+
+        scheduler.update_time(UnixTime::new_only_secs(205));
+
+        let mut tc_buf: [u8; 64] = [0; 64];
+        let tc_info_vec = scheduler
+            .release_telecommands_no_deletion(&mut test_closure_1, &pool, &mut tc_buf)
+            .expect("deletion failed");
+        assert_eq!(tc_info_vec[0], tc_info_0);
+        assert_eq!(tc_info_vec[1], tc_info_1);
     }
 
     #[test]
-    fn release_time_within_time_margin() {
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-
+    fn test_release_with_report() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
 
         let mut buf: [u8; 32] = [0; 32];
-
-        let tc = scheduled_tc(UnixTime::new_only_secs(4), &mut buf);
-        let insert_res = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
-        assert!(insert_res.is_err());
-        let err = insert_res.unwrap_err();
-        match err {
-            ScheduleError::ReleaseTimeInTimeMargin {
-                current_time,
-                time_margin,
-                release_time,
-            } => {
-                assert_eq!(current_time, UnixTime::new_only_secs(0));
-                assert_eq!(time_margin, Duration::from_secs(5));
-                assert_eq!(release_time, UnixTime::new_only_secs(4));
+        let tc_infos: Vec<TcInfo> = (0..4)
+            .map(|seq_count| ping_tc_to_store(&mut pool, &mut buf, seq_count, &[]))
+            .collect();
+        for tc_info in &tc_infos {
+            scheduler
+                .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), *tc_info)
+                .expect("insertion failed");
+        }
+        // Simulate a TC which was removed from the store externally.
+        pool.delete(tc_infos[2].addr()).unwrap();
+
+        let mut released = Vec::new();
+        let releaser = |enabled: bool, tc_info: &TcInfo, tc: &[u8]| {
+            assert!(enabled);
+            let ping_tc = PusTcReader::new(tc).expect("invalid TC").0;
+            assert_eq!(RequestId::from_tc(&ping_tc), tc_info.request_id());
+            if tc_info.request_id() == tc_infos[1].request_id() {
+                return Err("release failed");
             }
-            _ => panic!("unexepcted error {err}"),
+            released.push(*tc_info);
+            Ok(())
+        };
+        scheduler.update_time(UnixTime::new_only_secs(100));
+        let mut tc_buf: [u8; 64] = [0; 64];
+        let report = scheduler.release_telecommands_with_report(releaser, &mut pool, &mut tc_buf);
+        assert!(!report.all_released());
+        assert_eq!(report.released, 2);
+        assert_eq!(released, vec![tc_infos[0], tc_infos[3]]);
+        assert_eq!(report.failures.len(), 3);
+        assert_eq!(
+            report.failures[0],
+            TcReleaseFailure {
+                info: tc_infos[1],
+                error: TcReleaseError::Release("release failed"),
+            }
+        );
+        assert_eq!(report.failures[1].info, tc_infos[2]);
+        assert!(matches!(
+            report.failures[1].error,
+            TcReleaseError::Read(PoolError::DataDoesNotExist(_))
+        ));
+        assert_eq!(report.failures[2].info, tc_infos[2]);
+        assert!(matches!(
+            report.failures[2].error,
+            TcReleaseError::Deletion(PoolError::DataDoesNotExist(_))
+        ));
+        // All due TCs are removed from the schedule and the store.
+        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
+        for tc_info in &tc_infos {
+            assert!(!pool.has_element_at(&tc_info.addr()).unwrap());
         }
+
+        let report: ReleaseReport<()> =
+            scheduler.release_telecommands_with_report(|_, _, _| Ok(()), &mut pool, &mut tc_buf);
+        assert!(report.all_released());
+        assert_eq!(report.released, 0);
     }
 
     #[test]
-    fn test_store_error_propagation_release() {
+    fn test_release_with_report_buf_too_small() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+
         let mut buf: [u8; 32] = [0; 32];
         let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
-        scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
-            .expect("insertion failed");
+        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[0; 8]);
+        let tc_info_2 = ping_tc_to_store(&mut pool, &mut buf, 2, &[]);
+        for tc_info in [tc_info_0, tc_info_1, tc_info_2] {
+            scheduler
+                .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info)
+                .expect("insertion failed");
+        }
 
-        let mut i = 0;
-        let test_closure_1 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
-            common_check_disabled(boolvar, &tc_info.addr, vec![tc_info_0.addr()], &mut i);
-            true
+        let mut released = Vec::new();
+        let releaser = |_: bool, tc_info: &TcInfo, _: &[u8]| -> Result<(), ()> {
+            released.push(*tc_info);
+            Ok(())
         };
-
-        // premature deletion
-        pool.delete(tc_info_0.addr()).expect("deletion failed");
-        // scheduler will only auto-delete if it is disabled.
-        scheduler.disable();
         scheduler.update_time(UnixTime::new_only_secs(100));
-        let release_res = scheduler.release_telecommands(test_closure_1, &mut pool);
-        assert!(release_res.is_err());
-        let err = release_res.unwrap_err();
-        // TC could not even be read..
-        assert_eq!(err.0, 0);
-        match err.1 {
-            PoolError::DataDoesNotExist(addr) => {
-                assert_eq!(tc_info_0.addr(), addr);
-            }
-            _ => panic!("unexpected error {}", err.1),
+        // Only the TCs without application data fit into the buffer.
+        let mut tc_buf: [u8; 16] = [0; 16];
+        let report = scheduler.release_telecommands_with_report(releaser, &mut pool, &mut tc_buf);
+        assert_eq!(report.released, 2);
+        assert_eq!(released, vec![tc_info_0, tc_info_2]);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].info, tc_info_1);
+        assert!(matches!(report.failures[0].error, TcReleaseError::Read(_)));
+        // The TC which could not be read is deleted from the store as well.
+        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
+        for tc_info in [tc_info_0, tc_info_1, tc_info_2] {
+            assert!(!pool.has_element_at(&tc_info.addr()).unwrap());
         }
     }
 
     #[test]
-    fn test_store_error_propagation_reset() {
+    fn test_schedule_queries() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        assert_eq!(scheduler.next_release_time(), None);
+        assert_eq!(
+            scheduler
+                .num_scheduled_telecommands_in_window(TimeWindow::<cds::CdsTime>::new_select_all()),
+            0
+        );
+
         let mut buf: [u8; 32] = [0; 32];
-        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+        let release_secs = [100, 200, 200, 300];
+        let mut tc_infos = Vec::new();
+        for (seq_count, secs) in release_secs.iter().enumerate() {
+            let tc_info = ping_tc_to_store(&mut pool, &mut buf, seq_count as u16, &[]);
+            scheduler
+                .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(*secs), tc_info)
+                .expect("insertion failed");
+            tc_infos.push(tc_info);
+        }
+        assert_eq!(scheduler.num_scheduled_telecommands(), 4);
+        assert_eq!(
+            scheduler.next_release_time(),
+            Some(UnixTime::new_only_secs(100))
+        );
+
+        let cds_time = |secs: i64| {
+            cds::CdsTime::from_unix_time_with_u16_days(
+                &UnixTime::new_only_secs(secs),
+                cds::SubmillisPrecision::Absent,
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            scheduler.num_scheduled_telecommands_in_window(TimeWindow::new_from_time_to_time(
+                &cds_time(150),
+                &cds_time(300)
+            )),
+            3
+        );
+        assert_eq!(
+            scheduler.num_scheduled_telecommands_in_window(TimeWindow::new_to_time(&cds_time(199))),
+            1
+        );
+        assert_eq!(
+            scheduler
+                .num_scheduled_telecommands_in_window(TimeWindow::new_from_time(&cds_time(301))),
+            0
+        );
+
+        // The queries do not modify the schedule, and empty time buckets are skipped.
         scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
-            .expect("insertion failed");
+            .delete_by_request_id(&tc_infos[0].request_id())
+            .unwrap();
+        assert_eq!(
+            scheduler.next_release_time(),
+            Some(UnixTime::new_only_secs(200))
+        );
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
+    }
 
-        // premature deletion
-        pool.delete(tc_info_0.addr()).expect("deletion failed");
-        let reset_res = scheduler.reset(&mut pool);
-        assert!(reset_res.is_err());
-        let err = reset_res.unwrap_err();
-        match err {
-            PoolError::DataDoesNotExist(addr) => {
-                assert_eq!(addr, tc_info_0.addr());
-            }
-            _ => panic!("unexpected error {err}"),
+    #[test]
+    fn test_generic_insert_app_data_test() {
+        let time_writer = cds::CdsTime::new_with_u16_days(1, 1);
+        let sph = SpHeader::new(
+            PacketId::new(PacketType::Tc, true, 0x002),
+            PacketSequenceCtrl::new(SequenceFlags::Unsegmented, 5),
+            0,
+        );
+        let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
+        let ping_tc = PusTcCreator::new_no_app_data(sph, sec_header, true);
+        let mut buf: [u8; 64] = [0; 64];
+        let result = generate_insert_telecommand_app_data(&mut buf, &time_writer, &ping_tc);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 2 + 7 + ping_tc.len_written());
+        let n = u16::from_be_bytes(buf[0..2].try_into().unwrap());
+        assert_eq!(n, 1);
+        let time_reader = cds::CdsTime::from_bytes_with_u16_days(&buf[2..2 + 7]).unwrap();
+        assert_eq!(time_reader, time_writer);
+        let pus_tc_reader = PusTcReader::new(&buf[9..]).unwrap().0;
+        assert_eq!(pus_tc_reader, ping_tc);
+    }
+
+    #[test]
+    fn test_generic_insert_app_data_test_byte_conv_error() {
+        let time_writer = cds::CdsTime::new_with_u16_days(1, 1);
+        let sph = SpHeader::new(
+            PacketId::new(PacketType::Tc, true, 0x002),
+            PacketSequenceCtrl::new(SequenceFlags::Unsegmented, 5),
+            0,
+        );
+        let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
+        let ping_tc = PusTcCreator::new_no_app_data(sph, sec_header, true);
+        let mut buf: [u8; 16] = [0; 16];
+        let result = generate_insert_telecommand_app_data(&mut buf, &time_writer, &ping_tc);
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        if let ScheduleError::ByteConversionError(ByteConversionError::ToSliceTooSmall {
+            found,
+            expected,
+        }) = error
+        {
+            assert_eq!(found, 16);
+            assert_eq!(
+                expected,
+                2 + time_writer.len_written() + ping_tc.len_written()
+            );
+        } else {
+            panic!("unexpected error {error}")
         }
     }
 
     #[test]
-    fn test_delete_by_req_id_simple_retrieve_addr() {
-        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
-            vec![(10, 32), (5, 64)],
-            false,
-        ));
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+    fn test_generic_insert_app_data_test_as_vec() {
+        let time_writer = cds::CdsTime::new_with_u16_days(1, 1);
+        let sph = SpHeader::new(
+            PacketId::new(PacketType::Tc, true, 0x002),
+            PacketSequenceCtrl::new(SequenceFlags::Unsegmented, 5),
+            0,
+        );
+        let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
+        let ping_tc = PusTcCreator::new_no_app_data(sph, sec_header, true);
+        let mut buf: [u8; 64] = [0; 64];
+        generate_insert_telecommand_app_data(&mut buf, &time_writer, &ping_tc).unwrap();
+        let vec = generate_insert_telecommand_app_data_as_vec(&time_writer, &ping_tc)
+            .expect("vec generation failed");
+        assert_eq!(&buf[..vec.len()], vec);
+    }
+
+    #[test]
+    fn test_request_id_raw_conversion() {
+        let req_id = RequestId {
+            source_id: 12,
+            apid: 0x22,
+            seq_count: 105,
+        };
+        let mut buf: [u8; 8] = [0; 8];
+        assert_eq!(
+            req_id.write_to_be_bytes(&mut buf).unwrap(),
+            RequestId::RAW_LEN
+        );
+        assert_eq!(RequestId::from_bytes(&buf).unwrap(), req_id);
+        assert!(RequestId::from_bytes(&buf[0..5]).is_err());
+        assert!(req_id.write_to_be_bytes(&mut buf[0..5]).is_err());
+    }
+
+    #[test]
+    fn test_delete_by_request_id_app_data() {
+        let req_id_0 = RequestId {
+            source_id: 0,
+            apid: 0x22,
+            seq_count: 1,
+        };
+        let req_id_1 = RequestId {
+            source_id: 1,
+            apid: 0x23,
+            seq_count: 2,
+        };
         let mut buf: [u8; 32] = [0; 32];
-        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
-        scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
-            .expect("inserting tc failed");
-        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
-        let addr = scheduler
-            .delete_by_request_id(&tc_info_0.request_id())
-            .unwrap();
-        assert!(pool.has_element_at(&tc_info_0.addr()).unwrap());
-        assert_eq!(tc_info_0.addr(), addr);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
+        let written_len =
+            generate_delete_by_request_id_app_data(&mut buf, &[req_id_0, req_id_1]).unwrap();
+        assert_eq!(written_len, 2 + 2 * RequestId::RAW_LEN);
+        assert_eq!(u16::from_be_bytes(buf[0..2].try_into().unwrap()), 2);
+        assert_eq!(RequestId::from_bytes(&buf[2..]).unwrap(), req_id_0);
+        assert_eq!(RequestId::from_bytes(&buf[8..]).unwrap(), req_id_1);
+        let error = generate_delete_by_request_id_app_data(&mut buf[0..10], &[req_id_0, req_id_1])
+            .unwrap_err();
+        if let ScheduleError::ByteConversionError(ByteConversionError::ToSliceTooSmall {
+            found,
+            expected,
+        }) = error
+        {
+            assert_eq!(found, 10);
+            assert_eq!(expected, 14);
+        } else {
+            panic!("unexpected error {error}")
+        }
     }
 
     #[test]
-    fn test_delete_by_req_id_simple_delete_all() {
-        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
-            vec![(10, 32), (5, 64)],
-            false,
-        ));
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+    fn test_time_window_byte_conversion() {
+        let start_stamp = cds::CdsTime::new_with_u16_days(1, 1);
+        let end_stamp = cds::CdsTime::new_with_u16_days(2, 1);
+        let time_window = TimeWindow::new_from_time_to_time(&start_stamp, &end_stamp);
         let mut buf: [u8; 32] = [0; 32];
-        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
-        scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
-            .expect("inserting tc failed");
-        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
-        let del_res =
-            scheduler.delete_by_request_id_and_from_pool(&tc_info_0.request_id(), &mut pool);
-        assert!(del_res.is_ok());
-        assert!(del_res.unwrap());
-        assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
-        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
+        let written_len = time_window.write_to_bytes(&mut buf).unwrap();
+        assert_eq!(written_len, 4 + 7 + 7);
+        assert_eq!(written_len, time_window.len_written());
+        let read_window = TimeWindow::<cds::CdsTime>::from_bytes(&buf[..written_len]).unwrap();
+        assert!(matches!(
+            read_window.time_window_type(),
+            TimeWindowType::TimeTagToTimeTag
+        ));
+        assert_eq!(read_window.start_time().unwrap(), &start_stamp);
+        assert_eq!(read_window.end_time().unwrap(), &end_stamp);
+
+        let time_window = TimeWindow::new_to_time(&end_stamp);
+        let written_len = time_window.write_to_bytes(&mut buf).unwrap();
+        assert_eq!(written_len, 4 + 7);
+        let read_window = TimeWindow::<cds::CdsTime>::from_bytes(&buf[..written_len]).unwrap();
+        assert!(matches!(
+            read_window.time_window_type(),
+            TimeWindowType::ToTimeTag
+        ));
+        assert!(read_window.start_time().is_none());
+        assert_eq!(read_window.end_time().unwrap(), &end_stamp);
     }
 
     #[test]
-    fn test_delete_by_req_id_complex() {
+    fn test_time_window_invalid_type() {
+        let buf = 4_u32.to_be_bytes();
+        let error = TimeWindow::<cds::CdsTime>::from_bytes(&buf)
+            .err()
+            .expect("reading time window should fail");
+        assert_eq!(error, ScheduleError::InvalidTimeWindowType(4));
+        assert!(TimeWindow::<cds::CdsTime>::from_bytes(&buf[0..2]).is_err());
+    }
+
+    #[test]
+    fn test_deletion_by_time_window_provider_api() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        let mut buf: [u8; 32] = [0; 32];
-        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
-        scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
-            .expect("inserting tc failed");
-        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
-        scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_1)
-            .expect("inserting tc failed");
-        let tc_info_2 = ping_tc_to_store(&mut pool, &mut buf, 2, &[]);
-        scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_2)
-            .expect("inserting tc failed");
-        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
-
-        // Delete first packet
-        let addr_0 = scheduler.delete_by_request_id(&tc_info_0.request_id());
-        assert!(addr_0.is_some());
-        assert_eq!(addr_0.unwrap(), tc_info_0.addr());
-        assert!(pool.has_element_at(&tc_info_0.addr()).unwrap());
-        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
-
-        // Delete next packet
-        let del_res =
-            scheduler.delete_by_request_id_and_from_pool(&tc_info_2.request_id(), &mut pool);
-        assert!(del_res.is_ok());
-        assert!(del_res.unwrap());
-        assert!(!pool.has_element_at(&tc_info_2.addr()).unwrap());
-        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
-
-        // Delete last packet
-        let addr_1 =
-            scheduler.delete_by_request_id_and_from_pool(&tc_info_1.request_id(), &mut pool);
-        assert!(addr_1.is_ok());
-        assert!(addr_1.unwrap());
-        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
+        let cmd_0 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let cmd_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 1, 100);
+        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(50),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating end stamp failed");
+        let del_res = PusSchedulerProvider::delete_by_time_window(
+            &mut scheduler,
+            TimeWindow::new_to_time(&end_stamp),
+            &mut pool,
+        );
+        assert_eq!(del_res.unwrap(), 1);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+        assert!(!pool.has_element_at(&cmd_0.addr()).unwrap());
+        assert!(PusSchedulerProvider::delete_by_request_id_and_from_pool(
+            &mut scheduler,
+            &cmd_1.request_id(),
+            &mut pool
+        )
+        .unwrap());
+        assert!(!pool.has_element_at(&cmd_1.addr()).unwrap());
         assert_eq!(scheduler.num_scheduled_telecommands(), 0);
     }
 
     #[test]
-    fn insert_full_store_test() {
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+    fn test_shift_unix_time() {
+        let time = UnixTime::new(10, 500_000_000);
+        assert_eq!(
+            shift_unix_time(&time, 1_700).unwrap(),
+            UnixTime::new(12, 200_000_000)
+        );
+        assert_eq!(
+            shift_unix_time(&time, -10_600).unwrap(),
+            UnixTime::new(-1, 900_000_000)
+        );
+        assert!(shift_unix_time(&UnixTime::new(i64::MAX, 0), 1000).is_none());
+    }
 
+    #[test]
+    fn test_time_shift_all() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
-            vec![(1, 64)],
+            vec![(10, 32), (5, 64)],
             false,
         ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let tc_info_0 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 1, 100);
+        assert_eq!(scheduler.time_shift_all(-20_000).unwrap(), 2);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
+        let mut range = scheduler.retrieve_all();
+        let (release_time, tc_infos) = range.next().unwrap();
+        assert_eq!(*release_time, UnixTime::new_only_secs(30));
+        assert_eq!(tc_infos, &vec![tc_info_0]);
+        let (release_time, tc_infos) = range.next().unwrap();
+        assert_eq!(*release_time, UnixTime::new_only_secs(80));
+        assert_eq!(tc_infos, &vec![tc_info_1]);
+        assert!(range.next().is_none());
+    }
 
-        let mut buf: [u8; 32] = [0; 32];
-        // Store is full after this.
-        pool.add(&[0, 1, 2]).unwrap();
-        let tc = scheduled_tc(UnixTime::new_only_secs(100), &mut buf);
-
-        let insert_res = scheduler.insert_wrapped_tc::<cds::CdsTime>(&tc, &mut pool);
-        assert!(insert_res.is_err());
-        let err = insert_res.unwrap_err();
-        match err {
-            ScheduleError::StoreError(e) => match e {
-                PoolError::StoreFull(_) => {}
-                _ => panic!("unexpected store error {e}"),
-            },
-            _ => panic!("unexpected error {err}"),
-        }
+    fn release_times(scheduler: &mut PusScheduler) -> Vec<UnixTime> {
+        scheduler.retrieve_all().map(|(t, _)| *t).collect()
     }
 
-    fn insert_command_with_release_time(
-        pool: &mut StaticMemoryPool,
-        scheduler: &mut PusScheduler,
-        seq_count: u16,
-        release_secs: u64,
-    ) -> TcInfo {
-        let mut buf: [u8; 32] = [0; 32];
-        let tc_info = ping_tc_to_store(pool, &mut buf, seq_count, &[]);
+    #[test]
+    fn test_time_correction_shift_schedule() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(10), Duration::from_secs(5));
+        assert_eq!(scheduler.time_jump_policy(), TimeJumpPolicy::ShiftSchedule);
+        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        insert_command_with_release_time(&mut pool, &mut scheduler, 1, 100);
+        // Forward jump: Nothing is released because the schedule is shifted as well.
+        assert_eq!(
+            scheduler
+                .handle_time_correction(200_000, &mut pool)
+                .unwrap(),
+            2
+        );
+        assert_eq!(scheduler.current_time().as_secs(), 210);
+        assert_eq!(scheduler.telecommands_to_release().count(), 0);
+        assert_eq!(
+            release_times(&mut scheduler),
+            vec![UnixTime::new_only_secs(250), UnixTime::new_only_secs(300)]
+        );
+        // Backward jump: The remaining time until the release is preserved.
+        assert_eq!(
+            scheduler
+                .handle_time_correction(-205_000, &mut pool)
+                .unwrap(),
+            2
+        );
+        assert_eq!(scheduler.current_time().as_secs(), 5);
+        assert_eq!(
+            release_times(&mut scheduler),
+            vec![UnixTime::new_only_secs(45), UnixTime::new_only_secs(95)]
+        );
+    }
 
+    #[test]
+    fn test_time_correction_release_overdue_with_margin() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(10), Duration::from_secs(5));
+        scheduler.set_time_jump_policy(TimeJumpPolicy::ReleaseOverdueWithMargin(
+            Duration::from_secs(20),
+        ));
+        let tc_info_0 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 1, 100);
+        let tc_info_2 = insert_command_with_release_time(&mut pool, &mut scheduler, 2, 200);
+        // Backward jump: The absolute release times are kept.
+        assert_eq!(
+            scheduler.handle_time_correction(-5_000, &mut pool).unwrap(),
+            0
+        );
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
+        // Forward jump to 110 seconds: The first TC is overdue by more than the margin and is
+        // deleted, the second one is still released.
+        assert_eq!(
+            scheduler
+                .handle_time_correction(105_000, &mut pool)
+                .unwrap(),
+            1
+        );
+        assert!(!pool.has_element_at(&tc_info_0.addr()).unwrap());
+        let mut released = Vec::new();
         scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(release_secs as i64), tc_info)
-            .expect("inserting tc failed");
-        tc_info
+            .release_telecommands(
+                |_, info, _| {
+                    released.push(*info);
+                    true
+                },
+                &mut pool,
+            )
+            .unwrap();
+        assert_eq!(released, vec![tc_info_1]);
+        assert_eq!(
+            release_times(&mut scheduler),
+            vec![UnixTime::new_only_secs(200)]
+        );
+        assert!(pool.has_element_at(&tc_info_2.addr()).unwrap());
     }
 
     #[test]
-    fn test_time_window_retrieval_select_all() {
+    fn test_time_correction_suspend() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(10), Duration::from_secs(5));
+        scheduler.set_time_jump_policy(TimeJumpPolicy::Suspend);
         let tc_info_0 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
-        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
+        insert_command_with_release_time(&mut pool, &mut scheduler, 1, 100);
+        assert_eq!(
+            scheduler.handle_time_correction(50_000, &mut pool).unwrap(),
+            0
+        );
+        assert!(scheduler.is_suspended());
+        assert_eq!(scheduler.telecommands_to_release().count(), 0);
+        let released = scheduler
+            .release_telecommands(|_, _, _| panic!("no TC should be released"), &mut pool)
+            .unwrap();
+        assert_eq!(released, 0);
         assert_eq!(scheduler.num_scheduled_telecommands(), 2);
-        let check_range = |range: Range<UnixTime, Vec<TcInfo>>| {
-            let mut tcs_in_range = 0;
-            for (idx, time_bucket) in range.enumerate() {
-                tcs_in_range += 1;
-                if idx == 0 {
-                    assert_eq!(*time_bucket.0, UnixTime::new_only_secs(50));
-                    assert_eq!(time_bucket.1.len(), 1);
-                    assert_eq!(time_bucket.1[0].request_id, tc_info_0.request_id);
-                } else if idx == 1 {
-                    assert_eq!(*time_bucket.0, UnixTime::new_only_secs(100));
-                    assert_eq!(time_bucket.1.len(), 1);
-                    assert_eq!(time_bucket.1[0].request_id, tc_info_1.request_id);
-                }
-            }
-            assert_eq!(tcs_in_range, 2);
-        };
-        let range = scheduler.retrieve_all();
-        check_range(range);
-        let range = scheduler.retrieve_by_time_filter(TimeWindow::<cds::CdsTime>::new_select_all());
-        check_range(range);
+        scheduler.resume();
+        let mut released = Vec::new();
+        scheduler
+            .release_telecommands(
+                |_, info, _| {
+                    released.push(*info);
+                    true
+                },
+                &mut pool,
+            )
+            .unwrap();
+        assert_eq!(released, vec![tc_info_0]);
     }
 
     #[test]
-    fn test_time_window_retrieval_select_from_stamp() {
+    fn test_time_shift_into_time_margin_rejected() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        let _ = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
-        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
-        let tc_info_2 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
-        let start_stamp = cds::CdsTime::from_unix_time_with_u16_days(
-            &UnixTime::new_only_secs(100),
-            cds::SubmillisPrecision::Absent,
-        )
-        .expect("creating start stamp failed");
-        let time_window = TimeWindow::new_from_time(&start_stamp);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
-
-        let range = scheduler.retrieve_by_time_filter(time_window);
-        let mut tcs_in_range = 0;
-        for (idx, time_bucket) in range.enumerate() {
-            tcs_in_range += 1;
-            if idx == 0 {
-                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(100));
-                assert_eq!(time_bucket.1.len(), 1);
-                assert_eq!(time_bucket.1[0].request_id, tc_info_1.request_id());
-            } else if idx == 1 {
-                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(150));
-                assert_eq!(time_bucket.1.len(), 1);
-                assert_eq!(time_bucket.1[0].request_id, tc_info_2.request_id());
-            }
+        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        insert_command_with_release_time(&mut pool, &mut scheduler, 1, 100);
+        let error = scheduler.time_shift_all(-47_000).unwrap_err();
+        if let ScheduleError::ReleaseTimeInTimeMargin { release_time, .. } = error {
+            assert_eq!(release_time, UnixTime::new_only_secs(3));
+        } else {
+            panic!("unexpected error {error}")
         }
-        assert_eq!(tcs_in_range, 2);
+        // The schedule is not modified.
+        let release_times: Vec<UnixTime> = scheduler.retrieve_all().map(|(t, _)| *t).collect();
+        assert_eq!(
+            release_times,
+            vec![UnixTime::new_only_secs(50), UnixTime::new_only_secs(100)]
+        );
     }
 
     #[test]
-    fn test_time_window_retrieval_select_to_time() {
+    fn test_time_shift_by_request_id_and_apid() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
         let tc_info_0 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
-        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
-        let _ = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
-
-        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
-            &UnixTime::new_only_secs(100),
-            cds::SubmillisPrecision::Absent,
-        )
-        .expect("creating start stamp failed");
-        let time_window = TimeWindow::new_to_time(&end_stamp);
-        let range = scheduler.retrieve_by_time_filter(time_window);
-        let mut tcs_in_range = 0;
-        for (idx, time_bucket) in range.enumerate() {
-            tcs_in_range += 1;
-            if idx == 0 {
-                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(50));
-                assert_eq!(time_bucket.1.len(), 1);
-                assert_eq!(time_bucket.1[0].request_id, tc_info_0.request_id());
-            } else if idx == 1 {
-                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(100));
-                assert_eq!(time_bucket.1.len(), 1);
-                assert_eq!(time_bucket.1[0].request_id, tc_info_1.request_id());
-            }
-        }
-        assert_eq!(tcs_in_range, 2);
+        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 1, 50);
+        assert!(scheduler
+            .time_shift_by_request_id(&tc_info_1.request_id(), 10_000)
+            .unwrap());
+        let mut range = scheduler.retrieve_all();
+        assert_eq!(
+            range.next().unwrap(),
+            (&UnixTime::new_only_secs(50), &vec![tc_info_0])
+        );
+        assert_eq!(
+            range.next().unwrap(),
+            (&UnixTime::new_only_secs(60), &vec![tc_info_1])
+        );
+        let unknown_id = RequestId {
+            source_id: 0,
+            apid: 0x7ff,
+            seq_count: 0,
+        };
+        assert!(!scheduler
+            .time_shift_by_request_id(&unknown_id, 10_000)
+            .unwrap());
+        assert_eq!(
+            scheduler
+                .time_shift_by_apid(tc_info_0.request_id().apid(), 5_000)
+                .unwrap(),
+            2
+        );
+        assert_eq!(scheduler.time_shift_by_apid(0x7ff, 5_000).unwrap(), 0);
+        let release_times: Vec<UnixTime> = scheduler.retrieve_all().map(|(t, _)| *t).collect();
+        assert_eq!(
+            release_times,
+            vec![UnixTime::new_only_secs(55), UnixTime::new_only_secs(65)]
+        );
     }
 
     #[test]
-    fn test_time_window_retrieval_select_from_time_to_time() {
+    fn test_time_shift_by_time_window() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        let _ = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
-        let tc_info_1 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
-        let tc_info_2 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
-        let _ = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 200);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 4);
-
+        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        insert_command_with_release_time(&mut pool, &mut scheduler, 1, 100);
+        insert_command_with_release_time(&mut pool, &mut scheduler, 2, 150);
         let start_stamp = cds::CdsTime::from_unix_time_with_u16_days(
             &UnixTime::new_only_secs(100),
             cds::SubmillisPrecision::Absent,
         )
         .expect("creating start stamp failed");
-        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
-            &UnixTime::new_only_secs(150),
-            cds::SubmillisPrecision::Absent,
+        assert_eq!(
+            scheduler
+                .time_shift_by_time_window(TimeWindow::new_from_time(&start_stamp), 60_000)
+                .unwrap(),
+            2
+        );
+        let release_times: Vec<UnixTime> = scheduler.retrieve_all().map(|(t, _)| *t).collect();
+        assert_eq!(
+            release_times,
+            vec![
+                UnixTime::new_only_secs(50),
+                UnixTime::new_only_secs(160),
+                UnixTime::new_only_secs(210)
+            ]
+        );
+    }
+
+    fn read_release_time(buf: &[u8]) -> UnixTime {
+        UnixTime::new(
+            i64::from_be_bytes(buf[0..8].try_into().unwrap()),
+            u32::from_be_bytes(buf[8..12].try_into().unwrap()),
         )
-        .expect("creating end stamp failed");
-        let time_window = TimeWindow::new_from_time_to_time(&start_stamp, &end_stamp);
-        let range = scheduler.retrieve_by_time_filter(time_window);
-        let mut tcs_in_range = 0;
-        for (idx, time_bucket) in range.enumerate() {
-            tcs_in_range += 1;
-            if idx == 0 {
-                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(100));
-                assert_eq!(time_bucket.1.len(), 1);
-                assert_eq!(time_bucket.1[0].request_id, tc_info_1.request_id());
-            } else if idx == 1 {
-                assert_eq!(*time_bucket.0, UnixTime::new_only_secs(150));
-                assert_eq!(time_bucket.1.len(), 1);
-                assert_eq!(time_bucket.1[0].request_id, tc_info_2.request_id());
-            }
-        }
-        assert_eq!(tcs_in_range, 2);
     }
 
     #[test]
-    fn test_deletion_all() {
+    fn test_summary_report() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
-        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
-        let del_res = scheduler.delete_all(&mut pool);
-        assert!(del_res.is_ok());
-        assert_eq!(del_res.unwrap(), 2);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
-        // Contrary to reset, this does not disable the scheduler.
-        assert!(scheduler.is_enabled());
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let mut report_creator =
+            PusScheduleReportCreator::new(TEST_COMPONENT_ID_0.id(), 0x02, 0, 64).unwrap();
+        report_creator
+            .summary_report(&tm_tx, &[0; 7], &scheduler)
+            .unwrap();
+        let packet = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(tm.service(), 11);
+        assert_eq!(
+            tm.subservice(),
+            scheduling::Subservice::TmSummaryReport as u8
+        );
+        assert_eq!(tm.source_data(), &[0, 0, 0, 0]);
 
-        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
         insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
-        let del_res = scheduler
-            .delete_by_time_filter(TimeWindow::<cds::CdsTime>::new_select_all(), &mut pool);
-        assert!(del_res.is_ok());
-        assert_eq!(del_res.unwrap(), 2);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
-        // Contrary to reset, this does not disable the scheduler.
-        assert!(scheduler.is_enabled());
+        insert_command_with_release_time(&mut pool, &mut scheduler, 1, 50);
+        report_creator
+            .summary_report(&tm_tx, &[0; 7], &scheduler)
+            .unwrap();
+        let packet = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        let source_data = tm.source_data();
+        assert_eq!(source_data.len(), 4 + SCHEDULE_REPORT_RELEASE_TIME_LEN);
+        assert_eq!(u32::from_be_bytes(source_data[0..4].try_into().unwrap()), 2);
+        assert_eq!(
+            read_release_time(&source_data[4..]),
+            UnixTime::new_only_secs(50)
+        );
+        assert!(tm_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_detailed_report_chunked() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let entry_len = SCHEDULE_REPORT_RELEASE_TIME_LEN + RequestId::RAW_LEN + 2;
+        // Only two entries fit into one report.
+        let mut report_creator =
+            PusScheduleReportCreator::new(TEST_COMPONENT_ID_0.id(), 0x02, 0, 2 + 2 * entry_len + 1)
+                .unwrap();
+        assert_eq!(
+            report_creator
+                .detailed_report(&tm_tx, &[0; 7], &scheduler)
+                .unwrap(),
+            1
+        );
+        let packet = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(
+            tm.subservice(),
+            scheduling::Subservice::TmDetailReport as u8
+        );
+        assert_eq!(tm.source_data(), &[0, 0]);
+
+        let mut tc_infos = Vec::new();
+        for (seq_count, release_secs) in [(0, 50), (1, 100), (2, 150)] {
+            tc_infos.push(insert_command_with_release_time(
+                &mut pool,
+                &mut scheduler,
+                seq_count,
+                release_secs,
+            ));
+        }
+        assert_eq!(
+            report_creator
+                .detailed_report(&tm_tx, &[0; 7], &scheduler)
+                .unwrap(),
+            2
+        );
+        let mut expected_entries = tc_infos.iter().zip([50, 100, 150]);
+        for expected_num_entries in [2, 1] {
+            let packet = tm_rx.try_recv().unwrap();
+            let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+            let source_data = tm.source_data();
+            assert_eq!(
+                u16::from_be_bytes(source_data[0..2].try_into().unwrap()),
+                expected_num_entries
+            );
+            assert_eq!(
+                source_data.len(),
+                2 + expected_num_entries as usize * entry_len
+            );
+            for entry in source_data[2..].chunks_exact(entry_len) {
+                let (tc_info, release_secs) = expected_entries.next().unwrap();
+                assert_eq!(
+                    read_release_time(entry),
+                    UnixTime::new_only_secs(release_secs)
+                );
+                assert_eq!(
+                    RequestId::from_bytes(&entry[SCHEDULE_REPORT_RELEASE_TIME_LEN..]).unwrap(),
+                    tc_info.request_id()
+                );
+                assert_eq!(&entry[entry_len - 2..], &[0, 0]);
+            }
+        }
+        assert!(tm_rx.try_recv().is_err());
     }
 
     #[test]
-    fn test_deletion_from_start_time() {
+    fn test_detailed_report_with_tcs() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
-        let cmd_0_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
-        let cmd_1_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
-        let start_stamp = cds::CdsTime::from_unix_time_with_u16_days(
-            &UnixTime::new_only_secs(100),
-            cds::SubmillisPrecision::Absent,
-        )
-        .expect("creating start stamp failed");
-        let time_window = TimeWindow::new_from_time(&start_stamp);
-        let del_res = scheduler.delete_by_time_filter(time_window, &mut pool);
-        assert!(del_res.is_ok());
-        assert_eq!(del_res.unwrap(), 2);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
-        assert!(!pool.has_element_at(&cmd_0_to_delete.addr()).unwrap());
-        assert!(!pool.has_element_at(&cmd_1_to_delete.addr()).unwrap());
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let tc_info = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
+        let raw_tc = pool.read_as_vec(&tc_info.addr()).unwrap();
+        let mut report_creator =
+            PusScheduleReportCreator::new(TEST_COMPONENT_ID_0.id(), 0x02, 0, 128).unwrap();
+        assert_eq!(
+            report_creator
+                .detailed_report_with_tcs(&tm_tx, &[0; 7], &scheduler, &pool)
+                .unwrap(),
+            1
+        );
+        let packet = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        let source_data = tm.source_data();
+        assert_eq!(u16::from_be_bytes(source_data[0..2].try_into().unwrap()), 1);
+        let tc_len_idx = 2 + SCHEDULE_REPORT_RELEASE_TIME_LEN + RequestId::RAW_LEN;
+        assert_eq!(
+            u16::from_be_bytes(source_data[tc_len_idx..tc_len_idx + 2].try_into().unwrap())
+                as usize,
+            raw_tc.len()
+        );
+        assert_eq!(&source_data[tc_len_idx + 2..], raw_tc.as_slice());
+
+        // The report buffer is too small for a single entry.
+        let mut report_creator =
+            PusScheduleReportCreator::new(TEST_COMPONENT_ID_0.id(), 0x02, 0, 16).unwrap();
+        assert!(report_creator
+            .detailed_report_with_tcs(&tm_tx, &[0; 7], &scheduler, &pool)
+            .is_err());
     }
 
     #[test]
-    fn test_deletion_to_end_time() {
-        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
-            vec![(10, 32), (5, 64)],
-            false,
-        ));
-        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        let cmd_0_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
-        let cmd_1_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
-        insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
+    fn test_detailed_report_buffer_too_small() {
+        let scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let mut report_creator =
+            PusScheduleReportCreator::new(TEST_COMPONENT_ID_0.id(), 0x02, 0, 1).unwrap();
+        let error = report_creator
+            .detailed_report(&tm_tx, &[0; 7], &scheduler)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            crate::pus::EcssTmtcError::ByteConversion(ByteConversionError::ToSliceTooSmall {
+                found: 1,
+                expected: 2
+            })
+        );
+        assert!(report_creator
+            .groups_status_report(&tm_tx, &[0; 7], &scheduler)
+            .is_err());
+        assert!(tm_rx.try_recv().is_err());
+    }
 
-        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
-            &UnixTime::new_only_secs(100),
-            cds::SubmillisPrecision::Absent,
-        )
-        .expect("creating start stamp failed");
-        let time_window = TimeWindow::new_to_time(&end_stamp);
-        let del_res = scheduler.delete_by_time_filter(time_window, &mut pool);
-        assert!(del_res.is_ok());
-        assert_eq!(del_res.unwrap(), 2);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
-        assert!(!pool.has_element_at(&cmd_0_to_delete.addr()).unwrap());
-        assert!(!pool.has_element_at(&cmd_1_to_delete.addr()).unwrap());
+    #[test]
+    fn test_group_management() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        assert!(scheduler.is_group_enabled(1).is_none());
+        scheduler.create_group(1).unwrap();
+        assert_eq!(
+            scheduler.create_group(1).unwrap_err(),
+            ScheduleError::GroupAlreadyExists(1)
+        );
+        assert_eq!(scheduler.is_group_enabled(1), Some(true));
+        scheduler.disable_group(1).unwrap();
+        assert_eq!(scheduler.is_group_enabled(1), Some(false));
+        scheduler.enable_group(1).unwrap();
+        assert_eq!(scheduler.is_group_enabled(1), Some(true));
+        assert_eq!(
+            scheduler.enable_group(2).unwrap_err(),
+            ScheduleError::UnknownGroup(2)
+        );
+        assert_eq!(
+            scheduler.disable_group(2).unwrap_err(),
+            ScheduleError::UnknownGroup(2)
+        );
+        scheduler.create_group(2).unwrap();
+        let mut groups = Vec::new();
+        scheduler.for_each_group(|group_id, enabled| groups.push((group_id, enabled)));
+        assert_eq!(groups, vec![(1, true), (2, true)]);
+        scheduler.delete_group(2).unwrap();
+        assert_eq!(
+            scheduler.delete_group(2).unwrap_err(),
+            ScheduleError::UnknownGroup(2)
+        );
     }
 
     #[test]
-    fn test_deletion_from_start_time_to_end_time() {
+    fn test_insertion_into_group() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-        let cmd_out_of_range_0 = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 50);
-        let cmd_0_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 100);
-        let cmd_1_to_delete = insert_command_with_release_time(&mut pool, &mut scheduler, 0, 150);
-        let cmd_out_of_range_1 =
-            insert_command_with_release_time(&mut pool, &mut scheduler, 0, 200);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 4);
-
-        let start_stamp = cds::CdsTime::from_unix_time_with_u16_days(
-            &UnixTime::new_only_secs(100),
-            cds::SubmillisPrecision::Absent,
-        )
-        .expect("creating start stamp failed");
-        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
-            &UnixTime::new_only_secs(150),
-            cds::SubmillisPrecision::Absent,
-        )
-        .expect("creating end stamp failed");
-        let time_window = TimeWindow::new_from_time_to_time(&start_stamp, &end_stamp);
-        let del_res = scheduler.delete_by_time_filter(time_window, &mut pool);
-        assert!(del_res.is_ok());
-        assert_eq!(del_res.unwrap(), 2);
-        assert_eq!(scheduler.num_scheduled_telecommands(), 2);
-        assert!(pool.has_element_at(&cmd_out_of_range_0.addr()).unwrap());
-        assert!(!pool.has_element_at(&cmd_0_to_delete.addr()).unwrap());
-        assert!(!pool.has_element_at(&cmd_1_to_delete.addr()).unwrap());
-        assert!(pool.has_element_at(&cmd_out_of_range_1.addr()).unwrap());
+        let mut buf: [u8; 32] = [0; 32];
+        let ping_tc = base_ping_tc_simple_ctor(0, &[]);
+        let ping_size = ping_tc.write_to_bytes(&mut buf).unwrap();
+        assert_eq!(
+            scheduler
+                .insert_unwrapped_tc_in_group(
+                    UnixTime::new_only_secs(100),
+                    &buf[..ping_size],
+                    1,
+                    &mut pool
+                )
+                .unwrap_err(),
+            ScheduleError::UnknownGroup(1)
+        );
+        let tc_info = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+        assert_eq!(
+            scheduler
+                .insert_unwrapped_and_stored_tc(
+                    UnixTime::new_only_secs(100),
+                    TcInfo::new_with_group(tc_info.addr(), tc_info.request_id(), 1)
+                )
+                .unwrap_err(),
+            ScheduleError::UnknownGroup(1)
+        );
+        scheduler.create_group(1).unwrap();
+        let tc_info = scheduler
+            .insert_unwrapped_tc_in_group(
+                UnixTime::new_only_secs(100),
+                &buf[..ping_size],
+                1,
+                &mut pool,
+            )
+            .unwrap();
+        assert_eq!(tc_info.group_id(), Some(1));
+        assert_eq!(scheduler.num_scheduled_telecommands_in_group(1), 1);
+        assert_eq!(
+            scheduler.delete_group(1).unwrap_err(),
+            ScheduleError::GroupNotEmpty(1)
+        );
+        assert!(matches!(
+            scheduler.insert_unwrapped_tc_in_group(
+                UnixTime::new_only_secs(1),
+                &buf[..ping_size],
+                1,
+                &mut pool,
+            ),
+            Err(ScheduleError::ReleaseTimeInTimeMargin { .. })
+        ));
+        scheduler.reset(&mut pool).unwrap();
+        assert!(scheduler.is_group_enabled(1).is_none());
     }
 
     #[test]
-    fn test_release_without_deletion() {
+    fn test_release_disabled_group() {
         let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
             vec![(10, 32), (5, 64)],
             false,
         ));
         let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
-
         let mut buf: [u8; 32] = [0; 32];
+        scheduler.create_group(1).unwrap();
         let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
-
         scheduler
             .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
-            .expect("insertion failed");
-
+            .unwrap();
         let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+        let tc_info_1 = TcInfo::new_with_group(tc_info_1.addr(), tc_info_1.request_id(), 1);
         scheduler
-            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(200), tc_info_1)
-            .expect("insertion failed");
-
-        let mut i = 0;
-        let mut test_closure_1 = |boolvar: bool, tc_info: &TcInfo, _tc: &[u8]| {
-            common_check(
-                boolvar,
-                &tc_info.addr,
-                vec![tc_info_0.addr(), tc_info_1.addr()],
-                &mut i,
-            );
-        };
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_1)
+            .unwrap();
+        scheduler.disable_group(1).unwrap();
+        scheduler.update_time(UnixTime::new_only_secs(100));
+        let mut released = Vec::new();
+        let num_released = scheduler
+            .release_telecommands(
+                |enabled, info, _| {
+                    released.push((enabled, *info));
+                    true
+                },
+                &mut pool,
+            )
+            .unwrap();
+        assert_eq!(num_released, 2);
+        assert_eq!(released, vec![(true, tc_info_0), (false, tc_info_1)]);
+        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
+    }
 
-        scheduler.update_time(UnixTime::new_only_secs(205));
+    #[test]
+    fn test_groups_status_report() {
+        let mut scheduler = PusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        // Only two groups fit into one report.
+        let mut report_creator =
+            PusScheduleReportCreator::new(TEST_COMPONENT_ID_0.id(), 0x02, 0, 2 + 2 * 3).unwrap();
+        assert_eq!(
+            report_creator
+                .groups_status_report(&tm_tx, &[0; 7], &scheduler)
+                .unwrap(),
+            1
+        );
+        let packet = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(tm.service(), 11);
+        assert_eq!(tm.subservice(), GroupSubservice::TmGroupsStatusReport as u8);
+        assert_eq!(tm.source_data(), &[0, 0]);
+
+        for group_id in [3, 1, 2] {
+            scheduler.create_group(group_id).unwrap();
+        }
+        scheduler.disable_group(2).unwrap();
+        assert_eq!(
+            report_creator
+                .groups_status_report(&tm_tx, &[0; 7], &scheduler)
+                .unwrap(),
+            2
+        );
+        let packet = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(tm.source_data(), &[0, 2, 0, 1, 1, 0, 2, 0]);
+        let packet = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&packet.packet, 7).unwrap();
+        assert_eq!(tm.source_data(), &[0, 1, 0, 3, 1]);
+        assert!(tm_rx.try_recv().is_err());
+    }
 
-        let mut tc_buf: [u8; 64] = [0; 64];
-        let tc_info_vec = scheduler
-            .release_telecommands_no_deletion(&mut test_closure_1, &pool, &mut tc_buf)
-            .expect("deletion failed");
-        assert_eq!(tc_info_vec[0], tc_info_0);
-        assert_eq!(tc_info_vec[1], tc_info_1);
+    #[cfg(feature = "heapless")]
+    fn heapless_release_times<const N: usize>(
+        scheduler: &HeaplessPusScheduler<N>,
+    ) -> Vec<UnixTime> {
+        let mut release_times = Vec::new();
+        scheduler.for_each_scheduled_tc(|release_time, _| release_times.push(*release_time));
+        release_times
     }
 
     #[test]
-    fn test_generic_insert_app_data_test() {
-        let time_writer = cds::CdsTime::new_with_u16_days(1, 1);
-        let sph = SpHeader::new(
-            PacketId::new(PacketType::Tc, true, 0x002),
-            PacketSequenceCtrl::new(SequenceFlags::Unsegmented, 5),
-            0,
+    #[cfg(feature = "heapless")]
+    fn test_heapless_insert_and_release() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler: HeaplessPusScheduler<4> =
+            HeaplessPusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        assert_eq!(scheduler.capacity(), 4);
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+        let tc_info_2 = ping_tc_to_store(&mut pool, &mut buf, 2, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(200), tc_info_0)
+            .unwrap();
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_1)
+            .unwrap();
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_2)
+            .unwrap();
+        assert_eq!(scheduler.num_scheduled_telecommands(), 3);
+        assert_eq!(
+            heapless_release_times(&scheduler),
+            vec![
+                UnixTime::new_only_secs(100),
+                UnixTime::new_only_secs(100),
+                UnixTime::new_only_secs(200)
+            ]
         );
-        let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
-        let ping_tc = PusTcCreator::new_no_app_data(sph, sec_header, true);
-        let mut buf: [u8; 64] = [0; 64];
-        let result = generate_insert_telecommand_app_data(&mut buf, &time_writer, &ping_tc);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), 2 + 7 + ping_tc.len_written());
-        let n = u16::from_be_bytes(buf[0..2].try_into().unwrap());
-        assert_eq!(n, 1);
-        let time_reader = cds::CdsTime::from_bytes_with_u16_days(&buf[2..2 + 7]).unwrap();
-        assert_eq!(time_reader, time_writer);
-        let pus_tc_reader = PusTcReader::new(&buf[9..]).unwrap().0;
-        assert_eq!(pus_tc_reader, ping_tc);
+
+        scheduler.update_time(UnixTime::new_only_secs(150));
+        let mut released = Vec::new();
+        let mut tc_buf: [u8; 64] = [0; 64];
+        let released_tcs = scheduler
+            .release_telecommands_with_buffer(
+                |enabled, info, _| {
+                    assert!(enabled);
+                    released.push(*info);
+                    true
+                },
+                &mut pool,
+                &mut tc_buf,
+            )
+            .expect("releasing TCs failed");
+        assert_eq!(released_tcs, 2);
+        // Telecommands with the same release time are released in the order of insertion.
+        assert_eq!(released, vec![tc_info_1, tc_info_2]);
+        assert!(!pool.has_element_at(&tc_info_1.addr()).unwrap());
+        assert!(!pool.has_element_at(&tc_info_2.addr()).unwrap());
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+        assert!(pool.has_element_at(&tc_info_0.addr()).unwrap());
     }
 
     #[test]
-    fn test_generic_insert_app_data_test_byte_conv_error() {
-        let time_writer = cds::CdsTime::new_with_u16_days(1, 1);
-        let sph = SpHeader::new(
-            PacketId::new(PacketType::Tc, true, 0x002),
-            PacketSequenceCtrl::new(SequenceFlags::Unsegmented, 5),
-            0,
-        );
-        let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
-        let ping_tc = PusTcCreator::new_no_app_data(sph, sec_header, true);
-        let mut buf: [u8; 16] = [0; 16];
-        let result = generate_insert_telecommand_app_data(&mut buf, &time_writer, &ping_tc);
-        assert!(result.is_err());
-        let error = result.unwrap_err();
-        if let ScheduleError::ByteConversionError(ByteConversionError::ToSliceTooSmall {
-            found,
-            expected,
-        }) = error
-        {
-            assert_eq!(found, 16);
-            assert_eq!(
-                expected,
-                2 + time_writer.len_written() + ping_tc.len_written()
-            );
-        } else {
-            panic!("unexpected error {error}")
-        }
+    #[cfg(feature = "heapless")]
+    fn test_heapless_schedule_full() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler: HeaplessPusScheduler<1> =
+            HeaplessPusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let mut buf: [u8; 32] = [0; 32];
+        let tc_info_0 = ping_tc_to_store(&mut pool, &mut buf, 0, &[]);
+        let tc_info_1 = ping_tc_to_store(&mut pool, &mut buf, 1, &[]);
+        scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(100), tc_info_0)
+            .unwrap();
+        let error = scheduler
+            .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(50), tc_info_1)
+            .unwrap_err();
+        assert_eq!(error, ScheduleError::ScheduleFull);
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
     }
 
     #[test]
-    fn test_generic_insert_app_data_test_as_vec() {
-        let time_writer = cds::CdsTime::new_with_u16_days(1, 1);
-        let sph = SpHeader::new(
-            PacketId::new(PacketType::Tc, true, 0x002),
-            PacketSequenceCtrl::new(SequenceFlags::Unsegmented, 5),
-            0,
+    #[cfg(feature = "heapless")]
+    fn test_heapless_deletion_and_time_shift() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(10, 32), (5, 64)],
+            false,
+        ));
+        let mut scheduler: HeaplessPusScheduler<8> =
+            HeaplessPusScheduler::new(UnixTime::new_only_secs(0), Duration::from_secs(5));
+        let mut buf: [u8; 32] = [0; 32];
+        let mut tc_infos = Vec::new();
+        for (seq_count, release_secs) in [50, 100, 150].iter().enumerate() {
+            let tc_info = ping_tc_to_store(&mut pool, &mut buf, seq_count as u16, &[]);
+            scheduler
+                .insert_unwrapped_and_stored_tc(UnixTime::new_only_secs(*release_secs), tc_info)
+                .unwrap();
+            tc_infos.push(tc_info);
+        }
+        assert!(scheduler
+            .delete_by_request_id_and_from_pool(&tc_infos[1].request_id(), &mut pool)
+            .unwrap());
+        assert!(!pool.has_element_at(&tc_infos[1].addr()).unwrap());
+        // The first command is moved behind the last command.
+        assert!(scheduler
+            .time_shift_by_request_id(&tc_infos[0].request_id(), 110_000)
+            .unwrap());
+        assert_eq!(
+            heapless_release_times(&scheduler),
+            vec![UnixTime::new_only_secs(150), UnixTime::new_only_secs(160)]
         );
-        let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
-        let ping_tc = PusTcCreator::new_no_app_data(sph, sec_header, true);
-        let mut buf: [u8; 64] = [0; 64];
-        generate_insert_telecommand_app_data(&mut buf, &time_writer, &ping_tc).unwrap();
-        let vec = generate_insert_telecommand_app_data_as_vec(&time_writer, &ping_tc)
-            .expect("vec generation failed");
-        assert_eq!(&buf[..vec.len()], vec);
+        assert!(scheduler.time_shift_all(-150_000).is_err());
+        assert_eq!(scheduler.time_shift_all(-10_000).unwrap(), 2);
+
+        let end_stamp = cds::CdsTime::from_unix_time_with_u16_days(
+            &UnixTime::new_only_secs(145),
+            cds::SubmillisPrecision::Absent,
+        )
+        .expect("creating end stamp failed");
+        assert_eq!(
+            scheduler
+                .delete_by_time_window(TimeWindow::new_to_time(&end_stamp), &mut pool)
+                .unwrap(),
+            1
+        );
+        assert!(!pool.has_element_at(&tc_infos[2].addr()).unwrap());
+        assert_eq!(scheduler.num_scheduled_telecommands(), 1);
+        scheduler.reset(&mut pool).unwrap();
+        assert!(!scheduler.is_enabled());
+        assert_eq!(scheduler.num_scheduled_telecommands(), 0);
+        assert!(!pool.has_element_at(&tc_infos[0].addr()).unwrap());
     }
 }
@@ -1,18 +1,140 @@
-use super::scheduler::PusSchedulerProvider;
-use super::verification::{VerificationReporter, VerificationReportingProvider};
+use super::harness::{PusServiceContext, PusServiceCore};
+use super::scheduler::{
+    GroupId, GroupSubservice, InsertionChecks, PusScheduleReportCreator, PusSchedulerProvider,
+    RequestId, ScheduleError, TcInfo, TimeWindow,
+};
+use super::tm_limit::TmSizeLimit;
+use super::verification::{
+    TcStateAccepted, TcStateStarted, VerificationReporter, VerificationReportingProvider,
+    VerificationToken,
+};
 use super::{
     DirectPusPacketHandlerResult, EcssTcInMemConverter, EcssTcInSharedStoreConverter,
-    EcssTcInVecConverter, EcssTcReceiver, EcssTmSender, HandlingStatus, MpscTcReceiver,
-    PartialPusHandlingError, PusServiceHelper,
+    EcssTcInVecConverter, EcssTcReceiver, EcssTmSender, EcssTmtcError, GenericConversionError,
+    HandlingStatus, MpscTcReceiver, PartialPusHandlingError, PusServiceHelper,
 };
 use crate::pool::PoolProvider;
 use crate::pus::PusPacketHandlingError;
+use crate::time::TimestampFormat;
 use crate::tmtc::{PacketAsVec, PacketSenderWithSharedPool};
 use alloc::string::ToString;
+use satrs_shared::res_code::ResultU16;
+use spacepackets::ecss::tc::{GenericPusTcSecondaryHeader, PusTcReader};
 use spacepackets::ecss::{scheduling, PusPacket};
-use spacepackets::time::cds::CdsTime;
+use spacepackets::ByteConversionError;
 use std::sync::mpsc;
 
+/// Result codes used by the [PusSchedServiceHandler] for verification failure reports. The group
+/// ID of these codes is the PUS service number 11.
+pub mod sched_res_code {
+    use satrs_shared::res_code::ResultU16;
+
+    /// At least one request ID of a deletion request was not found in the schedule.
+    pub const REQUEST_ID_NOT_FOUND: ResultU16 = ResultU16::new(11, 0);
+    /// Deleting at least one telecommand from the TC pool failed.
+    pub const TC_POOL_DELETION_FAILED: ResultU16 = ResultU16::new(11, 1);
+    /// A time shift was rejected because a release time would end up inside the time margin or
+    /// could not be represented.
+    pub const TIME_SHIFT_REJECTED: ResultU16 = ResultU16::new(11, 2);
+    /// Generating or sending a schedule report failed.
+    pub const REPORT_GENERATION_FAILED: ResultU16 = ResultU16::new(11, 3);
+    /// At least one group ID of a group request is unknown.
+    pub const UNKNOWN_GROUP: ResultU16 = ResultU16::new(11, 4);
+    /// At least one group request was rejected, for example because the group already exists,
+    /// is not empty or the scheduler does not support groups.
+    pub const INVALID_GROUP_REQUEST: ResultU16 = ResultU16::new(11, 5);
+    /// The APID of the telecommand to insert is not allowed by the insertion checks.
+    pub const APID_NOT_ALLOWED: ResultU16 = ResultU16::new(11, 6);
+    /// The service of the telecommand to insert is not allowed by the insertion checks.
+    pub const SERVICE_NOT_ALLOWED: ResultU16 = ResultU16::new(11, 7);
+    /// The release time of the telecommand to insert lies too far in the past.
+    pub const RELEASE_TIME_IN_PAST: ResultU16 = ResultU16::new(11, 8);
+    /// The release time of the telecommand to insert lies beyond the scheduling horizon.
+    pub const RELEASE_TIME_BEYOND_HORIZON: ResultU16 = ResultU16::new(11, 9);
+    /// The release time of the telecommand to insert lies inside the time margin of the
+    /// scheduler.
+    pub const RELEASE_TIME_IN_TIME_MARGIN: ResultU16 = ResultU16::new(11, 10);
+    /// Inserting the telecommand failed for another reason, for example because the wrapped
+    /// telecommand is invalid or the schedule or the TC pool is full.
+    pub const INSERTION_FAILED: ResultU16 = ResultU16::new(11, 11);
+}
+
+/// Verification failure code for a failed insertion of a time-tagged telecommand.
+fn insertion_failure_code(error: &ScheduleError) -> ResultU16 {
+    match error {
+        ScheduleError::ApidNotAllowed(_) => sched_res_code::APID_NOT_ALLOWED,
+        ScheduleError::ServiceNotAllowed(_) => sched_res_code::SERVICE_NOT_ALLOWED,
+        ScheduleError::ReleaseTimeInPast { .. } => sched_res_code::RELEASE_TIME_IN_PAST,
+        ScheduleError::ReleaseTimeBeyondHorizon { .. } => {
+            sched_res_code::RELEASE_TIME_BEYOND_HORIZON
+        }
+        ScheduleError::ReleaseTimeInTimeMargin { .. } => {
+            sched_res_code::RELEASE_TIME_IN_TIME_MARGIN
+        }
+        ScheduleError::UnknownGroup(_) => sched_res_code::UNKNOWN_GROUP,
+        ScheduleError::GroupsNotSupported => sched_res_code::INVALID_GROUP_REQUEST,
+        _ => sched_res_code::INSERTION_FAILED,
+    }
+}
+
+/// Read the request ID list consisting of the N field as a [u16] followed by N request IDs.
+fn request_ids_from_app_data(
+    app_data: &[u8],
+) -> Result<impl Iterator<Item = RequestId> + '_, GenericConversionError> {
+    if app_data.len() < 2 {
+        return Err(GenericConversionError::NotEnoughAppData {
+            expected: 2,
+            found: app_data.len(),
+        });
+    }
+    let num_ids = u16::from_be_bytes(app_data[0..2].try_into().unwrap()) as usize;
+    let expected_len = 2 + num_ids * RequestId::RAW_LEN;
+    if app_data.len() < expected_len {
+        return Err(GenericConversionError::NotEnoughAppData {
+            expected: expected_len,
+            found: app_data.len(),
+        });
+    }
+    Ok(app_data[2..expected_len]
+        .chunks_exact(RequestId::RAW_LEN)
+        .map(|raw_id| RequestId::from_bytes(raw_id).unwrap()))
+}
+
+/// Read the group ID list consisting of the N field as a [u16] followed by N group IDs, each
+/// serialized as a big-endian [u16].
+fn group_ids_from_app_data(
+    app_data: &[u8],
+) -> Result<impl Iterator<Item = GroupId> + '_, GenericConversionError> {
+    if app_data.len() < 2 {
+        return Err(GenericConversionError::NotEnoughAppData {
+            expected: 2,
+            found: app_data.len(),
+        });
+    }
+    let num_ids = u16::from_be_bytes(app_data[0..2].try_into().unwrap()) as usize;
+    let expected_len = 2 + num_ids * 2;
+    if app_data.len() < expected_len {
+        return Err(GenericConversionError::NotEnoughAppData {
+            expected: expected_len,
+            found: app_data.len(),
+        });
+    }
+    Ok(app_data[2..expected_len]
+        .chunks_exact(2)
+        .map(|raw_id| u16::from_be_bytes(raw_id.try_into().unwrap())))
+}
+
+/// Read the time offset in milliseconds, which is serialized as a big-endian [i64].
+fn time_offset_from_app_data(app_data: &[u8]) -> Result<i64, GenericConversionError> {
+    if app_data.len() < 8 {
+        return Err(GenericConversionError::NotEnoughAppData {
+            expected: 8,
+            found: app_data.len(),
+        });
+    }
+    Ok(i64::from_be_bytes(app_data[0..8].try_into().unwrap()))
+}
+
 /// This is a helper class for [std] environments to handle generic PUS 11 (scheduling service)
 /// packets. This handler is able to handle the most important PUS requests for a scheduling
 /// service which provides the [PusSchedulerProvider].
@@ -31,8 +153,23 @@ pub struct PusSchedServiceHandler<
     pub service_helper:
         PusServiceHelper<TcReceiver, TmSender, TcInMemConverter, VerificationReporter>,
     scheduler: PusScheduler,
+    /// Maximum source data length of the generated schedule reports.
+    pub max_report_source_data_len: usize,
+    /// Format of the release times of inserted time-tagged telecommands. This is the
+    /// [TimestampFormat] of the service helper by default.
+    pub release_time_format: TimestampFormat,
+    /// Checks for inserted time-tagged telecommands, which are all disabled by default.
+    pub insertion_checks: InsertionChecks,
+    /// Whether the application data of TC[11,4] starts with a [u16] group ID field, which
+    /// assigns the inserted telecommand to an existing group. The group ID is followed by the
+    /// release time and the telecommand. Disabled by default.
+    pub group_id_field: bool,
 }
 
+/// Default maximum source data length of schedule reports generated by the
+/// [PusSchedServiceHandler].
+pub const DEFAULT_MAX_REPORT_SOURCE_DATA_LEN: usize = 1024;
+
 impl<
         TcReceiver: EcssTcReceiver,
         TmSender: EcssTmSender,
@@ -52,11 +189,35 @@ impl<
         scheduler: Scheduler,
     ) -> Self {
         Self {
+            release_time_format: service_helper.timestamp_format(),
             service_helper,
             scheduler,
+            max_report_source_data_len: DEFAULT_MAX_REPORT_SOURCE_DATA_LEN,
+            insertion_checks: InsertionChecks::default(),
+            group_id_field: false,
         }
     }
 
+    /// Configure the checks for inserted time-tagged telecommands. Rejected telecommands are
+    /// reported with a completion failure with a dedicated [sched_res_code].
+    pub fn with_insertion_checks(mut self, insertion_checks: InsertionChecks) -> Self {
+        self.insertion_checks = insertion_checks;
+        self
+    }
+
+    /// Enable the group ID field of TC[11,4], see [Self::group_id_field]. The field can only be
+    /// used with schedulers which support groups.
+    pub fn with_group_id_field(mut self) -> Self {
+        self.group_id_field = true;
+        self
+    }
+
+    /// Derive the maximum report source data length from a central [TmSizeLimit], so that the
+    /// generated reports with a timestamp of the given length never exceed the limit.
+    pub fn apply_tm_size_limit(&mut self, limit: &TmSizeLimit, time_stamp_len: usize) {
+        self.max_report_source_data_len = limit.max_source_data_len(time_stamp_len);
+    }
+
     pub fn scheduler_mut(&mut self) -> &mut Scheduler {
         &mut self.scheduler
     }
@@ -71,46 +232,126 @@ impl<
         time_stamp: &[u8],
         sched_tc_pool: &mut (impl PoolProvider + ?Sized),
     ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
-        let possible_packet = self.service_helper.retrieve_and_accept_next_packet()?;
-        if possible_packet.is_none() {
-            return Ok(HandlingStatus::Empty.into());
-        }
-        let ecss_tc_and_token = possible_packet.unwrap();
-        self.service_helper
-            .tc_in_mem_converter_mut()
-            .cache(&ecss_tc_and_token.tc_in_memory)?;
-        let tc = self.service_helper.tc_in_mem_converter().convert()?;
-        let subservice = PusPacket::subservice(&tc);
+        let mut core = PusSchedServiceCore {
+            scheduler: &mut self.scheduler,
+            sched_tc_pool,
+            max_report_source_data_len: self.max_report_source_data_len,
+            release_time_format: self.release_time_format,
+            insertion_checks: &self.insertion_checks,
+            group_id_field: self.group_id_field,
+        };
+        self.service_helper.poll_and_handle_next_tc_with_core(
+            &mut core,
+            &mut error_callback,
+            time_stamp,
+        )
+    }
+}
+
+/// [PusServiceCore] of the PUS 11 scheduling service. It is created by the
+/// [PusSchedServiceHandler] for each handled telecommand because it borrows the scheduler and
+/// the TC pool of the scheduler.
+pub struct PusSchedServiceCore<'a, Scheduler: PusSchedulerProvider, Pool: PoolProvider + ?Sized> {
+    pub scheduler: &'a mut Scheduler,
+    pub sched_tc_pool: &'a mut Pool,
+    /// Maximum source data length of the generated schedule reports.
+    pub max_report_source_data_len: usize,
+    /// Format of the release times of inserted time-tagged telecommands.
+    pub release_time_format: TimestampFormat,
+    /// Checks for inserted time-tagged telecommands.
+    pub insertion_checks: &'a InsertionChecks,
+    /// Whether the application data of TC[11,4] starts with a group ID field.
+    pub group_id_field: bool,
+}
+
+impl<
+        TmSender: EcssTmSender,
+        VerificationReporter: VerificationReportingProvider,
+        Scheduler: PusSchedulerProvider,
+        Pool: PoolProvider + ?Sized,
+    > PusServiceCore<TmSender, VerificationReporter> for PusSchedServiceCore<'_, Scheduler, Pool>
+{
+    fn service(&self) -> u8 {
+        11
+    }
+
+    fn check_subservice(&self, subservice: u8) -> bool {
+        GroupSubservice::try_from(subservice).is_ok()
+            || scheduling::Subservice::try_from(subservice).is_ok()
+    }
+
+    fn handle_request(
+        &mut self,
+        ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+        tc: &PusTcReader,
+        token: VerificationToken<TcStateAccepted>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
+        let subservice = PusPacket::subservice(tc);
+        if let Ok(group_subservice) = GroupSubservice::try_from(subservice) {
+            let group_op: fn(&mut Scheduler, GroupId) -> Result<(), ScheduleError> =
+                match group_subservice {
+                    GroupSubservice::TcCreateGroups => Scheduler::create_group,
+                    GroupSubservice::TcDeleteGroups => Scheduler::delete_group,
+                    GroupSubservice::TcEnableGroups => Scheduler::enable_group,
+                    GroupSubservice::TcDisableGroups => Scheduler::disable_group,
+                    GroupSubservice::TcReportAllGroupsStatus => {
+                        let opt_started_token = ctx.start_success(token, error_callback);
+                        let mut report_creator = self.report_creator(ctx, tc.source_id())?;
+                        let result = report_creator
+                            .groups_status_report(ctx.tm_sender, ctx.time_stamp, &*self.scheduler)
+                            .map(|_| ());
+                        Self::report_completion(ctx, opt_started_token, result, error_callback);
+                        return Ok(HandlingStatus::HandledOne.into());
+                    }
+                    GroupSubservice::TmGroupsStatusReport => {
+                        return Err(GenericConversionError::InvalidSubservice(subservice).into())
+                    }
+                };
+            let group_ids = group_ids_from_app_data(tc.user_data())?;
+            let opt_started_token = ctx.start_success(token, error_callback);
+            let mut num_success: u32 = 0;
+            let mut num_failed: u32 = 0;
+            let mut failure_code = None;
+            for group_id in group_ids {
+                match group_op(self.scheduler, group_id) {
+                    Ok(_) => num_success += 1,
+                    Err(e) => {
+                        num_failed += 1;
+                        if failure_code.is_none() {
+                            failure_code = Some(match e {
+                                ScheduleError::UnknownGroup(_) => sched_res_code::UNKNOWN_GROUP,
+                                _ => sched_res_code::INVALID_GROUP_REQUEST,
+                            });
+                        }
+                    }
+                }
+            }
+            let mut failure_data: [u8; 8] = [0; 8];
+            failure_data[0..4].copy_from_slice(&num_success.to_be_bytes());
+            failure_data[4..8].copy_from_slice(&num_failed.to_be_bytes());
+            ctx.completion(
+                opt_started_token,
+                failure_code
+                    .as_ref()
+                    .map(|code| (code, failure_data.as_slice())),
+                error_callback,
+            );
+            return Ok(HandlingStatus::HandledOne.into());
+        }
         let standard_subservice = scheduling::Subservice::try_from(subservice);
         if standard_subservice.is_err() {
             return Ok(DirectPusPacketHandlerResult::CustomSubservice(
-                subservice,
-                ecss_tc_and_token.token,
+                subservice, token,
             ));
         }
         match standard_subservice.unwrap() {
             scheduling::Subservice::TcEnableScheduling => {
-                let opt_started_token = match self.service_helper.verif_reporter().start_success(
-                    &self.service_helper.common.tm_sender,
-                    ecss_tc_and_token.token,
-                    time_stamp,
-                ) {
-                    Ok(started_token) => Some(started_token),
-                    Err(e) => {
-                        error_callback(&PartialPusHandlingError::Verification(e));
-                        None
-                    }
-                };
+                let opt_started_token = ctx.start_success(token, error_callback);
                 self.scheduler.enable();
 
                 if self.scheduler.is_enabled() && opt_started_token.is_some() {
-                    if let Err(e) = self.service_helper.verif_reporter().completion_success(
-                        &self.service_helper.common.tm_sender,
-                        opt_started_token.unwrap(),
-                        time_stamp,
-                    ) {
-                        error_callback(&PartialPusHandlingError::Verification(e));
-                    }
+                    ctx.completion_success(opt_started_token, error_callback);
                 } else {
                     return Err(PusPacketHandlingError::Other(
                         "failed to enabled scheduler".to_string(),
@@ -118,27 +359,11 @@ impl<
                 }
             }
             scheduling::Subservice::TcDisableScheduling => {
-                let opt_started_token = match self.service_helper.verif_reporter().start_success(
-                    &self.service_helper.common.tm_sender,
-                    ecss_tc_and_token.token,
-                    time_stamp,
-                ) {
-                    Ok(started_token) => Some(started_token),
-                    Err(e) => {
-                        error_callback(&PartialPusHandlingError::Verification(e));
-                        None
-                    }
-                };
+                let opt_started_token = ctx.start_success(token, error_callback);
 
                 self.scheduler.disable();
                 if !self.scheduler.is_enabled() && opt_started_token.is_some() {
-                    if let Err(e) = self.service_helper.verif_reporter().completion_success(
-                        &self.service_helper.common.tm_sender,
-                        opt_started_token.unwrap(),
-                        time_stamp,
-                    ) {
-                        error_callback(&PartialPusHandlingError::Verification(e));
-                    }
+                    ctx.completion_success(opt_started_token, error_callback);
                 } else {
                     return Err(PusPacketHandlingError::Other(
                         "failed to disable scheduler".to_string(),
@@ -146,66 +371,273 @@ impl<
                 }
             }
             scheduling::Subservice::TcResetScheduling => {
-                let start_token = self
-                    .service_helper
-                    .verif_reporter()
-                    .start_success(
-                        &self.service_helper.common.tm_sender,
-                        ecss_tc_and_token.token,
-                        time_stamp,
-                    )
+                let start_token = ctx
+                    .verif_reporter
+                    .start_success(ctx.tm_sender, token, ctx.time_stamp)
                     .expect("Error sending start success");
 
                 self.scheduler
-                    .reset(sched_tc_pool)
+                    .reset(self.sched_tc_pool)
                     .expect("Error resetting TC Pool");
 
-                self.service_helper
-                    .verif_reporter()
-                    .completion_success(
-                        &self.service_helper.common.tm_sender,
-                        start_token,
-                        time_stamp,
-                    )
+                ctx.verif_reporter
+                    .completion_success(ctx.tm_sender, start_token, ctx.time_stamp)
                     .expect("Error sending completion success");
             }
             scheduling::Subservice::TcInsertActivity => {
-                let start_token = self
-                    .service_helper
-                    .common
-                    .verif_reporter
-                    .start_success(
-                        &self.service_helper.common.tm_sender,
-                        ecss_tc_and_token.token,
-                        time_stamp,
-                    )
-                    .expect("error sending start success");
-
-                // let mut pool = self.sched_tc_pool.write().expect("locking pool failed");
-                self.scheduler
-                    .insert_wrapped_tc::<CdsTime>(&tc, sched_tc_pool)
-                    .expect("insertion of activity into pool failed");
-
-                self.service_helper
-                    .verif_reporter()
-                    .completion_success(
-                        &self.service_helper.common.tm_sender,
-                        start_token,
-                        time_stamp,
+                let opt_started_token = ctx.start_success(token, error_callback);
+                match self.insert_activity(tc) {
+                    Ok(_) => ctx.completion_success(opt_started_token, error_callback),
+                    Err(e) => ctx.completion_failure(
+                        opt_started_token,
+                        &insertion_failure_code(&e),
+                        &[],
+                        error_callback,
+                    ),
+                }
+            }
+            scheduling::Subservice::TcDeleteActivityByRequestId => {
+                let request_ids = request_ids_from_app_data(tc.user_data())?;
+                let opt_started_token = ctx.start_success(token, error_callback);
+                let mut num_deleted: u32 = 0;
+                let mut num_not_found: u32 = 0;
+                let mut num_pool_errors: u32 = 0;
+                for request_id in request_ids {
+                    match self
+                        .scheduler
+                        .delete_by_request_id_and_from_pool(&request_id, self.sched_tc_pool)
+                    {
+                        Ok(true) => num_deleted += 1,
+                        Ok(false) => num_not_found += 1,
+                        Err(_) => num_pool_errors += 1,
+                    }
+                }
+                let failure_code = if num_pool_errors > 0 {
+                    Some(sched_res_code::TC_POOL_DELETION_FAILED)
+                } else if num_not_found > 0 {
+                    Some(sched_res_code::REQUEST_ID_NOT_FOUND)
+                } else {
+                    None
+                };
+                let mut failure_data: [u8; 8] = [0; 8];
+                failure_data[0..4].copy_from_slice(&num_deleted.to_be_bytes());
+                failure_data[4..8]
+                    .copy_from_slice(&(num_not_found + num_pool_errors).to_be_bytes());
+                ctx.completion(
+                    opt_started_token,
+                    failure_code
+                        .as_ref()
+                        .map(|code| (code, failure_data.as_slice())),
+                    error_callback,
+                );
+            }
+            scheduling::Subservice::TcDeleteActivitiesByFilter => {
+                let time_window = TimeWindow::<Scheduler::TimeProvider>::from_bytes(tc.user_data())
+                    .map_err(|e| GenericConversionError::InvalidAppData(e.to_string()))?;
+                let opt_started_token = ctx.start_success(token, error_callback);
+                match self
+                    .scheduler
+                    .delete_by_time_window(time_window, self.sched_tc_pool)
+                {
+                    Ok(_) => ctx.completion_success(opt_started_token, error_callback),
+                    Err((num_deleted, _)) => {
+                        let failure_data = (num_deleted as u32).to_be_bytes();
+                        ctx.completion_failure(
+                            opt_started_token,
+                            &sched_res_code::TC_POOL_DELETION_FAILED,
+                            failure_data.as_slice(),
+                            error_callback,
+                        );
+                    }
+                }
+            }
+            scheduling::Subservice::TcTimeShiftAll => {
+                let offset_ms = time_offset_from_app_data(tc.user_data())?;
+                let opt_started_token = ctx.start_success(token, error_callback);
+                let result = self.scheduler.time_shift_all(offset_ms);
+                Self::time_shift_completion(ctx, opt_started_token, result, error_callback);
+            }
+            scheduling::Subservice::TcTimeShiftActivitiesByFilter => {
+                let offset_ms = time_offset_from_app_data(tc.user_data())?;
+                let time_window =
+                    TimeWindow::<Scheduler::TimeProvider>::from_bytes(&tc.user_data()[8..])
+                        .map_err(|e| GenericConversionError::InvalidAppData(e.to_string()))?;
+                let opt_started_token = ctx.start_success(token, error_callback);
+                let result = self
+                    .scheduler
+                    .time_shift_by_time_window(time_window, offset_ms);
+                Self::time_shift_completion(ctx, opt_started_token, result, error_callback);
+            }
+            scheduling::Subservice::TcTimeShiftActivityWithRequestId => {
+                let offset_ms = time_offset_from_app_data(tc.user_data())?;
+                let request_ids = request_ids_from_app_data(&tc.user_data()[8..])?;
+                let opt_started_token = ctx.start_success(token, error_callback);
+                let mut num_shifted: u32 = 0;
+                let mut num_not_found: u32 = 0;
+                let mut num_rejected: u32 = 0;
+                for request_id in request_ids {
+                    match self
+                        .scheduler
+                        .time_shift_by_request_id(&request_id, offset_ms)
+                    {
+                        Ok(true) => num_shifted += 1,
+                        Ok(false) => num_not_found += 1,
+                        Err(_) => num_rejected += 1,
+                    }
+                }
+                let failure_code = if num_rejected > 0 {
+                    Some(sched_res_code::TIME_SHIFT_REJECTED)
+                } else if num_not_found > 0 {
+                    Some(sched_res_code::REQUEST_ID_NOT_FOUND)
+                } else {
+                    None
+                };
+                let mut failure_data: [u8; 8] = [0; 8];
+                failure_data[0..4].copy_from_slice(&num_shifted.to_be_bytes());
+                failure_data[4..8].copy_from_slice(&(num_not_found + num_rejected).to_be_bytes());
+                ctx.completion(
+                    opt_started_token,
+                    failure_code
+                        .as_ref()
+                        .map(|code| (code, failure_data.as_slice())),
+                    error_callback,
+                );
+            }
+            scheduling::Subservice::TcSummaryReportAll => {
+                let opt_started_token = ctx.start_success(token, error_callback);
+                let mut report_creator = self.report_creator(ctx, tc.source_id())?;
+                let result =
+                    report_creator.summary_report(ctx.tm_sender, ctx.time_stamp, &*self.scheduler);
+                Self::report_completion(ctx, opt_started_token, result, error_callback);
+            }
+            scheduling::Subservice::TcDetailReportAll => {
+                let opt_started_token = ctx.start_success(token, error_callback);
+                let mut report_creator = self.report_creator(ctx, tc.source_id())?;
+                let result = report_creator
+                    .detailed_report_with_tcs(
+                        ctx.tm_sender,
+                        ctx.time_stamp,
+                        &*self.scheduler,
+                        &*self.sched_tc_pool,
                     )
-                    .expect("sending completion success failed");
+                    .map(|_| ());
+                Self::report_completion(ctx, opt_started_token, result, error_callback);
             }
             _ => {
                 // Treat unhandled standard subservices as custom subservices for now.
                 return Ok(DirectPusPacketHandlerResult::CustomSubservice(
-                    subservice,
-                    ecss_tc_and_token.token,
+                    subservice, token,
                 ));
             }
         }
         Ok(HandlingStatus::HandledOne.into())
     }
 }
+
+impl<Scheduler: PusSchedulerProvider, Pool: PoolProvider + ?Sized>
+    PusSchedServiceCore<'_, Scheduler, Pool>
+{
+    /// Check the time-tagged telecommand wrapped inside a TC[11,4] telecommand against the
+    /// insertion checks and insert it into the schedule.
+    fn insert_activity(&mut self, tc: &PusTcReader) -> Result<TcInfo, ScheduleError> {
+        let mut user_data = tc.user_data();
+        if user_data.is_empty() {
+            return Err(ScheduleError::TcDataEmpty);
+        }
+        let mut group_id = None;
+        if self.group_id_field {
+            if user_data.len() < 2 {
+                return Err(ByteConversionError::FromSliceTooSmall {
+                    found: user_data.len(),
+                    expected: 2,
+                }
+                .into());
+            }
+            group_id = Some(GroupId::from_be_bytes(user_data[0..2].try_into().unwrap()));
+            user_data = &user_data[2..];
+        }
+        let (release_time, stamp_len) = self.release_time_format.read_stamp(user_data)?;
+        let raw_tc = &user_data[stamp_len..];
+        let (wrapped_tc, _) = PusTcReader::new(raw_tc)?;
+        self.insertion_checks.check(
+            self.scheduler.scheduler_time().as_ref(),
+            &release_time,
+            &wrapped_tc,
+        )?;
+        match group_id {
+            Some(group_id) => self.scheduler.insert_unwrapped_tc_in_group(
+                release_time,
+                raw_tc,
+                group_id,
+                self.sched_tc_pool,
+            ),
+            None => self
+                .scheduler
+                .insert_unwrapped_tc(release_time, raw_tc, self.sched_tc_pool),
+        }
+    }
+
+    fn time_shift_completion<
+        TmSender: EcssTmSender,
+        VerificationReporter: VerificationReportingProvider,
+    >(
+        ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+        opt_started_token: Option<VerificationToken<TcStateStarted>>,
+        result: Result<u64, ScheduleError>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) {
+        match result {
+            Ok(_) => ctx.completion_success(opt_started_token, error_callback),
+            Err(_) => ctx.completion_failure(
+                opt_started_token,
+                &sched_res_code::TIME_SHIFT_REJECTED,
+                &[],
+                error_callback,
+            ),
+        }
+    }
+
+    fn report_creator<
+        TmSender: EcssTmSender,
+        VerificationReporter: VerificationReportingProvider,
+    >(
+        &self,
+        ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+        dest_id: u16,
+    ) -> Result<PusScheduleReportCreator, PusPacketHandlingError> {
+        PusScheduleReportCreator::new(
+            ctx.id,
+            ctx.verif_reporter.apid(),
+            dest_id,
+            self.max_report_source_data_len,
+        )
+        .ok_or_else(|| PusPacketHandlingError::Other("invalid report APID".to_string()))
+    }
+
+    fn report_completion<
+        TmSender: EcssTmSender,
+        VerificationReporter: VerificationReportingProvider,
+    >(
+        ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+        opt_started_token: Option<VerificationToken<TcStateStarted>>,
+        result: Result<(), EcssTmtcError>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) {
+        match result {
+            Ok(_) => ctx.completion_success(opt_started_token, error_callback),
+            Err(e) => {
+                error_callback(&PartialPusHandlingError::TmSend(e));
+                ctx.completion_failure(
+                    opt_started_token,
+                    &sched_res_code::REPORT_GENERATION_FAILED,
+                    &[],
+                    error_callback,
+                )
+            }
+        }
+    }
+}
+
 /// Helper type definition for a PUS 11 handler with a dynamic TMTC memory backend and regular
 /// mpsc queues.
 pub type PusService11SchedHandlerDynWithMpsc<PusScheduler> = PusSchedServiceHandler<
@@ -245,23 +677,30 @@ pub type PusService11SchedHandlerStaticWithBoundedMpsc<PusScheduler> = PusSchedS
 
 #[cfg(test)]
 mod tests {
-    use crate::pool::{StaticMemoryPool, StaticPoolConfig};
+    use crate::pool::{PoolProvider, StaticMemoryPool, StaticPoolConfig};
     use crate::pus::test_util::{PusTestHarness, TEST_APID};
     use crate::pus::verification::{VerificationReporter, VerificationReportingProvider};
 
     use crate::pus::{
-        scheduler::{self, PusSchedulerProvider, TcInfo},
+        scheduler::{
+            self, GroupId, GroupSubservice, InsertionChecks, PusSchedulerProvider, TcInfo,
+            TimeWindow,
+        },
         tests::PusServiceHandlerWithSharedStoreCommon,
         verification::{RequestId, TcStateAccepted, VerificationToken},
         EcssTcInSharedStoreConverter,
     };
-    use crate::pus::{DirectPusPacketHandlerResult, MpscTcReceiver, PusPacketHandlingError};
+    use crate::pus::{
+        DirectPusPacketHandlerResult, GenericConversionError, MpscTcReceiver,
+        PusPacketHandlingError,
+    };
     use crate::tmtc::PacketSenderWithSharedPool;
-    use alloc::collections::VecDeque;
+    use alloc::collections::{BTreeMap, VecDeque};
+    use core::time::Duration;
     use delegate::delegate;
     use spacepackets::ecss::scheduling::Subservice;
     use spacepackets::ecss::tc::PusTcSecondaryHeader;
-    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::ecss::{PusPacket, WritablePusPacket};
     use spacepackets::time::TimeWriter;
     use spacepackets::SpHeader;
     use spacepackets::{
@@ -269,7 +708,8 @@ mod tests {
         time::cds,
     };
 
-    use super::PusSchedServiceHandler;
+    use super::{sched_res_code, PusSchedServiceHandler};
+    use satrs_shared::res_code::ResultU16;
 
     struct Pus11HandlerWithStoreTester {
         common: PusServiceHandlerWithSharedStoreCommon,
@@ -338,7 +778,12 @@ mod tests {
         enabled: bool,
         enabled_count: u32,
         disabled_count: u32,
+        time_window_deletion_count: u32,
+        reject_time_shifts: bool,
+        time_shift_offsets: VecDeque<i64>,
         inserted_tcs: VecDeque<TcInfo>,
+        groups: BTreeMap<GroupId, bool>,
+        current_time: Option<spacepackets::time::UnixTime>,
     }
 
     impl PusSchedulerProvider for TestScheduler {
@@ -366,14 +811,122 @@ mod tests {
             self.enabled = false;
         }
 
+        fn scheduler_time(&self) -> Option<spacepackets::time::UnixTime> {
+            self.current_time
+        }
+
         fn insert_unwrapped_and_stored_tc(
             &mut self,
             _time_stamp: spacepackets::time::UnixTime,
             info: crate::pus::scheduler::TcInfo,
         ) -> Result<(), crate::pus::scheduler::ScheduleError> {
+            if let Some(group_id) = info.group_id() {
+                if !self.groups.contains_key(&group_id) {
+                    return Err(scheduler::ScheduleError::UnknownGroup(group_id));
+                }
+            }
             self.inserted_tcs.push_back(info);
             Ok(())
         }
+
+        fn delete_by_request_id_and_from_pool(
+            &mut self,
+            req_id: &scheduler::RequestId,
+            _pool: &mut (impl crate::pool::PoolProvider + ?Sized),
+        ) -> Result<bool, crate::pool::PoolError> {
+            match self
+                .inserted_tcs
+                .iter()
+                .position(|info| info.request_id() == *req_id)
+            {
+                Some(idx) => {
+                    self.inserted_tcs.remove(idx);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+
+        fn delete_by_time_window(
+            &mut self,
+            _time_window: TimeWindow<Self::TimeProvider>,
+            _pool: &mut (impl crate::pool::PoolProvider + ?Sized),
+        ) -> Result<u64, (u64, crate::pool::PoolError)> {
+            self.time_window_deletion_count += 1;
+            let num_deleted = self.inserted_tcs.len() as u64;
+            self.inserted_tcs.clear();
+            Ok(num_deleted)
+        }
+
+        fn time_shift_all(&mut self, offset_ms: i64) -> Result<u64, scheduler::ScheduleError> {
+            if self.reject_time_shifts {
+                return Err(scheduler::ScheduleError::TimeShiftOverflow(offset_ms));
+            }
+            self.time_shift_offsets.push_back(offset_ms);
+            Ok(self.inserted_tcs.len() as u64)
+        }
+
+        fn time_shift_by_request_id(
+            &mut self,
+            req_id: &scheduler::RequestId,
+            offset_ms: i64,
+        ) -> Result<bool, scheduler::ScheduleError> {
+            if self.reject_time_shifts {
+                return Err(scheduler::ScheduleError::TimeShiftOverflow(offset_ms));
+            }
+            if self
+                .inserted_tcs
+                .iter()
+                .any(|info| info.request_id() == *req_id)
+            {
+                self.time_shift_offsets.push_back(offset_ms);
+                return Ok(true);
+            }
+            Ok(false)
+        }
+
+        fn time_shift_by_time_window(
+            &mut self,
+            _time_window: TimeWindow<Self::TimeProvider>,
+            offset_ms: i64,
+        ) -> Result<u64, scheduler::ScheduleError> {
+            self.time_shift_all(offset_ms)
+        }
+
+        fn for_each_scheduled_tc(&self, mut f: impl FnMut(&spacepackets::time::UnixTime, &TcInfo)) {
+            for info in &self.inserted_tcs {
+                f(&spacepackets::time::UnixTime::new_only_secs(0), info);
+            }
+        }
+
+        fn create_group(&mut self, group_id: GroupId) -> Result<(), scheduler::ScheduleError> {
+            if self.groups.insert(group_id, true).is_some() {
+                return Err(scheduler::ScheduleError::GroupAlreadyExists(group_id));
+            }
+            Ok(())
+        }
+
+        fn enable_group(&mut self, group_id: GroupId) -> Result<(), scheduler::ScheduleError> {
+            *self
+                .groups
+                .get_mut(&group_id)
+                .ok_or(scheduler::ScheduleError::UnknownGroup(group_id))? = true;
+            Ok(())
+        }
+
+        fn disable_group(&mut self, group_id: GroupId) -> Result<(), scheduler::ScheduleError> {
+            *self
+                .groups
+                .get_mut(&group_id)
+                .ok_or(scheduler::ScheduleError::UnknownGroup(group_id))? = false;
+            Ok(())
+        }
+
+        fn for_each_group(&self, mut f: impl FnMut(GroupId, bool)) {
+            for (group_id, enabled) in &self.groups {
+                f(*group_id, *enabled);
+            }
+        }
     }
 
     fn generic_subservice_send(
@@ -461,4 +1014,552 @@ mod tests {
             .unwrap();
         assert_eq!(tc_info.request_id(), req_id_ping_tc);
     }
+
+    fn send_insert_activity_tc(
+        test_harness: &mut Pus11HandlerWithStoreTester,
+        apid: u16,
+        service: u8,
+        release_time: &cds::CdsTime,
+    ) -> RequestId {
+        send_insert_activity_tc_generic(test_harness, None, apid, service, release_time)
+    }
+
+    fn send_insert_activity_tc_generic(
+        test_harness: &mut Pus11HandlerWithStoreTester,
+        group_id: Option<GroupId>,
+        apid: u16,
+        service: u8,
+        release_time: &cds::CdsTime,
+    ) -> RequestId {
+        let tc_header = SpHeader::new_for_unseg_tc(apid, 0, 0);
+        let tc = PusTcCreator::new_simple(tc_header, service, 1, &[], true);
+        let mut app_data: [u8; 64] = [0; 64];
+        let mut written_len = 0;
+        if let Some(group_id) = group_id {
+            app_data[0..2].copy_from_slice(&group_id.to_be_bytes());
+            written_len += 2;
+        }
+        written_len += release_time
+            .write_to_bytes(&mut app_data[written_len..])
+            .unwrap();
+        written_len += tc.write_to_bytes(&mut app_data[written_len..]).unwrap();
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 1, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(11, Subservice::TcInsertActivity as u8);
+        let insert_tc = PusTcCreator::new(tc_header, sec_header, &app_data[..written_len], true);
+        let token = test_harness.init_verification(&insert_tc);
+        test_harness.send_tc(&token, &insert_tc);
+        test_harness.handle_one_tc().unwrap();
+        token.request_id()
+    }
+
+    #[test]
+    fn test_insert_activity_in_group() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        test_harness.handler = test_harness.handler.with_group_id_field();
+        test_harness.handler.scheduler_mut().groups.insert(1, true);
+        let release_time = cds::CdsTime::new_with_u16_days(1, 0);
+        let request_id = send_insert_activity_tc_generic(
+            &mut test_harness,
+            Some(2),
+            TEST_APID,
+            17,
+            &release_time,
+        );
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        check_completion_failure(&mut test_harness, request_id, sched_res_code::UNKNOWN_GROUP);
+        assert!(test_harness.handler.scheduler().inserted_tcs.is_empty());
+
+        let request_id = send_insert_activity_tc_generic(
+            &mut test_harness,
+            Some(1),
+            TEST_APID,
+            17,
+            &release_time,
+        );
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        test_harness.check_next_verification_tm(7, request_id);
+        let tc_info = test_harness
+            .handler
+            .scheduler_mut()
+            .inserted_tcs
+            .pop_front()
+            .unwrap();
+        assert_eq!(tc_info.group_id(), Some(1));
+    }
+
+    #[test]
+    fn test_insert_activity_checks() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        let current_time = spacepackets::time::UnixTime::new_only_secs(1000);
+        test_harness.handler.scheduler_mut().current_time = Some(current_time);
+        test_harness.handler.insertion_checks = InsertionChecks::default()
+            .with_allowed_apids(&[TEST_APID])
+            .with_allowed_services(&[17])
+            .with_past_margin(Duration::from_secs(10))
+            .with_horizon(Duration::from_secs(100));
+        let release_time = |secs: i64| {
+            cds::CdsTime::from_unix_time_with_u16_days(
+                &spacepackets::time::UnixTime::new_only_secs(secs),
+                cds::SubmillisPrecision::Absent,
+            )
+            .unwrap()
+        };
+        let cases = [
+            (TEST_APID + 1, 17, 1050, sched_res_code::APID_NOT_ALLOWED),
+            (TEST_APID, 3, 1050, sched_res_code::SERVICE_NOT_ALLOWED),
+            (TEST_APID, 17, 980, sched_res_code::RELEASE_TIME_IN_PAST),
+            (
+                TEST_APID,
+                17,
+                1200,
+                sched_res_code::RELEASE_TIME_BEYOND_HORIZON,
+            ),
+        ];
+        for (apid, service, release_secs, failure_code) in cases {
+            let request_id = send_insert_activity_tc(
+                &mut test_harness,
+                apid,
+                service,
+                &release_time(release_secs),
+            );
+            test_harness.check_next_verification_tm(1, request_id);
+            test_harness.check_next_verification_tm(3, request_id);
+            check_completion_failure(&mut test_harness, request_id, failure_code);
+        }
+        assert!(test_harness.handler.scheduler().inserted_tcs.is_empty());
+
+        let request_id =
+            send_insert_activity_tc(&mut test_harness, TEST_APID, 17, &release_time(1050));
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        test_harness.check_next_verification_tm(7, request_id);
+        assert_eq!(test_harness.handler.scheduler().inserted_tcs.len(), 1);
+    }
+
+    fn insert_test_tcs(test_harness: &mut Pus11HandlerWithStoreTester, num_tcs: u16) {
+        for seq_count in 0..num_tcs {
+            let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, seq_count, 0);
+            let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
+            let ping_tc = PusTcCreator::new(tc_header, sec_header, &[], true);
+            test_harness
+                .handler
+                .scheduler_mut()
+                .insert_unwrapped_and_stored_tc(
+                    spacepackets::time::UnixTime::new_only_secs(0),
+                    TcInfo::new(seq_count as u64, scheduler::RequestId::from_tc(&ping_tc)),
+                )
+                .unwrap();
+        }
+    }
+
+    fn send_delete_by_request_id_tc(
+        test_harness: &mut Pus11HandlerWithStoreTester,
+        request_ids: &[scheduler::RequestId],
+    ) -> RequestId {
+        let mut app_data: [u8; 64] = [0; 64];
+        let app_data_len =
+            scheduler::generate_delete_by_request_id_app_data(&mut app_data, request_ids).unwrap();
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 100, 0);
+        let sec_header =
+            PusTcSecondaryHeader::new_simple(11, Subservice::TcDeleteActivityByRequestId as u8);
+        let delete_tc = PusTcCreator::new(tc_header, sec_header, &app_data[..app_data_len], true);
+        let token = test_harness.init_verification(&delete_tc);
+        test_harness.send_tc(&token, &delete_tc);
+        test_harness.handle_one_tc().unwrap();
+        token.request_id()
+    }
+
+    #[test]
+    fn test_delete_by_request_id_tc() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        insert_test_tcs(&mut test_harness, 2);
+        let to_delete = test_harness.handler.scheduler().inserted_tcs[0].request_id();
+        let request_id = send_delete_by_request_id_tc(&mut test_harness, &[to_delete]);
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        test_harness.check_next_verification_tm(7, request_id);
+        assert!(test_harness.check_no_tm_available());
+        let scheduler = test_harness.handler.scheduler();
+        assert_eq!(scheduler.inserted_tcs.len(), 1);
+        assert_ne!(scheduler.inserted_tcs[0].request_id(), to_delete);
+    }
+
+    #[test]
+    fn test_delete_by_request_id_tc_partial_failure() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        insert_test_tcs(&mut test_harness, 1);
+        let to_delete = test_harness.handler.scheduler().inserted_tcs[0].request_id();
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 20, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
+        let unknown_tc = PusTcCreator::new(tc_header, sec_header, &[], true);
+        let request_id = send_delete_by_request_id_tc(
+            &mut test_harness,
+            &[to_delete, scheduler::RequestId::from_tc(&unknown_tc)],
+        );
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        let failure_tm = test_harness.read_next_tm();
+        assert_eq!(failure_tm.service(), 1);
+        assert_eq!(failure_tm.subservice(), 8);
+        let user_data = failure_tm.user_data();
+        assert_eq!(
+            RequestId::from_bytes(user_data).unwrap(),
+            request_id,
+            "unexpected request ID"
+        );
+        let failure_code = u16::from_be_bytes(user_data[4..6].try_into().unwrap());
+        assert_eq!(failure_code, sched_res_code::REQUEST_ID_NOT_FOUND.raw());
+        let failure_data = &user_data[6..];
+        assert_eq!(
+            u32::from_be_bytes(failure_data[0..4].try_into().unwrap()),
+            1
+        );
+        assert_eq!(
+            u32::from_be_bytes(failure_data[4..8].try_into().unwrap()),
+            1
+        );
+        assert!(test_harness.handler.scheduler().inserted_tcs.is_empty());
+    }
+
+    #[test]
+    fn test_delete_by_request_id_tc_app_data_too_short() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header =
+            PusTcSecondaryHeader::new_simple(11, Subservice::TcDeleteActivityByRequestId as u8);
+        // N is 1, but no request ID follows.
+        let delete_tc = PusTcCreator::new(tc_header, sec_header, &[0, 1], true);
+        let token = test_harness.init_verification(&delete_tc);
+        test_harness.send_tc(&token, &delete_tc);
+        let result = test_harness.handle_one_tc();
+        assert!(result.is_err());
+        if let Err(PusPacketHandlingError::RequestConversion(
+            GenericConversionError::NotEnoughAppData { expected, found },
+        )) = result
+        {
+            assert_eq!(expected, 8);
+            assert_eq!(found, 2);
+        } else {
+            panic!("unexpected result {:?}", result);
+        }
+    }
+
+    #[test]
+    fn test_delete_by_time_window_tc() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        insert_test_tcs(&mut test_harness, 2);
+        let end_stamp = cds::CdsTime::new_with_u16_days(1, 0);
+        let mut app_data: [u8; 32] = [0; 32];
+        let app_data_len = TimeWindow::new_to_time(&end_stamp)
+            .write_to_bytes(&mut app_data)
+            .unwrap();
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header =
+            PusTcSecondaryHeader::new_simple(11, Subservice::TcDeleteActivitiesByFilter as u8);
+        let delete_tc = PusTcCreator::new(tc_header, sec_header, &app_data[..app_data_len], true);
+        let token = test_harness.init_verification(&delete_tc);
+        test_harness.send_tc(&token, &delete_tc);
+        test_harness.handle_one_tc().unwrap();
+        let request_id = token.request_id();
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        test_harness.check_next_verification_tm(7, request_id);
+        let scheduler = test_harness.handler.scheduler();
+        assert_eq!(scheduler.time_window_deletion_count, 1);
+        assert!(scheduler.inserted_tcs.is_empty());
+    }
+
+    #[test]
+    fn test_delete_by_time_window_tc_invalid_type() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header =
+            PusTcSecondaryHeader::new_simple(11, Subservice::TcDeleteActivitiesByFilter as u8);
+        let delete_tc = PusTcCreator::new(tc_header, sec_header, &7_u32.to_be_bytes(), true);
+        let token = test_harness.init_verification(&delete_tc);
+        test_harness.send_tc(&token, &delete_tc);
+        let result = test_harness.handle_one_tc();
+        assert!(matches!(
+            result,
+            Err(PusPacketHandlingError::RequestConversion(
+                GenericConversionError::InvalidAppData(_)
+            ))
+        ));
+        assert_eq!(
+            test_harness.handler.scheduler().time_window_deletion_count,
+            0
+        );
+    }
+
+    fn send_time_shift_tc(
+        test_harness: &mut Pus11HandlerWithStoreTester,
+        subservice: Subservice,
+        app_data: &[u8],
+    ) -> RequestId {
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(11, subservice as u8);
+        let time_shift_tc = PusTcCreator::new(tc_header, sec_header, app_data, true);
+        let token = test_harness.init_verification(&time_shift_tc);
+        test_harness.send_tc(&token, &time_shift_tc);
+        test_harness.handle_one_tc().unwrap();
+        token.request_id()
+    }
+
+    fn check_completion_failure(
+        test_harness: &mut Pus11HandlerWithStoreTester,
+        request_id: RequestId,
+        failure_code: ResultU16,
+    ) {
+        let failure_tm = test_harness.read_next_tm();
+        assert_eq!(failure_tm.service(), 1);
+        assert_eq!(failure_tm.subservice(), 8);
+        let user_data = failure_tm.user_data();
+        assert_eq!(RequestId::from_bytes(user_data).unwrap(), request_id);
+        assert_eq!(
+            u16::from_be_bytes(user_data[4..6].try_into().unwrap()),
+            failure_code.raw()
+        );
+    }
+
+    #[test]
+    fn test_time_shift_all_tc() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        let request_id = send_time_shift_tc(
+            &mut test_harness,
+            Subservice::TcTimeShiftAll,
+            &(-2000_i64).to_be_bytes(),
+        );
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        test_harness.check_next_verification_tm(7, request_id);
+        assert_eq!(
+            test_harness
+                .handler
+                .scheduler_mut()
+                .time_shift_offsets
+                .pop_front()
+                .unwrap(),
+            -2000
+        );
+    }
+
+    #[test]
+    fn test_time_shift_all_tc_rejected() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        test_harness.handler.scheduler_mut().reject_time_shifts = true;
+        let request_id = send_time_shift_tc(
+            &mut test_harness,
+            Subservice::TcTimeShiftAll,
+            &1000_i64.to_be_bytes(),
+        );
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        check_completion_failure(
+            &mut test_harness,
+            request_id,
+            sched_res_code::TIME_SHIFT_REJECTED,
+        );
+    }
+
+    #[test]
+    fn test_time_shift_by_filter_tc() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        let mut app_data: [u8; 32] = [0; 32];
+        app_data[0..8].copy_from_slice(&5000_i64.to_be_bytes());
+        let window_len = TimeWindow::<cds::CdsTime>::new_select_all()
+            .write_to_bytes(&mut app_data[8..])
+            .unwrap();
+        let request_id = send_time_shift_tc(
+            &mut test_harness,
+            Subservice::TcTimeShiftActivitiesByFilter,
+            &app_data[..8 + window_len],
+        );
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        test_harness.check_next_verification_tm(7, request_id);
+        assert_eq!(
+            test_harness
+                .handler
+                .scheduler_mut()
+                .time_shift_offsets
+                .pop_front()
+                .unwrap(),
+            5000
+        );
+    }
+
+    #[test]
+    fn test_time_shift_by_request_id_tc_partial_failure() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        insert_test_tcs(&mut test_harness, 1);
+        let to_shift = test_harness.handler.scheduler().inserted_tcs[0].request_id();
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 20, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
+        let unknown_tc = PusTcCreator::new(tc_header, sec_header, &[], true);
+        let mut app_data: [u8; 32] = [0; 32];
+        let app_data_len = scheduler::generate_time_shift_by_request_id_app_data(
+            &mut app_data,
+            1000,
+            &[to_shift, scheduler::RequestId::from_tc(&unknown_tc)],
+        )
+        .unwrap();
+        let request_id = send_time_shift_tc(
+            &mut test_harness,
+            Subservice::TcTimeShiftActivityWithRequestId,
+            &app_data[..app_data_len],
+        );
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        check_completion_failure(
+            &mut test_harness,
+            request_id,
+            sched_res_code::REQUEST_ID_NOT_FOUND,
+        );
+        assert_eq!(test_harness.handler.scheduler().time_shift_offsets.len(), 1);
+    }
+
+    #[test]
+    fn test_time_shift_tc_app_data_too_short() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(11, Subservice::TcTimeShiftAll as u8);
+        let time_shift_tc = PusTcCreator::new(tc_header, sec_header, &[0; 4], true);
+        let token = test_harness.init_verification(&time_shift_tc);
+        test_harness.send_tc(&token, &time_shift_tc);
+        let result = test_harness.handle_one_tc();
+        assert!(matches!(
+            result,
+            Err(PusPacketHandlingError::RequestConversion(
+                GenericConversionError::NotEnoughAppData {
+                    expected: 8,
+                    found: 4
+                }
+            ))
+        ));
+        assert!(test_harness
+            .handler
+            .scheduler()
+            .time_shift_offsets
+            .is_empty());
+    }
+
+    #[test]
+    fn test_summary_report_tc() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        insert_test_tcs(&mut test_harness, 3);
+        let request_id = send_time_shift_tc(&mut test_harness, Subservice::TcSummaryReportAll, &[]);
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        let report = test_harness.read_next_tm();
+        assert_eq!(report.service(), 11);
+        assert_eq!(report.subservice(), Subservice::TmSummaryReport as u8);
+        let source_data = report.source_data();
+        assert_eq!(source_data.len(), 4 + 12);
+        assert_eq!(u32::from_be_bytes(source_data[0..4].try_into().unwrap()), 3);
+        test_harness.check_next_verification_tm(7, request_id);
+    }
+
+    #[test]
+    fn test_detailed_report_tc() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        insert_test_tcs(&mut test_harness, 1);
+        let tc_pool_entry = [1, 2, 3, 4];
+        let stored_addr = test_harness.sched_tc_pool.add(&tc_pool_entry).unwrap();
+        let scheduler = test_harness.handler.scheduler_mut();
+        scheduler.inserted_tcs[0] =
+            TcInfo::new(stored_addr, scheduler.inserted_tcs[0].request_id());
+        let request_id = send_time_shift_tc(&mut test_harness, Subservice::TcDetailReportAll, &[]);
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        let report = test_harness.read_next_tm();
+        assert_eq!(report.service(), 11);
+        assert_eq!(report.subservice(), Subservice::TmDetailReport as u8);
+        let source_data = report.source_data();
+        assert_eq!(u16::from_be_bytes(source_data[0..2].try_into().unwrap()), 1);
+        let entry_len = 12 + 6 + 2 + tc_pool_entry.len();
+        assert_eq!(source_data.len(), 2 + entry_len);
+        assert_eq!(&source_data[2 + 20..2 + entry_len], &tc_pool_entry);
+        test_harness.check_next_verification_tm(7, request_id);
+    }
+
+    fn send_group_tc(
+        test_harness: &mut Pus11HandlerWithStoreTester,
+        subservice: GroupSubservice,
+        group_ids: &[GroupId],
+    ) -> RequestId {
+        let mut app_data = alloc::vec::Vec::new();
+        app_data.extend_from_slice(&(group_ids.len() as u16).to_be_bytes());
+        for group_id in group_ids {
+            app_data.extend_from_slice(&group_id.to_be_bytes());
+        }
+        let tc_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(11, subservice as u8);
+        let group_tc = PusTcCreator::new(tc_header, sec_header, &app_data, true);
+        let token = test_harness.init_verification(&group_tc);
+        test_harness.send_tc(&token, &group_tc);
+        test_harness.handle_one_tc().unwrap();
+        token.request_id()
+    }
+
+    #[test]
+    fn test_group_management_tcs() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        let request_id = send_group_tc(&mut test_harness, GroupSubservice::TcCreateGroups, &[1, 2]);
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        test_harness.check_next_verification_tm(7, request_id);
+        let request_id = send_group_tc(&mut test_harness, GroupSubservice::TcDisableGroups, &[2]);
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        test_harness.check_next_verification_tm(7, request_id);
+        let groups = &test_harness.handler.scheduler().groups;
+        assert_eq!(groups.get(&1), Some(&true));
+        assert_eq!(groups.get(&2), Some(&false));
+
+        let request_id = send_group_tc(
+            &mut test_harness,
+            GroupSubservice::TcReportAllGroupsStatus,
+            &[],
+        );
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        let report = test_harness.read_next_tm();
+        assert_eq!(report.service(), 11);
+        assert_eq!(
+            report.subservice(),
+            GroupSubservice::TmGroupsStatusReport as u8
+        );
+        assert_eq!(report.source_data(), &[0, 2, 0, 1, 1, 0, 2, 0]);
+        test_harness.check_next_verification_tm(7, request_id);
+    }
+
+    #[test]
+    fn test_group_tc_unknown_group() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        test_harness
+            .handler
+            .scheduler_mut()
+            .create_group(1)
+            .unwrap();
+        let request_id = send_group_tc(&mut test_harness, GroupSubservice::TcEnableGroups, &[1, 5]);
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        check_completion_failure(&mut test_harness, request_id, sched_res_code::UNKNOWN_GROUP);
+    }
+
+    #[test]
+    fn test_group_tc_not_supported() {
+        let mut test_harness = Pus11HandlerWithStoreTester::new();
+        // The test scheduler uses the default implementation which does not support deletion.
+        let request_id = send_group_tc(&mut test_harness, GroupSubservice::TcDeleteGroups, &[1]);
+        test_harness.check_next_verification_tm(1, request_id);
+        test_harness.check_next_verification_tm(3, request_id);
+        check_completion_failure(
+            &mut test_harness,
+            request_id,
+            sched_res_code::INVALID_GROUP_REQUEST,
+        );
+    }
 }
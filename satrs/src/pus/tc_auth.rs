@@ -0,0 +1,256 @@
+//! # Telecommand authentication and acceptance filtering support
+//!
+//! Missions with authenticated uplinks or other acceptance criteria need to inspect all
+//! telecommands before they are accepted and distributed to the PUS service handlers. The
+//! [TcAuthenticator] trait provides this insertion point. It can inspect the full telecommand,
+//! for example the APID, the source ID or a message authentication code (MAC), and reject it
+//! with a configurable failure code.
+//!
+//! Please note that mission-specific spare fields of the PUS TC secondary header are not
+//! parsed by the [PusTcReader]. A MAC transported inside these fields or appended to the
+//! application data is therefore part of the [spacepackets::ecss::PusPacket::user_data] slice
+//! and has to be extracted by the authenticator itself.
+//!
+//! The [authenticate_and_accept_tc] function integrates the authenticator with the
+//! [verification reporter][VerificationReportingProvider]: It generates the acceptance success
+//! or the acceptance failure report for the telecommand depending on the authentication result.
+use spacepackets::ecss::tc::{GenericPusTcSecondaryHeader, PusTcReader};
+use spacepackets::CcsdsPacket;
+
+use crate::pus::verification::{
+    FailParams, TcStateAccepted, TcStateNone, VerificationReportingProvider, VerificationToken,
+};
+use crate::pus::{EcssTmSender, EcssTmtcError};
+use crate::res_code::ResultU16;
+use crate::ValidatorU16Id;
+
+/// Rejection of a telecommand by a [TcAuthenticator].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TcRejection {
+    /// Failure code which is used for the TM[1,2] acceptance failure report.
+    pub failure_code: ResultU16,
+}
+
+impl TcRejection {
+    pub fn new(failure_code: ResultU16) -> Self {
+        Self { failure_code }
+    }
+}
+
+/// Generic trait for components which authenticate or filter telecommands before they are
+/// accepted.
+pub trait TcAuthenticator {
+    fn authenticate(&mut self, tc: &PusTcReader) -> Result<(), TcRejection>;
+}
+
+impl<F: FnMut(&PusTcReader) -> Result<(), TcRejection>> TcAuthenticator for F {
+    fn authenticate(&mut self, tc: &PusTcReader) -> Result<(), TcRejection> {
+        self(tc)
+    }
+}
+
+/// Authenticator which accepts all telecommands.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct AcceptAllTcs;
+
+impl TcAuthenticator for AcceptAllTcs {
+    fn authenticate(&mut self, _tc: &PusTcReader) -> Result<(), TcRejection> {
+        Ok(())
+    }
+}
+
+/// Validator which accepts all IDs. It can be used for the [TcSourceFilter] if only one of
+/// the two IDs should be checked.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct AnyId;
+
+impl ValidatorU16Id for AnyId {
+    fn validate(&self, _id: u16) -> bool {
+        true
+    }
+}
+
+/// Simple [TcAuthenticator] which only accepts telecommands with a valid APID and a valid
+/// source ID.
+#[derive(Debug, Clone)]
+pub struct TcSourceFilter<ApidValidator: ValidatorU16Id, SourceIdValidator: ValidatorU16Id> {
+    pub apid_validator: ApidValidator,
+    pub source_id_validator: SourceIdValidator,
+    pub failure_code: ResultU16,
+}
+
+impl<ApidValidator: ValidatorU16Id, SourceIdValidator: ValidatorU16Id>
+    TcSourceFilter<ApidValidator, SourceIdValidator>
+{
+    pub fn new(
+        apid_validator: ApidValidator,
+        source_id_validator: SourceIdValidator,
+        failure_code: ResultU16,
+    ) -> Self {
+        Self {
+            apid_validator,
+            source_id_validator,
+            failure_code,
+        }
+    }
+}
+
+impl<ApidValidator: ValidatorU16Id, SourceIdValidator: ValidatorU16Id> TcAuthenticator
+    for TcSourceFilter<ApidValidator, SourceIdValidator>
+{
+    fn authenticate(&mut self, tc: &PusTcReader) -> Result<(), TcRejection> {
+        if !self.apid_validator.validate(tc.apid())
+            || !self.source_id_validator.validate(tc.source_id())
+        {
+            return Err(TcRejection::new(self.failure_code));
+        }
+        Ok(())
+    }
+}
+
+/// Authenticate a telecommand and generate the acceptance verification report for it.
+///
+/// Returns the accepted token if the telecommand was accepted, and [None] if it was rejected.
+/// The failure data of the acceptance failure report consists of the APID and the source ID
+/// of the rejected telecommand, both as big endian [u16]s.
+///
+/// ## Parameter
+///
+/// * `authenticator` - Authenticator which decides whether the telecommand is accepted.
+/// * `tc` - The telecommand to authenticate.
+/// * `token` - Initial verification token of the telecommand.
+/// * `verif_reporter` - Verification reporter used to generate the acceptance report.
+/// * `tm_sender` - Sender for the verification telemetry.
+/// * `time_stamp` - Time stamp of the verification telemetry.
+pub fn authenticate_and_accept_tc(
+    authenticator: &mut (impl TcAuthenticator + ?Sized),
+    tc: &PusTcReader,
+    token: VerificationToken<TcStateNone>,
+    verif_reporter: &impl VerificationReportingProvider,
+    tm_sender: &(impl EcssTmSender + ?Sized),
+    time_stamp: &[u8],
+) -> Result<Option<VerificationToken<TcStateAccepted>>, EcssTmtcError> {
+    match authenticator.authenticate(tc) {
+        Ok(()) => Ok(Some(
+            verif_reporter.acceptance_success(tm_sender, token, time_stamp)?,
+        )),
+        Err(rejection) => {
+            let mut failure_data: [u8; 4] = [0; 4];
+            failure_data[0..2].copy_from_slice(&tc.apid().to_be_bytes());
+            failure_data[2..4].copy_from_slice(&tc.source_id().to_be_bytes());
+            verif_reporter.acceptance_failure(
+                tm_sender,
+                token,
+                FailParams::new(time_stamp, &rejection.failure_code, &failure_data),
+            )?;
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::vec;
+    use std::vec::Vec;
+
+    use spacepackets::ecss::tc::{PusTcCreator, PusTcSecondaryHeader};
+    use spacepackets::ecss::tm::PusTmReader;
+    use spacepackets::ecss::{PusPacket, WritablePusPacket};
+    use spacepackets::SpHeader;
+
+    use super::*;
+    use crate::pus::verification::{VerificationReporter, VerificationReporterCfg};
+    use crate::tmtc::PacketAsVec;
+
+    const TEST_APID: u16 = 0x02;
+    const TEST_SOURCE_ID: u16 = 0x10;
+    const REJECTED: ResultU16 = ResultU16::new(1, 5);
+
+    fn raw_tc(apid: u16, source_id: u16) -> Vec<u8> {
+        let mut sec_header = PusTcSecondaryHeader::new_simple(17, 1);
+        sec_header.source_id = source_id;
+        PusTcCreator::new_no_app_data(SpHeader::new_from_apid(apid), sec_header, true)
+            .to_vec()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_source_filter() {
+        let mut filter = TcSourceFilter::new(TEST_APID, vec![TEST_SOURCE_ID], REJECTED);
+        let tc = raw_tc(TEST_APID, TEST_SOURCE_ID);
+        assert!(filter
+            .authenticate(&PusTcReader::new(&tc).unwrap().0)
+            .is_ok());
+        let tc = raw_tc(TEST_APID + 1, TEST_SOURCE_ID);
+        assert_eq!(
+            filter.authenticate(&PusTcReader::new(&tc).unwrap().0),
+            Err(TcRejection::new(REJECTED))
+        );
+        let tc = raw_tc(TEST_APID, TEST_SOURCE_ID + 1);
+        assert!(filter
+            .authenticate(&PusTcReader::new(&tc).unwrap().0)
+            .is_err());
+
+        let mut apid_filter = TcSourceFilter::new(TEST_APID, AnyId, REJECTED);
+        let tc = raw_tc(TEST_APID, TEST_SOURCE_ID + 1);
+        assert!(apid_filter
+            .authenticate(&PusTcReader::new(&tc).unwrap().0)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_acceptance() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let verif_reporter = VerificationReporter::new(
+            0,
+            &VerificationReporterCfg::new(TEST_APID, 1, 2, 8).unwrap(),
+        );
+        // Closure based authenticator which rejects all telecommands from source ID 0.
+        let mut authenticator = |tc: &PusTcReader| {
+            if tc.source_id() == 0 {
+                return Err(TcRejection::new(REJECTED));
+            }
+            Ok(())
+        };
+
+        let tc = raw_tc(TEST_APID, TEST_SOURCE_ID);
+        let tc_reader = PusTcReader::new(&tc).unwrap().0;
+        let token = verif_reporter.add_tc(&tc_reader);
+        let accepted = authenticate_and_accept_tc(
+            &mut authenticator,
+            &tc_reader,
+            token,
+            &verif_reporter,
+            &tm_tx,
+            &[0; 7],
+        )
+        .unwrap();
+        assert!(accepted.is_some());
+        let tm = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&tm.packet, 7).unwrap();
+        assert_eq!(tm.service(), 1);
+        assert_eq!(tm.subservice(), 1);
+
+        let tc = raw_tc(TEST_APID, 0);
+        let tc_reader = PusTcReader::new(&tc).unwrap().0;
+        let token = verif_reporter.add_tc(&tc_reader);
+        let accepted = authenticate_and_accept_tc(
+            &mut authenticator,
+            &tc_reader,
+            token,
+            &verif_reporter,
+            &tm_tx,
+            &[0; 7],
+        )
+        .unwrap();
+        assert!(accepted.is_none());
+        let tm = tm_rx.try_recv().unwrap();
+        let (tm, _) = PusTmReader::new(&tm.packet, 7).unwrap();
+        assert_eq!(tm.service(), 1);
+        assert_eq!(tm.subservice(), 2);
+        // Request ID (4 bytes), failure code (2 bytes), failure data (APID and source ID).
+        assert_eq!(&tm.user_data()[4..6], &REJECTED.raw().to_be_bytes());
+        assert_eq!(&tm.user_data()[6..], &[0, TEST_APID as u8, 0, 0]);
+    }
+}
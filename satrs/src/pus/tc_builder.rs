@@ -0,0 +1,210 @@
+//! # Fluent builder for PUS telecommands
+//!
+//! Creating a [PusTcCreator] directly requires creating the [SpHeader] and the
+//! [PusTcSecondaryHeader] separately and serializing the application data into a separate buffer
+//! first. The [TcBuilder] bundles these steps: it owns the application data, which can be
+//! appended from typed values, and yields a ready-to-serialize [PusTcCreator]. The packet
+//! sequence count can be retrieved from any [SequenceCountProviderCore].
+//!
+//! # Example
+//!
+//! ```
+//! use satrs::pus::tc_builder::TcBuilder;
+//! use satrs::params::U32Pair;
+//! use satrs::seq_count::CcsdsSimpleSeqCountProvider;
+//! use satrs::spacepackets::ecss::PusPacket;
+//! use satrs::spacepackets::CcsdsPacket;
+//!
+//! let seq_count_provider = CcsdsSimpleSeqCountProvider::default();
+//! let builder = TcBuilder::new(3, 27)
+//!     .apid(0x02)
+//!     .seq_count_from(&seq_count_provider)
+//!     .app_data_value(U32Pair(0x1020, 3));
+//! let tc = builder.create();
+//! assert_eq!(tc.apid(), 0x02);
+//! assert_eq!((tc.service(), tc.subservice()), (3, 27));
+//! assert_eq!(tc.app_data(), &[0, 0, 0x10, 0x20, 0, 0, 0, 3]);
+//! let raw_tc = builder.to_vec().unwrap();
+//! ```
+use alloc::vec::Vec;
+
+use spacepackets::ecss::tc::{PusTcCreator, PusTcSecondaryHeader, ACK_ALL};
+use spacepackets::ecss::{PusError, WritablePusPacket};
+use spacepackets::util::ToBeBytes;
+use spacepackets::SpHeader;
+
+use crate::params::WritableToBeBytes;
+use crate::seq_count::SequenceCountProviderCore;
+
+use super::verification::RequestId;
+
+/// Fluent builder for PUS telecommands.
+///
+/// The APID, the sequence count and the source ID default to 0 and all acknowledgement flags
+/// are set by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcBuilder {
+    service: u8,
+    subservice: u8,
+    apid: u16,
+    seq_count: u16,
+    ack_flags: u8,
+    source_id: u16,
+    app_data: Vec<u8>,
+}
+
+impl TcBuilder {
+    pub fn new(service: u8, subservice: u8) -> Self {
+        Self {
+            service,
+            subservice,
+            apid: 0,
+            seq_count: 0,
+            ack_flags: ACK_ALL,
+            source_id: 0,
+            app_data: Vec::new(),
+        }
+    }
+
+    pub fn apid(mut self, apid: u16) -> Self {
+        self.apid = apid;
+        self
+    }
+
+    pub fn seq_count(mut self, seq_count: u16) -> Self {
+        self.seq_count = seq_count;
+        self
+    }
+
+    /// Set the sequence count to the next value of the given provider, which is incremented.
+    pub fn seq_count_from(self, provider: &impl SequenceCountProviderCore<u16>) -> Self {
+        self.seq_count(provider.get_and_increment())
+    }
+
+    pub fn source_id(mut self, source_id: u16) -> Self {
+        self.source_id = source_id;
+        self
+    }
+
+    pub fn ack_flags(mut self, ack_flags: u8) -> Self {
+        self.ack_flags = ack_flags;
+        self
+    }
+
+    /// Append raw bytes to the application data.
+    pub fn app_data(mut self, app_data: &[u8]) -> Self {
+        self.app_data.extend_from_slice(app_data);
+        self
+    }
+
+    /// Append a typed value like a primitive integer or a [crate::params::U32Pair] in big endian
+    /// format to the application data.
+    pub fn app_data_value(mut self, value: impl ToBeBytes) -> Self {
+        self.app_data
+            .extend_from_slice(value.to_be_bytes().as_ref());
+        self
+    }
+
+    /// Append a parameter, for example a [crate::params::ParamsRaw] value, to the application
+    /// data.
+    pub fn app_data_param(mut self, param: &impl WritableToBeBytes) -> Self {
+        let start = self.app_data.len();
+        self.app_data.resize(start + param.written_len(), 0);
+        // Can not fail, the buffer has the required size.
+        param
+            .write_to_be_bytes(&mut self.app_data[start..])
+            .unwrap();
+        self
+    }
+
+    pub fn app_data_slice(&self) -> &[u8] {
+        &self.app_data
+    }
+
+    /// Create the telecommand. The CCSDS data length field is set to the correct value.
+    pub fn create(&self) -> PusTcCreator<'_> {
+        let sp_header = SpHeader::new_for_unseg_tc(self.apid, self.seq_count, 0);
+        let sec_header = PusTcSecondaryHeader::new(
+            self.service,
+            self.subservice,
+            self.ack_flags,
+            self.source_id,
+        );
+        PusTcCreator::new(sp_header, sec_header, &self.app_data, true)
+    }
+
+    /// Verification request ID of the telecommand.
+    pub fn request_id(&self) -> RequestId {
+        RequestId::new(&self.create())
+    }
+
+    /// Serialize the telecommand into the given buffer and return the written size.
+    pub fn write_to_bytes(&self, buf: &mut [u8]) -> Result<usize, PusError> {
+        self.create().write_to_bytes(buf)
+    }
+
+    pub fn to_vec(&self) -> Result<Vec<u8>, PusError> {
+        self.create().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{ParamsRaw, U32Pair};
+    use crate::seq_count::CcsdsSimpleSeqCountProvider;
+    use spacepackets::ecss::tc::{GenericPusTcSecondaryHeader, PusTcReader};
+    use spacepackets::ecss::PusPacket;
+    use spacepackets::CcsdsPacket;
+
+    const TEST_APID: u16 = 0x02;
+
+    #[test]
+    fn test_defaults() {
+        let builder = TcBuilder::new(17, 1);
+        let tc = builder.create();
+        assert_eq!(
+            builder.to_vec().unwrap(),
+            PusTcCreator::new_simple(SpHeader::new_for_unseg_tc(0, 0, 0), 17, 1, &[], true)
+                .to_vec()
+                .unwrap()
+        );
+        assert_eq!(tc.source_id(), 0);
+        assert_eq!(tc.ack_flags(), ACK_ALL);
+    }
+
+    #[test]
+    fn test_typed_app_data() {
+        let builder = TcBuilder::new(8, 128)
+            .apid(TEST_APID)
+            .source_id(5)
+            .app_data_value(0x1020_u32)
+            .app_data_value(U32Pair(1, 2))
+            .app_data_param(&ParamsRaw::from(3_u16))
+            .app_data(&[4, 5]);
+        let raw_tc = builder.to_vec().unwrap();
+        let (tc, _) = PusTcReader::new(&raw_tc).unwrap();
+        assert_eq!(tc.apid(), TEST_APID);
+        assert_eq!(tc.source_id(), 5);
+        assert_eq!((tc.service(), tc.subservice()), (8, 128));
+        assert_eq!(
+            tc.app_data(),
+            &[0, 0, 0x10, 0x20, 0, 0, 0, 1, 0, 0, 0, 2, 0, 3, 4, 5]
+        );
+        assert_eq!(builder.app_data_slice(), tc.app_data());
+        assert_eq!(builder.request_id(), RequestId::new(&tc));
+        let mut buf = [0; 64];
+        let written = builder.write_to_bytes(&mut buf).unwrap();
+        assert_eq!(&buf[..written], raw_tc.as_slice());
+    }
+
+    #[test]
+    fn test_seq_count_provider() {
+        let seq_count_provider = CcsdsSimpleSeqCountProvider::default();
+        let first = TcBuilder::new(17, 1).seq_count_from(&seq_count_provider);
+        let second = TcBuilder::new(17, 1).seq_count_from(&seq_count_provider);
+        assert_eq!(first.create().seq_count(), 0);
+        assert_eq!(second.create().seq_count(), 1);
+        assert_ne!(first.request_id(), second.request_id());
+    }
+}
@@ -0,0 +1,316 @@
+//! # Quarantine for telecommands with an invalid CRC
+//!
+//! The [PusTcReader] rejects all telecommands with an invalid CRC. On bench setups with known
+//! bad CRC sources, for example a test generator with a wrong CRC configuration or a noisy link,
+//! these telecommands silently disappear, which makes link issues hard to diagnose.
+//!
+//! The [TcCrcFilter] parses telecommands like the [PusTcReader] but can be configured with
+//! [CrcCheckPolicy::Quarantine] to pass telecommands with an invalid CRC to a
+//! [TcQuarantineHandler] instead of dropping them. Quarantined telecommands are never
+//! distributed. All CRC failures are counted in the shared [TcCrcStatistics] and can be reported
+//! with the [TC_CRC_FAILURE] event using [report_crc_failure].
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use spacepackets::ecss::tc::PusTcReader;
+use spacepackets::ecss::PusError;
+use spacepackets::CcsdsPacket;
+
+use crate::error_report::FRAMEWORK_EVENT_GROUP_ID;
+use crate::event_man::{EventMessage, EventSendProvider};
+use crate::events::{EventU32, SeverityLow};
+use crate::params::{Params, ParamsRaw};
+use crate::pus::memory::CRC_CCITT_FALSE;
+use crate::ComponentId;
+
+crate::event_definitions! {
+    pub TC_QUARANTINE_EVENT_DEFINITIONS;
+    /// A telecommand with an invalid CRC was received.
+    pub TC_CRC_FAILURE: SeverityLow = (FRAMEWORK_EVENT_GROUP_ID, 7), U32Pair,
+        "Telecommand with invalid CRC, contains the APID and the total number of CRC failures";
+}
+
+/// Handling of telecommands with an invalid CRC.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum CrcCheckPolicy {
+    /// Reject telecommands with an invalid CRC. This is the behaviour of the [PusTcReader].
+    #[default]
+    Reject,
+    /// Parse telecommands with an invalid CRC anyway and pass them to the quarantine handler.
+    /// This should only be used on bench setups.
+    Quarantine,
+}
+
+/// Generic trait for components which handle quarantined telecommands, for example by logging
+/// or recording them.
+pub trait TcQuarantineHandler {
+    /// The telecommand was parsed from a copy of the raw telecommand with a corrected CRC. The
+    /// raw telecommand is passed as it was received.
+    fn handle_quarantined_tc(&mut self, sender_id: ComponentId, tc: &PusTcReader, raw_tc: &[u8]);
+}
+
+impl<F: FnMut(ComponentId, &PusTcReader, &[u8])> TcQuarantineHandler for F {
+    fn handle_quarantined_tc(&mut self, sender_id: ComponentId, tc: &PusTcReader, raw_tc: &[u8]) {
+        self(sender_id, tc, raw_tc)
+    }
+}
+
+/// Lock-free CRC error counters. All counters wrap around on overflow.
+#[derive(Debug, Default)]
+pub struct TcCrcStatistics {
+    crc_failures: AtomicU32,
+    quarantined: AtomicU32,
+}
+
+impl TcCrcStatistics {
+    /// Number of received telecommands with an invalid CRC.
+    pub fn crc_failures(&self) -> u32 {
+        self.crc_failures.load(Ordering::Relaxed)
+    }
+
+    /// Number of telecommands which were passed to the quarantine handler.
+    pub fn quarantined(&self) -> u32 {
+        self.quarantined.load(Ordering::Relaxed)
+    }
+
+    /// Reset all counters to 0.
+    pub fn reset(&self) {
+        self.crc_failures.store(0, Ordering::Relaxed);
+        self.quarantined.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Result of the [TcCrcFilter::check] function.
+#[derive(Debug)]
+pub enum TcCheckResult<'tc> {
+    /// The telecommand is valid and can be distributed. Contains the reader and the packet length.
+    Valid(PusTcReader<'tc>, usize),
+    /// The telecommand has an invalid CRC and was passed to the quarantine handler.
+    Quarantined {
+        apid: u16,
+        /// Total number of CRC failures, which can be used for the [TC_CRC_FAILURE] event.
+        num_crc_failures: u32,
+    },
+    /// The telecommand could not be parsed.
+    Rejected(PusError),
+}
+
+/// Parses telecommands and handles telecommands with an invalid CRC according to its
+/// [CrcCheckPolicy].
+pub struct TcCrcFilter {
+    policy: CrcCheckPolicy,
+    quarantine_handler: Option<Box<dyn TcQuarantineHandler + Send>>,
+    stats: Arc<TcCrcStatistics>,
+    // Intermediate buffer for the copy of the telecommand with the corrected CRC.
+    repaired_buf: Vec<u8>,
+}
+
+impl Default for TcCrcFilter {
+    fn default() -> Self {
+        Self::new(CrcCheckPolicy::default())
+    }
+}
+
+impl TcCrcFilter {
+    pub fn new(policy: CrcCheckPolicy) -> Self {
+        Self {
+            policy,
+            quarantine_handler: None,
+            stats: Arc::default(),
+            repaired_buf: Vec::new(),
+        }
+    }
+
+    /// Set the handler for quarantined telecommands. Without a handler, telecommands with an
+    /// invalid CRC are still counted and reported as [TcCheckResult::Quarantined] if the policy
+    /// is [CrcCheckPolicy::Quarantine].
+    pub fn with_quarantine_handler(
+        mut self,
+        handler: impl TcQuarantineHandler + Send + 'static,
+    ) -> Self {
+        self.quarantine_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Use the given shared statistics instead of statistics owned by the filter.
+    pub fn with_stats(mut self, stats: Arc<TcCrcStatistics>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    pub fn policy(&self) -> CrcCheckPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: CrcCheckPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn stats(&self) -> &Arc<TcCrcStatistics> {
+        &self.stats
+    }
+
+    /// Parse the raw telecommand received from the component with the given sender ID.
+    pub fn check<'tc>(&mut self, sender_id: ComponentId, raw_tc: &'tc [u8]) -> TcCheckResult<'tc> {
+        let error = match PusTcReader::new(raw_tc) {
+            Ok((tc, tc_len)) => return TcCheckResult::Valid(tc, tc_len),
+            Err(e) => e,
+        };
+        if !matches!(error, PusError::ChecksumFailure(_)) {
+            return TcCheckResult::Rejected(error);
+        }
+        let num_crc_failures = self
+            .stats
+            .crc_failures
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_add(1);
+        if self.policy == CrcCheckPolicy::Reject {
+            return TcCheckResult::Rejected(error);
+        }
+        // The length was already checked by the reader, which only checks the CRC after
+        // the packet length.
+        let tc_len = u16::from_be_bytes([raw_tc[4], raw_tc[5]]) as usize + 7;
+        self.repaired_buf.clear();
+        self.repaired_buf.extend_from_slice(&raw_tc[0..tc_len]);
+        let crc = CRC_CCITT_FALSE.checksum(&self.repaired_buf[0..tc_len - 2]);
+        self.repaired_buf[tc_len - 2..tc_len].copy_from_slice(&crc.to_be_bytes());
+        let tc = match PusTcReader::new(&self.repaired_buf) {
+            Ok((tc, _)) => tc,
+            Err(_) => return TcCheckResult::Rejected(error),
+        };
+        self.stats.quarantined.fetch_add(1, Ordering::Relaxed);
+        if let Some(handler) = &mut self.quarantine_handler {
+            handler.handle_quarantined_tc(sender_id, &tc, &raw_tc[0..tc_len]);
+        }
+        TcCheckResult::Quarantined {
+            apid: tc.apid(),
+            num_crc_failures,
+        }
+    }
+}
+
+/// Send the [TC_CRC_FAILURE] event for a telecommand with the given APID.
+pub fn report_crc_failure<Sender: EventSendProvider<EventU32>>(
+    sender_id: ComponentId,
+    apid: u16,
+    num_crc_failures: u32,
+    event_sender: &Sender,
+) -> Result<(), Sender::Error> {
+    event_sender.send(EventMessage::new_with_params(
+        sender_id,
+        TC_CRC_FAILURE.into(),
+        &Params::from(ParamsRaw::from((apid as u32, num_crc_failures))),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use spacepackets::ecss::tc::{PusTcCreator, PusTcSecondaryHeader};
+    use spacepackets::ecss::{PusPacket, WritablePusPacket};
+    use spacepackets::SpHeader;
+    use std::sync::mpsc;
+
+    use crate::event_man::EventU32SenderMpsc;
+    use crate::params::{ParamsHeapless, U32Pair};
+
+    use super::*;
+
+    const TEST_APID: u16 = 0x42;
+
+    fn create_tc_with_bad_crc() -> Vec<u8> {
+        let mut tc = PusTcCreator::new(
+            SpHeader::new_for_unseg_tc(TEST_APID, 0, 0),
+            PusTcSecondaryHeader::new_simple(17, 1),
+            &[1, 2, 3],
+            true,
+        )
+        .to_vec()
+        .unwrap();
+        let len = tc.len();
+        tc[len - 1] ^= 0xff;
+        tc
+    }
+
+    #[test]
+    fn test_valid_tc() {
+        let tc = PusTcCreator::new_simple(
+            SpHeader::new_for_unseg_tc(TEST_APID, 0, 0),
+            17,
+            1,
+            &[],
+            true,
+        )
+        .to_vec()
+        .unwrap();
+        let mut filter = TcCrcFilter::new(CrcCheckPolicy::Quarantine);
+        match filter.check(0, &tc) {
+            TcCheckResult::Valid(reader, len) => {
+                assert_eq!(reader.service(), 17);
+                assert_eq!(len, tc.len());
+            }
+            result => panic!("unexpected check result {result:?}"),
+        }
+        assert_eq!(filter.stats().crc_failures(), 0);
+    }
+
+    #[test]
+    fn test_bad_crc_rejected() {
+        let tc = create_tc_with_bad_crc();
+        let mut filter = TcCrcFilter::default();
+        assert!(matches!(
+            filter.check(0, &tc),
+            TcCheckResult::Rejected(PusError::ChecksumFailure(_))
+        ));
+        assert_eq!(filter.stats().crc_failures(), 1);
+        assert_eq!(filter.stats().quarantined(), 0);
+    }
+
+    #[test]
+    fn test_bad_crc_quarantined() {
+        let tc = create_tc_with_bad_crc();
+        let (quarantine_tx, quarantine_rx) = mpsc::channel();
+        let mut filter = TcCrcFilter::new(CrcCheckPolicy::Quarantine).with_quarantine_handler(
+            move |sender_id: ComponentId, tc: &PusTcReader, raw_tc: &[u8]| {
+                quarantine_tx
+                    .send((sender_id, tc.user_data().to_vec(), raw_tc.to_vec()))
+                    .unwrap();
+            },
+        );
+        for expected_failures in 1..=2 {
+            match filter.check(5, &tc) {
+                TcCheckResult::Quarantined {
+                    apid,
+                    num_crc_failures,
+                } => {
+                    assert_eq!(apid, TEST_APID);
+                    assert_eq!(num_crc_failures, expected_failures);
+                }
+                result => panic!("unexpected check result {result:?}"),
+            }
+        }
+        let (sender_id, app_data, raw_tc) = quarantine_rx.try_recv().unwrap();
+        assert_eq!(sender_id, 5);
+        assert_eq!(app_data, vec![1, 2, 3]);
+        assert_eq!(raw_tc, tc);
+        assert_eq!(filter.stats().quarantined(), 2);
+        filter.stats().reset();
+        assert_eq!(filter.stats().crc_failures(), 0);
+    }
+
+    #[test]
+    fn test_report_crc_failure() {
+        let (event_tx, event_rx) = mpsc::channel();
+        report_crc_failure(1, TEST_APID, 3, &EventU32SenderMpsc::new(0, event_tx)).unwrap();
+        let event_msg = event_rx.try_recv().unwrap();
+        assert_eq!(event_msg.event(), EventU32::from(TC_CRC_FAILURE));
+        assert_eq!(
+            event_msg.params(),
+            Some(&Params::Heapless(ParamsHeapless::Raw(ParamsRaw::U32Pair(
+                U32Pair(TEST_APID as u32, 3)
+            ))))
+        );
+    }
+}
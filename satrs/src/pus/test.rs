@@ -2,19 +2,40 @@ use crate::pus::{
     DirectPusPacketHandlerResult, PartialPusHandlingError, PusPacketHandlingError, PusTmVariant,
 };
 use crate::tmtc::{PacketAsVec, PacketSenderWithSharedPool};
+use core::time::Duration;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use spacepackets::ecss::tc::PusTcReader;
 use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
 use spacepackets::ecss::PusPacket;
 use spacepackets::SpHeader;
 use std::sync::mpsc;
+use std::thread;
 
-use super::verification::{VerificationReporter, VerificationReportingProvider};
+use super::harness::{PusServiceContext, PusServiceCore};
+use super::verification::{
+    TcStateAccepted, VerificationReporter, VerificationReportingProvider, VerificationToken,
+};
 use super::{
     EcssTcInMemConverter, EcssTcInSharedStoreConverter, EcssTcInVecConverter, EcssTcReceiver,
-    EcssTmSender, GenericConversionError, HandlingStatus, MpscTcReceiver, PusServiceHelper,
+    EcssTmSender, HandlingStatus, MpscTcReceiver, PusServiceHelper,
 };
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum TestSubservice {
+    TcPing = 1,
+    TmPingReply = 2,
+    /// Ping with arbitrary application data which is echoed in the reply.
+    TcPingWithData = 3,
+    TmPingWithDataReply = 4,
+}
+
 /// This is a helper class for [std] environments to handle generic PUS 17 (test service) packets.
-/// This handler only processes ping requests and generates a ping reply for them accordingly.
+/// This handler processes ping requests and pings with data and generates the corresponding
+/// replies. See [PusService17Core] for more details.
 pub struct PusService17TestHandler<
     TcReceiver: EcssTcReceiver,
     TmSender: EcssTmSender,
@@ -23,6 +44,7 @@ pub struct PusService17TestHandler<
 > {
     pub service_helper:
         PusServiceHelper<TcReceiver, TmSender, TcInMemConverter, VerificationReporter>,
+    core: PusService17Core,
 }
 
 impl<
@@ -40,7 +62,25 @@ impl<
             VerificationReporter,
         >,
     ) -> Self {
-        Self { service_helper }
+        Self {
+            service_helper,
+            core: PusService17Core::default(),
+        }
+    }
+
+    /// Delay the replies by the given artificial processing delay. See
+    /// [PusService17Core::with_processing_delay].
+    pub fn with_processing_delay(mut self, processing_delay: Duration) -> Self {
+        self.core.processing_delay = processing_delay;
+        self
+    }
+
+    pub fn processing_delay(&self) -> Duration {
+        self.core.processing_delay
+    }
+
+    pub fn set_processing_delay(&mut self, processing_delay: Duration) {
+        self.core.processing_delay = processing_delay;
     }
 
     pub fn poll_and_handle_next_tc<ErrorCb: FnMut(&PartialPusHandlingError)>(
@@ -48,60 +88,82 @@ impl<
         mut error_callback: ErrorCb,
         time_stamp: &[u8],
     ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
-        let possible_packet = self.service_helper.retrieve_and_accept_next_packet()?;
-        if possible_packet.is_none() {
-            return Ok(HandlingStatus::Empty.into());
-        }
-        let ecss_tc_and_token = possible_packet.unwrap();
-        self.service_helper
-            .tc_in_mem_converter_mut()
-            .cache(&ecss_tc_and_token.tc_in_memory)?;
-        let tc = self.service_helper.tc_in_mem_converter().convert()?;
-        if tc.service() != 17 {
-            return Err(GenericConversionError::WrongService(tc.service()).into());
+        self.service_helper.poll_and_handle_next_tc_with_core(
+            &mut self.core,
+            &mut error_callback,
+            time_stamp,
+        )
+    }
+}
+
+/// [PusServiceCore] of the PUS 17 test service.
+///
+/// It handles ping requests ([TestSubservice::TcPing]) by generating an empty ping reply and
+/// pings with data ([TestSubservice::TcPingWithData]) by generating a reply which echoes the
+/// application data of the telecommand. All other subservices are returned as custom subservices.
+///
+/// An artificial processing delay can be configured for link latency tests. The delay is applied
+/// after the start success report by blocking the handler thread.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PusService17Core {
+    pub processing_delay: Duration,
+}
+
+impl PusService17Core {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_processing_delay(mut self, processing_delay: Duration) -> Self {
+        self.processing_delay = processing_delay;
+        self
+    }
+}
+
+impl<TmSender: EcssTmSender, VerificationReporter: VerificationReportingProvider>
+    PusServiceCore<TmSender, VerificationReporter> for PusService17Core
+{
+    fn service(&self) -> u8 {
+        17
+    }
+
+    fn check_subservice(&self, subservice: u8) -> bool {
+        matches!(
+            TestSubservice::try_from(subservice),
+            Ok(TestSubservice::TcPing) | Ok(TestSubservice::TcPingWithData)
+        )
+    }
+
+    fn handle_request(
+        &mut self,
+        ctx: &PusServiceContext<'_, TmSender, VerificationReporter>,
+        tc: &PusTcReader,
+        token: VerificationToken<TcStateAccepted>,
+        error_callback: &mut dyn FnMut(&PartialPusHandlingError),
+    ) -> Result<DirectPusPacketHandlerResult, PusPacketHandlingError> {
+        let opt_started_token = ctx.start_success(token, error_callback);
+        if !self.processing_delay.is_zero() {
+            thread::sleep(self.processing_delay);
         }
-        if tc.subservice() == 1 {
-            let opt_started_token = match self.service_helper.verif_reporter().start_success(
-                &self.service_helper.common.tm_sender,
-                ecss_tc_and_token.token,
-                time_stamp,
-            ) {
-                Ok(token) => Some(token),
-                Err(e) => {
-                    error_callback(&PartialPusHandlingError::Verification(e));
-                    None
-                }
-            };
-            // Sequence count will be handled centrally in TM funnel.
-            // It is assumed that the verification reporter was built with a valid APID, so we use
-            // the unchecked API here.
-            let reply_header =
-                SpHeader::new_for_unseg_tm(self.service_helper.verif_reporter().apid(), 0, 0);
-            let tc_header = PusTmSecondaryHeader::new_simple(17, 2, time_stamp);
-            let ping_reply = PusTmCreator::new(reply_header, tc_header, &[], true);
-            if let Err(e) = self
-                .service_helper
-                .common
-                .tm_sender
-                .send_tm(self.service_helper.id(), PusTmVariant::Direct(ping_reply))
-            {
-                error_callback(&PartialPusHandlingError::TmSend(e));
-            }
-            if let Some(start_token) = opt_started_token {
-                if let Err(e) = self.service_helper.verif_reporter().completion_success(
-                    &self.service_helper.common.tm_sender,
-                    start_token,
-                    time_stamp,
-                ) {
-                    error_callback(&PartialPusHandlingError::Verification(e));
-                }
-            }
+        let (reply_subservice, reply_data) = if tc.subservice() == TestSubservice::TcPing as u8 {
+            (TestSubservice::TmPingReply, &[][..])
         } else {
-            return Ok(DirectPusPacketHandlerResult::CustomSubservice(
-                tc.subservice(),
-                ecss_tc_and_token.token,
-            ));
+            (TestSubservice::TmPingWithDataReply, tc.app_data())
+        };
+        // Sequence count will be handled centrally in TM funnel.
+        // It is assumed that the verification reporter was built with a valid APID, so we use
+        // the unchecked API here.
+        let reply_header = SpHeader::new_for_unseg_tm(ctx.verif_reporter.apid(), 0, 0);
+        let tc_header =
+            PusTmSecondaryHeader::new_simple(17, reply_subservice.into(), ctx.time_stamp);
+        let ping_reply = PusTmCreator::new(reply_header, tc_header, reply_data, true);
+        if let Err(e) = ctx
+            .tm_sender
+            .send_tm(ctx.id, PusTmVariant::Direct(ping_reply))
+        {
+            error_callback(&PartialPusHandlingError::TmSend(e));
         }
+        ctx.completion_success(opt_started_token, error_callback);
         Ok(HandlingStatus::HandledOne.into())
     }
 }
@@ -133,6 +195,7 @@ pub type PusService17TestHandlerStaticWithBoundedMpsc = PusService17TestHandler<
 
 #[cfg(test)]
 mod tests {
+    use crate::pus::test_harness::PusServiceTestHarness;
     use crate::pus::test_util::{PusTestHarness, SimplePusPacketHandler, TEST_APID};
     use crate::pus::tests::{
         PusServiceHandlerWithSharedStoreCommon, PusServiceHandlerWithVecCommon,
@@ -151,9 +214,10 @@ mod tests {
     use delegate::delegate;
     use spacepackets::ecss::tc::{PusTcCreator, PusTcSecondaryHeader};
     use spacepackets::ecss::tm::PusTmReader;
-    use spacepackets::ecss::PusPacket;
+    use spacepackets::ecss::{PusPacket, WritablePusPacket};
     use spacepackets::time::{cds, TimeWriter};
     use spacepackets::SpHeader;
+    use std::time::{Duration, Instant};
 
     use super::PusService17TestHandler;
 
@@ -348,6 +412,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ping_with_test_harness() {
+        let (mut harness, service_helper) = PusServiceTestHarness::new(0, TEST_APID);
+        let mut handler = PusService17TestHandler::new(service_helper);
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(17, 1);
+        let ping_tc = PusTcCreator::new_no_app_data(sp_header, sec_header, true);
+        let token = harness.inject_tc(&ping_tc.to_vec().unwrap()).unwrap();
+        let result = handler.poll_and_handle_next_tc(
+            |_partial_error: &PartialPusHandlingError| {},
+            harness.time_stamp(),
+        );
+        assert!(result.is_ok());
+        let timeout = Duration::from_millis(50);
+        assert!(harness.expect_tm(17, 2, timeout).user_data().is_empty());
+        harness.expect_verification_tm(1, token.request_id(), timeout);
+        harness.expect_verification_tm(3, token.request_id(), timeout);
+        harness.expect_verification_tm(7, token.request_id(), timeout);
+        assert!(harness.check_no_tm_available());
+    }
+
+    #[test]
+    fn test_ping_with_data_and_processing_delay() {
+        let (mut harness, service_helper) = PusServiceTestHarness::new(0, TEST_APID);
+        let mut handler = PusService17TestHandler::new(service_helper)
+            .with_processing_delay(Duration::from_millis(20));
+        assert_eq!(handler.processing_delay(), Duration::from_millis(20));
+        let sp_header = SpHeader::new_for_unseg_tc(TEST_APID, 0, 0);
+        let sec_header = PusTcSecondaryHeader::new_simple(17, 3);
+        let ping_tc = PusTcCreator::new(sp_header, sec_header, &[1, 2, 3, 4], true);
+        let token = harness.inject_tc(&ping_tc.to_vec().unwrap()).unwrap();
+        let start = Instant::now();
+        let result = handler.poll_and_handle_next_tc(
+            |_partial_error: &PartialPusHandlingError| {},
+            harness.time_stamp(),
+        );
+        assert!(result.is_ok());
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        let timeout = Duration::from_millis(50);
+        harness.expect_verification_tm(1, token.request_id(), timeout);
+        harness.expect_verification_tm(3, token.request_id(), timeout);
+        assert_eq!(harness.expect_tm(17, 4, timeout).user_data(), [1, 2, 3, 4]);
+        harness.expect_verification_tm(7, token.request_id(), timeout);
+        assert!(harness.check_no_tm_available());
+    }
+
     #[test]
     fn test_sending_custom_subservice() {
         let mut test_harness = Pus17HandlerWithStoreTester::new(0);
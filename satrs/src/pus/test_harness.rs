@@ -0,0 +1,284 @@
+//! # Replay and injection test harness for PUS service handlers
+//!
+//! Unit tests for PUS service handlers usually need the same setup: TC and TM queues, a
+//! verification reporter, the acceptance of injected telecommands and the parsing and checking
+//! of generated telemetry. The [PusServiceTestHarness] bundles this setup. It creates the
+//! [PusServiceHelper] for the handler under test, which is connected to in-memory queues, and
+//! offers helper methods to inject telecommands and to check the generated telemetry.
+//!
+//! Verification TM is collected separately from all other TM, so tests can check the
+//! service-specific replies and the verification reports independently.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//! use satrs::pus::test::PusService17TestHandler;
+//! use satrs::pus::test_harness::PusServiceTestHarness;
+//! use satrs::pus::PartialPusHandlingError;
+//! use satrs::spacepackets::ecss::tc::PusTcCreator;
+//! use satrs::spacepackets::ecss::WritablePusPacket;
+//! use satrs::spacepackets::SpHeader;
+//!
+//! let (mut harness, service_helper) = PusServiceTestHarness::new(1, 0x02);
+//! let mut handler = PusService17TestHandler::new(service_helper);
+//! let ping = PusTcCreator::new_simple(SpHeader::new_from_apid(0x02), 17, 1, &[], true);
+//! let token = harness.inject_tc(&ping.to_vec().unwrap()).unwrap();
+//! handler
+//!     .poll_and_handle_next_tc(|_: &PartialPusHandlingError| {}, harness.time_stamp())
+//!     .unwrap();
+//! harness.expect_tm(17, 2, Duration::from_millis(50));
+//! harness.expect_verification_tm(1, token.request_id(), Duration::from_millis(50));
+//! harness.expect_verification_tm(3, token.request_id(), Duration::from_millis(50));
+//! harness.expect_verification_tm(7, token.request_id(), Duration::from_millis(50));
+//! assert!(harness.check_no_tm_available());
+//! ```
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use spacepackets::ecss::tc::PusTcReader;
+use spacepackets::ecss::tm::PusTmReader;
+use spacepackets::ecss::{PusError, PusPacket};
+
+use crate::tmtc::PacketAsVec;
+use crate::ComponentId;
+
+use super::verification::{
+    RequestId, TcStateAccepted, VerificationReporter, VerificationReporterCfg,
+    VerificationReportingProvider, VerificationToken,
+};
+use super::{
+    EcssTcAndToken, EcssTcInVecConverter, MpscTcReceiver, MpscTmAsVecSender, PusServiceHelper,
+    TcInMemory,
+};
+
+/// [PusServiceHelper] created by the [PusServiceTestHarness].
+pub type PusServiceTestHelper =
+    PusServiceHelper<MpscTcReceiver, MpscTmAsVecSender, EcssTcInVecConverter, VerificationReporter>;
+
+/// Test harness which connects a PUS service handler to in-memory TC and TM queues.
+///
+/// All helper methods which expect telemetry panic with a descriptive message if the expected
+/// telemetry was not received, so they can be used directly inside unit tests.
+pub struct PusServiceTestHarness {
+    id: ComponentId,
+    time_stamp: [u8; 7],
+    tc_sender: mpsc::Sender<EcssTcAndToken>,
+    tm_sender: MpscTmAsVecSender,
+    tm_receiver: mpsc::Receiver<PacketAsVec>,
+    verif_reporter: VerificationReporter,
+    verification_tm: VecDeque<Vec<u8>>,
+    other_tm: VecDeque<Vec<u8>>,
+    current_tm: Vec<u8>,
+}
+
+impl PusServiceTestHarness {
+    /// Create the harness and the [PusServiceHelper] for the handler under test.
+    ///
+    /// ## Parameter
+    ///
+    /// * `id` - Component ID of the service handler.
+    /// * `apid` - APID of the verification reporter and the expected APID of the telemetry.
+    pub fn new(id: ComponentId, apid: u16) -> (Self, PusServiceTestHelper) {
+        let (tc_sender, tc_receiver) = mpsc::channel();
+        let (tm_sender, tm_receiver) = mpsc::channel();
+        let verif_cfg = VerificationReporterCfg::new(apid, 1, 2, 8).unwrap();
+        let verif_reporter = VerificationReporter::new(id, &verif_cfg);
+        (
+            Self {
+                id,
+                time_stamp: [0; 7],
+                tc_sender,
+                tm_sender: tm_sender.clone(),
+                tm_receiver,
+                verif_reporter: verif_reporter.clone(),
+                verification_tm: VecDeque::new(),
+                other_tm: VecDeque::new(),
+                current_tm: Vec::new(),
+            },
+            PusServiceHelper::new(
+                id,
+                tc_receiver,
+                tm_sender,
+                verif_reporter,
+                EcssTcInVecConverter::default(),
+            ),
+        )
+    }
+
+    /// Timestamp used for the acceptance reports. It can also be passed to the handler under
+    /// test.
+    pub fn time_stamp(&self) -> &[u8] {
+        &self.time_stamp
+    }
+
+    pub fn set_time_stamp(&mut self, time_stamp: [u8; 7]) {
+        self.time_stamp = time_stamp;
+    }
+
+    /// Inject a raw telecommand. The telecommand is accepted, which generates the acceptance
+    /// success verification TM, and sent to the handler under test. The returned token can be
+    /// used to check the verification reports for the telecommand.
+    pub fn inject_tc(
+        &mut self,
+        raw_tc: &[u8],
+    ) -> Result<VerificationToken<TcStateAccepted>, PusError> {
+        let (tc, _) = PusTcReader::new(raw_tc)?;
+        let init_token = self.verif_reporter.add_tc(&tc);
+        let accepted_token = self
+            .verif_reporter
+            .acceptance_success(&self.tm_sender, init_token, &self.time_stamp)
+            .expect("acceptance success failure");
+        self.tc_sender
+            .send(EcssTcAndToken::new(
+                TcInMemory::Vec(PacketAsVec::new(self.id, raw_tc.to_vec())),
+                accepted_token,
+            ))
+            .expect("sending TC failed");
+        Ok(accepted_token)
+    }
+
+    /// Sort all telemetry which was received so far into the verification TM and the other TM.
+    pub fn collect_tm(&mut self) {
+        while let Ok(tm) = self.tm_receiver.try_recv() {
+            self.sort_tm(tm.packet);
+        }
+    }
+
+    fn sort_tm(&mut self, raw_tm: Vec<u8>) {
+        let (tm, _) = PusTmReader::new(&raw_tm, self.time_stamp.len()).expect("invalid PUS TM");
+        if tm.service() == 1 {
+            self.verification_tm.push_back(raw_tm);
+        } else {
+            self.other_tm.push_back(raw_tm);
+        }
+    }
+
+    /// Wait for the next TM of the given queue, while sorting all received TM.
+    fn next_tm(&mut self, verification: bool, timeout: Duration) -> Option<Vec<u8>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.collect_tm();
+            let queue = if verification {
+                &mut self.verification_tm
+            } else {
+                &mut self.other_tm
+            };
+            if let Some(tm) = queue.pop_front() {
+                return Some(tm);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.tm_receiver.recv_timeout(remaining) {
+                Ok(tm) => self.sort_tm(tm.packet),
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Expect the next non-verification TM to have the given service and subservice and return
+    /// it.
+    pub fn expect_tm(&mut self, service: u8, subservice: u8, timeout: Duration) -> PusTmReader<'_> {
+        self.current_tm = self
+            .next_tm(false, timeout)
+            .unwrap_or_else(|| panic!("no TM[{service},{subservice}] received until timeout"));
+        let (tm, _) = PusTmReader::new(&self.current_tm, self.time_stamp.len()).unwrap();
+        assert_eq!(
+            (tm.service(), tm.subservice()),
+            (service, subservice),
+            "unexpected TM service and subservice"
+        );
+        tm
+    }
+
+    /// Expect the next verification TM to have the given subservice and to be a report for the
+    /// given request ID.
+    pub fn expect_verification_tm(
+        &mut self,
+        subservice: u8,
+        request_id: RequestId,
+        timeout: Duration,
+    ) -> PusTmReader<'_> {
+        self.current_tm = self
+            .next_tm(true, timeout)
+            .unwrap_or_else(|| panic!("no verification TM[1,{subservice}] received until timeout"));
+        let (tm, _) = PusTmReader::new(&self.current_tm, self.time_stamp.len()).unwrap();
+        assert_eq!(
+            tm.subservice(),
+            subservice,
+            "unexpected verification TM subservice"
+        );
+        assert_eq!(
+            RequestId::from_bytes(tm.user_data()),
+            Some(request_id),
+            "unexpected verification TM request ID"
+        );
+        tm
+    }
+
+    /// All verification TM which was collected but not checked yet.
+    pub fn verification_tm(&mut self) -> &mut VecDeque<Vec<u8>> {
+        self.collect_tm();
+        &mut self.verification_tm
+    }
+
+    /// All non-verification TM which was collected but not checked yet.
+    pub fn other_tm(&mut self) -> &mut VecDeque<Vec<u8>> {
+        self.collect_tm();
+        &mut self.other_tm
+    }
+
+    /// Returns true if all received TM was checked.
+    pub fn check_no_tm_available(&mut self) -> bool {
+        self.collect_tm();
+        self.verification_tm.is_empty() && self.other_tm.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pus::test_util::{TEST_APID, TEST_COMPONENT_ID_0};
+    use spacepackets::ecss::tc::PusTcCreator;
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::SpHeader;
+
+    const TIMEOUT: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn test_verification_tm_is_collected_separately() {
+        let (mut harness, service_helper) =
+            PusServiceTestHarness::new(TEST_COMPONENT_ID_0.id(), TEST_APID);
+        let tc = PusTcCreator::new_simple(SpHeader::new_from_apid(TEST_APID), 3, 1, &[], true);
+        let token = harness.inject_tc(&tc.to_vec().unwrap()).unwrap();
+        let sec_header = PusTmSecondaryHeader::new_simple(3, 2, &[0; 7]);
+        let reply = PusTmCreator::new(SpHeader::new_from_apid(TEST_APID), sec_header, &[1], true);
+        service_helper
+            .tm_sender()
+            .send(PacketAsVec::new(0, reply.to_vec().unwrap()))
+            .unwrap();
+        assert_eq!(harness.verification_tm().len(), 1);
+        let tm = harness.expect_tm(3, 2, TIMEOUT);
+        assert_eq!(tm.source_data(), &[1]);
+        harness.expect_verification_tm(1, token.request_id(), TIMEOUT);
+        assert!(harness.check_no_tm_available());
+    }
+
+    #[test]
+    #[should_panic(expected = "no TM[17,2] received until timeout")]
+    fn test_expect_tm_timeout() {
+        let (mut harness, _service_helper) =
+            PusServiceTestHarness::new(TEST_COMPONENT_ID_0.id(), TEST_APID);
+        harness.expect_tm(17, 2, TIMEOUT);
+    }
+
+    #[test]
+    fn test_invalid_tc() {
+        let (mut harness, _service_helper) =
+            PusServiceTestHarness::new(TEST_COMPONENT_ID_0.id(), TEST_APID);
+        assert!(harness.inject_tc(&[0; 4]).is_err());
+        assert!(harness.check_no_tm_available());
+    }
+}
@@ -0,0 +1,327 @@
+//! # Central PUS TM packet size limit
+//!
+//! The maximum size of a telemetry packet is usually limited by the downlink, for example by the
+//! maximum transfer frame data field length. The [TmSizeLimit] configures this limit in one
+//! place, so that TM generators do not need to rely on their own internal buffer sizes to
+//! guard against oversized packets.
+//!
+//! The [SizeLimitedTmSender] wraps any [EcssTmSender] and enforces the limit for all packets
+//! sent through it, for example event reports with large auxiliary data, housekeeping reports or
+//! schedule reports. Depending on the configured [TmSizePolicy], oversized packets are either
+//! rejected with [EcssTmtcError::TmTooLarge], or the complete raw packet is segmented into
+//! TM[13,1], TM[13,2] and TM[13,3] parts of the PUS 13 large packet transfer service, which
+//! allows the ground segment to reassemble the original packet.
+//!
+//! Service handlers which split their reports on their own, for example the
+//! [crate::pus::memory_srv::PusMemoryServiceHandler], can derive their maximum report source
+//! data length from the limit with [TmSizeLimit::max_source_data_len].
+use spacepackets::ecss::tm::PusTmCreator;
+use spacepackets::ecss::WritablePusPacket;
+
+use crate::pus::EcssTmtcError;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+/// Length of a PUS C TM packet without the source data and without the timestamp: primary
+/// header, secondary header and CRC16.
+pub const TM_OVERHEAD_WITHOUT_TIMESTAMP: usize = 6 + 7 + 2;
+
+/// Handling of TM packets which exceed the [TmSizeLimit].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TmSizePolicy {
+    /// Oversized packets are rejected with [EcssTmtcError::TmTooLarge].
+    #[default]
+    Reject,
+    /// Oversized packets are segmented into PUS 13 downlink parts.
+    Segment,
+}
+
+/// Maximum length of a single TM packet, including all headers and the CRC.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TmSizeLimit {
+    max_packet_len: usize,
+    pub policy: TmSizePolicy,
+}
+
+impl TmSizeLimit {
+    /// Create a new limit. Returns [None] if the limit does not leave space for any source
+    /// data.
+    pub const fn new(max_packet_len: usize, policy: TmSizePolicy) -> Option<Self> {
+        if max_packet_len <= TM_OVERHEAD_WITHOUT_TIMESTAMP {
+            return None;
+        }
+        Some(Self {
+            max_packet_len,
+            policy,
+        })
+    }
+
+    pub fn max_packet_len(&self) -> usize {
+        self.max_packet_len
+    }
+
+    /// Maximum source data length of a TM packet with a timestamp of the given length.
+    pub fn max_source_data_len(&self, time_stamp_len: usize) -> usize {
+        self.max_packet_len
+            .saturating_sub(TM_OVERHEAD_WITHOUT_TIMESTAMP + time_stamp_len)
+    }
+
+    /// Check the length of a TM packet against the limit.
+    pub fn check(&self, tm: &PusTmCreator) -> Result<(), EcssTmtcError> {
+        self.check_len(tm.len_written())
+    }
+
+    /// Check a raw packet length against the limit.
+    pub fn check_len(&self, len: usize) -> Result<(), EcssTmtcError> {
+        if len > self.max_packet_len {
+            return Err(EcssTmtcError::TmTooLarge {
+                len,
+                max_len: self.max_packet_len,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use core::sync::atomic::{AtomicU16, Ordering};
+
+    use spacepackets::ecss::tm::GenericPusTmSecondaryHeader;
+    use spacepackets::ecss::PusPacket;
+    use spacepackets::CcsdsPacket;
+
+    use super::*;
+    use crate::pus::large_data::{LargeDataError, PartHeader, PusLargeDataTmCreator};
+    use crate::pus::{EcssTmSender, PusTmVariant};
+    use crate::ComponentId;
+
+    /// TM sender wrapper which enforces a [TmSizeLimit] for all directly sent packets.
+    ///
+    /// Oversized packets are handled according to the [TmSizePolicy] of the limit. Segmented
+    /// packets are sent with the APID, destination ID and timestamp of the original packet, and
+    /// each segmented packet uses a new large message transaction ID.
+    ///
+    /// Packets which are already stored in a pool are forwarded without a check, because the
+    /// wrapper has no access to the pool. These packets must be checked by their generator.
+    #[derive(Debug)]
+    pub struct SizeLimitedTmSender<Sender> {
+        sender: Sender,
+        limit: TmSizeLimit,
+        next_transaction_id: AtomicU16,
+    }
+
+    impl<Sender: EcssTmSender> SizeLimitedTmSender<Sender> {
+        pub fn new(sender: Sender, limit: TmSizeLimit) -> Self {
+            Self {
+                sender,
+                limit,
+                next_transaction_id: AtomicU16::new(0),
+            }
+        }
+
+        pub fn sender(&self) -> &Sender {
+            &self.sender
+        }
+
+        pub fn limit(&self) -> &TmSizeLimit {
+            &self.limit
+        }
+
+        pub fn set_limit(&mut self, limit: TmSizeLimit) {
+            self.limit = limit;
+        }
+
+        fn segment(&self, sender_id: ComponentId, tm: &PusTmCreator) -> Result<(), EcssTmtcError> {
+            let len = tm.len_written();
+            let max_part_len = self
+                .limit
+                .max_source_data_len(tm.timestamp().len())
+                .saturating_sub(PartHeader::LEN);
+            let mut creator =
+                PusLargeDataTmCreator::new(sender_id, tm.apid(), tm.dest_id(), max_part_len)
+                    .ok_or(EcssTmtcError::TmTooLarge {
+                        len,
+                        max_len: self.limit.max_packet_len,
+                    })?;
+            let raw_tm = tm.to_vec().map_err(EcssTmtcError::Pus)?;
+            let transaction_id = self.next_transaction_id.fetch_add(1, Ordering::Relaxed);
+            match creator.send_large_data(transaction_id, &raw_tm, tm.timestamp(), &self.sender) {
+                Ok(_) => Ok(()),
+                Err(LargeDataError::Send(e)) => Err(e),
+                Err(LargeDataError::ByteConversion(e)) => Err(EcssTmtcError::ByteConversion(e)),
+                Err(_) => Err(EcssTmtcError::TmTooLarge {
+                    len,
+                    max_len: self.limit.max_packet_len,
+                }),
+            }
+        }
+    }
+
+    impl<Sender: EcssTmSender + Clone> Clone for SizeLimitedTmSender<Sender> {
+        fn clone(&self) -> Self {
+            Self {
+                sender: self.sender.clone(),
+                limit: self.limit,
+                next_transaction_id: AtomicU16::new(
+                    self.next_transaction_id.load(Ordering::Relaxed),
+                ),
+            }
+        }
+    }
+
+    impl<Sender: EcssTmSender> EcssTmSender for SizeLimitedTmSender<Sender> {
+        fn send_tm(&self, sender_id: ComponentId, tm: PusTmVariant) -> Result<(), EcssTmtcError> {
+            if let PusTmVariant::Direct(tm) = &tm {
+                if let Err(e) = self.limit.check(tm) {
+                    return match self.limit.policy {
+                        TmSizePolicy::Reject => Err(e),
+                        TmSizePolicy::Segment => self.segment(sender_id, tm),
+                    };
+                }
+            }
+            self.sender.send_tm(sender_id, tm)
+        }
+
+        fn send_tm_batch(
+            &self,
+            sender_id: ComponentId,
+            tms: &[PusTmCreator],
+        ) -> Result<(), EcssTmtcError> {
+            if tms.iter().all(|tm| self.limit.check(tm).is_ok()) {
+                return self.sender.send_tm_batch(sender_id, tms);
+            }
+            for tm in tms {
+                self.send_tm(sender_id, PusTmVariant::Direct(tm.clone()))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::sync::mpsc;
+    use std::vec;
+
+    use spacepackets::ecss::tm::{PusTmReader, PusTmSecondaryHeader};
+    use spacepackets::ecss::PusPacket;
+    use spacepackets::SpHeader;
+
+    use super::*;
+    use crate::pus::large_data::{LargeDataSubservice, PartHeader};
+    use crate::pus::{EcssTmSender, PusTmVariant};
+    use crate::tmtc::PacketAsVec;
+
+    const TEST_APID: u16 = 0x10;
+    const TEST_ID: crate::ComponentId = 0x05;
+    const TIME_STAMP: [u8; 7] = [0; 7];
+
+    fn tm_with_source_data(source_data: &[u8]) -> PusTmCreator<'_, '_> {
+        let sec_header = PusTmSecondaryHeader::new(5, 1, 0, 2, &TIME_STAMP);
+        PusTmCreator::new(
+            SpHeader::new_from_apid(TEST_APID),
+            sec_header,
+            source_data,
+            true,
+        )
+    }
+
+    #[test]
+    fn test_limit_calculations() {
+        assert!(TmSizeLimit::new(TM_OVERHEAD_WITHOUT_TIMESTAMP, TmSizePolicy::Reject).is_none());
+        let limit = TmSizeLimit::new(64, TmSizePolicy::Reject).unwrap();
+        assert_eq!(limit.max_packet_len(), 64);
+        assert_eq!(limit.max_source_data_len(TIME_STAMP.len()), 42);
+        assert_eq!(limit.max_source_data_len(100), 0);
+        let source_data = [0; 42];
+        assert!(limit.check(&tm_with_source_data(&source_data)).is_ok());
+        let source_data = [0; 43];
+        assert_eq!(
+            limit.check(&tm_with_source_data(&source_data)),
+            Err(EcssTmtcError::TmTooLarge {
+                len: 65,
+                max_len: 64
+            })
+        );
+    }
+
+    #[test]
+    fn test_reject_oversized_tm() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let sender =
+            SizeLimitedTmSender::new(tm_tx, TmSizeLimit::new(64, TmSizePolicy::Reject).unwrap());
+        let source_data = [1; 42];
+        sender
+            .send_tm(TEST_ID, tm_with_source_data(&source_data).into())
+            .unwrap();
+        assert_eq!(tm_rx.try_recv().unwrap().packet.len(), 64);
+        let source_data = [1; 100];
+        let result = sender.send_tm(TEST_ID, tm_with_source_data(&source_data).into());
+        assert!(matches!(
+            result,
+            Err(EcssTmtcError::TmTooLarge {
+                len: 122,
+                max_len: 64
+            })
+        ));
+        assert!(tm_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_segment_oversized_tm() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let limit = TmSizeLimit::new(64, TmSizePolicy::Segment).unwrap();
+        let sender = SizeLimitedTmSender::new(tm_tx, limit);
+        let source_data: vec::Vec<u8> = (0..100).collect();
+        let tm = tm_with_source_data(&source_data);
+        let raw_tm = tm.to_vec().unwrap();
+        sender.send_tm(TEST_ID, PusTmVariant::Direct(tm)).unwrap();
+
+        let mut reassembled = vec::Vec::new();
+        let mut subservices = vec::Vec::new();
+        while let Ok(packet) = tm_rx.try_recv() {
+            assert_eq!(packet.sender_id, TEST_ID);
+            assert!(packet.packet.len() <= limit.max_packet_len());
+            let (reader, _) = PusTmReader::new(&packet.packet, TIME_STAMP.len()).unwrap();
+            assert_eq!(reader.service(), 13);
+            assert_eq!(reader.apid(), TEST_APID);
+            assert_eq!(reader.dest_id(), 2);
+            let header = PartHeader::from_be_bytes(reader.source_data()).unwrap();
+            assert_eq!(header.transaction_id, 0);
+            subservices.push(reader.subservice());
+            reassembled.extend_from_slice(&reader.source_data()[PartHeader::LEN..]);
+        }
+        assert_eq!(reassembled, raw_tm);
+        assert_eq!(subservices.len(), 4);
+        assert_eq!(
+            subservices[0],
+            LargeDataSubservice::TmFirstDownlinkPart as u8
+        );
+        assert_eq!(
+            *subservices.last().unwrap(),
+            LargeDataSubservice::TmLastDownlinkPart as u8
+        );
+    }
+
+    #[test]
+    fn test_segment_batch() {
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let sender =
+            SizeLimitedTmSender::new(tm_tx, TmSizeLimit::new(64, TmSizePolicy::Segment).unwrap());
+        let small = [0; 4];
+        let large = [0; 60];
+        sender
+            .send_tm_batch(
+                TEST_ID,
+                &[tm_with_source_data(&small), tm_with_source_data(&large)],
+            )
+            .unwrap();
+        assert_eq!(tm_rx.try_iter().count(), 4);
+    }
+}
@@ -82,27 +82,35 @@
 //! for the verification module contains examples how this module could be used in a more complex
 //! context involving multiple threads
 use crate::params::{Params, WritableToBeBytes};
-use crate::pus::{source_buffer_large_enough, EcssTmSender, EcssTmtcError};
+use crate::pool::PoolError;
+use crate::pus::{source_buffer_large_enough, EcssTmSender, EcssTmtcError, PusTmVariant};
+use core::cell::RefCell;
 use core::fmt::{Debug, Display, Formatter};
 use core::hash::{Hash, Hasher};
 use core::marker::PhantomData;
 use core::mem::size_of;
 #[cfg(feature = "alloc")]
 use delegate::delegate;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use spacepackets::ecss::tc::IsPusTelecommand;
+use spacepackets::ecss::tc::{AckOpts, GenericPusTcSecondaryHeader, IsPusTelecommand, ACK_ALL};
 use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
-use spacepackets::ecss::EcssEnumeration;
+use spacepackets::ecss::{EcssEnumU16, EcssEnumU32, EcssEnumU8, EcssEnumeration, PusError};
+use spacepackets::SpHeader;
 use spacepackets::{ByteConversionError, CcsdsPacket, PacketId, PacketSequenceCtrl};
-use spacepackets::{SpHeader, MAX_APID};
+#[cfg(feature = "std")]
+use std::error::Error;
 
 pub use crate::seq_count::SeqCountProviderSimple;
 pub use spacepackets::ecss::verification::*;
 
 #[cfg(feature = "alloc")]
 pub use alloc_mod::*;
+#[cfg(feature = "std")]
+pub use std_mod::*;
 
+use crate::apid::ApidHandle;
 use crate::request::Apid;
 use crate::ComponentId;
 
@@ -221,23 +229,73 @@ impl<T> From<VerificationErrorWithToken<T>> for VerificationOrSendErrorWithToken
 
 /// Support token to allow type-state programming. This prevents calling the verification
 /// steps in an invalid order.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+///
+/// The token can also carry the source ID and the acknowledgement flags of the telecommand, which
+/// are preserved by all state transitions. This allows all components which receive the token,
+/// for example request handlers or reply handlers, to determine the origin of the telecommand and
+/// the requested success reports. Tokens are compared by their request ID only.
+#[derive(Debug, Clone, Copy)]
 pub struct VerificationToken<STATE> {
     state: PhantomData<STATE>,
     request_id: RequestId,
+    source_id: Option<u16>,
+    ack_flags: Option<u8>,
 }
 
+impl<STATE> PartialEq for VerificationToken<STATE> {
+    fn eq(&self, other: &Self) -> bool {
+        self.request_id == other.request_id
+    }
+}
+
+impl<STATE> Eq for VerificationToken<STATE> {}
+
 impl<STATE> VerificationToken<STATE> {
     fn new(req_id: RequestId) -> VerificationToken<TcStateNone> {
         VerificationToken {
             state: PhantomData,
             request_id: req_id,
+            source_id: None,
+            ack_flags: None,
         }
     }
 
     pub fn request_id(&self) -> RequestId {
         self.request_id
     }
+
+    /// Source ID of the telecommand, if it is known.
+    pub fn source_id(&self) -> Option<u16> {
+        self.source_id
+    }
+
+    /// Attach the source ID of the telecommand to the token.
+    pub fn with_source_id(mut self, source_id: u16) -> Self {
+        self.source_id = Some(source_id);
+        self
+    }
+
+    /// Acknowledgement flags of the telecommand, if they are known.
+    pub fn ack_flags(&self) -> Option<u8> {
+        self.ack_flags
+    }
+
+    /// Attach the acknowledgement flags of the telecommand to the token.
+    pub fn with_ack_flags(mut self, ack_flags: u8) -> Self {
+        self.ack_flags = Some(ack_flags);
+        self
+    }
+
+    /// Token for the next verification state which keeps the request ID, the source ID and the
+    /// acknowledgement flags.
+    fn next_state<NEXT>(&self) -> VerificationToken<NEXT> {
+        VerificationToken {
+            state: PhantomData,
+            request_id: self.request_id,
+            source_id: self.source_id,
+            ack_flags: self.ack_flags,
+        }
+    }
 }
 
 impl VerificationToken<TcStateAccepted> {
@@ -247,6 +305,8 @@ impl VerificationToken<TcStateAccepted> {
         VerificationToken {
             state: PhantomData,
             request_id: req_id,
+            source_id: None,
+            ack_flags: None,
         }
     }
 }
@@ -258,6 +318,8 @@ impl VerificationToken<TcStateStarted> {
         VerificationToken {
             state: PhantomData,
             request_id: req_id,
+            source_id: None,
+            ack_flags: None,
         }
     }
 }
@@ -296,6 +358,16 @@ impl TcStateToken {
             TcStateToken::Completed(token) => token.request_id(),
         }
     }
+
+    /// Source ID of the telecommand, if it is known.
+    pub fn source_id(&self) -> Option<u16> {
+        match self {
+            TcStateToken::None(token) => token.source_id(),
+            TcStateToken::Accepted(token) => token.source_id(),
+            TcStateToken::Started(token) => token.source_id(),
+            TcStateToken::Completed(token) => token.source_id(),
+        }
+    }
 }
 
 impl From<VerificationToken<TcStateNone>> for TcStateToken {
@@ -346,7 +418,243 @@ impl From<VerificationToken<TcStateCompleted>> for TcStateToken {
     }
 }
 
+/// Width of the step field used by the [StepTrackingToken].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StepWidth {
+    U8,
+    U16,
+    U32,
+}
+
+impl StepWidth {
+    pub const fn max_step(&self) -> u32 {
+        match self {
+            StepWidth::U8 => u8::MAX as u32,
+            StepWidth::U16 => u16::MAX as u32,
+            StepWidth::U32 => u32::MAX,
+        }
+    }
+}
+
+/// Determines the behaviour of the [StepTrackingToken] after the maximum step number for the
+/// configured [StepWidth] was reported.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StepOverflowPolicy {
+    /// Keep reporting the maximum step number.
+    Saturate,
+    /// Continue with step number 0.
+    WrapAround,
+    /// Return [StepTrackingError::StepOverflow] for all further step reports.
+    Error,
+}
+
+/// Determines how the acknowledgement flags of telecommands are handled by reporters which
+/// support them, for example the [VerificationReporter].
+///
+/// The acknowledgement flags only request the success reports. Failure reports are always
+/// generated as specified in the PUS standard.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AckFlagPolicy {
+    /// Generate all success reports regardless of the acknowledgement flags.
+    #[default]
+    Ignore,
+    /// Only generate the success reports requested by the acknowledgement flags of the
+    /// telecommand.
+    Honor,
+    /// Only generate the success reports requested by the given acknowledgement flags, and
+    /// ignore the flags of the telecommand.
+    Override(u8),
+}
+
+impl AckFlagPolicy {
+    /// Effective acknowledgement flags for a telecommand with the given acknowledgement flags.
+    pub const fn effective_flags(&self, tc_ack_flags: u8) -> u8 {
+        match self {
+            AckFlagPolicy::Ignore => ACK_ALL,
+            AckFlagPolicy::Honor => tc_ack_flags,
+            AckFlagPolicy::Override(ack_flags) => *ack_flags,
+        }
+    }
+
+    /// Returns whether the success report for the given acknowledgement option is requested for
+    /// a telecommand with the given acknowledgement flags.
+    pub const fn report_requested(&self, tc_ack_flags: u8, ack: AckOpts) -> bool {
+        self.effective_flags(tc_ack_flags) & ack as u8 != 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepTrackingError {
+    /// The maximum step number was already reported and the overflow policy is
+    /// [StepOverflowPolicy::Error].
+    StepOverflow,
+    Tmtc(EcssTmtcError),
+}
+
+impl Display for StepTrackingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            StepTrackingError::StepOverflow => write!(f, "step number overflow"),
+            StepTrackingError::Tmtc(e) => write!(f, "step tracking error: {e}"),
+        }
+    }
+}
+
+impl From<EcssTmtcError> for StepTrackingError {
+    fn from(value: EcssTmtcError) -> Self {
+        Self::Tmtc(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for StepTrackingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StepTrackingError::Tmtc(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Wrapper around a [VerificationToken] in the started state which tracks the current step number
+/// internally.
+///
+/// This can be used by handlers with many steps which do not want to manage the step
+/// enumerations manually. The [Self::step_success_auto] and [Self::step_failure_auto] methods
+/// report the current step and [Self::step_success_auto] increments the step number afterwards.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StepTrackingToken {
+    token: VerificationToken<TcStateStarted>,
+    current_step: u32,
+    width: StepWidth,
+    policy: StepOverflowPolicy,
+    overflowed: bool,
+}
+
+impl VerificationToken<TcStateStarted> {
+    /// Create a [StepTrackingToken] which starts with the given step number.
+    pub fn with_step_tracking(
+        self,
+        first_step: u32,
+        width: StepWidth,
+        policy: StepOverflowPolicy,
+    ) -> StepTrackingToken {
+        StepTrackingToken::new(self, first_step, width, policy)
+    }
+}
+
+impl StepTrackingToken {
+    /// The first step number is clamped to the maximum step number of the step width.
+    pub fn new(
+        token: VerificationToken<TcStateStarted>,
+        first_step: u32,
+        width: StepWidth,
+        policy: StepOverflowPolicy,
+    ) -> Self {
+        Self {
+            token,
+            current_step: first_step.min(width.max_step()),
+            width,
+            policy,
+            overflowed: false,
+        }
+    }
+
+    pub fn token(&self) -> VerificationToken<TcStateStarted> {
+        self.token
+    }
+
+    /// Step number which will be reported on the next step report.
+    pub fn current_step(&self) -> u32 {
+        self.current_step
+    }
+
+    pub fn width(&self) -> StepWidth {
+        self.width
+    }
+
+    pub fn policy(&self) -> StepOverflowPolicy {
+        self.policy
+    }
+
+    /// Report a step success for the current step and increment the step number. Returns the
+    /// reported step number.
+    pub fn step_success_auto(
+        &mut self,
+        reporter: &impl VerificationReportingProvider,
+        sender: &(impl EcssTmSender + ?Sized),
+        time_stamp: &[u8],
+    ) -> Result<u32, StepTrackingError> {
+        if self.overflowed {
+            return Err(StepTrackingError::StepOverflow);
+        }
+        let step = self.current_step;
+        match self.width {
+            StepWidth::U8 => {
+                reporter.step_success(sender, &self.token, time_stamp, EcssEnumU8::new(step as u8))
+            }
+            StepWidth::U16 => reporter.step_success(
+                sender,
+                &self.token,
+                time_stamp,
+                EcssEnumU16::new(step as u16),
+            ),
+            StepWidth::U32 => {
+                reporter.step_success(sender, &self.token, time_stamp, EcssEnumU32::new(step))
+            }
+        }?;
+        self.increment_step();
+        Ok(step)
+    }
+
+    /// Report a step failure for the current step. This consumes the token because verification
+    /// handling is done.
+    pub fn step_failure_auto(
+        self,
+        reporter: &impl VerificationReportingProvider,
+        sender: &(impl EcssTmSender + ?Sized),
+        params: FailParams,
+    ) -> Result<(), StepTrackingError> {
+        if self.overflowed {
+            return Err(StepTrackingError::StepOverflow);
+        }
+        let step_u8 = EcssEnumU8::new(self.current_step as u8);
+        let step_u16 = EcssEnumU16::new(self.current_step as u16);
+        let step_u32 = EcssEnumU32::new(self.current_step);
+        let step: &dyn EcssEnumeration = match self.width {
+            StepWidth::U8 => &step_u8,
+            StepWidth::U16 => &step_u16,
+            StepWidth::U32 => &step_u32,
+        };
+        reporter.step_failure(
+            sender,
+            self.token,
+            FailParamsWithStep {
+                common: params,
+                step,
+            },
+        )?;
+        Ok(())
+    }
+
+    fn increment_step(&mut self) {
+        if self.current_step < self.width.max_step() {
+            self.current_step += 1;
+            return;
+        }
+        match self.policy {
+            StepOverflowPolicy::Saturate => (),
+            StepOverflowPolicy::WrapAround => self.current_step = 0,
+            StepOverflowPolicy::Error => self.overflowed = true,
+        }
+    }
+}
+
 /// Composite helper struct to pass failure parameters to the [VerificationReporter]
+#[derive(Copy, Clone)]
 pub struct FailParams<'stamp, 'fargs> {
     pub time_stamp: &'stamp [u8],
     pub failure_code: &'fargs dyn EcssEnumeration,
@@ -410,13 +718,25 @@ pub trait VerificationReportingProvider {
 
     fn add_tc(
         &mut self,
-        pus_tc: &(impl CcsdsPacket + IsPusTelecommand),
+        pus_tc: &(impl CcsdsPacket + IsPusTelecommand + GenericPusTcSecondaryHeader),
     ) -> VerificationToken<TcStateNone> {
-        self.add_tc_with_req_id(RequestId::new(pus_tc))
+        self.add_tc_with_req_id_and_source_id(RequestId::new(pus_tc), pus_tc.source_id())
+            .with_ack_flags(pus_tc.ack_flags())
     }
 
     fn add_tc_with_req_id(&mut self, req_id: RequestId) -> VerificationToken<TcStateNone>;
 
+    /// Same as [Self::add_tc_with_req_id], but also supplies the source ID of the telecommand.
+    /// Reporters can use the source ID to route the verification TM back to the originator of the
+    /// telecommand. The default implementation only attaches the source ID to the token.
+    fn add_tc_with_req_id_and_source_id(
+        &mut self,
+        req_id: RequestId,
+        source_id: u16,
+    ) -> VerificationToken<TcStateNone> {
+        self.add_tc_with_req_id(req_id).with_source_id(source_id)
+    }
+
     fn acceptance_success(
         &self,
         sender: &(impl EcssTmSender + ?Sized),
@@ -485,27 +805,34 @@ pub trait VerificationReportingProvider {
 #[derive(Clone)]
 pub struct VerificationReportCreator {
     pub dest_id: u16,
-    apid: u16,
+    apid: ApidHandle,
 }
 
 impl VerificationReportCreator {
     pub fn new(apid: u16) -> Option<Self> {
-        if apid > MAX_APID {
-            return None;
+        Some(Self::new_with_apid_handle(ApidHandle::new_fixed(apid)?))
+    }
+
+    /// Create a report creator which reads the APID from the given handle, for example a
+    /// [crate::apid::SharedApid] which can be changed at run-time.
+    pub fn new_with_apid_handle(apid: impl Into<ApidHandle>) -> Self {
+        Self {
+            apid: apid.into(),
+            dest_id: 0,
         }
-        Some(Self { apid, dest_id: 0 })
     }
 
+    /// Set the APID. Please note that this changes the APID of all users of a shared APID.
     pub fn set_apid(&mut self, apid: u16) -> bool {
-        if apid > MAX_APID {
-            return false;
-        }
-        self.apid = apid;
-        true
+        self.apid.set_apid(apid)
+    }
+
+    pub fn set_apid_handle(&mut self, apid: impl Into<ApidHandle>) {
+        self.apid = apid.into();
     }
 
     pub fn apid(&self) -> u16 {
-        self.apid
+        self.apid.apid()
     }
 
     pub fn dest_id(&self) -> u16 {
@@ -599,13 +926,7 @@ impl VerificationReportCreator {
             msg_count,
             time_stamp,
         )?;
-        Ok((
-            tm_creator,
-            VerificationToken {
-                state: PhantomData,
-                request_id: token.request_id(),
-            },
-        ))
+        Ok((tm_creator, token.next_state()))
     }
 
     /// Package a PUS TM\[1, 2\] packet, see 8.1.2.2 of the PUS standard.
@@ -653,13 +974,7 @@ impl VerificationReportCreator {
             msg_count,
             time_stamp,
         )?;
-        Ok((
-            tm_creator,
-            VerificationToken {
-                state: PhantomData,
-                request_id: token.request_id(),
-            },
-        ))
+        Ok((tm_creator, token.next_state()))
     }
 
     /// Package and send a PUS TM\[1, 4\] packet, see 8.1.2.4 of the PUS standard.
@@ -824,6 +1139,12 @@ impl VerificationReportCreator {
         step: Option<&(impl EcssEnumeration + ?Sized)>,
         params: &FailParams<'time, '_>,
     ) -> Result<PusTmCreator<'time, 'src_data>, ByteConversionError> {
+        trace_warn!(
+            "verification failure TM[1,{}] for request ID {:#x}, failure code {}",
+            subservice,
+            req_id.raw(),
+            params.failure_code.value()
+        );
         let mut idx = 0;
         let mut source_data_len = RequestId::SIZE_AS_BYTES + params.failure_code.size();
         if let Some(step) = step {
@@ -875,17 +1196,549 @@ impl VerificationReportCreator {
     }
 }
 
+/// This trait allows hooking into the TM generation process of the verification reporters.
+///
+/// The [Self::modify_tm] function is called before the TM is sent. This allows users to change
+/// fields like the message count or sequence counter before the TM is sent.
+pub trait VerificationHookProvider {
+    fn modify_tm(&self, tm: &mut PusTmCreator);
+}
+
+/// [VerificationHookProvider] which does nothing. This is the default hook variant for
+/// the verification reporters, assuming that any necessary packet manipulation is performed by
+/// a centralized TM funnel or inlet.
+#[derive(Default, Copy, Clone)]
+pub struct DummyVerificationHook {}
+
+impl VerificationHookProvider for DummyVerificationHook {
+    fn modify_tm(&self, _tm: &mut PusTmCreator) {}
+}
+
+/// Apply the hook to a verification packet created by a [VerificationReportCreator] and send
+/// it. This is the common send path of all verification reporters.
+fn send_verification_tm(
+    owner_id: ComponentId,
+    tm_hook: &impl VerificationHookProvider,
+    sender: &(impl EcssTmSender + ?Sized),
+    mut tm_creator: PusTmCreator,
+) -> Result<(), EcssTmtcError> {
+    tm_hook.modify_tm(&mut tm_creator);
+    sender.send_tm(owner_id, PusTmVariant::Direct(tm_creator))
+}
+
+/// Size of the source data buffer which is required for verification reports with the given
+/// step field width, failure code field width and maximum failure data length.
+pub const fn verification_source_data_len(
+    step_field_width: usize,
+    fail_code_field_width: usize,
+    max_fail_data_len: usize,
+) -> usize {
+    RequestId::SIZE_AS_BYTES + step_field_width + fail_code_field_width + max_fail_data_len
+}
+
+/// Allocation-free variant of the [VerificationReporter] which can be used without [`alloc`]
+/// support.
+///
+/// The source data buffer is stored inline with the size `SOURCE_DATA_BUF_SIZE`, which can be
+/// calculated with [verification_source_data_len]. Reports which do not fit into the buffer are
+/// rejected with a [ByteConversionError]. The packets are created by the same
+/// [VerificationReportCreator] which is used by the [VerificationReporter], but the destination
+/// ID is always the static destination ID of the report creator.
+///
+/// # Example
+///
+/// ```
+/// use satrs::pus::verification::{
+///     verification_source_data_len, StaticVerificationReporter,
+/// };
+///
+/// const SOURCE_DATA_LEN: usize = verification_source_data_len(1, 2, 8);
+/// let reporter = StaticVerificationReporter::<SOURCE_DATA_LEN>::new(1, 0x02).unwrap();
+/// assert_eq!(reporter.allowed_source_data_len(), 15);
+/// ```
+pub struct StaticVerificationReporter<
+    const SOURCE_DATA_BUF_SIZE: usize,
+    VerificationHook: VerificationHookProvider = DummyVerificationHook,
+> {
+    owner_id: ComponentId,
+    source_data_buf: RefCell<[u8; SOURCE_DATA_BUF_SIZE]>,
+    pub reporter_creator: VerificationReportCreator,
+    pub tm_hook: VerificationHook,
+}
+
+impl<const SOURCE_DATA_BUF_SIZE: usize> StaticVerificationReporter<SOURCE_DATA_BUF_SIZE> {
+    /// Returns [None] if the APID is invalid.
+    pub fn new(owner_id: ComponentId, apid: u16) -> Option<Self> {
+        Some(Self::new_with_hook(
+            owner_id,
+            ApidHandle::new_fixed(apid)?,
+            DummyVerificationHook::default(),
+        ))
+    }
+}
+
+impl<const SOURCE_DATA_BUF_SIZE: usize, VerificationHook: VerificationHookProvider>
+    StaticVerificationReporter<SOURCE_DATA_BUF_SIZE, VerificationHook>
+{
+    /// The provided [VerificationHookProvider] can be used to modify a verification packet
+    /// before it is sent.
+    pub fn new_with_hook(
+        owner_id: ComponentId,
+        apid: impl Into<ApidHandle>,
+        tm_hook: VerificationHook,
+    ) -> Self {
+        Self {
+            owner_id,
+            source_data_buf: RefCell::new([0; SOURCE_DATA_BUF_SIZE]),
+            reporter_creator: VerificationReportCreator::new_with_apid_handle(apid),
+            tm_hook,
+        }
+    }
+
+    pub fn dest_id(&self) -> u16 {
+        self.reporter_creator.dest_id()
+    }
+
+    pub fn set_dest_id(&mut self, dest_id: u16) {
+        self.reporter_creator.set_dest_id(dest_id);
+    }
+
+    pub fn allowed_source_data_len(&self) -> usize {
+        SOURCE_DATA_BUF_SIZE
+    }
+}
+
+impl<const SOURCE_DATA_BUF_SIZE: usize, VerificationHook: VerificationHookProvider>
+    VerificationReportingProvider
+    for StaticVerificationReporter<SOURCE_DATA_BUF_SIZE, VerificationHook>
+{
+    fn owner_id(&self) -> ComponentId {
+        self.owner_id
+    }
+
+    fn set_apid(&mut self, apid: Apid) {
+        self.reporter_creator.set_apid(apid);
+    }
+
+    fn apid(&self) -> Apid {
+        self.reporter_creator.apid()
+    }
+
+    fn add_tc_with_req_id(&mut self, req_id: RequestId) -> VerificationToken<TcStateNone> {
+        self.reporter_creator.add_tc_with_req_id(req_id)
+    }
+
+    fn acceptance_success(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateNone>,
+        time_stamp: &[u8],
+    ) -> Result<VerificationToken<TcStateAccepted>, EcssTmtcError> {
+        let mut buf = self.source_data_buf.borrow_mut();
+        let (tm_creator, token) = self
+            .reporter_creator
+            .acceptance_success(buf.as_mut_slice(), token, 0, 0, time_stamp)
+            .map_err(PusError::ByteConversion)?;
+        send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
+        Ok(token)
+    }
+
+    fn acceptance_failure(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateNone>,
+        params: FailParams,
+    ) -> Result<(), EcssTmtcError> {
+        let mut buf = self.source_data_buf.borrow_mut();
+        let tm_creator = self
+            .reporter_creator
+            .acceptance_failure(buf.as_mut_slice(), token, 0, 0, params)
+            .map_err(PusError::ByteConversion)?;
+        send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)
+    }
+
+    fn start_success(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateAccepted>,
+        time_stamp: &[u8],
+    ) -> Result<VerificationToken<TcStateStarted>, EcssTmtcError> {
+        let mut buf = self.source_data_buf.borrow_mut();
+        let (tm_creator, started_token) = self
+            .reporter_creator
+            .start_success(buf.as_mut_slice(), token, 0, 0, time_stamp)
+            .map_err(PusError::ByteConversion)?;
+        send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
+        Ok(started_token)
+    }
+
+    fn start_failure(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateAccepted>,
+        params: FailParams,
+    ) -> Result<(), EcssTmtcError> {
+        let mut buf = self.source_data_buf.borrow_mut();
+        let tm_creator = self
+            .reporter_creator
+            .start_failure(buf.as_mut_slice(), token, 0, 0, params)
+            .map_err(PusError::ByteConversion)?;
+        send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)
+    }
+
+    fn step_success(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: &VerificationToken<TcStateStarted>,
+        time_stamp: &[u8],
+        step: impl EcssEnumeration,
+    ) -> Result<(), EcssTmtcError> {
+        let mut buf = self.source_data_buf.borrow_mut();
+        let tm_creator = self
+            .reporter_creator
+            .step_success(buf.as_mut_slice(), token, 0, 0, time_stamp, step)
+            .map_err(PusError::ByteConversion)?;
+        send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)
+    }
+
+    fn step_failure(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateStarted>,
+        params: FailParamsWithStep,
+    ) -> Result<(), EcssTmtcError> {
+        let mut buf = self.source_data_buf.borrow_mut();
+        let tm_creator = self
+            .reporter_creator
+            .step_failure(buf.as_mut_slice(), token, 0, 0, params)
+            .map_err(PusError::ByteConversion)?;
+        send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)
+    }
+
+    fn completion_success<TcState: WasAtLeastAccepted + Copy>(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcState>,
+        time_stamp: &[u8],
+    ) -> Result<(), EcssTmtcError> {
+        let mut buf = self.source_data_buf.borrow_mut();
+        let tm_creator = self
+            .reporter_creator
+            .completion_success(buf.as_mut_slice(), token, 0, 0, time_stamp)
+            .map_err(PusError::ByteConversion)?;
+        send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)
+    }
+
+    fn completion_failure<TcState: WasAtLeastAccepted + Copy>(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcState>,
+        params: FailParams,
+    ) -> Result<(), EcssTmtcError> {
+        let mut buf = self.source_data_buf.borrow_mut();
+        let tm_creator = self
+            .reporter_creator
+            .completion_failure(buf.as_mut_slice(), token, 0, 0, params)
+            .map_err(PusError::ByteConversion)?;
+        send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)
+    }
+}
+
+/// Verification state of a telecommand which is tracked inside a verification journal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum JournalTcState {
+    Accepted = 0,
+    Started = 1,
+}
+
+/// Entry of a verification journal. It contains the request ID of an outstanding telecommand and
+/// its last reported verification state.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JournalEntry {
+    pub req_id: RequestId,
+    pub state: JournalTcState,
+}
+
+impl JournalEntry {
+    /// Length of the serialized entry: The raw request ID followed by the state byte.
+    pub const SIZE_AS_BYTES: usize = RequestId::SIZE_AS_BYTES + 1;
+
+    pub fn new(req_id: RequestId, state: JournalTcState) -> Self {
+        Self { req_id, state }
+    }
+
+    /// Re-create the verification token for the journal entry. This can be used to resume the
+    /// verification of a telecommand after a reboot.
+    pub fn token(&self) -> TcStateToken {
+        match self.state {
+            JournalTcState::Accepted => {
+                TcStateToken::Accepted(VerificationToken::new_accepted_state(self.req_id))
+            }
+            JournalTcState::Started => {
+                TcStateToken::Started(VerificationToken::new_started_state(self.req_id))
+            }
+        }
+    }
+
+    /// Serialize the entry, which can be useful for journal implementations storing raw bytes.
+    pub fn write_to_be_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+        if buf.len() < Self::SIZE_AS_BYTES {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: Self::SIZE_AS_BYTES,
+            });
+        }
+        self.req_id.to_bytes(&mut buf[0..RequestId::SIZE_AS_BYTES]);
+        buf[RequestId::SIZE_AS_BYTES] = self.state as u8;
+        Ok(Self::SIZE_AS_BYTES)
+    }
+
+    /// Deserialize an entry previously written with [Self::write_to_be_bytes]. Returns [None]
+    /// if the buffer is too short or the state byte is invalid.
+    pub fn from_bytes(buf: &[u8]) -> Option<Self> {
+        if buf.len() < Self::SIZE_AS_BYTES {
+            return None;
+        }
+        let state = JournalTcState::try_from(buf[RequestId::SIZE_AS_BYTES]).ok()?;
+        Some(Self {
+            req_id: RequestId::from_bytes(&buf[0..RequestId::SIZE_AS_BYTES])?,
+            state,
+        })
+    }
+}
+
+/// Generic abstraction for a journal which keeps track of all outstanding telecommands, which
+/// are telecommands which were accepted but for which no completion or failure was reported yet.
+///
+/// The journal is generally backed by some non-volatile storage, for example a file or an
+/// EEPROM/FRAM region. This allows the [JournalingVerificationReporter] to resume or fail-close
+/// the verification of outstanding telecommands after a reboot.
+pub trait VerificationJournalProvider {
+    /// Insert an entry or update the state of an existing entry with the same request ID.
+    fn record(&mut self, entry: JournalEntry) -> Result<(), PoolError>;
+    /// Remove the entry for the given request ID. Removing an unknown request ID is not an
+    /// error.
+    fn remove(&mut self, req_id: &RequestId) -> Result<(), PoolError>;
+    /// Call the passed closure for all entries in the journal.
+    fn for_each_entry(&self, f: impl FnMut(&JournalEntry)) -> Result<(), PoolError>;
+    /// Remove all entries from the journal.
+    fn clear(&mut self) -> Result<(), PoolError>;
+}
+
+/// Wrapper around a generic [VerificationReportingProvider] which records the verification state
+/// of all outstanding telecommands inside a [VerificationJournalProvider].
+///
+/// Accepted and started telecommands are recorded before the corresponding verification TM is
+/// sent, and the entry is removed after a completion, start or step failure was reported. After a
+/// reboot, [Self::for_each_outstanding_token] can be used to resume the verification of
+/// outstanding telecommands, or [Self::fail_outstanding] can be used to emit completion failure
+/// TM for all of them.
+pub struct JournalingVerificationReporter<
+    Reporter: VerificationReportingProvider,
+    Journal: VerificationJournalProvider,
+> {
+    pub reporter: Reporter,
+    journal: RefCell<Journal>,
+}
+
+impl<Reporter: VerificationReportingProvider, Journal: VerificationJournalProvider>
+    JournalingVerificationReporter<Reporter, Journal>
+{
+    pub fn new(reporter: Reporter, journal: Journal) -> Self {
+        Self {
+            reporter,
+            journal: RefCell::new(journal),
+        }
+    }
+
+    pub fn journal_mut(&mut self) -> &mut Journal {
+        self.journal.get_mut()
+    }
+
+    pub fn into_inner(self) -> (Reporter, Journal) {
+        (self.reporter, self.journal.into_inner())
+    }
+
+    /// Call the passed closure with the re-created verification token for each outstanding
+    /// telecommand inside the journal.
+    pub fn for_each_outstanding_token(
+        &self,
+        mut f: impl FnMut(TcStateToken),
+    ) -> Result<(), PoolError> {
+        self.journal
+            .borrow()
+            .for_each_entry(|entry| f(entry.token()))
+    }
+
+    /// Send a completion failure TM for all outstanding telecommands inside the journal and
+    /// clear the journal afterwards. This is useful to fail-close all telecommands which were
+    /// accepted before a reboot. Returns the number of sent completion failures.
+    ///
+    /// The journal is only cleared if all completion failures were sent successfully.
+    pub fn fail_outstanding(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        params: FailParams,
+    ) -> Result<u32, EcssTmtcError> {
+        let mut num_failed = 0;
+        let mut send_result = Ok(());
+        self.journal.borrow().for_each_entry(|entry| {
+            if send_result.is_err() {
+                return;
+            }
+            send_result = match entry.token() {
+                TcStateToken::Accepted(token) => {
+                    self.reporter.completion_failure(sender, token, params)
+                }
+                TcStateToken::Started(token) => {
+                    self.reporter.completion_failure(sender, token, params)
+                }
+                _ => Ok(()),
+            };
+            if send_result.is_ok() {
+                num_failed += 1;
+            }
+        })?;
+        send_result?;
+        self.journal.borrow_mut().clear()?;
+        Ok(num_failed)
+    }
+
+    fn record(&self, req_id: RequestId, state: JournalTcState) -> Result<(), EcssTmtcError> {
+        self.journal
+            .borrow_mut()
+            .record(JournalEntry::new(req_id, state))
+            .map_err(EcssTmtcError::Store)
+    }
+
+    fn remove(&self, req_id: &RequestId) -> Result<(), EcssTmtcError> {
+        self.journal
+            .borrow_mut()
+            .remove(req_id)
+            .map_err(EcssTmtcError::Store)
+    }
+}
+
+impl<Reporter: VerificationReportingProvider, Journal: VerificationJournalProvider>
+    VerificationReportingProvider for JournalingVerificationReporter<Reporter, Journal>
+{
+    fn owner_id(&self) -> ComponentId {
+        self.reporter.owner_id()
+    }
+
+    fn set_apid(&mut self, apid: Apid) {
+        self.reporter.set_apid(apid)
+    }
+
+    fn apid(&self) -> Apid {
+        self.reporter.apid()
+    }
+
+    fn add_tc_with_req_id(&mut self, req_id: RequestId) -> VerificationToken<TcStateNone> {
+        self.reporter.add_tc_with_req_id(req_id)
+    }
+
+    fn add_tc_with_req_id_and_source_id(
+        &mut self,
+        req_id: RequestId,
+        source_id: u16,
+    ) -> VerificationToken<TcStateNone> {
+        self.reporter
+            .add_tc_with_req_id_and_source_id(req_id, source_id)
+    }
+
+    fn acceptance_success(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateNone>,
+        time_stamp: &[u8],
+    ) -> Result<VerificationToken<TcStateAccepted>, EcssTmtcError> {
+        self.record(token.request_id(), JournalTcState::Accepted)?;
+        self.reporter.acceptance_success(sender, token, time_stamp)
+    }
+
+    fn acceptance_failure(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateNone>,
+        params: FailParams,
+    ) -> Result<(), EcssTmtcError> {
+        self.reporter.acceptance_failure(sender, token, params)
+    }
+
+    fn start_success(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateAccepted>,
+        time_stamp: &[u8],
+    ) -> Result<VerificationToken<TcStateStarted>, EcssTmtcError> {
+        self.record(token.request_id(), JournalTcState::Started)?;
+        self.reporter.start_success(sender, token, time_stamp)
+    }
+
+    fn start_failure(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateAccepted>,
+        params: FailParams,
+    ) -> Result<(), EcssTmtcError> {
+        self.reporter.start_failure(sender, token, params)?;
+        self.remove(&token.request_id())
+    }
+
+    fn step_success(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: &VerificationToken<TcStateStarted>,
+        time_stamp: &[u8],
+        step: impl EcssEnumeration,
+    ) -> Result<(), EcssTmtcError> {
+        self.reporter.step_success(sender, token, time_stamp, step)
+    }
+
+    fn step_failure(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcStateStarted>,
+        params: FailParamsWithStep,
+    ) -> Result<(), EcssTmtcError> {
+        self.reporter.step_failure(sender, token, params)?;
+        self.remove(&token.request_id())
+    }
+
+    fn completion_success<TcState: WasAtLeastAccepted + Copy>(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcState>,
+        time_stamp: &[u8],
+    ) -> Result<(), EcssTmtcError> {
+        self.reporter
+            .completion_success(sender, token, time_stamp)?;
+        self.remove(&token.request_id())
+    }
+
+    fn completion_failure<TcState: WasAtLeastAccepted + Copy>(
+        &self,
+        sender: &(impl EcssTmSender + ?Sized),
+        token: VerificationToken<TcState>,
+        params: FailParams,
+    ) -> Result<(), EcssTmtcError> {
+        self.reporter.completion_failure(sender, token, params)?;
+        self.remove(&token.request_id())
+    }
+}
+
 #[cfg(feature = "alloc")]
 pub mod alloc_mod {
-    use spacepackets::ecss::PusError;
-
     use super::*;
-    use crate::pus::PusTmVariant;
-    use core::cell::RefCell;
+    use alloc::collections::BTreeMap;
 
     #[derive(Clone)]
     pub struct VerificationReporterCfg {
-        apid: u16,
+        apid: ApidHandle,
         pub step_field_width: usize,
         pub fail_code_field_width: usize,
         pub max_fail_data_len: usize,
@@ -898,34 +1751,55 @@ pub mod alloc_mod {
             fail_code_field_width: usize,
             max_fail_data_len: usize,
         ) -> Option<Self> {
-            if apid > MAX_APID {
-                return None;
-            }
-            Some(Self {
-                apid,
+            Some(Self::new_with_apid_handle(
+                ApidHandle::new_fixed(apid)?,
                 step_field_width,
                 fail_code_field_width,
                 max_fail_data_len,
-            })
+            ))
         }
-    }
 
-    /// This trait allows hooking into the TM generation process of the [VerificationReporter].
-    ///
-    /// The [Self::modify_tm] function is called before the TM is sent. This allows users to change
-    /// fields like the message count or sequence counter before the TM is sent.
-    pub trait VerificationHookProvider {
-        fn modify_tm(&self, tm: &mut PusTmCreator);
+        /// Like [Self::new], but the reporter reads the APID from the given handle, for example
+        /// a [crate::apid::SharedApid] which can be changed at run-time.
+        pub fn new_with_apid_handle(
+            apid: impl Into<ApidHandle>,
+            step_field_width: usize,
+            fail_code_field_width: usize,
+            max_fail_data_len: usize,
+        ) -> Self {
+            Self {
+                apid: apid.into(),
+                step_field_width,
+                fail_code_field_width,
+                max_fail_data_len,
+            }
+        }
     }
 
-    /// [VerificationHookProvider] which does nothing. This is the default hook variant for
-    /// the [VerificationReporter], assuming that any necessary packet manipulation is performed by
-    /// a centralized TM funnel or inlet.
-    #[derive(Default, Copy, Clone)]
-    pub struct DummyVerificationHook {}
+    /// Determines the destination ID of the verification TM generated by the
+    /// [VerificationReporter].
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    pub enum DestIdRouting {
+        /// Always use the static destination ID of the reporter.
+        #[default]
+        Static,
+        /// Use the source ID of the telecommand as the destination ID.
+        SourceId,
+        /// Map the source ID of the telecommand to a destination ID. The static destination ID
+        /// of the reporter is used for unknown source IDs.
+        Mapped(BTreeMap<u16, u16>),
+    }
 
-    impl VerificationHookProvider for DummyVerificationHook {
-        fn modify_tm(&self, _tm: &mut PusTmCreator) {}
+    impl DestIdRouting {
+        /// Destination ID for a telecommand with the given source ID, or [None] if the static
+        /// destination ID should be used.
+        pub fn dest_id(&self, tc_source_id: u16) -> Option<u16> {
+            match self {
+                DestIdRouting::Static => None,
+                DestIdRouting::SourceId => Some(tc_source_id),
+                DestIdRouting::Mapped(mapping) => mapping.get(&tc_source_id).copied(),
+            }
+        }
     }
 
     /// Primary verification reportewr object. It provides an API to send PUS 1 verification
@@ -933,8 +1807,19 @@ pub mod alloc_mod {
     /// PUS standard.
     ///
     /// It is assumed that the sequence counter and message counters are updated in a central
-    /// TM funnel or TM inlet. This helper will always set those fields to 0. The APID and
-    /// destination fields are assumed to be constant for a given repoter instance.
+    /// TM funnel or TM inlet. This helper will always set those fields to 0. The APID is assumed
+    /// to be constant for a given repoter instance.
+    ///
+    /// The destination ID is constant as well by default. Missions with multiple ground control
+    /// centers can configure a [DestIdRouting] so that the destination ID is derived from the
+    /// source ID of the telecommand, which is supplied when adding the telecommand with
+    /// [Self::add_tc] or [Self::add_tc_with_req_id_and_source_id] and carried by the
+    /// verification token.
+    ///
+    /// All success reports are generated by default. An [AckFlagPolicy] can be configured to
+    /// skip the success reports which were not requested by the acknowledgement flags of the
+    /// telecommand. The flags are attached to the verification token by [Self::add_tc] or with
+    /// [VerificationToken::with_ack_flags].
     #[derive(Clone)]
     pub struct VerificationReporter<
         VerificationHook: VerificationHookProvider = DummyVerificationHook,
@@ -942,12 +1827,14 @@ pub mod alloc_mod {
         owner_id: ComponentId,
         source_data_buf: RefCell<alloc::vec::Vec<u8>>,
         pub reporter_creator: VerificationReportCreator,
+        pub dest_id_routing: DestIdRouting,
+        pub ack_flag_policy: AckFlagPolicy,
         pub tm_hook: VerificationHook,
     }
 
     impl VerificationReporter<DummyVerificationHook> {
         pub fn new(owner_id: ComponentId, cfg: &VerificationReporterCfg) -> Self {
-            let reporter = VerificationReportCreator::new(cfg.apid).unwrap();
+            let reporter = VerificationReportCreator::new_with_apid_handle(cfg.apid.clone());
             Self {
                 owner_id,
                 source_data_buf: RefCell::new(alloc::vec![
@@ -958,6 +1845,8 @@ pub mod alloc_mod {
                         + cfg.max_fail_data_len
                 ]),
                 reporter_creator: reporter,
+                dest_id_routing: DestIdRouting::default(),
+                ack_flag_policy: AckFlagPolicy::default(),
                 tm_hook: DummyVerificationHook::default(),
             }
         }
@@ -971,7 +1860,7 @@ pub mod alloc_mod {
             cfg: &VerificationReporterCfg,
             tm_hook: VerificationHook,
         ) -> Self {
-            let reporter = VerificationReportCreator::new(cfg.apid).unwrap();
+            let reporter = VerificationReportCreator::new_with_apid_handle(cfg.apid.clone());
             Self {
                 owner_id,
                 source_data_buf: RefCell::new(alloc::vec![
@@ -982,23 +1871,162 @@ pub mod alloc_mod {
                         + cfg.max_fail_data_len
                 ]),
                 reporter_creator: reporter,
+                dest_id_routing: DestIdRouting::default(),
+                ack_flag_policy: AckFlagPolicy::default(),
                 tm_hook,
             }
         }
 
-        delegate!(
-            to self.reporter_creator {
-                pub fn set_apid(&mut self, apid: u16) -> bool;
-                pub fn apid(&self) -> u16;
-                pub fn add_tc(&mut self, pus_tc: &(impl CcsdsPacket + IsPusTelecommand)) -> VerificationToken<TcStateNone>;
-                pub fn add_tc_with_req_id(&mut self, req_id: RequestId) -> VerificationToken<TcStateNone>;
-                pub fn dest_id(&self) -> u16;
-                pub fn set_dest_id(&mut self, dest_id: u16);
-            }
-        );
-
-        pub fn allowed_source_data_len(&self) -> usize {
-            self.source_data_buf.borrow().capacity()
+        delegate!(
+            to self.reporter_creator {
+                pub fn set_apid(&mut self, apid: u16) -> bool;
+                pub fn set_apid_handle(&mut self, apid: impl Into<ApidHandle>);
+                pub fn apid(&self) -> u16;
+                pub fn add_tc_with_req_id(&mut self, req_id: RequestId) -> VerificationToken<TcStateNone>;
+                pub fn dest_id(&self) -> u16;
+                pub fn set_dest_id(&mut self, dest_id: u16);
+            }
+        );
+
+        /// Initialize verification handling by passing a TC reference. The source ID of the
+        /// telecommand is used to determine the destination ID of the verification TM if a
+        /// [DestIdRouting] is configured.
+        pub fn add_tc(
+            &mut self,
+            pus_tc: &(impl CcsdsPacket + IsPusTelecommand + GenericPusTcSecondaryHeader),
+        ) -> VerificationToken<TcStateNone> {
+            self.add_tc_with_req_id_and_source_id(RequestId::new(pus_tc), pus_tc.source_id())
+                .with_ack_flags(pus_tc.ack_flags())
+        }
+
+        /// Same as [Self::add_tc_with_req_id], but also supplies the source ID of the
+        /// telecommand which is used for the [DestIdRouting].
+        pub fn add_tc_with_req_id_and_source_id(
+            &mut self,
+            req_id: RequestId,
+            source_id: u16,
+        ) -> VerificationToken<TcStateNone> {
+            self.reporter_creator
+                .add_tc_with_req_id(req_id)
+                .with_source_id(source_id)
+        }
+
+        /// Returns whether the success report for the given acknowledgement option is requested
+        /// for the token. All success reports are requested for tokens without acknowledgement
+        /// flags.
+        fn success_report_requested<State>(
+            &self,
+            token: &VerificationToken<State>,
+            ack: AckOpts,
+        ) -> bool {
+            self.ack_flag_policy
+                .report_requested(token.ack_flags().unwrap_or(ACK_ALL), ack)
+        }
+
+        pub fn allowed_source_data_len(&self) -> usize {
+            self.source_data_buf.borrow().capacity()
+        }
+
+        /// Report creator with the destination ID which is derived from the source ID carried by
+        /// the token. The destination ID is not tracked by the reporter, so tokens can be passed
+        /// between reporters, for example from the TC distributor to the service handlers.
+        fn report_creator(&self, source_id: Option<u16>) -> VerificationReportCreator {
+            let mut creator = self.reporter_creator.clone();
+            if let Some(dest_id) = source_id.and_then(|id| self.dest_id_routing.dest_id(id)) {
+                creator.set_dest_id(dest_id);
+            }
+            creator
+        }
+
+        /// Package a success report with the given subservice for each token and send all
+        /// reports with a single [EcssTmSender::send_tm_batch] call.
+        fn success_batch<State: Copy>(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            subservice: Subservice,
+            ack: AckOpts,
+            tokens: &[VerificationToken<State>],
+            time_stamp: &[u8],
+        ) -> Result<(), EcssTmtcError> {
+            let mut source_data = alloc::vec![0; tokens.len() * RequestId::SIZE_AS_BYTES];
+            let mut tms = alloc::vec::Vec::with_capacity(tokens.len());
+            for (token, buf) in tokens
+                .iter()
+                .zip(source_data.chunks_exact_mut(RequestId::SIZE_AS_BYTES))
+            {
+                if !self.success_report_requested(token, ack) {
+                    continue;
+                }
+                let mut tm_creator = self
+                    .report_creator(token.source_id())
+                    .create_pus_verif_success_tm(
+                        buf,
+                        subservice.into(),
+                        0,
+                        0,
+                        &token.request_id(),
+                        time_stamp,
+                        None::<&dyn EcssEnumeration>,
+                    )
+                    .map_err(PusError::ByteConversion)?;
+                self.tm_hook.modify_tm(&mut tm_creator);
+                tms.push(tm_creator);
+            }
+            sender.send_tm_batch(self.owner_id, &tms)
+        }
+
+        /// Batched variant of [VerificationReportingProvider::acceptance_success]. All PUS
+        /// TM\[1, 1\] packets are sent with a single [EcssTmSender::send_tm_batch] call, which
+        /// reduces the lock contention when many telecommands are accepted in the same cycle.
+        pub fn acceptance_success_batch(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            tokens: &[VerificationToken<TcStateNone>],
+            time_stamp: &[u8],
+        ) -> Result<alloc::vec::Vec<VerificationToken<TcStateAccepted>>, EcssTmtcError> {
+            self.success_batch(
+                sender,
+                Subservice::TmAcceptanceSuccess,
+                AckOpts::Acceptance,
+                tokens,
+                time_stamp,
+            )?;
+            Ok(tokens.iter().map(|token| token.next_state()).collect())
+        }
+
+        /// Batched variant of [VerificationReportingProvider::start_success] which sends all
+        /// PUS TM\[1, 3\] packets with a single [EcssTmSender::send_tm_batch] call.
+        pub fn start_success_batch(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            tokens: &[VerificationToken<TcStateAccepted>],
+            time_stamp: &[u8],
+        ) -> Result<alloc::vec::Vec<VerificationToken<TcStateStarted>>, EcssTmtcError> {
+            self.success_batch(
+                sender,
+                Subservice::TmStartSuccess,
+                AckOpts::Start,
+                tokens,
+                time_stamp,
+            )?;
+            Ok(tokens.iter().map(|token| token.next_state()).collect())
+        }
+
+        /// Batched variant of [VerificationReportingProvider::completion_success] which sends
+        /// all PUS TM\[1, 7\] packets with a single [EcssTmSender::send_tm_batch] call.
+        pub fn completion_success_batch<TcState: WasAtLeastAccepted + Copy>(
+            &self,
+            sender: &(impl EcssTmSender + ?Sized),
+            tokens: &[VerificationToken<TcState>],
+            time_stamp: &[u8],
+        ) -> Result<(), EcssTmtcError> {
+            self.success_batch(
+                sender,
+                Subservice::TmCompletionSuccess,
+                AckOpts::Completion,
+                tokens,
+                time_stamp,
+            )
         }
     }
 
@@ -1009,11 +2037,18 @@ pub mod alloc_mod {
             to self.reporter_creator {
                 fn set_apid(&mut self, apid: Apid);
                 fn apid(&self) -> Apid;
-                fn add_tc(&mut self, pus_tc: &(impl CcsdsPacket + IsPusTelecommand)) -> VerificationToken<TcStateNone>;
                 fn add_tc_with_req_id(&mut self, req_id: RequestId) -> VerificationToken<TcStateNone>;
             }
         );
 
+        fn add_tc_with_req_id_and_source_id(
+            &mut self,
+            req_id: RequestId,
+            source_id: u16,
+        ) -> VerificationToken<TcStateNone> {
+            VerificationReporter::add_tc_with_req_id_and_source_id(self, req_id, source_id)
+        }
+
         fn owner_id(&self) -> ComponentId {
             self.owner_id
         }
@@ -1025,13 +2060,15 @@ pub mod alloc_mod {
             token: VerificationToken<TcStateNone>,
             time_stamp: &[u8],
         ) -> Result<VerificationToken<TcStateAccepted>, EcssTmtcError> {
+            if !self.success_report_requested(&token, AckOpts::Acceptance) {
+                return Ok(token.next_state());
+            }
             let mut source_data_buf = self.source_data_buf.borrow_mut();
-            let (mut tm_creator, token) = self
-                .reporter_creator
+            let (tm_creator, token) = self
+                .report_creator(token.source_id())
                 .acceptance_success(source_data_buf.as_mut_slice(), token, 0, 0, time_stamp)
                 .map_err(PusError::ByteConversion)?;
-            self.tm_hook.modify_tm(&mut tm_creator);
-            sender.send_tm(self.owner_id(), PusTmVariant::Direct(tm_creator))?;
+            send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
             Ok(token)
         }
 
@@ -1043,12 +2080,11 @@ pub mod alloc_mod {
             params: FailParams,
         ) -> Result<(), EcssTmtcError> {
             let mut buf = self.source_data_buf.borrow_mut();
-            let mut tm_creator = self
-                .reporter_creator
+            let tm_creator = self
+                .report_creator(token.source_id())
                 .acceptance_failure(buf.as_mut_slice(), token, 0, 0, params)
                 .map_err(PusError::ByteConversion)?;
-            self.tm_hook.modify_tm(&mut tm_creator);
-            sender.send_tm(self.owner_id(), PusTmVariant::Direct(tm_creator))?;
+            send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
             Ok(())
         }
 
@@ -1061,13 +2097,15 @@ pub mod alloc_mod {
             token: VerificationToken<TcStateAccepted>,
             time_stamp: &[u8],
         ) -> Result<VerificationToken<TcStateStarted>, EcssTmtcError> {
+            if !self.success_report_requested(&token, AckOpts::Start) {
+                return Ok(token.next_state());
+            }
             let mut buf = self.source_data_buf.borrow_mut();
-            let (mut tm_creator, started_token) = self
-                .reporter_creator
+            let (tm_creator, started_token) = self
+                .report_creator(token.source_id())
                 .start_success(buf.as_mut_slice(), token, 0, 0, time_stamp)
                 .map_err(PusError::ByteConversion)?;
-            self.tm_hook.modify_tm(&mut tm_creator);
-            sender.send_tm(self.owner_id(), PusTmVariant::Direct(tm_creator))?;
+            send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
             Ok(started_token)
         }
 
@@ -1082,12 +2120,11 @@ pub mod alloc_mod {
             params: FailParams,
         ) -> Result<(), EcssTmtcError> {
             let mut buf = self.source_data_buf.borrow_mut();
-            let mut tm_creator = self
-                .reporter_creator
+            let tm_creator = self
+                .report_creator(token.source_id())
                 .start_failure(buf.as_mut_slice(), token, 0, 0, params)
                 .map_err(PusError::ByteConversion)?;
-            self.tm_hook.modify_tm(&mut tm_creator);
-            sender.send_tm(self.owner_id(), PusTmVariant::Direct(tm_creator))?;
+            send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
             Ok(())
         }
 
@@ -1101,13 +2138,15 @@ pub mod alloc_mod {
             time_stamp: &[u8],
             step: impl EcssEnumeration,
         ) -> Result<(), EcssTmtcError> {
+            if !self.success_report_requested(token, AckOpts::Progress) {
+                return Ok(());
+            }
             let mut buf = self.source_data_buf.borrow_mut();
-            let mut tm_creator = self
-                .reporter_creator
+            let tm_creator = self
+                .report_creator(token.source_id())
                 .step_success(buf.as_mut_slice(), token, 0, 0, time_stamp, step)
                 .map_err(PusError::ByteConversion)?;
-            self.tm_hook.modify_tm(&mut tm_creator);
-            sender.send_tm(self.owner_id(), PusTmVariant::Direct(tm_creator))?;
+            send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
             Ok(())
         }
 
@@ -1122,12 +2161,11 @@ pub mod alloc_mod {
             params: FailParamsWithStep,
         ) -> Result<(), EcssTmtcError> {
             let mut buf = self.source_data_buf.borrow_mut();
-            let mut tm_creator = self
-                .reporter_creator
+            let tm_creator = self
+                .report_creator(token.source_id())
                 .step_failure(buf.as_mut_slice(), token, 0, 0, params)
                 .map_err(PusError::ByteConversion)?;
-            self.tm_hook.modify_tm(&mut tm_creator);
-            sender.send_tm(self.owner_id(), PusTmVariant::Direct(tm_creator))?;
+            send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
             Ok(())
         }
 
@@ -1142,13 +2180,15 @@ pub mod alloc_mod {
             token: VerificationToken<TcState>,
             time_stamp: &[u8],
         ) -> Result<(), EcssTmtcError> {
+            if !self.success_report_requested(&token, AckOpts::Completion) {
+                return Ok(());
+            }
             let mut buf = self.source_data_buf.borrow_mut();
-            let mut tm_creator = self
-                .reporter_creator
+            let tm_creator = self
+                .report_creator(token.source_id())
                 .completion_success(buf.as_mut_slice(), token, 0, 0, time_stamp)
                 .map_err(PusError::ByteConversion)?;
-            self.tm_hook.modify_tm(&mut tm_creator);
-            sender.send_tm(self.owner_id, PusTmVariant::Direct(tm_creator))?;
+            send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
             Ok(())
         }
 
@@ -1163,15 +2203,304 @@ pub mod alloc_mod {
             params: FailParams,
         ) -> Result<(), EcssTmtcError> {
             let mut buf = self.source_data_buf.borrow_mut();
-            let mut tm_creator = self
-                .reporter_creator
+            let tm_creator = self
+                .report_creator(token.source_id())
                 .completion_failure(buf.as_mut_slice(), token, 0, 00, params)
                 .map_err(PusError::ByteConversion)?;
-            self.tm_hook.modify_tm(&mut tm_creator);
-            sender.send_tm(self.owner_id(), PusTmVariant::Direct(tm_creator))?;
+            send_verification_tm(self.owner_id, &self.tm_hook, sender, tm_creator)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum VerificationHandleError {
+        /// No verification state is registered for the request ID.
+        UnknownRequestId(RequestId),
+        /// The registered verification state does not allow the requested verification step.
+        InvalidState {
+            req_id: RequestId,
+            state: TcStateToken,
+        },
+        Tmtc(EcssTmtcError),
+    }
+
+    impl Display for VerificationHandleError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                VerificationHandleError::UnknownRequestId(req_id) => {
+                    write!(f, "no verification state for request ID {req_id}")
+                }
+                VerificationHandleError::InvalidState { req_id, state } => {
+                    write!(
+                        f,
+                        "invalid verification state {state:?} for request ID {req_id}"
+                    )
+                }
+                VerificationHandleError::Tmtc(e) => write!(f, "verification handle error: {e}"),
+            }
+        }
+    }
+
+    impl From<EcssTmtcError> for VerificationHandleError {
+        fn from(value: EcssTmtcError) -> Self {
+            Self::Tmtc(value)
+        }
+    }
+
+    impl Error for VerificationHandleError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                VerificationHandleError::Tmtc(e) => Some(e),
+                _ => None,
+            }
+        }
+    }
+
+    /// Thread-safe registry which allows handing over the verification of a telecommand to another
+    /// thread using only the [RequestId] instead of the typed [VerificationToken].
+    ///
+    /// The registry tracks the verification state of each registered telecommand, and the type-state
+    /// guarantees of the [VerificationToken] API are preserved as runtime checks: Each verification
+    /// method checks that the registered state allows the verification step and returns
+    /// [VerificationHandleError::InvalidState] otherwise. The registry is cheap to clone and all
+    /// clones share the same state. Telecommands are removed from the registry after their final
+    /// verification report was sent.
+    #[derive(Debug, Default, Clone)]
+    pub struct VerificationHandleRegistry {
+        tokens: Arc<Mutex<HashMap<RequestId, TcStateToken>>>,
+    }
+
+    impl VerificationHandleRegistry {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Register a verification token. The returned request ID can be passed to another
+        /// thread which continues the verification. An existing entry for the same request ID
+        /// is replaced.
+        pub fn register(&self, token: impl Into<TcStateToken>) -> RequestId {
+            let token = token.into();
+            let req_id = token.request_id();
+            self.tokens.lock().unwrap().insert(req_id, token);
+            req_id
+        }
+
+        /// Current verification state for the given request ID.
+        pub fn state(&self, req_id: &RequestId) -> Option<TcStateToken> {
+            self.tokens.lock().unwrap().get(req_id).copied()
+        }
+
+        pub fn num_registered(&self) -> usize {
+            self.tokens.lock().unwrap().len()
+        }
+
+        /// Remove the entry for the given request ID without any verification reporting.
+        pub fn remove(&self, req_id: &RequestId) -> Option<TcStateToken> {
+            self.tokens.lock().unwrap().remove(req_id)
+        }
+
+        /// Remove the entry for the given request ID and return the typed token if the
+        /// telecommand is in the accepted state. The entry is not removed otherwise.
+        pub fn take_accepted(
+            &self,
+            req_id: &RequestId,
+        ) -> Result<VerificationToken<TcStateAccepted>, VerificationHandleError> {
+            let mut token = None;
+            self.transition(*req_id, |state| match state {
+                TcStateToken::Accepted(accepted) => {
+                    token = Some(accepted);
+                    Ok(None)
+                }
+                _ => Err(invalid_state(*req_id, state)),
+            })?;
+            Ok(token.unwrap())
+        }
+
+        /// Remove the entry for the given request ID and return the typed token if the
+        /// telecommand is in the started state. The entry is not removed otherwise.
+        pub fn take_started(
+            &self,
+            req_id: &RequestId,
+        ) -> Result<VerificationToken<TcStateStarted>, VerificationHandleError> {
+            let mut token = None;
+            self.transition(*req_id, |state| match state {
+                TcStateToken::Started(started) => {
+                    token = Some(started);
+                    Ok(None)
+                }
+                _ => Err(invalid_state(*req_id, state)),
+            })?;
+            Ok(token.unwrap())
+        }
+
+        pub fn acceptance_success(
+            &self,
+            reporter: &impl VerificationReportingProvider,
+            sender: &(impl EcssTmSender + ?Sized),
+            req_id: RequestId,
+            time_stamp: &[u8],
+        ) -> Result<(), VerificationHandleError> {
+            self.transition(req_id, |state| match state {
+                TcStateToken::None(token) => Ok(Some(
+                    reporter
+                        .acceptance_success(sender, token, time_stamp)?
+                        .into(),
+                )),
+                _ => Err(invalid_state(req_id, state)),
+            })
+        }
+
+        pub fn acceptance_failure(
+            &self,
+            reporter: &impl VerificationReportingProvider,
+            sender: &(impl EcssTmSender + ?Sized),
+            req_id: RequestId,
+            params: FailParams,
+        ) -> Result<(), VerificationHandleError> {
+            self.transition(req_id, |state| match state {
+                TcStateToken::None(token) => {
+                    reporter.acceptance_failure(sender, token, params)?;
+                    Ok(None)
+                }
+                _ => Err(invalid_state(req_id, state)),
+            })
+        }
+
+        pub fn start_success(
+            &self,
+            reporter: &impl VerificationReportingProvider,
+            sender: &(impl EcssTmSender + ?Sized),
+            req_id: RequestId,
+            time_stamp: &[u8],
+        ) -> Result<(), VerificationHandleError> {
+            self.transition(req_id, |state| match state {
+                TcStateToken::Accepted(token) => Ok(Some(
+                    reporter.start_success(sender, token, time_stamp)?.into(),
+                )),
+                _ => Err(invalid_state(req_id, state)),
+            })
+        }
+
+        pub fn start_failure(
+            &self,
+            reporter: &impl VerificationReportingProvider,
+            sender: &(impl EcssTmSender + ?Sized),
+            req_id: RequestId,
+            params: FailParams,
+        ) -> Result<(), VerificationHandleError> {
+            self.transition(req_id, |state| match state {
+                TcStateToken::Accepted(token) => {
+                    reporter.start_failure(sender, token, params)?;
+                    Ok(None)
+                }
+                _ => Err(invalid_state(req_id, state)),
+            })
+        }
+
+        pub fn step_success(
+            &self,
+            reporter: &impl VerificationReportingProvider,
+            sender: &(impl EcssTmSender + ?Sized),
+            req_id: RequestId,
+            time_stamp: &[u8],
+            step: impl EcssEnumeration,
+        ) -> Result<(), VerificationHandleError> {
+            self.transition(req_id, |state| match state {
+                TcStateToken::Started(token) => {
+                    reporter.step_success(sender, &token, time_stamp, step)?;
+                    Ok(Some(state))
+                }
+                _ => Err(invalid_state(req_id, state)),
+            })
+        }
+
+        pub fn step_failure(
+            &self,
+            reporter: &impl VerificationReportingProvider,
+            sender: &(impl EcssTmSender + ?Sized),
+            req_id: RequestId,
+            params: FailParamsWithStep,
+        ) -> Result<(), VerificationHandleError> {
+            self.transition(req_id, |state| match state {
+                TcStateToken::Started(token) => {
+                    reporter.step_failure(sender, token, params)?;
+                    Ok(None)
+                }
+                _ => Err(invalid_state(req_id, state)),
+            })
+        }
+
+        /// Completion success for a telecommand in the accepted or started state.
+        pub fn completion_success(
+            &self,
+            reporter: &impl VerificationReportingProvider,
+            sender: &(impl EcssTmSender + ?Sized),
+            req_id: RequestId,
+            time_stamp: &[u8],
+        ) -> Result<(), VerificationHandleError> {
+            self.transition(req_id, |state| match state {
+                TcStateToken::Accepted(token) => {
+                    reporter.completion_success(sender, token, time_stamp)?;
+                    Ok(None)
+                }
+                TcStateToken::Started(token) => {
+                    reporter.completion_success(sender, token, time_stamp)?;
+                    Ok(None)
+                }
+                _ => Err(invalid_state(req_id, state)),
+            })
+        }
+
+        /// Completion failure for a telecommand in the accepted or started state.
+        pub fn completion_failure(
+            &self,
+            reporter: &impl VerificationReportingProvider,
+            sender: &(impl EcssTmSender + ?Sized),
+            req_id: RequestId,
+            params: FailParams,
+        ) -> Result<(), VerificationHandleError> {
+            self.transition(req_id, |state| match state {
+                TcStateToken::Accepted(token) => {
+                    reporter.completion_failure(sender, token, params)?;
+                    Ok(None)
+                }
+                TcStateToken::Started(token) => {
+                    reporter.completion_failure(sender, token, params)?;
+                    Ok(None)
+                }
+                _ => Err(invalid_state(req_id, state)),
+            })
+        }
+
+        /// Apply a state transition for the given request ID. The closure returns the new state,
+        /// or [None] if the entry should be removed. The entry is left unchanged on errors.
+        fn transition(
+            &self,
+            req_id: RequestId,
+            f: impl FnOnce(TcStateToken) -> Result<Option<TcStateToken>, VerificationHandleError>,
+        ) -> Result<(), VerificationHandleError> {
+            let mut tokens = self.tokens.lock().unwrap();
+            let state = *tokens
+                .get(&req_id)
+                .ok_or(VerificationHandleError::UnknownRequestId(req_id))?;
+            match f(state)? {
+                Some(next_state) => tokens.insert(req_id, next_state),
+                None => tokens.remove(&req_id),
+            };
             Ok(())
         }
     }
+
+    fn invalid_state(req_id: RequestId, state: TcStateToken) -> VerificationHandleError {
+        VerificationHandleError::InvalidState { req_id, state }
+    }
 }
 
 pub struct FailParamHelper<'stamp, 'fargs, 'buf, 'params> {
@@ -1375,6 +2704,7 @@ pub mod test_util {
             VerificationToken {
                 state: PhantomData,
                 request_id: req_id,
+                source_id: None,
             }
         }
 
@@ -1391,10 +2721,7 @@ pub mod test_util {
                     time_stamp: time_stamp.to_vec(),
                 }),
             ));
-            Ok(VerificationToken {
-                state: PhantomData,
-                request_id: token.request_id,
-            })
+            Ok(token.next_state())
         }
 
         fn acceptance_failure(
@@ -1428,10 +2755,7 @@ pub mod test_util {
                     time_stamp: time_stamp.to_vec(),
                 }),
             ));
-            Ok(VerificationToken {
-                state: PhantomData,
-                request_id: token.request_id,
-            })
+            Ok(token.next_state())
         }
 
         fn start_failure(
@@ -1692,8 +3016,8 @@ pub mod test_util {
 #[cfg(test)]
 pub mod tests {
     use crate::params::Params;
-    use crate::pool::{SharedStaticMemoryPool, StaticMemoryPool, StaticPoolConfig};
-    use crate::pus::test_util::{TEST_APID, TEST_COMPONENT_ID_0};
+    use crate::pool::{PoolProvider, SharedStaticMemoryPool, StaticMemoryPool, StaticPoolConfig};
+    use crate::pus::test_util::{TEST_APID, TEST_COMPONENT_ID_0, TEST_COMPONENT_ID_1};
     use crate::pus::tests::CommonTmInfo;
     use crate::pus::verification::{
         handle_step_failure_with_generic_params, EcssTmSender, EcssTmtcError, FailParams,
@@ -1703,11 +3027,16 @@ pub mod tests {
     use crate::pus::{ChannelWithId, PusTmVariant};
     use crate::request::MessageMetadata;
     use crate::seq_count::{CcsdsSimpleSeqCountProvider, SequenceCountProviderCore};
-    use crate::tmtc::{PacketSenderWithSharedPool, SharedPacketPool};
+    use crate::tmtc::{
+        PacketAsVec, PacketBatchInPool, PacketSenderWithSharedPool, SharedPacketPool,
+    };
     use crate::ComponentId;
     use alloc::format;
     use alloc::string::ToString;
-    use spacepackets::ecss::tc::{PusTcCreator, PusTcReader, PusTcSecondaryHeader};
+    use spacepackets::ecss::tc::{
+        AckOpts, PusTcCreator, PusTcReader, PusTcSecondaryHeader, ACK_ALL,
+    };
+    use spacepackets::ecss::tm::PusTmReader;
     use spacepackets::ecss::{
         EcssEnumU16, EcssEnumU32, EcssEnumU8, EcssEnumeration, PusError, PusPacket,
         WritablePusPacket,
@@ -1715,16 +3044,24 @@ pub mod tests {
     use spacepackets::util::UnsignedEnum;
     use spacepackets::{ByteConversionError, SpHeader};
     use std::cell::RefCell;
-    use std::collections::VecDeque;
+    use std::collections::{BTreeMap, VecDeque};
     use std::sync::{mpsc, RwLock};
     use std::vec;
     use std::vec::Vec;
 
     use super::{
-        handle_completion_failure_with_generic_params, DummyVerificationHook, FailParamHelper,
-        SeqCountProviderSimple, TcStateAccepted, TcStateStarted, VerificationHookProvider,
-        VerificationReportingProvider, WasAtLeastAccepted,
+        handle_completion_failure_with_generic_params, AckFlagPolicy, DestIdRouting,
+        DummyVerificationHook, FailParamHelper, JournalEntry, JournalTcState,
+        JournalingVerificationReporter, SeqCountProviderSimple, StepOverflowPolicy,
+        StepTrackingError, StepWidth, TcStateAccepted, TcStateStarted, TcStateToken,
+        VerificationHookProvider, VerificationJournalProvider, VerificationReportingProvider,
+        WasAtLeastAccepted,
+    };
+    use super::{
+        verification_source_data_len, StaticVerificationReporter, VerificationHandleError,
+        VerificationHandleRegistry,
     };
+    use crate::pool::PoolError;
 
     fn is_send<T: Send>(_: &T) {}
     #[allow(dead_code)]
@@ -2413,4 +3750,698 @@ pub mod tests {
     fn test_completion_failure_helper_store_param_ignored() {
         // TODO: Test this.
     }
+
+    #[derive(Default)]
+    struct TestJournal {
+        entries: Vec<JournalEntry>,
+    }
+
+    impl VerificationJournalProvider for TestJournal {
+        fn record(&mut self, entry: JournalEntry) -> Result<(), PoolError> {
+            match self.entries.iter_mut().find(|e| e.req_id == entry.req_id) {
+                Some(existing) => existing.state = entry.state,
+                None => self.entries.push(entry),
+            }
+            Ok(())
+        }
+
+        fn remove(&mut self, req_id: &RequestId) -> Result<(), PoolError> {
+            self.entries.retain(|e| &e.req_id != req_id);
+            Ok(())
+        }
+
+        fn for_each_entry(&self, mut f: impl FnMut(&JournalEntry)) -> Result<(), PoolError> {
+            for entry in self.entries.iter() {
+                f(entry);
+            }
+            Ok(())
+        }
+
+        fn clear(&mut self) -> Result<(), PoolError> {
+            self.entries.clear();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_journal_entry_serialization() {
+        let req_id = RequestId::new(&create_generic_ping());
+        let entry = JournalEntry::new(req_id, JournalTcState::Started);
+        let mut buf: [u8; 8] = [0; 8];
+        assert_eq!(
+            entry.write_to_be_bytes(&mut buf).unwrap(),
+            JournalEntry::SIZE_AS_BYTES
+        );
+        assert_eq!(JournalEntry::from_bytes(&buf).unwrap(), entry);
+        buf[RequestId::SIZE_AS_BYTES] = 5;
+        assert!(JournalEntry::from_bytes(&buf).is_none());
+        assert!(entry.write_to_be_bytes(&mut buf[0..4]).is_err());
+    }
+
+    #[test]
+    fn test_journaling_reporter_tracks_outstanding_tcs() {
+        let sender = TestSender::default();
+        let mut reporter = JournalingVerificationReporter::new(
+            base_reporter(TEST_COMPONENT_ID_0.id(), 8),
+            TestJournal::default(),
+        );
+        let ping_0 = create_generic_ping();
+        let ping_1 = PusTcCreator::new_simple(
+            SpHeader::new_for_unseg_tc(TEST_APID, 0x35, 0),
+            17,
+            1,
+            &[],
+            true,
+        );
+        let token_0 = reporter.add_tc(&ping_0);
+        let token_1 = reporter.add_tc(&ping_1);
+        let accepted_0 = reporter
+            .acceptance_success(&sender, token_0, &EMPTY_STAMP)
+            .unwrap();
+        let accepted_1 = reporter
+            .acceptance_success(&sender, token_1, &EMPTY_STAMP)
+            .unwrap();
+        reporter
+            .start_success(&sender, accepted_1, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(
+            reporter.journal_mut().entries,
+            vec![
+                JournalEntry::new(accepted_0.request_id(), JournalTcState::Accepted),
+                JournalEntry::new(accepted_1.request_id(), JournalTcState::Started),
+            ]
+        );
+        reporter
+            .completion_success(&sender, accepted_0, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(reporter.journal_mut().entries.len(), 1);
+
+        // Simulate a reboot by re-creating the reporter with the same journal.
+        let (_, journal) = reporter.into_inner();
+        let reporter = JournalingVerificationReporter::new(
+            base_reporter(TEST_COMPONENT_ID_0.id(), 8),
+            journal,
+        );
+        let mut tokens = Vec::new();
+        reporter
+            .for_each_outstanding_token(|token| tokens.push(token))
+            .unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert!(matches!(tokens[0], TcStateToken::Started(_)));
+        assert_eq!(tokens[0].request_id(), accepted_1.request_id());
+    }
+
+    #[test]
+    fn test_journaling_reporter_fail_outstanding() {
+        let sender = TestSender::default();
+        let mut reporter = JournalingVerificationReporter::new(
+            base_reporter(TEST_COMPONENT_ID_0.id(), 8),
+            TestJournal::default(),
+        );
+        let token = reporter.add_tc(&create_generic_ping());
+        let accepted_token = reporter
+            .acceptance_success(&sender, token, &EMPTY_STAMP)
+            .unwrap();
+        sender.service_queue.borrow_mut().clear();
+
+        let fail_code = EcssEnumU16::new(2);
+        let num_failed = reporter
+            .fail_outstanding(
+                &sender,
+                FailParams::new_no_fail_data(&EMPTY_STAMP, &fail_code),
+            )
+            .unwrap();
+        assert_eq!(num_failed, 1);
+        let info = sender.service_queue.borrow_mut().pop_front().unwrap();
+        assert_eq!(info.common.subservice, 8);
+        assert_eq!(
+            info.requestor.request_id(),
+            accepted_token.request_id().raw()
+        );
+        assert_eq!(info.additional_data, Some(fail_code.to_vec()));
+        assert!(sender.service_queue.borrow().is_empty());
+        assert!(reporter.journal_mut().entries.is_empty());
+    }
+
+    fn ping_with_source_id(seq_count: u16, source_id: u16) -> PusTcCreator<'static> {
+        let mut tc_header = PusTcSecondaryHeader::new_simple(17, 1);
+        tc_header.source_id = source_id;
+        PusTcCreator::new(
+            SpHeader::new_for_unseg_tc(TEST_APID, seq_count, 0),
+            tc_header,
+            &[],
+            true,
+        )
+    }
+
+    #[test]
+    fn test_dest_id_routing_by_source_id() {
+        let sender = TestSender::default();
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        reporter.set_dest_id(5);
+        reporter.dest_id_routing = DestIdRouting::SourceId;
+        let token_0 = reporter.add_tc(&ping_with_source_id(0, 1));
+        let token_1 = reporter.add_tc(&ping_with_source_id(1, 2));
+        let token_2 = reporter.add_tc_with_req_id(RequestId::new(&ping_with_source_id(2, 3)));
+        let accepted_0 = reporter
+            .acceptance_success(&sender, token_0, &EMPTY_STAMP)
+            .unwrap();
+        reporter
+            .acceptance_failure(
+                &sender,
+                token_1,
+                FailParams::new_no_fail_data(&EMPTY_STAMP, &EcssEnumU8::new(1)),
+            )
+            .unwrap();
+        reporter
+            .acceptance_success(&sender, token_2, &EMPTY_STAMP)
+            .unwrap();
+        reporter
+            .completion_success(&sender, accepted_0, &EMPTY_STAMP)
+            .unwrap();
+        let dest_ids: Vec<u16> = sender
+            .service_queue
+            .borrow()
+            .iter()
+            .map(|info| info.common.dest_id)
+            .collect();
+        assert_eq!(dest_ids, vec![1, 2, 5, 1]);
+    }
+
+    #[test]
+    fn test_source_id_is_carried_by_token() {
+        let sender = TestSender::default();
+        let mut distributor_reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        let token = distributor_reporter.add_tc(&ping_with_source_id(0, 3));
+        assert_eq!(token.source_id(), Some(3));
+        let accepted = distributor_reporter
+            .acceptance_success(&sender, token, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(accepted.source_id(), Some(3));
+        assert_eq!(TcStateToken::from(accepted).source_id(), Some(3));
+        assert_eq!(
+            accepted,
+            VerificationToken::new_accepted_state(accepted.request_id())
+        );
+
+        // The handler reporter never saw the telecommand, but can still route the reports with
+        // the source ID of the token.
+        let mut handler_reporter = base_reporter(TEST_COMPONENT_ID_1.id(), 8);
+        handler_reporter.set_dest_id(5);
+        handler_reporter.dest_id_routing = DestIdRouting::SourceId;
+        let started = handler_reporter
+            .start_success(&sender, accepted, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(started.source_id(), Some(3));
+        handler_reporter
+            .completion_success(&sender, started, &EMPTY_STAMP)
+            .unwrap();
+        let dest_ids: Vec<u16> = sender
+            .service_queue
+            .borrow()
+            .iter()
+            .map(|info| info.common.dest_id)
+            .collect();
+        assert_eq!(dest_ids, vec![0, 3, 3]);
+    }
+
+    #[test]
+    fn test_dest_id_routing_mapped() {
+        let sender = TestSender::default();
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        reporter.set_dest_id(5);
+        let mut mapping = BTreeMap::new();
+        mapping.insert(1, 10);
+        reporter.dest_id_routing = DestIdRouting::Mapped(mapping);
+        let token_0 = reporter.add_tc(&ping_with_source_id(0, 1));
+        let token_1 = reporter.add_tc(&ping_with_source_id(1, 2));
+        reporter
+            .acceptance_success(&sender, token_0, &EMPTY_STAMP)
+            .unwrap();
+        reporter
+            .acceptance_success(&sender, token_1, &EMPTY_STAMP)
+            .unwrap();
+        let dest_ids: Vec<u16> = sender
+            .service_queue
+            .borrow()
+            .iter()
+            .map(|info| info.common.dest_id)
+            .collect();
+        assert_eq!(dest_ids, vec![10, 5]);
+    }
+
+    fn ping_with_ack_flags(seq_count: u16, ack_flags: u8) -> PusTcCreator<'static> {
+        PusTcCreator::new(
+            SpHeader::new_for_unseg_tc(TEST_APID, seq_count, 0),
+            PusTcSecondaryHeader::new(17, 1, ack_flags, 0),
+            &[],
+            true,
+        )
+    }
+
+    fn sent_subservices(sender: &TestSender) -> Vec<u8> {
+        sender
+            .service_queue
+            .borrow_mut()
+            .drain(..)
+            .map(|info| info.common.subservice)
+            .collect()
+    }
+
+    #[test]
+    fn test_ack_flags_honored() {
+        let sender = TestSender::default();
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        reporter.ack_flag_policy = AckFlagPolicy::Honor;
+        let token = reporter.add_tc(&ping_with_ack_flags(0, AckOpts::Completion as u8));
+        assert_eq!(token.ack_flags(), Some(AckOpts::Completion as u8));
+        let accepted = reporter
+            .acceptance_success(&sender, token, &EMPTY_STAMP)
+            .unwrap();
+        let started = reporter
+            .start_success(&sender, accepted, &EMPTY_STAMP)
+            .unwrap();
+        reporter
+            .step_success(&sender, &started, &EMPTY_STAMP, EcssEnumU8::new(1))
+            .unwrap();
+        reporter
+            .completion_success(&sender, started, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(sent_subservices(&sender), vec![7]);
+
+        // Failure reports are always generated.
+        let token = reporter.add_tc(&ping_with_ack_flags(1, 0));
+        let accepted = reporter
+            .acceptance_success(&sender, token, &EMPTY_STAMP)
+            .unwrap();
+        reporter
+            .start_failure(
+                &sender,
+                accepted,
+                FailParams::new_no_fail_data(&EMPTY_STAMP, &EcssEnumU8::new(1)),
+            )
+            .unwrap();
+        assert_eq!(sent_subservices(&sender), vec![4]);
+
+        let token = reporter.add_tc(&ping_with_ack_flags(2, ACK_ALL));
+        let accepted = reporter
+            .acceptance_success(&sender, token, &EMPTY_STAMP)
+            .unwrap();
+        reporter
+            .completion_success(&sender, accepted, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(sent_subservices(&sender), vec![1, 7]);
+    }
+
+    #[test]
+    fn test_ack_flags_ignored_and_overridden() {
+        let sender = TestSender::default();
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        let token = reporter.add_tc(&ping_with_ack_flags(0, 0));
+        let accepted = reporter
+            .acceptance_success(&sender, token, &EMPTY_STAMP)
+            .unwrap();
+        reporter
+            .completion_success(&sender, accepted, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(sent_subservices(&sender), vec![1, 7]);
+
+        reporter.ack_flag_policy = AckFlagPolicy::Override(AckOpts::Acceptance as u8);
+        let tokens = [
+            reporter.add_tc(&ping_with_ack_flags(1, ACK_ALL)),
+            reporter.add_tc(&ping_with_ack_flags(2, 0)),
+        ];
+        let accepted = reporter
+            .acceptance_success_batch(&sender, &tokens, &EMPTY_STAMP)
+            .unwrap();
+        let started = reporter
+            .start_success_batch(&sender, &accepted, &EMPTY_STAMP)
+            .unwrap();
+        reporter
+            .completion_success_batch(&sender, &started, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(sent_subservices(&sender), vec![1, 1]);
+    }
+
+    #[test]
+    fn test_ack_flags_are_carried_by_token() {
+        let sender = TestSender::default();
+        // The distributor reporter ignores the flags, but attaches them to the token.
+        let mut distributor_reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        let token = distributor_reporter.add_tc(&ping_with_ack_flags(0, AckOpts::Completion as u8));
+        let accepted = distributor_reporter
+            .acceptance_success(&sender, token, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(accepted.ack_flags(), Some(AckOpts::Completion as u8));
+
+        let mut handler_reporter = base_reporter(TEST_COMPONENT_ID_1.id(), 8);
+        handler_reporter.ack_flag_policy = AckFlagPolicy::Honor;
+        let started = handler_reporter
+            .start_success(&sender, accepted, &EMPTY_STAMP)
+            .unwrap();
+        handler_reporter
+            .completion_success(&sender, started, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(sent_subservices(&sender), vec![1, 7]);
+    }
+
+    fn started_token(
+        reporter: &mut VerificationReporter,
+        sender: &TestSender,
+    ) -> VerificationToken<TcStateStarted> {
+        let token = reporter.add_tc(&create_generic_ping());
+        let accepted_token = reporter
+            .acceptance_success(sender, token, &EMPTY_STAMP)
+            .unwrap();
+        let started_token = reporter
+            .start_success(sender, accepted_token, &EMPTY_STAMP)
+            .unwrap();
+        sender.service_queue.borrow_mut().clear();
+        started_token
+    }
+
+    #[test]
+    fn test_step_tracking_auto_increment() {
+        let sender = TestSender::default();
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        let mut token = started_token(&mut reporter, &sender).with_step_tracking(
+            1,
+            StepWidth::U16,
+            StepOverflowPolicy::Error,
+        );
+        assert_eq!(token.current_step(), 1);
+        for expected_step in 1..=3 {
+            assert_eq!(
+                token
+                    .step_success_auto(&reporter, &sender, &EMPTY_STAMP)
+                    .unwrap(),
+                expected_step
+            );
+        }
+        assert_eq!(token.current_step(), 4);
+        let fail_code = EcssEnumU8::new(7);
+        token
+            .step_failure_auto(
+                &reporter,
+                &sender,
+                FailParams::new(&EMPTY_STAMP, &fail_code, &[1, 2]),
+            )
+            .unwrap();
+        let infos: Vec<TmInfo> = sender.service_queue.borrow_mut().drain(..).collect();
+        assert_eq!(infos.len(), 4);
+        for (idx, info) in infos[0..3].iter().enumerate() {
+            assert_eq!(info.common.subservice, 5);
+            assert_eq!(
+                info.additional_data,
+                Some((idx as u16 + 1).to_be_bytes().to_vec())
+            );
+        }
+        assert_eq!(infos[3].common.subservice, 6);
+        assert_eq!(infos[3].additional_data, Some(vec![0, 4, 7, 1, 2]));
+    }
+
+    #[test]
+    fn test_step_tracking_overflow_policies() {
+        let sender = TestSender::default();
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        let started_token = started_token(&mut reporter, &sender);
+
+        let mut saturating =
+            started_token.with_step_tracking(255, StepWidth::U8, StepOverflowPolicy::Saturate);
+        saturating
+            .step_success_auto(&reporter, &sender, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(saturating.current_step(), 255);
+
+        let mut wrapping =
+            started_token.with_step_tracking(255, StepWidth::U8, StepOverflowPolicy::WrapAround);
+        wrapping
+            .step_success_auto(&reporter, &sender, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(wrapping.current_step(), 0);
+
+        let mut erroring =
+            started_token.with_step_tracking(300, StepWidth::U8, StepOverflowPolicy::Error);
+        assert_eq!(erroring.current_step(), 255);
+        erroring
+            .step_success_auto(&reporter, &sender, &EMPTY_STAMP)
+            .unwrap();
+        assert_eq!(
+            erroring
+                .step_success_auto(&reporter, &sender, &EMPTY_STAMP)
+                .unwrap_err(),
+            StepTrackingError::StepOverflow
+        );
+        assert_eq!(sender.service_queue.borrow().len(), 3);
+    }
+
+    #[test]
+    fn test_verification_handle_registry_handover() {
+        let registry = VerificationHandleRegistry::new();
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        let sender = TestSender::default();
+        let token = reporter.add_tc(&create_generic_ping());
+        let accepted_token = reporter
+            .acceptance_success(&sender, token, &EMPTY_STAMP)
+            .unwrap();
+        let req_id = registry.register(accepted_token);
+        assert_eq!(registry.num_registered(), 1);
+
+        let registry_clone = registry.clone();
+        let reporter_clone = reporter.clone();
+        let subservices = std::thread::spawn(move || {
+            let sender = TestSender::default();
+            registry_clone
+                .start_success(&reporter_clone, &sender, req_id, &EMPTY_STAMP)
+                .unwrap();
+            registry_clone
+                .step_success(
+                    &reporter_clone,
+                    &sender,
+                    req_id,
+                    &EMPTY_STAMP,
+                    EcssEnumU8::new(1),
+                )
+                .unwrap();
+            registry_clone
+                .completion_success(&reporter_clone, &sender, req_id, &EMPTY_STAMP)
+                .unwrap();
+            let subservices: Vec<u8> = sender
+                .service_queue
+                .borrow()
+                .iter()
+                .map(|info| info.common.subservice)
+                .collect();
+            subservices
+        })
+        .join()
+        .unwrap();
+        assert_eq!(subservices, vec![3, 5, 7]);
+        assert_eq!(registry.num_registered(), 0);
+        assert!(registry.state(&req_id).is_none());
+    }
+
+    #[test]
+    fn test_verification_handle_registry_state_checks() {
+        let registry = VerificationHandleRegistry::new();
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        let sender = TestSender::default();
+        let token = reporter.add_tc(&create_generic_ping());
+        let req_id = registry.register(token);
+        let error = registry
+            .start_success(&reporter, &sender, req_id, &EMPTY_STAMP)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            VerificationHandleError::InvalidState {
+                req_id,
+                state: token.into()
+            }
+        );
+        assert!(sender.service_queue.borrow().is_empty());
+        registry
+            .acceptance_success(&reporter, &sender, req_id, &EMPTY_STAMP)
+            .unwrap();
+        assert!(registry.take_started(&req_id).is_err());
+        let accepted_token = registry.take_accepted(&req_id).unwrap();
+        assert_eq!(accepted_token.request_id(), req_id);
+        assert_eq!(
+            registry
+                .completion_success(&reporter, &sender, req_id, &EMPTY_STAMP)
+                .unwrap_err(),
+            VerificationHandleError::UnknownRequestId(req_id)
+        );
+    }
+
+    #[test]
+    fn test_static_reporter_matches_alloc_reporter() {
+        const SOURCE_DATA_LEN: usize = verification_source_data_len(1, 2, 8);
+        let mut static_reporter =
+            StaticVerificationReporter::<SOURCE_DATA_LEN>::new(TEST_COMPONENT_ID_0.id(), TEST_APID)
+                .unwrap();
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        assert_eq!(
+            static_reporter.allowed_source_data_len(),
+            reporter.allowed_source_data_len()
+        );
+        let (static_tx, static_rx) = mpsc::channel::<PacketAsVec>();
+        let (tx, rx) = mpsc::channel::<PacketAsVec>();
+        let fail_code = EcssEnumU16::new(2);
+        let fail_data = [0, 1, 2, 3];
+
+        let token = static_reporter.add_tc(&create_generic_ping());
+        let accepted = static_reporter
+            .acceptance_success(&static_tx, token, DUMMY_STAMP)
+            .unwrap();
+        let started = static_reporter
+            .start_success(&static_tx, accepted, DUMMY_STAMP)
+            .unwrap();
+        static_reporter
+            .step_success(&static_tx, &started, DUMMY_STAMP, EcssEnumU8::new(1))
+            .unwrap();
+        static_reporter
+            .completion_failure(
+                &static_tx,
+                started,
+                FailParams::new(DUMMY_STAMP, &fail_code, &fail_data),
+            )
+            .unwrap();
+
+        let token = reporter.add_tc(&create_generic_ping());
+        let accepted = reporter
+            .acceptance_success(&tx, token, DUMMY_STAMP)
+            .unwrap();
+        let started = reporter.start_success(&tx, accepted, DUMMY_STAMP).unwrap();
+        reporter
+            .step_success(&tx, &started, DUMMY_STAMP, EcssEnumU8::new(1))
+            .unwrap();
+        reporter
+            .completion_failure(
+                &tx,
+                started,
+                FailParams::new(DUMMY_STAMP, &fail_code, &fail_data),
+            )
+            .unwrap();
+
+        for _ in 0..4 {
+            let static_tm = static_rx.try_recv().unwrap();
+            assert_eq!(static_tm.sender_id, TEST_COMPONENT_ID_0.id());
+            assert_eq!(static_tm.packet, rx.try_recv().unwrap().packet);
+        }
+        assert!(static_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_static_reporter_source_data_too_large() {
+        let mut reporter =
+            StaticVerificationReporter::<{ verification_source_data_len(1, 2, 2) }>::new(
+                TEST_COMPONENT_ID_0.id(),
+                TEST_APID,
+            )
+            .unwrap();
+        let (tx, rx) = mpsc::channel::<PacketAsVec>();
+        let token = reporter.add_tc(&create_generic_ping());
+        let error = reporter
+            .acceptance_failure(
+                &tx,
+                token,
+                FailParams::new(EMPTY_STAMP.as_slice(), &EcssEnumU16::new(2), &[0; 4]),
+            )
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            EcssTmtcError::Pus(PusError::ByteConversion(
+                ByteConversionError::ToSliceTooSmall {
+                    found: 9,
+                    expected: 10
+                }
+            ))
+        ));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_batched_success_reports() {
+        let pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(16, 32)],
+            false,
+        ));
+        let shared_pool = SharedStaticMemoryPool::new(RwLock::new(pool));
+        let (batch_tx, batch_rx) = mpsc::channel::<PacketBatchInPool>();
+        let batch_sender =
+            PacketSenderWithSharedPool::new(batch_tx, SharedPacketPool::new(&shared_pool));
+        let mut reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        let tokens: Vec<_> = (0..3)
+            .map(|seq_count| {
+                let sph = SpHeader::new_for_unseg_tc(TEST_APID, seq_count, 0);
+                let tc_header = PusTcSecondaryHeader::new_simple(17, 1);
+                reporter.add_tc(&PusTcCreator::new(sph, tc_header, &[], true))
+            })
+            .collect();
+        let accepted = reporter
+            .acceptance_success_batch(&batch_sender, &tokens, DUMMY_STAMP)
+            .unwrap();
+        assert_eq!(accepted.len(), 3);
+        // All reports were sent with a single message.
+        let batch = batch_rx.try_recv().unwrap();
+        assert!(batch_rx.try_recv().is_err());
+        assert_eq!(batch.sender_id, TEST_COMPONENT_ID_0.id());
+        assert_eq!(batch.store_addrs.len(), 3);
+        let pool = shared_pool.read().unwrap();
+        for (addr, token) in batch.store_addrs.iter().zip(&accepted) {
+            let raw_tm = pool.read_as_vec(addr).unwrap();
+            let (tm, _) = PusTmReader::new(&raw_tm, 7).unwrap();
+            assert_eq!((tm.service(), tm.subservice()), (1, 1));
+            assert_eq!(tm.timestamp(), DUMMY_STAMP);
+            assert_eq!(
+                RequestId::from_bytes(tm.source_data()),
+                Some(token.request_id())
+            );
+        }
+        drop(pool);
+
+        // Senders without batch support send the reports individually.
+        let (tx, rx) = mpsc::channel::<PacketAsVec>();
+        let started = reporter
+            .start_success_batch(&tx, &accepted, DUMMY_STAMP)
+            .unwrap();
+        reporter
+            .completion_success_batch(&tx, &started, DUMMY_STAMP)
+            .unwrap();
+        for (idx, subservice) in [3, 3, 3, 7, 7, 7].into_iter().enumerate() {
+            let tm = rx.try_recv().unwrap();
+            let (tm, _) = PusTmReader::new(&tm.packet, 7).unwrap();
+            assert_eq!(tm.subservice(), subservice);
+            assert_eq!(
+                RequestId::from_bytes(tm.source_data()),
+                Some(tokens[idx % 3].request_id())
+            );
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_batch_is_rolled_back_if_pool_is_full() {
+        let pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(2, 32)],
+            false,
+        ));
+        let shared_pool = SharedStaticMemoryPool::new(RwLock::new(pool));
+        let (batch_tx, batch_rx) = mpsc::channel::<PacketBatchInPool>();
+        let batch_sender =
+            PacketSenderWithSharedPool::new(batch_tx, SharedPacketPool::new(&shared_pool));
+        let reporter = base_reporter(TEST_COMPONENT_ID_0.id(), 8);
+        let tokens: Vec<_> = (0..3)
+            .map(|raw: u32| VerificationToken::<TcStateNone>::new(RequestId::from(raw)))
+            .collect();
+        let error = reporter
+            .acceptance_success_batch(&batch_sender, &tokens, DUMMY_STAMP)
+            .unwrap_err();
+        assert!(matches!(error, EcssTmtcError::Store(_)));
+        assert!(batch_rx.try_recv().is_err());
+        // The two reports which were added before the pool was full were deleted again.
+        let mut pool = shared_pool.write().unwrap();
+        pool.add(&[0; 16]).unwrap();
+        pool.add(&[0; 16]).unwrap();
+    }
 }
@@ -8,6 +8,9 @@ pub use alloc_mod::*;
 #[cfg(feature = "std")]
 pub use std_mod::*;
 
+#[cfg(feature = "heapless")]
+pub use heapless_mod::*;
+
 use spacepackets::{
     ecss::{tc::IsPusTelecommand, PusPacket},
     ByteConversionError,
@@ -36,11 +39,11 @@ impl UniqueApidTargetId {
         }
     }
 
-    pub fn raw(&self) -> ComponentId {
+    pub const fn raw(&self) -> ComponentId {
         ((self.apid as u64) << 32) | (self.unique_id as u64)
     }
 
-    pub fn id(&self) -> ComponentId {
+    pub const fn id(&self) -> ComponentId {
         self.raw()
     }
 
@@ -113,6 +116,12 @@ impl MessageMetadata {
     pub fn sender_id(&self) -> ComponentId {
         self.sender_id
     }
+
+    /// Metadata for a reply to the message described by this metadata. The reply keeps the
+    /// request ID so that it can be correlated with the original request by the requestor.
+    pub const fn reply_metadata(&self, replier_id: ComponentId) -> Self {
+        Self::new(self.request_id, replier_id)
+    }
 }
 
 /// Generic message type which adds [metadata][MessageMetadata] to a generic message typ.
@@ -137,6 +146,17 @@ impl<Message> GenericMessage<Message> {
             pub fn sender_id(&self) -> ComponentId;
         }
     }
+
+    /// Create a reply message for this message. The reply has the same request ID and the
+    /// passed replier ID as the sender ID.
+    pub fn reply<Reply>(&self, replier_id: ComponentId, reply: Reply) -> GenericMessage<Reply> {
+        GenericMessage::new(self.requestor_info.reply_metadata(replier_id), reply)
+    }
+
+    /// Checks whether this message is a reply to a request with the given metadata.
+    pub fn is_reply_to(&self, request_info: &MessageMetadata) -> bool {
+        self.request_id() == request_info.request_id()
+    }
 }
 
 /// Generic trait for objects which can send targeted messages.
@@ -193,6 +213,7 @@ impl<MSG, R: MessageReceiver<MSG>> MessageReceiverWithId<MSG, R> {
 
 #[cfg(feature = "alloc")]
 pub mod alloc_mod {
+    use crate::pus::PusRequestRouter;
     use crate::queue::GenericSendError;
 
     use super::*;
@@ -229,6 +250,33 @@ pub mod alloc_mod {
             }
             Err(GenericSendError::TargetDoesNotExist(target_channel_id).into())
         }
+
+        /// Send a reply to the sender of the request with the given metadata.
+        pub fn send_reply(
+            &self,
+            request_info: &MessageMetadata,
+            replier_id: ComponentId,
+            reply: MSG,
+        ) -> Result<(), GenericTargetedMessagingError> {
+            self.send_message(
+                request_info.reply_metadata(replier_id),
+                request_info.sender_id(),
+                reply,
+            )
+        }
+    }
+
+    impl<MSG, S: MessageSender<MSG>> PusRequestRouter<MSG> for MessageSenderMap<MSG, S> {
+        type Error = GenericTargetedMessagingError;
+
+        fn route(
+            &self,
+            requestor_info: MessageMetadata,
+            target_id: ComponentId,
+            request: MSG,
+        ) -> Result<(), Self::Error> {
+            self.send_message(requestor_info, target_id, request)
+        }
     }
 
     pub struct MessageSenderAndReceiver<TO, FROM, S: MessageSender<TO>, R: MessageReceiver<FROM>> {
@@ -372,6 +420,114 @@ pub mod std_mod {
     }
 
     pub type MessageReceiverWithIdMpsc<MSG> = MessageReceiverWithId<MSG, mpsc::Receiver<MSG>>;
+
+    #[cfg(feature = "crossbeam")]
+    pub mod crossbeam {
+        use super::*;
+        use crossbeam_channel as cb;
+
+        impl<MSG: Send> MessageSender<MSG> for cb::Sender<GenericMessage<MSG>> {
+            fn send(
+                &self,
+                message: GenericMessage<MSG>,
+            ) -> Result<(), GenericTargetedMessagingError> {
+                self.try_send(message).map_err(GenericSendError::from)?;
+                Ok(())
+            }
+        }
+
+        impl<MSG> MessageReceiver<MSG> for cb::Receiver<GenericMessage<MSG>> {
+            fn try_recv(
+                &self,
+            ) -> Result<Option<GenericMessage<MSG>>, GenericTargetedMessagingError> {
+                match self.try_recv() {
+                    Ok(msg) => Ok(Some(msg)),
+                    Err(e) => match e {
+                        cb::TryRecvError::Empty => Ok(None),
+                        cb::TryRecvError::Disconnected => {
+                            Err(GenericReceiveError::TxDisconnected(None).into())
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+pub mod heapless_mod {
+    use crate::pus::PusRequestRouter;
+    use crate::queue::GenericSendError;
+
+    use super::*;
+
+    /// Variant of the `MessageSenderMap` which does not require `alloc` support and can hold up
+    /// to N message targets. N must be a power of two.
+    pub struct MessageSenderMapHeapless<MSG, S: MessageSender<MSG>, const N: usize>(
+        pub heapless::FnvIndexMap<ComponentId, S, N>,
+        PhantomData<MSG>,
+    );
+
+    impl<MSG, S: MessageSender<MSG>, const N: usize> Default for MessageSenderMapHeapless<MSG, S, N> {
+        fn default() -> Self {
+            Self(Default::default(), PhantomData)
+        }
+    }
+
+    impl<MSG, S: MessageSender<MSG>, const N: usize> MessageSenderMapHeapless<MSG, S, N> {
+        /// Add a message target. Returns the message sender back if the map is full.
+        pub fn add_message_target(
+            &mut self,
+            target_id: ComponentId,
+            message_sender: S,
+        ) -> Result<(), S> {
+            self.0
+                .insert(target_id, message_sender)
+                .map(|_| ())
+                .map_err(|(_, sender)| sender)
+        }
+
+        pub fn send_message(
+            &self,
+            requestor_info: MessageMetadata,
+            target_channel_id: ComponentId,
+            message: MSG,
+        ) -> Result<(), GenericTargetedMessagingError> {
+            match self.0.get(&target_channel_id) {
+                Some(sender) => sender.send(GenericMessage::new(requestor_info, message)),
+                None => Err(GenericSendError::TargetDoesNotExist(target_channel_id).into()),
+            }
+        }
+
+        /// Send a reply to the sender of the request with the given metadata.
+        pub fn send_reply(
+            &self,
+            request_info: &MessageMetadata,
+            replier_id: ComponentId,
+            reply: MSG,
+        ) -> Result<(), GenericTargetedMessagingError> {
+            self.send_message(
+                request_info.reply_metadata(replier_id),
+                request_info.sender_id(),
+                reply,
+            )
+        }
+    }
+
+    impl<MSG, S: MessageSender<MSG>, const N: usize> PusRequestRouter<MSG>
+        for MessageSenderMapHeapless<MSG, S, N>
+    {
+        type Error = GenericTargetedMessagingError;
+
+        fn route(
+            &self,
+            requestor_info: MessageMetadata,
+            target_id: ComponentId,
+            request: MSG,
+        ) -> Result<(), Self::Error> {
+            self.send_message(requestor_info, target_id, request)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -385,11 +541,17 @@ mod tests {
     };
 
     use crate::{
+        pus::verification::{RequestId as VerifRequestId, VerificationToken},
+        pus::PusRequestEnvelope,
         queue::{GenericReceiveError, GenericSendError, GenericTargetedMessagingError},
         request::{MessageMetadata, MessageSenderMap},
     };
 
+    #[cfg(feature = "heapless")]
+    use super::MessageSenderMapHeapless;
     use super::{GenericMessage, MessageReceiverWithId, UniqueApidTargetId};
+    #[cfg(feature = "heapless")]
+    use crate::pus::PusRequestRouter;
 
     const TEST_CHANNEL_ID_0: u64 = 1;
     const TEST_CHANNEL_ID_1: u64 = 2;
@@ -581,4 +743,108 @@ mod tests {
             panic!("Unexpected error type {}", error);
         }
     }
+
+    #[test]
+    fn test_sender_map_send_reply() {
+        let (sender0, receiver0) = mpsc::channel();
+        let mut sender_map = MessageSenderMap::default();
+        sender_map.add_message_target(TEST_CHANNEL_ID_0, sender0);
+        let request = GenericMessage::new(MessageMetadata::new(7, TEST_CHANNEL_ID_0), 1);
+        sender_map
+            .send_reply(&request.requestor_info, TEST_CHANNEL_ID_1, 2)
+            .expect("sending reply failed");
+        let reply = receiver0.recv().expect("receiving reply failed");
+        assert_eq!(reply.request_id(), 7);
+        assert_eq!(reply.sender_id(), TEST_CHANNEL_ID_1);
+        assert_eq!(reply.message, 2);
+        assert!(reply.is_reply_to(&request.requestor_info));
+        assert_eq!(request.reply(TEST_CHANNEL_ID_1, 2), reply);
+    }
+
+    #[test]
+    fn test_routing_request_envelope() {
+        let (sender0, receiver0) = mpsc::channel();
+        let mut sender_map = MessageSenderMap::default();
+        sender_map.add_message_target(TEST_CHANNEL_ID_1, sender0);
+        let sp_header = SpHeader::new_for_unseg_tc(0x111, 5, 0);
+        let pus_tc = PusTcCreator::new_simple(sp_header, 8, 128, &[], true);
+        let token = VerificationToken::new_accepted_state(VerifRequestId::new(&pus_tc));
+        let envelope = PusRequestEnvelope::new(token, TEST_CHANNEL_ID_0, TEST_CHANNEL_ID_1, 5);
+        assert_eq!(envelope.route(&sender_map).unwrap(), token);
+        let request = receiver0.recv().expect("receiving request failed");
+        assert_eq!(request.request_id(), token.request_id().raw());
+        assert_eq!(request.sender_id(), TEST_CHANNEL_ID_0);
+        assert_eq!(request.message, 5);
+
+        let envelope = PusRequestEnvelope::new(token, TEST_CHANNEL_ID_0, TEST_CHANNEL_ID_2, 5);
+        assert!(matches!(
+            envelope.route(&sender_map),
+            Err(GenericTargetedMessagingError::Send(
+                GenericSendError::TargetDoesNotExist(TEST_CHANNEL_ID_2)
+            ))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "crossbeam")]
+    fn test_sender_map_crossbeam() {
+        let (sender0, receiver0) = crossbeam_channel::bounded(1);
+        let mut sender_map = MessageSenderMap::default();
+        sender_map.add_message_target(TEST_CHANNEL_ID_1, sender0);
+        sender_map
+            .send_message(
+                MessageMetadata::new(1, TEST_CHANNEL_ID_0),
+                TEST_CHANNEL_ID_1,
+                5,
+            )
+            .expect("sending message failed");
+        let result = sender_map.send_message(
+            MessageMetadata::new(2, TEST_CHANNEL_ID_0),
+            TEST_CHANNEL_ID_1,
+            6,
+        );
+        assert!(matches!(
+            result,
+            Err(GenericTargetedMessagingError::Send(
+                GenericSendError::QueueFull(None)
+            ))
+        ));
+        let receiver = MessageReceiverWithId::new(TEST_CHANNEL_ID_1, receiver0);
+        let message = receiver.try_recv_message().unwrap().unwrap();
+        assert_eq!(message.message, 5);
+        assert!(receiver.try_recv_message().unwrap().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "heapless")]
+    fn test_heapless_sender_map() {
+        let (sender0, receiver0) = mpsc::channel();
+        let (sender1, _receiver1) = mpsc::channel();
+        let (sender2, _receiver2) = mpsc::channel();
+        let mut sender_map: MessageSenderMapHeapless<i32, _, 2> = Default::default();
+        assert!(sender_map
+            .add_message_target(TEST_CHANNEL_ID_1, sender0)
+            .is_ok());
+        assert!(sender_map
+            .add_message_target(TEST_CHANNEL_ID_0, sender1)
+            .is_ok());
+        assert!(sender_map
+            .add_message_target(TEST_CHANNEL_ID_2, sender2)
+            .is_err());
+        sender_map
+            .route(
+                MessageMetadata::new(1, TEST_CHANNEL_ID_0),
+                TEST_CHANNEL_ID_1,
+                5,
+            )
+            .expect("routing message failed");
+        assert_eq!(receiver0.recv().unwrap().message, 5);
+        assert!(sender_map
+            .send_message(
+                MessageMetadata::new(1, TEST_CHANNEL_ID_0),
+                TEST_CHANNEL_ID_2,
+                5
+            )
+            .is_err());
+    }
 }
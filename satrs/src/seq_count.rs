@@ -1,4 +1,4 @@
-use core::cell::Cell;
+use core::cell::{Cell, RefCell};
 #[cfg(feature = "alloc")]
 use dyn_clone::DynClone;
 use paste::paste;
@@ -114,6 +114,154 @@ impl SequenceCountProviderCore<u16> for CcsdsSimpleSeqCountProvider {
     }
 }
 
+/// Non-volatile memory backend for the [PersistentSeqCountProvider].
+///
+/// The backend only needs to be able to load and store a single counter value. It could for
+/// example be implemented on top of an EEPROM, a FRAM, a flash page or a file.
+pub trait SeqCountNvBackend {
+    type Error;
+
+    /// Load the stored counter value. Returns [None] if no value was stored yet.
+    fn load(&mut self) -> Result<Option<u16>, Self::Error>;
+
+    fn store(&mut self, value: u16) -> Result<(), Self::Error>;
+}
+
+/// Sequence count provider which persists its counter using a user-supplied
+/// [SeqCountNvBackend], so that sequence counts remain monotonic across reboots.
+///
+/// To avoid wearing out the NV memory, the counter is not stored on every increment. Instead,
+/// a block of `store_interval` counter values is reserved by storing the first value after the
+/// block before any value of the block is handed out. After a reboot, the counter therefore
+/// continues at the start of the next block. This guarantees that no counter value is re-used,
+/// at the cost of skipping at most `store_interval - 1` values on each reboot. A store interval
+/// of 1 stores the exact next value on every increment. The [Self::flush] method can be used
+/// on orderly shutdown to store the exact next value and avoid skipping any values.
+///
+/// The counter wraps around to 0 after the configured maximum value, which defaults to
+/// [MAX_SEQ_COUNT].
+pub struct PersistentSeqCountProvider<Backend: SeqCountNvBackend> {
+    backend: RefCell<Backend>,
+    seq_count: Cell<u16>,
+    max_val: u16,
+    store_interval: u16,
+    reserved_until: Cell<u16>,
+    store_pending: Cell<bool>,
+    failed_stores: Cell<u32>,
+}
+
+impl<Backend: SeqCountNvBackend> PersistentSeqCountProvider<Backend> {
+    /// Create a new CCSDS sequence count provider which wraps around at [MAX_SEQ_COUNT]. The
+    /// initial counter value is loaded from the backend.
+    ///
+    /// ## Parameter
+    ///
+    /// * `backend` - NV memory backend.
+    /// * `store_interval` - Number of counter values reserved with each store operation. A value
+    ///   of 0 is treated like 1.
+    pub fn new(backend: Backend, store_interval: u16) -> Result<Self, Backend::Error> {
+        Self::new_with_max_val(backend, MAX_SEQ_COUNT, store_interval)
+    }
+
+    /// Like [Self::new], but with a custom maximum value after which the counter wraps around.
+    pub fn new_with_max_val(
+        mut backend: Backend,
+        max_val: u16,
+        store_interval: u16,
+    ) -> Result<Self, Backend::Error> {
+        let initial_count = match backend.load()? {
+            Some(value) if value <= max_val => value,
+            Some(value) => (value as u32 % (max_val as u32 + 1)) as u16,
+            None => 0,
+        };
+        Ok(Self {
+            backend: RefCell::new(backend),
+            seq_count: Cell::new(initial_count),
+            max_val,
+            store_interval: store_interval.clamp(1, max_val.saturating_add(1).max(1)),
+            reserved_until: Cell::new(initial_count),
+            store_pending: Cell::new(false),
+            failed_stores: Cell::new(0),
+        })
+    }
+
+    pub fn max_val(&self) -> u16 {
+        self.max_val
+    }
+
+    pub fn store_interval(&self) -> u16 {
+        self.store_interval
+    }
+
+    /// Number of store operations which failed when using the infallible
+    /// [SequenceCountProviderCore] API.
+    pub fn num_failed_stores(&self) -> u32 {
+        self.failed_stores.get()
+    }
+
+    pub fn backend(&self) -> core::cell::Ref<'_, Backend> {
+        self.backend.borrow()
+    }
+
+    /// Retrieve the current counter value and increment it. The reservation of the next block
+    /// is stored before the counter value is returned. If storing fails, the counter is not
+    /// incremented and the error is returned.
+    pub fn try_get_and_increment(&self) -> Result<u16, Backend::Error> {
+        let current = self.seq_count.get();
+        if current == self.reserved_until.get() || self.store_pending.get() {
+            let next_reservation = self.add_wrapping(current, self.store_interval);
+            self.store_pending.set(true);
+            self.backend.borrow_mut().store(next_reservation)?;
+            self.store_pending.set(false);
+            self.reserved_until.set(next_reservation);
+        }
+        self.seq_count.set(self.add_wrapping(current, 1));
+        Ok(current)
+    }
+
+    /// Store the exact next counter value. This should be called on orderly shutdown so no
+    /// counter values are skipped after the next boot.
+    pub fn flush(&self) -> Result<(), Backend::Error> {
+        let current = self.seq_count.get();
+        self.backend.borrow_mut().store(current)?;
+        self.store_pending.set(false);
+        self.reserved_until.set(current);
+        Ok(())
+    }
+
+    fn add_wrapping(&self, value: u16, increment: u16) -> u16 {
+        ((value as u32 + increment as u32) % (self.max_val as u32 + 1)) as u16
+    }
+}
+
+impl<Backend: SeqCountNvBackend> SequenceCountProviderCore<u16>
+    for PersistentSeqCountProvider<Backend>
+{
+    fn get(&self) -> u16 {
+        self.seq_count.get()
+    }
+
+    fn increment(&self) {
+        self.get_and_increment();
+    }
+
+    /// Store errors of the backend are not propagated by this function. The counter is
+    /// incremented regardless and storing the reservation is retried on the next increment.
+    /// Use [PersistentSeqCountProvider::try_get_and_increment] to handle store errors explicitly.
+    fn get_and_increment(&self) -> u16 {
+        match self.try_get_and_increment() {
+            Ok(value) => value,
+            Err(_) => {
+                self.failed_stores
+                    .set(self.failed_stores.get().saturating_add(1));
+                let current = self.seq_count.get();
+                self.seq_count.set(self.add_wrapping(current, 1));
+                current
+            }
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 pub mod stdmod {
     use super::*;
@@ -180,11 +328,35 @@ pub mod stdmod {
 #[cfg(test)]
 mod tests {
     use crate::seq_count::{
-        CcsdsSimpleSeqCountProvider, SeqCountProviderSimple, SeqCountProviderSyncU8,
-        SequenceCountProviderCore,
+        CcsdsSimpleSeqCountProvider, PersistentSeqCountProvider, SeqCountNvBackend,
+        SeqCountProviderSimple, SeqCountProviderSyncU8, SequenceCountProviderCore,
     };
     use spacepackets::MAX_SEQ_COUNT;
 
+    #[derive(Default)]
+    struct TestNvBackend {
+        value: Option<u16>,
+        num_stores: u32,
+        fail_stores: bool,
+    }
+
+    impl SeqCountNvBackend for TestNvBackend {
+        type Error = ();
+
+        fn load(&mut self) -> Result<Option<u16>, Self::Error> {
+            Ok(self.value)
+        }
+
+        fn store(&mut self, value: u16) -> Result<(), Self::Error> {
+            if self.fail_stores {
+                return Err(());
+            }
+            self.value = Some(value);
+            self.num_stores += 1;
+            Ok(())
+        }
+    }
+
     #[test]
     fn test_u8_counter() {
         let u8_counter = SeqCountProviderSimple::<u8>::default();
@@ -247,4 +419,82 @@ mod tests {
         }
         assert_eq!(sync_u8_counter.get(), 0);
     }
+
+    #[test]
+    fn test_persistent_counter_store_every_increment() {
+        let counter = PersistentSeqCountProvider::new(TestNvBackend::default(), 1).unwrap();
+        assert_eq!(counter.get(), 0);
+        assert_eq!(counter.get_and_increment(), 0);
+        assert_eq!(counter.backend().value, Some(1));
+        assert_eq!(counter.get_and_increment(), 1);
+        assert_eq!(counter.backend().value, Some(2));
+        assert_eq!(counter.backend().num_stores, 2);
+    }
+
+    #[test]
+    fn test_persistent_counter_throttled() {
+        let counter = PersistentSeqCountProvider::new(TestNvBackend::default(), 10).unwrap();
+        for i in 0..10 {
+            assert_eq!(counter.get_and_increment(), i);
+        }
+        assert_eq!(counter.backend().value, Some(10));
+        assert_eq!(counter.backend().num_stores, 1);
+        assert_eq!(counter.get_and_increment(), 10);
+        assert_eq!(counter.backend().value, Some(20));
+        assert_eq!(counter.backend().num_stores, 2);
+    }
+
+    #[test]
+    fn test_persistent_counter_reboot() {
+        let counter = PersistentSeqCountProvider::new(TestNvBackend::default(), 10).unwrap();
+        for _ in 0..3 {
+            counter.increment();
+        }
+        let backend = TestNvBackend {
+            value: counter.backend().value,
+            ..Default::default()
+        };
+        // The remaining values of the reserved block are skipped after a reboot.
+        let counter = PersistentSeqCountProvider::new(backend, 10).unwrap();
+        assert_eq!(counter.get_and_increment(), 10);
+        counter.increment();
+        counter.flush().unwrap();
+        assert_eq!(counter.backend().value, Some(12));
+        let backend = TestNvBackend {
+            value: counter.backend().value,
+            ..Default::default()
+        };
+        let counter = PersistentSeqCountProvider::new(backend, 10).unwrap();
+        assert_eq!(counter.get_and_increment(), 12);
+    }
+
+    #[test]
+    fn test_persistent_counter_wrap_around() {
+        let backend = TestNvBackend {
+            value: Some(MAX_SEQ_COUNT),
+            ..Default::default()
+        };
+        let counter = PersistentSeqCountProvider::new(backend, 4).unwrap();
+        assert_eq!(counter.get_and_increment(), MAX_SEQ_COUNT);
+        assert_eq!(counter.backend().value, Some(3));
+        assert_eq!(counter.get_and_increment(), 0);
+    }
+
+    #[test]
+    fn test_persistent_counter_store_failure() {
+        let backend = TestNvBackend {
+            fail_stores: true,
+            ..Default::default()
+        };
+        let counter = PersistentSeqCountProvider::new(backend, 10).unwrap();
+        assert!(counter.try_get_and_increment().is_err());
+        assert_eq!(counter.get(), 0);
+        assert_eq!(counter.get_and_increment(), 0);
+        assert_eq!(counter.num_failed_stores(), 1);
+        counter.backend.borrow_mut().fail_stores = false;
+        // The pending reservation is stored on the next increment.
+        assert_eq!(counter.get_and_increment(), 1);
+        assert_eq!(counter.backend().value, Some(11));
+        assert_eq!(counter.num_failed_stores(), 1);
+    }
 }
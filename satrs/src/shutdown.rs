@@ -0,0 +1,155 @@
+//! # Graceful shutdown support
+//!
+//! Long-running components like the TMTC servers, the event manager, the PUS service handlers or
+//! the TM funnel usually run inside an infinite loop. A clean shutdown, for example before a
+//! software update, requires that these components stop accepting new input, process the input
+//! which is already queued and finally report that they are done.
+//!
+//! The shutdown is requested with a [ShutdownSignal], which can be shared between all threads.
+//! Components should check the signal periodically, drain their queues using
+//! [drain_with_timeout] and report the [DrainOutcome] with the [SHUTDOWN] event using
+//! [report_shutdown].
+//!
+//! The TCP TMTC servers accept the flag of the signal, which is retrieved with
+//! [ShutdownSignal::flag], as their stop signal. The [crate::event_man::EventManager] and the
+//! [crate::pus::harness::PusServiceHarness] provide `drain` methods.
+use core::time::Duration;
+use std::time::Instant;
+
+use crate::error_report::FRAMEWORK_EVENT_GROUP_ID;
+use crate::event_man::{EventMessage, EventSendProvider};
+use crate::events::{EventU32, SeverityInfo};
+use crate::params::{Params, ParamsRaw};
+use crate::pus::HandlingStatus;
+use crate::ComponentId;
+
+pub use crate::executable::ShutdownSignal;
+
+crate::event_definitions! {
+    pub SHUTDOWN_EVENT_DEFINITIONS;
+    /// A component shut down after draining its queues.
+    pub SHUTDOWN: SeverityInfo = (FRAMEWORK_EVENT_GROUP_ID, 6), U32Pair,
+        "Component shut down, contains the number of drained items and whether the drain timed out";
+}
+
+/// Result of draining the queues of a component.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct DrainOutcome {
+    /// Number of items, for example telecommands or events, which were handled while draining.
+    pub num_drained: u32,
+    /// The timeout elapsed before all queues were empty.
+    pub timed_out: bool,
+}
+
+impl DrainOutcome {
+    /// Parameters of the [SHUTDOWN] event. The first value is the number of drained items and
+    /// the second value is 1 if the drain timed out and 0 otherwise.
+    pub fn event_params(&self) -> Params {
+        Params::from(ParamsRaw::from((self.num_drained, self.timed_out as u32)))
+    }
+}
+
+/// Call `handle_next` until it returns [HandlingStatus::Empty] or the timeout elapsed.
+///
+/// The timeout is only checked between two calls, so it should be chosen with the longest
+/// handling time of a single item in mind.
+pub fn drain_with_timeout(
+    timeout: Duration,
+    mut handle_next: impl FnMut() -> HandlingStatus,
+) -> DrainOutcome {
+    let start = Instant::now();
+    let mut outcome = DrainOutcome::default();
+    loop {
+        if handle_next() == HandlingStatus::Empty {
+            return outcome;
+        }
+        outcome.num_drained += 1;
+        if start.elapsed() >= timeout {
+            outcome.timed_out = true;
+            return outcome;
+        }
+    }
+}
+
+/// Send the [SHUTDOWN] event for the component with the given ID.
+pub fn report_shutdown<Sender: EventSendProvider<EventU32>>(
+    sender_id: ComponentId,
+    outcome: &DrainOutcome,
+    event_sender: &Sender,
+) -> Result<(), Sender::Error> {
+    event_sender.send(EventMessage::new_with_params(
+        sender_id,
+        SHUTDOWN.into(),
+        &outcome.event_params(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use crate::event_man::EventU32SenderMpsc;
+    use crate::params::{ParamsHeapless, U32Pair};
+
+    use super::*;
+
+    const TEST_ID: ComponentId = 0x05;
+
+    #[test]
+    fn test_drain_until_empty() {
+        let mut remaining = 3;
+        let outcome = drain_with_timeout(Duration::from_secs(1), || {
+            if remaining == 0 {
+                return HandlingStatus::Empty;
+            }
+            remaining -= 1;
+            HandlingStatus::HandledOne
+        });
+        assert_eq!(
+            outcome,
+            DrainOutcome {
+                num_drained: 3,
+                timed_out: false
+            }
+        );
+    }
+
+    #[test]
+    fn test_drain_timeout() {
+        let outcome = drain_with_timeout(Duration::from_millis(20), || {
+            std::thread::sleep(Duration::from_millis(5));
+            HandlingStatus::HandledOne
+        });
+        assert!(outcome.timed_out);
+        assert!(outcome.num_drained >= 1);
+    }
+
+    #[test]
+    fn test_report_shutdown() {
+        let (event_tx, event_rx) = mpsc::channel();
+        let outcome = DrainOutcome {
+            num_drained: 12,
+            timed_out: true,
+        };
+        report_shutdown(TEST_ID, &outcome, &EventU32SenderMpsc::new(0, event_tx)).unwrap();
+        let event_msg = event_rx.try_recv().unwrap();
+        assert_eq!(event_msg.sender_id(), TEST_ID);
+        assert_eq!(event_msg.event(), EventU32::from(SHUTDOWN));
+        assert_eq!(
+            event_msg.params(),
+            Some(&Params::Heapless(ParamsHeapless::Raw(ParamsRaw::U32Pair(
+                U32Pair(12, 1)
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_signal_flag() {
+        let signal = ShutdownSignal::new();
+        let flag = signal.flag();
+        assert!(!flag.load(std::sync::atomic::Ordering::Relaxed));
+        signal.request_shutdown();
+        assert!(flag.load(std::sync::atomic::Ordering::Relaxed));
+        assert!(ShutdownSignal::from_flag(flag).shutdown_requested());
+    }
+}
@@ -1,7 +1,471 @@
-use core::fmt::Debug;
+use core::fmt::{Debug, Display, Formatter};
+use core::ops::Deref;
+use spacepackets::time::cds::{CdsTime, DaysLen16Bits, DaysLen24Bits, SubmillisPrecision};
+use spacepackets::time::cuc::{CucTime, FractionalResolution};
+use spacepackets::time::{CcsdsTimeProvider, TimeReader, TimeWriter, TimestampError, UnixTime};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
 
 /// Generic abstraction for a check/countdown timer.
 pub trait CountdownProvider: Debug {
     fn has_expired(&self) -> bool;
     fn reset(&mut self);
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeSourceError {
+    /// The underlying clock could not be read.
+    ClockUnavailable,
+    Timestamp(TimestampError),
+}
+
+impl Display for TimeSourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TimeSourceError::ClockUnavailable => write!(f, "clock unavailable"),
+            TimeSourceError::Timestamp(e) => write!(f, "timestamp error: {e}"),
+        }
+    }
+}
+
+impl From<TimestampError> for TimeSourceError {
+    fn from(value: TimestampError) -> Self {
+        Self::Timestamp(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for TimeSourceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TimeSourceError::Timestamp(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Rate of a simulated time source which is negative, infinite or NaN.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InvalidRateError(pub f64);
+
+impl Display for InvalidRateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid time rate {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for InvalidRateError {}
+
+/// Maximum length of a timestamp with any [TimestampFormat].
+pub const MAX_TIMESTAMP_LEN: usize = 8;
+
+/// CCSDS timestamp format used to timestamp the generated telemetry and to parse the release
+/// time of time-tagged telecommands.
+///
+/// CDS timestamps are created without a sub-millisecond field. CUC timestamps use the CCSDS
+/// epoch, a 4 byte coarse time field and the configured fine time resolution.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// CDS timestamp with a 16 bit days field, which has a length of 7 bytes.
+    #[default]
+    CdsShort,
+    /// CDS timestamp with a 24 bit days field, which has a length of 8 bytes.
+    CdsLong,
+    /// CUC timestamp with the given fine time resolution. The number of leap seconds is
+    /// required for the conversion between the TAI based CCSDS epoch and UNIX time.
+    Cuc {
+        fine_resolution: FractionalResolution,
+        leap_seconds: u32,
+    },
+}
+
+impl TimestampFormat {
+    /// Length of a timestamp with this format.
+    pub fn stamp_len(&self) -> usize {
+        match self {
+            TimestampFormat::CdsShort => 7,
+            TimestampFormat::CdsLong => 8,
+            TimestampFormat::Cuc {
+                fine_resolution, ..
+            } => {
+                // P-field and 4 byte coarse time field.
+                5 + match fine_resolution {
+                    FractionalResolution::Seconds => 0,
+                    FractionalResolution::FourMs => 1,
+                    FractionalResolution::FifteenUs => 2,
+                    FractionalResolution::SixtyNs => 3,
+                }
+            }
+        }
+    }
+
+    /// Write the given time as a timestamp with this format into the provided buffer. Returns
+    /// the written length.
+    pub fn write_stamp(&self, time: &UnixTime, buf: &mut [u8]) -> Result<usize, TimestampError> {
+        match self {
+            TimestampFormat::CdsShort => {
+                CdsTime::from_unix_time_with_u16_days(time, SubmillisPrecision::Absent)?
+                    .write_to_bytes(buf)
+            }
+            TimestampFormat::CdsLong => {
+                CdsTime::from_unix_time_with_u24_days(time, SubmillisPrecision::Absent)?
+                    .write_to_bytes(buf)
+            }
+            TimestampFormat::Cuc {
+                fine_resolution,
+                leap_seconds,
+            } => {
+                CucTime::from_unix_time(time, *fine_resolution, *leap_seconds)?.write_to_bytes(buf)
+            }
+        }
+    }
+
+    /// Read a timestamp with this format from the start of the provided buffer. Returns the time
+    /// and the length of the timestamp.
+    pub fn read_stamp(&self, buf: &[u8]) -> Result<(UnixTime, usize), TimestampError> {
+        match self {
+            TimestampFormat::CdsShort => {
+                let stamp = CdsTime::<DaysLen16Bits>::from_bytes(buf)?;
+                Ok((stamp.unix_time(), stamp.len_as_bytes()))
+            }
+            TimestampFormat::CdsLong => {
+                let stamp = CdsTime::<DaysLen24Bits>::from_bytes(buf)?;
+                Ok((stamp.unix_time(), stamp.len_as_bytes()))
+            }
+            TimestampFormat::Cuc { leap_seconds, .. } => {
+                let stamp = CucTime::from_bytes_with_leap_seconds(buf, *leap_seconds)?;
+                Ok((stamp.unix_time(), stamp.len_as_bytes()))
+            }
+        }
+    }
+}
+
+/// Raw timestamp with a maximum length of [MAX_TIMESTAMP_LEN] which can be used like a byte
+/// slice.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RawTimestamp {
+    buf: [u8; MAX_TIMESTAMP_LEN],
+    len: usize,
+}
+
+impl RawTimestamp {
+    /// Zeroed timestamp with the length of the given format.
+    pub fn zeroed(format: TimestampFormat) -> Self {
+        Self {
+            buf: [0; MAX_TIMESTAMP_LEN],
+            len: format.stamp_len(),
+        }
+    }
+
+    /// Create the timestamp for the given time with the given format.
+    pub fn new(format: TimestampFormat, time: &UnixTime) -> Result<Self, TimestampError> {
+        let mut stamp = Self::zeroed(format);
+        stamp.len = format.write_stamp(time, &mut stamp.buf)?;
+        Ok(stamp)
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Deref for RawTimestamp {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for RawTimestamp {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// Common abstraction for the source of the current time.
+///
+/// Components which need the current time, for example to timestamp telemetry or to release
+/// scheduled telecommands, should retrieve it from a time source instead of reading the system
+/// clock directly. This allows running the software with a simulated mission time, for example
+/// inside a software-in-the-loop simulation with accelerated or frozen time.
+///
+/// Only [Self::now] needs to be implemented. Other time formats like the CCSDS Unsegmented Time
+/// Code (CUC) can be created from the returned [UnixTime].
+pub trait TimeSource {
+    /// Current time as a UNIX timestamp.
+    fn now(&self) -> Result<UnixTime, TimeSourceError>;
+
+    /// Current time as a CDS timestamp with a 16 bit days field and without a sub-millisecond
+    /// field.
+    fn now_as_cds(&self) -> Result<CdsTime, TimeSourceError> {
+        Ok(CdsTime::from_unix_time_with_u16_days(
+            &self.now()?,
+            SubmillisPrecision::Absent,
+        )?)
+    }
+
+    /// Write the current time as a CDS short timestamp into the provided buffer. Returns the
+    /// written length.
+    fn write_cds_short_stamp(&self, buf: &mut [u8]) -> Result<usize, TimeSourceError> {
+        Ok(self.now_as_cds()?.write_to_bytes(buf)?)
+    }
+
+    /// Current time as a CDS short timestamp.
+    fn cds_short_stamp(&self) -> Result<[u8; 7], TimeSourceError> {
+        let mut stamp = [0; 7];
+        self.write_cds_short_stamp(&mut stamp)?;
+        Ok(stamp)
+    }
+
+    /// Current time as a timestamp with the given format.
+    fn stamp(&self, format: TimestampFormat) -> Result<RawTimestamp, TimeSourceError> {
+        Ok(RawTimestamp::new(format, &self.now()?)?)
+    }
+}
+
+/// Time source which always returns the same time. The time can be updated manually.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FixedTimeSource {
+    pub time: UnixTime,
+}
+
+impl FixedTimeSource {
+    pub fn new(time: UnixTime) -> Self {
+        Self { time }
+    }
+}
+
+impl TimeSource for FixedTimeSource {
+    fn now(&self) -> Result<UnixTime, TimeSourceError> {
+        Ok(self.time)
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    /// Time source which uses the system clock.
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+    pub struct SystemTimeSource;
+
+    impl TimeSource for SystemTimeSource {
+        fn now(&self) -> Result<UnixTime, TimeSourceError> {
+            UnixTime::now().map_err(|_| TimeSourceError::ClockUnavailable)
+        }
+    }
+
+    #[derive(Debug)]
+    struct SimulatedTimeState {
+        reference_time: UnixTime,
+        reference_instant: Instant,
+        rate: f64,
+    }
+
+    impl SimulatedTimeState {
+        fn now(&self) -> UnixTime {
+            let elapsed = self.reference_instant.elapsed().as_secs_f64() * self.rate;
+            self.reference_time + Duration::from_secs_f64(elapsed)
+        }
+    }
+
+    fn check_rate(rate: f64) -> Result<f64, InvalidRateError> {
+        if !rate.is_finite() || rate < 0.0 {
+            return Err(InvalidRateError(rate));
+        }
+        Ok(rate)
+    }
+
+    /// Settable mission time source which is intended for simulations.
+    ///
+    /// The simulated time advances with a configurable rate relative to the monotonic system
+    /// clock. A rate of 1.0 advances the time in real-time, larger rates accelerate the time and
+    /// a rate of 0.0 freezes it. Frozen time can still be advanced manually with
+    /// [Self::advance]. The time source can be cloned and shared between threads, and all clones
+    /// share the same simulated time.
+    #[derive(Debug, Clone)]
+    pub struct SimulatedTimeSource {
+        state: Arc<Mutex<SimulatedTimeState>>,
+    }
+
+    impl SimulatedTimeSource {
+        /// Create a new simulated time source starting at `start_time` which advances with the
+        /// given `rate`. Returns an error if the rate is negative, infinite or NaN.
+        pub fn new(start_time: UnixTime, rate: f64) -> Result<Self, InvalidRateError> {
+            Ok(Self::new_unchecked(start_time, check_rate(rate)?))
+        }
+
+        /// Create a new frozen time source which only advances when [Self::advance] or
+        /// [Self::set_time] is called.
+        pub fn new_frozen(start_time: UnixTime) -> Self {
+            Self::new_unchecked(start_time, 0.0)
+        }
+
+        fn new_unchecked(start_time: UnixTime, rate: f64) -> Self {
+            Self {
+                state: Arc::new(Mutex::new(SimulatedTimeState {
+                    reference_time: start_time,
+                    reference_instant: Instant::now(),
+                    rate,
+                })),
+            }
+        }
+
+        /// Set the current simulated time.
+        pub fn set_time(&self, time: UnixTime) {
+            let mut state = self.state.lock().unwrap();
+            state.reference_time = time;
+            state.reference_instant = Instant::now();
+        }
+
+        /// Advance the current simulated time by the given duration.
+        pub fn advance(&self, duration: Duration) {
+            let mut state = self.state.lock().unwrap();
+            state.reference_time = state.now() + duration;
+            state.reference_instant = Instant::now();
+        }
+
+        pub fn rate(&self) -> f64 {
+            self.state.lock().unwrap().rate
+        }
+
+        /// Set the rate of the simulated time relative to real-time. The current simulated time
+        /// is preserved. Returns an error and keeps the current rate if the rate is negative,
+        /// infinite or NaN.
+        pub fn set_rate(&self, rate: f64) -> Result<(), InvalidRateError> {
+            self.set_rate_unchecked(check_rate(rate)?);
+            Ok(())
+        }
+
+        /// Freeze the simulated time. This is equivalent to setting the rate to 0.0.
+        pub fn freeze(&self) {
+            self.set_rate_unchecked(0.0);
+        }
+
+        fn set_rate_unchecked(&self, rate: f64) {
+            let mut state = self.state.lock().unwrap();
+            state.reference_time = state.now();
+            state.reference_instant = Instant::now();
+            state.rate = rate;
+        }
+
+        pub fn is_frozen(&self) -> bool {
+            self.rate() == 0.0
+        }
+    }
+
+    impl TimeSource for SimulatedTimeSource {
+        fn now(&self) -> Result<UnixTime, TimeSourceError> {
+            Ok(self
+                .state
+                .lock()
+                .map_err(|_| TimeSourceError::ClockUnavailable)?
+                .now())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use spacepackets::time::cds::CdsTime;
+    use spacepackets::time::{CcsdsTimeProvider, UnixTime};
+
+    use super::*;
+
+    #[test]
+    fn test_fixed_time_source() {
+        let time_source = FixedTimeSource::new(UnixTime::new_only_secs(100));
+        assert_eq!(time_source.now().unwrap(), UnixTime::new_only_secs(100));
+        let cds = time_source.now_as_cds().unwrap();
+        assert_eq!(cds.unix_time(), UnixTime::new_only_secs(100));
+        let stamp = time_source.cds_short_stamp().unwrap();
+        let read_back = CdsTime::from_bytes_with_u16_days(&stamp).unwrap();
+        assert_eq!(read_back.unix_time(), UnixTime::new_only_secs(100));
+    }
+
+    #[test]
+    fn test_timestamp_formats() {
+        let time = UnixTime::new(1_700_000_000, 250_000_000);
+        let time_source = FixedTimeSource::new(time);
+        let cuc = TimestampFormat::Cuc {
+            fine_resolution: FractionalResolution::FifteenUs,
+            leap_seconds: 37,
+        };
+        for format in [TimestampFormat::CdsShort, TimestampFormat::CdsLong, cuc] {
+            let stamp = time_source.stamp(format).unwrap();
+            assert_eq!(stamp.len(), format.stamp_len());
+            assert!(stamp.len() <= MAX_TIMESTAMP_LEN);
+            let (read_back, len) = format.read_stamp(&stamp).unwrap();
+            assert_eq!(len, format.stamp_len());
+            assert_eq!(read_back, time);
+        }
+        assert_eq!(
+            time_source
+                .stamp(TimestampFormat::CdsShort)
+                .unwrap()
+                .as_slice(),
+            time_source.cds_short_stamp().unwrap()
+        );
+        assert_eq!(RawTimestamp::zeroed(cuc).as_slice(), &[0; 7]);
+    }
+
+    #[test]
+    fn test_system_time_source() {
+        let time_source = SystemTimeSource;
+        let before = UnixTime::now().unwrap();
+        let now = time_source.now().unwrap();
+        assert!(now >= before);
+    }
+
+    #[test]
+    fn test_frozen_time_source() {
+        let time_source = SimulatedTimeSource::new_frozen(UnixTime::new_only_secs(1000));
+        assert!(time_source.is_frozen());
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(time_source.now().unwrap(), UnixTime::new_only_secs(1000));
+        time_source.advance(Duration::from_secs(10));
+        assert_eq!(time_source.now().unwrap(), UnixTime::new_only_secs(1010));
+        let clone = time_source.clone();
+        clone.set_time(UnixTime::new_only_secs(5));
+        assert_eq!(time_source.now().unwrap(), UnixTime::new_only_secs(5));
+    }
+
+    #[test]
+    fn test_accelerated_time_source() {
+        let time_source = SimulatedTimeSource::new(UnixTime::new_only_secs(1000), 1000.0).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        let now = time_source.now().unwrap();
+        assert!(now >= UnixTime::new_only_secs(1010));
+        time_source.freeze();
+        let frozen = time_source.now().unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(time_source.now().unwrap(), frozen);
+    }
+
+    #[test]
+    fn test_invalid_rates() {
+        let start_time = UnixTime::new_only_secs(1000);
+        for rate in [-1.0, f64::NAN, f64::INFINITY] {
+            assert!(SimulatedTimeSource::new(start_time, rate).is_err());
+        }
+        let time_source = SimulatedTimeSource::new(start_time, 2.0).unwrap();
+        assert_eq!(
+            time_source.set_rate(f64::NEG_INFINITY),
+            Err(InvalidRateError(f64::NEG_INFINITY))
+        );
+        assert!(time_source.set_rate(f64::NAN).is_err());
+        assert_eq!(time_source.rate(), 2.0);
+        time_source.set_rate(0.0).unwrap();
+        assert!(time_source.is_frozen());
+    }
+}
@@ -0,0 +1,298 @@
+//! # Mode-based TM generation suppression
+//!
+//! The amount of generated telemetry often depends on the system mode. For example, a satellite
+//! in safe mode should only generate essential telemetry to save downlink bandwidth and
+//! processing power.
+//!
+//! A [TmGenerationPolicy] specifies the minimum severity of generated event TM and how HK sets
+//! are generated. The [ModeTmPolicyTable] assigns a policy to each system mode, and the
+//! [SystemModeObserver] tracks the current system mode and provides the matching policy
+//! to all TM generators using the [TmSuppressionProvider] trait. The following TM generators
+//! support suppression:
+//!
+//!  - The [crate::hk::HkSetRegistry] which suppresses or reduces periodic HK reports.
+//!  - The [crate::pus::event_man::PusEventTmCreatorWithMap] which suppresses event TM below a
+//!    minimum severity.
+use crate::events::Severity;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// Generation policy for periodic HK reports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HkGenerationPolicy {
+    /// All periodic HK reports are generated.
+    All,
+    /// All periodic HK reports are generated, but their collection intervals are multiplied with
+    /// the given factor.
+    Reduced { interval_factor: u32 },
+    /// Only periodic HK reports of sets marked as essential are generated.
+    EssentialOnly,
+    /// No periodic HK reports are generated.
+    Disabled,
+}
+
+impl HkGenerationPolicy {
+    pub fn set_enabled(&self, essential: bool) -> bool {
+        match self {
+            HkGenerationPolicy::All | HkGenerationPolicy::Reduced { .. } => true,
+            HkGenerationPolicy::EssentialOnly => essential,
+            HkGenerationPolicy::Disabled => false,
+        }
+    }
+
+    /// Factor for the collection interval. An interval factor of 0 is treated like 1.
+    pub fn interval_factor(&self) -> u32 {
+        match self {
+            HkGenerationPolicy::Reduced { interval_factor } => (*interval_factor).max(1),
+            _ => 1,
+        }
+    }
+}
+
+/// Policy which specifies which TM is generated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TmGenerationPolicy {
+    /// Event TM is only generated for events with at least this severity.
+    pub min_event_severity: Severity,
+    pub hk: HkGenerationPolicy,
+}
+
+impl TmGenerationPolicy {
+    /// Policy which does not suppress any TM.
+    pub const ALL: Self = Self::new(Severity::Info, HkGenerationPolicy::All);
+
+    pub const fn new(min_event_severity: Severity, hk: HkGenerationPolicy) -> Self {
+        Self {
+            min_event_severity,
+            hk,
+        }
+    }
+
+    pub fn event_enabled(&self, severity: Severity) -> bool {
+        severity as u8 >= self.min_event_severity as u8
+    }
+}
+
+impl Default for TmGenerationPolicy {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Generic trait for objects which provide the currently active [TmGenerationPolicy].
+pub trait TmSuppressionProvider {
+    fn tm_policy(&self) -> TmGenerationPolicy;
+}
+
+/// A fixed policy.
+impl TmSuppressionProvider for TmGenerationPolicy {
+    fn tm_policy(&self) -> TmGenerationPolicy {
+        *self
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use hashbrown::HashMap;
+
+    use super::*;
+    use crate::mode::Mode;
+
+    /// Table which assigns a [TmGenerationPolicy] to system modes. The default policy is used for
+    /// all modes without an explicit policy.
+    #[derive(Debug, Default, Clone)]
+    pub struct ModeTmPolicyTable {
+        default: TmGenerationPolicy,
+        policies: HashMap<Mode, TmGenerationPolicy>,
+    }
+
+    impl ModeTmPolicyTable {
+        pub fn new(default: TmGenerationPolicy) -> Self {
+            Self {
+                default,
+                policies: HashMap::new(),
+            }
+        }
+
+        pub fn with_policy(mut self, mode: Mode, policy: TmGenerationPolicy) -> Self {
+            self.set_policy(mode, policy);
+            self
+        }
+
+        pub fn set_policy(&mut self, mode: Mode, policy: TmGenerationPolicy) {
+            self.policies.insert(mode, policy);
+        }
+
+        pub fn remove_policy(&mut self, mode: Mode) -> Option<TmGenerationPolicy> {
+            self.policies.remove(&mode)
+        }
+
+        pub fn policy(&self, mode: Mode) -> TmGenerationPolicy {
+            self.policies.get(&mode).copied().unwrap_or(self.default)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, RwLock};
+
+    use super::*;
+    use crate::mode::{Mode, ModeAndSubmode, ModeReply, ModeRequest};
+
+    struct ObserverState {
+        /// Mode in the upper 32 bits and submode in the lower 16 bits.
+        mode: AtomicU64,
+        policies: RwLock<ModeTmPolicyTable>,
+    }
+
+    /// Central observer of the system mode which provides the [TmGenerationPolicy] for the
+    /// current mode.
+    ///
+    /// The observer is a cheaply clonable handle to shared state, so one clone can be passed to
+    /// every TM generator while the system mode is updated by the component which commands the
+    /// system mode, for example with [Self::handle_mode_reply].
+    #[derive(Clone)]
+    pub struct SystemModeObserver {
+        state: Arc<ObserverState>,
+    }
+
+    impl SystemModeObserver {
+        pub fn new(initial_mode: ModeAndSubmode, policies: ModeTmPolicyTable) -> Self {
+            Self {
+                state: Arc::new(ObserverState {
+                    mode: AtomicU64::new(Self::pack_mode(initial_mode)),
+                    policies: RwLock::new(policies),
+                }),
+            }
+        }
+
+        fn pack_mode(mode: ModeAndSubmode) -> u64 {
+            ((mode.mode() as u64) << 32) | mode.submode() as u64
+        }
+
+        pub fn mode(&self) -> ModeAndSubmode {
+            let raw = self.state.mode.load(Ordering::Acquire);
+            ModeAndSubmode::new((raw >> 32) as Mode, raw as u16)
+        }
+
+        pub fn set_mode(&self, mode: ModeAndSubmode) {
+            self.state
+                .mode
+                .store(Self::pack_mode(mode), Ordering::Release);
+        }
+
+        /// Update the system mode from the reply of the system mode commanding. Returns whether
+        /// the reply contained a reached mode.
+        pub fn handle_mode_reply(&self, reply: &ModeReply) -> bool {
+            match reply {
+                ModeReply::ModeReply(mode) => self.set_mode(*mode),
+                ModeReply::WrongMode { reached, .. } => self.set_mode(*reached),
+                ModeReply::CantReachMode(_) => return false,
+            }
+            true
+        }
+
+        /// Update the system mode from a [ModeRequest::ModeInfo] notification. Returns whether
+        /// the request was a mode information.
+        pub fn handle_mode_info(&self, request: &ModeRequest) -> bool {
+            if let ModeRequest::ModeInfo(mode) = request {
+                self.set_mode(*mode);
+                return true;
+            }
+            false
+        }
+
+        pub fn set_policy(&self, mode: Mode, policy: TmGenerationPolicy) {
+            self.state
+                .policies
+                .write()
+                .unwrap()
+                .set_policy(mode, policy);
+        }
+
+        pub fn policy(&self, mode: Mode) -> TmGenerationPolicy {
+            self.state.policies.read().unwrap().policy(mode)
+        }
+    }
+
+    impl TmSuppressionProvider for SystemModeObserver {
+        fn tm_policy(&self) -> TmGenerationPolicy {
+            self.policy(self.mode().mode())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::mode::{ModeAndSubmode, ModeReply, ModeRequest};
+    use satrs_shared::res_code::ResultU16;
+
+    const MODE_NORMAL: u32 = 1;
+    const MODE_SAFE: u32 = 2;
+    const SAFE_POLICY: TmGenerationPolicy =
+        TmGenerationPolicy::new(Severity::Medium, HkGenerationPolicy::EssentialOnly);
+
+    fn create_observer() -> SystemModeObserver {
+        SystemModeObserver::new(
+            ModeAndSubmode::new_mode_only(MODE_NORMAL),
+            ModeTmPolicyTable::new(TmGenerationPolicy::ALL).with_policy(MODE_SAFE, SAFE_POLICY),
+        )
+    }
+
+    #[test]
+    fn test_policy() {
+        assert!(SAFE_POLICY.event_enabled(Severity::High));
+        assert!(SAFE_POLICY.event_enabled(Severity::Medium));
+        assert!(!SAFE_POLICY.event_enabled(Severity::Low));
+        assert!(SAFE_POLICY.hk.set_enabled(true));
+        assert!(!SAFE_POLICY.hk.set_enabled(false));
+        assert!(!HkGenerationPolicy::Disabled.set_enabled(true));
+        assert_eq!(
+            HkGenerationPolicy::Reduced { interval_factor: 0 }.interval_factor(),
+            1
+        );
+        assert_eq!(
+            HkGenerationPolicy::Reduced { interval_factor: 4 }.interval_factor(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_observer_follows_mode() {
+        let observer = create_observer();
+        let shared = observer.clone();
+        assert_eq!(shared.tm_policy(), TmGenerationPolicy::ALL);
+        observer.set_mode(ModeAndSubmode::new(MODE_SAFE, 3));
+        assert_eq!(shared.mode(), ModeAndSubmode::new(MODE_SAFE, 3));
+        assert_eq!(shared.tm_policy(), SAFE_POLICY);
+        observer.set_policy(MODE_SAFE, TmGenerationPolicy::ALL);
+        assert_eq!(shared.tm_policy(), TmGenerationPolicy::ALL);
+    }
+
+    #[test]
+    fn test_observer_mode_messages() {
+        let observer = create_observer();
+        assert!(
+            observer.handle_mode_reply(&ModeReply::ModeReply(ModeAndSubmode::new_mode_only(
+                MODE_SAFE
+            )))
+        );
+        assert_eq!(observer.tm_policy(), SAFE_POLICY);
+        assert!(!observer.handle_mode_reply(&ModeReply::CantReachMode(ResultU16::new(1, 0))));
+        assert_eq!(observer.mode().mode(), MODE_SAFE);
+        assert!(
+            observer.handle_mode_info(&ModeRequest::ModeInfo(ModeAndSubmode::new_mode_only(
+                MODE_NORMAL
+            )))
+        );
+        assert!(!observer.handle_mode_info(&ModeRequest::ReadMode));
+        assert_eq!(observer.tm_policy(), TmGenerationPolicy::ALL);
+    }
+}
@@ -0,0 +1,318 @@
+//! Bounded TM channels with configurable drop policies.
+//!
+//! The regular [std::sync::mpsc] TM channels are unbounded, so a stuck downlink can exhaust the
+//! available memory. The [bounded_tm_channel] function creates a TM channel with a fixed
+//! capacity. The [TmDropPolicy] of the channel determines what happens when a telemetry packet
+//! is sent while the channel is full. All dropped packets are counted, and the drop counter can
+//! be retrieved from both the [BoundedTmSender] and the [BoundedTmReceiver].
+//!
+//! The [TmDropPolicy::DropByPriority] policy requires a [TmPriorityClassifier] which assigns
+//! a priority to each packet based on its PUS service and subservice. The
+//! [default_tm_priority] classifier is used by default.
+//! See the [super::tm_priority] module for more information.
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use core::time::Duration;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+
+use spacepackets::ecss::{PusPacket, WritablePusPacket};
+
+use crate::pus::{EcssTmSender, EcssTmtcError, PusTmVariant};
+use crate::queue::GenericSendError;
+use crate::ComponentId;
+
+use super::tm_priority::{default_tm_priority, TmPriority, TmPriorityClassifier};
+use super::PacketAsVec;
+
+/// Policy applied when a packet is sent to a full [bounded_tm_channel].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TmDropPolicy {
+    /// Block the sender until the receiver has removed a packet.
+    Block,
+    /// Drop the packet which is sent. The sender receives a
+    /// [GenericSendError::QueueFull] error.
+    DropNewest,
+    /// Drop the oldest packet inside the channel to make room for the new packet.
+    DropOldest,
+    /// Drop the oldest packet with the lowest priority. The packet which is sent is dropped
+    /// instead if its priority is not higher than the lowest priority inside the channel. The
+    /// sender receives a [GenericSendError::QueueFull] error in that case.
+    DropByPriority,
+}
+
+#[derive(Debug)]
+struct BoundedTmChannel {
+    queue: Mutex<VecDeque<(TmPriority, PacketAsVec)>>,
+    not_full: Condvar,
+    not_empty: Condvar,
+    capacity: usize,
+    policy: TmDropPolicy,
+    classifier: TmPriorityClassifier,
+    num_dropped: AtomicU32,
+    receiver_alive: AtomicBool,
+}
+
+/// Create a new bounded TM channel which uses the [default_tm_priority] classifier.
+///
+/// ## Parameter
+///
+/// * `capacity` - Maximum number of packets inside the channel. Must be larger than 0.
+/// * `policy` - Policy which is applied when a packet is sent while the channel is full.
+pub fn bounded_tm_channel(
+    capacity: usize,
+    policy: TmDropPolicy,
+) -> (BoundedTmSender, BoundedTmReceiver) {
+    bounded_tm_channel_with_classifier(capacity, policy, default_tm_priority)
+}
+
+/// Create a new bounded TM channel with a custom [TmPriorityClassifier].
+pub fn bounded_tm_channel_with_classifier(
+    capacity: usize,
+    policy: TmDropPolicy,
+    classifier: TmPriorityClassifier,
+) -> (BoundedTmSender, BoundedTmReceiver) {
+    assert!(
+        capacity > 0,
+        "capacity of bounded TM channel must be larger than 0"
+    );
+    let channel = Arc::new(BoundedTmChannel {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        not_full: Condvar::new(),
+        not_empty: Condvar::new(),
+        capacity,
+        policy,
+        classifier,
+        num_dropped: AtomicU32::new(0),
+        receiver_alive: AtomicBool::new(true),
+    });
+    (
+        BoundedTmSender {
+            channel: channel.clone(),
+        },
+        BoundedTmReceiver { channel },
+    )
+}
+
+/// Sender half of a [bounded_tm_channel]. It can be cloned to create multiple senders.
+#[derive(Debug, Clone)]
+pub struct BoundedTmSender {
+    channel: Arc<BoundedTmChannel>,
+}
+
+impl BoundedTmSender {
+    pub fn capacity(&self) -> usize {
+        self.channel.capacity
+    }
+
+    pub fn policy(&self) -> TmDropPolicy {
+        self.channel.policy
+    }
+
+    /// Number of packets which were dropped because the channel was full.
+    pub fn num_dropped(&self) -> u32 {
+        self.channel.num_dropped.load(Ordering::Relaxed)
+    }
+
+    /// Send a packet with the given priority.
+    pub fn send_with_priority(
+        &self,
+        priority: TmPriority,
+        packet: PacketAsVec,
+    ) -> Result<(), GenericSendError> {
+        let channel = &self.channel;
+        let mut queue = channel.queue.lock().unwrap();
+        loop {
+            if !channel.receiver_alive.load(Ordering::Relaxed) {
+                return Err(GenericSendError::RxDisconnected);
+            }
+            if queue.len() < channel.capacity {
+                break;
+            }
+            match channel.policy {
+                TmDropPolicy::Block => {
+                    queue = channel.not_full.wait(queue).unwrap();
+                }
+                TmDropPolicy::DropNewest => {
+                    channel.num_dropped.fetch_add(1, Ordering::Relaxed);
+                    return Err(GenericSendError::QueueFull(Some(channel.capacity as u32)));
+                }
+                TmDropPolicy::DropOldest => {
+                    queue.pop_front();
+                    channel.num_dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                TmDropPolicy::DropByPriority => {
+                    // The queue is full, so there is at least one packet.
+                    let (lowest_idx, lowest_prio) = queue
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, (prio, _))| (idx, *prio))
+                        .min_by_key(|(_, prio)| *prio)
+                        .unwrap();
+                    channel.num_dropped.fetch_add(1, Ordering::Relaxed);
+                    if lowest_prio >= priority {
+                        return Err(GenericSendError::QueueFull(Some(channel.capacity as u32)));
+                    }
+                    queue.remove(lowest_idx);
+                }
+            }
+        }
+        queue.push_back((priority, packet));
+        channel.not_empty.notify_one();
+        Ok(())
+    }
+}
+
+impl EcssTmSender for BoundedTmSender {
+    fn send_tm(&self, sender_id: ComponentId, tm: PusTmVariant) -> Result<(), EcssTmtcError> {
+        match tm {
+            PusTmVariant::InStore(addr) => Err(EcssTmtcError::CantSendAddr(addr)),
+            PusTmVariant::Direct(tm) => {
+                let priority = (self.channel.classifier)(tm.service(), tm.subservice());
+                self.send_with_priority(priority, PacketAsVec::new(sender_id, tm.to_vec()?))
+                    .map_err(EcssTmtcError::Send)
+            }
+        }
+    }
+}
+
+/// Receiver half of a [bounded_tm_channel].
+#[derive(Debug)]
+pub struct BoundedTmReceiver {
+    channel: Arc<BoundedTmChannel>,
+}
+
+impl BoundedTmReceiver {
+    /// Number of packets which were dropped because the channel was full.
+    pub fn num_dropped(&self) -> u32 {
+        self.channel.num_dropped.load(Ordering::Relaxed)
+    }
+
+    /// Number of packets currently inside the channel.
+    pub fn len(&self) -> usize {
+        self.channel.queue.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Try to receive the oldest packet without blocking.
+    pub fn try_recv(&self) -> Option<PacketAsVec> {
+        let packet = self.channel.queue.lock().unwrap().pop_front();
+        if packet.is_some() {
+            self.channel.not_full.notify_one();
+        }
+        packet.map(|(_, packet)| packet)
+    }
+
+    /// Receive the oldest packet, waiting at most for the given timeout.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<PacketAsVec> {
+        let queue = self.channel.queue.lock().unwrap();
+        let (mut queue, _) = self
+            .channel
+            .not_empty
+            .wait_timeout_while(queue, timeout, |queue| queue.is_empty())
+            .unwrap();
+        let packet = queue.pop_front();
+        if packet.is_some() {
+            self.channel.not_full.notify_one();
+        }
+        packet.map(|(_, packet)| packet)
+    }
+}
+
+impl Drop for BoundedTmReceiver {
+    fn drop(&mut self) {
+        self.channel.receiver_alive.store(false, Ordering::Relaxed);
+        // Wake up all blocked senders.
+        let _queue = self.channel.queue.lock().unwrap();
+        self.channel.not_full.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::vec;
+
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmReader, PusTmSecondaryHeader};
+    use spacepackets::SpHeader;
+
+    use super::*;
+
+    fn send_tm(sender: &BoundedTmSender, service: u8) -> Result<(), EcssTmtcError> {
+        let sec_header = PusTmSecondaryHeader::new_simple(service, 1, &[]);
+        let tm = PusTmCreator::new(SpHeader::new_from_apid(0x02), sec_header, &[], true);
+        sender.send_tm(0, tm.into())
+    }
+
+    fn received_services(receiver: &BoundedTmReceiver) -> vec::Vec<u8> {
+        let mut services = vec::Vec::new();
+        while let Some(packet) = receiver.try_recv() {
+            services.push(PusTmReader::new(&packet.packet, 0).unwrap().0.service());
+        }
+        services
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        let (tx, rx) = bounded_tm_channel(2, TmDropPolicy::DropNewest);
+        send_tm(&tx, 1).unwrap();
+        send_tm(&tx, 2).unwrap();
+        assert!(matches!(
+            send_tm(&tx, 3),
+            Err(EcssTmtcError::Send(GenericSendError::QueueFull(Some(2))))
+        ));
+        assert_eq!(tx.num_dropped(), 1);
+        assert_eq!(rx.len(), 2);
+        assert_eq!(received_services(&rx), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let (tx, rx) = bounded_tm_channel(2, TmDropPolicy::DropOldest);
+        send_tm(&tx, 1).unwrap();
+        send_tm(&tx, 2).unwrap();
+        send_tm(&tx, 3).unwrap();
+        assert_eq!(rx.num_dropped(), 1);
+        assert_eq!(received_services(&rx), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_drop_by_priority() {
+        let (tx, rx) = bounded_tm_channel(3, TmDropPolicy::DropByPriority);
+        send_tm(&tx, 3).unwrap();
+        send_tm(&tx, 17).unwrap();
+        send_tm(&tx, 3).unwrap();
+        // The oldest HK packet is dropped for the verification packet.
+        send_tm(&tx, 1).unwrap();
+        // The other HK packet is dropped for the event packet.
+        send_tm(&tx, 5).unwrap();
+        // The HK packet itself has the lowest priority and is dropped.
+        assert!(send_tm(&tx, 3).is_err());
+        assert_eq!(tx.num_dropped(), 3);
+        assert_eq!(received_services(&rx), vec![17, 1, 5]);
+    }
+
+    #[test]
+    fn test_block() {
+        let (tx, rx) = bounded_tm_channel(1, TmDropPolicy::Block);
+        send_tm(&tx, 1).unwrap();
+        let tx_clone = tx.clone();
+        let jh = thread::spawn(move || send_tm(&tx_clone, 2));
+        let packet = rx.recv_timeout(Duration::from_millis(100)).unwrap();
+        assert_eq!(PusTmReader::new(&packet.packet, 0).unwrap().0.service(), 1);
+        jh.join().unwrap().unwrap();
+        let packet = rx.recv_timeout(Duration::from_millis(100)).unwrap();
+        assert_eq!(PusTmReader::new(&packet.packet, 0).unwrap().0.service(), 2);
+        assert!(rx.recv_timeout(Duration::from_millis(5)).is_none());
+        assert_eq!(tx.num_dropped(), 0);
+
+        send_tm(&tx, 1).unwrap();
+        drop(rx);
+        assert!(matches!(
+            send_tm(&tx, 2),
+            Err(EcssTmtcError::Send(GenericSendError::RxDisconnected))
+        ));
+    }
+}
@@ -0,0 +1,542 @@
+//! Store-and-forward recording of TM during link outages.
+//!
+//! The [DownlinkGate] is placed between the TM funnel and the live downlink sink. As long as the
+//! downlink is available, all TM is forwarded to the sink. During link outages, the TM is
+//! diverted into an on-board storage pool instead. Once the link returns, the stored backlog is
+//! replayed in the original order with [DownlinkGate::replay]. The replay is rate limited by a
+//! [ReplayLimit] so that the backlog does not saturate the downlink, and live TM is still
+//! forwarded immediately while the backlog is replayed.
+//!
+//! The gate can either follow the [LinkState] reported by the communication hardware, or it can
+//! be commanded to always forward or always store TM with a [DownlinkGateCommand]. The
+//! [LinkStateProvider] trait is the link-state API used to drive the gate, and the
+//! [SharedLinkState] is a simple thread-safe implementation which can be updated by the
+//! component handling the transceiver.
+use alloc::collections::VecDeque;
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::pool::{PoolAddr, PoolError, PoolProvider};
+use crate::ComponentId;
+
+use super::PacketSenderRaw;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// State of the downlink.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LinkState {
+    Up,
+    Down,
+}
+
+/// Generic trait for components which provide the current [LinkState], for example the handler
+/// of a transceiver.
+pub trait LinkStateProvider {
+    fn link_state(&self) -> LinkState;
+}
+
+impl LinkStateProvider for LinkState {
+    fn link_state(&self) -> LinkState {
+        *self
+    }
+}
+
+/// Operating mode of the [DownlinkGate].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DownlinkGateMode {
+    /// Forward TM while the link is up and store it while the link is down.
+    #[default]
+    FollowLink,
+    /// Always forward TM to the live downlink sink.
+    Forward,
+    /// Always store TM in the pool. The backlog is not replayed in this mode.
+    Store,
+}
+
+/// Limits for one [DownlinkGate::replay] cycle. At least one packet is replayed per cycle, even
+/// if it is larger than the maximum number of bytes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayLimit {
+    pub max_packets: usize,
+    pub max_bytes: Option<usize>,
+}
+
+impl ReplayLimit {
+    pub const fn new(max_packets: usize, max_bytes: Option<usize>) -> Self {
+        Self {
+            max_packets,
+            max_bytes,
+        }
+    }
+}
+
+impl Default for ReplayLimit {
+    fn default() -> Self {
+        Self::new(10, None)
+    }
+}
+
+/// Commands which can be sent to a [DownlinkGate].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DownlinkGateCommand {
+    SetMode(DownlinkGateMode),
+    SetReplayLimit(ReplayLimit),
+    /// Delete all stored packets.
+    ClearBacklog,
+}
+
+/// Action which was performed for a packet passed to [DownlinkGate::handle_tm].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GateAction {
+    Forwarded,
+    Stored,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownlinkGateError<SinkError> {
+    /// Storing or reading a packet failed.
+    Pool(PoolError),
+    Sink(SinkError),
+}
+
+impl<SinkError: Display> Display for DownlinkGateError<SinkError> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DownlinkGateError::Pool(e) => write!(f, "TM storage error: {e}"),
+            DownlinkGateError::Sink(e) => write!(f, "sending TM to downlink sink failed: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<SinkError: Error + 'static> Error for DownlinkGateError<SinkError> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DownlinkGateError::Pool(e) => Some(e),
+            DownlinkGateError::Sink(e) => Some(e),
+        }
+    }
+}
+
+impl<SinkError> From<PoolError> for DownlinkGateError<SinkError> {
+    fn from(value: PoolError) -> Self {
+        Self::Pool(value)
+    }
+}
+
+/// Gate which forwards TM to the live downlink sink or diverts it into a storage pool, and
+/// replays the stored backlog once the downlink is available again.
+///
+/// If the pool is full, storing a packet fails with a [PoolError] by default. The gate can be
+/// configured to overwrite the oldest stored packets instead with [Self::with_overwrite_oldest].
+/// All packets which could not be stored are counted.
+pub struct DownlinkGate<Sink: PacketSenderRaw, Pool: PoolProvider> {
+    sink: Sink,
+    pool: Pool,
+    backlog: VecDeque<(ComponentId, PoolAddr)>,
+    mode: DownlinkGateMode,
+    link_state: LinkState,
+    replay_limit: ReplayLimit,
+    overwrite_oldest: bool,
+    num_dropped: u32,
+}
+
+impl<Sink: PacketSenderRaw, Pool: PoolProvider> DownlinkGate<Sink, Pool> {
+    /// Create a new gate. The link is assumed to be down initially, so all TM is stored until
+    /// the link state is updated to [LinkState::Up].
+    pub fn new(sink: Sink, pool: Pool, replay_limit: ReplayLimit) -> Self {
+        Self {
+            sink,
+            pool,
+            backlog: VecDeque::new(),
+            mode: DownlinkGateMode::default(),
+            link_state: LinkState::Down,
+            replay_limit,
+            overwrite_oldest: false,
+            num_dropped: 0,
+        }
+    }
+
+    pub fn with_overwrite_oldest(mut self, overwrite_oldest: bool) -> Self {
+        self.overwrite_oldest = overwrite_oldest;
+        self
+    }
+
+    pub fn sink(&self) -> &Sink {
+        &self.sink
+    }
+
+    pub fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    pub fn mode(&self) -> DownlinkGateMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: DownlinkGateMode) {
+        self.mode = mode;
+    }
+
+    pub fn link_state(&self) -> LinkState {
+        self.link_state
+    }
+
+    pub fn set_link_state(&mut self, link_state: LinkState) {
+        self.link_state = link_state;
+    }
+
+    /// Update the link state from the given provider.
+    pub fn update_link_state(&mut self, provider: &impl LinkStateProvider) {
+        self.link_state = provider.link_state();
+    }
+
+    pub fn replay_limit(&self) -> ReplayLimit {
+        self.replay_limit
+    }
+
+    pub fn set_replay_limit(&mut self, replay_limit: ReplayLimit) {
+        self.replay_limit = replay_limit;
+    }
+
+    /// Number of stored packets which were not replayed yet.
+    pub fn backlog_len(&self) -> usize {
+        self.backlog.len()
+    }
+
+    /// Number of packets which were dropped because they could not be stored or could not be
+    /// read back from the pool for the replay.
+    pub fn num_dropped(&self) -> u32 {
+        self.num_dropped
+    }
+
+    /// Whether TM is currently forwarded to the live downlink sink.
+    pub fn is_forwarding(&self) -> bool {
+        match self.mode {
+            DownlinkGateMode::FollowLink => self.link_state == LinkState::Up,
+            DownlinkGateMode::Forward => true,
+            DownlinkGateMode::Store => false,
+        }
+    }
+
+    pub fn handle_command(&mut self, command: DownlinkGateCommand) -> Result<(), PoolError> {
+        match command {
+            DownlinkGateCommand::SetMode(mode) => self.set_mode(mode),
+            DownlinkGateCommand::SetReplayLimit(limit) => self.set_replay_limit(limit),
+            DownlinkGateCommand::ClearBacklog => self.clear_backlog()?,
+        }
+        Ok(())
+    }
+
+    /// Forward the packet to the live downlink sink or store it, depending on the current mode
+    /// and link state.
+    pub fn handle_tm(
+        &mut self,
+        sender_id: ComponentId,
+        packet: &[u8],
+    ) -> Result<GateAction, DownlinkGateError<Sink::Error>> {
+        if self.is_forwarding() {
+            self.sink
+                .send_packet(sender_id, packet)
+                .map_err(DownlinkGateError::Sink)?;
+            return Ok(GateAction::Forwarded);
+        }
+        self.store(sender_id, packet)?;
+        Ok(GateAction::Stored)
+    }
+
+    fn store(&mut self, sender_id: ComponentId, packet: &[u8]) -> Result<(), PoolError> {
+        loop {
+            match self.pool.add(packet) {
+                Ok(addr) => {
+                    self.backlog.push_back((sender_id, addr));
+                    return Ok(());
+                }
+                Err(PoolError::StoreFull(_))
+                    if self.overwrite_oldest && !self.backlog.is_empty() =>
+                {
+                    let (_, oldest) = self.backlog.pop_front().unwrap();
+                    self.pool.delete(oldest)?;
+                    self.num_dropped += 1;
+                }
+                Err(e) => {
+                    self.num_dropped += 1;
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Replay stored packets to the live downlink sink in the order they were stored. At most
+    /// the number of packets and bytes specified by the [ReplayLimit] are replayed. This
+    /// function should be called periodically and does nothing if TM is not forwarded.
+    ///
+    /// Returns the number of replayed packets. A packet which could not be sent remains
+    /// in the backlog. A packet which could not be read from the pool is removed from the
+    /// backlog and counted as dropped, so it does not block the replay of the other packets.
+    pub fn replay(&mut self) -> Result<usize, DownlinkGateError<Sink::Error>> {
+        if !self.is_forwarding() {
+            return Ok(0);
+        }
+        let mut num_replayed = 0;
+        let mut num_bytes = 0;
+        while num_replayed < self.replay_limit.max_packets {
+            let (sender_id, addr) = match self.backlog.front() {
+                Some(entry) => *entry,
+                None => break,
+            };
+            let packet = match self.pool.read_as_vec(&addr) {
+                Ok(packet) => packet,
+                Err(_) => {
+                    self.backlog.pop_front();
+                    // The entry might be corrupt or already be deleted, so a deletion failure is
+                    // ignored.
+                    self.pool.delete(addr).ok();
+                    self.num_dropped += 1;
+                    continue;
+                }
+            };
+            if let Some(max_bytes) = self.replay_limit.max_bytes {
+                if num_replayed > 0 && num_bytes + packet.len() > max_bytes {
+                    break;
+                }
+            }
+            self.sink
+                .send_packet(sender_id, &packet)
+                .map_err(DownlinkGateError::Sink)?;
+            self.backlog.pop_front();
+            self.pool.delete(addr)?;
+            num_bytes += packet.len();
+            num_replayed += 1;
+        }
+        Ok(num_replayed)
+    }
+
+    /// Delete all stored packets.
+    pub fn clear_backlog(&mut self) -> Result<(), PoolError> {
+        while let Some((_, addr)) = self.backlog.pop_front() {
+            self.pool.delete(addr)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Thread-safe [DownlinkGate] which can be used as the [PacketSenderRaw] of the TM funnel,
+    /// while another component replays the backlog or updates the link state.
+    pub struct SharedDownlinkGate<Sink: PacketSenderRaw, Pool: PoolProvider>(
+        pub Arc<Mutex<DownlinkGate<Sink, Pool>>>,
+    );
+
+    impl<Sink: PacketSenderRaw, Pool: PoolProvider> SharedDownlinkGate<Sink, Pool> {
+        pub fn new(gate: DownlinkGate<Sink, Pool>) -> Self {
+            Self(Arc::new(Mutex::new(gate)))
+        }
+    }
+
+    impl<Sink: PacketSenderRaw, Pool: PoolProvider> Clone for SharedDownlinkGate<Sink, Pool> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+
+    impl<Sink: PacketSenderRaw, Pool: PoolProvider + Send> PacketSenderRaw
+        for SharedDownlinkGate<Sink, Pool>
+    {
+        type Error = DownlinkGateError<Sink::Error>;
+
+        fn send_packet(&self, sender_id: ComponentId, packet: &[u8]) -> Result<(), Self::Error> {
+            self.0
+                .lock()
+                .map_err(|_| PoolError::LockError)?
+                .handle_tm(sender_id, packet)?;
+            Ok(())
+        }
+    }
+
+    /// Thread-safe [LinkState] which can be shared between the component handling the
+    /// communication hardware and the [DownlinkGate].
+    #[derive(Debug, Clone)]
+    pub struct SharedLinkState(Arc<AtomicBool>);
+
+    impl SharedLinkState {
+        pub fn new(initial: LinkState) -> Self {
+            Self(Arc::new(AtomicBool::new(initial == LinkState::Up)))
+        }
+
+        pub fn set(&self, link_state: LinkState) {
+            self.0.store(link_state == LinkState::Up, Ordering::Relaxed);
+        }
+    }
+
+    impl LinkStateProvider for SharedLinkState {
+        fn link_state(&self) -> LinkState {
+            if self.0.load(Ordering::Relaxed) {
+                LinkState::Up
+            } else {
+                LinkState::Down
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::sync::mpsc;
+    use std::vec;
+    use std::vec::Vec;
+
+    use crate::pool::{StaticMemoryPool, StaticPoolConfig};
+    use crate::tmtc::PacketAsVec;
+
+    use super::*;
+
+    const TEST_SENDER_ID: ComponentId = 5;
+
+    type TestGate = DownlinkGate<mpsc::Sender<PacketAsVec>, StaticMemoryPool>;
+
+    fn create_gate(num_blocks: u16, limit: ReplayLimit) -> (TestGate, mpsc::Receiver<PacketAsVec>) {
+        let (tx, rx) = mpsc::channel();
+        let pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            vec![(num_blocks, 8)],
+            false,
+        ));
+        (DownlinkGate::new(tx, pool, limit), rx)
+    }
+
+    fn received(rx: &mpsc::Receiver<PacketAsVec>) -> Vec<Vec<u8>> {
+        rx.try_iter().map(|packet| packet.packet).collect()
+    }
+
+    #[test]
+    fn test_store_and_replay() {
+        let (mut gate, rx) = create_gate(8, ReplayLimit::new(2, None));
+        let link_state = SharedLinkState::new(LinkState::Down);
+        gate.update_link_state(&link_state);
+        for i in 0..3 {
+            assert_eq!(
+                gate.handle_tm(TEST_SENDER_ID, &[i; 4]).unwrap(),
+                GateAction::Stored
+            );
+        }
+        assert_eq!(gate.backlog_len(), 3);
+        assert_eq!(gate.replay().unwrap(), 0);
+        assert!(received(&rx).is_empty());
+
+        link_state.set(LinkState::Up);
+        gate.update_link_state(&link_state);
+        assert_eq!(
+            gate.handle_tm(TEST_SENDER_ID, &[10; 4]).unwrap(),
+            GateAction::Forwarded
+        );
+        assert_eq!(gate.replay().unwrap(), 2);
+        assert_eq!(gate.replay().unwrap(), 1);
+        assert_eq!(gate.replay().unwrap(), 0);
+        assert_eq!(
+            received(&rx),
+            vec![vec![10; 4], vec![0; 4], vec![1; 4], vec![2; 4]]
+        );
+        assert_eq!(gate.backlog_len(), 0);
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        let (mut gate, rx) = create_gate(8, ReplayLimit::new(10, Some(6)));
+        gate.handle_tm(TEST_SENDER_ID, &[1; 4]).unwrap();
+        gate.handle_tm(TEST_SENDER_ID, &[2; 4]).unwrap();
+        gate.set_link_state(LinkState::Up);
+        assert_eq!(gate.replay().unwrap(), 1);
+        assert_eq!(gate.replay().unwrap(), 1);
+        assert_eq!(received(&rx).len(), 2);
+    }
+
+    #[test]
+    fn test_commanded_mode() {
+        let (mut gate, rx) = create_gate(8, ReplayLimit::default());
+        gate.handle_command(DownlinkGateCommand::SetMode(DownlinkGateMode::Forward))
+            .unwrap();
+        assert_eq!(
+            gate.handle_tm(TEST_SENDER_ID, &[1; 4]).unwrap(),
+            GateAction::Forwarded
+        );
+        gate.set_link_state(LinkState::Up);
+        gate.handle_command(DownlinkGateCommand::SetMode(DownlinkGateMode::Store))
+            .unwrap();
+        assert_eq!(
+            gate.handle_tm(TEST_SENDER_ID, &[2; 4]).unwrap(),
+            GateAction::Stored
+        );
+        assert_eq!(gate.replay().unwrap(), 0);
+        gate.handle_command(DownlinkGateCommand::ClearBacklog)
+            .unwrap();
+        assert_eq!(gate.backlog_len(), 0);
+        assert_eq!(received(&rx), vec![vec![1; 4]]);
+    }
+
+    #[test]
+    fn test_pool_full() {
+        let (mut gate, _rx) = create_gate(2, ReplayLimit::default());
+        gate.handle_tm(TEST_SENDER_ID, &[1; 4]).unwrap();
+        gate.handle_tm(TEST_SENDER_ID, &[2; 4]).unwrap();
+        assert!(matches!(
+            gate.handle_tm(TEST_SENDER_ID, &[3; 4]),
+            Err(DownlinkGateError::Pool(PoolError::StoreFull(_)))
+        ));
+        assert_eq!(gate.num_dropped(), 1);
+        assert_eq!(gate.backlog_len(), 2);
+    }
+
+    #[test]
+    fn test_overwrite_oldest() {
+        let (gate, rx) = create_gate(2, ReplayLimit::default());
+        let mut gate = gate.with_overwrite_oldest(true);
+        for i in 0..3 {
+            gate.handle_tm(TEST_SENDER_ID, &[i; 4]).unwrap();
+        }
+        assert_eq!(gate.num_dropped(), 1);
+        gate.set_link_state(LinkState::Up);
+        assert_eq!(gate.replay().unwrap(), 2);
+        assert_eq!(received(&rx), vec![vec![1; 4], vec![2; 4]]);
+    }
+
+    #[test]
+    fn test_unreadable_entry_is_dropped() {
+        let (mut gate, rx) = create_gate(8, ReplayLimit::default());
+        for i in 0..3 {
+            gate.handle_tm(TEST_SENDER_ID, &[i; 4]).unwrap();
+        }
+        // Delete the second packet behind the back of the gate.
+        let (_, addr) = gate.backlog[1];
+        gate.pool.delete(addr).unwrap();
+        gate.set_link_state(LinkState::Up);
+        assert_eq!(gate.replay().unwrap(), 2);
+        assert_eq!(gate.num_dropped(), 1);
+        assert_eq!(gate.backlog_len(), 0);
+        assert_eq!(received(&rx), vec![vec![0; 4], vec![2; 4]]);
+    }
+
+    #[test]
+    fn test_shared_gate_as_packet_sender() {
+        let (gate, rx) = create_gate(8, ReplayLimit::default());
+        let shared_gate = SharedDownlinkGate::new(gate);
+        shared_gate.send_packet(TEST_SENDER_ID, &[1; 4]).unwrap();
+        assert_eq!(shared_gate.0.lock().unwrap().backlog_len(), 1);
+        shared_gate.0.lock().unwrap().set_link_state(LinkState::Up);
+        shared_gate.send_packet(TEST_SENDER_ID, &[2; 4]).unwrap();
+        assert_eq!(shared_gate.0.lock().unwrap().replay().unwrap(), 1);
+        assert_eq!(received(&rx), vec![vec![2; 4], vec![1; 4]]);
+    }
+}
@@ -12,6 +12,8 @@ use crate::{
     pool::{PoolAddr, PoolError},
     ComponentId,
 };
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 #[cfg(feature = "std")]
 pub use alloc_mod::*;
 #[cfg(feature = "alloc")]
@@ -28,7 +30,25 @@ use std::sync::mpsc;
 #[cfg(feature = "std")]
 pub use std_mod::*;
 
+#[cfg(feature = "std")]
+pub mod bounded_tm;
+#[cfg(feature = "alloc")]
+pub mod downlink_gate;
+#[cfg(feature = "std")]
+pub mod recorder;
+#[cfg(feature = "std")]
+pub mod replay;
+#[cfg(feature = "alloc")]
+pub mod stats;
+#[cfg(feature = "alloc")]
+pub mod tm_compression;
+#[cfg(feature = "alloc")]
+pub mod tm_funnel;
 pub mod tm_helper;
+#[cfg(feature = "alloc")]
+pub mod tm_priority;
+#[cfg(feature = "alloc")]
+pub mod tm_shaper;
 
 /// Simple type modelling packet stored inside a pool structure. This structure is intended to
 /// be used when sending a packet via a message queue, so it also contains the sender ID.
@@ -187,6 +207,18 @@ pub trait PusTcPool {
 pub trait PusTmPool {
     fn add_pus_tm_from_reader(&mut self, pus_tm: &PusTmReader) -> Result<PoolAddr, PoolError>;
     fn add_pus_tm_from_creator(&mut self, pus_tm: &PusTmCreator) -> Result<PoolAddr, PoolError>;
+
+    /// Add a batch of telemetry packets and return their store addresses in the same order.
+    ///
+    /// The default implementation adds the packets individually. Packets which were added
+    /// before an error occured are not removed from the pool.
+    #[cfg(feature = "alloc")]
+    fn add_pus_tm_batch(&mut self, pus_tms: &[PusTmCreator]) -> Result<Vec<PoolAddr>, PoolError> {
+        pus_tms
+            .iter()
+            .map(|pus_tm| self.add_pus_tm_from_creator(pus_tm))
+            .collect()
+    }
 }
 
 /// Generic trait for any sender component able to send packets stored inside a pool structure.
@@ -196,11 +228,28 @@ pub trait PacketInPoolSender: Send {
         sender_id: ComponentId,
         store_addr: PoolAddr,
     ) -> Result<(), GenericSendError>;
+
+    /// Send a batch of packets stored inside a pool structure. The default implementation sends
+    /// the packets individually.
+    fn send_packet_batch(
+        &self,
+        sender_id: ComponentId,
+        store_addrs: &[PoolAddr],
+    ) -> Result<(), GenericSendError> {
+        for store_addr in store_addrs {
+            self.send_packet(sender_id, *store_addr)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(feature = "alloc")]
 pub mod alloc_mod {
     use alloc::vec::Vec;
+    use core::fmt::{Display, Formatter};
+    use spacepackets::CcsdsPacket;
+    #[cfg(feature = "std")]
+    use std::error::Error;
 
     use super::*;
 
@@ -217,6 +266,232 @@ pub mod alloc_mod {
             Self { sender_id, packet }
         }
     }
+
+    /// Selects the APIDs which are handled by a handler registered at the [CcsdsApidRouter].
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum ApidSelector {
+        /// Exactly one APID.
+        Exact(u16),
+        /// All APIDs inside the inclusive range.
+        Range { start: u16, end: u16 },
+        /// All APIDs. This can be used for a default handler.
+        Wildcard,
+    }
+
+    impl ApidSelector {
+        pub fn matches(&self, apid: u16) -> bool {
+            match self {
+                ApidSelector::Exact(exact) => *exact == apid,
+                ApidSelector::Range { start, end } => (*start..=*end).contains(&apid),
+                ApidSelector::Wildcard => true,
+            }
+        }
+
+        /// Precedence key for the selector. Lower values have a higher precedence: Exact APIDs
+        /// have the highest precedence, followed by ranges with narrower ranges taking
+        /// precedence. The wildcard selector has the lowest precedence.
+        fn precedence(&self) -> u32 {
+            match self {
+                ApidSelector::Exact(_) => 0,
+                ApidSelector::Range { start, end } => 1 + (*end - *start) as u32,
+                ApidSelector::Wildcard => u32::MAX,
+            }
+        }
+    }
+
+    /// ID which is assigned to each handler registered at the [CcsdsApidRouter]. It can be used
+    /// to remove the handler again.
+    pub type ApidHandlerId = u32;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum ApidRouterConfigError {
+        /// The start of the APID range is larger than the end or the end is larger than the
+        /// maximum APID.
+        InvalidRange { start: u16, end: u16 },
+        /// Handler for the exact APID is already registered.
+        ApidAlreadyRegistered(u16),
+        /// Wildcard handler is already registered.
+        WildcardAlreadyRegistered,
+    }
+
+    impl Display for ApidRouterConfigError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                ApidRouterConfigError::InvalidRange { start, end } => {
+                    write!(f, "invalid APID range [{start}, {end}]")
+                }
+                ApidRouterConfigError::ApidAlreadyRegistered(apid) => {
+                    write!(f, "handler for APID {apid} already registered")
+                }
+                ApidRouterConfigError::WildcardAlreadyRegistered => {
+                    write!(f, "wildcard handler already registered")
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl Error for ApidRouterConfigError {}
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum ApidRoutingError<SendError> {
+        /// No handler is registered for the APID of the packet.
+        NoHandlerForApid(u16),
+        Send(SendError),
+    }
+
+    impl<SendError: Display> Display for ApidRoutingError<SendError> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+            match self {
+                ApidRoutingError::NoHandlerForApid(apid) => {
+                    write!(f, "no handler for APID {apid}")
+                }
+                ApidRoutingError::Send(e) => write!(f, "apid routing error: {e}"),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<SendError: Error + 'static> Error for ApidRoutingError<SendError> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                ApidRoutingError::Send(e) => Some(e),
+                _ => None,
+            }
+        }
+    }
+
+    /// Router for CCSDS space packets which forwards each packet to a handler based on the APID
+    /// of the packet.
+    ///
+    /// Handlers can be registered for exact APIDs, for inclusive APID ranges and as a wildcard
+    /// handler for all APIDs. If multiple handlers match an APID, the handler for the exact APID
+    /// takes precedence, followed by the handler with the narrowest matching range and finally the
+    /// wildcard handler. If multiple ranges with the same width match, the handler registered
+    /// first is used. Handlers can be listed and removed at runtime.
+    ///
+    /// The router itself implements [PacketSenderCcsds], so it can be used like any other CCSDS
+    /// packet sender, for example as the target of a TC source.
+    pub struct CcsdsApidRouter<Sender: PacketSenderCcsds> {
+        handlers: Vec<(ApidHandlerId, ApidSelector, Sender)>,
+        next_id: ApidHandlerId,
+    }
+
+    impl<Sender: PacketSenderCcsds> Default for CcsdsApidRouter<Sender> {
+        fn default() -> Self {
+            Self {
+                handlers: Vec::new(),
+                next_id: 0,
+            }
+        }
+    }
+
+    impl<Sender: PacketSenderCcsds> CcsdsApidRouter<Sender> {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Register a handler for the given APID selector. Returns the ID of the handler which
+        /// can be used to remove the handler.
+        pub fn add_handler(
+            &mut self,
+            selector: ApidSelector,
+            handler: Sender,
+        ) -> Result<ApidHandlerId, ApidRouterConfigError> {
+            match selector {
+                ApidSelector::Exact(apid) => {
+                    if self
+                        .handlers
+                        .iter()
+                        .any(|(_, existing, _)| *existing == selector)
+                    {
+                        return Err(ApidRouterConfigError::ApidAlreadyRegistered(apid));
+                    }
+                }
+                ApidSelector::Range { start, end } => {
+                    if start > end || end > spacepackets::MAX_APID {
+                        return Err(ApidRouterConfigError::InvalidRange { start, end });
+                    }
+                }
+                ApidSelector::Wildcard => {
+                    if self
+                        .handlers
+                        .iter()
+                        .any(|(_, existing, _)| *existing == selector)
+                    {
+                        return Err(ApidRouterConfigError::WildcardAlreadyRegistered);
+                    }
+                }
+            }
+            let id = self.next_id;
+            self.next_id = self.next_id.wrapping_add(1);
+            self.handlers.push((id, selector, handler));
+            Ok(id)
+        }
+
+        /// Remove the handler with the given ID. Returns the selector and the handler if a
+        /// handler was removed.
+        pub fn remove_handler(&mut self, id: ApidHandlerId) -> Option<(ApidSelector, Sender)> {
+            let idx = self
+                .handlers
+                .iter()
+                .position(|(handler_id, _, _)| *handler_id == id)?;
+            let (_, selector, handler) = self.handlers.remove(idx);
+            Some((selector, handler))
+        }
+
+        /// Iterator over the IDs and APID selectors of all registered handlers in the order of
+        /// registration.
+        pub fn handlers(&self) -> impl Iterator<Item = (ApidHandlerId, &ApidSelector)> {
+            self.handlers
+                .iter()
+                .map(|(id, selector, _)| (*id, selector))
+        }
+
+        pub fn num_handlers(&self) -> usize {
+            self.handlers.len()
+        }
+
+        /// Retrieve the ID and the handler which would be used for the given APID.
+        pub fn handler_for_apid(&self, apid: u16) -> Option<(ApidHandlerId, &Sender)> {
+            let mut selected: Option<&(ApidHandlerId, ApidSelector, Sender)> = None;
+            for entry in self.handlers.iter() {
+                if !entry.1.matches(apid) {
+                    continue;
+                }
+                if selected.map_or(true, |current| {
+                    entry.1.precedence() < current.1.precedence()
+                }) {
+                    selected = Some(entry);
+                }
+            }
+            selected.map(|(id, _, handler)| (*id, handler))
+        }
+    }
+
+    impl<Sender: PacketSenderCcsds> PacketSenderCcsds for CcsdsApidRouter<Sender> {
+        type Error = ApidRoutingError<Sender::Error>;
+
+        fn send_ccsds(
+            &self,
+            sender_id: ComponentId,
+            header: &SpHeader,
+            packet_raw: &[u8],
+        ) -> Result<(), Self::Error> {
+            let apid = header.apid();
+            let (handler_id, handler) = match self.handler_for_apid(apid) {
+                Some(entry) => entry,
+                None => {
+                    trace_warn!("no TC handler for APID {:#x}", apid);
+                    return Err(ApidRoutingError::NoHandlerForApid(apid));
+                }
+            };
+            trace_debug!("routing TC with APID {:#x} to handler {}", apid, handler_id);
+            handler
+                .send_ccsds(sender_id, header, packet_raw)
+                .map_err(ApidRoutingError::Send)
+        }
+    }
 }
 #[cfg(feature = "std")]
 pub mod std_mod {
@@ -228,8 +503,11 @@ pub mod std_mod {
     use spacepackets::ecss::WritablePusPacket;
     use thiserror::Error;
 
-    use crate::pool::PoolProvider;
+    use crate::pool::{
+        PoolAddrProvider, PoolProvider, PriorityClass, PriorityQueueError, SharedPriorityPoolQueue,
+    };
     use crate::pus::{EcssTmSender, EcssTmtcError, PacketSenderPusTc};
+    use spacepackets::ecss::tm::PusTmSecondaryHeader;
 
     use super::*;
 
@@ -242,6 +520,26 @@ pub mod std_mod {
         pub fn new(pool: &SharedStaticMemoryPool) -> Self {
             Self(pool.clone())
         }
+
+        /// Add a PUS TM whose source data is written directly into the pool by the
+        /// `source_data_writer`, which avoids serializing the source data into a separate buffer
+        /// first. See [tm_helper::add_pus_tm_in_place] for more details.
+        pub fn add_pus_tm_in_place<W: FnMut(&mut [u8])>(
+            &mut self,
+            sp_header: SpHeader,
+            sec_header: PusTmSecondaryHeader,
+            source_data_len: usize,
+            source_data_writer: W,
+        ) -> Result<PoolAddr, PoolError> {
+            let mut pg = self.0.write().map_err(|_| PoolError::LockError)?;
+            tm_helper::add_pus_tm_in_place(
+                &mut *pg,
+                sp_header,
+                sec_header,
+                source_data_len,
+                source_data_writer,
+            )
+        }
     }
 
     impl PusTcPool for SharedPacketPool {
@@ -268,12 +566,101 @@ pub mod std_mod {
             pus_tm: &PusTmCreator,
         ) -> Result<PoolAddr, PoolError> {
             let mut pg = self.0.write().map_err(|_| PoolError::LockError)?;
-            let mut result = Ok(0);
-            let addr = pg.free_element(pus_tm.len_written(), |buf| {
-                result = pus_tm.write_to_bytes(buf);
-            })?;
-            result?;
-            Ok(addr)
+            add_pus_tm_to_pool(&mut *pg, pus_tm)
+        }
+
+        /// Adds all packets while holding the pool lock only once. If adding any packet fails,
+        /// all packets of the batch which were already added are removed again.
+        fn add_pus_tm_batch(
+            &mut self,
+            pus_tms: &[PusTmCreator],
+        ) -> Result<Vec<PoolAddr>, PoolError> {
+            let mut pg = self.0.write().map_err(|_| PoolError::LockError)?;
+            let mut addrs = Vec::with_capacity(pus_tms.len());
+            for pus_tm in pus_tms {
+                match add_pus_tm_to_pool(&mut *pg, pus_tm) {
+                    Ok(addr) => addrs.push(addr),
+                    Err(e) => {
+                        for addr in addrs {
+                            // Best effort rollback, the original error is more relevant.
+                            let _ = pg.delete(addr);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(addrs)
+        }
+    }
+
+    fn add_pus_tm_to_pool(
+        pool: &mut impl PoolProvider,
+        pus_tm: &PusTmCreator,
+    ) -> Result<PoolAddr, PoolError> {
+        let mut result = Ok(0);
+        let addr = pool.free_element(pus_tm.len_written(), |buf| {
+            result = pus_tm.write_to_bytes(buf);
+        })?;
+        result?;
+        Ok(addr)
+    }
+
+    /// Batch of packets stored inside a pool structure. This structure is intended to be used
+    /// when sending multiple packets with a single message queue operation, for example with
+    /// [PacketInPoolSender::send_packet_batch].
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct PacketBatchInPool {
+        pub sender_id: ComponentId,
+        pub store_addrs: Vec<PoolAddr>,
+    }
+
+    impl PacketBatchInPool {
+        pub fn new(sender_id: ComponentId, store_addrs: Vec<PoolAddr>) -> Self {
+            Self {
+                sender_id,
+                store_addrs,
+            }
+        }
+    }
+
+    impl PacketInPoolSender for mpsc::Sender<PacketBatchInPool> {
+        fn send_packet(
+            &self,
+            sender_id: ComponentId,
+            store_addr: PoolAddr,
+        ) -> Result<(), GenericSendError> {
+            self.send_packet_batch(sender_id, &[store_addr])
+        }
+
+        fn send_packet_batch(
+            &self,
+            sender_id: ComponentId,
+            store_addrs: &[PoolAddr],
+        ) -> Result<(), GenericSendError> {
+            self.send(PacketBatchInPool::new(sender_id, store_addrs.to_vec()))
+                .map_err(|_| GenericSendError::RxDisconnected)
+        }
+    }
+
+    impl PacketInPoolSender for mpsc::SyncSender<PacketBatchInPool> {
+        fn send_packet(
+            &self,
+            sender_id: ComponentId,
+            store_addr: PoolAddr,
+        ) -> Result<(), GenericSendError> {
+            self.send_packet_batch(sender_id, &[store_addr])
+        }
+
+        fn send_packet_batch(
+            &self,
+            sender_id: ComponentId,
+            store_addrs: &[PoolAddr],
+        ) -> Result<(), GenericSendError> {
+            self.try_send(PacketBatchInPool::new(sender_id, store_addrs.to_vec()))
+                .map_err(|e| match e {
+                    mpsc::TrySendError::Full(_) => GenericSendError::QueueFull(None),
+                    mpsc::TrySendError::Disconnected(_) => GenericSendError::RxDisconnected,
+                })
         }
     }
 
@@ -360,6 +747,53 @@ pub mod std_mod {
         }
     }
 
+    impl PoolAddrProvider for PacketInPool {
+        fn pool_addr(&self) -> PoolAddr {
+            self.store_addr
+        }
+    }
+
+    /// [PacketInPoolSender] implementation which pushes packets into a shared
+    /// [PriorityPoolQueue] with a fixed [PriorityClass].
+    ///
+    /// This allows multiple packet sources to send telecommands with different priorities to a
+    /// single TC source, which can then release the telecommands using [PriorityPoolQueue::pop].
+    #[derive(Clone)]
+    pub struct PriorityPacketInPoolSender {
+        pub queue: SharedPriorityPoolQueue<PacketInPool>,
+        pub priority: PriorityClass,
+    }
+
+    impl PriorityPacketInPoolSender {
+        pub fn new(queue: SharedPriorityPoolQueue<PacketInPool>, priority: PriorityClass) -> Self {
+            Self { queue, priority }
+        }
+    }
+
+    impl PacketInPoolSender for PriorityPacketInPoolSender {
+        fn send_packet(
+            &self,
+            sender_id: ComponentId,
+            store_addr: PoolAddr,
+        ) -> Result<(), GenericSendError> {
+            let mut queue = self
+                .queue
+                .lock()
+                .map_err(|_| GenericSendError::RxDisconnected)?;
+            let lane_capacity = queue.lane_capacity();
+            queue
+                .push(self.priority, PacketInPool::new(sender_id, store_addr))
+                .map_err(|e| match e {
+                    PriorityQueueError::InvalidPriority(_) => {
+                        GenericSendError::TargetDoesNotExist(self.priority as ComponentId)
+                    }
+                    PriorityQueueError::LaneFull(_) => {
+                        GenericSendError::QueueFull(Some(lane_capacity as u32))
+                    }
+                })
+        }
+    }
+
     /// This is the primary structure used to send packets stored in a dedicated memory pool
     /// structure.
     #[derive(Clone)]
@@ -381,6 +815,27 @@ pub mod std_mod {
                 shared_pool: RefCell::new(SharedPacketPool::new(shared_pool)),
             }
         }
+
+        /// Serialize a PUS TM directly into a free pool element with
+        /// [SharedPacketPool::add_pus_tm_in_place] and send its store address.
+        pub fn send_pus_tm_in_place<W: FnMut(&mut [u8])>(
+            &self,
+            sender_id: ComponentId,
+            sp_header: SpHeader,
+            sec_header: PusTmSecondaryHeader,
+            source_data_len: usize,
+            source_data_writer: W,
+        ) -> Result<(), EcssTmtcError> {
+            let store_addr = self.shared_pool.borrow_mut().add_pus_tm_in_place(
+                sp_header,
+                sec_header,
+                source_data_len,
+                source_data_writer,
+            )?;
+            self.sender
+                .send_packet(sender_id, store_addr)
+                .map_err(EcssTmtcError::Send)
+        }
     }
 
     impl<Sender: PacketInPoolSender, PacketStore: CcsdsPacketPool>
@@ -477,6 +932,19 @@ pub mod std_mod {
                 }
             }
         }
+
+        /// Adds all packets to the pool with [PusTmPool::add_pus_tm_batch] and sends the store
+        /// addresses with [PacketInPoolSender::send_packet_batch].
+        fn send_tm_batch(
+            &self,
+            sender_id: crate::ComponentId,
+            tms: &[PusTmCreator],
+        ) -> Result<(), crate::pus::EcssTmtcError> {
+            let store_addrs = self.shared_pool.borrow_mut().add_pus_tm_batch(tms)?;
+            self.sender
+                .send_packet_batch(sender_id, &store_addrs)
+                .map_err(EcssTmtcError::Send)
+        }
     }
 }
 
@@ -487,10 +955,12 @@ pub(crate) mod tests {
     use std::sync::RwLock;
 
     use crate::pool::{
-        PoolProviderWithGuards, SharedStaticMemoryPool, StaticMemoryPool, StaticPoolConfig,
+        PoolProvider, PoolProviderWithGuards, PriorityPoolQueue, SharedStaticMemoryPool,
+        StaticMemoryPool, StaticPoolConfig,
     };
 
     use super::*;
+    use spacepackets::ecss::tm::PusTmSecondaryHeader;
     use std::sync::mpsc;
 
     pub(crate) fn send_with_sender<SendError>(
@@ -570,6 +1040,34 @@ pub(crate) mod tests {
         assert_eq!(packet_in_pool.sender_id, 5)
     }
 
+    #[test]
+    fn test_shared_store_sender_tm_in_place() {
+        let (tm_tx, tm_rx) = mpsc::channel();
+        let pool_cfg = StaticPoolConfig::new_from_subpool_cfg_tuples(vec![(2, 32)], true);
+        let shared_pool = SharedStaticMemoryPool::new(RwLock::new(StaticMemoryPool::new(pool_cfg)));
+        let tm_sender =
+            PacketSenderWithSharedPool::new_with_shared_packet_pool(tm_tx, &shared_pool);
+        let sec_header = PusTmSecondaryHeader::new_simple(3, 25, &[0; 7]);
+        tm_sender
+            .send_pus_tm_in_place(
+                5,
+                SpHeader::new_for_unseg_tm(0x02, 0, 0),
+                sec_header,
+                4,
+                |src_data| src_data.copy_from_slice(&[1, 2, 3, 4]),
+            )
+            .unwrap();
+        let packet_in_pool: PacketInPool = tm_rx.try_recv().unwrap();
+        assert_eq!(packet_in_pool.sender_id, 5);
+        let raw_tm = shared_pool
+            .read()
+            .unwrap()
+            .read_as_vec(&packet_in_pool.store_addr)
+            .unwrap();
+        let (tm, _) = PusTmReader::new(&raw_tm, 7).unwrap();
+        assert_eq!(tm.source_data(), &[1, 2, 3, 4]);
+    }
+
     #[test]
     fn test_basic_shared_store_sender() {
         let (tc_tx, tc_rx) = mpsc::sync_channel(10);
@@ -650,4 +1148,143 @@ pub(crate) mod tests {
         assert_eq!(read_guard.read_as_vec().unwrap(), some_packet);
         assert_eq!(packet_in_pool.sender_id, 4);
     }
+
+    #[test]
+    fn test_priority_sender_with_shared_store() {
+        let pool_cfg = StaticPoolConfig::new_from_subpool_cfg_tuples(vec![(4, 8)], true);
+        let shared_pool = SharedPacketPool::new(&SharedStaticMemoryPool::new(RwLock::new(
+            StaticMemoryPool::new(pool_cfg),
+        )));
+        let queue = PriorityPoolQueue::new_shared(2, 1);
+        let low_prio_sender = PacketSenderWithSharedPool::new(
+            PriorityPacketInPoolSender::new(queue.clone(), 1),
+            shared_pool.clone(),
+        );
+        let high_prio_sender = PacketSenderWithSharedPool::new(
+            PriorityPacketInPoolSender::new(queue.clone(), 0),
+            shared_pool.clone(),
+        );
+        let low_prio_packet = vec![1, 2, 3];
+        let high_prio_packet = vec![4, 5, 6];
+        send_with_sender(1, &low_prio_sender, &low_prio_packet).expect("failed to send packet");
+        send_with_sender(2, &high_prio_sender, &high_prio_packet).expect("failed to send packet");
+        let result = send_with_sender(1, &low_prio_sender, &low_prio_packet);
+        assert!(matches!(
+            result.unwrap_err(),
+            StoreAndSendError::Send(GenericSendError::QueueFull(Some(1)))
+        ));
+        let mut queue = queue.lock().unwrap();
+        let mut pool = shared_pool.0.write().unwrap();
+        let (prio, packet_in_pool) = queue.pop().unwrap();
+        assert_eq!(prio, 0);
+        assert_eq!(packet_in_pool.sender_id, 2);
+        let read_guard = pool.read_with_guard(packet_in_pool.store_addr);
+        assert_eq!(read_guard.read_as_vec().unwrap(), high_prio_packet);
+        drop(read_guard);
+        let (prio, packet_in_pool) = queue.pop().unwrap();
+        assert_eq!(prio, 1);
+        assert_eq!(packet_in_pool.sender_id, 1);
+        assert!(queue.pop().is_none());
+    }
+
+    #[test]
+    fn test_apid_router_precedence() {
+        let (exact_tx, exact_rx) = mpsc::channel::<PacketAsVec>();
+        let (range_tx, range_rx) = mpsc::channel::<PacketAsVec>();
+        let (narrow_range_tx, narrow_range_rx) = mpsc::channel::<PacketAsVec>();
+        let (wildcard_tx, wildcard_rx) = mpsc::channel::<PacketAsVec>();
+        let mut router = CcsdsApidRouter::new();
+        router
+            .add_handler(ApidSelector::Wildcard, wildcard_tx)
+            .unwrap();
+        router
+            .add_handler(
+                ApidSelector::Range {
+                    start: 0x10,
+                    end: 0x30,
+                },
+                range_tx,
+            )
+            .unwrap();
+        router
+            .add_handler(
+                ApidSelector::Range {
+                    start: 0x18,
+                    end: 0x20,
+                },
+                narrow_range_tx,
+            )
+            .unwrap();
+        let exact_id = router
+            .add_handler(ApidSelector::Exact(0x19), exact_tx)
+            .unwrap();
+        assert_eq!(router.num_handlers(), 4);
+
+        let packet = [1, 2, 3];
+        for apid in [0x19, 0x1a, 0x11, 0x40] {
+            router
+                .send_ccsds(5, &SpHeader::new_from_apid(apid), &packet)
+                .expect("routing packet failed");
+        }
+        assert_eq!(exact_rx.try_recv().unwrap().sender_id, 5);
+        assert!(exact_rx.try_recv().is_err());
+        assert_eq!(narrow_range_rx.try_recv().unwrap().packet, packet);
+        assert!(narrow_range_rx.try_recv().is_err());
+        assert_eq!(range_rx.try_recv().unwrap().packet, packet);
+        assert!(range_rx.try_recv().is_err());
+        assert_eq!(wildcard_rx.try_recv().unwrap().packet, packet);
+        assert!(wildcard_rx.try_recv().is_err());
+
+        // After removing the exact handler, the narrow range is used.
+        assert_eq!(
+            router.remove_handler(exact_id).unwrap().0,
+            ApidSelector::Exact(0x19)
+        );
+        assert!(router.remove_handler(exact_id).is_none());
+        router
+            .send_ccsds(5, &SpHeader::new_from_apid(0x19), &packet)
+            .unwrap();
+        assert!(narrow_range_rx.try_recv().is_ok());
+        let selectors: alloc::vec::Vec<ApidSelector> = router.handlers().map(|(_, s)| *s).collect();
+        assert_eq!(selectors[0], ApidSelector::Wildcard);
+        assert_eq!(selectors.len(), 3);
+    }
+
+    #[test]
+    fn test_apid_router_config_errors() {
+        let (tx, _rx) = mpsc::channel::<PacketAsVec>();
+        let mut router = CcsdsApidRouter::new();
+        router
+            .add_handler(ApidSelector::Exact(2), tx.clone())
+            .unwrap();
+        router
+            .add_handler(ApidSelector::Wildcard, tx.clone())
+            .unwrap();
+        assert_eq!(
+            router.add_handler(ApidSelector::Exact(2), tx.clone()),
+            Err(ApidRouterConfigError::ApidAlreadyRegistered(2))
+        );
+        assert_eq!(
+            router.add_handler(ApidSelector::Wildcard, tx.clone()),
+            Err(ApidRouterConfigError::WildcardAlreadyRegistered)
+        );
+        assert_eq!(
+            router.add_handler(ApidSelector::Range { start: 5, end: 4 }, tx),
+            Err(ApidRouterConfigError::InvalidRange { start: 5, end: 4 })
+        );
+    }
+
+    #[test]
+    fn test_apid_router_no_handler() {
+        let (tx, _rx) = mpsc::channel::<PacketAsVec>();
+        let mut router = CcsdsApidRouter::new();
+        router
+            .add_handler(ApidSelector::Range { start: 1, end: 3 }, tx)
+            .unwrap();
+        assert!(router.handler_for_apid(2).is_some());
+        assert_eq!(
+            router.send_ccsds(0, &SpHeader::new_from_apid(4), &[1]),
+            Err(ApidRoutingError::NoHandlerForApid(4))
+        );
+    }
 }
@@ -0,0 +1,517 @@
+//! Raw packet recorder for ground testing.
+//!
+//! The [PacketRecorder] writes all telecommands and telemetry passing through the TMTC chain into
+//! capture files, so test campaigns can be replayed and analyzed offline. The [RecordingSender]
+//! wraps an existing [PacketSenderRaw] and records every packet before forwarding it. It can be
+//! used to wrap the TC source sender passed to the TC servers or the sinks of the
+//! [TmFunnel][super::tm_funnel::TmFunnel]. Recording errors are counted, but they do not affect
+//! the forwarding of the packets.
+//!
+//! The capture files start with the [CAPTURE_FILE_MAGIC] and the [CAPTURE_FILE_VERSION]. Each
+//! record consists of the following big endian header followed by the raw packet:
+//!
+//!  1. The [PacketDirection] as one byte.
+//!  2. The seconds of the UNIX reception timestamp as a signed 8 byte integer.
+//!  3. The sub-second nanoseconds of the reception timestamp as a 4 byte integer.
+//!  4. The [ComponentId] of the sender as an 8 byte integer.
+//!  5. The length of the packet as a 4 byte integer, which is at most [MAX_RECORDED_PACKET_LEN].
+//!
+//! A new capture file is started when the current file would exceed the configured maximum file
+//! size. The file names contain the configured prefix, the UNIX time of the file creation and a
+//! running index. The oldest capture files can be removed automatically. The [CaptureReader]
+//! can be used to read the recorded packets from a capture file, and the
+//! [TcReplaySource][super::replay::TcReplaySource] replays the recorded telecommands.
+//!
+//! # Example
+//!
+//! ```
+//! use std::fs::File;
+//! use std::sync::{mpsc, Arc};
+//! use satrs::tmtc::recorder::{
+//!     CaptureReader, PacketDirection, PacketRecorder, RecorderCfg, RecordingSender,
+//! };
+//! use satrs::tmtc::{PacketAsVec, PacketSenderRaw};
+//!
+//! let dir = std::env::temp_dir().join(format!("satrs-recorder-doc-{}", std::process::id()));
+//! let recorder = Arc::new(PacketRecorder::new(RecorderCfg::new(&dir)).unwrap());
+//! let (tc_tx, tc_rx) = mpsc::channel::<PacketAsVec>();
+//! let tc_tx = RecordingSender::new(tc_tx, recorder.clone(), PacketDirection::Tc);
+//! tc_tx.send_packet(1, &[0x18, 0x01, 0xc0, 0x00, 0x00, 0x00, 0x00]).unwrap();
+//! assert!(tc_rx.try_recv().is_ok());
+//!
+//! recorder.flush().unwrap();
+//! let capture_file = File::open(recorder.current_file().unwrap()).unwrap();
+//! let mut reader = CaptureReader::new(capture_file).unwrap();
+//! let captured = reader.read_record().unwrap().unwrap();
+//! assert_eq!(captured.direction, PacketDirection::Tc);
+//! assert_eq!(captured.sender_id, 1);
+//! std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+use core::sync::atomic::{AtomicU32, Ordering};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::sync::{Arc, Mutex};
+use std::vec;
+use std::vec::Vec;
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use spacepackets::time::UnixTime;
+use spacepackets::CCSDS_HEADER_LEN;
+
+use super::PacketSenderRaw;
+use crate::time::{SystemTimeSource, TimeSource};
+use crate::ComponentId;
+
+/// Magic bytes at the start of each capture file.
+pub const CAPTURE_FILE_MAGIC: [u8; 8] = *b"SATRSCAP";
+/// Version of the capture file format, which follows the [CAPTURE_FILE_MAGIC].
+pub const CAPTURE_FILE_VERSION: u8 = 1;
+/// Length of the capture file header.
+pub const CAPTURE_FILE_HEADER_LEN: usize = CAPTURE_FILE_MAGIC.len() + 1;
+/// Length of the header of each record.
+pub const RECORD_HEADER_LEN: usize = 1 + 8 + 4 + 8 + 4;
+/// Maximum length of a recorded packet, which is the maximum size of a space packet.
+pub const MAX_RECORDED_PACKET_LEN: usize = CCSDS_HEADER_LEN + u16::MAX as usize + 1;
+
+/// Direction of a recorded packet.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum PacketDirection {
+    Tc = 0,
+    Tm = 1,
+}
+
+/// Configuration of the [PacketRecorder].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecorderCfg {
+    /// Directory of the capture files, which is created if it does not exist.
+    pub directory: PathBuf,
+    pub file_prefix: String,
+    /// A new capture file is started if the current file would exceed this size.
+    pub max_file_size: u64,
+    /// Maximum number of capture files. The oldest capture files of the recorder are removed if
+    /// this number is exceeded. [None] keeps all capture files.
+    pub max_num_files: Option<usize>,
+}
+
+impl RecorderCfg {
+    /// Default maximum size of a capture file, which is 16 MiB.
+    pub const DEFAULT_MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+    /// Configuration with the file prefix `capture`, the [Self::DEFAULT_MAX_FILE_SIZE] and
+    /// without a limit for the number of capture files.
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            file_prefix: "capture".into(),
+            max_file_size: Self::DEFAULT_MAX_FILE_SIZE,
+            max_num_files: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct RecorderState {
+    writer: Option<BufWriter<File>>,
+    current_size: u64,
+    next_file_idx: u32,
+    files: VecDeque<PathBuf>,
+    num_records: u64,
+}
+
+/// Writes raw packets into rotating capture files. See the [module documentation][self] for
+/// the file format.
+///
+/// The recorder can be shared between threads, for example with an [Arc]. The records are
+/// buffered, so [Self::flush] should be called periodically and before the capture files are
+/// analyzed.
+pub struct PacketRecorder<Time: TimeSource = SystemTimeSource> {
+    cfg: RecorderCfg,
+    time_source: Time,
+    state: Mutex<RecorderState>,
+    num_record_errors: AtomicU32,
+}
+
+impl PacketRecorder<SystemTimeSource> {
+    /// Create a new recorder which timestamps the records with the system time.
+    pub fn new(cfg: RecorderCfg) -> io::Result<Self> {
+        Self::new_with_time_source(cfg, SystemTimeSource)
+    }
+}
+
+impl<Time: TimeSource> PacketRecorder<Time> {
+    /// Create a new recorder. The capture directory is created if it does not exist. The first
+    /// capture file is created with the first record.
+    pub fn new_with_time_source(cfg: RecorderCfg, time_source: Time) -> io::Result<Self> {
+        fs::create_dir_all(&cfg.directory)?;
+        Ok(Self {
+            cfg,
+            time_source,
+            state: Mutex::new(RecorderState::default()),
+            num_record_errors: AtomicU32::new(0),
+        })
+    }
+
+    pub fn cfg(&self) -> &RecorderCfg {
+        &self.cfg
+    }
+
+    /// Record a packet. Packets larger than [MAX_RECORDED_PACKET_LEN] are rejected.
+    pub fn record(
+        &self,
+        direction: PacketDirection,
+        sender_id: ComponentId,
+        packet: &[u8],
+    ) -> io::Result<()> {
+        if packet.len() > MAX_RECORDED_PACKET_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "packet too large",
+            ));
+        }
+        // Cannot fail because of the check above.
+        let packet_len = packet.len() as u32;
+        let time = self.time_source.now().unwrap_or(UnixTime::new_only_secs(0));
+        let mut state = self.lock_state();
+        let record_len = (RECORD_HEADER_LEN + packet.len()) as u64;
+        let file_full = state.current_size > CAPTURE_FILE_HEADER_LEN as u64
+            && state.current_size + record_len > self.cfg.max_file_size;
+        if state.writer.is_none() || file_full {
+            self.start_new_file(&mut state, &time)?;
+        }
+        let mut header = [0; RECORD_HEADER_LEN];
+        header[0] = direction.into();
+        header[1..9].copy_from_slice(&time.as_secs().to_be_bytes());
+        header[9..13].copy_from_slice(&time.subsec_nanos().to_be_bytes());
+        header[13..21].copy_from_slice(&sender_id.to_be_bytes());
+        header[21..25].copy_from_slice(&packet_len.to_be_bytes());
+        // The writer was created above.
+        let writer = state.writer.as_mut().unwrap();
+        writer.write_all(&header)?;
+        writer.write_all(packet)?;
+        state.current_size += record_len;
+        state.num_records += 1;
+        Ok(())
+    }
+
+    /// Write all buffered records to the current capture file.
+    pub fn flush(&self) -> io::Result<()> {
+        match self.lock_state().writer.as_mut() {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Path of the current capture file, or [None] if nothing was recorded yet.
+    pub fn current_file(&self) -> Option<PathBuf> {
+        self.lock_state().files.back().cloned()
+    }
+
+    /// Paths of all capture files of the recorder which were not removed, starting with the
+    /// oldest file.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.lock_state().files.iter().cloned().collect()
+    }
+
+    pub fn num_records(&self) -> u64 {
+        self.lock_state().num_records
+    }
+
+    /// Number of packets which could not be recorded by a [RecordingSender].
+    pub fn num_record_errors(&self) -> u32 {
+        self.num_record_errors.load(Ordering::Relaxed)
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, RecorderState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn start_new_file(&self, state: &mut RecorderState, time: &UnixTime) -> io::Result<()> {
+        if let Some(mut writer) = state.writer.take() {
+            writer.flush()?;
+        }
+        let path = self.cfg.directory.join(std::format!(
+            "{}_{}_{:04}.cap",
+            self.cfg.file_prefix,
+            time.as_secs(),
+            state.next_file_idx
+        ));
+        state.next_file_idx = state.next_file_idx.wrapping_add(1);
+        let mut writer = BufWriter::new(File::create(&path)?);
+        writer.write_all(&CAPTURE_FILE_MAGIC)?;
+        writer.write_all(&[CAPTURE_FILE_VERSION])?;
+        state.writer = Some(writer);
+        state.current_size = CAPTURE_FILE_HEADER_LEN as u64;
+        state.files.push_back(path);
+        if let Some(max_num_files) = self.cfg.max_num_files {
+            while state.files.len() > max_num_files.max(1) {
+                let oldest = state.files.pop_front().unwrap();
+                match fs::remove_file(oldest) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                    _ => (),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Wrapper around a [PacketSenderRaw] which records every packet with a [PacketRecorder] before
+/// forwarding it.
+pub struct RecordingSender<Sender: PacketSenderRaw, Time: TimeSource = SystemTimeSource> {
+    pub sender: Sender,
+    pub recorder: Arc<PacketRecorder<Time>>,
+    pub direction: PacketDirection,
+}
+
+impl<Sender: PacketSenderRaw, Time: TimeSource> RecordingSender<Sender, Time> {
+    pub fn new(
+        sender: Sender,
+        recorder: Arc<PacketRecorder<Time>>,
+        direction: PacketDirection,
+    ) -> Self {
+        Self {
+            sender,
+            recorder,
+            direction,
+        }
+    }
+}
+
+impl<Sender: PacketSenderRaw + Clone, Time: TimeSource> Clone for RecordingSender<Sender, Time> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            recorder: self.recorder.clone(),
+            direction: self.direction,
+        }
+    }
+}
+
+impl<Sender: PacketSenderRaw, Time: TimeSource + Send + Sync> PacketSenderRaw
+    for RecordingSender<Sender, Time>
+{
+    type Error = Sender::Error;
+
+    fn send_packet(&self, sender_id: ComponentId, packet: &[u8]) -> Result<(), Self::Error> {
+        if self
+            .recorder
+            .record(self.direction, sender_id, packet)
+            .is_err()
+        {
+            self.recorder
+                .num_record_errors
+                .fetch_add(1, Ordering::Relaxed);
+        }
+        self.sender.send_packet(sender_id, packet)
+    }
+}
+
+/// Packet read from a capture file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedPacket {
+    pub direction: PacketDirection,
+    /// Time at which the packet was recorded.
+    pub time: UnixTime,
+    pub sender_id: ComponentId,
+    pub packet: Vec<u8>,
+}
+
+/// Reads the records of a capture file written by the [PacketRecorder].
+pub struct CaptureReader<Reader: Read> {
+    reader: Reader,
+}
+
+impl<Reader: Read> CaptureReader<Reader> {
+    /// Create a new reader. The file header is read and validated.
+    pub fn new(mut reader: Reader) -> io::Result<Self> {
+        let mut header = [0; CAPTURE_FILE_HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        if header[0..CAPTURE_FILE_MAGIC.len()] != CAPTURE_FILE_MAGIC
+            || header[CAPTURE_FILE_MAGIC.len()] != CAPTURE_FILE_VERSION
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid capture file header",
+            ));
+        }
+        Ok(Self { reader })
+    }
+
+    /// Read the next record. Returns [None] at the end of the file. Records with a packet
+    /// length above [MAX_RECORDED_PACKET_LEN] are rejected as invalid data.
+    pub fn read_record(&mut self) -> io::Result<Option<CapturedPacket>> {
+        let mut header = [0; RECORD_HEADER_LEN];
+        let mut read_len = 0;
+        while read_len < RECORD_HEADER_LEN {
+            match self.reader.read(&mut header[read_len..]) {
+                Ok(0) if read_len == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(len) => read_len += len,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        let direction = PacketDirection::try_from(header[0])
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid packet direction"))?;
+        let secs = i64::from_be_bytes(header[1..9].try_into().unwrap());
+        let subsec_nanos = u32::from_be_bytes(header[9..13].try_into().unwrap());
+        let sender_id = ComponentId::from_be_bytes(header[13..21].try_into().unwrap());
+        let packet_len = u32::from_be_bytes(header[21..25].try_into().unwrap()) as usize;
+        if packet_len > MAX_RECORDED_PACKET_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid packet length",
+            ));
+        }
+        let mut packet = vec![0; packet_len];
+        self.reader.read_exact(&mut packet)?;
+        Ok(Some(CapturedPacket {
+            direction,
+            time: UnixTime::new(secs, subsec_nanos),
+            sender_id,
+            packet,
+        }))
+    }
+}
+
+impl<Reader: Read> Iterator for CaptureReader<Reader> {
+    type Item = io::Result<CapturedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::time::FixedTimeSource;
+    use crate::tmtc::PacketAsVec;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("satrs-recorder-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn read_all(path: &Path) -> Vec<CapturedPacket> {
+        CaptureReader::new(File::open(path).unwrap())
+            .unwrap()
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_recording_senders() {
+        let dir = test_dir("senders");
+        let time = UnixTime::new(1_700_000_000, 250_000_000);
+        let recorder = Arc::new(
+            PacketRecorder::new_with_time_source(
+                RecorderCfg::new(&dir),
+                FixedTimeSource::new(time),
+            )
+            .unwrap(),
+        );
+        let (tc_tx, tc_rx) = mpsc::channel::<PacketAsVec>();
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let tc_tx = RecordingSender::new(tc_tx, recorder.clone(), PacketDirection::Tc);
+        let tm_tx = RecordingSender::new(tm_tx, recorder.clone(), PacketDirection::Tm);
+        tc_tx.send_packet(1, &[1, 2, 3]).unwrap();
+        tm_tx.send_packet(2, &[4, 5]).unwrap();
+        assert_eq!(tc_rx.try_recv().unwrap().packet, [1, 2, 3]);
+        assert_eq!(tm_rx.try_recv().unwrap().packet, [4, 5]);
+        assert_eq!(recorder.num_records(), 2);
+        assert_eq!(recorder.num_record_errors(), 0);
+        recorder.flush().unwrap();
+
+        let current_file = recorder.current_file().unwrap();
+        assert_eq!(
+            current_file.file_name().unwrap(),
+            "capture_1700000000_0000.cap"
+        );
+        let records = read_all(&current_file);
+        assert_eq!(
+            records,
+            [
+                CapturedPacket {
+                    direction: PacketDirection::Tc,
+                    time,
+                    sender_id: 1,
+                    packet: vec![1, 2, 3],
+                },
+                CapturedPacket {
+                    direction: PacketDirection::Tm,
+                    time,
+                    sender_id: 2,
+                    packet: vec![4, 5],
+                }
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = test_dir("rotation");
+        let mut cfg = RecorderCfg::new(&dir);
+        // Each file can hold two records with a packet length of 10.
+        cfg.max_file_size = (CAPTURE_FILE_HEADER_LEN + 2 * (RECORD_HEADER_LEN + 10)) as u64;
+        cfg.max_num_files = Some(2);
+        let recorder = PacketRecorder::new(cfg).unwrap();
+        for idx in 0..6 {
+            recorder.record(PacketDirection::Tm, idx, &[0; 10]).unwrap();
+        }
+        recorder.flush().unwrap();
+        let files = recorder.files();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].to_str().unwrap().ends_with("_0001.cap"));
+        assert!(files[1].to_str().unwrap().ends_with("_0002.cap"));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        let sender_ids: Vec<_> = read_all(&files[1])
+            .iter()
+            .map(|record| record.sender_id)
+            .collect();
+        assert_eq!(sender_ids, [4, 5]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_capture_file() {
+        assert!(CaptureReader::new(&b"SATRSCAP\x02"[..]).is_err());
+        let mut truncated = Vec::from(CAPTURE_FILE_MAGIC);
+        truncated.push(CAPTURE_FILE_VERSION);
+        truncated.extend_from_slice(&[1, 0, 0]);
+        let mut reader = CaptureReader::new(truncated.as_slice()).unwrap();
+        assert!(reader.read_record().is_err());
+    }
+
+    #[test]
+    fn test_oversized_packet() {
+        let mut capture = Vec::from(CAPTURE_FILE_MAGIC);
+        capture.push(CAPTURE_FILE_VERSION);
+        let mut header = [0; RECORD_HEADER_LEN];
+        header[21..25].copy_from_slice(&u32::MAX.to_be_bytes());
+        capture.extend_from_slice(&header);
+        let mut reader = CaptureReader::new(capture.as_slice()).unwrap();
+        let error = reader.read_record().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let dir = test_dir("oversized");
+        let recorder = PacketRecorder::new(RecorderCfg::new(&dir)).unwrap();
+        let packet = vec![0; MAX_RECORDED_PACKET_LEN + 1];
+        let error = recorder
+            .record(PacketDirection::Tm, 1, &packet)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(recorder.num_records(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
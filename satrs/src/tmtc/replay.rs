@@ -0,0 +1,385 @@
+//! Telecommand replay from capture files.
+//!
+//! The [TcReplaySource] reads the telecommands of a capture file written by the
+//! [PacketRecorder][super::recorder::PacketRecorder] and injects them into a TC source, which is
+//! any [PacketSenderRaw] like the TC sender passed to the TC servers. This allows regression
+//! testing of the full TMTC pipeline without a ground system in the loop. Recorded telemetry is
+//! skipped.
+//!
+//! The telecommands are replayed relative to the reception time of the first recorded
+//! telecommand with the configured [ReplayTiming]. The replay can either be driven by a periodic
+//! task with [TcReplaySource::poll], or it can be run to completion in the current thread with
+//! [TcReplaySource::run].
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::mpsc;
+//! use std::time::Duration;
+//! use satrs::tmtc::recorder::{PacketDirection, PacketRecorder, RecorderCfg};
+//! use satrs::tmtc::replay::{ReplayTiming, TcReplaySource};
+//! use satrs::tmtc::PacketAsVec;
+//!
+//! let dir = std::env::temp_dir().join(format!("satrs-replay-doc-{}", std::process::id()));
+//! let recorder = PacketRecorder::new(RecorderCfg::new(&dir)).unwrap();
+//! recorder.record(PacketDirection::Tc, 1, &[1, 2, 3]).unwrap();
+//! recorder.record(PacketDirection::Tm, 2, &[4, 5, 6]).unwrap();
+//! recorder.flush().unwrap();
+//!
+//! let (tc_tx, tc_rx) = mpsc::channel::<PacketAsVec>();
+//! let mut replay =
+//!     TcReplaySource::open(recorder.current_file().unwrap(), ReplayTiming::AsFastAsPossible)
+//!         .unwrap();
+//! assert_eq!(replay.run(&tc_tx).unwrap(), 1);
+//! assert_eq!(tc_rx.try_recv().unwrap().packet, [1, 2, 3]);
+//! std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+use core::fmt::{Display, Formatter};
+use core::time::Duration;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::thread;
+use std::time::Instant;
+
+use spacepackets::time::UnixTime;
+
+use super::recorder::{CaptureReader, CapturedPacket, PacketDirection};
+use super::PacketSenderRaw;
+use crate::time::InvalidRateError;
+use crate::ComponentId;
+
+/// Timing of the replayed telecommands.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReplayTiming {
+    /// Replay the telecommands with the recorded time differences.
+    Original,
+    /// Replay the telecommands with the recorded time differences divided by the given factor.
+    /// The factor must be finite and larger than 0.
+    Accelerated(f64),
+    /// Replay all telecommands without any delay.
+    AsFastAsPossible,
+}
+
+#[derive(Debug)]
+pub enum ReplayError<SendError> {
+    Io(io::Error),
+    Send(SendError),
+}
+
+impl<SendError: Display> Display for ReplayError<SendError> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "reading capture file failed: {e}"),
+            ReplayError::Send(e) => write!(f, "sending replayed TC failed: {e}"),
+        }
+    }
+}
+
+impl<SendError> From<io::Error> for ReplayError<SendError> {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl<SendError: Error + 'static> Error for ReplayError<SendError> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::Io(e) => Some(e),
+            ReplayError::Send(e) => Some(e),
+        }
+    }
+}
+
+impl ReplayTiming {
+    /// Check that the factor of [Self::Accelerated] is finite and larger than 0.
+    pub fn validate(&self) -> Result<(), InvalidRateError> {
+        match *self {
+            ReplayTiming::Accelerated(factor) if !factor.is_finite() || factor <= 0.0 => {
+                Err(InvalidRateError(factor))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Injects the telecommands of a capture file into a TC source. See the
+/// [module documentation][self] for more details.
+pub struct TcReplaySource<Reader: Read> {
+    reader: CaptureReader<Reader>,
+    timing: ReplayTiming,
+    sender_id: Option<ComponentId>,
+    next_tc: Option<CapturedPacket>,
+    first_tc_time: Option<UnixTime>,
+    finished: bool,
+    num_replayed: usize,
+}
+
+impl TcReplaySource<BufReader<File>> {
+    /// Open the capture file with the given path. Returns an error with the
+    /// [io::ErrorKind::InvalidInput] kind if the timing is invalid.
+    pub fn open(path: impl AsRef<Path>, timing: ReplayTiming) -> io::Result<Self> {
+        Self::new(
+            CaptureReader::new(BufReader::new(File::open(path)?))?,
+            timing,
+        )
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+}
+
+impl<Reader: Read> TcReplaySource<Reader> {
+    /// Create a new replay source. Returns an error if the timing is invalid, see
+    /// [ReplayTiming::validate].
+    pub fn new(
+        reader: CaptureReader<Reader>,
+        timing: ReplayTiming,
+    ) -> Result<Self, InvalidRateError> {
+        timing.validate()?;
+        Ok(Self {
+            reader,
+            timing,
+            sender_id: None,
+            next_tc: None,
+            first_tc_time: None,
+            finished: false,
+            num_replayed: 0,
+        })
+    }
+
+    /// Replay all telecommands with the given sender ID instead of the recorded sender IDs.
+    pub fn with_sender_id(mut self, sender_id: ComponentId) -> Self {
+        self.sender_id = Some(sender_id);
+        self
+    }
+
+    pub fn timing(&self) -> ReplayTiming {
+        self.timing
+    }
+
+    /// Returns whether all telecommands of the capture file were replayed.
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn num_replayed(&self) -> usize {
+        self.num_replayed
+    }
+
+    /// Replay all telecommands which are due after the given time since the start of the replay.
+    /// Returns the number of replayed telecommands.
+    pub fn poll<Sender: PacketSenderRaw>(
+        &mut self,
+        sender: &Sender,
+        elapsed: Duration,
+    ) -> Result<usize, ReplayError<Sender::Error>> {
+        let mut num_replayed = 0;
+        while let Some(due) = self.next_due()? {
+            if due > elapsed {
+                break;
+            }
+            self.send_next(sender)?;
+            num_replayed += 1;
+        }
+        Ok(num_replayed)
+    }
+
+    /// Replay all remaining telecommands and block the current thread between them according to
+    /// the [ReplayTiming]. The time since the start of the replay is measured from the call of
+    /// this function. Returns the number of replayed telecommands.
+    pub fn run<Sender: PacketSenderRaw>(
+        &mut self,
+        sender: &Sender,
+    ) -> Result<usize, ReplayError<Sender::Error>> {
+        let start = Instant::now();
+        let mut num_replayed = 0;
+        while let Some(due) = self.next_due()? {
+            let elapsed = start.elapsed();
+            if due > elapsed {
+                thread::sleep(due - elapsed);
+            }
+            self.send_next(sender)?;
+            num_replayed += 1;
+        }
+        Ok(num_replayed)
+    }
+
+    /// Time since the start of the replay at which the next telecommand is due, or [None] if all
+    /// telecommands were replayed.
+    pub fn next_due(&mut self) -> io::Result<Option<Duration>> {
+        if self.next_tc.is_none() && !self.finished {
+            self.next_tc = self.read_next_tc()?;
+            self.finished = self.next_tc.is_none();
+        }
+        let tc = match &self.next_tc {
+            Some(tc) => tc,
+            None => return Ok(None),
+        };
+        let first_tc_time = *self.first_tc_time.get_or_insert(tc.time);
+        Ok(Some(self.scaled_offset(&first_tc_time, &tc.time)))
+    }
+
+    fn read_next_tc(&mut self) -> io::Result<Option<CapturedPacket>> {
+        while let Some(packet) = self.reader.read_record()? {
+            if packet.direction == PacketDirection::Tc {
+                return Ok(Some(packet));
+            }
+        }
+        Ok(None)
+    }
+
+    fn send_next<Sender: PacketSenderRaw>(
+        &mut self,
+        sender: &Sender,
+    ) -> Result<(), ReplayError<Sender::Error>> {
+        if let Some(tc) = self.next_tc.take() {
+            sender
+                .send_packet(self.sender_id.unwrap_or(tc.sender_id), &tc.packet)
+                .map_err(ReplayError::Send)?;
+            self.num_replayed += 1;
+        }
+        Ok(())
+    }
+
+    fn scaled_offset(&self, first_tc_time: &UnixTime, time: &UnixTime) -> Duration {
+        const NANOS_PER_SEC: i128 = 1_000_000_000;
+        let to_nanos =
+            |time: &UnixTime| time.as_secs() as i128 * NANOS_PER_SEC + time.subsec_nanos() as i128;
+        // Telecommands which were recorded out of order are replayed immediately.
+        let offset_nanos = (to_nanos(time) - to_nanos(first_tc_time)).max(0);
+        match self.timing {
+            ReplayTiming::Original => {
+                Duration::from_nanos(u64::try_from(offset_nanos).unwrap_or(u64::MAX))
+            }
+            // Very small factors can exceed the range of a duration.
+            ReplayTiming::Accelerated(factor) => {
+                Duration::try_from_secs_f64(offset_nanos as f64 / NANOS_PER_SEC as f64 / factor)
+                    .unwrap_or(Duration::MAX)
+            }
+            ReplayTiming::AsFastAsPossible => Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::tmtc::recorder::{CAPTURE_FILE_MAGIC, CAPTURE_FILE_VERSION};
+    use crate::tmtc::PacketAsVec;
+
+    fn capture_file(records: &[(PacketDirection, UnixTime, ComponentId, &[u8])]) -> Vec<u8> {
+        let mut file = Vec::from(CAPTURE_FILE_MAGIC);
+        file.push(CAPTURE_FILE_VERSION);
+        for (direction, time, sender_id, packet) in records {
+            file.push((*direction).into());
+            file.extend_from_slice(&time.as_secs().to_be_bytes());
+            file.extend_from_slice(&time.subsec_nanos().to_be_bytes());
+            file.extend_from_slice(&sender_id.to_be_bytes());
+            file.extend_from_slice(&(packet.len() as u32).to_be_bytes());
+            file.extend_from_slice(packet);
+        }
+        file
+    }
+
+    fn test_capture() -> Vec<u8> {
+        capture_file(&[
+            (PacketDirection::Tm, UnixTime::new(99, 0), 5, &[0]),
+            (PacketDirection::Tc, UnixTime::new(100, 0), 1, &[1]),
+            (
+                PacketDirection::Tm,
+                UnixTime::new(100, 500_000_000),
+                5,
+                &[2],
+            ),
+            (PacketDirection::Tc, UnixTime::new(101, 0), 2, &[3]),
+            (PacketDirection::Tc, UnixTime::new(104, 0), 1, &[4]),
+        ])
+    }
+
+    #[test]
+    fn test_poll_original_timing() {
+        let capture = test_capture();
+        let mut replay = TcReplaySource::new(
+            CaptureReader::new(capture.as_slice()).unwrap(),
+            ReplayTiming::Original,
+        )
+        .unwrap();
+        let (tc_tx, tc_rx) = mpsc::channel::<PacketAsVec>();
+        assert_eq!(replay.poll(&tc_tx, Duration::ZERO).unwrap(), 1);
+        assert_eq!(replay.next_due().unwrap(), Some(Duration::from_secs(1)));
+        assert_eq!(replay.poll(&tc_tx, Duration::from_millis(999)).unwrap(), 0);
+        assert_eq!(replay.poll(&tc_tx, Duration::from_secs(3)).unwrap(), 1);
+        assert!(!replay.finished());
+        assert_eq!(replay.poll(&tc_tx, Duration::from_secs(4)).unwrap(), 1);
+        assert_eq!(replay.next_due().unwrap(), None);
+        assert!(replay.finished());
+        assert_eq!(replay.num_replayed(), 3);
+        let replayed: Vec<_> = tc_rx
+            .try_iter()
+            .map(|tc| (tc.sender_id, tc.packet))
+            .collect();
+        assert_eq!(
+            replayed,
+            [
+                (1, Vec::from([1])),
+                (2, Vec::from([3])),
+                (1, Vec::from([4]))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_accelerated_timing_and_sender_id() {
+        let capture = test_capture();
+        let mut replay = TcReplaySource::new(
+            CaptureReader::new(capture.as_slice()).unwrap(),
+            ReplayTiming::Accelerated(8.0),
+        )
+        .unwrap()
+        .with_sender_id(7);
+        let (tc_tx, tc_rx) = mpsc::channel::<PacketAsVec>();
+        assert_eq!(replay.poll(&tc_tx, Duration::from_millis(125)).unwrap(), 2);
+        assert_eq!(replay.next_due().unwrap(), Some(Duration::from_millis(500)));
+        let start = Instant::now();
+        assert_eq!(replay.run(&tc_tx).unwrap(), 1);
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert!(tc_rx.try_iter().all(|tc| tc.sender_id == 7));
+    }
+
+    #[test]
+    fn test_invalid_and_tiny_acceleration_factors() {
+        let capture = test_capture();
+        for factor in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(TcReplaySource::new(
+                CaptureReader::new(capture.as_slice()).unwrap(),
+                ReplayTiming::Accelerated(factor),
+            )
+            .is_err());
+        }
+        let mut replay = TcReplaySource::new(
+            CaptureReader::new(capture.as_slice()).unwrap(),
+            ReplayTiming::Accelerated(f64::MIN_POSITIVE),
+        )
+        .unwrap();
+        let (tc_tx, _tc_rx) = mpsc::channel::<PacketAsVec>();
+        assert_eq!(replay.poll(&tc_tx, Duration::ZERO).unwrap(), 1);
+        assert_eq!(replay.next_due().unwrap(), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn test_send_error() {
+        let capture = test_capture();
+        let mut replay = TcReplaySource::new(
+            CaptureReader::new(capture.as_slice()).unwrap(),
+            ReplayTiming::AsFastAsPossible,
+        )
+        .unwrap();
+        let (tc_tx, tc_rx) = mpsc::channel::<PacketAsVec>();
+        drop(tc_rx);
+        assert!(matches!(replay.run(&tc_tx), Err(ReplayError::Send(_))));
+        assert_eq!(replay.num_replayed(), 0);
+    }
+}
@@ -0,0 +1,399 @@
+//! TMTC statistics support.
+//!
+//! The [TmtcStatistics] structure contains lock-free counters for the whole TMTC chain: received
+//! telecommands and their size, rejected telecommands with their [TcRejectionReason], generated
+//! telemetry per PUS service and telemetry which was dropped because of full queues. It is
+//! intended to be shared between all components of the TMTC chain, for example with an
+//! [alloc::sync::Arc].
+//!
+//! Some counters can be updated automatically by wrapping existing senders: The [StatsTcSender]
+//! counts all telecommands passed to a TC source, and the [StatsTmSender] counts all generated
+//! and dropped telemetry. A [snapshot][TmtcStatisticsSnapshot] of the counters can be serialized
+//! as a diagnostic HK set, and the [create_stats_hk_set] function creates a
+//! [crate::hk::HkSetDefinition] for periodic HK reports with the [crate::hk::HkSetRegistry].
+use core::sync::atomic::{AtomicU32, Ordering};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use spacepackets::ByteConversionError;
+
+#[cfg(feature = "alloc")]
+pub use alloc_mod::*;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// Reason for the rejection of a telecommand inside the TMTC chain.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum TcRejectionReason {
+    /// The packet could not be parsed.
+    InvalidFormat = 0,
+    /// The packet has an unknown or invalid APID.
+    InvalidApid = 1,
+    /// The telecommand was rejected during authentication.
+    AuthenticationFailed = 2,
+    /// The telecommand could not be forwarded, for example because of a full queue.
+    ForwardingFailed = 3,
+    Other = 4,
+}
+
+pub const NUM_TC_REJECTION_REASONS: usize = 5;
+
+/// Lock-free counters of the TMTC chain. All counters wrap around on overflow.
+#[derive(Debug)]
+pub struct TmtcStatistics {
+    tc_packets_received: AtomicU32,
+    tc_bytes_received: AtomicU32,
+    tc_rejected: [AtomicU32; NUM_TC_REJECTION_REASONS],
+    tm_generated: AtomicU32,
+    tm_generated_per_service: [AtomicU32; 256],
+    tm_dropped: AtomicU32,
+}
+
+impl Default for TmtcStatistics {
+    fn default() -> Self {
+        Self {
+            tc_packets_received: AtomicU32::new(0),
+            tc_bytes_received: AtomicU32::new(0),
+            tc_rejected: core::array::from_fn(|_| AtomicU32::new(0)),
+            tm_generated: AtomicU32::new(0),
+            tm_generated_per_service: core::array::from_fn(|_| AtomicU32::new(0)),
+            tm_dropped: AtomicU32::new(0),
+        }
+    }
+}
+
+impl TmtcStatistics {
+    /// Count a received telecommand with the given length.
+    pub fn tc_received(&self, len: usize) {
+        self.tc_packets_received.fetch_add(1, Ordering::Relaxed);
+        self.tc_bytes_received
+            .fetch_add(len as u32, Ordering::Relaxed);
+    }
+
+    pub fn tc_rejected(&self, reason: TcRejectionReason) {
+        self.tc_rejected[reason as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a generated telemetry packet. The PUS service might not be known, for example for
+    /// telemetry which is sent as a pool address. Only the total count is incremented in that
+    /// case.
+    pub fn tm_generated(&self, service: Option<u8>) {
+        self.tm_generated.fetch_add(1, Ordering::Relaxed);
+        if let Some(service) = service {
+            self.tm_generated_per_service[service as usize].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn tm_dropped(&self) {
+        self.tm_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> TmtcStatisticsSnapshot {
+        TmtcStatisticsSnapshot {
+            tc_packets_received: self.tc_packets_received.load(Ordering::Relaxed),
+            tc_bytes_received: self.tc_bytes_received.load(Ordering::Relaxed),
+            tc_rejected: core::array::from_fn(|idx| self.tc_rejected[idx].load(Ordering::Relaxed)),
+            tm_generated: self.tm_generated.load(Ordering::Relaxed),
+            tm_generated_per_service: core::array::from_fn(|idx| {
+                self.tm_generated_per_service[idx].load(Ordering::Relaxed)
+            }),
+            tm_dropped: self.tm_dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reset all counters to 0.
+    pub fn reset(&self) {
+        self.tc_packets_received.store(0, Ordering::Relaxed);
+        self.tc_bytes_received.store(0, Ordering::Relaxed);
+        self.tc_rejected
+            .iter()
+            .for_each(|counter| counter.store(0, Ordering::Relaxed));
+        self.tm_generated.store(0, Ordering::Relaxed);
+        self.tm_generated_per_service
+            .iter()
+            .for_each(|counter| counter.store(0, Ordering::Relaxed));
+        self.tm_dropped.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Copy of all [TmtcStatistics] counters at one point in time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TmtcStatisticsSnapshot {
+    pub tc_packets_received: u32,
+    pub tc_bytes_received: u32,
+    pub tc_rejected: [u32; NUM_TC_REJECTION_REASONS],
+    pub tm_generated: u32,
+    pub tm_generated_per_service: [u32; 256],
+    pub tm_dropped: u32,
+}
+
+impl TmtcStatisticsSnapshot {
+    /// Length of the fixed part of the serialized snapshot.
+    pub const FIXED_LEN: usize = 4 * (4 + NUM_TC_REJECTION_REASONS) + 1;
+
+    pub fn tc_rejected(&self, reason: TcRejectionReason) -> u32 {
+        self.tc_rejected[reason as usize]
+    }
+
+    /// Iterator over all PUS services with their generated TM count, which generated at least
+    /// one telemetry packet.
+    pub fn active_services(&self) -> impl Iterator<Item = (u8, u32)> + '_ {
+        self.tm_generated_per_service
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(service, count)| (service as u8, *count))
+    }
+
+    pub fn len_written(&self) -> usize {
+        Self::FIXED_LEN + self.active_services().count() * 5
+    }
+
+    /// Serialize the snapshot. All counters are written as big endian [u32]s in the following
+    /// order: Received TC packets, received TC bytes, the rejected TC counts in the order of the
+    /// [TcRejectionReason] values, generated TM and dropped TM. This is followed by the number
+    /// of PUS services which generated telemetry as a [u8] and the service number and TM count
+    /// of each of these services.
+    pub fn write_to_be_bytes(&self, buf: &mut [u8]) -> Result<usize, ByteConversionError> {
+        let len = self.len_written();
+        if buf.len() < len {
+            return Err(ByteConversionError::ToSliceTooSmall {
+                found: buf.len(),
+                expected: len,
+            });
+        }
+        let mut current_idx = 0;
+        let mut write_u32 = |buf: &mut [u8], value: u32| {
+            buf[current_idx..current_idx + 4].copy_from_slice(&value.to_be_bytes());
+            current_idx += 4;
+        };
+        write_u32(buf, self.tc_packets_received);
+        write_u32(buf, self.tc_bytes_received);
+        for rejected in self.tc_rejected {
+            write_u32(buf, rejected);
+        }
+        write_u32(buf, self.tm_generated);
+        write_u32(buf, self.tm_dropped);
+        buf[current_idx] = self.active_services().count() as u8;
+        current_idx += 1;
+        for (service, count) in self.active_services() {
+            buf[current_idx] = service;
+            buf[current_idx + 1..current_idx + 5].copy_from_slice(&count.to_be_bytes());
+            current_idx += 5;
+        }
+        Ok(current_idx)
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc_mod {
+    use alloc::sync::Arc;
+    use spacepackets::ecss::PusPacket;
+
+    use super::*;
+    use crate::pus::{EcssTmSender, EcssTmtcError, PusTmVariant};
+    use crate::queue::GenericSendError;
+    use crate::tmtc::PacketSenderRaw;
+    use crate::ComponentId;
+
+    /// Wrapper around a [EcssTmSender] which counts all generated telemetry and all telemetry
+    /// which was dropped because the queue was full.
+    #[derive(Debug, Clone)]
+    pub struct StatsTmSender<Sender: EcssTmSender> {
+        pub sender: Sender,
+        pub stats: Arc<TmtcStatistics>,
+    }
+
+    impl<Sender: EcssTmSender> StatsTmSender<Sender> {
+        pub fn new(sender: Sender, stats: Arc<TmtcStatistics>) -> Self {
+            Self { sender, stats }
+        }
+    }
+
+    impl<Sender: EcssTmSender> EcssTmSender for StatsTmSender<Sender> {
+        fn send_tm(&self, sender_id: ComponentId, tm: PusTmVariant) -> Result<(), EcssTmtcError> {
+            let service = match &tm {
+                PusTmVariant::InStore(_) => None,
+                PusTmVariant::Direct(tm) => Some(tm.service()),
+            };
+            let result = self.sender.send_tm(sender_id, tm);
+            match &result {
+                Ok(()) => self.stats.tm_generated(service),
+                Err(EcssTmtcError::Send(GenericSendError::QueueFull(_))) => self.stats.tm_dropped(),
+                Err(_) => (),
+            }
+            result
+        }
+    }
+
+    /// Wrapper around a [PacketSenderRaw] which counts all telecommands passed to a TC source.
+    /// Telecommands which could not be forwarded are counted as rejected with the
+    /// [TcRejectionReason::ForwardingFailed] reason.
+    #[derive(Debug, Clone)]
+    pub struct StatsTcSender<Sender: PacketSenderRaw> {
+        pub sender: Sender,
+        pub stats: Arc<TmtcStatistics>,
+    }
+
+    impl<Sender: PacketSenderRaw> StatsTcSender<Sender> {
+        pub fn new(sender: Sender, stats: Arc<TmtcStatistics>) -> Self {
+            Self { sender, stats }
+        }
+    }
+
+    impl<Sender: PacketSenderRaw> PacketSenderRaw for StatsTcSender<Sender> {
+        type Error = Sender::Error;
+
+        fn send_packet(&self, sender_id: ComponentId, packet: &[u8]) -> Result<(), Self::Error> {
+            self.stats.tc_received(packet.len());
+            let result = self.sender.send_packet(sender_id, packet);
+            if result.is_err() {
+                self.stats.tc_rejected(TcRejectionReason::ForwardingFailed);
+            }
+            result
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::hk::{HkSetDefinition, UniqueId};
+
+    /// Create a HK set which contains the serialized [TmtcStatisticsSnapshot] of the given
+    /// statistics.
+    pub fn create_stats_hk_set(
+        stats: Arc<TmtcStatistics>,
+        set_id: UniqueId,
+        collection_interval: Duration,
+    ) -> HkSetDefinition {
+        HkSetDefinition::new(set_id, collection_interval)
+            .with_provider(move |buf| stats.snapshot().write_to_be_bytes(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{mpsc, Arc};
+    use std::time::Duration;
+    use std::vec;
+
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::SpHeader;
+
+    use super::*;
+    use crate::hk::{HkRequest, HkRequestVariant, HkSetRegistry};
+    use crate::pus::{EcssTmSender, EcssTmtcError, PusTmVariant};
+    use crate::queue::GenericSendError;
+    use crate::request::UniqueApidTargetId;
+    use crate::tmtc::{PacketAsVec, PacketSenderRaw};
+    use crate::ComponentId;
+
+    #[test]
+    fn test_counters_and_snapshot() {
+        let stats = TmtcStatistics::default();
+        stats.tc_received(12);
+        stats.tc_received(20);
+        stats.tc_rejected(TcRejectionReason::AuthenticationFailed);
+        stats.tm_generated(Some(17));
+        stats.tm_generated(Some(17));
+        stats.tm_generated(Some(1));
+        stats.tm_generated(None);
+        stats.tm_dropped();
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.tc_packets_received, 2);
+        assert_eq!(snapshot.tc_bytes_received, 32);
+        assert_eq!(
+            snapshot.tc_rejected(TcRejectionReason::AuthenticationFailed),
+            1
+        );
+        assert_eq!(snapshot.tc_rejected(TcRejectionReason::InvalidFormat), 0);
+        assert_eq!(snapshot.tm_generated, 4);
+        assert_eq!(snapshot.tm_dropped, 1);
+        assert_eq!(
+            snapshot.active_services().collect::<vec::Vec<_>>(),
+            vec![(1, 1), (17, 2)]
+        );
+
+        let mut buf = [0; 64];
+        let len = snapshot.write_to_be_bytes(&mut buf).unwrap();
+        assert_eq!(len, snapshot.len_written());
+        assert_eq!(len, TmtcStatisticsSnapshot::FIXED_LEN + 10);
+        assert_eq!(&buf[0..4], &2_u32.to_be_bytes());
+        assert_eq!(&buf[4..8], &32_u32.to_be_bytes());
+        assert_eq!(&buf[16..20], &1_u32.to_be_bytes());
+        assert_eq!(buf[36], 2);
+        assert_eq!(&buf[37..42], &[1, 0, 0, 0, 1]);
+        assert_eq!(&buf[42..47], &[17, 0, 0, 0, 2]);
+        assert!(snapshot.write_to_be_bytes(&mut buf[0..40]).is_err());
+
+        stats.reset();
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.tc_packets_received, 0);
+        assert_eq!(snapshot.active_services().count(), 0);
+    }
+
+    struct FullQueueSender;
+
+    impl EcssTmSender for FullQueueSender {
+        fn send_tm(&self, _sender_id: ComponentId, _tm: PusTmVariant) -> Result<(), EcssTmtcError> {
+            Err(EcssTmtcError::Send(GenericSendError::QueueFull(None)))
+        }
+    }
+
+    #[test]
+    fn test_stats_senders() {
+        let stats = Arc::new(TmtcStatistics::default());
+        let (tm_tx, tm_rx) = mpsc::channel::<PacketAsVec>();
+        let tm_sender = StatsTmSender::new(tm_tx, stats.clone());
+        let sec_header = PusTmSecondaryHeader::new_simple(17, 2, &[]);
+        let tm = PusTmCreator::new(SpHeader::new_from_apid(0x02), sec_header, &[], true);
+        tm_sender.send_tm(0, tm.clone().into()).unwrap();
+        assert!(tm_rx.try_recv().is_ok());
+        let full_tm_sender = StatsTmSender::new(FullQueueSender, stats.clone());
+        assert!(full_tm_sender.send_tm(0, tm.into()).is_err());
+
+        let (tc_tx, tc_rx) = mpsc::channel::<PacketAsVec>();
+        let tc_sender = StatsTcSender::new(tc_tx, stats.clone());
+        tc_sender.send_packet(0, &[1, 2, 3]).unwrap();
+        drop(tc_rx);
+        assert!(tc_sender.send_packet(0, &[1, 2, 3]).is_err());
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.tm_generated, 1);
+        assert_eq!(snapshot.tm_generated_per_service[17], 1);
+        assert_eq!(snapshot.tm_dropped, 1);
+        assert_eq!(snapshot.tc_packets_received, 2);
+        assert_eq!(snapshot.tc_bytes_received, 6);
+        assert_eq!(snapshot.tc_rejected(TcRejectionReason::ForwardingFailed), 1);
+    }
+
+    #[test]
+    fn test_stats_hk_set() {
+        let stats = Arc::new(TmtcStatistics::default());
+        stats.tc_received(10);
+        let mut hk_sets =
+            HkSetRegistry::new(UniqueApidTargetId::new(0x02, 1), Duration::from_secs(1), 64);
+        hk_sets
+            .add_set(create_stats_hk_set(stats, 0, Duration::from_secs(10)))
+            .unwrap();
+        let mut buf = [0; 64];
+        let len = hk_sets.pack_set(0, &mut buf).unwrap();
+        assert_eq!(len, 8 + TmtcStatisticsSnapshot::FIXED_LEN);
+        assert_eq!(&buf[8..12], &1_u32.to_be_bytes());
+        hk_sets
+            .handle_request(
+                &HkRequest::new(0, HkRequestVariant::EnablePeriodic),
+                &[0; 7],
+                &mpsc::channel::<PacketAsVec>().0,
+            )
+            .unwrap();
+        assert_eq!(hk_sets.is_periodic(0), Some(true));
+    }
+}
@@ -0,0 +1,490 @@
+//! Pluggable compression of PUS telemetry for low-rate downlinks.
+//!
+//! The [TmCompressionStage] compresses the source data of selected PUS TM packets with a
+//! [TmCompressor] before they are handed to the HAL servers. It can be used stand-alone or as
+//! part of the [super::tm_funnel::TmFunnel]. The primary header and the PUS secondary header are
+//! kept, so the packets can still be routed and classified by their APID, service and
+//! subservice.
+//!
+//! A compressed packet is flagged by setting a configurable bit of its APID, which is
+//! [DEFAULT_COMPRESSED_APID_FLAG] by default. The compressed source data is preceded by a
+//! compression header with the following format:
+//!
+//!  1. The [CompressionAlgorithmId] of the used compressor as a [u8].
+//!  2. The length of the uncompressed source data as a big endian [u16].
+//!
+//! Packets are only compressed if this reduces their size. The ground segment can restore the
+//! original packet with [decompress_pus_tm] and a [TmDecompressor] for the used algorithm.
+//!
+//! The [RleCompressor] provides a simple run-length encoding which is well suited for sparse
+//! housekeeping data. Other algorithms, for example heatshrink or LZ4, can be added by
+//! implementing the [TmCompressor] and [TmDecompressor] traits.
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use spacepackets::CCSDS_HEADER_LEN;
+
+use super::tm_shaper::{TmCategory, TmCategoryClassifier};
+use crate::pus::memory::CRC_CCITT_FALSE;
+
+/// Identifier of a compression algorithm which is stored in the compression header.
+pub type CompressionAlgorithmId = u8;
+
+/// Algorithm ID of the [RleCompressor].
+pub const RLE_ALGORITHM_ID: CompressionAlgorithmId = 1;
+
+/// Default flag which is set in the APID of compressed packets. This is the most significant
+/// bit of the 11 bit APID.
+pub const DEFAULT_COMPRESSED_APID_FLAG: u16 = 0x400;
+
+/// Length of the compression header which precedes the compressed source data.
+pub const COMPRESSION_HEADER_LEN: usize = 3;
+
+/// Size of the PUS C TM secondary header without the timestamp.
+const PUS_TM_SEC_HEADER_LEN_WITHOUT_TIMESTAMP: usize = 7;
+const CRC_LEN: usize = 2;
+const APID_MASK: u16 = 0x7ff;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TmCompressionError {
+    /// The packet could not be parsed as a PUS TM with the configured timestamp length.
+    InvalidPusTm,
+    /// The packet is not flagged as compressed.
+    NotCompressed,
+    /// The packet was compressed with a different algorithm than the one of the decompressor.
+    AlgorithmMismatch {
+        expected: CompressionAlgorithmId,
+        found: CompressionAlgorithmId,
+    },
+    /// The compressed data is corrupt or does not match the length in the compression header.
+    InvalidCompressedData,
+    /// The APID flag does not contain any bit of the 11 bit APID.
+    InvalidApidFlag(u16),
+    /// The APID of the packet already has the APID flag set, so a compressed packet could not be
+    /// distinguished from the original packet.
+    ApidFlagAlreadySet { apid: u16 },
+}
+
+impl Display for TmCompressionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TmCompressionError::InvalidPusTm => write!(f, "invalid PUS TM packet"),
+            TmCompressionError::NotCompressed => write!(f, "packet is not compressed"),
+            TmCompressionError::AlgorithmMismatch { expected, found } => write!(
+                f,
+                "packet compressed with algorithm {found}, expected algorithm {expected}"
+            ),
+            TmCompressionError::InvalidCompressedData => write!(f, "invalid compressed data"),
+            TmCompressionError::InvalidApidFlag(flag) => {
+                write!(f, "invalid APID flag {flag:#x}")
+            }
+            TmCompressionError::ApidFlagAlreadySet { apid } => {
+                write!(f, "APID {apid:#x} already has the compression flag set")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for TmCompressionError {}
+
+/// Generic trait for compression algorithms.
+pub trait TmCompressor {
+    fn algorithm_id(&self) -> CompressionAlgorithmId;
+
+    /// Compress the data and append the compressed data to the output.
+    fn compress(&mut self, data: &[u8], output: &mut Vec<u8>);
+}
+
+/// Generic trait for decompression algorithms, which are usually only required by the ground
+/// segment.
+pub trait TmDecompressor {
+    fn algorithm_id(&self) -> CompressionAlgorithmId;
+
+    /// Decompress the data and append the decompressed data to the output.
+    fn decompress(&mut self, data: &[u8], output: &mut Vec<u8>) -> Result<(), TmCompressionError>;
+}
+
+/// Run-length encoding in the PackBits format.
+///
+/// Each block starts with a header byte N. For N in the range 0 to 127, the next N + 1 bytes are
+/// copied literally. For N in the range 129 to 255, the next byte is repeated 257 - N times.
+/// A header byte of 128 is ignored.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RleCompressor;
+
+impl TmCompressor for RleCompressor {
+    fn algorithm_id(&self) -> CompressionAlgorithmId {
+        RLE_ALGORITHM_ID
+    }
+
+    fn compress(&mut self, data: &[u8], output: &mut Vec<u8>) {
+        let mut idx = 0;
+        while idx < data.len() {
+            let mut run_len = 1;
+            while idx + run_len < data.len() && run_len < 128 && data[idx + run_len] == data[idx] {
+                run_len += 1;
+            }
+            if run_len >= 2 {
+                output.push((257 - run_len) as u8);
+                output.push(data[idx]);
+                idx += run_len;
+                continue;
+            }
+            // Collect literals until the next run of at least three bytes.
+            let start = idx;
+            while idx < data.len() && idx - start < 128 {
+                if idx + 2 < data.len() && data[idx] == data[idx + 1] && data[idx] == data[idx + 2]
+                {
+                    break;
+                }
+                idx += 1;
+            }
+            output.push((idx - start - 1) as u8);
+            output.extend_from_slice(&data[start..idx]);
+        }
+    }
+}
+
+impl TmDecompressor for RleCompressor {
+    fn algorithm_id(&self) -> CompressionAlgorithmId {
+        RLE_ALGORITHM_ID
+    }
+
+    fn decompress(&mut self, data: &[u8], output: &mut Vec<u8>) -> Result<(), TmCompressionError> {
+        let mut idx = 0;
+        while idx < data.len() {
+            let header = data[idx] as usize;
+            idx += 1;
+            match header {
+                0..=127 => {
+                    let literal = data
+                        .get(idx..idx + header + 1)
+                        .ok_or(TmCompressionError::InvalidCompressedData)?;
+                    output.extend_from_slice(literal);
+                    idx += header + 1;
+                }
+                128 => (),
+                _ => {
+                    let value = *data
+                        .get(idx)
+                        .ok_or(TmCompressionError::InvalidCompressedData)?;
+                    output.resize(output.len() + 257 - header, value);
+                    idx += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Length of the packet and the length of its headers, which precede the source data.
+fn pus_tm_lengths(
+    raw_tm: &[u8],
+    timestamp_len: usize,
+) -> Result<(usize, usize), TmCompressionError> {
+    if raw_tm.len() < CCSDS_HEADER_LEN {
+        return Err(TmCompressionError::InvalidPusTm);
+    }
+    let packet_len = u16::from_be_bytes([raw_tm[4], raw_tm[5]]) as usize + CCSDS_HEADER_LEN + 1;
+    let header_len = CCSDS_HEADER_LEN + PUS_TM_SEC_HEADER_LEN_WITHOUT_TIMESTAMP + timestamp_len;
+    if raw_tm.len() < packet_len || packet_len < header_len + CRC_LEN {
+        return Err(TmCompressionError::InvalidPusTm);
+    }
+    Ok((packet_len, header_len))
+}
+
+/// Update the packet length field and append the CRC. The buffer must contain the packet without
+/// the CRC.
+fn finish_packet(packet: &mut Vec<u8>) {
+    let data_len = (packet.len() + CRC_LEN - CCSDS_HEADER_LEN - 1) as u16;
+    packet[4..6].copy_from_slice(&data_len.to_be_bytes());
+    let crc = CRC_CCITT_FALSE.checksum(packet);
+    packet.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Check whether the PUS TM is flagged as compressed with the given APID flag.
+pub fn is_compressed(raw_tm: &[u8], apid_flag: u16) -> bool {
+    raw_tm.len() >= 2 && u16::from_be_bytes([raw_tm[0], raw_tm[1]]) & apid_flag & APID_MASK != 0
+}
+
+/// Restore the original packet from a PUS TM which was compressed by a [TmCompressionStage] with
+/// the given APID flag.
+pub fn decompress_pus_tm(
+    raw_tm: &[u8],
+    timestamp_len: usize,
+    apid_flag: u16,
+    decompressor: &mut (impl TmDecompressor + ?Sized),
+) -> Result<Vec<u8>, TmCompressionError> {
+    let (packet_len, header_len) = pus_tm_lengths(raw_tm, timestamp_len)?;
+    if !is_compressed(raw_tm, apid_flag) {
+        return Err(TmCompressionError::NotCompressed);
+    }
+    let data = &raw_tm[header_len..packet_len - CRC_LEN];
+    if data.len() < COMPRESSION_HEADER_LEN {
+        return Err(TmCompressionError::InvalidCompressedData);
+    }
+    if data[0] != decompressor.algorithm_id() {
+        return Err(TmCompressionError::AlgorithmMismatch {
+            expected: decompressor.algorithm_id(),
+            found: data[0],
+        });
+    }
+    let source_data_len = u16::from_be_bytes([data[1], data[2]]) as usize;
+    let mut packet = Vec::with_capacity(header_len + source_data_len + CRC_LEN);
+    packet.extend_from_slice(&raw_tm[0..header_len]);
+    let apid_field = u16::from_be_bytes([packet[0], packet[1]]) & !(apid_flag & APID_MASK);
+    packet[0..2].copy_from_slice(&apid_field.to_be_bytes());
+    decompressor.decompress(&data[COMPRESSION_HEADER_LEN..], &mut packet)?;
+    if packet.len() != header_len + source_data_len {
+        return Err(TmCompressionError::InvalidCompressedData);
+    }
+    finish_packet(&mut packet);
+    Ok(packet)
+}
+
+/// Compression stage for PUS TM packets.
+///
+/// All packets are compressed by default. The compression can be restricted to selected
+/// [TmCategory]s with [Self::with_categories].
+pub struct TmCompressionStage {
+    compressor: Box<dyn TmCompressor + Send>,
+    timestamp_len: usize,
+    apid_flag: u16,
+    categories: Option<(TmCategoryClassifier, Vec<TmCategory>)>,
+    packet_buf: Vec<u8>,
+    num_compressed: u64,
+    bytes_saved: u64,
+}
+
+impl TmCompressionStage {
+    /// Create a new compression stage for PUS TM packets with a timestamp of length
+    /// `timestamp_len`.
+    pub fn new(compressor: impl TmCompressor + Send + 'static, timestamp_len: usize) -> Self {
+        Self {
+            compressor: Box::new(compressor),
+            timestamp_len,
+            apid_flag: DEFAULT_COMPRESSED_APID_FLAG,
+            categories: None,
+            packet_buf: Vec::new(),
+            num_compressed: 0,
+            bytes_saved: 0,
+        }
+    }
+
+    /// Only compress packets of the given categories, which are determined with the given
+    /// classifier.
+    pub fn with_categories(
+        mut self,
+        classifier: TmCategoryClassifier,
+        categories: &[TmCategory],
+    ) -> Self {
+        self.categories = Some((classifier, categories.to_vec()));
+        self
+    }
+
+    /// Set the flag which is set in the APID of compressed packets. Only the lower 11 bits are
+    /// used, and at least one of them must be set.
+    pub fn with_apid_flag(mut self, apid_flag: u16) -> Result<Self, TmCompressionError> {
+        if apid_flag & APID_MASK == 0 {
+            return Err(TmCompressionError::InvalidApidFlag(apid_flag));
+        }
+        self.apid_flag = apid_flag & APID_MASK;
+        Ok(self)
+    }
+
+    pub fn apid_flag(&self) -> u16 {
+        self.apid_flag
+    }
+
+    pub fn timestamp_len(&self) -> usize {
+        self.timestamp_len
+    }
+
+    /// Number of packets which were compressed.
+    pub fn num_compressed(&self) -> u64 {
+        self.num_compressed
+    }
+
+    /// Total number of bytes which were saved by the compression.
+    pub fn bytes_saved(&self) -> u64 {
+        self.bytes_saved
+    }
+
+    /// Check whether the packet belongs to one of the selected categories.
+    pub fn selected(&self, raw_tm: &[u8]) -> bool {
+        match &self.categories {
+            Some((classifier, categories)) => {
+                // 6 bytes CCSDS primary header followed by the PUS version byte, service and
+                // subservice.
+                raw_tm.len() >= 9 && categories.contains(&classifier(raw_tm[7], raw_tm[8]))
+            }
+            None => true,
+        }
+    }
+
+    /// Compress the PUS TM.
+    ///
+    /// Returns the compressed packet, or [None] if the packet was not selected for compression
+    /// or if the compression would not reduce its size. The original packet should be sent in
+    /// that case.
+    ///
+    /// Packets whose APID already has the APID flag set are rejected with
+    /// [TmCompressionError::ApidFlagAlreadySet], because the ground segment would interpret them
+    /// as compressed packets.
+    pub fn compress(&mut self, raw_tm: &[u8]) -> Result<Option<&[u8]>, TmCompressionError> {
+        let (packet_len, header_len) = pus_tm_lengths(raw_tm, self.timestamp_len)?;
+        let apid = u16::from_be_bytes([raw_tm[0], raw_tm[1]]) & APID_MASK;
+        if apid & self.apid_flag != 0 {
+            return Err(TmCompressionError::ApidFlagAlreadySet { apid });
+        }
+        if !self.selected(raw_tm) {
+            return Ok(None);
+        }
+        let source_data = &raw_tm[header_len..packet_len - CRC_LEN];
+        self.packet_buf.clear();
+        self.packet_buf.extend_from_slice(&raw_tm[0..header_len]);
+        let apid_field = u16::from_be_bytes([raw_tm[0], raw_tm[1]]) | self.apid_flag;
+        self.packet_buf[0..2].copy_from_slice(&apid_field.to_be_bytes());
+        self.packet_buf.push(self.compressor.algorithm_id());
+        self.packet_buf
+            .extend_from_slice(&(source_data.len() as u16).to_be_bytes());
+        self.compressor.compress(source_data, &mut self.packet_buf);
+        if self.packet_buf.len() + CRC_LEN >= packet_len {
+            return Ok(None);
+        }
+        finish_packet(&mut self.packet_buf);
+        self.num_compressed += 1;
+        self.bytes_saved += (packet_len - self.packet_buf.len()) as u64;
+        Ok(Some(self.packet_buf.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmReader, PusTmSecondaryHeader};
+    use spacepackets::ecss::{PusPacket, WritablePusPacket};
+    use spacepackets::time::cds::MIN_CDS_FIELD_LEN;
+    use spacepackets::{CcsdsPacket, SpHeader};
+
+    use super::*;
+    use crate::tmtc::tm_shaper::{default_tm_category, TM_CATEGORY_HK};
+
+    const TEST_APID: u16 = 0x02;
+
+    fn create_tm(service: u8, source_data: &[u8]) -> Vec<u8> {
+        let timestamp = [0; 7];
+        let sp_header = SpHeader::new_for_unseg_tm(TEST_APID, 0, 0);
+        let sec_header = PusTmSecondaryHeader::new_simple(service, 25, &timestamp);
+        PusTmCreator::new(sp_header, sec_header, source_data, true)
+            .to_vec()
+            .unwrap()
+    }
+
+    fn rle_roundtrip(data: &[u8]) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        RleCompressor.compress(data, &mut compressed);
+        let mut decompressed = Vec::new();
+        RleCompressor
+            .decompress(&compressed, &mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, data);
+        compressed
+    }
+
+    #[test]
+    fn test_rle() {
+        assert!(rle_roundtrip(&[]).is_empty());
+        assert_eq!(rle_roundtrip(&[0; 200]), vec![129, 0, 185, 0]);
+        assert_eq!(rle_roundtrip(&[1, 2, 3]), vec![2, 1, 2, 3]);
+        rle_roundtrip(&[1, 1, 2, 3, 3, 3, 4, 5, 5]);
+        rle_roundtrip(&(0..=255).collect::<Vec<u8>>());
+        let mut output = Vec::new();
+        assert_eq!(
+            RleCompressor.decompress(&[5, 1, 2], &mut output),
+            Err(TmCompressionError::InvalidCompressedData)
+        );
+    }
+
+    #[test]
+    fn test_compress_and_decompress() {
+        let mut source_data = vec![0; 64];
+        source_data[10] = 5;
+        let tm = create_tm(3, &source_data);
+        let mut stage = TmCompressionStage::new(RleCompressor, MIN_CDS_FIELD_LEN);
+        let compressed = stage.compress(&tm).unwrap().unwrap().to_vec();
+        assert!(compressed.len() < tm.len());
+        assert_eq!(stage.num_compressed(), 1);
+        assert_eq!(stage.bytes_saved(), (tm.len() - compressed.len()) as u64);
+        assert!(is_compressed(&compressed, DEFAULT_COMPRESSED_APID_FLAG));
+        // The compressed packet is a valid PUS TM.
+        let (reader, _) = PusTmReader::new(&compressed, MIN_CDS_FIELD_LEN).unwrap();
+        assert_eq!(reader.apid(), TEST_APID | DEFAULT_COMPRESSED_APID_FLAG);
+        assert_eq!(reader.service(), 3);
+        assert_eq!(reader.user_data()[0], RLE_ALGORITHM_ID);
+
+        let decompressed = decompress_pus_tm(
+            &compressed,
+            MIN_CDS_FIELD_LEN,
+            DEFAULT_COMPRESSED_APID_FLAG,
+            &mut RleCompressor,
+        )
+        .unwrap();
+        assert_eq!(decompressed, tm);
+        assert_eq!(
+            decompress_pus_tm(
+                &tm,
+                MIN_CDS_FIELD_LEN,
+                DEFAULT_COMPRESSED_APID_FLAG,
+                &mut RleCompressor
+            ),
+            Err(TmCompressionError::NotCompressed)
+        );
+    }
+
+    #[test]
+    fn test_category_selection_and_incompressible_data() {
+        let mut stage = TmCompressionStage::new(RleCompressor, MIN_CDS_FIELD_LEN)
+            .with_categories(default_tm_category, &[TM_CATEGORY_HK]);
+        assert!(stage.compress(&create_tm(17, &[0; 32])).unwrap().is_none());
+        assert!(stage
+            .compress(&create_tm(3, &[1, 2, 3, 4]))
+            .unwrap()
+            .is_none());
+        assert!(stage.compress(&create_tm(3, &[0; 32])).unwrap().is_some());
+        assert_eq!(stage.num_compressed(), 1);
+        assert_eq!(
+            stage.compress(&[0; 4]).unwrap_err(),
+            TmCompressionError::InvalidPusTm
+        );
+    }
+
+    #[test]
+    fn test_apid_flag_validation() {
+        assert_eq!(
+            TmCompressionStage::new(RleCompressor, MIN_CDS_FIELD_LEN)
+                .with_apid_flag(0x800)
+                .err(),
+            Some(TmCompressionError::InvalidApidFlag(0x800))
+        );
+        let mut stage = TmCompressionStage::new(RleCompressor, MIN_CDS_FIELD_LEN)
+            .with_apid_flag(0x100)
+            .unwrap();
+        assert_eq!(stage.apid_flag(), 0x100);
+        let timestamp = [0; 7];
+        let sp_header = SpHeader::new_for_unseg_tm(0x7ff, 0, 0);
+        let sec_header = PusTmSecondaryHeader::new_simple(3, 25, &timestamp);
+        let tm = PusTmCreator::new(sp_header, sec_header, &[0; 32], true)
+            .to_vec()
+            .unwrap();
+        assert_eq!(
+            stage.compress(&tm).unwrap_err(),
+            TmCompressionError::ApidFlagAlreadySet { apid: 0x7ff }
+        );
+        assert_eq!(stage.num_compressed(), 0);
+    }
+}
@@ -0,0 +1,452 @@
+//! Reusable TM funnel components.
+//!
+//! A TM funnel is the central component which receives all telemetry generated by the various
+//! components of an on-board software before it is forwarded to the TM sinks, for example the
+//! TCP or UDP servers. It is also a good place to apply common packet processing operations like
+//! setting the CCSDS sequence count or the PUS message counter.
+//!
+//! The [TmFunnelCounters] only handle the counting and the in-place patching of PUS TM packets
+//! while the [TmFunnel] additionally forwards the patched packets to an arbitrary number of TM
+//! sinks. The [TmFunnel] can optionally compress selected packets with a
+//! [super::tm_compression::TmCompressionStage] before they are forwarded.
+use core::fmt::{Display, Formatter};
+use hashbrown::HashMap;
+use spacepackets::ecss::tm::PusTmZeroCopyWriter;
+use spacepackets::ecss::PusPacket;
+use spacepackets::time::cds::MIN_CDS_FIELD_LEN;
+use spacepackets::CcsdsPacket;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::seq_count::{
+    CcsdsSimpleSeqCountProvider, SeqCountProviderSimple, SequenceCountProviderCore,
+};
+use crate::ComponentId;
+
+use super::tm_compression::{TmCompressionError, TmCompressionStage};
+use super::tm_priority::PriorityTmQueue;
+use super::PacketSenderRaw;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TmFunnelError<SinkError> {
+    /// The packet could not be parsed as a PUS TM with the configured timestamp length.
+    InvalidPusTm,
+    /// The packet could not be compressed, see [TmCompressionStage::compress].
+    Compression(TmCompressionError),
+    /// Sending the packet to a sink failed.
+    Sink { index: usize, error: SinkError },
+}
+
+impl<SinkError: Display> Display for TmFunnelError<SinkError> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TmFunnelError::InvalidPusTm => write!(f, "invalid PUS TM packet"),
+            TmFunnelError::Compression(error) => write!(f, "TM compression failed: {error}"),
+            TmFunnelError::Sink { index, error } => {
+                write!(f, "sending TM to sink {index} failed: {error}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<SinkError: Error + 'static> Error for TmFunnelError<SinkError> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TmFunnelError::Compression(error) => Some(error),
+            TmFunnelError::Sink { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Per-APID CCSDS sequence counting and per-service PUS message counting for PUS TM packets.
+///
+/// The CCSDS sequence count wraps around at [spacepackets::MAX_SEQ_COUNT] while the message
+/// counter wraps around at [u16::MAX].
+pub struct TmFunnelCounters {
+    seq_counter_map: HashMap<u16, CcsdsSimpleSeqCountProvider>,
+    msg_counter_map: HashMap<u8, SeqCountProviderSimple<u16>>,
+    timestamp_len: usize,
+}
+
+impl Default for TmFunnelCounters {
+    fn default() -> Self {
+        Self::new(MIN_CDS_FIELD_LEN)
+    }
+}
+
+impl TmFunnelCounters {
+    /// Create new counters for PUS TM packets with a timestamp of length `timestamp_len`.
+    /// The default implementation uses the length of a CDS short timestamp.
+    pub fn new(timestamp_len: usize) -> Self {
+        Self {
+            seq_counter_map: HashMap::new(),
+            msg_counter_map: HashMap::new(),
+            timestamp_len,
+        }
+    }
+
+    pub fn timestamp_len(&self) -> usize {
+        self.timestamp_len
+    }
+
+    /// Retrieve the next CCSDS sequence count for the given APID and increment it.
+    pub fn next_seq_count(&mut self, apid: u16) -> u16 {
+        self.seq_counter_map
+            .entry(apid)
+            .or_default()
+            .get_and_increment()
+    }
+
+    /// Retrieve the next PUS message counter for the given service and increment it.
+    pub fn next_msg_counter(&mut self, service: u8) -> u16 {
+        self.msg_counter_map
+            .entry(service)
+            .or_default()
+            .get_and_increment()
+    }
+
+    /// Set the sequence count and the message counter using the provided zero-copy writer.
+    ///
+    /// The caller still has to call [PusTmZeroCopyWriter::finish] to update the CRC of the
+    /// packet. This allows inspecting the patched packet before it is finished.
+    pub fn apply_to_writer(&mut self, writer: &mut PusTmZeroCopyWriter) {
+        let seq_count = self.next_seq_count(writer.apid());
+        writer.set_seq_count(seq_count);
+        let msg_counter = self.next_msg_counter(writer.service());
+        writer.set_msg_count(msg_counter);
+    }
+
+    /// Set the sequence count and the message counter of the raw PUS TM in place and update
+    /// the CRC of the packet. The packet is not re-serialized.
+    pub fn patch_pus_tm<SinkError>(
+        &mut self,
+        raw_tm: &mut [u8],
+    ) -> Result<(), TmFunnelError<SinkError>> {
+        let mut writer = PusTmZeroCopyWriter::new(raw_tm, self.timestamp_len)
+            .ok_or(TmFunnelError::InvalidPusTm)?;
+        self.apply_to_writer(&mut writer);
+        writer.finish();
+        Ok(())
+    }
+}
+
+/// TM funnel which applies the counting and patching of the [TmFunnelCounters] to all received
+/// PUS TM packets and forwards them to all registered TM sinks.
+///
+/// Header patching can be disabled, for example if the TM generators already set the sequence
+/// count and the message counter themselves. In that case, the funnel only fans out the packets.
+pub struct TmFunnel<Sink: PacketSenderRaw> {
+    counters: TmFunnelCounters,
+    patch_headers: bool,
+    sinks: alloc::vec::Vec<Sink>,
+    compression: Option<TmCompressionStage>,
+}
+
+impl<Sink: PacketSenderRaw> Default for TmFunnel<Sink> {
+    fn default() -> Self {
+        Self::new(TmFunnelCounters::default())
+    }
+}
+
+impl<Sink: PacketSenderRaw> TmFunnel<Sink> {
+    /// Create a new TM funnel without any sinks. Header patching is enabled by default.
+    pub fn new(counters: TmFunnelCounters) -> Self {
+        Self {
+            counters,
+            patch_headers: true,
+            sinks: alloc::vec::Vec::new(),
+            compression: None,
+        }
+    }
+
+    /// Compress the packets with the given compression stage after the header patching. Packets
+    /// which are not selected by the stage or which can not be compressed are forwarded
+    /// unchanged.
+    pub fn with_compression(mut self, compression: TmCompressionStage) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn compression(&self) -> Option<&TmCompressionStage> {
+        self.compression.as_ref()
+    }
+
+    pub fn add_sink(&mut self, sink: Sink) {
+        self.sinks.push(sink);
+    }
+
+    pub fn sinks(&self) -> &[Sink] {
+        &self.sinks
+    }
+
+    pub fn num_sinks(&self) -> usize {
+        self.sinks.len()
+    }
+
+    pub fn counters(&self) -> &TmFunnelCounters {
+        &self.counters
+    }
+
+    pub fn counters_mut(&mut self) -> &mut TmFunnelCounters {
+        &mut self.counters
+    }
+
+    pub fn header_patching(&self) -> bool {
+        self.patch_headers
+    }
+
+    pub fn set_header_patching(&mut self, enabled: bool) {
+        self.patch_headers = enabled;
+    }
+
+    /// Patch the PUS TM in place if header patching is enabled, compress it if a compression
+    /// stage is configured and forward it to all sinks.
+    ///
+    /// The packet is forwarded to all sinks even if sending it to one of them fails. The error
+    /// of the first failing sink is returned in that case.
+    pub fn process_and_forward(
+        &mut self,
+        sender_id: ComponentId,
+        raw_tm: &mut [u8],
+    ) -> Result<(), TmFunnelError<Sink::Error>> {
+        if self.patch_headers {
+            self.counters.patch_pus_tm(raw_tm)?;
+        }
+        if let Some(compression) = &mut self.compression {
+            if let Some(compressed) = compression
+                .compress(raw_tm)
+                .map_err(TmFunnelError::Compression)?
+            {
+                return Self::forward_to_sinks(&self.sinks, sender_id, compressed);
+            }
+        }
+        self.forward(sender_id, raw_tm)
+    }
+
+    /// Forward the packet to all sinks without any processing.
+    pub fn forward(
+        &self,
+        sender_id: ComponentId,
+        raw_tm: &[u8],
+    ) -> Result<(), TmFunnelError<Sink::Error>> {
+        Self::forward_to_sinks(&self.sinks, sender_id, raw_tm)
+    }
+
+    fn forward_to_sinks(
+        sinks: &[Sink],
+        sender_id: ComponentId,
+        raw_tm: &[u8],
+    ) -> Result<(), TmFunnelError<Sink::Error>> {
+        let mut result = Ok(());
+        for (index, sink) in sinks.iter().enumerate() {
+            if let Err(error) = sink.send_packet(sender_id, raw_tm) {
+                if result.is_ok() {
+                    result = Err(TmFunnelError::Sink { index, error });
+                }
+            }
+        }
+        result
+    }
+
+    /// Retrieve up to `max_packets` packets from the [PriorityTmQueue] in the order determined
+    /// by its scheduling policy, and process and forward them to all sinks. Because the packets
+    /// are patched when they are retrieved, the sequence counts reflect the downlink order.
+    ///
+    /// Returns the number of forwarded packets. Processing stops at the first error, and the
+    /// packet which caused the error is not re-queued.
+    pub fn forward_prioritized(
+        &mut self,
+        queue: &mut PriorityTmQueue,
+        max_packets: usize,
+    ) -> Result<usize, TmFunnelError<Sink::Error>> {
+        let mut num_forwarded = 0;
+        while num_forwarded < max_packets {
+            let mut packet = match queue.pop() {
+                Some(packet) => packet,
+                None => break,
+            };
+            self.process_and_forward(packet.sender_id, &mut packet.packet)?;
+            num_forwarded += 1;
+        }
+        Ok(num_forwarded)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::sync::mpsc;
+
+    use spacepackets::ecss::tm::{
+        GenericPusTmSecondaryHeader, PusTmCreator, PusTmReader, PusTmSecondaryHeader,
+    };
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::SpHeader;
+
+    use crate::queue::GenericSendError;
+    use crate::tmtc::tm_compression::{
+        decompress_pus_tm, RleCompressor, DEFAULT_COMPRESSED_APID_FLAG,
+    };
+    use crate::tmtc::tm_priority::{TmSchedulingPolicy, NUM_DEFAULT_TM_PRIORITIES};
+    use crate::tmtc::PacketAsVec;
+
+    use super::*;
+
+    const TEST_SENDER_ID: ComponentId = 5;
+
+    fn create_tm(apid: u16, service: u8) -> alloc::vec::Vec<u8> {
+        let timestamp = [0; 7];
+        let sp_header = SpHeader::new_for_unseg_tm(apid, 0, 0);
+        let sec_header = PusTmSecondaryHeader::new_simple(service, 1, &timestamp);
+        PusTmCreator::new(sp_header, sec_header, &[1, 2, 3], true)
+            .to_vec()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_counters() {
+        let mut counters = TmFunnelCounters::default();
+        assert_eq!(counters.next_seq_count(0x02), 0);
+        assert_eq!(counters.next_seq_count(0x02), 1);
+        assert_eq!(counters.next_seq_count(0x03), 0);
+        assert_eq!(counters.next_msg_counter(17), 0);
+        assert_eq!(counters.next_msg_counter(17), 1);
+        assert_eq!(counters.next_msg_counter(1), 0);
+    }
+
+    #[test]
+    fn test_patching() {
+        let mut counters = TmFunnelCounters::default();
+        let mut tm_0 = create_tm(0x02, 17);
+        let mut tm_1 = create_tm(0x02, 17);
+        let mut tm_2 = create_tm(0x03, 1);
+        counters.patch_pus_tm::<()>(&mut tm_0).unwrap();
+        counters.patch_pus_tm::<()>(&mut tm_1).unwrap();
+        counters.patch_pus_tm::<()>(&mut tm_2).unwrap();
+        let (reader, _) = PusTmReader::new(&tm_1, MIN_CDS_FIELD_LEN).unwrap();
+        assert_eq!(reader.seq_count(), 1);
+        assert_eq!(reader.msg_counter(), 1);
+        assert_eq!(reader.service(), 17);
+        let (reader, _) = PusTmReader::new(&tm_2, MIN_CDS_FIELD_LEN).unwrap();
+        assert_eq!(reader.seq_count(), 0);
+        assert_eq!(reader.msg_counter(), 0);
+    }
+
+    #[test]
+    fn test_invalid_tm() {
+        let mut counters = TmFunnelCounters::default();
+        let mut buf = [0; 4];
+        assert_eq!(
+            counters.patch_pus_tm::<()>(&mut buf).unwrap_err(),
+            TmFunnelError::InvalidPusTm
+        );
+    }
+
+    #[test]
+    fn test_fan_out() {
+        let (tx_0, rx_0) = mpsc::channel::<PacketAsVec>();
+        let (tx_1, rx_1) = mpsc::channel::<PacketAsVec>();
+        let mut funnel = TmFunnel::default();
+        funnel.add_sink(tx_0);
+        funnel.add_sink(tx_1);
+        assert_eq!(funnel.num_sinks(), 2);
+        let mut tm_0 = create_tm(0x02, 17);
+        let mut tm_1 = create_tm(0x02, 17);
+        funnel
+            .process_and_forward(TEST_SENDER_ID, &mut tm_0)
+            .unwrap();
+        funnel
+            .process_and_forward(TEST_SENDER_ID, &mut tm_1)
+            .unwrap();
+        for rx in [rx_0, rx_1] {
+            for expected_count in 0..2 {
+                let packet = rx.try_recv().unwrap();
+                assert_eq!(packet.sender_id, TEST_SENDER_ID);
+                let (reader, _) = PusTmReader::new(&packet.packet, MIN_CDS_FIELD_LEN).unwrap();
+                assert_eq!(reader.seq_count(), expected_count);
+                assert_eq!(reader.msg_counter(), expected_count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_patching_disabled_and_sink_error() {
+        let (tx_0, rx_0) = mpsc::channel::<PacketAsVec>();
+        let (tx_1, rx_1) = mpsc::channel::<PacketAsVec>();
+        drop(rx_0);
+        let mut funnel = TmFunnel::default();
+        funnel.set_header_patching(false);
+        funnel.add_sink(tx_0);
+        funnel.add_sink(tx_1);
+        let mut tm = create_tm(0x02, 17);
+        // Sequence count which would be reset to 0 by the header patching.
+        tm[3] = 5;
+        let tm_unpatched = tm.clone();
+        let result = funnel.process_and_forward(TEST_SENDER_ID, &mut tm);
+        assert_eq!(
+            result.unwrap_err(),
+            TmFunnelError::Sink {
+                index: 0,
+                error: GenericSendError::RxDisconnected
+            }
+        );
+        let packet = rx_1.try_recv().unwrap();
+        assert_eq!(packet.packet, tm_unpatched);
+    }
+
+    #[test]
+    fn test_forward_prioritized() {
+        let (tx, rx) = mpsc::channel::<PacketAsVec>();
+        let mut funnel = TmFunnel::default();
+        funnel.add_sink(tx);
+        let mut queue = PriorityTmQueue::new(
+            NUM_DEFAULT_TM_PRIORITIES,
+            TmSchedulingPolicy::StrictPriority,
+        );
+        for service in [3, 17, 1] {
+            queue.push_classified(PacketAsVec::new(TEST_SENDER_ID, create_tm(0x02, service)));
+        }
+        assert_eq!(funnel.forward_prioritized(&mut queue, 2).unwrap(), 2);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(funnel.forward_prioritized(&mut queue, 5).unwrap(), 1);
+        for (expected_count, expected_service) in [1, 17, 3].into_iter().enumerate() {
+            let packet = rx.try_recv().unwrap();
+            let (reader, _) = PusTmReader::new(&packet.packet, MIN_CDS_FIELD_LEN).unwrap();
+            assert_eq!(reader.service(), expected_service);
+            assert_eq!(reader.seq_count(), expected_count as u16);
+        }
+    }
+
+    #[test]
+    fn test_compression() {
+        let (tx, rx) = mpsc::channel::<PacketAsVec>();
+        let mut funnel = TmFunnel::default()
+            .with_compression(TmCompressionStage::new(RleCompressor, MIN_CDS_FIELD_LEN));
+        funnel.add_sink(tx);
+        let timestamp = [0; 7];
+        let sp_header = SpHeader::new_for_unseg_tm(0x02, 0, 0);
+        let sec_header = PusTmSecondaryHeader::new_simple(3, 25, &timestamp);
+        let mut hk_tm = PusTmCreator::new(sp_header, sec_header, &[0; 64], true)
+            .to_vec()
+            .unwrap();
+        let mut tm = create_tm(0x02, 17);
+        funnel
+            .process_and_forward(TEST_SENDER_ID, &mut hk_tm)
+            .unwrap();
+        funnel.process_and_forward(TEST_SENDER_ID, &mut tm).unwrap();
+        let compressed = rx.try_recv().unwrap();
+        assert!(compressed.packet.len() < hk_tm.len());
+        let decompressed = decompress_pus_tm(
+            &compressed.packet,
+            MIN_CDS_FIELD_LEN,
+            DEFAULT_COMPRESSED_APID_FLAG,
+            &mut RleCompressor,
+        )
+        .unwrap();
+        assert_eq!(decompressed, hk_tm);
+        // The source data of this packet can not be compressed.
+        assert_eq!(rx.try_recv().unwrap().packet, tm);
+        assert_eq!(funnel.compression().unwrap().num_compressed(), 1);
+    }
+}
@@ -1,7 +1,13 @@
-use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+use core::fmt::{Display, Formatter};
+use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader, PusTmZeroCopyWriter};
+use spacepackets::ecss::WritablePusPacket;
 use spacepackets::time::cds::CdsTime;
 use spacepackets::time::TimeWriter;
-use spacepackets::SpHeader;
+use spacepackets::{ByteConversionError, SpHeader, MAX_APID, MAX_SEQ_COUNT};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::pool::{PoolAddr, PoolError, PoolProvider};
 
 pub struct PusTmWithCdsShortHelper {
     apid: u16,
@@ -54,11 +60,241 @@ impl PusTmWithCdsShortHelper {
     }
 }
 
+/// Total size of a PUS TM with the given headers and source data length.
+pub fn pus_tm_len(
+    sp_header: SpHeader,
+    sec_header: PusTmSecondaryHeader,
+    source_data_len: usize,
+) -> usize {
+    PusTmCreator::new_no_source_data(sp_header, sec_header, false).len_written() + source_data_len
+}
+
+/// Serialize a PUS TM into the given buffer and let the `source_data_writer` write the source
+/// data directly into the packet. This avoids serializing the source data into a separate buffer
+/// first. The CCSDS data length field and the CRC16 are set after the source data was written.
+/// Returns the size of the packet.
+///
+/// ## Parameter
+///
+/// * `buf` - Target buffer, which must be at least [pus_tm_len] bytes large.
+/// * `source_data_len` - Size of the source data.
+/// * `source_data_writer` - Is called with a slice of exactly `source_data_len` bytes.
+pub fn write_pus_tm_in_place<W: FnMut(&mut [u8])>(
+    buf: &mut [u8],
+    sp_header: SpHeader,
+    sec_header: PusTmSecondaryHeader,
+    source_data_len: usize,
+    mut source_data_writer: W,
+) -> Result<usize, ByteConversionError> {
+    let timestamp_len = sec_header.timestamp.len();
+    let header_creator = PusTmCreator::new_no_source_data(sp_header, sec_header, false);
+    let header_len = header_creator.len_written() - 2;
+    let total_len = header_creator.len_written() + source_data_len;
+    if buf.len() < total_len {
+        return Err(ByteConversionError::ToSliceTooSmall {
+            found: buf.len(),
+            expected: total_len,
+        });
+    }
+    header_creator.write_to_bytes(buf)?;
+    // The CCSDS data length field contains the packet length minus the primary header length
+    // minus one.
+    buf[4..6].copy_from_slice(&((total_len - 7) as u16).to_be_bytes());
+    source_data_writer(&mut buf[header_len..header_len + source_data_len]);
+    // Can not fail, the buffer contains a complete PUS TM with the given timestamp length.
+    PusTmZeroCopyWriter::new(&mut buf[..total_len], timestamp_len)
+        .unwrap()
+        .finish();
+    Ok(total_len)
+}
+
+/// Obtain a free element of the exact PUS TM size from the pool and serialize the PUS TM
+/// directly into it with [write_pus_tm_in_place].
+pub fn add_pus_tm_in_place<W: FnMut(&mut [u8])>(
+    pool: &mut impl PoolProvider,
+    sp_header: SpHeader,
+    sec_header: PusTmSecondaryHeader,
+    source_data_len: usize,
+    mut source_data_writer: W,
+) -> Result<PoolAddr, PoolError> {
+    let mut result = Ok(0);
+    let addr = pool.free_element(pus_tm_len(sp_header, sec_header, source_data_len), |buf| {
+        result = write_pus_tm_in_place(
+            buf,
+            sp_header,
+            sec_header,
+            source_data_len,
+            &mut source_data_writer,
+        );
+    })?;
+    if let Err(e) = result {
+        // Best effort cleanup, the serialization error is more relevant.
+        let _ = pool.delete(addr);
+        return Err(e.into());
+    }
+    Ok(addr)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PusTmPatchError {
+    /// The packet could not be parsed as a PUS TM with the given timestamp length.
+    InvalidPusTm,
+    /// The APID exceeds [MAX_APID].
+    InvalidApid(u16),
+    /// The sequence count exceeds [MAX_SEQ_COUNT].
+    InvalidSeqCount(u16),
+    Pool(PoolError),
+}
+
+impl Display for PusTmPatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PusTmPatchError::InvalidPusTm => write!(f, "invalid PUS TM packet"),
+            PusTmPatchError::InvalidApid(apid) => write!(f, "invalid APID {apid}"),
+            PusTmPatchError::InvalidSeqCount(seq_count) => {
+                write!(f, "invalid sequence count {seq_count}")
+            }
+            PusTmPatchError::Pool(e) => write!(f, "pool error: {e}"),
+        }
+    }
+}
+
+impl From<PoolError> for PusTmPatchError {
+    fn from(value: PoolError) -> Self {
+        Self::Pool(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for PusTmPatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PusTmPatchError::Pool(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Set of PUS TM header fields which can be patched directly inside an already serialized PUS TM
+/// packet.
+///
+/// Only the fields which are set are modified, and the CRC16 of the packet is recalculated
+/// afterwards. This avoids deserializing and re-serializing the whole packet, which makes it
+/// suitable for the TM routing hot path, for example inside a TM funnel.
+///
+/// ## Example
+///
+/// ```
+/// use satrs::tmtc::tm_helper::PusTmHeaderPatch;
+/// use satrs::spacepackets::ecss::tm::{PusTmCreator, PusTmReader, PusTmSecondaryHeader};
+/// use satrs::spacepackets::ecss::WritablePusPacket;
+/// use satrs::spacepackets::{CcsdsPacket, SpHeader};
+///
+/// let sp_header = SpHeader::new_for_unseg_tm(0x02, 0, 0);
+/// let sec_header = PusTmSecondaryHeader::new_simple(17, 2, &[0; 7]);
+/// let mut raw_tm = PusTmCreator::new(sp_header, sec_header, &[], true)
+///     .to_vec()
+///     .unwrap();
+/// PusTmHeaderPatch::new()
+///     .with_apid(0x03)
+///     .with_seq_count(12)
+///     .apply(&mut raw_tm, 7)
+///     .unwrap();
+/// let (tm, _) = PusTmReader::new(&raw_tm, 7).unwrap();
+/// assert_eq!(tm.apid(), 0x03);
+/// assert_eq!(tm.seq_count(), 12);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PusTmHeaderPatch {
+    pub apid: Option<u16>,
+    pub seq_count: Option<u16>,
+    pub msg_counter: Option<u16>,
+}
+
+impl PusTmHeaderPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_apid(mut self, apid: u16) -> Self {
+        self.apid = Some(apid);
+        self
+    }
+
+    pub fn with_seq_count(mut self, seq_count: u16) -> Self {
+        self.seq_count = Some(seq_count);
+        self
+    }
+
+    pub fn with_msg_counter(mut self, msg_counter: u16) -> Self {
+        self.msg_counter = Some(msg_counter);
+        self
+    }
+
+    /// Patch the serialized PUS TM in place and recalculate its CRC16.
+    ///
+    /// ## Parameter
+    ///
+    /// * `raw_tm` - Raw PUS TM packet. The slice may be larger than the packet itself.
+    /// * `timestamp_len` - Length of the timestamp of the PUS TM.
+    pub fn apply(&self, raw_tm: &mut [u8], timestamp_len: usize) -> Result<(), PusTmPatchError> {
+        if let Some(apid) = self.apid {
+            if apid > MAX_APID {
+                return Err(PusTmPatchError::InvalidApid(apid));
+            }
+        }
+        if let Some(seq_count) = self.seq_count {
+            if seq_count > MAX_SEQ_COUNT {
+                return Err(PusTmPatchError::InvalidSeqCount(seq_count));
+            }
+        }
+        let mut writer =
+            PusTmZeroCopyWriter::new(raw_tm, timestamp_len).ok_or(PusTmPatchError::InvalidPusTm)?;
+        if let Some(apid) = self.apid {
+            writer.set_apid(apid);
+        }
+        if let Some(seq_count) = self.seq_count {
+            writer.set_seq_count(seq_count);
+        }
+        if let Some(msg_counter) = self.msg_counter {
+            writer.set_msg_count(msg_counter);
+        }
+        writer.finish();
+        Ok(())
+    }
+
+    /// Patch a serialized PUS TM which is stored inside a pool in place and recalculate its
+    /// CRC16.
+    pub fn apply_in_pool(
+        &self,
+        pool: &mut impl PoolProvider,
+        addr: &PoolAddr,
+        timestamp_len: usize,
+    ) -> Result<(), PusTmPatchError> {
+        let mut result = Ok(());
+        pool.modify(addr, |raw_tm| {
+            result = self.apply(raw_tm, timestamp_len);
+        })?;
+        result
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use spacepackets::{ecss::PusPacket, time::cds::CdsTime, CcsdsPacket};
 
-    use super::PusTmWithCdsShortHelper;
+    use spacepackets::ecss::tm::{
+        GenericPusTmSecondaryHeader, PusTmCreator, PusTmReader, PusTmSecondaryHeader,
+    };
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::SpHeader;
+
+    use crate::pool::{PoolProvider, StaticMemoryPool, StaticPoolConfig};
+
+    use super::{
+        add_pus_tm_in_place, pus_tm_len, write_pus_tm_in_place, PusTmHeaderPatch, PusTmPatchError,
+        PusTmWithCdsShortHelper,
+    };
 
     #[test]
     fn test_helper_with_stamper() {
@@ -82,4 +318,134 @@ mod tests {
         assert_eq!(tm.seq_count(), 25);
         assert_eq!(tm.timestamp().len(), 7);
     }
+
+    fn create_raw_tm() -> alloc::vec::Vec<u8> {
+        let mut pus_tm_helper = PusTmWithCdsShortHelper::new(0x02);
+        let stamper = CdsTime::new_with_u16_days(0, 0);
+        pus_tm_helper
+            .create_pus_tm_with_stamper(17, 2, &[1, 2, 3], &stamper, 0)
+            .to_vec()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_header_patch() {
+        let mut raw_tm = create_raw_tm();
+        PusTmHeaderPatch::new()
+            .with_apid(0x03)
+            .with_seq_count(25)
+            .with_msg_counter(5)
+            .apply(&mut raw_tm, 7)
+            .unwrap();
+        let (tm, _) = PusTmReader::new(&raw_tm, 7).expect("CRC check failed");
+        assert_eq!(tm.apid(), 0x03);
+        assert_eq!(tm.seq_count(), 25);
+        assert_eq!(tm.msg_counter(), 5);
+        assert_eq!(tm.service(), 17);
+        assert_eq!(tm.subservice(), 2);
+        assert_eq!(tm.user_data(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_header_patch_partial() {
+        let mut raw_tm = create_raw_tm();
+        PusTmHeaderPatch::new()
+            .with_msg_counter(5)
+            .apply(&mut raw_tm, 7)
+            .unwrap();
+        let (tm, _) = PusTmReader::new(&raw_tm, 7).unwrap();
+        assert_eq!(tm.apid(), 0x02);
+        assert_eq!(tm.seq_count(), 0);
+        assert_eq!(tm.msg_counter(), 5);
+    }
+
+    #[test]
+    fn test_header_patch_invalid_values() {
+        let mut raw_tm = create_raw_tm();
+        assert_eq!(
+            PusTmHeaderPatch::new()
+                .with_apid(0x800)
+                .apply(&mut raw_tm, 7)
+                .unwrap_err(),
+            PusTmPatchError::InvalidApid(0x800)
+        );
+        assert_eq!(
+            PusTmHeaderPatch::new()
+                .with_seq_count(0x4000)
+                .apply(&mut raw_tm, 7)
+                .unwrap_err(),
+            PusTmPatchError::InvalidSeqCount(0x4000)
+        );
+        assert_eq!(
+            PusTmHeaderPatch::new()
+                .with_seq_count(1)
+                .apply(&mut raw_tm[0..4], 7)
+                .unwrap_err(),
+            PusTmPatchError::InvalidPusTm
+        );
+    }
+
+    #[test]
+    fn test_header_patch_in_pool() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            alloc::vec![(4, 64)],
+            false,
+        ));
+        let addr = pool.add(&create_raw_tm()).unwrap();
+        PusTmHeaderPatch::new()
+            .with_seq_count(3)
+            .apply_in_pool(&mut pool, &addr, 7)
+            .unwrap();
+        let mut buf = [0; 64];
+        let len = pool.read(&addr, &mut buf).unwrap();
+        let (tm, _) = PusTmReader::new(&buf[0..len], 7).unwrap();
+        assert_eq!(tm.seq_count(), 3);
+    }
+
+    #[test]
+    fn test_write_in_place_matches_creator() {
+        let sp_header = SpHeader::new_for_unseg_tm(0x02, 5, 0);
+        let sec_header = PusTmSecondaryHeader::new_simple(3, 25, &[1, 2, 3, 4, 5, 6, 7]);
+        let source_data = [0, 0, 0, 1, 10, 20, 30];
+        let expected = PusTmCreator::new(sp_header, sec_header, &source_data, true)
+            .to_vec()
+            .unwrap();
+        assert_eq!(
+            pus_tm_len(sp_header, sec_header, source_data.len()),
+            expected.len()
+        );
+        let mut buf = [0xff; 64];
+        let written = write_pus_tm_in_place(&mut buf, sp_header, sec_header, 7, |src_data| {
+            assert_eq!(src_data.len(), source_data.len());
+            src_data.copy_from_slice(&source_data);
+        })
+        .unwrap();
+        assert_eq!(&buf[0..written], expected.as_slice());
+        assert!(write_pus_tm_in_place(
+            &mut buf[0..expected.len() - 1],
+            sp_header,
+            sec_header,
+            7,
+            |_| panic!("writer called for too small buffer")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_add_in_place() {
+        let mut pool = StaticMemoryPool::new(StaticPoolConfig::new_from_subpool_cfg_tuples(
+            alloc::vec![(4, 64)],
+            false,
+        ));
+        let sp_header = SpHeader::new_for_unseg_tm(0x02, 0, 0);
+        let sec_header = PusTmSecondaryHeader::new_simple(17, 2, &[0; 7]);
+        let addr = add_pus_tm_in_place(&mut pool, sp_header, sec_header, 3, |src_data| {
+            src_data.copy_from_slice(&[1, 2, 3])
+        })
+        .unwrap();
+        let raw_tm = pool.read_as_vec(&addr).unwrap();
+        let (tm, _) = PusTmReader::new(&raw_tm, 7).unwrap();
+        assert_eq!((tm.service(), tm.subservice()), (17, 2));
+        assert_eq!(tm.source_data(), &[1, 2, 3]);
+    }
 }
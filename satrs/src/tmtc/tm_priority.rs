@@ -0,0 +1,268 @@
+//! TM priority classification and prioritized TM queuing.
+//!
+//! On constrained downlinks, it is important that verification and event telemetry is not
+//! starved by large amounts of housekeeping telemetry. This module provides a
+//! [TmPriorityClassifier] which tags PUS TM packets with a [TmPriority] and the
+//! [PriorityTmQueue], which stores the packets in one queue per priority level.
+//!
+//! The packets are retrieved from the [PriorityTmQueue] according to the configured
+//! [TmSchedulingPolicy]:
+//!
+//!  - [TmSchedulingPolicy::StrictPriority] always serves the highest priority level first.
+//!    Lower priority telemetry is only served if there is no higher priority telemetry.
+//!  - [TmSchedulingPolicy::WeightedFair] serves the priority levels proportionally to their
+//!    configured weights, so low priority telemetry is still served under load.
+//!
+//! The [super::tm_funnel::TmFunnel::forward_prioritized] method can be used to forward the
+//! queued packets to the TM sinks.
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use super::PacketAsVec;
+
+/// Priority of a telemetry packet. Higher values have a higher priority.
+pub type TmPriority = u8;
+
+/// Function which assigns a priority to a telemetry packet based on its PUS service and
+/// subservice.
+pub type TmPriorityClassifier = fn(u8, u8) -> TmPriority;
+
+pub const TM_PRIORITY_LOW: TmPriority = 0;
+pub const TM_PRIORITY_MEDIUM: TmPriority = 1;
+pub const TM_PRIORITY_HIGH: TmPriority = 2;
+/// Number of priority levels used by the [default_tm_priority] classifier.
+pub const NUM_DEFAULT_TM_PRIORITIES: usize = 3;
+
+/// Default priority classification: Verification (PUS 1) and event (PUS 5) telemetry has a high
+/// priority, housekeeping (PUS 3) telemetry has a low priority and all other telemetry has a
+/// medium priority.
+pub fn default_tm_priority(service: u8, _subservice: u8) -> TmPriority {
+    match service {
+        1 | 5 => TM_PRIORITY_HIGH,
+        3 => TM_PRIORITY_LOW,
+        _ => TM_PRIORITY_MEDIUM,
+    }
+}
+
+/// Classify a raw PUS C TM packet. Returns [None] if the packet is too short to contain the
+/// service and subservice fields.
+pub fn classify_raw_pus_tm(raw_tm: &[u8], classifier: TmPriorityClassifier) -> Option<TmPriority> {
+    // 6 bytes CCSDS primary header followed by the PUS version byte, service and subservice.
+    if raw_tm.len() < 9 {
+        return None;
+    }
+    Some(classifier(raw_tm[7], raw_tm[8]))
+}
+
+/// Scheduling policy of the [PriorityTmQueue].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TmSchedulingPolicy {
+    /// Always serve the highest non-empty priority level.
+    StrictPriority,
+    /// Serve the non-empty priority levels proportionally to their weights. The weight at
+    /// index N is used for priority N. Missing weights and weights of 0 are treated as 1.
+    WeightedFair { weights: Vec<u32> },
+}
+
+/// Prioritized TM queue with one FIFO queue per priority level.
+///
+/// Priorities larger than the highest priority level are clamped to the highest priority level.
+#[derive(Debug)]
+pub struct PriorityTmQueue<Packet = PacketAsVec> {
+    queues: Vec<VecDeque<Packet>>,
+    policy: TmSchedulingPolicy,
+    /// Current credits of the priority levels for the weighted fair scheduling.
+    credits: Vec<i64>,
+    classifier: TmPriorityClassifier,
+}
+
+impl<Packet> PriorityTmQueue<Packet> {
+    /// Create a new queue with the given number of priority levels which uses the
+    /// [default_tm_priority] classifier.
+    pub fn new(num_priorities: usize, policy: TmSchedulingPolicy) -> Self {
+        Self::new_with_classifier(num_priorities, policy, default_tm_priority)
+    }
+
+    pub fn new_with_classifier(
+        num_priorities: usize,
+        policy: TmSchedulingPolicy,
+        classifier: TmPriorityClassifier,
+    ) -> Self {
+        assert!(
+            num_priorities > 0,
+            "at least one priority level is required"
+        );
+        let mut queues = Vec::with_capacity(num_priorities);
+        queues.resize_with(num_priorities, VecDeque::new);
+        Self {
+            queues,
+            policy,
+            credits: alloc::vec![0; num_priorities],
+            classifier,
+        }
+    }
+
+    pub fn num_priorities(&self) -> usize {
+        self.queues.len()
+    }
+
+    pub fn policy(&self) -> &TmSchedulingPolicy {
+        &self.policy
+    }
+
+    pub fn classifier(&self) -> TmPriorityClassifier {
+        self.classifier
+    }
+
+    /// Total number of queued packets.
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(|queue| queue.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(|queue| queue.is_empty())
+    }
+
+    /// Number of queued packets with the given priority.
+    pub fn len_of_priority(&self, priority: TmPriority) -> usize {
+        self.queues[self.level(priority)].len()
+    }
+
+    /// Queue a packet with the given priority.
+    pub fn push(&mut self, priority: TmPriority, packet: Packet) {
+        let level = self.level(priority);
+        self.queues[level].push_back(packet);
+    }
+
+    /// Retrieve the next packet according to the [TmSchedulingPolicy].
+    pub fn pop(&mut self) -> Option<Packet> {
+        let level = match &self.policy {
+            TmSchedulingPolicy::StrictPriority => {
+                self.queues.iter().rposition(|queue| !queue.is_empty())?
+            }
+            TmSchedulingPolicy::WeightedFair { weights } => {
+                // Smooth weighted round-robin over all non-empty priority levels.
+                let mut total_weight = 0;
+                let mut selected: Option<usize> = None;
+                for (level, queue) in self.queues.iter().enumerate() {
+                    if queue.is_empty() {
+                        continue;
+                    }
+                    let weight = weights.get(level).copied().unwrap_or(1).max(1) as i64;
+                    total_weight += weight;
+                    self.credits[level] += weight;
+                    // Higher priorities win ties.
+                    let better = match selected {
+                        Some(sel) => self.credits[level] >= self.credits[sel],
+                        None => true,
+                    };
+                    if better {
+                        selected = Some(level);
+                    }
+                }
+                let level = selected?;
+                self.credits[level] -= total_weight;
+                level
+            }
+        };
+        let packet = self.queues[level].pop_front();
+        if self.queues[level].is_empty() {
+            self.credits[level] = 0;
+        }
+        packet
+    }
+
+    fn level(&self, priority: TmPriority) -> usize {
+        (priority as usize).min(self.queues.len() - 1)
+    }
+}
+
+impl PriorityTmQueue<PacketAsVec> {
+    /// Classify the packet with the configured [TmPriorityClassifier] and queue it. Packets
+    /// which can not be classified are queued with the lowest priority.
+    pub fn push_classified(&mut self, packet: PacketAsVec) {
+        let priority = classify_raw_pus_tm(&packet.packet, self.classifier).unwrap_or(0);
+        self.push(priority, packet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::SpHeader;
+
+    use super::*;
+
+    fn create_tm(service: u8) -> PacketAsVec {
+        let sec_header = PusTmSecondaryHeader::new_simple(service, 1, &[]);
+        let tm = PusTmCreator::new(SpHeader::new_from_apid(0x02), sec_header, &[], true);
+        PacketAsVec::new(0, tm.to_vec().unwrap())
+    }
+
+    fn pop_services(queue: &mut PriorityTmQueue) -> Vec<u8> {
+        let mut services = Vec::new();
+        while let Some(packet) = queue.pop() {
+            services.push(packet.packet[7]);
+        }
+        services
+    }
+
+    #[test]
+    fn test_classification() {
+        assert_eq!(
+            classify_raw_pus_tm(&create_tm(1).packet, default_tm_priority),
+            Some(TM_PRIORITY_HIGH)
+        );
+        assert_eq!(
+            classify_raw_pus_tm(&create_tm(3).packet, default_tm_priority),
+            Some(TM_PRIORITY_LOW)
+        );
+        assert_eq!(
+            classify_raw_pus_tm(&create_tm(17).packet, default_tm_priority),
+            Some(TM_PRIORITY_MEDIUM)
+        );
+        assert_eq!(classify_raw_pus_tm(&[0; 8], default_tm_priority), None);
+    }
+
+    #[test]
+    fn test_strict_priority() {
+        let mut queue = PriorityTmQueue::new(
+            NUM_DEFAULT_TM_PRIORITIES,
+            TmSchedulingPolicy::StrictPriority,
+        );
+        for service in [3, 17, 3, 5, 1] {
+            queue.push_classified(create_tm(service));
+        }
+        assert_eq!(queue.len(), 5);
+        assert_eq!(queue.len_of_priority(TM_PRIORITY_LOW), 2);
+        assert_eq!(pop_services(&mut queue), vec![5, 1, 17, 3, 3]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_weighted_fair() {
+        let mut queue = PriorityTmQueue::new(
+            2,
+            TmSchedulingPolicy::WeightedFair {
+                weights: vec![1, 3],
+            },
+        );
+        for _ in 0..4 {
+            queue.push(0, create_tm(3));
+            queue.push(1, create_tm(1));
+        }
+        // The high priority level is served three times as often as the low priority level,
+        // until it is empty.
+        assert_eq!(pop_services(&mut queue), vec![1, 1, 3, 1, 1, 3, 3, 3]);
+    }
+
+    #[test]
+    fn test_priority_clamping() {
+        let mut queue = PriorityTmQueue::new(1, TmSchedulingPolicy::StrictPriority);
+        queue.push(TM_PRIORITY_HIGH, create_tm(1));
+        assert_eq!(queue.len_of_priority(0), 1);
+    }
+}
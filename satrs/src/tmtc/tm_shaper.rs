@@ -0,0 +1,513 @@
+//! Downlink bandwidth shaping per TM category.
+//!
+//! Missions with a fixed downlink budget need to limit the bitrate of the generated telemetry.
+//! The [TmBandwidthShaper] is placed between the TM funnel and the HAL servers. It queues the
+//! telemetry per [TmCategory] and releases it to a TM sink with a configurable maximum bitrate.
+//!
+//! The shaping uses token buckets which are measured over the serialized packet size:
+//!
+//!  - A global [TokenBucket] enforces the maximum bitrate of all telemetry.
+//!  - Each category can be limited to a share of the maximum bitrate with an additional
+//!    [TokenBucket]. Categories without a configured share can use the full bitrate.
+//!
+//! The shares are upper limits, so their sum may exceed 100 %. The global bucket still limits
+//! the total bitrate in that case. The categories are served in a round-robin fashion.
+//!
+//! The telemetry is classified with a [TmCategoryClassifier] based on the PUS service and
+//! subservice. The [default_tm_category] classifier separates housekeeping, event and
+//! verification, dump and other telemetry.
+//!
+//! All functions which refill the token buckets expect a monotonic timestamp with an arbitrary
+//! epoch, for example the time elapsed since the start of the software.
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use super::tm_priority::classify_raw_pus_tm;
+use super::{PacketAsVec, PacketSenderRaw};
+
+/// Category of a telemetry packet which is used to apply the bandwidth shares.
+pub type TmCategory = u8;
+
+/// Function which assigns a category to a telemetry packet based on its PUS service and
+/// subservice.
+pub type TmCategoryClassifier = fn(u8, u8) -> TmCategory;
+
+pub const TM_CATEGORY_HK: TmCategory = 0;
+pub const TM_CATEGORY_EVENTS: TmCategory = 1;
+pub const TM_CATEGORY_DUMPS: TmCategory = 2;
+pub const TM_CATEGORY_OTHER: TmCategory = 3;
+/// Number of categories used by the [default_tm_category] classifier.
+pub const NUM_DEFAULT_TM_CATEGORIES: usize = 4;
+
+/// Default category classification: Housekeeping (PUS 3) telemetry, event (PUS 5) and
+/// verification (PUS 1) telemetry, dump telemetry of the memory management (PUS 6), large data
+/// transfer (PUS 13) and on-board storage (PUS 15) services and all other telemetry are
+/// separate categories.
+pub fn default_tm_category(service: u8, _subservice: u8) -> TmCategory {
+    match service {
+        3 => TM_CATEGORY_HK,
+        1 | 5 => TM_CATEGORY_EVENTS,
+        6 | 13 | 15 => TM_CATEGORY_DUMPS,
+        _ => TM_CATEGORY_OTHER,
+    }
+}
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Token bucket which limits a bitrate. The tokens are bits.
+///
+/// The bucket starts full. A packet may be sent as long as the bucket contains tokens, and
+/// sending it may empty the bucket into a deficit. This allows sending packets which are larger
+/// than the bucket capacity. The deficit delays all following packets accordingly, so the
+/// average bitrate is still limited to the configured rate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenBucket {
+    rate: u64,
+    capacity: i64,
+    tokens: i64,
+    last_refill: Option<Duration>,
+}
+
+impl TokenBucket {
+    /// Create a new full token bucket.
+    ///
+    /// ## Parameter
+    ///
+    /// * `rate` - Refill rate in bits per second.
+    /// * `capacity` - Capacity of the bucket in bits, which is the maximum burst size.
+    pub fn new(rate: u64, capacity: u64) -> Self {
+        let capacity = capacity.min(i64::MAX as u64) as i64;
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last_refill: None,
+        }
+    }
+
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// Set a new refill rate. The current tokens are kept.
+    pub fn set_rate(&mut self, rate: u64) {
+        self.rate = rate;
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity as u64
+    }
+
+    /// Current number of tokens in bits. This is negative if the bucket has a deficit.
+    pub fn tokens(&self) -> i64 {
+        self.tokens
+    }
+
+    pub fn has_tokens(&self) -> bool {
+        self.tokens > 0
+    }
+
+    /// Refill the bucket according to the time elapsed since the last refill.
+    pub fn refill(&mut self, now: Duration) {
+        let last_refill = match self.last_refill {
+            Some(last_refill) if now > last_refill => last_refill,
+            Some(_) => return,
+            None => {
+                self.last_refill = Some(now);
+                return;
+            }
+        };
+        let elapsed_nanos = (now - last_refill).as_nanos();
+        let added = (self.rate as u128 * elapsed_nanos / NANOS_PER_SEC).min(i64::MAX as u128);
+        if added == 0 {
+            return;
+        }
+        let tokens = self.tokens.saturating_add(added as i64);
+        if tokens >= self.capacity {
+            self.tokens = self.capacity;
+            self.last_refill = Some(now);
+        } else {
+            self.tokens = tokens;
+            // Only account for the time of the added whole bits so that no fractional bits
+            // are lost.
+            let used_nanos = added * NANOS_PER_SEC / self.rate as u128;
+            self.last_refill = Some(last_refill + duration_from_nanos(used_nanos));
+        }
+    }
+
+    /// Remove the given number of bits from the bucket.
+    pub fn consume(&mut self, bits: u64) {
+        self.tokens = self.tokens.saturating_sub(bits.min(i64::MAX as u64) as i64);
+    }
+
+    /// Time until the bucket contains tokens again, based on the last refill. Returns
+    /// [Duration::ZERO] if the bucket contains tokens and [None] if the rate is 0 and the
+    /// bucket is empty.
+    pub fn time_until_tokens(&self) -> Option<Duration> {
+        if self.has_tokens() {
+            return Some(Duration::ZERO);
+        }
+        if self.rate == 0 {
+            return None;
+        }
+        let missing_bits = (1 - self.tokens) as u128;
+        let nanos = missing_bits * NANOS_PER_SEC / self.rate as u128;
+        Some(duration_from_nanos(nanos + 1))
+    }
+}
+
+fn duration_from_nanos(nanos: u128) -> Duration {
+    Duration::new(
+        (nanos / NANOS_PER_SEC).min(u64::MAX as u128) as u64,
+        (nanos % NANOS_PER_SEC) as u32,
+    )
+}
+
+/// Configuration of the [TmBandwidthShaper].
+///
+/// ## Parameters
+///
+/// * `max_bitrate` - Maximum bitrate of all telemetry in bits per second.
+/// * `burst_size` - Maximum burst size in bytes, which is the capacity of the global token
+///     bucket. The category token buckets have a proportional capacity.
+/// * `shares` - Share of the maximum bitrate in percent for each category. The category is
+///     the index of the list. A share of [None] means that the category is only limited by the
+///     maximum bitrate.
+/// * `max_queue_len` - Maximum number of queued packets per category. Additional packets are
+///     dropped.
+/// * `classifier` - Classifier used by [TmBandwidthShaper::push].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaperConfig {
+    pub max_bitrate: u64,
+    pub burst_size: usize,
+    pub shares: Vec<Option<u8>>,
+    pub max_queue_len: usize,
+    pub classifier: TmCategoryClassifier,
+}
+
+impl ShaperConfig {
+    /// Create a configuration for the [NUM_DEFAULT_TM_CATEGORIES] default categories without
+    /// category shares, a maximum queue length of 256 packets per category and the
+    /// [default_tm_category] classifier.
+    pub fn new(max_bitrate: u64, burst_size: usize) -> Self {
+        Self {
+            max_bitrate,
+            burst_size,
+            shares: vec![None; NUM_DEFAULT_TM_CATEGORIES],
+            max_queue_len: 256,
+            classifier: default_tm_category,
+        }
+    }
+
+    /// Limit the category to the given share of the maximum bitrate in percent. Shares larger
+    /// than 100 % are clamped.
+    pub fn with_share(mut self, category: TmCategory, share_percent: u8) -> Self {
+        let idx = category as usize;
+        if idx >= self.shares.len() {
+            self.shares.resize(idx + 1, None);
+        }
+        self.shares[idx] = Some(share_percent.min(100));
+        self
+    }
+
+    pub fn with_max_queue_len(mut self, max_queue_len: usize) -> Self {
+        self.max_queue_len = max_queue_len;
+        self
+    }
+
+    /// Use a custom classifier. The number of categories is determined by the length of the
+    /// share list, which can be extended with [Self::with_share].
+    pub fn with_classifier(mut self, classifier: TmCategoryClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+}
+
+#[derive(Debug)]
+struct CategoryState {
+    share: Option<u8>,
+    bucket: Option<TokenBucket>,
+    queue: VecDeque<PacketAsVec>,
+    num_dropped: u32,
+    sent_bytes: u64,
+}
+
+/// Statistics of a single TM category of the [TmBandwidthShaper].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CategoryStats {
+    pub queued_packets: usize,
+    pub dropped_packets: u32,
+    pub sent_bytes: u64,
+}
+
+/// Rate limiter for the TM chain which enforces a maximum bitrate with per-category shares.
+/// See the [module documentation][self] for more details.
+///
+/// Categories which are larger than the highest configured category are clamped to the highest
+/// category.
+#[derive(Debug)]
+pub struct TmBandwidthShaper {
+    max_bitrate: u64,
+    bucket: TokenBucket,
+    categories: Vec<CategoryState>,
+    max_queue_len: usize,
+    classifier: TmCategoryClassifier,
+    next_category: usize,
+}
+
+impl TmBandwidthShaper {
+    pub fn new(cfg: ShaperConfig) -> Self {
+        assert!(!cfg.shares.is_empty(), "at least one category is required");
+        let burst_bits = cfg.burst_size as u64 * 8;
+        let categories = cfg
+            .shares
+            .iter()
+            .map(|share| CategoryState {
+                share: *share,
+                bucket: share.map(|share| {
+                    TokenBucket::new(
+                        share_of(cfg.max_bitrate, share),
+                        share_of(burst_bits, share),
+                    )
+                }),
+                queue: VecDeque::new(),
+                num_dropped: 0,
+                sent_bytes: 0,
+            })
+            .collect();
+        Self {
+            max_bitrate: cfg.max_bitrate,
+            bucket: TokenBucket::new(cfg.max_bitrate, burst_bits),
+            categories,
+            max_queue_len: cfg.max_queue_len,
+            classifier: cfg.classifier,
+            next_category: 0,
+        }
+    }
+
+    pub fn max_bitrate(&self) -> u64 {
+        self.max_bitrate
+    }
+
+    /// Change the maximum bitrate, for example if the downlink budget changes. The bitrates of
+    /// the categories are updated according to their shares.
+    pub fn set_max_bitrate(&mut self, max_bitrate: u64) {
+        self.max_bitrate = max_bitrate;
+        self.bucket.set_rate(max_bitrate);
+        for category in self.categories.iter_mut() {
+            if let (Some(share), Some(bucket)) = (category.share, category.bucket.as_mut()) {
+                bucket.set_rate(share_of(max_bitrate, share));
+            }
+        }
+    }
+
+    pub fn num_categories(&self) -> usize {
+        self.categories.len()
+    }
+
+    /// Total number of queued packets.
+    pub fn len(&self) -> usize {
+        self.categories
+            .iter()
+            .map(|category| category.queue.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories
+            .iter()
+            .all(|category| category.queue.is_empty())
+    }
+
+    pub fn stats(&self, category: TmCategory) -> CategoryStats {
+        let category = &self.categories[self.idx(category)];
+        CategoryStats {
+            queued_packets: category.queue.len(),
+            dropped_packets: category.num_dropped,
+            sent_bytes: category.sent_bytes,
+        }
+    }
+
+    /// Classify the packet with the configured [TmCategoryClassifier] and queue it. Packets
+    /// which can not be classified are queued in the highest category. Returns [false] if the
+    /// packet was dropped because the queue of its category is full.
+    pub fn push(&mut self, packet: PacketAsVec) -> bool {
+        let category = classify_raw_pus_tm(&packet.packet, self.classifier)
+            .unwrap_or((self.categories.len() - 1) as TmCategory);
+        self.push_with_category(category, packet)
+    }
+
+    /// Queue a packet with the given category. Returns [false] if the packet was dropped
+    /// because the queue of the category is full.
+    pub fn push_with_category(&mut self, category: TmCategory, packet: PacketAsVec) -> bool {
+        let max_queue_len = self.max_queue_len;
+        let idx = self.idx(category);
+        let category = &mut self.categories[idx];
+        if category.queue.len() >= max_queue_len {
+            category.num_dropped += 1;
+            return false;
+        }
+        category.queue.push_back(packet);
+        true
+    }
+
+    /// Send all queued packets to the sink which are allowed by the bandwidth limits at the
+    /// given monotonic timestamp. This function should be called periodically.
+    ///
+    /// Returns the number of sent packets. A packet which could not be sent remains queued.
+    pub fn poll<Sink: PacketSenderRaw + ?Sized>(
+        &mut self,
+        now: Duration,
+        sink: &Sink,
+    ) -> Result<usize, Sink::Error> {
+        self.bucket.refill(now);
+        for category in self.categories.iter_mut() {
+            if let Some(bucket) = category.bucket.as_mut() {
+                bucket.refill(now);
+            }
+        }
+        let mut num_sent = 0;
+        let num_categories = self.categories.len();
+        loop {
+            let mut sent_in_round = false;
+            for offset in 0..num_categories {
+                if !self.bucket.has_tokens() {
+                    return Ok(num_sent);
+                }
+                let idx = (self.next_category + offset) % num_categories;
+                let category = &mut self.categories[idx];
+                if let Some(bucket) = &category.bucket {
+                    if !bucket.has_tokens() {
+                        continue;
+                    }
+                }
+                let packet = match category.queue.front() {
+                    Some(packet) => packet,
+                    None => continue,
+                };
+                sink.send_packet(packet.sender_id, &packet.packet)?;
+                let packet = category.queue.pop_front().unwrap();
+                let bits = packet.packet.len() as u64 * 8;
+                if let Some(bucket) = category.bucket.as_mut() {
+                    bucket.consume(bits);
+                }
+                self.bucket.consume(bits);
+                category.sent_bytes += packet.packet.len() as u64;
+                num_sent += 1;
+                sent_in_round = true;
+            }
+            // Continue with the next category in the next round so that no category is
+            // preferred.
+            self.next_category = (self.next_category + 1) % num_categories;
+            if !sent_in_round {
+                return Ok(num_sent);
+            }
+        }
+    }
+
+    fn idx(&self, category: TmCategory) -> usize {
+        (category as usize).min(self.categories.len() - 1)
+    }
+}
+
+fn share_of(value: u64, share_percent: u8) -> u64 {
+    (value as u128 * share_percent as u128 / 100) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::vec;
+
+    use spacepackets::ecss::tm::{PusTmCreator, PusTmSecondaryHeader};
+    use spacepackets::ecss::WritablePusPacket;
+    use spacepackets::SpHeader;
+
+    use super::*;
+
+    fn create_tm(service: u8, data_len: usize) -> PacketAsVec {
+        let sec_header = PusTmSecondaryHeader::new_simple(service, 1, &[]);
+        let data = vec![0; data_len];
+        let tm = PusTmCreator::new(SpHeader::new_from_apid(0x02), sec_header, &data, true);
+        PacketAsVec::new(0, tm.to_vec().unwrap())
+    }
+
+    #[test]
+    fn test_classification() {
+        assert_eq!(default_tm_category(3, 25), TM_CATEGORY_HK);
+        assert_eq!(default_tm_category(5, 1), TM_CATEGORY_EVENTS);
+        assert_eq!(default_tm_category(1, 7), TM_CATEGORY_EVENTS);
+        assert_eq!(default_tm_category(13, 1), TM_CATEGORY_DUMPS);
+        assert_eq!(default_tm_category(17, 2), TM_CATEGORY_OTHER);
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let mut bucket = TokenBucket::new(1000, 100);
+        bucket.refill(Duration::ZERO);
+        assert_eq!(bucket.tokens(), 100);
+        bucket.consume(300);
+        assert_eq!(bucket.tokens(), -200);
+        assert!(!bucket.has_tokens());
+        assert_eq!(
+            bucket.time_until_tokens(),
+            Some(Duration::from_nanos(201_000_001))
+        );
+        bucket.refill(Duration::from_millis(100));
+        assert_eq!(bucket.tokens(), -100);
+        bucket.refill(Duration::from_millis(1000));
+        assert_eq!(bucket.tokens(), 100);
+    }
+
+    #[test]
+    fn test_max_bitrate() {
+        let (sink, rx) = mpsc::channel::<PacketAsVec>();
+        // 800 bit/s, so 100 bytes per second with a burst size of 100 bytes.
+        let mut shaper = TmBandwidthShaper::new(ShaperConfig::new(800, 100));
+        for _ in 0..4 {
+            assert!(shaper.push(create_tm(17, 35)));
+        }
+        let packet_len = create_tm(17, 35).packet.len();
+        assert_eq!(packet_len, 50);
+        assert_eq!(shaper.poll(Duration::ZERO, &sink).unwrap(), 2);
+        // The bucket contains tokens again, and sending the packet creates a deficit.
+        assert_eq!(shaper.poll(Duration::from_millis(100), &sink).unwrap(), 1);
+        assert_eq!(shaper.poll(Duration::from_millis(500), &sink).unwrap(), 0);
+        assert_eq!(shaper.poll(Duration::from_millis(510), &sink).unwrap(), 1);
+        assert!(shaper.is_empty());
+        assert_eq!(rx.try_iter().count(), 4);
+        assert_eq!(shaper.stats(TM_CATEGORY_OTHER).sent_bytes, 200);
+    }
+
+    #[test]
+    fn test_category_shares_and_queue_limit() {
+        let (sink, rx) = mpsc::channel::<PacketAsVec>();
+        let mut shaper = TmBandwidthShaper::new(
+            ShaperConfig::new(8000, 500)
+                .with_share(TM_CATEGORY_HK, 20)
+                .with_max_queue_len(4),
+        );
+        for _ in 0..5 {
+            shaper.push(create_tm(3, 35));
+        }
+        for _ in 0..2 {
+            shaper.push(create_tm(5, 35));
+        }
+        assert_eq!(shaper.stats(TM_CATEGORY_HK).queued_packets, 4);
+        assert_eq!(shaper.stats(TM_CATEGORY_HK).dropped_packets, 1);
+        // The HK category may only use 20 % of the burst size, which is 100 bytes, while the
+        // events are only limited by the global bucket.
+        assert_eq!(shaper.poll(Duration::ZERO, &sink).unwrap(), 4);
+        assert_eq!(shaper.stats(TM_CATEGORY_HK).sent_bytes, 100);
+        assert_eq!(shaper.stats(TM_CATEGORY_EVENTS).sent_bytes, 100);
+        assert_eq!(shaper.stats(TM_CATEGORY_HK).queued_packets, 2);
+        // 20 % of 8000 bits per second is 1600 bits per second for HK.
+        assert_eq!(shaper.poll(Duration::from_millis(100), &sink).unwrap(), 1);
+        assert_eq!(shaper.poll(Duration::from_millis(200), &sink).unwrap(), 0);
+        assert_eq!(shaper.poll(Duration::from_millis(260), &sink).unwrap(), 1);
+        assert!(shaper.is_empty());
+        assert_eq!(rx.try_iter().count(), 6);
+    }
+}
@@ -0,0 +1,275 @@
+//! # Event-driven wake-up of handler threads
+//!
+//! Handler threads usually poll their queues and sleep for a fixed time when all queues are
+//! empty, which adds latency and wastes CPU time. A thread which only waits for one queue can use
+//! the blocking receive variants, for example
+//! [EcssTcReceiver::recv_tc_timeout](crate::pus::EcssTcReceiver::recv_tc_timeout) or
+//! [EventReceiveProvider::recv_event_timeout](crate::event_man::EventReceiveProvider::recv_event_timeout).
+//! A thread which polls multiple queues can instead wait on a [WakeupSource] which is signalled
+//! by all producers.
+//!
+//! With the `std` feature, the [WakeupSignal] can be used as the wake-up source. Producers can
+//! either call [WakeupSignal::notify] directly or wrap their senders into a [WakingSender],
+//! which notifies the signal after every successful send.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::mpsc;
+//! use std::time::Duration;
+//! use satrs::pus::{EcssTcAndToken, EcssTcReceiver, EcssTcSender};
+//! use satrs::spacepackets::ecss::tc::PusTcCreator;
+//! use satrs::spacepackets::SpHeader;
+//! use satrs::wakeup::{WakeupSignal, WakeupSource, WakingSender};
+//!
+//! let wakeup = WakeupSignal::new();
+//! let (tc_tx, tc_rx) = mpsc::channel::<EcssTcAndToken>();
+//! let tc_tx = WakingSender::new(tc_tx, wakeup.clone());
+//! let ping = PusTcCreator::new_simple(SpHeader::new_from_apid(0x02), 17, 1, &[], true);
+//! tc_tx.send_tc(ping, None).unwrap();
+//!
+//! // Handler thread: Sleep until work arrives, but at most for one second.
+//! assert!(wakeup.wait_timeout(Duration::from_secs(1)));
+//! assert!(tc_rx.recv_tc().is_ok());
+//! ```
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+pub use std_mod::*;
+
+/// Source of wake-ups for a handler thread.
+pub trait WakeupSource {
+    /// Block until a wake-up was signalled or until the timeout elapsed. Returns whether a
+    /// wake-up was signalled.
+    ///
+    /// A wake-up which was signalled while the thread was busy is not lost: the next call
+    /// returns immediately. Multiple such wake-ups are coalesced into one.
+    fn wait_timeout(&self, timeout: Duration) -> bool;
+}
+
+#[cfg(feature = "std")]
+pub mod std_mod {
+    use super::*;
+    use crate::event_man::{EventMessage, EventSendProvider};
+    use crate::events::GenericEvent;
+    use crate::pus::{EcssTcSender, EcssTmSender, EcssTmtcError, PusTmVariant};
+    use crate::request::{GenericMessage, GenericTargetedMessagingError, MessageSender};
+    use crate::tmtc::PacketSenderRaw;
+    use crate::ComponentId;
+    use core::fmt::Debug;
+    use spacepackets::ecss::tc::PusTcCreator;
+    use spacepackets::ecss::tm::PusTmCreator;
+    use std::sync::{Arc, Condvar, Mutex};
+
+    use crate::pus::verification::TcStateToken;
+
+    #[derive(Debug, Default)]
+    struct WakeupState {
+        pending: Mutex<bool>,
+        cond: Condvar,
+    }
+
+    /// Clonable [WakeupSource] which can be notified from any thread.
+    ///
+    /// The signal is intended to wake up one handler thread. If multiple threads wait on the
+    /// same signal, only one of them consumes a notification.
+    #[derive(Debug, Default, Clone)]
+    pub struct WakeupSignal(Arc<WakeupState>);
+
+    impl WakeupSignal {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn notify(&self) {
+            let mut pending = self.0.pending.lock().unwrap_or_else(|e| e.into_inner());
+            *pending = true;
+            self.0.cond.notify_one();
+        }
+
+        /// Returns whether a wake-up was signalled which was not consumed yet.
+        pub fn is_pending(&self) -> bool {
+            *self.0.pending.lock().unwrap_or_else(|e| e.into_inner())
+        }
+
+        /// Block until a wake-up was signalled.
+        pub fn wait(&self) {
+            let pending = self.0.pending.lock().unwrap_or_else(|e| e.into_inner());
+            let mut pending = self
+                .0
+                .cond
+                .wait_while(pending, |pending| !*pending)
+                .unwrap_or_else(|e| e.into_inner());
+            *pending = false;
+        }
+    }
+
+    impl WakeupSource for WakeupSignal {
+        fn wait_timeout(&self, timeout: Duration) -> bool {
+            let pending = self.0.pending.lock().unwrap_or_else(|e| e.into_inner());
+            let (mut pending, _) = self
+                .0
+                .cond
+                .wait_timeout_while(pending, timeout, |pending| !*pending)
+                .unwrap_or_else(|e| e.into_inner());
+            core::mem::replace(&mut *pending, false)
+        }
+    }
+
+    /// Sender wrapper which notifies a [WakeupSignal] after every successful send.
+    ///
+    /// It implements the sender traits of the wrapped sender, so it can be used as a drop-in
+    /// replacement for the TC, TM, event and request senders.
+    #[derive(Debug, Clone)]
+    pub struct WakingSender<Sender> {
+        sender: Sender,
+        wakeup: WakeupSignal,
+    }
+
+    impl<Sender> WakingSender<Sender> {
+        pub fn new(sender: Sender, wakeup: WakeupSignal) -> Self {
+            Self { sender, wakeup }
+        }
+
+        pub fn sender(&self) -> &Sender {
+            &self.sender
+        }
+
+        pub fn wakeup_signal(&self) -> &WakeupSignal {
+            &self.wakeup
+        }
+
+        fn notify_on_success<T, E>(&self, result: Result<T, E>) -> Result<T, E> {
+            if result.is_ok() {
+                self.wakeup.notify();
+            }
+            result
+        }
+    }
+
+    impl<Sender: EcssTcSender> EcssTcSender for WakingSender<Sender> {
+        fn send_tc(
+            &self,
+            tc: PusTcCreator,
+            token: Option<TcStateToken>,
+        ) -> Result<(), EcssTmtcError> {
+            self.notify_on_success(self.sender.send_tc(tc, token))
+        }
+    }
+
+    impl<Sender: EcssTmSender> EcssTmSender for WakingSender<Sender> {
+        fn send_tm(&self, sender_id: ComponentId, tm: PusTmVariant) -> Result<(), EcssTmtcError> {
+            self.notify_on_success(self.sender.send_tm(sender_id, tm))
+        }
+
+        fn send_tm_batch(
+            &self,
+            sender_id: ComponentId,
+            tms: &[PusTmCreator],
+        ) -> Result<(), EcssTmtcError> {
+            self.notify_on_success(self.sender.send_tm_batch(sender_id, tms))
+        }
+    }
+
+    impl<Sender: PacketSenderRaw> PacketSenderRaw for WakingSender<Sender> {
+        type Error = Sender::Error;
+
+        fn send_packet(&self, sender_id: ComponentId, packet: &[u8]) -> Result<(), Self::Error> {
+            self.notify_on_success(self.sender.send_packet(sender_id, packet))
+        }
+    }
+
+    impl<
+            Event: GenericEvent,
+            ParamProvider: Debug,
+            Sender: EventSendProvider<Event, ParamProvider>,
+        > EventSendProvider<Event, ParamProvider> for WakingSender<Sender>
+    {
+        type Error = Sender::Error;
+
+        fn target_id(&self) -> ComponentId {
+            self.sender.target_id()
+        }
+
+        fn send(&self, message: EventMessage<Event, ParamProvider>) -> Result<(), Self::Error> {
+            self.notify_on_success(self.sender.send(message))
+        }
+    }
+
+    impl<Msg, Sender: MessageSender<Msg>> MessageSender<Msg> for WakingSender<Sender> {
+        fn send(&self, message: GenericMessage<Msg>) -> Result<(), GenericTargetedMessagingError> {
+            self.notify_on_success(self.sender.send(message))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::event_man::{EventMessageU32, EventSendProvider, EventU32SenderMpsc};
+    use crate::events::{EventU32, Severity};
+    use crate::pus::{EcssTcAndToken, EcssTcSender};
+    use spacepackets::ecss::tc::PusTcCreator;
+    use spacepackets::SpHeader;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Instant;
+
+    const TEST_EVENT: EventU32 = EventU32::new(Severity::Info, 0, 5);
+
+    #[test]
+    fn test_timeout_without_notification() {
+        let wakeup = WakeupSignal::new();
+        let start = Instant::now();
+        assert!(!wakeup.wait_timeout(Duration::from_millis(10)));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_pending_notifications_are_coalesced() {
+        let wakeup = WakeupSignal::new();
+        wakeup.notify();
+        wakeup.notify();
+        assert!(wakeup.is_pending());
+        assert!(wakeup.wait_timeout(Duration::ZERO));
+        assert!(!wakeup.is_pending());
+        assert!(!wakeup.wait_timeout(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn test_wakeup_from_other_thread() {
+        let wakeup = WakeupSignal::new();
+        let wakeup_shared = wakeup.clone();
+        let notifier = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            wakeup_shared.notify();
+        });
+        assert!(wakeup.wait_timeout(Duration::from_secs(2)));
+        notifier.join().unwrap();
+        wakeup.notify();
+        wakeup.wait();
+    }
+
+    #[test]
+    fn test_waking_senders() {
+        let wakeup = WakeupSignal::new();
+        let (tc_tx, tc_rx) = mpsc::channel::<EcssTcAndToken>();
+        let tc_tx = WakingSender::new(tc_tx, wakeup.clone());
+        let ping = PusTcCreator::new_simple(SpHeader::new_from_apid(0x02), 17, 1, &[], true);
+        tc_tx.send_tc(ping.clone(), None).unwrap();
+        assert!(wakeup.wait_timeout(Duration::ZERO));
+        assert!(tc_rx.try_recv().is_ok());
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let event_tx = WakingSender::new(EventU32SenderMpsc::new(1, event_tx), wakeup.clone());
+        assert_eq!(event_tx.target_id(), 1);
+        event_tx.send(EventMessageU32::new(2, TEST_EVENT)).unwrap();
+        assert!(wakeup.wait_timeout(Duration::ZERO));
+        assert_eq!(event_rx.try_recv().unwrap().event(), TEST_EVENT);
+
+        // Failed sends do not wake up the receiver.
+        drop(tc_rx);
+        assert!(tc_tx.send_tc(ping, None).is_err());
+        assert!(!wakeup.is_pending());
+    }
+}
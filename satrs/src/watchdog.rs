@@ -0,0 +1,510 @@
+//! Software watchdog supervision for on-board software tasks.
+//!
+//! Each supervised task registers at the [WatchdogSupervisor] and receives a [WatchdogHandle]
+//! which must be used to check in periodically, for example at the end of each task cycle. The
+//! supervisor itself is polled periodically by a dedicated task and detects tasks which did not
+//! check in within their timeout. For each missed check-in, a HIGH severity event is generated
+//! and the optional [WatchdogRecovery] action of the task is executed.
+//!
+//! The supervisor can also kick an external [HwWatchdog], but only when all supervised tasks are
+//! healthy. This way, a stuck task will eventually cause a reset by the hardware watchdog if the
+//! recovery actions were not successful.
+use core::time::Duration;
+use std::boxed::Box;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use std::vec::Vec;
+
+use crate::event_man::{EventMessage, EventSendProvider};
+use crate::events::{EventU32, EventU32TypedSev, SeverityHigh};
+use crate::mode::{ModeAndSubmode, ModeRequest, ModeRequestSender};
+use crate::params::{Params, ParamsRaw, U32Pair};
+use crate::request::RequestId;
+use crate::ComponentId;
+
+pub type WatchdogTaskId = u32;
+
+/// Handle which is used by a supervised task to check in at the [WatchdogSupervisor].
+///
+/// Checking in is lock-free, so the handle can also be used inside time critical code.
+#[derive(Debug, Clone)]
+pub struct WatchdogHandle {
+    task_id: WatchdogTaskId,
+    check_ins: Arc<AtomicU32>,
+}
+
+impl WatchdogHandle {
+    pub fn task_id(&self) -> WatchdogTaskId {
+        self.task_id
+    }
+
+    /// Signal that the task is alive.
+    pub fn check_in(&self) {
+        self.check_ins.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Information about a missed check-in of a supervised task.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MissedCheckIn {
+    pub task_id: WatchdogTaskId,
+    pub task_name: &'static str,
+    pub timeout: Duration,
+    /// Time elapsed since the last check-in or the registration of the task.
+    pub elapsed: Duration,
+    /// Total number of missed check-ins of the task, including this one.
+    pub num_misses: u32,
+}
+
+/// Recovery action which is executed once when a supervised task missed its check-in.
+///
+/// This trait is implemented for all closures taking a [MissedCheckIn], which can be used to
+/// implement restart callbacks.
+pub trait WatchdogRecovery: Send {
+    fn recover(&mut self, missed: &MissedCheckIn);
+}
+
+impl<F: FnMut(&MissedCheckIn) + Send> WatchdogRecovery for F {
+    fn recover(&mut self, missed: &MissedCheckIn) {
+        self(missed)
+    }
+}
+
+/// [WatchdogRecovery] which commands a component into a fallback mode, for example a safe or
+/// off mode.
+pub struct ModeFallbackRecovery<Sender: ModeRequestSender + Send> {
+    sender: Sender,
+    target_id: ComponentId,
+    fallback_mode: ModeAndSubmode,
+    next_request_id: RequestId,
+}
+
+impl<Sender: ModeRequestSender + Send> ModeFallbackRecovery<Sender> {
+    pub fn new(sender: Sender, target_id: ComponentId, fallback_mode: ModeAndSubmode) -> Self {
+        Self {
+            sender,
+            target_id,
+            fallback_mode,
+            next_request_id: 0,
+        }
+    }
+
+    pub fn sender(&self) -> &Sender {
+        &self.sender
+    }
+}
+
+impl<Sender: ModeRequestSender + Send> WatchdogRecovery for ModeFallbackRecovery<Sender> {
+    fn recover(&mut self, _missed: &MissedCheckIn) {
+        let request_id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        // The missed check-in is still reported as an event if the request can not be sent.
+        let _ = self.sender.send_mode_request(
+            request_id,
+            self.target_id,
+            ModeRequest::SetMode(self.fallback_mode),
+        );
+    }
+}
+
+/// Abstraction for an external hardware watchdog.
+///
+/// This trait is implemented for all closures without arguments.
+pub trait HwWatchdog: Send {
+    fn kick(&mut self);
+}
+
+impl<F: FnMut() + Send> HwWatchdog for F {
+    fn kick(&mut self) {
+        self()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TaskHealth {
+    Healthy,
+    /// The task missed its check-in and did not check in again since then.
+    Unhealthy,
+}
+
+/// Result of one supervision cycle of the [WatchdogSupervisor].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SupervisionReport {
+    pub num_unhealthy: usize,
+    /// Number of tasks which became unhealthy in this cycle.
+    pub newly_missed: usize,
+    /// Number of tasks which checked in again after being unhealthy.
+    pub newly_recovered: usize,
+    pub hw_watchdog_kicked: bool,
+    /// Number of events which could not be sent.
+    pub event_send_failures: usize,
+}
+
+struct SupervisedTask {
+    task_id: WatchdogTaskId,
+    name: &'static str,
+    timeout: Duration,
+    check_ins: Arc<AtomicU32>,
+    last_check_in_count: u32,
+    last_check_in: Instant,
+    health: TaskHealth,
+    num_misses: u32,
+    recovery: Option<Box<dyn WatchdogRecovery>>,
+}
+
+/// Software watchdog supervisor which detects missed check-ins of the registered tasks.
+///
+/// A missed check-in is reported with the HIGH severity missed check-in event, using a
+/// [U32Pair] parameter containing the task ID and the time elapsed since the last check-in in
+/// milliseconds. A task which checks in again after missing its check-in is healthy again, which
+/// can optionally be reported with a separate event.
+pub struct WatchdogSupervisor<EventSender: EventSendProvider<EventU32>> {
+    id: ComponentId,
+    event_sender: EventSender,
+    missed_event: EventU32TypedSev<SeverityHigh>,
+    recovered_event: Option<EventU32>,
+    tasks: Vec<SupervisedTask>,
+    next_task_id: WatchdogTaskId,
+    hw_watchdog: Option<Box<dyn HwWatchdog>>,
+}
+
+impl<EventSender: EventSendProvider<EventU32>> WatchdogSupervisor<EventSender> {
+    /// Create a new supervisor without any tasks.
+    ///
+    /// ## Parameter
+    ///
+    /// * `id` - Component ID of the supervisor which is used as the sender ID of the events.
+    /// * `event_sender` - Sender for the generated events.
+    /// * `missed_event` - Event which is generated when a task missed its check-in.
+    pub fn new(
+        id: ComponentId,
+        event_sender: EventSender,
+        missed_event: EventU32TypedSev<SeverityHigh>,
+    ) -> Self {
+        Self {
+            id,
+            event_sender,
+            missed_event,
+            recovered_event: None,
+            tasks: Vec::new(),
+            next_task_id: 0,
+            hw_watchdog: None,
+        }
+    }
+
+    /// Register a task which has to check in at least once within the given timeout.
+    pub fn register(&mut self, name: &'static str, timeout: Duration) -> WatchdogHandle {
+        self.register_generic(name, timeout, None)
+    }
+
+    /// Variant of [Self::register] which additionally executes the given recovery action when
+    /// the task misses its check-in.
+    pub fn register_with_recovery(
+        &mut self,
+        name: &'static str,
+        timeout: Duration,
+        recovery: impl WatchdogRecovery + 'static,
+    ) -> WatchdogHandle {
+        self.register_generic(name, timeout, Some(Box::new(recovery)))
+    }
+
+    fn register_generic(
+        &mut self,
+        name: &'static str,
+        timeout: Duration,
+        recovery: Option<Box<dyn WatchdogRecovery>>,
+    ) -> WatchdogHandle {
+        let task_id = self.next_task_id;
+        self.next_task_id = self.next_task_id.wrapping_add(1);
+        let check_ins = Arc::new(AtomicU32::new(0));
+        self.tasks.push(SupervisedTask {
+            task_id,
+            name,
+            timeout,
+            check_ins: check_ins.clone(),
+            last_check_in_count: 0,
+            last_check_in: Instant::now(),
+            health: TaskHealth::Healthy,
+            num_misses: 0,
+            recovery,
+        });
+        WatchdogHandle { task_id, check_ins }
+    }
+
+    /// Remove a task from the supervision. Returns whether the task was registered.
+    pub fn unregister(&mut self, task_id: WatchdogTaskId) -> bool {
+        let num_tasks = self.tasks.len();
+        self.tasks.retain(|task| task.task_id != task_id);
+        self.tasks.len() != num_tasks
+    }
+
+    /// Set an additional event which is generated when a task checks in again after it missed
+    /// its check-in. The event is sent with the task ID as a [u32] parameter.
+    pub fn set_recovered_event(&mut self, event: impl Into<EventU32>) {
+        self.recovered_event = Some(event.into());
+    }
+
+    pub fn set_hw_watchdog(&mut self, hw_watchdog: impl HwWatchdog + 'static) {
+        self.hw_watchdog = Some(Box::new(hw_watchdog));
+    }
+
+    pub fn num_tasks(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn task_health(&self, task_id: WatchdogTaskId) -> Option<TaskHealth> {
+        self.task(task_id).map(|task| task.health)
+    }
+
+    pub fn num_misses(&self, task_id: WatchdogTaskId) -> Option<u32> {
+        self.task(task_id).map(|task| task.num_misses)
+    }
+
+    pub fn all_healthy(&self) -> bool {
+        self.tasks
+            .iter()
+            .all(|task| task.health == TaskHealth::Healthy)
+    }
+
+    pub fn event_sender(&self) -> &EventSender {
+        &self.event_sender
+    }
+
+    fn task(&self, task_id: WatchdogTaskId) -> Option<&SupervisedTask> {
+        self.tasks.iter().find(|task| task.task_id == task_id)
+    }
+
+    /// Check all registered tasks using the current time. This should be called periodically
+    /// with a period which is smaller than the smallest task timeout.
+    pub fn supervise(&mut self) -> SupervisionReport {
+        self.supervise_at(Instant::now())
+    }
+
+    /// Check all registered tasks using the given time.
+    ///
+    /// The hardware watchdog is only kicked if all tasks are healthy after the check.
+    pub fn supervise_at(&mut self, now: Instant) -> SupervisionReport {
+        let mut report = SupervisionReport::default();
+        for task in &mut self.tasks {
+            let check_in_count = task.check_ins.load(Ordering::Relaxed);
+            if check_in_count != task.last_check_in_count {
+                task.last_check_in_count = check_in_count;
+                task.last_check_in = now;
+                if task.health == TaskHealth::Unhealthy {
+                    task.health = TaskHealth::Healthy;
+                    report.newly_recovered += 1;
+                    if let Some(event) = self.recovered_event {
+                        let params = Params::from(ParamsRaw::from(task.task_id));
+                        if self
+                            .event_sender
+                            .send(EventMessage::new_with_params(self.id, event, &params))
+                            .is_err()
+                        {
+                            report.event_send_failures += 1;
+                        }
+                    }
+                }
+                continue;
+            }
+            let elapsed = now.saturating_duration_since(task.last_check_in);
+            if task.health == TaskHealth::Healthy && elapsed > task.timeout {
+                task.health = TaskHealth::Unhealthy;
+                task.num_misses = task.num_misses.saturating_add(1);
+                report.newly_missed += 1;
+                let params = Params::from(ParamsRaw::from(U32Pair(
+                    task.task_id,
+                    u32::try_from(elapsed.as_millis()).unwrap_or(u32::MAX),
+                )));
+                if self
+                    .event_sender
+                    .send(EventMessage::new_with_params(
+                        self.id,
+                        self.missed_event.into(),
+                        &params,
+                    ))
+                    .is_err()
+                {
+                    report.event_send_failures += 1;
+                }
+                if let Some(recovery) = task.recovery.as_mut() {
+                    recovery.recover(&MissedCheckIn {
+                        task_id: task.task_id,
+                        task_name: task.name,
+                        timeout: task.timeout,
+                        elapsed,
+                        num_misses: task.num_misses,
+                    });
+                }
+            }
+            if task.health == TaskHealth::Unhealthy {
+                report.num_unhealthy += 1;
+            }
+        }
+        if report.num_unhealthy == 0 {
+            if let Some(hw_watchdog) = self.hw_watchdog.as_mut() {
+                hw_watchdog.kick();
+                report.hw_watchdog_kicked = true;
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::sync::Mutex;
+
+    use crate::event_man::EventSenderMpsc;
+    use crate::events::{GenericEvent, Severity};
+    use crate::params::ParamsHeapless;
+    use crate::queue::GenericTargetedMessagingError;
+
+    use super::*;
+
+    const MISSED_EVENT: EventU32TypedSev<SeverityHigh> = EventU32TypedSev::new(1, 1);
+    const RECOVERED_EVENT: EventU32 = EventU32::new(Severity::Info, 1, 2);
+    const SUPERVISOR_ID: ComponentId = 5;
+
+    fn create_supervisor(
+        event_tx: mpsc::Sender<EventMessage<EventU32>>,
+    ) -> WatchdogSupervisor<EventSenderMpsc<EventU32>> {
+        WatchdogSupervisor::new(
+            SUPERVISOR_ID,
+            EventSenderMpsc::new(1, event_tx),
+            MISSED_EVENT,
+        )
+    }
+
+    #[derive(Default, Clone)]
+    struct ModeRequestRecorder {
+        requests: Arc<Mutex<Vec<(RequestId, ComponentId, ModeRequest)>>>,
+    }
+
+    impl ModeRequestSender for ModeRequestRecorder {
+        fn local_channel_id(&self) -> ComponentId {
+            SUPERVISOR_ID
+        }
+
+        fn send_mode_request(
+            &self,
+            request_id: RequestId,
+            target_id: ComponentId,
+            request: ModeRequest,
+        ) -> Result<(), GenericTargetedMessagingError> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((request_id, target_id, request));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_healthy_tasks() {
+        let (event_tx, event_rx) = mpsc::channel::<EventMessage<EventU32>>();
+        let mut supervisor = create_supervisor(event_tx);
+        let kicks = Arc::new(AtomicU32::new(0));
+        let kicks_clone = kicks.clone();
+        supervisor.set_hw_watchdog(move || {
+            kicks_clone.fetch_add(1, Ordering::Relaxed);
+        });
+        let handle_0 = supervisor.register("task 0", Duration::from_millis(100));
+        let handle_1 = supervisor.register("task 1", Duration::from_millis(200));
+        assert_eq!(supervisor.num_tasks(), 2);
+        let start = Instant::now();
+        handle_0.check_in();
+        handle_1.check_in();
+        let report = supervisor.supervise_at(start + Duration::from_millis(50));
+        assert_eq!(report.num_unhealthy, 0);
+        assert!(report.hw_watchdog_kicked);
+        handle_0.check_in();
+        let report = supervisor.supervise_at(start + Duration::from_millis(120));
+        assert_eq!(report.num_unhealthy, 0);
+        assert!(supervisor.all_healthy());
+        assert_eq!(kicks.load(Ordering::Relaxed), 2);
+        assert!(event_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_missed_check_in() {
+        let (event_tx, event_rx) = mpsc::channel::<EventMessage<EventU32>>();
+        let mut supervisor = create_supervisor(event_tx);
+        supervisor.set_recovered_event(RECOVERED_EVENT);
+        let kicks = Arc::new(AtomicU32::new(0));
+        let kicks_clone = kicks.clone();
+        supervisor.set_hw_watchdog(move || {
+            kicks_clone.fetch_add(1, Ordering::Relaxed);
+        });
+        let missed_check_ins: Arc<Mutex<Vec<MissedCheckIn>>> = Arc::default();
+        let missed_clone = missed_check_ins.clone();
+        let handle = supervisor.register_with_recovery(
+            "task 0",
+            Duration::from_millis(100),
+            move |missed: &MissedCheckIn| missed_clone.lock().unwrap().push(*missed),
+        );
+        let start = Instant::now();
+        let report = supervisor.supervise_at(start + Duration::from_millis(150));
+        assert_eq!(report.newly_missed, 1);
+        assert_eq!(report.num_unhealthy, 1);
+        assert!(!report.hw_watchdog_kicked);
+        assert_eq!(
+            supervisor.task_health(handle.task_id()),
+            Some(TaskHealth::Unhealthy)
+        );
+        // The miss is only reported once.
+        let report = supervisor.supervise_at(start + Duration::from_millis(300));
+        assert_eq!(report.newly_missed, 0);
+        assert_eq!(report.num_unhealthy, 1);
+        assert_eq!(kicks.load(Ordering::Relaxed), 0);
+
+        let missed_check_ins = missed_check_ins.lock().unwrap();
+        assert_eq!(missed_check_ins.len(), 1);
+        assert_eq!(missed_check_ins[0].task_id, handle.task_id());
+        assert_eq!(missed_check_ins[0].task_name, "task 0");
+        assert!(missed_check_ins[0].elapsed >= Duration::from_millis(100));
+        let event = event_rx.try_recv().expect("no missed check-in event");
+        assert_eq!(event.sender_id(), SUPERVISOR_ID);
+        assert_eq!(event.event(), MISSED_EVENT.into());
+        assert_eq!(event.event().severity(), Severity::High);
+        if let Some(Params::Heapless(ParamsHeapless::Raw(ParamsRaw::U32Pair(U32Pair(
+            task_id,
+            elapsed_ms,
+        ))))) = event.params()
+        {
+            assert_eq!(*task_id, handle.task_id());
+            assert!(*elapsed_ms >= 100);
+        } else {
+            panic!("unexpected event parameters {:?}", event.params());
+        }
+
+        handle.check_in();
+        let report = supervisor.supervise_at(start + Duration::from_millis(350));
+        assert_eq!(report.newly_recovered, 1);
+        assert!(report.hw_watchdog_kicked);
+        assert_eq!(supervisor.num_misses(handle.task_id()), Some(1));
+        let event = event_rx.try_recv().expect("no recovered event");
+        assert_eq!(event.event(), RECOVERED_EVENT);
+    }
+
+    #[test]
+    fn test_mode_fallback_recovery() {
+        let (event_tx, _event_rx) = mpsc::channel::<EventMessage<EventU32>>();
+        let mut supervisor = create_supervisor(event_tx);
+        let fallback_mode = ModeAndSubmode::new_mode_only(0);
+        let recorder = ModeRequestRecorder::default();
+        let handle = supervisor.register_with_recovery(
+            "task 0",
+            Duration::from_millis(10),
+            ModeFallbackRecovery::new(recorder.clone(), 20, fallback_mode),
+        );
+        supervisor.supervise_at(Instant::now() + Duration::from_millis(20));
+        let requests = recorder.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0], (0, 20, ModeRequest::SetMode(fallback_mode)));
+        assert!(supervisor.unregister(handle.task_id()));
+        assert!(!supervisor.unregister(handle.task_id()));
+        assert_eq!(supervisor.num_tasks(), 0);
+    }
+}